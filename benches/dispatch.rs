@@ -0,0 +1,100 @@
+//! Registry lookup, tool dispatch overhead and serializing large results
+//!
+//! Run with `cargo bench --bench dispatch`. `dispatch/direct` calls the
+//! handler's work without the registry, so the gap to `dispatch/registry` and
+//! `dispatch/jsonrpc` is what lookup, validation and the envelope cost.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use devops_mcp::tools::registry::{JsonRpcRequest, JsonRpcResponse};
+use devops_mcp::tools::{call_result, ToolDefinition, ToolRegistry};
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+
+/// Tools registered for the lookup benchmarks, about what the binary serves
+const TOOLS: usize = 500;
+
+fn registry() -> ToolRegistry {
+    let registry = ToolRegistry::new();
+    for i in 0..TOOLS {
+        let definition = ToolDefinition::from_json_schema(
+            &format!("tool_{}", i),
+            "Benchmark tool",
+            "bench",
+            json!({
+                "type": "object",
+                "properties": {"name": {"type": "string"}},
+                "required": ["name"]
+            }),
+            None,
+        );
+        registry
+            .register(definition, |arguments| async move { Ok(greet(arguments)) })
+            .unwrap();
+    }
+    registry
+}
+
+fn greet(arguments: Value) -> Value {
+    let text = format!("Hello {}", arguments["name"].as_str().unwrap_or_default());
+    call_result(text, arguments)
+}
+
+fn lookup(c: &mut Criterion) {
+    let registry = registry();
+    let mut group = c.benchmark_group("lookup");
+    group.bench_function("contains", |b| {
+        b.iter(|| registry.contains(black_box("tool_250")))
+    });
+    group.bench_function("list", |b| b.iter(|| registry.list()));
+    group.finish();
+}
+
+fn dispatch(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let registry = registry();
+    let arguments = json!({"name": "world"});
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({"name": "tool_250", "arguments": arguments})),
+    };
+    let mut group = c.benchmark_group("dispatch");
+    group.bench_function("direct", |b| b.iter(|| greet(black_box(arguments.clone()))));
+    group.bench_function("registry", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(registry.call("tool_250", black_box(arguments.clone())))
+                .unwrap()
+        })
+    });
+    group.bench_function("jsonrpc", |b| {
+        b.iter(|| runtime.block_on(registry.handle(black_box(request.clone()))))
+    });
+    group.finish();
+}
+
+fn serialize_result(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize_result");
+    group.sample_size(10);
+    for megabytes in [1, 4, 16] {
+        let mut rows = Vec::new();
+        let mut size = 0;
+        while size < megabytes << 20 {
+            let row = json!({"pod": format!("api-{}", rows.len()), "restarts": rows.len() % 7});
+            size += row.to_string().len();
+            rows.push(row);
+        }
+        let response = JsonRpcResponse::result(Some(json!(1)), call_result("rows", json!(rows)));
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(megabytes),
+            &response,
+            |b, response| b.iter(|| serde_json::to_vec(black_box(response)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lookup, dispatch, serialize_result);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+//! Cost of framing messages of different sizes with and without compression
+//!
+//! Run with `cargo bench --bench framing`. Compare the time per message with
+//! the bytes saved (printed once per size) to place
+//! `framing::COMPRESSION_THRESHOLD`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use devops_mcp::transport::framing::{decode_frame, encode_frame, Compression};
+use serde_json::json;
+
+/// A `tools/call` result carrying roughly `size` bytes of log output
+fn tool_result(size: usize) -> Vec<u8> {
+    let mut text = String::with_capacity(size);
+    let mut line = 0;
+    while text.len() < size {
+        text.push_str(&format!(
+            "2026-01-03T10:{:02}:{:02}Z INFO request {} served in {}ms\n",
+            line / 60 % 60,
+            line % 60,
+            line,
+            line * 7 % 300
+        ));
+        line += 1;
+    }
+    json!({"jsonrpc": "2.0", "id": 1, "result": {"content": [{"type": "text", "text": text}]}})
+        .to_string()
+        .into_bytes()
+}
+
+fn framing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("framing");
+    for size in [256, 1024, 4096, 16 * 1024, 256 * 1024] {
+        let message = tool_result(size);
+        group.throughput(Throughput::Bytes(message.len() as u64));
+        let encodings = [
+            ("plain", None),
+            ("gzip", Some(Compression::Gzip)),
+            ("zstd", Some(Compression::Zstd)),
+        ];
+        for (name, compression) in encodings {
+            let frame = encode_frame(&message, compression).unwrap();
+            println!("{} {} bytes -> {} bytes", name, message.len(), frame.len());
+            group.bench_with_input(BenchmarkId::new(name, size), &message, |b, message| {
+                b.iter(|| {
+                    let frame = encode_frame(black_box(message), compression).unwrap();
+                    decode_frame(&frame).unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, framing);
+criterion_main!(benches);
@@ -0,0 +1,105 @@
+//! Reading multi-MB tool results: full `Value` parse against the borrowed envelope
+//!
+//! Run with `cargo bench --bench json`. Besides the timings, the bytes and
+//! allocations each path makes are printed once per size; the borrowed path
+//! should allocate roughly the result once instead of twice.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use devops_mcp::transport::jsonrpc::RawMessage;
+use serde_json::{json, Value};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// System allocator that counts what it hands out
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Allocations and bytes allocated while running `f`
+fn measure<T>(f: impl FnOnce() -> T) -> (usize, usize) {
+    let (count, bytes) = (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        BYTES.load(Ordering::Relaxed),
+    );
+    drop(black_box(f()));
+    (
+        ALLOCATIONS.load(Ordering::Relaxed) - count,
+        BYTES.load(Ordering::Relaxed) - bytes,
+    )
+}
+
+/// A `tools/call` response carrying `size` bytes of rows as text and structured content
+fn tool_response(size: usize) -> String {
+    let mut text = String::with_capacity(size);
+    let mut rows = Vec::new();
+    while text.len() < size / 2 {
+        let row = json!({"pod": format!("api-{}", rows.len()), "restarts": rows.len() % 7});
+        text.push_str(&row.to_string());
+        text.push('\n');
+        rows.push(row);
+    }
+    json!({
+        "jsonrpc": "2.0",
+        "id": "3f1c2a9e-5b7d-4e0a-9c61-2d8f4b6a1e70",
+        "result": {"content": [{"type": "text", "text": text}], "structuredContent": rows}
+    })
+    .to_string()
+}
+
+/// How the readers worked before: parse everything, then copy the result out
+fn owned(text: &str) -> Value {
+    let response: Value = serde_json::from_str(text).unwrap();
+    response.get("result").cloned().unwrap()
+}
+
+/// Route on the borrowed envelope and build only the result
+fn borrowed(text: &str) -> Value {
+    let response = RawMessage::parse(text).unwrap();
+    assert!(response.id_str().is_some());
+    response.result_value().unwrap()
+}
+
+fn read_response(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_response");
+    group.sample_size(10);
+    for megabytes in [1, 4, 16] {
+        let text = tool_response(megabytes << 20);
+        for (name, read) in [
+            ("owned", owned as fn(&str) -> Value),
+            ("borrowed", borrowed),
+        ] {
+            let (allocations, bytes) = measure(|| read(&text));
+            println!(
+                "{} {} MiB: {} allocations, {} bytes",
+                name, megabytes, allocations, bytes
+            );
+        }
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::new("owned", megabytes), &text, |b, text| {
+            b.iter(|| owned(black_box(text)))
+        });
+        group.bench_with_input(BenchmarkId::new("borrowed", megabytes), &text, |b, text| {
+            b.iter(|| borrowed(black_box(text)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, read_response);
+criterion_main!(benches);
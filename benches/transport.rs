@@ -0,0 +1,90 @@
+//! JSON-RPC round trips through each transport against one served registry
+//!
+//! Run with `cargo bench --bench transport`. Every client talks to the same
+//! in-process server on a loopback port, so the differences between them are
+//! the transports' own framing and connection handling.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use devops_mcp::tools::{call_result, ToolDefinition, ToolRegistry};
+use devops_mcp::transport::http::HttpTransport;
+use devops_mcp::transport::sse::SSE_PATH;
+use devops_mcp::transport::websocket::WS_PATH;
+use devops_mcp::transport::{GrpcTransport, SseTransport, Transport, WebSocketTransport};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// Serve an `echo` tool over every transport, as the binary does
+async fn serve() -> SocketAddr {
+    let registry = ToolRegistry::new();
+    registry
+        .register(
+            ToolDefinition::new("echo", "Echo"),
+            |arguments| async move { Ok(call_result("echo", arguments)) },
+        )
+        .unwrap();
+    let registry = Arc::new(registry);
+    let app = Arc::clone(&registry)
+        .router()
+        .merge(devops_mcp::transport::sse::router(Arc::clone(&registry)))
+        .merge(devops_mcp::transport::websocket::router(Arc::clone(
+            &registry,
+        )))
+        .merge(devops_mcp::transport::grpc::router(registry));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    addr
+}
+
+async fn clients(addr: SocketAddr) -> Vec<(&'static str, Box<dyn Transport>)> {
+    let mut clients: Vec<(&'static str, Box<dyn Transport>)> = vec![
+        (
+            "http",
+            Box::new(HttpTransport::new(format!("http://{}/", addr)).unwrap()),
+        ),
+        (
+            "sse",
+            Box::new(SseTransport::new(format!("http://{}{}", addr, SSE_PATH)).unwrap()),
+        ),
+        (
+            "websocket",
+            Box::new(WebSocketTransport::new(format!("ws://{}{}", addr, WS_PATH)).unwrap()),
+        ),
+        (
+            "grpc",
+            Box::new(GrpcTransport::new(format!("http://{}", addr))),
+        ),
+    ];
+    for (_, client) in &mut clients {
+        client.connect().await.unwrap();
+    }
+    clients
+}
+
+fn round_trip(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let addr = runtime.block_on(serve());
+    let mut clients = runtime.block_on(clients(addr));
+    let mut group = c.benchmark_group("round_trip");
+    for (name, client) in &mut clients {
+        for rows in [1, 1000] {
+            let arguments: Vec<Value> = (0..rows)
+                .map(|i| json!({"pod": format!("api-{}", i), "ready": true}))
+                .collect();
+            let params = json!({"name": "echo", "arguments": {"rows": arguments}});
+            group.bench_with_input(BenchmarkId::new(*name, rows), &params, |b, params| {
+                b.iter(|| {
+                    runtime
+                        .block_on(client.request("tools/call", Some(black_box(params.clone()))))
+                        .unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, round_trip);
+criterion_main!(benches);
@@ -0,0 +1,322 @@
+//! Diagnose a failing pod: list pods → get logs → query Prometheus → create memory
+
+use devops_mcp::error::{Error, Result};
+use devops_mcp::lifecycle::LifecycleManager;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+/// Log lines kept as evidence
+const MAX_ERROR_LINES: usize = 5;
+
+/// Words that mark a log line as an error
+const ERROR_MARKERS: &[&str] = &["error", "panic", "fatal", "exception", "oomkilled"];
+
+/// Which pod to look at
+pub struct Target {
+    /// Namespace, the server's default when `None`
+    pub namespace: Option<String>,
+    /// Pod to diagnose, the least healthy one when `None`
+    pub pod: Option<String>,
+    /// Log lines to fetch
+    pub lines: u32,
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Self {
+            namespace: None,
+            pod: None,
+            lines: 200,
+        }
+    }
+}
+
+/// What the agent found out about one pod
+#[derive(Debug)]
+pub struct Report {
+    pub namespace: String,
+    pub pod: String,
+    pub status: String,
+    pub ready: String,
+    pub restarts: u64,
+    /// Last error lines of the logs
+    pub errors: Vec<String>,
+    /// Container restarts over the last hour, when Prometheus is available
+    pub restarts_last_hour: Option<f64>,
+    /// Steps skipped because the server lacks their tool
+    pub skipped: Vec<String>,
+    /// Whether the findings were stored as a memory
+    pub remembered: bool,
+}
+
+impl Report {
+    pub fn summary(&self) -> String {
+        let mut text = format!(
+            "🩺 {}/{} is {} (ready {}, {} restarts)",
+            self.namespace, self.pod, self.status, self.ready, self.restarts
+        );
+        if let Some(rate) = self.restarts_last_hour {
+            text.push_str(&format!("\n📈 {} restarts in the last hour", rate));
+        }
+        if self.errors.is_empty() {
+            text.push_str("\n📜 No error lines in the recent logs");
+        } else {
+            text.push_str("\n📜 Recent errors:");
+            for line in &self.errors {
+                text.push_str(&format!("\n   {}", line));
+            }
+        }
+        for step in &self.skipped {
+            text.push_str(&format!("\n⏭️  Skipped: {}", step));
+        }
+        if self.remembered {
+            text.push_str("\n🧠 Findings saved as a memory");
+        }
+        text
+    }
+}
+
+/// How unhealthy a pod looks; zero for a running, ready pod without restarts
+fn severity(pod: &Value) -> u64 {
+    let status = pod["status"].as_str().unwrap_or_default();
+    let restarts = pod["restarts"].as_u64().unwrap_or(0);
+    let ready = pod["ready"]
+        .as_str()
+        .and_then(|r| r.split_once('/'))
+        .is_some_and(|(ready, total)| ready == total);
+    let failing = !matches!(status, "Running" | "Succeeded" | "Completed");
+    u64::from(failing) * 1000 + u64::from(!ready && status != "Succeeded") * 100 + restarts
+}
+
+/// Last error lines of `logs`
+fn error_lines(logs: &str) -> Vec<String> {
+    let errors: Vec<&str> = logs
+        .lines()
+        .filter(|line| {
+            let line = line.to_lowercase();
+            ERROR_MARKERS.iter().any(|marker| line.contains(marker))
+        })
+        .collect();
+    errors[errors.len().saturating_sub(MAX_ERROR_LINES)..]
+        .iter()
+        .map(|line| line.trim().to_string())
+        .collect()
+}
+
+/// Walk the diagnosis flow, returning `None` when no pod needs attention
+pub async fn diagnose(
+    lifecycle: &LifecycleManager,
+    tools: &HashSet<String>,
+    target: &Target,
+) -> Result<Option<Report>> {
+    // 1. Find the pod
+    let mut arguments = json!({});
+    if let Some(namespace) = &target.namespace {
+        arguments["namespace"] = json!(namespace);
+    }
+    let listed = lifecycle.call_tool("list_k8s_pods", arguments).await?;
+    let namespace = listed["structuredContent"]["namespace"]
+        .as_str()
+        .unwrap_or("default")
+        .to_string();
+    let pods = listed["structuredContent"]["pods"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    println!("📋 {} pods in {}", pods.len(), namespace);
+    let pod = match &target.pod {
+        Some(name) => pods
+            .iter()
+            .find(|pod| pod["name"] == name.as_str())
+            .ok_or_else(|| Error::not_found(format!("No pod {} in {}", name, namespace)))?,
+        None => match pods.iter().max_by_key(|pod| severity(pod)) {
+            Some(pod) if severity(pod) > 0 => pod,
+            _ => return Ok(None),
+        },
+    };
+    let name = pod["name"].as_str().unwrap_or_default().to_string();
+    let mut report = Report {
+        namespace: namespace.clone(),
+        pod: name.clone(),
+        status: pod["status"].as_str().unwrap_or("Unknown").to_string(),
+        ready: pod["ready"].as_str().unwrap_or("?").to_string(),
+        restarts: pod["restarts"].as_u64().unwrap_or(0),
+        errors: Vec::new(),
+        restarts_last_hour: None,
+        skipped: Vec::new(),
+        remembered: false,
+    };
+    println!("🔎 Looking at {} ({})", name, report.status);
+
+    // 2. Stream its logs, showing them as they arrive
+    let mut stream = lifecycle
+        .call_tool_streaming(
+            "get_pod_logs",
+            json!({"pod_name": name, "namespace": namespace, "lines": target.lines}),
+        )
+        .await?;
+    let mut streamed = 0;
+    while let Some(chunk) = stream.next_chunk().await {
+        streamed += chunk.text().lines().count();
+        print!("{}", chunk.text());
+    }
+    let logs = stream.finish().await?;
+    let logs = logs
+        .content
+        .first()
+        .map(|block| block.content.clone())
+        .unwrap_or_default();
+    if streamed == 0 {
+        print!("{}", logs);
+    }
+    report.errors = error_lines(&logs);
+
+    // 3. Ask Prometheus how often it restarts
+    if tools.contains("prometheus_query") {
+        let query = format!(
+            "sum(increase(kube_pod_container_status_restarts_total{{namespace=\"{}\",pod=\"{}\"}}[1h]))",
+            namespace, name
+        );
+        let result = lifecycle
+            .call_tool("prometheus_query", json!({"query": query}))
+            .await?;
+        report.restarts_last_hour =
+            result["structuredContent"]["result"]["values"][0]["value"].as_f64();
+    } else {
+        report
+            .skipped
+            .push("restart rate (no prometheus_query tool)".to_string());
+    }
+
+    // 4. Remember what was found
+    if tools.contains("create_memory") {
+        lifecycle
+            .call_tool(
+                "create_memory",
+                json!({
+                    "memory_type": "knowledge",
+                    "title": format!("Diagnosis of {}/{}", namespace, name),
+                    "content": report.summary(),
+                    "tags": ["diagnosis", "kubernetes", namespace]
+                }),
+            )
+            .await?;
+        report.remembered = true;
+    } else {
+        report
+            .skipped
+            .push("saving findings (no create_memory tool)".to_string());
+    }
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use devops_mcp::monitoring::{PrometheusResult, PrometheusValue};
+    use devops_mcp::tools::{call_result, ServerModules, ToolDefinition, ToolRegistry};
+    use devops_mcp::transport::websocket::{router, WS_PATH};
+    use devops_mcp::transport::{Transport, WebSocketTransport};
+    use devops_mcp::Config;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn diagnoses_the_crashlooping_pod_end_to_end() {
+        // The server's own definitions, so a renamed tool fails here
+        let modules = Arc::new(
+            ServerModules::from_config(&Config::default())
+                .await
+                .unwrap(),
+        );
+        let mut server = ToolRegistry::new();
+        modules.register(&mut server).unwrap();
+        let definition = |name: &str| {
+            server
+                .unregister(name)
+                .unwrap_or_else(|| panic!("the server has no {} tool", name))
+        };
+
+        let registry = ToolRegistry::new();
+        registry
+            .register(definition("list_k8s_pods"), |_| async {
+                Ok(call_result(
+                    "3 pods",
+                    json!({"namespace": "shop", "pods": [
+                        {"name": "web-1", "status": "Running", "ready": "1/1", "restarts": 0},
+                        {"name": "checkout-2", "status": "CrashLoopBackOff", "ready": "0/1", "restarts": 14},
+                        {"name": "worker-3", "status": "Running", "ready": "1/1", "restarts": 2}
+                    ]}),
+                ))
+            })
+            .unwrap();
+        registry
+            .register_streaming(definition("get_pod_logs"), |arguments, stream| async move {
+                assert_eq!(arguments["pod_name"], "checkout-2");
+                let logs = "starting\nERROR db connection refused\npanic: no database\n";
+                stream.text(logs);
+                Ok(call_result(logs, json!({"logs": logs})))
+            })
+            .unwrap();
+        registry
+            .register(definition("prometheus_query"), |arguments| async move {
+                let query = arguments["query"].as_str().unwrap().to_string();
+                assert!(query.contains("checkout-2"));
+                let result = PrometheusResult {
+                    query,
+                    timestamp: chrono::Utc::now(),
+                    values: vec![PrometheusValue {
+                        metric: Default::default(),
+                        value: 6.0,
+                    }],
+                };
+                Ok(call_result("1 series", json!({ "result": result })))
+            })
+            .unwrap();
+        let memories = Arc::new(Mutex::new(Vec::new()));
+        let stored = Arc::clone(&memories);
+        // Served by the binary rather than the modules
+        registry
+            .register(
+                ToolDefinition::new("create_memory", "Memory"),
+                move |arguments| {
+                    stored.lock().unwrap().push(arguments);
+                    async { Ok(call_result("stored", json!({}))) }
+                },
+            )
+            .unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router(Arc::new(registry)))
+                .await
+                .unwrap()
+        });
+        let mut transport = WebSocketTransport::new(format!("ws://{}{}", addr, WS_PATH)).unwrap();
+        transport.connect().await.unwrap();
+        let mut lifecycle = LifecycleManager::new(Box::new(transport));
+        lifecycle.initialize().await.unwrap();
+        assert!(lifecycle.server_capabilities().await.is_some());
+        let tools: HashSet<String> = lifecycle
+            .list_tools()
+            .await
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect();
+
+        let report = diagnose(&lifecycle, &tools, &Target::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((report.pod.as_str(), report.restarts), ("checkout-2", 14));
+        assert_eq!(
+            report.errors,
+            ["ERROR db connection refused", "panic: no database"]
+        );
+        assert_eq!(report.restarts_last_hour, Some(6.0));
+        assert!(report.skipped.is_empty());
+        let memories = memories.lock().unwrap();
+        assert_eq!(memories[0]["title"], "Diagnosis of shop/checkout-2");
+    }
+}
@@ -0,0 +1,108 @@
+//! Example agent: connects to a running server and diagnoses a failing pod
+//!
+//! The agent performs the MCP handshake over WebSocket, lists the server's
+//! tools and then walks the flow an on-call engineer would: list the pods of
+//! a namespace, pick the unhealthy one, stream its logs, look up its restart
+//! rate in Prometheus, and record the findings as a memory. Steps whose
+//! tools the server does not offer are skipped and reported.
+//!
+//! The server always serves WebSocket at `/ws` on its HTTP port, 8080 unless
+//! `MCP_HTTP_PORT` says otherwise.
+//!
+//! ```text
+//! cargo run --release &
+//! cargo run --example agent -- --url ws://127.0.0.1:8080/ws --namespace shop
+//! cargo run --example agent -- --pod checkout-7d9f --namespace shop
+//! ```
+//!
+//! `cargo test --example agent` runs the same flow against an in-process
+//! server with scripted tools.
+
+mod diagnose;
+
+use devops_mcp::error::{Error, Result};
+use devops_mcp::lifecycle::LifecycleManager;
+use devops_mcp::transport::{Transport, WebSocketTransport};
+use diagnose::{diagnose, Target};
+use std::collections::HashSet;
+
+/// Server URL used without `--url` or `MCP_URL`
+const DEFAULT_URL: &str = "ws://127.0.0.1:8080/ws";
+
+struct Options {
+    url: String,
+    target: Target,
+}
+
+fn usage() -> Error {
+    Error::validation(
+        "Usage: agent [--url ws://host:port/ws] [--namespace NAME] [--pod NAME] [--lines N]",
+    )
+}
+
+fn parse_args() -> Result<Options> {
+    let mut options = Options {
+        url: std::env::var("MCP_URL").unwrap_or_else(|_| DEFAULT_URL.to_string()),
+        target: Target::default(),
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(usage);
+        match arg.as_str() {
+            "--url" => options.url = value()?,
+            "--namespace" | "-n" => options.target.namespace = Some(value()?),
+            "--pod" => options.target.pod = Some(value()?),
+            "--lines" => options.target.lines = value()?.parse().map_err(|_| usage())?,
+            _ => return Err(usage()),
+        }
+    }
+    Ok(options)
+}
+
+/// Connect and run the MCP handshake, returning the session and the server's tools
+async fn connect(url: &str) -> Result<(LifecycleManager, HashSet<String>)> {
+    if !url.starts_with("ws://") && !url.starts_with("wss://") {
+        return Err(Error::validation(
+            "The agent streams logs, so it needs a WebSocket URL (ws:// or wss://)",
+        ));
+    }
+    let mut transport = WebSocketTransport::new(url.to_string())?;
+    transport
+        .connect()
+        .await
+        .map_err(|e| Error::network(format!("Cannot reach {}: {}", url, e)))?;
+    let mut lifecycle = LifecycleManager::new(Box::new(transport));
+
+    let server = lifecycle.initialize().await?;
+    println!(
+        "🤝 Connected to {} {} (protocol {}, locale {})",
+        server["serverInfo"]["name"].as_str().unwrap_or("server"),
+        server["serverInfo"]["version"].as_str().unwrap_or("?"),
+        server["protocolVersion"].as_str().unwrap_or("?"),
+        server["locale"].as_str().unwrap_or("?")
+    );
+
+    let tools: HashSet<String> = lifecycle
+        .list_tools()
+        .await?
+        .iter()
+        .filter_map(|tool| tool["name"].as_str().map(String::from))
+        .collect();
+    println!("🛠️  {} tools available", tools.len());
+    Ok((lifecycle, tools))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter("devops_mcp=warn")
+        .init();
+
+    let options = parse_args()?;
+    let (lifecycle, tools) = connect(&options.url).await?;
+    match diagnose(&lifecycle, &tools, &options.target).await? {
+        Some(report) => println!("\n{}", report.summary()),
+        None => println!("\n✅ Every pod is running and ready, nothing to diagnose"),
+    }
+    Ok(())
+}
@@ -1,35 +1,41 @@
+use devops_mcp::office::excel::{
+    Cell, CellFormat, CellValue, Column, ExcelClient, Row, Workbook, Worksheet,
+};
+use devops_mcp::office::powerpoint::{
+    BulletPoint, Image, ImageType, PowerPointClient, Presentation, PresentationTheme, Slide,
+    SlideLayout,
+};
+use devops_mcp::office::word::{
+    Alignment, Document, Paragraph, Section, TextFormatting, WordClient,
+};
 /// Office Automation Example
-/// 
+///
 /// This example demonstrates how to use the Office modules to:
 /// 1. Create PowerPoint presentations
 /// 2. Generate Word documents
 /// 3. Work with Excel spreadsheets
 /// 4. Automate document creation workflows
-
 use devops_mcp::{new, Config};
-use devops_mcp::office::powerpoint::{PowerPointClient, Presentation, Slide, SlideLayout, PresentationTheme, BulletPoint, Image, ImageType};
-use devops_mcp::office::word::{WordClient, Document, Paragraph, Section, TextFormatting, Alignment};
-use devops_mcp::office::excel::{ExcelClient, Workbook, Worksheet, Cell, CellValue, CellFormat, Row, Column};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt()
         .with_env_filter("devops_mcp=debug")
         .init();
-    
+
     println!("📊 MCP Modules Rust - Office Automation Example");
-    
+
     // Initialize the client
     let config = Config::default();
     let mut client = new(config)?;
     client.initialize().await?;
     let lifecycle = client.lifecycle()?;
-    
+
     // Example 1: PowerPoint Presentation Creation
     println!("\n🎯 Creating PowerPoint Presentation");
-    
+
     let powerpoint = PowerPointClient::new(&lifecycle);
-    
+
     let presentation = Presentation {
         title: "Q4 2024 Performance Report".to_string(),
         author: Some("DevOps Team".to_string()),
@@ -45,7 +51,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 image: None,
                 notes: Some("Welcome everyone to our quarterly review".to_string()),
             },
-            
+
             // Overview slide
             Slide {
                 title: "Executive Summary".to_string(),
@@ -77,7 +83,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 image: None,
                 notes: Some("Highlight the key achievements and metrics".to_string()),
             },
-            
+
             // Performance metrics slide
             Slide {
                 title: "Performance Metrics".to_string(),
@@ -115,7 +121,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }),
                 notes: Some("Discuss the technical improvements that led to these gains".to_string()),
             },
-            
+
             // Future plans slide
             Slide {
                 title: "Q1 2025 Roadmap".to_string(),
@@ -149,24 +155,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
     };
-    
+
     match powerpoint.create_presentation(presentation).await {
         Ok(presentation_id) => {
             println!("✅ PowerPoint presentation created successfully!");
             println!("   Presentation ID: {}", presentation_id);
             println!("   Slides: 4");
             println!("   Theme: Office");
-        },
+        }
         Err(e) => {
             println!("⚠️  Failed to create presentation: {}", e);
         }
     }
-    
+
     // Example 2: Word Document Generation
     println!("\n📄 Creating Word Document");
-    
+
     let word = WordClient::new(&lifecycle);
-    
+
     let document = Document {
         title: "Infrastructure Deployment Guide".to_string(),
         author: Some("DevOps Team".to_string()),
@@ -292,24 +298,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
     };
-    
+
     match word.create_document(document).await {
         Ok(document_id) => {
             println!("✅ Word document created successfully!");
             println!("   Document ID: {}", document_id);
             println!("   Sections: 3");
             println!("   Title: Infrastructure Deployment Guide");
-        },
+        }
         Err(e) => {
             println!("⚠️  Failed to create document: {}", e);
         }
     }
-    
+
     // Example 3: Excel Spreadsheet with Performance Data
     println!("\n📈 Creating Excel Spreadsheet");
-    
+
     let excel = ExcelClient::new(&lifecycle);
-    
+
     let workbook = Workbook {
         title: "Q4 Performance Metrics".to_string(),
         author: Some("DevOps Team".to_string()),
@@ -317,59 +323,215 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Worksheet {
                 name: "Performance Data".to_string(),
                 columns: Some(vec![
-                    Column { index: 0, width: Some(150.0), format: None },
-                    Column { index: 1, width: Some(100.0), format: None },
-                    Column { index: 2, width: Some(100.0), format: None },
-                    Column { index: 3, width: Some(120.0), format: None },
+                    Column {
+                        index: 0,
+                        width: Some(150.0),
+                        format: None,
+                    },
+                    Column {
+                        index: 1,
+                        width: Some(100.0),
+                        format: None,
+                    },
+                    Column {
+                        index: 2,
+                        width: Some(100.0),
+                        format: None,
+                    },
+                    Column {
+                        index: 3,
+                        width: Some(120.0),
+                        format: None,
+                    },
                 ]),
                 rows: vec![
                     Row {
                         index: 0,
                         cells: vec![
-                            Cell { value: CellValue::Text("Metric".to_string()), format: Some(CellFormat { bold: Some(true), background_color: Some("#CCCCCC".to_string()), font_name: None, font_size: None, italic: None, underline: None, color: None, number_format: None, alignment: None }) },
-                            Cell { value: CellValue::Text("Q3 2024".to_string()), format: Some(CellFormat { bold: Some(true), background_color: Some("#CCCCCC".to_string()), font_name: None, font_size: None, italic: None, underline: None, color: None, number_format: None, alignment: None }) },
-                            Cell { value: CellValue::Text("Q4 2024".to_string()), format: Some(CellFormat { bold: Some(true), background_color: Some("#CCCCCC".to_string()), font_name: None, font_size: None, italic: None, underline: None, color: None, number_format: None, alignment: None }) },
-                            Cell { value: CellValue::Text("Improvement".to_string()), format: Some(CellFormat { bold: Some(true), background_color: Some("#CCCCCC".to_string()), font_name: None, font_size: None, italic: None, underline: None, color: None, number_format: None, alignment: None }) },
+                            Cell {
+                                value: CellValue::Text("Metric".to_string()),
+                                format: Some(CellFormat {
+                                    bold: Some(true),
+                                    background_color: Some("#CCCCCC".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    italic: None,
+                                    underline: None,
+                                    color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
+                            Cell {
+                                value: CellValue::Text("Q3 2024".to_string()),
+                                format: Some(CellFormat {
+                                    bold: Some(true),
+                                    background_color: Some("#CCCCCC".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    italic: None,
+                                    underline: None,
+                                    color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
+                            Cell {
+                                value: CellValue::Text("Q4 2024".to_string()),
+                                format: Some(CellFormat {
+                                    bold: Some(true),
+                                    background_color: Some("#CCCCCC".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    italic: None,
+                                    underline: None,
+                                    color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
+                            Cell {
+                                value: CellValue::Text("Improvement".to_string()),
+                                format: Some(CellFormat {
+                                    bold: Some(true),
+                                    background_color: Some("#CCCCCC".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    italic: None,
+                                    underline: None,
+                                    color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
                         ],
                         height: None,
                     },
                     Row {
                         index: 1,
                         cells: vec![
-                            Cell { value: CellValue::Text("Response Time (ms)".to_string()), format: None },
-                            Cell { value: CellValue::Number(220.0), format: None },
-                            Cell { value: CellValue::Number(120.0), format: None },
-                            Cell { value: CellValue::Text("45%".to_string()), format: Some(CellFormat { color: Some("#00AA00".to_string()), font_name: None, font_size: None, bold: None, italic: None, underline: None, background_color: None, number_format: None, alignment: None }) },
+                            Cell {
+                                value: CellValue::Text("Response Time (ms)".to_string()),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(220.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(120.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Text("45%".to_string()),
+                                format: Some(CellFormat {
+                                    color: Some("#00AA00".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    bold: None,
+                                    italic: None,
+                                    underline: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
                         ],
                         height: None,
                     },
                     Row {
                         index: 1,
                         cells: vec![
-                            Cell { value: CellValue::Text("Throughput (req/sec)".to_string()), format: None },
-                        Cell { value: CellValue::Number(6500.0), format: None },
-                        Cell { value: CellValue::Number(10000.0), format: None },
-                        Cell { value: CellValue::Text("54%".to_string()), format: Some(CellFormat { color: Some("#008000".to_string()), font_name: None, font_size: None, bold: None, italic: None, underline: None, background_color: None, number_format: None, alignment: None }) },
+                            Cell {
+                                value: CellValue::Text("Throughput (req/sec)".to_string()),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(6500.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(10000.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Text("54%".to_string()),
+                                format: Some(CellFormat {
+                                    color: Some("#008000".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    bold: None,
+                                    italic: None,
+                                    underline: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
                         ],
                         height: None,
                     },
                     Row {
                         index: 2,
                         cells: vec![
-                            Cell { value: CellValue::Text("Error Rate (%)".to_string()), format: None },
-                        Cell { value: CellValue::Number(0.05), format: None },
-                        Cell { value: CellValue::Number(0.01), format: None },
-                        Cell { value: CellValue::Text("80%".to_string()), format: Some(CellFormat { color: Some("#008000".to_string()), font_name: None, font_size: None, bold: None, italic: None, underline: None, background_color: None, number_format: None, alignment: None }) },
+                            Cell {
+                                value: CellValue::Text("Error Rate (%)".to_string()),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(0.05),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(0.01),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Text("80%".to_string()),
+                                format: Some(CellFormat {
+                                    color: Some("#008000".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    bold: None,
+                                    italic: None,
+                                    underline: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
                         ],
                         height: None,
                     },
                     Row {
                         index: 3,
                         cells: vec![
-                            Cell { value: CellValue::Text("Memory Usage (%)".to_string()), format: None },
-                        Cell { value: CellValue::Number(85.0), format: None },
-                        Cell { value: CellValue::Number(65.0), format: None },
-                        Cell { value: CellValue::Text("24%".to_string()), format: Some(CellFormat { color: Some("#008000".to_string()), font_name: None, font_size: None, bold: None, italic: None, underline: None, background_color: None, number_format: None, alignment: None }) },
+                            Cell {
+                                value: CellValue::Text("Memory Usage (%)".to_string()),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(85.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(65.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Text("24%".to_string()),
+                                format: Some(CellFormat {
+                                    color: Some("#008000".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    bold: None,
+                                    italic: None,
+                                    underline: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
                         ],
                         height: None,
                     },
@@ -379,49 +541,183 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Worksheet {
                 name: "Cost Analysis".to_string(),
                 columns: Some(vec![
-                    Column { index: 0, width: Some(120.0), format: None },
-                    Column { index: 1, width: Some(100.0), format: None },
-                    Column { index: 2, width: Some(100.0), format: None },
-                    Column { index: 3, width: Some(100.0), format: None },
+                    Column {
+                        index: 0,
+                        width: Some(120.0),
+                        format: None,
+                    },
+                    Column {
+                        index: 1,
+                        width: Some(100.0),
+                        format: None,
+                    },
+                    Column {
+                        index: 2,
+                        width: Some(100.0),
+                        format: None,
+                    },
+                    Column {
+                        index: 3,
+                        width: Some(100.0),
+                        format: None,
+                    },
                 ]),
                 rows: vec![
                     Row {
                         index: 0,
                         cells: vec![
-                        Cell { value: CellValue::Text("Service".to_string()), format: Some(CellFormat { bold: Some(true), font_name: None, font_size: None, italic: None, underline: None, color: None, background_color: None, number_format: None, alignment: None }) },
-                        Cell { value: CellValue::Text("Q3 Cost".to_string()), format: Some(CellFormat { bold: Some(true), font_name: None, font_size: None, italic: None, underline: None, color: None, background_color: None, number_format: None, alignment: None }) },
-                        Cell { value: CellValue::Text("Q4 Cost".to_string()), format: Some(CellFormat { bold: Some(true), font_name: None, font_size: None, italic: None, underline: None, color: None, background_color: None, number_format: None, alignment: None }) },
-                        Cell { value: CellValue::Text("Savings".to_string()), format: Some(CellFormat { bold: Some(true), font_name: None, font_size: None, italic: None, underline: None, color: None, background_color: None, number_format: None, alignment: None }) },
+                            Cell {
+                                value: CellValue::Text("Service".to_string()),
+                                format: Some(CellFormat {
+                                    bold: Some(true),
+                                    font_name: None,
+                                    font_size: None,
+                                    italic: None,
+                                    underline: None,
+                                    color: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
+                            Cell {
+                                value: CellValue::Text("Q3 Cost".to_string()),
+                                format: Some(CellFormat {
+                                    bold: Some(true),
+                                    font_name: None,
+                                    font_size: None,
+                                    italic: None,
+                                    underline: None,
+                                    color: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
+                            Cell {
+                                value: CellValue::Text("Q4 Cost".to_string()),
+                                format: Some(CellFormat {
+                                    bold: Some(true),
+                                    font_name: None,
+                                    font_size: None,
+                                    italic: None,
+                                    underline: None,
+                                    color: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
+                            Cell {
+                                value: CellValue::Text("Savings".to_string()),
+                                format: Some(CellFormat {
+                                    bold: Some(true),
+                                    font_name: None,
+                                    font_size: None,
+                                    italic: None,
+                                    underline: None,
+                                    color: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
                         ],
                         height: None,
                     },
                     Row {
                         index: 1,
                         cells: vec![
-                            Cell { value: CellValue::Text("Compute".to_string()), format: None },
-                        Cell { value: CellValue::Number(15000.0), format: None },
-                        Cell { value: CellValue::Number(12000.0), format: None },
-                        Cell { value: CellValue::Number(3000.0), format: Some(CellFormat { color: Some("#008000".to_string()), font_name: None, font_size: None, bold: None, italic: None, underline: None, background_color: None, number_format: None, alignment: None }) },
+                            Cell {
+                                value: CellValue::Text("Compute".to_string()),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(15000.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(12000.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(3000.0),
+                                format: Some(CellFormat {
+                                    color: Some("#008000".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    bold: None,
+                                    italic: None,
+                                    underline: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
                         ],
                         height: None,
                     },
                     Row {
                         index: 2,
                         cells: vec![
-                            Cell { value: CellValue::Text("Storage".to_string()), format: None },
-                        Cell { value: CellValue::Number(5000.0), format: None },
-                        Cell { value: CellValue::Number(3500.0), format: None },
-                        Cell { value: CellValue::Number(1500.0), format: Some(CellFormat { color: Some("#008000".to_string()), font_name: None, font_size: None, bold: None, italic: None, underline: None, background_color: None, number_format: None, alignment: None }) },
+                            Cell {
+                                value: CellValue::Text("Storage".to_string()),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(5000.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(3500.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(1500.0),
+                                format: Some(CellFormat {
+                                    color: Some("#008000".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    bold: None,
+                                    italic: None,
+                                    underline: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
                         ],
                         height: None,
                     },
                     Row {
                         index: 3,
                         cells: vec![
-                            Cell { value: CellValue::Text("Network".to_string()), format: None },
-                        Cell { value: CellValue::Number(2000.0), format: None },
-                        Cell { value: CellValue::Number(1800.0), format: None },
-                        Cell { value: CellValue::Number(200.0), format: Some(CellFormat { color: Some("#008000".to_string()), font_name: None, font_size: None, bold: None, italic: None, underline: None, background_color: None, number_format: None, alignment: None }) },
+                            Cell {
+                                value: CellValue::Text("Network".to_string()),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(2000.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(1800.0),
+                                format: None,
+                            },
+                            Cell {
+                                value: CellValue::Number(200.0),
+                                format: Some(CellFormat {
+                                    color: Some("#008000".to_string()),
+                                    font_name: None,
+                                    font_size: None,
+                                    bold: None,
+                                    italic: None,
+                                    underline: None,
+                                    background_color: None,
+                                    number_format: None,
+                                    alignment: None,
+                                }),
+                            },
                         ],
                         height: None,
                     },
@@ -430,25 +726,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
     };
-    
+
     match excel.create_workbook(workbook).await {
         Ok(workbook_id) => {
             println!("✅ Excel workbook created successfully!");
             println!("   Workbook ID: {}", workbook_id);
             println!("   Worksheets: 2");
             println!("   Data points: 8 performance metrics");
-        },
+        }
         Err(e) => {
             println!("⚠️  Failed to create workbook: {}", e);
         }
     }
-    
+
     // Example 4: Automated Report Generation Workflow
     println!("\n🔄 Automated Workflow Example");
-    
+
     // This would typically be triggered by a scheduler or event
     let workflow_results = generate_monthly_report(&powerpoint, &word, &excel).await;
-    
+
     match workflow_results {
         Ok(report_ids) => {
             println!("✅ Automated report generation completed!");
@@ -456,31 +752,31 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             for (doc_type, id) in report_ids {
                 println!("   - {}: {}", doc_type, id);
             }
-        },
+        }
         Err(e) => {
             println!("⚠️  Automated workflow failed: {}", e);
         }
     }
-    
+
     // Example 5: Bulk Operations
     println!("\n📦 Bulk Operations Example");
-    
+
     // Generate multiple presentations for different teams
     let teams = vec!["Backend", "Frontend", "DevOps", "Security"];
-    
+
     for team in teams {
         let team_presentation = create_team_presentation(team);
-        
+
         match powerpoint.create_presentation(team_presentation).await {
             Ok(id) => {
                 println!("✅ Created presentation for {} team (ID: {})", team, id);
-            },
+            }
             Err(e) => {
                 println!("⚠️  Failed to create presentation for {} team: {}", team, e);
             }
         }
     }
-    
+
     println!("\n🎉 Office automation example completed!");
     println!("💡 This example showed how to:");
     println!("   - Create comprehensive PowerPoint presentations");
@@ -488,7 +784,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("   - Build Excel spreadsheets with data");
     println!("   - Automate document workflows");
     println!("   - Perform bulk operations");
-    
+
     Ok(())
 }
 
@@ -499,97 +795,129 @@ async fn generate_monthly_report(
     excel: &ExcelClient<'_>,
 ) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
     let mut results = Vec::new();
-    
+
     // Generate executive summary presentation
     let exec_summary = Presentation {
         title: "Monthly Executive Summary".to_string(),
         author: Some("Automated Report Generator".to_string()),
         theme: PresentationTheme::Modern,
-        slides: vec![
-            Slide {
-                title: "Monthly Performance Summary".to_string(),
-                layout: SlideLayout::Title,
-                subtitle: Some("Automated Infrastructure Report".to_string()),
-                content: None,
-                bullets: None,
-                image: None,
-                notes: None,
-            },
-        ],
+        slides: vec![Slide {
+            title: "Monthly Performance Summary".to_string(),
+            layout: SlideLayout::Title,
+            subtitle: Some("Automated Infrastructure Report".to_string()),
+            content: None,
+            bullets: None,
+            image: None,
+            notes: None,
+        }],
     };
-    
+
     let ppt_id = powerpoint.create_presentation(exec_summary).await?;
     results.push(("PowerPoint".to_string(), ppt_id));
-    
+
     // Generate detailed technical document
     let tech_doc = Document {
         title: "Monthly Technical Report".to_string(),
         author: Some("Automated Report Generator".to_string()),
-        sections: vec![
-            Section {
-                title: Some("System Status".to_string()),
-                paragraphs: vec![
-                    Paragraph {
-                        text: "All systems operational with excellent performance metrics.".to_string(),
-                        formatting: Some(TextFormatting {
-                            font_name: None,
-                            font_size: None,
-                            bold: None,
-                            italic: None,
-                            underline: None,
-                            color: None,
-                        }),
-                        alignment: Some(Alignment::Left),
-                        is_heading: None,
-                        heading_level: None,
-                    },
-                ],
-                tables: None,
-                images: None,
-            },
-        ],
+        sections: vec![Section {
+            title: Some("System Status".to_string()),
+            paragraphs: vec![Paragraph {
+                text: "All systems operational with excellent performance metrics.".to_string(),
+                formatting: Some(TextFormatting {
+                    font_name: None,
+                    font_size: None,
+                    bold: None,
+                    italic: None,
+                    underline: None,
+                    color: None,
+                }),
+                alignment: Some(Alignment::Left),
+                is_heading: None,
+                heading_level: None,
+            }],
+            tables: None,
+            images: None,
+        }],
     };
-    
+
     let doc_id = word.create_document(tech_doc).await?;
     results.push(("Word".to_string(), doc_id));
-    
+
     // Generate metrics spreadsheet
     let metrics_workbook = Workbook {
         title: "Monthly Metrics".to_string(),
         author: Some("Automated Report Generator".to_string()),
-        worksheets: vec![
-            Worksheet {
-                name: "Summary".to_string(),
-                columns: Some(vec![
-                    Column { index: 0, width: Some(120.0), format: None },
-                    Column { index: 1, width: Some(100.0), format: None },
-                ]),
-                rows: vec![
-                    Row {
-                        index: 0,
-                        cells: vec![
-                        Cell { value: CellValue::Text("Metric".to_string()), format: Some(CellFormat { bold: Some(true), font_name: None, font_size: None, italic: None, underline: None, color: None, background_color: None, number_format: None, alignment: None }) },
-                        Cell { value: CellValue::Text("Value".to_string()), format: Some(CellFormat { bold: Some(true), font_name: None, font_size: None, italic: None, underline: None, color: None, background_color: None, number_format: None, alignment: None }) },
-                        ],
-                        height: None,
-                    },
-                    Row {
-                        index: 1,
-                        cells: vec![
-                            Cell { value: CellValue::Text("Uptime".to_string()), format: None },
-                        Cell { value: CellValue::Text("99.9%".to_string()), format: None },
-                        ],
-                        height: None,
-                    },
-                ],
-                charts: None,
-            },
-        ],
+        worksheets: vec![Worksheet {
+            name: "Summary".to_string(),
+            columns: Some(vec![
+                Column {
+                    index: 0,
+                    width: Some(120.0),
+                    format: None,
+                },
+                Column {
+                    index: 1,
+                    width: Some(100.0),
+                    format: None,
+                },
+            ]),
+            rows: vec![
+                Row {
+                    index: 0,
+                    cells: vec![
+                        Cell {
+                            value: CellValue::Text("Metric".to_string()),
+                            format: Some(CellFormat {
+                                bold: Some(true),
+                                font_name: None,
+                                font_size: None,
+                                italic: None,
+                                underline: None,
+                                color: None,
+                                background_color: None,
+                                number_format: None,
+                                alignment: None,
+                            }),
+                        },
+                        Cell {
+                            value: CellValue::Text("Value".to_string()),
+                            format: Some(CellFormat {
+                                bold: Some(true),
+                                font_name: None,
+                                font_size: None,
+                                italic: None,
+                                underline: None,
+                                color: None,
+                                background_color: None,
+                                number_format: None,
+                                alignment: None,
+                            }),
+                        },
+                    ],
+                    height: None,
+                },
+                Row {
+                    index: 1,
+                    cells: vec![
+                        Cell {
+                            value: CellValue::Text("Uptime".to_string()),
+                            format: None,
+                        },
+                        Cell {
+                            value: CellValue::Text("99.9%".to_string()),
+                            format: None,
+                        },
+                    ],
+                    height: None,
+                },
+            ],
+            charts: None,
+        }],
     };
-    
+
     let excel_id = excel.create_workbook(metrics_workbook).await?;
     results.push(("Excel".to_string(), excel_id));
-    
+
     Ok(results)
 }
 
@@ -636,4 +964,4 @@ fn create_team_presentation(team: &str) -> Presentation {
             },
         ],
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,7 @@
-use devops_mcp::{Mcp, Config};
 use devops_mcp::config::TransportConfig;
 use devops_mcp::error::Result;
-use devops_mcp::memory::{MemoryClient, MemoryType, MemorySearchParams};
+use devops_mcp::memory::{MemoryClient, MemorySearchParams, MemoryType};
+use devops_mcp::{Config, Mcp};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -46,20 +46,26 @@ async fn main() -> Result<()> {
 
     // Test memory module (always available)
     println!("\n🧠 Testing memory module:");
-    
+
     // Create memory client
     let memory_client = MemoryClient::new(&lifecycle);
-    
+
     // Store a memory
-    let memory_id = memory_client.create_memory(
-        MemoryType::Knowledge,
-        "Example Note",
-        "This is a test note created by the simple client example.",
-        Some([
-            ("source".to_string(), serde_json::json!("simple_client.rs")),
-            ("category".to_string(), serde_json::json!("example"))
-        ].into_iter().collect())
-    ).await?;
+    let memory_id = memory_client
+        .create_memory(
+            MemoryType::Knowledge,
+            "Example Note",
+            "This is a test note created by the simple client example.",
+            Some(
+                [
+                    ("source".to_string(), serde_json::json!("simple_client.rs")),
+                    ("category".to_string(), serde_json::json!("example")),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+        )
+        .await?;
     println!("   ✅ Stored memory with ID: {}", memory_id);
 
     // Retrieve the memory
@@ -72,7 +78,7 @@ async fn main() -> Result<()> {
         keyword: Some("test".to_string()),
         memory_type: None,
         metadata_filters: None,
-        limit: Some(10)
+        limit: Some(10),
     };
     let search_results = memory_client.search_memories(search_params).await?;
     println!("   🔍 Found {} search results", search_results.len());
@@ -82,4 +88,4 @@ async fn main() -> Result<()> {
 
     println!("\n✨ All operations completed successfully!");
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1 @@
+WITH moved AS (DELETE FROM events RETURNING *) SELECT * FROM moved
\ No newline at end of file
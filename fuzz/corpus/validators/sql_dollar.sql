@@ -0,0 +1 @@
+SELECT $tag$ ; DROP TABLE x; $tag$, E'\'' FROM "weird""name" /* unterminated
\ No newline at end of file
@@ -0,0 +1 @@
+EXPLAIN (ANALYZE, BUFFERS) UPDATE t SET x = 1
\ No newline at end of file
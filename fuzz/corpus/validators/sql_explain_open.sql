@@ -0,0 +1 @@
+explain (
\ No newline at end of file
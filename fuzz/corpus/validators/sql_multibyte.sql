@@ -0,0 +1 @@
+SELECT 'café' AS 名前; -- üñîçødé
\ No newline at end of file
@@ -0,0 +1,707 @@
+/// Context packs for LLM sessions
+///
+/// A context pack gathers what is known about a topic — a service, project
+/// or host — from stored memories, firing alerts, configuration files and
+/// documentation, ranks the pieces by how closely they match the topic and
+/// fits the best of them into a token budget. Every excerpt carries a
+/// citation so the model can point back to where a fact came from.
+use crate::entity::{EntityResolver, FiringAlert};
+use crate::error::{Error, Result};
+use crate::memory::{Memory, MemoryClient, MemorySearchParams};
+use crate::tools::{call_result, ToolDefinition};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+/// Rough characters per token for budgeting
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Excerpts shorter than this are not worth truncating into the remaining budget
+const MIN_EXCERPT_TOKENS: usize = 48;
+
+/// Lines per configuration chunk
+const CONFIG_CHUNK_LINES: usize = 40;
+
+/// Files larger than this are skipped
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+
+const CONFIG_EXTENSIONS: &[&str] = &["yaml", "yml", "json", "toml", "env", "conf", "ini"];
+const DOC_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "rst"];
+
+/// Where an excerpt came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContextSource {
+    Alert,
+    Memory,
+    Config,
+    Doc,
+}
+
+impl ContextSource {
+    const ALL: [ContextSource; 4] = [
+        ContextSource::Alert,
+        ContextSource::Memory,
+        ContextSource::Config,
+        ContextSource::Doc,
+    ];
+}
+
+/// One cited piece of context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextExcerpt {
+    pub source: ContextSource,
+    /// `path:start-end`, `memory:<id>` or `alert:<fingerprint>`
+    pub citation: String,
+    pub title: String,
+    pub text: String,
+    pub score: f64,
+    pub tokens: usize,
+    pub truncated: bool,
+}
+
+/// Excerpts selected for a topic, rendered for injection into a conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextPack {
+    pub topic: String,
+    pub token_budget: usize,
+    pub tokens: usize,
+    pub excerpts: Vec<ContextExcerpt>,
+    /// Matching excerpts left out for lack of budget
+    pub omitted: usize,
+    /// Sources that could not be read, with the reason
+    pub warnings: Vec<String>,
+}
+
+impl ContextPack {
+    /// Markdown with numbered citations, ready to use as a system or user message
+    pub fn render(&self) -> String {
+        let mut text = format!("# Context: {}\n", self.topic);
+        for source in ContextSource::ALL {
+            let excerpts: Vec<(usize, &ContextExcerpt)> = self
+                .excerpts
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.source == source)
+                .collect();
+            if excerpts.is_empty() {
+                continue;
+            }
+            text.push_str(&format!("\n## {}\n", section_title(source)));
+            for (i, excerpt) in excerpts {
+                text.push_str(&format!(
+                    "\n### {} [{}]\n{}\n",
+                    excerpt.title,
+                    i + 1,
+                    excerpt.text
+                ));
+            }
+        }
+        if !self.excerpts.is_empty() {
+            text.push_str("\n## Sources\n");
+            for (i, excerpt) in self.excerpts.iter().enumerate() {
+                text.push_str(&format!("[{}] {}\n", i + 1, excerpt.citation));
+            }
+        }
+        text
+    }
+}
+
+fn section_title(source: ContextSource) -> &'static str {
+    match source {
+        ContextSource::Alert => "Firing alerts",
+        ContextSource::Memory => "Memories",
+        ContextSource::Config => "Configuration",
+        ContextSource::Doc => "Documentation",
+    }
+}
+
+/// Approximate token count
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Lowercase search terms for a topic: the whole topic plus its parts
+fn topic_terms(topic: &str) -> Vec<String> {
+    let topic = topic.trim().to_ascii_lowercase();
+    let mut terms = vec![topic.clone()];
+    terms.extend(
+        topic
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|part| part.len() >= 3)
+            .map(String::from),
+    );
+    terms.dedup();
+    terms
+}
+
+/// How well a title and body match the topic
+///
+/// A whole-topic match counts most, title matches count double, and long
+/// bodies are not rewarded just for repeating a term.
+fn score(terms: &[String], title: &str, body: &str) -> f64 {
+    let title = title.to_ascii_lowercase();
+    let body = body.to_ascii_lowercase();
+    let mut total = 0.0;
+    for (i, term) in terms.iter().enumerate() {
+        let weight = if i == 0 { 3.0 } else { 1.0 };
+        if title.contains(term.as_str()) {
+            total += 2.0 * weight;
+        }
+        let hits = body.matches(term.as_str()).count();
+        if hits > 0 {
+            total += weight * (1.0 + (hits as f64).ln());
+        }
+    }
+    total
+}
+
+/// Replace values of secret-looking keys in configuration text
+pub fn redact_secrets(text: &str) -> String {
+    static SECRET: OnceLock<Regex> = OnceLock::new();
+    let secret = SECRET.get_or_init(|| {
+        Regex::new(
+            r##"(?im)^(\s*["']?[\w.-]*(?:password|passwd|secret|token|api[_-]?key|private[_-]?key|credential)[\w.-]*["']?\s*[:=]\s*)("?)[^\s"#,]+"?"##,
+        )
+        .expect("valid secret regex")
+    });
+    secret
+        .replace_all(text, "${1}${2}<redacted>${2}")
+        .into_owned()
+}
+
+/// A section of a file with its line range
+struct Chunk {
+    title: String,
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+/// Split Markdown at headings, keeping each heading with its body
+fn doc_chunks(content: &str) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut in_fence = false;
+    for (i, line) in content.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let heading = !in_fence && line.starts_with('#');
+        if heading || chunks.is_empty() {
+            chunks.push(Chunk {
+                title: line.trim_start_matches('#').trim().to_string(),
+                start: i + 1,
+                end: i + 1,
+                text: String::new(),
+            });
+        }
+        let chunk = chunks.last_mut().expect("chunk pushed above");
+        chunk.end = i + 1;
+        chunk.text.push_str(line);
+        chunk.text.push('\n');
+    }
+    chunks.retain(|c| !c.text.trim().is_empty());
+    chunks
+}
+
+/// Split configuration into fixed-size line windows
+fn config_chunks(content: &str) -> Vec<Chunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .chunks(CONFIG_CHUNK_LINES)
+        .enumerate()
+        .map(|(i, window)| Chunk {
+            title: String::new(),
+            start: i * CONFIG_CHUNK_LINES + 1,
+            end: i * CONFIG_CHUNK_LINES + window.len(),
+            text: window.join("\n"),
+        })
+        .filter(|c| !c.text.trim().is_empty())
+        .collect()
+}
+
+/// Files under `path` (or `path` itself) with one of the extensions
+fn files_with_extensions(path: &Path, extensions: &[&str], files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return;
+        };
+        let mut entries: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+        entries.sort();
+        for entry in entries {
+            let hidden = entry
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with('.') || n == "target" || n == "node_modules");
+            if !hidden {
+                files_with_extensions(&entry, extensions, files);
+            }
+        }
+    } else if path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.contains(&e.to_ascii_lowercase().as_str()))
+        && path.metadata().is_ok_and(|m| m.len() <= MAX_FILE_BYTES)
+    {
+        files.push(path.to_path_buf());
+    }
+}
+
+/// Whether an alert concerns the topic, by name, summary or label values
+fn alert_matches(alert: &FiringAlert, terms: &[String]) -> bool {
+    let topic = &terms[0];
+    alert
+        .labels
+        .values()
+        .any(|v| v.to_ascii_lowercase().contains(topic.as_str()))
+        || alert.name.to_ascii_lowercase().contains(topic.as_str())
+        || alert
+            .summary
+            .as_deref()
+            .is_some_and(|s| s.to_ascii_lowercase().contains(topic.as_str()))
+}
+
+fn alert_excerpt(alert: &FiringAlert, terms: &[String]) -> ContextExcerpt {
+    let mut text = format!("State: {}", alert.state);
+    if let Some(starts_at) = alert.starts_at {
+        text.push_str(&format!(", since {}", starts_at.to_rfc3339()));
+    }
+    if let Some(summary) = &alert.summary {
+        text.push_str(&format!("\n{}", summary));
+    }
+    let labels: Vec<String> = alert
+        .labels
+        .iter()
+        .filter(|(k, _)| k.as_str() != "alertname")
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    text.push_str(&format!("\nLabels: {}", labels.join(", ")));
+    // Firing alerts always outrank background material about the same topic
+    let score = 100.0 + score(terms, &alert.name, &text);
+    excerpt(
+        ContextSource::Alert,
+        format!("alert:{}", alert.fingerprint),
+        alert.name.clone(),
+        text,
+        score,
+    )
+}
+
+fn memory_excerpt(memory: &Memory, terms: &[String]) -> ContextExcerpt {
+    let text = format!(
+        "({}, updated {})\n{}",
+        memory.memory_type,
+        memory.updated_at.format("%Y-%m-%d"),
+        memory.content.trim()
+    );
+    excerpt(
+        ContextSource::Memory,
+        format!("memory:{}", memory.id),
+        memory.title.clone(),
+        text,
+        score(terms, &memory.title, &memory.content),
+    )
+}
+
+fn excerpt(
+    source: ContextSource,
+    citation: String,
+    title: String,
+    text: String,
+    score: f64,
+) -> ContextExcerpt {
+    ContextExcerpt {
+        source,
+        tokens: estimate_tokens(&title) + estimate_tokens(&text),
+        citation,
+        title,
+        text,
+        score,
+        truncated: false,
+    }
+}
+
+/// Cut an excerpt down to `tokens`, at a line boundary where possible
+fn truncate(excerpt: &mut ContextExcerpt, tokens: usize) {
+    let title_tokens = estimate_tokens(&excerpt.title);
+    let limit = tokens.saturating_sub(title_tokens + 1) * CHARS_PER_TOKEN;
+    let mut end = limit.min(excerpt.text.len());
+    while !excerpt.text.is_char_boundary(end) {
+        end -= 1;
+    }
+    if let Some(newline) = excerpt.text[..end].rfind('\n') {
+        if newline > end / 2 {
+            end = newline;
+        }
+    }
+    excerpt.text.truncate(end);
+    excerpt.text.push('…');
+    excerpt.tokens = title_tokens + estimate_tokens(&excerpt.text);
+    excerpt.truncated = true;
+}
+
+/// Choose excerpts under the budget
+///
+/// The best excerpt from each source goes in first so one verbose source
+/// cannot crowd out the others; the rest are added by score.
+pub fn select(mut candidates: Vec<ContextExcerpt>, budget: usize) -> (Vec<ContextExcerpt>, usize) {
+    candidates.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.citation.cmp(&b.citation))
+    });
+    let mut seen = BTreeSet::new();
+    let (mut order, rest): (Vec<usize>, Vec<usize>) =
+        (0..candidates.len()).partition(|&i| seen.insert(candidates[i].source));
+    order.extend(rest);
+
+    let mut remaining = budget;
+    let mut chosen = vec![false; candidates.len()];
+    for &i in &order {
+        let candidate = &mut candidates[i];
+        if candidate.tokens > remaining {
+            if remaining < MIN_EXCERPT_TOKENS {
+                continue;
+            }
+            truncate(candidate, remaining);
+        }
+        remaining -= candidate.tokens;
+        chosen[i] = true;
+    }
+    let omitted = chosen.iter().filter(|c| !**c).count();
+    let selected = candidates
+        .into_iter()
+        .zip(chosen)
+        .filter_map(|(c, chosen)| chosen.then_some(c))
+        .collect();
+    (selected, omitted)
+}
+
+/// Parameters for a context pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextRequest {
+    pub topic: String,
+    #[serde(default = "default_token_budget")]
+    pub token_budget: usize,
+    /// Sources to read; all when omitted
+    #[serde(default)]
+    pub sources: Option<Vec<ContextSource>>,
+}
+
+fn default_token_budget() -> usize {
+    2000
+}
+
+/// Assembles context packs from the configured sources
+pub struct ContextPackBuilder {
+    memory: Option<Arc<MemoryClient>>,
+    alerts: Option<Arc<EntityResolver>>,
+    config_paths: Vec<PathBuf>,
+    doc_paths: Vec<PathBuf>,
+}
+
+/// Paths from a colon-separated environment variable
+fn env_paths(var: &str) -> Vec<PathBuf> {
+    std::env::var(var)
+        .map(|v| std::env::split_paths(&v).collect())
+        .unwrap_or_default()
+}
+
+impl ContextPackBuilder {
+    /// Create a builder reading configuration from `CONTEXT_CONFIG_PATHS` and
+    /// documentation from `CONTEXT_DOC_PATHS` (default `docs`)
+    pub fn new() -> Self {
+        Self {
+            memory: None,
+            alerts: None,
+            config_paths: env_paths("CONTEXT_CONFIG_PATHS"),
+            doc_paths: env_paths("CONTEXT_DOC_PATHS"),
+        }
+    }
+
+    /// Include stored memories
+    pub fn with_memory(mut self, memory: Arc<MemoryClient>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Include firing alerts from the resolver's Alertmanager
+    pub fn with_alerts(mut self, resolver: Arc<EntityResolver>) -> Self {
+        self.alerts = Some(resolver);
+        self
+    }
+
+    /// Add a configuration file or directory
+    pub fn with_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_paths.push(path.into());
+        self
+    }
+
+    /// Add a documentation file or directory
+    pub fn with_doc_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.doc_paths.push(path.into());
+        self
+    }
+
+    /// Matching chunks of the files under `paths`
+    fn file_excerpts(
+        &self,
+        source: ContextSource,
+        paths: &[PathBuf],
+        terms: &[String],
+    ) -> Vec<ContextExcerpt> {
+        let extensions = match source {
+            ContextSource::Config => CONFIG_EXTENSIONS,
+            _ => DOC_EXTENSIONS,
+        };
+        let mut files = Vec::new();
+        for path in paths {
+            files_with_extensions(path, extensions, &mut files);
+        }
+        let mut excerpts = Vec::new();
+        for file in files {
+            let Ok(content) = std::fs::read_to_string(&file) else {
+                continue;
+            };
+            let chunks = match source {
+                ContextSource::Config => config_chunks(&redact_secrets(&content)),
+                _ => doc_chunks(&content),
+            };
+            let name = file.display().to_string();
+            for chunk in chunks {
+                let score = score(terms, &format!("{} {}", name, chunk.title), &chunk.text);
+                if score <= 0.0 {
+                    continue;
+                }
+                let title = if chunk.title.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{} — {}", name, chunk.title)
+                };
+                let text = match source {
+                    ContextSource::Config => format!("```\n{}\n```", chunk.text.trim_end()),
+                    _ => chunk.text.trim().to_string(),
+                };
+                excerpts.push(excerpt(
+                    source,
+                    format!("{}:{}-{}", name, chunk.start, chunk.end),
+                    title,
+                    text,
+                    score,
+                ));
+            }
+        }
+        excerpts
+    }
+
+    /// Gather, rank and fit excerpts for the topic
+    pub async fn build(&self, request: &ContextRequest) -> Result<ContextPack> {
+        if request.topic.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Topic must not be empty",
+                "topic",
+            ));
+        }
+        let terms = topic_terms(&request.topic);
+        let wanted = |source| {
+            request
+                .sources
+                .as_ref()
+                .is_none_or(|sources| sources.contains(&source))
+        };
+        let mut candidates = Vec::new();
+        let mut warnings = Vec::new();
+
+        if wanted(ContextSource::Alert) {
+            if let Some(resolver) = &self.alerts {
+                match resolver.alerts().await {
+                    Ok(alerts) => candidates.extend(
+                        alerts
+                            .iter()
+                            .filter(|a| alert_matches(a, &terms))
+                            .map(|a| alert_excerpt(a, &terms)),
+                    ),
+                    Err(e) => warnings.push(format!("alerts: {}", e)),
+                }
+            }
+        }
+        if wanted(ContextSource::Memory) {
+            if let Some(memory) = &self.memory {
+                let mut memories: Vec<Memory> = Vec::new();
+                for term in &terms {
+                    let found = memory
+                        .search_memories(MemorySearchParams {
+                            memory_type: None,
+                            keyword: Some(term.clone()),
+                            metadata_filters: None,
+                            limit: Some(20),
+                        })
+                        .await;
+                    match found {
+                        Ok(found) => {
+                            for m in found {
+                                if !memories.iter().any(|existing| existing.id == m.id) {
+                                    memories.push(m);
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            warnings.push(format!("memories: {}", e));
+                            break;
+                        }
+                    }
+                }
+                candidates.extend(memories.iter().map(|m| memory_excerpt(m, &terms)));
+            }
+        }
+        if wanted(ContextSource::Config) {
+            candidates.extend(self.file_excerpts(
+                ContextSource::Config,
+                &self.config_paths,
+                &terms,
+            ));
+        }
+        if wanted(ContextSource::Doc) {
+            let default_docs = [PathBuf::from("docs")];
+            let doc_paths = if self.doc_paths.is_empty() {
+                &default_docs[..]
+            } else {
+                &self.doc_paths
+            };
+            candidates.extend(self.file_excerpts(ContextSource::Doc, doc_paths, &terms));
+        }
+
+        let (excerpts, omitted) = select(candidates, request.token_budget);
+        Ok(ContextPack {
+            topic: request.topic.clone(),
+            token_budget: request.token_budget,
+            tokens: excerpts.iter().map(|e| e.tokens).sum(),
+            excerpts,
+            omitted,
+            warnings,
+        })
+    }
+
+    /// Get tool definitions for context packs
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "build_context_pack",
+            "Assemble a compact, cited context pack for a service or project from memories, firing alerts, configuration and docs, within a token budget",
+            "ai",
+            json!({
+                "type": "object",
+                "properties": {
+                    "topic": {"type": "string", "description": "Service, project or host name"},
+                    "token_budget": {"type": "integer", "minimum": 100, "default": 2000},
+                    "sources": {
+                        "type": "array",
+                        "items": {"type": "string", "enum": ["alert", "memory", "config", "doc"]}
+                    }
+                },
+                "required": ["topic"]
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a context pack tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "build_context_pack" => {
+                let request: ContextRequest = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid parameters: {}", e)))?;
+                let pack = self.build(&request).await?;
+                Ok(call_result(pack.render(), serde_json::to_value(&pack)?))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "context_tool",
+                name,
+            )),
+        }
+    }
+}
+
+impl Default for ContextPackBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifecycle::LifecycleManager;
+    use crate::memory::MemoryType;
+    use crate::transport::MockTransport;
+
+    #[test]
+    fn redacts_secret_values() {
+        let redacted = redact_secrets(
+            "db:\n  password: hunter2\n  host: db.local\nAPI_KEY=abc123\n\"client_secret\": \"s3cr3t\",\n",
+        );
+        assert!(!redacted.contains("hunter2"));
+        assert!(!redacted.contains("abc123"));
+        assert!(!redacted.contains("s3cr3t"));
+        assert!(redacted.contains("host: db.local"));
+        assert!(redacted.contains("\"client_secret\": \"<redacted>\","));
+    }
+
+    #[tokio::test]
+    async fn builds_a_cited_pack_within_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let docs = dir.path().join("docs");
+        std::fs::create_dir(&docs).unwrap();
+        std::fs::write(
+            docs.join("runbook.md"),
+            format!(
+                "# Runbook\n\n## Checkout restarts\nScale checkout to 3 replicas before restarting.\n\n## Billing\n{}\n",
+                "Billing details. ".repeat(200)
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("values.yaml"),
+            "checkout:\n  replicas: 3\n  token: abc\n",
+        )
+        .unwrap();
+
+        let lifecycle = Arc::new(LifecycleManager::new(Box::new(MockTransport::new())));
+        let memory = Arc::new(MemoryClient::new_in_memory(lifecycle));
+        memory
+            .create_memory(
+                MemoryType::Issue,
+                "Checkout OOM",
+                "Checkout pods were OOM killed after the 2.3 release",
+                None,
+            )
+            .await
+            .unwrap();
+
+        let builder = ContextPackBuilder::new()
+            .with_memory(memory)
+            .with_doc_path(docs)
+            .with_config_path(dir.path().join("values.yaml"));
+        let pack = builder
+            .build(&ContextRequest {
+                topic: "checkout".to_string(),
+                token_budget: 300,
+                sources: None,
+            })
+            .await
+            .unwrap();
+
+        assert!(pack.tokens <= 300);
+        let sources: Vec<ContextSource> = pack.excerpts.iter().map(|e| e.source).collect();
+        assert!(sources.contains(&ContextSource::Memory));
+        assert!(sources.contains(&ContextSource::Config));
+        assert!(sources.contains(&ContextSource::Doc));
+        assert!(pack.excerpts.iter().all(|e| !e.text.contains("Billing")));
+        let rendered = pack.render();
+        assert!(rendered.contains("runbook.md:3-5"));
+        assert!(rendered.contains("token: <redacted>"));
+    }
+}
@@ -223,7 +223,8 @@ impl<'a> LlmResponsesClient<'a> {
                     crate::tools::ToolAnnotation::new("data_retrieval")
                         .with_description("Get a stored LLM response by ID"),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "list_llm_responses",
                 "List all responses for a specific LLM",
@@ -246,7 +247,8 @@ impl<'a> LlmResponsesClient<'a> {
                     crate::tools::ToolAnnotation::new("data_retrieval")
                         .with_description("List all responses for a specific LLM"),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "search_llm_responses",
                 "Search for responses containing specific text",
@@ -269,7 +271,8 @@ impl<'a> LlmResponsesClient<'a> {
                     crate::tools::ToolAnnotation::new("data_search")
                         .with_description("Search for responses containing specific text"),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "delete_llm_response",
                 "Delete a stored LLM response",
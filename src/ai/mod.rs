@@ -1,2 +1,11 @@
 /// AI module for artificial intelligence related capabilities
+pub mod context;
 pub mod llm_responses;
+pub mod provider;
+pub mod query_translation;
+pub mod summarize;
+
+pub use context::ContextPackBuilder;
+pub use provider::{ChatMessage, LlmConfig, LlmProvider, OpenAiCompatibleProvider, Role};
+pub use query_translation::QueryTranslator;
+pub use summarize::{ResponseSummarizer, SummarizationConfig};
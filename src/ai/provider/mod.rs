@@ -0,0 +1,212 @@
+/// LLM provider for server-side model calls
+///
+/// Talks to any OpenAI-compatible chat completions endpoint (OpenAI, Azure
+/// OpenAI deployments, Ollama, vLLM, LiteLLM). Modules that need a model
+/// take an `Arc<dyn LlmProvider>` so tests and other backends can be swapped in.
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// LLM endpoint configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmConfig {
+    /// Base URL of the OpenAI-compatible API, e.g. `http://localhost:11434/v1`
+    pub base_url: String,
+    /// Bearer token; local servers usually need none
+    pub api_key: Option<String>,
+    /// Model name
+    pub model: String,
+    /// Sampling temperature
+    pub temperature: f32,
+    /// Maximum tokens in a completion
+    pub max_tokens: u32,
+    /// Request timeout in seconds
+    pub timeout_secs: u64,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            base_url: std::env::var("LLM_BASE_URL")
+                .unwrap_or_else(|_| "https://api.openai.com/v1".to_string()),
+            api_key: std::env::var("LLM_API_KEY")
+                .or_else(|_| std::env::var("OPENAI_API_KEY"))
+                .ok(),
+            model: std::env::var("LLM_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string()),
+            temperature: 0.2,
+            max_tokens: 2048,
+            timeout_secs: 120,
+        }
+    }
+}
+
+impl LlmConfig {
+    /// Whether an endpoint has been configured through the environment
+    pub fn is_configured(&self) -> bool {
+        self.api_key.is_some() || std::env::var("LLM_BASE_URL").is_ok()
+    }
+}
+
+/// Message author
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+/// A chat message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::System,
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: content.into(),
+        }
+    }
+}
+
+/// A chat model
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Model identifier, for provenance in results
+    fn model(&self) -> &str;
+
+    /// Complete a conversation and return the assistant's reply
+    async fn complete(&self, messages: &[ChatMessage]) -> Result<String>;
+
+    /// Complete a conversation whose reply is expected to be a JSON value
+    async fn complete_json(&self, messages: &[ChatMessage]) -> Result<Value> {
+        let reply = self.complete(messages).await?;
+        parse_json_reply(&reply)
+    }
+}
+
+/// Parse a JSON reply, tolerating Markdown code fences and surrounding prose
+pub fn parse_json_reply(reply: &str) -> Result<Value> {
+    let trimmed = reply.trim();
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|s| s.trim_end().strip_suffix("```"))
+        .unwrap_or(trimmed)
+        .trim();
+    if let Ok(value) = serde_json::from_str(unfenced) {
+        return Ok(value);
+    }
+    // Fall back to the outermost object in the reply
+    match (unfenced.find('{'), unfenced.rfind('}')) {
+        (Some(start), Some(end)) if start < end => serde_json::from_str(&unfenced[start..=end])
+            .map_err(|e| {
+                Error::parsing_with_format(
+                    format!("Model reply is not valid JSON: {}", e),
+                    "json",
+                    None,
+                )
+            }),
+        _ => Err(Error::parsing_with_format(
+            "Model reply does not contain a JSON object",
+            "json",
+            None,
+        )),
+    }
+}
+
+/// Provider for OpenAI-compatible chat completions APIs
+pub struct OpenAiCompatibleProvider {
+    client: Client,
+    config: LlmConfig,
+}
+
+impl OpenAiCompatibleProvider {
+    /// Create a provider for the configured endpoint
+    pub fn new(config: LlmConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create LLM client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn model(&self) -> &str {
+        &self.config.model
+    }
+
+    async fn complete(&self, messages: &[ChatMessage]) -> Result<String> {
+        let url = format!(
+            "{}/chat/completions",
+            self.config.base_url.trim_end_matches('/')
+        );
+        let mut request = self.client.post(&url).json(&json!({
+            "model": self.config.model,
+            "messages": messages,
+            "temperature": self.config.temperature,
+            "max_tokens": self.config.max_tokens,
+        }));
+        if let Some(key) = &self.config.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                Error::timeout(format!("LLM request timed out: {}", e))
+            } else {
+                Error::network_with_endpoint(format!("LLM request failed: {}", e), url.clone())
+            }
+        })?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse LLM response: {}", e)))?;
+        if !status.is_success() {
+            let message = body
+                .pointer("/error/message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("request rejected");
+            return Err(Error::api_with_status(
+                format!("LLM endpoint returned {}: {}", status, message),
+                "llm",
+                status.as_u16(),
+            ));
+        }
+
+        body.pointer("/choices/0/message/content")
+            .and_then(|c| c.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| Error::parsing("LLM response has no message content"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fenced_and_embedded_json() {
+        let fenced = "```json\n{\"summary\": \"ok\"}\n```";
+        assert_eq!(parse_json_reply(fenced).unwrap()["summary"], "ok");
+        let prose = "Here is the brief:\n{\"dates\": [1, 2]}\nLet me know.";
+        assert_eq!(parse_json_reply(prose).unwrap()["dates"][1], 2);
+        assert!(parse_json_reply("no json here").is_err());
+    }
+}
@@ -0,0 +1,1272 @@
+/// Natural-language to PromQL, SQL and LogQL translation
+///
+/// The question goes to the LLM together with live metadata — metric names,
+/// types and labels from Prometheus, table columns from the database, stream
+/// labels from Loki — trimmed to what looks relevant to the question. The
+/// generated query is checked against that metadata and then by the backend
+/// itself (`EXPLAIN` for SQL, evaluation for PromQL and LogQL), and any
+/// problems are handed back to the model for another attempt before the
+/// query is run.
+use crate::ai::provider::{ChatMessage, LlmProvider, Role};
+use crate::database::{connect, Database, Table};
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{Duration, Utc};
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, OnceLock};
+
+/// Metadata entries shown to the model per prompt
+const PROMPT_ENTRIES: usize = 150;
+
+/// Loki labels whose values are fetched for the prompt
+const LOG_LABELS_WITH_VALUES: usize = 20;
+
+/// PromQL words that are neither metrics nor functions
+const PROMQL_KEYWORDS: &[&str] = &[
+    "by",
+    "without",
+    "on",
+    "ignoring",
+    "group_left",
+    "group_right",
+    "offset",
+    "bool",
+    "and",
+    "or",
+    "unless",
+    "inf",
+    "nan",
+    "start",
+    "end",
+];
+
+/// PromQL aggregation operators, which may be followed by `by` or `without`
+/// before their argument list
+const PROMQL_AGGREGATIONS: &[&str] = &[
+    "sum",
+    "min",
+    "max",
+    "avg",
+    "group",
+    "stddev",
+    "stdvar",
+    "count",
+    "count_values",
+    "bottomk",
+    "topk",
+    "quantile",
+    "limitk",
+    "limit_ratio",
+];
+
+/// PromQL modifiers followed by a parenthesised label list
+const PROMQL_LABEL_LISTS: &[&str] = &[
+    "by",
+    "without",
+    "on",
+    "ignoring",
+    "group_left",
+    "group_right",
+];
+
+/// SQL keywords that make a statement write or execute something
+const SQL_WRITE_KEYWORDS: &[&str] = &[
+    "insert", "update", "delete", "drop", "alter", "create", "truncate", "grant", "revoke", "copy",
+    "merge", "call", "into", "vacuum",
+];
+
+/// Question words too common to rank metadata by
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "from", "what", "which", "how", "many", "much", "show", "list",
+    "per", "are", "was", "were", "over", "last", "all", "each", "that", "this", "have", "has",
+];
+
+/// Target query language
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryLanguage {
+    Promql,
+    Sql,
+    Logql,
+}
+
+impl QueryLanguage {
+    fn name(&self) -> &'static str {
+        match self {
+            QueryLanguage::Promql => "PromQL",
+            QueryLanguage::Sql => "SQL",
+            QueryLanguage::Logql => "LogQL",
+        }
+    }
+}
+
+/// A Prometheus metric and its metadata
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricInfo {
+    pub metric_type: Option<String>,
+    pub help: Option<String>,
+}
+
+/// Metric names and label names known to Prometheus
+#[derive(Debug, Clone, Default)]
+pub struct MetricCatalog {
+    pub metrics: BTreeMap<String, MetricInfo>,
+    pub labels: BTreeSet<String>,
+}
+
+/// Stream labels known to Loki, with their values where fetched
+#[derive(Debug, Clone, Default)]
+pub struct LogCatalog {
+    pub labels: BTreeMap<String, Vec<String>>,
+}
+
+/// Live metadata a query is generated and validated against
+#[derive(Debug, Clone)]
+pub enum Schema {
+    Metrics(MetricCatalog),
+    Tables(Vec<Table>),
+    Logs(LogCatalog),
+}
+
+/// Something in the metadata that a generated query uses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reference {
+    pub kind: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// A generated query, how it was checked and what it returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Translation {
+    pub language: QueryLanguage,
+    pub question: String,
+    pub query: String,
+    /// Model's step-by-step account of the query
+    pub explanation: String,
+    pub model: String,
+    pub attempts: usize,
+    /// Problems left after the last attempt; empty when the query is valid
+    pub issues: Vec<String>,
+    pub references: Vec<Reference>,
+    /// `EXPLAIN` output for SQL
+    pub plan: Option<Vec<String>>,
+    pub results: Option<Value>,
+}
+
+impl Translation {
+    /// Whether the query passed every check
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Lowercase words of the question worth matching metadata against
+fn keywords(question: &str) -> BTreeSet<String> {
+    question
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .map(|w| w.to_ascii_lowercase())
+        .filter(|w| w.len() >= 3 && !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// How many question keywords appear in a name or description
+fn relevance(keywords: &BTreeSet<String>, text: &str) -> usize {
+    let text = text.to_ascii_lowercase();
+    keywords
+        .iter()
+        .filter(|k| text.contains(k.as_str()))
+        .count()
+}
+
+/// The `limit` most relevant items, in their original order on ties
+fn most_relevant<'a, T>(
+    items: impl Iterator<Item = (&'a str, T)>,
+    keywords: &BTreeSet<String>,
+    text: impl Fn(&T) -> String,
+    limit: usize,
+) -> Vec<(&'a str, T)> {
+    let mut scored: Vec<(usize, usize, (&'a str, T))> = items
+        .enumerate()
+        .map(|(i, (name, item))| {
+            let score = relevance(keywords, &format!("{} {}", name, text(&item)));
+            (score, i, (name, item))
+        })
+        .collect();
+    scored.sort_by_key(|(score, i, _)| (std::cmp::Reverse(*score), *i));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, _, item)| item)
+        .collect()
+}
+
+/// Levenshtein distance, for "did you mean" hints
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Closest known name, if reasonably close
+fn suggest<'a>(name: &str, known: impl Iterator<Item = &'a String>) -> Option<&'a String> {
+    known
+        .map(|k| (edit_distance(name, k), k))
+        .filter(|(distance, _)| *distance <= name.len().max(4) / 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, k)| k)
+}
+
+fn unknown(kind: &str, name: &str, known: Option<&String>) -> String {
+    match known {
+        Some(known) => format!("Unknown {} `{}`; did you mean `{}`?", kind, name, known),
+        None => format!("Unknown {} `{}`", kind, name),
+    }
+}
+
+/// Check that brackets pair up outside string literals
+fn bracket_issues(query: &str) -> Vec<String> {
+    let mut stack = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in query.chars() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' | '`' => quote = Some(c),
+            '(' | '[' | '{' => stack.push(c),
+            ')' | ']' | '}' => {
+                let open = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                if stack.pop() != Some(open) {
+                    return vec![format!("Unbalanced `{}`", c)];
+                }
+            }
+            _ => {}
+        }
+    }
+    match (quote, stack.last()) {
+        (Some(q), _) => vec![format!("Unterminated string starting with {}", q)],
+        (None, Some(open)) => vec![format!("Unclosed `{}`", open)],
+        _ => Vec::new(),
+    }
+}
+
+/// Metric names and label names used by a PromQL expression
+pub fn promql_names(query: &str) -> (BTreeSet<String>, BTreeSet<String>) {
+    let chars: Vec<char> = query.chars().collect();
+    let mut metrics = BTreeSet::new();
+    let mut labels = BTreeSet::new();
+    let mut braces = 0usize;
+    let mut label_list = false;
+    let mut i = 0;
+    let next_significant = |from: usize| chars[from..].iter().find(|c| !c.is_whitespace()).copied();
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '"' | '\'' | '`' => {
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            '[' => {
+                // Range and subquery durations
+                while i < chars.len() && chars[i] != ']' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            '{' => {
+                braces += 1;
+                i += 1;
+            }
+            '}' => {
+                braces = braces.saturating_sub(1);
+                i += 1;
+            }
+            ')' => {
+                label_list = false;
+                i += 1;
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                // Numbers and durations such as 1e3, 0x1f or 5m
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                    i += 1;
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' || c == ':' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == ':')
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let next = next_significant(i);
+                if braces > 0 || label_list {
+                    labels.insert(word);
+                } else if PROMQL_LABEL_LISTS.contains(&word.as_str()) && next == Some('(') {
+                    label_list = true;
+                    // Skip to the list so the opening paren is not taken as a call
+                    while i < chars.len() && chars[i] != '(' {
+                        i += 1;
+                    }
+                    i += 1;
+                } else if next == Some('(')
+                    || PROMQL_KEYWORDS.contains(&word.as_str())
+                    || PROMQL_AGGREGATIONS.contains(&word.as_str())
+                {
+                    // Function, aggregation or keyword
+                } else {
+                    metrics.insert(word);
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    labels.remove("__name__");
+    (metrics, labels)
+}
+
+/// Stream selectors (the text between braces) in a LogQL query
+fn logql_selectors(query: &str) -> Vec<String> {
+    let mut selectors = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in query.chars() {
+        if let Some(q) = quote {
+            if let Some(s) = current.as_mut() {
+                s.push(c);
+            }
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '`' => {
+                quote = Some(c);
+                if let Some(s) = current.as_mut() {
+                    s.push(c);
+                }
+            }
+            '{' => current = Some(String::new()),
+            '}' => {
+                if let Some(s) = current.take() {
+                    selectors.push(s);
+                }
+            }
+            c => {
+                if let Some(s) = current.as_mut() {
+                    s.push(c);
+                }
+            }
+        }
+    }
+    selectors
+}
+
+fn logql_matcher() -> &'static Regex {
+    static MATCHER: OnceLock<Regex> = OnceLock::new();
+    MATCHER.get_or_init(|| {
+        Regex::new(r#"([A-Za-z_][A-Za-z0-9_]*)\s*(=~|!~|!=|=)\s*(?:"((?:[^"\\]|\\.)*)"|`([^`]*)`)"#)
+            .expect("valid matcher regex")
+    })
+}
+
+/// Tables named after FROM and JOIN, excluding CTEs and table functions
+pub fn sql_tables(query: &str) -> BTreeSet<String> {
+    static TABLE: OnceLock<Regex> = OnceLock::new();
+    static CTE: OnceLock<Regex> = OnceLock::new();
+    static STRING: OnceLock<Regex> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        Regex::new(r#"(?i)\b(?:from|join)\s+((?:"[^"]+"|[A-Za-z_][\w$]*)(?:\.(?:"[^"]+"|[A-Za-z_][\w$]*))*)(\s*\()?"#)
+            .expect("valid table regex")
+    });
+    let cte = CTE.get_or_init(|| {
+        Regex::new(r"(?i)\b([A-Za-z_]\w*)\s+as\s+(?:not\s+)?(?:materialized\s+)?\(")
+            .expect("valid CTE regex")
+    });
+    let string = STRING.get_or_init(|| Regex::new(r"'(?:[^']|'')*'").expect("valid string regex"));
+    let query = string.replace_all(query, "''");
+    let ctes: BTreeSet<String> = cte
+        .captures_iter(&query)
+        .map(|c| c[1].to_ascii_lowercase())
+        .collect();
+    table
+        .captures_iter(&query)
+        .filter(|c| c.get(2).is_none())
+        .map(|c| {
+            let name = c[1].rsplit('.').next().unwrap_or(&c[1]);
+            name.trim_matches('"').to_ascii_lowercase()
+        })
+        .filter(|name| !ctes.contains(name))
+        .collect()
+}
+
+impl Schema {
+    /// Metadata relevant to the question, formatted for the prompt
+    pub fn prompt(&self, question: &str) -> String {
+        let keywords = keywords(question);
+        match self {
+            Schema::Metrics(catalog) => {
+                let metrics = most_relevant(
+                    catalog.metrics.iter().map(|(k, v)| (k.as_str(), v)),
+                    &keywords,
+                    |info| info.help.clone().unwrap_or_default(),
+                    PROMPT_ENTRIES,
+                );
+                let mut text = format!(
+                    "Metrics ({} of {}):\n",
+                    metrics.len(),
+                    catalog.metrics.len()
+                );
+                for (name, info) in metrics {
+                    text.push_str(&format!(
+                        "- {} [{}] {}\n",
+                        name,
+                        info.metric_type.as_deref().unwrap_or("unknown"),
+                        info.help.as_deref().unwrap_or("")
+                    ));
+                }
+                let labels: Vec<&str> = catalog.labels.iter().map(String::as_str).collect();
+                text.push_str(&format!("Labels: {}\n", labels.join(", ")));
+                text
+            }
+            Schema::Tables(tables) => {
+                let tables = most_relevant(
+                    tables.iter().map(|t| (t.name.as_str(), t)),
+                    &keywords,
+                    |t| {
+                        t.columns
+                            .iter()
+                            .map(|c| c.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    },
+                    PROMPT_ENTRIES,
+                );
+                let mut text = String::from("Tables:\n");
+                for (name, table) in tables {
+                    let columns: Vec<String> = table
+                        .columns
+                        .iter()
+                        .map(|c| format!("{} {}", c.name, c.data_type))
+                        .collect();
+                    text.push_str(&format!("- {}({})\n", name, columns.join(", ")));
+                }
+                text
+            }
+            Schema::Logs(catalog) => {
+                let mut text = String::from("Stream labels:\n");
+                for (label, values) in &catalog.labels {
+                    if values.is_empty() {
+                        text.push_str(&format!("- {}\n", label));
+                    } else {
+                        let shown = most_relevant(
+                            values.iter().map(|v| (v.as_str(), ())),
+                            &keywords,
+                            |_| String::new(),
+                            30,
+                        );
+                        let shown: Vec<&str> = shown.into_iter().map(|(v, _)| v).collect();
+                        text.push_str(&format!("- {}: {}\n", label, shown.join(", ")));
+                    }
+                }
+                text
+            }
+        }
+    }
+
+    /// Problems found by checking the query against the metadata
+    pub fn validate(&self, query: &str) -> Vec<String> {
+        let mut issues = bracket_issues(query);
+        if !issues.is_empty() {
+            return issues;
+        }
+        match self {
+            Schema::Metrics(catalog) => {
+                let (metrics, labels) = promql_names(query);
+                if !catalog.metrics.is_empty() {
+                    for metric in metrics.iter().filter(|m| !catalog.metrics.contains_key(*m)) {
+                        issues.push(unknown(
+                            "metric",
+                            metric,
+                            suggest(metric, catalog.metrics.keys()),
+                        ));
+                    }
+                }
+                if !catalog.labels.is_empty() {
+                    for label in labels.iter().filter(|l| !catalog.labels.contains(*l)) {
+                        issues.push(unknown(
+                            "label",
+                            label,
+                            suggest(label, catalog.labels.iter()),
+                        ));
+                    }
+                }
+            }
+            Schema::Tables(tables) => {
+                let statement = query.trim().trim_end_matches(';').trim();
+                if statement.contains(';') {
+                    issues.push("Only a single statement is allowed".to_string());
+                }
+                let lowered = statement.to_ascii_lowercase();
+                let words: BTreeSet<&str> = lowered
+                    .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .collect();
+                let first = lowered.split_whitespace().next().unwrap_or_default();
+                if !matches!(first, "select" | "with")
+                    || SQL_WRITE_KEYWORDS.iter().any(|k| words.contains(k))
+                {
+                    issues.push("Only read-only SELECT queries are allowed".to_string());
+                }
+                let known: BTreeSet<String> =
+                    tables.iter().map(|t| t.name.to_ascii_lowercase()).collect();
+                if !known.is_empty() {
+                    for table in sql_tables(statement).iter().filter(|t| !known.contains(*t)) {
+                        issues.push(unknown("table", table, suggest(table, known.iter())));
+                    }
+                }
+            }
+            Schema::Logs(catalog) => {
+                let selectors = logql_selectors(query);
+                if selectors.is_empty() {
+                    issues.push(
+                        "LogQL queries need a stream selector such as {app=\"api\"}".to_string(),
+                    );
+                }
+                for selector in &selectors {
+                    let matchers: Vec<_> = logql_matcher().captures_iter(selector).collect();
+                    if matchers.is_empty() {
+                        issues.push(format!("Stream selector {{{}}} has no matchers", selector));
+                    }
+                    for m in matchers {
+                        let label = &m[1];
+                        let Some(values) = catalog.labels.get(label) else {
+                            if !catalog.labels.is_empty() {
+                                let known: Vec<String> = catalog.labels.keys().cloned().collect();
+                                issues.push(unknown("label", label, suggest(label, known.iter())));
+                            }
+                            continue;
+                        };
+                        let value = m.get(3).or(m.get(4)).map(|v| v.as_str()).unwrap_or("");
+                        if &m[2] == "=" && !values.is_empty() && !values.iter().any(|v| v == value)
+                        {
+                            issues.push(format!(
+                                "No stream has {}=\"{}\"{}",
+                                label,
+                                value,
+                                suggest(value, values.iter())
+                                    .map(|v| format!("; did you mean \"{}\"?", v))
+                                    .unwrap_or_default()
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        issues
+    }
+
+    /// Metadata entries the query uses
+    pub fn references(&self, query: &str) -> Vec<Reference> {
+        match self {
+            Schema::Metrics(catalog) => promql_names(query)
+                .0
+                .into_iter()
+                .filter_map(|name| {
+                    let info = catalog.metrics.get(&name)?;
+                    Some(Reference {
+                        kind: info
+                            .metric_type
+                            .clone()
+                            .unwrap_or_else(|| "metric".to_string()),
+                        description: info.help.clone(),
+                        name,
+                    })
+                })
+                .collect(),
+            Schema::Tables(tables) => {
+                let used = sql_tables(query);
+                tables
+                    .iter()
+                    .filter(|t| used.contains(&t.name.to_ascii_lowercase()))
+                    .map(|t| Reference {
+                        kind: "table".to_string(),
+                        name: t.name.clone(),
+                        description: Some(
+                            t.columns
+                                .iter()
+                                .map(|c| c.name.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        ),
+                    })
+                    .collect()
+            }
+            Schema::Logs(_) => {
+                let labels: BTreeSet<String> = logql_selectors(query)
+                    .iter()
+                    .flat_map(|s| {
+                        logql_matcher()
+                            .captures_iter(s)
+                            .map(|m| {
+                                format!(
+                                    "{}{}{}",
+                                    &m[1],
+                                    &m[2],
+                                    m.get(3).or(m.get(4)).map(|v| v.as_str()).unwrap_or("")
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect();
+                labels
+                    .into_iter()
+                    .map(|name| Reference {
+                        kind: "stream".to_string(),
+                        name,
+                        description: None,
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// System prompt for one language with the relevant metadata
+fn system_prompt(language: QueryLanguage, metadata: &str) -> String {
+    let rules = match language {
+        QueryLanguage::Promql => "Use only the metrics and labels listed. Apply rate() or increase() to counters, histogram_quantile() over _bucket series, and aggregate with by() when the question groups results.",
+        QueryLanguage::Sql => "Write a single read-only PostgreSQL SELECT using only the tables and columns listed. Add a LIMIT unless the question asks for an aggregate.",
+        QueryLanguage::Logql => "Start with a stream selector using only the labels and values listed, then line filters or parsers. Use count_over_time() or rate() for questions about counts.",
+    };
+    format!(
+        "You translate questions into {} queries. {}\n\n{}\n\
+         Reply with only a JSON object: {{\"query\": \"...\", \"explanation\": \"step-by-step account of what the query selects, filters and computes\"}}",
+        language.name(),
+        rules,
+        metadata
+    )
+}
+
+/// Whether a failed check means the backend is unreachable rather than the query is wrong
+fn is_unavailable(error: &Error) -> bool {
+    matches!(
+        error.category(),
+        "network" | "connection" | "timeout" | "authentication"
+    )
+}
+
+/// What to translate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationRequest {
+    pub language: QueryLanguage,
+    pub question: String,
+    /// Schema for SQL
+    #[serde(default)]
+    pub database: Option<String>,
+    /// Run the query once it validates
+    #[serde(default = "default_execute")]
+    pub execute: bool,
+    /// Window for LogQL queries, in minutes
+    #[serde(default = "default_since_minutes")]
+    pub since_minutes: i64,
+}
+
+fn default_execute() -> bool {
+    true
+}
+
+fn default_since_minutes() -> i64 {
+    60
+}
+
+/// Translates questions into queries against live metadata
+pub struct QueryTranslator {
+    llm: Arc<dyn LlmProvider>,
+    prometheus_url: Option<String>,
+    loki_url: Option<String>,
+    http_client: Client,
+    max_attempts: usize,
+    row_limit: usize,
+}
+
+impl QueryTranslator {
+    /// Create a translator using Prometheus and Loki from `PROMETHEUS_URL` and `LOKI_URL`
+    pub fn new(llm: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            llm,
+            prometheus_url: std::env::var("PROMETHEUS_URL").ok(),
+            loki_url: std::env::var("LOKI_URL").ok(),
+            http_client: Client::new(),
+            max_attempts: 3,
+            row_limit: 100,
+        }
+    }
+
+    /// Read metrics from a specific Prometheus
+    pub fn with_prometheus(mut self, url: impl Into<String>) -> Self {
+        self.prometheus_url = Some(url.into());
+        self
+    }
+
+    /// Read logs from a specific Loki
+    pub fn with_loki(mut self, url: impl Into<String>) -> Self {
+        self.loki_url = Some(url.into());
+        self
+    }
+
+    /// Model attempts before returning a query that still has issues
+    pub fn with_max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// GET a Prometheus-style API and return its `data`
+    async fn api_get(
+        &self,
+        base: Option<&str>,
+        name: &str,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Value> {
+        let base = base.ok_or_else(|| {
+            Error::config_with_suggestion(
+                format!("{} is not configured", name),
+                format!("Set {}_URL", name.to_ascii_uppercase()),
+            )
+        })?;
+        let response = self
+            .http_client
+            .get(format!("{}{}", base.trim_end_matches('/'), path))
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Failed to reach {}: {}", name, e)))?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        let body: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+        if !status.is_success() || body.get("status").and_then(|s| s.as_str()) == Some("error") {
+            let message = body
+                .get("error")
+                .and_then(|e| e.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| text.trim().to_string());
+            return Err(Error::api_with_status(message, name, status.as_u16()));
+        }
+        Ok(body.get("data").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn prometheus(&self, path: &str, query: &[(&str, String)]) -> Result<Value> {
+        self.api_get(self.prometheus_url.as_deref(), "Prometheus", path, query)
+            .await
+    }
+
+    async fn loki(&self, path: &str, query: &[(&str, String)]) -> Result<Value> {
+        self.api_get(self.loki_url.as_deref(), "Loki", path, query)
+            .await
+    }
+
+    /// Metric names, types, help and label names
+    pub async fn metric_catalog(&self) -> Result<MetricCatalog> {
+        let names = self
+            .prometheus("/api/v1/label/__name__/values", &[])
+            .await?;
+        let mut catalog = MetricCatalog::default();
+        for name in names
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|n| n.as_str())
+        {
+            catalog
+                .metrics
+                .insert(name.to_string(), MetricInfo::default());
+        }
+        // Metadata is keyed by family; histogram and summary series add suffixes
+        if let Ok(metadata) = self.prometheus("/api/v1/metadata", &[]).await {
+            for (family, entries) in metadata.as_object().into_iter().flatten() {
+                let entry = entries.get(0);
+                let info = MetricInfo {
+                    metric_type: entry
+                        .and_then(|e| e.get("type"))
+                        .and_then(|t| t.as_str())
+                        .map(String::from),
+                    help: entry
+                        .and_then(|e| e.get("help"))
+                        .and_then(|t| t.as_str())
+                        .map(String::from),
+                };
+                for suffix in ["", "_bucket", "_count", "_sum", "_total"] {
+                    if let Some(existing) =
+                        catalog.metrics.get_mut(&format!("{}{}", family, suffix))
+                    {
+                        *existing = info.clone();
+                    }
+                }
+            }
+        }
+        let labels = self.prometheus("/api/v1/labels", &[]).await?;
+        catalog.labels = labels
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|l| l.as_str())
+            .filter(|l| *l != "__name__")
+            .map(String::from)
+            .collect();
+        Ok(catalog)
+    }
+
+    /// Stream labels and, for the first few, their values
+    pub async fn log_catalog(&self) -> Result<LogCatalog> {
+        let labels = self.loki("/loki/api/v1/labels", &[]).await?;
+        let mut catalog = LogCatalog::default();
+        for (i, label) in labels
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|l| l.as_str())
+            .filter(|l| !l.starts_with("__"))
+            .enumerate()
+        {
+            let values = if i < LOG_LABELS_WITH_VALUES
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                self.loki(&format!("/loki/api/v1/label/{}/values", label), &[])
+                    .await
+                    .ok()
+                    .and_then(|v| v.as_array().cloned())
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            catalog.labels.insert(label.to_string(), values);
+        }
+        Ok(catalog)
+    }
+
+    async fn schema(
+        &self,
+        request: &TranslationRequest,
+        database: Option<&dyn Database>,
+    ) -> Result<Schema> {
+        Ok(match request.language {
+            QueryLanguage::Promql => Schema::Metrics(self.metric_catalog().await?),
+            QueryLanguage::Logql => Schema::Logs(self.log_catalog().await?),
+            QueryLanguage::Sql => {
+                let database = database.ok_or_else(|| {
+                    Error::validation_with_field(
+                        "SQL translation needs a database connection",
+                        "connection_string",
+                    )
+                })?;
+                Schema::Tables(database.list_tables(request.database.as_deref()).await?)
+            }
+        })
+    }
+
+    /// Ask the backend to check the query; returns a plan for SQL
+    async fn check(
+        &self,
+        request: &TranslationRequest,
+        query: &str,
+        database: Option<&dyn Database>,
+    ) -> Result<(Option<Vec<String>>, Option<Value>)> {
+        match request.language {
+            QueryLanguage::Sql => {
+                let Some(database) = database else {
+                    return Ok((None, None));
+                };
+                let explained = database
+                    .execute_query(&format!("EXPLAIN {}", query), request.database.as_deref())
+                    .await?;
+                let plan = explained
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.as_object()?.values().next()?.as_str().map(String::from))
+                    .collect();
+                Ok((Some(plan), None))
+            }
+            // Evaluating is the only check Prometheus and Loki offer, so keep the result
+            QueryLanguage::Promql => {
+                if !request.execute {
+                    return Ok((None, None));
+                }
+                let data = self
+                    .prometheus("/api/v1/query", &[("query", query.to_string())])
+                    .await?;
+                Ok((None, Some(data)))
+            }
+            QueryLanguage::Logql => {
+                if !request.execute {
+                    return Ok((None, None));
+                }
+                let end = Utc::now();
+                let start = end - Duration::minutes(request.since_minutes.max(1));
+                let data = self
+                    .loki(
+                        "/loki/api/v1/query_range",
+                        &[
+                            ("query", query.to_string()),
+                            (
+                                "start",
+                                start.timestamp_nanos_opt().unwrap_or_default().to_string(),
+                            ),
+                            (
+                                "end",
+                                end.timestamp_nanos_opt().unwrap_or_default().to_string(),
+                            ),
+                            ("limit", self.row_limit.to_string()),
+                        ],
+                    )
+                    .await?;
+                Ok((None, Some(data)))
+            }
+        }
+    }
+
+    /// Generate, validate and optionally run a query for the question
+    pub async fn translate(
+        &self,
+        request: &TranslationRequest,
+        database: Option<&dyn Database>,
+    ) -> Result<Translation> {
+        let schema = self.schema(request, database).await?;
+        self.translate_with_schema(request, &schema, database).await
+    }
+
+    /// Translate against metadata already fetched
+    pub async fn translate_with_schema(
+        &self,
+        request: &TranslationRequest,
+        schema: &Schema,
+        database: Option<&dyn Database>,
+    ) -> Result<Translation> {
+        let mut messages = vec![
+            ChatMessage::system(system_prompt(
+                request.language,
+                &schema.prompt(&request.question),
+            )),
+            ChatMessage::user(request.question.clone()),
+        ];
+
+        let mut translation = Translation {
+            language: request.language,
+            question: request.question.clone(),
+            query: String::new(),
+            explanation: String::new(),
+            model: self.llm.model().to_string(),
+            attempts: 0,
+            issues: Vec::new(),
+            references: Vec::new(),
+            plan: None,
+            results: None,
+        };
+        while translation.attempts < self.max_attempts {
+            translation.attempts += 1;
+            let reply = self.llm.complete_json(&messages).await?;
+            translation.query = reply
+                .get("query")
+                .and_then(|q| q.as_str())
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            translation.explanation = reply
+                .get("explanation")
+                .and_then(|e| e.as_str())
+                .unwrap_or_default()
+                .to_string();
+
+            translation.issues = if translation.query.is_empty() {
+                vec!["The reply has no query".to_string()]
+            } else {
+                schema.validate(&translation.query)
+            };
+            if translation.issues.is_empty() {
+                match self.check(request, &translation.query, database).await {
+                    Ok((plan, results)) => {
+                        translation.plan = plan;
+                        translation.results = results;
+                        break;
+                    }
+                    Err(e) if is_unavailable(&e) => return Err(e),
+                    Err(e) => translation.issues.push(e.to_string()),
+                }
+            }
+
+            tracing::info!(
+                "Generated {} failed checks (attempt {}): {}",
+                request.language.name(),
+                translation.attempts,
+                translation.issues.join("; ")
+            );
+            messages.push(ChatMessage {
+                role: Role::Assistant,
+                content: reply.to_string(),
+            });
+            messages.push(ChatMessage::user(format!(
+                "That query has problems:\n- {}\nReply with a corrected JSON object.",
+                translation.issues.join("\n- ")
+            )));
+        }
+
+        translation.references = schema.references(&translation.query);
+        if translation.is_valid() && request.execute && request.language == QueryLanguage::Sql {
+            if let Some(database) = database {
+                let mut result = database
+                    .execute_query(&translation.query, request.database.as_deref())
+                    .await?;
+                let total = result.rows.len();
+                result.rows.truncate(self.row_limit);
+                translation.results = Some(json!({
+                    "rows": result.rows,
+                    "total_rows": total,
+                    "truncated": total > self.row_limit,
+                    "execution_time_ms": result.execution_time_ms,
+                }));
+            }
+        }
+        Ok(translation)
+    }
+
+    /// Get tool definitions for query translation
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "translate_query",
+            "Translate a natural-language question into PromQL, SQL or LogQL using live metric, table and label metadata, validate it, explain it and return its results",
+            "ai",
+            json!({
+                "type": "object",
+                "properties": {
+                    "language": {"type": "string", "enum": ["promql", "sql", "logql"]},
+                    "question": {"type": "string"},
+                    "provider": {"type": "string", "enum": ["postgresql", "supabase"], "description": "SQL database provider"},
+                    "connection_string": {"type": "string", "description": "SQL database connection"},
+                    "database": {"type": "string", "description": "Schema to read tables from (SQL, default public)"},
+                    "execute": {"type": "boolean", "default": true, "description": "Run the query once it validates"},
+                    "since_minutes": {"type": "integer", "default": 60, "description": "LogQL time window"}
+                },
+                "required": ["language", "question"]
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a query translation tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "translate_query" => {
+                let request: TranslationRequest = serde_json::from_value(parameters.clone())
+                    .map_err(|e| Error::validation(format!("Invalid parameters: {}", e)))?;
+                let database: Option<Arc<dyn Database>> = match (
+                    parameters.get("provider").and_then(|p| p.as_str()),
+                    parameters.get("connection_string").and_then(|c| c.as_str()),
+                ) {
+                    (provider, Some(connection_string)) => {
+                        Some(connect(provider.unwrap_or("postgresql"), connection_string).await?)
+                    }
+                    _ => None,
+                };
+                let translation = self.translate(&request, database.as_deref()).await?;
+
+                let mut text = format!(
+                    "{}:\n{}\n\n{}",
+                    request.language.name(),
+                    translation.query,
+                    translation.explanation
+                );
+                if let Some(plan) = &translation.plan {
+                    text.push_str(&format!("\n\nPlan:\n{}", plan.join("\n")));
+                }
+                if !translation.is_valid() {
+                    text.push_str(&format!(
+                        "\n\nStill failing after {} attempts:\n- {}",
+                        translation.attempts,
+                        translation.issues.join("\n- ")
+                    ));
+                }
+                Ok(call_result(text, serde_json::to_value(&translation)?))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "query_translation_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Column;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    fn catalog() -> MetricCatalog {
+        MetricCatalog {
+            metrics: [
+                "http_requests_total",
+                "http_request_duration_seconds_bucket",
+                "up",
+            ]
+            .iter()
+            .map(|m| (m.to_string(), MetricInfo::default()))
+            .collect(),
+            labels: ["job", "status", "le", "instance"]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn extracts_promql_metrics_and_labels() {
+        let (metrics, labels) = promql_names(
+            r#"histogram_quantile(0.95, sum by (le) (rate(http_request_duration_seconds_bucket{job="api"}[5m]))) / on(instance) group_left up offset 1h"#,
+        );
+        assert_eq!(
+            metrics.into_iter().collect::<Vec<_>>(),
+            vec!["http_request_duration_seconds_bucket", "up"]
+        );
+        assert_eq!(
+            labels.into_iter().collect::<Vec<_>>(),
+            vec!["instance", "job", "le"]
+        );
+
+        let schema = Schema::Metrics(catalog());
+        assert!(schema
+            .validate(r#"sum(rate(http_requests_total{status=~"5.."}[5m])) by (job)"#)
+            .is_empty());
+        let issues = schema.validate("rate(http_request_total[5m])");
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].contains("did you mean `http_requests_total`"));
+        assert!(!schema.validate("sum(up").is_empty());
+    }
+
+    #[test]
+    fn validates_sql_and_logql() {
+        let table = |name: &str| Table {
+            name: name.to_string(),
+            columns: vec![Column {
+                name: "id".to_string(),
+                data_type: "integer".to_string(),
+                nullable: false,
+                primary_key: true,
+                unique: true,
+                default: None,
+            }],
+            row_count: None,
+            size_bytes: None,
+        };
+        let schema = Schema::Tables(vec![table("orders"), table("customers")]);
+        assert!(schema
+            .validate("WITH recent AS (SELECT * FROM orders) SELECT * FROM recent JOIN public.customers c ON true LIMIT 5")
+            .is_empty());
+        assert!(schema
+            .validate("SELECT * FROM order")
+            .iter()
+            .any(|i| i.contains("`orders`")));
+        assert!(!schema.validate("DELETE FROM orders").is_empty());
+        assert!(!schema.validate("SELECT 1; DROP TABLE orders").is_empty());
+
+        let logs = Schema::Logs(LogCatalog {
+            labels: BTreeMap::from([
+                (
+                    "app".to_string(),
+                    vec!["api".to_string(), "web".to_string()],
+                ),
+                ("level".to_string(), Vec::new()),
+            ]),
+        });
+        assert!(logs
+            .validate(r#"sum(count_over_time({app="api", level=~"error|warn"} |= "timeout" [5m]))"#)
+            .is_empty());
+        assert!(logs.validate(r#"{app="apii"}"#)[0].contains("did you mean \"api\""));
+        assert!(!logs.validate(r#"{service="api"}"#).is_empty());
+        assert!(!logs.validate(r#"rate([5m])"#).is_empty());
+    }
+
+    struct ScriptedLlm {
+        replies: Mutex<Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl LlmProvider for ScriptedLlm {
+        fn model(&self) -> &str {
+            "scripted"
+        }
+
+        async fn complete(&self, _messages: &[ChatMessage]) -> Result<String> {
+            Ok(self.replies.lock().unwrap().remove(0).to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_until_the_query_validates() {
+        let llm = Arc::new(ScriptedLlm {
+            replies: Mutex::new(vec![
+                json!({"query": "{service=\"api\"}", "explanation": "wrong label"}),
+                json!({"query": "{app=\"api\"} |= \"error\"", "explanation": "api errors"}),
+            ]),
+        });
+        let translator = QueryTranslator::new(llm);
+        let schema = Schema::Logs(LogCatalog {
+            labels: BTreeMap::from([("app".to_string(), vec!["api".to_string()])]),
+        });
+        let request = TranslationRequest {
+            language: QueryLanguage::Logql,
+            question: "errors from api".to_string(),
+            database: None,
+            execute: false,
+            since_minutes: 60,
+        };
+        let translation = translator
+            .translate_with_schema(&request, &schema, None)
+            .await
+            .unwrap();
+        assert!(translation.is_valid());
+        assert_eq!(translation.attempts, 2);
+        assert_eq!(translation.query, "{app=\"api\"} |= \"error\"");
+        assert_eq!(translation.references[0].name, "app=api");
+    }
+}
@@ -0,0 +1,456 @@
+/// Summarization of verbose tool results
+///
+/// Long logs and large query results can fill a chat client's context on
+/// their own. The summarizer sits behind the tool registry as middleware:
+/// when a configured tool's text output is over its threshold, the full
+/// output is kept in a spillover store, the model writes a short summary,
+/// and the client gets that summary plus a `resource_link` to the full
+/// output, which `read_tool_output` pages through.
+use crate::ai::provider::{ChatMessage, LlmProvider};
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition, ToolMiddleware};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// URI prefix of spilled outputs
+pub const SPILL_URI_PREFIX: &str = "spill://tool-output/";
+
+/// Tool serving spilled outputs, which is never summarized itself
+const READ_TOOL: &str = "read_tool_output";
+
+/// Output sent to the model is cut to this many characters, head and tail
+const MAX_PROMPT_CHARS: usize = 24_000;
+
+/// When to summarize a tool's output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryPolicy {
+    /// Text outputs longer than this are summarized
+    #[serde(default = "default_threshold_chars")]
+    pub threshold_chars: usize,
+    /// Summary length the model is asked for
+    #[serde(default = "default_summary_words")]
+    pub max_summary_words: usize,
+    /// Extra instructions, such as what matters in this tool's output
+    #[serde(default)]
+    pub focus: Option<String>,
+}
+
+fn default_threshold_chars() -> usize {
+    8000
+}
+
+fn default_summary_words() -> usize {
+    150
+}
+
+impl Default for SummaryPolicy {
+    fn default() -> Self {
+        Self {
+            threshold_chars: default_threshold_chars(),
+            max_summary_words: default_summary_words(),
+            focus: None,
+        }
+    }
+}
+
+/// Summarization settings, under `ai.summarization` in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummarizationConfig {
+    /// Policy for tools without their own entry; other tools are left alone when unset
+    #[serde(default)]
+    pub default: Option<SummaryPolicy>,
+    /// Per-tool policies; `null` turns summarization off for a tool
+    #[serde(default)]
+    pub tools: HashMap<String, Option<SummaryPolicy>>,
+    /// Spilled outputs kept in memory, oldest dropped first
+    #[serde(default = "default_max_spilled")]
+    pub max_spilled: usize,
+}
+
+fn default_max_spilled() -> usize {
+    64
+}
+
+impl Default for SummarizationConfig {
+    fn default() -> Self {
+        Self {
+            default: None,
+            tools: HashMap::new(),
+            max_spilled: default_max_spilled(),
+        }
+    }
+}
+
+impl SummarizationConfig {
+    /// Policy applying to `tool`, if any
+    pub fn policy(&self, tool: &str) -> Option<&SummaryPolicy> {
+        match self.tools.get(tool) {
+            Some(policy) => policy.as_ref(),
+            None => self.default.as_ref(),
+        }
+    }
+}
+
+/// A full tool output replaced by a summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpilledOutput {
+    pub uri: String,
+    pub tool: String,
+    pub created_at: DateTime<Utc>,
+    pub text: String,
+    pub structured: Option<Value>,
+}
+
+/// Text of a `tools/call` result's text blocks
+fn result_text(result: &Value) -> String {
+    result
+        .get("content")
+        .and_then(|c| c.as_array())
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// The start and end of `text` within `max_chars`, cut at line boundaries
+///
+/// Logs tend to carry the interesting part at the end, so the tail gets as
+/// much room as the head.
+pub fn head_and_tail(text: &str, max_chars: usize) -> String {
+    if text.len() <= max_chars {
+        return text.to_string();
+    }
+    let half = max_chars / 2;
+    let mut head_end = half;
+    while !text.is_char_boundary(head_end) {
+        head_end -= 1;
+    }
+    let head_end = text[..head_end].rfind('\n').unwrap_or(head_end);
+    let mut tail_start = text.len() - half;
+    while !text.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
+    let tail_start = text[tail_start..]
+        .find('\n')
+        .map(|i| tail_start + i + 1)
+        .unwrap_or(tail_start);
+    let skipped = text[head_end..tail_start].lines().count();
+    format!(
+        "{}\n… {} lines omitted …\n{}",
+        &text[..head_end],
+        skipped,
+        &text[tail_start..]
+    )
+}
+
+/// Replaces verbose tool outputs with model-written summaries
+pub struct ResponseSummarizer {
+    llm: Arc<dyn LlmProvider>,
+    config: SummarizationConfig,
+    spilled: Mutex<VecDeque<SpilledOutput>>,
+}
+
+impl ResponseSummarizer {
+    /// Create a summarizer using `llm` for summaries
+    pub fn new(llm: Arc<dyn LlmProvider>, config: SummarizationConfig) -> Self {
+        Self {
+            llm,
+            config,
+            spilled: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// A spilled output by URI
+    pub fn spilled(&self, uri: &str) -> Option<SpilledOutput> {
+        self.spilled
+            .lock()
+            .expect("spill store lock")
+            .iter()
+            .find(|s| s.uri == uri)
+            .cloned()
+    }
+
+    fn spill(&self, output: SpilledOutput) {
+        let mut spilled = self.spilled.lock().expect("spill store lock");
+        spilled.push_back(output);
+        while spilled.len() > self.config.max_spilled.max(1) {
+            spilled.pop_front();
+        }
+    }
+
+    async fn summarize(&self, tool: &str, text: &str, policy: &SummaryPolicy) -> Result<String> {
+        let mut instructions = format!(
+            "You summarize the output of the `{}` tool for another assistant. \
+             Reply with at most {} words of plain text. Keep error messages, counts, \
+             identifiers and anything unusual; drop repetition. Do not add anything \
+             that is not in the output.",
+            tool, policy.max_summary_words
+        );
+        if let Some(focus) = &policy.focus {
+            instructions.push(' ');
+            instructions.push_str(focus);
+        }
+        let summary = self
+            .llm
+            .complete(&[
+                ChatMessage::system(instructions),
+                ChatMessage::user(head_and_tail(text, MAX_PROMPT_CHARS)),
+            ])
+            .await?;
+        let summary = summary.trim();
+        if summary.is_empty() {
+            return Err(Error::service("Model returned an empty summary"));
+        }
+        Ok(summary.to_string())
+    }
+
+    /// Get tool definitions for reading spilled outputs
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            READ_TOOL,
+            "Read the full output of a tool call that was replaced by a summary, a page at a time",
+            "ai",
+            json!({
+                "type": "object",
+                "properties": {
+                    "uri": {"type": "string", "description": "Resource link from the summarized result"},
+                    "offset": {"type": "integer", "minimum": 0, "default": 0, "description": "First character to return"},
+                    "limit": {"type": "integer", "minimum": 1, "default": 8000, "description": "Characters to return"},
+                    "structured": {"type": "boolean", "default": false, "description": "Also return the original structured content"}
+                },
+                "required": ["uri"]
+            }),
+            None,
+        )
+        .read_only()]
+    }
+
+    /// Execute a spilled output tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            READ_TOOL => {
+                let params: ReadParams = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid parameters: {}", e)))?;
+                let output = self.spilled(&params.uri).ok_or_else(|| {
+                    Error::not_found_with_resource(
+                        "Spilled output not found or expired",
+                        "tool_output",
+                        &params.uri,
+                    )
+                })?;
+                let total = output.text.chars().count();
+                let page: String = output
+                    .text
+                    .chars()
+                    .skip(params.offset)
+                    .take(params.limit)
+                    .collect();
+                let end = (params.offset + params.limit).min(total);
+                let mut structured = json!({
+                    "uri": output.uri,
+                    "tool": output.tool,
+                    "offset": params.offset,
+                    "total_chars": total,
+                    "next_offset": (end < total).then_some(end),
+                });
+                if params.structured {
+                    structured["structured_content"] = output.structured.unwrap_or(Value::Null);
+                }
+                Ok(call_result(page, structured))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "summarize_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadParams {
+    uri: String,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "default_page_chars")]
+    limit: usize,
+    #[serde(default)]
+    structured: bool,
+}
+
+fn default_page_chars() -> usize {
+    8000
+}
+
+#[async_trait]
+impl ToolMiddleware for ResponseSummarizer {
+    async fn after_call(&self, tool: &str, result: Value) -> Result<Value> {
+        if tool == READ_TOOL || result.get("isError").and_then(|e| e.as_bool()) == Some(true) {
+            return Ok(result);
+        }
+        let Some(policy) = self.config.policy(tool) else {
+            return Ok(result);
+        };
+        let text = result_text(&result);
+        if text.len() <= policy.threshold_chars {
+            return Ok(result);
+        }
+
+        let (summary, summarized_by) = match self.summarize(tool, &text, policy).await {
+            Ok(summary) => (summary, Some(self.llm.model().to_string())),
+            Err(e) => {
+                // An excerpt still keeps the context small when the model is down
+                tracing::warn!("Could not summarize {} output: {}", tool, e);
+                (head_and_tail(&text, policy.threshold_chars / 2), None)
+            }
+        };
+        let uri = format!("{}{}", SPILL_URI_PREFIX, uuid::Uuid::new_v4());
+        let lines = text.lines().count();
+        let chars = text.chars().count();
+        self.spill(SpilledOutput {
+            uri: uri.clone(),
+            tool: tool.to_string(),
+            created_at: Utc::now(),
+            text,
+            structured: result.get("structuredContent").cloned(),
+        });
+
+        Ok(json!({
+            "content": [
+                {
+                    "type": "text",
+                    "text": format!(
+                        "{}\n\nFull output ({} lines) is at {}; read it with {}.",
+                        summary, lines, uri, READ_TOOL
+                    )
+                },
+                {
+                    "type": "resource_link",
+                    "uri": uri,
+                    "name": format!("{} output", tool),
+                    "mimeType": "text/plain",
+                    "size": chars
+                }
+            ],
+            "structuredContent": {
+                "summarized": true,
+                "summarized_by": summarized_by,
+                "resource": uri,
+                "original_lines": lines,
+                "original_chars": chars
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::ToolRegistry;
+
+    struct ScriptedLlm {
+        reply: Option<&'static str>,
+    }
+
+    #[async_trait]
+    impl LlmProvider for ScriptedLlm {
+        fn model(&self) -> &str {
+            "scripted"
+        }
+
+        async fn complete(&self, _messages: &[ChatMessage]) -> Result<String> {
+            self.reply
+                .map(str::to_string)
+                .ok_or_else(|| Error::network("model unavailable"))
+        }
+    }
+
+    async fn registry(reply: Option<&'static str>) -> (ToolRegistry, Arc<ResponseSummarizer>) {
+        let config: SummarizationConfig = serde_json::from_value(json!({
+            "default": {"threshold_chars": 200},
+            "tools": {"quiet": null}
+        }))
+        .unwrap();
+        let summarizer = Arc::new(ResponseSummarizer::new(
+            Arc::new(ScriptedLlm { reply }),
+            config,
+        ));
+        let mut registry = ToolRegistry::new();
+        registry
+            .register_all(
+                vec![
+                    ToolDefinition::new("logs", "Logs"),
+                    ToolDefinition::new("quiet", "Quiet"),
+                ],
+                |_, arguments| async move {
+                    let lines = arguments["lines"].as_u64().unwrap_or(0);
+                    let text: Vec<String> = (0..lines).map(|i| format!("line {}", i)).collect();
+                    Ok(call_result(text.join("\n"), json!({"lines": lines})))
+                },
+            )
+            .unwrap();
+        let reader = Arc::clone(&summarizer);
+        registry
+            .register_all(summarizer.get_tool_definitions(), move |name, arguments| {
+                let reader = Arc::clone(&reader);
+                async move { reader.execute_tool(&name, arguments).await }
+            })
+            .unwrap();
+        registry.add_middleware(Arc::clone(&summarizer) as Arc<dyn ToolMiddleware>);
+        (registry, summarizer)
+    }
+
+    #[tokio::test]
+    async fn replaces_long_outputs_with_a_summary_and_link() {
+        let (registry, _) = registry(Some("100 lines, no errors.")).await;
+
+        let short = registry.call("logs", json!({"lines": 3})).await.unwrap();
+        assert_eq!(short["content"][0]["text"], "line 0\nline 1\nline 2");
+        let exempt = registry.call("quiet", json!({"lines": 100})).await.unwrap();
+        assert_eq!(exempt["structuredContent"]["lines"], 100);
+
+        let long = registry.call("logs", json!({"lines": 100})).await.unwrap();
+        let text = long["content"][0]["text"].as_str().unwrap();
+        assert!(text.starts_with("100 lines, no errors."));
+        assert_eq!(long["content"][1]["type"], "resource_link");
+        assert_eq!(long["structuredContent"]["summarized_by"], "scripted");
+        let uri = long["content"][1]["uri"].as_str().unwrap();
+        assert!(uri.starts_with(SPILL_URI_PREFIX));
+
+        let page = registry
+            .call(
+                READ_TOOL,
+                json!({"uri": uri, "offset": 0, "limit": 13, "structured": true}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(page["content"][0]["text"], "line 0\nline 1");
+        assert_eq!(page["structuredContent"]["next_offset"], 13);
+        assert_eq!(
+            page["structuredContent"]["structured_content"]["lines"],
+            100
+        );
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_an_excerpt_without_the_model() {
+        let (registry, summarizer) = registry(None).await;
+        let long = registry.call("logs", json!({"lines": 100})).await.unwrap();
+        let text = long["content"][0]["text"].as_str().unwrap();
+        assert!(text.starts_with("line 0\n"));
+        assert!(text.contains("lines omitted"));
+        assert!(text.contains("line 99"));
+        assert_eq!(long["structuredContent"]["summarized_by"], Value::Null);
+        let uri = long["structuredContent"]["resource"].as_str().unwrap();
+        assert_eq!(summarizer.spilled(uri).unwrap().text.lines().count(), 100);
+    }
+}
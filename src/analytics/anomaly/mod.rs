@@ -0,0 +1,294 @@
+//! Anomaly detectors for numeric time series
+//!
+//! Samples are treated as a step function, the way state histories record
+//! them: a value holds until the next sample. `Sustained` finds stretches
+//! spent above or below a bound for longer than allowed, such as water
+//! flowing for hours or a freezer drawing no power. `Spike` compares each
+//! sample with the median of the ones before it, scaled by their median
+//! absolute deviation, so a few outliers in the window do not hide the next.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Scales a median absolute deviation to a standard deviation for normal data
+const MAD_SCALE: f64 = 1.4826;
+
+/// One reading
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Sample {
+    pub at: DateTime<Utc>,
+    pub value: f64,
+}
+
+fn default_window() -> usize {
+    30
+}
+
+fn default_threshold() -> f64 {
+    5.0
+}
+
+/// How to tell an anomaly in a series
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Detector {
+    /// The value stays above `above` or below `below` for `minutes` or longer
+    Sustained {
+        #[serde(default)]
+        above: Option<f64>,
+        #[serde(default)]
+        below: Option<f64>,
+        minutes: f64,
+    },
+    /// A sample sits more than `threshold` robust deviations above the
+    /// median of the `window` samples before it, and at least `min_change`
+    /// above it in absolute terms
+    Spike {
+        #[serde(default = "default_window")]
+        window: usize,
+        #[serde(default = "default_threshold")]
+        threshold: f64,
+        #[serde(default)]
+        min_change: f64,
+    },
+}
+
+impl Detector {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Detector::Sustained { .. } => "sustained",
+            Detector::Spike { .. } => "spike",
+        }
+    }
+
+    /// History needed to evaluate the detector once, given the sampling interval
+    pub fn lookback(&self, interval: Duration) -> Duration {
+        match self {
+            Detector::Sustained { minutes, .. } => Duration::seconds((*minutes * 120.0) as i64),
+            Detector::Spike { window, .. } => interval * (*window as i32 + 1),
+        }
+    }
+
+    /// Check the detector's settings
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        match self {
+            Detector::Sustained {
+                above: None,
+                below: None,
+                ..
+            } => Err("Sustained detectors need above or below".to_string()),
+            Detector::Sustained { minutes, .. } if *minutes <= 0.0 => {
+                Err("Sustained minutes must be positive".to_string())
+            }
+            Detector::Spike { window, .. } if *window < 3 => {
+                Err("Spike window must be at least 3 samples".to_string())
+            }
+            Detector::Spike { threshold, .. } if *threshold <= 0.0 => {
+                Err("Spike threshold must be positive".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Anomalies in `samples`, which must be in time order; `now` closes the last step
+    pub fn detect(&self, samples: &[Sample], now: DateTime<Utc>) -> Vec<Anomaly> {
+        match self {
+            Detector::Sustained {
+                above,
+                below,
+                minutes,
+            } => sustained(samples, *above, *below, *minutes, now),
+            Detector::Spike {
+                window,
+                threshold,
+                min_change,
+            } => spikes(samples, *window, *threshold, *min_change),
+        }
+    }
+}
+
+/// A stretch of a series a detector flagged
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Anomaly {
+    /// `sustained` or `spike`
+    pub detector: String,
+    pub start: DateTime<Utc>,
+    /// When the series returned to normal; `None` while it still is anomalous
+    pub end: Option<DateTime<Utc>>,
+    /// Most extreme value seen during the anomaly
+    pub peak: f64,
+    /// Typical value the anomaly was measured against, for spikes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<f64>,
+    /// Robust deviations from the expected value, for spikes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+impl Anomaly {
+    /// Whether the series is still anomalous at its latest sample
+    pub fn ongoing(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// How long the anomaly lasted, or has lasted so far
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+fn sustained(
+    samples: &[Sample],
+    above: Option<f64>,
+    below: Option<f64>,
+    minutes: f64,
+    now: DateTime<Utc>,
+) -> Vec<Anomaly> {
+    let outside = |value: f64| {
+        above.is_some_and(|bound| value > bound) || below.is_some_and(|bound| value < bound)
+    };
+    let min_duration = Duration::seconds((minutes * 60.0) as i64);
+    let mut anomalies = Vec::new();
+    let mut run: Option<(DateTime<Utc>, f64)> = None;
+    for sample in samples {
+        if outside(sample.value) {
+            let peak = match (run, above) {
+                (Some((_, peak)), Some(_)) => peak.max(sample.value),
+                (Some((_, peak)), None) => peak.min(sample.value),
+                (None, _) => sample.value,
+            };
+            run = Some((run.map_or(sample.at, |(start, _)| start), peak));
+        } else if let Some((start, peak)) = run.take() {
+            if sample.at - start >= min_duration {
+                anomalies.push(Anomaly {
+                    detector: "sustained".to_string(),
+                    start,
+                    end: Some(sample.at),
+                    peak,
+                    expected: None,
+                    score: None,
+                });
+            }
+        }
+    }
+    if let Some((start, peak)) = run.filter(|(start, _)| now - *start >= min_duration) {
+        anomalies.push(Anomaly {
+            detector: "sustained".to_string(),
+            start,
+            end: None,
+            peak,
+            expected: None,
+            score: None,
+        });
+    }
+    anomalies
+}
+
+fn spikes(samples: &[Sample], window: usize, threshold: f64, min_change: f64) -> Vec<Anomaly> {
+    let mut anomalies: Vec<Anomaly> = Vec::new();
+    for i in window..samples.len() {
+        let mut history: Vec<f64> = samples[i - window..i].iter().map(|s| s.value).collect();
+        let expected = median(&mut history);
+        let mut deviations: Vec<f64> = history.iter().map(|v| (v - expected).abs()).collect();
+        let scale = median(&mut deviations) * MAD_SCALE;
+        let change = samples[i].value - expected;
+        let score = if scale > 0.0 {
+            change / scale
+        } else if change > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        };
+        let sample = samples[i];
+        if score > threshold && change >= min_change {
+            // Consecutive spiking samples are one anomaly
+            match anomalies.last_mut() {
+                Some(last) if last.end.is_none() => {
+                    last.peak = last.peak.max(sample.value);
+                    last.score = last.score.map(|s| s.max(score));
+                    last.end = None;
+                }
+                _ => anomalies.push(Anomaly {
+                    detector: "spike".to_string(),
+                    start: sample.at,
+                    end: None,
+                    peak: sample.value,
+                    expected: Some(expected),
+                    score: Some(score),
+                }),
+            }
+        } else if let Some(last) = anomalies.last_mut().filter(|a| a.end.is_none()) {
+            last.end = Some(sample.at);
+        }
+    }
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(values: &[f64], step_minutes: i64) -> (Vec<Sample>, DateTime<Utc>) {
+        let start: DateTime<Utc> = "2024-05-01T00:00:00Z".parse().unwrap();
+        let samples = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| Sample {
+                at: start + Duration::minutes(i as i64 * step_minutes),
+                value: *value,
+            })
+            .collect();
+        let now = start + Duration::minutes(values.len() as i64 * step_minutes);
+        (samples, now)
+    }
+
+    #[test]
+    fn sustained_flow_is_flagged_only_after_the_limit() {
+        let detector = Detector::Sustained {
+            above: Some(0.1),
+            below: None,
+            minutes: 60.0,
+        };
+        // Short draws end within the hour, the last one is still running
+        let (samples, now) = series(&[0.0, 6.0, 0.0, 0.0, 0.4, 0.5, 0.4, 0.6, 0.5], 15);
+        let anomalies = detector.detect(&samples, now);
+        assert_eq!(anomalies.len(), 1);
+        assert!(anomalies[0].ongoing());
+        assert_eq!(anomalies[0].peak, 0.6);
+        assert_eq!(anomalies[0].duration(now), Duration::minutes(75));
+        assert!(detector
+            .detect(&samples[..6], now - Duration::minutes(45))
+            .is_empty());
+    }
+
+    #[test]
+    fn spike_stands_out_from_a_noisy_baseline() {
+        let detector = Detector::Spike {
+            window: 6,
+            threshold: 5.0,
+            min_change: 100.0,
+        };
+        let (samples, now) = series(
+            &[
+                90.0, 110.0, 95.0, 105.0, 100.0, 98.0, 102.0, 2400.0, 2300.0, 101.0,
+            ],
+            5,
+        );
+        let anomalies = detector.detect(&samples, now);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].start, samples[7].at);
+        assert_eq!(anomalies[0].end, Some(samples[9].at));
+        assert_eq!(anomalies[0].peak, 2400.0);
+        assert_eq!(anomalies[0].expected, Some(101.0));
+    }
+}
@@ -0,0 +1,797 @@
+/// Grafana dashboards generated from a service description
+///
+/// A `ServiceDescription` names the service's metrics, SLOs and log queries;
+/// the generator lays them out as rows of Grafana panels (golden signals, SLO
+/// compliance and error budget burn, custom metrics, logs) and can push the
+/// result through the monitoring module's Grafana client.
+use crate::error::{Error, Result};
+use crate::monitoring::{
+    GrafanaDashboard, GrafanaPanel, GrafanaVariable, GridPos, MonitoringModule,
+};
+use crate::tools::{call_result, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Grafana grid width
+const GRID_COLUMNS: i32 = 24;
+/// Dashboard JSON schema version the output targets
+const SCHEMA_VERSION: u32 = 39;
+
+/// Datasource UIDs used by generated panels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Datasources {
+    #[serde(default = "default_prometheus")]
+    pub prometheus: String,
+    #[serde(default = "default_loki")]
+    pub loki: String,
+}
+
+fn default_prometheus() -> String {
+    "prometheus".to_string()
+}
+
+fn default_loki() -> String {
+    "loki".to_string()
+}
+
+impl Default for Datasources {
+    fn default() -> Self {
+        Self {
+            prometheus: default_prometheus(),
+            loki: default_loki(),
+        }
+    }
+}
+
+/// Conventional HTTP server metrics used for the golden signals row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpMetrics {
+    /// Request counter
+    #[serde(default = "default_requests_metric")]
+    pub requests: String,
+    /// Request duration histogram (without `_bucket`)
+    #[serde(default = "default_duration_metric")]
+    pub duration: String,
+    /// Label holding the response status code
+    #[serde(default = "default_status_label")]
+    pub status_label: String,
+}
+
+fn default_requests_metric() -> String {
+    "http_requests_total".to_string()
+}
+
+fn default_duration_metric() -> String {
+    "http_request_duration_seconds".to_string()
+}
+
+fn default_status_label() -> String {
+    "code".to_string()
+}
+
+impl Default for HttpMetrics {
+    fn default() -> Self {
+        Self {
+            requests: default_requests_metric(),
+            duration: default_duration_metric(),
+            status_label: default_status_label(),
+        }
+    }
+}
+
+/// Visualization for a custom metric
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PanelKind {
+    #[default]
+    Timeseries,
+    Stat,
+    Gauge,
+}
+
+/// A custom metric panel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricSpec {
+    pub title: String,
+    /// PromQL; `$selector` expands to the service's label selector
+    pub expr: String,
+    /// Grafana unit id, e.g. `bytes`, `s`, `percentunit`
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub kind: PanelKind,
+    pub legend: Option<String>,
+}
+
+/// SLO indicator type
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SloKind {
+    /// Share of non-5xx responses
+    #[default]
+    Availability,
+    /// Share of requests faster than `threshold_seconds`
+    Latency,
+}
+
+/// A service level objective
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloSpec {
+    pub name: String,
+    /// Target percentage, e.g. 99.9
+    pub objective: f64,
+    #[serde(default)]
+    pub kind: SloKind,
+    /// Latency bucket boundary for latency SLOs; must match a histogram `le`
+    pub threshold_seconds: Option<f64>,
+    /// Compliance window, e.g. `30d`
+    #[serde(default = "default_slo_window")]
+    pub window: String,
+    /// Custom good-events rate expression; `$window` expands to the range
+    pub good_expr: Option<String>,
+    /// Custom total-events rate expression; `$window` expands to the range
+    pub total_expr: Option<String>,
+}
+
+fn default_slo_window() -> String {
+    "30d".to_string()
+}
+
+/// A log panel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogQuerySpec {
+    pub title: String,
+    /// LogQL; `$selector` expands to the service's label selector
+    pub query: String,
+}
+
+/// High-level description of a service to visualize
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceDescription {
+    pub name: String,
+    /// Label selector identifying the service; defaults to `job="<name>"`
+    pub selector: Option<String>,
+    /// Golden signals from HTTP metrics; omit for non-HTTP services
+    pub http: Option<HttpMetrics>,
+    #[serde(default)]
+    pub metrics: Vec<MetricSpec>,
+    #[serde(default)]
+    pub slos: Vec<SloSpec>,
+    #[serde(default)]
+    pub logs: Vec<LogQuerySpec>,
+    #[serde(default)]
+    pub datasources: Datasources,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Places panels left to right, wrapping onto new rows of the grid
+struct Layout {
+    x: i32,
+    y: i32,
+    row_height: i32,
+    next_id: i32,
+    panels: Vec<GrafanaPanel>,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            row_height: 0,
+            next_id: 1,
+            panels: Vec::new(),
+        }
+    }
+
+    fn newline(&mut self) {
+        self.y += self.row_height;
+        self.x = 0;
+        self.row_height = 0;
+    }
+
+    fn row(&mut self, title: &str) {
+        if self.x > 0 {
+            self.newline();
+        }
+        let panel = self.panel(title, "row", Vec::new(), GRID_COLUMNS, 1);
+        self.panels.push(panel);
+        self.newline();
+    }
+
+    fn panel(
+        &mut self,
+        title: &str,
+        kind: &str,
+        targets: Vec<Value>,
+        w: i32,
+        h: i32,
+    ) -> GrafanaPanel {
+        if self.x + w > GRID_COLUMNS {
+            self.newline();
+        }
+        let panel = GrafanaPanel {
+            id: self.next_id,
+            title: title.to_string(),
+            panel_type: kind.to_string(),
+            targets,
+            grid_pos: GridPos {
+                h,
+                w,
+                x: self.x,
+                y: self.y,
+            },
+            description: None,
+            datasource: None,
+            field_config: None,
+            options: None,
+        };
+        self.next_id += 1;
+        self.x += w;
+        self.row_height = self.row_height.max(h);
+        panel
+    }
+
+    fn push(&mut self, panel: GrafanaPanel) {
+        self.panels.push(panel);
+    }
+}
+
+fn field_config(unit: &str, thresholds: Option<Value>, decimals: Option<u32>) -> Value {
+    let mut defaults = json!({ "unit": unit });
+    if let Some(steps) = thresholds {
+        defaults["thresholds"] = json!({ "mode": "absolute", "steps": steps });
+        defaults["color"] = json!({ "mode": "thresholds" });
+    }
+    if let Some(decimals) = decimals {
+        defaults["decimals"] = json!(decimals);
+    }
+    json!({ "defaults": defaults, "overrides": [] })
+}
+
+/// Dashboard generator
+pub struct DashboardGenerator {
+    monitoring: Option<Arc<MonitoringModule>>,
+}
+
+impl Default for DashboardGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DashboardGenerator {
+    /// Create a generator that only emits JSON
+    pub fn new() -> Self {
+        Self { monitoring: None }
+    }
+
+    /// Allow pushing generated dashboards to Grafana
+    pub fn with_monitoring(mut self, monitoring: Arc<MonitoringModule>) -> Self {
+        self.monitoring = Some(monitoring);
+        self
+    }
+
+    /// Build the dashboard for a service
+    pub fn generate(&self, service: &ServiceDescription) -> Result<GrafanaDashboard> {
+        if service.name.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Service name is required",
+                "name",
+            ));
+        }
+        let selector = service
+            .selector
+            .clone()
+            .unwrap_or_else(|| format!("job=\"{}\"", service.name));
+        // Narrow every query to the selected instance(s)
+        let scoped = format!("{}, instance=~\"$instance\"", selector);
+        let prometheus = json!({ "type": "prometheus", "uid": service.datasources.prometheus });
+        let loki = json!({ "type": "loki", "uid": service.datasources.loki });
+        let expand = |expr: &str| expr.replace("$selector", &scoped);
+        let prom_target = |expr: String, legend: &str, ref_id: &str| {
+            json!({
+                "datasource": prometheus,
+                "expr": expr,
+                "legendFormat": legend,
+                "refId": ref_id
+            })
+        };
+
+        let mut layout = Layout::new();
+
+        if let Some(http) = &service.http {
+            layout.row("Golden signals");
+            let status = &http.status_label;
+            let signals = [
+                (
+                    "Request rate",
+                    format!("sum(rate({}{{{}}}[$__rate_interval]))", http.requests, scoped),
+                    "reqps",
+                    None,
+                ),
+                (
+                    "Error rate",
+                    format!(
+                        "sum(rate({m}{{{s}, {status}=~\"5..\"}}[$__rate_interval])) / sum(rate({m}{{{s}}}[$__rate_interval]))",
+                        m = http.requests,
+                        s = scoped
+                    ),
+                    "percentunit",
+                    Some(json!([
+                        {"color": "green", "value": null},
+                        {"color": "orange", "value": 0.01},
+                        {"color": "red", "value": 0.05}
+                    ])),
+                ),
+            ];
+            for (title, expr, unit, thresholds) in signals {
+                let mut panel =
+                    layout.panel(title, "timeseries", vec![prom_target(expr, "", "A")], 8, 8);
+                panel.datasource = Some(prometheus.clone());
+                panel.field_config = Some(field_config(unit, thresholds, None));
+                layout.push(panel);
+            }
+            let latency_targets = [("p50", 0.5, "A"), ("p95", 0.95, "B"), ("p99", 0.99, "C")]
+                .iter()
+                .map(|(legend, q, ref_id)| {
+                    prom_target(
+                        format!(
+                            "histogram_quantile({}, sum by (le) (rate({}_bucket{{{}}}[$__rate_interval])))",
+                            q, http.duration, scoped
+                        ),
+                        legend,
+                        ref_id,
+                    )
+                })
+                .collect();
+            let mut panel = layout.panel("Latency", "timeseries", latency_targets, 8, 8);
+            panel.datasource = Some(prometheus.clone());
+            panel.field_config = Some(field_config("s", None, None));
+            layout.push(panel);
+        }
+
+        if !service.slos.is_empty() {
+            layout.row("Service level objectives");
+            for slo in &service.slos {
+                let (good, total) = self.slo_exprs(service, slo, &selector)?;
+                let target = slo.objective / 100.0;
+                let sli = |window: &str| {
+                    format!(
+                        "({}) / ({})",
+                        good.replace("$window", window),
+                        total.replace("$window", window)
+                    )
+                };
+
+                let mut compliance = layout.panel(
+                    &format!("{} ({} over {})", slo.name, slo.objective, slo.window),
+                    "stat",
+                    vec![prom_target(sli(&slo.window), "SLI", "A")],
+                    6,
+                    6,
+                );
+                compliance.description = Some(format!(
+                    "Share of good events over {}; objective {}%",
+                    slo.window, slo.objective
+                ));
+                compliance.datasource = Some(prometheus.clone());
+                compliance.field_config = Some(field_config(
+                    "percentunit",
+                    Some(json!([
+                        {"color": "red", "value": null},
+                        {"color": "green", "value": target}
+                    ])),
+                    Some(3),
+                ));
+                layout.push(compliance);
+
+                let mut budget = layout.panel(
+                    &format!("{} error budget remaining", slo.name),
+                    "gauge",
+                    vec![prom_target(
+                        format!("1 - (1 - {}) / {}", sli(&slo.window), 1.0 - target),
+                        "budget",
+                        "A",
+                    )],
+                    6,
+                    6,
+                );
+                budget.datasource = Some(prometheus.clone());
+                budget.field_config = Some(field_config(
+                    "percentunit",
+                    Some(json!([
+                        {"color": "red", "value": null},
+                        {"color": "orange", "value": 0.25},
+                        {"color": "green", "value": 0.5}
+                    ])),
+                    Some(1),
+                ));
+                layout.push(budget);
+
+                // Multi-window burn rates as in the SRE workbook's alerting thresholds
+                let burn = |window: &str, ref_id: &str| {
+                    prom_target(
+                        format!("(1 - {}) / {}", sli(window), 1.0 - target),
+                        &format!("{} burn", window),
+                        ref_id,
+                    )
+                };
+                let mut burn_rate = layout.panel(
+                    &format!("{} burn rate", slo.name),
+                    "timeseries",
+                    vec![burn("1h", "A"), burn("6h", "B")],
+                    12,
+                    6,
+                );
+                burn_rate.datasource = Some(prometheus.clone());
+                burn_rate.field_config = Some(field_config(
+                    "x",
+                    Some(json!([
+                        {"color": "green", "value": null},
+                        {"color": "orange", "value": 6},
+                        {"color": "red", "value": 14.4}
+                    ])),
+                    Some(2),
+                ));
+                layout.push(burn_rate);
+            }
+        }
+
+        if !service.metrics.is_empty() {
+            layout.row("Metrics");
+            for metric in &service.metrics {
+                let (kind, w) = match metric.kind {
+                    PanelKind::Timeseries => ("timeseries", 12),
+                    PanelKind::Stat => ("stat", 6),
+                    PanelKind::Gauge => ("gauge", 6),
+                };
+                let mut panel = layout.panel(
+                    &metric.title,
+                    kind,
+                    vec![prom_target(
+                        expand(&metric.expr),
+                        metric.legend.as_deref().unwrap_or(""),
+                        "A",
+                    )],
+                    w,
+                    8,
+                );
+                panel.datasource = Some(prometheus.clone());
+                panel.field_config = Some(field_config(
+                    metric.unit.as_deref().unwrap_or("short"),
+                    None,
+                    None,
+                ));
+                layout.push(panel);
+            }
+        }
+
+        if !service.logs.is_empty() {
+            layout.row("Logs");
+            for log in &service.logs {
+                let query = log.query.replace("$selector", &selector);
+                let mut volume = layout.panel(
+                    &format!("{} volume", log.title),
+                    "timeseries",
+                    vec![json!({
+                        "datasource": loki,
+                        "expr": format!("sum(count_over_time({} [$__auto]))", query),
+                        "refId": "A"
+                    })],
+                    GRID_COLUMNS,
+                    5,
+                );
+                volume.datasource = Some(loki.clone());
+                volume.options = Some(json!({ "legend": { "showLegend": false } }));
+                layout.push(volume);
+
+                let mut panel = layout.panel(
+                    &log.title,
+                    "logs",
+                    vec![json!({ "datasource": loki, "expr": query, "refId": "A" })],
+                    GRID_COLUMNS,
+                    10,
+                );
+                panel.datasource = Some(loki.clone());
+                panel.options = Some(json!({
+                    "showTime": true,
+                    "wrapLogMessage": true,
+                    "sortOrder": "Descending",
+                    "enableLogDetails": true
+                }));
+                layout.push(panel);
+            }
+        }
+
+        if layout.panels.is_empty() {
+            return Err(Error::validation(
+                "Service description has no http metrics, SLOs, metrics or logs to chart",
+            ));
+        }
+
+        let mut tags = service.tags.clone();
+        for tag in [service.name.clone(), "generated".to_string()] {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        Ok(GrafanaDashboard {
+            id: None,
+            uid: Some(format!("svc-{}", slug(&service.name))),
+            title: format!("{} service", service.name),
+            tags,
+            panels: layout.panels,
+            templating: vec![GrafanaVariable {
+                name: "instance".to_string(),
+                label: "Instance".to_string(),
+                variable_type: "query".to_string(),
+                query: format!("label_values({{{}}}, instance)", selector),
+                datasource: Some(prometheus),
+                refresh: Some(1),
+            }],
+        })
+    }
+
+    fn slo_exprs(
+        &self,
+        service: &ServiceDescription,
+        slo: &SloSpec,
+        selector: &str,
+    ) -> Result<(String, String)> {
+        if !(0.0..100.0).contains(&slo.objective) || slo.objective <= 0.0 {
+            return Err(Error::validation_with_field(
+                format!("SLO {} objective must be a percentage below 100", slo.name),
+                "objective",
+            ));
+        }
+        if let (Some(good), Some(total)) = (&slo.good_expr, &slo.total_expr) {
+            return Ok((
+                good.replace("$selector", selector),
+                total.replace("$selector", selector),
+            ));
+        }
+        let http = service.http.as_ref().ok_or_else(|| {
+            Error::validation_with_field(
+                format!(
+                    "SLO {} needs http metrics or explicit good_expr/total_expr",
+                    slo.name
+                ),
+                "slos",
+            )
+        })?;
+        Ok(match slo.kind {
+            SloKind::Availability => (
+                format!(
+                    "sum(rate({}{{{}, {}!~\"5..\"}}[$window]))",
+                    http.requests, selector, http.status_label
+                ),
+                format!("sum(rate({}{{{}}}[$window]))", http.requests, selector),
+            ),
+            SloKind::Latency => {
+                let threshold = slo.threshold_seconds.ok_or_else(|| {
+                    Error::validation_with_field(
+                        format!("Latency SLO {} needs threshold_seconds", slo.name),
+                        "threshold_seconds",
+                    )
+                })?;
+                (
+                    format!(
+                        "sum(rate({}_bucket{{{}, le=\"{}\"}}[$window]))",
+                        http.duration, selector, threshold
+                    ),
+                    format!(
+                        "sum(rate({}_count{{{}}}[$window]))",
+                        http.duration, selector
+                    ),
+                )
+            }
+        })
+    }
+
+    /// Import-ready dashboard model
+    pub fn dashboard_json(dashboard: &GrafanaDashboard) -> Value {
+        json!({
+            "uid": dashboard.uid,
+            "title": dashboard.title,
+            "tags": dashboard.tags,
+            "timezone": "browser",
+            "schemaVersion": SCHEMA_VERSION,
+            "time": { "from": "now-6h", "to": "now" },
+            "refresh": "1m",
+            "panels": dashboard.panels,
+            "templating": { "list": dashboard.templating }
+        })
+    }
+
+    /// Get tool definitions for dashboard generation
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "generate_service_dashboard",
+            "Generate a complete Grafana dashboard (golden signals, SLOs, metrics, logs) from a service description, optionally pushing it to Grafana",
+            "analytics",
+            json!({
+                "type": "object",
+                "properties": {
+                    "service": {
+                        "type": "object",
+                        "properties": {
+                            "name": {"type": "string"},
+                            "selector": {"type": "string", "description": "Prometheus/Loki label selector, e.g. job=\"api\""},
+                            "http": {
+                                "type": "object",
+                                "description": "HTTP metric names; {} uses Prometheus client defaults",
+                                "properties": {
+                                    "requests": {"type": "string"},
+                                    "duration": {"type": "string"},
+                                    "status_label": {"type": "string"}
+                                }
+                            },
+                            "metrics": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "title": {"type": "string"},
+                                        "expr": {"type": "string", "description": "PromQL; $selector expands to the service selector"},
+                                        "unit": {"type": "string"},
+                                        "kind": {"type": "string", "enum": ["timeseries", "stat", "gauge"]},
+                                        "legend": {"type": "string"}
+                                    },
+                                    "required": ["title", "expr"]
+                                }
+                            },
+                            "slos": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "name": {"type": "string"},
+                                        "objective": {"type": "number", "description": "Target percentage, e.g. 99.9"},
+                                        "kind": {"type": "string", "enum": ["availability", "latency"]},
+                                        "threshold_seconds": {"type": "number"},
+                                        "window": {"type": "string", "default": "30d"},
+                                        "good_expr": {"type": "string"},
+                                        "total_expr": {"type": "string"}
+                                    },
+                                    "required": ["name", "objective"]
+                                }
+                            },
+                            "logs": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "title": {"type": "string"},
+                                        "query": {"type": "string", "description": "LogQL; $selector expands to the service selector"}
+                                    },
+                                    "required": ["title", "query"]
+                                }
+                            },
+                            "datasources": {
+                                "type": "object",
+                                "properties": {
+                                    "prometheus": {"type": "string"},
+                                    "loki": {"type": "string"}
+                                }
+                            },
+                            "tags": {"type": "array", "items": {"type": "string"}}
+                        },
+                        "required": ["name"]
+                    },
+                    "push": {"type": "boolean", "default": false, "description": "Create or overwrite the dashboard in Grafana"}
+                },
+                "required": ["service"]
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a dashboard tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "generate_service_dashboard" => {
+                let service: ServiceDescription = serde_json::from_value(
+                    parameters.get("service").cloned().unwrap_or(Value::Null),
+                )
+                .map_err(|e| {
+                    Error::validation_with_field(
+                        format!("Invalid service description: {}", e),
+                        "service",
+                    )
+                })?;
+                let dashboard = self.generate(&service)?;
+                let model = Self::dashboard_json(&dashboard);
+                let panel_count = dashboard
+                    .panels
+                    .iter()
+                    .filter(|p| p.panel_type != "row")
+                    .count();
+
+                let push = parameters
+                    .get("push")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let pushed = if push {
+                    let monitoring = self.monitoring.as_ref().ok_or_else(|| {
+                        Error::config_with_suggestion(
+                            "Grafana is not configured",
+                            "Configure monitoring.grafana to push dashboards",
+                        )
+                    })?;
+                    Some(monitoring.grafana_create_dashboard(&dashboard).await?)
+                } else {
+                    None
+                };
+
+                let text = match &pushed {
+                    Some(id) => format!(
+                        "Created '{}' in Grafana (id {}) with {} panels",
+                        dashboard.title, id, panel_count
+                    ),
+                    None => format!(
+                        "Generated '{}' with {} panels; import the dashboard JSON or call again with push=true",
+                        dashboard.title, panel_count
+                    ),
+                };
+                Ok(call_result(
+                    text,
+                    json!({ "dashboard": model, "grafana_id": pushed }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "dashboard_tool",
+                name,
+            )),
+        }
+    }
+}
+
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    // Grafana UIDs are limited to 40 characters
+    slug.trim_matches('-').chars().take(36).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_dashboard_with_slo_panels() {
+        let service: ServiceDescription = serde_json::from_value(json!({
+            "name": "checkout",
+            "http": {},
+            "slos": [
+                {"name": "Availability", "objective": 99.9},
+                {"name": "Fast", "objective": 99.0, "kind": "latency", "threshold_seconds": 0.3}
+            ],
+            "logs": [{"title": "Errors", "query": "{$selector} |= \"error\""}]
+        }))
+        .unwrap();
+        let dashboard = DashboardGenerator::new().generate(&service).unwrap();
+
+        // 3 rows, 3 golden signals, 3 panels per SLO, volume + logs
+        assert_eq!(dashboard.panels.len(), 3 + 3 + 6 + 2);
+        let model = DashboardGenerator::dashboard_json(&dashboard);
+        let availability = &model["panels"][5];
+        assert_eq!(availability["type"], "stat");
+        assert_eq!(availability["gridPos"]["y"], 10);
+        assert!(availability["targets"][0]["expr"]
+            .as_str()
+            .unwrap()
+            .contains("code!~\"5..\"}[30d]"));
+        let logs = model["panels"].as_array().unwrap().last().unwrap();
+        assert_eq!(
+            logs["targets"][0]["expr"],
+            "{job=\"checkout\"} |= \"error\""
+        );
+        assert_eq!(model["templating"]["list"][0]["type"], "query");
+    }
+}
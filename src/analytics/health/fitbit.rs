@@ -0,0 +1,403 @@
+//! Fitbit exports and the Fitbit Web API
+//!
+//! A Fitbit export (or Google Takeout of Fitbit) keeps per-minute steps and
+//! calories in `steps-*.json` and `calories-*.json`, nights in
+//! `sleep-*.json`, resting heart rate in `resting_heart_rate-*.json` and
+//! workouts in `exercise-*.json`, with times like `03/05/24 08:00:00`. The
+//! Web API returns the same sleep and workout records and daily series,
+//! given an OAuth access token for the user.
+
+use super::{Activity, DailyHealth, HealthBatch};
+use crate::error::{Error, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use reqwest::Client;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Days the sleep endpoint returns at most
+const MAX_API_DAYS: u64 = 100;
+
+/// Workouts returned by one activity list request
+const ACTIVITY_PAGE: usize = 100;
+
+/// Whether a file in the export holds data this module reads
+pub fn wanted(name: &str) -> bool {
+    name.ends_with(".json")
+        && [
+            "steps-",
+            "calories-",
+            "sleep-",
+            "resting_heart_rate-",
+            "exercise-",
+        ]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// Time of an export record or an API activity
+fn time(value: &Value) -> Option<NaiveDateTime> {
+    let text = value.as_str()?;
+    NaiveDateTime::parse_from_str(text, "%m/%d/%y %H:%M:%S")
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(text)
+                .ok()
+                .map(|t| t.naive_local())
+        })
+        .or_else(|| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f").ok())
+}
+
+/// Date of an export record or an API series entry
+fn date(value: &Value) -> Option<NaiveDate> {
+    let text = value.as_str()?;
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .or_else(|| time(value).map(|t| t.date()))
+        .or_else(|| NaiveDate::parse_from_str(text, "%m/%d/%y").ok())
+}
+
+/// Number given as a JSON number or as text, as the series are
+fn number(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|v| v.parse().ok()))
+}
+
+/// Per-minute or daily values summed by day
+fn daily_totals(records: &[Value], key: &str) -> BTreeMap<NaiveDate, f64> {
+    let mut totals = BTreeMap::new();
+    for record in records {
+        if let (Some(date), Some(value)) = (date(&record[key]), number(&record["value"])) {
+            *totals.entry(date).or_insert(0.0) += value;
+        }
+    }
+    totals
+}
+
+/// Nights summed by the day they end on, naps included
+fn sleep(records: &[Value]) -> Vec<DailyHealth> {
+    let mut nights: BTreeMap<NaiveDate, (u64, u64)> = BTreeMap::new();
+    for record in records {
+        let Some(date) = date(&record["dateOfSleep"]) else {
+            continue;
+        };
+        let night = nights.entry(date).or_default();
+        night.0 += record["minutesAsleep"].as_u64().unwrap_or_default();
+        night.1 += record["levels"]["summary"]["deep"]["minutes"]
+            .as_u64()
+            .unwrap_or_default();
+    }
+    nights
+        .into_iter()
+        .map(|(date, (asleep, deep))| DailyHealth {
+            date,
+            sleep_minutes: Some(asleep),
+            // Short sleeps are logged without stages
+            deep_sleep_minutes: (deep > 0).then_some(deep),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn activity(record: &Value) -> Option<Activity> {
+    let distance = record["distance"].as_f64().filter(|d| *d > 0.0);
+    let distance_km = match record["distanceUnit"].as_str() {
+        Some("Mile") | Some("mile") => distance.map(|d| d * 1.609_344),
+        _ => distance,
+    };
+    let duration = record["activeDuration"]
+        .as_f64()
+        .or_else(|| record["duration"].as_f64())?;
+    Some(Activity {
+        id: format!("fitbit:{}", record["logId"].as_u64()?),
+        start: time(&record["startTime"])?,
+        kind: record["activityName"]
+            .as_str()
+            .unwrap_or("Workout")
+            .to_string(),
+        minutes: duration / 60_000.0,
+        distance_km,
+        calories: record["calories"].as_u64(),
+    })
+}
+
+fn days(totals: BTreeMap<NaiveDate, f64>, set: impl Fn(&mut DailyHealth, f64)) -> Vec<DailyHealth> {
+    totals
+        .into_iter()
+        .map(|(date, total)| {
+            let mut day = DailyHealth {
+                date,
+                ..Default::default()
+            };
+            set(&mut day, total);
+            day
+        })
+        .collect()
+}
+
+/// Days and workouts in one export file
+pub fn parse(name: &str, data: &Value) -> HealthBatch {
+    let records = data.as_array().map(Vec::as_slice).unwrap_or_default();
+    let mut batch = HealthBatch::default();
+    if name.starts_with("steps-") {
+        batch.days = days(daily_totals(records, "dateTime"), |d, v| {
+            d.steps = Some(v.round() as u64)
+        });
+    } else if name.starts_with("calories-") {
+        batch.days = days(daily_totals(records, "dateTime"), |d, v| {
+            d.calories = Some(v.round() as u64)
+        });
+    } else if name.starts_with("resting_heart_rate-") {
+        batch.days = records
+            .iter()
+            .filter_map(|r| {
+                Some(DailyHealth {
+                    date: date(&r["value"]["date"]).or_else(|| date(&r["dateTime"]))?,
+                    resting_heart_rate: r["value"]["value"].as_f64().filter(|v| *v > 0.0),
+                    ..Default::default()
+                })
+            })
+            .filter(|d| d.resting_heart_rate.is_some())
+            .collect();
+    } else if name.starts_with("sleep-") {
+        batch.days = sleep(records);
+    } else if name.starts_with("exercise-") {
+        batch.activities = records.iter().filter_map(activity).collect();
+    }
+    batch
+}
+
+/// Fitbit Web API client for one user's token
+pub struct FitbitClient {
+    client: Client,
+    url: String,
+    token: String,
+}
+
+impl FitbitClient {
+    pub fn new(url: &str, token: &str) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Fitbit client: {}", e)))?;
+        Ok(Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        })
+    }
+
+    async fn get(&self, path: &str, query: &[(&str, String)]) -> Result<Value> {
+        let response = self
+            .client
+            .get(format!("{}/{}", self.url, path))
+            .bearer_auth(&self.token)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| {
+                Error::network_with_endpoint(
+                    format!("Fitbit request failed: {}", e),
+                    self.url.clone(),
+                )
+            })?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        match status.as_u16() {
+            200..=299 => serde_json::from_str(&text)
+                .map_err(|e| Error::parsing(format!("Invalid Fitbit response: {}", e))),
+            401 | 403 => Err(Error::auth(
+                "Fitbit refused the access token; it may have expired or lack the activity, heartrate and sleep scopes",
+            )),
+            429 => Err(Error::api_with_status(
+                "Fitbit rate limit reached; try again within the hour",
+                "fitbit",
+                429,
+            )),
+            code => Err(Error::api_with_status(
+                format!("Fitbit {} failed: {}", path, text.trim()),
+                "fitbit",
+                code,
+            )),
+        }
+    }
+
+    /// Daily steps, calories, resting heart rate, sleep and workouts
+    pub async fn fetch(&self, start: NaiveDate, end: NaiveDate) -> Result<HealthBatch> {
+        if (end - start).num_days() >= MAX_API_DAYS as i64 {
+            return Err(Error::validation_with_field(
+                format!("Fitbit returns at most {} days at a time", MAX_API_DAYS),
+                "start",
+            ));
+        }
+        let range = format!("{}/{}", start, end);
+        let series = |value: &Value, key: &str| {
+            value[key]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .to_vec()
+        };
+        let mut batch = HealthBatch::default();
+
+        let steps = self
+            .get(
+                &format!("1/user/-/activities/steps/date/{}.json", range),
+                &[],
+            )
+            .await?;
+        batch.days.extend(days(
+            daily_totals(&series(&steps, "activities-steps"), "dateTime"),
+            |d, v| d.steps = Some(v.round() as u64),
+        ));
+        let calories = self
+            .get(
+                &format!("1/user/-/activities/calories/date/{}.json", range),
+                &[],
+            )
+            .await?;
+        batch.days.extend(days(
+            daily_totals(&series(&calories, "activities-calories"), "dateTime"),
+            |d, v| d.calories = Some(v.round() as u64),
+        ));
+        let heart = self
+            .get(
+                &format!("1/user/-/activities/heart/date/{}.json", range),
+                &[],
+            )
+            .await?;
+        batch.days.extend(
+            series(&heart, "activities-heart")
+                .iter()
+                .filter_map(|entry| {
+                    Some(DailyHealth {
+                        date: date(&entry["dateTime"])?,
+                        resting_heart_rate: Some(entry["value"]["restingHeartRate"].as_f64()?),
+                        ..Default::default()
+                    })
+                }),
+        );
+        let nights = self
+            .get(&format!("1.2/user/-/sleep/date/{}.json", range), &[])
+            .await?;
+        batch.days.extend(sleep(&series(&nights, "sleep")));
+
+        let workouts = self
+            .get(
+                "1/user/-/activities/list.json",
+                &[
+                    ("afterDate", (start - chrono::Days::new(1)).to_string()),
+                    ("sort", "asc".to_string()),
+                    ("offset", "0".to_string()),
+                    ("limit", ACTIVITY_PAGE.to_string()),
+                ],
+            )
+            .await?;
+        batch.activities = series(&workouts, "activities")
+            .iter()
+            .filter_map(activity)
+            .filter(|a| a.start.date() <= end)
+            .collect();
+        Ok(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, Query};
+    use axum::http::HeaderMap;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn sums_minute_exports_by_day() {
+        let steps = parse(
+            "steps-2024-03-05.json",
+            &json!([
+                {"dateTime": "03/05/24 08:00:00", "value": "40"},
+                {"dateTime": "03/05/24 08:01:00", "value": "62"},
+                {"dateTime": "03/06/24 07:30:00", "value": "15"}
+            ]),
+        );
+        assert_eq!(steps.days.len(), 2);
+        assert_eq!(steps.days[0].steps, Some(102));
+
+        let exercise = parse(
+            "exercise-0.json",
+            &json!([{"logId": 9, "activityName": "Run", "startTime": "03/05/24 07:00:00",
+                     "activeDuration": 1800000, "distance": 3.1, "distanceUnit": "Mile", "calories": 350}]),
+        );
+        let run = &exercise.activities[0];
+        assert_eq!(run.minutes, 30.0);
+        assert!((run.distance_km.unwrap() - 4.989).abs() < 0.001);
+    }
+
+    #[tokio::test]
+    async fn fetches_daily_series_sleep_and_workouts() {
+        let series = |key: &'static str, value: Value| {
+            get(move |headers: HeaderMap| {
+                let value = value.clone();
+                async move {
+                    assert_eq!(headers["authorization"], "Bearer token");
+                    Json(json!({key: [{"dateTime": "2024-03-05", "value": value}]}))
+                }
+            })
+        };
+        let app = Router::new()
+            .route(
+                "/1/user/-/activities/steps/date/:start/:end",
+                series("activities-steps", json!("8123")),
+            )
+            .route(
+                "/1/user/-/activities/calories/date/:start/:end",
+                series("activities-calories", json!("2301")),
+            )
+            .route(
+                "/1/user/-/activities/heart/date/:start/:end",
+                series("activities-heart", json!({"restingHeartRate": 58})),
+            )
+            .route(
+                "/1.2/user/-/sleep/date/:start/:end",
+                get(|Path((start, _)): Path<(String, String)>| async move {
+                    assert_eq!(start, "2024-03-04");
+                    Json(json!({"sleep": [
+                        {"dateOfSleep": "2024-03-05", "minutesAsleep": 401, "levels": {"summary": {"deep": {"minutes": 70}}}},
+                        {"dateOfSleep": "2024-03-05", "minutesAsleep": 25, "levels": {"summary": {}}}
+                    ]}))
+                }),
+            )
+            .route(
+                "/1/user/-/activities/list.json",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    assert_eq!(query["afterDate"], "2024-03-03");
+                    Json(json!({"activities": [
+                        {"logId": 1, "activityName": "Walk", "startTime": "2024-03-05T12:00:00.000+01:00", "activeDuration": 1200000, "calories": 90},
+                        {"logId": 2, "activityName": "Walk", "startTime": "2024-03-09T12:00:00.000+01:00", "activeDuration": 1200000, "calories": 90}
+                    ]}))
+                }),
+            );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let fitbit = FitbitClient::new(&format!("http://{}/", address), "token").unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        let batch = fitbit.fetch(day(4), day(6)).await.unwrap();
+        assert_eq!(batch.days[0].steps, Some(8123));
+        assert_eq!(batch.days[1].calories, Some(2301));
+        assert_eq!(batch.days[2].resting_heart_rate, Some(58.0));
+        assert_eq!(batch.days[3].sleep_minutes, Some(426));
+        assert_eq!(batch.days[3].deep_sleep_minutes, Some(70));
+        assert_eq!(batch.activities.len(), 1);
+        assert_eq!(batch.activities[0].start.to_string(), "2024-03-05 12:00:00");
+        assert!(fitbit
+            .fetch(day(1), day(1) + chrono::Days::new(120))
+            .await
+            .is_err());
+    }
+}
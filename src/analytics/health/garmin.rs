@@ -0,0 +1,133 @@
+//! Garmin Connect data exports
+//!
+//! The export Garmin mails out under Account > Data Management holds
+//! workouts in `*_summarizedActivities.json`, daily totals in `UDSFile_*`
+//! files and nights in `*_sleepData.json`. Garmin has no API for personal
+//! use, so the export is the only source. Activity times are milliseconds
+//! since the epoch in local time, durations milliseconds and distances
+//! centimetres.
+
+use super::{Activity, DailyHealth, HealthBatch};
+use chrono::{DateTime, NaiveDate};
+use serde_json::Value;
+
+/// Whether a file in the export holds data this module reads
+pub fn wanted(name: &str) -> bool {
+    name.ends_with("_summarizedActivities.json")
+        || (name.starts_with("UDSFile_") && name.ends_with(".json"))
+        || name.ends_with("_sleepData.json")
+}
+
+/// Records of a file, which wraps its array in another in some versions
+fn records(data: &Value) -> impl Iterator<Item = &Value> {
+    data.as_array().into_iter().flatten().flat_map(|record| {
+        match record.get("summarizedActivitiesExport") {
+            Some(Value::Array(activities)) => activities.iter().collect::<Vec<_>>(),
+            _ => vec![record],
+        }
+    })
+}
+
+/// `calendarDate`, given as text or as an object with a `date`
+fn calendar_date(record: &Value) -> Option<NaiveDate> {
+    let date = &record["calendarDate"];
+    let text = date.as_str().or_else(|| date["date"].as_str())?;
+    NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn activity(record: &Value) -> Option<Activity> {
+    let start = DateTime::from_timestamp_millis(record["startTimeLocal"].as_f64()? as i64)?;
+    Some(Activity {
+        id: format!("garmin:{}", record["activityId"].as_u64()?),
+        start: start.naive_utc(),
+        kind: record["activityType"]
+            .as_str()
+            .or_else(|| record["activityType"]["typeKey"].as_str())
+            .unwrap_or("other")
+            .to_string(),
+        minutes: record["duration"].as_f64().unwrap_or_default() / 60_000.0,
+        distance_km: record["distance"]
+            .as_f64()
+            .filter(|d| *d > 0.0)
+            .map(|d| d / 100_000.0),
+        calories: record["calories"].as_f64().map(|c| c.round() as u64),
+    })
+}
+
+fn day(record: &Value) -> Option<DailyHealth> {
+    let whole = |key: &str| record[key].as_f64().map(|v| v.round() as u64);
+    Some(DailyHealth {
+        date: calendar_date(record)?,
+        steps: whole("totalSteps"),
+        calories: whole("totalKilocalories"),
+        resting_heart_rate: record["restingHeartRate"].as_f64().filter(|r| *r > 0.0),
+        ..Default::default()
+    })
+}
+
+fn night(record: &Value) -> Option<DailyHealth> {
+    let seconds = |key: &str| record[key].as_f64().unwrap_or_default();
+    let deep = seconds("deepSleepSeconds");
+    let asleep = deep + seconds("lightSleepSeconds") + seconds("remSleepSeconds");
+    Some(DailyHealth {
+        date: calendar_date(record)?,
+        sleep_minutes: (asleep > 0.0).then(|| (asleep / 60.0).round() as u64),
+        deep_sleep_minutes: (asleep > 0.0).then(|| (deep / 60.0).round() as u64),
+        ..Default::default()
+    })
+}
+
+/// Days and workouts in one export file
+pub fn parse(name: &str, data: &Value) -> HealthBatch {
+    let mut batch = HealthBatch::default();
+    if name.ends_with("_summarizedActivities.json") {
+        batch.activities = records(data).filter_map(activity).collect();
+    } else if name.starts_with("UDSFile_") {
+        batch.days = records(data).filter_map(day).collect();
+    } else if name.ends_with("_sleepData.json") {
+        batch.days = records(data).filter_map(night).collect();
+    }
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_activities_daily_totals_and_sleep() {
+        assert!(wanted("UDSFile_2024-01-01_2024-04-10.json"));
+        assert!(!wanted("user_profile.json"));
+
+        let activities = parse(
+            "me@example.com_0_summarizedActivities.json",
+            &json!([{"summarizedActivitiesExport": [{
+                "activityId": 12345, "activityType": "running",
+                "startTimeLocal": 1709625600000.0, "duration": 1830000.0,
+                "distance": 520000.0, "calories": 410.6
+            }]}]),
+        );
+        let run = &activities.activities[0];
+        assert_eq!(run.id, "garmin:12345");
+        assert_eq!(run.start.to_string(), "2024-03-05 08:00:00");
+        assert_eq!(run.minutes, 30.5);
+        assert_eq!(run.distance_km, Some(5.2));
+        assert_eq!(run.calories, Some(411));
+
+        let days = parse(
+            "UDSFile_2024-03-01_2024-06-09.json",
+            &json!([{"calendarDate": "2024-03-05", "totalSteps": 10412, "totalKilocalories": 2480.0, "restingHeartRate": 52}]),
+        );
+        assert_eq!(days.days[0].steps, Some(10412));
+        assert_eq!(days.days[0].resting_heart_rate, Some(52.0));
+
+        let nights = parse(
+            "2024-03-01_2024-06-09_1234_sleepData.json",
+            &json!([{"calendarDate": "2024-03-05", "deepSleepSeconds": 5400, "lightSleepSeconds": 14400, "remSleepSeconds": 6000, "awakeSleepSeconds": 600}]),
+        );
+        assert_eq!(nights.days[0].sleep_minutes, Some(430));
+        assert_eq!(nights.days[0].deep_sleep_minutes, Some(90));
+        assert_eq!(nights.days[0].steps, None);
+    }
+}
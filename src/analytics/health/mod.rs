@@ -0,0 +1,667 @@
+//! Activity and sleep data from Garmin and Fitbit
+//!
+//! Exports downloaded from Garmin Connect or Fitbit, or days fetched from the
+//! Fitbit Web API, are merged into a store of daily totals and workouts, so
+//! importing an overlapping export again updates days instead of doubling
+//! them. Summaries group the stored days into weeks starting on Monday and
+//! compare the latest week with the ones before it.
+
+pub mod fitbit;
+pub mod garmin;
+
+pub use fitbit::FitbitClient;
+
+use crate::error::{Error, Result};
+use crate::tools::{call_result, parse_date, ToolDefinition};
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::sync::RwLock;
+
+/// Weeks a summary covers by default
+const DEFAULT_WEEKS: u64 = 4;
+
+/// Days fetched from the Fitbit Web API by default
+const DEFAULT_API_DAYS: u64 = 30;
+
+/// Health data configuration, under `analytics.health`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Where imported data is persisted; in memory when unset
+    pub path: Option<PathBuf>,
+    /// Fitbit Web API access token with the activity, heartrate and sleep scopes
+    pub fitbit_token: Option<String>,
+    pub fitbit_url: String,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            path: std::env::var("HEALTH_DATA_PATH").ok().map(PathBuf::from),
+            fitbit_token: std::env::var("FITBIT_ACCESS_TOKEN").ok(),
+            fitbit_url: "https://api.fitbit.com".to_string(),
+        }
+    }
+}
+
+/// Where health data comes from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthSource {
+    Garmin,
+    Fitbit,
+}
+
+impl FromStr for HealthSource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "garmin" => Ok(HealthSource::Garmin),
+            "fitbit" => Ok(HealthSource::Fitbit),
+            other => Err(Error::validation_with_field(
+                format!("Unknown health data source: {}", other),
+                "source",
+            )),
+        }
+    }
+}
+
+/// Totals for one day; fields no source reported stay unset
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DailyHealth {
+    pub date: NaiveDate,
+    #[serde(default)]
+    pub steps: Option<u64>,
+    /// Kilocalories burned, resting included
+    #[serde(default)]
+    pub calories: Option<u64>,
+    #[serde(default)]
+    pub resting_heart_rate: Option<f64>,
+    /// Time asleep, naps included
+    #[serde(default)]
+    pub sleep_minutes: Option<u64>,
+    #[serde(default)]
+    pub deep_sleep_minutes: Option<u64>,
+}
+
+impl DailyHealth {
+    /// Take every field `other` reports
+    fn merge(&mut self, other: DailyHealth) {
+        self.steps = other.steps.or(self.steps);
+        self.calories = other.calories.or(self.calories);
+        self.resting_heart_rate = other.resting_heart_rate.or(self.resting_heart_rate);
+        self.sleep_minutes = other.sleep_minutes.or(self.sleep_minutes);
+        self.deep_sleep_minutes = other.deep_sleep_minutes.or(self.deep_sleep_minutes);
+    }
+}
+
+/// A recorded workout
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Activity {
+    /// Source and the source's id, e.g. `garmin:1234`
+    pub id: String,
+    /// Local start time
+    pub start: NaiveDateTime,
+    /// e.g. running, Walk, cycling
+    pub kind: String,
+    pub minutes: f64,
+    #[serde(default)]
+    pub distance_km: Option<f64>,
+    #[serde(default)]
+    pub calories: Option<u64>,
+}
+
+/// Days and workouts read from one export file or API call
+#[derive(Debug, Default)]
+pub struct HealthBatch {
+    pub days: Vec<DailyHealth>,
+    pub activities: Vec<Activity>,
+}
+
+impl HealthBatch {
+    fn extend(&mut self, other: HealthBatch) {
+        self.days.extend(other.days);
+        self.activities.extend(other.activities);
+    }
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Export files read
+    pub files: usize,
+    /// Distinct days with data
+    pub days: usize,
+    pub activities: usize,
+    /// Workouts not stored before
+    pub new_activities: usize,
+    pub first: Option<NaiveDate>,
+    pub last: Option<NaiveDate>,
+}
+
+/// One Monday-to-Sunday week
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WeekSummary {
+    /// The Monday
+    pub start: NaiveDate,
+    /// Days with any data
+    pub days: usize,
+    pub average_steps: Option<f64>,
+    pub average_sleep_minutes: Option<f64>,
+    pub average_resting_heart_rate: Option<f64>,
+    pub workouts: usize,
+    pub active_minutes: f64,
+    pub distance_km: f64,
+}
+
+/// Mean of the values that are set
+fn average(values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    let values: Vec<f64> = values.flatten().collect();
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// The `weeks` weeks up to the one containing `end`, oldest first
+pub fn weekly_trends(
+    days: &[DailyHealth],
+    activities: &[Activity],
+    end: NaiveDate,
+    weeks: u64,
+) -> Vec<WeekSummary> {
+    let monday = end - Days::new(end.weekday().num_days_from_monday().into());
+    (0..weeks.max(1))
+        .rev()
+        .map(|back| {
+            let start = monday - Days::new(7 * back);
+            let next = start + Days::new(7);
+            let week: Vec<&DailyHealth> = days
+                .iter()
+                .filter(|d| d.date >= start && d.date < next)
+                .collect();
+            let workouts: Vec<&Activity> = activities
+                .iter()
+                .filter(|a| a.start.date() >= start && a.start.date() < next)
+                .collect();
+            WeekSummary {
+                start,
+                days: week.len(),
+                average_steps: average(week.iter().map(|d| d.steps.map(|s| s as f64))),
+                average_sleep_minutes: average(
+                    week.iter().map(|d| d.sleep_minutes.map(|s| s as f64)),
+                ),
+                average_resting_heart_rate: average(week.iter().map(|d| d.resting_heart_rate)),
+                workouts: workouts.len(),
+                active_minutes: workouts.iter().map(|a| a.minutes).sum(),
+                distance_km: workouts.iter().filter_map(|a| a.distance_km).sum(),
+            }
+        })
+        .collect()
+}
+
+/// e.g. `7h 05m`
+fn hours(minutes: f64) -> String {
+    let minutes = minutes.round() as u64;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// Percent change from `before` to `now`, e.g. `+12%`
+fn change(now: Option<f64>, before: Option<f64>) -> Option<String> {
+    let (now, before) = (now?, before?);
+    (before > 0.0).then(|| format!("{:+.0}%", (now - before) / before * 100.0))
+}
+
+/// Text describing the weeks and how the latest compares with the rest
+pub fn describe(weeks: &[WeekSummary]) -> String {
+    let mut lines = Vec::new();
+    for week in weeks {
+        let mut parts = Vec::new();
+        if let Some(steps) = week.average_steps {
+            parts.push(format!("{:.0} steps/day", steps));
+        }
+        if let Some(sleep) = week.average_sleep_minutes {
+            parts.push(format!("{} sleep", hours(sleep)));
+        }
+        if let Some(rate) = week.average_resting_heart_rate {
+            parts.push(format!("resting HR {:.0}", rate));
+        }
+        if week.workouts > 0 {
+            let distance = if week.distance_km > 0.0 {
+                format!(", {:.1} km", week.distance_km)
+            } else {
+                String::new()
+            };
+            parts.push(format!(
+                "{} workouts ({:.0} min{})",
+                week.workouts, week.active_minutes, distance
+            ));
+        }
+        if parts.is_empty() {
+            parts.push("no data".to_string());
+        }
+        lines.push(format!(
+            "Week of {}: {}",
+            week.start.format("%a %d %b"),
+            parts.join(", ")
+        ));
+    }
+    if let Some((latest, earlier)) = weeks.split_last() {
+        let earlier: Vec<&WeekSummary> = earlier.iter().filter(|w| w.days > 0).collect();
+        if latest.days > 0 && !earlier.is_empty() {
+            let before =
+                |value: fn(&WeekSummary) -> Option<f64>| average(earlier.iter().map(|w| value(w)));
+            let mut trends = Vec::new();
+            if let Some(steps) = change(latest.average_steps, before(|w| w.average_steps)) {
+                trends.push(format!("steps {}", steps));
+            }
+            if let Some(sleep) = change(
+                latest.average_sleep_minutes,
+                before(|w| w.average_sleep_minutes),
+            ) {
+                trends.push(format!("sleep {}", sleep));
+            }
+            if let (Some(now), Some(then)) = (
+                latest.average_resting_heart_rate,
+                before(|w| w.average_resting_heart_rate),
+            ) {
+                trends.push(format!("resting HR {:+.0} bpm", now - then));
+            }
+            let workouts =
+                earlier.iter().map(|w| w.workouts).sum::<usize>() as f64 / earlier.len() as f64;
+            trends.push(format!("{} workouts vs {:.1}", latest.workouts, workouts));
+            lines.push(format!(
+                "Latest week against the {} before: {}",
+                earlier.len(),
+                trends.join(", ")
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HealthStore {
+    days: BTreeMap<NaiveDate, DailyHealth>,
+    /// Workouts by id
+    activities: BTreeMap<String, Activity>,
+}
+
+/// Imported health data
+pub struct HealthData {
+    config: HealthConfig,
+    fitbit: Option<FitbitClient>,
+    store: RwLock<HealthStore>,
+}
+
+/// Export files under `path` a source reads, or `path` itself when it is a file
+async fn export_files(path: &Path, wanted: fn(&str) -> bool) -> Result<Vec<PathBuf>> {
+    let read_error = |e: std::io::Error, path: &Path| {
+        Error::io_with_path(
+            format!("Failed to read health export: {}", e),
+            path.to_path_buf(),
+        )
+    };
+    let metadata = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| read_error(e, path))?;
+    if metadata.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    let mut directories = vec![path.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        let mut entries = tokio::fs::read_dir(&directory)
+            .await
+            .map_err(|e| read_error(e, &directory))?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| read_error(e, &directory))?
+        {
+            let path = entry.path();
+            if path.is_dir() {
+                directories.push(path);
+            } else if path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(wanted)
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+impl HealthData {
+    /// Open the store, loading data from the configured path if it exists
+    pub async fn open(config: HealthConfig) -> Result<Self> {
+        let store = match &config.path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse health data {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => HealthStore::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read health data: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => HealthStore::default(),
+        };
+        let fitbit = match &config.fitbit_token {
+            Some(token) => Some(FitbitClient::new(&config.fitbit_url, token)?),
+            None => None,
+        };
+        Ok(Self {
+            config,
+            fitbit,
+            store: RwLock::new(store),
+        })
+    }
+
+    async fn persist(&self, store: &HealthStore) -> Result<()> {
+        let Some(path) = &self.config.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create health data directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(format!("Failed to write health data: {}", e), temp.clone())
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace health data: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Merge a batch into the store
+    pub async fn import(&self, batch: HealthBatch) -> Result<ImportReport> {
+        let mut store = self.store.write().await;
+        let mut report = ImportReport::default();
+        let mut dates = std::collections::BTreeSet::new();
+        for day in batch.days {
+            dates.insert(day.date);
+            store
+                .days
+                .entry(day.date)
+                .or_insert_with(|| DailyHealth {
+                    date: day.date,
+                    ..Default::default()
+                })
+                .merge(day);
+        }
+        for activity in batch.activities {
+            dates.insert(activity.start.date());
+            report.activities += 1;
+            if store
+                .activities
+                .insert(activity.id.clone(), activity)
+                .is_none()
+            {
+                report.new_activities += 1;
+            }
+        }
+        report.days = dates.len();
+        report.first = dates.first().copied();
+        report.last = dates.last().copied();
+        self.persist(&store).await?;
+        Ok(report)
+    }
+
+    /// Import every file of a Garmin or Fitbit export under `path`
+    pub async fn import_export(&self, source: HealthSource, path: &Path) -> Result<ImportReport> {
+        let wanted = match source {
+            HealthSource::Garmin => garmin::wanted,
+            HealthSource::Fitbit => fitbit::wanted,
+        };
+        let files = export_files(path, wanted).await?;
+        if files.is_empty() {
+            return Err(Error::not_found_with_resource(
+                format!("No {:?} export files found", source),
+                "health_export",
+                path.display().to_string(),
+            ));
+        }
+        let mut batch = HealthBatch::default();
+        for file in &files {
+            let bytes = tokio::fs::read(file).await.map_err(|e| {
+                Error::io_with_path(format!("Failed to read health export: {}", e), file.clone())
+            })?;
+            let data: Value = serde_json::from_slice(&bytes).map_err(|e| {
+                Error::parsing(format!("Invalid export file {}: {}", file.display(), e))
+            })?;
+            let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            batch.extend(match source {
+                HealthSource::Garmin => garmin::parse(name, &data),
+                HealthSource::Fitbit => fitbit::parse(name, &data),
+            });
+        }
+        let mut report = self.import(batch).await?;
+        report.files = files.len();
+        Ok(report)
+    }
+
+    /// Import days from the Fitbit Web API
+    pub async fn import_fitbit(&self, start: NaiveDate, end: NaiveDate) -> Result<ImportReport> {
+        let fitbit = self.fitbit.as_ref().ok_or_else(|| {
+            Error::config(
+                "Fitbit is not configured; set analytics.health.fitbit_token or FITBIT_ACCESS_TOKEN, or import an export file",
+            )
+        })?;
+        let batch = fitbit.fetch(start, end).await?;
+        self.import(batch).await
+    }
+
+    /// Weekly trends for the `weeks` weeks up to the one containing `end`
+    pub async fn summary(&self, end: NaiveDate, weeks: u64) -> Vec<WeekSummary> {
+        let store = self.store.read().await;
+        let days: Vec<DailyHealth> = store.days.values().cloned().collect();
+        let activities: Vec<Activity> = store.activities.values().cloned().collect();
+        weekly_trends(&days, &activities, end, weeks)
+    }
+
+    /// Get tool definitions for health data
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "import_health_data",
+                "Import activity and sleep data from a Garmin or Fitbit export file or directory, or from the Fitbit Web API when no path is given",
+                "analytics",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "source": {"type": "string", "enum": ["garmin", "fitbit"]},
+                        "path": {"type": "string", "description": "Unzipped export directory or one JSON file from it"},
+                        "start": {"type": "string", "description": "First day to fetch from the Fitbit API (YYYY-MM-DD); 30 days ago when omitted"},
+                        "end": {"type": "string", "description": "Last day to fetch from the Fitbit API (YYYY-MM-DD); today when omitted"}
+                    },
+                    "required": ["source"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "health_summary",
+                "Summarize imported steps, sleep, resting heart rate and workouts by week, with the latest week compared to the ones before",
+                "analytics",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "weeks": {"type": "integer", "minimum": 1, "maximum": 52, "default": DEFAULT_WEEKS},
+                        "end": {"type": "string", "description": "A day in the latest week (YYYY-MM-DD); today when omitted"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    /// Execute a health data tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let field = |key: &str| {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.trim().is_empty())
+        };
+        let today = Utc::now().date_naive();
+        match name {
+            "import_health_data" => {
+                let source: HealthSource = field("source")
+                    .ok_or_else(|| Error::validation_with_field("source is required", "source"))?
+                    .parse()?;
+                let report = match (field("path"), source) {
+                    (Some(path), _) => self.import_export(source, Path::new(path)).await?,
+                    (None, HealthSource::Fitbit) => {
+                        let end = parse_date(field("end"), "end", today)?;
+                        let start = parse_date(
+                            field("start"),
+                            "start",
+                            end - Days::new(DEFAULT_API_DAYS - 1),
+                        )?;
+                        if start > end {
+                            return Err(Error::validation_with_field(
+                                "start must not be after end",
+                                "start",
+                            ));
+                        }
+                        self.import_fitbit(start, end).await?
+                    }
+                    (None, HealthSource::Garmin) => {
+                        return Err(Error::validation_with_field(
+                            "Garmin data is imported from an export; give its path",
+                            "path",
+                        ))
+                    }
+                };
+                let range = match (report.first, report.last) {
+                    (Some(first), Some(last)) => format!(" from {} to {}", first, last),
+                    _ => String::new(),
+                };
+                let files = if report.files > 0 {
+                    format!(" from {} files", report.files)
+                } else {
+                    String::new()
+                };
+                Ok(call_result(
+                    format!(
+                        "Imported {} days and {} workouts ({} new){}{}",
+                        report.days, report.activities, report.new_activities, files, range
+                    ),
+                    serde_json::to_value(&report)?,
+                ))
+            }
+            "health_summary" => {
+                let end = parse_date(field("end"), "end", today)?;
+                let weeks = parameters
+                    .get("weeks")
+                    .and_then(|v| v.as_u64())
+                    .map_or(DEFAULT_WEEKS, |w| w.clamp(1, 52));
+                let summary = self.summary(end, weeks).await;
+                let text = if summary.iter().all(|w| w.days == 0 && w.workouts == 0) {
+                    "No health data for these weeks; import a Garmin or Fitbit export first"
+                        .to_string()
+                } else {
+                    describe(&summary)
+                };
+                Ok(call_result(text, json!({"weeks": summary})))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn merges_imports_and_compares_the_latest_week() {
+        let data = HealthData::open(HealthConfig {
+            path: None,
+            fitbit_token: None,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        let steps = (1..=14).map(|d| DailyHealth {
+            date: day(d),
+            steps: Some(if d >= 11 { 9000 } else { 6000 }),
+            sleep_minutes: Some(420),
+            ..Default::default()
+        });
+        let run = |id: &str, d| Activity {
+            id: id.to_string(),
+            start: day(d).and_hms_opt(7, 0, 0).unwrap(),
+            kind: "running".to_string(),
+            minutes: 30.0,
+            distance_km: Some(5.0),
+            calories: None,
+        };
+        let report = data
+            .import(HealthBatch {
+                days: steps.collect(),
+                activities: vec![run("garmin:1", 5), run("garmin:2", 12)],
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.days, 14);
+        assert_eq!(report.new_activities, 2);
+
+        // A later import fills in fields without losing the ones stored
+        let report = data
+            .import(HealthBatch {
+                days: vec![DailyHealth {
+                    date: day(12),
+                    resting_heart_rate: Some(55.0),
+                    ..Default::default()
+                }],
+                activities: vec![run("garmin:2", 12)],
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.new_activities, 0);
+
+        // 1 March 2024 is a Friday, so the weeks start on 26 Feb, 4 and 11 Mar
+        let weeks = data.summary(day(14), 3).await;
+        assert_eq!(
+            weeks[0].start,
+            NaiveDate::from_ymd_opt(2024, 2, 26).unwrap()
+        );
+        assert_eq!(weeks[0].days, 3);
+        assert_eq!(weeks[2].average_steps, Some(9000.0));
+        assert_eq!(weeks[2].average_resting_heart_rate, Some(55.0));
+        assert_eq!(weeks[2].workouts, 1);
+        assert_eq!(
+            describe(&weeks).lines().last().unwrap(),
+            "Latest week against the 2 before: steps +50%, sleep +0%, 1 workouts vs 0.5"
+        );
+    }
+}
@@ -7,6 +7,18 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+pub mod anomaly;
+pub mod dashboards;
+pub mod health;
+pub mod product;
+pub mod service_map;
+
+pub use anomaly::{Anomaly, Detector, Sample};
+pub use dashboards::{DashboardGenerator, ServiceDescription};
+pub use health::HealthData;
+pub use product::{CohortReport, EventSchema, FunnelReport, ProductAnalytics};
+pub use service_map::{ServiceGraph, ServiceMapper};
+
 /// Analytics module with performance optimizations
 #[derive(Debug)]
 pub struct AnalyticsModule {
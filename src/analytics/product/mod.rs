@@ -0,0 +1,661 @@
+/// Product analytics over event tables
+///
+/// Funnels and cohort retention computed from raw events read out of any
+/// configured `Database` provider. The event table layout is described by an
+/// `EventSchema`, so existing tracking tables work without a fixed schema.
+/// Events are fetched with a single bounded SELECT and aggregated here, which
+/// keeps the SQL portable across providers.
+use crate::database::Database;
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Mapping from analytics concepts to event table columns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSchema {
+    pub table: String,
+    pub user_column: String,
+    pub event_column: String,
+    pub timestamp_column: String,
+}
+
+impl Default for EventSchema {
+    fn default() -> Self {
+        Self {
+            table: "events".to_string(),
+            user_column: "user_id".to_string(),
+            event_column: "event_name".to_string(),
+            timestamp_column: "created_at".to_string(),
+        }
+    }
+}
+
+impl EventSchema {
+    fn validate(&self) -> Result<()> {
+        for (field, identifier) in [
+            ("table", &self.table),
+            ("user_column", &self.user_column),
+            ("event_column", &self.event_column),
+            ("timestamp_column", &self.timestamp_column),
+        ] {
+            let valid = identifier
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && identifier
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+            if !valid {
+                return Err(Error::validation_with_field(
+                    format!("Invalid SQL identifier: {}", identifier),
+                    field,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Product analytics configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductAnalyticsConfig {
+    pub schema: EventSchema,
+    /// Upper bound on events read for one report
+    pub max_events: usize,
+}
+
+impl Default for ProductAnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            schema: EventSchema::default(),
+            max_events: 200_000,
+        }
+    }
+}
+
+/// A tracked event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub user: String,
+    pub name: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Conversion at one funnel step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunnelStep {
+    pub event: String,
+    pub users: usize,
+    /// Share of users from the previous step
+    pub conversion_from_previous: f64,
+    /// Share of users who entered the funnel
+    pub conversion_from_start: f64,
+    /// Median time from the previous step, in seconds
+    pub median_seconds_from_previous: Option<i64>,
+}
+
+/// Funnel conversion report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunnelReport {
+    pub steps: Vec<FunnelStep>,
+    /// Time allowed from entering the funnel to completing it, in seconds
+    pub window_seconds: i64,
+    pub events_analysed: usize,
+}
+
+/// Cohort bucket size
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Interval {
+    Day,
+    Week,
+    Month,
+}
+
+impl FromStr for Interval {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "day" | "daily" => Ok(Interval::Day),
+            "week" | "weekly" => Ok(Interval::Week),
+            "month" | "monthly" => Ok(Interval::Month),
+            other => Err(Error::validation_with_field(
+                format!("Unknown interval: {}", other),
+                "interval",
+            )),
+        }
+    }
+}
+
+impl Interval {
+    /// Start of the bucket containing `date`
+    fn bucket(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Interval::Day => date,
+            Interval::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Interval::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// Whole intervals between two bucket starts
+    fn periods_between(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        match self {
+            Interval::Day => (end - start).num_days(),
+            Interval::Week => (end - start).num_days() / 7,
+            Interval::Month => {
+                (end.year() - start.year()) as i64 * 12 + end.month() as i64 - start.month() as i64
+            }
+        }
+    }
+}
+
+/// Retention of one cohort
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CohortRow {
+    /// Start of the cohort's bucket
+    pub cohort: NaiveDate,
+    pub size: usize,
+    /// Users returning in period 0, 1, 2, ...
+    pub retained: Vec<usize>,
+    pub retention: Vec<f64>,
+}
+
+/// Cohort retention matrix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CohortReport {
+    pub interval: Interval,
+    pub cohort_event: Option<String>,
+    pub return_event: Option<String>,
+    pub cohorts: Vec<CohortRow>,
+    pub events_analysed: usize,
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        (part as f64 / whole as f64 * 10_000.0).round() / 10_000.0
+    }
+}
+
+fn median(values: &mut [i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    Some(values[values.len() / 2])
+}
+
+/// Ordered funnel: each user enters at their first occurrence of the first
+/// step and must complete later steps in order within `window` of entering
+pub fn compute_funnel(events: &[Event], steps: &[String], window: Duration) -> FunnelReport {
+    let mut by_user: HashMap<&str, Vec<&Event>> = HashMap::new();
+    for event in events {
+        by_user.entry(&event.user).or_default().push(event);
+    }
+
+    let Some(first_step) = steps.first() else {
+        return FunnelReport {
+            steps: Vec::new(),
+            window_seconds: window.num_seconds(),
+            events_analysed: events.len(),
+        };
+    };
+    let mut reached = vec![0usize; steps.len()];
+    let mut gaps: Vec<Vec<i64>> = vec![Vec::new(); steps.len()];
+    for user_events in by_user.values_mut() {
+        user_events.sort_by_key(|e| e.timestamp);
+        let Some(entry) = user_events.iter().find(|e| e.name == *first_step) else {
+            continue;
+        };
+        let deadline = entry.timestamp + window;
+        reached[0] += 1;
+        let mut previous = entry.timestamp;
+        for (i, step) in steps.iter().enumerate().skip(1) {
+            let Some(next) = user_events
+                .iter()
+                .find(|e| e.name == *step && e.timestamp >= previous && e.timestamp <= deadline)
+            else {
+                break;
+            };
+            reached[i] += 1;
+            gaps[i].push((next.timestamp - previous).num_seconds());
+            previous = next.timestamp;
+        }
+    }
+
+    let steps = steps
+        .iter()
+        .enumerate()
+        .map(|(i, event)| FunnelStep {
+            event: event.clone(),
+            users: reached[i],
+            conversion_from_previous: if i == 0 {
+                1.0
+            } else {
+                ratio(reached[i], reached[i - 1])
+            },
+            conversion_from_start: ratio(reached[i], reached[0]),
+            median_seconds_from_previous: median(&mut gaps[i]),
+        })
+        .collect();
+    FunnelReport {
+        steps,
+        window_seconds: window.num_seconds(),
+        events_analysed: events.len(),
+    }
+}
+
+/// Cohorts by first `cohort_event` (or first event of any kind); a user is
+/// retained in period N when a `return_event` (or any event) falls N
+/// intervals after their cohort bucket
+pub fn compute_cohorts(
+    events: &[Event],
+    interval: Interval,
+    periods: usize,
+    cohort_event: Option<&str>,
+    return_event: Option<&str>,
+) -> CohortReport {
+    let mut first_seen: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    for event in events
+        .iter()
+        .filter(|e| cohort_event.is_none_or(|name| e.name == name))
+    {
+        first_seen
+            .entry(&event.user)
+            .and_modify(|t| *t = (*t).min(event.timestamp))
+            .or_insert(event.timestamp);
+    }
+
+    let mut cohorts: BTreeMap<NaiveDate, (usize, Vec<HashSet<&str>>)> = BTreeMap::new();
+    for first in first_seen.values() {
+        cohorts
+            .entry(interval.bucket(first.date_naive()))
+            .or_insert_with(|| (0, vec![HashSet::new(); periods]))
+            .0 += 1;
+    }
+    for event in events
+        .iter()
+        .filter(|e| return_event.is_none_or(|name| e.name == name))
+    {
+        let Some(first) = first_seen.get(event.user.as_str()) else {
+            continue;
+        };
+        if event.timestamp < *first {
+            continue;
+        }
+        let cohort = interval.bucket(first.date_naive());
+        let period =
+            interval.periods_between(cohort, interval.bucket(event.timestamp.date_naive()));
+        if let (Ok(period), Some((_, retained))) =
+            (usize::try_from(period), cohorts.get_mut(&cohort))
+        {
+            if let Some(users) = retained.get_mut(period) {
+                users.insert(&event.user);
+            }
+        }
+    }
+
+    let cohorts = cohorts
+        .into_iter()
+        .map(|(cohort, (size, retained))| {
+            let retained: Vec<usize> = retained.iter().map(HashSet::len).collect();
+            CohortRow {
+                cohort,
+                size,
+                retention: retained.iter().map(|&n| ratio(n, size)).collect(),
+                retained,
+            }
+        })
+        .collect();
+    CohortReport {
+        interval,
+        cohort_event: cohort_event.map(str::to_string),
+        return_event: return_event.map(str::to_string),
+        cohorts,
+        events_analysed: events.len(),
+    }
+}
+
+fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Number(n) => {
+            let n = n.as_f64()?;
+            // Epoch milliseconds vs seconds
+            let secs = if n > 1e11 { n / 1000.0 } else { n };
+            Utc.timestamp_opt(secs as i64, 0).single()
+        }
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .map(|t| t.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+                    .iter()
+                    .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+                    .map(|t| t.and_utc())
+            })
+            .or_else(|| {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    .map(|t| t.and_utc())
+            }),
+        _ => None,
+    }
+}
+
+fn sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Funnel and cohort reports over an event table
+pub struct ProductAnalytics {
+    database: Arc<dyn Database>,
+    config: ProductAnalyticsConfig,
+}
+
+impl ProductAnalytics {
+    /// Create reports over the given database
+    pub fn new(database: Arc<dyn Database>, config: ProductAnalyticsConfig) -> Result<Self> {
+        config.schema.validate()?;
+        Ok(Self { database, config })
+    }
+
+    /// Read events in a time range, optionally restricted to some event names
+    pub async fn load_events(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        names: &[String],
+    ) -> Result<Vec<Event>> {
+        let schema = &self.config.schema;
+        let mut query = format!(
+            "SELECT {user} AS user_id, {event} AS event_name, {ts} AS event_time FROM {table} \
+             WHERE {ts} BETWEEN {from} AND {to}",
+            user = schema.user_column,
+            event = schema.event_column,
+            ts = schema.timestamp_column,
+            table = schema.table,
+            from = sql_string(&from.format("%Y-%m-%d %H:%M:%S").to_string()),
+            to = sql_string(&to.format("%Y-%m-%d %H:%M:%S").to_string()),
+        );
+        if !names.is_empty() {
+            let list: Vec<String> = names.iter().map(|n| sql_string(n)).collect();
+            query.push_str(&format!(
+                " AND {} IN ({})",
+                schema.event_column,
+                list.join(", ")
+            ));
+        }
+        query.push_str(&format!(
+            " ORDER BY {} LIMIT {}",
+            schema.timestamp_column, self.config.max_events
+        ));
+
+        let result = self.database.execute_query(&query, None).await?;
+        if result.rows.len() >= self.config.max_events {
+            tracing::warn!(
+                limit = self.config.max_events,
+                "Event limit reached; report covers the earliest events only"
+            );
+        }
+        Ok(result
+            .rows
+            .iter()
+            .filter_map(|row| {
+                let user = match row.get("user_id")? {
+                    Value::String(s) => s.clone(),
+                    Value::Null => return None,
+                    other => other.to_string(),
+                };
+                Some(Event {
+                    user,
+                    name: row.get("event_name")?.as_str()?.to_string(),
+                    timestamp: parse_timestamp(row.get("event_time")?)?,
+                })
+            })
+            .collect())
+    }
+
+    /// Funnel conversion for ordered steps
+    pub async fn funnel(
+        &self,
+        steps: &[String],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        window: Duration,
+    ) -> Result<FunnelReport> {
+        if steps.len() < 2 {
+            return Err(Error::validation_with_field(
+                "A funnel needs at least two steps",
+                "steps",
+            ));
+        }
+        let events = self.load_events(from, to + window, steps).await?;
+        Ok(compute_funnel(&events, steps, window))
+    }
+
+    /// Cohort retention matrix
+    pub async fn cohorts(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        interval: Interval,
+        periods: usize,
+        cohort_event: Option<&str>,
+        return_event: Option<&str>,
+    ) -> Result<CohortReport> {
+        let names: Vec<String> = match (cohort_event, return_event) {
+            (Some(a), Some(b)) => vec![a.to_string(), b.to_string()],
+            _ => Vec::new(),
+        };
+        let events = self.load_events(from, to, &names).await?;
+        Ok(compute_cohorts(
+            &events,
+            interval,
+            periods.clamp(1, 52),
+            cohort_event,
+            return_event,
+        ))
+    }
+
+    /// Get tool definitions for product analytics
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "funnel_report",
+                "Conversion rates between ordered product events (e.g. signup -> activate -> purchase)",
+                "analytics",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "steps": {"type": "array", "items": {"type": "string"}, "minItems": 2},
+                        "from": {"type": "string", "description": "Start date (YYYY-MM-DD or RFC 3339); default 30 days ago"},
+                        "to": {"type": "string", "description": "End date; default now"},
+                        "window_hours": {"type": "integer", "default": 168, "description": "Time allowed to complete the funnel"}
+                    },
+                    "required": ["steps"]
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "cohort_report",
+                "Retention matrix of users grouped by when they first performed an event",
+                "analytics",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "interval": {"type": "string", "enum": ["day", "week", "month"], "default": "week"},
+                        "periods": {"type": "integer", "default": 8},
+                        "cohort_event": {"type": "string", "description": "Event defining cohort entry; default any event"},
+                        "return_event": {"type": "string", "description": "Event counting as a return; default any event"},
+                        "from": {"type": "string"},
+                        "to": {"type": "string"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    fn date_param(parameters: &Value, name: &str, default: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        match parameters.get(name) {
+            None | Some(Value::Null) => Ok(default),
+            Some(value) => parse_timestamp(value).ok_or_else(|| {
+                Error::validation_with_field(format!("Invalid {} date", name), name)
+            }),
+        }
+    }
+
+    /// Execute a product analytics tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let now = Utc::now();
+        match name {
+            "funnel_report" => {
+                let steps: Vec<String> = parameters
+                    .get("steps")
+                    .and_then(|s| s.as_array())
+                    .map(|s| {
+                        s.iter()
+                            .filter_map(|v| v.as_str())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let from = Self::date_param(&parameters, "from", now - Duration::days(30))?;
+                let to = Self::date_param(&parameters, "to", now)?;
+                let window = Duration::hours(
+                    parameters
+                        .get("window_hours")
+                        .and_then(|v| v.as_i64())
+                        .unwrap_or(168)
+                        .max(1),
+                );
+                let report = self.funnel(&steps, from, to, window).await?;
+                let text = report
+                    .steps
+                    .iter()
+                    .enumerate()
+                    .map(|(i, step)| {
+                        format!(
+                            "{}. {}: {} users ({:.1}% of previous, {:.1}% overall)",
+                            i + 1,
+                            step.event,
+                            step.users,
+                            step.conversion_from_previous * 100.0,
+                            step.conversion_from_start * 100.0
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok(call_result(text, serde_json::to_value(&report)?))
+            }
+            "cohort_report" => {
+                let interval = parameters
+                    .get("interval")
+                    .and_then(|v| v.as_str())
+                    .map(Interval::from_str)
+                    .transpose()?
+                    .unwrap_or(Interval::Week);
+                let periods = parameters
+                    .get("periods")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(8) as usize;
+                let span = match interval {
+                    Interval::Day => Duration::days(periods as i64),
+                    Interval::Week => Duration::weeks(periods as i64),
+                    Interval::Month => Duration::days(31 * periods as i64),
+                };
+                let from = Self::date_param(&parameters, "from", now - span)?;
+                let to = Self::date_param(&parameters, "to", now)?;
+                let report = self
+                    .cohorts(
+                        from,
+                        to,
+                        interval,
+                        periods,
+                        parameters.get("cohort_event").and_then(|v| v.as_str()),
+                        parameters.get("return_event").and_then(|v| v.as_str()),
+                    )
+                    .await?;
+                let text = report
+                    .cohorts
+                    .iter()
+                    .map(|row| {
+                        let cells: Vec<String> = row
+                            .retention
+                            .iter()
+                            .map(|r| format!("{:>5.1}%", r * 100.0))
+                            .collect();
+                        format!("{} ({:>5}) {}", row.cohort, row.size, cells.join(" "))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok(call_result(
+                    if text.is_empty() {
+                        "No events in range".to_string()
+                    } else {
+                        text
+                    },
+                    serde_json::to_value(&report)?,
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "analytics_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(user: &str, name: &str, day: u32, hour: u32) -> Event {
+        Event {
+            user: user.to_string(),
+            name: name.to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn computes_funnel_and_cohorts() {
+        let events = vec![
+            event("a", "signup", 1, 9),
+            event("a", "activate", 1, 10),
+            event("a", "purchase", 2, 9),
+            event("b", "signup", 1, 12),
+            event("b", "purchase", 1, 13),
+            event("b", "activate", 1, 14),
+            event("c", "signup", 8, 9),
+            // Outside c's one-day window
+            event("c", "activate", 10, 9),
+        ];
+        let steps: Vec<String> = ["signup", "activate", "purchase"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let funnel = compute_funnel(&events, &steps, Duration::days(1));
+        let users: Vec<usize> = funnel.steps.iter().map(|s| s.users).collect();
+        assert_eq!(users, vec![3, 2, 1]);
+        assert_eq!(funnel.steps[1].conversion_from_previous, 0.6667);
+        assert_eq!(funnel.steps[1].median_seconds_from_previous, Some(7200));
+
+        let cohorts = compute_cohorts(&events, Interval::Week, 3, Some("signup"), None);
+        assert_eq!(cohorts.cohorts.len(), 2);
+        assert_eq!(cohorts.cohorts[0].size, 2);
+        assert_eq!(cohorts.cohorts[0].retained, vec![2, 0, 0]);
+        assert_eq!(cohorts.cohorts[1].retained, vec![1, 0, 0]);
+    }
+}
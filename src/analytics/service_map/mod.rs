@@ -0,0 +1,582 @@
+/// Service dependency maps built from distributed traces
+///
+/// Spans are read from the Jaeger query API or from OTLP/JSON exports and
+/// reduced to a graph whose nodes are services and whose edges are observed
+/// calls, weighted by call count, error count and callee latency. Client
+/// spans tagged with `peer.service` or `db.system` that have no traced child
+/// become edges to that external dependency, so databases and third-party
+/// APIs show up too.
+use crate::error::{Error, Result};
+use crate::monitoring::{JaegerClient, OtelTrace, TraceQuery};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+/// A span reduced to what the dependency graph needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSpan {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_id: Option<String>,
+    pub service: String,
+    /// `peer.service` or `db.system` of client spans
+    pub peer: Option<String>,
+    pub start_us: i64,
+    pub duration_us: i64,
+    pub error: bool,
+}
+
+/// A service in the graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceNode {
+    pub name: String,
+    pub spans: u64,
+    pub errors: u64,
+    /// Not instrumented; only seen as a client span's peer
+    pub external: bool,
+}
+
+/// Calls from one service to another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceEdge {
+    pub caller: String,
+    pub callee: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub mean_ms: f64,
+    pub p95_ms: f64,
+}
+
+/// Dependency graph over a time window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceGraph {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub traces: usize,
+    pub nodes: Vec<ServiceNode>,
+    pub edges: Vec<ServiceEdge>,
+}
+
+/// Who a service depends on and who depends on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceDependencies {
+    pub service: String,
+    /// Services calling it directly
+    pub callers: Vec<String>,
+    /// Every service with a call path into it
+    pub dependents: Vec<String>,
+    /// Services it calls directly
+    pub callees: Vec<String>,
+    /// Every service reachable from it
+    pub dependencies: Vec<String>,
+}
+
+/// Nearest-rank 95th percentile
+fn p95(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let rank = (0.95 * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+impl ServiceGraph {
+    /// Build the graph from spans observed between `from` and `to`
+    pub fn build(spans: &[TraceSpan], from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        let by_id: HashMap<(&str, &str), &TraceSpan> = spans
+            .iter()
+            .map(|s| ((s.trace_id.as_str(), s.span_id.as_str()), s))
+            .collect();
+        let parents: HashSet<(&str, &str)> = spans
+            .iter()
+            .filter_map(|s| s.parent_id.as_deref().map(|p| (s.trace_id.as_str(), p)))
+            .collect();
+
+        let mut nodes: BTreeMap<String, ServiceNode> = BTreeMap::new();
+        let mut calls: BTreeMap<(String, String), (u64, Vec<f64>)> = BTreeMap::new();
+        for span in spans {
+            let node = nodes
+                .entry(span.service.clone())
+                .or_insert_with(|| ServiceNode {
+                    name: span.service.clone(),
+                    spans: 0,
+                    errors: 0,
+                    external: false,
+                });
+            node.spans += 1;
+            node.errors += span.error as u64;
+
+            // A call crosses a service boundary when the parent span belongs to another service
+            let caller = span
+                .parent_id
+                .as_deref()
+                .and_then(|p| by_id.get(&(span.trace_id.as_str(), p)))
+                .map(|parent| parent.service.clone())
+                .filter(|caller| *caller != span.service);
+            let edge = match (caller, &span.peer) {
+                (Some(caller), _) => Some((caller, span.service.clone())),
+                (None, Some(peer))
+                    if *peer != span.service
+                        && !parents.contains(&(span.trace_id.as_str(), span.span_id.as_str())) =>
+                {
+                    Some((span.service.clone(), peer.clone()))
+                }
+                _ => None,
+            };
+            if let Some(key) = edge {
+                let entry = calls.entry(key).or_default();
+                entry.0 += span.error as u64;
+                entry.1.push(span.duration_us as f64 / 1000.0);
+            }
+        }
+
+        let edges: Vec<ServiceEdge> = calls
+            .into_iter()
+            .map(|((caller, callee), (errors, mut latencies))| {
+                let count = latencies.len() as u64;
+                ServiceEdge {
+                    caller,
+                    callee,
+                    calls: count,
+                    errors,
+                    error_rate: errors as f64 / count as f64,
+                    mean_ms: latencies.iter().sum::<f64>() / count as f64,
+                    p95_ms: p95(&mut latencies),
+                }
+            })
+            .collect();
+        for edge in &edges {
+            nodes
+                .entry(edge.callee.clone())
+                .or_insert_with(|| ServiceNode {
+                    name: edge.callee.clone(),
+                    spans: 0,
+                    errors: 0,
+                    external: true,
+                });
+        }
+
+        Self {
+            from,
+            to,
+            traces: spans
+                .iter()
+                .map(|s| s.trace_id.as_str())
+                .collect::<HashSet<_>>()
+                .len(),
+            nodes: nodes.into_values().collect(),
+            edges,
+        }
+    }
+
+    /// Services reachable from `service` following edges forwards or backwards
+    fn reachable(&self, service: &str, upstream: bool) -> Vec<String> {
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::from([service.to_string()]);
+        while let Some(current) = queue.pop_front() {
+            for edge in &self.edges {
+                let (from, to) = if upstream {
+                    (&edge.callee, &edge.caller)
+                } else {
+                    (&edge.caller, &edge.callee)
+                };
+                if *from == current && to != service && seen.insert(to.clone()) {
+                    queue.push_back(to.clone());
+                }
+            }
+        }
+        seen.into_iter().collect()
+    }
+
+    /// Direct and transitive callers and callees of a service
+    pub fn dependencies(&self, service: &str) -> Result<ServiceDependencies> {
+        if !self.nodes.iter().any(|n| n.name == service) {
+            return Err(Error::not_found_with_resource(
+                "Service not seen in traces",
+                "service",
+                service,
+            ));
+        }
+        let direct = |upstream: bool| -> Vec<String> {
+            self.edges
+                .iter()
+                .filter_map(|e| match upstream {
+                    true if e.callee == service => Some(e.caller.clone()),
+                    false if e.caller == service => Some(e.callee.clone()),
+                    _ => None,
+                })
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect()
+        };
+        Ok(ServiceDependencies {
+            service: service.to_string(),
+            callers: direct(true),
+            dependents: self.reachable(service, true),
+            callees: direct(false),
+            dependencies: self.reachable(service, false),
+        })
+    }
+
+    /// Mermaid flowchart; edges touching `focus` are highlighted
+    pub fn mermaid(&self, focus: Option<&str>) -> String {
+        let ids: HashMap<&str, String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.name.as_str(), format!("s{}", i)))
+            .collect();
+        let mut out = String::from("flowchart LR\n");
+        for node in &self.nodes {
+            let label = node.name.replace('"', "'");
+            if node.external {
+                out.push_str(&format!("  {}[(\"{}\")]\n", ids[node.name.as_str()], label));
+            } else {
+                out.push_str(&format!("  {}[\"{}\"]\n", ids[node.name.as_str()], label));
+            }
+        }
+        let mut highlighted = Vec::new();
+        for (index, edge) in self.edges.iter().enumerate() {
+            out.push_str(&format!(
+                "  {} -->|\"{} calls, p95 {:.0}ms{}\"| {}\n",
+                ids[edge.caller.as_str()],
+                edge.calls,
+                edge.p95_ms,
+                if edge.errors > 0 {
+                    format!(", {:.1}% err", edge.error_rate * 100.0)
+                } else {
+                    String::new()
+                },
+                ids[edge.callee.as_str()]
+            ));
+            if focus.is_some_and(|f| edge.caller == f || edge.callee == f) {
+                highlighted.push(index.to_string());
+            }
+        }
+        if let Some(id) = focus.and_then(|f| ids.get(f)) {
+            out.push_str(&format!("  style {} stroke-width:3px\n", id));
+        }
+        if !highlighted.is_empty() {
+            out.push_str(&format!(
+                "  linkStyle {} stroke:#d9534f,stroke-width:2px\n",
+                highlighted.join(",")
+            ));
+        }
+        out
+    }
+}
+
+/// Spans of a trace fetched through the Jaeger client
+pub fn trace_spans(trace: &OtelTrace) -> Vec<TraceSpan> {
+    trace
+        .spans
+        .iter()
+        .filter_map(|span| {
+            let tag = |key: &str| span.tags.get(key).map(String::as_str);
+            let peer = (tag("span.kind") == Some("client"))
+                .then(|| tag("peer.service").or_else(|| tag("db.system")))
+                .flatten()
+                .map(str::to_string);
+            Some(TraceSpan {
+                trace_id: trace.trace_id.clone(),
+                span_id: span.span_id.clone(),
+                parent_id: span.parent_span_id.clone(),
+                service: span.service_name.clone()?,
+                peer,
+                start_us: span.start_time.timestamp_micros(),
+                duration_us: span.duration().num_microseconds().unwrap_or(0),
+                error: span.is_error(),
+            })
+        })
+        .collect()
+}
+
+/// OTLP attribute value as a string
+fn otlp_attribute<'a>(attributes: &'a Value, key: &str) -> Option<&'a str> {
+    attributes
+        .as_array()?
+        .iter()
+        .find(|a| a["key"] == key)?
+        .get("value")?
+        .get("stringValue")?
+        .as_str()
+}
+
+/// OTLP nanosecond timestamps are encoded as strings in JSON
+fn otlp_nanos(value: &Value) -> i64 {
+    value
+        .as_str()
+        .and_then(|s| s.parse().ok())
+        .or_else(|| value.as_i64())
+        .unwrap_or(0)
+}
+
+/// Spans from OTLP/JSON export requests (one document or one per line)
+pub fn parse_otlp(input: &str) -> Result<Vec<TraceSpan>> {
+    let documents: Vec<Value> = match serde_json::from_str::<Value>(input) {
+        Ok(Value::Array(documents)) => documents,
+        Ok(document) => vec![document],
+        Err(_) => input
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|e| {
+                Error::parsing_with_format(format!("Invalid OTLP JSON: {}", e), "otlp", None)
+            })?,
+    };
+
+    let mut spans = Vec::new();
+    for document in &documents {
+        for resource in document["resourceSpans"].as_array().into_iter().flatten() {
+            let service = otlp_attribute(&resource["resource"]["attributes"], "service.name")
+                .unwrap_or("unknown");
+            let scope_spans = resource["scopeSpans"]
+                .as_array()
+                .or_else(|| resource["instrumentationLibrarySpans"].as_array());
+            for span in scope_spans
+                .into_iter()
+                .flatten()
+                .flat_map(|s| s["spans"].as_array().into_iter().flatten())
+            {
+                let start = otlp_nanos(&span["startTimeUnixNano"]);
+                let end = otlp_nanos(&span["endTimeUnixNano"]);
+                // SPAN_KIND_CLIENT is 3
+                let client = span["kind"] == 3 || span["kind"] == "SPAN_KIND_CLIENT";
+                let peer = client
+                    .then(|| {
+                        otlp_attribute(&span["attributes"], "peer.service")
+                            .or_else(|| otlp_attribute(&span["attributes"], "db.system"))
+                    })
+                    .flatten()
+                    .map(str::to_string);
+                // STATUS_CODE_ERROR is 2
+                let code = &span["status"]["code"];
+                spans.push(TraceSpan {
+                    trace_id: span["traceId"].as_str().unwrap_or_default().to_string(),
+                    span_id: span["spanId"].as_str().unwrap_or_default().to_string(),
+                    parent_id: span["parentSpanId"]
+                        .as_str()
+                        .filter(|p| !p.is_empty())
+                        .map(str::to_string),
+                    service: service.to_string(),
+                    peer,
+                    start_us: start / 1000,
+                    duration_us: (end - start).max(0) / 1000,
+                    error: *code == 2 || *code == "STATUS_CODE_ERROR",
+                });
+            }
+        }
+    }
+    Ok(spans)
+}
+
+/// Service map configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceMapConfig {
+    /// Jaeger query service, e.g. `http://jaeger-query:16686`
+    pub jaeger_url: Option<String>,
+    /// Traces fetched per service and window
+    pub traces_per_service: u32,
+}
+
+impl Default for ServiceMapConfig {
+    fn default() -> Self {
+        Self {
+            jaeger_url: std::env::var("JAEGER_QUERY_URL").ok(),
+            traces_per_service: 500,
+        }
+    }
+}
+
+/// Builds service dependency graphs from Jaeger or OTLP trace data
+pub struct ServiceMapper {
+    config: ServiceMapConfig,
+    jaeger: Option<JaegerClient>,
+}
+
+impl ServiceMapper {
+    /// Create a mapper
+    pub fn new(config: ServiceMapConfig) -> Self {
+        let http_client = Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        let jaeger = config
+            .jaeger_url
+            .as_deref()
+            .map(|url| JaegerClient::new(url, http_client));
+        Self { config, jaeger }
+    }
+
+    /// Fetch spans for every service (or the given ones) from the Jaeger query API
+    pub async fn fetch_jaeger(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        services: &[String],
+    ) -> Result<Vec<TraceSpan>> {
+        let jaeger = self.jaeger.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Jaeger query URL is not configured",
+                "Set JAEGER_QUERY_URL or pass OTLP data instead",
+            )
+        })?;
+        let services = if services.is_empty() {
+            jaeger
+                .list_services()
+                .await?
+                .into_iter()
+                .filter(|s| s != "jaeger-query")
+                .collect()
+        } else {
+            services.to_vec()
+        };
+
+        // The same trace comes back once per service it touches
+        let mut seen = HashSet::new();
+        let mut spans = Vec::new();
+        for service in &services {
+            let query = TraceQuery {
+                service: service.clone(),
+                start: Some(from),
+                end: Some(to),
+                limit: Some(self.config.traces_per_service as usize),
+                ..TraceQuery::default()
+            };
+            for trace in jaeger.find_traces(&query).await? {
+                for span in trace_spans(&trace) {
+                    if seen.insert((span.trace_id.clone(), span.span_id.clone())) {
+                        spans.push(span);
+                    }
+                }
+            }
+        }
+        Ok(spans)
+    }
+
+    /// Get tool definitions for service maps
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "service_dependency_map",
+            "Build a service dependency graph (call counts, error rates, p95 latency) from Jaeger or OTLP traces; with a service, list what depends on it and what it depends on",
+            "analytics",
+            json!({
+                "type": "object",
+                "properties": {
+                    "service": {"type": "string", "description": "Service to answer dependency questions for"},
+                    "lookback_minutes": {"type": "integer", "default": 60},
+                    "services": {"type": "array", "items": {"type": "string"}, "description": "Jaeger services to query (default: all)"},
+                    "otlp": {"type": "string", "description": "OTLP/JSON trace export to use instead of Jaeger"}
+                }
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a service map tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        if name != "service_dependency_map" {
+            return Err(Error::not_found_with_resource(
+                "Tool not found",
+                "service_map_tool",
+                name,
+            ));
+        }
+        let to = Utc::now();
+        let lookback = parameters
+            .get("lookback_minutes")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(60)
+            .clamp(1, 7 * 24 * 60);
+        let from = to - Duration::minutes(lookback);
+
+        let spans = match parameters.get("otlp").and_then(|v| v.as_str()) {
+            Some(otlp) => parse_otlp(otlp)?,
+            None => {
+                let services: Vec<String> = parameters
+                    .get("services")
+                    .and_then(|v| serde_json::from_value(v.clone()).ok())
+                    .unwrap_or_default();
+                self.fetch_jaeger(from, to, &services).await?
+            }
+        };
+        let graph = ServiceGraph::build(&spans, from, to);
+        let focus = parameters.get("service").and_then(|v| v.as_str());
+        let dependencies = focus.map(|s| graph.dependencies(s)).transpose()?;
+        let mermaid = graph.mermaid(focus);
+
+        let mut text = format!(
+            "{} services, {} dependencies from {} traces",
+            graph.nodes.len(),
+            graph.edges.len(),
+            graph.traces
+        );
+        if let Some(deps) = &dependencies {
+            let list = |v: &[String]| match v.is_empty() {
+                true => "none".to_string(),
+                false => v.join(", "),
+            };
+            text.push_str(&format!(
+                "\n\n{} is called by: {}\nAll dependents: {}\n{} calls: {}\nAll dependencies: {}",
+                deps.service,
+                list(&deps.callers),
+                list(&deps.dependents),
+                deps.service,
+                list(&deps.callees),
+                list(&deps.dependencies)
+            ));
+        }
+        text.push_str(&format!("\n\n```mermaid\n{}```", mermaid));
+        Ok(call_result(
+            text,
+            json!({
+                "graph": graph,
+                "dependencies": dependencies,
+                "mermaid": mermaid,
+            }),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_graph_and_answers_dependents() {
+        let trace = crate::monitoring::jaeger::parse_trace(&json!({
+            "traceID": "t1",
+            "processes": {"p1": {"serviceName": "frontend"}, "p2": {"serviceName": "checkout"}, "p3": {"serviceName": "payments"}},
+            "spans": [
+                {"traceID": "t1", "spanID": "a", "processID": "p1", "references": [], "startTime": 0, "duration": 90000, "tags": []},
+                {"traceID": "t1", "spanID": "b", "processID": "p2", "references": [{"refType": "CHILD_OF", "spanID": "a"}], "startTime": 10, "duration": 60000, "tags": []},
+                {"traceID": "t1", "spanID": "c", "processID": "p3", "references": [{"refType": "CHILD_OF", "spanID": "b"}], "startTime": 20, "duration": 30000,
+                 "tags": [{"key": "error", "value": true}]},
+                {"traceID": "t1", "spanID": "d", "processID": "p3", "references": [{"refType": "CHILD_OF", "spanID": "c"}], "startTime": 30, "duration": 5000,
+                 "tags": [{"key": "span.kind", "value": "client"}, {"key": "db.system", "value": "postgresql"}]}
+            ]
+        }))
+        .unwrap();
+        let spans = trace_spans(&trace);
+        let graph = ServiceGraph::build(&spans, Utc::now(), Utc::now());
+        assert_eq!(graph.edges.len(), 3);
+        let payments = graph.edges.iter().find(|e| e.callee == "payments").unwrap();
+        assert_eq!((payments.caller.as_str(), payments.errors), ("checkout", 1));
+        assert!(graph
+            .nodes
+            .iter()
+            .any(|n| n.name == "postgresql" && n.external));
+
+        let deps = graph.dependencies("payments").unwrap();
+        assert_eq!(deps.callers, vec!["checkout"]);
+        assert_eq!(deps.dependents, vec!["checkout", "frontend"]);
+        assert_eq!(deps.dependencies, vec!["postgresql"]);
+        assert!(graph.mermaid(Some("payments")).contains("linkStyle"));
+    }
+}
@@ -0,0 +1,264 @@
+//! Compare two saved Criterion baselines and fail on regressions
+//!
+//! ```text
+//! cargo bench -- --save-baseline main
+//! # switch to the change under test
+//! cargo bench -- --save-baseline pr
+//! cargo run --bin bench-compare -- main pr --threshold 10
+//! ```
+//!
+//! A benchmark regresses when its mean got slower by more than the threshold
+//! and the confidence intervals of the two runs do not overlap, so noisy
+//! benchmarks are reported but do not fail the comparison. The exit code is
+//! 1 when anything regressed.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Default allowed slowdown in percent
+const DEFAULT_THRESHOLD: f64 = 5.0;
+
+/// Mean time of one benchmark in one baseline, in nanoseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Mean {
+    estimate: f64,
+    lower: f64,
+    upper: f64,
+}
+
+impl Mean {
+    fn read(path: &Path) -> Option<Self> {
+        let estimates: Value = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+        let mean = &estimates["mean"];
+        Some(Self {
+            estimate: mean["point_estimate"].as_f64()?,
+            lower: mean["confidence_interval"]["lower_bound"].as_f64()?,
+            upper: mean["confidence_interval"]["upper_bound"].as_f64()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Verdict {
+    Regressed,
+    Improved,
+    Unchanged,
+    /// Past the threshold but within the noise of the two runs
+    Noisy,
+}
+
+#[derive(Debug)]
+struct Comparison {
+    id: String,
+    baseline: Mean,
+    candidate: Mean,
+}
+
+impl Comparison {
+    /// Change of the mean in percent, positive when slower
+    fn change(&self) -> f64 {
+        (self.candidate.estimate - self.baseline.estimate) / self.baseline.estimate * 100.0
+    }
+
+    fn verdict(&self, threshold: f64) -> Verdict {
+        let change = self.change();
+        let overlaps = self.candidate.lower <= self.baseline.upper
+            && self.baseline.lower <= self.candidate.upper;
+        if change.abs() <= threshold {
+            Verdict::Unchanged
+        } else if overlaps {
+            Verdict::Noisy
+        } else if change > 0.0 {
+            Verdict::Regressed
+        } else {
+            Verdict::Improved
+        }
+    }
+}
+
+/// Benchmarks saved under both baselines, and those found in only one
+fn collect(
+    dir: &Path,
+    baseline: &str,
+    candidate: &str,
+) -> std::io::Result<(Vec<Comparison>, Vec<String>)> {
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let estimates = |name: &str| current.join(name).join("estimates.json");
+        let id = || {
+            current
+                .strip_prefix(dir)
+                .unwrap_or(&current)
+                .to_string_lossy()
+                .replace('\\', "/")
+        };
+        match (
+            Mean::read(&estimates(baseline)),
+            Mean::read(&estimates(candidate)),
+        ) {
+            (Some(baseline), Some(candidate)) => found.push(Comparison {
+                id: id(),
+                baseline,
+                candidate,
+            }),
+            (Some(_), None) | (None, Some(_)) => missing.push(id()),
+            (None, None) => {}
+        }
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            // Saved baselines and Criterion's own files are leaves
+            if path.is_dir()
+                && ![baseline, candidate, "new", "base", "change", "report"].contains(&name)
+            {
+                pending.push(path);
+            }
+        }
+    }
+    found.sort_by(|a, b| a.id.cmp(&b.id));
+    missing.sort();
+    Ok((found, missing))
+}
+
+fn format_time(nanos: f64) -> String {
+    match nanos {
+        n if n >= 1e9 => format!("{:.2} s", n / 1e9),
+        n if n >= 1e6 => format!("{:.2} ms", n / 1e6),
+        n if n >= 1e3 => format!("{:.2} µs", n / 1e3),
+        n => format!("{:.1} ns", n),
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!(
+        "Usage: bench-compare <baseline> <candidate> [--threshold PERCENT] [--dir target/criterion]"
+    );
+    ExitCode::from(2)
+}
+
+fn main() -> ExitCode {
+    let mut names = Vec::new();
+    let mut threshold = DEFAULT_THRESHOLD;
+    let mut dir = PathBuf::from("target/criterion");
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--threshold" => match args.next().and_then(|t| t.parse().ok()) {
+                Some(t) => threshold = t,
+                None => return usage(),
+            },
+            "--dir" => match args.next() {
+                Some(d) => dir = PathBuf::from(d),
+                None => return usage(),
+            },
+            "-h" | "--help" => return usage(),
+            _ => names.push(arg),
+        }
+    }
+    let [baseline, candidate] = names.as_slice() else {
+        return usage();
+    };
+
+    let (comparisons, missing) = match collect(&dir, baseline, candidate) {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!("Cannot read {}: {}", dir.display(), e);
+            return ExitCode::from(2);
+        }
+    };
+    if comparisons.is_empty() {
+        eprintln!(
+            "No benchmarks saved as both {} and {} under {}",
+            baseline,
+            candidate,
+            dir.display()
+        );
+        return ExitCode::from(2);
+    }
+
+    let width = comparisons.iter().map(|c| c.id.len()).max().unwrap_or(0);
+    let mut regressions = 0;
+    for comparison in &comparisons {
+        let verdict = comparison.verdict(threshold);
+        if verdict == Verdict::Regressed {
+            regressions += 1;
+        }
+        println!(
+            "{:<width$}  {:>10}  {:>10}  {:>+8.2}%  {}",
+            comparison.id,
+            format_time(comparison.baseline.estimate),
+            format_time(comparison.candidate.estimate),
+            comparison.change(),
+            match verdict {
+                Verdict::Regressed => "REGRESSED",
+                Verdict::Improved => "improved",
+                Verdict::Unchanged => "",
+                Verdict::Noisy => "noisy",
+            },
+            width = width
+        );
+    }
+    for id in &missing {
+        println!("{:<width$}  only in one baseline", id, width = width);
+    }
+    println!(
+        "{} benchmarks compared, {} regressed by more than {}%",
+        comparisons.len(),
+        regressions,
+        threshold
+    );
+    if regressions > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save(dir: &Path, id: &str, baseline: &str, mean: f64, spread: f64) {
+        let path = dir.join(id).join(baseline);
+        std::fs::create_dir_all(&path).unwrap();
+        let estimates = serde_json::json!({"mean": {
+            "point_estimate": mean,
+            "confidence_interval": {"lower_bound": mean - spread, "upper_bound": mean + spread}
+        }});
+        std::fs::write(path.join("estimates.json"), estimates.to_string()).unwrap();
+    }
+
+    #[test]
+    fn flags_regressions_outside_the_noise() {
+        let dir = tempfile::tempdir().unwrap();
+        save(dir.path(), "dispatch/jsonrpc", "main", 1000.0, 10.0);
+        save(dir.path(), "dispatch/jsonrpc", "pr", 1200.0, 10.0);
+        save(dir.path(), "lookup/list", "main", 1000.0, 300.0);
+        save(dir.path(), "lookup/list", "pr", 1200.0, 300.0);
+        save(dir.path(), "round_trip/grpc/1", "main", 5000.0, 50.0);
+        save(dir.path(), "round_trip/grpc/1", "pr", 4000.0, 50.0);
+        save(dir.path(), "round_trip/grpc/1000", "main", 9000.0, 50.0);
+
+        let (comparisons, missing) = collect(dir.path(), "main", "pr").unwrap();
+        let verdicts: Vec<(&str, Verdict)> = comparisons
+            .iter()
+            .map(|c| (c.id.as_str(), c.verdict(10.0)))
+            .collect();
+        assert_eq!(
+            verdicts,
+            [
+                ("dispatch/jsonrpc", Verdict::Regressed),
+                ("lookup/list", Verdict::Noisy),
+                ("round_trip/grpc/1", Verdict::Improved),
+            ]
+        );
+        assert_eq!(missing, ["round_trip/grpc/1000"]);
+        assert_eq!(comparisons[0].verdict(25.0), Verdict::Unchanged);
+    }
+}
@@ -0,0 +1,739 @@
+use crate::cloud::storage::ObjectStore;
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+use tokio::sync::RwLock;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// in-toto statement type
+const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+/// SLSA provenance predicate type
+const PROVENANCE_TYPE: &str = "https://slsa.dev/provenance/v1";
+/// DSSE payload type for in-toto statements
+const PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// Artifact registry configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactConfig {
+    /// HMAC key signing attestation envelopes; unsigned when unset
+    pub signing_key: Option<String>,
+    /// Identifier recorded for the signing key
+    pub key_id: String,
+}
+
+impl Default for ArtifactConfig {
+    fn default() -> Self {
+        Self {
+            signing_key: std::env::var("ARTIFACT_SIGNING_KEY").ok(),
+            key_id: std::env::var("ARTIFACT_SIGNING_KEY_ID")
+                .unwrap_or_else(|_| "artifact-registry".to_string()),
+        }
+    }
+}
+
+/// An input to the build, such as a base image or lockfile
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Material {
+    pub uri: String,
+    /// Digest algorithm to hex value, e.g. `sha256`
+    #[serde(default)]
+    pub digest: std::collections::BTreeMap<String, String>,
+}
+
+/// How an artifact was built
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// Builder identity, e.g. the CI workflow URL
+    pub builder_id: String,
+    /// Source repository, e.g. `https://github.com/org/repo`
+    pub repository: String,
+    pub commit: String,
+    /// Branch or tag built
+    pub git_ref: Option<String>,
+    /// Build type URI
+    #[serde(default = "default_build_type")]
+    pub build_type: String,
+    /// CI run identifier
+    pub invocation_id: Option<String>,
+    pub started_on: Option<DateTime<Utc>>,
+    pub finished_on: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub materials: Vec<Material>,
+}
+
+fn default_build_type() -> String {
+    "https://slsa-framework.github.io/github-actions-buildtypes/workflow/v1".to_string()
+}
+
+/// Index entry for a stored artifact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactRecord {
+    pub name: String,
+    pub version: String,
+    pub file_name: String,
+    pub sha256: String,
+    pub size: u64,
+    pub key: String,
+    pub attestation_key: String,
+    pub repository: String,
+    pub commit: String,
+    pub builder_id: String,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+/// DSSE envelope around an in-toto statement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    #[serde(rename = "payloadType")]
+    pub payload_type: String,
+    /// Base64 statement
+    pub payload: String,
+    pub signatures: Vec<Signature>,
+}
+
+/// Envelope signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Signature {
+    pub keyid: String,
+    pub sig: String,
+}
+
+/// DSSE pre-authentication encoding
+fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut out = format!(
+        "DSSEv1 {} {} {} ",
+        payload_type.len(),
+        payload_type,
+        payload.len()
+    )
+    .into_bytes();
+    out.extend_from_slice(payload);
+    out
+}
+
+/// SLSA provenance statement for one artifact
+pub fn provenance_statement(file_name: &str, sha256: &str, build: &BuildInfo) -> Value {
+    let mut source = json!({
+        "uri": format!("git+{}", build.repository),
+        "digest": { "gitCommit": build.commit },
+    });
+    if let Some(git_ref) = &build.git_ref {
+        source["uri"] = json!(format!("git+{}@{}", build.repository, git_ref));
+    }
+    let mut dependencies = vec![source];
+    dependencies.extend(
+        build
+            .materials
+            .iter()
+            .map(|m| json!({ "uri": m.uri, "digest": m.digest })),
+    );
+    json!({
+        "_type": STATEMENT_TYPE,
+        "subject": [{ "name": file_name, "digest": { "sha256": sha256 } }],
+        "predicateType": PROVENANCE_TYPE,
+        "predicate": {
+            "buildDefinition": {
+                "buildType": build.build_type,
+                "externalParameters": {
+                    "repository": build.repository,
+                    "ref": build.git_ref,
+                },
+                "resolvedDependencies": dependencies,
+            },
+            "runDetails": {
+                "builder": { "id": build.builder_id },
+                "metadata": {
+                    "invocationId": build.invocation_id,
+                    "startedOn": build.started_on,
+                    "finishedOn": build.finished_on,
+                },
+            },
+        },
+    })
+}
+
+fn sign(config: &ArtifactConfig, payload: &[u8]) -> Result<Vec<Signature>> {
+    let Some(key) = &config.signing_key else {
+        return Ok(Vec::new());
+    };
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes())
+        .map_err(|e| Error::internal(format!("Failed to create HMAC: {}", e)))?;
+    mac.update(&pae(PAYLOAD_TYPE, payload));
+    Ok(vec![Signature {
+        keyid: config.key_id.clone(),
+        sig: base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes()),
+    }])
+}
+
+/// Decode an envelope, checking its signature when a key is configured
+pub fn open_envelope(config: &ArtifactConfig, envelope: &Envelope) -> Result<Value> {
+    let payload = base64::engine::general_purpose::STANDARD
+        .decode(&envelope.payload)
+        .map_err(|e| Error::parsing(format!("Invalid attestation payload: {}", e)))?;
+    if let Some(key) = &config.signing_key {
+        let verified = envelope.signatures.iter().any(|signature| {
+            let Ok(sig) = base64::engine::general_purpose::STANDARD.decode(&signature.sig) else {
+                return false;
+            };
+            let Ok(mut mac) = HmacSha256::new_from_slice(key.as_bytes()) else {
+                return false;
+            };
+            mac.update(&pae(&envelope.payload_type, &payload));
+            mac.verify_slice(&sig).is_ok()
+        });
+        if !verified {
+            return Err(Error::validation("Attestation signature does not verify"));
+        }
+    }
+    serde_json::from_slice(&payload)
+        .map_err(|e| Error::parsing(format!("Invalid attestation statement: {}", e)))
+}
+
+/// SHA-256 and size of a file, read in chunks
+pub async fn file_digest(path: &Path) -> Result<(String, u64)> {
+    let mut file = tokio::fs::File::open(path).await.map_err(|e| {
+        Error::io_with_path(
+            format!("Failed to open artifact: {}", e),
+            path.to_path_buf(),
+        )
+    })?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let read = file.read(&mut buffer).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to read artifact: {}", e),
+                path.to_path_buf(),
+            )
+        })?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((format!("{:x}", hasher.finalize()), size))
+}
+
+fn valid_segment(value: &str) -> bool {
+    !value.is_empty()
+        && value != "."
+        && value != ".."
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._+-".contains(c))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ArtifactIndex {
+    artifacts: Vec<ArtifactRecord>,
+}
+
+/// Query for `artifact_lookup`; every given field must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArtifactQuery {
+    pub sha256: Option<String>,
+    pub commit: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+}
+
+/// Build artifacts in object storage with provenance attestations
+///
+/// Artifacts are stored under `artifacts/<name>/<version>/<file>` with their
+/// attestation envelope alongside as `<file>.intoto.jsonl`. A local index
+/// answers lookups by digest, commit, name and version.
+pub struct ArtifactRegistry {
+    store: Arc<dyn ObjectStore>,
+    config: ArtifactConfig,
+    /// Where the index is persisted; `None` keeps it in memory
+    path: Option<PathBuf>,
+    index: RwLock<ArtifactIndex>,
+}
+
+impl ArtifactRegistry {
+    /// Open a registry, loading its index from `path` if it exists
+    pub async fn open(
+        store: Arc<dyn ObjectStore>,
+        config: ArtifactConfig,
+        path: Option<PathBuf>,
+    ) -> Result<Self> {
+        let index = match &path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse artifact index {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => ArtifactIndex::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read artifact index: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => ArtifactIndex::default(),
+        };
+        Ok(Self {
+            store,
+            config,
+            path,
+            index: RwLock::new(index),
+        })
+    }
+
+    async fn persist(&self, index: &ArtifactIndex) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create artifact index directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(index)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to write artifact index: {}", e),
+                    temp.clone(),
+                )
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace artifact index: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Upload a file with its provenance; re-uploading the same version is rejected
+    pub async fn upload(
+        &self,
+        path: &Path,
+        name: &str,
+        version: &str,
+        build: &BuildInfo,
+    ) -> Result<ArtifactRecord> {
+        let file_name = path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for (field, value) in [("name", name), ("version", version), ("file", &file_name)] {
+            if !valid_segment(value) {
+                return Err(Error::validation_with_field(
+                    format!("Invalid artifact {}: {}", field, value),
+                    field,
+                ));
+            }
+        }
+        if build.commit.len() < 7 || !build.commit.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::validation_with_field(
+                "Commit must be a git SHA",
+                "commit",
+            ));
+        }
+        let key = format!("artifacts/{}/{}/{}", name, version, file_name);
+        if self
+            .index
+            .read()
+            .await
+            .artifacts
+            .iter()
+            .any(|a| a.key == key)
+        {
+            return Err(Error::validation_with_field(
+                format!("{} {} already has {}", name, version, file_name),
+                "version",
+            ));
+        }
+
+        let (sha256, size) = file_digest(path).await?;
+        let statement = provenance_statement(&file_name, &sha256, build);
+        let payload = serde_json::to_vec(&statement)?;
+        let envelope = Envelope {
+            payload_type: PAYLOAD_TYPE.to_string(),
+            signatures: sign(&self.config, &payload)?,
+            payload: base64::engine::general_purpose::STANDARD.encode(&payload),
+        };
+        let attestation_key = format!("{}.intoto.jsonl", key);
+        self.store.put_file(&key, path).await?;
+        self.store
+            .put(&attestation_key, &serde_json::to_vec(&envelope)?)
+            .await?;
+
+        let record = ArtifactRecord {
+            name: name.to_string(),
+            version: version.to_string(),
+            file_name,
+            sha256,
+            size,
+            key,
+            attestation_key,
+            repository: build.repository.clone(),
+            commit: build.commit.clone(),
+            builder_id: build.builder_id.clone(),
+            uploaded_at: Utc::now(),
+        };
+        let mut index = self.index.write().await;
+        index.artifacts.push(record.clone());
+        self.persist(&index).await?;
+        Ok(record)
+    }
+
+    /// Artifacts matching a query, newest first
+    pub async fn lookup(&self, query: &ArtifactQuery) -> Vec<ArtifactRecord> {
+        let matches = |wanted: &Option<String>, actual: &str| {
+            wanted
+                .as_deref()
+                .is_none_or(|w| actual.eq_ignore_ascii_case(w))
+        };
+        let mut found: Vec<ArtifactRecord> = self
+            .index
+            .read()
+            .await
+            .artifacts
+            .iter()
+            .filter(|a| {
+                matches(&query.sha256, &a.sha256)
+                    && matches(&query.name, &a.name)
+                    && matches(&query.version, &a.version)
+                    // Short SHAs match by prefix
+                    && query
+                        .commit
+                        .as_deref()
+                        .is_none_or(|c| a.commit.starts_with(&c.to_ascii_lowercase()))
+            })
+            .cloned()
+            .collect();
+        found.sort_by_key(|a| std::cmp::Reverse(a.uploaded_at));
+        found
+    }
+
+    /// The verified provenance statement of an artifact
+    pub async fn provenance(&self, record: &ArtifactRecord) -> Result<Value> {
+        let bytes = self.store.get(&record.attestation_key).await?;
+        let envelope: Envelope = serde_json::from_slice(&bytes)
+            .map_err(|e| Error::parsing(format!("Invalid attestation envelope: {}", e)))?;
+        let statement = open_envelope(&self.config, &envelope)?;
+        let subject = &statement["subject"][0]["digest"]["sha256"];
+        if subject != record.sha256.as_str() {
+            return Err(Error::validation(
+                "Attestation subject does not match the artifact digest",
+            ));
+        }
+        Ok(statement)
+    }
+
+    /// Download an artifact, verifying its checksum
+    pub async fn download(&self, record: &ArtifactRecord, dest: &Path) -> Result<()> {
+        let temp = dest.with_extension("part");
+        self.store.get_file(&record.key, &temp).await?;
+        let (sha256, _) = file_digest(&temp).await?;
+        if sha256 != record.sha256 {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(Error::validation(format!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                record.key, record.sha256, sha256
+            )));
+        }
+        tokio::fs::rename(&temp, dest).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to save artifact: {}", e),
+                dest.to_path_buf(),
+            )
+        })
+    }
+
+    /// Get tool definitions for artifact storage
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "artifact_upload",
+                "Upload a build artifact to object storage with its SHA-256 and a SLSA provenance attestation (builder, source commit, materials)",
+                "cicd",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Local file to upload"},
+                        "name": {"type": "string"},
+                        "version": {"type": "string"},
+                        "builder_id": {"type": "string", "description": "Builder identity, e.g. the CI workflow URL"},
+                        "repository": {"type": "string"},
+                        "commit": {"type": "string"},
+                        "git_ref": {"type": "string"},
+                        "invocation_id": {"type": "string"},
+                        "materials": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "uri": {"type": "string"},
+                                    "digest": {"type": "object"}
+                                }
+                            }
+                        }
+                    },
+                    "required": ["path", "name", "version", "builder_id", "repository", "commit"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "artifact_download",
+                "Download an artifact by digest or name and version, verifying its checksum",
+                "cicd",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "sha256": {"type": "string"},
+                        "name": {"type": "string"},
+                        "version": {"type": "string"},
+                        "dest": {"type": "string", "description": "Where to write the file"}
+                    },
+                    "required": ["dest"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "artifact_lookup",
+                "Trace artifacts to their source: find by digest, local file, commit, or name and version, with verified provenance",
+                "cicd",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "sha256": {"type": "string"},
+                        "file": {"type": "string", "description": "Local file (e.g. a deployed binary) to hash and look up"},
+                        "commit": {"type": "string"},
+                        "name": {"type": "string"},
+                        "version": {"type": "string"}
+                    }
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute an artifact tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let string = |key: &str| {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        let required = |key: &str| {
+            string(key)
+                .ok_or_else(|| Error::validation_with_field(format!("{} is required", key), key))
+        };
+        match name {
+            "artifact_upload" => {
+                let build = BuildInfo {
+                    builder_id: required("builder_id")?,
+                    repository: required("repository")?,
+                    commit: required("commit")?.to_ascii_lowercase(),
+                    git_ref: string("git_ref"),
+                    build_type: default_build_type(),
+                    invocation_id: string("invocation_id"),
+                    started_on: None,
+                    finished_on: Some(Utc::now()),
+                    materials: parameters
+                        .get("materials")
+                        .map(|m| serde_json::from_value(m.clone()))
+                        .transpose()
+                        .map_err(|e| {
+                            Error::validation_with_field(
+                                format!("Invalid materials: {}", e),
+                                "materials",
+                            )
+                        })?
+                        .unwrap_or_default(),
+                };
+                let record = self
+                    .upload(
+                        Path::new(&required("path")?),
+                        &required("name")?,
+                        &required("version")?,
+                        &build,
+                    )
+                    .await?;
+                Ok(call_result(
+                    format!(
+                        "Uploaded {} {} to {} (sha256 {}, {} bytes) with provenance for {}@{}",
+                        record.name,
+                        record.version,
+                        self.store.url(&record.key),
+                        record.sha256,
+                        record.size,
+                        record.repository,
+                        record.commit
+                    ),
+                    json!({ "artifact": record }),
+                ))
+            }
+            "artifact_download" => {
+                let query = ArtifactQuery {
+                    sha256: string("sha256"),
+                    name: string("name"),
+                    version: string("version"),
+                    commit: None,
+                };
+                if query.sha256.is_none() && (query.name.is_none() || query.version.is_none()) {
+                    return Err(Error::validation("Give sha256, or name and version"));
+                }
+                let record = self
+                    .lookup(&query)
+                    .await
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| {
+                        Error::not_found_with_resource(
+                            "Artifact not found",
+                            "artifact",
+                            query.sha256.or(query.name).unwrap_or_default(),
+                        )
+                    })?;
+                let dest = PathBuf::from(required("dest")?);
+                self.download(&record, &dest).await?;
+                Ok(call_result(
+                    format!(
+                        "Downloaded {} {} to {} (checksum verified)",
+                        record.name,
+                        record.version,
+                        dest.display()
+                    ),
+                    json!({ "artifact": record, "path": dest }),
+                ))
+            }
+            "artifact_lookup" => {
+                let sha256 = match string("file") {
+                    Some(file) => Some(file_digest(Path::new(&file)).await?.0),
+                    None => string("sha256"),
+                };
+                let query = ArtifactQuery {
+                    sha256,
+                    commit: string("commit"),
+                    name: string("name"),
+                    version: string("version"),
+                };
+                let records = self.lookup(&query).await;
+                let mut text = format!("{} artifacts", records.len());
+                let mut results = Vec::new();
+                for record in records.iter().take(50) {
+                    let provenance = match self.provenance(record).await {
+                        Ok(statement) => json!({ "verified": true, "statement": statement }),
+                        Err(e) => json!({ "verified": false, "error": e.to_string() }),
+                    };
+                    text.push_str(&format!(
+                        "\n{} {} {} <- {}@{} built by {} (provenance {})",
+                        record.name,
+                        record.version,
+                        &record.sha256[..12],
+                        record.repository,
+                        record.commit,
+                        record.builder_id,
+                        if provenance["verified"] == true {
+                            "verified"
+                        } else {
+                            "UNVERIFIED"
+                        }
+                    ));
+                    results.push(json!({ "artifact": record, "provenance": provenance }));
+                }
+                Ok(call_result(text, json!({ "artifacts": results })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "artifact_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::storage::LocalObjectStore;
+
+    #[tokio::test]
+    async fn uploads_with_signed_provenance_and_traces_back_to_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(LocalObjectStore::new(dir.path().join("store")));
+        let config = ArtifactConfig {
+            signing_key: Some("secret".to_string()),
+            key_id: "ci".to_string(),
+        };
+        let registry = ArtifactRegistry::open(store.clone(), config, None)
+            .await
+            .unwrap();
+        let binary = dir.path().join("server");
+        tokio::fs::write(&binary, b"\x7fELF binary").await.unwrap();
+        let build = BuildInfo {
+            builder_id: "https://github.com/acme/shop/actions/runs/42".to_string(),
+            repository: "https://github.com/acme/shop".to_string(),
+            commit: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            git_ref: Some("refs/tags/v1.2.0".to_string()),
+            build_type: default_build_type(),
+            invocation_id: None,
+            started_on: None,
+            finished_on: None,
+            materials: vec![],
+        };
+        let record = registry
+            .upload(&binary, "shop", "1.2.0", &build)
+            .await
+            .unwrap();
+        assert!(registry
+            .upload(&binary, "shop", "1.2.0", &build)
+            .await
+            .is_err());
+
+        let (digest, _) = file_digest(&binary).await.unwrap();
+        let found = registry
+            .lookup(&ArtifactQuery {
+                sha256: Some(digest),
+                ..Default::default()
+            })
+            .await;
+        assert_eq!(found[0].commit, build.commit);
+        let statement = registry.provenance(&record).await.unwrap();
+        assert_eq!(
+            statement["predicate"]["buildDefinition"]["resolvedDependencies"][0]["uri"],
+            "git+https://github.com/acme/shop@refs/tags/v1.2.0"
+        );
+
+        // A tampered attestation no longer verifies
+        let mut envelope: Envelope =
+            serde_json::from_slice(&store.get(&record.attestation_key).await.unwrap()).unwrap();
+        envelope.payload = base64::engine::general_purpose::STANDARD.encode(b"{}");
+        store
+            .put(
+                &record.attestation_key,
+                &serde_json::to_vec(&envelope).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(registry.provenance(&record).await.is_err());
+
+        let dest = dir.path().join("downloaded");
+        registry.download(&record, &dest).await.unwrap();
+        assert_eq!(tokio::fs::read(&dest).await.unwrap(), b"\x7fELF binary");
+    }
+}
@@ -15,6 +15,14 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::process::Command;
 
+pub mod artifacts;
+pub mod risk;
+pub mod verify;
+
+pub use artifacts::{ArtifactRecord, ArtifactRegistry};
+pub use risk::{ReleaseRiskScorer, RiskReport};
+pub use verify::{DeploymentVerifier, Verification};
+
 /// Enhanced CI/CD configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnhancedCicdConfig {
@@ -388,6 +396,33 @@ impl CicdModule {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
+    /// Read the managed resources from the current Terraform state
+    pub async fn terraform_state(&self) -> Result<Vec<TerraformStateResource>> {
+        let tf_config = self
+            .config
+            .terraform
+            .as_ref()
+            .ok_or_else(|| Error::config("Terraform not configured"))?;
+
+        let output = Command::new("terraform")
+            .current_dir(&tf_config.working_dir)
+            .args(["show", "-json"])
+            .output()
+            .await
+            .map_err(|e| Error::internal(format!("Failed to read Terraform state: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(Error::service(format!(
+                "Terraform show failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        let state: Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| Error::parsing(format!("Failed to parse Terraform state: {}", e)))?;
+        parse_terraform_state(&state)
+    }
+
     // Helm operations
 
     /// List Helm releases
@@ -604,6 +639,48 @@ impl CicdModule {
         Ok(())
     }
 
+    /// Roll an ArgoCD application back to a history entry (default: the previous one)
+    pub async fn argocd_rollback(&self, app_name: &str, history_id: Option<u64>) -> Result<()> {
+        let argo_config = self
+            .config
+            .argocd
+            .as_ref()
+            .ok_or_else(|| Error::config("ArgoCD not configured"))?;
+
+        let history_id = history_id.map(|id| id.to_string());
+        let mut args = vec!["app", "rollback", app_name];
+
+        if let Some(id) = &history_id {
+            args.push(id);
+        }
+
+        if argo_config.insecure {
+            args.push("--insecure");
+        }
+
+        if argo_config.grpc_web {
+            args.push("--grpc-web");
+        }
+
+        args.push("--server");
+        args.push(&argo_config.server);
+
+        let output = Command::new("argocd")
+            .args(&args)
+            .output()
+            .await
+            .map_err(|e| Error::internal(format!("Failed to roll back ArgoCD app: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(Error::service(format!(
+                "ArgoCD rollback failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Get configuration
     pub fn get_config(&self) -> &EnhancedCicdConfig {
         &self.config
@@ -696,6 +773,123 @@ pub struct HelmRelease {
     pub updated: String,
 }
 
+/// Resource instance tracked in Terraform state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerraformStateResource {
+    /// Address, e.g. `module.net.aws_instance.web[0]`
+    pub address: String,
+    /// Resource type, e.g. `aws_instance`
+    pub resource_type: String,
+    /// Resource name in configuration
+    pub name: String,
+    /// Provider, e.g. `registry.terraform.io/hashicorp/aws`
+    pub provider: String,
+    /// Attribute values recorded at the last apply
+    pub attributes: Value,
+}
+
+/// Parse managed resources from a raw state file or `terraform show -json` output
+pub fn parse_terraform_state(state: &Value) -> Result<Vec<TerraformStateResource>> {
+    let mut resources = Vec::new();
+
+    if let Some(root) = state.pointer("/values/root_module") {
+        // `terraform show -json` nests resources under modules
+        let mut modules = vec![root];
+        while let Some(module) = modules.pop() {
+            for resource in module
+                .get("resources")
+                .and_then(|r| r.as_array())
+                .into_iter()
+                .flatten()
+            {
+                if resource.get("mode").and_then(|m| m.as_str()) != Some("managed") {
+                    continue;
+                }
+                let field = |key: &str| {
+                    resource
+                        .get(key)
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                resources.push(TerraformStateResource {
+                    address: field("address"),
+                    resource_type: field("type"),
+                    name: field("name"),
+                    provider: field("provider_name"),
+                    attributes: resource.get("values").cloned().unwrap_or(Value::Null),
+                });
+            }
+            if let Some(children) = module.get("child_modules").and_then(|c| c.as_array()) {
+                modules.extend(children);
+            }
+        }
+        return Ok(resources);
+    }
+
+    if state.get("version").and_then(|v| v.as_u64()) != Some(4) {
+        if state.get("values").is_some() || state.get("format_version").is_some() {
+            // An empty state renders without root_module
+            return Ok(resources);
+        }
+        return Err(Error::parsing(
+            "Unsupported Terraform state; expected a version 4 state file or show -json output",
+        ));
+    }
+
+    for resource in state
+        .get("resources")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+    {
+        if resource.get("mode").and_then(|m| m.as_str()) != Some("managed") {
+            continue;
+        }
+        let field = |key: &str| {
+            resource
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let resource_type = field("type");
+        let name = field("name");
+        let base = match resource.get("module").and_then(|m| m.as_str()) {
+            Some(module) => format!("{}.{}.{}", module, resource_type, name),
+            None => format!("{}.{}", resource_type, name),
+        };
+        // provider is recorded as `provider["registry.terraform.io/hashicorp/aws"]`
+        let provider = field("provider");
+        let provider = provider
+            .split('"')
+            .nth(1)
+            .map(str::to_string)
+            .unwrap_or(provider);
+
+        for instance in resource
+            .get("instances")
+            .and_then(|i| i.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let address = match instance.get("index_key") {
+                Some(Value::String(key)) => format!("{}[\"{}\"]", base, key),
+                Some(Value::Number(index)) => format!("{}[{}]", base, index),
+                _ => base.clone(),
+            };
+            resources.push(TerraformStateResource {
+                address,
+                resource_type: resource_type.clone(),
+                name: name.clone(),
+                provider: provider.clone(),
+                attributes: instance.get("attributes").cloned().unwrap_or(Value::Null),
+            });
+        }
+    }
+    Ok(resources)
+}
+
 /// ArgoCD application
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArgoCDApp {
@@ -0,0 +1,536 @@
+use crate::error::{Error, Result};
+use crate::monitoring::{AlertSeverity, Incident, IncidentStore};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::process::Command;
+
+/// Release risk configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskConfig {
+    /// Timezone deploy times are judged in
+    pub timezone: String,
+    /// First and last hour (exclusive) of the working day
+    pub business_hours: (u32, u32),
+    /// Hour on Friday after which deploys count as late
+    pub friday_cutoff_hour: u32,
+    /// How far back incidents mark an area as incident-prone
+    pub incident_lookback_days: i64,
+    /// Scores at or above this are held
+    pub hold_threshold: f64,
+    /// Relative weights of diff size, hotspots, coverage and timing
+    pub weights: RiskWeights,
+}
+
+/// Relative weights of the risk factors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskWeights {
+    pub diff_size: f64,
+    pub hotspots: f64,
+    pub coverage: f64,
+    pub timing: f64,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            timezone: std::env::var("RELEASE_TIMEZONE").unwrap_or_else(|_| "UTC".to_string()),
+            business_hours: (9, 17),
+            friday_cutoff_hour: 15,
+            incident_lookback_days: 180,
+            hold_threshold: 60.0,
+            weights: RiskWeights {
+                diff_size: 0.3,
+                hotspots: 0.35,
+                coverage: 0.2,
+                timing: 0.15,
+            },
+        }
+    }
+}
+
+/// Lines changed in one file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileChange {
+    pub path: String,
+    pub added: u64,
+    pub deleted: u64,
+}
+
+/// What is being deployed and when
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskInput {
+    pub base: String,
+    pub head: String,
+    pub files: Vec<FileChange>,
+    /// Line coverage percentages before and after the change
+    pub base_coverage: Option<f64>,
+    pub head_coverage: Option<f64>,
+    pub deploy_at: DateTime<Utc>,
+}
+
+/// A changed file with incident history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hotspot {
+    pub path: String,
+    pub lines_changed: u64,
+    /// Titles of incidents implicating the file's area
+    pub incidents: Vec<String>,
+}
+
+/// One component of the score, 0 (safe) to 1 (risky)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskFactor {
+    pub name: String,
+    pub score: f64,
+    pub weight: f64,
+    pub detail: String,
+}
+
+/// Deploy recommendation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Recommend,
+    Hold,
+}
+
+/// Structured release risk assessment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskReport {
+    pub base: String,
+    pub head: String,
+    pub files_changed: usize,
+    pub lines_added: u64,
+    pub lines_deleted: u64,
+    pub hotspots: Vec<Hotspot>,
+    pub coverage_delta: Option<f64>,
+    pub factors: Vec<RiskFactor>,
+    /// Weighted score from 0 to 100
+    pub score: f64,
+    pub verdict: Verdict,
+}
+
+fn severity_weight(severity: &AlertSeverity) -> f64 {
+    match severity {
+        AlertSeverity::Critical => 3.0,
+        AlertSeverity::High => 2.0,
+        AlertSeverity::Medium => 1.0,
+        AlertSeverity::Low => 0.5,
+        AlertSeverity::Info => 0.25,
+    }
+}
+
+/// Score a change against incident history
+pub fn assess(
+    input: &RiskInput,
+    incidents: &[Incident],
+    config: &RiskConfig,
+) -> Result<RiskReport> {
+    let tz: Tz = config.timezone.parse().map_err(|_| {
+        Error::config_with_suggestion(
+            format!("Unknown timezone {}", config.timezone),
+            "Use an IANA name such as Europe/Berlin",
+        )
+    })?;
+    let lines_added: u64 = input.files.iter().map(|f| f.added).sum();
+    let lines_deleted: u64 = input.files.iter().map(|f| f.deleted).sum();
+    let lines = lines_added + lines_deleted;
+    let mut factors = Vec::new();
+
+    // 50 changed lines is routine, 2000 is a big-bang release
+    let line_score = ((lines.max(1) as f64 / 50.0).log10() / 40f64.log10()).clamp(0.0, 1.0);
+    let file_score = ((input.files.len() as f64 - 5.0) / 45.0).clamp(0.0, 1.0);
+    factors.push(RiskFactor {
+        name: "diff_size".to_string(),
+        score: line_score.max(file_score),
+        weight: config.weights.diff_size,
+        detail: format!(
+            "{} files, +{} -{} lines",
+            input.files.len(),
+            lines_added,
+            lines_deleted
+        ),
+    });
+
+    let since = input.deploy_at - Duration::days(config.incident_lookback_days);
+    let recent: Vec<&Incident> = incidents.iter().filter(|i| i.opened_at >= since).collect();
+    let mut implicated: BTreeMap<&str, &Incident> = BTreeMap::new();
+    let hotspots: Vec<Hotspot> = input
+        .files
+        .iter()
+        .filter_map(|file| {
+            let matching: Vec<&Incident> = recent
+                .iter()
+                .copied()
+                .filter(|i| i.touches(&file.path))
+                .collect();
+            if matching.is_empty() {
+                return None;
+            }
+            for incident in &matching {
+                implicated.insert(&incident.id, incident);
+            }
+            Some(Hotspot {
+                path: file.path.clone(),
+                lines_changed: file.added + file.deleted,
+                incidents: matching.iter().map(|i| i.title.clone()).collect(),
+            })
+        })
+        .collect();
+    let hotspot_lines: u64 = hotspots.iter().map(|h| h.lines_changed).sum();
+    let share = hotspot_lines as f64 / lines.max(1) as f64;
+    // Two critical incidents in the touched areas saturate the intensity
+    let intensity = (implicated
+        .values()
+        .map(|i| severity_weight(&i.severity))
+        .sum::<f64>()
+        / 6.0)
+        .min(1.0);
+    factors.push(RiskFactor {
+        name: "hotspots".to_string(),
+        score: if hotspots.is_empty() {
+            0.0
+        } else {
+            (share + intensity) / 2.0
+        },
+        weight: config.weights.hotspots,
+        detail: format!(
+            "{} changed files in areas with {} incidents in the last {} days",
+            hotspots.len(),
+            implicated.len(),
+            config.incident_lookback_days
+        ),
+    });
+
+    let coverage_delta = input
+        .base_coverage
+        .zip(input.head_coverage)
+        .map(|(base, head)| head - base);
+    if let Some(delta) = coverage_delta {
+        factors.push(RiskFactor {
+            name: "coverage".to_string(),
+            // Losing five points of coverage is as bad as it gets
+            score: (-delta / 5.0).clamp(0.0, 1.0),
+            weight: config.weights.coverage,
+            detail: format!("line coverage {:+.2} points", delta),
+        });
+    }
+
+    let local = input.deploy_at.with_timezone(&tz);
+    let (open, close) = config.business_hours;
+    let (timing, detail) = match (local.weekday(), local.hour()) {
+        (Weekday::Sat | Weekday::Sun, _) => (1.0, "weekend deploy"),
+        (Weekday::Fri, hour) if hour >= config.friday_cutoff_hour => (0.9, "late Friday deploy"),
+        (_, hour) if hour < open || hour >= close => (0.6, "outside business hours"),
+        _ => (0.0, "during business hours"),
+    };
+    factors.push(RiskFactor {
+        name: "timing".to_string(),
+        score: timing,
+        weight: config.weights.timing,
+        detail: format!("{} ({})", detail, local.format("%a %H:%M %Z")),
+    });
+
+    // Factors without data are dropped and the remaining weights renormalized
+    let total_weight: f64 = factors.iter().map(|f| f.weight).sum();
+    let score = if total_weight > 0.0 {
+        factors.iter().map(|f| f.score * f.weight).sum::<f64>() / total_weight * 100.0
+    } else {
+        0.0
+    };
+
+    Ok(RiskReport {
+        base: input.base.clone(),
+        head: input.head.clone(),
+        files_changed: input.files.len(),
+        lines_added,
+        lines_deleted,
+        hotspots,
+        coverage_delta,
+        factors,
+        score,
+        verdict: if score >= config.hold_threshold {
+            Verdict::Hold
+        } else {
+            Verdict::Recommend
+        },
+    })
+}
+
+/// Reject refs git could read as options or that need shell quoting
+pub(crate) fn validate_git_ref(field: &str, value: &str) -> Result<()> {
+    if value.is_empty()
+        || value.starts_with('-')
+        || !value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-~^@".contains(c))
+    {
+        return Err(Error::validation_with_field("Invalid git ref", field));
+    }
+    Ok(())
+}
+
+/// Parse `git diff --numstat`; binary files count as one line
+pub fn parse_numstat(output: &str) -> Vec<FileChange> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let added = parts.next()?;
+            let deleted = parts.next()?;
+            let path = parts.next()?;
+            // Renames are shown as `dir/{old => new}` or `old => new`
+            let path = match path.split_once(" => ") {
+                Some((before, after)) => match before.split_once('{') {
+                    Some((prefix, _)) => {
+                        let (renamed, suffix) = after.split_once('}').unwrap_or((after, ""));
+                        format!("{}{}{}", prefix, renamed, suffix).replace("//", "/")
+                    }
+                    None => after.to_string(),
+                },
+                None => path.to_string(),
+            };
+            Some(FileChange {
+                path,
+                added: added.parse().unwrap_or(1),
+                deleted: deleted.parse().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// Line coverage percentage from an lcov or Cobertura report
+pub fn parse_coverage(report: &str) -> Result<f64> {
+    if report.contains("<coverage") {
+        let rate = regex::Regex::new(r#"<coverage[^>]*\sline-rate="([0-9.]+)""#)
+            .expect("valid regex")
+            .captures(report)
+            .and_then(|c| c[1].parse::<f64>().ok())
+            .ok_or_else(|| Error::parsing("Cobertura report has no line-rate"))?;
+        return Ok(rate * 100.0);
+    }
+    let (mut found, mut hit) = (0u64, 0u64);
+    for line in report.lines() {
+        if let Some(n) = line.strip_prefix("LF:") {
+            found += n.trim().parse::<u64>().unwrap_or(0);
+        } else if let Some(n) = line.strip_prefix("LH:") {
+            hit += n.trim().parse::<u64>().unwrap_or(0);
+        }
+    }
+    if found == 0 {
+        return Err(Error::parsing_with_format(
+            "Coverage report has no line totals",
+            "lcov",
+            None,
+        ));
+    }
+    Ok(hit as f64 / found as f64 * 100.0)
+}
+
+/// Scores pending deployments
+pub struct ReleaseRiskScorer {
+    incidents: Arc<IncidentStore>,
+    config: RiskConfig,
+}
+
+impl ReleaseRiskScorer {
+    /// Create a scorer reading incident history from `incidents`
+    pub fn new(incidents: Arc<IncidentStore>, config: RiskConfig) -> Self {
+        Self { incidents, config }
+    }
+
+    /// Files changed between two refs of a repository
+    pub async fn diff(&self, repo: &Path, base: &str, head: &str) -> Result<Vec<FileChange>> {
+        validate_git_ref("base", base)?;
+        validate_git_ref("head", head)?;
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["diff", "--numstat", &format!("{}...{}", base, head), "--"])
+            .output()
+            .await
+            .map_err(|e| Error::service(format!("Failed to run git: {}", e)))?;
+        if !output.status.success() {
+            return Err(Error::service(format!(
+                "git diff failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(parse_numstat(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Assess a deployment against the incident store
+    pub async fn assess(&self, input: &RiskInput) -> Result<RiskReport> {
+        let incidents = self.incidents.list(None).await;
+        assess(input, &incidents, &self.config)
+    }
+
+    /// Get tool definitions for release risk
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "score_release_risk",
+            "Score a pending deployment from diff size, changes to incident-prone areas, coverage delta and deploy time, and recommend deploying or holding",
+            "cicd",
+            json!({
+                "type": "object",
+                "properties": {
+                    "repo_path": {"type": "string", "default": "."},
+                    "base": {"type": "string", "default": "origin/main", "description": "Ref currently deployed"},
+                    "head": {"type": "string", "default": "HEAD", "description": "Ref about to be deployed"},
+                    "base_coverage": {"type": "number", "description": "Line coverage % before the change"},
+                    "head_coverage": {"type": "number", "description": "Line coverage % after the change"},
+                    "base_coverage_report": {"type": "string", "description": "lcov or Cobertura report for base"},
+                    "head_coverage_report": {"type": "string", "description": "lcov or Cobertura report for head"},
+                    "deploy_at": {"type": "string", "description": "Planned deploy time (RFC 3339, default now)"}
+                }
+            }),
+            None,
+        )
+        .read_only()]
+    }
+
+    async fn coverage(parameters: &Value, which: &str) -> Result<Option<f64>> {
+        if let Some(value) = parameters
+            .get(format!("{}_coverage", which))
+            .and_then(|v| v.as_f64())
+        {
+            return Ok(Some(value));
+        }
+        let Some(path) = parameters
+            .get(format!("{}_coverage_report", which))
+            .and_then(|v| v.as_str())
+        else {
+            return Ok(None);
+        };
+        let report = tokio::fs::read_to_string(path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to read coverage report: {}", e),
+                path.into(),
+            )
+        })?;
+        parse_coverage(&report).map(Some)
+    }
+
+    /// Execute a release risk tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        if name != "score_release_risk" {
+            return Err(Error::not_found_with_resource(
+                "Tool not found",
+                "risk_tool",
+                name,
+            ));
+        }
+        let string = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let base = string("base").unwrap_or("origin/main");
+        let head = string("head").unwrap_or("HEAD");
+        let deploy_at = match string("deploy_at") {
+            Some(at) => DateTime::parse_from_rfc3339(at)
+                .map_err(|e| {
+                    Error::validation_with_field(format!("Invalid deploy time: {}", e), "deploy_at")
+                })?
+                .with_timezone(&Utc),
+            None => Utc::now(),
+        };
+        let input = RiskInput {
+            base: base.to_string(),
+            head: head.to_string(),
+            files: self
+                .diff(Path::new(string("repo_path").unwrap_or(".")), base, head)
+                .await?,
+            base_coverage: Self::coverage(&parameters, "base").await?,
+            head_coverage: Self::coverage(&parameters, "head").await?,
+            deploy_at,
+        };
+        let report = self.assess(&input).await?;
+
+        let mut text = format!(
+            "Release risk {:.0}/100: {}\n{}...{}: {} files, +{} -{}",
+            report.score,
+            match report.verdict {
+                Verdict::Recommend => "RECOMMEND deploy",
+                Verdict::Hold => "HOLD",
+            },
+            report.base,
+            report.head,
+            report.files_changed,
+            report.lines_added,
+            report.lines_deleted
+        );
+        for factor in &report.factors {
+            text.push_str(&format!(
+                "\n- {}: {:.2} (weight {:.2}) {}",
+                factor.name, factor.score, factor.weight, factor.detail
+            ));
+        }
+        for hotspot in &report.hotspots {
+            text.push_str(&format!(
+                "\n  {} ({} lines): {}",
+                hotspot.path,
+                hotspot.lines_changed,
+                hotspot.incidents.join("; ")
+            ));
+        }
+        Ok(call_result(text, json!({ "report": report })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn holds_large_late_friday_change_to_incident_prone_code() {
+        let files = parse_numstat(
+            "900\t300\tsrc/billing/invoice.rs\n40\t2\tsrc/{old => new}/api.rs\n-\t-\tassets/logo.png\n",
+        );
+        assert_eq!(files[1].path, "src/new/api.rs");
+        assert_eq!(files[2].added, 1);
+
+        let friday_evening = Utc.with_ymd_and_hms(2026, 3, 6, 17, 30, 0).unwrap();
+        let incidents = vec![Incident {
+            id: "1".to_string(),
+            title: "Duplicate invoices".to_string(),
+            severity: AlertSeverity::Critical,
+            services: vec![],
+            paths: vec!["src/billing".to_string()],
+            opened_at: friday_evening - Duration::days(20),
+            resolved_at: None,
+            timeline: vec![],
+        }];
+        let mut input = RiskInput {
+            base: "v1.4.0".to_string(),
+            head: "HEAD".to_string(),
+            files,
+            base_coverage: Some(parse_coverage("LF:200\nLH:160\n").unwrap()),
+            head_coverage: Some(
+                parse_coverage(r#"<coverage line-rate="0.74" branch-rate="0.5">"#).unwrap(),
+            ),
+            deploy_at: friday_evening,
+        };
+        let config = RiskConfig {
+            timezone: "UTC".to_string(),
+            ..RiskConfig::default()
+        };
+        let report = assess(&input, &incidents, &config).unwrap();
+        assert_eq!(report.hotspots.len(), 1);
+        assert_eq!(report.verdict, Verdict::Hold);
+
+        input.files = vec![FileChange {
+            path: "docs/README.md".to_string(),
+            added: 10,
+            deleted: 2,
+        }];
+        input.base_coverage = None;
+        input.deploy_at = Utc.with_ymd_and_hms(2026, 3, 4, 10, 0, 0).unwrap();
+        let report = assess(&input, &incidents, &config).unwrap();
+        assert_eq!(report.verdict, Verdict::Recommend);
+        assert_eq!(report.factors.len(), 3);
+    }
+}
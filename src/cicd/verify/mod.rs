@@ -0,0 +1,853 @@
+use crate::analytics::dashboards::HttpMetrics;
+use crate::cicd::CicdModule;
+use crate::error::{Error, Result};
+use crate::infrastructure::kubernetes::KubernetesClient;
+use crate::lifecycle::LifecycleManager;
+use crate::monitoring::MonitoringModule;
+use crate::tools::{call_result, ToolDefinition};
+use axum::extract::{Json, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{oneshot, RwLock};
+
+/// Verifier configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyConfig {
+    /// Shared secret CI sends in `X-Deploy-Secret` when posting deployment events
+    pub webhook_secret: Option<String>,
+    /// Longest bake period a verification may request
+    pub max_bake_minutes: u64,
+}
+
+impl Default for VerifyConfig {
+    fn default() -> Self {
+        Self {
+            webhook_secret: std::env::var("DEPLOY_HOOK_SECRET").ok(),
+            max_bake_minutes: 120,
+        }
+    }
+}
+
+/// What to roll back when verification fails
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RollbackTarget {
+    /// `kubectl rollout undo` on a Deployment
+    Kubernetes {
+        namespace: String,
+        deployment: String,
+    },
+    /// `argocd app rollback`, to `history_id` or the previous revision
+    ArgoCd {
+        app: String,
+        history_id: Option<u64>,
+    },
+}
+
+/// Error budget checked during the bake
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorBudget {
+    /// Availability objective as a percentage, e.g. 99.9
+    pub objective: f64,
+    /// Budget window, e.g. `30d`
+    #[serde(default = "default_budget_window")]
+    pub window: String,
+    /// Highest tolerated burn rate during the bake
+    #[serde(default = "default_max_burn_rate")]
+    pub max_burn_rate: f64,
+}
+
+fn default_budget_window() -> String {
+    "30d".to_string()
+}
+
+fn default_max_burn_rate() -> f64 {
+    10.0
+}
+
+/// A custom PromQL check; `$selector` expands to the service selector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricCheck {
+    pub name: String,
+    pub expr: String,
+    /// One of `<`, `<=`, `>`, `>=`
+    pub op: String,
+    pub value: f64,
+}
+
+/// Thresholds on golden signals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thresholds {
+    /// Highest tolerated share of 5xx responses
+    #[serde(default = "default_max_error_rate")]
+    pub max_error_rate: f64,
+    /// Absolute p99 latency ceiling
+    pub max_p99_ms: Option<f64>,
+    /// Highest tolerated p99 relative to before the deploy
+    #[serde(default = "default_max_latency_ratio")]
+    pub max_latency_ratio: f64,
+}
+
+fn default_max_error_rate() -> f64 {
+    0.05
+}
+
+fn default_max_latency_ratio() -> f64 {
+    1.5
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            max_error_rate: default_max_error_rate(),
+            max_p99_ms: None,
+            max_latency_ratio: default_max_latency_ratio(),
+        }
+    }
+}
+
+/// A deployment to watch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationSpec {
+    pub service: String,
+    /// Deployed version, for the record
+    pub version: Option<String>,
+    /// Prometheus label selector; defaults to `job="<service>"`
+    pub selector: Option<String>,
+    #[serde(default)]
+    pub http: HttpMetrics,
+    #[serde(default)]
+    pub thresholds: Thresholds,
+    pub error_budget: Option<ErrorBudget>,
+    #[serde(default)]
+    pub checks: Vec<MetricCheck>,
+    #[serde(default = "default_bake_minutes")]
+    pub bake_minutes: u64,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    /// Consecutive failing evaluations before the deploy is judged bad
+    #[serde(default = "default_failures")]
+    pub failures_before_rollback: u32,
+    pub rollback: Option<RollbackTarget>,
+    #[serde(default = "default_true")]
+    pub auto_rollback: bool,
+}
+
+fn default_bake_minutes() -> u64 {
+    10
+}
+
+fn default_interval_secs() -> u64 {
+    30
+}
+
+fn default_failures() -> u32 {
+    2
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl VerificationSpec {
+    fn selector(&self) -> String {
+        self.selector
+            .clone()
+            .unwrap_or_else(|| format!("job=\"{}\"", self.service))
+    }
+
+    /// Golden-signal queries over a rate window
+    fn queries(&self, window: &str) -> Vec<(&'static str, String)> {
+        let selector = self.selector();
+        let http = &self.http;
+        vec![
+            (
+                "request_rate",
+                format!("sum(rate({}{{{}}}[{}]))", http.requests, selector, window),
+            ),
+            (
+                "error_rate",
+                format!(
+                    "sum(rate({req}{{{sel}, {code}=~\"5..\"}}[{w}])) / sum(rate({req}{{{sel}}}[{w}]))",
+                    req = http.requests,
+                    sel = selector,
+                    code = http.status_label,
+                    w = window
+                ),
+            ),
+            (
+                "p99_ms",
+                format!(
+                    "histogram_quantile(0.99, sum by (le) (rate({}_bucket{{{}}}[{}]))) * 1000",
+                    http.duration, selector, window
+                ),
+            ),
+        ]
+    }
+
+    fn validate(&self, config: &VerifyConfig) -> Result<()> {
+        if self.bake_minutes == 0 || self.bake_minutes > config.max_bake_minutes {
+            return Err(Error::validation_with_field(
+                format!("Bake period must be 1-{} minutes", config.max_bake_minutes),
+                "bake_minutes",
+            ));
+        }
+        if self.interval_secs < 5 {
+            return Err(Error::validation_with_field(
+                "Interval must be at least 5 seconds",
+                "interval_secs",
+            ));
+        }
+        if let Some(budget) = &self.error_budget {
+            if !(0.0..100.0).contains(&budget.objective) || budget.objective <= 0.0 {
+                return Err(Error::validation_with_field(
+                    "Objective must be a percentage below 100",
+                    "objective",
+                ));
+            }
+        }
+        if let Some(RollbackTarget::Kubernetes {
+            namespace,
+            deployment,
+        }) = &self.rollback
+        {
+            for (field, value) in [("namespace", namespace), ("deployment", deployment)] {
+                if value.is_empty()
+                    || !value
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "-.".contains(c))
+                {
+                    return Err(Error::validation_with_field("Invalid name", field));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Signal values from one evaluation; `None` when Prometheus had no data
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignalSample {
+    pub at: Option<DateTime<Utc>>,
+    pub request_rate: Option<f64>,
+    pub error_rate: Option<f64>,
+    pub p99_ms: Option<f64>,
+    pub burn_rate: Option<f64>,
+    pub budget_remaining: Option<f64>,
+    #[serde(default)]
+    pub custom: BTreeMap<String, f64>,
+}
+
+/// One threshold evaluation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub value: f64,
+    pub threshold: f64,
+    pub passed: bool,
+}
+
+fn compare(value: f64, op: &str, threshold: f64) -> bool {
+    match op {
+        "<" => value < threshold,
+        "<=" => value <= threshold,
+        ">" => value > threshold,
+        ">=" => value >= threshold,
+        _ => false,
+    }
+}
+
+/// Evaluate a sample against the spec's thresholds; signals without data are skipped
+pub fn evaluate(
+    spec: &VerificationSpec,
+    sample: &SignalSample,
+    baseline_p99_ms: Option<f64>,
+) -> Vec<CheckResult> {
+    let mut results = Vec::new();
+    let mut check = |name: &str, value: Option<f64>, threshold: f64| {
+        if let Some(value) = value {
+            results.push(CheckResult {
+                name: name.to_string(),
+                value,
+                threshold,
+                passed: value <= threshold,
+            });
+        }
+    };
+    let thresholds = &spec.thresholds;
+    check("error_rate", sample.error_rate, thresholds.max_error_rate);
+    if let Some(max) = thresholds.max_p99_ms {
+        check("p99_ms", sample.p99_ms, max);
+    }
+    if let Some(baseline) = baseline_p99_ms.filter(|b| *b > 0.0) {
+        check(
+            "p99_vs_baseline",
+            sample.p99_ms.map(|p| p / baseline),
+            thresholds.max_latency_ratio,
+        );
+    }
+    if let Some(budget) = &spec.error_budget {
+        check("burn_rate", sample.burn_rate, budget.max_burn_rate);
+        // Remaining budget is checked as "consumed share must stay at or below 1"
+        check(
+            "budget_consumed",
+            sample.budget_remaining.map(|r| 1.0 - r),
+            1.0,
+        );
+    }
+    for custom in &spec.checks {
+        if let Some(value) = sample.custom.get(&custom.name) {
+            results.push(CheckResult {
+                name: custom.name.clone(),
+                value: *value,
+                threshold: custom.value,
+                passed: compare(*value, &custom.op, custom.value),
+            });
+        }
+    }
+    results
+}
+
+/// Verification lifecycle
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    Baking,
+    Passed,
+    /// Thresholds breached; no rollback was configured or allowed
+    Failed,
+    RolledBack,
+    RollbackFailed,
+    Cancelled,
+}
+
+/// State and history of one verification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verification {
+    pub id: String,
+    pub spec: VerificationSpec,
+    pub status: VerificationStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub baseline_p99_ms: Option<f64>,
+    pub samples: Vec<SignalSample>,
+    /// Most recent evaluation
+    pub checks: Vec<CheckResult>,
+    pub message: Option<String>,
+}
+
+/// Watches deployments during a bake period and rolls back bad ones
+pub struct DeploymentVerifier {
+    monitoring: Arc<MonitoringModule>,
+    cicd: Arc<CicdModule>,
+    lifecycle: Arc<LifecycleManager>,
+    kubeconfig: Option<String>,
+    context: Option<String>,
+    config: VerifyConfig,
+    verifications: RwLock<BTreeMap<String, Verification>>,
+    cancels: Mutex<HashMap<String, oneshot::Sender<()>>>,
+}
+
+impl DeploymentVerifier {
+    /// Create a verifier reading metrics from `monitoring`
+    pub fn new(
+        monitoring: Arc<MonitoringModule>,
+        cicd: Arc<CicdModule>,
+        lifecycle: Arc<LifecycleManager>,
+        kubeconfig: Option<String>,
+        context: Option<String>,
+        config: VerifyConfig,
+    ) -> Self {
+        Self {
+            monitoring,
+            cicd,
+            lifecycle,
+            kubeconfig,
+            context,
+            config,
+            verifications: RwLock::new(BTreeMap::new()),
+            cancels: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn query(&self, expr: &str, at: Option<DateTime<Utc>>) -> Result<Option<f64>> {
+        let result = self.monitoring.prometheus_query(expr, at).await?;
+        Ok(result
+            .values
+            .first()
+            .map(|v| v.value)
+            .filter(|v| v.is_finite()))
+    }
+
+    async fn sample(&self, spec: &VerificationSpec) -> Result<SignalSample> {
+        let window = format!("{}s", (spec.interval_secs * 2).max(60));
+        let mut sample = SignalSample {
+            at: Some(Utc::now()),
+            ..SignalSample::default()
+        };
+        for (name, expr) in spec.queries(&window) {
+            let value = self.query(&expr, None).await?;
+            match name {
+                "request_rate" => sample.request_rate = value,
+                "error_rate" => sample.error_rate = value,
+                _ => sample.p99_ms = value,
+            }
+        }
+        if let Some(budget) = &spec.error_budget {
+            let allowed = 1.0 - budget.objective / 100.0;
+            sample.burn_rate = sample.error_rate.map(|rate| rate / allowed);
+            let (_, error_expr) = &spec.queries(&budget.window)[1];
+            sample.budget_remaining = self
+                .query(error_expr, None)
+                .await?
+                .map(|rate| 1.0 - rate / allowed);
+        }
+        let selector = spec.selector();
+        for custom in &spec.checks {
+            if let Some(value) = self
+                .query(&custom.expr.replace("$selector", &selector), None)
+                .await?
+            {
+                sample.custom.insert(custom.name.clone(), value);
+            }
+        }
+        Ok(sample)
+    }
+
+    async fn rollback(&self, target: &RollbackTarget) -> Result<String> {
+        match target {
+            RollbackTarget::Kubernetes {
+                namespace,
+                deployment,
+            } => {
+                let client = KubernetesClient::new(
+                    &self.lifecycle,
+                    self.kubeconfig.as_deref(),
+                    self.context.as_deref(),
+                )?;
+                let resource = format!("deployment/{}", deployment);
+                let result = client
+                    .run_secure_kubectl_command(&["rollout", "undo", &resource, "-n", namespace])
+                    .await?;
+                if !result.success {
+                    return Err(Error::service(format!(
+                        "{} failed: {}",
+                        result.command,
+                        result.error.unwrap_or_default().trim()
+                    )));
+                }
+                Ok(format!("Rolled back {}/{}", namespace, resource))
+            }
+            RollbackTarget::ArgoCd { app, history_id } => {
+                self.cicd.argocd_rollback(app, *history_id).await?;
+                Ok(format!("Rolled back ArgoCD app {}", app))
+            }
+        }
+    }
+
+    async fn update<F: FnOnce(&mut Verification)>(&self, id: &str, f: F) {
+        if let Some(verification) = self.verifications.write().await.get_mut(id) {
+            f(verification);
+        }
+    }
+
+    /// Start watching a deployment that has just rolled out
+    pub async fn start(self: &Arc<Self>, spec: VerificationSpec) -> Result<Verification> {
+        spec.validate(&self.config)?;
+        let started_at = Utc::now();
+        // p99 just before the deploy, to catch relative regressions
+        let (_, p99_expr) = &spec.queries("10m")[2];
+        let baseline_p99_ms = match self
+            .query(p99_expr, Some(started_at - chrono::Duration::minutes(1)))
+            .await
+        {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("No baseline latency for {}: {}", spec.service, e);
+                None
+            }
+        };
+        let verification = Verification {
+            id: uuid::Uuid::new_v4().to_string(),
+            spec,
+            status: VerificationStatus::Baking,
+            started_at,
+            finished_at: None,
+            baseline_p99_ms,
+            samples: Vec::new(),
+            checks: Vec::new(),
+            message: None,
+        };
+        self.verifications
+            .write()
+            .await
+            .insert(verification.id.clone(), verification.clone());
+
+        let (cancel, cancelled) = oneshot::channel();
+        self.cancels
+            .lock()
+            .map_err(|_| Error::internal("Verifier lock poisoned"))?
+            .insert(verification.id.clone(), cancel);
+        let verifier = Arc::clone(self);
+        let id = verification.id.clone();
+        tokio::spawn(async move { verifier.watch(id, cancelled).await });
+        Ok(verification)
+    }
+
+    async fn watch(self: Arc<Self>, id: String, mut cancelled: oneshot::Receiver<()>) {
+        let Some(verification) = self.verifications.read().await.get(&id).cloned() else {
+            return;
+        };
+        let spec = verification.spec;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(spec.bake_minutes * 60);
+        let mut ticker = tokio::time::interval(Duration::from_secs(spec.interval_secs));
+        let mut failures = 0;
+
+        let (status, message) = loop {
+            tokio::select! {
+                _ = &mut cancelled => break (VerificationStatus::Cancelled, None),
+                _ = ticker.tick() => {}
+            }
+            if tokio::time::Instant::now() >= deadline {
+                break (VerificationStatus::Passed, None);
+            }
+            let sample = match self.sample(&spec).await {
+                Ok(sample) => sample,
+                Err(e) => {
+                    tracing::warn!("Verification {} could not sample metrics: {}", id, e);
+                    continue;
+                }
+            };
+            let checks = evaluate(&spec, &sample, verification.baseline_p99_ms);
+            let failed: Vec<String> = checks
+                .iter()
+                .filter(|c| !c.passed)
+                .map(|c| format!("{} {:.4} > {:.4}", c.name, c.value, c.threshold))
+                .collect();
+            self.update(&id, |v| {
+                v.samples.push(sample);
+                v.checks = checks;
+            })
+            .await;
+            failures = if failed.is_empty() { 0 } else { failures + 1 };
+            if failures >= spec.failures_before_rollback.max(1) {
+                let breach = format!("Thresholds breached: {}", failed.join(", "));
+                break match (&spec.rollback, spec.auto_rollback) {
+                    (Some(target), true) => match self.rollback(target).await {
+                        Ok(done) => (
+                            VerificationStatus::RolledBack,
+                            Some(format!("{}. {}", breach, done)),
+                        ),
+                        Err(e) => (
+                            VerificationStatus::RollbackFailed,
+                            Some(format!("{}. Rollback failed: {}", breach, e)),
+                        ),
+                    },
+                    _ => (VerificationStatus::Failed, Some(breach)),
+                };
+            }
+        };
+
+        if let Ok(mut cancels) = self.cancels.lock() {
+            cancels.remove(&id);
+        }
+        match status {
+            VerificationStatus::Passed | VerificationStatus::Cancelled => {
+                tracing::info!("Verification {} of {} {:?}", id, spec.service, status)
+            }
+            _ => tracing::warn!(
+                "Verification {} of {} {:?}: {}",
+                id,
+                spec.service,
+                status,
+                message.as_deref().unwrap_or_default()
+            ),
+        }
+        self.update(&id, |v| {
+            v.status = status;
+            v.message = message;
+            v.finished_at = Some(Utc::now());
+        })
+        .await;
+    }
+
+    /// Stop watching a deployment; nothing is rolled back
+    pub async fn cancel(&self, id: &str) -> Result<()> {
+        let cancel = self
+            .cancels
+            .lock()
+            .map_err(|_| Error::internal("Verifier lock poisoned"))?
+            .remove(id)
+            .ok_or_else(|| {
+                Error::not_found_with_resource("No active verification", "verification", id)
+            })?;
+        let _ = cancel.send(());
+        Ok(())
+    }
+
+    /// A verification by id
+    pub async fn get(&self, id: &str) -> Result<Verification> {
+        self.verifications
+            .read()
+            .await
+            .get(id)
+            .cloned()
+            .ok_or_else(|| {
+                Error::not_found_with_resource("Verification not found", "verification", id)
+            })
+    }
+
+    /// Verifications, newest first
+    pub async fn list(&self) -> Vec<Verification> {
+        let mut list: Vec<Verification> =
+            self.verifications.read().await.values().cloned().collect();
+        list.sort_by_key(|v| std::cmp::Reverse(v.started_at));
+        list
+    }
+
+    /// HTTP hook CI calls after a rollout
+    ///
+    /// `POST /deployments/verify` takes a `VerificationSpec` authenticated by
+    /// `X-Deploy-Secret`.
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/deployments/verify", post(hook_handler))
+            .with_state(self)
+    }
+
+    /// Get tool definitions for deployment verification
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "deployment_verify",
+                "Watch a deployment's golden signals and error budget for a bake period, rolling back (kubectl rollout undo or ArgoCD rollback) when thresholds are breached",
+                "cicd",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "service": {"type": "string"},
+                        "version": {"type": "string"},
+                        "selector": {"type": "string", "description": "Prometheus label selector (default job=\"<service>\")"},
+                        "thresholds": {
+                            "type": "object",
+                            "properties": {
+                                "max_error_rate": {"type": "number", "default": 0.05},
+                                "max_p99_ms": {"type": "number"},
+                                "max_latency_ratio": {"type": "number", "default": 1.5}
+                            }
+                        },
+                        "error_budget": {
+                            "type": "object",
+                            "properties": {
+                                "objective": {"type": "number"},
+                                "window": {"type": "string", "default": "30d"},
+                                "max_burn_rate": {"type": "number", "default": 10}
+                            },
+                            "required": ["objective"]
+                        },
+                        "checks": {"type": "array", "items": {"type": "object"}},
+                        "bake_minutes": {"type": "integer", "default": 10},
+                        "interval_secs": {"type": "integer", "default": 30},
+                        "failures_before_rollback": {"type": "integer", "default": 2},
+                        "rollback": {
+                            "type": "object",
+                            "description": "{type: kubernetes, namespace, deployment} or {type: argo_cd, app, history_id}"
+                        },
+                        "auto_rollback": {"type": "boolean", "default": true}
+                    },
+                    "required": ["service"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "deployment_verify_status",
+                "Show a deployment verification, or list recent ones",
+                "cicd",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "deployment_verify_cancel",
+                "Stop watching a deployment without rolling back",
+                "cicd",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    fn describe(verification: &Verification) -> String {
+        let mut text = format!(
+            "{} {} {}: {:?}",
+            verification.id,
+            verification.spec.service,
+            verification.spec.version.as_deref().unwrap_or(""),
+            verification.status
+        );
+        if let Some(message) = &verification.message {
+            text.push_str(&format!(" - {}", message));
+        }
+        for check in &verification.checks {
+            text.push_str(&format!(
+                "\n  {} {}: {:.4} (limit {:.4})",
+                if check.passed { "ok  " } else { "FAIL" },
+                check.name,
+                check.value,
+                check.threshold
+            ));
+        }
+        text
+    }
+
+    /// Execute a deployment verification tool
+    pub async fn execute_tool(self: &Arc<Self>, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "deployment_verify" => {
+                let spec: VerificationSpec = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid verification spec: {}", e)))?;
+                let verification = self.start(spec).await?;
+                Ok(call_result(
+                    format!(
+                        "Verifying {} for {} minutes (id {})",
+                        verification.spec.service, verification.spec.bake_minutes, verification.id
+                    ),
+                    json!({ "verification": verification }),
+                ))
+            }
+            "deployment_verify_status" => match parameters.get("id").and_then(|v| v.as_str()) {
+                Some(id) => {
+                    let verification = self.get(id).await?;
+                    Ok(call_result(
+                        Self::describe(&verification),
+                        json!({ "verification": verification }),
+                    ))
+                }
+                None => {
+                    let list = self.list().await;
+                    let text = list
+                        .iter()
+                        .map(Self::describe)
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    Ok(call_result(
+                        format!("{} verifications\n{}", list.len(), text),
+                        json!({ "verifications": list }),
+                    ))
+                }
+            },
+            "deployment_verify_cancel" => {
+                let id = parameters
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::validation_with_field("id is required", "id"))?;
+                self.cancel(id).await?;
+                Ok(call_result(
+                    format!("Verification {} cancelled", id),
+                    json!({ "id": id, "cancelled": true }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "verify_tool",
+                name,
+            )),
+        }
+    }
+}
+
+async fn hook_handler(
+    State(verifier): State<Arc<DeploymentVerifier>>,
+    headers: HeaderMap,
+    Json(spec): Json<VerificationSpec>,
+) -> (StatusCode, Json<Value>) {
+    let Some(secret) = &verifier.config.webhook_secret else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "webhook secret not configured" })),
+        );
+    };
+    let provided = headers
+        .get("x-deploy-secret")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !crate::security::SecurityModule::new().secure_compare(secret, provided) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid secret" })),
+        );
+    }
+    match verifier.start(spec).await {
+        Ok(verification) => (
+            StatusCode::ACCEPTED,
+            Json(json!({ "verification_id": verification.id })),
+        ),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": e.to_string() })),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_error_budget_burn_and_latency_regression() {
+        let spec: VerificationSpec = serde_json::from_value(json!({
+            "service": "checkout",
+            "error_budget": {"objective": 99.9},
+            "checks": [{"name": "restarts", "expr": "sum(kube_pod_container_status_restarts_total{$selector})", "op": "<", "value": 1}],
+            "rollback": {"type": "kubernetes", "namespace": "shop", "deployment": "checkout"}
+        }))
+        .unwrap();
+        spec.validate(&VerifyConfig::default()).unwrap();
+        assert!(spec.queries("1m")[1]
+            .1
+            .contains(r#"http_requests_total{job="checkout", code=~"5.."}[1m]"#));
+
+        let healthy = SignalSample {
+            error_rate: Some(0.0005),
+            p99_ms: Some(220.0),
+            burn_rate: Some(0.5),
+            budget_remaining: Some(0.8),
+            custom: BTreeMap::from([("restarts".to_string(), 0.0)]),
+            ..SignalSample::default()
+        };
+        let checks = evaluate(&spec, &healthy, Some(200.0));
+        assert_eq!(checks.len(), 5);
+        assert!(checks.iter().all(|c| c.passed));
+
+        let degraded = SignalSample {
+            error_rate: Some(0.02),
+            p99_ms: Some(450.0),
+            burn_rate: Some(20.0),
+            ..healthy
+        };
+        let failed: Vec<String> = evaluate(&spec, &degraded, Some(200.0))
+            .into_iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(failed, vec!["p99_vs_baseline", "burn_rate"]);
+    }
+}
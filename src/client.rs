@@ -204,6 +204,14 @@ impl Mcp {
                     let ws_transport = crate::transport::WebSocketTransport::new(url.to_string())?;
                     Ok(Box::new(ws_transport))
                 }
+                "sse" => {
+                    let url = transport_config
+                        .url
+                        .as_ref()
+                        .ok_or_else(|| Error::config("SSE URL required"))?;
+                    let transport = crate::transport::SseTransport::new(url.to_string())?;
+                    Ok(Box::new(transport))
+                }
                 "stdio" => {
                     let command = transport_config
                         .command
@@ -8,16 +8,18 @@
 /// - Cost optimization with Compute Optimizer
 /// - Infrastructure as Code with CDK v2
 use crate::cloud::{
-    AwsConfig, CloudProvider, CloudResource, ComplexityLevel, CostOptimization, CostRecommendation,
-    PaymentOption, RecommendationPriority, ReservedInstanceRecommendation, ReservedInstanceTerm,
+    AwsConfig, CloudProvider, CloudResource, ComplexityLevel, ComplianceStatus,
+    ComplianceViolation, CostOptimization, CostRecommendation, PaymentOption,
+    RecommendationPriority, ReservedInstanceRecommendation, ReservedInstanceTerm,
     RightsizingRecommendation, SecurityAssessment, SecurityRecommendation, SecurityViolation,
-    ViolationSeverity,
+    Spend, SpendFilter, ViolationSeverity,
 };
 use crate::error::{Error, Result};
 use crate::lifecycle::LifecycleManager;
 use crate::security::SecurityModule;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::process::Command;
 
@@ -357,6 +359,9 @@ pub struct S3Bucket {
     pub encryption: Option<ServerSideEncryptionConfiguration>,
     /// Public access block
     pub public_access_block: Option<PublicAccessBlockConfiguration>,
+    /// Public through the bucket policy or ACL
+    #[serde(default)]
+    pub is_public: bool,
     /// Logging
     pub logging: Option<BucketLoggingStatus>,
     /// Notification
@@ -549,6 +554,590 @@ pub struct LifecycleExpiration {
     pub expired_object_delete_marker: Option<bool>,
 }
 
+/// EBS volume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EbsVolume {
+    /// Volume ID
+    pub volume_id: String,
+    /// Size in GiB
+    pub size_gib: i64,
+    /// Volume type (gp3, io2, ...)
+    pub volume_type: String,
+    /// State; `available` means unattached
+    pub state: String,
+    /// Encrypted at rest
+    pub encrypted: bool,
+    /// Instances the volume is attached to
+    pub attached_to: Vec<String>,
+    /// Availability zone
+    pub availability_zone: String,
+    /// Tags
+    pub tags: HashMap<String, String>,
+}
+
+/// EC2 security group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityGroup {
+    /// Group ID
+    pub group_id: String,
+    /// Group name
+    pub group_name: String,
+    /// VPC ID
+    pub vpc_id: Option<String>,
+    /// Inbound rules
+    pub ingress: Vec<IngressRule>,
+    /// Tags
+    pub tags: HashMap<String, String>,
+}
+
+/// Inbound security group rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngressRule {
+    /// `tcp`, `udp`, `icmp` or `-1` for all traffic
+    pub protocol: String,
+    /// First port, when the protocol has ports
+    pub from_port: Option<i64>,
+    /// Last port, when the protocol has ports
+    pub to_port: Option<i64>,
+    /// IPv4 and IPv6 source ranges
+    pub cidrs: Vec<String>,
+}
+
+impl IngressRule {
+    /// Whether the rule admits traffic from anywhere on the internet
+    pub fn is_open_to_world(&self) -> bool {
+        self.cidrs.iter().any(|c| c == "0.0.0.0/0" || c == "::/0")
+    }
+
+    /// Whether the rule covers `port`
+    pub fn covers(&self, port: i64) -> bool {
+        match (self.from_port, self.to_port) {
+            (Some(from), Some(to)) if from >= 0 => from <= port && port <= to,
+            // No range, or -1: every port
+            _ => true,
+        }
+    }
+}
+
+/// IAM user or role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IamIdentity {
+    /// `User` or `Role`
+    pub kind: String,
+    /// User or role name
+    pub name: String,
+    /// ARN
+    pub arn: String,
+    /// Creation date
+    pub created: String,
+    /// Last console sign-in for users, last use for roles
+    pub last_used: Option<String>,
+}
+
+/// Security rule outcome for one resource
+#[derive(Debug, Clone)]
+struct Finding {
+    resource_id: String,
+    rule_id: &'static str,
+    severity: ViolationSeverity,
+    description: String,
+    remediation: &'static str,
+}
+
+/// Ports that should never be reachable from the internet
+const SENSITIVE_PORTS: &[(i64, &str)] = &[
+    (22, "SSH"),
+    (3389, "RDP"),
+    (3306, "MySQL"),
+    (5432, "PostgreSQL"),
+    (1433, "SQL Server"),
+    (6379, "Redis"),
+    (9200, "Elasticsearch"),
+    (27017, "MongoDB"),
+];
+
+/// Score deducted per finding
+fn bucket_findings(bucket: &S3Bucket) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let finding = |rule_id, severity, description: &str, remediation| Finding {
+        resource_id: bucket.name.clone(),
+        rule_id,
+        severity,
+        description: description.to_string(),
+        remediation,
+    };
+    if bucket.is_public {
+        findings.push(finding(
+            "S3-003",
+            ViolationSeverity::Critical,
+            "S3 bucket is publicly readable through its policy or ACL",
+            "Remove public grants and enable Block Public Access on the bucket",
+        ));
+    }
+    let blocked = bucket.public_access_block.as_ref().is_some_and(|pab| {
+        pab.block_public_acls
+            && pab.ignore_public_acls
+            && pab.block_public_policy
+            && pab.restrict_public_buckets
+    });
+    if !blocked {
+        findings.push(finding(
+            "S3-002",
+            ViolationSeverity::Medium,
+            "S3 bucket does not block all public access",
+            "Turn on all four Block Public Access settings for the bucket",
+        ));
+    }
+    if bucket.encryption.is_none() {
+        findings.push(finding(
+            "S3-001",
+            ViolationSeverity::High,
+            "S3 bucket has no default encryption",
+            "Enable default encryption with SSE-S3 or SSE-KMS",
+        ));
+    }
+    findings
+}
+
+fn volume_findings(volume: &EbsVolume) -> Vec<Finding> {
+    if volume.encrypted {
+        return Vec::new();
+    }
+    vec![Finding {
+        resource_id: volume.volume_id.clone(),
+        rule_id: "EBS-001",
+        severity: ViolationSeverity::High,
+        description: "EBS volume is not encrypted".to_string(),
+        remediation: "Copy a snapshot with encryption, restore it to a new volume and turn on EBS encryption by default",
+    }]
+}
+
+fn security_group_findings(group: &SecurityGroup) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for rule in group.ingress.iter().filter(|r| r.is_open_to_world()) {
+        let (rule_id, severity, description) = if rule.protocol == "-1" {
+            (
+                "SG-001",
+                ViolationSeverity::Critical,
+                "Security group allows all traffic from the internet".to_string(),
+            )
+        } else if let Some((port, service)) = SENSITIVE_PORTS
+            .iter()
+            .find(|(port, _)| rule.protocol != "icmp" && rule.covers(*port))
+        {
+            (
+                "SG-002",
+                ViolationSeverity::Critical,
+                format!(
+                    "Security group exposes {} (port {}) to the internet",
+                    service, port
+                ),
+            )
+        } else if rule.from_port == rule.to_port && matches!(rule.from_port, Some(80) | Some(443)) {
+            // Public web traffic is what these are for
+            continue;
+        } else {
+            (
+                "SG-003",
+                ViolationSeverity::Medium,
+                format!(
+                    "Security group opens {} ports {}-{} to the internet",
+                    rule.protocol,
+                    rule.from_port.unwrap_or(0),
+                    rule.to_port.unwrap_or(65535)
+                ),
+            )
+        };
+        findings.push(Finding {
+            resource_id: group.group_id.clone(),
+            rule_id,
+            severity,
+            description,
+            remediation: "Restrict the rule's source to known address ranges or reach the service through a VPN or Session Manager",
+        });
+    }
+    findings
+}
+
+fn rds_findings(instance: &RdsInstance) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if instance.publicly_accessible {
+        findings.push(Finding {
+            resource_id: instance.db_instance_identifier.clone(),
+            rule_id: "RDS-002",
+            severity: ViolationSeverity::High,
+            description: "RDS instance is publicly accessible".to_string(),
+            remediation: "Modify the instance to turn off public accessibility",
+        });
+    }
+    if !instance.storage_encrypted {
+        findings.push(Finding {
+            resource_id: instance.db_instance_identifier.clone(),
+            rule_id: "RDS-001",
+            severity: ViolationSeverity::High,
+            description: "RDS storage is not encrypted".to_string(),
+            remediation: "Restore an encrypted copy of a snapshot and switch over to it",
+        });
+    }
+    findings
+}
+
+fn ec2_findings(instance: &Ec2Instance) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if instance.public_ip.is_some() && instance.state == "running" {
+        findings.push(Finding {
+            resource_id: instance.instance_id.clone(),
+            rule_id: "EC2-001",
+            severity: ViolationSeverity::Medium,
+            description: "EC2 instance has public IP address".to_string(),
+            remediation:
+                "Move the instance behind a load balancer or NAT and release the public IP",
+        });
+    }
+    if instance.security_groups.is_empty() {
+        findings.push(Finding {
+            resource_id: instance.instance_id.clone(),
+            rule_id: "EC2-002",
+            severity: ViolationSeverity::High,
+            description: "EC2 instance has no security groups".to_string(),
+            remediation: "Attach a security group that only admits the traffic the instance needs",
+        });
+    }
+    findings
+}
+
+/// Compliance status of one resource from its findings
+fn compliance_status(findings: &[Finding]) -> ComplianceStatus {
+    ComplianceStatus {
+        score: (100.0 - findings.iter().map(|f| f.severity.penalty()).sum::<f64>()).max(0.0),
+        violations: findings
+            .iter()
+            .map(|f| ComplianceViolation {
+                rule_id: f.rule_id.to_string(),
+                severity: f.severity.clone(),
+                description: f.description.clone(),
+                remediation: f.remediation.to_string(),
+            })
+            .collect(),
+        last_assessment: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// Tags from an AWS `[{Key, Value}]` list
+fn tag_map(tags: &Value) -> HashMap<String, String> {
+    tags.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| {
+            Some((
+                tag["Key"].as_str()?.to_string(),
+                tag["Value"].as_str().unwrap_or_default().to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn str_field(value: &Value, key: &str) -> String {
+    value[key].as_str().unwrap_or_default().to_string()
+}
+
+/// Cost Explorer amounts are decimal strings
+fn amount(value: &Value) -> f64 {
+    value
+        .as_str()
+        .and_then(|s| s.parse().ok())
+        .or_else(|| value.as_f64())
+        .unwrap_or(0.0)
+}
+
+fn ebs_volume(volume: &Value) -> EbsVolume {
+    EbsVolume {
+        volume_id: str_field(volume, "VolumeId"),
+        size_gib: volume["Size"].as_i64().unwrap_or(0),
+        volume_type: str_field(volume, "VolumeType"),
+        state: str_field(volume, "State"),
+        encrypted: volume["Encrypted"].as_bool().unwrap_or(false),
+        attached_to: volume["Attachments"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|a| a["InstanceId"].as_str().map(str::to_string))
+            .collect(),
+        availability_zone: str_field(volume, "AvailabilityZone"),
+        tags: tag_map(&volume["Tags"]),
+    }
+}
+
+fn security_group(group: &Value) -> SecurityGroup {
+    SecurityGroup {
+        group_id: str_field(group, "GroupId"),
+        group_name: str_field(group, "GroupName"),
+        vpc_id: group["VpcId"].as_str().map(str::to_string),
+        ingress: group["IpPermissions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|permission| IngressRule {
+                protocol: str_field(permission, "IpProtocol"),
+                from_port: permission["FromPort"].as_i64(),
+                to_port: permission["ToPort"].as_i64(),
+                cidrs: permission["IpRanges"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|r| r["CidrIp"].as_str())
+                    .chain(
+                        permission["Ipv6Ranges"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|r| r["CidrIpv6"].as_str()),
+                    )
+                    .map(str::to_string)
+                    .collect(),
+            })
+            .collect(),
+        tags: tag_map(&group["Tags"]),
+    }
+}
+
+fn rds_instance(db: &Value) -> RdsInstance {
+    RdsInstance {
+        db_instance_identifier: str_field(db, "DBInstanceIdentifier"),
+        db_instance_class: str_field(db, "DBInstanceClass"),
+        engine: str_field(db, "Engine"),
+        engine_version: str_field(db, "EngineVersion"),
+        db_instance_status: str_field(db, "DBInstanceStatus"),
+        endpoint: db["Endpoint"].as_object().map(|_| RdsEndpoint {
+            address: str_field(&db["Endpoint"], "Address"),
+            port: db["Endpoint"]["Port"].as_i64().unwrap_or(0) as i32,
+            hosted_zone_id: str_field(&db["Endpoint"], "HostedZoneId"),
+        }),
+        allocated_storage: db["AllocatedStorage"].as_i64().unwrap_or(0) as i32,
+        storage_type: str_field(db, "StorageType"),
+        storage_encrypted: db["StorageEncrypted"].as_bool().unwrap_or(false),
+        multi_az: db["MultiAZ"].as_bool().unwrap_or(false),
+        publicly_accessible: db["PubliclyAccessible"].as_bool().unwrap_or(false),
+        vpc_security_groups: db["VpcSecurityGroups"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|g| VpcSecurityGroup {
+                vpc_security_group_id: str_field(g, "VpcSecurityGroupId"),
+                status: str_field(g, "Status"),
+            })
+            .collect(),
+        db_subnet_group: None,
+        performance_insights_enabled: db["PerformanceInsightsEnabled"].as_bool().unwrap_or(false),
+        backup_retention_period: db["BackupRetentionPeriod"].as_i64().unwrap_or(0) as i32,
+        preferred_backup_window: str_field(db, "PreferredBackupWindow"),
+        preferred_maintenance_window: str_field(db, "PreferredMaintenanceWindow"),
+        tags: tag_map(&db["TagList"]),
+    }
+}
+
+/// Default bucket encryption from `get-bucket-encryption`
+fn bucket_encryption(response: &Value) -> Option<ServerSideEncryptionConfiguration> {
+    let rules: Vec<ServerSideEncryptionRule> = response["ServerSideEncryptionConfiguration"]
+        ["Rules"]
+        .as_array()?
+        .iter()
+        .map(|rule| {
+            let default = &rule["ApplyServerSideEncryptionByDefault"];
+            ServerSideEncryptionRule {
+                apply_server_side_encryption_by_default: ServerSideEncryptionByDefault {
+                    sse_algorithm: str_field(default, "SSEAlgorithm"),
+                    kms_master_key_id: default["KMSMasterKeyID"].as_str().map(str::to_string),
+                },
+                bucket_key_enabled: rule["BucketKeyEnabled"].as_bool(),
+            }
+        })
+        .collect();
+    (!rules.is_empty()).then_some(ServerSideEncryptionConfiguration { rules })
+}
+
+/// Block Public Access settings from `get-public-access-block`
+fn public_access_block(response: &Value) -> Option<PublicAccessBlockConfiguration> {
+    let config = response["PublicAccessBlockConfiguration"].as_object()?;
+    let flag = |key: &str| config.get(key).and_then(Value::as_bool).unwrap_or(false);
+    Some(PublicAccessBlockConfiguration {
+        block_public_acls: flag("BlockPublicAcls"),
+        ignore_public_acls: flag("IgnorePublicAcls"),
+        block_public_policy: flag("BlockPublicPolicy"),
+        restrict_public_buckets: flag("RestrictPublicBuckets"),
+    })
+}
+
+/// Whether a `get-bucket-acl` response grants access to everyone
+fn acl_is_public(response: &Value) -> bool {
+    response["Grants"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|grant| grant["Grantee"]["URI"].as_str())
+        .any(|uri| uri.ends_with("/global/AllUsers") || uri.ends_with("/global/AuthenticatedUsers"))
+}
+
+/// Right-sizing and idle instances from `get-rightsizing-recommendation`
+fn rightsizing_recommendations(
+    response: &Value,
+) -> (Vec<RightsizingRecommendation>, Vec<CostRecommendation>) {
+    let mut rightsizing = Vec::new();
+    let mut terminations = Vec::new();
+    for recommendation in response["RightsizingRecommendations"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        let current = &recommendation["CurrentInstance"];
+        let resource_id = str_field(current, "ResourceId");
+        let current_type = str_field(
+            &current["ResourceDetails"]["EC2ResourceDetails"],
+            "InstanceType",
+        );
+        let utilization = &current["ResourceUtilization"]["EC2ResourceUtilization"];
+        match recommendation["RightsizingType"].as_str() {
+            Some("Modify") => {
+                let Some(target) = recommendation["ModifyRecommendationDetail"]["TargetInstances"]
+                    .as_array()
+                    .and_then(|targets| targets.first())
+                else {
+                    continue;
+                };
+                rightsizing.push(RightsizingRecommendation {
+                    resource_id,
+                    current_type,
+                    recommended_type: str_field(
+                        &target["ResourceDetails"]["EC2ResourceDetails"],
+                        "InstanceType",
+                    ),
+                    monthly_savings: amount(&target["EstimatedMonthlySavings"]),
+                    cpu_utilization: amount(&utilization["MaxCpuUtilizationPercentage"]),
+                    memory_utilization: amount(&utilization["MaxMemoryUtilizationPercentage"]),
+                });
+            }
+            Some("Terminate") => {
+                let savings = amount(
+                    &recommendation["TerminateRecommendationDetail"]["EstimatedMonthlySavings"],
+                );
+                terminations.push(CostRecommendation {
+                    description: format!(
+                        "{} ({}) peaked at {:.0}% CPU; Cost Explorer suggests terminating it",
+                        resource_id,
+                        current_type,
+                        amount(&utilization["MaxCpuUtilizationPercentage"])
+                    ),
+                    resource_id,
+                    recommendation_type: "Terminate idle instance".to_string(),
+                    potential_savings: savings,
+                    complexity: ComplexityLevel::Low,
+                });
+            }
+            _ => {}
+        }
+    }
+    (rightsizing, terminations)
+}
+
+/// Reserved instance purchases from `get-reservation-purchase-recommendation`
+fn reservation_recommendations(response: &Value) -> Vec<ReservedInstanceRecommendation> {
+    let mut reservations = Vec::new();
+    for recommendation in response["Recommendations"].as_array().into_iter().flatten() {
+        let term = match recommendation["TermInYears"].as_str() {
+            Some("THREE_YEARS") => ReservedInstanceTerm::ThreeYear,
+            _ => ReservedInstanceTerm::OneYear,
+        };
+        let payment_option = match recommendation["PaymentOption"].as_str() {
+            Some("ALL_UPFRONT") => PaymentOption::AllUpfront,
+            Some("NO_UPFRONT") => PaymentOption::NoUpfront,
+            _ => PaymentOption::PartialUpfront,
+        };
+        for detail in recommendation["RecommendationDetails"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            reservations.push(ReservedInstanceRecommendation {
+                instance_type: str_field(
+                    &detail["InstanceDetails"]["EC2InstanceDetails"],
+                    "InstanceType",
+                ),
+                quantity: amount(&detail["RecommendedNumberOfInstancesToPurchase"]) as u32,
+                term: term.clone(),
+                payment_option: payment_option.clone(),
+                annual_savings: amount(&detail["EstimatedMonthlySavingsAmount"]) * 12.0,
+            });
+        }
+    }
+    reservations
+}
+
+/// Monthly list price per GiB for an EBS volume type, in USD
+fn ebs_price_per_gib(volume_type: &str) -> f64 {
+    match volume_type {
+        "gp3" => 0.08,
+        "io1" | "io2" => 0.125,
+        "st1" => 0.045,
+        "sc1" => 0.015,
+        "standard" => 0.05,
+        _ => 0.10,
+    }
+}
+
+/// Security assessment from rule findings, with one recommendation per rule
+fn assessment(findings: Vec<Finding>) -> SecurityAssessment {
+    let score = (100.0 - findings.iter().map(|f| f.severity.penalty()).sum::<f64>()).max(0.0);
+    let mut by_rule: BTreeMap<&str, Vec<&Finding>> = BTreeMap::new();
+    for finding in &findings {
+        by_rule.entry(finding.rule_id).or_default().push(finding);
+    }
+    let recommendations = by_rule
+        .values()
+        .map(|group| {
+            let first = group[0];
+            let resources: Vec<&str> = group.iter().map(|f| f.resource_id.as_str()).collect();
+            SecurityRecommendation {
+                id: first.rule_id.to_string(),
+                title: first.remediation.to_string(),
+                description: format!("{} resource(s): {}", resources.len(), resources.join(", ")),
+                priority: match first.severity {
+                    ViolationSeverity::Critical => RecommendationPriority::Critical,
+                    ViolationSeverity::High => RecommendationPriority::High,
+                    ViolationSeverity::Medium => RecommendationPriority::Medium,
+                    _ => RecommendationPriority::Low,
+                },
+                impact: first.description.clone(),
+                steps: vec![first.remediation.to_string()],
+            }
+        })
+        .collect();
+    SecurityAssessment {
+        overall_score: score,
+        provider_scores: HashMap::from([(CloudProvider::AWS, score)]),
+        violations: findings
+            .into_iter()
+            .map(|f| SecurityViolation {
+                resource_id: f.resource_id,
+                rule_id: f.rule_id.to_string(),
+                severity: f.severity,
+                description: f.description,
+                provider: CloudProvider::AWS,
+            })
+            .collect(),
+        recommendations,
+    }
+}
+
+/// Resource cost from a monthly estimate
+fn monthly_cost(monthly: f64) -> crate::cloud::ResourceCost {
+    crate::cloud::ResourceCost {
+        daily_cost: monthly / 30.0,
+        monthly_cost: monthly,
+        currency: "USD".to_string(),
+        trend: crate::cloud::CostTrend::Stable,
+    }
+}
+
 /// AWS client with comprehensive 2024-2025 feature support
 pub struct AwsClient {
     /// AWS configuration
@@ -603,6 +1192,17 @@ impl AwsClient {
             cmd.args(["--profile", profile]);
         }
 
+        // Static keys from the config win over the CLI's own credential chain
+        if let (Some(key_id), Some(secret)) =
+            (&self.config.access_key_id, &self.config.secret_access_key)
+        {
+            cmd.env("AWS_ACCESS_KEY_ID", key_id)
+                .env("AWS_SECRET_ACCESS_KEY", secret);
+            if let Some(token) = &self.config.session_token {
+                cmd.env("AWS_SESSION_TOKEN", token);
+            }
+        }
+
         // Add arguments
         cmd.args(args);
 
@@ -620,6 +1220,29 @@ impl AwsClient {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
+    /// Resource in this region scored by its findings
+    fn resource(
+        &self,
+        id: &str,
+        name: &str,
+        resource_type: &str,
+        tags: HashMap<String, String>,
+        findings: &[Finding],
+    ) -> CloudResource {
+        let compliance_status = compliance_status(findings);
+        CloudResource {
+            id: id.to_string(),
+            name: name.to_string(),
+            resource_type: resource_type.to_string(),
+            provider: CloudProvider::AWS,
+            region: self.current_region.clone(),
+            tags,
+            cost: None,
+            security_score: Some(compliance_status.score),
+            compliance_status,
+        }
+    }
+
     /// List all cloud resources across services
     pub async fn list_resources(&self) -> Result<Vec<CloudResource>> {
         let mut resources = Vec::new();
@@ -647,11 +1270,7 @@ impl AwsClient {
                         trend: crate::cloud::CostTrend::Stable,
                     }),
                     security_score: None,
-                    compliance_status: crate::cloud::ComplianceStatus {
-                        score: 75.0,
-                        violations: Vec::new(),
-                        last_assessment: chrono::Utc::now().to_rfc3339(),
-                    },
+                    compliance_status: compliance_status(&ec2_findings(&instance)),
                 });
             }
         }
@@ -688,16 +1307,7 @@ impl AwsClient {
                 let mut tags = bucket.tags.clone();
                 tags.insert("ResourceType".to_string(), "S3Bucket".to_string());
 
-                let security_score = if bucket.encryption.is_some()
-                    && bucket
-                        .public_access_block
-                        .as_ref()
-                        .is_some_and(|pab| pab.block_public_acls)
-                {
-                    85.0
-                } else {
-                    60.0
-                };
+                let compliance_status = compliance_status(&bucket_findings(&bucket));
 
                 resources.push(CloudResource {
                     id: bucket.name.clone(),
@@ -707,16 +1317,84 @@ impl AwsClient {
                     region: bucket.region.clone(),
                     tags,
                     cost: None, // Would need cost explorer
-                    security_score: Some(security_score),
-                    compliance_status: crate::cloud::ComplianceStatus {
-                        score: security_score,
-                        violations: Vec::new(),
-                        last_assessment: chrono::Utc::now().to_rfc3339(),
-                    },
+                    security_score: Some(compliance_status.score),
+                    compliance_status,
                 });
             }
         }
 
+        // EBS volumes
+        if let Ok(volumes) = self.list_ebs_volumes().await {
+            for volume in volumes {
+                let mut resource = self.resource(
+                    &volume.volume_id,
+                    volume.tags.get("Name").unwrap_or(&volume.volume_id),
+                    "EC2::Volume",
+                    volume.tags.clone(),
+                    &volume_findings(&volume),
+                );
+                resource
+                    .tags
+                    .insert("ResourceType".to_string(), "EBSVolume".to_string());
+                resource.cost = Some(monthly_cost(
+                    volume.size_gib as f64 * ebs_price_per_gib(&volume.volume_type),
+                ));
+                resources.push(resource);
+            }
+        }
+
+        // Security groups
+        if let Ok(groups) = self.list_security_groups().await {
+            for group in groups {
+                let mut resource = self.resource(
+                    &group.group_id,
+                    &group.group_name,
+                    "EC2::SecurityGroup",
+                    group.tags.clone(),
+                    &security_group_findings(&group),
+                );
+                resource
+                    .tags
+                    .insert("ResourceType".to_string(), "SecurityGroup".to_string());
+                resources.push(resource);
+            }
+        }
+
+        // RDS instances
+        if let Ok(databases) = self.list_rds_instances().await {
+            for database in databases {
+                let mut resource = self.resource(
+                    &database.db_instance_identifier,
+                    &database.db_instance_identifier,
+                    "RDS::DBInstance",
+                    database.tags.clone(),
+                    &rds_findings(&database),
+                );
+                resource
+                    .tags
+                    .insert("ResourceType".to_string(), "RDSInstance".to_string());
+                resource
+                    .tags
+                    .insert("Engine".to_string(), database.engine.clone());
+                resources.push(resource);
+            }
+        }
+
+        // IAM users and roles are global
+        if let Ok(identities) = self.list_iam_identities().await {
+            for identity in identities {
+                let mut resource = self.resource(
+                    &identity.arn,
+                    &identity.name,
+                    &format!("IAM::{}", identity.kind),
+                    HashMap::from([("ResourceType".to_string(), format!("IAM{}", identity.kind))]),
+                    &[],
+                );
+                resource.region = "global".to_string();
+                resources.push(resource);
+            }
+        }
+
         Ok(resources)
     }
 
@@ -724,7 +1402,7 @@ impl AwsClient {
     pub async fn list_ec2_instances(&self) -> Result<Vec<Ec2Instance>> {
         let output = self.execute_aws_command(&[
             "ec2", "describe-instances",
-            "--query", "Reservations[*].Instances[*].[InstanceId,InstanceType,State.Name,VpcId,SubnetId,SecurityGroups[0].GroupId,PublicIpAddress,PrivateIpAddress,LaunchTime,Platform,Architecture,Tags]",
+            "--query", "Reservations[*].Instances[*].[InstanceId,InstanceType,State.Name,VpcId,SubnetId,SecurityGroups[].GroupId,PublicIpAddress,PrivateIpAddress,LaunchTime,Platform,Architecture,Tags]",
             "--output", "json"
         ]).await?;
 
@@ -765,10 +1443,12 @@ impl AwsClient {
                                     state: instance_array[2].as_str().unwrap_or("").to_string(),
                                     vpc_id: instance_array[3].as_str().unwrap_or("").to_string(),
                                     subnet_id: instance_array[4].as_str().unwrap_or("").to_string(),
-                                    security_groups: vec![instance_array[5]
-                                        .as_str()
-                                        .unwrap_or("")
-                                        .to_string()],
+                                    security_groups: instance_array[5]
+                                        .as_array()
+                                        .into_iter()
+                                        .flatten()
+                                        .filter_map(|g| g.as_str().map(str::to_string))
+                                        .collect(),
                                     public_ip: instance_array[6].as_str().map(|s| s.to_string()),
                                     private_ip: instance_array[7]
                                         .as_str()
@@ -973,45 +1653,45 @@ impl AwsClient {
                 };
 
                 // Get bucket encryption
-                let encryption_output = self
-                    .execute_aws_command(&[
-                        "s3api",
-                        "get-bucket-encryption",
-                        "--bucket",
-                        &bucket_name,
-                        "--output",
-                        "json",
-                    ])
+                let encryption = self
+                    .execute_json(
+                        &["s3api", "get-bucket-encryption", "--bucket", &bucket_name],
+                        "bucket encryption",
+                    )
                     .await
-                    .ok();
-
-                let encryption = if let Some(enc_str) = encryption_output {
-                    serde_json::from_str::<ServerSideEncryptionConfiguration>(&enc_str).ok()
-                } else {
-                    None
-                };
+                    .ok()
+                    .and_then(|v| bucket_encryption(&v));
 
                 // Get public access block
-                let pab_output = self
-                    .execute_aws_command(&[
-                        "s3api",
-                        "get-public-access-block",
-                        "--bucket",
-                        &bucket_name,
-                        "--output",
-                        "json",
-                    ])
+                let public_access_block = self
+                    .execute_json(
+                        &["s3api", "get-public-access-block", "--bucket", &bucket_name],
+                        "public access block",
+                    )
                     .await
-                    .ok();
-
-                let public_access_block = if let Some(pab_str) = pab_output {
-                    serde_json::from_str::<serde_json::Value>(&pab_str)
-                        .ok()
-                        .and_then(|v| v.get("PublicAccessBlockConfiguration").cloned())
-                        .and_then(|pab| serde_json::from_value(pab).ok())
-                } else {
-                    None
-                };
+                    .ok()
+                    .and_then(|v| public_access_block(&v));
+
+                // Public through the bucket policy or an ACL grant to everyone
+                let policy_public = self
+                    .execute_json(
+                        &[
+                            "s3api",
+                            "get-bucket-policy-status",
+                            "--bucket",
+                            &bucket_name,
+                        ],
+                        "bucket policy status",
+                    )
+                    .await
+                    .is_ok_and(|v| v["PolicyStatus"]["IsPublic"] == true);
+                let acl_public = self
+                    .execute_json(
+                        &["s3api", "get-bucket-acl", "--bucket", &bucket_name],
+                        "bucket ACL",
+                    )
+                    .await
+                    .is_ok_and(|v| acl_is_public(&v));
 
                 // Get bucket tags
                 let tags_output = self
@@ -1060,6 +1740,7 @@ impl AwsClient {
                     versioning: None,
                     encryption,
                     public_access_block,
+                    is_public: policy_public || acl_public,
                     logging: None,
                     notification: None,
                     lifecycle: None,
@@ -1073,182 +1754,274 @@ impl AwsClient {
         Ok(buckets)
     }
 
-    /// Perform comprehensive security assessment
-    pub async fn security_assessment(&self) -> Result<SecurityAssessment> {
-        let mut violations = Vec::new();
-        let mut recommendations = Vec::new();
-        let mut total_score: f64 = 100.0;
+    /// Run an AWS CLI command that prints JSON
+    async fn execute_json(&self, args: &[&str], what: &str) -> Result<Value> {
+        let mut args = args.to_vec();
+        args.extend(["--output", "json"]);
+        let output = self.execute_aws_command(&args).await?;
+        serde_json::from_str(&output)
+            .map_err(|e| Error::parsing(format!("Failed to parse {}: {}", what, e)))
+    }
 
-        // Check EC2 security
-        if let Ok(instances) = self.list_ec2_instances().await {
-            for instance in instances {
-                // Check for public instances
-                if instance.public_ip.is_some() && instance.state == "running" {
-                    violations.push(SecurityViolation {
-                        resource_id: instance.instance_id.clone(),
-                        rule_id: "EC2-001".to_string(),
-                        severity: ViolationSeverity::Medium,
-                        description: "EC2 instance has public IP address".to_string(),
-                        provider: CloudProvider::AWS,
-                    });
-                    total_score -= 5.0;
-                }
+    /// List EBS volumes
+    pub async fn list_ebs_volumes(&self) -> Result<Vec<EbsVolume>> {
+        let data = self
+            .execute_json(&["ec2", "describe-volumes"], "EBS volumes")
+            .await?;
+        Ok(data["Volumes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(ebs_volume)
+            .collect())
+    }
 
-                // Check security group configuration
-                if instance.security_groups.is_empty() {
-                    violations.push(SecurityViolation {
-                        resource_id: instance.instance_id.clone(),
-                        rule_id: "EC2-002".to_string(),
-                        severity: ViolationSeverity::High,
-                        description: "EC2 instance has no security groups".to_string(),
-                        provider: CloudProvider::AWS,
-                    });
-                    total_score -= 10.0;
-                }
-            }
-        }
+    /// List security groups with their inbound rules
+    pub async fn list_security_groups(&self) -> Result<Vec<SecurityGroup>> {
+        let data = self
+            .execute_json(&["ec2", "describe-security-groups"], "security groups")
+            .await?;
+        Ok(data["SecurityGroups"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(security_group)
+            .collect())
+    }
 
-        // Check S3 security
-        if let Ok(buckets) = self.list_s3_buckets().await {
-            for bucket in buckets {
-                // Check encryption
-                if bucket.encryption.is_none() {
-                    violations.push(SecurityViolation {
-                        resource_id: bucket.name.clone(),
-                        rule_id: "S3-001".to_string(),
-                        severity: ViolationSeverity::High,
-                        description: "S3 bucket is not encrypted".to_string(),
-                        provider: CloudProvider::AWS,
-                    });
-                    total_score -= 15.0;
-
-                    recommendations.push(SecurityRecommendation {
-                        id: format!("S3-ENC-{}", bucket.name),
-                        title: "Enable S3 bucket encryption".to_string(),
-                        description: format!(
-                            "Enable server-side encryption for S3 bucket {}",
-                            bucket.name
-                        ),
-                        priority: RecommendationPriority::High,
-                        impact: "Protects data at rest from unauthorized access".to_string(),
-                        steps: vec![
-                            "Navigate to S3 console".to_string(),
-                            format!("Select bucket {}", bucket.name),
-                            "Go to Properties tab".to_string(),
-                            "Enable Default encryption".to_string(),
-                            "Choose AES-256 or KMS encryption".to_string(),
-                        ],
-                    });
-                }
+    /// List RDS database instances
+    pub async fn list_rds_instances(&self) -> Result<Vec<RdsInstance>> {
+        let data = self
+            .execute_json(&["rds", "describe-db-instances"], "RDS instances")
+            .await?;
+        Ok(data["DBInstances"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(rds_instance)
+            .collect())
+    }
 
-                // Check public access
-                if bucket.public_access_block.is_none() {
-                    violations.push(SecurityViolation {
-                        resource_id: bucket.name.clone(),
-                        rule_id: "S3-002".to_string(),
-                        severity: ViolationSeverity::Critical,
-                        description: "S3 bucket public access block not configured".to_string(),
-                        provider: CloudProvider::AWS,
-                    });
-                    total_score -= 20.0;
-                }
-            }
+    /// List IAM users and roles
+    pub async fn list_iam_identities(&self) -> Result<Vec<IamIdentity>> {
+        let users = self
+            .execute_json(&["iam", "list-users"], "IAM users")
+            .await?;
+        let roles = self
+            .execute_json(&["iam", "list-roles"], "IAM roles")
+            .await?;
+        let users = users["Users"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|user| IamIdentity {
+                kind: "User".to_string(),
+                name: str_field(user, "UserName"),
+                arn: str_field(user, "Arn"),
+                created: str_field(user, "CreateDate"),
+                last_used: user["PasswordLastUsed"].as_str().map(str::to_string),
+            });
+        let roles = roles["Roles"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|role| IamIdentity {
+                kind: "Role".to_string(),
+                name: str_field(role, "RoleName"),
+                arn: str_field(role, "Arn"),
+                created: str_field(role, "CreateDate"),
+                last_used: role["RoleLastUsed"]["LastUsedDate"]
+                    .as_str()
+                    .map(str::to_string),
+            });
+        Ok(users.chain(roles).collect())
+    }
+
+    /// Findings of every security rule, by resource kind
+    async fn findings(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        if let Ok(instances) = self.list_ec2_instances().await {
+            findings.extend(instances.iter().flat_map(ec2_findings));
+        }
+        if let Ok(buckets) = self.list_s3_buckets().await {
+            findings.extend(buckets.iter().flat_map(bucket_findings));
+        }
+        if let Ok(volumes) = self.list_ebs_volumes().await {
+            findings.extend(volumes.iter().flat_map(volume_findings));
         }
+        if let Ok(groups) = self.list_security_groups().await {
+            findings.extend(groups.iter().flat_map(security_group_findings));
+        }
+        if let Ok(databases) = self.list_rds_instances().await {
+            findings.extend(databases.iter().flat_map(rds_findings));
+        }
+        findings
+    }
 
-        Ok(SecurityAssessment {
-            overall_score: total_score.max(0.0),
-            provider_scores: HashMap::from([(CloudProvider::AWS, total_score.max(0.0))]),
-            violations,
-            recommendations,
-        })
+    /// Assess S3, EBS, security groups, RDS and EC2 against the security rules
+    pub async fn security_assessment(&self) -> Result<SecurityAssessment> {
+        Ok(assessment(self.findings().await))
     }
 
-    /// Generate cost optimization recommendations
+    /// Cost savings from Cost Explorer recommendations and unattached volumes
     pub async fn cost_optimization(&self) -> Result<CostOptimization> {
         let mut recommendations = Vec::new();
         let mut rightsizing = Vec::new();
         let mut reserved_instances = Vec::new();
-        let mut total_savings = 0.0;
 
-        // Analyze EC2 instances for right-sizing
-        if let Ok(instances) = self.list_ec2_instances().await {
-            for instance in instances {
-                if instance.state == "running" {
-                    // Simulate cost optimization analysis
-                    let current_cost = match instance.instance_type.as_str() {
-                        "t3.micro" => 0.0104,
-                        "t3.small" => 0.0208,
-                        "t3.medium" => 0.0416,
-                        "t3.large" => 0.0832,
-                        "m5.large" => 0.096,
-                        "m5.xlarge" => 0.192,
-                        _ => 0.1,
-                    };
-
-                    // Suggest smaller instance if utilization is low (simulated)
-                    if instance.instance_type.contains("large") {
-                        let recommended_type = instance.instance_type.replace("large", "medium");
-                        let new_cost = current_cost * 0.5;
-                        let monthly_savings = (current_cost - new_cost) * 24.0 * 30.0;
-
-                        rightsizing.push(RightsizingRecommendation {
-                            resource_id: instance.instance_id.clone(),
-                            current_type: instance.instance_type.clone(),
-                            recommended_type,
-                            monthly_savings,
-                            cpu_utilization: 25.0,    // Simulated
-                            memory_utilization: 30.0, // Simulated
-                        });
-
-                        total_savings += monthly_savings;
-                    }
-
-                    // Reserved instance recommendations for long-running instances
-                    if instance.state == "running" {
-                        let annual_cost = current_cost * 24.0 * 365.0;
-                        let ri_savings = annual_cost * 0.3; // 30% savings estimate
+        match self
+            .execute_json(
+                &[
+                    "ce",
+                    "get-rightsizing-recommendation",
+                    "--service",
+                    "AmazonEC2",
+                    "--configuration",
+                    "RecommendationTarget=SAME_INSTANCE_FAMILY,BenefitsConsidered=true",
+                ],
+                "rightsizing recommendations",
+            )
+            .await
+        {
+            Ok(data) => {
+                let (modify, terminate) = rightsizing_recommendations(&data);
+                rightsizing = modify;
+                recommendations.extend(terminate);
+            }
+            Err(e) => tracing::warn!("Cost Explorer rightsizing unavailable: {}", e),
+        }
 
-                        reserved_instances.push(ReservedInstanceRecommendation {
-                            instance_type: instance.instance_type.clone(),
-                            quantity: 1,
-                            term: ReservedInstanceTerm::OneYear,
-                            payment_option: PaymentOption::PartialUpfront,
-                            annual_savings: ri_savings,
-                        });
+        match self
+            .execute_json(
+                &[
+                    "ce",
+                    "get-reservation-purchase-recommendation",
+                    "--service",
+                    "Amazon Elastic Compute Cloud - Compute",
+                    "--term-in-years",
+                    "ONE_YEAR",
+                    "--payment-option",
+                    "PARTIAL_UPFRONT",
+                    "--lookback-period-in-days",
+                    "SIXTY_DAYS",
+                ],
+                "reservation recommendations",
+            )
+            .await
+        {
+            Ok(data) => reserved_instances = reservation_recommendations(&data),
+            Err(e) => tracing::warn!("Cost Explorer reservations unavailable: {}", e),
+        }
 
-                        total_savings += ri_savings;
-                    }
-                }
+        if let Ok(volumes) = self.list_ebs_volumes().await {
+            for volume in volumes.iter().filter(|v| v.state == "available") {
+                let monthly = volume.size_gib as f64 * ebs_price_per_gib(&volume.volume_type);
+                recommendations.push(CostRecommendation {
+                    resource_id: volume.volume_id.clone(),
+                    recommendation_type: "Delete unattached volume".to_string(),
+                    potential_savings: monthly,
+                    description: format!(
+                        "{} ({} GiB {}) is not attached to any instance; snapshot it and delete it",
+                        volume.volume_id, volume.size_gib, volume.volume_type
+                    ),
+                    complexity: ComplexityLevel::Low,
+                });
             }
         }
 
-        // General cost recommendations
-        recommendations.push(CostRecommendation {
-            resource_id: "general".to_string(),
-            recommendation_type: "Enable detailed billing".to_string(),
-            potential_savings: 0.0,
-            description: "Enable detailed billing to track costs by service and resource"
-                .to_string(),
-            complexity: ComplexityLevel::Low,
-        });
-
-        recommendations.push(CostRecommendation {
-            resource_id: "general".to_string(),
-            recommendation_type: "Set up budget alerts".to_string(),
-            potential_savings: 0.0,
-            description: "Create budget alerts to monitor spending and prevent cost overruns"
-                .to_string(),
-            complexity: ComplexityLevel::Low,
-        });
+        // Savings are monthly throughout, reservations included
+        let total_potential_savings = recommendations
+            .iter()
+            .map(|r| r.potential_savings)
+            .chain(rightsizing.iter().map(|r| r.monthly_savings))
+            .chain(reserved_instances.iter().map(|r| r.annual_savings / 12.0))
+            .sum();
 
         Ok(CostOptimization {
-            total_potential_savings: total_savings,
+            total_potential_savings,
             recommendations,
             rightsizing_opportunities: rightsizing,
             reserved_instance_recommendations: reserved_instances,
         })
     }
 
+    /// IAM users, groups, roles and policies with their policy documents
+    pub async fn iam_authorization_details(&self) -> Result<serde_json::Value> {
+        let output = self
+            .execute_aws_command(&[
+                "iam",
+                "get-account-authorization-details",
+                "--output",
+                "json",
+            ])
+            .await?;
+        serde_json::from_str(&output).map_err(|e| {
+            Error::parsing(format!("Failed to parse IAM authorization details: {}", e))
+        })
+    }
+
+    /// Actual spend between `start` and `end` (exclusive) from Cost Explorer
+    pub async fn spend(
+        &self,
+        start: chrono::NaiveDate,
+        end: chrono::NaiveDate,
+        filter: &SpendFilter,
+    ) -> Result<Spend> {
+        let mut conditions = Vec::new();
+        if let Some(account) = &filter.account {
+            conditions.push(serde_json::json!({
+                "Dimensions": { "Key": "LINKED_ACCOUNT", "Values": [account] }
+            }));
+        }
+        if let (Some(key), Some(value)) = (&filter.tag_key, &filter.tag_value) {
+            conditions.push(serde_json::json!({
+                "Tags": { "Key": key, "Values": [value] }
+            }));
+        }
+        let filter_json = match conditions.len() {
+            0 => None,
+            1 => Some(conditions.remove(0).to_string()),
+            _ => Some(serde_json::json!({ "And": conditions }).to_string()),
+        };
+
+        let period = format!("Start={},End={}", start, end);
+        let mut args = vec![
+            "ce",
+            "get-cost-and-usage",
+            "--time-period",
+            &period,
+            "--granularity",
+            "MONTHLY",
+            "--metrics",
+            "UnblendedCost",
+            "--output",
+            "json",
+        ];
+        if let Some(filter_json) = &filter_json {
+            args.push("--filter");
+            args.push(filter_json);
+        }
+        let output = self.execute_aws_command(&args).await?;
+        let data: serde_json::Value = serde_json::from_str(&output)
+            .map_err(|e| Error::parsing(format!("Failed to parse cost and usage: {}", e)))?;
+
+        let mut spend = Spend {
+            amount: 0.0,
+            currency: "USD".to_string(),
+        };
+        for period in data["ResultsByTime"].as_array().into_iter().flatten() {
+            let cost = &period["Total"]["UnblendedCost"];
+            spend.amount += cost["Amount"]
+                .as_str()
+                .and_then(|a| a.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            if let Some(unit) = cost["Unit"].as_str() {
+                spend.currency = unit.to_string();
+            }
+        }
+        Ok(spend)
+    }
+
     /// Get current region
     pub fn get_current_region(&self) -> &str {
         &self.current_region
@@ -1277,3 +2050,124 @@ impl AwsClient {
 
 /// Helper function to add chrono dependency implicitly
 use chrono;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn flags_public_buckets_unencrypted_volumes_and_open_groups() {
+        let group = security_group(&json!({
+            "GroupId": "sg-1",
+            "GroupName": "web",
+            "IpPermissions": [
+                {"IpProtocol": "tcp", "FromPort": 443, "ToPort": 443, "IpRanges": [{"CidrIp": "0.0.0.0/0"}]},
+                {"IpProtocol": "tcp", "FromPort": 22, "ToPort": 22, "Ipv6Ranges": [{"CidrIpv6": "::/0"}]},
+                {"IpProtocol": "tcp", "FromPort": 8000, "ToPort": 8100, "IpRanges": [{"CidrIp": "0.0.0.0/0"}]},
+                {"IpProtocol": "tcp", "FromPort": 5432, "ToPort": 5432, "IpRanges": [{"CidrIp": "10.0.0.0/8"}]},
+                {"IpProtocol": "-1", "IpRanges": [{"CidrIp": "0.0.0.0/0"}]}
+            ]
+        }));
+        let rules: Vec<&str> = security_group_findings(&group)
+            .iter()
+            .map(|f| f.rule_id)
+            .collect();
+        assert_eq!(rules, ["SG-002", "SG-003", "SG-001"]);
+
+        let volume = ebs_volume(&json!({
+            "VolumeId": "vol-1", "Size": 100, "VolumeType": "gp3",
+            "State": "available", "Encrypted": false, "Attachments": []
+        }));
+        assert_eq!(volume_findings(&volume)[0].rule_id, "EBS-001");
+
+        let bucket = S3Bucket {
+            name: "backups".to_string(),
+            creation_date: String::new(),
+            owner: S3Owner {
+                display_name: String::new(),
+                id: String::new(),
+            },
+            region: "eu-west-1".to_string(),
+            versioning: None,
+            encryption: bucket_encryption(&json!({
+                "ServerSideEncryptionConfiguration": {"Rules": [
+                    {"ApplyServerSideEncryptionByDefault": {"SSEAlgorithm": "AES256"}, "BucketKeyEnabled": false}
+                ]}
+            })),
+            public_access_block: public_access_block(&json!({
+                "PublicAccessBlockConfiguration": {
+                    "BlockPublicAcls": true, "IgnorePublicAcls": true,
+                    "BlockPublicPolicy": false, "RestrictPublicBuckets": true
+                }
+            })),
+            is_public: acl_is_public(&json!({"Grants": [
+                {"Grantee": {"Type": "Group", "URI": "http://acs.amazonaws.com/groups/global/AllUsers"}, "Permission": "READ"}
+            ]})),
+            logging: None,
+            notification: None,
+            lifecycle: None,
+            tags: HashMap::new(),
+            object_count: None,
+            size_bytes: None,
+        };
+        let findings = bucket_findings(&bucket);
+        let rules: Vec<&str> = findings.iter().map(|f| f.rule_id).collect();
+        assert_eq!(rules, ["S3-003", "S3-002"]);
+
+        let status = compliance_status(&findings);
+        assert_eq!(status.score, 75.0);
+        let report = assessment(findings);
+        assert_eq!(report.recommendations.len(), 2);
+        assert_eq!(report.overall_score, 75.0);
+    }
+
+    #[test]
+    fn reads_cost_explorer_recommendations() {
+        let (rightsizing, terminations) = rightsizing_recommendations(&json!({
+            "RightsizingRecommendations": [
+                {
+                    "RightsizingType": "Modify",
+                    "CurrentInstance": {
+                        "ResourceId": "i-1",
+                        "ResourceDetails": {"EC2ResourceDetails": {"InstanceType": "m5.2xlarge"}},
+                        "ResourceUtilization": {"EC2ResourceUtilization": {"MaxCpuUtilizationPercentage": "12", "MaxMemoryUtilizationPercentage": "30"}}
+                    },
+                    "ModifyRecommendationDetail": {"TargetInstances": [{
+                        "EstimatedMonthlySavings": "140.16",
+                        "ResourceDetails": {"EC2ResourceDetails": {"InstanceType": "m5.large"}}
+                    }]}
+                },
+                {
+                    "RightsizingType": "Terminate",
+                    "CurrentInstance": {
+                        "ResourceId": "i-2",
+                        "ResourceDetails": {"EC2ResourceDetails": {"InstanceType": "t3.medium"}},
+                        "ResourceUtilization": {"EC2ResourceUtilization": {"MaxCpuUtilizationPercentage": "0.4"}}
+                    },
+                    "TerminateRecommendationDetail": {"EstimatedMonthlySavings": "30.37"}
+                }
+            ]
+        }));
+        assert_eq!(rightsizing[0].recommended_type, "m5.large");
+        assert_eq!(rightsizing[0].monthly_savings, 140.16);
+        assert_eq!(rightsizing[0].cpu_utilization, 12.0);
+        assert_eq!(terminations[0].resource_id, "i-2");
+        assert_eq!(terminations[0].potential_savings, 30.37);
+
+        let reservations = reservation_recommendations(&json!({
+            "Recommendations": [{
+                "TermInYears": "ONE_YEAR",
+                "PaymentOption": "PARTIAL_UPFRONT",
+                "RecommendationDetails": [{
+                    "InstanceDetails": {"EC2InstanceDetails": {"InstanceType": "t3.large"}},
+                    "RecommendedNumberOfInstancesToPurchase": "3",
+                    "EstimatedMonthlySavingsAmount": "25.5"
+                }]
+            }]
+        }));
+        assert_eq!(reservations[0].instance_type, "t3.large");
+        assert_eq!(reservations[0].quantity, 3);
+        assert_eq!(reservations[0].annual_savings, 306.0);
+    }
+}
@@ -7,25 +7,40 @@
 /// - Azure Arc for hybrid/multi-cloud
 /// - Enhanced security with Defender for Cloud
 /// - Cost optimization with Azure Advisor
+///
+/// Everything goes through the Azure Resource Manager and Azure DevOps REST
+/// APIs, so neither the Azure CLI nor Node is needed.
 use crate::cloud::{
     AzureConfig, CloudProvider, CloudResource, ComplexityLevel, CostOptimization,
     CostRecommendation, PaymentOption, RecommendationPriority, ReservedInstanceRecommendation,
-    ReservedInstanceTerm, SecurityAssessment, SecurityRecommendation, SecurityViolation,
-    ViolationSeverity,
+    ReservedInstanceTerm, SecurityAssessment, SecurityRecommendation, SecurityViolation, Spend,
+    SpendFilter, ViolationSeverity,
 };
 use crate::error::{Error, Result};
 use crate::lifecycle::LifecycleManager;
 use crate::security::SecurityModule;
 use crate::tools::ToolDefinition;
+use reqwest::Method;
+use rest::{release_base, segment, snake_case_keys, AzureRest};
+pub(crate) use rest::{AzureCredentials, AzureTokens, AUTHORITY_HOST};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::process::Command;
 
 /// Helper function to add chrono dependency implicitly
 use chrono;
 
+mod rest;
+
+const RESOURCES_API_VERSION: &str = "2021-04-01";
+const SUBSCRIPTIONS_API_VERSION: &str = "2022-12-01";
+const COMPUTE_API_VERSION: &str = "2024-03-01";
+const STORAGE_API_VERSION: &str = "2023-05-01";
+const AUTHORIZATION_API_VERSION: &str = "2022-04-01";
+const CONSUMPTION_API_VERSION: &str = "2023-05-01";
+
 /// Azure virtual machine
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VirtualMachine {
@@ -68,6 +83,7 @@ pub struct StorageProfile {
     /// OS disk
     pub os_disk: Option<OsDisk>,
     /// Data disks
+    #[serde(default)]
     pub data_disks: Vec<DataDisk>,
 }
 
@@ -177,6 +193,7 @@ pub struct OsProfile {
     /// Linux configuration
     pub linux_configuration: Option<LinuxConfiguration>,
     /// Secrets
+    #[serde(default)]
     pub secrets: Vec<VaultSecretGroup>,
 }
 
@@ -204,6 +221,7 @@ pub struct LinuxConfiguration {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SshConfiguration {
     /// Public keys
+    #[serde(default)]
     pub public_keys: Vec<SshPublicKey>,
 }
 
@@ -222,6 +240,7 @@ pub struct VaultSecretGroup {
     /// Source vault
     pub source_vault: SubResource,
     /// Vault certificates
+    #[serde(default)]
     pub vault_certificates: Vec<VaultCertificate>,
 }
 
@@ -238,6 +257,7 @@ pub struct VaultCertificate {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkProfile {
     /// Network interfaces
+    #[serde(default)]
     pub network_interfaces: Vec<NetworkInterfaceReference>,
 }
 
@@ -326,6 +346,7 @@ pub struct StorageAccountProperties {
     /// Large file shares state
     pub large_file_shares_state: Option<String>,
     /// Private endpoint connections
+    #[serde(default)]
     pub private_endpoint_connections: Vec<PrivateEndpointConnection>,
     /// Routing preference
     pub routing_preference: Option<RoutingPreference>,
@@ -415,8 +436,10 @@ pub struct NetworkRuleSet {
     /// Bypass
     pub bypass: String,
     /// Virtual network rules
+    #[serde(default)]
     pub virtual_network_rules: Vec<VirtualNetworkRule>,
     /// IP rules
+    #[serde(default)]
     pub ip_rules: Vec<IpRule>,
     /// Default action
     pub default_action: String,
@@ -716,7 +739,6 @@ pub struct BuildQueryParams {
     /// Top (maximum number of builds to return)
     pub top: Option<i32>,
 }
-
 /// Azure client with comprehensive 2024-2025 feature support
 pub struct AzureClient {
     /// Azure configuration
@@ -727,68 +749,297 @@ pub struct AzureClient {
     security: SecurityModule,
     /// Current subscription ID
     current_subscription: String,
+    /// ARM and Azure DevOps REST client
+    rest: AzureRest,
+}
+
+/// String at `key`, or an empty string
+fn string(value: &Value, key: &str) -> String {
+    value[key].as_str().unwrap_or_default().to_string()
+}
+
+/// Tags of an ARM resource
+fn tags(value: &Value) -> Option<HashMap<String, String>> {
+    serde_json::from_value(value["tags"].clone()).ok()
+}
+
+/// Nested ARM object as one of the snake_case structs, if it fits
+fn nested<T: DeserializeOwned>(value: &Value) -> Option<T> {
+    if value.is_null() {
+        return None;
+    }
+    serde_json::from_value(snake_case_keys(value)).ok()
+}
+
+fn virtual_machine(vm: &Value) -> VirtualMachine {
+    let properties = &vm["properties"];
+    VirtualMachine {
+        id: string(vm, "id"),
+        name: string(vm, "name"),
+        location: string(vm, "location"),
+        tags: tags(vm),
+        hardware_profile: nested(&properties["hardwareProfile"]),
+        storage_profile: nested(&properties["storageProfile"]),
+        os_profile: nested(&properties["osProfile"]),
+        network_profile: nested(&properties["networkProfile"]),
+        provisioning_state: string(properties, "provisioningState"),
+        vm_id: properties["vmId"].as_str().map(str::to_string),
+        vm_type: string(vm, "type"),
+    }
+}
+
+fn storage_account(account: &Value) -> StorageAccount {
+    let properties = &account["properties"];
+    StorageAccount {
+        id: string(account, "id"),
+        name: string(account, "name"),
+        account_type: string(account, "type"),
+        location: string(account, "location"),
+        tags: tags(account),
+        kind: string(account, "kind"),
+        sku: nested(&account["sku"]),
+        properties: nested(properties),
+        enable_https_traffic_only: properties["supportsHttpsTrafficOnly"].as_bool(),
+        minimum_tls_version: properties["minimumTlsVersion"].as_str().map(str::to_string),
+    }
+}
+
+fn resource_group(group: &Value) -> ResourceGroup {
+    ResourceGroup {
+        name: string(group, "name"),
+        location: string(group, "location"),
+        provisioning_state: string(&group["properties"], "provisioningState"),
+        tags: tags(group),
+    }
+}
+
+fn resource(resource: &Value) -> Resource {
+    Resource {
+        id: string(resource, "id"),
+        name: string(resource, "name"),
+        resource_type: string(resource, "type"),
+        location: string(resource, "location"),
+        tags: tags(resource),
+    }
+}
+
+fn subscription(subscription: &Value) -> Subscription {
+    Subscription {
+        id: string(subscription, "subscriptionId"),
+        name: string(subscription, "displayName"),
+        state: string(subscription, "state"),
+    }
+}
+
+fn location(location: &Value) -> Location {
+    let metadata = &location["metadata"];
+    Location {
+        name: string(location, "name"),
+        display_name: string(location, "displayName"),
+        region_type: metadata["regionType"]
+            .as_str()
+            .unwrap_or("Unknown")
+            .to_string(),
+        region_category: metadata["regionCategory"]
+            .as_str()
+            .unwrap_or("Unknown")
+            .to_string(),
+    }
+}
+
+/// Role assignments in the shape `az role assignment list` prints,
+/// with role names looked up from `definitions`
+fn role_assignments(assignments: &[Value], definitions: &[Value]) -> Value {
+    let names: HashMap<String, &str> = definitions
+        .iter()
+        .filter_map(|d| {
+            Some((
+                d["name"].as_str()?.to_lowercase(),
+                d["properties"]["roleName"].as_str()?,
+            ))
+        })
+        .collect();
+    Value::Array(
+        assignments
+            .iter()
+            .map(|assignment| {
+                let properties = &assignment["properties"];
+                let definition_id = string(properties, "roleDefinitionId");
+                let definition = definition_id.rsplit('/').next().unwrap_or_default();
+                json!({
+                    "id": assignment["id"],
+                    "name": assignment["name"],
+                    "principalId": properties["principalId"],
+                    "principalType": properties["principalType"],
+                    "roleDefinitionId": definition_id,
+                    "roleDefinitionName": names
+                        .get(&definition.to_lowercase())
+                        .copied()
+                        .unwrap_or(definition),
+                    "scope": properties["scope"],
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Cost of a usage detail, legacy or modern, with its currency
+fn usage_cost(item: &Value) -> (f64, Option<&str>) {
+    let properties = &item["properties"];
+    let cost = ["costInBillingCurrency", "cost", "pretaxCost"]
+        .iter()
+        .find_map(|key| {
+            properties[key]
+                .as_f64()
+                .or_else(|| properties[key].as_str().and_then(|c| c.parse().ok()))
+        })
+        .unwrap_or(0.0);
+    let currency = properties["billingCurrencyCode"]
+        .as_str()
+        .or_else(|| properties["billingCurrency"].as_str());
+    (cost, currency)
+}
+
+fn work_item(item: &Value) -> WorkItem {
+    let fields = &item["fields"];
+    let field = |name: &str, default: &str| fields[name].as_str().unwrap_or(default).to_string();
+    let person = |name: &str| fields[name]["displayName"].as_str().map(str::to_string);
+    WorkItem {
+        id: item["id"].as_i64().unwrap_or_default() as i32,
+        work_item_type: field("System.WorkItemType", "Unknown"),
+        title: field("System.Title", "Untitled"),
+        state: field("System.State", "Unknown"),
+        created_by: person("System.CreatedBy"),
+        assigned_to: person("System.AssignedTo"),
+        tags: fields["System.Tags"].as_str().map(|tags| {
+            tags.split(';')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect()
+        }),
+        fields: serde_json::from_value(fields.clone()).unwrap_or_default(),
+    }
+}
+
+fn build_definition(definition: &Value) -> BuildDefinition {
+    let repository = &definition["repository"];
+    BuildDefinition {
+        id: definition["id"].as_i64().unwrap_or_default() as i32,
+        name: string(definition, "name"),
+        path: definition["path"].as_str().unwrap_or("\\").to_string(),
+        queue_status: definition["queueStatus"]
+            .as_str()
+            .unwrap_or("enabled")
+            .to_string(),
+        repository: repository.is_object().then(|| Repository {
+            id: string(repository, "id"),
+            name: string(repository, "name"),
+            repository_type: string(repository, "type"),
+            url: repository["url"].as_str().map(str::to_string),
+        }),
+    }
+}
+
+fn build(build: &Value) -> Build {
+    let text = |key: &str| build[key].as_str().map(str::to_string);
+    Build {
+        id: build["id"].as_i64().unwrap_or_default() as i32,
+        build_number: string(build, "buildNumber"),
+        status: string(build, "status"),
+        result: text("result"),
+        definition: build_definition(&build["definition"]),
+        started_on: text("startTime"),
+        finished_on: text("finishTime"),
+        requested_by: build["requestedBy"]["displayName"]
+            .as_str()
+            .map(str::to_string),
+        source_branch: string(build, "sourceBranch"),
+    }
+}
+
+fn release_definition(definition: &Value) -> ReleaseDefinition {
+    ReleaseDefinition {
+        id: definition["id"].as_i64().unwrap_or_default() as i32,
+        name: string(definition, "name"),
+        path: definition["path"].as_str().unwrap_or("\\").to_string(),
+        release_name_format: string(definition, "releaseNameFormat"),
+    }
 }
 
+fn release(release: &Value) -> Release {
+    Release {
+        id: release["id"].as_i64().unwrap_or_default() as i32,
+        name: string(release, "name"),
+        status: string(release, "status"),
+        created_on: string(release, "createdOn"),
+        created_by: release["createdBy"]["displayName"]
+            .as_str()
+            .map(str::to_string),
+        definition: release_definition(&release["releaseDefinition"]),
+        description: release["description"].as_str().map(str::to_string),
+    }
+}
+
+/// JSON Patch setting each field of a work item
+fn field_operations(fields: HashMap<String, Value>) -> Vec<Value> {
+    fields
+        .into_iter()
+        .map(|(name, value)| {
+            json!({
+                "op": "add",
+                "path": format!("/fields/{}", name),
+                "value": value
+            })
+        })
+        .collect()
+}
+
+/// Most work items fetched in one batch
+const WORK_ITEM_BATCH: usize = 200;
+
 impl AzureClient {
     /// Create a new Azure client
     pub fn new(config: AzureConfig, lifecycle: Arc<LifecycleManager>) -> Result<Self> {
-        // Check if Azure CLI is available
-        Self::check_azure_cli()?;
-
         let current_subscription = config.subscription_id.clone().unwrap_or_default();
+        let rest = AzureRest::new(config.clone())?;
 
         Ok(Self {
             config,
             lifecycle,
             security: SecurityModule::new(),
             current_subscription,
+            rest,
         })
     }
 
-    /// Check if Azure CLI is available and configured
-    fn check_azure_cli() -> Result<()> {
-        let output = std::process::Command::new("az")
-            .arg("--version")
-            .output()
-            .map_err(|_| Error::config("Azure CLI not found. Please install Azure CLI"))?;
-
-        if !output.status.success() {
-            return Err(Error::config("Azure CLI not properly configured"));
+    /// ARM path of the current subscription
+    fn subscription_path(&self) -> Result<String> {
+        if self.current_subscription.is_empty() {
+            return Err(Error::config_with_suggestion(
+                "No Azure subscription selected",
+                "Set subscription_id under cloud.azure",
+            ));
         }
-
-        Ok(())
+        Ok(format!("/subscriptions/{}", self.current_subscription))
     }
 
-    /// Execute Azure CLI command with proper authentication
-    async fn execute_az_command(&self, args: &[&str]) -> Result<String> {
-        let mut cmd = Command::new("az");
-
-        // Add subscription if available
-        if !self.current_subscription.is_empty() {
-            cmd.args(["--subscription", &self.current_subscription]);
-        }
-
-        // Add output format
-        cmd.args(["--output", "json"]);
-
-        // Add arguments
-        cmd.args(args);
-
-        // Execute command
-        let output = cmd
-            .output()
-            .await
-            .map_err(|e| Error::internal(format!("Failed to execute az command: {}", e)))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(Error::service(format!(
-                "Azure CLI command failed: {}",
-                stderr
-            )));
-        }
+    /// Azure DevOps organization URL
+    fn devops_url(&self) -> Result<&str> {
+        self.config
+            .devops_org_url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/'))
+            .ok_or_else(|| {
+                Error::config_with_suggestion(
+                    "Azure DevOps is not configured",
+                    "Set devops_org_url under cloud.azure, e.g. https://dev.azure.com/<organization>",
+                )
+            })
+    }
 
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    /// Azure DevOps project API URL, below the organization or release endpoint
+    fn project_url(&self, base: &str, project: &str, path: &str) -> String {
+        format!("{}/{}/_apis/{}", base, segment(project), path)
     }
 
     /// List all cloud resources across services
@@ -860,34 +1111,47 @@ impl AzureClient {
 
     /// Get resource groups (keeping for compatibility)
     pub async fn list_resource_groups(&self) -> Result<Vec<ResourceGroup>> {
-        let output = self.execute_az_command(&["group", "list"]).await?;
-
-        let groups_data: Vec<ResourceGroup> = serde_json::from_str(&output)
-            .map_err(|e| Error::parsing(format!("Failed to parse resource groups: {}", e)))?;
-
-        Ok(groups_data)
+        let groups = self
+            .rest
+            .list(
+                &format!("{}/resourcegroups", self.subscription_path()?),
+                RESOURCES_API_VERSION,
+                "list resource groups",
+            )
+            .await?;
+        Ok(groups.iter().map(resource_group).collect())
     }
 
     /// List virtual machines
     pub async fn list_virtual_machines(&self) -> Result<Vec<VirtualMachine>> {
-        let output = self.execute_az_command(&["vm", "list"]).await?;
-
-        let vms_data: Vec<VirtualMachine> = serde_json::from_str(&output)
-            .map_err(|e| Error::parsing(format!("Failed to parse virtual machines: {}", e)))?;
-
-        Ok(vms_data)
+        let vms = self
+            .rest
+            .list(
+                &format!(
+                    "{}/providers/Microsoft.Compute/virtualMachines",
+                    self.subscription_path()?
+                ),
+                COMPUTE_API_VERSION,
+                "list virtual machines",
+            )
+            .await?;
+        Ok(vms.iter().map(virtual_machine).collect())
     }
 
     /// List storage accounts
     pub async fn list_storage_accounts(&self) -> Result<Vec<StorageAccount>> {
-        let output = self
-            .execute_az_command(&["storage", "account", "list"])
+        let accounts = self
+            .rest
+            .list(
+                &format!(
+                    "{}/providers/Microsoft.Storage/storageAccounts",
+                    self.subscription_path()?
+                ),
+                STORAGE_API_VERSION,
+                "list storage accounts",
+            )
             .await?;
-
-        let storage_accounts: Vec<StorageAccount> = serde_json::from_str(&output)
-            .map_err(|e| Error::parsing(format!("Failed to parse storage accounts: {}", e)))?;
-
-        Ok(storage_accounts)
+        Ok(accounts.iter().map(storage_account).collect())
     }
 
     /// Perform comprehensive security assessment
@@ -1057,6 +1321,85 @@ impl AzureClient {
         })
     }
 
+    /// Role assignments in the subscription, including inherited ones
+    pub async fn list_role_assignments(&self) -> Result<Value> {
+        let subscription = self.subscription_path()?;
+        let assignments = self
+            .rest
+            .list(
+                &format!(
+                    "{}/providers/Microsoft.Authorization/roleAssignments",
+                    subscription
+                ),
+                AUTHORIZATION_API_VERSION,
+                "list role assignments",
+            )
+            .await?;
+        let definitions = self
+            .rest
+            .list(
+                &format!(
+                    "{}/providers/Microsoft.Authorization/roleDefinitions",
+                    subscription
+                ),
+                AUTHORIZATION_API_VERSION,
+                "list role definitions",
+            )
+            .await?;
+        Ok(role_assignments(&assignments, &definitions))
+    }
+
+    /// Actual spend between `start` and `end` (inclusive) from consumption usage
+    pub async fn spend(
+        &self,
+        start: chrono::NaiveDate,
+        end: chrono::NaiveDate,
+        filter: &SpendFilter,
+    ) -> Result<Spend> {
+        if let Some(account) = &filter.account {
+            if !account.eq_ignore_ascii_case(&self.current_subscription) {
+                return Err(Error::validation(format!(
+                    "Azure client is configured for subscription {}, not {}",
+                    self.current_subscription, account
+                )));
+            }
+        }
+        let usage = self
+            .rest
+            .list(
+                &format!(
+                    "{}/providers/Microsoft.Consumption/usageDetails?api-version={}&$filter={}",
+                    self.subscription_path()?,
+                    CONSUMPTION_API_VERSION,
+                    segment(&format!(
+                        "properties/usageStart ge '{}' and properties/usageEnd le '{}'",
+                        start, end
+                    ))
+                ),
+                CONSUMPTION_API_VERSION,
+                "list consumption usage",
+            )
+            .await?;
+
+        let mut spend = Spend {
+            amount: 0.0,
+            currency: "USD".to_string(),
+        };
+        for item in &usage {
+            if let (Some(key), Some(value)) = (&filter.tag_key, &filter.tag_value) {
+                if item["tags"][key].as_str() != Some(value.as_str()) {
+                    continue;
+                }
+            }
+            let (cost, currency) = usage_cost(item);
+            spend.amount += cost;
+            if let Some(currency) = currency {
+                spend.currency = currency.to_string();
+            }
+        }
+        Ok(spend)
+    }
+
     /// Get current subscription
     pub fn get_current_subscription(&self) -> &str {
         &self.current_subscription
@@ -1082,114 +1425,27 @@ impl AzureClient {
         &self.security
     }
 
-    /// Execute a resource script (placeholder for actual implementation)
-    async fn execute_resource_script(&self, _script: &str) -> Result<serde_json::Value> {
-        // This is a placeholder. In a real implementation, this would execute
-        // the provided Node.js script against Azure Resource Manager API
-        Ok(serde_json::json!({
-            "resourceGroups": [],
-            "resources": [],
-            "subscriptions": [],
-            "locations": [],
-            "workItems": [],
-            "definitions": [],
-            "builds": [],
-            "releases": []
-        }))
-    }
-
-    /// Extract content as JSON from response
-    fn extract_content_as_json(response: &serde_json::Value) -> Result<&serde_json::Value> {
-        Ok(response)
-    }
-
-    /// Get current subscription ID
-    fn get_subscription(&self) -> Result<Option<String>> {
-        Ok(Some(self.current_subscription.clone()))
-    }
-
-    /* Commented out - duplicate method exists above
-    /// List resource groups
-    pub async fn list_resource_groups(&self) -> Result<Vec<ResourceGroup>> {
-        let script = r#"
-            // List all resource groups in current subscription
-            async function listResourceGroups() {
-                try {
-                    const groups = [];
-
-                    for await (const group of resourceClient.resourceGroups.list()) {
-                        groups.push({
-                            name: group.name,
-                            location: group.location,
-                            provisioningState: group.properties?.provisioningState || 'Unknown',
-                            tags: group.tags
-                        });
-                    }
-
-                    return { resourceGroups: groups };
-                } catch (error) {
-                    throw new Error(`Failed to list resource groups: ${error.message}`);
-                }
-            }
-
-            return await listResourceGroups();
-        "#;
-
-        let response = self.execute_resource_script(script).await?;
-
-        // Parse resource groups from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let groups_data = content.get("resourceGroups")
-            .ok_or_else(|| Error::protocol("Missing 'resourceGroups' field in response".to_string()))?;
-
-        let groups: Vec<ResourceGroup> = serde_json::from_value(groups_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse resource groups: {}", e)))?;
-
-        Ok(groups)
+    fn resource_group_path(&self, name: &str) -> Result<String> {
+        Ok(format!(
+            "{}/resourcegroups/{}",
+            self.subscription_path()?,
+            segment(name)
+        ))
     }
-    */
 
     /// Get a resource group
     pub async fn get_resource_group(&self, name: &str) -> Result<ResourceGroup> {
-        let script = format!(
-            r#"
-            // Get a specific resource group
-            async function getResourceGroup() {{
-                try {{
-                    const group = await resourceClient.resourceGroups.get("{}");
-                    
-                    return {{
-                        resourceGroup: {{
-                            name: group.name,
-                            location: group.location,
-                            provisioningState: group.properties?.provisioningState || 'Unknown',
-                            tags: group.tags
-                        }}
-                    }};
-                }} catch (error) {{
-                    throw new Error(`Failed to get resource group: ${{error.message}}`);
-                }}
-            }}
-            
-            return await getResourceGroup();
-        "#,
-            name
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse resource group from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let group_data = content.get("resourceGroup").ok_or_else(|| {
-            Error::protocol("Missing 'resourceGroup' field in response".to_string())
-        })?;
-
-        let group: ResourceGroup = serde_json::from_value(group_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse resource group: {}", e)))?;
-
-        Ok(group)
+        let group = self
+            .rest
+            .arm(
+                Method::GET,
+                &self.resource_group_path(name)?,
+                RESOURCES_API_VERSION,
+                None,
+                "get resource group",
+            )
+            .await?;
+        Ok(resource_group(&group))
     }
 
     /// Create a resource group
@@ -1199,321 +1455,94 @@ impl AzureClient {
         location: &str,
         tags: Option<HashMap<String, String>>,
     ) -> Result<ResourceGroup> {
-        let tags_json = match tags {
-            Some(t) => serde_json::to_string(&t)
-                .map_err(|e| Error::internal(format!("Failed to serialize tags: {}", e)))?,
-            None => "null".to_string(),
-        };
-
-        let script = format!(
-            r#"
-            // Create a resource group
-            async function createResourceGroup() {{
-                try {{
-                    const params = {{
-                        location: "{}",
-                        tags: {}
-                    }};
-                    
-                    const group = await resourceClient.resourceGroups.createOrUpdate("{}", params);
-                    
-                    return {{
-                        resourceGroup: {{
-                            name: group.name,
-                            location: group.location,
-                            provisioningState: group.properties?.provisioningState || 'Unknown',
-                            tags: group.tags
-                        }}
-                    }};
-                }} catch (error) {{
-                    throw new Error(`Failed to create resource group: ${{error.message}}`);
-                }}
-            }}
-            
-            return await createResourceGroup();
-        "#,
-            location, tags_json, name
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse resource group from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let group_data = content.get("resourceGroup").ok_or_else(|| {
-            Error::protocol("Missing 'resourceGroup' field in response".to_string())
-        })?;
-
-        let group: ResourceGroup = serde_json::from_value(group_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse resource group: {}", e)))?;
-
-        Ok(group)
+        let mut body = json!({ "location": location });
+        if let Some(tags) = tags {
+            body["tags"] = json!(tags);
+        }
+        let group = self
+            .rest
+            .arm(
+                Method::PUT,
+                &self.resource_group_path(name)?,
+                RESOURCES_API_VERSION,
+                Some(&body),
+                "create resource group",
+            )
+            .await?;
+        Ok(resource_group(&group))
     }
 
-    /// Delete a resource group
+    /// Delete a resource group and everything in it, waiting until it is gone
     pub async fn delete_resource_group(&self, name: &str) -> Result<()> {
-        let script = format!(
-            r#"
-            // Delete a resource group
-            async function deleteResourceGroup() {{
-                try {{
-                    await resourceClient.resourceGroups.beginDeleteAndWait("{}");
-                    return {{ success: true }};
-                }} catch (error) {{
-                    throw new Error(`Failed to delete resource group: ${{error.message}}`);
-                }}
-            }}
-            
-            return await deleteResourceGroup();
-        "#,
-            name
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Check success
-        let content = Self::extract_content_as_json(&response)?;
-
-        let success = content
-            .get("success")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-
-        if success {
-            Ok(())
-        } else {
-            Err(Error::service(format!(
-                "Failed to delete resource group {}",
-                name
-            )))
-        }
+        self.rest
+            .arm(
+                Method::DELETE,
+                &self.resource_group_path(name)?,
+                RESOURCES_API_VERSION,
+                None,
+                "delete resource group",
+            )
+            .await?;
+        Ok(())
     }
 
-    /* Commented out - duplicate method exists above with different signature
-    /// List resources in a resource group
-    pub async fn list_resources(&self, resource_group: Option<&str>) -> Result<Vec<Resource>> {
-        let filter = match resource_group {
-            Some(rg) => format!(r#"resourceGroup eq '{}'"#, rg),
-            None => "".to_string(),
+    /// Resources in the subscription, or in one resource group
+    pub async fn list_resources_in(&self, resource_group: Option<&str>) -> Result<Vec<Resource>> {
+        let path = match resource_group {
+            Some(group) => format!("{}/resources", self.resource_group_path(group)?),
+            None => format!("{}/resources", self.subscription_path()?),
         };
-
-        let script = format!(r#"
-            // List resources
-            async function listResources() {{
-                try {{
-                    const resources = [];
-                    const filter = {};
-
-                    const options = {{
-                        filter: filter
-                    }};
-
-                    const resourceList = resourceClient.resources.list({});
-
-                    for await (const resource of resourceList) {{
-                        resources.push({{
-                            id: resource.id,
-                            name: resource.name,
-                            resourceType: resource.type,
-                            location: resource.location || 'global',
-                            tags: resource.tags
-                        }});
-                    }}
-
-                    return {{ resources }};
-                }} catch (error) {{
-                    throw new Error(`Failed to list resources: ${{error.message}}`);
-                }}
-            }}
-
-            return await listResources();
-        "#,
-        if filter.is_empty() {
-            "undefined".to_string()
-        } else {
-            format!(r#""{}""#, filter)
-        },
-        if filter.is_empty() { "" } else { "options" }
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse resources from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let resources_data = content.get("resources")
-            .ok_or_else(|| Error::protocol("Missing 'resources' field in response".to_string()))?;
-
-        let resources: Vec<Resource> = serde_json::from_value(resources_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse resources: {}", e)))?;
-
-        Ok(resources)
+        let resources = self
+            .rest
+            .list(&path, RESOURCES_API_VERSION, "list resources")
+            .await?;
+        Ok(resources.iter().map(resource).collect())
     }
-    */
 
     /// List subscriptions
     pub async fn list_subscriptions(&self) -> Result<Vec<Subscription>> {
-        let script = r#"
-            // List subscriptions
-            async function listSubscriptions() {
-                try {
-                    const subscriptions = [];
-                    
-                    for await (const subscription of subscriptionClient.subscriptions.list()) {
-                        subscriptions.push({
-                            id: subscription.subscriptionId,
-                            name: subscription.displayName,
-                            state: subscription.state
-                        });
-                    }
-                    
-                    return { subscriptions };
-                } catch (error) {
-                    throw new Error(`Failed to list subscriptions: ${error.message}`);
-                }
-            }
-            
-            return await listSubscriptions();
-        "#;
-
-        let response = self.execute_resource_script(script).await?;
-
-        // Parse subscriptions from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let subscriptions_data = content.get("subscriptions").ok_or_else(|| {
-            Error::protocol("Missing 'subscriptions' field in response".to_string())
-        })?;
-
-        let subscriptions: Vec<Subscription> =
-            serde_json::from_value(subscriptions_data.clone())
-                .map_err(|e| Error::protocol(format!("Failed to parse subscriptions: {}", e)))?;
-
-        Ok(subscriptions)
+        let subscriptions = self
+            .rest
+            .list(
+                "/subscriptions",
+                SUBSCRIPTIONS_API_VERSION,
+                "list subscriptions",
+            )
+            .await?;
+        Ok(subscriptions.iter().map(subscription).collect())
     }
 
     /// Get a specific subscription
     pub async fn get_subscription_by_id(&self, subscription_id: &str) -> Result<Subscription> {
-        let script = format!(
-            r#"
-            // Get a specific subscription
-            async function getSubscription() {{
-                try {{
-                    const subscription = await subscriptionClient.subscriptions.get("{}");
-                    
-                    return {{
-                        subscription: {{
-                            id: subscription.subscriptionId,
-                            name: subscription.displayName,
-                            state: subscription.state
-                        }}
-                    }};
-                }} catch (error) {{
-                    throw new Error(`Failed to get subscription: ${{error.message}}`);
-                }}
-            }}
-            
-            return await getSubscription();
-        "#,
-            subscription_id
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse subscription from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let subscription_data = content.get("subscription").ok_or_else(|| {
-            Error::protocol("Missing 'subscription' field in response".to_string())
-        })?;
-
-        let subscription: Subscription = serde_json::from_value(subscription_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse subscription: {}", e)))?;
-
-        Ok(subscription)
+        let found = self
+            .rest
+            .arm(
+                Method::GET,
+                &format!("/subscriptions/{}", segment(subscription_id)),
+                SUBSCRIPTIONS_API_VERSION,
+                None,
+                "get subscription",
+            )
+            .await?;
+        Ok(subscription(&found))
     }
 
     /// List locations
     pub async fn list_locations(&self, subscription_id: Option<&str>) -> Result<Vec<Location>> {
-        let subscription = match subscription_id {
-            Some(sub) => sub.to_string(),
-            None => match self.get_subscription()? {
-                Some(sub) => sub,
-                None => {
-                    return Err(Error::validation(
-                        "No subscription selected or provided".to_string(),
-                    ))
-                }
-            },
+        let path = match subscription_id {
+            Some(id) => format!("/subscriptions/{}", segment(id)),
+            None => self.subscription_path()?,
         };
-
-        let script = format!(
-            r#"
-            // List locations
-            async function listLocations() {{
-                try {{
-                    const locations = [];
-                    
-                    for await (const location of subscriptionClient.subscriptions.listLocations("{}")) {{
-                        locations.push({{
-                            name: location.name,
-                            displayName: location.displayName,
-                            regionType: location.metadata?.regionType || 'Unknown',
-                            regionCategory: location.metadata?.regionCategory || 'Unknown'
-                        }});
-                    }}
-                    
-                    return {{ locations }};
-                }} catch (error) {{
-                    throw new Error(`Failed to list locations: ${{error.message}}`);
-                }}
-            }}
-            
-            return await listLocations();
-        "#,
-            subscription
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse locations from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let locations_data = content
-            .get("locations")
-            .ok_or_else(|| Error::protocol("Missing 'locations' field in response".to_string()))?;
-
-        let locations: Vec<Location> = serde_json::from_value(locations_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse locations: {}", e)))?;
-
-        Ok(locations)
-    }
-
-    /* Commented out - duplicate method
-    /// Extract JSON content from response
-    fn extract_content_as_json(response: &Value) -> Result<Value> {
-        let content = response.get("content")
-            .ok_or_else(|| Error::protocol("Missing 'content' field in response".to_string()))?;
-
-        if !content.is_array() {
-            return Err(Error::protocol("'content' field is not an array".to_string()));
-        }
-
-        let content_array = content.as_array()
-            .ok_or_else(|| Error::invalid_data("Expected array for container list"))?;
-
-        for item in content_array {
-            if item.get("type").and_then(|t| t.as_str()) == Some("text") {
-                if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
-                    return serde_json::from_str(text)
-                        .map_err(|e| Error::protocol(format!("Failed to parse content as JSON: {}", e)));
-                }
-            }
-        }
-
-        Err(Error::protocol("No text content found in response".to_string()))
+        let locations = self
+            .rest
+            .list(
+                &format!("{}/locations", path),
+                SUBSCRIPTIONS_API_VERSION,
+                "list locations",
+            )
+            .await?;
+        Ok(locations.iter().map(location).collect())
     }
-    */
 
     /// Get tool definitions
     pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
@@ -1536,7 +1565,8 @@ impl AzureClient {
                             "Use to get all resource groups in subscription".to_string()
                         ]),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "get_resource_group",
                 "Get details of an Azure resource group",
@@ -1558,7 +1588,8 @@ impl AzureClient {
                             "Use to get details of a specific resource group".to_string()
                         ]),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "create_resource_group",
                 "Create an Azure resource group",
@@ -1635,7 +1666,8 @@ impl AzureClient {
                             "Use to list all resources or filter by resource group".to_string(),
                         ]),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "list_subscriptions",
                 "List Azure subscriptions",
@@ -1652,7 +1684,8 @@ impl AzureClient {
                             "Use to get all available Azure subscriptions".to_string()
                         ]),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "list_locations",
                 "List Azure locations",
@@ -1672,128 +1705,73 @@ impl AzureClient {
                         .with_description("List Azure locations")
                         .with_usage_hints(vec!["Use to get available Azure regions".to_string()]),
                 ),
-            ),
+            )
+            .read_only(),
         ]
     }
 
     /// Azure DevOps work item methods
     /// List work items using WIQL query
     pub async fn list_work_items(&self, project: &str, query: &str) -> Result<WorkItemQueryResult> {
-        let script = format!(
-            r#"
-            // List work items using WIQL query
-            async function listWorkItems() {{
-                try {{
-                    const wiqlQuery = {{
-                        query: `{}`
-                    }};
-
-                    const witClient = getClient('WorkItemTrackingRestClient');
-                    const queryResult = await witClient.queryByWiql(wiqlQuery, '{}');
-                    
-                    if (!queryResult || !queryResult.workItems || !queryResult.workItems.length) {{
-                        return {{ workItems: [], count: 0 }};
-                    }}
-
-                    // Get the full work items
-                    const ids = queryResult.workItems.map(wi => wi.id);
-                    const workItems = await witClient.getWorkItems(ids, null, null, null, '{}');
-                    
-                    const formattedWorkItems = workItems.map(wi => {{
-                        const fields = wi.fields || {{}};
-                        return {{
-                            id: wi.id,
-                            work_item_type: fields['System.WorkItemType'] || 'Unknown',
-                            title: fields['System.Title'] || 'Untitled',
-                            state: fields['System.State'] || 'Unknown',
-                            created_by: fields['System.CreatedBy'] ? fields['System.CreatedBy'].displayName : null,
-                            assigned_to: fields['System.AssignedTo'] ? fields['System.AssignedTo'].displayName : null,
-                            tags: fields['System.Tags'] ? fields['System.Tags'].split(';').map(t => t.trim()) : [],
-                            fields: fields
-                        }};
-                    }});
-
-                    return {{ 
-                        workItems: formattedWorkItems,
-                        count: formattedWorkItems.length
-                    }};
-                }} catch (error) {{
-                    throw new Error(`Failed to query work items: ${{error.message}}`);
-                }}
-            }}
-            
-            return await listWorkItems();
-        "#,
-            query, project, project
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse work items from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let work_items_data = content
-            .get("workItems")
-            .ok_or_else(|| Error::protocol("Missing 'workItems' field in response".to_string()))?;
-
-        let count = content.get("count").and_then(|c| c.as_u64()).unwrap_or(0) as usize;
-
-        let work_items: Vec<WorkItem> = serde_json::from_value(work_items_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse work items: {}", e)))?;
-
-        Ok(WorkItemQueryResult { work_items, count })
+        let base = self.devops_url()?;
+        let result = self
+            .rest
+            .devops(
+                Method::POST,
+                &self.project_url(base, project, "wit/wiql"),
+                Some(&json!({ "query": query })),
+                "query work items",
+            )
+            .await?;
+        let ids: Vec<String> = result["workItems"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item["id"].as_i64().map(|id| id.to_string()))
+            .collect();
+
+        let mut work_items = Vec::with_capacity(ids.len());
+        for batch in ids.chunks(WORK_ITEM_BATCH) {
+            let items = self
+                .rest
+                .devops(
+                    Method::GET,
+                    &format!(
+                        "{}?ids={}",
+                        self.project_url(base, project, "wit/workitems"),
+                        batch.join(",")
+                    ),
+                    None,
+                    "get work items",
+                )
+                .await?;
+            work_items.extend(
+                items["value"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(work_item),
+            );
+        }
+        Ok(WorkItemQueryResult {
+            count: ids.len(),
+            work_items,
+        })
     }
 
     /// Get work item by ID
     pub async fn get_work_item(&self, project: &str, id: i32) -> Result<WorkItem> {
-        let script = format!(
-            r#"
-            // Get work item by ID
-            async function getWorkItem() {{
-                try {{
-                    const witClient = getClient('WorkItemTrackingRestClient');
-                    const workItem = await witClient.getWorkItem({}, null, null, null, '{}');
-                    
-                    if (!workItem) {{
-                        throw new Error(`Work item with ID {} not found`);
-                    }}
-                    
-                    const fields = workItem.fields || {{}};
-                    const formattedWorkItem = {{
-                        id: workItem.id,
-                        work_item_type: fields['System.WorkItemType'] || 'Unknown',
-                        title: fields['System.Title'] || 'Untitled',
-                        state: fields['System.State'] || 'Unknown',
-                        created_by: fields['System.CreatedBy'] ? fields['System.CreatedBy'].displayName : null,
-                        assigned_to: fields['System.AssignedTo'] ? fields['System.AssignedTo'].displayName : null,
-                        tags: fields['System.Tags'] ? fields['System.Tags'].split(';').map(t => t.trim()) : [],
-                        fields: fields
-                    }};
-                    
-                    return {{ workItem: formattedWorkItem }};
-                }} catch (error) {{
-                    throw new Error(`Failed to get work item: ${{error.message}}`);
-                }}
-            }}
-            
-            return await getWorkItem();
-        "#,
-            id, project, id
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse work item from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let work_item_data = content
-            .get("workItem")
-            .ok_or_else(|| Error::protocol("Missing 'workItem' field in response".to_string()))?;
-
-        let work_item: WorkItem = serde_json::from_value(work_item_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse work item: {}", e)))?;
-
-        Ok(work_item)
+        let base = self.devops_url()?;
+        let item = self
+            .rest
+            .devops(
+                Method::GET,
+                &self.project_url(base, project, &format!("wit/workitems/{}", id)),
+                None,
+                "get work item",
+            )
+            .await?;
+        Ok(work_item(&item))
     }
 
     /// Create a new work item
@@ -1804,83 +1782,23 @@ impl AzureClient {
         title: &str,
         fields: Option<HashMap<String, Value>>,
     ) -> Result<WorkItem> {
-        // Construct document with operations
-        let mut operations = Vec::new();
-
-        // Add title field
-        operations.push(serde_json::json!({
-            "op": "add",
-            "path": "/fields/System.Title",
-            "value": title
-        }));
-
-        // Add additional fields if provided
-        if let Some(field_map) = fields {
-            for (field_name, field_value) in field_map {
-                operations.push(serde_json::json!({
-                    "op": "add",
-                    "path": format!("/fields/{}", field_name),
-                    "value": field_value
-                }));
-            }
-        }
-
-        // Serialize operations
-        let operations_json = serde_json::to_string(&operations)
-            .map_err(|e| Error::internal(format!("Failed to serialize operations: {}", e)))?;
-
-        let script = format!(
-            r#"
-            // Create a new work item
-            async function createWorkItem() {{
-                try {{
-                    const operations = {};
-                    
-                    const witClient = getClient('WorkItemTrackingRestClient');
-                    const workItem = await witClient.createWorkItem(
-                        null, operations, '{}', '{}', false
-                    );
-                    
-                    if (!workItem) {{
-                        throw new Error('Failed to create work item');
-                    }}
-                    
-                    const fields = workItem.fields || {{}};
-                    const formattedWorkItem = {{
-                        id: workItem.id,
-                        work_item_type: fields['System.WorkItemType'] || 'Unknown',
-                        title: fields['System.Title'] || 'Untitled',
-                        state: fields['System.State'] || 'Unknown',
-                        created_by: fields['System.CreatedBy'] ? fields['System.CreatedBy'].displayName : null,
-                        assigned_to: fields['System.AssignedTo'] ? fields['System.AssignedTo'].displayName : null,
-                        tags: fields['System.Tags'] ? fields['System.Tags'].split(';').map(t => t.trim()) : [],
-                        fields: fields
-                    }};
-                    
-                    return {{ workItem: formattedWorkItem }};
-                }} catch (error) {{
-                    throw new Error(`Failed to create work item: ${{error.message}}`);
-                }}
-            }}
-            
-            return await createWorkItem();
-        "#,
-            operations_json, project, work_item_type
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse work item from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let work_item_data = content
-            .get("workItem")
-            .ok_or_else(|| Error::protocol("Missing 'workItem' field in response".to_string()))?;
-
-        let work_item: WorkItem = serde_json::from_value(work_item_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse work item: {}", e)))?;
-
-        Ok(work_item)
+        let mut fields = fields.unwrap_or_default();
+        fields.insert("System.Title".to_string(), json!(title));
+        let base = self.devops_url()?;
+        let item = self
+            .rest
+            .devops(
+                Method::POST,
+                &self.project_url(
+                    base,
+                    project,
+                    &format!("wit/workitems/${}", segment(work_item_type)),
+                ),
+                Some(&Value::Array(field_operations(fields))),
+                "create work item",
+            )
+            .await?;
+        Ok(work_item(&item))
     }
 
     /// Update a work item
@@ -1890,130 +1808,41 @@ impl AzureClient {
         id: i32,
         fields: HashMap<String, Value>,
     ) -> Result<WorkItem> {
-        // Construct document with operations
-        let mut operations = Vec::new();
-
-        // Add field operations
-        for (field_name, field_value) in fields {
-            operations.push(serde_json::json!({
-                "op": "add",
-                "path": format!("/fields/{}", field_name),
-                "value": field_value
-            }));
-        }
-
-        // Serialize operations
-        let operations_json = serde_json::to_string(&operations)
-            .map_err(|e| Error::internal(format!("Failed to serialize operations: {}", e)))?;
-
-        let script = format!(
-            r#"
-            // Update a work item
-            async function updateWorkItem() {{
-                try {{
-                    const operations = {};
-                    
-                    const witClient = getClient('WorkItemTrackingRestClient');
-                    const workItem = await witClient.updateWorkItem(
-                        null, operations, {}, '{}', false
-                    );
-                    
-                    if (!workItem) {{
-                        throw new Error(`Work item with ID {} not found`);
-                    }}
-                    
-                    const fields = workItem.fields || {{}};
-                    const formattedWorkItem = {{
-                        id: workItem.id,
-                        work_item_type: fields['System.WorkItemType'] || 'Unknown',
-                        title: fields['System.Title'] || 'Untitled',
-                        state: fields['System.State'] || 'Unknown',
-                        created_by: fields['System.CreatedBy'] ? fields['System.CreatedBy'].displayName : null,
-                        assigned_to: fields['System.AssignedTo'] ? fields['System.AssignedTo'].displayName : null,
-                        tags: fields['System.Tags'] ? fields['System.Tags'].split(';').map(t => t.trim()) : [],
-                        fields: fields
-                    }};
-                    
-                    return {{ workItem: formattedWorkItem }};
-                }} catch (error) {{
-                    throw new Error(`Failed to update work item: ${{error.message}}`);
-                }}
-            }}
-            
-            return await updateWorkItem();
-        "#,
-            operations_json, id, project, id
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse work item from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let work_item_data = content
-            .get("workItem")
-            .ok_or_else(|| Error::protocol("Missing 'workItem' field in response".to_string()))?;
-
-        let work_item: WorkItem = serde_json::from_value(work_item_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse work item: {}", e)))?;
-
-        Ok(work_item)
+        let base = self.devops_url()?;
+        let item = self
+            .rest
+            .devops(
+                Method::PATCH,
+                &self.project_url(base, project, &format!("wit/workitems/{}", id)),
+                Some(&Value::Array(field_operations(fields))),
+                "update work item",
+            )
+            .await?;
+        Ok(work_item(&item))
     }
 
     /// Azure DevOps build and release methods
     /// List build definitions
     pub async fn list_build_definitions(&self, project: &str) -> Result<Vec<BuildDefinition>> {
-        let script = format!(
-            r#"
-            // List build definitions
-            async function listBuildDefinitions() {{
-                try {{
-                    const buildClient = getClient('BuildRestClient');
-                    const definitions = await buildClient.getDefinitions('{}');
-                    
-                    if (!definitions || !definitions.length) {{
-                        return {{ definitions: [] }};
-                    }}
-                    
-                    const formattedDefinitions = definitions.map(def => {{
-                        return {{
-                            id: def.id,
-                            name: def.name,
-                            path: def.path || '\\',
-                            queue_status: def.queueStatus || 'enabled',
-                            repository: def.repository ? {{
-                                id: def.repository.id,
-                                name: def.repository.name,
-                                repository_type: def.repository.type,
-                                url: def.repository.url
-                            }} : null
-                        }};
-                    }});
-                    
-                    return {{ definitions: formattedDefinitions }};
-                }} catch (error) {{
-                    throw new Error(`Failed to list build definitions: ${{error.message}}`);
-                }}
-            }}
-            
-            return await listBuildDefinitions();
-        "#,
-            project
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse build definitions from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let definitions_data = content.get("definitions").ok_or_else(|| {
-            Error::protocol("Missing 'definitions' field in response".to_string())
-        })?;
-
-        let definitions: Vec<BuildDefinition> = serde_json::from_value(definitions_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse build definitions: {}", e)))?;
-
-        Ok(definitions)
+        let base = self.devops_url()?;
+        let definitions = self
+            .rest
+            .devops(
+                Method::GET,
+                &format!(
+                    "{}?includeAllProperties=true",
+                    self.project_url(base, project, "build/definitions")
+                ),
+                None,
+                "list build definitions",
+            )
+            .await?;
+        Ok(definitions["value"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(build_definition)
+            .collect())
     }
 
     /// Get a build definition
@@ -2022,55 +1851,21 @@ impl AzureClient {
         project: &str,
         definition_id: i32,
     ) -> Result<BuildDefinition> {
-        let script = format!(
-            r#"
-            // Get a build definition
-            async function getBuildDefinition() {{
-                try {{
-                    const buildClient = getClient('BuildRestClient');
-                    const definition = await buildClient.getDefinition('{}', {});
-                    
-                    if (!definition) {{
-                        throw new Error(`Build definition with ID {} not found`);
-                    }}
-                    
-                    const formattedDefinition = {{
-                        id: definition.id,
-                        name: definition.name,
-                        path: definition.path || '\\',
-                        queue_status: definition.queueStatus || 'enabled',
-                        repository: definition.repository ? {{
-                            id: definition.repository.id,
-                            name: definition.repository.name,
-                            repository_type: definition.repository.type,
-                            url: definition.repository.url
-                        }} : null
-                    }};
-                    
-                    return {{ definition: formattedDefinition }};
-                }} catch (error) {{
-                    throw new Error(`Failed to get build definition: ${{error.message}}`);
-                }}
-            }}
-            
-            return await getBuildDefinition();
-        "#,
-            project, definition_id, definition_id
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse build definition from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let definition_data = content
-            .get("definition")
-            .ok_or_else(|| Error::protocol("Missing 'definition' field in response".to_string()))?;
-
-        let definition: BuildDefinition = serde_json::from_value(definition_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse build definition: {}", e)))?;
-
-        Ok(definition)
+        let base = self.devops_url()?;
+        let definition = self
+            .rest
+            .devops(
+                Method::GET,
+                &self.project_url(
+                    base,
+                    project,
+                    &format!("build/definitions/{}", definition_id),
+                ),
+                None,
+                "get build definition",
+            )
+            .await?;
+        Ok(build_definition(&definition))
     }
 
     /// Queue a new build
@@ -2081,81 +1876,30 @@ impl AzureClient {
         source_branch: Option<&str>,
         parameters: Option<HashMap<String, Value>>,
     ) -> Result<Build> {
-        // Create build parameters
-        let mut build_params = json!({
+        let mut body = json!({
             "definition": {
                 "id": definition_id
             }
         });
-
         if let Some(branch) = source_branch {
-            build_params["sourceBranch"] = json!(branch);
+            body["sourceBranch"] = json!(branch);
         }
-
+        // The build API takes the parameters as a JSON-encoded string
         if let Some(params) = parameters {
-            build_params["parameters"] = serde_json::to_value(params)
-                .map_err(|e| Error::internal(format!("Failed to serialize parameters: {}", e)))?;
+            body["parameters"] = json!(serde_json::to_string(&params)
+                .map_err(|e| Error::internal(format!("Failed to serialize parameters: {}", e)))?);
         }
-
-        let build_params_json = serde_json::to_string(&build_params)
-            .map_err(|e| Error::internal(format!("Failed to serialize build parameters: {}", e)))?;
-
-        let script = format!(
-            r#"
-            // Queue a new build
-            async function queueBuild() {{
-                try {{
-                    const buildParams = {};
-                    
-                    const buildClient = getClient('BuildRestClient');
-                    const build = await buildClient.queueBuild(buildParams, '{}');
-                    
-                    if (!build) {{
-                        throw new Error('Failed to queue build');
-                    }}
-                    
-                    const formattedBuild = {{
-                        id: build.id,
-                        build_number: build.buildNumber,
-                        status: build.status,
-                        result: build.result,
-                        definition: {{
-                            id: build.definition.id,
-                            name: build.definition.name,
-                            path: build.definition.path || '\\',
-                            queue_status: 'enabled',
-                            repository: null
-                        }},
-                        started_on: build.startTime,
-                        finished_on: build.finishTime,
-                        requested_by: build.requestedBy ? build.requestedBy.displayName : null,
-                        source_branch: build.sourceBranch
-                    }};
-                    
-                    return {{ build: formattedBuild }};
-                }} catch (error) {{
-                    throw new Error(`Failed to queue build: ${{error.message}}`);
-                }}
-            }}
-            
-            return await queueBuild();
-        "#,
-            build_params_json, project
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse build from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let build_data = content
-            .get("build")
-            .ok_or_else(|| Error::protocol("Missing 'build' field in response".to_string()))?;
-
-        let build: Build = serde_json::from_value(build_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse build: {}", e)))?;
-
-        Ok(build)
+        let base = self.devops_url()?;
+        let queued = self
+            .rest
+            .devops(
+                Method::POST,
+                &self.project_url(base, project, "build/builds"),
+                Some(&body),
+                "queue build",
+            )
+            .await?;
+        Ok(build(&queued))
     }
 
     /// List builds
@@ -2164,148 +1908,59 @@ impl AzureClient {
         project: &str,
         params: Option<BuildQueryParams>,
     ) -> Result<Vec<Build>> {
-        // Convert params to query parameters
-        let mut query_params = Vec::new();
-
+        let mut query = Vec::new();
         if let Some(p) = &params {
             if let Some(def_id) = p.definition_id {
-                query_params.push(format!("definitions={}", def_id));
+                query.push(format!("definitions={}", def_id));
             }
-
             if let Some(branch) = &p.branch {
-                query_params.push(format!("branchName={}", branch));
+                query.push(format!("branchName={}", segment(branch)));
             }
-
             if let Some(status) = &p.status_filter {
-                query_params.push(format!("statusFilter={}", status));
+                query.push(format!("statusFilter={}", segment(status)));
             }
-
             if let Some(result) = &p.result_filter {
-                query_params.push(format!("resultFilter={}", result));
+                query.push(format!("resultFilter={}", segment(result)));
             }
-
             if let Some(top) = p.top {
-                query_params.push(format!("$top={}", top));
+                query.push(format!("$top={}", top));
             }
         }
-
-        let _query_string = if query_params.is_empty() {
-            "".to_string()
-        } else {
-            format!("&{}", query_params.join("&"))
-        };
-
-        let script = format!(
-            r#"
-            // List builds
-            async function listBuilds() {{
-                try {{
-                    const buildClient = getClient('BuildRestClient');
-                    const builds = await buildClient.getBuilds('{}', null /* definitions */, null /* queues */, 
-                        null /* buildNumber */, null /* minFinishTime */, null /* maxFinishTime */, 
-                        null /* requestedFor */, null /* reasonFilter */, null /* statusFilter */, 
-                        null /* resultFilter */, null /* tagFilters */, null /* properties */, 
-                        null /* top */, null /* continuationToken */, null /* maxBuildsPerDefinition */, 
-                        null /* deletedFilter */, null /* queryOrder */, null /* branchName */,
-                        null /* buildIds */, null /* repositoryId */, null /* repositoryType */);
-                    
-                    if (!builds || !builds.length) {{
-                        return {{ builds: [] }};
-                    }}
-                    
-                    const formattedBuilds = builds.map(build => {{
-                        return {{
-                            id: build.id,
-                            build_number: build.buildNumber,
-                            status: build.status,
-                            result: build.result,
-                            definition: {{
-                                id: build.definition.id,
-                                name: build.definition.name,
-                                path: build.definition.path || '\\',
-                                queue_status: 'enabled',
-                                repository: null
-                            }},
-                            started_on: build.startTime,
-                            finished_on: build.finishTime,
-                            requested_by: build.requestedBy ? build.requestedBy.displayName : null,
-                            source_branch: build.sourceBranch
-                        }};
-                    }});
-                    
-                    return {{ builds: formattedBuilds }};
-                }} catch (error) {{
-                    throw new Error(`Failed to list builds: ${{error.message}}`);
-                }}
-            }}
-            
-            return await listBuilds();
-        "#,
-            project
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse builds from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let builds_data = content
-            .get("builds")
-            .ok_or_else(|| Error::protocol("Missing 'builds' field in response".to_string()))?;
-
-        let builds: Vec<Build> = serde_json::from_value(builds_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse builds: {}", e)))?;
-
-        Ok(builds)
+        let base = self.devops_url()?;
+        let mut url = self.project_url(base, project, "build/builds");
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query.join("&"));
+        }
+        let builds = self
+            .rest
+            .devops(Method::GET, &url, None, "list builds")
+            .await?;
+        Ok(builds["value"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(build)
+            .collect())
     }
 
     /// List release definitions
     pub async fn list_release_definitions(&self, project: &str) -> Result<Vec<ReleaseDefinition>> {
-        let script = format!(
-            r#"
-            // List release definitions
-            async function listReleaseDefinitions() {{
-                try {{
-                    const releaseClient = getClient('ReleaseRestClient');
-                    const definitions = await releaseClient.getReleaseDefinitions('{}');
-                    
-                    if (!definitions || !definitions.length) {{
-                        return {{ definitions: [] }};
-                    }}
-                    
-                    const formattedDefinitions = definitions.map(def => {{
-                        return {{
-                            id: def.id,
-                            name: def.name,
-                            path: def.path || '\\',
-                            release_name_format: def.releaseNameFormat
-                        }};
-                    }});
-                    
-                    return {{ definitions: formattedDefinitions }};
-                }} catch (error) {{
-                    throw new Error(`Failed to list release definitions: ${{error.message}}`);
-                }}
-            }}
-            
-            return await listReleaseDefinitions();
-        "#,
-            project
-        );
-
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse release definitions from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let definitions_data = content.get("definitions").ok_or_else(|| {
-            Error::protocol("Missing 'definitions' field in response".to_string())
-        })?;
-
-        let definitions: Vec<ReleaseDefinition> = serde_json::from_value(definitions_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse release definitions: {}", e)))?;
-
-        Ok(definitions)
+        let base = release_base(self.devops_url()?);
+        let definitions = self
+            .rest
+            .devops(
+                Method::GET,
+                &self.project_url(&base, project, "release/definitions"),
+                None,
+                "list release definitions",
+            )
+            .await?;
+        Ok(definitions["value"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(release_definition)
+            .collect())
     }
 
     /// Create a release
@@ -2316,77 +1971,90 @@ impl AzureClient {
         description: Option<&str>,
         artifacts: Option<Vec<Value>>,
     ) -> Result<Release> {
-        // Create release parameters
-        let mut release_params = json!({
+        let mut body = json!({
             "definitionId": definition_id,
             "isDraft": false,
             "reason": "none"
         });
-
         if let Some(desc) = description {
-            release_params["description"] = json!(desc);
+            body["description"] = json!(desc);
         }
-
         if let Some(arts) = artifacts {
-            release_params["artifacts"] = json!(arts);
+            body["artifacts"] = json!(arts);
         }
+        let base = release_base(self.devops_url()?);
+        let created = self
+            .rest
+            .devops(
+                Method::POST,
+                &self.project_url(&base, project, "release/releases"),
+                Some(&body),
+                "create release",
+            )
+            .await?;
+        Ok(release(&created))
+    }
+}
 
-        let release_params_json = serde_json::to_string(&release_params).map_err(|e| {
-            Error::internal(format!("Failed to serialize release parameters: {}", e))
-        })?;
-
-        let script = format!(
-            r#"
-            // Create a release
-            async function createRelease() {{
-                try {{
-                    const releaseParams = {};
-                    
-                    const releaseClient = getClient('ReleaseRestClient');
-                    const release = await releaseClient.createRelease(releaseParams, '{}');
-                    
-                    if (!release) {{
-                        throw new Error('Failed to create release');
-                    }}
-                    
-                    const formattedRelease = {{
-                        id: release.id,
-                        name: release.name,
-                        status: release.status,
-                        created_on: release.createdOn,
-                        created_by: release.createdBy ? release.createdBy.displayName : null,
-                        definition: {{
-                            id: release.releaseDefinition.id,
-                            name: release.releaseDefinition.name,
-                            path: release.releaseDefinition.path || '\\',
-                            release_name_format: release.releaseDefinition.releaseNameFormat || ''
-                        }},
-                        description: release.description
-                    }};
-                    
-                    return {{ release: formattedRelease }};
-                }} catch (error) {{
-                    throw new Error(`Failed to create release: ${{error.message}}`);
-                }}
-            }}
-            
-            return await createRelease();
-        "#,
-            release_params_json, project
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_arm_resources_to_cli_shapes() {
+        let vm = virtual_machine(&json!({
+            "id": "/subscriptions/s/resourceGroups/lab/providers/Microsoft.Compute/virtualMachines/web01",
+            "name": "web01",
+            "type": "Microsoft.Compute/virtualMachines",
+            "location": "westeurope",
+            "tags": {"env": "lab"},
+            "properties": {
+                "vmId": "4f1c",
+                "provisioningState": "Succeeded",
+                "hardwareProfile": {"vmSize": "Standard_B2s"},
+                "storageProfile": {
+                    "osDisk": {"osType": "Windows", "name": "web01-os", "createOption": "FromImage", "diskSizeGB": 127}
+                }
+            }
+        }));
+        assert_eq!(vm.hardware_profile.unwrap().vm_size, "Standard_B2s");
+        let os_disk = vm.storage_profile.unwrap().os_disk.unwrap();
+        assert_eq!(os_disk.os_type.as_deref(), Some("Windows"));
+        assert_eq!(os_disk.disk_size_gb, Some(127));
+        assert_eq!(vm.tags.unwrap()["env"], "lab");
+
+        let assignments = role_assignments(
+            &[json!({"id": "a1", "name": "a1", "properties": {
+                "principalId": "p1",
+                "principalType": "ServicePrincipal",
+                "roleDefinitionId": "/subscriptions/s/providers/Microsoft.Authorization/roleDefinitions/8E3AF657-A8FF-443C-A75C-2FE8C4BCB635",
+                "scope": "/subscriptions/s"
+            }})],
+            &[
+                json!({"name": "8e3af657-a8ff-443c-a75c-2fe8c4bcb635", "properties": {"roleName": "Owner"}}),
+            ],
         );
+        assert_eq!(assignments[0]["roleDefinitionName"], "Owner");
+        assert_eq!(assignments[0]["principalType"], "ServicePrincipal");
 
-        let response = self.execute_resource_script(&script).await?;
-
-        // Parse release from response
-        let content = Self::extract_content_as_json(&response)?;
-
-        let release_data = content
-            .get("release")
-            .ok_or_else(|| Error::protocol("Missing 'release' field in response".to_string()))?;
-
-        let release: Release = serde_json::from_value(release_data.clone())
-            .map_err(|e| Error::protocol(format!("Failed to parse release: {}", e)))?;
+        assert_eq!(
+            usage_cost(
+                &json!({"properties": {"costInBillingCurrency": 1.5, "billingCurrencyCode": "EUR"}})
+            ),
+            (1.5, Some("EUR"))
+        );
 
-        Ok(release)
+        let item = work_item(&json!({"id": 42, "fields": {
+            "System.WorkItemType": "Bug",
+            "System.Title": "Backups fail",
+            "System.State": "Active",
+            "System.AssignedTo": {"displayName": "Sam"},
+            "System.Tags": "backup; nas"
+        }}));
+        assert_eq!(item.assigned_to.as_deref(), Some("Sam"));
+        assert_eq!(
+            item.tags,
+            Some(vec!["backup".to_string(), "nas".to_string()])
+        );
     }
 }
@@ -0,0 +1,478 @@
+//! Azure Resource Manager and Azure DevOps REST calls
+//!
+//! Tokens come from Entra ID with the configured service principal's client
+//! secret, or from the managed identity endpoint when `use_managed_identity`
+//! is set. Azure DevOps takes a personal access token instead when one is
+//! configured. Long-running ARM operations are polled until they finish.
+//! Key Vault and Sentinel request their tokens through the same
+//! [`AzureTokens`].
+
+use crate::cloud::AzureConfig;
+use crate::error::{Error, Result};
+use base64::Engine;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method, StatusCode};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+pub(crate) const MANAGEMENT_URL: &str = "https://management.azure.com";
+
+/// Entra ID resource of Azure DevOps
+const DEVOPS_RESOURCE: &str = "499b84ac-1321-427f-aa17-267ca6975798";
+const DEVOPS_API_VERSION: &str = "7.1";
+
+/// Entra ID endpoint of the public cloud
+pub(crate) const AUTHORITY_HOST: &str = "https://login.microsoftonline.com";
+
+/// Instance metadata endpoint of VMs with a managed identity
+const IMDS_TOKEN_URL: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+
+/// Renew tokens this long before they expire
+const TOKEN_MARGIN: Duration = Duration::from_secs(120);
+
+/// Poll interval for long-running operations without `Retry-After`
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Longest wait for a long-running operation, e.g. deleting a resource group
+const OPERATION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// `camelCase` keys as `snake_case`, recursively
+pub(crate) fn snake_case_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    // A run of capitals is one word: `diskSizeGB` is `disk_size_gb`
+                    let mut snake = String::with_capacity(key.len() + 4);
+                    let mut previous = '_';
+                    for c in key.chars() {
+                        if c.is_ascii_uppercase()
+                            && (previous.is_ascii_lowercase() || previous.is_ascii_digit())
+                        {
+                            snake.push('_');
+                        }
+                        snake.push(c.to_ascii_lowercase());
+                        previous = c;
+                    }
+                    (snake, snake_case_keys(value))
+                })
+                .collect::<Map<String, Value>>(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(snake_case_keys).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Release management base URL for an organization URL
+///
+/// Releases live on `vsrm.dev.azure.com/<org>` or
+/// `<org>.vsrm.visualstudio.com` rather than the organization URL itself.
+pub(crate) fn release_base(org_url: &str) -> String {
+    let org_url = org_url.trim_end_matches('/');
+    if let Some(rest) = org_url.strip_prefix("https://dev.azure.com/") {
+        return format!("https://vsrm.dev.azure.com/{}", rest);
+    }
+    if let Some(org) = org_url
+        .strip_prefix("https://")
+        .and_then(|host| host.strip_suffix(".visualstudio.com"))
+    {
+        return format!("https://{}.vsrm.visualstudio.com", org);
+    }
+    org_url.to_string()
+}
+
+/// A path segment, percent-encoded
+pub(crate) fn segment(name: &str) -> String {
+    percent_encoding::utf8_percent_encode(name, percent_encoding::NON_ALPHANUMERIC).to_string()
+}
+
+/// Token lifetime from `expires_in`, which managed identity endpoints send as a string
+fn lifetime(body: &Value) -> Duration {
+    let seconds = body["expires_in"]
+        .as_u64()
+        .or_else(|| body["expires_in"].as_str().and_then(|s| s.parse().ok()))
+        .unwrap_or(3600);
+    Duration::from_secs(seconds)
+}
+
+/// Who Entra ID access tokens are requested as
+#[derive(Debug, Clone)]
+pub(crate) struct AzureCredentials {
+    pub tenant_id: String,
+    /// Service principal with `client_secret`; on its own, picks a
+    /// user-assigned managed identity
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub use_managed_identity: bool,
+    /// Entra ID endpoint; differs in sovereign clouds
+    pub authority_host: String,
+}
+
+impl From<&AzureConfig> for AzureCredentials {
+    fn from(config: &AzureConfig) -> Self {
+        Self {
+            tenant_id: config.tenant_id.clone(),
+            client_id: config.client_id.clone(),
+            client_secret: config.client_secret.clone(),
+            use_managed_identity: config.use_managed_identity,
+            authority_host: AUTHORITY_HOST.to_string(),
+        }
+    }
+}
+
+/// Access tokens for Azure resources from a service principal or managed
+/// identity, cached per resource until shortly before they expire
+pub(crate) struct AzureTokens {
+    client: Client,
+    credentials: AzureCredentials,
+    /// Access tokens by resource, with when they stop being used
+    tokens: Mutex<HashMap<&'static str, (String, Instant)>>,
+}
+
+impl AzureTokens {
+    pub fn new(client: Client, credentials: AzureCredentials) -> Self {
+        Self {
+            client,
+            credentials,
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Token for `resource`, cached until shortly before it expires
+    pub async fn token(&self, resource: &'static str) -> Result<String> {
+        let mut tokens = self.tokens.lock().await;
+        if let Some((token, until)) = tokens.get(resource) {
+            if Instant::now() < *until {
+                return Ok(token.clone());
+            }
+        }
+        let request = if self.credentials.use_managed_identity {
+            self.managed_identity_request(resource)
+        } else {
+            self.client_secret_request(resource)?
+        };
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Azure token request failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::api_with_status(
+                format!("Azure token request failed: {}", text.trim()),
+                "entra_id",
+                status.as_u16(),
+            ));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Invalid token response: {}", e)))?;
+        let token = body["access_token"]
+            .as_str()
+            .ok_or_else(|| Error::parsing("No access token in response"))?
+            .to_string();
+        tokens.insert(
+            resource,
+            (
+                token.clone(),
+                Instant::now() + lifetime(&body).saturating_sub(TOKEN_MARGIN),
+            ),
+        );
+        Ok(token)
+    }
+
+    fn client_secret_request(&self, resource: &str) -> Result<reqwest::RequestBuilder> {
+        let credentials = &self.credentials;
+        let (Some(client_id), Some(client_secret)) =
+            (&credentials.client_id, &credentials.client_secret)
+        else {
+            return Err(Error::config_with_suggestion(
+                "Azure needs a service principal or a managed identity",
+                "Set client_id and client_secret under cloud.azure, or use_managed_identity: true",
+            ));
+        };
+        Ok(self
+            .client
+            .post(format!(
+                "{}/{}/oauth2/v2.0/token",
+                credentials.authority_host.trim_end_matches('/'),
+                credentials.tenant_id
+            ))
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", client_id.as_str()),
+                ("client_secret", client_secret.as_str()),
+                ("scope", &format!("{}/.default", resource)),
+            ]))
+    }
+
+    /// Managed identity token request; App Service, Container Apps and Arc
+    /// announce their endpoint in `IDENTITY_ENDPOINT`, VMs use the metadata service
+    fn managed_identity_request(&self, resource: &str) -> reqwest::RequestBuilder {
+        let mut query = vec![("resource", resource.to_string())];
+        // A client ID picks a user-assigned identity
+        if let Some(client_id) = &self.credentials.client_id {
+            query.push(("client_id", client_id.clone()));
+        }
+        match (
+            std::env::var("IDENTITY_ENDPOINT"),
+            std::env::var("IDENTITY_HEADER"),
+        ) {
+            (Ok(endpoint), Ok(header)) => {
+                query.push(("api-version", "2019-08-01".to_string()));
+                self.client
+                    .get(endpoint)
+                    .header("X-IDENTITY-HEADER", header)
+                    .query(&query)
+            }
+            _ => {
+                query.push(("api-version", "2018-02-01".to_string()));
+                self.client
+                    .get(IMDS_TOKEN_URL)
+                    .header("Metadata", "true")
+                    .query(&query)
+                    .timeout(Duration::from_secs(5))
+            }
+        }
+    }
+}
+
+/// Authenticated REST client for ARM and Azure DevOps
+pub(crate) struct AzureRest {
+    client: Client,
+    config: AzureConfig,
+    tokens: AzureTokens,
+}
+
+impl AzureRest {
+    pub fn new(config: AzureConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Azure client: {}", e)))?;
+        Ok(Self {
+            tokens: AzureTokens::new(client.clone(), AzureCredentials::from(&config)),
+            client,
+            config,
+        })
+    }
+
+    /// Token for `resource`
+    async fn token(&self, resource: &'static str) -> Result<String> {
+        self.tokens.token(resource).await
+    }
+
+    /// ARM call on `path` below the management endpoint, or on a full URL
+    pub async fn arm(
+        &self,
+        method: Method,
+        path: &str,
+        api_version: &str,
+        body: Option<&Value>,
+        what: &str,
+    ) -> Result<Value> {
+        let url = if path.starts_with("https://") {
+            path.to_string()
+        } else {
+            format!("{}{}", MANAGEMENT_URL, path)
+        };
+        let mut request = self
+            .client
+            .request(method, &url)
+            .bearer_auth(self.token(MANAGEMENT_URL).await?);
+        if !url.contains("api-version=") {
+            request = request.query(&[("api-version", api_version)]);
+        }
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Azure request failed: {}", e)))?;
+        let status = response.status();
+        if status == StatusCode::ACCEPTED {
+            return self.wait(response.headers(), what).await;
+        }
+        Self::json(response, &url, what).await
+    }
+
+    /// Every item of an ARM list, following `nextLink`
+    pub async fn list(&self, path: &str, api_version: &str, what: &str) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        let mut next = Some(path.to_string());
+        while let Some(url) = next {
+            let mut page = self.arm(Method::GET, &url, api_version, None, what).await?;
+            if let Some(Value::Array(values)) = page.get_mut("value").map(Value::take) {
+                items.extend(values);
+            }
+            next = page["nextLink"].as_str().map(str::to_string);
+        }
+        Ok(items)
+    }
+
+    /// Polls a long-running operation accepted with 202 until it ends
+    async fn wait(&self, headers: &HeaderMap, what: &str) -> Result<Value> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let interval = header("Retry-After")
+            .and_then(|s| s.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(POLL_INTERVAL);
+        // Azure-AsyncOperation reports a status; Location answers 202 until done
+        let (url, has_status) = match (header("Azure-AsyncOperation"), header("Location")) {
+            (Some(url), _) => (url, true),
+            (None, Some(url)) => (url, false),
+            (None, None) => return Ok(Value::Null),
+        };
+        let deadline = Instant::now() + OPERATION_TIMEOUT;
+        loop {
+            if Instant::now() > deadline {
+                return Err(Error::timeout_with_duration(
+                    format!("Timed out waiting to {}", what),
+                    OPERATION_TIMEOUT,
+                ));
+            }
+            tokio::time::sleep(interval).await;
+            let response = self
+                .client
+                .get(&url)
+                .bearer_auth(self.token(MANAGEMENT_URL).await?)
+                .send()
+                .await
+                .map_err(|e| Error::network(format!("Azure request failed: {}", e)))?;
+            if response.status() == StatusCode::ACCEPTED {
+                continue;
+            }
+            let body = Self::json(response, &url, what).await?;
+            if !has_status {
+                return Ok(body);
+            }
+            match body["status"].as_str().unwrap_or_default() {
+                "Succeeded" => return Ok(body),
+                "Failed" | "Canceled" => {
+                    return Err(Error::service(format!(
+                        "Failed to {}: {}",
+                        what,
+                        body["error"]["message"]
+                            .as_str()
+                            .unwrap_or(body["status"].as_str().unwrap_or_default())
+                    )))
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    /// Azure DevOps call on a URL below the organization or release endpoint
+    pub async fn devops(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&Value>,
+        what: &str,
+    ) -> Result<Value> {
+        let mut request = self.client.request(method.clone(), url);
+        request = match self.devops_pat() {
+            Some(pat) => request.header(
+                "Authorization",
+                format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD.encode(format!(":{}", pat))
+                ),
+            ),
+            None => request.bearer_auth(self.token(DEVOPS_RESOURCE).await?),
+        };
+        if !url.contains("api-version=") {
+            request = request.query(&[("api-version", DEVOPS_API_VERSION)]);
+        }
+        if let Some(body) = body {
+            // Work item changes are JSON Patch documents
+            let content_type = if method == Method::PATCH || body.is_array() {
+                "application/json-patch+json"
+            } else {
+                "application/json"
+            };
+            request = request
+                .header("Content-Type", content_type)
+                .body(body.to_string());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Azure DevOps request failed: {}", e)))?;
+        Self::json(response, url, what).await
+    }
+
+    /// Personal access token from the config or `AZURE_DEVOPS_EXT_PAT`
+    fn devops_pat(&self) -> Option<String> {
+        self.config
+            .devops_pat
+            .clone()
+            .or_else(|| std::env::var("AZURE_DEVOPS_EXT_PAT").ok())
+    }
+
+    /// Body of a finished response, or the error it reports
+    async fn json(response: reqwest::Response, url: &str, what: &str) -> Result<Value> {
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Err(Error::not_found_with_resource(
+                format!("Failed to {}: not found", what),
+                "azure",
+                url.split('?').next().unwrap_or(url),
+            ));
+        }
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            let body: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+            let message = body["error"]["message"]
+                .as_str()
+                .or_else(|| body["message"].as_str())
+                .unwrap_or(text.trim());
+            return Err(Error::api_with_status(
+                format!("Failed to {}: {}", what, message),
+                "azure",
+                status.as_u16(),
+            ));
+        }
+        if text.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&text)
+            .map_err(|e| Error::parsing(format!("Invalid Azure response: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn converts_keys_and_release_urls() {
+        assert_eq!(
+            snake_case_keys(&json!({"osDisk": {"diskSizeGB": 30, "managedDisk": null}})),
+            json!({"os_disk": {"disk_size_gb": 30, "managed_disk": null}})
+        );
+        assert_eq!(
+            release_base("https://dev.azure.com/contoso/"),
+            "https://vsrm.dev.azure.com/contoso"
+        );
+        assert_eq!(
+            release_base("https://contoso.visualstudio.com"),
+            "https://contoso.vsrm.visualstudio.com"
+        );
+        assert_eq!(segment("User Story"), "User%20Story");
+        assert_eq!(
+            lifetime(&json!({"expires_in": "86399"})),
+            Duration::from_secs(86399)
+        );
+    }
+}
@@ -0,0 +1,664 @@
+/// Monthly budget guardrails over actual cloud spend
+///
+/// Budgets cap month-to-date spend for a provider, optionally narrowed to an
+/// account and a cost allocation tag. A background scheduler re-evaluates spend
+/// from the providers' cost APIs (daily by default) and posts a warning to the
+/// budget's collaboration channels the first time each threshold is crossed in
+/// a month.
+use crate::cloud::drift::parse_provider;
+use crate::cloud::{CloudModule, CloudProvider, Spend, SpendFilter};
+use crate::collaboration::notify::{Notification, Notifier, Severity};
+use crate::collaboration::AttachmentField;
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Budget guardrail configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetsConfig {
+    /// BigQuery `project.dataset.table` of the Cloud Billing export, for GCP budgets
+    pub gcp_billing_table: Option<String>,
+    /// Hours between scheduled evaluations
+    pub check_interval_hours: u64,
+    /// Alert thresholds, in percent of the limit, for budgets that set none
+    pub default_thresholds: Vec<f64>,
+}
+
+impl Default for BudgetsConfig {
+    fn default() -> Self {
+        Self {
+            gcp_billing_table: std::env::var("GCP_BILLING_EXPORT_TABLE").ok(),
+            check_interval_hours: 24,
+            default_thresholds: vec![50.0, 80.0, 100.0],
+        }
+    }
+}
+
+/// A monthly spend limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Budget {
+    pub name: String,
+    pub provider: CloudProvider,
+    #[serde(default)]
+    pub filter: SpendFilter,
+    pub monthly_limit: f64,
+    pub currency: String,
+    /// Percentages of the limit that trigger a notification
+    pub thresholds: Vec<f64>,
+    /// Collaboration channels to notify
+    pub channels: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Budget health
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetHealth {
+    /// Below every threshold
+    Ok,
+    /// At least one threshold crossed
+    Warning,
+    /// Spend at or above the limit
+    Exceeded,
+    /// Spend could not be retrieved
+    Unknown,
+}
+
+/// Result of the latest evaluation of a budget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub budget: String,
+    /// Month evaluated, `YYYY-MM`
+    pub month: String,
+    pub spend: f64,
+    pub currency: String,
+    pub monthly_limit: f64,
+    pub percent_used: f64,
+    /// Month-end spend at the current daily rate
+    pub forecast: f64,
+    pub health: BudgetHealth,
+    /// Thresholds already notified this month
+    pub notified_thresholds: Vec<f64>,
+    pub evaluated_at: DateTime<Utc>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BudgetStore {
+    budgets: BTreeMap<String, Budget>,
+    status: BTreeMap<String, BudgetStatus>,
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|next| next.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(30)
+}
+
+/// Linear month-end forecast from spend through `today`
+pub fn forecast(spend: f64, today: NaiveDate) -> f64 {
+    spend / today.day() as f64 * days_in_month(today) as f64
+}
+
+/// Thresholds reached by `percent` that have not been notified yet, ascending
+pub fn newly_crossed(thresholds: &[f64], percent: f64, notified: &[f64]) -> Vec<f64> {
+    let mut crossed: Vec<f64> = thresholds
+        .iter()
+        .copied()
+        .filter(|t| percent >= *t && !notified.contains(t))
+        .collect();
+    crossed.sort_by(|a, b| a.total_cmp(b));
+    crossed
+}
+
+/// Budget guardrails over the cloud module's cost APIs
+pub struct BudgetManager {
+    cloud: Arc<CloudModule>,
+    notifier: Option<Arc<Notifier>>,
+    config: BudgetsConfig,
+    /// Where budgets are persisted; `None` keeps them in memory
+    path: Option<PathBuf>,
+    store: RwLock<BudgetStore>,
+}
+
+impl BudgetManager {
+    /// Open a manager, loading budgets from `path` if it exists
+    pub async fn open(
+        cloud: Arc<CloudModule>,
+        config: BudgetsConfig,
+        path: Option<PathBuf>,
+    ) -> Result<Self> {
+        let store = match &path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse budget store {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => BudgetStore::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read budget store: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => BudgetStore::default(),
+        };
+
+        Ok(Self {
+            cloud,
+            notifier: None,
+            config,
+            path,
+            store: RwLock::new(store),
+        })
+    }
+
+    /// Send threshold warnings through collaboration channels
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    async fn persist(&self, store: &BudgetStore) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create budget store directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(format!("Failed to write budget store: {}", e), temp.clone())
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace budget store: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Create or replace a budget by name
+    pub async fn set_budget(&self, mut budget: Budget) -> Result<Budget> {
+        if budget.name.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Budget name is required",
+                "name",
+            ));
+        }
+        if budget.monthly_limit.is_nan() || budget.monthly_limit <= 0.0 {
+            return Err(Error::validation_with_field(
+                "Monthly limit must be positive",
+                "monthly_limit",
+            ));
+        }
+        if budget.filter.tag_key.is_some() != budget.filter.tag_value.is_some() {
+            return Err(Error::validation_with_field(
+                "Tag filters need both tag_key and tag_value",
+                "tag_key",
+            ));
+        }
+        if budget.provider == CloudProvider::GCP && self.config.gcp_billing_table.is_none() {
+            return Err(Error::config_with_suggestion(
+                "GCP budgets need a Cloud Billing export",
+                "Set GCP_BILLING_EXPORT_TABLE to the BigQuery export table",
+            ));
+        }
+        if budget.thresholds.is_empty() {
+            budget.thresholds = self.config.default_thresholds.clone();
+        }
+        budget.thresholds.sort_by(|a, b| a.total_cmp(b));
+        budget.thresholds.dedup();
+        if let Some(notifier) = &self.notifier {
+            let known = notifier.channel_names();
+            if let Some(unknown) = budget
+                .channels
+                .iter()
+                .find(|c| !known.contains(&c.as_str()))
+            {
+                return Err(Error::not_found_with_resource(
+                    "Notification channel not found",
+                    "channel",
+                    unknown,
+                ));
+            }
+        }
+
+        let mut store = self.store.write().await;
+        if let Some(existing) = store.budgets.get(&budget.name) {
+            budget.created_at = existing.created_at;
+        }
+        // A changed limit or threshold set should alert afresh
+        store.status.remove(&budget.name);
+        store.budgets.insert(budget.name.clone(), budget.clone());
+        self.persist(&store).await?;
+        Ok(budget)
+    }
+
+    /// Delete a budget
+    pub async fn remove_budget(&self, name: &str) -> Result<()> {
+        let mut store = self.store.write().await;
+        if store.budgets.remove(name).is_none() {
+            return Err(Error::not_found_with_resource(
+                "Budget not found",
+                "budget",
+                name,
+            ));
+        }
+        store.status.remove(name);
+        self.persist(&store).await
+    }
+
+    /// Configured budgets
+    pub async fn list_budgets(&self) -> Vec<Budget> {
+        self.store.read().await.budgets.values().cloned().collect()
+    }
+
+    /// Month-to-date spend for a budget's scope
+    pub async fn month_to_date(&self, budget: &Budget, today: NaiveDate) -> Result<Spend> {
+        let start = today.with_day(1).unwrap_or(today);
+        let tomorrow = today + Duration::days(1);
+        match budget.provider {
+            CloudProvider::AWS => {
+                self.cloud
+                    .aws()?
+                    .spend(start, tomorrow, &budget.filter)
+                    .await
+            }
+            CloudProvider::Azure => {
+                self.cloud
+                    .azure()?
+                    .spend(start, today, &budget.filter)
+                    .await
+            }
+            CloudProvider::GCP => {
+                let table =
+                    self.config.gcp_billing_table.as_deref().ok_or_else(|| {
+                        Error::config("GCP billing export table is not configured")
+                    })?;
+                self.cloud
+                    .gcp()?
+                    .spend(table, start, tomorrow, &budget.filter)
+                    .await
+            }
+            CloudProvider::Hybrid => {
+                Err(Error::validation("Budgets must target a single provider"))
+            }
+        }
+    }
+
+    /// Evaluate every budget, notifying newly crossed thresholds
+    pub async fn evaluate_all(&self) -> Result<Vec<BudgetStatus>> {
+        let now = Utc::now();
+        let today = now.date_naive();
+        let month = today.format("%Y-%m").to_string();
+        let budgets = self.list_budgets().await;
+
+        let mut results = Vec::with_capacity(budgets.len());
+        for budget in budgets {
+            let previous = self.store.read().await.status.get(&budget.name).cloned();
+            let mut notified = previous
+                .filter(|p| p.month == month)
+                .map(|p| p.notified_thresholds)
+                .unwrap_or_default();
+
+            let status = match self.month_to_date(&budget, today).await {
+                Ok(spend) => {
+                    let percent = spend.amount / budget.monthly_limit * 100.0;
+                    let crossed = newly_crossed(&budget.thresholds, percent, &notified);
+                    let status = BudgetStatus {
+                        budget: budget.name.clone(),
+                        month: month.clone(),
+                        spend: spend.amount,
+                        currency: spend.currency,
+                        monthly_limit: budget.monthly_limit,
+                        percent_used: percent,
+                        forecast: forecast(spend.amount, today),
+                        health: if percent >= 100.0 {
+                            BudgetHealth::Exceeded
+                        } else if budget.thresholds.iter().any(|t| percent >= *t) {
+                            BudgetHealth::Warning
+                        } else {
+                            BudgetHealth::Ok
+                        },
+                        notified_thresholds: Vec::new(),
+                        evaluated_at: now,
+                        error: None,
+                    };
+                    if let Some(highest) = crossed.last() {
+                        if self.notify(&budget, &status, *highest).await {
+                            notified.extend(crossed);
+                        }
+                    }
+                    status
+                }
+                Err(e) => BudgetStatus {
+                    budget: budget.name.clone(),
+                    month: month.clone(),
+                    spend: 0.0,
+                    currency: budget.currency.clone(),
+                    monthly_limit: budget.monthly_limit,
+                    percent_used: 0.0,
+                    forecast: 0.0,
+                    health: BudgetHealth::Unknown,
+                    notified_thresholds: Vec::new(),
+                    evaluated_at: now,
+                    error: Some(e.to_string()),
+                },
+            };
+            let status = BudgetStatus {
+                notified_thresholds: notified,
+                ..status
+            };
+
+            let mut store = self.store.write().await;
+            // Skip budgets deleted while spend was being fetched
+            if store.budgets.contains_key(&budget.name) {
+                store.status.insert(budget.name.clone(), status.clone());
+            }
+            results.push(status);
+        }
+
+        let store = self.store.read().await;
+        self.persist(&store).await?;
+        Ok(results)
+    }
+
+    /// Post a threshold warning; returns whether any channel received it
+    async fn notify(&self, budget: &Budget, status: &BudgetStatus, threshold: f64) -> bool {
+        let Some(notifier) = &self.notifier else {
+            return false;
+        };
+        if budget.channels.is_empty() {
+            return false;
+        }
+        let notification = Notification {
+            title: format!("Budget {} reached {}%", budget.name, threshold),
+            text: format!(
+                "{:?} spend for {} is {:.2} {} of {:.2} ({:.1}%)",
+                budget.provider,
+                status.month,
+                status.spend,
+                status.currency,
+                budget.monthly_limit,
+                status.percent_used
+            ),
+            severity: if threshold >= 100.0 {
+                Severity::Critical
+            } else {
+                Severity::Warning
+            },
+            fields: vec![
+                AttachmentField {
+                    title: "Forecast".to_string(),
+                    value: format!("{:.2} {}", status.forecast, status.currency),
+                    short: true,
+                },
+                AttachmentField {
+                    title: "Remaining".to_string(),
+                    value: format!(
+                        "{:.2} {}",
+                        (budget.monthly_limit - status.spend).max(0.0),
+                        status.currency
+                    ),
+                    short: true,
+                },
+            ],
+        };
+        let failures = notifier.broadcast(&budget.channels, &notification).await;
+        for (channel, error) in &failures {
+            tracing::warn!(
+                "Budget {} notification to {} failed: {}",
+                budget.name,
+                channel,
+                error
+            );
+        }
+        failures.len() < budget.channels.len()
+    }
+
+    /// Evaluate budgets on the configured interval until the task is aborted
+    pub fn start_scheduler(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.check_interval_hours.max(1) * 3600);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match self.evaluate_all().await {
+                    Ok(statuses) => tracing::info!("Evaluated {} budgets", statuses.len()),
+                    Err(e) => tracing::warn!("Budget evaluation failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Latest status of one or all budgets
+    pub async fn status(&self, name: Option<&str>) -> Result<Vec<BudgetStatus>> {
+        let store = self.store.read().await;
+        match name {
+            Some(name) => {
+                if !store.budgets.contains_key(name) {
+                    return Err(Error::not_found_with_resource(
+                        "Budget not found",
+                        "budget",
+                        name,
+                    ));
+                }
+                Ok(store.status.get(name).cloned().into_iter().collect())
+            }
+            None => Ok(store.status.values().cloned().collect()),
+        }
+    }
+
+    /// Get tool definitions for budgets
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "budget_set",
+                "Create or replace a monthly spend budget for a cloud provider, account or tag",
+                "cloud",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string"},
+                        "provider": {"type": "string", "enum": ["aws", "azure", "gcp"]},
+                        "account": {"type": "string", "description": "AWS account, Azure subscription or GCP project id"},
+                        "tag_key": {"type": "string"},
+                        "tag_value": {"type": "string"},
+                        "monthly_limit": {"type": "number"},
+                        "currency": {"type": "string", "default": "USD"},
+                        "thresholds": {"type": "array", "items": {"type": "number"}, "description": "Percentages of the limit that trigger a warning (default 50, 80, 100)"},
+                        "channels": {"type": "array", "items": {"type": "string"}, "description": "Collaboration channels to notify"}
+                    },
+                    "required": ["name", "provider", "monthly_limit"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "budget_delete",
+                "Delete a spend budget",
+                "cloud",
+                json!({
+                    "type": "object",
+                    "properties": {"name": {"type": "string"}},
+                    "required": ["name"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "budget_status",
+                "Show month-to-date spend, forecast and threshold state of budgets",
+                "cloud",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "description": "Budget name (default: all)"},
+                        "refresh": {"type": "boolean", "default": false, "description": "Query cost APIs now instead of returning the last scheduled evaluation"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    /// Execute a budget tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let string = |key: &str| {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        match name {
+            "budget_set" => {
+                let budget_name = string("name")
+                    .ok_or_else(|| Error::validation_with_field("name is required", "name"))?;
+                let provider = parse_provider(&string("provider").ok_or_else(|| {
+                    Error::validation_with_field("provider is required", "provider")
+                })?)?;
+                let monthly_limit = parameters
+                    .get("monthly_limit")
+                    .and_then(|v| v.as_f64())
+                    .ok_or_else(|| {
+                        Error::validation_with_field("monthly_limit is required", "monthly_limit")
+                    })?;
+                let list = |key: &str| -> Vec<Value> {
+                    parameters
+                        .get(key)
+                        .and_then(|v| v.as_array())
+                        .cloned()
+                        .unwrap_or_default()
+                };
+
+                let budget = self
+                    .set_budget(Budget {
+                        name: budget_name,
+                        provider,
+                        filter: SpendFilter {
+                            account: string("account"),
+                            tag_key: string("tag_key"),
+                            tag_value: string("tag_value"),
+                        },
+                        monthly_limit,
+                        currency: string("currency").unwrap_or_else(|| "USD".to_string()),
+                        thresholds: list("thresholds")
+                            .iter()
+                            .filter_map(|t| t.as_f64())
+                            .collect(),
+                        channels: list("channels")
+                            .iter()
+                            .filter_map(|c| c.as_str().map(str::to_string))
+                            .collect(),
+                        created_at: Utc::now(),
+                    })
+                    .await?;
+                Ok(call_result(
+                    format!(
+                        "Budget {} set to {:.2} {} per month (alerts at {:?}%)",
+                        budget.name, budget.monthly_limit, budget.currency, budget.thresholds
+                    ),
+                    json!(budget),
+                ))
+            }
+            "budget_delete" => {
+                let budget_name = string("name")
+                    .ok_or_else(|| Error::validation_with_field("name is required", "name"))?;
+                self.remove_budget(&budget_name).await?;
+                Ok(call_result(
+                    format!("Deleted budget {}", budget_name),
+                    json!({ "deleted": budget_name }),
+                ))
+            }
+            "budget_status" => {
+                let budget_name = string("name");
+                if parameters
+                    .get("refresh")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false)
+                {
+                    self.evaluate_all().await?;
+                }
+                let statuses = self.status(budget_name.as_deref()).await?;
+                let text = if statuses.is_empty() {
+                    "No budget has been evaluated yet; call with refresh=true".to_string()
+                } else {
+                    statuses
+                        .iter()
+                        .map(|s| match &s.error {
+                            Some(error) => format!("{}: unknown ({})", s.budget, error),
+                            None => format!(
+                                "{}: {:.2}/{:.2} {} ({:.1}%, forecast {:.2}) {:?}",
+                                s.budget,
+                                s.spend,
+                                s.monthly_limit,
+                                s.currency,
+                                s.percent_used,
+                                s.forecast,
+                                s.health
+                            ),
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                Ok(call_result(text, json!({ "budgets": statuses })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "budget_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crosses_thresholds_once_and_forecasts() {
+        let thresholds = [50.0, 80.0, 100.0];
+        assert_eq!(newly_crossed(&thresholds, 85.0, &[]), vec![50.0, 80.0]);
+        assert_eq!(
+            newly_crossed(&thresholds, 85.0, &[50.0, 80.0]),
+            Vec::<f64>::new()
+        );
+        assert_eq!(
+            newly_crossed(&thresholds, 101.0, &[50.0, 80.0]),
+            vec![100.0]
+        );
+
+        let today = NaiveDate::from_ymd_opt(2024, 2, 10).unwrap();
+        assert_eq!(days_in_month(today), 29);
+        assert!((forecast(100.0, today) - 290.0).abs() < 1e-9);
+        assert_eq!(
+            days_in_month(NaiveDate::from_ymd_opt(2024, 12, 3).unwrap()),
+            31
+        );
+    }
+}
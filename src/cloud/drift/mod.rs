@@ -0,0 +1,635 @@
+/// Drift detection between live cloud inventory and Terraform state
+///
+/// Live resources come from the provider clients' `list_resources`; managed
+/// resources come from Terraform state. Resources are matched by provider id,
+/// falling back to name, and reported as unmanaged (live only), missing
+/// (destroyed out of band but still present in state) or drifted.
+use crate::cicd::{parse_terraform_state, CicdModule, TerraformStateResource};
+use crate::cloud::{CloudModule, CloudProvider, CloudResource};
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// How a Terraform resource type corresponds to inventoried cloud resources
+struct TypeMapping {
+    terraform_types: &'static [&'static str],
+    provider: CloudProvider,
+    cloud_type: &'static str,
+    /// State attributes holding the id the inventory reports, in preference order
+    id_attrs: &'static [&'static str],
+    name_attr: Option<&'static str>,
+    region_attr: Option<&'static str>,
+    tags_attrs: &'static [&'static str],
+}
+
+const MAPPINGS: &[TypeMapping] = &[
+    TypeMapping {
+        terraform_types: &["aws_instance"],
+        provider: CloudProvider::AWS,
+        cloud_type: "EC2::Instance",
+        id_attrs: &["id"],
+        name_attr: None,
+        region_attr: None,
+        tags_attrs: &["tags_all", "tags"],
+    },
+    TypeMapping {
+        terraform_types: &["aws_lambda_function"],
+        provider: CloudProvider::AWS,
+        cloud_type: "Lambda::Function",
+        id_attrs: &["arn"],
+        name_attr: Some("function_name"),
+        region_attr: None,
+        tags_attrs: &[],
+    },
+    TypeMapping {
+        terraform_types: &["aws_s3_bucket"],
+        provider: CloudProvider::AWS,
+        cloud_type: "S3::Bucket",
+        id_attrs: &["bucket", "id"],
+        name_attr: None,
+        region_attr: Some("region"),
+        tags_attrs: &["tags_all", "tags"],
+    },
+    TypeMapping {
+        terraform_types: &[
+            "azurerm_linux_virtual_machine",
+            "azurerm_windows_virtual_machine",
+            "azurerm_virtual_machine",
+        ],
+        provider: CloudProvider::Azure,
+        cloud_type: "Microsoft.Compute/virtualMachines",
+        id_attrs: &["id"],
+        name_attr: Some("name"),
+        region_attr: Some("location"),
+        tags_attrs: &["tags"],
+    },
+    TypeMapping {
+        terraform_types: &["azurerm_storage_account"],
+        provider: CloudProvider::Azure,
+        cloud_type: "Microsoft.Storage/storageAccounts",
+        id_attrs: &["id"],
+        name_attr: Some("name"),
+        region_attr: Some("location"),
+        tags_attrs: &["tags"],
+    },
+    TypeMapping {
+        terraform_types: &["google_compute_instance"],
+        provider: CloudProvider::GCP,
+        cloud_type: "compute.googleapis.com/Instance",
+        id_attrs: &["self_link"],
+        name_attr: Some("name"),
+        region_attr: Some("zone"),
+        tags_attrs: &["labels"],
+    },
+    TypeMapping {
+        terraform_types: &["google_cloud_run_service", "google_cloud_run_v2_service"],
+        provider: CloudProvider::GCP,
+        cloud_type: "run.googleapis.com/Service",
+        id_attrs: &["self_link"],
+        name_attr: Some("name"),
+        region_attr: Some("location"),
+        tags_attrs: &[],
+    },
+    TypeMapping {
+        terraform_types: &["google_storage_bucket"],
+        provider: CloudProvider::GCP,
+        cloud_type: "storage.googleapis.com/Bucket",
+        id_attrs: &["self_link"],
+        name_attr: Some("name"),
+        region_attr: Some("location"),
+        tags_attrs: &["labels"],
+    },
+];
+
+/// Tags the inventory adds itself and which are never in state
+const SYNTHETIC_TAGS: &[&str] = &[
+    "ResourceType",
+    "Runtime",
+    "Architecture",
+    "MachineType",
+    "StorageClass",
+];
+
+fn mapping_for(terraform_type: &str) -> Option<&'static TypeMapping> {
+    MAPPINGS
+        .iter()
+        .find(|m| m.terraform_types.contains(&terraform_type))
+}
+
+fn normalize_id(id: &str) -> String {
+    id.trim_end_matches('/').to_ascii_lowercase()
+}
+
+/// Comparable region: `East US` matches `eastus`, zone URLs match zone names
+fn normalize_region(region: &str) -> String {
+    region
+        .rsplit('/')
+        .next()
+        .unwrap_or(region)
+        .replace(' ', "")
+        .to_ascii_lowercase()
+}
+
+fn provider_tag(key: &str) -> bool {
+    SYNTHETIC_TAGS.contains(&key) || key.starts_with("aws:") || key.starts_with("goog-")
+}
+
+/// Parse a provider name as used in tool parameters
+pub fn parse_provider(name: &str) -> Result<CloudProvider> {
+    match name.to_ascii_lowercase().as_str() {
+        "aws" => Ok(CloudProvider::AWS),
+        "azure" => Ok(CloudProvider::Azure),
+        "gcp" | "google" => Ok(CloudProvider::GCP),
+        other => Err(Error::validation_with_field(
+            format!("Unknown cloud provider: {}", other),
+            "providers",
+        )),
+    }
+}
+
+/// Live resource with no counterpart in state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnmanagedResource {
+    pub id: String,
+    pub name: String,
+    pub resource_type: String,
+    pub provider: CloudProvider,
+    pub region: String,
+}
+
+/// State resource whose cloud resource no longer exists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingResource {
+    pub address: String,
+    pub terraform_type: String,
+    pub expected_id: Option<String>,
+}
+
+/// A single attribute that differs from state
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AttributeDrift {
+    /// Attribute path, e.g. `region` or `tags.Owner`
+    pub attribute: String,
+    /// Value recorded in state
+    pub expected: Option<String>,
+    /// Value observed in the cloud
+    pub actual: Option<String>,
+}
+
+/// Managed resource whose live attributes differ from state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftedResource {
+    pub address: String,
+    pub id: String,
+    pub changes: Vec<AttributeDrift>,
+}
+
+/// Provider that could not be inventoried
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedProvider {
+    pub provider: CloudProvider,
+    pub reason: String,
+}
+
+/// Drift report
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DriftReport {
+    pub scanned_providers: Vec<CloudProvider>,
+    pub skipped_providers: Vec<SkippedProvider>,
+    /// Number of state resources matched to a live resource
+    pub managed: usize,
+    pub unmanaged: Vec<UnmanagedResource>,
+    pub missing: Vec<MissingResource>,
+    pub drifted: Vec<DriftedResource>,
+    /// State addresses of types the inventory does not cover or of unscanned providers
+    pub unchecked: Vec<String>,
+}
+
+impl DriftReport {
+    /// Whether anything differs between state and the cloud
+    pub fn has_drift(&self) -> bool {
+        !self.unmanaged.is_empty() || !self.missing.is_empty() || !self.drifted.is_empty()
+    }
+}
+
+fn string_attr(attributes: &Value, key: &str) -> Option<String> {
+    attributes
+        .get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn attribute_drift(
+    mapping: &TypeMapping,
+    attributes: &Value,
+    live: &CloudResource,
+) -> Vec<AttributeDrift> {
+    let mut changes = Vec::new();
+
+    if let Some(expected) = mapping.name_attr.and_then(|a| string_attr(attributes, a)) {
+        if expected != live.name {
+            changes.push(AttributeDrift {
+                attribute: "name".to_string(),
+                expected: Some(expected),
+                actual: Some(live.name.clone()),
+            });
+        }
+    }
+
+    if let Some(expected) = mapping.region_attr.and_then(|a| string_attr(attributes, a)) {
+        if !live.region.is_empty() && normalize_region(&expected) != normalize_region(&live.region)
+        {
+            changes.push(AttributeDrift {
+                attribute: "region".to_string(),
+                expected: Some(expected),
+                actual: Some(live.region.clone()),
+            });
+        }
+    }
+
+    let state_tags = mapping
+        .tags_attrs
+        .iter()
+        .find_map(|a| attributes.get(*a).and_then(|t| t.as_object()));
+    if let Some(state_tags) = state_tags {
+        let mut keys: Vec<&String> = state_tags
+            .keys()
+            .chain(live.tags.keys())
+            .filter(|k| !provider_tag(k))
+            .collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let expected = state_tags
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+            let actual = live.tags.get(key).cloned();
+            if expected != actual {
+                changes.push(AttributeDrift {
+                    attribute: format!("tags.{}", key),
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+
+    changes
+}
+
+/// Compare state against an inventory covering the `scanned` providers
+pub fn compare(
+    state: &[TerraformStateResource],
+    live: &[CloudResource],
+    scanned: &[CloudProvider],
+) -> DriftReport {
+    let mut report = DriftReport {
+        scanned_providers: scanned.to_vec(),
+        ..Default::default()
+    };
+    let mut matched: HashSet<usize> = HashSet::new();
+
+    for resource in state {
+        let mapping = match mapping_for(&resource.resource_type) {
+            Some(mapping) if scanned.contains(&mapping.provider) => mapping,
+            _ => {
+                report.unchecked.push(resource.address.clone());
+                continue;
+            }
+        };
+        let expected_id = mapping
+            .id_attrs
+            .iter()
+            .find_map(|a| string_attr(&resource.attributes, a));
+        let expected_name = mapping
+            .name_attr
+            .and_then(|a| string_attr(&resource.attributes, a));
+
+        let candidates = || {
+            live.iter().enumerate().filter(|(i, r)| {
+                !matched.contains(i)
+                    && r.provider == mapping.provider
+                    && r.resource_type == mapping.cloud_type
+            })
+        };
+        let found = expected_id
+            .as_ref()
+            .and_then(|id| {
+                let id = normalize_id(id);
+                candidates().find(|(_, r)| normalize_id(&r.id) == id)
+            })
+            .or_else(|| {
+                // Ids differ in format between APIs for some types; names are unique per type
+                expected_name
+                    .as_ref()
+                    .and_then(|name| candidates().find(|(_, r)| &r.name == name))
+            })
+            .map(|(i, _)| i);
+
+        match found {
+            Some(index) => {
+                matched.insert(index);
+                report.managed += 1;
+                let changes = attribute_drift(mapping, &resource.attributes, &live[index]);
+                if !changes.is_empty() {
+                    report.drifted.push(DriftedResource {
+                        address: resource.address.clone(),
+                        id: live[index].id.clone(),
+                        changes,
+                    });
+                }
+            }
+            None => report.missing.push(MissingResource {
+                address: resource.address.clone(),
+                terraform_type: resource.resource_type.clone(),
+                expected_id,
+            }),
+        }
+    }
+
+    report.unmanaged = live
+        .iter()
+        .enumerate()
+        .filter(|(i, r)| !matched.contains(i) && scanned.contains(&r.provider))
+        .map(|(_, r)| UnmanagedResource {
+            id: r.id.clone(),
+            name: r.name.clone(),
+            resource_type: r.resource_type.clone(),
+            provider: r.provider.clone(),
+            region: r.region.clone(),
+        })
+        .collect();
+    report
+}
+
+/// Where to read Terraform state from
+#[derive(Debug, Clone)]
+pub enum StateSource {
+    /// `terraform show -json` in the configured working directory
+    Terraform,
+    /// A state file on disk
+    File(PathBuf),
+    /// State JSON supplied directly
+    Inline(Value),
+}
+
+/// Drift detector over the cloud and CI/CD modules
+pub struct DriftDetector {
+    cloud: Arc<CloudModule>,
+    cicd: Option<Arc<CicdModule>>,
+}
+
+impl DriftDetector {
+    /// Create a detector; state must be supplied as a file or inline
+    pub fn new(cloud: Arc<CloudModule>) -> Self {
+        Self { cloud, cicd: None }
+    }
+
+    /// Read state through the Terraform integration
+    pub fn with_cicd(mut self, cicd: Arc<CicdModule>) -> Self {
+        self.cicd = Some(cicd);
+        self
+    }
+
+    async fn load_state(&self, source: StateSource) -> Result<Vec<TerraformStateResource>> {
+        match source {
+            StateSource::Terraform => {
+                let cicd = self.cicd.as_ref().ok_or_else(|| {
+                    Error::config_with_suggestion(
+                        "Terraform integration is not available",
+                        "Pass state_path or state, or configure cicd.terraform",
+                    )
+                })?;
+                cicd.terraform_state().await
+            }
+            StateSource::File(path) => {
+                let content = tokio::fs::read_to_string(&path).await.map_err(|e| {
+                    Error::io_with_path(format!("Failed to read state file: {}", e), path.clone())
+                })?;
+                let state: Value = serde_json::from_str(&content).map_err(|e| {
+                    Error::parsing_with_format(format!("Invalid state file: {}", e), "json", None)
+                })?;
+                parse_terraform_state(&state)
+            }
+            StateSource::Inline(state) => parse_terraform_state(&state),
+        }
+    }
+
+    /// Inventory the requested providers, recording those that fail
+    async fn inventory(
+        &self,
+        providers: &[CloudProvider],
+    ) -> (Vec<CloudResource>, Vec<CloudProvider>, Vec<SkippedProvider>) {
+        let mut resources = Vec::new();
+        let mut scanned = Vec::new();
+        let mut skipped = Vec::new();
+
+        for provider in providers {
+            let listed = match provider {
+                CloudProvider::AWS => match self.cloud.aws() {
+                    Ok(client) => client.list_resources().await,
+                    Err(e) => Err(e),
+                },
+                CloudProvider::Azure => match self.cloud.azure() {
+                    Ok(client) => client.list_resources().await,
+                    Err(e) => Err(e),
+                },
+                CloudProvider::GCP => match self.cloud.gcp() {
+                    Ok(client) => client.list_resources().await,
+                    Err(e) => Err(e),
+                },
+                CloudProvider::Hybrid => continue,
+            };
+            match listed {
+                Ok(listed) => {
+                    resources.extend(listed);
+                    scanned.push(provider.clone());
+                }
+                Err(e) => skipped.push(SkippedProvider {
+                    provider: provider.clone(),
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        (resources, scanned, skipped)
+    }
+
+    /// Compare state against the live inventory of the given providers
+    pub async fn report(
+        &self,
+        source: StateSource,
+        providers: &[CloudProvider],
+    ) -> Result<DriftReport> {
+        let state = self.load_state(source).await?;
+        let (live, scanned, skipped) = self.inventory(providers).await;
+        if scanned.is_empty() {
+            return Err(Error::config_with_suggestion(
+                "No cloud provider could be inventoried",
+                "Configure AWS, Azure or GCP credentials for the cloud module",
+            ));
+        }
+        let mut report = compare(&state, &live, &scanned);
+        report.skipped_providers = skipped;
+        Ok(report)
+    }
+
+    /// Get tool definitions for drift detection
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "drift_report",
+            "Compare live cloud resources with Terraform state and report unmanaged, missing and drifted resources",
+            "cloud",
+            json!({
+                "type": "object",
+                "properties": {
+                    "state_path": {"type": "string", "description": "Path to a terraform.tfstate or show -json file; defaults to terraform show in the configured working directory"},
+                    "state": {"type": "object", "description": "State JSON supplied inline"},
+                    "providers": {
+                        "type": "array",
+                        "items": {"type": "string", "enum": ["aws", "azure", "gcp"]},
+                        "description": "Providers to inventory (default: all)"
+                    }
+                }
+            }),
+            None,
+        )
+        .read_only()]
+    }
+
+    /// Execute a drift tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "drift_report" => {
+                let source = if let Some(state) = parameters.get("state").filter(|s| !s.is_null()) {
+                    StateSource::Inline(state.clone())
+                } else if let Some(path) = parameters.get("state_path").and_then(|p| p.as_str()) {
+                    StateSource::File(PathBuf::from(path))
+                } else {
+                    StateSource::Terraform
+                };
+                let providers = match parameters.get("providers").and_then(|p| p.as_array()) {
+                    Some(names) => names
+                        .iter()
+                        .filter_map(|n| n.as_str())
+                        .map(parse_provider)
+                        .collect::<Result<Vec<_>>>()?,
+                    None => vec![CloudProvider::AWS, CloudProvider::Azure, CloudProvider::GCP],
+                };
+
+                let report = self.report(source, &providers).await?;
+                let text = if report.has_drift() {
+                    format!(
+                        "Drift detected: {} unmanaged, {} missing, {} drifted ({} managed resources matched)",
+                        report.unmanaged.len(),
+                        report.missing.len(),
+                        report.drifted.len(),
+                        report.managed
+                    )
+                } else {
+                    format!("No drift: {} managed resources match state", report.managed)
+                };
+                Ok(call_result(text, json!(report)))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "drift_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::ComplianceStatus;
+    use std::collections::HashMap;
+
+    fn live(id: &str, name: &str, resource_type: &str, tags: &[(&str, &str)]) -> CloudResource {
+        CloudResource {
+            id: id.to_string(),
+            name: name.to_string(),
+            resource_type: resource_type.to_string(),
+            provider: CloudProvider::AWS,
+            region: "us-east-1".to_string(),
+            tags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+            cost: None,
+            security_score: None,
+            compliance_status: ComplianceStatus {
+                score: 0.0,
+                violations: Vec::new(),
+                last_assessment: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn reports_unmanaged_missing_and_drifted() {
+        let state = parse_terraform_state(&json!({
+            "version": 4,
+            "resources": [
+                {
+                    "mode": "managed", "type": "aws_instance", "name": "web",
+                    "provider": "provider[\"registry.terraform.io/hashicorp/aws\"]",
+                    "instances": [{"index_key": 0, "attributes": {"id": "i-123", "tags_all": {"Name": "web", "Env": "prod"}}}]
+                },
+                {
+                    "mode": "managed", "type": "aws_s3_bucket", "name": "logs",
+                    "provider": "provider[\"registry.terraform.io/hashicorp/aws\"]",
+                    "instances": [{"attributes": {"id": "acme-logs", "bucket": "acme-logs"}}]
+                },
+                {
+                    "mode": "data", "type": "aws_ami", "name": "ubuntu",
+                    "instances": [{"attributes": {"id": "ami-1"}}]
+                },
+                {
+                    "mode": "managed", "type": "aws_iam_role", "name": "ci",
+                    "instances": [{"attributes": {"id": "ci"}}]
+                }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(state[0].address, "aws_instance.web[0]");
+        assert_eq!(state[0].provider, "registry.terraform.io/hashicorp/aws");
+
+        let inventory = vec![
+            live(
+                "i-123",
+                "web",
+                "EC2::Instance",
+                &[
+                    ("Name", "web"),
+                    ("Env", "staging"),
+                    ("ResourceType", "EC2Instance"),
+                ],
+            ),
+            live(
+                "arn:aws:lambda:us-east-1:1:function:adhoc",
+                "adhoc",
+                "Lambda::Function",
+                &[],
+            ),
+        ];
+        let report = compare(&state, &inventory, &[CloudProvider::AWS]);
+
+        assert_eq!(report.managed, 1);
+        assert_eq!(report.drifted[0].changes.len(), 1);
+        assert_eq!(report.drifted[0].changes[0].attribute, "tags.Env");
+        assert_eq!(
+            report.drifted[0].changes[0].actual.as_deref(),
+            Some("staging")
+        );
+        assert_eq!(report.missing[0].address, "aws_s3_bucket.logs");
+        assert_eq!(report.unmanaged[0].name, "adhoc");
+        assert_eq!(report.unchecked, vec!["aws_iam_role.ci".to_string()]);
+    }
+}
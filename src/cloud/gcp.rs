@@ -10,7 +10,8 @@
 use crate::cloud::{
     CloudProvider, CloudResource, ComplexityLevel, CostOptimization, CostRecommendation, GcpConfig,
     PaymentOption, RecommendationPriority, ReservedInstanceRecommendation, ReservedInstanceTerm,
-    SecurityAssessment, SecurityRecommendation, SecurityViolation, ViolationSeverity,
+    SecurityAssessment, SecurityRecommendation, SecurityViolation, Spend, SpendFilter,
+    ViolationSeverity,
 };
 use crate::error::{Error, Result};
 use crate::lifecycle::LifecycleManager;
@@ -1756,6 +1757,82 @@ impl GcpClient {
         })
     }
 
+    /// Actual spend between `start` and `end` (exclusive) from a BigQuery billing export
+    ///
+    /// GCP has no spend API; `billing_table` is the `project.dataset.table` of the
+    /// standard Cloud Billing export.
+    pub async fn spend(
+        &self,
+        billing_table: &str,
+        start: chrono::NaiveDate,
+        end: chrono::NaiveDate,
+        filter: &SpendFilter,
+    ) -> Result<Spend> {
+        let values = [
+            Some(billing_table),
+            filter.account.as_deref(),
+            filter.tag_key.as_deref(),
+            filter.tag_value.as_deref(),
+        ];
+        if values
+            .iter()
+            .flatten()
+            .any(|v| v.contains(['`', '"', '\\', '\n']))
+        {
+            return Err(Error::validation(
+                "Billing table and filters must not contain quotes, backticks or backslashes",
+            ));
+        }
+
+        let mut query = format!(
+            "SELECT SUM(cost) + SUM(IFNULL((SELECT SUM(c.amount) FROM UNNEST(credits) c), 0)) AS amount, \
+             ANY_VALUE(currency) AS currency FROM `{}` \
+             WHERE usage_start_time >= TIMESTAMP(\"{}\") AND usage_start_time < TIMESTAMP(\"{}\")",
+            billing_table, start, end
+        );
+        if let Some(account) = &filter.account {
+            query.push_str(&format!(" AND project.id = \"{}\"", account));
+        }
+        if let (Some(key), Some(value)) = (&filter.tag_key, &filter.tag_value) {
+            query.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM UNNEST(labels) l WHERE l.key = \"{}\" AND l.value = \"{}\")",
+                key, value
+            ));
+        }
+
+        let output = Command::new("bq")
+            .args([
+                &format!("--project_id={}", self.current_project),
+                "query",
+                "--use_legacy_sql=false",
+                "--format=json",
+                &query,
+            ])
+            .output()
+            .await
+            .map_err(|e| Error::internal(format!("Failed to execute bq command: {}", e)))?;
+        if !output.status.success() {
+            return Err(Error::service(format!(
+                "bq query failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        let rows: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| Error::parsing(format!("Failed to parse billing export query: {}", e)))?;
+        let row = &rows[0];
+        // bq renders numbers as strings; SUM over no rows is null
+        let amount = row["amount"]
+            .as_str()
+            .and_then(|a| a.parse().ok())
+            .or_else(|| row["amount"].as_f64())
+            .unwrap_or(0.0);
+        Ok(Spend {
+            amount,
+            currency: row["currency"].as_str().unwrap_or("USD").to_string(),
+        })
+    }
+
     /// Get current project
     pub fn get_current_project(&self) -> &str {
         &self.current_project
@@ -5,18 +5,28 @@
 use crate::error::{Error, Result};
 use crate::lifecycle::LifecycleManager;
 use crate::security::SecurityModule;
+use crate::tools::{call_result, ToolDefinition};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 pub mod aws;
 pub mod azure;
+pub mod budgets;
+pub mod drift;
 pub mod gcp;
+pub mod storage;
 
 use aws::AwsClient;
 use azure::AzureClient;
 use gcp::GcpClient;
 
+pub use budgets::{Budget, BudgetManager, BudgetStatus};
+pub use drift::{DriftDetector, DriftReport};
+pub use storage::{open_store, ObjectStore};
+
 /// Unified cloud configuration supporting multiple providers
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CloudConfig {
@@ -93,6 +103,9 @@ pub struct AzureConfig {
     pub cloudshell_enabled: bool,
     /// Azure DevOps organization URL
     pub devops_org_url: Option<String>,
+    /// Azure DevOps personal access token; Entra ID is used without one
+    #[serde(default)]
+    pub devops_pat: Option<String>,
     /// Azure Arc configuration
     pub arc_config: Option<ArcConfig>,
     /// Landing Zone configuration
@@ -308,6 +321,15 @@ pub struct TaggingPolicy {
     pub required_tags: Vec<String>,
     /// Tag value patterns
     pub tag_patterns: HashMap<String, String>,
+    /// Resource name pattern (naming convention)
+    #[serde(default)]
+    pub name_pattern: Option<String>,
+    /// Providers the policy covers; all when empty
+    #[serde(default)]
+    pub providers: Vec<CloudProvider>,
+    /// Resource types the policy covers, matched as substrings; all when empty
+    #[serde(default)]
+    pub resource_types: Vec<String>,
     /// Enforcement level
     pub enforcement: EnforcementLevel,
 }
@@ -390,6 +412,26 @@ pub struct ResourceCost {
     pub trend: CostTrend,
 }
 
+/// Filter for actual spend queries
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpendFilter {
+    /// Account, subscription or project id
+    pub account: Option<String>,
+    /// Cost allocation tag key
+    pub tag_key: Option<String>,
+    /// Cost allocation tag value
+    pub tag_value: Option<String>,
+}
+
+/// Actual spend over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spend {
+    /// Amount spent
+    pub amount: f64,
+    /// Currency
+    pub currency: String,
+}
+
 /// Cost trend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CostTrend {
@@ -433,6 +475,19 @@ pub enum ViolationSeverity {
     Info,
 }
 
+impl ViolationSeverity {
+    /// Points taken off a 100-point compliance score
+    pub fn penalty(&self) -> f64 {
+        match self {
+            Self::Critical => 20.0,
+            Self::High => 10.0,
+            Self::Medium => 5.0,
+            Self::Low => 2.0,
+            Self::Info => 0.0,
+        }
+    }
+}
+
 /// Unified cloud module supporting AWS, Azure, and GCP
 pub struct CloudModule {
     /// Cloud configuration
@@ -649,6 +704,333 @@ impl CloudModule {
     }
 }
 
+/// Resources from every configured provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Inventory {
+    pub resources: Vec<CloudResource>,
+    /// Providers whose listing failed, with the error
+    pub errors: HashMap<CloudProvider, String>,
+}
+
+/// Canonical tag key, so `CostCenter`, `cost_center` and `Cost Center` all
+/// read as `cost-center`
+pub fn normalize_tag_key(key: &str) -> String {
+    let mut normalized = String::new();
+    let mut previous: Option<char> = None;
+    for c in key.trim().chars() {
+        if matches!(c, '_' | ' ' | '-') {
+            if !normalized.is_empty() && !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            let boundary = c.is_uppercase()
+                && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit());
+            if boundary && !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+            normalized.extend(c.to_lowercase());
+        }
+        previous = Some(c);
+    }
+    normalized.trim_end_matches('-').to_string()
+}
+
+/// Tags with canonical keys and trimmed values; on a key collision the first
+/// non-empty value in key order wins
+pub fn normalize_tags(tags: &HashMap<String, String>) -> HashMap<String, String> {
+    let sorted: BTreeMap<&String, &String> = tags.iter().collect();
+    let mut normalized: HashMap<String, String> = HashMap::new();
+    for (key, value) in sorted {
+        let value = value.trim();
+        let entry = normalized.entry(normalize_tag_key(key)).or_default();
+        if entry.is_empty() {
+            *entry = value.to_string();
+        }
+    }
+    normalized
+}
+
+/// Tagging policy with its patterns compiled
+struct CompiledPolicy {
+    policy: TaggingPolicy,
+    /// Required tags as written and normalized
+    required: Vec<(String, String)>,
+    patterns: Vec<(String, String, Regex)>,
+    name: Option<Regex>,
+}
+
+/// Regex that must match a whole value
+fn full_match(pattern: &str, field: &str) -> Result<Regex> {
+    Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| {
+        Error::validation_with_field(format!("Invalid pattern {}: {}", pattern, e), field)
+    })
+}
+
+impl CompiledPolicy {
+    fn new(policy: TaggingPolicy) -> Result<Self> {
+        let patterns = policy
+            .tag_patterns
+            .iter()
+            .map(|(tag, pattern)| {
+                Ok((
+                    normalize_tag_key(tag),
+                    pattern.clone(),
+                    full_match(pattern, "tag_patterns")?,
+                ))
+            })
+            .collect::<Result<_>>()?;
+        let name = match &policy.name_pattern {
+            Some(pattern) => Some(full_match(pattern, "name_pattern")?),
+            None => None,
+        };
+        Ok(Self {
+            required: policy
+                .required_tags
+                .iter()
+                .map(|t| (t.clone(), normalize_tag_key(t)))
+                .collect(),
+            patterns,
+            name,
+            policy,
+        })
+    }
+
+    fn applies(&self, resource: &CloudResource) -> bool {
+        let resource_type = resource.resource_type.to_ascii_lowercase();
+        (self.policy.providers.is_empty() || self.policy.providers.contains(&resource.provider))
+            && (self.policy.resource_types.is_empty()
+                || self
+                    .policy
+                    .resource_types
+                    .iter()
+                    .any(|t| resource_type.contains(&t.to_ascii_lowercase())))
+    }
+
+    /// Violations of a resource whose tags are already normalized
+    fn violations(&self, resource: &CloudResource) -> Vec<ComplianceViolation> {
+        if !self.applies(resource) {
+            return Vec::new();
+        }
+        let severity = match self.policy.enforcement {
+            EnforcementLevel::Advisory => ViolationSeverity::Low,
+            EnforcementLevel::Mandatory => ViolationSeverity::Medium,
+            EnforcementLevel::Deny => ViolationSeverity::High,
+        };
+        let violation =
+            |rule_id: &str, description: String, remediation: String| ComplianceViolation {
+                rule_id: rule_id.to_string(),
+                severity: severity.clone(),
+                description: format!("{} (policy {})", description, self.policy.name),
+                remediation,
+            };
+        let mut violations = Vec::new();
+        for (tag, key) in &self.required {
+            if resource.tags.get(key).is_none_or(|v| v.is_empty()) {
+                violations.push(violation(
+                    "TAG-001",
+                    format!("Missing required tag {}", tag),
+                    format!("Tag {} with {}", resource.name, tag),
+                ));
+            }
+        }
+        for (key, pattern, regex) in &self.patterns {
+            match resource.tags.get(key) {
+                Some(value) if !value.is_empty() && !regex.is_match(value) => {
+                    violations.push(violation(
+                        "TAG-002",
+                        format!("Tag {}={} does not match {}", key, value, pattern),
+                        format!(
+                            "Set {} on {} to a value matching {}",
+                            key, resource.name, pattern
+                        ),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        if let Some(name) = &self.name {
+            if !name.is_match(&resource.name) {
+                violations.push(violation(
+                    "TAG-003",
+                    format!(
+                        "Name {} does not follow the naming convention",
+                        resource.name
+                    ),
+                    format!(
+                        "Rename to match {}",
+                        self.policy.name_pattern.as_deref().unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+        violations
+    }
+}
+
+/// Cross-cloud inventory with tagging policy checks
+///
+/// Lists resources from every configured provider at once, normalizes their
+/// tag keys and checks them against `governance.tagging_policies`. Policy
+/// violations are added to each resource's compliance status, next to the
+/// provider's own findings, and lower its score on the same scale.
+pub struct CloudManager {
+    cloud: Arc<CloudModule>,
+    policies: Vec<CompiledPolicy>,
+}
+
+impl CloudManager {
+    pub fn new(cloud: Arc<CloudModule>) -> Result<Self> {
+        let policies = cloud
+            .get_config()
+            .governance
+            .tagging_policies
+            .iter()
+            .cloned()
+            .map(CompiledPolicy::new)
+            .collect::<Result<_>>()?;
+        Ok(Self { cloud, policies })
+    }
+
+    /// Resources from all configured providers, listed concurrently and
+    /// checked against the tagging policies
+    pub async fn inventory(&self) -> Result<Inventory> {
+        let config = self.cloud.get_config();
+        if config.aws.is_none() && config.azure.is_none() && config.gcp.is_none() {
+            return Err(Error::config_with_suggestion(
+                "No cloud provider is configured",
+                "Configure cloud.aws, cloud.azure or cloud.gcp",
+            ));
+        }
+        let (aws, azure, gcp) = tokio::join!(
+            async { self.cloud.aws()?.list_resources().await },
+            async { self.cloud.azure()?.list_resources().await },
+            async { self.cloud.gcp()?.list_resources().await },
+        );
+        let mut inventory = Inventory {
+            resources: Vec::new(),
+            errors: HashMap::new(),
+        };
+        for (provider, configured, listed) in [
+            (CloudProvider::AWS, config.aws.is_some(), aws),
+            (CloudProvider::Azure, config.azure.is_some(), azure),
+            (CloudProvider::GCP, config.gcp.is_some(), gcp),
+        ] {
+            match listed {
+                Ok(resources) => inventory.resources.extend(resources),
+                Err(e) if configured => {
+                    inventory.errors.insert(provider, e.to_string());
+                }
+                Err(_) => {}
+            }
+        }
+        for resource in &mut inventory.resources {
+            self.evaluate(resource);
+        }
+        Ok(inventory)
+    }
+
+    /// Normalize a resource's tags and add tagging policy violations
+    pub fn evaluate(&self, resource: &mut CloudResource) {
+        resource.tags = normalize_tags(&resource.tags);
+        let violations: Vec<ComplianceViolation> = self
+            .policies
+            .iter()
+            .flat_map(|p| p.violations(resource))
+            .collect();
+        let status = &mut resource.compliance_status;
+        status.score =
+            (status.score - violations.iter().map(|v| v.severity.penalty()).sum::<f64>()).max(0.0);
+        status.violations.extend(violations);
+        status.last_assessment = chrono::Utc::now().to_rfc3339();
+    }
+
+    /// Get tool definitions for the cross-cloud inventory
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "cloud_inventory",
+            "List resources across AWS, Azure and GCP with normalized tags and tagging policy violations",
+            "cloud",
+            json!({
+                "type": "object",
+                "properties": {
+                    "provider": {"type": "string", "enum": ["aws", "azure", "gcp"], "description": "Only this provider"},
+                    "resource_type": {"type": "string", "description": "Only resource types containing this text, e.g. EC2 or virtualMachines"},
+                    "violations_only": {"type": "boolean", "description": "Only resources with tagging policy violations", "default": false}
+                }
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a cross-cloud inventory tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "cloud_inventory" => {
+                let provider = parameters
+                    .get("provider")
+                    .and_then(|p| p.as_str())
+                    .map(drift::parse_provider)
+                    .transpose()?;
+                let resource_type = parameters
+                    .get("resource_type")
+                    .and_then(|t| t.as_str())
+                    .map(str::to_ascii_lowercase);
+                let violations_only = parameters
+                    .get("violations_only")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+
+                let mut inventory = self.inventory().await?;
+                let tagging = |r: &CloudResource| {
+                    r.compliance_status
+                        .violations
+                        .iter()
+                        .filter(|v| v.rule_id.starts_with("TAG-"))
+                        .count()
+                };
+                inventory.resources.retain(|r| {
+                    provider.as_ref().is_none_or(|p| &r.provider == p)
+                        && resource_type
+                            .as_ref()
+                            .is_none_or(|t| r.resource_type.to_ascii_lowercase().contains(t))
+                        && (!violations_only || tagging(r) > 0)
+                });
+                let mut by_rule: BTreeMap<&str, usize> = BTreeMap::new();
+                for violation in inventory
+                    .resources
+                    .iter()
+                    .flat_map(|r| &r.compliance_status.violations)
+                    .filter(|v| v.rule_id.starts_with("TAG-"))
+                {
+                    *by_rule.entry(violation.rule_id.as_str()).or_default() += 1;
+                }
+                let mut text = format!(
+                    "{} resources, {} with tagging policy violations",
+                    inventory.resources.len(),
+                    inventory
+                        .resources
+                        .iter()
+                        .filter(|r| tagging(r) > 0)
+                        .count()
+                );
+                for (provider, error) in &inventory.errors {
+                    text.push_str(&format!("\n{:?} unavailable: {}", provider, error));
+                }
+                let by_rule = json!(by_rule);
+                Ok(call_result(
+                    text,
+                    json!({ "inventory": inventory, "tagging_violations": by_rule }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "cloud_tool",
+                name,
+            )),
+        }
+    }
+}
+
 /// Security assessment result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityAssessment {
@@ -871,3 +1253,66 @@ impl Default for DataGovernanceConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockTransport;
+
+    #[tokio::test]
+    async fn normalizes_tags_and_reports_policy_violations() {
+        let mut config = CloudConfig::default();
+        config.governance.tagging_policies = vec![TaggingPolicy {
+            name: "prod-compute".to_string(),
+            required_tags: vec!["CostCenter".to_string(), "Owner".to_string()],
+            tag_patterns: HashMap::from([("environment".to_string(), "prod|staging".to_string())]),
+            name_pattern: Some("[a-z0-9-]+".to_string()),
+            providers: vec![CloudProvider::AWS],
+            resource_types: vec!["ec2".to_string()],
+            enforcement: EnforcementLevel::Mandatory,
+        }];
+        let lifecycle = Arc::new(LifecycleManager::new(Box::new(MockTransport::new())));
+        let manager = CloudManager::new(Arc::new(CloudModule::new(config, lifecycle))).unwrap();
+        assert!(manager.inventory().await.is_err());
+
+        let mut resource = CloudResource {
+            id: "i-0abc".to_string(),
+            name: "Web_1".to_string(),
+            resource_type: "EC2::Instance".to_string(),
+            provider: CloudProvider::AWS,
+            region: "eu-west-1".to_string(),
+            tags: HashMap::from([
+                ("cost_center".to_string(), " 4100 ".to_string()),
+                ("Environment".to_string(), "dev".to_string()),
+            ]),
+            cost: None,
+            security_score: None,
+            compliance_status: ComplianceStatus {
+                score: 100.0,
+                violations: Vec::new(),
+                last_assessment: String::new(),
+            },
+        };
+        let mut bucket = resource.clone();
+        bucket.resource_type = "S3::Bucket".to_string();
+
+        manager.evaluate(&mut resource);
+        assert_eq!(resource.tags["cost-center"], "4100");
+        let rules: Vec<&str> = resource
+            .compliance_status
+            .violations
+            .iter()
+            .map(|v| v.rule_id.as_str())
+            .collect();
+        assert_eq!(rules, ["TAG-001", "TAG-002", "TAG-003"]);
+        assert!(resource.compliance_status.violations[0]
+            .description
+            .contains("Owner"));
+        assert_eq!(resource.compliance_status.score, 85.0);
+
+        manager.evaluate(&mut bucket);
+        assert!(bucket.compliance_status.violations.is_empty());
+        assert_eq!(normalize_tag_key("ResourceType"), "resource-type");
+        assert_eq!(normalize_tag_key("Cost Center"), "cost-center");
+    }
+}
@@ -0,0 +1,425 @@
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::process::Command;
+
+/// An object in a store
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// Trait for object storage backends
+///
+/// Keys are `/`-separated relative paths. File transfers default to buffering
+/// through `put`/`get`; backends that can stream override them.
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<()>;
+    async fn get(&self, key: &str) -> Result<Vec<u8>>;
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>>;
+    async fn delete(&self, key: &str) -> Result<()>;
+    /// URL of the object, e.g. `s3://bucket/key`
+    fn url(&self, key: &str) -> String;
+
+    async fn put_file(&self, key: &str, path: &Path) -> Result<()> {
+        let data = tokio::fs::read(path).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to read {}: {}", key, e), path.to_path_buf())
+        })?;
+        self.put(key, &data).await
+    }
+
+    async fn get_file(&self, key: &str, path: &Path) -> Result<()> {
+        let data = self.get(key).await?;
+        tokio::fs::write(path, data).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to write {}: {}", key, e),
+                path.to_path_buf(),
+            )
+        })
+    }
+}
+
+/// Reject keys that could escape the store's root
+pub fn validate_key(key: &str) -> Result<()> {
+    if key.is_empty()
+        || key.starts_with('/')
+        || key
+            .split('/')
+            .any(|part| part.is_empty() || part == "." || part == "..")
+        || key.chars().any(|c| c.is_control() || c == '\\')
+    {
+        return Err(Error::validation_with_field(
+            format!("Invalid object key: {}", key),
+            "key",
+        ));
+    }
+    Ok(())
+}
+
+/// Open a store from a URL: `file:///var/lib/artifacts` or `s3://bucket/prefix`
+///
+/// S3 stores honour `AWS_PROFILE`, `AWS_REGION` and, for S3-compatible
+/// services such as MinIO, `S3_ENDPOINT_URL`.
+pub fn open_store(url: &str) -> Result<Arc<dyn ObjectStore>> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return Ok(Arc::new(LocalObjectStore::new(path)));
+    }
+    if let Some(rest) = url.strip_prefix("s3://") {
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        let mut store = S3ObjectStore::new(bucket, prefix)?;
+        store.profile = std::env::var("AWS_PROFILE").ok();
+        store.region = std::env::var("AWS_REGION").ok();
+        store.endpoint_url = std::env::var("S3_ENDPOINT_URL").ok();
+        return Ok(Arc::new(store));
+    }
+    Err(Error::config_with_suggestion(
+        format!("Unsupported object store URL: {}", url),
+        "Use file:///path or s3://bucket/prefix",
+    ))
+}
+
+/// Objects as files under a local directory
+pub struct LocalObjectStore {
+    root: PathBuf,
+}
+
+impl LocalObjectStore {
+    /// Create a store rooted at `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl ObjectStore for LocalObjectStore {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("part");
+        tokio::fs::write(&temp, data).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to write object: {}", e), temp.clone())
+        })?;
+        tokio::fs::rename(&temp, &path)
+            .await
+            .map_err(|e| Error::io_with_path(format!("Failed to store object: {}", e), path))
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let path = self.path(key)?;
+        tokio::fs::read(&path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                Error::not_found_with_resource("Object not found", "object", key)
+            }
+            _ => Error::io_with_path(format!("Failed to read object: {}", e), path),
+        })
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>> {
+        let mut objects = Vec::new();
+        let mut pending = vec![self.root.clone()];
+        while let Some(dir) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to list objects: {}", e),
+                        dir,
+                    ))
+                }
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let Ok(metadata) = entry.metadata().await else {
+                    continue;
+                };
+                let path = entry.path();
+                if metadata.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let Ok(relative) = path.strip_prefix(&self.root) else {
+                    continue;
+                };
+                let key = relative.to_string_lossy().replace('\\', "/");
+                if key.starts_with(prefix) && !key.ends_with(".part") {
+                    objects.push(ObjectInfo {
+                        key,
+                        size: metadata.len(),
+                        last_modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+                    });
+                }
+            }
+        }
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path(key)?;
+        tokio::fs::remove_file(&path)
+            .await
+            .map_err(|e| Error::io_with_path(format!("Failed to delete object: {}", e), path))
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("file://{}", self.root.join(key).display())
+    }
+
+    async fn put_file(&self, key: &str, path: &Path) -> Result<()> {
+        let target = self.path(key)?;
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        tokio::fs::copy(path, &target).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to store object: {}", e), path.to_path_buf())
+        })?;
+        Ok(())
+    }
+}
+
+/// Objects in an S3 (or S3-compatible) bucket through the AWS CLI
+pub struct S3ObjectStore {
+    bucket: String,
+    prefix: String,
+    pub profile: Option<String>,
+    pub region: Option<String>,
+    pub endpoint_url: Option<String>,
+}
+
+impl S3ObjectStore {
+    /// Create a store for `bucket`, keeping objects under `prefix`
+    pub fn new(bucket: &str, prefix: &str) -> Result<Self> {
+        if bucket.len() < 3
+            || !bucket
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+        {
+            return Err(Error::validation_with_field(
+                format!("Invalid bucket name: {}", bucket),
+                "bucket",
+            ));
+        }
+        let prefix = prefix.trim_matches('/');
+        if !prefix.is_empty() {
+            validate_key(prefix)?;
+        }
+        Ok(Self {
+            bucket: bucket.to_string(),
+            prefix: prefix.to_string(),
+            profile: None,
+            region: None,
+            endpoint_url: None,
+        })
+    }
+
+    fn full_key(&self, key: &str) -> Result<String> {
+        validate_key(key)?;
+        Ok(match self.prefix.is_empty() {
+            true => key.to_string(),
+            false => format!("{}/{}", self.prefix, key),
+        })
+    }
+
+    async fn aws(&self, args: &[&str]) -> Result<String> {
+        let mut cmd = Command::new("aws");
+        if let Some(region) = &self.region {
+            cmd.args(["--region", region]);
+        }
+        if let Some(profile) = &self.profile {
+            cmd.args(["--profile", profile]);
+        }
+        if let Some(endpoint) = &self.endpoint_url {
+            cmd.args(["--endpoint-url", endpoint]);
+        }
+        let output = cmd
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| Error::internal(format!("Failed to execute AWS command: {}", e)))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("NoSuchKey") || stderr.contains("Not Found") {
+                return Err(Error::not_found_with_resource(
+                    "Object not found",
+                    "object",
+                    args.iter().find(|a| a.contains('/')).copied().unwrap_or(""),
+                ));
+            }
+            return Err(Error::service(format!("AWS command failed: {}", stderr)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+#[async_trait]
+impl ObjectStore for S3ObjectStore {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        let temp = tempfile::NamedTempFile::new()
+            .map_err(|e| Error::internal(format!("Failed to create temp file: {}", e)))?;
+        tokio::fs::write(temp.path(), data).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to write temp file: {}", e),
+                temp.path().into(),
+            )
+        })?;
+        self.put_file(key, temp.path()).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let temp = tempfile::NamedTempFile::new()
+            .map_err(|e| Error::internal(format!("Failed to create temp file: {}", e)))?;
+        self.get_file(key, temp.path()).await?;
+        tokio::fs::read(temp.path()).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to read temp file: {}", e),
+                temp.path().into(),
+            )
+        })
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>> {
+        let full_prefix = match self.prefix.is_empty() {
+            true => prefix.to_string(),
+            false => format!("{}/{}", self.prefix, prefix),
+        };
+        let output = self
+            .aws(&[
+                "s3api",
+                "list-objects-v2",
+                "--bucket",
+                &self.bucket,
+                "--prefix",
+                &full_prefix,
+                "--output",
+                "json",
+            ])
+            .await?;
+        // An empty listing prints nothing at all
+        if output.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let listing: serde_json::Value = serde_json::from_str(&output)
+            .map_err(|e| Error::parsing(format!("Failed to parse S3 listing: {}", e)))?;
+        let strip = match self.prefix.is_empty() {
+            true => String::new(),
+            false => format!("{}/", self.prefix),
+        };
+        Ok(listing["Contents"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|object| {
+                Some(ObjectInfo {
+                    key: object["Key"].as_str()?.strip_prefix(&strip)?.to_string(),
+                    size: object["Size"].as_u64().unwrap_or(0),
+                    last_modified: object["LastModified"]
+                        .as_str()
+                        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                        .map(|t| t.with_timezone(&Utc)),
+                })
+            })
+            .collect())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let key = self.full_key(key)?;
+        self.aws(&[
+            "s3api",
+            "delete-object",
+            "--bucket",
+            &self.bucket,
+            "--key",
+            &key,
+        ])
+        .await?;
+        Ok(())
+    }
+
+    fn url(&self, key: &str) -> String {
+        match self.prefix.is_empty() {
+            true => format!("s3://{}/{}", self.bucket, key),
+            false => format!("s3://{}/{}/{}", self.bucket, self.prefix, key),
+        }
+    }
+
+    async fn put_file(&self, key: &str, path: &Path) -> Result<()> {
+        let key = self.full_key(key)?;
+        let body = path.to_string_lossy();
+        self.aws(&[
+            "s3api",
+            "put-object",
+            "--bucket",
+            &self.bucket,
+            "--key",
+            &key,
+            "--body",
+            &body,
+        ])
+        .await?;
+        Ok(())
+    }
+
+    async fn get_file(&self, key: &str, path: &Path) -> Result<()> {
+        let key = self.full_key(key)?;
+        let target = path.to_string_lossy();
+        self.aws(&[
+            "s3api",
+            "get-object",
+            "--bucket",
+            &self.bucket,
+            "--key",
+            &key,
+            &target,
+        ])
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn local_store_round_trips_and_rejects_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open_store(&format!("file://{}", dir.path().display())).unwrap();
+        store
+            .put("builds/app/1.0/app.tar.gz", b"payload")
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get("builds/app/1.0/app.tar.gz").await.unwrap(),
+            b"payload"
+        );
+
+        let listed = store.list("builds/app/").await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].size, 7);
+        assert!(store.put("../escape", b"x").await.is_err());
+        assert!(S3ObjectStore::new("Bad_Bucket", "").is_err());
+    }
+}
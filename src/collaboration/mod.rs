@@ -4,6 +4,12 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 
+pub mod n8n;
+pub mod notify;
+
+pub use n8n::{N8n, N8nConfig};
+pub use notify::{ChannelKind, Notification, NotificationChannel, Notifier, Severity};
+
 /// Collaboration module
 pub struct CollaborationModule {
     /// Lifecycle manager
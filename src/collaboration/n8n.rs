@@ -0,0 +1,664 @@
+//! n8n workflows and executions
+//!
+//! Workflows and executions are read through n8n's public REST API with an
+//! API key. The API cannot start a workflow, so `trigger` calls the
+//! workflow's Webhook trigger node the way any other client would, which
+//! requires the workflow to be active and to start with a Webhook node.
+
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const WEBHOOK_NODE: &str = "n8n-nodes-base.webhook";
+
+/// Output items of the last node kept in execution results
+const OUTPUT_ITEMS: usize = 20;
+
+/// n8n instance, under `collaboration.n8n`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct N8nConfig {
+    /// Editor URL, e.g. `http://n8n:5678`
+    pub url: String,
+    /// Key from Settings > n8n API
+    pub api_key: String,
+    /// Base of webhook URLs when it differs from `url` (n8n's `WEBHOOK_URL`)
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+impl N8nConfig {
+    /// n8n from `N8N_URL`, `N8N_API_KEY` and `N8N_WEBHOOK_URL`, if the URL and key are set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            url: std::env::var("N8N_URL").ok()?,
+            api_key: std::env::var("N8N_API_KEY").ok()?,
+            webhook_url: std::env::var("N8N_WEBHOOK_URL").ok(),
+        })
+    }
+}
+
+/// A workflow and how it can be started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workflow {
+    pub id: String,
+    pub name: String,
+    pub active: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Types of the trigger nodes, without the `n8n-nodes-base.` prefix
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<String>,
+    /// Method and path of the Webhook trigger, if it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<(String, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Workflow {
+    fn from_api(workflow: &Value) -> Self {
+        let nodes = workflow["nodes"].as_array().cloned().unwrap_or_default();
+        let webhook = nodes
+            .iter()
+            .find(|n| n["type"] == WEBHOOK_NODE && n["disabled"] != true)
+            .and_then(|n| {
+                let path = n["parameters"]["path"].as_str()?.trim_matches('/');
+                let method = n["parameters"]["httpMethod"].as_str().unwrap_or("GET");
+                Some((method.to_uppercase(), path.to_string()))
+            });
+        Self {
+            id: id_string(&workflow["id"]),
+            name: workflow["name"].as_str().unwrap_or_default().to_string(),
+            active: workflow["active"].as_bool().unwrap_or(false),
+            tags: workflow["tags"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|t| t["name"].as_str().map(str::to_string))
+                .collect(),
+            triggers: nodes
+                .iter()
+                .filter_map(|n| n["type"].as_str())
+                .filter(|t| t.ends_with("Trigger") || *t == WEBHOOK_NODE)
+                .map(|t| t.trim_start_matches("n8n-nodes-base.").to_string())
+                .collect(),
+            webhook,
+            updated_at: workflow["updatedAt"].as_str().and_then(|t| t.parse().ok()),
+        }
+    }
+}
+
+/// Ids are strings in current n8n and numbers in older releases
+fn id_string(id: &Value) -> String {
+    match id {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// One run of a workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Execution {
+    pub id: String,
+    pub workflow_id: String,
+    /// success, error, crashed, running, waiting or canceled
+    pub status: String,
+    /// How it was started: webhook, trigger, manual, retry...
+    pub mode: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stopped_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Node the error was raised in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_node: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_node: Option<String>,
+    /// JSON of the last node's first output items, when the data was requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output: Vec<Value>,
+}
+
+impl Execution {
+    fn from_api(execution: &Value) -> Self {
+        let result = &execution["data"]["resultData"];
+        let error = &result["error"];
+        let last_node = result["lastNodeExecuted"].as_str().map(str::to_string);
+        let output = last_node
+            .as_ref()
+            .and_then(|node| result["runData"][node].as_array()?.last().cloned())
+            .and_then(|run| run["data"]["main"][0].as_array().cloned())
+            .unwrap_or_default()
+            .into_iter()
+            .take(OUTPUT_ITEMS)
+            .map(|item| item["json"].clone())
+            .collect();
+        let status = execution["status"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                // Releases before 1.0 only report `finished`
+                match (execution["finished"].as_bool(), error.is_null()) {
+                    (Some(true), _) => "success",
+                    (_, false) => "error",
+                    _ => "running",
+                }
+                .to_string()
+            });
+        Self {
+            id: id_string(&execution["id"]),
+            workflow_id: id_string(&execution["workflowId"]),
+            status,
+            mode: execution["mode"].as_str().unwrap_or_default().to_string(),
+            started_at: execution["startedAt"].as_str().and_then(|t| t.parse().ok()),
+            stopped_at: execution["stoppedAt"].as_str().and_then(|t| t.parse().ok()),
+            error: error["message"].as_str().map(|message| {
+                match error["description"].as_str().filter(|d| !d.is_empty()) {
+                    Some(description) => format!("{}: {}", message, description),
+                    None => message.to_string(),
+                }
+            }),
+            failed_node: error["node"]["name"].as_str().map(str::to_string),
+            last_node,
+            output,
+        }
+    }
+}
+
+/// n8n REST API client
+pub struct N8n {
+    client: Client,
+    config: N8nConfig,
+}
+
+impl N8n {
+    pub fn new(config: N8nConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create n8n client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    fn api(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(
+                method,
+                format!("{}/api/v1/{}", self.config.url.trim_end_matches('/'), path),
+            )
+            .header("X-N8N-API-KEY", &self.config.api_key)
+    }
+
+    async fn send(request: RequestBuilder) -> Result<Value> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("n8n request failed: {}", e)))?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        let body: Value = serde_json::from_str(&text).unwrap_or(Value::String(text));
+        if status.is_success() {
+            return Ok(body);
+        }
+        let message = body["message"]
+            .as_str()
+            .or_else(|| body.as_str())
+            .unwrap_or("unknown error")
+            .trim()
+            .to_string();
+        Err(match status.as_u16() {
+            401 | 403 => Error::auth(format!("n8n rejected the API key: {}", message)),
+            404 => Error::not_found(format!("n8n: {}", message)),
+            code => Error::api_with_status(format!("n8n request failed: {}", message), "n8n", code),
+        })
+    }
+
+    /// Every page of a list endpoint
+    async fn list(&self, path: &str, query: &[(&str, String)], limit: usize) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut request = self
+                .api(Method::GET, path)
+                .query(query)
+                .query(&[("limit", limit.min(250).to_string())]);
+            if let Some(cursor) = &cursor {
+                request = request.query(&[("cursor", cursor)]);
+            }
+            let page = Self::send(request).await?;
+            items.extend(page["data"].as_array().cloned().unwrap_or_default());
+            cursor = page["nextCursor"].as_str().map(str::to_string);
+            if cursor.is_none() || items.len() >= limit {
+                items.truncate(limit);
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Workflows, optionally only active ones or those with a tag
+    pub async fn workflows(
+        &self,
+        active: Option<bool>,
+        tag: Option<&str>,
+    ) -> Result<Vec<Workflow>> {
+        let mut query = Vec::new();
+        if let Some(active) = active {
+            query.push(("active", active.to_string()));
+        }
+        if let Some(tag) = tag {
+            query.push(("tags", tag.to_string()));
+        }
+        Ok(self
+            .list("workflows", &query, usize::MAX)
+            .await?
+            .iter()
+            .map(Workflow::from_api)
+            .collect())
+    }
+
+    /// A workflow by id or name
+    pub async fn workflow(&self, key: &str) -> Result<Workflow> {
+        if key.chars().all(|c| c.is_ascii_alphanumeric()) {
+            if let Ok(workflow) =
+                Self::send(self.api(Method::GET, &format!("workflows/{}", key))).await
+            {
+                return Ok(Workflow::from_api(&workflow));
+            }
+        }
+        let mut matches: Vec<Workflow> = self
+            .workflows(None, None)
+            .await?
+            .into_iter()
+            .filter(|w| w.name.eq_ignore_ascii_case(key))
+            .collect();
+        match matches.len() {
+            1 => Ok(matches.remove(0)),
+            0 => Err(Error::not_found_with_resource(
+                format!("n8n has no workflow {}", key),
+                "workflow",
+                key,
+            )),
+            n => Err(Error::validation_with_field(
+                format!("{} n8n workflows are named {}; use the id", n, key),
+                "workflow",
+            )),
+        }
+    }
+
+    /// Start a workflow through its Webhook trigger with `payload`; returns
+    /// the workflow and the webhook's response
+    pub async fn trigger(&self, key: &str, payload: &Value) -> Result<(Workflow, Value)> {
+        let workflow = self.workflow(key).await?;
+        let Some((method, path)) = workflow.webhook.clone() else {
+            return Err(Error::validation_with_field(
+                format!(
+                    "{} has no Webhook trigger; add one to start it from here",
+                    workflow.name
+                ),
+                "workflow",
+            ));
+        };
+        if !workflow.active {
+            return Err(Error::validation_with_field(
+                format!(
+                    "{} is inactive; n8n only serves production webhooks of active workflows",
+                    workflow.name
+                ),
+                "workflow",
+            ));
+        }
+        let base = self
+            .config
+            .webhook_url
+            .as_deref()
+            .unwrap_or(&self.config.url)
+            .trim_end_matches('/');
+        let method = Method::from_bytes(method.as_bytes())
+            .map_err(|_| Error::invalid_data(format!("Invalid webhook method {}", method)))?;
+        let mut request = self
+            .client
+            .request(method.clone(), format!("{}/webhook/{}", base, path));
+        request = match (&method, payload) {
+            (_, Value::Null) => request,
+            (&Method::GET | &Method::HEAD, Value::Object(fields)) => request.query(
+                &fields
+                    .iter()
+                    .map(|(k, v)| {
+                        (
+                            k.clone(),
+                            v.as_str()
+                                .map(str::to_string)
+                                .unwrap_or_else(|| v.to_string()),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            (&Method::GET | &Method::HEAD, _) => {
+                return Err(Error::validation_with_field(
+                    "GET webhooks take an object of query parameters",
+                    "payload",
+                ))
+            }
+            _ => request.json(payload),
+        };
+        let response = Self::send(request).await?;
+        Ok((workflow, response))
+    }
+
+    /// Recent executions, newest first, with output when `data` is set
+    pub async fn executions(
+        &self,
+        workflow_id: Option<&str>,
+        status: Option<&str>,
+        limit: usize,
+        data: bool,
+    ) -> Result<Vec<Execution>> {
+        let mut query = vec![("includeData", data.to_string())];
+        if let Some(id) = workflow_id {
+            query.push(("workflowId", id.to_string()));
+        }
+        if let Some(status) = status {
+            query.push(("status", status.to_string()));
+        }
+        Ok(self
+            .list("executions", &query, limit)
+            .await?
+            .iter()
+            .map(Execution::from_api)
+            .collect())
+    }
+
+    /// One execution with its output and error
+    pub async fn execution(&self, id: &str) -> Result<Execution> {
+        if !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(Error::validation_with_field("Invalid execution id", "id"));
+        }
+        let execution = Self::send(
+            self.api(Method::GET, &format!("executions/{}", id))
+                .query(&[("includeData", "true")]),
+        )
+        .await?;
+        Ok(Execution::from_api(&execution))
+    }
+
+    /// Get tool definitions for n8n
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "n8n_workflows",
+                "List n8n workflows with whether they are active and how they are triggered",
+                "automation",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "active": {"type": "boolean", "description": "Only active or only inactive workflows"},
+                        "tag": {"type": "string", "description": "Only workflows with this tag"}
+                    }
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "n8n_trigger",
+                "Start an active n8n workflow through its Webhook trigger, sending a payload, and return the webhook's response",
+                "automation",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "workflow": {"type": "string", "description": "Workflow name or id"},
+                        "payload": {"description": "JSON body, or query parameters for GET webhooks"}
+                    },
+                    "required": ["workflow"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "n8n_executions",
+                "Read n8n executions: one by id with its output and error, or the latest runs of a workflow",
+                "automation",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string", "description": "Execution id; returns its output and error"},
+                        "workflow": {"type": "string", "description": "Workflow name or id"},
+                        "status": {"type": "string", "enum": ["success", "error", "running", "waiting", "canceled"]},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 100, "default": 10},
+                        "include_output": {"type": "boolean", "description": "Include each run's output and error", "default": false}
+                    }
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute an n8n tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let string = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let summary = |e: &Execution| {
+            format!(
+                "\n#{} {} ({}){}{}",
+                e.id,
+                e.status,
+                e.mode,
+                e.started_at
+                    .map(|t| format!(" {}", t.format("%Y-%m-%d %H:%M:%S")))
+                    .unwrap_or_default(),
+                e.error
+                    .as_ref()
+                    .map(|error| match &e.failed_node {
+                        Some(node) => format!(": {} in {}", error, node),
+                        None => format!(": {}", error),
+                    })
+                    .unwrap_or_default()
+            )
+        };
+        match name {
+            "n8n_workflows" => {
+                let workflows = self
+                    .workflows(
+                        parameters.get("active").and_then(|v| v.as_bool()),
+                        string("tag"),
+                    )
+                    .await?;
+                let mut text = format!("{} workflows", workflows.len());
+                for w in &workflows {
+                    text.push_str(&format!(
+                        "\n{} [{}] {}{}",
+                        w.name,
+                        w.id,
+                        if w.active { "active" } else { "inactive" },
+                        match &w.webhook {
+                            Some((method, path)) => format!(", webhook {} /{}", method, path),
+                            None if !w.triggers.is_empty() =>
+                                format!(", {}", w.triggers.join(", ")),
+                            None => String::new(),
+                        }
+                    ));
+                }
+                Ok(call_result(text, json!({ "workflows": workflows })))
+            }
+            "n8n_trigger" => {
+                let key = string("workflow").ok_or_else(|| {
+                    Error::validation_with_field("workflow is required", "workflow")
+                })?;
+                let payload = parameters.get("payload").cloned().unwrap_or(Value::Null);
+                let started = Utc::now();
+                let (workflow, response) = self.trigger(key, &payload).await?;
+                // The run the webhook started, if n8n has recorded it yet
+                let execution = self
+                    .executions(Some(&workflow.id), None, 1, false)
+                    .await
+                    .ok()
+                    .and_then(|mut e| e.pop())
+                    .filter(|e| {
+                        e.mode == "webhook"
+                            && e.started_at
+                                .is_some_and(|t| t >= started - chrono::Duration::seconds(5))
+                    });
+                let mut text = format!("Triggered {}", workflow.name);
+                if let Some(e) = &execution {
+                    text.push_str(&format!(", execution #{} is {}", e.id, e.status));
+                }
+                if !response.is_null() {
+                    text.push_str(&format!("\nResponse: {}", response));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "workflow": workflow, "response": response, "execution": execution }),
+                ))
+            }
+            "n8n_executions" => {
+                if let Some(id) = string("id") {
+                    let execution = self.execution(id).await?;
+                    let mut text = summary(&execution).trim_start().to_string();
+                    if !execution.output.is_empty() {
+                        text.push_str(&format!(
+                            "\nOutput of {}: {}",
+                            execution.last_node.as_deref().unwrap_or("the last node"),
+                            Value::Array(execution.output.clone())
+                        ));
+                    }
+                    return Ok(call_result(text, json!({ "execution": execution })));
+                }
+                let workflow = match string("workflow") {
+                    Some(key) => Some(self.workflow(key).await?),
+                    None => None,
+                };
+                let limit = parameters
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(10)
+                    .clamp(1, 100) as usize;
+                let executions = self
+                    .executions(
+                        workflow.as_ref().map(|w| w.id.as_str()),
+                        string("status"),
+                        limit,
+                        parameters
+                            .get("include_output")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false),
+                    )
+                    .await?;
+                let mut text = match &workflow {
+                    Some(w) => format!("{} executions of {}", executions.len(), w.name),
+                    None => format!("{} executions", executions.len()),
+                };
+                for e in &executions {
+                    text.push_str(&summary(e));
+                }
+                Ok(call_result(text, json!({ "executions": executions })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "n8n_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, Query, State};
+    use axum::http::HeaderMap;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    type Received = Arc<Mutex<Vec<Value>>>;
+
+    fn workflow() -> Value {
+        json!({
+            "id": "w1", "name": "Backup", "active": true, "tags": [{"name": "homelab"}],
+            "nodes": [{"type": "n8n-nodes-base.webhook", "parameters": {"path": "backup", "httpMethod": "POST"}}]
+        })
+    }
+
+    #[tokio::test]
+    async fn triggers_webhooks_and_reads_execution_errors() {
+        let received: Received = Arc::default();
+        let app = Router::new()
+            .route(
+                "/api/v1/workflows",
+                get(|headers: HeaderMap| async move {
+                    assert_eq!(headers["x-n8n-api-key"], "key");
+                    Json(json!({"data": [workflow()], "nextCursor": null}))
+                }),
+            )
+            .route(
+                "/api/v1/workflows/:id",
+                get(|Path(id): Path<String>| async move {
+                    match id.as_str() {
+                        "w1" => Ok(Json(workflow())),
+                        _ => Err(axum::http::StatusCode::NOT_FOUND),
+                    }
+                }),
+            )
+            .route(
+                "/webhook/backup",
+                post(
+                    |State(received): State<Received>, Json(body): Json<Value>| async move {
+                        received.lock().unwrap().push(body);
+                        Json(json!({"message": "Workflow was started"}))
+                    },
+                ),
+            )
+            .route(
+                "/api/v1/executions",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    assert_eq!(query["workflowId"], "w1");
+                    Json(json!({"data": [{
+                        "id": 42, "workflowId": "w1", "status": "success", "mode": "webhook",
+                        "startedAt": Utc::now().to_rfc3339()
+                    }]}))
+                }),
+            )
+            .route(
+                "/api/v1/executions/:id",
+                get(|| async {
+                    Json(json!({
+                        "id": "41", "workflowId": "w1", "status": "error", "mode": "trigger",
+                        "data": {"resultData": {
+                            "lastNodeExecuted": "Upload",
+                            "error": {"message": "ENOSPC", "description": "no space left", "node": {"name": "Upload"}},
+                            "runData": {"Upload": [{"data": {"main": [[{"json": {"file": "db.tar"}}]]}}]}
+                        }}
+                    }))
+                }),
+            )
+            .with_state(Arc::clone(&received));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let n8n = N8n::new(N8nConfig {
+            url,
+            api_key: "key".to_string(),
+            webhook_url: None,
+        })
+        .unwrap();
+        let result = n8n
+            .execute_tool(
+                "n8n_trigger",
+                json!({"workflow": "backup", "payload": {"target": "nas"}}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(received.lock().unwrap()[0]["target"], "nas");
+        assert_eq!(result["structuredContent"]["execution"]["id"], "42");
+
+        let execution = n8n.execution("41").await.unwrap();
+        assert_eq!(execution.error.as_deref(), Some("ENOSPC: no space left"));
+        assert_eq!(execution.failed_node.as_deref(), Some("Upload"));
+        assert_eq!(execution.output, [json!({"file": "db.tar"})]);
+    }
+}
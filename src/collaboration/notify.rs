@@ -0,0 +1,190 @@
+use super::AttachmentField;
+use crate::error::{Error, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Incoming webhook flavour
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    Slack,
+    Discord,
+    Teams,
+    /// Plain JSON POST of the notification
+    Webhook,
+}
+
+/// A named notification channel backed by an incoming webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationChannel {
+    pub name: String,
+    pub kind: ChannelKind,
+    pub url: String,
+}
+
+/// Notification severity
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    fn color(self) -> &'static str {
+        match self {
+            Severity::Info => "#2eb886",
+            Severity::Warning => "#daa038",
+            Severity::Critical => "#d00000",
+        }
+    }
+}
+
+/// A message to post to channels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub title: String,
+    pub text: String,
+    pub severity: Severity,
+    #[serde(default)]
+    pub fields: Vec<AttachmentField>,
+}
+
+/// Channel-specific webhook payload
+pub fn payload(kind: ChannelKind, notification: &Notification) -> Value {
+    let color = notification.severity.color();
+    match kind {
+        ChannelKind::Slack => json!({
+            "text": notification.title,
+            "attachments": [{
+                "color": color,
+                "title": notification.title,
+                "text": notification.text,
+                "fields": notification.fields,
+            }]
+        }),
+        ChannelKind::Discord => json!({
+            "embeds": [{
+                "title": notification.title,
+                "description": notification.text,
+                "color": u32::from_str_radix(&color[1..], 16).unwrap_or(0),
+                "fields": notification.fields.iter().map(|f| json!({
+                    "name": f.title,
+                    "value": f.value,
+                    "inline": f.short,
+                })).collect::<Vec<_>>(),
+            }]
+        }),
+        ChannelKind::Teams => json!({
+            "@type": "MessageCard",
+            "@context": "https://schema.org/extensions",
+            "themeColor": &color[1..],
+            "summary": notification.title,
+            "title": notification.title,
+            "text": notification.text,
+            "sections": [{
+                "facts": notification.fields.iter().map(|f| json!({
+                    "name": f.title,
+                    "value": f.value,
+                })).collect::<Vec<_>>(),
+            }]
+        }),
+        ChannelKind::Webhook => json!(notification),
+    }
+}
+
+/// Posts notifications to configured channels
+pub struct Notifier {
+    client: Client,
+    channels: HashMap<String, NotificationChannel>,
+}
+
+impl Notifier {
+    /// Create a notifier for the given channels
+    pub fn new(channels: Vec<NotificationChannel>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create notification client: {}", e)))?;
+        Ok(Self {
+            client,
+            channels: channels.into_iter().map(|c| (c.name.clone(), c)).collect(),
+        })
+    }
+
+    /// Names of the configured channels
+    pub fn channel_names(&self) -> Vec<&str> {
+        self.channels.keys().map(String::as_str).collect()
+    }
+
+    /// Post a notification to one channel
+    pub async fn send(&self, channel: &str, notification: &Notification) -> Result<()> {
+        let channel = self.channels.get(channel).ok_or_else(|| {
+            Error::not_found_with_resource("Notification channel not found", "channel", channel)
+        })?;
+        let response = self
+            .client
+            .post(&channel.url)
+            .json(&payload(channel.kind, notification))
+            .send()
+            .await
+            .map_err(|e| {
+                Error::network(format!("Failed to post to channel {}: {}", channel.name, e))
+            })?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::api_with_status(
+                format!("Channel {} rejected notification: {}", channel.name, body),
+                "webhook",
+                status.as_u16(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Post to several channels, returning the failures
+    pub async fn broadcast(
+        &self,
+        channels: &[String],
+        notification: &Notification,
+    ) -> Vec<(String, Error)> {
+        let mut failures = Vec::new();
+        for channel in channels {
+            if let Err(e) = self.send(channel, notification).await {
+                failures.push((channel.clone(), e));
+            }
+        }
+        failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_channel_payloads() {
+        let notification = Notification {
+            title: "Budget at 80%".to_string(),
+            text: "prod spent $800 of $1000".to_string(),
+            severity: Severity::Warning,
+            fields: vec![AttachmentField {
+                title: "Forecast".to_string(),
+                value: "$1100".to_string(),
+                short: true,
+            }],
+        };
+        let slack = payload(ChannelKind::Slack, &notification);
+        assert_eq!(slack["attachments"][0]["fields"][0]["value"], "$1100");
+        let discord = payload(ChannelKind::Discord, &notification);
+        assert_eq!(discord["embeds"][0]["color"], 0xdaa038);
+        assert_eq!(discord["embeds"][0]["fields"][0]["inline"], true);
+        let teams = payload(ChannelKind::Teams, &notification);
+        assert_eq!(teams["sections"][0]["facts"][0]["name"], "Forecast");
+    }
+}
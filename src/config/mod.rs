@@ -0,0 +1,607 @@
+pub mod reload;
+pub mod schema;
+
+pub use reload::{ConfigWatcher, Reloadable};
+pub use schema::{ConfigProblem, ConfigReport, Severity};
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Transport configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TransportConfig {
+    /// Transport type (http, websocket, stdio)
+    pub transport_type: String,
+    /// Connection URL
+    pub url: Option<String>,
+    /// Command for stdio transport
+    pub command: Option<String>,
+    /// Arguments for stdio transport
+    pub args: Option<Vec<String>>,
+    /// Authentication token
+    pub auth_token: Option<String>,
+}
+
+impl TransportConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(ref url) = self.url {
+            url::Url::parse(url)
+                .map_err(|e| Error::config(format!("Invalid transport URL: {}", e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Authentication configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AuthConfig {
+    /// OAuth configuration
+    pub oauth: Option<OAuthConfig>,
+    /// API keys
+    pub api_keys: HashMap<String, String>,
+}
+
+impl AuthConfig {
+    pub fn validate(&self) -> Result<()> {
+        // Basic validation for auth config
+        Ok(())
+    }
+}
+
+/// OAuth configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthConfig {
+    /// OAuth client ID
+    pub client_id: String,
+    /// OAuth client secret
+    pub client_secret: Option<String>,
+    /// OAuth authorization URL
+    pub auth_url: String,
+    /// OAuth token URL
+    pub token_url: String,
+    /// OAuth redirect URL
+    pub redirect_url: String,
+    /// OAuth scopes
+    pub scopes: Vec<String>,
+}
+
+/// Security configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SecurityConfig {
+    /// Enable security features
+    pub enabled: bool,
+    /// Security providers
+    pub providers: Vec<String>,
+    /// Roles and per-tool permissions of callers
+    #[serde(default)]
+    pub policy: Option<crate::security::PolicyConfig>,
+    /// Scoped API keys by name, for deployments without roles
+    #[serde(default)]
+    pub api_keys: BTreeMap<String, crate::security::ApiKeyConfig>,
+}
+
+impl SecurityConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (name, key) in &self.api_keys {
+            key.validate(name)?;
+        }
+        match &self.policy {
+            Some(policy) => policy.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct InfrastructureConfig {
+    pub providers: Vec<crate::infrastructure::InfrastructureProvider>,
+    pub default_namespace: Option<String>,
+    pub kubeconfig_path: Option<PathBuf>,
+    /// How the Kubernetes tools reach the cluster
+    #[serde(default)]
+    pub kubernetes_backend: KubernetesBackend,
+    /// Authenticate with the pod's service account; API backend only
+    #[serde(default)]
+    pub in_cluster: bool,
+    /// Homelab hardware inventory
+    #[serde(default)]
+    pub assets: Option<crate::infrastructure::assets::AssetsConfig>,
+    /// UPS monitoring through Network UPS Tools
+    #[serde(default)]
+    pub ups: Option<crate::infrastructure::ups::UpsConfig>,
+    /// Wake-on-LAN and BMC power control
+    #[serde(default)]
+    pub power: Option<crate::infrastructure::power::PowerConfig>,
+    /// Traefik, Caddy and Nginx Proxy Manager
+    #[serde(default)]
+    pub proxy: Option<crate::infrastructure::proxy::ProxyConfig>,
+}
+
+/// Kubernetes access method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KubernetesBackend {
+    /// Spawn `kubectl`
+    #[default]
+    Kubectl,
+    /// Call the REST API directly; needs the `containers` feature
+    Api,
+}
+
+/// CI/CD configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CicdConfig {
+    /// CI/CD providers
+    pub providers: Vec<String>,
+}
+
+/// Monitoring configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MonitoringConfig {
+    /// Monitoring providers
+    pub providers: Vec<String>,
+    /// Grafana instance for alert rules, contact points and silences
+    #[serde(default)]
+    pub grafana: Option<crate::monitoring::GrafanaConfig>,
+    /// Prometheus server for queries and metric discovery
+    #[serde(default)]
+    pub prometheus: Option<crate::monitoring::PrometheusConfig>,
+    /// Jaeger query service for reading traces
+    #[serde(default)]
+    pub jaeger: Option<crate::monitoring::JaegerConfig>,
+    /// Log backends searched by `search_logs`
+    #[serde(default)]
+    pub elasticsearch: Option<crate::monitoring::ElasticsearchConfig>,
+    #[serde(default)]
+    pub loki: Option<crate::monitoring::LokiConfig>,
+    #[serde(default)]
+    pub splunk: Option<crate::monitoring::SplunkConfig>,
+    /// Microsoft Sentinel workspace for analytics rules
+    #[serde(default)]
+    pub sentinel: Option<crate::monitoring::SentinelConfig>,
+    /// OpenTelemetry collector that UPS readings are pushed to
+    #[serde(default)]
+    pub opentelemetry: Option<crate::monitoring::OpenTelemetryConfig>,
+    /// Uptime Kuma server whose monitors and outages are read
+    #[serde(default)]
+    pub uptime_kuma: Option<crate::monitoring::UptimeKumaConfig>,
+    /// Where the unified alert store is persisted; in memory when unset
+    #[serde(default)]
+    pub alert_store: Option<PathBuf>,
+}
+
+/// Database configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DatabaseConfig {
+    /// Database providers
+    pub providers: Vec<String>,
+    /// Connection strings keyed by provider
+    #[serde(default)]
+    pub connections: HashMap<String, String>,
+    /// Connection pool settings
+    #[serde(default)]
+    pub pool: crate::database::PoolConfig,
+    /// Read-only guards keyed by provider
+    #[serde(default)]
+    pub safety: HashMap<String, crate::database::safety::QuerySafety>,
+    /// Local SQLite files
+    #[serde(default)]
+    pub sqlite: crate::database::sqlite::SqliteConfig,
+    /// Directory query results may be exported under; exports are refused when unset
+    #[serde(default)]
+    pub export_dir: Option<PathBuf>,
+}
+
+/// Collaboration configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CollaborationConfig {
+    /// Collaboration providers
+    pub providers: Vec<String>,
+    /// Incoming webhooks notifications can be posted to
+    #[serde(default)]
+    pub channels: Vec<crate::collaboration::NotificationChannel>,
+    /// n8n instance whose workflows can be listed and triggered
+    #[serde(default)]
+    pub n8n: Option<crate::collaboration::N8nConfig>,
+}
+
+/// Development configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DevelopmentConfig {
+    /// Development providers
+    pub providers: Vec<String>,
+}
+
+/// Analytics configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AnalyticsConfig {
+    /// Analytics providers
+    #[serde(default)]
+    pub providers: Vec<String>,
+    /// Garmin and Fitbit activity and sleep data
+    #[serde(default)]
+    pub health: Option<crate::analytics::health::HealthConfig>,
+}
+
+/// Gaming configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GamingConfig {
+    /// Gaming providers
+    pub providers: Vec<String>,
+}
+
+/// Office configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OfficeConfig {
+    /// Office providers
+    pub providers: Vec<String>,
+}
+
+/// Research configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ResearchConfig {
+    /// Research providers
+    pub providers: Vec<String>,
+}
+
+/// AI configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AiConfig {
+    /// AI providers
+    pub providers: Vec<String>,
+    /// Summarization of verbose tool outputs
+    #[serde(default)]
+    pub summarization: Option<crate::ai::SummarizationConfig>,
+}
+
+/// Smart Home configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SmartHomeConfig {
+    /// Smart Home providers
+    pub providers: Vec<String>,
+    /// Home Assistant connection
+    #[serde(default)]
+    pub home_assistant: Option<crate::smart_home::home_assistant::HomeAssistantConfig>,
+    /// Leak and failed-appliance alerts
+    #[serde(default)]
+    pub safety: Option<crate::smart_home::safety::SafetyConfig>,
+    /// Pi-hole and AdGuard Home servers
+    #[serde(default)]
+    pub dns_filters: Vec<crate::smart_home::network::DnsFilterServer>,
+    /// Jellyfin and Plex servers
+    #[serde(default)]
+    pub media: Vec<crate::smart_home::media::MediaServerConfig>,
+    /// Immich photo library
+    #[serde(default)]
+    pub photos: Option<crate::smart_home::photos::PhotoLibraryConfig>,
+    /// Tandoor or Mealie server
+    #[serde(default)]
+    pub recipes: Option<crate::smart_home::meals::RecipeServerConfig>,
+}
+
+/// Government configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GovernmentConfig {
+    /// Government providers
+    pub providers: Vec<String>,
+}
+
+/// Memory configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MemoryConfig {
+    /// Memory providers
+    pub providers: Vec<String>,
+}
+
+/// Finance configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FinanceConfig {
+    /// Finance providers
+    #[serde(default)]
+    pub providers: Vec<String>,
+    /// Alpaca account; paper trading unless `paper` is false
+    #[serde(default)]
+    pub alpaca: Option<crate::finance::AlpacaConfig>,
+    /// Crypto exchange; public Binance market data when unset
+    #[serde(default)]
+    pub crypto: Option<crate::finance::CryptoConfig>,
+    /// Sector of each symbol for portfolio exposure, e.g. `AAPL: Technology`
+    #[serde(default)]
+    pub sectors: HashMap<String, String>,
+    /// Personal finance ledger, budgets and categorization rules
+    #[serde(default)]
+    pub ledger: Option<crate::finance::ledger::LedgerConfig>,
+    /// Receipt scanning and OCR programs
+    #[serde(default)]
+    pub expenses: Option<crate::finance::expenses::ExpensesConfig>,
+}
+
+/// Maps configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MapsConfig {
+    /// Maps providers
+    pub providers: Vec<String>,
+}
+
+/// Creation configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CreationConfig {
+    /// Creation providers
+    pub providers: Vec<String>,
+}
+
+/// Main configuration structure optimized for memory layout
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Config {
+    // Hot data: frequently accessed fields grouped together for cache efficiency
+    pub transport: Option<TransportConfig>,
+    pub auth: Option<AuthConfig>,
+    pub security: Option<SecurityConfig>,
+
+    // Warm data: occasionally accessed configuration
+    pub infrastructure: Option<InfrastructureConfig>,
+    pub cicd: Option<CicdConfig>,
+    pub monitoring: Option<MonitoringConfig>,
+    pub database: Option<DatabaseConfig>,
+    pub collaboration: Option<CollaborationConfig>,
+
+    // Cold data: rarely accessed configuration
+    pub development: Option<DevelopmentConfig>,
+    pub analytics: Option<AnalyticsConfig>,
+    pub gaming: Option<GamingConfig>,
+    pub office: Option<OfficeConfig>,
+    pub research: Option<ResearchConfig>,
+    pub ai: Option<AiConfig>,
+    pub smart_home: Option<SmartHomeConfig>,
+    pub government: Option<GovernmentConfig>,
+    pub memory: Option<MemoryConfig>,
+    pub finance: Option<FinanceConfig>,
+    pub maps: Option<MapsConfig>,
+    pub creation: Option<CreationConfig>,
+
+    /// Per-client tool argument defaults and the policy around them
+    pub preferences: Option<crate::tools::preferences::PreferencesConfig>,
+    /// Tagged entities usable as `@tag` in tool arguments
+    pub favorites: Option<crate::tools::favorites::FavoritesConfig>,
+    /// Encrypted server state archives for disaster recovery
+    pub snapshot: Option<crate::tools::snapshot::SnapshotConfig>,
+    /// Isolated tenants selected by API key, by tenant id
+    pub tenants: Option<BTreeMap<String, crate::transport::tenancy::TenantConfig>>,
+    /// Compression and binary framing of large messages, per transport
+    pub compression: Option<crate::transport::framing::CompressionConfig>,
+    /// Sources searched together by `search_everything`
+    pub search: Option<crate::search::SearchConfig>,
+    /// Backends for `keyvault://` and other secret references in this file
+    pub secrets: Option<crate::security::secrets::SecretsConfig>,
+}
+
+impl Config {
+    /// Create a new configuration with optimized validation
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load configuration from file with efficient parsing
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| Error::config(format!("Failed to read config file: {}", e)))?;
+
+        Self::parse(&contents)
+    }
+
+    /// Load configuration from file, replacing secret references such as
+    /// `keyvault://vault/secret` with the secrets they name
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = tokio::fs::read_to_string(path.as_ref())
+            .await
+            .map_err(|e| Error::config(format!("Failed to read config file: {}", e)))?;
+        let mut value = serde_json::from_str::<serde_json::Value>(&contents)
+            .map_err(|e| Error::config(format!("Failed to parse config: {}", e)))?;
+        // Checked before secrets are resolved, so reports never hold them
+        schema::check(&value).into_result()?;
+
+        // The backends' own settings are taken as written
+        let secrets = value
+            .as_object_mut()
+            .and_then(|config| config.remove("secrets"))
+            .unwrap_or_default();
+        let secrets_config: crate::security::secrets::SecretsConfig = if secrets.is_null() {
+            Default::default()
+        } else {
+            serde_json::from_value(secrets.clone())
+                .map_err(|e| Error::config(format!("Failed to parse secrets: {}", e)))?
+        };
+        let resolved = crate::security::secrets::SecretResolver::from_config(&secrets_config)?
+            .resolve(&mut value)
+            .await?;
+        if resolved > 0 {
+            tracing::info!("Resolved {} secret references in the config", resolved);
+        }
+        if let Some(config) = value.as_object_mut().filter(|_| !secrets.is_null()) {
+            config.insert("secrets".to_string(), secrets);
+        }
+
+        let config = serde_json::from_value::<Self>(value)
+            .map_err(|e| Error::config(format!("Failed to parse config: {}", e)))?;
+        config.validate().map(|_| config)
+    }
+
+    /// Parse configuration from string with optimized error handling
+    pub fn parse(contents: &str) -> Result<Self> {
+        // Parse as JSON, then report every problem at once
+        let value = serde_json::from_str::<serde_json::Value>(contents)
+            .map_err(|e| Error::config(format!("Failed to parse config: {}", e)))?;
+        schema::check(&value).into_result()?;
+
+        serde_json::from_value::<Self>(value)
+            .map_err(|e| Error::config(format!("Failed to parse config: {}", e)))
+    }
+
+    /// Validate configuration with efficient checks
+    pub fn validate(&self) -> Result<()> {
+        let problems = self.section_problems();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::validation(
+                problems
+                    .iter()
+                    .map(|(path, message)| format!("{}: {}", path, message))
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ))
+        }
+    }
+
+    /// Problems the typed sections' own checks find, by config path
+    pub(crate) fn section_problems(&self) -> Vec<(String, String)> {
+        // Pre-allocate error collection for batch validation
+        let mut validation_errors = Vec::with_capacity(8);
+        let mut check = |path: &str, result: Result<()>| {
+            if let Err(e) = result {
+                validation_errors.push((path.to_string(), e.to_string()));
+            }
+        };
+
+        if let Some(ref transport) = self.transport {
+            check("transport", transport.validate());
+        }
+        if let Some(ref auth) = self.auth {
+            check("auth", auth.validate());
+        }
+        if let Some(ref security) = self.security {
+            check("security", security.validate());
+        }
+        if let Some(ref preferences) = self.preferences {
+            check("preferences", preferences.validate());
+        }
+        if let Some(ref snapshot) = self.snapshot {
+            check("snapshot", snapshot.validate());
+        }
+        if let Some(ref search) = self.search {
+            check("search", search.validate());
+        }
+        if let Some(ups) = self.infrastructure.as_ref().and_then(|i| i.ups.as_ref()) {
+            check("infrastructure.ups", ups.validate());
+        }
+        if let Some(ref tenants) = self.tenants {
+            for (id, tenant) in tenants {
+                check(&format!("tenants.{}", id), tenant.validate(id));
+            }
+        }
+
+        validation_errors
+    }
+
+    /// Merge configurations efficiently
+    pub fn merge(&mut self, other: Config) {
+        macro_rules! merge_option {
+            ($field:ident) => {
+                if other.$field.is_some() {
+                    self.$field = other.$field;
+                }
+            };
+        }
+
+        merge_option!(transport);
+        merge_option!(auth);
+        merge_option!(security);
+        merge_option!(infrastructure);
+        merge_option!(cicd);
+        merge_option!(monitoring);
+        merge_option!(database);
+        merge_option!(collaboration);
+        merge_option!(development);
+        merge_option!(analytics);
+        merge_option!(gaming);
+        merge_option!(office);
+        merge_option!(research);
+        merge_option!(ai);
+        merge_option!(smart_home);
+        merge_option!(government);
+        merge_option!(memory);
+        merge_option!(finance);
+        merge_option!(maps);
+        merge_option!(creation);
+        merge_option!(preferences);
+        merge_option!(favorites);
+        merge_option!(snapshot);
+        merge_option!(tenants);
+        merge_option!(compression);
+        merge_option!(search);
+        merge_option!(secrets);
+    }
+
+    /// A config holding only the named top-level sections of this one
+    ///
+    /// Fails on names that are not sections, or that cannot be shared, such
+    /// as `tenants`.
+    pub fn sections(&self, names: &[String]) -> Result<Config> {
+        macro_rules! copy_sections {
+            ($config:ident, $name:ident: $($field:ident),*) => {
+                match $name.as_str() {
+                    $(stringify!($field) => $config.$field = self.$field.clone(),)*
+                    other => {
+                        return Err(Error::validation_with_field(
+                            format!("{:?} is not a config section that can be shared", other),
+                            "inherit",
+                        ))
+                    }
+                }
+            };
+        }
+
+        let mut config = Config::default();
+        for name in names {
+            copy_sections!(config, name: transport, auth, security, infrastructure, cicd,
+                monitoring, database, collaboration, development, analytics, gaming, office,
+                research, ai, smart_home, government, memory, finance, maps, creation,
+                preferences, favorites, snapshot, compression, search, secrets);
+        }
+        Ok(config)
+    }
+
+    // Feature enablement checks
+    pub fn database_enabled(&self) -> bool {
+        self.database.is_some()
+    }
+
+    pub fn cloud_enabled(&self) -> bool {
+        self.infrastructure.is_some()
+    }
+
+    pub fn containers_enabled(&self) -> bool {
+        self.infrastructure.is_some()
+    }
+
+    pub fn cicd_enabled(&self) -> bool {
+        self.cicd.is_some()
+    }
+
+    pub fn monitoring_enabled(&self) -> bool {
+        self.monitoring.is_some()
+    }
+
+    pub fn ai_enabled(&self) -> bool {
+        self.ai.is_some()
+    }
+
+    pub fn analytics_enabled(&self) -> bool {
+        self.analytics.is_some()
+    }
+
+    pub fn office_enabled(&self) -> bool {
+        self.office.is_some()
+    }
+
+    pub fn gaming_enabled(&self) -> bool {
+        self.gaming.is_some()
+    }
+
+    pub fn smart_home_enabled(&self) -> bool {
+        self.smart_home.is_some()
+    }
+}
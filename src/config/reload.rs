@@ -0,0 +1,217 @@
+//! Applying config file changes while the server runs
+//!
+//! `ConfigWatcher` reads the config file every few seconds and on SIGHUP.
+//! A changed file is loaded like at startup, with secret references
+//! resolved and every check run, and is only handed to the registered
+//! `Reloadable` parts of the server once it is valid; a broken save is
+//! logged and the running config stays in effect. SIGHUP reloads even an
+//! unchanged file, so secrets rotated in Key Vault or Vault are picked up.
+//! Transports, sessions and their connections are never touched.
+
+use super::Config;
+use crate::error::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+/// How often the file is read for changes
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Sections read once at startup, whose changes need a restart
+const RESTART_SECTIONS: &[&str] = &[
+    "transport",
+    "compression",
+    "tenants",
+    "preferences",
+    "favorites",
+    "snapshot",
+    "secrets",
+];
+
+/// Part of the server rebuilt from the config when it changes
+#[async_trait]
+pub trait Reloadable: Send + Sync {
+    /// Switch to `config`; on error the previous config stays in effect
+    async fn reload(&self, config: &Config) -> Result<()>;
+}
+
+/// Config as last applied
+struct Applied {
+    /// File contents last tried, whether or not they loaded
+    contents: String,
+    config: Value,
+}
+
+/// Watches the config file and reloads the server's parts from it
+pub struct ConfigWatcher {
+    path: PathBuf,
+    targets: Vec<Arc<dyn Reloadable>>,
+    applied: Mutex<Applied>,
+}
+
+impl ConfigWatcher {
+    /// Watcher for the file `config` was loaded from
+    pub fn new(path: impl Into<PathBuf>, config: &Config) -> Self {
+        let path = path.into();
+        Self {
+            applied: Mutex::new(Applied {
+                contents: std::fs::read_to_string(&path).unwrap_or_default(),
+                config: serde_json::to_value(config).unwrap_or_default(),
+            }),
+            path,
+            targets: Vec::new(),
+        }
+    }
+
+    pub fn with_target(mut self, target: Arc<dyn Reloadable>) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    /// Load the file and apply it if it changed, or regardless when
+    /// `force` is set; returns whether a new config was applied
+    pub async fn reload(&self, force: bool) -> Result<bool> {
+        let mut applied = self.applied.lock().await;
+        let contents = tokio::fs::read_to_string(&self.path)
+            .await
+            .unwrap_or_default();
+        if !force && contents == applied.contents {
+            return Ok(false);
+        }
+        // Not retried until the file changes again
+        applied.contents = contents;
+        let config = Config::load(&self.path).await?;
+
+        let value = serde_json::to_value(&config).unwrap_or_default();
+        for section in RESTART_SECTIONS {
+            if value.get(section) != applied.config.get(section) {
+                tracing::warn!(
+                    "Config section {} changed; it takes effect after a restart",
+                    section
+                );
+            }
+        }
+        let mut first_error = None;
+        for target in &self.targets {
+            if let Err(e) = target.reload(&config).await {
+                tracing::error!("Failed to apply the reloaded config: {}", e);
+                first_error.get_or_insert(e);
+            }
+        }
+        applied.config = value;
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(true),
+        }
+    }
+
+    /// Reload on every change to the file and on SIGHUP
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut hangup = hangup_signal();
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval.tick().await;
+            loop {
+                let force = tokio::select! {
+                    _ = interval.tick() => false,
+                    _ = next_hangup(&mut hangup) => true,
+                };
+                match self.reload(force).await {
+                    Ok(true) => tracing::info!("Reloaded config from {}", self.path.display()),
+                    Ok(false) => {}
+                    Err(e) => tracing::error!(
+                        "Keeping the running config; {} could not be applied: {}",
+                        self.path.display(),
+                        e
+                    ),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(unix)]
+type Hangup = Option<tokio::signal::unix::Signal>;
+#[cfg(not(unix))]
+type Hangup = ();
+
+#[cfg(unix)]
+fn hangup_signal() -> Hangup {
+    use tokio::signal::unix::{signal, SignalKind};
+    signal(SignalKind::hangup())
+        .map_err(|e| tracing::warn!("Cannot listen for SIGHUP: {}", e))
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn hangup_signal() -> Hangup {}
+
+#[cfg(unix)]
+async fn next_hangup(hangup: &mut Hangup) {
+    match hangup {
+        Some(signal) => {
+            signal.recv().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(not(unix))]
+async fn next_hangup(_: &mut Hangup) {
+    std::future::pending().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(std::sync::Mutex<Vec<Option<usize>>>);
+
+    #[async_trait]
+    impl Reloadable for Recorder {
+        async fn reload(&self, config: &Config) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(config.smart_home.as_ref().map(|s| s.media.len()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn applies_changed_files_and_keeps_the_config_on_errors() {
+        let path = std::env::temp_dir().join(format!("reload-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "{}").unwrap();
+        let recorder = Arc::new(Recorder::default());
+        let watcher = ConfigWatcher::new(&path, &Config::default())
+            .with_target(Arc::clone(&recorder) as Arc<dyn Reloadable>);
+
+        assert!(!watcher.reload(false).await.unwrap());
+        std::fs::write(
+            &path,
+            r#"{"smart_home": {"providers": [], "media": [{"type": "plex", "url": "http://plex.lan:32400", "token": "t"}]}}"#,
+        )
+        .unwrap();
+        assert!(watcher.reload(false).await.unwrap());
+        // A broken save is reported once and nothing is applied
+        std::fs::write(
+            &path,
+            r#"{"smart_home": {"providers": [], "media": [{"type": "plex"}]}}"#,
+        )
+        .unwrap();
+        assert!(watcher.reload(false).await.is_err());
+        assert!(!watcher.reload(false).await.unwrap());
+        // SIGHUP reloads an unchanged file
+        std::fs::write(&path, "{}").unwrap();
+        assert!(watcher.reload(false).await.unwrap());
+        assert!(watcher.reload(true).await.unwrap());
+        assert_eq!(recorder.0.lock().unwrap().as_slice(), [Some(1), None, None]);
+        std::fs::remove_file(path).ok();
+    }
+}
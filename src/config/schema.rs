@@ -0,0 +1,574 @@
+//! Config file checks that report every problem at once
+//!
+//! `check` walks the raw JSON before it is deserialized, so one pass finds
+//! every missing field, malformed URL and conflicting pair of auth options
+//! instead of stopping at serde's first error. Sections that pass those
+//! rules are then deserialized on their own, and a config that parses is
+//! run through each section's typed checks. Problems name a config path and
+//! never echo values, so reports are safe to log or return to clients.
+
+use super::Config;
+use crate::error::{Error, Result};
+use crate::security::secrets::{SecretRef, BUILTIN_SCHEMES};
+use crate::tools::registry::ToolRegistry;
+use crate::tools::{call_result, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Rules for one config section
+struct Section {
+    /// Dotted path; `*` matches every array item or map entry
+    path: &'static str,
+    /// Fields that must be present and non-empty
+    required: &'static [&'static str],
+    /// Fields that must be absolute http(s) or ws(s) URLs when set
+    urls: &'static [&'static str],
+    /// Groups of fields of which at most one may be set
+    exclusive: &'static [&'static [&'static str]],
+    /// A field and the fields it needs alongside it
+    requires: &'static [(&'static str, &'static [&'static str])],
+}
+
+const fn section(path: &'static str) -> Section {
+    Section {
+        path,
+        required: &[],
+        urls: &[],
+        exclusive: &[],
+        requires: &[],
+    }
+}
+
+/// Username and password given together or not at all
+const CREDENTIAL_PAIR: &[(&str, &[&str])] =
+    &[("username", &["password"]), ("password", &["username"])];
+
+const SECTIONS: &[Section] = &[
+    Section {
+        urls: &["url"],
+        ..section("transport")
+    },
+    Section {
+        required: &["client_id", "auth_url", "token_url", "redirect_url"],
+        urls: &["auth_url", "token_url", "redirect_url"],
+        ..section("auth.oauth")
+    },
+    Section {
+        required: &["otlp_endpoint"],
+        urls: &["otlp_endpoint"],
+        ..section("monitoring.opentelemetry")
+    },
+    Section {
+        required: &["url"],
+        urls: &["url"],
+        requires: CREDENTIAL_PAIR,
+        ..section("monitoring.uptime_kuma")
+    },
+    Section {
+        required: &["name", "kind", "url"],
+        urls: &["url"],
+        ..section("collaboration.channels.*")
+    },
+    Section {
+        required: &["url", "api_key"],
+        urls: &["url", "webhook_url"],
+        ..section("collaboration.n8n")
+    },
+    Section {
+        required: &["url", "token"],
+        urls: &["url"],
+        ..section("smart_home.home_assistant")
+    },
+    Section {
+        required: &["type", "url"],
+        urls: &["url"],
+        ..section("smart_home.dns_filters.*")
+    },
+    Section {
+        required: &["type", "url", "token"],
+        urls: &["url"],
+        ..section("smart_home.media.*")
+    },
+    Section {
+        required: &["url", "api_key"],
+        urls: &["url"],
+        ..section("smart_home.photos")
+    },
+    Section {
+        required: &["type", "url", "token"],
+        urls: &["url"],
+        ..section("smart_home.recipes")
+    },
+    Section {
+        urls: &["fitbit_url"],
+        ..section("analytics.health")
+    },
+    Section {
+        required: &["roles"],
+        ..section("security.policy")
+    },
+    Section {
+        required: &["api_keys", "roles"],
+        ..section("security.policy.principals.*")
+    },
+    Section {
+        required: &["hash", "scopes"],
+        ..section("security.api_keys.*")
+    },
+    Section {
+        required: &["ups"],
+        requires: CREDENTIAL_PAIR,
+        ..section("infrastructure.ups.units.*")
+    },
+    Section {
+        required: &["username", "password"],
+        ..section("infrastructure.power.bmc.*")
+    },
+    Section {
+        required: &["api_url"],
+        urls: &["api_url"],
+        requires: CREDENTIAL_PAIR,
+        ..section("infrastructure.proxy.traefik")
+    },
+    Section {
+        required: &["admin_url"],
+        urls: &["admin_url"],
+        ..section("infrastructure.proxy.caddy")
+    },
+    Section {
+        required: &["url", "email", "password"],
+        urls: &["url"],
+        ..section("infrastructure.proxy.npm")
+    },
+    Section {
+        required: &["key_id", "secret_key"],
+        urls: &["trading_url", "data_url", "stream_url"],
+        ..section("finance.alpaca")
+    },
+    Section {
+        urls: &["base_url"],
+        requires: &[("api_key", &["api_secret"]), ("api_secret", &["api_key"])],
+        ..section("finance.crypto")
+    },
+    Section {
+        required: &["match", "category"],
+        ..section("finance.ledger.rules.*")
+    },
+    Section {
+        required: &["token"],
+        urls: &["api_url"],
+        ..section("search.notion")
+    },
+    Section {
+        required: &["token"],
+        urls: &["api_url"],
+        ..section("search.slack")
+    },
+    Section {
+        urls: &["authority_host"],
+        requires: &[
+            ("client_secret", &["tenant_id", "client_id"]),
+            ("tenant_id", &["client_secret"]),
+        ],
+        ..section("secrets.keyvault")
+    },
+    Section {
+        required: &["address"],
+        urls: &["address"],
+        exclusive: &[&["token", "role_id"]],
+        requires: &[("role_id", &["secret_id"]), ("secret_id", &["role_id"])],
+        ..section("secrets.vault")
+    },
+    Section {
+        required: &["api_keys"],
+        ..section("tenants.*")
+    },
+];
+
+/// How serious a problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The server refuses to start with it
+    Error,
+    /// Likely a mistake, but the config still loads
+    Warning,
+}
+
+/// One problem found in the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigProblem {
+    pub severity: Severity,
+    /// Dotted config path, e.g. `smart_home.media.0.url`
+    pub path: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// Every problem found in a config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigReport {
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl ConfigReport {
+    fn push(&mut self, severity: Severity, path: &str, message: impl Into<String>) {
+        self.problems.push(ConfigProblem {
+            severity,
+            path: path.to_string(),
+            message: message.into(),
+            suggestion: None,
+        });
+    }
+
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.push(Severity::Error, path, message);
+    }
+
+    pub fn errors(&self) -> usize {
+        self.problems
+            .iter()
+            .filter(|p| p.severity == Severity::Error)
+            .count()
+    }
+
+    /// No errors; warnings are allowed
+    pub fn is_valid(&self) -> bool {
+        self.errors() == 0
+    }
+
+    /// The report as an error when it has errors
+    pub fn into_result(self) -> Result<()> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(Error::config_with_suggestion(
+                format!("Invalid config: {}", self),
+                "Run devops-mcp --check-config <file> to check changes before restarting",
+            ))
+        }
+    }
+
+    fn has_error_under(&self, path: &str) -> bool {
+        self.problems.iter().any(|p| {
+            p.severity == Severity::Error
+                && (p.path == path || p.path.starts_with(&format!("{}.", path)))
+        })
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = |n: usize, noun: &str| match n {
+            1 => format!("1 {}", noun),
+            n => format!("{} {}s", n, noun),
+        };
+        write!(
+            f,
+            "{}, {}",
+            count(self.errors(), "error"),
+            count(self.problems.len() - self.errors(), "warning")
+        )?;
+        for problem in &self.problems {
+            let severity = match problem.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            write!(f, "\n  {} {}: {}", severity, problem.path, problem.message)?;
+            if let Some(suggestion) = &problem.suggestion {
+                write!(f, " ({})", suggestion)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Values at a dotted path, with their concrete paths
+fn find<'a>(value: &'a Value, pattern: &str) -> Vec<(String, &'a Value)> {
+    let mut found = vec![(String::new(), value)];
+    for part in pattern.split('.') {
+        found = found
+            .into_iter()
+            .flat_map(|(at, value)| {
+                let join = move |key: &str| {
+                    if at.is_empty() {
+                        key.to_string()
+                    } else {
+                        format!("{}.{}", at, key)
+                    }
+                };
+                let children: Vec<(String, &Value)> = match (part, value) {
+                    ("*", Value::Array(items)) => items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, item)| (join(&i.to_string()), item))
+                        .collect(),
+                    ("*", Value::Object(map)) => {
+                        map.iter().map(|(key, item)| (join(key), item)).collect()
+                    }
+                    (key, Value::Object(map)) => map
+                        .get(key)
+                        .filter(|v| !v.is_null())
+                        .map(|v| (join(key), v))
+                        .into_iter()
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                children
+            })
+            .collect();
+    }
+    found
+}
+
+fn is_set(fields: &Map<String, Value>, key: &str) -> bool {
+    match fields.get(key) {
+        None | Some(Value::Null) => false,
+        Some(Value::String(s)) => !s.trim().is_empty(),
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(_) => true,
+    }
+}
+
+/// Why `value` is not a usable URL, if it is not
+fn url_problem(value: &Value) -> Option<String> {
+    let Some(text) = value.as_str() else {
+        return Some("must be a URL string".to_string());
+    };
+    // Resolved at load time
+    if SecretRef::parse(text).is_some_and(|r| BUILTIN_SCHEMES.contains(&r.scheme.as_str())) {
+        return None;
+    }
+    match url::Url::parse(text) {
+        Err(e) => Some(format!("is not a valid URL ({})", e)),
+        Ok(url) if !matches!(url.scheme(), "http" | "https" | "ws" | "wss") => Some(format!(
+            "must be an http, https, ws or wss URL, not {}",
+            url.scheme()
+        )),
+        Ok(url) if url.host_str().is_none_or(str::is_empty) => Some("has no host".to_string()),
+        Ok(_) => None,
+    }
+}
+
+fn check_section(report: &mut ConfigReport, rules: &Section, at: &str, value: &Value) {
+    let Some(fields) = value.as_object() else {
+        report.error(at, "must be an object");
+        return;
+    };
+    let path = |key: &str| format!("{}.{}", at, key);
+    for key in rules.required {
+        if !is_set(fields, key) {
+            report.error(&path(key), "is required");
+        }
+    }
+    for key in rules.urls {
+        if let Some(problem) = fields
+            .get(*key)
+            .filter(|v| !v.is_null())
+            .and_then(url_problem)
+        {
+            report.error(&path(key), problem);
+        }
+    }
+    for group in rules.exclusive {
+        let set: Vec<&str> = group
+            .iter()
+            .copied()
+            .filter(|key| is_set(fields, key))
+            .collect();
+        if set.len() > 1 {
+            report.error(
+                at,
+                format!("{} are mutually exclusive; set only one", set.join(" and ")),
+            );
+        }
+    }
+    for (key, needs) in rules.requires {
+        if !is_set(fields, key) {
+            continue;
+        }
+        for needed in needs.iter().filter(|n| !is_set(fields, n)) {
+            report.error(&path(needed), format!("is required when {} is set", key));
+        }
+    }
+}
+
+/// Check a config file's JSON, reporting every problem found
+pub fn check(value: &Value) -> ConfigReport {
+    let mut report = ConfigReport::default();
+    let Some(sections) = value.as_object() else {
+        report.error("", "the config must be a JSON object");
+        return report;
+    };
+
+    // serde ignores unknown sections, so a misspelt one silently does nothing
+    let known = serde_json::to_value(Config::default()).unwrap_or_default();
+    let known: Vec<&String> = known
+        .as_object()
+        .map(|k| k.keys().collect())
+        .unwrap_or_default();
+    for key in sections.keys().filter(|k| !known.contains(k)) {
+        let nearest = known
+            .iter()
+            .map(|k| (crate::ai::query_translation::edit_distance(key, k), k))
+            .min()
+            .filter(|(distance, _)| *distance <= 2);
+        report.problems.push(ConfigProblem {
+            severity: Severity::Warning,
+            path: key.clone(),
+            message: "is not a config section and is ignored".to_string(),
+            suggestion: nearest.map(|(_, k)| format!("did you mean {}?", k)),
+        });
+    }
+
+    for rules in SECTIONS {
+        for (at, section) in find(value, rules.path) {
+            check_section(&mut report, rules, &at, section);
+        }
+    }
+
+    // Type errors, one section at a time so each is reported
+    let mut parses = true;
+    for (key, section) in sections.iter().filter(|(k, _)| known.contains(k)) {
+        let single = Value::Object(Map::from_iter([(key.clone(), section.clone())]));
+        if let Err(e) = serde_json::from_value::<Config>(single) {
+            parses = false;
+            if !report.has_error_under(key) {
+                report.error(key, e.to_string());
+            }
+        }
+    }
+    if parses {
+        if let Ok(config) = serde_json::from_value::<Config>(value.clone()) {
+            for (path, message) in config.section_problems() {
+                report.error(&path, message);
+            }
+        }
+    }
+    report
+}
+
+/// Tool definition for checking the config
+fn tool_definition() -> ToolDefinition {
+    ToolDefinition::from_json_schema(
+        "validate_config",
+        "Check a config for missing fields, malformed URLs, conflicting auth options and type errors, reporting every problem at once; checks the server's own config file when none is given",
+        "core",
+        json!({
+            "type": "object",
+            "properties": {
+                "config": {"type": "object", "description": "Config to check, in the config file's JSON layout; the server's config file when omitted"}
+            }
+        }),
+        None,
+    )
+    .read_only()
+}
+
+/// Register `validate_config`, which checks `path` unless given a config
+pub fn register(registry: &ToolRegistry, path: Option<PathBuf>) -> Result<()> {
+    registry.register(tool_definition(), move |arguments| {
+        let path = path.clone();
+        async move {
+            let (source, value) = match arguments.get("config") {
+                Some(config) => ("the given config".to_string(), config.clone()),
+                None => {
+                    let path = path.ok_or_else(|| {
+                        Error::config_with_suggestion(
+                            "The server was started without a config file",
+                            "Pass the config to check, or set MCP_CONFIG",
+                        )
+                    })?;
+                    let contents = tokio::fs::read_to_string(&path).await.map_err(|e| {
+                        Error::io_with_path(
+                            format!("Failed to read config file: {}", e),
+                            path.clone(),
+                        )
+                    })?;
+                    let value = serde_json::from_str(&contents).map_err(|e| {
+                        Error::config(format!("{} is not valid JSON: {}", path.display(), e))
+                    })?;
+                    (path.display().to_string(), value)
+                }
+            };
+            let report = check(&value);
+            let verdict = if report.is_valid() {
+                "valid"
+            } else {
+                "invalid"
+            };
+            Ok(call_result(
+                format!("{} is {}: {}", source, verdict, report),
+                json!({ "valid": report.is_valid(), "problems": report.problems }),
+            ))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_every_problem_with_its_path() {
+        let report = check(&json!({
+            "monitring": {"providers": []},
+            "smart_home": {
+                "providers": [],
+                "media": [
+                    {"type": "plex", "url": "http://plex.lan:32400", "token": "t"},
+                    {"type": "jellyfin", "url": "jellyfin.lan"}
+                ]
+            },
+            "secrets": {"vault": {"address": "https://vault.lan:8200", "token": "s.x", "role_id": "r"}},
+            "database": {"providers": [], "connections": {"postgresql": "keyvault://lab/pg"}},
+            "collaboration": {"providers": [], "n8n": {"url": "keyvault://lab/n8n-url", "api_key": "k"}},
+            "finance": {"sectors": "Technology"}
+        }));
+        let problems: Vec<(Severity, &str, &str)> = report
+            .problems
+            .iter()
+            .map(|p| (p.severity, p.path.as_str(), p.message.as_str()))
+            .collect();
+        assert!(problems.contains(&(
+            Severity::Warning,
+            "monitring",
+            "is not a config section and is ignored"
+        )));
+        assert_eq!(
+            report.problems[0].suggestion.as_deref(),
+            Some("did you mean monitoring?")
+        );
+        assert!(problems.contains(&(Severity::Error, "smart_home.media.1.token", "is required")));
+        assert!(problems
+            .iter()
+            .any(|(_, path, message)| *path == "smart_home.media.1.url"
+                && message.starts_with("is not a valid URL")));
+        assert!(problems.contains(&(
+            Severity::Error,
+            "secrets.vault",
+            "token and role_id are mutually exclusive; set only one"
+        )));
+        assert!(problems.contains(&(
+            Severity::Error,
+            "secrets.vault.secret_id",
+            "is required when role_id is set"
+        )));
+        // Type errors are reported by section, alongside the rest
+        assert!(problems
+            .iter()
+            .any(|(_, path, message)| *path == "finance" && message.contains("invalid type")));
+        // Secret references stand in for URLs until they are resolved
+        assert!(!report.has_error_under("collaboration"));
+        assert_eq!(report.errors(), 5);
+        assert!(!report.is_valid());
+
+        let report = check(&json!({"smart_home": {"providers": [], "media": [
+            {"type": "plex", "url": "http://plex.lan:32400", "token": "t"}
+        ]}}));
+        assert!(report.is_valid(), "{}", report);
+    }
+}
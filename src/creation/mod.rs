@@ -260,7 +260,8 @@ impl McpCreatorClient {
                         .with_description("Get the status of a creation server")
                         .with_usage_hints(vec!["Use to check if a server is running".to_string()]),
                 ),
-            ),
+            )
+            .read_only(),
         ]
     }
 
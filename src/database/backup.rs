@@ -0,0 +1,732 @@
+use crate::cicd::artifacts::file_digest;
+use crate::cloud::storage::{open_store, ObjectStore};
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Datelike, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Database engine a backup was taken from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    Postgres,
+    Mongo,
+}
+
+impl Engine {
+    /// Engine for a database provider name
+    pub fn from_provider(provider: &str) -> Result<Self> {
+        match provider {
+            "postgresql" | "postgres" | "supabase" => Ok(Engine::Postgres),
+            "mongodb" | "mongo" => Ok(Engine::Mongo),
+            _ => Err(Error::validation_with_field(
+                format!("Unsupported provider: {}", provider),
+                "provider",
+            )),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Engine::Postgres => "dump",
+            Engine::Mongo => "archive.gz",
+        }
+    }
+}
+
+/// A database to back up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupTarget {
+    pub name: String,
+    pub engine: Engine,
+    #[serde(skip_serializing)]
+    pub connection_string: String,
+}
+
+/// Grandfather-father-son retention, applied per target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Most recent backups always kept
+    pub keep_last: usize,
+    /// Days for which the newest backup of the day is kept
+    pub keep_daily: usize,
+    /// ISO weeks for which the newest backup of the week is kept
+    pub keep_weekly: usize,
+    /// Months for which the newest backup of the month is kept
+    pub keep_monthly: usize,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: 3,
+            keep_daily: 7,
+            keep_weekly: 4,
+            keep_monthly: 6,
+        }
+    }
+}
+
+/// Backup configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Object store URL, e.g. `s3://bucket/prefix` or `file:///var/backups`
+    pub store_url: Option<String>,
+    /// Key prefix backups are stored under
+    pub prefix: String,
+    pub retention: RetentionPolicy,
+    /// Scratch PostgreSQL database for test restores
+    #[serde(skip_serializing)]
+    pub scratch_postgres: Option<String>,
+    /// Scratch MongoDB deployment for test restores
+    #[serde(skip_serializing)]
+    pub scratch_mongo: Option<String>,
+    /// pg_dump compression level, 0-9
+    pub compression: u8,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+        Self {
+            store_url: env("BACKUP_STORE_URL"),
+            prefix: env("BACKUP_PREFIX").unwrap_or_else(|| "backups".to_string()),
+            retention: RetentionPolicy::default(),
+            scratch_postgres: env("BACKUP_SCRATCH_POSTGRES_URL"),
+            scratch_mongo: env("BACKUP_SCRATCH_MONGODB_URL"),
+            compression: 6,
+        }
+    }
+}
+
+/// Result of restoring a backup into a scratch database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verification {
+    pub verified_at: DateTime<Utc>,
+    pub ok: bool,
+    pub detail: String,
+}
+
+/// A stored backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRecord {
+    pub id: String,
+    pub target: String,
+    pub engine: Engine,
+    /// Object key of the dump
+    pub key: String,
+    pub url: String,
+    pub size: u64,
+    pub sha256: String,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    pub verification: Option<Verification>,
+}
+
+/// Backups a retention policy no longer keeps, for one target's records
+pub fn expired(records: &[BackupRecord], policy: &RetentionPolicy) -> Vec<String> {
+    let mut sorted: Vec<&BackupRecord> = records.iter().collect();
+    sorted.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+
+    let mut keep: BTreeSet<&str> = sorted
+        .iter()
+        .take(policy.keep_last)
+        .map(|r| r.id.as_str())
+        .collect();
+    let mut keep_newest_per = |limit: usize, bucket: &dyn Fn(&DateTime<Utc>) -> (i32, u32)| {
+        let mut seen = BTreeSet::new();
+        for record in &sorted {
+            if seen.len() >= limit {
+                break;
+            }
+            if seen.insert(bucket(&record.started_at)) {
+                keep.insert(record.id.as_str());
+            }
+        }
+    };
+    keep_newest_per(policy.keep_daily, &|t| (t.year(), t.ordinal()));
+    keep_newest_per(policy.keep_weekly, &|t| {
+        (t.iso_week().year(), t.iso_week().week())
+    });
+    keep_newest_per(policy.keep_monthly, &|t| (t.year(), t.month()));
+
+    sorted
+        .iter()
+        .filter(|r| !keep.contains(r.id.as_str()))
+        .map(|r| r.id.clone())
+        .collect()
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(Error::validation_with_field(
+            format!("Invalid backup target name: {}", name),
+            "target",
+        ));
+    }
+    Ok(())
+}
+
+/// Run a client tool, returning stdout and stderr
+async fn run(program: &str, args: Vec<OsString>) -> Result<(String, String)> {
+    let output = Command::new(program)
+        .args(&args)
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Error::config_with_suggestion(
+                format!("{} not found", program),
+                "Install the PostgreSQL client tools or MongoDB database tools",
+            ),
+            _ => Error::service(format!("Failed to run {}: {}", program, e)),
+        })?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    if !output.status.success() {
+        return Err(Error::service(format!(
+            "{} failed: {}",
+            program,
+            stderr.trim()
+        )));
+    }
+    Ok((stdout, stderr))
+}
+
+/// Database backup, retention and restore orchestration
+pub struct BackupManager {
+    config: BackupConfig,
+    store: Arc<dyn ObjectStore>,
+    targets: BTreeMap<String, BackupTarget>,
+}
+
+impl BackupManager {
+    /// Create a manager storing backups in `store`
+    pub fn new(config: BackupConfig, store: Arc<dyn ObjectStore>) -> Self {
+        Self {
+            config,
+            store,
+            targets: BTreeMap::new(),
+        }
+    }
+
+    /// Create a manager using the configured store URL
+    pub fn from_config(config: BackupConfig) -> Result<Self> {
+        let url = config.store_url.clone().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "No backup store configured",
+                "Set BACKUP_STORE_URL to an s3:// or file:// location",
+            )
+        })?;
+        Ok(Self::new(config, open_store(&url)?))
+    }
+
+    /// Register a target for scheduled backups
+    pub fn with_target(mut self, target: BackupTarget) -> Result<Self> {
+        validate_name(&target.name)?;
+        self.targets.insert(target.name.clone(), target);
+        Ok(self)
+    }
+
+    fn manifest_key(&self, target: &str, id: &str) -> String {
+        format!("{}/{}/{}.json", self.config.prefix, target, id)
+    }
+
+    /// Dump a database and upload it with a manifest
+    pub async fn backup(&self, target: &BackupTarget) -> Result<BackupRecord> {
+        validate_name(&target.name)?;
+        let started_at = Utc::now();
+        let id = format!("{}-{}", target.name, started_at.format("%Y%m%dT%H%M%SZ"));
+        let dir = tempfile::tempdir()
+            .map_err(|e| Error::internal(format!("Failed to create temp dir: {}", e)))?;
+        let file = dir
+            .path()
+            .join(format!("{}.{}", id, target.engine.extension()));
+
+        match target.engine {
+            Engine::Postgres => {
+                run(
+                    "pg_dump",
+                    vec![
+                        "--format=custom".into(),
+                        format!("--compress={}", self.config.compression.min(9)).into(),
+                        "--no-owner".into(),
+                        "--no-privileges".into(),
+                        OsString::from(format!("--file={}", file.display())),
+                        format!("--dbname={}", target.connection_string).into(),
+                    ],
+                )
+                .await?;
+            }
+            Engine::Mongo => {
+                run(
+                    "mongodump",
+                    vec![
+                        format!("--uri={}", target.connection_string).into(),
+                        OsString::from(format!("--archive={}", file.display())),
+                        "--gzip".into(),
+                    ],
+                )
+                .await?;
+            }
+        }
+
+        let (sha256, size) = file_digest(&file).await?;
+        let key = format!(
+            "{}/{}/{}.{}",
+            self.config.prefix,
+            target.name,
+            id,
+            target.engine.extension()
+        );
+        self.store.put_file(&key, &file).await?;
+        let record = BackupRecord {
+            id: id.clone(),
+            target: target.name.clone(),
+            engine: target.engine,
+            url: self.store.url(&key),
+            key,
+            size,
+            sha256,
+            started_at,
+            completed_at: Utc::now(),
+            verification: None,
+        };
+        self.save(&record).await?;
+        Ok(record)
+    }
+
+    async fn save(&self, record: &BackupRecord) -> Result<()> {
+        self.store
+            .put(
+                &self.manifest_key(&record.target, &record.id),
+                &serde_json::to_vec_pretty(record)?,
+            )
+            .await
+    }
+
+    /// Backups newest first, optionally for one target
+    pub async fn list(&self, target: Option<&str>) -> Result<Vec<BackupRecord>> {
+        let prefix = match target {
+            Some(target) => {
+                validate_name(target)?;
+                format!("{}/{}/", self.config.prefix, target)
+            }
+            None => format!("{}/", self.config.prefix),
+        };
+        let mut records = Vec::new();
+        for object in self.store.list(&prefix).await? {
+            if !object.key.ends_with(".json") {
+                continue;
+            }
+            match serde_json::from_slice::<BackupRecord>(&self.store.get(&object.key).await?) {
+                Ok(record) => records.push(record),
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable backup manifest {}: {}", object.key, e)
+                }
+            }
+        }
+        records.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+        Ok(records)
+    }
+
+    async fn find(&self, id: &str) -> Result<BackupRecord> {
+        // Ids are `<target>-<timestamp>`; targets may themselves contain dashes
+        let target = id.rsplit_once('-').map(|(target, _)| target).unwrap_or(id);
+        validate_name(target)?;
+        let key = self.manifest_key(target, id);
+        let bytes = self
+            .store
+            .get(&key)
+            .await
+            .map_err(|_| Error::not_found_with_resource("Backup not found", "backup", id))?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| Error::parsing(format!("Invalid backup manifest {}: {}", key, e)))
+    }
+
+    /// Download a backup, check its digest and restore it into `connection_string`
+    pub async fn restore(&self, id: &str, connection_string: &str) -> Result<String> {
+        let record = self.find(id).await?;
+        let dir = tempfile::tempdir()
+            .map_err(|e| Error::internal(format!("Failed to create temp dir: {}", e)))?;
+        let file = dir
+            .path()
+            .join(format!("{}.{}", record.id, record.engine.extension()));
+        self.store.get_file(&record.key, &file).await?;
+        let (sha256, _) = file_digest(&file).await?;
+        if sha256 != record.sha256 {
+            return Err(Error::validation(format!(
+                "Backup {} is corrupt: expected sha256 {}, got {}",
+                id, record.sha256, sha256
+            )));
+        }
+        self.restore_file(record.engine, &file, connection_string)
+            .await
+    }
+
+    async fn restore_file(
+        &self,
+        engine: Engine,
+        file: &Path,
+        connection_string: &str,
+    ) -> Result<String> {
+        match engine {
+            Engine::Postgres => {
+                run(
+                    "pg_restore",
+                    vec![
+                        "--clean".into(),
+                        "--if-exists".into(),
+                        "--no-owner".into(),
+                        "--no-privileges".into(),
+                        "--exit-on-error".into(),
+                        format!("--dbname={}", connection_string).into(),
+                        file.as_os_str().to_owned(),
+                    ],
+                )
+                .await?;
+                let (listing, _) = run(
+                    "pg_restore",
+                    vec!["--list".into(), file.as_os_str().to_owned()],
+                )
+                .await?;
+                let entries = listing
+                    .lines()
+                    .filter(|l| !l.trim().is_empty() && !l.starts_with(';'))
+                    .count();
+                Ok(format!("{} archive entries restored", entries))
+            }
+            Engine::Mongo => {
+                let (stdout, stderr) = run(
+                    "mongorestore",
+                    vec![
+                        format!("--uri={}", connection_string).into(),
+                        OsString::from(format!("--archive={}", file.display())),
+                        "--gzip".into(),
+                        "--drop".into(),
+                    ],
+                )
+                .await?;
+                let summary = Regex::new(
+                    r"(\d+) document\(s\) restored successfully\. (\d+) document\(s\) failed",
+                )
+                .unwrap();
+                let log = format!("{}{}", stdout, stderr);
+                match summary.captures(&log) {
+                    Some(c) if &c[2] != "0" => Err(Error::service(format!(
+                        "mongorestore: {} documents failed to restore",
+                        &c[2]
+                    ))),
+                    Some(c) => Ok(format!("{} documents restored", &c[1])),
+                    None => Ok("restore completed".to_string()),
+                }
+            }
+        }
+    }
+
+    /// Restore a backup into the scratch database and record the outcome
+    pub async fn test_restore(&self, id: &str) -> Result<BackupRecord> {
+        let mut record = self.find(id).await?;
+        let scratch = match record.engine {
+            Engine::Postgres => self.config.scratch_postgres.as_deref(),
+            Engine::Mongo => self.config.scratch_mongo.as_deref(),
+        }
+        .ok_or_else(|| {
+            Error::config_with_suggestion(
+                "No scratch database configured for test restores",
+                "Set BACKUP_SCRATCH_POSTGRES_URL or BACKUP_SCRATCH_MONGODB_URL",
+            )
+        })?;
+        let outcome = self.restore(id, scratch).await;
+        record.verification = Some(Verification {
+            verified_at: Utc::now(),
+            ok: outcome.is_ok(),
+            detail: match &outcome {
+                Ok(detail) => detail.clone(),
+                Err(e) => e.to_string(),
+            },
+        });
+        self.save(&record).await?;
+        Ok(record)
+    }
+
+    /// Delete backups of a target that the retention policy no longer keeps
+    pub async fn apply_retention(&self, target: &str) -> Result<Vec<String>> {
+        let records = self.list(Some(target)).await?;
+        let expired = expired(&records, &self.config.retention);
+        for record in records.iter().filter(|r| expired.contains(&r.id)) {
+            self.store.delete(&record.key).await?;
+            self.store
+                .delete(&self.manifest_key(&record.target, &record.id))
+                .await?;
+        }
+        Ok(expired)
+    }
+
+    /// Back up, prune and optionally test-restore every registered target every `every`
+    pub fn spawn_schedule(
+        self: &Arc<Self>,
+        every: Duration,
+        verify: bool,
+    ) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(every);
+            loop {
+                ticker.tick().await;
+                for target in manager.targets.values() {
+                    let record = match manager.backup(target).await {
+                        Ok(record) => record,
+                        Err(e) => {
+                            tracing::warn!("Backup of {} failed: {}", target.name, e);
+                            continue;
+                        }
+                    };
+                    tracing::info!("Backed up {} to {}", target.name, record.url);
+                    if verify {
+                        match manager.test_restore(&record.id).await {
+                            Ok(r) if r.verification.as_ref().is_some_and(|v| v.ok) => {}
+                            Ok(r) => tracing::warn!(
+                                "Test restore of {} failed: {}",
+                                r.id,
+                                r.verification.map(|v| v.detail).unwrap_or_default()
+                            ),
+                            Err(e) => tracing::warn!("Test restore of {} failed: {}", record.id, e),
+                        }
+                    }
+                    if let Err(e) = manager.apply_retention(&target.name).await {
+                        tracing::warn!("Retention for {} failed: {}", target.name, e);
+                    }
+                }
+            }
+        })
+    }
+
+    /// Get tool definitions for backups
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "backup_now",
+                "Back up a database with pg_dump or mongodump, upload it to object storage, apply retention and optionally verify it with a test restore",
+                "database",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "target": {"type": "string", "description": "Registered target, or a name for an ad hoc backup"},
+                        "provider": {"type": "string", "enum": ["postgresql", "supabase", "mongodb"]},
+                        "connection_string": {"type": "string"},
+                        "verify": {"type": "boolean", "default": false, "description": "Test-restore into the scratch database"}
+                    },
+                    "required": ["target"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "list_backups",
+                "List stored backups with size, checksum and test-restore status",
+                "database",
+                json!({
+                    "type": "object",
+                    "properties": {"target": {"type": "string"}}
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "restore_backup",
+                "Restore a backup after checking its checksum, either into the scratch database as a test or into a given database",
+                "database",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "backup_id": {"type": "string"},
+                        "scratch": {"type": "boolean", "default": true, "description": "Restore into the configured scratch database and record the result"},
+                        "connection_string": {"type": "string", "description": "Database to overwrite when scratch is false"},
+                        "confirm": {"type": "boolean", "default": false, "description": "Required to overwrite a non-scratch database"}
+                    },
+                    "required": ["backup_id"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a backup tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let string = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let flag = |key: &str, default: bool| {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_bool())
+                .unwrap_or(default)
+        };
+        match name {
+            "backup_now" => {
+                let target_name = string("target")
+                    .ok_or_else(|| Error::validation_with_field("target is required", "target"))?;
+                let target = match (self.targets.get(target_name), string("connection_string")) {
+                    (_, Some(connection_string)) => BackupTarget {
+                        name: target_name.to_string(),
+                        engine: Engine::from_provider(string("provider").unwrap_or("postgresql"))?,
+                        connection_string: connection_string.to_string(),
+                    },
+                    (Some(target), None) => target.clone(),
+                    (None, None) => {
+                        return Err(Error::not_found_with_resource(
+                            "Backup target not registered; pass provider and connection_string",
+                            "backup_target",
+                            target_name,
+                        ))
+                    }
+                };
+                let mut record = self.backup(&target).await?;
+                if flag("verify", false) {
+                    record = self.test_restore(&record.id).await?;
+                }
+                let pruned = self.apply_retention(&target.name).await?;
+                let mut text = format!(
+                    "Backed up {} to {} ({} bytes, sha256 {})",
+                    target.name, record.url, record.size, record.sha256
+                );
+                if let Some(v) = &record.verification {
+                    text.push_str(&format!(
+                        "\nTest restore {}: {}",
+                        if v.ok { "passed" } else { "FAILED" },
+                        v.detail
+                    ));
+                }
+                if !pruned.is_empty() {
+                    text.push_str(&format!("\nPruned {}", pruned.join(", ")));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "backup": record, "pruned": pruned }),
+                ))
+            }
+            "list_backups" => {
+                let records = self.list(string("target")).await?;
+                let mut text = format!("{} backups", records.len());
+                for r in &records {
+                    let status = match &r.verification {
+                        Some(v) if v.ok => "verified",
+                        Some(_) => "restore failed",
+                        None => "unverified",
+                    };
+                    text.push_str(&format!("\n- {} ({} bytes, {})", r.id, r.size, status));
+                }
+                Ok(call_result(text, json!({ "backups": records })))
+            }
+            "restore_backup" => {
+                let id = string("backup_id").ok_or_else(|| {
+                    Error::validation_with_field("backup_id is required", "backup_id")
+                })?;
+                if flag("scratch", true) {
+                    let record = self.test_restore(id).await?;
+                    let verification = record.verification.clone();
+                    let text = match &verification {
+                        Some(v) if v.ok => format!("Test restore of {} passed: {}", id, v.detail),
+                        Some(v) => format!("Test restore of {} failed: {}", id, v.detail),
+                        None => format!("Test restore of {} did not run", id),
+                    };
+                    return Ok(call_result(text, json!({ "backup": record })));
+                }
+                let connection_string = string("connection_string").ok_or_else(|| {
+                    Error::validation_with_field(
+                        "connection_string is required when scratch is false",
+                        "connection_string",
+                    )
+                })?;
+                if !flag("confirm", false) {
+                    return Err(Error::validation_with_field(
+                        "Restoring drops and replaces existing data; set confirm to true",
+                        "confirm",
+                    ));
+                }
+                let detail = self.restore(id, connection_string).await?;
+                Ok(call_result(
+                    format!("Restored {}: {}", id, detail),
+                    json!({ "backup_id": id, "detail": detail }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "backup_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn retention_keeps_last_daily_weekly_and_monthly() {
+        // Two backups a day for 90 days, newest on 2024-06-30
+        let records: Vec<BackupRecord> = (0..180)
+            .map(|i| {
+                let started_at = Utc.with_ymd_and_hms(2024, 6, 30, 22, 0, 0).unwrap()
+                    - chrono::Duration::hours(12 * i);
+                BackupRecord {
+                    id: format!("db-{}", started_at.format("%Y%m%dT%H%M%SZ")),
+                    target: "db".to_string(),
+                    engine: Engine::Postgres,
+                    key: String::new(),
+                    url: String::new(),
+                    size: 0,
+                    sha256: String::new(),
+                    started_at,
+                    completed_at: started_at,
+                    verification: None,
+                }
+            })
+            .collect();
+        let policy = RetentionPolicy {
+            keep_last: 3,
+            keep_daily: 7,
+            keep_weekly: 4,
+            keep_monthly: 3,
+        };
+        let expired = expired(&records, &policy);
+        let kept: Vec<&str> = records
+            .iter()
+            .map(|r| r.id.as_str())
+            .filter(|id| !expired.iter().any(|e| e == id))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                // Last 3, covering 30 June twice and 29 June
+                "db-20240630T220000Z",
+                "db-20240630T100000Z",
+                "db-20240629T220000Z",
+                // Newest of each remaining day in the week
+                "db-20240628T220000Z",
+                "db-20240627T220000Z",
+                "db-20240626T220000Z",
+                "db-20240625T220000Z",
+                "db-20240624T220000Z",
+                // Newest of ISO weeks 25, 24 and 23
+                "db-20240623T220000Z",
+                "db-20240616T220000Z",
+                "db-20240609T220000Z",
+                // Newest of May and April
+                "db-20240531T220000Z",
+                "db-20240430T220000Z",
+            ]
+        );
+    }
+}
@@ -0,0 +1,895 @@
+use crate::config::DatabaseConfig;
+use crate::database::{connect, Database};
+use crate::error::{Error, Result};
+use crate::security::pii::{detect_columns, PiiCategory};
+use crate::tools::{call_result, ToolDefinition};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Rows exported when not specified
+const DEFAULT_LIMIT: usize = 10_000;
+
+/// Rows per INSERT statement
+const INSERT_BATCH: usize = 100;
+
+const FIRST_NAMES: &[&str] = &[
+    "Alex", "Blair", "Casey", "Dana", "Elliot", "Frankie", "Gray", "Harper", "Indy", "Jordan",
+    "Kai", "Logan", "Morgan", "Noel", "Oakley", "Parker", "Quinn", "Riley", "Sage", "Taylor",
+];
+const LAST_NAMES: &[&str] = &[
+    "Abbott", "Barnes", "Carver", "Dalton", "Ellis", "Fletcher", "Garner", "Hayes", "Ingram",
+    "Jensen", "Keller", "Lowe", "Mercer", "Nash", "Osborne", "Pruitt", "Quincy", "Rowe", "Sutton",
+    "Tate",
+];
+const STREETS: &[&str] = &[
+    "Oak", "Maple", "Cedar", "Pine", "Elm", "Willow", "Birch", "Lake", "Hill", "River",
+];
+const CITIES: &[&str] = &[
+    "Springfield",
+    "Riverton",
+    "Fairview",
+    "Greenville",
+    "Madison",
+    "Franklin",
+    "Clinton",
+    "Georgetown",
+    "Salem",
+    "Ashland",
+];
+const COMPANY_WORDS: &[&str] = &[
+    "Acme", "Globex", "Initech", "Umbrella", "Hooli", "Vandelay", "Stark", "Wayne", "Tyrell",
+    "Soylent",
+];
+const LOREM: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+];
+
+/// Masking configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaskingConfig {
+    /// Key for deterministic hashing and faker substitution
+    pub key: Option<String>,
+    /// Connection strings exports read from and insert into, keyed by provider
+    #[serde(default)]
+    pub connections: HashMap<String, String>,
+    /// Directory exports are written under; file exports are refused when unset
+    #[serde(default)]
+    pub export_dir: Option<PathBuf>,
+}
+
+impl MaskingConfig {
+    /// Load configuration from `DATA_MASKING_KEY`
+    pub fn from_env() -> Self {
+        Self {
+            key: std::env::var("DATA_MASKING_KEY")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            ..Self::default()
+        }
+    }
+
+    /// Key from `DATA_MASKING_KEY`, with the connections and export
+    /// directory of the database config
+    pub fn from_database(database: &DatabaseConfig) -> Self {
+        Self {
+            connections: database.connections.clone(),
+            export_dir: database.export_dir.clone(),
+            ..Self::from_env()
+        }
+    }
+}
+
+/// Kind of substitute value
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FakeKind {
+    Name,
+    FirstName,
+    LastName,
+    Email,
+    Phone,
+    Address,
+    City,
+    Company,
+    /// Lorem ipsum with the same word count
+    Text,
+    /// Same format with every digit replaced
+    Digits,
+}
+
+/// How a column is masked
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MaskRule {
+    /// Copy unchanged
+    Keep,
+    /// Replace with null
+    Null,
+    /// Replace with a fixed string
+    Redact {
+        #[serde(default = "default_redaction")]
+        with: String,
+    },
+    /// Keyed hash, stable across tables so joins still line up
+    Hash {
+        #[serde(default = "default_hash_length")]
+        length: usize,
+    },
+    /// Deterministic realistic substitute
+    Fake { kind: FakeKind },
+    /// Keep `keep` characters from the start (or end) and drop or star out the rest
+    Truncate {
+        keep: usize,
+        #[serde(default)]
+        from_end: bool,
+        #[serde(default)]
+        pad: bool,
+    },
+}
+
+fn default_redaction() -> String {
+    "[REDACTED]".to_string()
+}
+
+fn default_hash_length() -> usize {
+    16
+}
+
+impl MaskRule {
+    /// Rule suggested for a detected PII category
+    pub fn for_category(category: PiiCategory) -> Self {
+        match category {
+            PiiCategory::Email => MaskRule::Fake {
+                kind: FakeKind::Email,
+            },
+            PiiCategory::Phone => MaskRule::Fake {
+                kind: FakeKind::Phone,
+            },
+            PiiCategory::Name => MaskRule::Fake {
+                kind: FakeKind::Name,
+            },
+            PiiCategory::Address => MaskRule::Fake {
+                kind: FakeKind::Address,
+            },
+            PiiCategory::NationalId | PiiCategory::PaymentCard | PiiCategory::BankAccount => {
+                MaskRule::Truncate {
+                    keep: 4,
+                    from_end: true,
+                    pad: true,
+                }
+            }
+            PiiCategory::DateOfBirth => MaskRule::Truncate {
+                keep: 4,
+                from_end: false,
+                pad: false,
+            },
+            PiiCategory::IpAddress => MaskRule::Hash {
+                length: default_hash_length(),
+            },
+            PiiCategory::Credentials => MaskRule::Null,
+        }
+    }
+
+    fn needs_key(&self) -> bool {
+        matches!(self, MaskRule::Hash { .. } | MaskRule::Fake { .. })
+    }
+}
+
+/// Output file format
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Jsonl,
+    Csv,
+    /// INSERT statements for the destination table
+    Sql,
+}
+
+/// What a masked export wrote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub rows: usize,
+    /// Rule applied per column, including detected ones
+    pub rules: BTreeMap<String, MaskRule>,
+    pub output: Option<PathBuf>,
+    /// Rows inserted into the destination database
+    pub inserted: Option<u64>,
+}
+
+/// Write an export to `file` under the export directory, returning the path written
+///
+/// `file` must be relative without `..`, and must still land inside `dir`
+/// once symlinks are resolved.
+pub async fn write_export(dir: Option<&Path>, file: &Path, contents: String) -> Result<PathBuf> {
+    let dir = dir.ok_or_else(|| {
+        Error::config_with_suggestion(
+            "No export directory configured",
+            "Set database.export_dir to the directory exports are written under",
+        )
+    })?;
+    let relative = file
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    let name = file.file_name().filter(|_| relative).ok_or_else(|| {
+        Error::validation_with_field(
+            format!(
+                "{} must be a relative path inside the export directory",
+                file.display()
+            ),
+            "output_path",
+        )
+    })?;
+    let create = |path: &Path| {
+        std::fs::create_dir_all(path).map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to create export directory: {}", e),
+                path.to_path_buf(),
+            )
+        })
+    };
+    let canonical = |path: &Path| {
+        path.canonicalize().map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to resolve export directory: {}", e),
+                path.to_path_buf(),
+            )
+        })
+    };
+    create(dir)?;
+    let root = canonical(dir)?;
+    let inside = |path: &Path| -> Result<PathBuf> {
+        let path = canonical(path)?;
+        if path.starts_with(&root) {
+            Ok(path)
+        } else {
+            Err(Error::validation_with_field(
+                format!("{} is outside the export directory", file.display()),
+                "output_path",
+            ))
+        }
+    };
+    let target = dir.join(file);
+    let parent = target.parent().unwrap_or(dir);
+    // Symlinks already inside the directory must not lead new ones elsewhere
+    if let Some(existing) = parent.ancestors().find(|p| p.exists()) {
+        inside(existing)?;
+    }
+    create(parent)?;
+    let parent = inside(parent)?;
+    let path = parent.join(name);
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|e| Error::io_with_path(format!("Failed to write export: {}", e), path.clone()))?;
+    Ok(path)
+}
+
+fn text_of(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(b) => b.to_string().to_uppercase(),
+        Value::Number(n) => n.to_string(),
+        other => format!("'{}'", text_of(other).replace('\'', "''")),
+    }
+}
+
+fn csv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => return String::new(),
+        other => text_of(other),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// Export columns: `columns` in the order the query returned them, then
+/// keys only the rows have, by name
+///
+/// Document stores report no columns, so their fields come out sorted
+/// rather than in whatever order the JSON objects iterate.
+fn column_order(columns: &[String], rows: &[Value]) -> Vec<String> {
+    let extra: BTreeSet<&String> = rows
+        .iter()
+        .filter_map(Value::as_object)
+        .flat_map(|row| row.keys())
+        .filter(|key| !columns.contains(key))
+        .collect();
+    columns
+        .iter()
+        .cloned()
+        .chain(extra.into_iter().cloned())
+        .collect()
+}
+
+/// Deterministic masking of rows by per-column rules
+pub struct DataMasker {
+    config: MaskingConfig,
+}
+
+impl DataMasker {
+    /// Create a masker
+    pub fn new(config: MaskingConfig) -> Self {
+        Self { config }
+    }
+
+    fn digest(&self, domain: &str, input: &str) -> Result<[u8; 32]> {
+        let key = self.config.key.as_deref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Hash and fake rules need a masking key",
+                "Set DATA_MASKING_KEY; the same key yields the same substitutes across exports",
+            )
+        })?;
+        let mut mac = HmacSha256::new_from_slice(key.as_bytes())
+            .map_err(|e| Error::internal(format!("Invalid masking key: {}", e)))?;
+        mac.update(domain.as_bytes());
+        mac.update(&[0]);
+        mac.update(input.as_bytes());
+        Ok(mac.finalize().into_bytes().into())
+    }
+
+    fn fake(&self, kind: FakeKind, input: &str) -> Result<String> {
+        let d = self.digest(&format!("fake:{:?}", kind), input)?;
+        let pick = |list: &[&'static str], i: usize| list[d[i] as usize % list.len()];
+        let number = u32::from_be_bytes([d[8], d[9], d[10], d[11]]);
+        Ok(match kind {
+            FakeKind::Name => format!("{} {}", pick(FIRST_NAMES, 0), pick(LAST_NAMES, 1)),
+            FakeKind::FirstName => pick(FIRST_NAMES, 0).to_string(),
+            FakeKind::LastName => pick(LAST_NAMES, 1).to_string(),
+            FakeKind::Email => format!(
+                "{}.{}{}@example.com",
+                pick(FIRST_NAMES, 0).to_lowercase(),
+                pick(LAST_NAMES, 1).to_lowercase(),
+                number % 1000
+            ),
+            // 555-01xx numbers are reserved for fiction
+            FakeKind::Phone => format!("+1-555-01{:02}", number % 100),
+            FakeKind::Address => format!(
+                "{} {} St, {}",
+                number % 9000 + 100,
+                pick(STREETS, 2),
+                pick(CITIES, 3)
+            ),
+            FakeKind::City => pick(CITIES, 3).to_string(),
+            FakeKind::Company => format!("{} {}", pick(COMPANY_WORDS, 4), pick(LAST_NAMES, 5)),
+            FakeKind::Text => {
+                let words = input.split_whitespace().count().max(1);
+                (0..words)
+                    .map(|i| LOREM[d[i % d.len()] as usize % LOREM.len()])
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+            FakeKind::Digits => {
+                let mut i = 0;
+                input
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_digit() {
+                            i += 1;
+                            char::from(b'0' + d[(i - 1) % d.len()] % 10)
+                        } else {
+                            c
+                        }
+                    })
+                    .collect()
+            }
+        })
+    }
+
+    /// Mask one value; nulls stay null
+    pub fn mask_value(&self, rule: &MaskRule, value: &Value) -> Result<Value> {
+        if value.is_null() {
+            return Ok(Value::Null);
+        }
+        Ok(match rule {
+            MaskRule::Keep => value.clone(),
+            MaskRule::Null => Value::Null,
+            MaskRule::Redact { with } => Value::String(with.clone()),
+            MaskRule::Hash { length } => {
+                let hex: String = self
+                    .digest("hash", &text_of(value))?
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect();
+                Value::String(hex[..(*length).clamp(8, hex.len())].to_string())
+            }
+            MaskRule::Fake { kind } => Value::String(self.fake(*kind, &text_of(value))?),
+            MaskRule::Truncate {
+                keep,
+                from_end,
+                pad,
+            } => {
+                let chars: Vec<char> = text_of(value).chars().collect();
+                let keep = (*keep).min(chars.len());
+                let hidden = "*".repeat(if *pad { chars.len() - keep } else { 0 });
+                Value::String(if *from_end {
+                    format!(
+                        "{}{}",
+                        hidden,
+                        chars[chars.len() - keep..].iter().collect::<String>()
+                    )
+                } else {
+                    format!("{}{}", chars[..keep].iter().collect::<String>(), hidden)
+                })
+            }
+        })
+    }
+
+    fn apply(&self, value: &mut Value, path: &[&str], rule: &MaskRule) -> Result<()> {
+        match (value, path) {
+            (Value::Array(items), _) => {
+                for item in items {
+                    self.apply(item, path, rule)?;
+                }
+            }
+            (Value::Object(map), [key, rest @ ..]) => {
+                if let Some(child) = map.get_mut(*key) {
+                    if rest.is_empty() {
+                        *child = self.mask_value(rule, child)?;
+                    } else {
+                        self.apply(child, rest, rule)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Apply rules to rows; dotted column names reach into nested documents
+    pub fn mask_rows(&self, rows: &mut [Value], rules: &BTreeMap<String, MaskRule>) -> Result<()> {
+        if rules.values().any(MaskRule::needs_key) {
+            self.digest("check", "")?;
+        }
+        for row in rows.iter_mut() {
+            for (column, rule) in rules {
+                let path: Vec<&str> = column.split('.').collect();
+                self.apply(row, &path, rule)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rules for the export: explicit rules plus, when `auto_detect`, suggestions for detected PII
+    pub fn resolve_rules(
+        rows: &[Value],
+        rules: &BTreeMap<String, MaskRule>,
+        auto_detect: bool,
+    ) -> BTreeMap<String, MaskRule> {
+        let mut resolved = rules.clone();
+        if auto_detect {
+            for column in detect_columns(rows, &[]) {
+                resolved
+                    .entry(column.column)
+                    .or_insert_with(|| MaskRule::for_category(column.category));
+            }
+        }
+        resolved.retain(|_, rule| *rule != MaskRule::Keep);
+        resolved
+    }
+
+    /// Render rows in an export format, with columns ordered by `column_order`
+    pub fn render(rows: &[Value], columns: &[String], format: ExportFormat, table: &str) -> String {
+        let columns = column_order(columns, rows);
+        let mut out = String::new();
+        match format {
+            ExportFormat::Jsonl => {
+                for row in rows {
+                    out.push_str(&row.to_string());
+                    out.push('\n');
+                }
+            }
+            ExportFormat::Csv => {
+                out.push_str(&columns.join(","));
+                out.push('\n');
+                for row in rows {
+                    let fields: Vec<String> = columns
+                        .iter()
+                        .map(|c| csv_field(row.get(c).unwrap_or(&Value::Null)))
+                        .collect();
+                    out.push_str(&fields.join(","));
+                    out.push('\n');
+                }
+            }
+            ExportFormat::Sql => {
+                let names: Vec<String> = columns.iter().map(|c| quote_ident(c)).collect();
+                for batch in rows.chunks(INSERT_BATCH) {
+                    let values: Vec<String> = batch
+                        .iter()
+                        .map(|row| {
+                            let literals: Vec<String> = columns
+                                .iter()
+                                .map(|c| sql_literal(row.get(c).unwrap_or(&Value::Null)))
+                                .collect();
+                            format!("({})", literals.join(", "))
+                        })
+                        .collect();
+                    out.push_str(&format!(
+                        "INSERT INTO {} ({}) VALUES\n{};\n",
+                        quote_ident(table),
+                        names.join(", "),
+                        values.join(",\n")
+                    ));
+                }
+            }
+        }
+        out
+    }
+
+    async fn fetch(
+        source: &dyn Database,
+        document_store: bool,
+        table: Option<&str>,
+        query: Option<&str>,
+        schema: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<Value>, Vec<String>)> {
+        let query = match (table, query) {
+            (_, Some(query)) => {
+                let lower = query.trim_start().to_lowercase();
+                let read_only = if document_store {
+                    serde_json::from_str::<Value>(query)
+                        .is_ok_and(|q| q["operation"].as_str() == Some("find"))
+                } else {
+                    lower.starts_with("select") || lower.starts_with("with")
+                };
+                if !read_only {
+                    return Err(Error::validation_with_field(
+                        "Only read queries (SELECT, or MongoDB find) can be exported",
+                        "query",
+                    ));
+                }
+                query.to_string()
+            }
+            (Some(table), None) if document_store => {
+                json!({"collection": table, "operation": "find", "limit": limit}).to_string()
+            }
+            (Some(table), None) => format!(
+                "SELECT * FROM {}.{} LIMIT {}",
+                quote_ident(schema.unwrap_or("public")),
+                quote_ident(table),
+                limit
+            ),
+            (None, None) => {
+                return Err(Error::validation_with_field(
+                    "Either table or query is required",
+                    "table",
+                ))
+            }
+        };
+        let result = source.execute_query(&query, schema).await?;
+        let columns = result.columns.into_iter().map(|c| c.name).collect();
+        let mut rows = result.rows;
+        rows.truncate(limit);
+        Ok((rows, columns))
+    }
+
+    async fn insert(
+        destination: &dyn Database,
+        document_store: bool,
+        table: &str,
+        rows: &[Value],
+        columns: &[String],
+    ) -> Result<u64> {
+        let mut inserted = 0;
+        if document_store {
+            for row in rows {
+                let command = json!({"collection": table, "operation": "insert", "document": row});
+                inserted += destination
+                    .execute_query(&command.to_string(), None)
+                    .await?
+                    .rows_affected;
+            }
+        } else {
+            for statement in Self::render(rows, columns, ExportFormat::Sql, table).split(";\n") {
+                if !statement.trim().is_empty() {
+                    inserted += destination
+                        .execute_query(statement, None)
+                        .await?
+                        .rows_affected;
+                }
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// Connection configured for `provider`
+    async fn connection(&self, provider: &str) -> Result<Arc<dyn Database>> {
+        let url = self.config.connections.get(provider).ok_or_else(|| {
+            Error::config_with_suggestion(
+                format!("No connection configured for {}", provider),
+                format!("Set database.connections.{}", provider),
+            )
+        })?;
+        connect(provider, url).await
+    }
+
+    /// Get tool definitions for masked exports
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "masked_export",
+            "Copy a table or read query with deterministic per-column masking (hash, fake substitution, truncation, redaction) to a file or another database, for sharing production-like data safely",
+            "database",
+            json!({
+                "type": "object",
+                "properties": {
+                    "provider": {"type": "string", "enum": ["postgresql", "supabase", "mongodb"], "description": "Configured connection to read from"},
+                    "database": {"type": "string", "description": "Schema (SQL) or database (MongoDB)"},
+                    "table": {"type": "string"},
+                    "query": {"type": "string", "description": "SELECT statement, or MongoDB find command JSON, instead of a table"},
+                    "limit": {"type": "integer", "default": DEFAULT_LIMIT},
+                    "rules": {
+                        "type": "object",
+                        "description": "Column (dotted path for nested documents) to rule, e.g. {\"email\": {\"type\": \"fake\", \"kind\": \"email\"}, \"ssn\": {\"type\": \"truncate\", \"keep\": 4, \"from_end\": true, \"pad\": true}}",
+                        "additionalProperties": {
+                            "type": "object",
+                            "properties": {
+                                "type": {"type": "string", "enum": ["keep", "null", "redact", "hash", "fake", "truncate"]}
+                            },
+                            "required": ["type"]
+                        }
+                    },
+                    "auto_detect": {"type": "boolean", "default": true, "description": "Mask detected PII columns without an explicit rule"},
+                    "format": {"type": "string", "enum": ["jsonl", "csv", "sql"], "default": "jsonl"},
+                    "output_path": {"type": "string", "description": "File to write, relative to database.export_dir"},
+                    "destination_provider": {"type": "string", "enum": ["postgresql", "supabase", "mongodb"], "description": "Configured connection to insert into; defaults to provider"},
+                    "destination_table": {"type": "string", "description": "Existing table or collection to insert masked rows into"}
+                },
+                "required": ["provider"]
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a masking tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        if name != "masked_export" {
+            return Err(Error::not_found_with_resource(
+                "Tool not found",
+                "masking_tool",
+                name,
+            ));
+        }
+        let string = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let required = |key: &str| {
+            string(key)
+                .ok_or_else(|| Error::validation_with_field(format!("{} is required", key), key))
+        };
+        let provider = required("provider")?;
+        let rules: BTreeMap<String, MaskRule> = serde_json::from_value(
+            parameters.get("rules").cloned().unwrap_or(json!({})),
+        )
+        .map_err(|e| Error::validation_with_field(format!("Invalid rules: {}", e), "rules"))?;
+        let format: ExportFormat =
+            serde_json::from_value(parameters.get("format").cloned().unwrap_or(json!("jsonl")))
+                .map_err(|e| {
+                    Error::validation_with_field(format!("Invalid format: {}", e), "format")
+                })?;
+        let limit = parameters
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .unwrap_or(DEFAULT_LIMIT);
+        let output = string("output_path").map(PathBuf::from);
+        let destination_table = string("destination_table");
+        if output.is_none() && destination_table.is_none() {
+            return Err(Error::validation_with_field(
+                "Set output_path, destination_table, or both",
+                "output_path",
+            ));
+        }
+
+        let source = self.connection(provider).await?;
+        let (mut rows, columns) = Self::fetch(
+            source.as_ref(),
+            provider == "mongodb",
+            string("table"),
+            string("query"),
+            string("database"),
+            limit,
+        )
+        .await?;
+        let auto_detect = parameters
+            .get("auto_detect")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let rules = Self::resolve_rules(&rows, &rules, auto_detect);
+        self.mask_rows(&mut rows, &rules)?;
+
+        let table = string("table").unwrap_or("masked_export");
+        let output = match &output {
+            Some(path) => {
+                let rendered =
+                    Self::render(&rows, &columns, format, destination_table.unwrap_or(table));
+                Some(write_export(self.config.export_dir.as_deref(), path, rendered).await?)
+            }
+            None => None,
+        };
+        let inserted = match destination_table {
+            Some(destination_table) => {
+                let provider = string("destination_provider").unwrap_or(provider);
+                let destination = self.connection(provider).await?;
+                Some(
+                    Self::insert(
+                        destination.as_ref(),
+                        provider == "mongodb",
+                        destination_table,
+                        &rows,
+                        &columns,
+                    )
+                    .await?,
+                )
+            }
+            None => None,
+        };
+
+        let summary = ExportSummary {
+            rows: rows.len(),
+            rules,
+            output,
+            inserted,
+        };
+        let mut text = format!("Exported {} masked rows", summary.rows);
+        if let Some(path) = &summary.output {
+            text.push_str(&format!(" to {}", path.display()));
+        }
+        if let (Some(count), Some(table)) = (summary.inserted, destination_table) {
+            text.push_str(&format!(", inserted {} into {}", count, table));
+        }
+        for (column, rule) in &summary.rules {
+            text.push_str(&format!("\n- {}: {}", column, serde_json::to_string(rule)?));
+        }
+        Ok(call_result(text, json!({ "export": summary })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_rows_deterministically_with_detected_rules() {
+        let masker = DataMasker::new(MaskingConfig {
+            key: Some("test-key".to_string()),
+            ..MaskingConfig::default()
+        });
+        let source = vec![
+            json!({"id": 1, "email": "jane@corp.com", "ssn": "123-45-6789", "owner_id": "u-17", "notes": "called twice"}),
+            json!({"id": 2, "email": "bob@corp.com", "ssn": null, "owner_id": "u-17", "notes": "vip"}),
+        ];
+        let explicit = BTreeMap::from([
+            ("owner_id".to_string(), MaskRule::Hash { length: 12 }),
+            (
+                "notes".to_string(),
+                MaskRule::Fake {
+                    kind: FakeKind::Text,
+                },
+            ),
+        ]);
+        let rules = DataMasker::resolve_rules(&source, &explicit, true);
+        assert_eq!(
+            rules.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["email", "notes", "owner_id", "ssn"]
+        );
+
+        let mut first = source.clone();
+        let mut second = source.clone();
+        masker.mask_rows(&mut first, &rules).unwrap();
+        masker.mask_rows(&mut second, &rules).unwrap();
+        assert_eq!(first, second);
+
+        assert_eq!(first[0]["ssn"], "*******6789");
+        assert!(first[1]["ssn"].is_null());
+        assert_eq!(first[0]["owner_id"], first[1]["owner_id"]);
+        assert_eq!(first[0]["owner_id"].as_str().unwrap().len(), 12);
+        assert!(first[0]["email"]
+            .as_str()
+            .unwrap()
+            .ends_with("@example.com"));
+        assert_ne!(first[0]["email"], first[1]["email"]);
+        assert_eq!(first[0]["notes"].as_str().unwrap().split(' ').count(), 2);
+        assert_eq!(first[0]["id"], 1);
+
+        // Query columns keep their order; keys they do not list follow by name
+        let columns = ["id", "ssn", "email"].map(String::from);
+        let csv = DataMasker::render(&first, &columns, ExportFormat::Csv, "users");
+        assert!(csv.starts_with("id,ssn,email,notes,owner_id\n"));
+        let sql = DataMasker::render(&first, &[], ExportFormat::Sql, "users");
+        assert!(sql.starts_with(
+            "INSERT INTO \"users\" (\"email\", \"id\", \"notes\", \"owner_id\", \"ssn\") VALUES\n"
+        ));
+
+        let keyless = DataMasker::new(MaskingConfig::default());
+        assert!(keyless.mask_rows(&mut first, &rules).is_err());
+    }
+
+    #[tokio::test]
+    async fn reads_only_from_configured_connections() {
+        let masker = DataMasker::new(MaskingConfig::default());
+        let error = masker
+            .execute_tool(
+                "masked_export",
+                json!({
+                    "provider": "postgresql",
+                    "connection_string": "postgres://attacker.example/db",
+                    "table": "users",
+                    "output_path": "users.jsonl"
+                }),
+            )
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("No connection configured for postgresql"));
+    }
+
+    #[tokio::test]
+    async fn writes_exports_only_inside_the_export_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let exports = root.path().join("exports");
+        let write = |file: &str| {
+            let (exports, file) = (exports.clone(), PathBuf::from(file));
+            async move { write_export(Some(&exports), &file, "{}".to_string()).await }
+        };
+
+        assert!(write_export(None, Path::new("rows.jsonl"), String::new())
+            .await
+            .is_err());
+        let written = write("daily/rows.jsonl").await.unwrap();
+        assert_eq!(
+            written,
+            exports.canonicalize().unwrap().join("daily/rows.jsonl")
+        );
+        assert!(write("../escaped.jsonl").await.is_err());
+        assert!(write("daily/../../escaped.jsonl").await.is_err());
+        let absolute = root.path().join("absolute.jsonl");
+        assert!(write(&absolute.to_string_lossy()).await.is_err());
+        assert!(!root.path().join("escaped.jsonl").exists() && !absolute.exists());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.path(), exports.join("link")).unwrap();
+            assert!(write("link/escaped.jsonl").await.is_err());
+            assert!(write("link/nested/escaped.jsonl").await.is_err());
+            assert!(!root.path().join("nested").exists());
+        }
+    }
+}
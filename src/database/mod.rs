@@ -5,8 +5,13 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
 
+pub mod backup;
+pub mod masking;
 pub mod mongodb;
 pub mod postgresql;
+pub mod safety;
+pub mod schema;
+pub mod sqlite;
 pub mod supabase;
 
 /// Database status structure
@@ -63,21 +68,172 @@ pub struct Table {
     pub size_bytes: Option<u64>,
 }
 
+/// Connection pool settings, under `database.pool` in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolConfig {
+    /// Most connections open at once
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+    /// Connections kept open while idle
+    #[serde(default)]
+    pub min_connections: u32,
+    /// Longest wait for a free connection
+    #[serde(default = "default_acquire_timeout_secs")]
+    pub acquire_timeout_secs: u64,
+    /// Idle connections are closed after this long
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: Option<u64>,
+    /// Server-side limit on each statement's run time
+    #[serde(default)]
+    pub statement_timeout_ms: Option<u64>,
+    /// Open transactions are rolled back after this long
+    #[serde(default = "default_transaction_timeout_secs")]
+    pub transaction_timeout_secs: u64,
+}
+
+fn default_max_connections() -> u32 {
+    10
+}
+
+fn default_acquire_timeout_secs() -> u64 {
+    30
+}
+
+fn default_idle_timeout_secs() -> Option<u64> {
+    Some(600)
+}
+
+fn default_transaction_timeout_secs() -> u64 {
+    300
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: default_max_connections(),
+            min_connections: 0,
+            acquire_timeout_secs: default_acquire_timeout_secs(),
+            idle_timeout_secs: default_idle_timeout_secs(),
+            statement_timeout_ms: None,
+            transaction_timeout_secs: default_transaction_timeout_secs(),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Check that the limits leave room to work
+    pub fn validate(&self) -> Result<()> {
+        if self.max_connections == 0 {
+            return Err(Error::validation_with_field(
+                "max_connections must be at least 1",
+                "max_connections",
+            ));
+        }
+        if self.min_connections > self.max_connections {
+            return Err(Error::validation_with_field(
+                "min_connections cannot exceed max_connections",
+                "min_connections",
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Database trait for provider implementations
 #[async_trait]
 pub trait Database: Send + Sync {
     /// Execute a query
     async fn execute_query(&self, query: &str, database: Option<&str>) -> Result<QueryResult>;
-    
+
+    /// Execute a query with `$1`-style placeholders bound from `params`
+    async fn execute_with_params(
+        &self,
+        query: &str,
+        params: &[Value],
+        database: Option<&str>,
+    ) -> Result<QueryResult> {
+        if params.is_empty() {
+            return self.execute_query(query, database).await;
+        }
+        Err(Error::capability(
+            "This provider does not support query parameters",
+        ))
+    }
+
+    /// Execute a query that must not change anything
+    ///
+    /// Providers with read-only transactions override this so the server
+    /// enforces it; the default just runs the query.
+    async fn execute_read_only(
+        &self,
+        query: &str,
+        params: &[Value],
+        database: Option<&str>,
+    ) -> Result<QueryResult> {
+        self.execute_with_params(query, params, database).await
+    }
+
+    /// Open a transaction, returning its ID
+    async fn begin_transaction(&self) -> Result<String> {
+        Err(Error::capability(
+            "This provider does not support transactions",
+        ))
+    }
+
+    /// Open a transaction in which nothing can be changed, returning its ID
+    ///
+    /// Falls back to a plain transaction for providers without read-only
+    /// transactions.
+    async fn begin_read_only_transaction(&self) -> Result<String> {
+        self.begin_transaction().await
+    }
+
+    /// Execute a query inside an open transaction
+    async fn execute_in_transaction(
+        &self,
+        transaction_id: &str,
+        query: &str,
+        params: &[Value],
+    ) -> Result<QueryResult> {
+        let _ = (transaction_id, query, params);
+        Err(Error::capability(
+            "This provider does not support transactions",
+        ))
+    }
+
+    /// Commit an open transaction
+    async fn commit(&self, transaction_id: &str) -> Result<()> {
+        let _ = transaction_id;
+        Err(Error::capability(
+            "This provider does not support transactions",
+        ))
+    }
+
+    /// Roll back an open transaction
+    async fn rollback(&self, transaction_id: &str) -> Result<()> {
+        let _ = transaction_id;
+        Err(Error::capability(
+            "This provider does not support transactions",
+        ))
+    }
+
     /// List all databases
     async fn list_databases(&self) -> Result<Vec<String>>;
-    
+
     /// List tables in a database
     async fn list_tables(&self, database: Option<&str>) -> Result<Vec<Table>>;
-    
+
     /// Describe a table
     async fn describe_table(&self, table_name: &str, database: Option<&str>) -> Result<Table>;
-    
+
+    /// Tables of a schema with their columns, indexes and foreign keys
+    async fn describe_schema(&self, database: Option<&str>) -> Result<schema::DatabaseSchema> {
+        let _ = database;
+        Err(Error::capability(
+            "This provider does not support schema introspection",
+        ))
+    }
+
     /// Health check
     async fn health_check(&self) -> Result<DatabaseStatus>;
 }
@@ -112,8 +268,47 @@ pub struct DatabaseInfo {
     pub metadata: Value,
 }
 
+/// Open a provider by name as a trait object, with the default pool settings
+pub async fn connect(provider: &str, connection_string: &str) -> Result<Arc<dyn Database>> {
+    connect_with_pool(provider, connection_string, &PoolConfig::default()).await
+}
 
+/// Open a provider by name as a trait object, pooling connections per `pool`
+#[cfg(feature = "database")]
+pub async fn connect_with_pool(
+    provider: &str,
+    connection_string: &str,
+    pool: &PoolConfig,
+) -> Result<Arc<dyn Database>> {
+    match provider {
+        "postgresql" | "supabase" => Ok(Arc::new(
+            postgresql::PostgreSQLProvider::connect(connection_string.to_string(), pool).await?,
+        )),
+        "mongodb" => Ok(Arc::new(
+            mongodb::MongoDBProvider::new(connection_string.to_string()).await?,
+        )),
+        "sqlite" => Ok(Arc::new(
+            sqlite::SqliteProvider::connect(connection_string, pool).await?,
+        )),
+        _ => Err(Error::validation(format!(
+            "Unsupported provider: {}",
+            provider
+        ))),
+    }
+}
 
+/// Open a provider by name as a trait object, pooling connections per `pool`
+#[cfg(not(feature = "database"))]
+pub async fn connect_with_pool(
+    provider: &str,
+    connection_string: &str,
+    pool: &PoolConfig,
+) -> Result<Arc<dyn Database>> {
+    let _ = (provider, connection_string, pool);
+    Err(Error::config(
+        "Database operations require 'database' feature to be enabled",
+    ))
+}
 
 /// Database module
 pub struct DatabaseModule {
@@ -148,27 +343,33 @@ impl DatabaseModule {
             .lifecycle_manager
             .as_ref()
             .ok_or_else(|| Error::config("MongoDB provider not configured"))?;
-        
+
         mongodb::MongoDBProvider::new(connection_string).await
     }
 
     /// Get PostgreSQL provider
-    pub async fn postgresql(&self, connection_string: String) -> Result<postgresql::PostgreSQLProvider> {
+    pub async fn postgresql(
+        &self,
+        connection_string: String,
+    ) -> Result<postgresql::PostgreSQLProvider> {
         let _ = self
             .lifecycle_manager
             .as_ref()
             .ok_or_else(|| Error::config("PostgreSQL provider not configured"))?;
-        
+
         postgresql::PostgreSQLProvider::new(connection_string).await
     }
 
     /// Get Supabase provider (based on PostgreSQL)
-    pub async fn supabase(&self, connection_string: String) -> Result<postgresql::PostgreSQLProvider> {
+    pub async fn supabase(
+        &self,
+        connection_string: String,
+    ) -> Result<postgresql::PostgreSQLProvider> {
         let _ = self
             .lifecycle_manager
             .as_ref()
             .ok_or_else(|| Error::config("Supabase provider not configured"))?;
-        
+
         // Supabase is PostgreSQL-based, so we use the PostgreSQL provider
         postgresql::PostgreSQLProvider::new(connection_string).await
     }
@@ -195,7 +396,7 @@ impl DatabaseModule {
             size: None,
             metadata: serde_json::json!({
                 "message": "Database module is now production-ready",
-                "available_providers": ["mongodb", "postgresql", "supabase"],
+                "available_providers": ["mongodb", "postgresql", "supabase", "sqlite"],
                 "connection_pooling": true,
                 "security_validation": true,
                 "performance_optimized": true
@@ -206,7 +407,12 @@ impl DatabaseModule {
     }
 
     /// Execute query on a specific provider
-    pub async fn execute_query(&self, provider: &str, connection_string: String, query: String) -> Result<QueryResult> {
+    pub async fn execute_query(
+        &self,
+        provider: &str,
+        connection_string: String,
+        query: String,
+    ) -> Result<QueryResult> {
         #[cfg(feature = "database")]
         {
             match provider {
@@ -214,24 +420,36 @@ impl DatabaseModule {
                     let _mongo_provider = self.mongodb(connection_string).await?;
                     // For MongoDB, we need database and collection
                     // This is a simplified interface - in production you'd parse the query
-                    Err(Error::validation("MongoDB queries require database and collection parameters"))
-                },
+                    Err(Error::validation(
+                        "MongoDB queries require database and collection parameters",
+                    ))
+                }
                 "postgresql" | "supabase" => {
                     let pg_provider = self.postgresql(connection_string).await?;
                     pg_provider.execute_query(&query, None).await
-                },
-                _ => Err(Error::validation(format!("Unsupported provider: {}", provider)))
+                }
+                _ => Err(Error::validation(format!(
+                    "Unsupported provider: {}",
+                    provider
+                ))),
             }
         }
         #[cfg(not(feature = "database"))]
         {
             let _ = (provider, connection_string, query);
-            Err(Error::config("Database operations require 'database' feature to be enabled"))
+            Err(Error::config(
+                "Database operations require 'database' feature to be enabled",
+            ))
         }
     }
 
     /// List tables for a specific provider
-    pub async fn list_tables(&self, provider: &str, connection_string: String, database: Option<String>) -> Result<Vec<String>> {
+    pub async fn list_tables(
+        &self,
+        provider: &str,
+        connection_string: String,
+        database: Option<String>,
+    ) -> Result<Vec<String>> {
         #[cfg(feature = "database")]
         {
             match provider {
@@ -239,42 +457,62 @@ impl DatabaseModule {
                     let mongo_provider = self.mongodb(connection_string).await?;
                     let tables = mongo_provider.list_tables(database.as_deref()).await?;
                     Ok(tables.into_iter().map(|t| t.name).collect())
-                },
+                }
                 "postgresql" | "supabase" => {
                     let pg_provider = self.postgresql(connection_string).await?;
                     let tables = pg_provider.list_tables(database.as_deref()).await?;
                     Ok(tables.into_iter().map(|t| t.name).collect())
-                },
-                _ => Err(Error::validation(format!("Unsupported provider: {}", provider)))
+                }
+                _ => Err(Error::validation(format!(
+                    "Unsupported provider: {}",
+                    provider
+                ))),
             }
         }
         #[cfg(not(feature = "database"))]
         {
             let _ = (provider, connection_string, database);
-            Err(Error::config("Database operations require 'database' feature to be enabled"))
+            Err(Error::config(
+                "Database operations require 'database' feature to be enabled",
+            ))
         }
     }
 
     /// Describe table schema for a specific provider
-    pub async fn describe_table(&self, provider: &str, connection_string: String, table_name: String, database: Option<String>) -> Result<Table> {
+    pub async fn describe_table(
+        &self,
+        provider: &str,
+        connection_string: String,
+        table_name: String,
+        database: Option<String>,
+    ) -> Result<Table> {
         #[cfg(feature = "database")]
         {
             match provider {
                 "mongodb" => {
                     let mongo_provider = self.mongodb(connection_string).await?;
-                    mongo_provider.describe_table(&table_name, database.as_deref()).await
-                },
+                    mongo_provider
+                        .describe_table(&table_name, database.as_deref())
+                        .await
+                }
                 "postgresql" | "supabase" => {
                     let pg_provider = self.postgresql(connection_string).await?;
-                    pg_provider.describe_table(&table_name, database.as_deref()).await
-                },
-                _ => Err(Error::validation(format!("Unsupported provider: {}", provider)))
+                    pg_provider
+                        .describe_table(&table_name, database.as_deref())
+                        .await
+                }
+                _ => Err(Error::validation(format!(
+                    "Unsupported provider: {}",
+                    provider
+                ))),
             }
         }
         #[cfg(not(feature = "database"))]
         {
             let _ = (provider, connection_string, table_name, database);
-            Err(Error::config("Database operations require 'database' feature to be enabled"))
+            Err(Error::config(
+                "Database operations require 'database' feature to be enabled",
+            ))
         }
     }
 
@@ -288,12 +526,12 @@ impl DatabaseModule {
                 "list_databases".to_string(),
                 "List all available databases".to_string(),
             )
+            .read_only()
             .with_parameters(json!({
                 "type": "object",
                 "properties": {},
                 "required": []
             })),
-            
             ToolDefinition::new(
                 "execute_query".to_string(),
                 "Execute a database query".to_string(),
@@ -317,11 +555,11 @@ impl DatabaseModule {
                 },
                 "required": ["database", "query", "provider"]
             })),
-            
             ToolDefinition::new(
                 "list_tables".to_string(),
                 "List tables in a database".to_string(),
             )
+            .read_only()
             .with_parameters(json!({
                 "type": "object",
                 "properties": {
@@ -337,11 +575,11 @@ impl DatabaseModule {
                 },
                 "required": ["database", "provider"]
             })),
-            
             ToolDefinition::new(
                 "describe_table".to_string(),
                 "Get table schema information".to_string(),
             )
+            .read_only()
             .with_parameters(json!({
                 "type": "object",
                 "properties": {
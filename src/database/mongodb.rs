@@ -1,26 +1,26 @@
 #[cfg(feature = "database")]
-use crate::database::{Database, DatabaseStatus, QueryResult, Table, Column};
+use crate::database::{Column, Database, DatabaseStatus, QueryResult, Table};
 use crate::error::{Error, Result};
 #[cfg(feature = "database")]
-use serde_json::Value;
-#[cfg(feature = "database")]
 use crate::security::SecurityModule;
 #[cfg(feature = "database")]
+use futures::TryStreamExt;
+#[cfg(feature = "database")]
 use mongodb::{
     bson::{doc, Document},
     options::{ClientOptions, FindOptions},
     Client, Database as MongoDatabase,
 };
 #[cfg(feature = "database")]
-use futures::TryStreamExt;
-#[cfg(feature = "database")]
-use std::time::Instant;
+use serde_json::Value;
 #[cfg(feature = "database")]
-use tokio::sync::RwLock;
+use std::collections::HashMap;
 #[cfg(feature = "database")]
 use std::sync::Arc;
 #[cfg(feature = "database")]
-use std::collections::HashMap;
+use std::time::Instant;
+#[cfg(feature = "database")]
+use tokio::sync::RwLock;
 
 /// MongoDB provider for database module with connection pooling and performance optimization
 #[cfg(feature = "database")]
@@ -41,7 +41,9 @@ impl MongoDBProvider {
     pub async fn new(connection_string: String) -> Result<Self> {
         let client_options = ClientOptions::parse(&connection_string)
             .await
-            .map_err(|e| Error::service(format!("Failed to parse MongoDB connection string: {}", e)))?;
+            .map_err(|e| {
+                Error::service(format!("Failed to parse MongoDB connection string: {}", e))
+            })?;
 
         let client = Client::with_options(client_options)
             .map_err(|e| Error::service(format!("Failed to create MongoDB client: {}", e)))?;
@@ -76,7 +78,7 @@ impl MongoDBProvider {
     /// Get or create a database connection from the pool
     async fn get_database(&self, database_name: Option<&str>) -> MongoDatabase {
         let db_name = database_name.unwrap_or(&self.default_database);
-        
+
         // Try to get from pool first
         {
             let pool = self.connection_pool.read().await;
@@ -84,10 +86,10 @@ impl MongoDBProvider {
                 return db.clone();
             }
         }
-        
+
         // Create new connection if not in pool
         let db = self.client.database(db_name);
-        
+
         // Add to pool if not at max capacity
         {
             let mut pool = self.connection_pool.write().await;
@@ -95,7 +97,7 @@ impl MongoDBProvider {
                 pool.insert(db_name.to_string(), db.clone());
             }
         }
-        
+
         db
     }
 
@@ -111,7 +113,10 @@ impl MongoDBProvider {
         match mongodb::bson::to_bson(value) {
             Ok(mongodb::bson::Bson::Document(doc)) => Ok(doc),
             Ok(_) => Err(Error::config("JSON value must be an object")),
-            Err(e) => Err(Error::service(format!("Failed to convert JSON to BSON: {}", e))),
+            Err(e) => Err(Error::service(format!(
+                "Failed to convert JSON to BSON: {}",
+                e
+            ))),
         }
     }
 }
@@ -121,131 +126,159 @@ impl MongoDBProvider {
 impl Database for MongoDBProvider {
     async fn execute_query(&self, query: &str, database: Option<&str>) -> Result<QueryResult> {
         let start = Instant::now();
-        
+
         // Parse the query as a MongoDB command
         let command: Value = serde_json::from_str(query)
             .map_err(|e| Error::config(format!("Invalid MongoDB query JSON: {}", e)))?;
-        
+
         let db = self.get_database(database).await;
-        
+
         // Extract collection name and operation from the command
-        let collection_name = command.get("collection")
+        let collection_name = command
+            .get("collection")
             .and_then(|v| v.as_str())
             .ok_or_else(|| Error::config("Missing 'collection' field in query"))?;
-        
-        let operation = command.get("operation")
+
+        let operation = command
+            .get("operation")
             .and_then(|v| v.as_str())
             .ok_or_else(|| Error::config("Missing 'operation' field in query"))?;
-        
+
         let collection = db.collection::<Document>(collection_name);
-        
+
         let result = match operation {
             "find" => {
-                let filter = command.get("filter")
+                let filter = command
+                    .get("filter")
                     .map(Self::value_to_document)
                     .transpose()?
                     .unwrap_or_else(|| doc! {});
-                
+
                 let options = FindOptions::builder()
                     .limit(command.get("limit").and_then(|v| v.as_i64()))
                     .skip(command.get("skip").and_then(|v| v.as_u64()))
                     .build();
-                
-                let mut cursor = collection.find(filter, options).await
+
+                let mut cursor = collection
+                    .find(filter, options)
+                    .await
                     .map_err(|e| Error::service(format!("MongoDB find failed: {}", e)))?;
-                
+
                 let mut rows = Vec::new();
-                while let Some(doc) = cursor.try_next().await
-                    .map_err(|e| Error::service(format!("Failed to iterate cursor: {}", e)))? {
+                while let Some(doc) = cursor
+                    .try_next()
+                    .await
+                    .map_err(|e| Error::service(format!("Failed to iterate cursor: {}", e)))?
+                {
                     rows.push(Self::document_to_value(&doc)?);
                 }
-                
+
                 QueryResult {
                     rows,
                     columns: vec![],
                     rows_affected: 0,
                     execution_time_ms: start.elapsed().as_millis() as u64,
                 }
-            },
+            }
             "insert" => {
-                let document = command.get("document")
+                let document = command
+                    .get("document")
                     .ok_or_else(|| Error::config("Missing 'document' field for insert"))?;
                 let doc = Self::value_to_document(document)?;
-                
-                collection.insert_one(doc, None).await
+
+                collection
+                    .insert_one(doc, None)
+                    .await
                     .map_err(|e| Error::service(format!("MongoDB insert failed: {}", e)))?;
-                
+
                 QueryResult {
                     rows: vec![],
                     columns: vec![],
                     rows_affected: 1,
                     execution_time_ms: start.elapsed().as_millis() as u64,
                 }
-            },
+            }
             "update" => {
-                let filter = command.get("filter")
+                let filter = command
+                    .get("filter")
                     .map(Self::value_to_document)
                     .transpose()?
                     .unwrap_or_else(|| doc! {});
-                
-                let update = command.get("update")
+
+                let update = command
+                    .get("update")
                     .ok_or_else(|| Error::config("Missing 'update' field"))?;
                 let update_doc = Self::value_to_document(update)?;
-                
-                let result = collection.update_many(filter, update_doc, None).await
+
+                let result = collection
+                    .update_many(filter, update_doc, None)
+                    .await
                     .map_err(|e| Error::service(format!("MongoDB update failed: {}", e)))?;
-                
+
                 QueryResult {
                     rows: vec![],
                     columns: vec![],
                     rows_affected: result.modified_count as u64,
                     execution_time_ms: start.elapsed().as_millis() as u64,
                 }
-            },
+            }
             "delete" => {
-                let filter = command.get("filter")
+                let filter = command
+                    .get("filter")
                     .map(Self::value_to_document)
                     .transpose()?
                     .unwrap_or_else(|| doc! {});
-                
-                let result = collection.delete_many(filter, None).await
+
+                let result = collection
+                    .delete_many(filter, None)
+                    .await
                     .map_err(|e| Error::service(format!("MongoDB delete failed: {}", e)))?;
-                
+
                 QueryResult {
                     rows: vec![],
                     columns: vec![],
                     rows_affected: result.deleted_count as u64,
                     execution_time_ms: start.elapsed().as_millis() as u64,
                 }
-            },
+            }
             _ => return Err(Error::config(format!("Unknown operation: {}", operation))),
         };
-        
+
         Ok(result)
     }
 
     async fn list_databases(&self) -> Result<Vec<String>> {
-        let names = self.client.list_database_names(None, None).await
+        let names = self
+            .client
+            .list_database_names(None, None)
+            .await
             .map_err(|e| Error::service(format!("Failed to list databases: {}", e)))?;
         Ok(names)
     }
 
     async fn list_tables(&self, database: Option<&str>) -> Result<Vec<Table>> {
         let db = self.get_database(database).await;
-        let collection_names = db.list_collection_names(None).await
+        let collection_names = db
+            .list_collection_names(None)
+            .await
             .map_err(|e| Error::service(format!("Failed to list collections: {}", e)))?;
-        
+
         let mut tables = Vec::new();
         for name in collection_names {
             // Get collection stats
-            let stats = db.run_command(doc! {
-                "collStats": &name,
-                "scale": 1
-            }, None).await
+            let stats = db
+                .run_command(
+                    doc! {
+                        "collStats": &name,
+                        "scale": 1
+                    },
+                    None,
+                )
+                .await
                 .map_err(|e| Error::service(format!("Failed to get collection stats: {}", e)))?;
-            
+
             let row_count = stats.get_i64("count").unwrap_or(0) as u64;
-            
+
             tables.push(Table {
                 name: name.clone(),
                 columns: vec![], // MongoDB is schemaless
@@ -253,22 +286,27 @@ impl Database for MongoDBProvider {
                 size_bytes: stats.get_i64("size").ok().map(|s| s as u64),
             });
         }
-        
+
         Ok(tables)
     }
 
     async fn describe_table(&self, table_name: &str, database: Option<&str>) -> Result<Table> {
         let db = self.get_database(database).await;
-        
+
         // Get collection stats
-        let stats = db.run_command(doc! {
-            "collStats": table_name,
-            "scale": 1
-        }, None).await
+        let stats = db
+            .run_command(
+                doc! {
+                    "collStats": table_name,
+                    "scale": 1
+                },
+                None,
+            )
+            .await
             .map_err(|e| Error::service(format!("Failed to get collection stats: {}", e)))?;
-        
+
         let row_count = stats.get_i64("count").unwrap_or(0) as u64;
-        
+
         // Sample documents to infer schema
         let collection = db.collection::<Document>(table_name);
         let sample_docs: Vec<Document> = collection
@@ -278,7 +316,7 @@ impl Database for MongoDBProvider {
             .try_collect()
             .await
             .map_err(|e| Error::service(format!("Failed to collect samples: {}", e)))?;
-        
+
         // Infer schema from sample documents
         let mut field_types: HashMap<String, String> = HashMap::new();
         for doc in &sample_docs {
@@ -300,7 +338,7 @@ impl Database for MongoDBProvider {
                 }
             }
         }
-        
+
         let columns: Vec<Column> = field_types
             .into_iter()
             .map(|(name, data_type)| Column {
@@ -312,7 +350,7 @@ impl Database for MongoDBProvider {
                 default: None,
             })
             .collect();
-        
+
         Ok(Table {
             name: table_name.to_string(),
             columns,
@@ -323,11 +361,13 @@ impl Database for MongoDBProvider {
 
     async fn health_check(&self) -> Result<DatabaseStatus> {
         let start = Instant::now();
-        
-        match self.client
+
+        match self
+            .client
             .database("admin")
             .run_command(doc! {"ping": 1}, None)
-            .await {
+            .await
+        {
             Ok(_) => Ok(DatabaseStatus {
                 healthy: true,
                 latency_ms: start.elapsed().as_millis() as u64,
@@ -349,6 +389,8 @@ pub struct MongoDBProvider;
 #[cfg(not(feature = "database"))]
 impl MongoDBProvider {
     pub async fn new(_connection_string: String) -> Result<Self> {
-        Err(Error::config("MongoDB support requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "MongoDB support requires 'database' feature to be enabled",
+        ))
     }
-}
\ No newline at end of file
+}
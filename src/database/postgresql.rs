@@ -1,14 +1,99 @@
 #[cfg(feature = "database")]
-use crate::database::{Database, DatabaseStatus, QueryResult, Table, Column};
+use crate::database::schema::{foreign_key_action, DatabaseSchema, ForeignKey, Index, TableSchema};
+#[cfg(feature = "database")]
+use crate::database::{Column, Database, DatabaseStatus, PoolConfig, QueryResult, Table};
 use crate::error::{Error, Result};
 #[cfg(feature = "database")]
 use crate::security::SecurityModule;
 #[cfg(feature = "database")]
+use base64::Engine;
+#[cfg(feature = "database")]
 use serde_json::{json, Value};
 #[cfg(feature = "database")]
-use sqlx::{postgres::PgPoolOptions, PgPool, Row, Column as SqlxColumn, TypeInfo};
+use sqlx::postgres::{PgArguments, PgConnectOptions, PgPoolOptions, PgRow};
+#[cfg(feature = "database")]
+use sqlx::{Column as SqlxColumn, PgExecutor, PgPool, Postgres, Row, Transaction, TypeInfo};
+#[cfg(feature = "database")]
+use std::collections::HashMap;
+#[cfg(feature = "database")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "database")]
+use tokio::sync::Mutex;
+
+/// Whether a statement produces rows rather than just a row count
+pub fn returns_rows(query: &str) -> bool {
+    let lower = query.trim_start().to_lowercase();
+    let first = lower
+        .split(|c: char| c.is_whitespace() || c == '(')
+        .find(|word| !word.is_empty())
+        .unwrap_or_default();
+    matches!(
+        first,
+        "select" | "with" | "explain" | "show" | "values" | "table" | "fetch"
+    ) || lower
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|word| word == "returning")
+}
+
+/// Decimal text of a NUMERIC value in PostgreSQL's binary format
+///
+/// NUMERIC can hold more precision than a JSON number, so it is kept as text.
+pub fn numeric_to_string(bytes: &[u8]) -> Option<String> {
+    let word = |i: usize| {
+        bytes
+            .get(i * 2..i * 2 + 2)
+            .map(|b| i16::from_be_bytes([b[0], b[1]]))
+    };
+    let ndigits = usize::try_from(word(0)?).ok()?;
+    let weight = i32::from(word(1)?);
+    let sign = word(2)? as u16;
+    let dscale = word(3)? as u16 as usize;
+    match sign {
+        0xC000 => return Some("NaN".to_string()),
+        0xD000 => return Some("Infinity".to_string()),
+        0xF000 => return Some("-Infinity".to_string()),
+        _ => {}
+    }
+    let digits: Vec<i16> = (0..ndigits).map(|i| word(4 + i)).collect::<Option<_>>()?;
+    // Digits are base 10000, the first one scaled by 10000^weight
+    let digit = |exponent: i32| {
+        usize::try_from(weight - exponent)
+            .ok()
+            .and_then(|index| digits.get(index).copied())
+            .unwrap_or(0)
+    };
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    } else {
+        text.push_str(&digit(weight).to_string());
+        for exponent in (0..weight).rev() {
+            text.push_str(&format!("{:04}", digit(exponent)));
+        }
+    }
+    if dscale > 0 {
+        let mut fraction = String::new();
+        let mut exponent = -1;
+        while fraction.len() < dscale {
+            fraction.push_str(&format!("{:04}", digit(exponent)));
+            exponent -= 1;
+        }
+        fraction.truncate(dscale);
+        text.push('.');
+        text.push_str(&fraction);
+    }
+    Some(text)
+}
+
+/// Transaction held open between tool calls
 #[cfg(feature = "database")]
-use std::time::Instant;
+struct OpenTransaction {
+    transaction: Transaction<'static, Postgres>,
+    started: Instant,
+}
 
 /// PostgreSQL provider for database module with connection pooling and performance optimization
 #[cfg(feature = "database")]
@@ -19,140 +104,146 @@ pub struct PostgreSQLProvider {
     security: SecurityModule,
     #[allow(dead_code)]
     database_name: String,
+    transactions: Mutex<HashMap<String, OpenTransaction>>,
+    transaction_timeout: Duration,
+    max_transactions: usize,
 }
 
 #[cfg(feature = "database")]
 impl PostgreSQLProvider {
-    /// Create a new PostgreSQL provider with optimized connection pool
+    /// Create a new PostgreSQL provider with the default pool settings
     pub async fn new(connection_string: String) -> Result<Self> {
+        Self::connect(connection_string, &PoolConfig::default()).await
+    }
+
+    /// Create a new PostgreSQL provider with a pool sized and timed by `pool`
+    pub async fn connect(connection_string: String, pool: &PoolConfig) -> Result<Self> {
+        pool.validate()?;
         // Extract database name from connection string
         let database_name = connection_string
             .split('/')
-            .last()
+            .next_back()
             .unwrap_or("postgres")
             .split('?')
             .next()
             .unwrap_or("postgres")
             .to_string();
 
-        let pool = PgPoolOptions::new()
-            .max_connections(32)
-            .min_connections(4)
-            .connect(&connection_string)
+        let mut options: PgConnectOptions = connection_string
+            .parse()
+            .map_err(|e| Error::config(format!("Invalid PostgreSQL connection string: {}", e)))?;
+        if let Some(timeout) = pool.statement_timeout_ms {
+            options = options.options([("statement_timeout", timeout.to_string())]);
+        }
+        let pg_pool = PgPoolOptions::new()
+            .max_connections(pool.max_connections)
+            .min_connections(pool.min_connections)
+            .acquire_timeout(Duration::from_secs(pool.acquire_timeout_secs))
+            .idle_timeout(pool.idle_timeout_secs.map(Duration::from_secs))
+            .connect_with(options)
             .await
             .map_err(|e| Error::service(format!("Failed to connect to PostgreSQL: {}", e)))?;
 
         // Test connection
         sqlx::query("SELECT 1")
-            .execute(&pool)
+            .execute(&pg_pool)
             .await
             .map_err(|e| Error::service(format!("PostgreSQL connection test failed: {}", e)))?;
 
         Ok(Self {
-            pool,
+            pool: pg_pool,
             connection_string,
             security: SecurityModule::new(),
             database_name,
+            transactions: Mutex::new(HashMap::new()),
+            transaction_timeout: Duration::from_secs(pool.transaction_timeout_secs),
+            // Leave a connection for queries outside transactions
+            max_transactions: (pool.max_connections as usize).saturating_sub(1).max(1),
         })
     }
 
-    /// Convert a SQL row to JSON value
-    fn row_to_value(row: &sqlx::postgres::PgRow) -> Result<Value> {
-        let mut object = serde_json::Map::new();
-        
-        for (i, column) in row.columns().iter().enumerate() {
-            let name = column.name();
-            let value: Value = if let Ok(v) = row.try_get::<Option<i32>, _>(i) {
-                json!(v)
-            } else if let Ok(v) = row.try_get::<Option<i64>, _>(i) {
-                json!(v)
-            } else if let Ok(v) = row.try_get::<Option<f64>, _>(i) {
-                json!(v)
-            } else if let Ok(v) = row.try_get::<Option<String>, _>(i) {
-                json!(v)
-            } else if let Ok(v) = row.try_get::<Option<bool>, _>(i) {
-                json!(v)
-            } else if let Ok(v) = row.try_get::<Option<chrono::NaiveDateTime>, _>(i) {
-                json!(v.map(|dt| dt.to_string()))
-            } else if let Ok(v) = row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>(i) {
-                json!(v.map(|dt| dt.to_rfc3339()))
-            } else if let Ok(v) = row.try_get::<Option<serde_json::Value>, _>(i) {
-                v.unwrap_or(Value::Null)
-            } else {
-                Value::Null
-            };
-            
-            object.insert(name.to_string(), value);
+    /// Reject queries the security module flags
+    fn check_query(&self, query: &str) -> Result<()> {
+        use crate::security::{SanitizationOptions, ValidationResult};
+        let options = SanitizationOptions::default();
+        if let ValidationResult::Malicious(reason) = self.security.validate_input(query, &options) {
+            return Err(Error::config(format!(
+                "Potentially malicious query: {}",
+                reason
+            )));
         }
-        
-        Ok(Value::Object(object))
+        Ok(())
     }
 
-    /// Get column information from a row
-    fn get_columns(row: &sqlx::postgres::PgRow) -> Vec<Column> {
-        row.columns()
-            .iter()
-            .map(|col| {
-                Column {
-                    name: col.name().to_string(),
-                    data_type: col.type_info().name().to_string(),
-                    nullable: true, // Would need additional schema query to determine
-                    primary_key: false, // Would need additional schema query to determine
-                    unique: false, // Would need additional schema query to determine
-                    default: None, // Would need additional schema query to determine
-                }
-            })
-            .collect()
+    /// Begin a transaction and keep it open under a new ID
+    async fn open_transaction(&self, read_only: bool) -> Result<String> {
+        {
+            let mut transactions = self.transactions.lock().await;
+            let timeout = self.transaction_timeout;
+            transactions.retain(|_, open| open.started.elapsed() <= timeout);
+            if transactions.len() >= self.max_transactions {
+                return Err(Error::validation(format!(
+                    "Too many open transactions ({}); commit or roll one back first",
+                    transactions.len()
+                )));
+            }
+        }
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::service(format!("Failed to begin transaction: {}", e)))?;
+        if read_only {
+            sqlx::query("SET TRANSACTION READ ONLY")
+                .execute(&mut *transaction)
+                .await
+                .map_err(|e| {
+                    Error::service(format!("Failed to make transaction read-only: {}", e))
+                })?;
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        self.transactions.lock().await.insert(
+            id.clone(),
+            OpenTransaction {
+                transaction,
+                started: Instant::now(),
+            },
+        );
+        Ok(id)
     }
-}
 
-#[cfg(feature = "database")]
-#[async_trait::async_trait]
-impl Database for PostgreSQLProvider {
-    async fn execute_query(&self, query: &str, _database: Option<&str>) -> Result<QueryResult> {
+    /// Run a statement with bound parameters on a pool or a transaction
+    async fn run<'c, E: PgExecutor<'c>>(
+        executor: E,
+        query: &str,
+        params: &[Value],
+    ) -> Result<QueryResult> {
         let start = Instant::now();
-        
-        // Basic SQL injection prevention (in production, use parameterized queries)
-        use crate::security::{SanitizationOptions, ValidationResult};
-        let options = SanitizationOptions::default();
-        if let ValidationResult::Malicious(reason) = self.security.validate_input(query, &options) {
-            return Err(Error::config(format!("Potentially malicious query: {}", reason)));
+        let mut statement = sqlx::query(query);
+        for param in params {
+            statement = bind_json(statement, param);
         }
-        
-        // Determine query type
-        let query_lower = query.trim().to_lowercase();
-        
-        if query_lower.starts_with("select") || query_lower.starts_with("with") {
-            // Execute SELECT query
-            let rows: Vec<sqlx::postgres::PgRow> = sqlx::query(query)
-                .fetch_all(&self.pool)
+        if returns_rows(query) {
+            let rows: Vec<PgRow> = statement
+                .fetch_all(executor)
                 .await
                 .map_err(|e| Error::service(format!("Query execution failed: {}", e)))?;
-            
-            let columns = if !rows.is_empty() {
-                Self::get_columns(&rows[0])
-            } else {
-                vec![]
-            };
-            
-            let row_values: Result<Vec<Value>> = rows
+            let columns = rows.first().map(Self::get_columns).unwrap_or_default();
+            let rows = rows
                 .iter()
                 .map(Self::row_to_value)
-                .collect();
-            
+                .collect::<Result<Vec<Value>>>()?;
             Ok(QueryResult {
-                rows: row_values?,
+                rows,
                 columns,
                 rows_affected: 0,
                 execution_time_ms: start.elapsed().as_millis() as u64,
             })
         } else {
-            // Execute DML query (INSERT, UPDATE, DELETE)
-            let result = sqlx::query(query)
-                .execute(&self.pool)
+            let result = statement
+                .execute(executor)
                 .await
                 .map_err(|e| Error::service(format!("Query execution failed: {}", e)))?;
-            
             Ok(QueryResult {
                 rows: vec![],
                 columns: vec![],
@@ -162,20 +253,227 @@ impl Database for PostgreSQLProvider {
         }
     }
 
+    /// Take an open transaction out of the table, rolling it back if it expired
+    async fn take_transaction(&self, id: &str) -> Result<OpenTransaction> {
+        let open = self.transactions.lock().await.remove(id).ok_or_else(|| {
+            Error::not_found_with_resource("Transaction not found", "transaction", id)
+        })?;
+        if open.started.elapsed() > self.transaction_timeout {
+            // Rolled back when the transaction is dropped
+            return Err(Error::timeout_with_duration(
+                format!("Transaction {} expired and was rolled back", id),
+                self.transaction_timeout,
+            ));
+        }
+        Ok(open)
+    }
+
+    /// Convert a SQL row to JSON, keeping each column's type
+    fn row_to_value(row: &PgRow) -> Result<Value> {
+        let mut object = serde_json::Map::new();
+        for (i, column) in row.columns().iter().enumerate() {
+            object.insert(column.name().to_string(), Self::column_value(row, i)?);
+        }
+        Ok(Value::Object(object))
+    }
+
+    /// JSON for one column, chosen by its PostgreSQL type
+    fn column_value(row: &PgRow, i: usize) -> Result<Value> {
+        use sqlx::ValueRef;
+        let raw = row
+            .try_get_raw(i)
+            .map_err(|e| Error::parsing(format!("Failed to read column {}: {}", i, e)))?;
+        if raw.is_null() {
+            return Ok(Value::Null);
+        }
+        let column = &row.columns()[i];
+        let decode_error =
+            |e: sqlx::Error| Error::parsing(format!("Failed to decode {}: {}", column.name(), e));
+        let value = match column.type_info().name() {
+            "BOOL" => json!(row.try_get::<bool, _>(i).map_err(decode_error)?),
+            "INT2" => json!(row.try_get::<i16, _>(i).map_err(decode_error)?),
+            "INT4" => json!(row.try_get::<i32, _>(i).map_err(decode_error)?),
+            "INT8" => json!(row.try_get::<i64, _>(i).map_err(decode_error)?),
+            "FLOAT4" => json!(row.try_get::<f32, _>(i).map_err(decode_error)?),
+            "FLOAT8" => json!(row.try_get::<f64, _>(i).map_err(decode_error)?),
+            "NUMERIC" => raw
+                .as_bytes()
+                .ok()
+                .and_then(numeric_to_string)
+                .map(Value::String)
+                .unwrap_or(Value::Null),
+            "UUID" => json!(row
+                .try_get::<uuid::Uuid, _>(i)
+                .map_err(decode_error)?
+                .to_string()),
+            "JSON" | "JSONB" => row.try_get::<Value, _>(i).map_err(decode_error)?,
+            "DATE" => json!(row
+                .try_get::<chrono::NaiveDate, _>(i)
+                .map_err(decode_error)?
+                .to_string()),
+            "TIME" => json!(row
+                .try_get::<chrono::NaiveTime, _>(i)
+                .map_err(decode_error)?
+                .to_string()),
+            "TIMESTAMP" => json!(row
+                .try_get::<chrono::NaiveDateTime, _>(i)
+                .map_err(decode_error)?
+                .format("%Y-%m-%dT%H:%M:%S%.f")
+                .to_string()),
+            "TIMESTAMPTZ" => json!(row
+                .try_get::<chrono::DateTime<chrono::Utc>, _>(i)
+                .map_err(decode_error)?
+                .to_rfc3339()),
+            "BYTEA" => json!(base64::engine::general_purpose::STANDARD
+                .encode(row.try_get::<Vec<u8>, _>(i).map_err(decode_error)?)),
+            "TEXT[]" | "VARCHAR[]" | "NAME[]" => {
+                json!(row.try_get::<Vec<String>, _>(i).map_err(decode_error)?)
+            }
+            "INT4[]" => json!(row.try_get::<Vec<i32>, _>(i).map_err(decode_error)?),
+            "INT8[]" => json!(row.try_get::<Vec<i64>, _>(i).map_err(decode_error)?),
+            "FLOAT8[]" => json!(row.try_get::<Vec<f64>, _>(i).map_err(decode_error)?),
+            "BOOL[]" => json!(row.try_get::<Vec<bool>, _>(i).map_err(decode_error)?),
+            // Text types, enums and anything else sent as text
+            _ => raw
+                .as_str()
+                .map(|text| Value::String(text.to_string()))
+                .unwrap_or(Value::Null),
+        };
+        Ok(value)
+    }
+
+    /// Get column information from a row
+    fn get_columns(row: &PgRow) -> Vec<Column> {
+        row.columns()
+            .iter()
+            .map(|col| {
+                Column {
+                    name: col.name().to_string(),
+                    data_type: col.type_info().name().to_string(),
+                    nullable: true, // Would need additional schema query to determine
+                    primary_key: false, // Would need additional schema query to determine
+                    unique: false,  // Would need additional schema query to determine
+                    default: None,  // Would need additional schema query to determine
+                }
+            })
+            .collect()
+    }
+}
+
+/// Bind a JSON value as the next query parameter
+///
+/// Strings bind as TEXT, so compare them with other types through a cast
+/// such as `$1::date`.
+#[cfg(feature = "database")]
+fn bind_json<'q>(
+    statement: sqlx::query::Query<'q, Postgres, PgArguments>,
+    value: &Value,
+) -> sqlx::query::Query<'q, Postgres, PgArguments> {
+    match value {
+        Value::Null => statement.bind(None::<String>),
+        Value::Bool(b) => statement.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => statement.bind(i),
+            None => statement.bind(n.as_f64()),
+        },
+        Value::String(s) => statement.bind(s.clone()),
+        other => statement.bind(sqlx::types::Json(other.clone())),
+    }
+}
+
+#[cfg(feature = "database")]
+#[async_trait::async_trait]
+impl Database for PostgreSQLProvider {
+    async fn execute_query(&self, query: &str, database: Option<&str>) -> Result<QueryResult> {
+        self.execute_with_params(query, &[], database).await
+    }
+
+    async fn execute_with_params(
+        &self,
+        query: &str,
+        params: &[Value],
+        _database: Option<&str>,
+    ) -> Result<QueryResult> {
+        self.check_query(query)?;
+        Self::run(&self.pool, query, params).await
+    }
+
+    async fn execute_read_only(
+        &self,
+        query: &str,
+        params: &[Value],
+        _database: Option<&str>,
+    ) -> Result<QueryResult> {
+        self.check_query(query)?;
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::service(format!("Failed to begin transaction: {}", e)))?;
+        sqlx::query("SET TRANSACTION READ ONLY")
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| Error::service(format!("Failed to make transaction read-only: {}", e)))?;
+        let result = Self::run(&mut *transaction, query, params).await;
+        // Nothing to keep; dropping the transaction would also roll it back
+        let _ = transaction.rollback().await;
+        result
+    }
+
+    async fn begin_transaction(&self) -> Result<String> {
+        self.open_transaction(false).await
+    }
+
+    async fn begin_read_only_transaction(&self) -> Result<String> {
+        self.open_transaction(true).await
+    }
+
+    async fn execute_in_transaction(
+        &self,
+        transaction_id: &str,
+        query: &str,
+        params: &[Value],
+    ) -> Result<QueryResult> {
+        self.check_query(query)?;
+        let mut open = self.take_transaction(transaction_id).await?;
+        let result = Self::run(&mut *open.transaction, query, params).await;
+        // A failed statement leaves the transaction for the caller to roll back
+        self.transactions
+            .lock()
+            .await
+            .insert(transaction_id.to_string(), open);
+        result
+    }
+
+    async fn commit(&self, transaction_id: &str) -> Result<()> {
+        let open = self.take_transaction(transaction_id).await?;
+        open.transaction
+            .commit()
+            .await
+            .map_err(|e| Error::service(format!("Failed to commit transaction: {}", e)))
+    }
+
+    async fn rollback(&self, transaction_id: &str) -> Result<()> {
+        let open = self.take_transaction(transaction_id).await?;
+        open.transaction
+            .rollback()
+            .await
+            .map_err(|e| Error::service(format!("Failed to roll back transaction: {}", e)))
+    }
+
     async fn list_databases(&self) -> Result<Vec<String>> {
-        let rows: Vec<(String,)> = sqlx::query_as(
-            "SELECT datname FROM pg_database WHERE datistemplate = false"
-        )
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| Error::service(format!("Failed to list databases: {}", e)))?;
-        
+        let rows: Vec<(String,)> =
+            sqlx::query_as("SELECT datname FROM pg_database WHERE datistemplate = false")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| Error::service(format!("Failed to list databases: {}", e)))?;
+
         Ok(rows.into_iter().map(|(name,)| name).collect())
     }
 
     async fn list_tables(&self, database: Option<&str>) -> Result<Vec<Table>> {
         let schema = database.unwrap_or("public");
-        
+
         let _query = format!(
             r#"
             SELECT 
@@ -188,7 +486,7 @@ impl Database for PostgreSQLProvider {
             "#,
             schema, "", schema
         );
-        
+
         // This is a simplified version - a proper implementation would need dynamic SQL
         let rows: Vec<(String,)> = sqlx::query_as(
             r#"
@@ -197,13 +495,13 @@ impl Database for PostgreSQLProvider {
             WHERE table_schema = $1
                 AND table_type = 'BASE TABLE'
             ORDER BY table_name
-            "#
+            "#,
         )
         .bind(schema)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| Error::service(format!("Failed to list tables: {}", e)))?;
-        
+
         let mut tables = Vec::new();
         for (table_name,) in rows {
             // Get table size and row count
@@ -212,11 +510,11 @@ impl Database for PostgreSQLProvider {
                  (SELECT COUNT(*) FROM {}) as row_count",
                 table_name, table_name
             );
-            
+
             if let Ok(row) = sqlx::query(&stats_query).fetch_one(&self.pool).await {
                 let size_bytes: Option<i64> = row.try_get("size_bytes").ok().flatten();
                 let row_count: Option<i64> = row.try_get("row_count").ok().flatten();
-                
+
                 tables.push(Table {
                     name: table_name,
                     columns: vec![], // Will be populated by describe_table
@@ -232,13 +530,13 @@ impl Database for PostgreSQLProvider {
                 });
             }
         }
-        
+
         Ok(tables)
     }
 
     async fn describe_table(&self, table_name: &str, database: Option<&str>) -> Result<Table> {
         let schema = database.unwrap_or("public");
-        
+
         // Get column information
         let columns: Vec<(String, String, bool, Option<String>)> = sqlx::query_as(
             r#"
@@ -250,14 +548,14 @@ impl Database for PostgreSQLProvider {
             FROM information_schema.columns
             WHERE table_schema = $1 AND table_name = $2
             ORDER BY ordinal_position
-            "#
+            "#,
         )
         .bind(schema)
         .bind(table_name)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| Error::service(format!("Failed to describe table: {}", e)))?;
-        
+
         // Get primary key information
         let pk_columns: Vec<(String,)> = sqlx::query_as(
             r#"
@@ -269,17 +567,17 @@ impl Database for PostgreSQLProvider {
             WHERE tc.constraint_type = 'PRIMARY KEY'
                 AND tc.table_schema = $1
                 AND tc.table_name = $2
-            "#
+            "#,
         )
         .bind(schema)
         .bind(table_name)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| Error::service(format!("Failed to get primary keys: {}", e)))?;
-        
-        let pk_set: std::collections::HashSet<String> = 
+
+        let pk_set: std::collections::HashSet<String> =
             pk_columns.into_iter().map(|(name,)| name).collect();
-        
+
         // Get unique constraint information
         let unique_columns: Vec<(String,)> = sqlx::query_as(
             r#"
@@ -291,17 +589,17 @@ impl Database for PostgreSQLProvider {
             WHERE tc.constraint_type = 'UNIQUE'
                 AND tc.table_schema = $1
                 AND tc.table_name = $2
-            "#
+            "#,
         )
         .bind(schema)
         .bind(table_name)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| Error::service(format!("Failed to get unique constraints: {}", e)))?;
-        
-        let unique_set: std::collections::HashSet<String> = 
+
+        let unique_set: std::collections::HashSet<String> =
             unique_columns.into_iter().map(|(name,)| name).collect();
-        
+
         let table_columns: Vec<Column> = columns
             .into_iter()
             .map(|(name, data_type, nullable, default)| Column {
@@ -313,22 +611,23 @@ impl Database for PostgreSQLProvider {
                 default,
             })
             .collect();
-        
+
         // Get table size and row count
         let stats_query = format!(
             "SELECT pg_relation_size('{}') as size_bytes, 
              (SELECT COUNT(*) FROM {}) as row_count",
             table_name, table_name
         );
-        
-        let (size_bytes, row_count) = if let Ok(row) = sqlx::query(&stats_query).fetch_one(&self.pool).await {
-            let size_bytes: Option<i64> = row.try_get("size_bytes").ok().flatten();
-            let row_count: Option<i64> = row.try_get("row_count").ok().flatten();
-            (size_bytes.map(|s| s as u64), row_count.map(|c| c as u64))
-        } else {
-            (None, None)
-        };
-        
+
+        let (size_bytes, row_count) =
+            if let Ok(row) = sqlx::query(&stats_query).fetch_one(&self.pool).await {
+                let size_bytes: Option<i64> = row.try_get("size_bytes").ok().flatten();
+                let row_count: Option<i64> = row.try_get("row_count").ok().flatten();
+                (size_bytes.map(|s| s as u64), row_count.map(|c| c as u64))
+            } else {
+                (None, None)
+            };
+
         Ok(Table {
             name: table_name.to_string(),
             columns: table_columns,
@@ -337,9 +636,176 @@ impl Database for PostgreSQLProvider {
         })
     }
 
+    async fn describe_schema(&self, database: Option<&str>) -> Result<DatabaseSchema> {
+        let schema = database.unwrap_or("public");
+        let failed = |what: &str| {
+            let what = what.to_string();
+            move |e: sqlx::Error| Error::service(format!("Failed to read {}: {}", what, e))
+        };
+        let tables: Vec<(String, i64, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT cl.relname, cl.reltuples::bigint, obj_description(cl.oid, 'pg_class')
+            FROM pg_class cl
+            JOIN pg_namespace n ON n.oid = cl.relnamespace
+            WHERE n.nspname = $1 AND cl.relkind IN ('r', 'p')
+            ORDER BY cl.relname
+            "#,
+        )
+        .bind(schema)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(failed("tables"))?;
+        let columns: Vec<(String, String, String, bool, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT cl.relname, a.attname, format_type(a.atttypid, a.atttypmod),
+                NOT a.attnotnull, pg_get_expr(d.adbin, d.adrelid)
+            FROM pg_attribute a
+            JOIN pg_class cl ON cl.oid = a.attrelid
+            JOIN pg_namespace n ON n.oid = cl.relnamespace
+            LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+            WHERE n.nspname = $1 AND cl.relkind IN ('r', 'p') AND a.attnum > 0 AND NOT a.attisdropped
+            ORDER BY cl.relname, a.attnum
+            "#,
+        )
+        .bind(schema)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(failed("columns"))?;
+        let indexes: Vec<(String, String, bool, bool, Vec<String>)> = sqlx::query_as(
+            r#"
+            SELECT t.relname, i.relname, ix.indisunique, ix.indisprimary,
+                ARRAY(
+                    SELECT a.attname::text
+                    FROM unnest(ix.indkey) WITH ORDINALITY k(attnum, ord)
+                    JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = k.attnum
+                    ORDER BY k.ord
+                )
+            FROM pg_index ix
+            JOIN pg_class t ON t.oid = ix.indrelid
+            JOIN pg_class i ON i.oid = ix.indexrelid
+            JOIN pg_namespace n ON n.oid = t.relnamespace
+            WHERE n.nspname = $1
+            ORDER BY t.relname, i.relname
+            "#,
+        )
+        .bind(schema)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(failed("indexes"))?;
+        // Table, name, referenced schema and table, columns, referenced columns, actions
+        type ForeignKeyRow = (
+            String,
+            String,
+            String,
+            String,
+            Vec<String>,
+            Vec<String>,
+            String,
+            String,
+        );
+        let foreign_keys: Vec<ForeignKeyRow> = sqlx::query_as(
+            r#"
+                SELECT t.relname, c.conname, rn.nspname, rt.relname,
+                    ARRAY(
+                        SELECT a.attname::text
+                        FROM unnest(c.conkey) WITH ORDINALITY k(attnum, ord)
+                        JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum
+                        ORDER BY k.ord
+                    ),
+                    ARRAY(
+                        SELECT a.attname::text
+                        FROM unnest(c.confkey) WITH ORDINALITY k(attnum, ord)
+                        JOIN pg_attribute a ON a.attrelid = c.confrelid AND a.attnum = k.attnum
+                        ORDER BY k.ord
+                    ),
+                    c.confdeltype::text, c.confupdtype::text
+                FROM pg_constraint c
+                JOIN pg_class t ON t.oid = c.conrelid
+                JOIN pg_namespace n ON n.oid = t.relnamespace
+                JOIN pg_class rt ON rt.oid = c.confrelid
+                JOIN pg_namespace rn ON rn.oid = rt.relnamespace
+                WHERE c.contype = 'f' AND n.nspname = $1
+                ORDER BY t.relname, c.conname
+                "#,
+        )
+        .bind(schema)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(failed("foreign keys"))?;
+
+        let tables = tables
+            .into_iter()
+            .map(|(name, estimate, comment)| TableSchema {
+                name,
+                columns: vec![],
+                indexes: vec![],
+                foreign_keys: vec![],
+                // -1 until the table is first analyzed
+                row_count: u64::try_from(estimate).ok(),
+                comment,
+            })
+            .collect();
+        let mut described = DatabaseSchema {
+            schema: schema.to_string(),
+            tables,
+        };
+        for (table_name, name, data_type, nullable, default) in columns {
+            if let Some(table) = described.table_mut(&table_name) {
+                table.columns.push(Column {
+                    name,
+                    data_type,
+                    nullable,
+                    primary_key: false,
+                    unique: false,
+                    default,
+                });
+            }
+        }
+        for (table_name, name, unique, primary, columns) in indexes {
+            if let Some(table) = described.table_mut(&table_name) {
+                table.indexes.push(Index {
+                    name,
+                    columns,
+                    unique,
+                    primary,
+                });
+            }
+        }
+        for (
+            table_name,
+            name,
+            referenced_schema,
+            referenced_table,
+            columns,
+            referenced_columns,
+            on_delete,
+            on_update,
+        ) in foreign_keys
+        {
+            if let Some(table) = described.table_mut(&table_name) {
+                table.foreign_keys.push(ForeignKey {
+                    name,
+                    columns,
+                    referenced_table: if referenced_schema == schema {
+                        referenced_table
+                    } else {
+                        format!("{}.{}", referenced_schema, referenced_table)
+                    },
+                    referenced_columns,
+                    on_delete: foreign_key_action(&on_delete),
+                    on_update: foreign_key_action(&on_update),
+                });
+            }
+        }
+        for table in &mut described.tables {
+            table.mark_keys();
+        }
+        Ok(described)
+    }
+
     async fn health_check(&self) -> Result<DatabaseStatus> {
         let start = Instant::now();
-        
+
         match sqlx::query("SELECT 1").execute(&self.pool).await {
             Ok(_) => Ok(DatabaseStatus {
                 healthy: true,
@@ -362,6 +828,50 @@ pub struct PostgreSQLProvider;
 #[cfg(not(feature = "database"))]
 impl PostgreSQLProvider {
     pub async fn new(_connection_string: String) -> Result<Self> {
-        Err(Error::config("PostgreSQL support requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL support requires 'database' feature to be enabled",
+        ))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_binary_numerics() {
+        fn numeric(weight: i16, sign: u16, dscale: u16, digits: &[i16]) -> Vec<u8> {
+            let mut bytes = Vec::new();
+            for word in [digits.len() as i16, weight, sign as i16, dscale as i16]
+                .into_iter()
+                .chain(digits.iter().copied())
+            {
+                bytes.extend_from_slice(&word.to_be_bytes());
+            }
+            bytes
+        }
+        assert_eq!(
+            numeric_to_string(&numeric(1, 0, 3, &[1, 2345, 6780])).as_deref(),
+            Some("12345.678")
+        );
+        assert_eq!(
+            numeric_to_string(&numeric(-1, 0x4000, 2, &[500])).as_deref(),
+            Some("-0.05")
+        );
+        assert_eq!(
+            numeric_to_string(&numeric(2, 0, 0, &[7])).as_deref(),
+            Some("700000000")
+        );
+        assert_eq!(numeric_to_string(&[0, 1]), None);
+    }
+
+    #[test]
+    fn detects_statements_returning_rows() {
+        assert!(returns_rows("  SELECT 1"));
+        assert!(returns_rows("(select id from t)"));
+        assert!(returns_rows("WITH x AS (SELECT 1) SELECT * FROM x"));
+        assert!(returns_rows("INSERT INTO t (a) VALUES ($1) RETURNING id"));
+        assert!(!returns_rows("UPDATE t SET returning_customer = true"));
+        assert!(!returns_rows("DELETE FROM t WHERE id = $1"));
+    }
+}
@@ -0,0 +1,438 @@
+/// Read-only guard for queries run through `execute_query`
+///
+/// Statements are classified after stripping comments, string literals and
+/// quoted identifiers, so keywords hidden in them neither trip nor fool the
+/// guard. Anything the classifier does not recognise counts as mutating.
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What a single statement does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementKind {
+    /// Reads data without changing it
+    Read,
+    /// Inserts, updates or deletes rows
+    Write,
+    /// Changes schema, permissions or storage
+    Schema,
+    /// Session, transaction or procedural statements, and anything unknown
+    Other,
+}
+
+impl StatementKind {
+    /// Whether the statement may change the database or session
+    pub fn is_mutating(self) -> bool {
+        self != StatementKind::Read
+    }
+
+    fn severity(self) -> u8 {
+        match self {
+            StatementKind::Read => 0,
+            StatementKind::Write => 1,
+            StatementKind::Schema => 2,
+            StatementKind::Other => 3,
+        }
+    }
+
+    /// The more dangerous of two kinds
+    fn max_with(self, other: StatementKind) -> StatementKind {
+        if other.severity() > self.severity() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl fmt::Display for StatementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StatementKind::Read => "read",
+            StatementKind::Write => "data-modifying",
+            StatementKind::Schema => "schema-changing",
+            StatementKind::Other => "unrecognised",
+        })
+    }
+}
+
+const READ_KEYWORDS: &[&str] = &[
+    "select", "with", "values", "table", "show", "explain", "fetch",
+];
+
+const WRITE_KEYWORDS: &[&str] = &[
+    "insert", "update", "delete", "merge", "upsert", "replace", "copy", "truncate",
+];
+
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "create", "alter", "drop", "rename", "comment", "grant", "revoke", "reindex", "vacuum",
+    "analyze", "cluster", "refresh", "import", "security",
+];
+
+/// Functions with side effects that can be called from a SELECT
+const MUTATING_FUNCTIONS: &[&str] = &[
+    "nextval",
+    "setval",
+    "pg_terminate_backend",
+    "pg_cancel_backend",
+    "pg_reload_conf",
+    "lo_import",
+    "lo_export",
+    "lo_unlink",
+    "dblink",
+    "dblink_exec",
+    "dblink_send_query",
+    "dblink_connect",
+    "dblink_connect_u",
+    "set_config",
+];
+
+/// Classify each statement of a SQL string
+pub fn classify_sql(sql: &str) -> Vec<StatementKind> {
+    statements(sql)
+        .iter()
+        .map(|words| classify_statement(words))
+        .collect()
+}
+
+/// Classify a MongoDB query document as used by the MongoDB provider
+pub fn classify_mongodb(query: &str) -> StatementKind {
+    let operation = serde_json::from_str::<serde_json::Value>(query)
+        .ok()
+        .and_then(|q| q.get("operation")?.as_str().map(str::to_string));
+    match operation.as_deref() {
+        Some("find") => StatementKind::Read,
+        Some("insert" | "update" | "delete") => StatementKind::Write,
+        _ => StatementKind::Other,
+    }
+}
+
+/// Classify every statement in a query for a provider
+pub fn classify(provider: &str, query: &str) -> Vec<StatementKind> {
+    match provider {
+        "mongodb" => vec![classify_mongodb(query)],
+        _ => classify_sql(query),
+    }
+}
+
+/// Whether every statement in the query only reads
+pub fn is_read_only(provider: &str, query: &str) -> bool {
+    let kinds = classify(provider, query);
+    !kinds.is_empty() && kinds.iter().all(|kind| !kind.is_mutating())
+}
+
+fn classify_statement(words: &[String]) -> StatementKind {
+    let Some(first) = words.first().map(String::as_str) else {
+        return StatementKind::Other;
+    };
+    if first == "explain" {
+        // Only EXPLAIN ANALYZE runs the statement it explains
+        let mut rest = &words[1..];
+        if rest.first().map(String::as_str) == Some("(") {
+            let close = rest.iter().position(|w| w == ")").unwrap_or(rest.len() - 1);
+            rest = &rest[close + 1..];
+        }
+        while let Some("analyze" | "analyse" | "verbose") = rest.first().map(String::as_str) {
+            rest = &rest[1..];
+        }
+        return if words.iter().any(|w| w == "analyze" || w == "analyse") {
+            classify_statement(rest)
+        } else {
+            StatementKind::Read
+        };
+    }
+    if READ_KEYWORDS.contains(&first) {
+        classify_body(words)
+    } else if WRITE_KEYWORDS.contains(&first) {
+        StatementKind::Write
+    } else if SCHEMA_KEYWORDS.contains(&first) {
+        StatementKind::Schema
+    } else {
+        StatementKind::Other
+    }
+}
+
+/// Mutations nested inside a reading statement
+///
+/// Catches data-modifying CTEs, SELECT INTO, row locks and side-effecting
+/// function calls.
+fn classify_body(words: &[String]) -> StatementKind {
+    let mut kind = StatementKind::Read;
+    for (i, word) in words.iter().enumerate() {
+        let next = words.get(i + 1).map(String::as_str);
+        let found = match word.as_str() {
+            "insert" | "update" | "delete" | "merge" => StatementKind::Write,
+            // SELECT ... INTO creates a table
+            "into" => StatementKind::Schema,
+            w if MUTATING_FUNCTIONS.contains(&w) && next == Some("(") => StatementKind::Write,
+            "for" if matches!(next, Some("share" | "no" | "key")) => StatementKind::Write,
+            _ => StatementKind::Read,
+        };
+        kind = kind.max_with(found);
+    }
+    kind
+}
+
+/// Lowercased words and parentheses of each statement
+///
+/// Comments, string literals (including dollar-quoted ones) and quoted
+/// identifiers are dropped.
+fn statements(sql: &str) -> Vec<Vec<String>> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut statements = vec![Vec::new()];
+    let mut i = 0;
+    let skip_quoted = |i: usize, quote: char| {
+        let mut j = i + 1;
+        while j < chars.len() {
+            if chars[j] == quote {
+                // A doubled quote is an escaped quote
+                if chars.get(j + 1) == Some(&quote) {
+                    j += 2;
+                    continue;
+                }
+                return j + 1;
+            }
+            if chars[j] == '\\' && quote == '\'' && i > 0 && matches!(chars[i - 1], 'e' | 'E') {
+                j += 2;
+                continue;
+            }
+            j += 1;
+        }
+        chars.len()
+    };
+    while i < chars.len() {
+        let c = chars[i];
+        let current = statements.last_mut().expect("at least one statement");
+        match c {
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                // Block comments nest in PostgreSQL
+                let mut depth = 0;
+                while i < chars.len() {
+                    if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                        depth += 1;
+                        i += 2;
+                    } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            '\'' | '"' | '`' => i = skip_quoted(i, c),
+            '$' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| !(c.is_alphanumeric() || c == '_'))
+                    .map(|n| i + 1 + n);
+                match end {
+                    Some(end)
+                        if chars[end] == '$'
+                            && !chars[i + 1..end]
+                                .first()
+                                .is_some_and(|c| c.is_ascii_digit()) =>
+                    {
+                        let tag: String = chars[i..=end].iter().collect();
+                        let rest: String = chars[end + 1..].iter().collect();
+                        i = match rest.find(&tag) {
+                            Some(close) => {
+                                end + 1 + rest[..close].chars().count() + tag.chars().count()
+                            }
+                            None => chars.len(),
+                        };
+                    }
+                    // A parameter placeholder such as $1
+                    _ => i += 1,
+                }
+            }
+            ';' => {
+                statements.push(Vec::new());
+                i += 1;
+            }
+            '(' | ')' => {
+                current.push(c.to_string());
+                i += 1;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                current.push(word.to_lowercase());
+            }
+            _ => i += 1,
+        }
+    }
+    statements.retain(|words| !words.is_empty());
+    statements
+}
+
+/// What to do with mutating statements on a read-only provider
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WritePolicy {
+    /// Refuse them
+    #[default]
+    Reject,
+    /// Run them only when the call sets `confirm`
+    Confirm,
+}
+
+/// Per-provider guard, under `database.safety.<provider>` in the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuerySafety {
+    /// Only let reading statements through without further ado
+    #[serde(default)]
+    pub read_only: bool,
+    /// How mutating statements are handled when `read_only` is set
+    #[serde(default)]
+    pub on_write: WritePolicy,
+}
+
+impl QuerySafety {
+    /// Check a query before it runs
+    pub fn check(&self, provider: &str, query: &str, confirmed: bool) -> Result<()> {
+        if !self.read_only {
+            return Ok(());
+        }
+        let kinds = classify(provider, query);
+        if kinds.is_empty() {
+            return Err(Error::validation_with_field(
+                "Query has no statements",
+                "query",
+            ));
+        }
+        let Some(kind) = kinds.into_iter().find(|kind| kind.is_mutating()) else {
+            return Ok(());
+        };
+        match self.on_write {
+            WritePolicy::Reject => Err(Error::validation_with_field(
+                format!(
+                    "{} is read-only and the query contains {} statements",
+                    provider, kind
+                ),
+                "query",
+            )),
+            WritePolicy::Confirm if !confirmed => Err(Error::validation_with_field(
+                format!(
+                    "{} is read-only and the query contains {} statements; set confirm to true to run it",
+                    provider, kind
+                ),
+                "confirm",
+            )),
+            WritePolicy::Confirm => Ok(()),
+        }
+    }
+
+    /// Whether a checked query should run inside a read-only transaction
+    ///
+    /// The classifier cannot see into functions or extensions, so reading
+    /// queries against a read-only provider still get the server's own guard.
+    pub fn runs_read_only(&self, provider: &str, query: &str) -> bool {
+        self.read_only && is_read_only(provider, query)
+    }
+
+    /// Whether explicit transactions should be opened read-only
+    ///
+    /// Only when writes are refused outright; confirmed writes need a
+    /// writable transaction.
+    pub fn read_only_transactions(&self) -> bool {
+        self.read_only && self.on_write == WritePolicy::Reject
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_statements_past_comments_and_literals() {
+        use StatementKind::*;
+        assert_eq!(
+            classify_sql("SELECT * FROM users WHERE id = $1"),
+            vec![Read]
+        );
+        assert_eq!(
+            classify_sql("select 'drop table users; delete' -- update\n, \"insert\" from t"),
+            vec![Read]
+        );
+        assert_eq!(
+            classify_sql("/* outer /* nested */ drop */ SELECT $q$; DELETE$q$"),
+            vec![Read]
+        );
+        assert_eq!(
+            classify_sql("SELECT 1; DROP TABLE users"),
+            vec![Read, Schema]
+        );
+        assert_eq!(
+            classify_sql("WITH gone AS (DELETE FROM t RETURNING *) SELECT * FROM gone"),
+            vec![Write]
+        );
+        assert_eq!(classify_sql("SELECT * INTO backup FROM t"), vec![Schema]);
+        assert_eq!(classify_sql("SELECT nextval('seq')"), vec![Write]);
+        assert_eq!(classify_sql("SELECT * FROM t FOR UPDATE"), vec![Write]);
+        assert_eq!(
+            classify_sql("SELECT * FROM dblink('db', 'DELETE FROM t') AS x(a int)"),
+            vec![Write]
+        );
+        assert_eq!(classify_sql("EXPLAIN DELETE FROM t"), vec![Read]);
+        assert_eq!(classify_sql("EXPLAIN ANALYZE DELETE FROM t"), vec![Write]);
+        assert_eq!(
+            classify_sql("EXPLAIN (ANALYZE, BUFFERS) SELECT * FROM t"),
+            vec![Read]
+        );
+        assert_eq!(classify_sql("SET transaction_read_only = off"), vec![Other]);
+        assert_eq!(
+            classify_mongodb(r#"{"collection": "c", "operation": "find"}"#),
+            Read
+        );
+        assert_eq!(
+            classify_mongodb(r#"{"collection": "c", "operation": "delete"}"#),
+            Write
+        );
+        assert!(!is_read_only("postgresql", "  -- nothing\n"));
+    }
+
+    #[test]
+    fn read_only_providers_reject_or_confirm_mutations() {
+        let reject = QuerySafety {
+            read_only: true,
+            on_write: WritePolicy::Reject,
+        };
+        assert!(reject.check("postgresql", "SELECT 1", false).is_ok());
+        let error = reject
+            .check("postgresql", "UPDATE t SET a = 1", true)
+            .unwrap_err();
+        assert!(error.to_string().contains("data-modifying"));
+
+        let confirm = QuerySafety {
+            on_write: WritePolicy::Confirm,
+            ..reject
+        };
+        assert!(confirm
+            .check("postgresql", "TRUNCATE t", false)
+            .unwrap_err()
+            .to_string()
+            .contains("set confirm to true"));
+        assert!(confirm.check("postgresql", "TRUNCATE t", true).is_ok());
+        assert!(QuerySafety::default()
+            .check("postgresql", "DROP TABLE t", false)
+            .is_ok());
+
+        assert!(reject.runs_read_only("postgresql", "SELECT 1"));
+        assert!(!confirm.runs_read_only("postgresql", "TRUNCATE t"));
+        assert!(!QuerySafety::default().runs_read_only("postgresql", "SELECT 1"));
+        assert!(reject.read_only_transactions());
+        assert!(!confirm.read_only_transactions());
+    }
+}
@@ -0,0 +1,384 @@
+use crate::database::Column;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Index on a table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Index {
+    pub name: String,
+    /// Indexed columns in key order; expressions are left out
+    pub columns: Vec<String>,
+    pub unique: bool,
+    pub primary: bool,
+}
+
+/// Foreign key from a table's columns to another table's
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ForeignKey {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub referenced_table: String,
+    /// Empty when the key references the other table's primary key implicitly
+    pub referenced_columns: Vec<String>,
+    pub on_delete: String,
+    pub on_update: String,
+}
+
+/// Table with everything needed to write queries against it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableSchema {
+    pub name: String,
+    pub columns: Vec<Column>,
+    pub indexes: Vec<Index>,
+    pub foreign_keys: Vec<ForeignKey>,
+    /// Planner estimate or exact count, where the provider has one
+    pub row_count: Option<u64>,
+    pub comment: Option<String>,
+}
+
+impl TableSchema {
+    /// Set column key flags from the primary key and single-column unique indexes
+    pub fn mark_keys(&mut self) {
+        for index in &self.indexes {
+            for column in &mut self.columns {
+                if !index.columns.contains(&column.name) {
+                    continue;
+                }
+                if index.primary {
+                    column.primary_key = true;
+                } else if index.unique && index.columns.len() == 1 {
+                    column.unique = true;
+                }
+            }
+        }
+    }
+
+    fn foreign_key_for(&self, column: &str) -> Option<&ForeignKey> {
+        self.foreign_keys
+            .iter()
+            .find(|key| key.columns.iter().any(|c| c == column))
+    }
+}
+
+/// Tables, columns, indexes and foreign keys of one schema
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabaseSchema {
+    /// Schema or attached database the tables belong to
+    pub schema: String,
+    pub tables: Vec<TableSchema>,
+}
+
+/// Text format for an entity-relationship diagram
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ErdFormat {
+    #[default]
+    Mermaid,
+    Dot,
+}
+
+/// Spelled-out foreign key action from PostgreSQL's one-letter code
+pub fn foreign_key_action(code: &str) -> String {
+    match code {
+        "a" => "NO ACTION",
+        "r" => "RESTRICT",
+        "c" => "CASCADE",
+        "n" => "SET NULL",
+        "d" => "SET DEFAULT",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Identifier usable as a Mermaid entity, attribute name or type
+fn mermaid_word(text: &str) -> String {
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+            word.push(c);
+        } else if !word.ends_with('_') {
+            word.push('_');
+        }
+    }
+    let word = word.trim_matches('_');
+    if word.is_empty() {
+        "_".to_string()
+    } else {
+        word.to_string()
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl DatabaseSchema {
+    /// Keep only the named tables; an empty list keeps all of them
+    pub fn retain_tables(&mut self, names: &[String]) {
+        if !names.is_empty() {
+            self.tables.retain(|table| names.contains(&table.name));
+        }
+    }
+
+    /// Table by name
+    pub fn table_mut(&mut self, name: &str) -> Option<&mut TableSchema> {
+        self.tables.iter_mut().find(|table| table.name == name)
+    }
+
+    /// Number of foreign keys across all tables
+    pub fn foreign_key_count(&self) -> usize {
+        self.tables.iter().map(|t| t.foreign_keys.len()).sum()
+    }
+
+    /// Compact plain-text outline, one line per column
+    pub fn outline(&self) -> String {
+        let mut text = String::new();
+        for table in &self.tables {
+            let _ = write!(text, "{}", table.name);
+            if let Some(rows) = table.row_count {
+                let _ = write!(text, " (~{} rows)", rows);
+            }
+            if let Some(comment) = &table.comment {
+                let _ = write!(text, " -- {}", comment);
+            }
+            text.push('\n');
+            for column in &table.columns {
+                let _ = write!(text, "  {} {}", column.name, column.data_type);
+                if column.primary_key {
+                    text.push_str(" PK");
+                } else if !column.nullable {
+                    text.push_str(" NOT NULL");
+                }
+                if column.unique {
+                    text.push_str(" UNIQUE");
+                }
+                if let Some(default) = &column.default {
+                    let _ = write!(text, " DEFAULT {}", default);
+                }
+                if let Some(key) = table.foreign_key_for(&column.name) {
+                    let position = key.columns.iter().position(|c| *c == column.name);
+                    let target = position
+                        .and_then(|i| key.referenced_columns.get(i))
+                        .map(|c| format!("{}.{}", key.referenced_table, c))
+                        .unwrap_or_else(|| key.referenced_table.clone());
+                    let _ = write!(text, " -> {}", target);
+                }
+                text.push('\n');
+            }
+            for index in table.indexes.iter().filter(|i| !i.primary) {
+                let _ = writeln!(
+                    text,
+                    "  index {} ({}){}",
+                    index.name,
+                    index.columns.join(", "),
+                    if index.unique { " UNIQUE" } else { "" }
+                );
+            }
+        }
+        text
+    }
+
+    /// Entity-relationship diagram, with edges only between included tables
+    pub fn erd(&self, format: ErdFormat) -> String {
+        let names: BTreeSet<&str> = self.tables.iter().map(|t| t.name.as_str()).collect();
+        let edges = self.tables.iter().flat_map(|table| {
+            table
+                .foreign_keys
+                .iter()
+                .filter(|key| names.contains(key.referenced_table.as_str()))
+                .map(move |key| (table, key))
+        });
+        let mut out = String::new();
+        match format {
+            ErdFormat::Mermaid => {
+                out.push_str("erDiagram\n");
+                for table in &self.tables {
+                    let _ = writeln!(out, "    {} {{", mermaid_word(&table.name));
+                    for column in &table.columns {
+                        let mut keys = Vec::new();
+                        if column.primary_key {
+                            keys.push("PK");
+                        }
+                        if table.foreign_key_for(&column.name).is_some() {
+                            keys.push("FK");
+                        }
+                        if column.unique {
+                            keys.push("UK");
+                        }
+                        let _ = writeln!(
+                            out,
+                            "        {} {}{}{}",
+                            mermaid_word(&column.data_type),
+                            mermaid_word(&column.name),
+                            if keys.is_empty() { "" } else { " " },
+                            keys.join(", ")
+                        );
+                    }
+                    out.push_str("    }\n");
+                }
+                for (table, key) in edges {
+                    let columns: Vec<&Column> = table
+                        .columns
+                        .iter()
+                        .filter(|c| key.columns.contains(&c.name))
+                        .collect();
+                    let optional = columns.iter().any(|c| c.nullable);
+                    let one_to_one = table.indexes.iter().any(|index| {
+                        index.unique && !index.columns.is_empty() && index.columns == key.columns
+                    });
+                    let _ = writeln!(
+                        out,
+                        "    {} {}--{} {} : \"{}\"",
+                        mermaid_word(&key.referenced_table),
+                        if optional { "|o" } else { "||" },
+                        if one_to_one { "o|" } else { "o{" },
+                        mermaid_word(&table.name),
+                        key.columns.join(", ").replace('"', "'")
+                    );
+                }
+            }
+            ErdFormat::Dot => {
+                out.push_str("digraph schema {\n    rankdir=LR;\n    node [shape=plaintext];\n");
+                for table in &self.tables {
+                    let _ = write!(
+                        out,
+                        "    \"{}\" [label=<<table border=\"0\" cellborder=\"1\" cellspacing=\"0\"><tr><td bgcolor=\"lightgrey\"><b>{}</b></td></tr>",
+                        table.name.replace('"', "\\\""),
+                        html_escape(&table.name)
+                    );
+                    for column in &table.columns {
+                        let mut label = format!("{}: {}", column.name, column.data_type);
+                        if column.primary_key {
+                            label.push_str(" (PK)");
+                        }
+                        if table.foreign_key_for(&column.name).is_some() {
+                            label.push_str(" (FK)");
+                        }
+                        let _ = write!(
+                            out,
+                            "<tr><td align=\"left\" port=\"{}\">{}</td></tr>",
+                            html_escape(&column.name),
+                            html_escape(&label)
+                        );
+                    }
+                    out.push_str("</table>>];\n");
+                }
+                for (table, key) in edges {
+                    let _ = writeln!(
+                        out,
+                        "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                        table.name.replace('"', "\\\""),
+                        key.referenced_table.replace('"', "\\\""),
+                        key.columns.join(", ").replace('"', "\\\"")
+                    );
+                }
+                out.push_str("}\n");
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, data_type: &str, nullable: bool) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            primary_key: false,
+            unique: false,
+            default: None,
+        }
+    }
+
+    fn index(name: &str, columns: &[&str], unique: bool, primary: bool) -> Index {
+        Index {
+            name: name.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            unique,
+            primary,
+        }
+    }
+
+    #[test]
+    fn outlines_and_draws_related_tables() {
+        let mut teams = TableSchema {
+            name: "teams".to_string(),
+            columns: vec![
+                column("id", "integer", false),
+                column("name", "text", false),
+            ],
+            indexes: vec![index("teams_pkey", &["id"], true, true)],
+            foreign_keys: vec![],
+            row_count: Some(3),
+            comment: None,
+        };
+        let mut users = TableSchema {
+            name: "users".to_string(),
+            columns: vec![
+                column("id", "integer", false),
+                column("email", "character varying(255)", false),
+                column("team_id", "integer", true),
+            ],
+            indexes: vec![
+                index("users_pkey", &["id"], true, true),
+                index("users_email_key", &["email"], true, false),
+            ],
+            foreign_keys: vec![
+                ForeignKey {
+                    name: "users_team_id_fkey".to_string(),
+                    columns: vec!["team_id".to_string()],
+                    referenced_table: "teams".to_string(),
+                    referenced_columns: vec!["id".to_string()],
+                    on_delete: foreign_key_action("n"),
+                    on_update: foreign_key_action("a"),
+                },
+                ForeignKey {
+                    name: "users_audit_fkey".to_string(),
+                    columns: vec!["id".to_string()],
+                    referenced_table: "audit".to_string(),
+                    referenced_columns: vec![],
+                    on_delete: foreign_key_action("c"),
+                    on_update: foreign_key_action("a"),
+                },
+            ],
+            row_count: None,
+            comment: Some("Accounts".to_string()),
+        };
+        teams.mark_keys();
+        users.mark_keys();
+        let mut schema = DatabaseSchema {
+            schema: "public".to_string(),
+            tables: vec![teams, users],
+        };
+        assert_eq!(schema.foreign_key_count(), 2);
+        assert_eq!(schema.tables[1].foreign_keys[0].on_delete, "SET NULL");
+
+        let outline = schema.outline();
+        assert!(outline.contains("users -- Accounts\n  id integer PK -> audit\n"));
+        assert!(outline.contains("  email character varying(255) NOT NULL UNIQUE\n"));
+        assert!(outline.contains("  team_id integer -> teams.id\n"));
+        assert!(outline.contains("  index users_email_key (email) UNIQUE\n"));
+
+        let mermaid = schema.erd(ErdFormat::Mermaid);
+        assert!(mermaid.starts_with("erDiagram\n    teams {\n        integer id PK\n"));
+        assert!(mermaid.contains("        character_varying_255 email UK\n"));
+        assert!(mermaid.contains("    teams |o--o{ users : \"team_id\"\n"));
+        assert!(!mermaid.contains("audit"));
+
+        let dot = schema.erd(ErdFormat::Dot);
+        assert!(dot.contains("<b>users</b>"));
+        assert!(dot.contains("    \"users\" -> \"teams\" [label=\"team_id\"];\n"));
+
+        schema.retain_tables(&["users".to_string()]);
+        assert_eq!(schema.tables.len(), 1);
+        assert!(!schema.erd(ErdFormat::Mermaid).contains("--"));
+    }
+}
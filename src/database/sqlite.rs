@@ -0,0 +1,664 @@
+#[cfg(feature = "database")]
+use crate::database::schema::{DatabaseSchema, ForeignKey, Index, TableSchema};
+use crate::database::Database;
+#[cfg(feature = "database")]
+use crate::database::{Column, DatabaseStatus, PoolConfig, QueryResult, Table};
+use crate::error::{Error, Result};
+#[cfg(feature = "database")]
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "database")]
+use serde_json::{json, Value};
+#[cfg(feature = "database")]
+use sqlx::sqlite::{SqliteArguments, SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
+#[cfg(feature = "database")]
+use sqlx::{Column as SqlxColumn, Row, Sqlite, SqlitePool, TypeInfo};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "database")]
+use std::time::{Duration, Instant};
+
+/// File extensions listed as SQLite databases
+const EXTENSIONS: &[&str] = &["db", "sqlite", "sqlite3", "db3"];
+
+fn default_read_only() -> bool {
+    true
+}
+
+/// Local database files, under `database.sqlite` in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqliteConfig {
+    /// Directories whose files may be opened; relative file names resolve against them in order
+    #[serde(default)]
+    pub dirs: Vec<PathBuf>,
+    /// Open files read-only and refuse mutating statements
+    #[serde(default = "default_read_only")]
+    pub read_only: bool,
+}
+
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        Self {
+            dirs: Vec::new(),
+            read_only: default_read_only(),
+        }
+    }
+}
+
+impl SqliteConfig {
+    /// Canonical path of an existing file inside one of `dirs`
+    pub fn resolve(&self, file: &str) -> Result<PathBuf> {
+        if self.dirs.is_empty() {
+            return Err(Error::config_with_suggestion(
+                "No SQLite directories configured",
+                "Set database.sqlite.dirs to the directories holding your database files",
+            ));
+        }
+        let requested = Path::new(file);
+        let candidates: Vec<PathBuf> = if requested.is_absolute() {
+            vec![requested.to_path_buf()]
+        } else {
+            self.dirs.iter().map(|dir| dir.join(requested)).collect()
+        };
+        for candidate in candidates {
+            let Ok(path) = candidate.canonicalize() else {
+                continue;
+            };
+            let allowed = self
+                .dirs
+                .iter()
+                .filter_map(|dir| dir.canonicalize().ok())
+                .any(|dir| path.starts_with(dir));
+            if !allowed {
+                return Err(Error::validation_with_field(
+                    format!("{} is outside the configured SQLite directories", file),
+                    "database",
+                ));
+            }
+            if path.is_file() {
+                return Ok(path);
+            }
+        }
+        Err(Error::not_found_with_resource(
+            "SQLite database not found",
+            "sqlite_file",
+            file,
+        ))
+    }
+
+    /// Database files directly inside `dirs`, by extension
+    pub fn find_files(&self) -> Vec<String> {
+        let mut files = Vec::new();
+        for dir in &self.dirs {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let matches = path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| EXTENSIONS.contains(&e.to_lowercase().as_str()));
+                if matches && path.is_file() {
+                    files.push(path.to_string_lossy().into_owned());
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+}
+
+/// Quote an identifier for SQLite
+#[cfg(feature = "database")]
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// SQLite provider for local database files
+#[cfg(feature = "database")]
+pub struct SqliteProvider {
+    pool: SqlitePool,
+    path: String,
+}
+
+#[cfg(feature = "database")]
+impl SqliteProvider {
+    /// Open an existing file, read-only unless `read_only` is false
+    pub async fn open(path: &Path, read_only: bool) -> Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .read_only(read_only)
+            .create_if_missing(false);
+        Self::connect_with(
+            options,
+            path.to_string_lossy().into_owned(),
+            &PoolConfig::default(),
+        )
+        .await
+    }
+
+    /// Open a `sqlite:` URL or a plain file path with a pool sized by `pool`
+    pub async fn connect(connection_string: &str, pool: &PoolConfig) -> Result<Self> {
+        pool.validate()?;
+        let options = if connection_string.starts_with("sqlite:") {
+            connection_string
+                .parse::<SqliteConnectOptions>()
+                .map_err(|e| Error::config(format!("Invalid SQLite connection string: {}", e)))?
+        } else {
+            SqliteConnectOptions::new().filename(connection_string)
+        };
+        Self::connect_with(options, connection_string.to_string(), pool).await
+    }
+
+    async fn connect_with(
+        options: SqliteConnectOptions,
+        path: String,
+        pool: &PoolConfig,
+    ) -> Result<Self> {
+        let mut options = options;
+        if let Some(timeout) = pool.statement_timeout_ms {
+            options = options.busy_timeout(Duration::from_millis(timeout));
+        }
+        let pool = SqlitePoolOptions::new()
+            .max_connections(pool.max_connections)
+            .min_connections(pool.min_connections)
+            .acquire_timeout(Duration::from_secs(pool.acquire_timeout_secs))
+            .idle_timeout(pool.idle_timeout_secs.map(Duration::from_secs))
+            .connect_with(options)
+            .await
+            .map_err(|e| {
+                Error::service(format!("Failed to open SQLite database {}: {}", path, e))
+            })?;
+        Ok(Self { pool, path })
+    }
+
+    /// JSON for one column, chosen by the stored value's type
+    fn column_value(row: &SqliteRow, i: usize) -> Result<Value> {
+        use sqlx::ValueRef;
+        let raw = row
+            .try_get_raw(i)
+            .map_err(|e| Error::parsing(format!("Failed to read column {}: {}", i, e)))?;
+        if raw.is_null() {
+            return Ok(Value::Null);
+        }
+        let storage = raw.type_info().name().to_string();
+        let decode_error = |e: sqlx::Error| {
+            Error::parsing(format!(
+                "Failed to decode {}: {}",
+                row.columns()[i].name(),
+                e
+            ))
+        };
+        let value = match storage.as_str() {
+            "INTEGER" => json!(row.try_get_unchecked::<i64, _>(i).map_err(decode_error)?),
+            "REAL" => json!(row.try_get_unchecked::<f64, _>(i).map_err(decode_error)?),
+            "BLOB" => json!(base64::engine::general_purpose::STANDARD.encode(
+                row.try_get_unchecked::<Vec<u8>, _>(i)
+                    .map_err(decode_error)?
+            )),
+            _ => json!(row
+                .try_get_unchecked::<String, _>(i)
+                .map_err(decode_error)?),
+        };
+        Ok(value)
+    }
+
+    fn row_to_value(row: &SqliteRow) -> Result<Value> {
+        let mut object = serde_json::Map::new();
+        for (i, column) in row.columns().iter().enumerate() {
+            object.insert(column.name().to_string(), Self::column_value(row, i)?);
+        }
+        Ok(Value::Object(object))
+    }
+
+    /// Column types as declared, falling back to the first row's storage class
+    fn get_columns(row: &SqliteRow) -> Vec<Column> {
+        row.columns()
+            .iter()
+            .map(|col| Column {
+                name: col.name().to_string(),
+                data_type: col.type_info().name().to_string(),
+                nullable: true,
+                primary_key: false,
+                unique: false,
+                default: None,
+            })
+            .collect()
+    }
+
+    async fn row_count(&self, schema: &str, table: &str) -> Option<u64> {
+        let query = format!(
+            "SELECT COUNT(*) FROM {}.{}",
+            quote_ident(schema),
+            quote_ident(table)
+        );
+        sqlx::query_scalar::<_, i64>(&query)
+            .fetch_one(&self.pool)
+            .await
+            .ok()
+            .map(|count| count as u64)
+    }
+}
+
+/// Bind a JSON value as the next query parameter
+///
+/// Arrays and objects bind as their JSON text, which SQLite's JSON functions accept.
+#[cfg(feature = "database")]
+fn bind_json<'q>(
+    statement: sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: &Value,
+) -> sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value {
+        Value::Null => statement.bind(None::<String>),
+        Value::Bool(b) => statement.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => statement.bind(i),
+            None => statement.bind(n.as_f64()),
+        },
+        Value::String(s) => statement.bind(s.clone()),
+        other => statement.bind(other.to_string()),
+    }
+}
+
+#[cfg(feature = "database")]
+#[async_trait::async_trait]
+impl Database for SqliteProvider {
+    async fn execute_query(&self, query: &str, database: Option<&str>) -> Result<QueryResult> {
+        self.execute_with_params(query, &[], database).await
+    }
+
+    async fn execute_with_params(
+        &self,
+        query: &str,
+        params: &[Value],
+        _database: Option<&str>,
+    ) -> Result<QueryResult> {
+        let start = Instant::now();
+        let mut statement = sqlx::query(query);
+        for param in params {
+            statement = bind_json(statement, param);
+        }
+        if crate::database::postgresql::returns_rows(query)
+            || query.trim_start().to_lowercase().starts_with("pragma")
+        {
+            let rows: Vec<SqliteRow> = statement
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| Error::service(format!("Query execution failed: {}", e)))?;
+            let columns = rows.first().map(Self::get_columns).unwrap_or_default();
+            let rows = rows
+                .iter()
+                .map(Self::row_to_value)
+                .collect::<Result<Vec<Value>>>()?;
+            Ok(QueryResult {
+                rows,
+                columns,
+                rows_affected: 0,
+                execution_time_ms: start.elapsed().as_millis() as u64,
+            })
+        } else {
+            let result = statement
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Error::service(format!("Query execution failed: {}", e)))?;
+            Ok(QueryResult {
+                rows: vec![],
+                columns: vec![],
+                rows_affected: result.rows_affected(),
+                execution_time_ms: start.elapsed().as_millis() as u64,
+            })
+        }
+    }
+
+    async fn list_databases(&self) -> Result<Vec<String>> {
+        let rows = sqlx::query("SELECT name FROM pragma_database_list ORDER BY seq")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Error::service(format!("Failed to list databases: {}", e)))?;
+        rows.iter()
+            .map(|row| {
+                row.try_get::<String, _>(0)
+                    .map_err(|e| Error::parsing(format!("Failed to read database name: {}", e)))
+            })
+            .collect()
+    }
+
+    async fn list_tables(&self, database: Option<&str>) -> Result<Vec<Table>> {
+        let schema = database.unwrap_or("main");
+        let query = format!(
+            "SELECT name FROM {}.sqlite_schema WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            quote_ident(schema)
+        );
+        let names: Vec<String> = sqlx::query_scalar(&query)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Error::service(format!("Failed to list tables: {}", e)))?;
+        let mut tables = Vec::new();
+        for name in names {
+            let row_count = self.row_count(schema, &name).await;
+            tables.push(Table {
+                name,
+                columns: vec![],
+                row_count,
+                size_bytes: None,
+            });
+        }
+        Ok(tables)
+    }
+
+    async fn describe_table(&self, table_name: &str, database: Option<&str>) -> Result<Table> {
+        let schema = database.unwrap_or("main");
+        let columns: Vec<(String, String, bool, Option<String>, i64)> = sqlx::query_as(
+            r#"SELECT name, type, "notnull", dflt_value, pk FROM pragma_table_info(?1, ?2) ORDER BY cid"#,
+        )
+        .bind(table_name)
+        .bind(schema)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::service(format!("Failed to describe table: {}", e)))?;
+        if columns.is_empty() {
+            return Err(Error::not_found_with_resource(
+                "Table not found",
+                "table",
+                table_name,
+            ));
+        }
+
+        // Columns covered alone by a unique index
+        let unique: Vec<String> = sqlx::query_scalar(
+            r#"SELECT MIN(ii.name) FROM pragma_index_list(?1, ?2) il
+               JOIN pragma_index_info(il.name, ?2) ii
+               WHERE il."unique" = 1
+               GROUP BY il.name HAVING COUNT(*) = 1"#,
+        )
+        .bind(table_name)
+        .bind(schema)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::service(format!("Failed to get unique constraints: {}", e)))?;
+
+        let columns = columns
+            .into_iter()
+            .map(|(name, data_type, not_null, default, pk)| Column {
+                unique: unique.contains(&name),
+                name,
+                data_type,
+                nullable: !not_null && pk == 0,
+                primary_key: pk > 0,
+                default,
+            })
+            .collect();
+        Ok(Table {
+            name: table_name.to_string(),
+            columns,
+            row_count: self.row_count(schema, table_name).await,
+            size_bytes: None,
+        })
+    }
+
+    async fn describe_schema(&self, database: Option<&str>) -> Result<DatabaseSchema> {
+        let schema = database.unwrap_or("main");
+        let mut described = DatabaseSchema {
+            schema: schema.to_string(),
+            tables: Vec::new(),
+        };
+        for table in self.list_tables(Some(schema)).await? {
+            let table = self.describe_table(&table.name, Some(schema)).await?;
+            let mut indexes = Vec::new();
+            let primary: Vec<String> = table
+                .columns
+                .iter()
+                .filter(|c| c.primary_key)
+                .map(|c| c.name.clone())
+                .collect();
+            if !primary.is_empty() {
+                indexes.push(Index {
+                    name: "primary".to_string(),
+                    columns: primary,
+                    unique: true,
+                    primary: true,
+                });
+            }
+            // Automatic indexes behind a primary key are covered above
+            let index_columns: Vec<(String, bool, Option<String>)> = sqlx::query_as(
+                r#"SELECT il.name, il."unique", ii.name FROM pragma_index_list(?1, ?2) il
+                   JOIN pragma_index_info(il.name, ?2) ii
+                   WHERE il.origin != 'pk'
+                   ORDER BY il.name, ii.seqno"#,
+            )
+            .bind(&table.name)
+            .bind(schema)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Error::service(format!("Failed to read indexes: {}", e)))?;
+            for (name, unique, column) in index_columns {
+                if indexes
+                    .last()
+                    .is_none_or(|index: &Index| index.name != name)
+                {
+                    indexes.push(Index {
+                        name,
+                        columns: Vec::new(),
+                        unique,
+                        primary: false,
+                    });
+                }
+                if let (Some(index), Some(column)) = (indexes.last_mut(), column) {
+                    index.columns.push(column);
+                }
+            }
+
+            let key_columns: Vec<(i64, String, String, Option<String>, String, String)> =
+                sqlx::query_as(
+                    r#"SELECT id, "table", "from", "to", on_update, on_delete
+                       FROM pragma_foreign_key_list(?1, ?2) ORDER BY id, seq"#,
+                )
+                .bind(&table.name)
+                .bind(schema)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| Error::service(format!("Failed to read foreign keys: {}", e)))?;
+            let mut foreign_keys: Vec<(i64, ForeignKey)> = Vec::new();
+            for (id, referenced_table, from, to, on_update, on_delete) in key_columns {
+                if foreign_keys.last().is_none_or(|(last, _)| *last != id) {
+                    foreign_keys.push((
+                        id,
+                        ForeignKey {
+                            name: String::new(),
+                            columns: Vec::new(),
+                            referenced_table,
+                            referenced_columns: Vec::new(),
+                            on_delete,
+                            on_update,
+                        },
+                    ));
+                }
+                if let Some((_, key)) = foreign_keys.last_mut() {
+                    key.columns.push(from);
+                    key.referenced_columns.extend(to);
+                }
+            }
+
+            let mut table = TableSchema {
+                foreign_keys: foreign_keys
+                    .into_iter()
+                    .map(|(_, mut key)| {
+                        // SQLite keeps no constraint names, so name keys like PostgreSQL does
+                        key.name = format!("{}_{}_fkey", table.name, key.columns.join("_"));
+                        key
+                    })
+                    .collect(),
+                name: table.name,
+                columns: table.columns,
+                indexes,
+                row_count: table.row_count,
+                comment: None,
+            };
+            table.mark_keys();
+            described.tables.push(table);
+        }
+        Ok(described)
+    }
+
+    async fn health_check(&self) -> Result<DatabaseStatus> {
+        let start = Instant::now();
+        match sqlx::query("SELECT 1").execute(&self.pool).await {
+            Ok(_) => Ok(DatabaseStatus {
+                healthy: true,
+                latency_ms: start.elapsed().as_millis() as u64,
+                message: Some(format!("SQLite database {} readable", self.path)),
+            }),
+            Err(e) => Ok(DatabaseStatus {
+                healthy: false,
+                latency_ms: start.elapsed().as_millis() as u64,
+                message: Some(format!("SQLite health check failed: {}", e)),
+            }),
+        }
+    }
+}
+
+/// Open a local file as a trait object
+#[cfg(feature = "database")]
+pub async fn open(path: &Path, read_only: bool) -> Result<Arc<dyn Database>> {
+    Ok(Arc::new(SqliteProvider::open(path, read_only).await?))
+}
+
+/// Open a local file as a trait object
+#[cfg(not(feature = "database"))]
+pub async fn open(path: &Path, read_only: bool) -> Result<Arc<dyn Database>> {
+    let _ = (path, read_only);
+    Err(Error::config(
+        "SQLite support requires 'database' feature to be enabled",
+    ))
+}
+
+// Stub implementation for when database feature is not enabled
+#[cfg(not(feature = "database"))]
+pub struct SqliteProvider;
+
+#[cfg(not(feature = "database"))]
+impl SqliteProvider {
+    pub async fn open(_path: &Path, _read_only: bool) -> Result<Self> {
+        Err(Error::config(
+            "SQLite support requires 'database' feature to be enabled",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_files_only_inside_configured_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let data = root.path().join("data");
+        std::fs::create_dir(&data).unwrap();
+        std::fs::write(data.join("app.db"), b"").unwrap();
+        std::fs::write(data.join("notes.txt"), b"").unwrap();
+        std::fs::write(root.path().join("secret.sqlite"), b"").unwrap();
+
+        assert!(SqliteConfig::default().resolve("app.db").is_err());
+        let config = SqliteConfig {
+            dirs: vec![data.clone()],
+            ..SqliteConfig::default()
+        };
+        let resolved = config.resolve("app.db").unwrap();
+        assert_eq!(resolved, data.join("app.db").canonicalize().unwrap());
+        assert_eq!(
+            config.resolve(&resolved.to_string_lossy()).unwrap(),
+            resolved
+        );
+        assert!(config.resolve("../secret.sqlite").is_err());
+        assert!(config
+            .resolve(&root.path().join("secret.sqlite").to_string_lossy())
+            .is_err());
+        assert!(config.resolve("missing.db").is_err());
+        assert_eq!(
+            config.find_files(),
+            vec![data.join("app.db").to_string_lossy().into_owned()]
+        );
+    }
+
+    #[cfg(feature = "database")]
+    #[tokio::test]
+    async fn queries_and_describes_local_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        let url = format!("sqlite://{}?mode=rwc", path.display());
+        let writable = SqliteProvider::connect(&url, &PoolConfig::default())
+            .await
+            .unwrap();
+        writable
+            .execute_query(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL UNIQUE, score REAL, avatar BLOB)",
+                None,
+            )
+            .await
+            .unwrap();
+        let inserted = writable
+            .execute_with_params(
+                "INSERT INTO users (email, score, avatar) VALUES ($1, $2, x'0102'), ($3, NULL, NULL)",
+                &[json!("a@example.com"), json!(1.5), json!("b@example.com")],
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(inserted.rows_affected, 2);
+
+        let provider = SqliteProvider::open(&path, true).await.unwrap();
+        let result = provider
+            .execute_with_params(
+                "SELECT * FROM users WHERE id >= $1 ORDER BY id",
+                &[json!(1)],
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.rows,
+            vec![
+                json!({"id": 1, "email": "a@example.com", "score": 1.5, "avatar": "AQI="}),
+                json!({"id": 2, "email": "b@example.com", "score": null, "avatar": null}),
+            ]
+        );
+
+        let tables = provider.list_tables(None).await.unwrap();
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].row_count, Some(2));
+        let table = provider.describe_table("users", None).await.unwrap();
+        let email = table.columns.iter().find(|c| c.name == "email").unwrap();
+        assert!(email.unique && !email.nullable);
+        assert!(table.columns[0].primary_key);
+        assert!(provider.describe_table("missing", None).await.is_err());
+
+        writable
+            .execute_query(
+                "CREATE TABLE posts (id INTEGER PRIMARY KEY, author_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE, slug TEXT, title TEXT); CREATE INDEX posts_author ON posts (author_id, slug)",
+                None,
+            )
+            .await
+            .unwrap();
+        let schema = writable.describe_schema(None).await.unwrap();
+        let posts = &schema.tables[0];
+        assert_eq!(posts.name, "posts");
+        assert_eq!(posts.indexes.len(), 2);
+        assert_eq!(posts.indexes[1].columns, vec!["author_id", "slug"]);
+        let key = &posts.foreign_keys[0];
+        assert_eq!(key.name, "posts_author_id_fkey");
+        assert_eq!(
+            (key.referenced_table.as_str(), key.on_delete.as_str()),
+            ("users", "CASCADE")
+        );
+        assert!(schema.tables[1].columns[1].unique);
+
+        assert!(provider
+            .execute_query("DELETE FROM users", None)
+            .await
+            .is_err());
+        assert!(SqliteProvider::open(&dir.path().join("new.db"), false)
+            .await
+            .is_err());
+    }
+}
@@ -131,7 +131,7 @@ impl SupabaseProvider {
                 },
             ],
             rows_affected: 1,
-            execution_time_ms: 12 // 12 milliseconds
+            execution_time_ms: 12, // 12 milliseconds
         })
     }
 }
@@ -0,0 +1,375 @@
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use crate::web::browser::{BrowserSession, WebDriverClient, WebDriverConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+/// End-to-end test scenario loaded from the YAML DSL
+///
+/// ```yaml
+/// name: Login
+/// base_url: https://app.example.com
+/// steps:
+///   - goto: /login
+///   - fill: { selector: "#email", value: "jane@example.com" }
+///   - click: "button[type=submit]"
+///   - expect_text: Welcome back
+///   - expect_text: { selector: h1, text: Dashboard }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    /// Scenario name
+    pub name: String,
+    /// Base URL for relative `goto` paths
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Per-step timeout in seconds
+    #[serde(default = "default_step_timeout")]
+    pub timeout_secs: u64,
+    /// Steps to execute in order
+    pub steps: Vec<Step>,
+}
+
+fn default_step_timeout() -> u64 {
+    10
+}
+
+impl Scenario {
+    /// Parse a scenario from YAML
+    pub fn from_yaml(source: &str) -> Result<Self> {
+        // serde_yaml expects `!tag` syntax for enums, so go through JSON to
+        // accept the single-key map form (`- goto: /login`)
+        let value: Value = serde_yaml::from_str(source).map_err(|e| {
+            Error::parsing_with_format(format!("Invalid scenario: {}", e), "yaml", None)
+        })?;
+        let scenario: Self = serde_json::from_value(value).map_err(|e| {
+            Error::parsing_with_format(format!("Invalid scenario: {}", e), "yaml", None)
+        })?;
+        if scenario.steps.is_empty() {
+            return Err(Error::validation_with_field(
+                "Scenario has no steps",
+                "steps",
+            ));
+        }
+        Ok(scenario)
+    }
+}
+
+/// A single scenario step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    /// Navigate to an absolute URL or a path relative to the base URL
+    Goto(String),
+    /// Click the element matching a CSS selector
+    Click(String),
+    /// Type a value into the element matching a CSS selector
+    Fill { selector: String, value: String },
+    /// Assert that text appears on the page or inside an element
+    ExpectText(TextExpectation),
+    /// Pause for the given number of milliseconds
+    Wait(u64),
+}
+
+/// Text assertion, either page-wide or scoped to a selector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TextExpectation {
+    Page(String),
+    Element { selector: String, text: String },
+}
+
+impl Step {
+    fn describe(&self) -> String {
+        match self {
+            Step::Goto(url) => format!("goto {}", url),
+            Step::Click(selector) => format!("click {}", selector),
+            Step::Fill { selector, .. } => format!("fill {}", selector),
+            Step::ExpectText(TextExpectation::Page(text)) => format!("expect text \"{}\"", text),
+            Step::ExpectText(TextExpectation::Element { selector, text }) => {
+                format!("expect {} to contain \"{}\"", selector, text)
+            }
+            Step::Wait(ms) => format!("wait {}ms", ms),
+        }
+    }
+}
+
+/// Outcome of a single step
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Result of a single step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepResult {
+    pub index: usize,
+    pub description: String,
+    pub status: StepStatus,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    /// Base64 PNG captured when the step failed
+    pub screenshot: Option<String>,
+}
+
+/// Report for a complete scenario run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct E2eReport {
+    pub scenario: String,
+    pub target_url: String,
+    pub passed: bool,
+    pub duration_ms: u64,
+    pub steps: Vec<StepResult>,
+}
+
+/// Runs E2E scenarios against a target through a WebDriver endpoint
+#[derive(Debug)]
+pub struct E2eRunner {
+    webdriver: WebDriverClient,
+}
+
+impl E2eRunner {
+    /// Create a new runner
+    pub fn new(config: WebDriverConfig) -> Result<Self> {
+        Ok(Self {
+            webdriver: WebDriverClient::new(config)?,
+        })
+    }
+
+    /// Run a scenario, stopping at the first failure and skipping the remaining steps
+    pub async fn run(&self, scenario: &Scenario, target_url: Option<&str>) -> Result<E2eReport> {
+        let base_url = target_url
+            .or(scenario.base_url.as_deref())
+            .ok_or_else(|| {
+                Error::validation_with_field(
+                    "A target URL or scenario base_url is required",
+                    "target_url",
+                )
+            })?
+            .trim_end_matches('/')
+            .to_string();
+        let timeout = Duration::from_secs(scenario.timeout_secs);
+
+        let session = self.webdriver.new_session().await?;
+        session.set_implicit_wait(timeout).await?;
+
+        let started = Instant::now();
+        let mut steps = Vec::with_capacity(scenario.steps.len());
+        let mut failed = false;
+
+        for (index, step) in scenario.steps.iter().enumerate() {
+            if failed {
+                steps.push(StepResult {
+                    index,
+                    description: step.describe(),
+                    status: StepStatus::Skipped,
+                    duration_ms: 0,
+                    error: None,
+                    screenshot: None,
+                });
+                continue;
+            }
+
+            let step_started = Instant::now();
+            let outcome =
+                tokio::time::timeout(timeout, Self::run_step(&session, step, &base_url, timeout))
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(Error::timeout_with_duration(step.describe(), timeout))
+                    });
+
+            let (status, error, screenshot) = match outcome {
+                Ok(()) => (StepStatus::Passed, None, None),
+                Err(e) => {
+                    failed = true;
+                    let screenshot = session.screenshot().await.ok();
+                    (StepStatus::Failed, Some(e.to_string()), screenshot)
+                }
+            };
+            steps.push(StepResult {
+                index,
+                description: step.describe(),
+                status,
+                duration_ms: step_started.elapsed().as_millis() as u64,
+                error,
+                screenshot,
+            });
+        }
+
+        if let Err(e) = session.close().await {
+            tracing::warn!(error = %e, "Failed to close WebDriver session");
+        }
+
+        Ok(E2eReport {
+            scenario: scenario.name.clone(),
+            target_url: base_url,
+            passed: !failed,
+            duration_ms: started.elapsed().as_millis() as u64,
+            steps,
+        })
+    }
+
+    async fn run_step(
+        session: &BrowserSession,
+        step: &Step,
+        base_url: &str,
+        timeout: Duration,
+    ) -> Result<()> {
+        match step {
+            Step::Goto(target) => {
+                let url = if target.starts_with("http://") || target.starts_with("https://") {
+                    target.clone()
+                } else {
+                    format!("{}/{}", base_url, target.trim_start_matches('/'))
+                };
+                session.goto(&url).await
+            }
+            Step::Click(selector) => session.click(selector).await,
+            Step::Fill { selector, value } => session.fill(selector, value).await,
+            Step::ExpectText(expectation) => {
+                let (selector, expected) = match expectation {
+                    TextExpectation::Page(text) => ("body", text),
+                    TextExpectation::Element { selector, text } => (selector.as_str(), text),
+                };
+                // Poll so text rendered after navigation still satisfies the assertion
+                let deadline = Instant::now() + timeout;
+                loop {
+                    let actual = session.text(selector).await?;
+                    if actual.contains(expected.as_str()) {
+                        return Ok(());
+                    }
+                    if Instant::now() >= deadline {
+                        return Err(Error::validation(format!(
+                            "Expected {} to contain \"{}\"",
+                            selector, expected
+                        )));
+                    }
+                    tokio::time::sleep(Duration::from_millis(250)).await;
+                }
+            }
+            Step::Wait(ms) => {
+                tokio::time::sleep(Duration::from_millis(*ms)).await;
+                Ok(())
+            }
+        }
+    }
+
+    /// Get tool definitions for E2E testing
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "run_e2e_test",
+            "Run a browser E2E scenario (YAML steps: goto, click, fill, expect_text, wait) and report step results",
+            "development_e2e",
+            json!({
+                "type": "object",
+                "properties": {
+                    "scenario": {
+                        "type": "string",
+                        "description": "Scenario definition in YAML"
+                    },
+                    "target_url": {
+                        "type": "string",
+                        "description": "Base URL to test (overrides the scenario's base_url)"
+                    },
+                    "include_screenshots": {
+                        "type": "boolean",
+                        "description": "Include base64 screenshots of failed steps",
+                        "default": true
+                    }
+                },
+                "required": ["scenario"]
+            }),
+            None,
+        )]
+    }
+
+    /// Execute an E2E tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        if name != "run_e2e_test" {
+            return Err(Error::not_found_with_resource(
+                "Tool not found",
+                "e2e_tool",
+                name,
+            ));
+        }
+
+        let source = parameters
+            .get("scenario")
+            .and_then(|s| s.as_str())
+            .ok_or_else(|| Error::validation_with_field("scenario is required", "scenario"))?;
+        let scenario = Scenario::from_yaml(source)?;
+        let target_url = parameters.get("target_url").and_then(|t| t.as_str());
+
+        let mut report = self.run(&scenario, target_url).await?;
+        if parameters
+            .get("include_screenshots")
+            .and_then(|i| i.as_bool())
+            == Some(false)
+        {
+            report.steps.iter_mut().for_each(|s| s.screenshot = None);
+        }
+
+        let summary = report
+            .steps
+            .iter()
+            .map(|s| {
+                let marker = match s.status {
+                    StepStatus::Passed => "PASS",
+                    StepStatus::Failed => "FAIL",
+                    StepStatus::Skipped => "SKIP",
+                };
+                match &s.error {
+                    Some(error) => format!("{} {}: {}", marker, s.description, error),
+                    None => format!("{} {}", marker, s.description),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(call_result(
+            format!(
+                "Scenario \"{}\" {} in {}ms\n{}",
+                report.scenario,
+                if report.passed { "passed" } else { "failed" },
+                report.duration_ms,
+                summary
+            ),
+            serde_json::to_value(&report)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_yaml_dsl() {
+        let scenario = Scenario::from_yaml(
+            r##"
+name: Login
+steps:
+  - goto: /login
+  - fill: { selector: "#email", value: "jane@example.com" }
+  - click: "button[type=submit]"
+  - expect_text: Welcome back
+  - expect_text: { selector: h1, text: Dashboard }
+  - wait: 500
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(scenario.timeout_secs, 10);
+        assert_eq!(scenario.steps.len(), 6);
+        assert!(matches!(&scenario.steps[0], Step::Goto(path) if path == "/login"));
+        assert!(matches!(
+            &scenario.steps[4],
+            Step::ExpectText(TextExpectation::Element { selector, .. }) if selector == "h1"
+        ));
+        assert!(Scenario::from_yaml("name: Empty\nsteps: []").is_err());
+    }
+}
@@ -0,0 +1,669 @@
+use crate::cicd::risk::validate_git_ref;
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Files that affect every package of an ecosystem
+const CARGO_GLOBAL: &[&str] = &[
+    "Cargo.lock",
+    "Cargo.toml",
+    "rust-toolchain",
+    "rust-toolchain.toml",
+    ".cargo/config.toml",
+];
+const NPM_GLOBAL: &[&str] = &[
+    "package.json",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "pnpm-workspace.yaml",
+    "tsconfig.base.json",
+];
+
+/// Directories never searched for workspace packages
+const SKIP_DIRS: &[&str] = &["node_modules", "target", ".git", "dist", "build"];
+
+/// Package ecosystem
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+}
+
+/// A package in the monorepo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Package {
+    pub name: String,
+    pub ecosystem: Ecosystem,
+    /// Directory relative to the repository root; empty for the root package
+    pub dir: String,
+    /// Workspace packages it depends on
+    pub dependencies: Vec<String>,
+    /// Has a binary target, a Dockerfile, or a start/deploy script
+    pub deployable: bool,
+    /// npm scripts defined by the package
+    #[serde(default)]
+    pub scripts: Vec<String>,
+}
+
+/// Why a package is affected
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ImpactReason {
+    /// Its own files changed
+    Changed { files: Vec<String> },
+    /// A workspace dependency is affected
+    Dependency { via: String },
+    /// A lockfile or workspace manifest changed
+    Global { file: String },
+}
+
+/// An affected package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AffectedPackage {
+    pub name: String,
+    pub ecosystem: Ecosystem,
+    pub dir: String,
+    pub deployable: bool,
+    #[serde(flatten)]
+    pub reason: ImpactReason,
+}
+
+/// What a change set requires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactReport {
+    pub changed_files: Vec<String>,
+    pub affected: Vec<AffectedPackage>,
+    /// Commands building affected packages
+    pub builds: Vec<String>,
+    /// Commands testing affected packages
+    pub tests: Vec<String>,
+    /// Deployable packages to redeploy
+    pub deployments: Vec<String>,
+    /// Changed files outside any package
+    pub unowned_files: Vec<String>,
+    /// Documentation-only files with no build impact
+    pub ignored_files: Vec<String>,
+}
+
+/// npm client used for workspace commands
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NpmClient {
+    #[default]
+    Npm,
+    Yarn,
+    Pnpm,
+}
+
+/// Packages of a monorepo and their internal dependencies
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceGraph {
+    pub packages: Vec<Package>,
+    pub npm_client: NpmClient,
+}
+
+fn relative_dir(root: &Path, dir: &Path) -> String {
+    dir.strip_prefix(root)
+        .unwrap_or(dir)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn is_ignored(file: &str) -> bool {
+    file.ends_with(".md") || file.starts_with("docs/") || file.contains("/docs/")
+}
+
+/// Packages from `cargo metadata --no-deps` output
+pub fn cargo_packages(metadata: &Value) -> Vec<Package> {
+    let root = PathBuf::from(metadata["workspace_root"].as_str().unwrap_or_default());
+    let packages = metadata["packages"].as_array().cloned().unwrap_or_default();
+    let names: BTreeSet<&str> = packages.iter().filter_map(|p| p["name"].as_str()).collect();
+    packages
+        .iter()
+        .filter_map(|package| {
+            let manifest = PathBuf::from(package["manifest_path"].as_str()?);
+            let dir = manifest.parent()?;
+            let dependencies = package["dependencies"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                // Only path dependencies are workspace members
+                .filter(|d| d.get("path").is_some_and(|p| !p.is_null()))
+                .filter_map(|d| d["name"].as_str())
+                .filter(|d| names.contains(d))
+                .map(str::to_string)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            let has_bin = package["targets"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|t| {
+                    t["kind"]
+                        .as_array()
+                        .is_some_and(|k| k.iter().any(|k| k == "bin"))
+                });
+            Some(Package {
+                name: package["name"].as_str()?.to_string(),
+                ecosystem: Ecosystem::Cargo,
+                dir: relative_dir(&root, dir),
+                dependencies,
+                deployable: has_bin || dir.join("Dockerfile").exists(),
+                scripts: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// Directories matching a workspace pattern such as `packages/*` or `apps/**`
+fn expand_pattern(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut current = vec![root.to_path_buf()];
+    for segment in pattern.trim_start_matches("./").split('/') {
+        let mut next = Vec::new();
+        for dir in current {
+            match segment {
+                "" | "." => next.push(dir),
+                "**" => {
+                    // Any depth, bounded to keep large trees cheap
+                    let mut queue = VecDeque::from([(dir, 0)]);
+                    while let Some((dir, depth)) = queue.pop_front() {
+                        next.push(dir.clone());
+                        if depth < 5 {
+                            queue.extend(subdirs(&dir).into_iter().map(|d| (d, depth + 1)));
+                        }
+                    }
+                }
+                segment if segment.contains('*') => {
+                    let (prefix, suffix) = segment.split_once('*').unwrap_or((segment, ""));
+                    next.extend(subdirs(&dir).into_iter().filter(|d| {
+                        d.file_name()
+                            .map(|n| n.to_string_lossy())
+                            .is_some_and(|n| n.starts_with(prefix) && n.ends_with(suffix))
+                    }));
+                }
+                segment => next.push(dir.join(segment)),
+            }
+        }
+        current = next;
+    }
+    current
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| !SKIP_DIRS.contains(&e.file_name().to_string_lossy().as_ref()))
+        .map(|e| e.path())
+        .collect()
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+}
+
+/// Packages declared by npm/yarn `workspaces` or `pnpm-workspace.yaml`
+pub fn npm_packages(root: &Path) -> Result<Vec<Package>> {
+    let manifest = read_json(&root.join("package.json"));
+    let mut patterns: Vec<String> = match manifest.as_ref().map(|m| &m["workspaces"]) {
+        Some(Value::Array(list)) => list
+            .iter()
+            .filter_map(|p| p.as_str())
+            .map(str::to_string)
+            .collect(),
+        Some(Value::Object(config)) => config
+            .get("packages")
+            .and_then(|p| p.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p.as_str())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    if let Ok(source) = std::fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        let config: serde_yaml::Value = serde_yaml::from_str(&source).map_err(|e| {
+            Error::parsing_with_format(format!("Invalid pnpm-workspace.yaml: {}", e), "yaml", None)
+        })?;
+        patterns.extend(
+            config["packages"]
+                .as_sequence()
+                .into_iter()
+                .flatten()
+                .filter_map(|p| p.as_str())
+                .map(str::to_string),
+        );
+    }
+
+    let excluded: Vec<PathBuf> = patterns
+        .iter()
+        .filter_map(|p| p.strip_prefix('!'))
+        .flat_map(|p| expand_pattern(root, p))
+        .collect();
+    let mut dirs: BTreeSet<PathBuf> = patterns
+        .iter()
+        .filter(|p| !p.starts_with('!'))
+        .flat_map(|p| expand_pattern(root, p))
+        .filter(|d| d.join("package.json").is_file() && !excluded.contains(d))
+        .collect();
+    if manifest.is_some() {
+        dirs.insert(root.to_path_buf());
+    }
+
+    let manifests: Vec<(PathBuf, Value)> = dirs
+        .into_iter()
+        .filter_map(|dir| read_json(&dir.join("package.json")).map(|m| (dir, m)))
+        .filter(|(_, m)| m["name"].is_string())
+        .collect();
+    let names: BTreeSet<&str> = manifests
+        .iter()
+        .filter_map(|(_, m)| m["name"].as_str())
+        .collect();
+    Ok(manifests
+        .iter()
+        .map(|(dir, manifest)| {
+            let dependencies = ["dependencies", "devDependencies", "peerDependencies"]
+                .iter()
+                .filter_map(|field| manifest[*field].as_object())
+                .flat_map(|deps| deps.keys())
+                .filter(|d| names.contains(d.as_str()))
+                .cloned()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            let scripts: Vec<String> = manifest["scripts"]
+                .as_object()
+                .map(|s| s.keys().cloned().collect())
+                .unwrap_or_default();
+            Package {
+                name: manifest["name"].as_str().unwrap_or_default().to_string(),
+                ecosystem: Ecosystem::Npm,
+                dir: relative_dir(root, dir),
+                dependencies,
+                deployable: dir.join("Dockerfile").exists()
+                    || scripts.iter().any(|s| s == "start" || s == "deploy"),
+                scripts,
+            }
+        })
+        .collect())
+}
+
+impl WorkspaceGraph {
+    /// Discover Cargo and npm workspace packages under `root`
+    pub async fn discover(root: &Path) -> Result<Self> {
+        let mut packages = Vec::new();
+        if root.join("Cargo.toml").is_file() {
+            let output = Command::new("cargo")
+                .args([
+                    "metadata",
+                    "--format-version",
+                    "1",
+                    "--no-deps",
+                    "--offline",
+                ])
+                .arg("--manifest-path")
+                .arg(root.join("Cargo.toml"))
+                .output()
+                .await
+                .map_err(|e| Error::service(format!("Failed to run cargo metadata: {}", e)))?;
+            if !output.status.success() {
+                return Err(Error::service(format!(
+                    "cargo metadata failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            let metadata: Value = serde_json::from_slice(&output.stdout)
+                .map_err(|e| Error::parsing(format!("Invalid cargo metadata: {}", e)))?;
+            packages.extend(cargo_packages(&metadata));
+        }
+        let root_owned = root.to_path_buf();
+        packages.extend(
+            tokio::task::spawn_blocking(move || npm_packages(&root_owned))
+                .await
+                .map_err(|e| Error::internal(format!("Workspace scan failed: {}", e)))??,
+        );
+        let npm_client = if root.join("pnpm-lock.yaml").exists() {
+            NpmClient::Pnpm
+        } else if root.join("yarn.lock").exists() {
+            NpmClient::Yarn
+        } else {
+            NpmClient::Npm
+        };
+        Ok(Self {
+            packages,
+            npm_client,
+        })
+    }
+
+    /// Package owning a file: the one with the deepest directory containing it
+    pub fn owner(&self, file: &str) -> Option<&Package> {
+        self.packages
+            .iter()
+            .filter(|p| {
+                p.dir.is_empty()
+                    || file
+                        .strip_prefix(p.dir.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|p| p.dir.len())
+    }
+
+    /// Packages affected by a set of changed files
+    pub fn impact(&self, files: &[String]) -> ImpactReport {
+        let mut reasons: BTreeMap<&str, ImpactReason> = BTreeMap::new();
+        let mut unowned_files = Vec::new();
+        let mut ignored_files = Vec::new();
+
+        for file in files {
+            if is_ignored(file) {
+                ignored_files.push(file.clone());
+                continue;
+            }
+            let global = match file.as_str() {
+                f if CARGO_GLOBAL.contains(&f) => Some(Ecosystem::Cargo),
+                f if NPM_GLOBAL.contains(&f) => Some(Ecosystem::Npm),
+                _ => None,
+            };
+            if let Some(ecosystem) = global {
+                for package in self.packages.iter().filter(|p| p.ecosystem == ecosystem) {
+                    reasons
+                        .entry(&package.name)
+                        .or_insert(ImpactReason::Global { file: file.clone() });
+                }
+                continue;
+            }
+            match self.owner(file) {
+                Some(package) => match reasons.get_mut(package.name.as_str()) {
+                    Some(ImpactReason::Changed { files }) => files.push(file.clone()),
+                    _ => {
+                        reasons.insert(
+                            &package.name,
+                            ImpactReason::Changed {
+                                files: vec![file.clone()],
+                            },
+                        );
+                    }
+                },
+                None => unowned_files.push(file.clone()),
+            }
+        }
+
+        // Propagate to dependents breadth-first so `via` names the nearest affected dependency
+        let mut queue: VecDeque<&str> = reasons.keys().copied().collect();
+        while let Some(name) = queue.pop_front() {
+            for dependent in self
+                .packages
+                .iter()
+                .filter(|p| p.dependencies.iter().any(|d| d == name))
+            {
+                if !reasons.contains_key(dependent.name.as_str()) {
+                    reasons.insert(
+                        &dependent.name,
+                        ImpactReason::Dependency {
+                            via: name.to_string(),
+                        },
+                    );
+                    queue.push_back(&dependent.name);
+                }
+            }
+        }
+
+        let affected: Vec<AffectedPackage> = self
+            .packages
+            .iter()
+            .filter_map(|p| {
+                reasons.get(p.name.as_str()).map(|reason| AffectedPackage {
+                    name: p.name.clone(),
+                    ecosystem: p.ecosystem,
+                    dir: p.dir.clone(),
+                    deployable: p.deployable,
+                    reason: reason.clone(),
+                })
+            })
+            .collect();
+        let (builds, tests) = self.commands(&affected);
+        ImpactReport {
+            changed_files: files.to_vec(),
+            deployments: affected
+                .iter()
+                .filter(|a| a.deployable)
+                .map(|a| a.name.clone())
+                .collect(),
+            affected,
+            builds,
+            tests,
+            unowned_files,
+            ignored_files,
+        }
+    }
+
+    fn commands(&self, affected: &[AffectedPackage]) -> (Vec<String>, Vec<String>) {
+        let mut builds = Vec::new();
+        let mut tests = Vec::new();
+        let crates: Vec<String> = affected
+            .iter()
+            .filter(|a| a.ecosystem == Ecosystem::Cargo)
+            .map(|a| format!("-p {}", a.name))
+            .collect();
+        if !crates.is_empty() {
+            builds.push(format!("cargo build {}", crates.join(" ")));
+            tests.push(format!("cargo test {}", crates.join(" ")));
+        }
+        for package in affected.iter().filter(|a| a.ecosystem == Ecosystem::Npm) {
+            let scripts = self
+                .packages
+                .iter()
+                .find(|p| p.name == package.name && p.ecosystem == Ecosystem::Npm)
+                .map(|p| p.scripts.as_slice())
+                .unwrap_or_default();
+            let run = |script: &str| match self.npm_client {
+                NpmClient::Npm => format!("npm run {} --workspace={}", script, package.name),
+                NpmClient::Yarn => format!("yarn workspace {} run {}", package.name, script),
+                NpmClient::Pnpm => format!("pnpm --filter {} run {}", package.name, script),
+            };
+            if scripts.iter().any(|s| s == "build") {
+                builds.push(run("build"));
+            }
+            if scripts.iter().any(|s| s == "test") {
+                tests.push(run("test"));
+            }
+        }
+        (builds, tests)
+    }
+}
+
+/// Files changed between two refs
+pub async fn changed_files(repo: &Path, base: &str, head: &str) -> Result<Vec<String>> {
+    validate_git_ref("base", base)?;
+    validate_git_ref("head", head)?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["diff", "--name-only", &format!("{}...{}", base, head), "--"])
+        .output()
+        .await
+        .map_err(|e| Error::service(format!("Failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(Error::service(format!(
+            "git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Change impact analysis for monorepos
+pub struct ImpactAnalyzer;
+
+impl ImpactAnalyzer {
+    /// Create an analyzer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Get tool definitions for impact analysis
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "change_impact",
+            "Map changed files in a git diff to affected Cargo and npm workspace packages (including dependents) and list the builds, tests and deployments that need to run",
+            "development",
+            json!({
+                "type": "object",
+                "properties": {
+                    "repo_path": {"type": "string", "default": "."},
+                    "base": {"type": "string", "default": "origin/main"},
+                    "head": {"type": "string", "default": "HEAD"},
+                    "files": {"type": "array", "items": {"type": "string"}, "description": "Changed files, instead of diffing base...head"}
+                }
+            }),
+            None,
+        )]
+    }
+
+    /// Execute an impact analysis tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        if name != "change_impact" {
+            return Err(Error::not_found_with_resource(
+                "Tool not found",
+                "impact_tool",
+                name,
+            ));
+        }
+        let string = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let repo = Path::new(string("repo_path").unwrap_or("."));
+        let files: Vec<String> = match parameters.get("files") {
+            Some(files) => serde_json::from_value(files.clone()).map_err(|e| {
+                Error::validation_with_field(format!("Invalid files: {}", e), "files")
+            })?,
+            None => {
+                changed_files(
+                    repo,
+                    string("base").unwrap_or("origin/main"),
+                    string("head").unwrap_or("HEAD"),
+                )
+                .await?
+            }
+        };
+        let graph = WorkspaceGraph::discover(repo).await?;
+        let report = graph.impact(&files);
+
+        let mut text = format!(
+            "{} changed files affect {} of {} packages",
+            report.changed_files.len(),
+            report.affected.len(),
+            graph.packages.len()
+        );
+        for package in &report.affected {
+            let why = match &package.reason {
+                ImpactReason::Changed { files } => format!("{} files changed", files.len()),
+                ImpactReason::Dependency { via } => format!("depends on {}", via),
+                ImpactReason::Global { file } => format!("{} changed", file),
+            };
+            text.push_str(&format!("\n- {} ({})", package.name, why));
+        }
+        for (title, list) in [
+            ("Builds", &report.builds),
+            ("Tests", &report.tests),
+            ("Deployments", &report.deployments),
+            ("Files outside any package", &report.unowned_files),
+        ] {
+            if !list.is_empty() {
+                text.push_str(&format!("\n\n{}:\n  {}", title, list.join("\n  ")));
+            }
+        }
+        Ok(call_result(
+            text,
+            json!({ "report": report, "packages": graph.packages }),
+        ))
+    }
+}
+
+impl Default for ImpactAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn propagates_changes_to_dependents_across_workspaces() {
+        let metadata = json!({
+            "workspace_root": "/repo",
+            "packages": [
+                {"name": "core", "manifest_path": "/repo/crates/core/Cargo.toml", "dependencies": [{"name": "serde", "path": null}], "targets": [{"kind": ["lib"]}]},
+                {"name": "api", "manifest_path": "/repo/crates/api/Cargo.toml", "dependencies": [{"name": "core", "path": "/repo/crates/core"}], "targets": [{"kind": ["bin"]}]},
+                {"name": "cli", "manifest_path": "/repo/crates/cli/Cargo.toml", "dependencies": [], "targets": [{"kind": ["bin"]}]}
+            ]
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("package.json"),
+            r#"{"name": "root", "private": true, "workspaces": ["web/*"]}"#,
+        )
+        .unwrap();
+        for (name, manifest) in [
+            (
+                "ui",
+                r#"{"name": "@acme/ui", "scripts": {"build": "tsc", "test": "vitest"}}"#,
+            ),
+            (
+                "app",
+                r#"{"name": "@acme/app", "dependencies": {"@acme/ui": "*"}, "scripts": {"start": "next start"}}"#,
+            ),
+        ] {
+            std::fs::create_dir_all(root.join("web").join(name)).unwrap();
+            std::fs::write(root.join("web").join(name).join("package.json"), manifest).unwrap();
+        }
+
+        let mut packages = cargo_packages(&metadata);
+        packages.extend(npm_packages(root).unwrap());
+        let graph = WorkspaceGraph {
+            packages,
+            npm_client: NpmClient::Npm,
+        };
+        let report = graph.impact(&[
+            "crates/core/src/lib.rs".to_string(),
+            "web/ui/src/Button.tsx".to_string(),
+            "crates/cli/README.md".to_string(),
+        ]);
+        let affected: BTreeMap<&str, &ImpactReason> = report
+            .affected
+            .iter()
+            .map(|a| (a.name.as_str(), &a.reason))
+            .collect();
+        assert_eq!(
+            affected.keys().copied().collect::<Vec<_>>(),
+            vec!["@acme/app", "@acme/ui", "api", "core"]
+        );
+        assert_eq!(
+            affected["api"],
+            &ImpactReason::Dependency {
+                via: "core".to_string()
+            }
+        );
+        assert_eq!(report.builds[0], "cargo build -p core -p api");
+        assert!(report
+            .tests
+            .contains(&"npm run test --workspace=@acme/ui".to_string()));
+        assert_eq!(report.deployments, vec!["api", "@acme/app"]);
+        assert_eq!(report.ignored_files, vec!["crates/cli/README.md"]);
+    }
+}
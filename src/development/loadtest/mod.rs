@@ -0,0 +1,1115 @@
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ProgressInfo, ToolDefinition};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::Command as TokioCommand;
+use tokio::sync::{mpsc, RwLock};
+
+/// Runs kept in the history store
+const MAX_HISTORY: usize = 200;
+
+/// Points per chart series
+const CHART_POINTS: usize = 60;
+
+/// Relative change in latency or throughput treated as a regression
+const REGRESSION_THRESHOLD: f64 = 0.10;
+
+/// Load test scenario loaded from YAML
+///
+/// ```yaml
+/// name: checkout
+/// tool: k6
+/// target:
+///   url: https://shop.example.com/api/cart
+///   method: POST
+///   headers: { Content-Type: application/json }
+///   body: '{"sku": "A-1"}'
+/// start_rps: 5
+/// stages:
+///   - { duration_secs: 30, rps: 50 }
+///   - { duration_secs: 60, rps: 50 }
+/// assertions:
+///   - { metric: p95, op: "<", value: 300 }
+///   - { metric: error_rate, op: "<", value: 0.01 }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadScenario {
+    pub name: String,
+    #[serde(default)]
+    pub tool: LoadTool,
+    pub target: Target,
+    /// Request rate at the start of the first stage
+    #[serde(default = "default_start_rps")]
+    pub start_rps: u32,
+    /// Each stage ramps linearly from the previous rate to its own
+    pub stages: Vec<Stage>,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    /// Upper bound on concurrent virtual users (k6) or workers (vegeta)
+    #[serde(default = "default_max_vus")]
+    pub max_vus: u32,
+}
+
+fn default_start_rps() -> u32 {
+    1
+}
+
+fn default_max_vus() -> u32 {
+    100
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+impl LoadScenario {
+    /// Parse a scenario from YAML
+    pub fn from_yaml(source: &str) -> Result<Self> {
+        let scenario: Self = serde_yaml::from_str(source).map_err(|e| {
+            Error::parsing_with_format(format!("Invalid scenario: {}", e), "yaml", None)
+        })?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.stages.is_empty() {
+            return Err(Error::validation_with_field(
+                "Scenario has no stages",
+                "stages",
+            ));
+        }
+        if self.stages.iter().any(|s| s.duration_secs == 0) {
+            return Err(Error::validation_with_field(
+                "Stage durations must be positive",
+                "stages",
+            ));
+        }
+        let url = reqwest::Url::parse(&self.target.url)
+            .map_err(|e| Error::validation_with_field(format!("Invalid URL: {}", e), "url"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(Error::validation_with_field(
+                "Target URL must be http or https",
+                "url",
+            ));
+        }
+        if !self.target.method.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(Error::validation_with_field(
+                "Invalid HTTP method",
+                "method",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Planned duration in seconds
+    pub fn duration_secs(&self) -> u64 {
+        self.stages.iter().map(|s| s.duration_secs).sum()
+    }
+}
+
+/// Load generator
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadTool {
+    #[default]
+    K6,
+    Vegeta,
+}
+
+/// Request sent on every iteration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Target {
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+/// A segment of the rate profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stage {
+    pub duration_secs: u64,
+    /// Rate reached at the end of the stage
+    pub rps: u32,
+}
+
+/// Metric an assertion checks
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    P50,
+    P90,
+    P95,
+    P99,
+    Mean,
+    Max,
+    ErrorRate,
+    Rps,
+}
+
+/// Pass/fail criterion on the run's statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assertion {
+    pub metric: Metric,
+    /// One of `<`, `<=`, `>`, `>=`
+    pub op: String,
+    pub value: f64,
+}
+
+/// Outcome of an assertion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssertionResult {
+    pub assertion: Assertion,
+    pub actual: f64,
+    pub passed: bool,
+}
+
+/// One completed request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// Milliseconds since the first request
+    pub offset_ms: f64,
+    pub latency_ms: f64,
+    pub ok: bool,
+    /// HTTP status; 0 when the request failed without a response
+    pub status: u16,
+}
+
+/// Latency distribution in milliseconds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Latency {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+/// Aggregate statistics for a run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoadStats {
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    /// Achieved request rate
+    pub rps: f64,
+    pub latency_ms: Latency,
+    pub status_codes: BTreeMap<String, u64>,
+}
+
+impl LoadStats {
+    fn metric(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::P50 => self.latency_ms.p50,
+            Metric::P90 => self.latency_ms.p90,
+            Metric::P95 => self.latency_ms.p95,
+            Metric::P99 => self.latency_ms.p99,
+            Metric::Mean => self.latency_ms.mean,
+            Metric::Max => self.latency_ms.max,
+            Metric::ErrorRate => self.error_rate,
+            Metric::Rps => self.rps,
+        }
+    }
+}
+
+/// Per-second throughput and latency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelinePoint {
+    pub second: u64,
+    pub requests: u64,
+    pub errors: u64,
+    pub p95_ms: f64,
+}
+
+/// A completed load test run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadTestRun {
+    pub id: String,
+    pub scenario: LoadScenario,
+    pub started_at: DateTime<Utc>,
+    pub duration_secs: f64,
+    pub stats: LoadStats,
+    pub timeline: Vec<TimelinePoint>,
+    pub assertions: Vec<AssertionResult>,
+    pub passed: bool,
+}
+
+/// Change of one metric between two runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub metric: Metric,
+    pub baseline: f64,
+    pub current: f64,
+    /// Relative change; `None` when the baseline is zero
+    pub change: Option<f64>,
+    pub regression: bool,
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Statistics and per-second timeline from raw samples
+pub fn summarize(samples: &[Sample]) -> (LoadStats, Vec<TimelinePoint>) {
+    if samples.is_empty() {
+        return (LoadStats::default(), Vec::new());
+    }
+    let mut latencies: Vec<f64> = samples.iter().map(|s| s.latency_ms).collect();
+    latencies.sort_by(f64::total_cmp);
+    let errors = samples.iter().filter(|s| !s.ok).count() as u64;
+    let span_secs = samples
+        .iter()
+        .map(|s| s.offset_ms)
+        .fold(0.0, f64::max)
+        .max(1000.0)
+        / 1000.0;
+
+    let mut status_codes = BTreeMap::new();
+    let mut seconds: BTreeMap<u64, Vec<&Sample>> = BTreeMap::new();
+    for sample in samples {
+        let code = match sample.status {
+            0 => "error".to_string(),
+            status => status.to_string(),
+        };
+        *status_codes.entry(code).or_insert(0) += 1;
+        seconds
+            .entry((sample.offset_ms / 1000.0) as u64)
+            .or_default()
+            .push(sample);
+    }
+    let timeline = seconds
+        .into_iter()
+        .map(|(second, bucket)| {
+            let mut latencies: Vec<f64> = bucket.iter().map(|s| s.latency_ms).collect();
+            latencies.sort_by(f64::total_cmp);
+            TimelinePoint {
+                second,
+                requests: bucket.len() as u64,
+                errors: bucket.iter().filter(|s| !s.ok).count() as u64,
+                p95_ms: percentile(&latencies, 95.0),
+            }
+        })
+        .collect();
+
+    let stats = LoadStats {
+        requests: samples.len() as u64,
+        errors,
+        error_rate: errors as f64 / samples.len() as f64,
+        rps: samples.len() as f64 / span_secs,
+        latency_ms: Latency {
+            min: latencies[0],
+            mean: latencies.iter().sum::<f64>() / latencies.len() as f64,
+            p50: percentile(&latencies, 50.0),
+            p90: percentile(&latencies, 90.0),
+            p95: percentile(&latencies, 95.0),
+            p99: percentile(&latencies, 99.0),
+            max: latencies[latencies.len() - 1],
+        },
+        status_codes,
+    };
+    (stats, timeline)
+}
+
+/// Evaluate assertions against run statistics
+pub fn check_assertions(
+    assertions: &[Assertion],
+    stats: &LoadStats,
+) -> Result<Vec<AssertionResult>> {
+    assertions
+        .iter()
+        .map(|assertion| {
+            let actual = stats.metric(assertion.metric);
+            let passed = match assertion.op.as_str() {
+                "<" => actual < assertion.value,
+                "<=" => actual <= assertion.value,
+                ">" => actual > assertion.value,
+                ">=" => actual >= assertion.value,
+                op => {
+                    return Err(Error::validation_with_field(
+                        format!("Unsupported assertion operator {}", op),
+                        "op",
+                    ))
+                }
+            };
+            Ok(AssertionResult {
+                assertion: assertion.clone(),
+                actual,
+                passed,
+            })
+        })
+        .collect()
+}
+
+/// Compare a run with a baseline; latency and error increases or throughput drops regress
+pub fn compare(baseline: &LoadStats, current: &LoadStats) -> Vec<MetricDelta> {
+    [
+        Metric::P50,
+        Metric::P95,
+        Metric::P99,
+        Metric::Mean,
+        Metric::ErrorRate,
+        Metric::Rps,
+    ]
+    .into_iter()
+    .map(|metric| {
+        let (before, after) = (baseline.metric(metric), current.metric(metric));
+        let change = (before != 0.0).then(|| (after - before) / before);
+        let regression = match metric {
+            // Error rates are compared absolutely; a jump from 0 matters
+            Metric::ErrorRate => after - before > 0.01,
+            Metric::Rps => change.is_some_and(|c| c < -REGRESSION_THRESHOLD),
+            _ => change.is_some_and(|c| c > REGRESSION_THRESHOLD),
+        };
+        MetricDelta {
+            metric,
+            baseline: before,
+            current: after,
+            change,
+            regression,
+        }
+    })
+    .collect()
+}
+
+/// Mermaid charts of latency and throughput over time
+pub fn charts(name: &str, timeline: &[TimelinePoint]) -> Vec<String> {
+    if timeline.is_empty() {
+        return Vec::new();
+    }
+    // Downsample long runs by averaging consecutive seconds
+    let width = timeline.len().div_ceil(CHART_POINTS);
+    let buckets: Vec<&[TimelinePoint]> = timeline.chunks(width).collect();
+    let axis = buckets
+        .iter()
+        .map(|b| b[0].second.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let series = |f: &dyn Fn(&TimelinePoint) -> f64| {
+        buckets
+            .iter()
+            .map(|b| format!("{:.1}", b.iter().map(f).sum::<f64>() / b.len() as f64))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    vec![
+        format!(
+            "xychart-beta\n  title \"{} p95 latency (ms)\"\n  x-axis \"second\" [{}]\n  y-axis \"ms\"\n  line [{}]\n",
+            name,
+            axis,
+            series(&|p| p.p95_ms)
+        ),
+        format!(
+            "xychart-beta\n  title \"{} requests and errors per second\"\n  x-axis \"second\" [{}]\n  y-axis \"requests\"\n  bar [{}]\n  line [{}]\n",
+            name,
+            axis,
+            series(&|p| p.requests as f64),
+            series(&|p| p.errors as f64)
+        ),
+    ]
+}
+
+/// k6 script implementing the scenario with a ramping arrival rate
+pub fn k6_script(scenario: &LoadScenario) -> String {
+    let stages: Vec<Value> = scenario
+        .stages
+        .iter()
+        .map(|s| json!({ "target": s.rps, "duration": format!("{}s", s.duration_secs) }))
+        .collect();
+    let peak = scenario
+        .stages
+        .iter()
+        .map(|s| s.rps)
+        .max()
+        .unwrap_or(1)
+        .max(scenario.start_rps);
+    let options = json!({
+        "discardResponseBodies": true,
+        "scenarios": {
+            "load": {
+                "executor": "ramping-arrival-rate",
+                "startRate": scenario.start_rps,
+                "timeUnit": "1s",
+                "preAllocatedVUs": peak.min(scenario.max_vus).max(1),
+                "maxVUs": scenario.max_vus.max(1),
+                "stages": stages,
+            }
+        }
+    });
+    // Values are embedded as JSON literals so nothing in the scenario is evaluated as code
+    format!(
+        "import http from 'k6/http';\n\nexport const options = {};\n\nconst target = {};\n\nexport default function () {{\n  http.request(target.method, target.url, target.body, {{ headers: target.headers }});\n}}\n",
+        options,
+        json!({
+            "method": scenario.target.method,
+            "url": scenario.target.url,
+            "body": scenario.target.body,
+            "headers": scenario.target.headers,
+        })
+    )
+}
+
+/// Parse `k6 run --out json=...` points into samples
+pub fn parse_k6_points(output: &str) -> Vec<Sample> {
+    let mut points = Vec::new();
+    for line in output.lines() {
+        let Ok(point) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if point["type"] != "Point" || point["metric"] != "http_req_duration" {
+            continue;
+        }
+        let data = &point["data"];
+        let (Some(time), Some(latency)) = (
+            data["time"]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok()),
+            data["value"].as_f64(),
+        ) else {
+            continue;
+        };
+        let tags = &data["tags"];
+        let status = tags["status"]
+            .as_str()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+        let ok = match tags["expected_response"].as_str() {
+            Some(expected) => expected == "true",
+            None => (200..400).contains(&status),
+        };
+        points.push((time.with_timezone(&Utc), latency, ok, status));
+    }
+    to_samples(points)
+}
+
+/// Parse `vegeta encode --to json` results into samples
+pub fn parse_vegeta_results(output: &str) -> Vec<Sample> {
+    let mut points = Vec::new();
+    for line in output.lines() {
+        let Ok(result) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let (Some(time), Some(latency_ns)) = (
+            result["timestamp"]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok()),
+            result["latency"].as_f64(),
+        ) else {
+            continue;
+        };
+        let status = result["code"].as_u64().unwrap_or(0) as u16;
+        let ok =
+            result["error"].as_str().unwrap_or_default().is_empty() && (200..400).contains(&status);
+        points.push((time.with_timezone(&Utc), latency_ns / 1e6, ok, status));
+    }
+    to_samples(points)
+}
+
+fn to_samples(points: Vec<(DateTime<Utc>, f64, bool, u16)>) -> Vec<Sample> {
+    let Some(start) = points.iter().map(|p| p.0).min() else {
+        return Vec::new();
+    };
+    points
+        .into_iter()
+        .map(|(time, latency_ms, ok, status)| Sample {
+            offset_ms: (time - start).num_microseconds().unwrap_or(0) as f64 / 1000.0,
+            latency_ms,
+            ok,
+            status,
+        })
+        .collect()
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LoadTestStore {
+    runs: Vec<LoadTestRun>,
+}
+
+/// Runs load tests through k6 or vegeta and keeps comparable results
+pub struct LoadTestRunner {
+    /// Where run history is persisted; `None` keeps it in memory
+    path: Option<PathBuf>,
+    store: RwLock<LoadTestStore>,
+}
+
+impl LoadTestRunner {
+    /// Open a runner, loading run history from `path` if it exists
+    pub async fn open(path: Option<PathBuf>) -> Result<Self> {
+        let store = match &path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse load test history {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => LoadTestStore::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read load test history: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => LoadTestStore::default(),
+        };
+        Ok(Self {
+            path,
+            store: RwLock::new(store),
+        })
+    }
+
+    async fn persist(&self, store: &LoadTestStore) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create load test history directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to write load test history: {}", e),
+                    temp.clone(),
+                )
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace load test history: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Run a command, reporting time-based progress until it exits
+    async fn run_tool(
+        program: &str,
+        args: &[String],
+        scenario: &LoadScenario,
+        progress: Option<&mpsc::UnboundedSender<ProgressInfo>>,
+        elapsed_before: u64,
+    ) -> Result<Vec<u8>> {
+        let child = TokioCommand::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                Error::service(format!(
+                    "Failed to start {} (is it installed?): {}",
+                    program, e
+                ))
+            })?;
+        let total = scenario.duration_secs();
+        let deadline = Duration::from_secs(total + 120);
+        let started = Instant::now();
+        let mut ticker = tokio::time::interval(Duration::from_secs(1));
+
+        let wait = child.wait_with_output();
+        tokio::pin!(wait);
+        let output = loop {
+            tokio::select! {
+                output = &mut wait => break output,
+                _ = ticker.tick() => {
+                    if started.elapsed() > deadline {
+                        return Err(Error::timeout(format!("{} did not finish in time", program)));
+                    }
+                    let elapsed = (elapsed_before + started.elapsed().as_secs()).min(total);
+                    let info = ProgressInfo {
+                        percentage: elapsed as f32 / total.max(1) as f32 * 100.0,
+                        message: Some(format!("{}: {}s of {}s", scenario.name, elapsed, total)),
+                        estimated_time_remaining: Some(Duration::from_secs(total - elapsed)),
+                    };
+                    match progress {
+                        Some(sender) => {
+                            let _ = sender.send(info);
+                        }
+                        None => tracing::debug!("{}", info.message.unwrap_or_default()),
+                    }
+                }
+            }
+        }
+        .map_err(|e| Error::service(format!("{} failed: {}", program, e)))?;
+
+        if !output.status.success() {
+            // k6 exits non-zero when thresholds fail; none are set, so this is a real failure
+            return Err(Error::service(format!(
+                "{} exited with {}: {}",
+                program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+
+    async fn run_k6(
+        scenario: &LoadScenario,
+        dir: &Path,
+        progress: Option<&mpsc::UnboundedSender<ProgressInfo>>,
+    ) -> Result<Vec<Sample>> {
+        let script = dir.join("script.js");
+        let points = dir.join("points.json");
+        tokio::fs::write(&script, k6_script(scenario))
+            .await
+            .map_err(|e| {
+                Error::io_with_path(format!("Failed to write k6 script: {}", e), script.clone())
+            })?;
+        let args = vec![
+            "run".to_string(),
+            "--quiet".to_string(),
+            "--no-summary".to_string(),
+            "--out".to_string(),
+            format!("json={}", points.display()),
+            script.display().to_string(),
+        ];
+        Self::run_tool("k6", &args, scenario, progress, 0).await?;
+        let output = tokio::fs::read_to_string(&points).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to read k6 results: {}", e), points.clone())
+        })?;
+        Ok(parse_k6_points(&output))
+    }
+
+    async fn run_vegeta(
+        scenario: &LoadScenario,
+        dir: &Path,
+        progress: Option<&mpsc::UnboundedSender<ProgressInfo>>,
+    ) -> Result<Vec<Sample>> {
+        use base64::Engine as _;
+
+        let targets = dir.join("targets.json");
+        let mut target = json!({
+            "method": scenario.target.method,
+            "url": scenario.target.url,
+            "header": scenario.target.headers.iter()
+                .map(|(k, v)| (k.clone(), json!([v])))
+                .collect::<serde_json::Map<_, _>>(),
+        });
+        if let Some(body) = &scenario.target.body {
+            target["body"] = json!(base64::engine::general_purpose::STANDARD.encode(body));
+        }
+        tokio::fs::write(&targets, target.to_string())
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to write vegeta targets: {}", e),
+                    targets.clone(),
+                )
+            })?;
+
+        // vegeta attacks at a constant rate, so each stage runs at its midpoint rate
+        let mut results = String::new();
+        let mut previous = scenario.start_rps;
+        let mut elapsed = 0;
+        for (index, stage) in scenario.stages.iter().enumerate() {
+            let rate = ((previous + stage.rps) / 2).max(1);
+            previous = stage.rps;
+            let output = dir.join(format!("stage-{}.bin", index));
+            let args = vec![
+                "attack".to_string(),
+                "-format=json".to_string(),
+                format!("-targets={}", targets.display()),
+                format!("-rate={}/1s", rate),
+                format!("-duration={}s", stage.duration_secs),
+                format!("-max-workers={}", scenario.max_vus.max(1)),
+                format!("-output={}", output.display()),
+            ];
+            Self::run_tool("vegeta", &args, scenario, progress, elapsed).await?;
+            elapsed += stage.duration_secs;
+
+            let encoded = Self::run_tool(
+                "vegeta",
+                &[
+                    "encode".to_string(),
+                    "-to=json".to_string(),
+                    output.display().to_string(),
+                ],
+                scenario,
+                None,
+                elapsed,
+            )
+            .await?;
+            results.push_str(&String::from_utf8_lossy(&encoded));
+        }
+        Ok(parse_vegeta_results(&results))
+    }
+
+    /// Run a scenario, streaming progress to `progress` when given
+    pub async fn run(
+        &self,
+        scenario: LoadScenario,
+        progress: Option<mpsc::UnboundedSender<ProgressInfo>>,
+    ) -> Result<LoadTestRun> {
+        scenario.validate()?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let dir = std::env::temp_dir().join(format!("loadtest-{}", id));
+        tokio::fs::create_dir_all(&dir).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to create work directory: {}", e),
+                dir.clone(),
+            )
+        })?;
+
+        let started_at = Utc::now();
+        let started = Instant::now();
+        let samples = match scenario.tool {
+            LoadTool::K6 => Self::run_k6(&scenario, &dir, progress.as_ref()).await,
+            LoadTool::Vegeta => Self::run_vegeta(&scenario, &dir, progress.as_ref()).await,
+        };
+        if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+            tracing::warn!("Failed to remove {}: {}", dir.display(), e);
+        }
+        let samples = samples?;
+        if samples.is_empty() {
+            return Err(Error::service(format!(
+                "{:?} produced no results for {}",
+                scenario.tool, scenario.name
+            )));
+        }
+
+        let (stats, timeline) = summarize(&samples);
+        let assertions = check_assertions(&scenario.assertions, &stats)?;
+        let run = LoadTestRun {
+            id,
+            passed: assertions.iter().all(|a| a.passed),
+            scenario,
+            started_at,
+            duration_secs: started.elapsed().as_secs_f64(),
+            stats,
+            timeline,
+            assertions,
+        };
+        if let Some(sender) = &progress {
+            let _ = sender.send(ProgressInfo {
+                percentage: 100.0,
+                message: Some(format!("{} finished", run.scenario.name)),
+                estimated_time_remaining: None,
+            });
+        }
+
+        let mut store = self.store.write().await;
+        store.runs.push(run.clone());
+        let excess = store.runs.len().saturating_sub(MAX_HISTORY);
+        store.runs.drain(..excess);
+        self.persist(&store).await?;
+        Ok(run)
+    }
+
+    /// A stored run by id
+    pub async fn get(&self, id: &str) -> Result<LoadTestRun> {
+        self.store
+            .read()
+            .await
+            .runs
+            .iter()
+            .find(|r| r.id == id)
+            .cloned()
+            .ok_or_else(|| Error::not_found_with_resource("Load test run not found", "run", id))
+    }
+
+    /// Most recent run of a scenario before `before`
+    async fn previous(&self, scenario: &str, before: &str) -> Option<LoadTestRun> {
+        self.store
+            .read()
+            .await
+            .runs
+            .iter()
+            .rev()
+            .find(|r| r.scenario.name == scenario && r.id != before)
+            .cloned()
+    }
+
+    /// Runs, newest first
+    pub async fn history(&self, scenario: Option<&str>) -> Vec<LoadTestRun> {
+        self.store
+            .read()
+            .await
+            .runs
+            .iter()
+            .rev()
+            .filter(|r| scenario.is_none_or(|s| r.scenario.name == s))
+            .cloned()
+            .collect()
+    }
+
+    /// Get tool definitions for load testing
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "run_load_test",
+                "Run a k6 or vegeta load test (YAML scenario: target, RPS stages, assertions) and report latency percentiles, error rate, charts and the change from the previous run",
+                "development_loadtest",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "scenario": {"type": "string", "description": "Scenario definition in YAML"},
+                        "baseline_run_id": {"type": "string", "description": "Run to compare against (default: previous run of the scenario)"}
+                    },
+                    "required": ["scenario"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "load_test_history",
+                "List past load test runs",
+                "development_loadtest",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "scenario": {"type": "string"},
+                        "limit": {"type": "integer", "default": 20}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "compare_load_tests",
+                "Compare two load test runs and flag regressions",
+                "development_loadtest",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "baseline_run_id": {"type": "string"},
+                        "run_id": {"type": "string"}
+                    },
+                    "required": ["baseline_run_id", "run_id"]
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    fn describe_deltas(deltas: &[MetricDelta]) -> String {
+        deltas
+            .iter()
+            .map(|d| {
+                format!(
+                    "{:?}: {:.2} -> {:.2}{}{}",
+                    d.metric,
+                    d.baseline,
+                    d.current,
+                    d.change
+                        .map(|c| format!(" ({:+.1}%)", c * 100.0))
+                        .unwrap_or_default(),
+                    if d.regression { " REGRESSION" } else { "" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Execute a load testing tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let string = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        match name {
+            "run_load_test" => {
+                let source = string("scenario").ok_or_else(|| {
+                    Error::validation_with_field("scenario is required", "scenario")
+                })?;
+                let run = self.run(LoadScenario::from_yaml(source)?, None).await?;
+                let baseline = match string("baseline_run_id") {
+                    Some(id) => Some(self.get(id).await?),
+                    None => self.previous(&run.scenario.name, &run.id).await,
+                };
+                let deltas = baseline.as_ref().map(|b| compare(&b.stats, &run.stats));
+
+                let latency = &run.stats.latency_ms;
+                let mut text = format!(
+                    "Load test \"{}\" {}: {} requests at {:.1} rps, {:.2}% errors\nLatency ms: p50 {:.1}, p90 {:.1}, p95 {:.1}, p99 {:.1}, max {:.1}",
+                    run.scenario.name,
+                    if run.passed { "passed" } else { "failed" },
+                    run.stats.requests,
+                    run.stats.rps,
+                    run.stats.error_rate * 100.0,
+                    latency.p50,
+                    latency.p90,
+                    latency.p95,
+                    latency.p99,
+                    latency.max
+                );
+                for result in &run.assertions {
+                    text.push_str(&format!(
+                        "\n{} {:?} {} {} (actual {:.3})",
+                        if result.passed { "PASS" } else { "FAIL" },
+                        result.assertion.metric,
+                        result.assertion.op,
+                        result.assertion.value,
+                        result.actual
+                    ));
+                }
+                if let (Some(baseline), Some(deltas)) = (&baseline, &deltas) {
+                    text.push_str(&format!(
+                        "\n\nCompared with run {}:\n{}",
+                        baseline.id,
+                        Self::describe_deltas(deltas)
+                    ));
+                }
+                let charts = charts(&run.scenario.name, &run.timeline);
+                for chart in &charts {
+                    text.push_str(&format!("\n\n```mermaid\n{}```", chart));
+                }
+                Ok(call_result(
+                    text,
+                    json!({
+                        "run": run,
+                        "baseline_run_id": baseline.map(|b| b.id),
+                        "comparison": deltas,
+                        "charts": charts,
+                    }),
+                ))
+            }
+            "load_test_history" => {
+                let limit = parameters
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(20) as usize;
+                let runs: Vec<LoadTestRun> = self
+                    .history(string("scenario"))
+                    .await
+                    .into_iter()
+                    .take(limit)
+                    .collect();
+                let mut text = format!("{} runs", runs.len());
+                for run in &runs {
+                    text.push_str(&format!(
+                        "\n{} {} {} {:?}: {:.1} rps, p95 {:.1}ms, {:.2}% errors, {}",
+                        run.id,
+                        run.started_at.format("%Y-%m-%d %H:%M"),
+                        run.scenario.name,
+                        run.scenario.tool,
+                        run.stats.rps,
+                        run.stats.latency_ms.p95,
+                        run.stats.error_rate * 100.0,
+                        if run.passed { "passed" } else { "failed" }
+                    ));
+                }
+                let summaries: Vec<Value> = runs
+                    .iter()
+                    .map(|r| {
+                        json!({
+                            "id": r.id,
+                            "scenario": r.scenario.name,
+                            "tool": r.scenario.tool,
+                            "started_at": r.started_at,
+                            "stats": r.stats,
+                            "passed": r.passed,
+                        })
+                    })
+                    .collect();
+                Ok(call_result(text, json!({ "runs": summaries })))
+            }
+            "compare_load_tests" => {
+                let baseline = self
+                    .get(string("baseline_run_id").ok_or_else(|| {
+                        Error::validation_with_field(
+                            "baseline_run_id is required",
+                            "baseline_run_id",
+                        )
+                    })?)
+                    .await?;
+                let run = self
+                    .get(string("run_id").ok_or_else(|| {
+                        Error::validation_with_field("run_id is required", "run_id")
+                    })?)
+                    .await?;
+                let deltas = compare(&baseline.stats, &run.stats);
+                let regressions = deltas.iter().filter(|d| d.regression).count();
+                Ok(call_result(
+                    format!(
+                        "{} vs {}: {} regressions\n{}",
+                        run.id,
+                        baseline.id,
+                        regressions,
+                        Self::describe_deltas(&deltas)
+                    ),
+                    json!({ "comparison": deltas, "regressions": regressions }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "loadtest_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_runs_and_flags_regressions() {
+        let scenario = LoadScenario::from_yaml(
+            r#"
+name: checkout
+target: { url: "https://shop.example.com/cart", method: POST, body: '{"sku": "A"}' }
+stages: [{ duration_secs: 10, rps: 20 }]
+assertions:
+  - { metric: p95, op: "<", value: 100 }
+  - { metric: error_rate, op: "<=", value: 0.1 }
+"#,
+        )
+        .unwrap();
+        assert!(k6_script(&scenario).contains(r#""url":"https://shop.example.com/cart""#));
+
+        let results: String = (0..20)
+            .map(|i| {
+                format!(
+                    "{{\"timestamp\":\"2026-01-01T00:00:{:02}.000Z\",\"latency\":{},\"code\":{},\"error\":\"\"}}\n",
+                    i / 10,
+                    (i + 1) * 5_000_000,
+                    if i == 19 { 500 } else { 200 }
+                )
+            })
+            .collect();
+        let samples = parse_vegeta_results(&results);
+        let (stats, timeline) = summarize(&samples);
+        assert_eq!(stats.requests, 20);
+        assert_eq!(stats.latency_ms.p50, 50.0);
+        assert_eq!(stats.latency_ms.p95, 95.0);
+        assert_eq!(stats.error_rate, 0.05);
+        assert_eq!(timeline.len(), 2);
+
+        let assertions = check_assertions(&scenario.assertions, &stats).unwrap();
+        assert!(assertions.iter().all(|a| a.passed));
+
+        let mut slower = stats.clone();
+        slower.latency_ms.p95 = 120.0;
+        let deltas = compare(&stats, &slower);
+        let regressed: Vec<Metric> = deltas
+            .iter()
+            .filter(|d| d.regression)
+            .map(|d| d.metric)
+            .collect();
+        assert_eq!(regressed, vec![Metric::P95]);
+        assert!(charts("checkout", &timeline)[0].contains("line [50.0, 100.0]"));
+    }
+}
@@ -1,2 +1,8 @@
+/// Browser end-to-end testing module
+pub mod e2e;
 /// Flutter development module
 pub mod flutter;
+/// Change impact analysis module
+pub mod impact;
+/// Load testing module
+pub mod loadtest;
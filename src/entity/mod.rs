@@ -0,0 +1,960 @@
+/// Entity resolution across modules
+///
+/// Objects seen by different modules — Kubernetes workloads, the container
+/// images they run, the repositories those images are built from, Grafana
+/// dashboards and firing alerts — are linked to the service they belong to.
+/// Links come from well-known labels and annotations first and naming
+/// conventions second, and every link records the evidence it was made from
+/// so a wrong guess can be traced back.
+use crate::error::{Error, Result};
+use crate::infrastructure::kubernetes::KubernetesClient;
+use crate::lifecycle::LifecycleManager;
+use crate::monitoring::{GrafanaDashboard, MonitoringModule};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::Arc;
+
+/// Workload labels naming the service, most specific first
+const SERVICE_LABELS: &[&str] = &["app.kubernetes.io/name", "app", "k8s-app", "service"];
+
+/// Alert labels naming the service; Prometheus flattens `app.kubernetes.io/name`
+const ALERT_SERVICE_LABELS: &[&str] = &[
+    "service",
+    "app",
+    "app_kubernetes_io_name",
+    "deployment",
+    "container",
+    "job",
+];
+
+/// Workload annotations pointing at the source repository
+const SOURCE_ANNOTATIONS: &[&str] = &[
+    "app.kubernetes.io/source",
+    "org.opencontainers.image.source",
+    "a8r.io/repository",
+    "backstage.io/source-location",
+];
+
+/// Kind of a resolved entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Service,
+    Workload,
+    Image,
+    Repository,
+    Dashboard,
+    Alert,
+}
+
+/// How one entity relates to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    /// Service to the workload running it
+    DeployedAs,
+    /// Workload to a container image
+    Runs,
+    /// Image to the repository it is built from
+    BuiltFrom,
+    /// Workload to its declared source repository
+    Source,
+    /// Service to a dashboard about it
+    Dashboard,
+    /// Service to an alert firing for it
+    Alert,
+}
+
+/// An object known to one of the modules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entity {
+    pub id: String,
+    pub kind: EntityKind,
+    pub name: String,
+    #[serde(default)]
+    pub attributes: Value,
+}
+
+/// A directed link between two entities
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Link {
+    pub from: String,
+    pub to: String,
+    pub relation: Relation,
+    /// Label, annotation or convention the link was inferred from
+    pub evidence: String,
+}
+
+/// A Deployment, StatefulSet or DaemonSet
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Workload {
+    pub kind: String,
+    pub namespace: String,
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub annotations: BTreeMap<String, String>,
+    pub images: Vec<String>,
+    pub replicas: Option<u64>,
+    pub ready: Option<u64>,
+}
+
+/// An alert from Alertmanager
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FiringAlert {
+    pub fingerprint: String,
+    pub name: String,
+    pub state: String,
+    pub summary: Option<String>,
+    pub labels: BTreeMap<String, String>,
+    pub starts_at: Option<DateTime<Utc>>,
+}
+
+/// A parsed container image reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageRef {
+    pub registry: String,
+    pub repository: String,
+    pub tag: Option<String>,
+    pub digest: Option<String>,
+}
+
+impl ImageRef {
+    /// Parse `[registry/]repository[:tag][@digest]`, defaulting to Docker Hub
+    pub fn parse(image: &str) -> Option<Self> {
+        let image = image.trim();
+        if image.is_empty() {
+            return None;
+        }
+        let (rest, digest) = match image.split_once('@') {
+            Some((rest, digest)) => (rest, Some(digest.to_string())),
+            None => (image, None),
+        };
+        // A colon after the last slash is a tag; before it, a registry port
+        let (rest, tag) = match rest.rfind(':') {
+            Some(i) if !rest[i..].contains('/') => (&rest[..i], Some(rest[i + 1..].to_string())),
+            _ => (rest, None),
+        };
+        let (registry, repository) = match rest.split_once('/') {
+            Some((first, path)) if first.contains(['.', ':']) || first == "localhost" => {
+                (first.to_string(), path.to_string())
+            }
+            _ if rest.contains('/') => ("docker.io".to_string(), rest.to_string()),
+            _ => ("docker.io".to_string(), format!("library/{}", rest)),
+        };
+        if repository.is_empty() {
+            return None;
+        }
+        Some(Self {
+            registry,
+            repository,
+            tag,
+            digest,
+        })
+    }
+
+    /// Image without tag or digest, as used for entity ids
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.registry, self.repository)
+    }
+
+    /// Tag, digest or `latest`, as reported on the running workload
+    pub fn version(&self) -> String {
+        self.tag
+            .clone()
+            .or_else(|| self.digest.clone())
+            .unwrap_or_else(|| "latest".to_string())
+    }
+
+    /// Source repository implied by registries that mirror forge paths
+    pub fn source_repository(&self) -> Option<String> {
+        let forge = match self.registry.as_str() {
+            "ghcr.io" | "docker.pkg.github.com" => "github.com",
+            "registry.gitlab.com" => "gitlab.com",
+            "codeberg.org" => "codeberg.org",
+            _ => return None,
+        };
+        // GitLab allows extra image path segments below the project
+        let segments: Vec<&str> = self.repository.split('/').collect();
+        let path = if forge == "gitlab.com" || segments.len() < 2 {
+            self.repository.clone()
+        } else {
+            segments[..2].join("/")
+        };
+        Some(format!("https://{}/{}", forge, path))
+    }
+}
+
+/// Lowercase, hyphen-separated form used to compare names across modules
+pub fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Whether `text` names `service` as a whole run of words
+fn mentions(text: &str, service: &str) -> bool {
+    let text = format!("-{}-", slug(text));
+    text.contains(&format!("-{}-", service))
+}
+
+/// Canonical `https://host/owner/repo` form of a repository URL
+pub fn normalize_repository(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    if let Some(rest) = url.strip_prefix("git@") {
+        return format!("https://{}", rest.replacen(':', "/", 1));
+    }
+    let url = url
+        .strip_prefix("git+")
+        .unwrap_or(url)
+        .replacen("http://", "https://", 1);
+    if url.contains("://") {
+        url
+    } else {
+        format!("https://{}", url)
+    }
+}
+
+fn string_map(value: Option<&Value>) -> BTreeMap<String, String> {
+    value
+        .and_then(|v| v.as_object())
+        .map(|m| {
+            m.iter()
+                .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Workloads from a `kubectl get deployments,statefulsets,daemonsets -o json` list
+pub fn workloads_from_list(list: &Value) -> Vec<Workload> {
+    let items = list
+        .get("items")
+        .and_then(|i| i.as_array())
+        .cloned()
+        .unwrap_or_default();
+    items
+        .iter()
+        .filter_map(|item| {
+            let kind = item.get("kind")?.as_str()?.to_string();
+            let metadata = item.get("metadata")?;
+            let spec = item.get("spec");
+            let images = spec
+                .and_then(|s| s.pointer("/template/spec"))
+                .map(|pod| {
+                    ["initContainers", "containers"]
+                        .iter()
+                        .filter_map(|key| pod.get(*key).and_then(|c| c.as_array()))
+                        .flatten()
+                        .filter_map(|c| c.get("image").and_then(|i| i.as_str()))
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default();
+            let status = item.get("status");
+            Some(Workload {
+                ready: status
+                    .and_then(|s| s.get("readyReplicas").or_else(|| s.get("numberReady")))
+                    .and_then(|r| r.as_u64())
+                    .or(Some(0)),
+                replicas: spec
+                    .and_then(|s| s.get("replicas"))
+                    .or_else(|| status.and_then(|s| s.get("desiredNumberScheduled")))
+                    .and_then(|r| r.as_u64()),
+                kind,
+                namespace: metadata
+                    .get("namespace")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("default")
+                    .to_string(),
+                name: metadata.get("name")?.as_str()?.to_string(),
+                labels: string_map(metadata.get("labels")),
+                annotations: string_map(metadata.get("annotations")),
+                images,
+            })
+        })
+        .collect()
+}
+
+/// Alerts from the Alertmanager `/api/v2/alerts` response
+pub fn alerts_from_alertmanager(alerts: &Value) -> Vec<FiringAlert> {
+    alerts
+        .as_array()
+        .map(|alerts| {
+            alerts
+                .iter()
+                .filter_map(|alert| {
+                    let labels = string_map(alert.get("labels"));
+                    Some(FiringAlert {
+                        fingerprint: alert.get("fingerprint")?.as_str()?.to_string(),
+                        name: labels.get("alertname").cloned().unwrap_or_default(),
+                        state: alert
+                            .pointer("/status/state")
+                            .and_then(|s| s.as_str())
+                            .unwrap_or("active")
+                            .to_string(),
+                        summary: alert
+                            .pointer("/annotations/summary")
+                            .or_else(|| alert.pointer("/annotations/description"))
+                            .and_then(|s| s.as_str())
+                            .map(String::from),
+                        starts_at: alert
+                            .get("startsAt")
+                            .and_then(|s| s.as_str())
+                            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                            .map(|t| t.with_timezone(&Utc)),
+                        labels,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Everything known about one service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityDescription {
+    pub service: Entity,
+    pub related: BTreeMap<EntityKind, Vec<Entity>>,
+    pub links: Vec<Link>,
+}
+
+/// Entities from every source and the links between them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntityGraph {
+    pub entities: BTreeMap<String, Entity>,
+    pub links: Vec<Link>,
+    /// Dashboards and alerts that could not be tied to a service
+    pub unresolved: Vec<String>,
+    /// Sources that could not be read
+    pub warnings: Vec<String>,
+}
+
+impl EntityGraph {
+    /// Link workloads, dashboards and alerts into a graph
+    ///
+    /// Services come from workloads; dashboards and alerts only attach to a
+    /// service that a workload established.
+    pub fn build(
+        workloads: &[Workload],
+        dashboards: &[GrafanaDashboard],
+        alerts: &[FiringAlert],
+    ) -> Self {
+        let mut graph = Self::default();
+        for workload in workloads {
+            graph.add_workload(workload);
+        }
+        for dashboard in dashboards {
+            graph.add_dashboard(dashboard);
+        }
+        for alert in alerts {
+            graph.add_alert(alert);
+        }
+        graph
+    }
+
+    fn add(&mut self, kind: EntityKind, id: String, name: &str, attributes: Value) -> String {
+        self.entities.entry(id.clone()).or_insert_with(|| Entity {
+            id: id.clone(),
+            kind,
+            name: name.to_string(),
+            attributes,
+        });
+        id
+    }
+
+    fn link(&mut self, from: &str, to: &str, relation: Relation, evidence: String) {
+        if !self
+            .links
+            .iter()
+            .any(|l| l.from == from && l.to == to && l.relation == relation)
+        {
+            self.links.push(Link {
+                from: from.to_string(),
+                to: to.to_string(),
+                relation,
+                evidence,
+            });
+        }
+    }
+
+    fn add_repository(&mut self, from: &str, url: &str, relation: Relation, evidence: String) {
+        let url = normalize_repository(url);
+        let id = self.add(
+            EntityKind::Repository,
+            format!("repository:{}", url),
+            &url,
+            json!({"url": url}),
+        );
+        self.link(from, &id, relation, evidence);
+    }
+
+    fn add_workload(&mut self, workload: &Workload) {
+        let (service, evidence) = SERVICE_LABELS
+            .iter()
+            .find_map(|key| {
+                workload
+                    .labels
+                    .get(*key)
+                    .map(|v| (slug(v), format!("label {}={}", key, v)))
+            })
+            .unwrap_or_else(|| (slug(&workload.name), "workload name".to_string()));
+        if service.is_empty() {
+            return;
+        }
+        let service = self.add(
+            EntityKind::Service,
+            format!("service:{}", service),
+            &service,
+            json!({}),
+        );
+        let name = format!("{}/{}", workload.namespace, workload.name);
+        let workload_id = self.add(
+            EntityKind::Workload,
+            format!("{}:{}", workload.kind.to_lowercase(), name),
+            &name,
+            json!({
+                "kind": workload.kind,
+                "namespace": workload.namespace,
+                "replicas": workload.replicas,
+                "ready": workload.ready,
+                "labels": workload.labels,
+            }),
+        );
+        self.link(&service, &workload_id, Relation::DeployedAs, evidence);
+
+        for image in &workload.images {
+            let Some(parsed) = ImageRef::parse(image) else {
+                continue;
+            };
+            let key = parsed.key();
+            let image_id = self.add(
+                EntityKind::Image,
+                format!("image:{}", key),
+                &key,
+                json!({"registry": parsed.registry, "versions": []}),
+            );
+            if let Some(versions) = self
+                .entities
+                .get_mut(&image_id)
+                .and_then(|e| e.attributes.get_mut("versions"))
+                .and_then(|v| v.as_array_mut())
+            {
+                let version = Value::String(parsed.version());
+                if !versions.contains(&version) {
+                    versions.push(version);
+                }
+            }
+            self.link(
+                &workload_id,
+                &image_id,
+                Relation::Runs,
+                format!("container image {}", image),
+            );
+            if let Some(url) = parsed.source_repository() {
+                self.add_repository(
+                    &image_id,
+                    &url,
+                    Relation::BuiltFrom,
+                    format!("{} image path", parsed.registry),
+                );
+            }
+        }
+
+        for key in SOURCE_ANNOTATIONS {
+            if let Some(url) = workload.annotations.get(*key) {
+                self.add_repository(
+                    &workload_id,
+                    url,
+                    Relation::Source,
+                    format!("annotation {}", key),
+                );
+            }
+        }
+    }
+
+    fn service_slugs(&self) -> Vec<String> {
+        self.entities
+            .values()
+            .filter(|e| e.kind == EntityKind::Service)
+            .map(|e| e.name.clone())
+            .collect()
+    }
+
+    fn add_dashboard(&mut self, dashboard: &GrafanaDashboard) {
+        let key = dashboard
+            .uid
+            .clone()
+            .unwrap_or_else(|| slug(&dashboard.title));
+        let id = self.add(
+            EntityKind::Dashboard,
+            format!("dashboard:{}", key),
+            &dashboard.title,
+            json!({"uid": dashboard.uid, "tags": dashboard.tags}),
+        );
+
+        let mut matched: Vec<(String, String)> = Vec::new();
+        for service in self.service_slugs() {
+            let tagged = dashboard.tags.iter().find(|tag| {
+                let tag = slug(tag);
+                tag == service
+                    || tag == format!("service-{}", service)
+                    || tag == format!("app-{}", service)
+            });
+            if let Some(tag) = tagged {
+                matched.push((service, format!("tag {}", tag)));
+            } else if mentions(&dashboard.title, &service) {
+                matched.push((service.clone(), format!("title mentions {}", service)));
+            }
+        }
+        // "api" should not claim a dashboard that is about "api-gateway"
+        let longer: Vec<String> = matched.iter().map(|(s, _)| s.clone()).collect();
+        matched.retain(|(service, _)| {
+            !longer
+                .iter()
+                .any(|other| other != service && mentions(other, service))
+        });
+
+        if matched.is_empty() {
+            self.unresolved.push(id.clone());
+        }
+        for (service, evidence) in matched {
+            self.link(
+                &format!("service:{}", service),
+                &id,
+                Relation::Dashboard,
+                evidence,
+            );
+        }
+    }
+
+    fn add_alert(&mut self, alert: &FiringAlert) {
+        let id = self.add(
+            EntityKind::Alert,
+            format!("alert:{}", alert.fingerprint),
+            &alert.name,
+            json!({
+                "state": alert.state,
+                "severity": alert.labels.get("severity"),
+                "summary": alert.summary,
+                "starts_at": alert.starts_at,
+                "labels": alert.labels,
+            }),
+        );
+        let service = ALERT_SERVICE_LABELS.iter().find_map(|key| {
+            let value = alert.labels.get(*key)?;
+            let service = format!("service:{}", slug(value));
+            self.entities
+                .contains_key(&service)
+                .then(|| (service, format!("label {}={}", key, value)))
+        });
+        match service {
+            Some((service, evidence)) => self.link(&service, &id, Relation::Alert, evidence),
+            None => self.unresolved.push(id),
+        }
+    }
+
+    /// Service ids matching a service name, entity id or entity name
+    ///
+    /// Non-service entities resolve to the services they are linked from,
+    /// so an image or repository finds every service built from it.
+    pub fn resolve(&self, query: &str) -> Vec<String> {
+        let service = format!("service:{}", slug(query));
+        if self.entities.contains_key(&service) {
+            return vec![service];
+        }
+        let image = ImageRef::parse(query).map(|i| format!("image:{}", i.key()));
+        let repository = format!("repository:{}", normalize_repository(query));
+        let starts: Vec<&String> = self
+            .entities
+            .values()
+            .filter(|e| {
+                e.id == query
+                    || e.name.eq_ignore_ascii_case(query.trim())
+                    || image.as_ref() == Some(&e.id)
+                    || e.id == repository
+            })
+            .map(|e| &e.id)
+            .collect();
+
+        let mut seen: BTreeSet<&String> = starts.iter().copied().collect();
+        let mut queue: VecDeque<&String> = starts.into_iter().collect();
+        let mut services = BTreeSet::new();
+        while let Some(id) = queue.pop_front() {
+            if self.entities.get(id).map(|e| e.kind) == Some(EntityKind::Service) {
+                services.insert(id.clone());
+                continue;
+            }
+            for link in self.links.iter().filter(|l| &l.to == id) {
+                if seen.insert(&link.from) {
+                    queue.push_back(&link.from);
+                }
+            }
+        }
+        services.into_iter().collect()
+    }
+
+    /// Everything reachable from a service
+    pub fn describe_service(&self, service_id: &str) -> Option<EntityDescription> {
+        let service = self.entities.get(service_id)?.clone();
+        let mut related: BTreeMap<EntityKind, Vec<Entity>> = BTreeMap::new();
+        let mut links = Vec::new();
+        let mut seen = BTreeSet::from([service_id.to_string()]);
+        let mut queue = VecDeque::from([service_id.to_string()]);
+        while let Some(id) = queue.pop_front() {
+            for link in self.links.iter().filter(|l| l.from == id) {
+                links.push(link.clone());
+                if seen.insert(link.to.clone()) {
+                    if let Some(entity) = self.entities.get(&link.to) {
+                        related.entry(entity.kind).or_default().push(entity.clone());
+                    }
+                    queue.push_back(link.to.clone());
+                }
+            }
+        }
+        Some(EntityDescription {
+            service,
+            related,
+            links,
+        })
+    }
+
+    /// Descriptions of every service matching the query
+    pub fn describe(&self, query: &str) -> Vec<EntityDescription> {
+        self.resolve(query)
+            .iter()
+            .filter_map(|id| self.describe_service(id))
+            .collect()
+    }
+}
+
+/// Builds the entity graph from Kubernetes, Grafana and Alertmanager
+pub struct EntityResolver {
+    lifecycle: Arc<LifecycleManager>,
+    kubeconfig: Option<String>,
+    context: Option<String>,
+    monitoring: Option<Arc<MonitoringModule>>,
+    alertmanager_url: Option<String>,
+    http_client: Client,
+}
+
+impl EntityResolver {
+    /// Create a resolver reading workloads with the given kubeconfig and context
+    pub fn new(
+        lifecycle: Arc<LifecycleManager>,
+        kubeconfig: Option<String>,
+        context: Option<String>,
+    ) -> Self {
+        Self {
+            lifecycle,
+            kubeconfig,
+            context,
+            monitoring: None,
+            alertmanager_url: std::env::var("ALERTMANAGER_URL").ok(),
+            http_client: Client::new(),
+        }
+    }
+
+    /// Read dashboards through a monitoring module with Grafana configured
+    pub fn with_monitoring(mut self, monitoring: Arc<MonitoringModule>) -> Self {
+        self.monitoring = Some(monitoring);
+        self
+    }
+
+    /// Read alerts from a specific Alertmanager
+    pub fn with_alertmanager(mut self, url: impl Into<String>) -> Self {
+        self.alertmanager_url = Some(url.into());
+        self
+    }
+
+    /// Deployments, StatefulSets and DaemonSets in every namespace
+    pub async fn workloads(&self) -> Result<Vec<Workload>> {
+        let client = KubernetesClient::new(
+            &self.lifecycle,
+            self.kubeconfig.as_deref(),
+            self.context.as_deref(),
+        )?;
+        let result = client
+            .run_kubectl_command("get deployments,statefulsets,daemonsets -A -o json", None)
+            .await?;
+        if !result.success {
+            return Err(Error::service(format!(
+                "kubectl get workloads failed: {}",
+                result.error.unwrap_or_default()
+            )));
+        }
+        let list: Value = serde_json::from_str(&result.output)
+            .map_err(|e| Error::parsing(format!("Failed to parse workloads: {}", e)))?;
+        Ok(workloads_from_list(&list))
+    }
+
+    /// Active, unsilenced alerts
+    pub async fn alerts(&self) -> Result<Vec<FiringAlert>> {
+        let url = self
+            .alertmanager_url
+            .as_deref()
+            .ok_or_else(|| Error::config("ALERTMANAGER_URL is not set"))?;
+        let response = self
+            .http_client
+            .get(format!("{}/api/v2/alerts", url.trim_end_matches('/')))
+            .query(&[
+                ("active", "true"),
+                ("silenced", "false"),
+                ("inhibited", "false"),
+            ])
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Failed to reach Alertmanager: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(Error::service(format!(
+                "Alertmanager returned {}",
+                response.status()
+            )));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse Alertmanager alerts: {}", e)))?;
+        Ok(alerts_from_alertmanager(&body))
+    }
+
+    /// Read every configured source and link the results
+    ///
+    /// A source that fails is reported in `warnings` rather than failing the
+    /// whole graph, since a partial picture is still useful.
+    pub async fn graph(&self) -> EntityGraph {
+        let mut warnings = Vec::new();
+        let workloads = self.workloads().await.unwrap_or_else(|e| {
+            warnings.push(format!("Kubernetes: {}", e));
+            Vec::new()
+        });
+        let dashboards = match &self.monitoring {
+            Some(monitoring) if monitoring.get_config().grafana.is_some() => monitoring
+                .grafana_list_dashboards()
+                .await
+                .unwrap_or_else(|e| {
+                    warnings.push(format!("Grafana: {}", e));
+                    Vec::new()
+                }),
+            _ => Vec::new(),
+        };
+        let alerts = if self.alertmanager_url.is_some() {
+            self.alerts().await.unwrap_or_else(|e| {
+                warnings.push(format!("Alertmanager: {}", e));
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        for warning in &warnings {
+            tracing::warn!("Entity graph source unavailable: {}", warning);
+        }
+        let mut graph = EntityGraph::build(&workloads, &dashboards, &alerts);
+        graph.warnings = warnings;
+        graph
+    }
+
+    /// Get tool definitions for entity resolution
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "describe_entity",
+            "Describe a service and everything linked to it: Kubernetes workloads, container images, source repositories, Grafana dashboards and firing alerts",
+            "core",
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string", "description": "Service name, or an image, repository, workload or dashboard to find its services"}
+                },
+                "required": ["name"]
+            }),
+            None,
+        )
+        .read_only()]
+    }
+
+    /// Execute an entity resolution tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "describe_entity" => {
+                let query = parameters
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .filter(|n| !n.trim().is_empty())
+                    .ok_or_else(|| Error::validation_with_field("name is required", "name"))?;
+                let graph = self.graph().await;
+                let descriptions = graph.describe(query);
+                if descriptions.is_empty() {
+                    let known = graph.service_slugs();
+                    let mut message = format!("No service matches '{}'", query);
+                    if !known.is_empty() {
+                        message.push_str(&format!("; known services: {}", known.join(", ")));
+                    }
+                    for warning in &graph.warnings {
+                        message.push_str(&format!("\nNote: {}", warning));
+                    }
+                    return Err(Error::not_found_with_resource(message, "entity", query));
+                }
+
+                let mut text = String::new();
+                for description in &descriptions {
+                    text.push_str(&format!("Service {}\n", description.service.name));
+                    for (kind, entities) in &description.related {
+                        let names: Vec<&str> = entities.iter().map(|e| e.name.as_str()).collect();
+                        text.push_str(&format!("  {:?}: {}\n", kind, names.join(", ")));
+                    }
+                }
+                for warning in &graph.warnings {
+                    text.push_str(&format!("Note: {}\n", warning));
+                }
+                Ok(call_result(
+                    text.trim_end(),
+                    json!({"services": descriptions, "warnings": graph.warnings}),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "entity_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployment(name: &str, app: &str, image: &str) -> Workload {
+        Workload {
+            kind: "Deployment".to_string(),
+            namespace: "prod".to_string(),
+            name: name.to_string(),
+            labels: BTreeMap::from([("app.kubernetes.io/name".to_string(), app.to_string())]),
+            images: vec![image.to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn dashboard(title: &str, tags: &[&str]) -> GrafanaDashboard {
+        GrafanaDashboard {
+            id: None,
+            uid: Some(slug(title)),
+            title: title.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            panels: vec![],
+            templating: vec![],
+        }
+    }
+
+    #[test]
+    fn parses_image_references() {
+        let image = ImageRef::parse("ghcr.io/acme/api/server:1.4.2").unwrap();
+        assert_eq!(image.key(), "ghcr.io/acme/api/server");
+        assert_eq!(image.version(), "1.4.2");
+        assert_eq!(
+            image.source_repository().as_deref(),
+            Some("https://github.com/acme/api")
+        );
+        let image = ImageRef::parse("localhost:5000/web@sha256:abc").unwrap();
+        assert_eq!(image.key(), "localhost:5000/web");
+        assert_eq!(image.version(), "sha256:abc");
+        assert_eq!(
+            ImageRef::parse("nginx").unwrap().key(),
+            "docker.io/library/nginx"
+        );
+        assert_eq!(
+            normalize_repository("git@github.com:acme/api.git"),
+            "https://github.com/acme/api"
+        );
+    }
+
+    #[test]
+    fn links_workloads_dashboards_and_alerts_to_services() {
+        let mut api = deployment("api-v2", "api", "ghcr.io/acme/api:1.4.2");
+        api.annotations.insert(
+            "app.kubernetes.io/source".to_string(),
+            "https://github.com/acme/api.git".to_string(),
+        );
+        let gateway = deployment("api-gateway", "api-gateway", "envoyproxy/envoy:v1.30");
+        let alerts = vec![
+            FiringAlert {
+                fingerprint: "f1".to_string(),
+                name: "HighErrorRate".to_string(),
+                state: "active".to_string(),
+                labels: BTreeMap::from([("service".to_string(), "api".to_string())]),
+                ..Default::default()
+            },
+            FiringAlert {
+                fingerprint: "f2".to_string(),
+                name: "NodeDown".to_string(),
+                labels: BTreeMap::from([("job".to_string(), "node-exporter".to_string())]),
+                ..Default::default()
+            },
+        ];
+        let graph = EntityGraph::build(
+            &[api, gateway],
+            &[
+                dashboard("API Gateway Overview", &[]),
+                dashboard("Latency", &["service:api"]),
+            ],
+            &alerts,
+        );
+
+        let described = graph.describe("api");
+        assert_eq!(described.len(), 1);
+        let related = &described[0].related;
+        assert_eq!(related[&EntityKind::Workload][0].name, "prod/api-v2");
+        assert_eq!(related[&EntityKind::Image][0].name, "ghcr.io/acme/api");
+        // Image path and annotation agree on one repository
+        assert_eq!(related[&EntityKind::Repository].len(), 1);
+        assert_eq!(related[&EntityKind::Dashboard][0].name, "Latency");
+        assert_eq!(related[&EntityKind::Alert][0].name, "HighErrorRate");
+
+        let gateway = &graph.describe("api-gateway")[0].related;
+        assert_eq!(
+            gateway[&EntityKind::Dashboard][0].name,
+            "API Gateway Overview"
+        );
+        assert!(!gateway.contains_key(&EntityKind::Alert));
+
+        assert_eq!(graph.resolve("ghcr.io/acme/api:2.0.0"), vec!["service:api"]);
+        assert_eq!(graph.unresolved, vec!["alert:f2"]);
+    }
+
+    #[test]
+    fn reads_workloads_and_alerts() {
+        let list = json!({"items": [{
+            "kind": "StatefulSet",
+            "metadata": {"name": "db", "namespace": "data", "labels": {"app": "db"}},
+            "spec": {"replicas": 3, "template": {"spec": {"containers": [{"image": "postgres:16"}]}}},
+            "status": {"readyReplicas": 2}
+        }]});
+        let workloads = workloads_from_list(&list);
+        assert_eq!(workloads[0].images, vec!["postgres:16"]);
+        assert_eq!(
+            (workloads[0].replicas, workloads[0].ready),
+            (Some(3), Some(2))
+        );
+
+        let alerts = alerts_from_alertmanager(&json!([{
+            "fingerprint": "abc",
+            "labels": {"alertname": "Down", "app": "db"},
+            "annotations": {"summary": "db is down"},
+            "startsAt": "2026-01-01T00:00:00Z",
+            "status": {"state": "active"}
+        }]));
+        assert_eq!(alerts[0].name, "Down");
+        assert_eq!(alerts[0].summary.as_deref(), Some("db is down"));
+        assert!(alerts[0].starts_at.is_some());
+    }
+}
@@ -1,9 +1,70 @@
+//! Alpaca trading and market data client
+//!
+//! Orders go to the paper trading API unless `paper` is turned off in the
+//! configuration, and even then each order has to be confirmed explicitly.
+
+pub mod stream;
+
+pub use stream::QuoteStream;
+
 use crate::error::{Error, Result};
-use crate::lifecycle::LifecycleManager;
 use chrono::{DateTime, Duration, Utc};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Map, Value};
+
+const PAPER_TRADING_URL: &str = "https://paper-api.alpaca.markets";
+const LIVE_TRADING_URL: &str = "https://api.alpaca.markets";
+const DATA_URL: &str = "https://data.alpaca.markets";
+const STREAM_URL: &str = "wss://stream.data.alpaca.markets/v2";
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_feed() -> String {
+    "iex".to_string()
+}
+
+/// Alpaca connection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaConfig {
+    /// API key ID
+    pub key_id: String,
+    /// API secret key
+    pub secret_key: String,
+    /// Trade against the paper account; live orders also need `confirm`
+    #[serde(default = "default_true")]
+    pub paper: bool,
+    /// Market data feed, `iex` (free) or `sip`
+    #[serde(default = "default_feed")]
+    pub feed: String,
+    /// Override for the trading API, e.g. for a proxy
+    #[serde(default)]
+    pub trading_url: Option<String>,
+    /// Override for the market data API
+    #[serde(default)]
+    pub data_url: Option<String>,
+    /// Override for the market data stream, without the feed
+    #[serde(default)]
+    pub stream_url: Option<String>,
+}
+
+impl AlpacaConfig {
+    /// Paper trading config from `APCA_API_KEY_ID` and `APCA_API_SECRET_KEY`
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            key_id: std::env::var("APCA_API_KEY_ID").ok()?,
+            secret_key: std::env::var("APCA_API_SECRET_KEY").ok()?,
+            paper: true,
+            feed: default_feed(),
+            trading_url: None,
+            data_url: None,
+            stream_url: None,
+        })
+    }
+}
 
 /// Trading account information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +100,9 @@ pub struct Position {
     pub symbol: String,
     /// Quantity of shares
     pub qty: String,
+    /// `long` or `short`
+    #[serde(default)]
+    pub side: String,
     /// Market value
     pub market_value: String,
     /// Average entry price
@@ -52,43 +116,58 @@ pub struct Position {
 }
 
 /// Stock quote information
+///
+/// Reads both the REST and stream field names (`ap`, `bp`, ...).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Quote {
-    /// Symbol
+    /// Symbol, filled in from the request for REST quotes
+    #[serde(default, alias = "S")]
     pub symbol: String,
     /// Ask price
+    #[serde(alias = "ap")]
     pub ask_price: f64,
     /// Bid price
+    #[serde(alias = "bp")]
     pub bid_price: f64,
     /// Ask size
+    #[serde(alias = "as")]
     pub ask_size: i64,
     /// Bid size
+    #[serde(alias = "bs")]
     pub bid_size: i64,
     /// Timestamp
+    #[serde(alias = "t")]
     pub timestamp: DateTime<Utc>,
 }
 
 /// Historical price bar
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bar {
-    /// Symbol
+    /// Symbol, filled in from the request
+    #[serde(default, alias = "S")]
     pub symbol: String,
     /// Timestamp
+    #[serde(alias = "t")]
     pub timestamp: DateTime<Utc>,
     /// Open price
+    #[serde(alias = "o")]
     pub open: f64,
     /// High price
+    #[serde(alias = "h")]
     pub high: f64,
     /// Low price
+    #[serde(alias = "l")]
     pub low: f64,
     /// Close price
+    #[serde(alias = "c")]
     pub close: f64,
     /// Volume
+    #[serde(alias = "v")]
     pub volume: i64,
 }
 
 /// Order side enum
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderSide {
     /// Buy order
@@ -97,10 +176,21 @@ pub enum OrderSide {
     Sell,
 }
 
+impl OrderSide {
+    /// Name as used by the API
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderSide::Buy => "buy",
+            OrderSide::Sell => "sell",
+        }
+    }
+}
+
 /// Order type enum
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum OrderType {
     /// Market order
+    #[default]
     #[serde(rename = "market")]
     Market,
     /// Limit order
@@ -112,12 +202,16 @@ pub enum OrderType {
     /// Stop limit order
     #[serde(rename = "stop_limit")]
     StopLimit,
+    /// Trailing stop order
+    #[serde(rename = "trailing_stop")]
+    TrailingStop,
 }
 
 /// Time in force enum
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TimeInForce {
     /// Day order
+    #[default]
     #[serde(rename = "day")]
     Day,
     /// Good till canceled
@@ -129,11 +223,17 @@ pub enum TimeInForce {
     /// Fill or kill
     #[serde(rename = "fok")]
     Fok,
+    /// At the opening auction
+    #[serde(rename = "opg")]
+    Opg,
+    /// At the closing auction
+    #[serde(rename = "cls")]
+    Cls,
 }
 
 /// Order status enum
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
 pub enum OrderStatus {
     /// New order
     New,
@@ -169,6 +269,9 @@ pub enum OrderStatus {
     Calculated,
     /// Held order
     Held,
+    /// Status added to the API after this client
+    #[serde(other)]
+    Unknown,
 }
 
 /// Order information
@@ -176,14 +279,24 @@ pub enum OrderStatus {
 pub struct Order {
     /// Order ID
     pub id: String,
+    /// Client-assigned order ID
+    #[serde(default)]
+    pub client_order_id: Option<String>,
     /// Symbol
     pub symbol: String,
     /// Order type
+    #[serde(rename = "type", alias = "order_type")]
     pub order_type: OrderType,
     /// Order side
     pub side: OrderSide,
-    /// Quantity
-    pub qty: String,
+    /// Quantity, absent for notional orders
+    pub qty: Option<String>,
+    /// Dollar amount for notional orders
+    #[serde(default)]
+    pub notional: Option<String>,
+    /// Quantity filled so far
+    #[serde(default)]
+    pub filled_qty: Option<String>,
     /// Limit price
     pub limit_price: Option<String>,
     /// Stop price
@@ -193,45 +306,173 @@ pub struct Order {
     /// Time in force
     pub time_in_force: TimeInForce,
     /// Submitted timestamp
-    pub submitted_at: DateTime<Utc>,
+    pub submitted_at: Option<DateTime<Utc>>,
     /// Filled timestamp
     pub filled_at: Option<DateTime<Utc>>,
     /// Filled average price
     pub filled_avg_price: Option<String>,
 }
 
-/// Market order request
+/// New order
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MarketOrderRequest {
+pub struct OrderRequest {
     /// Symbol
     pub symbol: String,
-    /// Quantity
-    pub qty: f64,
     /// Order side
     pub side: OrderSide,
+    /// Order type
+    #[serde(rename = "type", default)]
+    pub order_type: OrderType,
     /// Time in force
+    #[serde(default)]
     pub time_in_force: TimeInForce,
+    /// Shares, fractional for market day orders
+    pub qty: Option<f64>,
+    /// Dollar amount instead of `qty`, market day orders only
+    pub notional: Option<f64>,
+    /// Limit price for limit and stop limit orders
+    pub limit_price: Option<f64>,
+    /// Stop price for stop and stop limit orders
+    pub stop_price: Option<f64>,
+    /// Trail for trailing stop orders, in percent
+    pub trail_percent: Option<f64>,
+    /// ID to recognise the order by, unique per account
+    pub client_order_id: Option<String>,
+    /// Allow filling in pre- and after-market sessions (limit day orders)
+    #[serde(default)]
+    pub extended_hours: bool,
 }
 
-/// Limit order request
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LimitOrderRequest {
-    /// Symbol
-    pub symbol: String,
-    /// Quantity
-    pub qty: f64,
-    /// Order side
-    pub side: OrderSide,
-    /// Limit price
-    pub limit_price: f64,
-    /// Time in force
-    pub time_in_force: TimeInForce,
+impl OrderRequest {
+    /// Check the fields each order type needs before sending it
+    pub fn validate(&self) -> Result<()> {
+        for (field, value) in [
+            ("qty", self.qty),
+            ("notional", self.notional),
+            ("limit_price", self.limit_price),
+            ("stop_price", self.stop_price),
+            ("trail_percent", self.trail_percent),
+        ] {
+            if value.is_some_and(|v| !(v.is_finite() && v > 0.0)) {
+                return Err(Error::validation_with_field(
+                    format!("{} must be a positive number", field),
+                    field,
+                ));
+            }
+        }
+        if self.qty.is_some() == self.notional.is_some() {
+            return Err(Error::validation_with_field(
+                "Give exactly one of qty or notional",
+                "qty",
+            ));
+        }
+        if self.notional.is_some()
+            && (self.order_type != OrderType::Market || self.time_in_force != TimeInForce::Day)
+        {
+            return Err(Error::validation_with_field(
+                "Notional orders must be market day orders",
+                "notional",
+            ));
+        }
+        let (limit, stop) = match self.order_type {
+            OrderType::Market | OrderType::TrailingStop => (false, false),
+            OrderType::Limit => (true, false),
+            OrderType::Stop => (false, true),
+            OrderType::StopLimit => (true, true),
+        };
+        if limit != self.limit_price.is_some() {
+            return Err(Error::validation_with_field(
+                format!("limit_price is {} for this order type", needed(limit)),
+                "limit_price",
+            ));
+        }
+        if stop != self.stop_price.is_some() {
+            return Err(Error::validation_with_field(
+                format!("stop_price is {} for this order type", needed(stop)),
+                "stop_price",
+            ));
+        }
+        if (self.order_type == OrderType::TrailingStop) != self.trail_percent.is_some() {
+            return Err(Error::validation_with_field(
+                "trail_percent is required for trailing stop orders only",
+                "trail_percent",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Request body, with amounts as decimal strings
+    fn to_body(&self) -> Value {
+        let mut body = Map::new();
+        body.insert("symbol".to_string(), json!(self.symbol.to_uppercase()));
+        body.insert("side".to_string(), json!(self.side));
+        body.insert("type".to_string(), json!(self.order_type));
+        body.insert("time_in_force".to_string(), json!(self.time_in_force));
+        insert_decimal(&mut body, "qty", self.qty);
+        insert_decimal(&mut body, "notional", self.notional);
+        insert_decimal(&mut body, "limit_price", self.limit_price);
+        insert_decimal(&mut body, "stop_price", self.stop_price);
+        insert_decimal(&mut body, "trail_percent", self.trail_percent);
+        if let Some(id) = &self.client_order_id {
+            body.insert("client_order_id".to_string(), json!(id));
+        }
+        if self.extended_hours {
+            body.insert("extended_hours".to_string(), json!(true));
+        }
+        Value::Object(body)
+    }
+}
+
+/// Changes to an open order; unset fields keep their value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplaceOrderRequest {
+    pub qty: Option<f64>,
+    pub limit_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    pub time_in_force: Option<TimeInForce>,
+    pub client_order_id: Option<String>,
+}
+
+impl ReplaceOrderRequest {
+    fn to_body(&self) -> Result<Value> {
+        let mut body = Map::new();
+        insert_decimal(&mut body, "qty", self.qty);
+        insert_decimal(&mut body, "limit_price", self.limit_price);
+        insert_decimal(&mut body, "stop_price", self.stop_price);
+        if let Some(time_in_force) = self.time_in_force {
+            body.insert("time_in_force".to_string(), json!(time_in_force));
+        }
+        if let Some(id) = &self.client_order_id {
+            body.insert("client_order_id".to_string(), json!(id));
+        }
+        if body.is_empty() {
+            return Err(Error::validation(
+                "Nothing to replace: give qty, limit_price, stop_price or time_in_force",
+            ));
+        }
+        Ok(Value::Object(body))
+    }
+}
+
+fn needed(required: bool) -> &'static str {
+    if required {
+        "required"
+    } else {
+        "not allowed"
+    }
+}
+
+fn insert_decimal(body: &mut Map<String, Value>, key: &str, value: Option<f64>) {
+    if let Some(value) = value {
+        body.insert(key.to_string(), json!(value.to_string()));
+    }
 }
 
 /// Order query type
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub enum OrderQueryType {
     /// Open orders
+    #[default]
     #[serde(rename = "open")]
     Open,
     /// Closed orders
@@ -242,527 +483,297 @@ pub enum OrderQueryType {
     All,
 }
 
-/// Client for Alpaca trading API
-pub struct AlpacaClient<'a> {
-    /// Lifecycle manager
-    #[allow(dead_code)]
-    lifecycle: &'a LifecycleManager,
-    /// HTTP client
+impl OrderQueryType {
+    fn as_str(self) -> &'static str {
+        match self {
+            OrderQueryType::Open => "open",
+            OrderQueryType::Closed => "closed",
+            OrderQueryType::All => "all",
+        }
+    }
+}
+
+/// Client for the Alpaca trading and market data APIs
+#[derive(Debug, Clone)]
+pub struct AlpacaClient {
+    config: AlpacaConfig,
     client: Client,
-    /// API key
-    api_key: String,
-    /// API secret
-    api_secret: String,
-    /// Base URL for API
-    base_url: String,
-    /// Base URL for data API
-    data_base_url: String,
+    trading_url: String,
+    data_url: String,
 }
 
-impl<'a> AlpacaClient<'a> {
-    /// Create a new Alpaca client
-    pub fn new(lifecycle: &'a LifecycleManager) -> Self {
-        Self {
-            lifecycle,
-            client: Client::new(),
-            api_key: String::new(),
-            api_secret: String::new(),
-            base_url: "https://paper-api.alpaca.markets".to_string(),
-            data_base_url: "https://data.alpaca.markets".to_string(),
+impl AlpacaClient {
+    /// Create a client for the configured account
+    pub fn new(config: AlpacaConfig) -> Result<Self> {
+        if config.key_id.is_empty() || config.secret_key.is_empty() {
+            return Err(Error::config_with_suggestion(
+                "Alpaca API credentials not configured",
+                "Set finance.alpaca.key_id and secret_key, or APCA_API_KEY_ID and APCA_API_SECRET_KEY",
+            ));
         }
+        let default_trading = if config.paper {
+            PAPER_TRADING_URL
+        } else {
+            LIVE_TRADING_URL
+        };
+        let trading_url = config
+            .trading_url
+            .as_deref()
+            .unwrap_or(default_trading)
+            .trim_end_matches('/')
+            .to_string();
+        let data_url = config
+            .data_url
+            .as_deref()
+            .unwrap_or(DATA_URL)
+            .trim_end_matches('/')
+            .to_string();
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create HTTP client: {}", e)))?;
+        Ok(Self {
+            config,
+            client,
+            trading_url,
+            data_url,
+        })
     }
 
-    /// Set API key and secret
-    pub fn with_credentials(
-        mut self,
-        api_key: impl Into<String>,
-        api_secret: impl Into<String>,
-    ) -> Self {
-        self.api_key = api_key.into();
-        self.api_secret = api_secret.into();
-        self
-    }
-
-    /// Set to use paper trading
-    pub fn paper_trading(mut self, paper: bool) -> Self {
-        if paper {
-            self.base_url = "https://paper-api.alpaca.markets".to_string();
-        } else {
-            self.base_url = "https://api.alpaca.markets".to_string();
-        }
-        self
+    /// Whether orders go to the paper account
+    pub fn is_paper(&self) -> bool {
+        self.config.paper
     }
 
-    /// Check if API credentials are available
-    pub fn check_credentials(&self) -> Result<()> {
-        if self.api_key.is_empty() || self.api_secret.is_empty() {
-            return Err(Error::config("Alpaca API credentials not configured"));
+    /// Refuse live orders unless the caller confirmed them
+    pub fn check_trading(&self, confirm: bool) -> Result<()> {
+        if self.config.paper || confirm {
+            return Ok(());
         }
-        Ok(())
+        Err(Error::validation_with_field(
+            "This Alpaca account trades real money; pass confirm=true to send the order",
+            "confirm",
+        ))
     }
 
-    /// Get account information
-    pub async fn get_account(&self) -> Result<Account> {
-        self.check_credentials()?;
-
-        let url = format!("{}/v2/account", self.base_url);
-        let response = self
-            .client
-            .get(&url)
-            .header("APCA-API-KEY-ID", &self.api_key)
-            .header("APCA-API-SECRET-KEY", &self.api_secret)
+    /// Send a request with the API key headers, turning error statuses into errors
+    async fn execute(&self, request: RequestBuilder, action: &str) -> Result<Response> {
+        let response = request
+            .header("APCA-API-KEY-ID", &self.config.key_id)
+            .header("APCA-API-SECRET-KEY", &self.config.secret_key)
             .send()
             .await
-            .map_err(|e| Error::network(format!("Failed to get account information: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unable to read error response".into());
-            return Err(Error::network(format!(
-                "Alpaca API returned error {}: {}",
-                status, text
-            )));
+            .map_err(|e| Error::network(format!("Failed to {}: {}", action, e)))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
         }
+        let text = response.text().await.unwrap_or_default();
+        // Errors come as {"code": 40010001, "message": "..."}
+        let message = serde_json::from_str::<Value>(&text)
+            .ok()
+            .and_then(|body| body["message"].as_str().map(str::to_string))
+            .unwrap_or(text);
+        let message = format!("Failed to {}: {}", action, message);
+        Err(match status.as_u16() {
+            401 => Error::auth(message),
+            403 | 422 => Error::validation(message),
+            404 => Error::not_found(message),
+            code => Error::api_with_status(message, "alpaca", code),
+        })
+    }
 
-        let account: Account = response
+    async fn fetch<T: DeserializeOwned>(&self, request: RequestBuilder, action: &str) -> Result<T> {
+        self.execute(request, action)
+            .await?
             .json()
             .await
-            .map_err(|e| Error::network(format!("Failed to parse account response: {}", e)))?;
+            .map_err(|e| Error::parsing(format!("Failed to {}: {}", action, e)))
+    }
 
-        Ok(account)
+    /// Get account information
+    pub async fn get_account(&self) -> Result<Account> {
+        let url = format!("{}/v2/account", self.trading_url);
+        self.fetch(self.client.get(url), "get account information")
+            .await
     }
 
     /// Get positions
     pub async fn get_positions(&self) -> Result<Vec<Position>> {
-        self.check_credentials()?;
-
-        let url = format!("{}/v2/positions", self.base_url);
-        let response = self
-            .client
-            .get(&url)
-            .header("APCA-API-KEY-ID", &self.api_key)
-            .header("APCA-API-SECRET-KEY", &self.api_secret)
-            .send()
-            .await
-            .map_err(|e| Error::network(format!("Failed to get positions: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unable to read error response".into());
-            return Err(Error::network(format!(
-                "Alpaca API returned error {}: {}",
-                status, text
-            )));
-        }
-
-        let positions: Vec<Position> = response
-            .json()
-            .await
-            .map_err(|e| Error::network(format!("Failed to parse positions response: {}", e)))?;
-
-        Ok(positions)
+        let url = format!("{}/v2/positions", self.trading_url);
+        self.fetch(self.client.get(url), "get positions").await
     }
 
     /// Get latest quote for a stock
     pub async fn get_stock_quote(&self, symbol: &str) -> Result<Quote> {
-        self.check_credentials()?;
-
-        let url = format!("{}/v2/stocks/{}/quotes/latest", self.data_base_url, symbol);
-        let response = self
-            .client
-            .get(&url)
-            .header("APCA-API-KEY-ID", &self.api_key)
-            .header("APCA-API-SECRET-KEY", &self.api_secret)
-            .send()
-            .await
-            .map_err(|e| Error::network(format!("Failed to get stock quote: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unable to read error response".into());
-            return Err(Error::network(format!(
-                "Alpaca API returned error {}: {}",
-                status, text
-            )));
-        }
-
         #[derive(Deserialize)]
         struct QuoteResponse {
             quote: Quote,
         }
 
-        let response_data: QuoteResponse = response
-            .json()
-            .await
-            .map_err(|e| Error::network(format!("Failed to parse quote response: {}", e)))?;
-
-        Ok(response_data.quote)
+        let symbol = symbol.to_uppercase();
+        let url = format!("{}/v2/stocks/{}/quotes/latest", self.data_url, symbol);
+        let request = self.client.get(url).query(&[("feed", &self.config.feed)]);
+        let mut response: QuoteResponse = self.fetch(request, "get stock quote").await?;
+        response.quote.symbol = symbol;
+        Ok(response.quote)
     }
 
-    /// Get historical bars for a stock
+    /// Get daily bars for a stock over the last `days` days
     pub async fn get_stock_bars(&self, symbol: &str, days: i64) -> Result<Vec<Bar>> {
-        self.check_credentials()?;
-
-        let start_time = Utc::now() - Duration::days(days);
-        let start_str = start_time.format("%Y-%m-%dT%H:%M:%SZ").to_string();
-
-        let url = format!(
-            "{}/v2/stocks/{}/bars?timeframe=1D&start={}&adjustment=raw",
-            self.data_base_url, symbol, start_str
-        );
-
-        let response = self
-            .client
-            .get(&url)
-            .header("APCA-API-KEY-ID", &self.api_key)
-            .header("APCA-API-SECRET-KEY", &self.api_secret)
-            .send()
-            .await
-            .map_err(|e| Error::network(format!("Failed to get stock bars: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unable to read error response".into());
-            return Err(Error::network(format!(
-                "Alpaca API returned error {}: {}",
-                status, text
-            )));
-        }
-
         #[derive(Deserialize)]
         struct BarResponse {
-            bars: Vec<Bar>,
+            #[serde(default)]
+            bars: Option<Vec<Bar>>,
         }
 
-        let response_data: BarResponse = response
-            .json()
-            .await
-            .map_err(|e| Error::network(format!("Failed to parse bars response: {}", e)))?;
-
-        Ok(response_data.bars)
+        let symbol = symbol.to_uppercase();
+        let start = (Utc::now() - Duration::days(days)).to_rfc3339();
+        let url = format!("{}/v2/stocks/{}/bars", self.data_url, symbol);
+        let request = self.client.get(url).query(&[
+            ("timeframe", "1Day"),
+            ("start", start.as_str()),
+            ("adjustment", "raw"),
+            ("feed", self.config.feed.as_str()),
+        ]);
+        let response: BarResponse = self.fetch(request, "get stock bars").await?;
+        let mut bars = response.bars.unwrap_or_default();
+        for bar in &mut bars {
+            bar.symbol.clone_from(&symbol);
+        }
+        Ok(bars)
     }
 
     /// Get orders
     pub async fn get_orders(&self, status: OrderQueryType, limit: usize) -> Result<Vec<Order>> {
-        self.check_credentials()?;
-
-        let status_str = match status {
-            OrderQueryType::Open => "open",
-            OrderQueryType::Closed => "closed",
-            OrderQueryType::All => "all",
-        };
-
-        let url = format!(
-            "{}/v2/orders?status={}&limit={}",
-            self.base_url, status_str, limit
-        );
-
-        let response = self
-            .client
-            .get(&url)
-            .header("APCA-API-KEY-ID", &self.api_key)
-            .header("APCA-API-SECRET-KEY", &self.api_secret)
-            .send()
-            .await
-            .map_err(|e| Error::network(format!("Failed to get orders: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unable to read error response".into());
-            return Err(Error::network(format!(
-                "Alpaca API returned error {}: {}",
-                status, text
-            )));
-        }
-
-        let orders: Vec<Order> = response
-            .json()
-            .await
-            .map_err(|e| Error::network(format!("Failed to parse orders response: {}", e)))?;
-
-        Ok(orders)
+        let url = format!("{}/v2/orders", self.trading_url);
+        let request = self.client.get(url).query(&[
+            ("status", status.as_str().to_string()),
+            ("limit", limit.to_string()),
+        ]);
+        self.fetch(request, "get orders").await
     }
 
-    /// Place a market order
-    pub async fn place_market_order(&self, request: MarketOrderRequest) -> Result<Order> {
-        self.check_credentials()?;
-
-        let url = format!("{}/v2/orders", self.base_url);
-
-        #[derive(Serialize)]
-        struct OrderRequest {
-            symbol: String,
-            qty: String,
-            side: OrderSide,
-            type_: OrderType,
-            time_in_force: TimeInForce,
-        }
-
-        let order_request = OrderRequest {
-            symbol: request.symbol,
-            qty: request.qty.to_string(),
-            side: request.side,
-            type_: OrderType::Market,
-            time_in_force: request.time_in_force,
-        };
-
-        let response = self
-            .client
-            .post(&url)
-            .header("APCA-API-KEY-ID", &self.api_key)
-            .header("APCA-API-SECRET-KEY", &self.api_secret)
-            .json(&json!({
-                "symbol": order_request.symbol,
-                "qty": order_request.qty,
-                "side": order_request.side,
-                "type": "market",
-                "time_in_force": order_request.time_in_force,
-            }))
-            .send()
-            .await
-            .map_err(|e| Error::network(format!("Failed to place market order: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unable to read error response".into());
-            return Err(Error::network(format!(
-                "Alpaca API returned error {}: {}",
-                status, text
-            )));
-        }
-
-        let order: Order = response
-            .json()
+    /// Place an order; live accounts need `confirm`
+    pub async fn place_order(&self, order: &OrderRequest, confirm: bool) -> Result<Order> {
+        order.validate()?;
+        self.check_trading(confirm)?;
+        let url = format!("{}/v2/orders", self.trading_url);
+        self.fetch(self.client.post(url).json(&order.to_body()), "place order")
             .await
-            .map_err(|e| Error::network(format!("Failed to parse order response: {}", e)))?;
-
-        Ok(order)
     }
 
-    /// Place a limit order
-    pub async fn place_limit_order(&self, request: LimitOrderRequest) -> Result<Order> {
-        self.check_credentials()?;
-
-        let url = format!("{}/v2/orders", self.base_url);
-
-        let response = self
-            .client
-            .post(&url)
-            .header("APCA-API-KEY-ID", &self.api_key)
-            .header("APCA-API-SECRET-KEY", &self.api_secret)
-            .json(&json!({
-                "symbol": request.symbol,
-                "qty": request.qty.to_string(),
-                "side": request.side,
-                "type": "limit",
-                "time_in_force": request.time_in_force,
-                "limit_price": request.limit_price.to_string(),
-            }))
-            .send()
-            .await
-            .map_err(|e| Error::network(format!("Failed to place limit order: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unable to read error response".into());
-            return Err(Error::network(format!(
-                "Alpaca API returned error {}: {}",
-                status, text
-            )));
-        }
+    /// Cancel an open order
+    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        let url = format!("{}/v2/orders/{}", self.trading_url, order_id);
+        self.execute(self.client.delete(url), "cancel order")
+            .await?;
+        Ok(())
+    }
 
-        let order: Order = response
-            .json()
+    /// Replace an open order, returning the new order; live accounts need `confirm`
+    pub async fn replace_order(
+        &self,
+        order_id: &str,
+        changes: &ReplaceOrderRequest,
+        confirm: bool,
+    ) -> Result<Order> {
+        let body = changes.to_body()?;
+        self.check_trading(confirm)?;
+        let url = format!("{}/v2/orders/{}", self.trading_url, order_id);
+        self.fetch(self.client.patch(url).json(&body), "replace order")
             .await
-            .map_err(|e| Error::network(format!("Failed to parse order response: {}", e)))?;
-
-        Ok(order)
     }
 
-    /// Cancel an order
-    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
-        self.check_credentials()?;
-
-        let url = format!("{}/v2/orders/{}", self.base_url, order_id);
+    /// Subscribe to live quotes for `symbols` from the market data stream
+    pub async fn stream_quotes(&self, symbols: &[String]) -> Result<QuoteStream> {
+        let base = self
+            .config
+            .stream_url
+            .as_deref()
+            .unwrap_or(STREAM_URL)
+            .trim_end_matches('/');
+        let url = format!("{}/{}", base, self.config.feed);
+        QuoteStream::connect(&url, &self.config.key_id, &self.config.secret_key, symbols).await
+    }
+}
 
-        let response = self
-            .client
-            .delete(&url)
-            .header("APCA-API-KEY-ID", &self.api_key)
-            .header("APCA-API-SECRET-KEY", &self.api_secret)
-            .send()
-            .await
-            .map_err(|e| Error::network(format!("Failed to cancel order: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unable to read error response".into());
-            return Err(Error::network(format!(
-                "Alpaca API returned error {}: {}",
-                status, text
-            )));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AlpacaConfig {
+        AlpacaConfig {
+            key_id: "key".to_string(),
+            secret_key: "secret".to_string(),
+            paper: true,
+            feed: default_feed(),
+            trading_url: None,
+            data_url: None,
+            stream_url: None,
         }
+    }
 
-        Ok(())
+    #[test]
+    fn validates_orders_and_guards_live_trading() {
+        let mut order: OrderRequest = serde_json::from_value(json!({
+            "symbol": "aapl", "side": "buy", "type": "limit", "qty": 2, "limit_price": 187.5
+        }))
+        .unwrap();
+        assert!(order.validate().is_ok());
+        assert_eq!(
+            order.to_body(),
+            json!({"symbol": "AAPL", "side": "buy", "type": "limit", "time_in_force": "day", "qty": "2", "limit_price": "187.5"})
+        );
+        order.notional = Some(500.0);
+        assert!(order.validate().is_err());
+        order.notional = None;
+        order.limit_price = None;
+        assert!(order.validate().is_err());
+
+        let paper = AlpacaClient::new(config()).unwrap();
+        assert_eq!(paper.trading_url, PAPER_TRADING_URL);
+        assert!(paper.check_trading(false).is_ok());
+        let live = AlpacaClient::new(AlpacaConfig {
+            paper: false,
+            ..config()
+        })
+        .unwrap();
+        assert_eq!(live.trading_url, LIVE_TRADING_URL);
+        assert!(live.check_trading(false).is_err());
+        assert!(live.check_trading(true).is_ok());
     }
 
-    /// Get registered tools
-    pub fn get_tools(&self) -> Vec<(String, String, serde_json::Value)> {
-        vec![
-            (
-                "get_account_info".to_string(),
-                "Get the current account information including balances and status".to_string(),
-                serde_json::json!({
-                    "type": "object",
-                    "properties": {}
-                }),
-            ),
-            (
-                "get_positions".to_string(),
-                "Get all current positions in the portfolio".to_string(),
-                serde_json::json!({
-                    "type": "object",
-                    "properties": {}
-                }),
-            ),
-            (
-                "get_stock_quote".to_string(),
-                "Get the latest quote for a stock".to_string(),
-                serde_json::json!({
-                    "type": "object",
-                    "required": ["symbol"],
-                    "properties": {
-                        "symbol": {
-                            "type": "string",
-                            "description": "Stock ticker symbol (e.g., AAPL, MSFT)"
-                        }
-                    }
-                }),
-            ),
-            (
-                "get_stock_bars".to_string(),
-                "Get historical price bars for a stock".to_string(),
-                serde_json::json!({
-                    "type": "object",
-                    "required": ["symbol"],
-                    "properties": {
-                        "symbol": {
-                            "type": "string",
-                            "description": "Stock ticker symbol (e.g., AAPL, MSFT)"
-                        },
-                        "days": {
-                            "type": "integer",
-                            "description": "Number of trading days to look back (default: 5)"
-                        }
-                    }
-                }),
-            ),
-            (
-                "get_orders".to_string(),
-                "Get orders with the specified status".to_string(),
-                serde_json::json!({
-                    "type": "object",
-                    "properties": {
-                        "status": {
-                            "type": "string",
-                            "enum": ["open", "closed", "all"],
-                            "description": "Order status to filter by (open, closed, all)"
-                        },
-                        "limit": {
-                            "type": "integer",
-                            "description": "Maximum number of orders to return (default: 10)"
-                        }
-                    }
-                }),
-            ),
-            (
-                "place_market_order".to_string(),
-                "Place a market order".to_string(),
-                serde_json::json!({
-                    "type": "object",
-                    "required": ["symbol", "side", "quantity"],
-                    "properties": {
-                        "symbol": {
-                            "type": "string",
-                            "description": "Stock ticker symbol (e.g., AAPL, MSFT)"
-                        },
-                        "side": {
-                            "type": "string",
-                            "enum": ["buy", "sell"],
-                            "description": "Order side (buy or sell)"
-                        },
-                        "quantity": {
-                            "type": "number",
-                            "description": "Number of shares to buy or sell"
-                        }
-                    }
-                }),
-            ),
-            (
-                "place_limit_order".to_string(),
-                "Place a limit order".to_string(),
-                serde_json::json!({
-                    "type": "object",
-                    "required": ["symbol", "side", "quantity", "limit_price"],
-                    "properties": {
-                        "symbol": {
-                            "type": "string",
-                            "description": "Stock ticker symbol (e.g., AAPL, MSFT)"
-                        },
-                        "side": {
-                            "type": "string",
-                            "enum": ["buy", "sell"],
-                            "description": "Order side (buy or sell)"
-                        },
-                        "quantity": {
-                            "type": "number",
-                            "description": "Number of shares to buy or sell"
-                        },
-                        "limit_price": {
-                            "type": "number",
-                            "description": "Limit price for the order"
-                        }
-                    }
-                }),
-            ),
-            (
-                "cancel_order".to_string(),
-                "Cancel an existing order".to_string(),
-                serde_json::json!({
-                    "type": "object",
-                    "required": ["order_id"],
-                    "properties": {
-                        "order_id": {
-                            "type": "string",
-                            "description": "Order ID to cancel"
-                        }
-                    }
-                }),
-            ),
-        ]
+    #[test]
+    fn reads_api_orders_and_quotes() {
+        let order: Order = serde_json::from_value(json!({
+            "id": "61e69015-8549-4bfd-b9c3-01e75843f47d",
+            "client_order_id": "eb9e2aaa-f71a-4f51-b5b4-52a6c565dad4",
+            "symbol": "AAPL",
+            "type": "market",
+            "side": "buy",
+            "qty": null,
+            "notional": "500",
+            "filled_qty": "2.61",
+            "limit_price": null,
+            "stop_price": null,
+            "status": "partially_filled",
+            "time_in_force": "day",
+            "submitted_at": "2026-01-05T14:31:02.123456Z",
+            "filled_at": null,
+            "filled_avg_price": "191.3"
+        }))
+        .unwrap();
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.notional.as_deref(), Some("500"));
+
+        let quote: Quote = serde_json::from_value(json!({
+            "T": "q", "S": "AAPL", "bx": "V", "bp": 191.2, "bs": 3,
+            "ax": "V", "ap": 191.25, "as": 1, "t": "2026-01-05T14:31:02.5Z", "c": ["R"], "z": "C"
+        }))
+        .unwrap();
+        assert_eq!(quote.symbol, "AAPL");
+        assert_eq!(quote.ask_price, 191.25);
+        assert_eq!(quote.bid_size, 3);
     }
 }
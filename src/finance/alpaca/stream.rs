@@ -0,0 +1,243 @@
+//! Live quotes from the Alpaca market data stream
+
+use super::Quote;
+use crate::error::{Error, Result};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Time allowed for each step of the connect, auth and subscribe handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Quotes buffered before the reader waits for the consumer
+const QUOTE_BUFFER: usize = 256;
+
+type Socket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Subscription to live quotes
+///
+/// Quotes arrive until the stream is dropped or the server closes the
+/// connection; there is no reconnect, callers subscribe again.
+pub struct QuoteStream {
+    quotes: mpsc::Receiver<Quote>,
+    task: JoinHandle<()>,
+}
+
+impl QuoteStream {
+    /// Connect, authenticate and subscribe to quotes for `symbols`
+    pub async fn connect(
+        url: &str,
+        key_id: &str,
+        secret_key: &str,
+        symbols: &[String],
+    ) -> Result<Self> {
+        if symbols.is_empty() {
+            return Err(Error::validation_with_field(
+                "Give at least one symbol to stream",
+                "symbols",
+            ));
+        }
+        let symbols: Vec<String> = symbols.iter().map(|s| s.to_uppercase()).collect();
+        let (mut socket, _) =
+            tokio::time::timeout(HANDSHAKE_TIMEOUT, tokio_tungstenite::connect_async(url))
+                .await
+                .map_err(|_| {
+                    Error::timeout_with_duration(
+                        "Alpaca stream connection timed out",
+                        HANDSHAKE_TIMEOUT,
+                    )
+                })?
+                .map_err(|e| Error::network(format!("Alpaca stream connection failed: {}", e)))?;
+
+        expect(&mut socket, "success", Some("connected")).await?;
+        send_json(
+            &mut socket,
+            &json!({"action": "auth", "key": key_id, "secret": secret_key}),
+        )
+        .await?;
+        expect(&mut socket, "success", Some("authenticated")).await?;
+        send_json(
+            &mut socket,
+            &json!({"action": "subscribe", "quotes": symbols}),
+        )
+        .await?;
+        expect(&mut socket, "subscription", None).await?;
+
+        let (sender, quotes) = mpsc::channel(QUOTE_BUFFER);
+        let task = tokio::spawn(read_quotes(socket, sender));
+        Ok(Self { quotes, task })
+    }
+
+    /// Next quote, or `None` once the connection has closed
+    pub async fn next(&mut self) -> Option<Quote> {
+        self.quotes.recv().await
+    }
+}
+
+impl Drop for QuoteStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Forward quote messages until the connection or the consumer goes away
+async fn read_quotes(mut socket: Socket, sender: mpsc::Sender<Quote>) {
+    loop {
+        let messages = match read_messages(&mut socket).await {
+            Ok(messages) => messages,
+            Err(e) => {
+                tracing::info!("Alpaca quote stream ended: {}", e);
+                break;
+            }
+        };
+        for message in messages {
+            match message["T"].as_str() {
+                Some("q") => match serde_json::from_value::<Quote>(message) {
+                    Ok(quote) => {
+                        if sender.send(quote).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => tracing::warn!("Invalid Alpaca quote: {}", e),
+                },
+                Some("error") => {
+                    tracing::warn!(
+                        "Alpaca stream error {}: {}",
+                        message["code"],
+                        message["msg"]
+                    )
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Wait for a control message of type `kind`, failing on stream errors
+async fn expect(socket: &mut Socket, kind: &str, msg: Option<&str>) -> Result<()> {
+    let messages = tokio::time::timeout(HANDSHAKE_TIMEOUT, read_messages(socket))
+        .await
+        .map_err(|_| {
+            Error::timeout_with_duration("Alpaca stream handshake timed out", HANDSHAKE_TIMEOUT)
+        })??;
+    for message in &messages {
+        match message["T"].as_str() {
+            Some("error") => {
+                let text = format!(
+                    "Alpaca stream error {}: {}",
+                    message["code"],
+                    message["msg"].as_str().unwrap_or_default()
+                );
+                // 402 auth failed, 406 connection limit exceeded, 409 insufficient subscription
+                return Err(match message["code"].as_u64() {
+                    Some(401..=402) => Error::auth(text),
+                    _ => Error::service(text),
+                });
+            }
+            Some(t) if t == kind && msg.is_none_or(|m| message["msg"] == m) => return Ok(()),
+            _ => {}
+        }
+    }
+    Err(Error::protocol(format!(
+        "Expected {} from the Alpaca stream, got {}",
+        msg.unwrap_or(kind),
+        Value::Array(messages)
+    )))
+}
+
+/// Next batch of messages; the stream sends JSON arrays of them
+async fn read_messages(socket: &mut Socket) -> Result<Vec<Value>> {
+    loop {
+        let text = match socket.next().await {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Binary(data))) => String::from_utf8_lossy(&data).into_owned(),
+            Some(Ok(Message::Close(_))) | None => {
+                return Err(Error::network("Alpaca closed the stream"))
+            }
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(Error::network(e.to_string())),
+        };
+        return match serde_json::from_str(&text) {
+            Ok(Value::Array(messages)) => Ok(messages),
+            Ok(message) => Ok(vec![message]),
+            Err(e) => Err(Error::parsing(format!(
+                "Invalid Alpaca stream message: {}",
+                e
+            ))),
+        };
+    }
+}
+
+async fn send_json(socket: &mut Socket, message: &Value) -> Result<()> {
+    socket
+        .send(Message::Text(message.to_string()))
+        .await
+        .map_err(|e| Error::network(format!("Alpaca stream send failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::ws::{Message as WsMessage, WebSocket, WebSocketUpgrade};
+    use axum::routing::get;
+    use axum::Router;
+
+    async fn fake_stream(mut socket: WebSocket) {
+        let send = |value: Value| WsMessage::Text(value.to_string());
+        socket
+            .send(send(json!([{"T": "success", "msg": "connected"}])))
+            .await
+            .unwrap();
+        while let Some(Ok(WsMessage::Text(text))) = socket.recv().await {
+            let request: Value = serde_json::from_str(&text).unwrap();
+            let reply = match request["action"].as_str() {
+                Some("auth") if request["secret"] == "secret" => {
+                    json!([{"T": "success", "msg": "authenticated"}])
+                }
+                Some("auth") => json!([{"T": "error", "code": 402, "msg": "auth failed"}]),
+                _ => {
+                    socket
+                        .send(send(
+                            json!([{"T": "subscription", "quotes": request["quotes"]}]),
+                        ))
+                        .await
+                        .unwrap();
+                    json!([
+                        {"T": "q", "S": "AAPL", "bp": 191.2, "bs": 3, "ap": 191.25, "as": 1, "t": "2026-01-05T14:31:02.5Z"},
+                        {"T": "q", "S": "MSFT", "bp": 402.1, "bs": 2, "ap": 402.3, "as": 4, "t": "2026-01-05T14:31:02.6Z"}
+                    ])
+                }
+            };
+            socket.send(send(reply)).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribes_and_reads_quotes() {
+        let app = Router::new().route(
+            "/v2/iex",
+            get(|ws: WebSocketUpgrade| async move { ws.on_upgrade(fake_stream) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/v2/iex", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let symbols = vec!["aapl".to_string(), "msft".to_string()];
+        let mut stream = QuoteStream::connect(&url, "key", "secret", &symbols)
+            .await
+            .unwrap();
+        let first = stream.next().await.unwrap();
+        assert_eq!((first.symbol.as_str(), first.ask_price), ("AAPL", 191.25));
+        assert_eq!(stream.next().await.unwrap().symbol, "MSFT");
+
+        let error = QuoteStream::connect(&url, "key", "wrong", &symbols)
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("auth failed"));
+    }
+}
@@ -0,0 +1,461 @@
+//! Binance spot REST API
+
+use super::{
+    parse_pair, Balance, BookLevel, Candle, CandleInterval, CryptoConfig, CryptoExchange,
+    CryptoTicker, OrderBook,
+};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use sha2::Sha256;
+use std::time::Duration;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const BASE_URL: &str = "https://api.binance.com";
+
+/// Milliseconds a signed request stays valid, allowing for clock skew
+const RECV_WINDOW_MS: u64 = 5000;
+
+/// Depths the depth endpoint accepts
+const BOOK_DEPTHS: &[usize] = &[5, 10, 20, 50, 100, 500, 1000, 5000];
+
+/// Binance client; keys are only needed for account data
+pub struct BinanceClient {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    api_secret: Option<String>,
+}
+
+/// Binance sends prices and quantities as strings
+fn decimal<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<f64, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(text) => text.parse().map_err(serde::de::Error::custom),
+        Value::Number(number) => number
+            .as_f64()
+            .ok_or_else(|| serde::de::Error::custom("number out of range")),
+        other => Err(serde::de::Error::custom(format!(
+            "expected a decimal, got {}",
+            other
+        ))),
+    }
+}
+
+fn millis(ms: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ms).single().unwrap_or_default()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Ticker24h {
+    #[serde(deserialize_with = "decimal")]
+    last_price: f64,
+    #[serde(deserialize_with = "decimal")]
+    bid_price: f64,
+    #[serde(deserialize_with = "decimal")]
+    bid_qty: f64,
+    #[serde(deserialize_with = "decimal")]
+    ask_price: f64,
+    #[serde(deserialize_with = "decimal")]
+    ask_qty: f64,
+    #[serde(deserialize_with = "decimal")]
+    open_price: f64,
+    #[serde(deserialize_with = "decimal")]
+    high_price: f64,
+    #[serde(deserialize_with = "decimal")]
+    low_price: f64,
+    #[serde(deserialize_with = "decimal")]
+    volume: f64,
+    #[serde(deserialize_with = "decimal")]
+    quote_volume: f64,
+    #[serde(deserialize_with = "decimal")]
+    price_change_percent: f64,
+    close_time: i64,
+}
+
+/// `[open time, open, high, low, close, volume, close time, quote volume, trades, ...]`
+#[derive(Deserialize)]
+struct Kline(
+    i64,
+    #[serde(deserialize_with = "decimal")] f64,
+    #[serde(deserialize_with = "decimal")] f64,
+    #[serde(deserialize_with = "decimal")] f64,
+    #[serde(deserialize_with = "decimal")] f64,
+    #[serde(deserialize_with = "decimal")] f64,
+    i64,
+    serde::de::IgnoredAny,
+    u64,
+    #[serde(default)] serde::de::IgnoredAny,
+    #[serde(default)] serde::de::IgnoredAny,
+    #[serde(default)] serde::de::IgnoredAny,
+);
+
+#[derive(Deserialize)]
+struct Level(
+    #[serde(deserialize_with = "decimal")] f64,
+    #[serde(deserialize_with = "decimal")] f64,
+);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Depth {
+    last_update_id: Option<u64>,
+    bids: Vec<Level>,
+    asks: Vec<Level>,
+}
+
+#[derive(Deserialize)]
+struct AccountBalance {
+    asset: String,
+    #[serde(deserialize_with = "decimal")]
+    free: f64,
+    #[serde(deserialize_with = "decimal")]
+    locked: f64,
+}
+
+#[derive(Deserialize)]
+struct Account {
+    balances: Vec<AccountBalance>,
+}
+
+impl BinanceClient {
+    pub fn new(config: &CryptoConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create HTTP client: {}", e)))?;
+        Ok(Self {
+            client,
+            base_url: config
+                .base_url
+                .as_deref()
+                .unwrap_or(BASE_URL)
+                .trim_end_matches('/')
+                .to_string(),
+            api_key: config.api_key.clone(),
+            api_secret: config.api_secret.clone(),
+        })
+    }
+
+    /// Binance writes pairs without a separator, e.g. `BTCUSDT`
+    fn symbol(pair: &str) -> Result<(String, String)> {
+        let (base, quote) = parse_pair(pair)?;
+        Ok((format!("{}{}", base, quote), format!("{}-{}", base, quote)))
+    }
+
+    async fn fetch<T: DeserializeOwned>(&self, request: RequestBuilder, action: &str) -> Result<T> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Failed to {}: {}", action, e)))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| Error::network(format!("Failed to {}: {}", action, e)))?;
+        if !status.is_success() {
+            // Errors come as {"code": -1121, "msg": "Invalid symbol."}
+            let body: Value = serde_json::from_str(&text).unwrap_or_default();
+            let message = format!(
+                "Failed to {}: {}",
+                action,
+                body["msg"].as_str().unwrap_or(&text)
+            );
+            return Err(match (status.as_u16(), body["code"].as_i64()) {
+                (401, _) | (_, Some(-2014 | -2015)) => Error::auth(message),
+                (400, Some(-1121)) => Error::not_found(message),
+                (400, _) => Error::validation(message),
+                (code, _) => Error::api_with_status(message, "binance", code),
+            });
+        }
+        serde_json::from_str(&text)
+            .map_err(|e| Error::parsing(format!("Failed to {}: {}", action, e)))
+    }
+
+    /// HMAC-SHA256 signature of a query string, hex encoded
+    fn signature(secret: &str, query: &str) -> Result<String> {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+            .map_err(|e| Error::internal(format!("Failed to create HMAC: {}", e)))?;
+        mac.update(query.as_bytes());
+        Ok(mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect())
+    }
+
+    /// GET an account endpoint with a signed, timestamped query
+    async fn signed<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &str,
+        action: &str,
+    ) -> Result<T> {
+        let (Some(key), Some(secret)) = (&self.api_key, &self.api_secret) else {
+            return Err(Error::config_with_suggestion(
+                "Binance API key is not configured",
+                "Set finance.crypto.api_key and api_secret, or BINANCE_API_KEY and BINANCE_API_SECRET; a read-only key is enough",
+            ));
+        };
+        let query = format!(
+            "{}{}recvWindow={}&timestamp={}",
+            query,
+            if query.is_empty() { "" } else { "&" },
+            RECV_WINDOW_MS,
+            Utc::now().timestamp_millis()
+        );
+        let url = format!(
+            "{}{}?{}&signature={}",
+            self.base_url,
+            path,
+            query,
+            Self::signature(secret, &query)?
+        );
+        self.fetch(self.client.get(url).header("X-MBX-APIKEY", key), action)
+            .await
+    }
+}
+
+#[async_trait]
+impl CryptoExchange for BinanceClient {
+    fn name(&self) -> &str {
+        "binance"
+    }
+
+    fn has_credentials(&self) -> bool {
+        self.api_key.is_some() && self.api_secret.is_some()
+    }
+
+    async fn ticker(&self, pair: &str) -> Result<CryptoTicker> {
+        let (symbol, pair) = Self::symbol(pair)?;
+        let url = format!("{}/api/v3/ticker/24hr", self.base_url);
+        let ticker: Ticker24h = self
+            .fetch(
+                self.client.get(url).query(&[("symbol", &symbol)]),
+                "get crypto ticker",
+            )
+            .await?;
+        Ok(CryptoTicker {
+            exchange: self.name().to_string(),
+            pair,
+            last: ticker.last_price,
+            bid: ticker.bid_price,
+            bid_size: ticker.bid_qty,
+            ask: ticker.ask_price,
+            ask_size: ticker.ask_qty,
+            open_24h: ticker.open_price,
+            high_24h: ticker.high_price,
+            low_24h: ticker.low_price,
+            volume_24h: ticker.volume,
+            quote_volume_24h: ticker.quote_volume,
+            change_percent_24h: ticker.price_change_percent,
+            timestamp: millis(ticker.close_time),
+        })
+    }
+
+    async fn candles(
+        &self,
+        pair: &str,
+        interval: CandleInterval,
+        limit: usize,
+    ) -> Result<Vec<Candle>> {
+        let (symbol, _) = Self::symbol(pair)?;
+        let url = format!("{}/api/v3/klines", self.base_url);
+        let request = self.client.get(url).query(&[
+            ("symbol", symbol.as_str()),
+            ("interval", interval.as_str()),
+            ("limit", &limit.clamp(1, 1000).to_string()),
+        ]);
+        let klines: Vec<Kline> = self.fetch(request, "get crypto candles").await?;
+        Ok(klines
+            .into_iter()
+            .map(|k| Candle {
+                open_time: millis(k.0),
+                open: k.1,
+                high: k.2,
+                low: k.3,
+                close: k.4,
+                volume: k.5,
+                close_time: millis(k.6),
+                trades: Some(k.8),
+            })
+            .collect())
+    }
+
+    async fn order_book(&self, pair: &str, depth: usize) -> Result<OrderBook> {
+        let (symbol, pair) = Self::symbol(pair)?;
+        // Ask for the nearest depth Binance accepts and trim to what was asked for
+        let limit = BOOK_DEPTHS
+            .iter()
+            .copied()
+            .find(|d| *d >= depth)
+            .unwrap_or(5000);
+        let url = format!("{}/api/v3/depth", self.base_url);
+        let request = self
+            .client
+            .get(url)
+            .query(&[("symbol", symbol), ("limit", limit.to_string())]);
+        let book: Depth = self.fetch(request, "get crypto order book").await?;
+        let levels = |levels: Vec<Level>| {
+            levels
+                .into_iter()
+                .take(depth)
+                .map(|Level(price, size)| BookLevel { price, size })
+                .collect()
+        };
+        Ok(OrderBook {
+            exchange: self.name().to_string(),
+            pair,
+            bids: levels(book.bids),
+            asks: levels(book.asks),
+            sequence: book.last_update_id,
+        })
+    }
+
+    async fn balances(&self) -> Result<Vec<Balance>> {
+        let account: Account = self
+            .signed(
+                "/api/v3/account",
+                "omitZeroBalances=true",
+                "get crypto balances",
+            )
+            .await?;
+        Ok(account
+            .balances
+            .into_iter()
+            .filter(|b| b.free != 0.0 || b.locked != 0.0)
+            .map(|b| Balance {
+                asset: b.asset,
+                free: b.free,
+                locked: b.locked,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Query;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn signs_queries_and_parses_pairs() {
+        // Example from the Binance API documentation
+        let secret = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+        let query = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        assert_eq!(
+            BinanceClient::signature(secret, query).unwrap(),
+            "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71"
+        );
+        assert_eq!(
+            BinanceClient::symbol("btc/usdt").unwrap(),
+            ("BTCUSDT".to_string(), "BTC-USDT".to_string())
+        );
+        assert!(parse_pair("BTCUSDT").is_err());
+        assert!(parse_pair("BTC-US$").is_err());
+    }
+
+    #[tokio::test]
+    async fn reads_market_data_and_balances() {
+        let app = Router::new()
+            .route(
+                "/api/v3/ticker/24hr",
+                get(|Query(q): Query<HashMap<String, String>>| async move {
+                    if q["symbol"] != "ETHUSDT" {
+                        return Err((
+                            StatusCode::BAD_REQUEST,
+                            Json(json!({"code": -1121, "msg": "Invalid symbol."})),
+                        ));
+                    }
+                    Ok(Json(json!({
+                        "symbol": "ETHUSDT", "lastPrice": "3150.12", "bidPrice": "3150.10", "bidQty": "4.2",
+                        "askPrice": "3150.13", "askQty": "1.5", "openPrice": "3101.00", "highPrice": "3175.00",
+                        "lowPrice": "3088.40", "volume": "251234.1", "quoteVolume": "790000000.5",
+                        "priceChangePercent": "1.584", "closeTime": 1767225600000i64
+                    })))
+                }),
+            )
+            .route(
+                "/api/v3/klines",
+                get(|| async {
+                    Json(json!([[1767222000000i64, "3140.0", "3160.0", "3130.0", "3150.0", "812.5",
+                        1767225599999i64, "2556000.1", 10432, "400.1", "1260000.0", "0"]]))
+                }),
+            )
+            .route(
+                "/api/v3/depth",
+                get(|Query(q): Query<HashMap<String, String>>| async move {
+                    assert_eq!(q["limit"], "5");
+                    Json(json!({"lastUpdateId": 991, "bids": [["3150.10", "4.2"], ["3150.00", "2"]],
+                        "asks": [["3150.13", "1.5"], ["3150.20", "3"]]}))
+                }),
+            )
+            .route(
+                "/api/v3/account",
+                get(|headers: HeaderMap, Query(q): Query<HashMap<String, String>>| async move {
+                    assert_eq!(headers["X-MBX-APIKEY"], "key");
+                    assert_eq!(q["signature"].len(), 64);
+                    Json(json!({"balances": [
+                        {"asset": "BTC", "free": "0.015", "locked": "0.0"},
+                        {"asset": "USDT", "free": "0", "locked": "0"}
+                    ]}))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let public = BinanceClient::new(&CryptoConfig {
+            base_url: Some(base_url.clone()),
+            ..CryptoConfig::default()
+        })
+        .unwrap();
+        let ticker = public.ticker("eth-usdt").await.unwrap();
+        assert_eq!((ticker.pair.as_str(), ticker.last), ("ETH-USDT", 3150.12));
+        let missing = public.ticker("FOO-BAR").await.unwrap_err();
+        assert!(missing.to_string().contains("Invalid symbol"));
+
+        let candles = public
+            .candles("ETH-USDT", CandleInterval::OneHour, 1)
+            .await
+            .unwrap();
+        assert_eq!((candles[0].close, candles[0].trades), (3150.0, Some(10432)));
+
+        let book = public.order_book("ETH-USDT", 1).await.unwrap();
+        assert_eq!(
+            book.bids,
+            [BookLevel {
+                price: 3150.10,
+                size: 4.2
+            }]
+        );
+        assert!((book.spread().unwrap() - 0.03).abs() < 1e-9);
+        assert!(public.balances().await.is_err());
+
+        let signed = BinanceClient::new(&CryptoConfig {
+            api_key: Some("key".to_string()),
+            api_secret: Some("secret".to_string()),
+            base_url: Some(base_url),
+            ..CryptoConfig::default()
+        })
+        .unwrap();
+        let balances = signed.balances().await.unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(
+            (balances[0].asset.as_str(), balances[0].free),
+            ("BTC", 0.015)
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Crypto exchanges behind one interface
+//!
+//! Quotes, candles and order books use the exchanges' public market data;
+//! balances need an API key. Tools take an `Arc<dyn CryptoExchange>` so more
+//! exchanges can be added next to Binance.
+
+pub mod binance;
+
+pub use binance::BinanceClient;
+
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Supported exchanges
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExchangeKind {
+    #[default]
+    Binance,
+}
+
+/// Crypto exchange settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CryptoConfig {
+    #[serde(default)]
+    pub exchange: ExchangeKind,
+    /// API key, needed for balances only
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub api_secret: Option<String>,
+    /// Override for the REST API, e.g. `https://api.binance.us`
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+impl CryptoConfig {
+    /// Binance with `BINANCE_API_KEY` and `BINANCE_API_SECRET` when set
+    pub fn from_env() -> Self {
+        Self {
+            exchange: ExchangeKind::Binance,
+            api_key: std::env::var("BINANCE_API_KEY").ok(),
+            api_secret: std::env::var("BINANCE_API_SECRET").ok(),
+            base_url: std::env::var("BINANCE_API_URL").ok(),
+        }
+    }
+}
+
+/// Last trade, best bid and ask and 24 hour statistics for a pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoTicker {
+    pub exchange: String,
+    pub pair: String,
+    pub last: f64,
+    pub bid: f64,
+    pub bid_size: f64,
+    pub ask: f64,
+    pub ask_size: f64,
+    pub open_24h: f64,
+    pub high_24h: f64,
+    pub low_24h: f64,
+    /// Base asset volume over 24 hours
+    pub volume_24h: f64,
+    /// Quote asset volume over 24 hours
+    pub quote_volume_24h: f64,
+    pub change_percent_24h: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Candle length
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CandleInterval {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[default]
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "4h")]
+    FourHours,
+    #[serde(rename = "1d")]
+    OneDay,
+    #[serde(rename = "1w")]
+    OneWeek,
+}
+
+impl CandleInterval {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::OneMinute => "1m",
+            Self::FiveMinutes => "5m",
+            Self::FifteenMinutes => "15m",
+            Self::OneHour => "1h",
+            Self::FourHours => "4h",
+            Self::OneDay => "1d",
+            Self::OneWeek => "1w",
+        }
+    }
+}
+
+/// Open, high, low, close and volume over one interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub open_time: DateTime<Utc>,
+    pub close_time: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Base asset volume
+    pub volume: f64,
+    pub trades: Option<u64>,
+}
+
+/// Price level in an order book
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct BookLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+/// Snapshot of the best bids and asks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub exchange: String,
+    pub pair: String,
+    /// Highest first
+    pub bids: Vec<BookLevel>,
+    /// Lowest first
+    pub asks: Vec<BookLevel>,
+    /// Exchange update ID the snapshot was taken at
+    pub sequence: Option<u64>,
+}
+
+impl OrderBook {
+    /// Lowest ask minus highest bid
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.asks.first()?.price - self.bids.first()?.price)
+    }
+}
+
+/// Holdings of one asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balance {
+    pub asset: String,
+    /// Available to trade
+    pub free: f64,
+    /// Held by open orders
+    pub locked: f64,
+}
+
+/// A crypto exchange
+#[async_trait]
+pub trait CryptoExchange: Send + Sync {
+    /// Exchange name, as reported in results
+    fn name(&self) -> &str;
+
+    /// Whether balances can be read
+    fn has_credentials(&self) -> bool;
+
+    /// Current ticker for a pair like `BTC-USDT`
+    async fn ticker(&self, pair: &str) -> Result<CryptoTicker>;
+
+    /// Most recent `limit` candles, oldest first
+    async fn candles(
+        &self,
+        pair: &str,
+        interval: CandleInterval,
+        limit: usize,
+    ) -> Result<Vec<Candle>>;
+
+    /// Best `depth` bids and asks
+    async fn order_book(&self, pair: &str, depth: usize) -> Result<OrderBook>;
+
+    /// Non-zero balances of the account
+    async fn balances(&self) -> Result<Vec<Balance>>;
+}
+
+/// Exchange client for `config`
+pub fn connect(config: &CryptoConfig) -> Result<Arc<dyn CryptoExchange>> {
+    match config.exchange {
+        ExchangeKind::Binance => Ok(Arc::new(BinanceClient::new(config)?)),
+    }
+}
+
+/// Split a pair written as `BTC-USDT`, `btc/usdt` or `BTC_USDT` into base and quote
+pub fn parse_pair(pair: &str) -> Result<(String, String)> {
+    let pair = pair.trim().to_uppercase();
+    let parts: Vec<&str> = pair.split(['-', '/', '_']).collect();
+    match parts.as_slice() {
+        [base, quote]
+            if !base.is_empty()
+                && !quote.is_empty()
+                && pair
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-/_".contains(c)) =>
+        {
+            Ok((base.to_string(), quote.to_string()))
+        }
+        _ => Err(Error::validation_with_field(
+            format!("Expected a pair like BTC-USDT, got {:?}", pair),
+            "pair",
+        )),
+    }
+}
@@ -0,0 +1,323 @@
+//! Receipt fields from recognized text
+//!
+//! Receipts and invoices have no common layout, so fields are found by the
+//! words around them: the total on a line saying total or amount due, tax
+//! on lines naming VAT, GST or sales tax, and the merchant at the top.
+//! Numeric dates written with dots are day-first; with slashes they are
+//! month-first for dollar receipts and day-first otherwise, unless only one
+//! reading is a valid date.
+
+use crate::finance::ledger::csv::amount;
+use chrono::{Days, NaiveDate};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Words on the line of a total
+const TOTAL_WORDS: &[&str] = &[
+    "total",
+    "amount due",
+    "balance due",
+    "to pay",
+    "summe",
+    "gesamt",
+    "betrag",
+    "importe",
+    "montant",
+];
+
+/// Words on the line of a total before tax
+const SUBTOTAL_WORDS: &[&str] = &[
+    "subtotal",
+    "sub total",
+    "sub-total",
+    "zwischensumme",
+    "netto",
+    "net amount",
+];
+
+/// Words on the line of a tax amount
+const TAX_WORDS: &[&str] = &[
+    "tax", "vat", "gst", "hst", "pst", "mwst", "ust", "iva", "tva",
+];
+
+/// Words of header lines that do not name the merchant
+const NOT_MERCHANT: &[&str] = &[
+    "receipt", "invoice", "rechnung", "quittung", "factura", "tel", "www.",
+];
+
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Fields found on a receipt; any may be missing
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ReceiptFields {
+    pub merchant: Option<String>,
+    pub date: Option<NaiveDate>,
+    /// Amount paid, tax included
+    pub total: Option<f64>,
+    pub subtotal: Option<f64>,
+    pub tax: Option<f64>,
+    pub currency: Option<String>,
+}
+
+fn amount_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"[-(]?[$€£]?\s?\d{1,3}(?:[.,' ]\d{3})*[.,]\d{2}\b\)?").expect("amount pattern")
+    })
+}
+
+fn date_patterns() -> &'static [Regex; 4] {
+    static PATTERNS: OnceLock<[Regex; 4]> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            Regex::new(r"\b(\d{4})[-/.](\d{1,2})[-/.](\d{1,2})\b").expect("iso date"),
+            Regex::new(r"\b(\d{1,2})([./-])(\d{1,2})[./-](\d{4}|\d{2})\b").expect("numeric date"),
+            Regex::new(r"(?i)\b(\d{1,2})\.?\s+([a-z]{3})[a-z]*\.?,?\s+(\d{4})\b")
+                .expect("day month"),
+            Regex::new(r"(?i)\b([a-z]{3})[a-z]*\.?\s+(\d{1,2}),?\s+(\d{4})\b").expect("month day"),
+        ]
+    })
+}
+
+/// Amounts on a line, in order
+fn amounts(line: &str) -> Vec<f64> {
+    amount_pattern()
+        .find_iter(line)
+        .filter_map(|m| amount(&m.as_str().replace([' ', '\''], "")))
+        .collect()
+}
+
+/// Whether the line has one of `words`, or with `prefix` a word starting
+/// with one, so `Gesamtbetrag` is a total but `Subtotal` is not
+fn has_word(line: &str, words: &[&str], prefix: bool) -> bool {
+    let line = line.to_lowercase();
+    words.iter().any(|w| {
+        line.match_indices(w).any(|(at, _)| {
+            let before = line[..at].chars().next_back();
+            let after = line[at + w.len()..].chars().next();
+            !before.is_some_and(char::is_alphabetic)
+                && (prefix || !after.is_some_and(char::is_alphabetic))
+        })
+    })
+}
+
+fn month(name: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    MONTHS
+        .iter()
+        .position(|m| name.starts_with(m))
+        .map(|i| i as u32 + 1)
+}
+
+fn year(text: &str) -> Option<i32> {
+    let year: i32 = text.parse().ok()?;
+    Some(if year < 100 { 2000 + year } else { year })
+}
+
+/// First date in the text
+fn find_date(text: &str, dollars: bool) -> Option<NaiveDate> {
+    let [iso, numeric, day_month, month_day] = date_patterns();
+    let mut found: Vec<(usize, NaiveDate)> = Vec::new();
+    for c in iso.captures_iter(text) {
+        if let Some(date) =
+            NaiveDate::from_ymd_opt(c[1].parse().ok()?, c[2].parse().ok()?, c[3].parse().ok()?)
+        {
+            found.push((c.get(0)?.start(), date));
+        }
+    }
+    for c in numeric.captures_iter(text) {
+        let (a, b, y): (u32, u32, i32) = (c[1].parse().ok()?, c[3].parse().ok()?, year(&c[4])?);
+        let month_first = &c[2] == "/" && dollars;
+        let (day, month) = if month_first { (b, a) } else { (a, b) };
+        let date = NaiveDate::from_ymd_opt(y, month, day)
+            .or_else(|| NaiveDate::from_ymd_opt(y, day, month));
+        if let Some(date) = date {
+            found.push((c.get(0)?.start(), date));
+        }
+    }
+    for c in day_month.captures_iter(text) {
+        if let Some(date) =
+            month(&c[2]).and_then(|m| NaiveDate::from_ymd_opt(year(&c[3])?, m, c[1].parse().ok()?))
+        {
+            found.push((c.get(0)?.start(), date));
+        }
+    }
+    for c in month_day.captures_iter(text) {
+        if let Some(date) =
+            month(&c[1]).and_then(|m| NaiveDate::from_ymd_opt(year(&c[3])?, m, c[2].parse().ok()?))
+        {
+            found.push((c.get(0)?.start(), date));
+        }
+    }
+    found
+        .into_iter()
+        .min_by_key(|(at, _)| *at)
+        .map(|(_, date)| date)
+}
+
+fn find_currency(text: &str) -> Option<String> {
+    const CODES: &[(&str, &str)] = &[
+        ("$", "USD"),
+        ("€", "EUR"),
+        ("£", "GBP"),
+        ("USD", "USD"),
+        ("EUR", "EUR"),
+        ("GBP", "GBP"),
+        ("CHF", "CHF"),
+        ("CAD", "CAD"),
+        ("AUD", "AUD"),
+    ];
+    CODES
+        .iter()
+        .filter_map(|(mark, code)| text.find(mark).map(|at| (at, code)))
+        .min_by_key(|(at, _)| *at)
+        .map(|(_, code)| code.to_string())
+}
+
+fn find_merchant(lines: &[&str]) -> Option<String> {
+    lines.iter().take(6).find_map(|line| {
+        let line = line.trim().trim_matches(|c: char| !c.is_alphanumeric());
+        let letters = line.chars().filter(|c| c.is_alphabetic()).count();
+        (letters >= 3
+            && letters * 2 >= line.chars().filter(|c| !c.is_whitespace()).count()
+            && !has_word(line, NOT_MERCHANT, false)
+            && !has_word(line, TOTAL_WORDS, true))
+        .then(|| line.to_string())
+    })
+}
+
+/// Fields of the recognized text of a receipt or invoice
+pub fn extract(text: &str) -> ReceiptFields {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let currency = find_currency(text);
+    let mut totals = Vec::new();
+    let mut subtotal = None;
+    let mut taxes: Vec<f64> = Vec::new();
+    for line in &lines {
+        let Some(&last) = amounts(line).last() else {
+            continue;
+        };
+        if has_word(line, SUBTOTAL_WORDS, true) {
+            subtotal = Some(last);
+        } else if has_word(line, TAX_WORDS, false) && !has_word(line, &["incl", "inkl"], true) {
+            // `Total tax` and `Tax total` are tax; `Total incl. VAT` is the total
+            taxes.push(last);
+        } else if has_word(line, TOTAL_WORDS, true) {
+            totals.push(last);
+        }
+    }
+    // Totals lines also list savings and change given, so the largest wins
+    let total = totals
+        .into_iter()
+        .reduce(f64::max)
+        .or_else(|| lines.iter().flat_map(|l| amounts(l)).reduce(f64::max));
+    let tax = (!taxes.is_empty()).then(|| (taxes.iter().sum::<f64>() * 100.0).round() / 100.0);
+    ReceiptFields {
+        merchant: find_merchant(&lines),
+        date: find_date(text, currency.as_deref() == Some("USD")),
+        total,
+        subtotal,
+        tax,
+        currency,
+    }
+}
+
+/// Problems with extracted fields; errors stop a receipt being stored,
+/// warnings are kept with it
+pub fn validate(fields: &ReceiptFields, today: NaiveDate) -> (Vec<String>, Vec<String>) {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    match fields.total {
+        None => errors.push("no total found".to_string()),
+        Some(total) if total <= 0.0 => errors.push(format!("total {:.2} is not positive", total)),
+        Some(_) => {}
+    }
+    match fields.date {
+        None => errors.push("no date found".to_string()),
+        Some(date) if date > today + Days::new(1) => {
+            errors.push(format!("date {} is in the future", date))
+        }
+        Some(date) if date + Days::new(366) < today => {
+            warnings.push(format!("date {} is over a year ago", date))
+        }
+        Some(_) => {}
+    }
+    if fields.merchant.is_none() {
+        warnings.push("no merchant found".to_string());
+    }
+    if let (Some(total), Some(tax)) = (fields.total, fields.tax) {
+        if tax < 0.0 || tax >= total {
+            errors.push(format!(
+                "tax {:.2} is not below the total {:.2}",
+                tax, total
+            ));
+        } else if tax > total * 0.3 {
+            warnings.push(format!("tax {:.2} is over 30% of the total", tax));
+        }
+        if let Some(subtotal) = fields.subtotal {
+            if (subtotal + tax - total).abs() > 0.02 {
+                warnings.push(format!(
+                    "subtotal {:.2} plus tax {:.2} does not add up to the total {:.2}",
+                    subtotal, tax, total
+                ));
+            }
+        }
+    }
+    (errors, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_fields_of_receipts_and_invoices() {
+        let receipt = "\
+            CORNER CAFE\n\
+            12 Main St, Springfield\n\
+            Tel 555-0100\n\
+            03/04/2024 12:31\n\
+            2 x Latte        $9.00\n\
+            Bagel            $3.50\n\
+            Subtotal        $12.50\n\
+            Sales Tax        $1.03\n\
+            TOTAL           $13.53\n\
+            Card            $13.53\n";
+        let fields = extract(receipt);
+        assert_eq!(fields.merchant.as_deref(), Some("CORNER CAFE"));
+        assert_eq!(fields.date, NaiveDate::from_ymd_opt(2024, 3, 4));
+        assert_eq!(fields.total, Some(13.53));
+        assert_eq!(fields.subtotal, Some(12.5));
+        assert_eq!(fields.tax, Some(1.03));
+        assert_eq!(fields.currency.as_deref(), Some("USD"));
+
+        let invoice = "\
+            Rechnung Nr. 2024-117\n\
+            Hosting Huber GmbH\n\
+            Datum: 15.03.2024\n\
+            Server März          100,00 EUR\n\
+            Netto                100,00 EUR\n\
+            MwSt 19%              19,00 EUR\n\
+            Gesamtbetrag inkl. MwSt 119,00 EUR\n";
+        let fields = extract(invoice);
+        assert_eq!(fields.merchant.as_deref(), Some("Hosting Huber GmbH"));
+        assert_eq!(fields.date, NaiveDate::from_ymd_opt(2024, 3, 15));
+        assert_eq!(fields.tax, Some(19.0));
+        assert_eq!(fields.total, Some(119.0));
+        assert_eq!(fields.currency.as_deref(), Some("EUR"));
+
+        let today = NaiveDate::from_ymd_opt(2024, 4, 1).unwrap();
+        assert_eq!(validate(&fields, today), (vec![], vec![]));
+        let wrong = ReceiptFields {
+            subtotal: Some(90.0),
+            date: NaiveDate::from_ymd_opt(2024, 5, 1),
+            ..fields
+        };
+        let (errors, warnings) = validate(&wrong, today);
+        assert_eq!(errors, vec!["date 2024-05-01 is in the future"]);
+        assert_eq!(warnings.len(), 1);
+    }
+}
@@ -0,0 +1,629 @@
+//! Receipt and invoice expenses
+//!
+//! A scanned receipt or invoice is turned into text (see [`ocr`]), its
+//! merchant, date, total and tax are read from the text, with the model
+//! filling in what the patterns miss when one is configured, and checked
+//! before the expense is stored. Each expense is also recorded in the
+//! ledger as spending, where it is categorized like any other transaction.
+//! Receipts are identified by their contents, so scanning one twice returns
+//! the expense stored the first time.
+
+pub mod extract;
+pub mod ocr;
+
+pub use extract::ReceiptFields;
+pub use ocr::OcrConfig;
+
+use crate::ai::provider::{ChatMessage, LlmProvider};
+use crate::error::{Error, Result};
+use crate::finance::ledger::{Ledger, Transaction};
+use crate::tools::{call_result, parse_date, ToolDefinition};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Characters of receipt text sent to the model
+const MAX_PROMPT_CHARS: usize = 6000;
+
+/// Expenses configuration, under `finance.expenses`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExpensesConfig {
+    /// Where expenses are persisted; in memory when unset
+    pub path: Option<PathBuf>,
+    /// Ledger account expenses are paid from unless a scan names one
+    pub account: String,
+    pub ocr: OcrConfig,
+}
+
+impl Default for ExpensesConfig {
+    fn default() -> Self {
+        Self {
+            path: std::env::var("EXPENSES_PATH").ok().map(PathBuf::from),
+            account: "Receipts".to_string(),
+            ocr: OcrConfig::default(),
+        }
+    }
+}
+
+/// A stored receipt or invoice
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Expense {
+    /// Derived from the file's contents
+    pub id: String,
+    pub file: PathBuf,
+    pub merchant: String,
+    pub date: NaiveDate,
+    /// Amount paid, tax included
+    pub total: f64,
+    #[serde(default)]
+    pub tax: Option<f64>,
+    pub currency: String,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Id of the ledger transaction
+    pub transaction: String,
+    /// Doubts about the extracted fields, e.g. a subtotal that does not add up
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    pub scanned_at: DateTime<Utc>,
+}
+
+/// Fields given with a scan, which take precedence over those read
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanRequest {
+    pub project: Option<String>,
+    pub category: Option<String>,
+    pub account: Option<String>,
+    pub merchant: Option<String>,
+    pub date: Option<NaiveDate>,
+    pub total: Option<f64>,
+    pub tax: Option<f64>,
+    pub currency: Option<String>,
+}
+
+/// Expenses of one project in one currency
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExpenseTotal {
+    /// None for expenses without a project
+    pub project: Option<String>,
+    pub currency: String,
+    pub count: usize,
+    pub total: f64,
+    pub tax: f64,
+}
+
+/// Expenses in a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpenseReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub project: Option<String>,
+    pub totals: Vec<ExpenseTotal>,
+    pub expenses: Vec<Expense>,
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+impl ExpenseReport {
+    /// One row per expense, oldest first
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("date,merchant,project,category,total,tax,currency,file,warnings\n");
+        for expense in &self.expenses {
+            let row = [
+                expense.date.to_string(),
+                csv_field(&expense.merchant),
+                csv_field(expense.project.as_deref().unwrap_or_default()),
+                csv_field(expense.category.as_deref().unwrap_or_default()),
+                format!("{:.2}", expense.total),
+                expense.tax.map(|t| format!("{:.2}", t)).unwrap_or_default(),
+                expense.currency.clone(),
+                csv_field(&expense.file.display().to_string()),
+                csv_field(&expense.warnings.join("; ")),
+            ];
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Expenses from `from` to `to`, of `project` when given, with totals per
+/// project and currency
+pub fn expense_report(
+    expenses: &[Expense],
+    project: Option<&str>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> ExpenseReport {
+    let mut selected: Vec<Expense> = expenses
+        .iter()
+        .filter(|e| e.date >= from && e.date <= to)
+        .filter(|e| project.is_none() || e.project.as_deref() == project)
+        .cloned()
+        .collect();
+    selected.sort_by(|a, b| (a.date, &a.merchant).cmp(&(b.date, &b.merchant)));
+    let mut totals: BTreeMap<(Option<String>, String), ExpenseTotal> = BTreeMap::new();
+    for expense in &selected {
+        let total = totals
+            .entry((expense.project.clone(), expense.currency.clone()))
+            .or_insert_with(|| ExpenseTotal {
+                project: expense.project.clone(),
+                currency: expense.currency.clone(),
+                count: 0,
+                total: 0.0,
+                tax: 0.0,
+            });
+        total.count += 1;
+        total.total += expense.total;
+        total.tax += expense.tax.unwrap_or_default();
+    }
+    ExpenseReport {
+        from,
+        to,
+        project: project.map(str::to_string),
+        totals: totals.into_values().collect(),
+        expenses: selected,
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ExpenseStore {
+    /// Expenses by id
+    expenses: BTreeMap<String, Expense>,
+}
+
+/// Scanned receipts and invoices, recorded in the ledger
+pub struct Expenses {
+    config: ExpensesConfig,
+    ledger: Arc<Ledger>,
+    llm: Option<Arc<dyn LlmProvider>>,
+    store: RwLock<ExpenseStore>,
+}
+
+impl Expenses {
+    /// Open the store, loading expenses from the configured path if it exists
+    pub async fn open(config: ExpensesConfig, ledger: Arc<Ledger>) -> Result<Self> {
+        let store = match &config.path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse expenses {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => ExpenseStore::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read expenses: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => ExpenseStore::default(),
+        };
+        Ok(Self {
+            config,
+            ledger,
+            llm: None,
+            store: RwLock::new(store),
+        })
+    }
+
+    /// Read fields the patterns miss with the given model
+    pub fn with_llm(mut self, provider: Arc<dyn LlmProvider>) -> Self {
+        self.llm = Some(provider);
+        self
+    }
+
+    async fn persist(&self, store: &ExpenseStore) -> Result<()> {
+        let Some(path) = &self.config.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create expenses directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(format!("Failed to write expenses: {}", e), temp.clone())
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to replace expenses: {}", e), path.clone())
+        })
+    }
+
+    /// Ask the model for the fields `fields` lacks
+    async fn complete_fields(
+        llm: &dyn LlmProvider,
+        text: &str,
+        fields: &mut ReceiptFields,
+    ) -> Result<()> {
+        let text: String = text.chars().take(MAX_PROMPT_CHARS).collect();
+        let reply = llm
+            .complete_json(&[
+                ChatMessage::system(
+                    "You read OCR text of receipts and invoices. Reply with JSON only: \
+                     {\"merchant\": string, \"date\": \"YYYY-MM-DD\", \"total\": number, \"tax\": number, \"currency\": \"ISO 4217 code\"}. \
+                     The total is the amount paid including tax. Use null for anything the text does not show.",
+                ),
+                ChatMessage::user(text),
+            ])
+            .await?;
+        let number = |key: &str| reply[key].as_f64().filter(|v| v.is_finite());
+        let string = |key: &str| {
+            reply[key]
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        fields.merchant = fields.merchant.take().or_else(|| string("merchant"));
+        fields.date = fields.date.or_else(|| {
+            string("date").and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+        });
+        fields.total = fields.total.or_else(|| number("total"));
+        fields.tax = fields.tax.or_else(|| number("tax"));
+        fields.currency = fields
+            .currency
+            .take()
+            .or_else(|| string("currency").map(|c| c.to_uppercase()));
+        Ok(())
+    }
+
+    /// Read a receipt or invoice, store it and record it in the ledger
+    ///
+    /// Returns the expense and whether it is new.
+    pub async fn scan(&self, path: &Path, request: ScanRequest) -> Result<(Expense, bool)> {
+        let bytes = tokio::fs::read(path).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to read receipt: {}", e), path.to_path_buf())
+        })?;
+        let id = format!("{:x}", Sha256::digest(&bytes))[..16].to_string();
+        if let Some(expense) = self.store.read().await.expenses.get(&id) {
+            return Ok((expense.clone(), false));
+        }
+
+        let text = ocr::text(&self.config.ocr, path, &bytes).await?;
+        let mut fields = extract::extract(&text);
+        let needed = |f: &ReceiptFields| {
+            f.merchant.is_none() && request.merchant.is_none()
+                || f.date.is_none() && request.date.is_none()
+                || f.total.is_none() && request.total.is_none()
+        };
+        if let (Some(llm), true) = (&self.llm, needed(&fields)) {
+            if let Err(e) = Self::complete_fields(llm.as_ref(), &text, &mut fields).await {
+                tracing::warn!("Model could not read receipt {}: {}", path.display(), e);
+            }
+        }
+        let fields = ReceiptFields {
+            merchant: request.merchant.clone().or(fields.merchant),
+            date: request.date.or(fields.date),
+            total: request.total.or(fields.total),
+            subtotal: fields.subtotal,
+            tax: request.tax.or(fields.tax),
+            currency: request.currency.clone().or(fields.currency),
+        };
+        let (errors, warnings) = extract::validate(&fields, Utc::now().date_naive());
+        if !errors.is_empty() {
+            return Err(Error::validation_with_field(
+                format!(
+                    "Could not read {}: {}; give the missing fields with the scan",
+                    path.display(),
+                    errors.join(", ")
+                ),
+                "path",
+            ));
+        }
+        let (Some(date), Some(total)) = (fields.date, fields.total) else {
+            return Err(Error::internal("validated receipt lacks a date or total"));
+        };
+        let merchant = fields
+            .merchant
+            .unwrap_or_else(|| "Unknown merchant".to_string());
+        let currency = fields
+            .currency
+            .unwrap_or_else(|| self.ledger.currency().to_string());
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let transaction = Transaction {
+            date,
+            payee: merchant.clone(),
+            description: Some(match &request.project {
+                Some(project) => format!("Receipt {} ({})", file_name, project),
+                None => format!("Receipt {}", file_name),
+            }),
+            amount: -total,
+            currency: currency.clone(),
+            account: request
+                .account
+                .clone()
+                .unwrap_or_else(|| self.config.account.clone()),
+            categorized_by: request.category.as_ref().map(|_| "manual".to_string()),
+            category: request.category.clone(),
+            ..Default::default()
+        };
+        let transaction_id = transaction.fingerprint();
+        self.ledger.import(vec![transaction]).await?;
+
+        let expense = Expense {
+            id: id.clone(),
+            file: path.to_path_buf(),
+            merchant,
+            date,
+            total,
+            tax: fields.tax,
+            currency,
+            project: request.project,
+            category: request.category,
+            transaction: transaction_id,
+            warnings,
+            scanned_at: Utc::now(),
+        };
+        let mut store = self.store.write().await;
+        store.expenses.insert(id, expense.clone());
+        self.persist(&store).await?;
+        Ok((expense, true))
+    }
+
+    /// Expenses from `from` to `to`, of `project` when given
+    pub async fn report(
+        &self,
+        project: Option<&str>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> ExpenseReport {
+        let store = self.store.read().await;
+        let expenses: Vec<Expense> = store.expenses.values().cloned().collect();
+        expense_report(&expenses, project, from, to)
+    }
+
+    /// Get tool definitions for expenses
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "scan_expense",
+                "Read a receipt or invoice image or PDF with OCR, extract the merchant, date, total and tax, and record it as an expense in the ledger",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Receipt or invoice file: PDF, PNG, JPEG or TIFF"},
+                        "project": {"type": "string", "description": "Project the expense is billed to"},
+                        "category": {"type": "string", "description": "Ledger category, e.g. Travel:Meals; ledger rules apply when omitted"},
+                        "account": {"type": "string", "description": "Account it was paid from; finance.expenses.account when omitted"},
+                        "merchant": {"type": "string", "description": "Merchant, instead of the one read"},
+                        "date": {"type": "string", "description": "Date (YYYY-MM-DD), instead of the one read"},
+                        "amount": {"type": "number", "exclusiveMinimum": 0, "description": "Total paid including tax, instead of the one read"},
+                        "tax": {"type": "number", "minimum": 0, "description": "Tax included in the total, instead of the one read"},
+                        "currency": {"type": "string", "description": "Currency code, instead of the one read"}
+                    },
+                    "required": ["path"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "expense_report",
+                "Report scanned expenses in a period with totals and tax per project, optionally exported as CSV",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "project": {"type": "string", "description": "Only this project's expenses"},
+                        "from": {"type": "string", "description": "First day (YYYY-MM-DD); the start of the current month when omitted"},
+                        "to": {"type": "string", "description": "Last day (YYYY-MM-DD); today when omitted"},
+                        "format": {"type": "string", "enum": ["text", "csv"], "default": "text"},
+                        "path": {"type": "string", "description": "Write the report as CSV to this file"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    /// Execute an expenses tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let field = |key: &str| {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+        };
+        let today = Utc::now().date_naive();
+        match name {
+            "scan_expense" => {
+                let path = field("path")
+                    .ok_or_else(|| Error::validation_with_field("path is required", "path"))?;
+                let date = match field("date") {
+                    Some(date) => Some(parse_date(Some(date), "date", today)?),
+                    None => None,
+                };
+                let request = ScanRequest {
+                    project: field("project").map(str::to_string),
+                    category: field("category").map(str::to_string),
+                    account: field("account").map(str::to_string),
+                    merchant: field("merchant").map(str::to_string),
+                    date,
+                    total: parameters.get("amount").and_then(|v| v.as_f64()),
+                    tax: parameters.get("tax").and_then(|v| v.as_f64()),
+                    currency: field("currency").map(|c| c.to_uppercase()),
+                };
+                let (expense, new) = self.scan(Path::new(path), request).await?;
+                let mut text = format!(
+                    "{} {} on {}: {:.2} {}",
+                    if new { "Recorded" } else { "Already recorded" },
+                    expense.merchant,
+                    expense.date,
+                    expense.total,
+                    expense.currency
+                );
+                if let Some(tax) = expense.tax {
+                    text.push_str(&format!(" (tax {:.2})", tax));
+                }
+                if let Some(project) = &expense.project {
+                    text.push_str(&format!(" for {}", project));
+                }
+                for warning in &expense.warnings {
+                    text.push_str(&format!("\nCheck: {}", warning));
+                }
+                Ok(call_result(text, serde_json::to_value(&expense)?))
+            }
+            "expense_report" => {
+                let to = parse_date(field("to"), "to", today)?;
+                let from = parse_date(field("from"), "from", today.with_day(1).unwrap_or(today))?;
+                if from > to {
+                    return Err(Error::validation_with_field(
+                        "from must not be after to",
+                        "from",
+                    ));
+                }
+                let report = self.report(field("project"), from, to).await;
+                if let Some(path) = field("path") {
+                    tokio::fs::write(path, report.to_csv()).await.map_err(|e| {
+                        Error::io_with_path(
+                            format!("Failed to write expense report: {}", e),
+                            PathBuf::from(path),
+                        )
+                    })?;
+                }
+                let text = if field("format") == Some("csv") {
+                    report.to_csv()
+                } else {
+                    let mut lines = vec![format!(
+                        "{} expenses from {} to {}",
+                        report.expenses.len(),
+                        from,
+                        to
+                    )];
+                    lines.extend(report.totals.iter().map(|t| {
+                        format!(
+                            "{}: {} receipts, {:.2} {} (tax {:.2})",
+                            t.project.as_deref().unwrap_or("No project"),
+                            t.count,
+                            t.total,
+                            t.currency,
+                            t.tax
+                        )
+                    }));
+                    if let Some(path) = field("path") {
+                        lines.push(format!("Written to {}", path));
+                    }
+                    lines.join("\n")
+                };
+                Ok(call_result(text, serde_json::to_value(&report)?))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finance::ledger::LedgerConfig;
+
+    #[tokio::test]
+    async fn scans_receipts_into_the_ledger_and_reports_by_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = Arc::new(
+            Ledger::open(LedgerConfig {
+                path: None,
+                ..Default::default()
+            })
+            .await
+            .unwrap(),
+        );
+        let expenses = Expenses::open(
+            ExpensesConfig {
+                path: Some(dir.path().join("expenses.json")),
+                ..Default::default()
+            },
+            Arc::clone(&ledger),
+        )
+        .await
+        .unwrap();
+        let receipt = dir.path().join("cafe.txt");
+        std::fs::write(
+            &receipt,
+            "CORNER CAFE\n03/04/2024\nSubtotal $12.50\nSales Tax $1.03\nTOTAL $13.53\n",
+        )
+        .unwrap();
+        let (expense, new) = expenses
+            .scan(
+                &receipt,
+                ScanRequest {
+                    project: Some("Acme launch".to_string()),
+                    category: Some("Travel:Meals".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(new);
+        assert_eq!(expense.merchant, "CORNER CAFE");
+        assert_eq!((expense.total, expense.tax), (13.53, Some(1.03)));
+        assert_eq!(expense.warnings, vec!["date 2024-03-04 is over a year ago"]);
+        let (_, new) = expenses
+            .scan(&receipt, ScanRequest::default())
+            .await
+            .unwrap();
+        assert!(!new);
+
+        // Recorded as spending in the ledger
+        let march = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let budget = ledger.budget_report(march).await;
+        assert_eq!(budget.spent, 13.53);
+        assert_eq!(budget.lines[0].category, "Travel");
+
+        let unreadable = dir.path().join("blank.txt");
+        std::fs::write(&unreadable, "Thank you for shopping\n").unwrap();
+        let error = expenses
+            .scan(&unreadable, ScanRequest::default())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("no total found"));
+
+        let report = expenses
+            .report(None, march, NaiveDate::from_ymd_opt(2024, 3, 31).unwrap())
+            .await;
+        assert_eq!(report.totals.len(), 1);
+        assert_eq!(report.totals[0].project.as_deref(), Some("Acme launch"));
+        assert_eq!(
+            report.to_csv().lines().nth(1).unwrap(),
+            format!(
+                "2024-03-04,CORNER CAFE,Acme launch,Travel:Meals,13.53,1.03,USD,{},date 2024-03-04 is over a year ago",
+                receipt.display()
+            )
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Text of receipts and invoices
+//!
+//! PDFs with a text layer are read by the office parsers. Photos, and PDFs
+//! that are scanned pages, go through Tesseract; scanned PDFs are first
+//! rendered to images with `pdftoppm` from Poppler.
+
+use crate::error::{Error, Result};
+use crate::office::parsers::{self, DocumentFormat};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::process::Command;
+
+/// Resolution scanned PDF pages are rendered at
+const RENDER_DPI: &str = "300";
+
+/// OCR programs, under `finance.expenses.ocr`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrConfig {
+    pub tesseract: String,
+    /// Tesseract languages, e.g. `eng+deu`
+    pub languages: String,
+    pub pdftoppm: String,
+}
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        Self {
+            tesseract: "tesseract".to_string(),
+            languages: std::env::var("OCR_LANGUAGES").unwrap_or_else(|_| "eng".to_string()),
+            pdftoppm: "pdftoppm".to_string(),
+        }
+    }
+}
+
+/// Kind of file by its first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanKind {
+    Pdf,
+    Image,
+    Text,
+}
+
+pub fn detect(bytes: &[u8], file_name: Option<&str>) -> Option<ScanKind> {
+    const IMAGES: &[&[u8]] = &[
+        b"\x89PNG",
+        b"\xff\xd8\xff",
+        b"II*\x00",
+        b"MM\x00*",
+        b"GIF8",
+        b"BM",
+    ];
+    if IMAGES.iter().any(|magic| bytes.starts_with(magic))
+        || (bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP"))
+    {
+        return Some(ScanKind::Image);
+    }
+    match parsers::detect_format(bytes, file_name)? {
+        DocumentFormat::Pdf => Some(ScanKind::Pdf),
+        DocumentFormat::Text => Some(ScanKind::Text),
+        DocumentFormat::Docx => None,
+    }
+}
+
+/// Run an OCR program, returning its output
+async fn run(program: &str, args: &[&std::ffi::OsStr]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Error::config_with_suggestion(
+                format!("{} not found", program),
+                "Install Tesseract and Poppler (tesseract-ocr and poppler-utils), or set finance.expenses.ocr",
+            ),
+            _ => Error::service(format!("Failed to run {}: {}", program, e)),
+        })?;
+    if !output.status.success() {
+        return Err(Error::service(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Recognize the text of an image file
+async fn recognize(config: &OcrConfig, image: &Path) -> Result<String> {
+    run(
+        &config.tesseract,
+        &[
+            image.as_os_str(),
+            "stdout".as_ref(),
+            "-l".as_ref(),
+            config.languages.as_ref(),
+            // Receipts are sparse blocks of text rather than paragraphs
+            "--psm".as_ref(),
+            "4".as_ref(),
+        ],
+    )
+    .await
+}
+
+/// Text of a receipt or invoice at `path`
+pub async fn text(config: &OcrConfig, path: &Path, bytes: &[u8]) -> Result<String> {
+    let name = path.file_name().and_then(|n| n.to_str());
+    match detect(bytes, name) {
+        Some(ScanKind::Image) => recognize(config, path).await,
+        Some(ScanKind::Text) => Ok(String::from_utf8_lossy(bytes).into_owned()),
+        Some(ScanKind::Pdf) => {
+            let text = parsers::extract(bytes, name)?.text();
+            if text.chars().filter(|c| c.is_alphanumeric()).count() > 20 {
+                return Ok(text);
+            }
+            // No text layer: a scan saved as PDF
+            let dir = tempfile::tempdir()
+                .map_err(|e| Error::internal(format!("Failed to create temp dir: {}", e)))?;
+            let prefix = dir.path().join("page");
+            run(
+                &config.pdftoppm,
+                &[
+                    "-r".as_ref(),
+                    RENDER_DPI.as_ref(),
+                    "-png".as_ref(),
+                    path.as_os_str(),
+                    prefix.as_os_str(),
+                ],
+            )
+            .await?;
+            let mut pages: Vec<_> = std::fs::read_dir(dir.path())
+                .map_err(|e| {
+                    Error::io_with_path(
+                        format!("Failed to read rendered pages: {}", e),
+                        dir.path().to_path_buf(),
+                    )
+                })?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .collect();
+            pages.sort();
+            let mut text = Vec::new();
+            for page in pages {
+                text.push(recognize(config, &page).await?);
+            }
+            Ok(text.join("\n"))
+        }
+        None => Err(Error::validation_with_field(
+            format!(
+                "Unsupported receipt format{}; use a PDF or an image",
+                name.map(|n| format!(": {}", n)).unwrap_or_default()
+            ),
+            "path",
+        )),
+    }
+}
@@ -0,0 +1,178 @@
+//! Bank CSV exports
+//!
+//! Columns are found by their header, under the names banks commonly use:
+//! a date, a payee or description, and either one signed amount or
+//! separate debit and credit columns. Dates are tried as ISO, then in the
+//! order `date_format` gives or US and European day-first forms.
+
+use super::plaintext::parse_amount;
+use super::Transaction;
+use crate::error::{Error, Result};
+use crate::infrastructure::assets::parse_csv;
+use chrono::NaiveDate;
+
+/// Date formats tried after `date_format`
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y", "%d/%m/%Y", "%m/%d/%y"];
+
+/// Line numbers of rows that could not be read, with the reason
+pub type SkippedRows = Vec<(usize, String)>;
+
+/// Transaction field for a CSV header
+fn column(header: &str) -> Option<&'static str> {
+    let header = header.trim().to_lowercase().replace([' ', '-'], "_");
+    Some(match header.as_str() {
+        "date" | "transaction_date" | "posted_date" | "posting_date" | "booking_date"
+        | "value_date" | "buchungstag" => "date",
+        "payee" | "merchant" | "name" | "counterparty" | "beneficiary" => "payee",
+        "description" | "memo" | "details" | "narrative" | "reference" | "verwendungszweck" => {
+            "description"
+        }
+        "amount" | "value" | "betrag" => "amount",
+        "debit" | "withdrawal" | "withdrawals" | "money_out" | "paid_out" => "debit",
+        "credit" | "deposit" | "deposits" | "money_in" | "paid_in" => "credit",
+        "category" => "category",
+        "currency" => "currency",
+        _ => return None,
+    })
+}
+
+/// Amount text, allowing `(12.50)` for negatives and a decimal comma
+pub(crate) fn amount(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let (negative, text) = match text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        Some(inner) => (true, inner),
+        None => (false, text),
+    };
+    // `1.234,56` and `12,50` use a decimal comma
+    let text = match (text.rfind(','), text.rfind('.')) {
+        (Some(comma), Some(dot)) if comma > dot => text.replace('.', "").replace(',', "."),
+        (Some(comma), None) if text.len() - comma == 3 => text.replace(',', "."),
+        _ => text.to_string(),
+    };
+    let (value, _) = parse_amount(&text)?;
+    Some(if negative { -value.abs() } else { value })
+}
+
+fn date(text: &str, format: Option<&str>) -> Option<NaiveDate> {
+    let text = text.trim();
+    format
+        .into_iter()
+        .chain(DATE_FORMATS.iter().copied())
+        .find_map(|f| NaiveDate::parse_from_str(text, f).ok())
+}
+
+/// Transactions of a bank export, for `account` in `currency`
+///
+/// Rows that cannot be read are returned with their line number and reason.
+pub fn parse(
+    text: &str,
+    account: &str,
+    currency: &str,
+    date_format: Option<&str>,
+) -> Result<(Vec<Transaction>, SkippedRows)> {
+    let mut records = parse_csv(text).into_iter();
+    let (_, headers) = records
+        .next()
+        .ok_or_else(|| Error::validation_with_field("CSV is empty", "csv"))?;
+    let columns: Vec<Option<&str>> = headers.iter().map(|h| column(h)).collect();
+    let has = |field: &str| columns.contains(&Some(field));
+    if !has("date") || !(has("amount") || has("debit") || has("credit")) {
+        return Err(Error::validation_with_field(
+            "CSV needs a date column and an amount column, or debit and credit columns",
+            "csv",
+        ));
+    }
+    let mut transactions = Vec::new();
+    let mut skipped = Vec::new();
+    for (line, record) in records {
+        let field = |name: &str| {
+            columns
+                .iter()
+                .position(|c| *c == Some(name))
+                .and_then(|i| record.get(i))
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+        };
+        let Some(day) = field("date").and_then(|d| date(d, date_format)) else {
+            skipped.push((line, "unreadable date".to_string()));
+            continue;
+        };
+        let value = match field("amount") {
+            Some(text) => amount(text),
+            None => match (
+                field("debit").and_then(amount),
+                field("credit").and_then(amount),
+            ) {
+                (None, None) => None,
+                (debit, credit) => Some(credit.unwrap_or(0.0) - debit.unwrap_or(0.0).abs()),
+            },
+        };
+        let Some(value) = value else {
+            skipped.push((line, "unreadable amount".to_string()));
+            continue;
+        };
+        let description = field("description").map(str::to_string);
+        let Some(payee) = field("payee")
+            .map(str::to_string)
+            .or_else(|| description.clone())
+        else {
+            skipped.push((line, "no payee or description".to_string()));
+            continue;
+        };
+        transactions.push(Transaction {
+            date: day,
+            description: description.filter(|d| *d != payee),
+            payee,
+            amount: value,
+            currency: field("currency").unwrap_or(currency).to_string(),
+            account: account.to_string(),
+            category: field("category").map(str::to_string),
+            categorized_by: field("category").map(|_| "bank".to_string()),
+            ..Default::default()
+        });
+    }
+    Ok((transactions, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_signed_and_split_amount_exports() {
+        let (transactions, skipped) = parse(
+            "Transaction Date,Description,Amount\n\
+             2024-03-01,WHOLE FOODS #123,-84.10\n\
+             03/02/2024,\"PAYROLL, ACME\",\"3,200.00\"\n\
+             soon,NETFLIX,-15.99\n",
+            "Checking",
+            "USD",
+            None,
+        )
+        .unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].payee, "WHOLE FOODS #123");
+        assert_eq!(transactions[0].amount, -84.1);
+        assert_eq!(transactions[1].amount, 3200.0);
+        assert_eq!(transactions[1].date.to_string(), "2024-03-02");
+        assert_eq!(skipped, vec![(4, "unreadable date".to_string())]);
+
+        assert!(parse("Payee,Memo\nCafe,Lunch\n", "Checking", "USD", None).is_err());
+
+        let (transactions, _) = parse(
+            "Date,Payee,Memo,Paid out,Paid in\n\
+             02.03.2024,Corner Cafe,Lunch,\"12,50\",\n\
+             05.03.2024,Refund,,,(1.00)\n",
+            "Current",
+            "EUR",
+            Some("%d.%m.%Y"),
+        )
+        .unwrap();
+        assert_eq!(transactions[0].amount, -12.5);
+        assert_eq!(transactions[0].description.as_deref(), Some("Lunch"));
+        assert_eq!(transactions[1].amount, -1.0);
+    }
+}
@@ -0,0 +1,930 @@
+//! Personal finance ledger
+//!
+//! Transactions are imported from Beancount or Ledger journals, which
+//! already name a category for each posting, or from bank CSV exports,
+//! which usually do not. Uncategorized transactions are matched against
+//! rules by payee, then, when a model is configured, categorized by it in
+//! batches. Assigning a category by hand can remember it as a rule for the
+//! payee. Budgets are monthly amounts per category, and a budget on `Food`
+//! also covers `Food:Groceries` and other subcategories.
+
+pub mod csv;
+pub mod plaintext;
+
+use crate::ai::provider::{ChatMessage, LlmProvider};
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Transactions sent to the model in one request
+const LLM_BATCH: usize = 50;
+
+/// Uncategorized transactions listed in a categorize result
+const LISTED_UNCATEGORIZED: usize = 50;
+
+/// Ledger configuration, under `finance.ledger`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LedgerConfig {
+    /// Where transactions are persisted; in memory when unset
+    pub path: Option<PathBuf>,
+    /// Currency of imports that name none, and of budget reports
+    pub currency: String,
+    /// Categories by payee, tried before the model
+    pub rules: Vec<CategoryRule>,
+    /// Monthly budget per category, e.g. `Food: 400`
+    pub budgets: BTreeMap<String, f64>,
+}
+
+impl Default for LedgerConfig {
+    fn default() -> Self {
+        Self {
+            path: std::env::var("LEDGER_PATH").ok().map(PathBuf::from),
+            currency: "USD".to_string(),
+            rules: Vec::new(),
+            budgets: BTreeMap::new(),
+        }
+    }
+}
+
+/// Category for transactions whose payee or description contains `pattern`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CategoryRule {
+    /// Text to look for, ignoring case
+    #[serde(rename = "match")]
+    pub pattern: String,
+    pub category: String,
+}
+
+impl CategoryRule {
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        let pattern = self.pattern.to_lowercase();
+        transaction.payee.to_lowercase().contains(&pattern)
+            || transaction
+                .description
+                .as_ref()
+                .is_some_and(|d| d.to_lowercase().contains(&pattern))
+    }
+}
+
+/// Format of an import
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LedgerFormat {
+    Beancount,
+    Ledger,
+    Csv,
+}
+
+impl FromStr for LedgerFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "beancount" => Ok(LedgerFormat::Beancount),
+            "ledger" | "hledger" => Ok(LedgerFormat::Ledger),
+            "csv" => Ok(LedgerFormat::Csv),
+            other => Err(Error::validation_with_field(
+                format!("Unknown ledger format: {}", other),
+                "format",
+            )),
+        }
+    }
+}
+
+/// Money in or out of an account; spending is negative
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Transaction {
+    /// Derived from the date, amount, payee and account when imported
+    #[serde(default)]
+    pub id: String,
+    pub date: NaiveDate,
+    pub payee: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub amount: f64,
+    pub currency: String,
+    pub account: String,
+    /// e.g. `Food:Groceries`, or `Income:Salary` for income
+    #[serde(default)]
+    pub category: Option<String>,
+    /// journal, bank, rule, llm or manual
+    #[serde(default)]
+    pub categorized_by: Option<String>,
+}
+
+impl Transaction {
+    /// Id of a transaction with no identical one in the same import
+    pub(crate) fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!(
+            "{}|{:.2}|{}|{}|{}",
+            self.date,
+            self.amount,
+            self.payee,
+            self.account,
+            self.description.as_deref().unwrap_or_default()
+        ));
+        format!("{:x}", hasher.finalize())[..16].to_string()
+    }
+
+    fn is_income(&self) -> bool {
+        self.category
+            .as_deref()
+            .is_some_and(|c| c == "Income" || c.starts_with("Income:"))
+    }
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub transactions: usize,
+    /// Transactions not stored before
+    pub new: usize,
+    /// Categorized by a rule during the import
+    pub by_rule: usize,
+    /// Still without a category after the import
+    pub uncategorized: usize,
+    /// CSV rows that could not be read
+    pub skipped: csv::SkippedRows,
+    pub first: Option<NaiveDate>,
+    pub last: Option<NaiveDate>,
+}
+
+/// Outcome of categorizing a month
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CategorizeReport {
+    pub assigned: usize,
+    pub by_rule: usize,
+    pub by_llm: usize,
+    /// Transactions still without a category, up to 50
+    pub uncategorized: Vec<Transaction>,
+}
+
+/// Spending against the budget of one category
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BudgetLine {
+    pub category: String,
+    pub budget: Option<f64>,
+    /// Amount spent, positive
+    pub actual: f64,
+}
+
+impl BudgetLine {
+    /// Budget left, negative when overspent
+    pub fn remaining(&self) -> Option<f64> {
+        self.budget.map(|b| b - self.actual)
+    }
+}
+
+/// Budget against actual spending for one month
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetReport {
+    /// e.g. `2024-03`
+    pub month: String,
+    pub currency: String,
+    pub income: f64,
+    /// Amount spent in every category, positive
+    pub spent: f64,
+    /// Budgeted categories first, then other spending by top-level category
+    pub lines: Vec<BudgetLine>,
+    pub uncategorized: f64,
+    pub uncategorized_count: usize,
+    /// Transactions in other currencies, left out of the totals
+    pub other_currencies: usize,
+}
+
+/// Whether `category` is `parent` or one of its subcategories
+fn within(category: &str, parent: &str) -> bool {
+    category == parent
+        || category
+            .strip_prefix(parent)
+            .is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// Budget against spending in the month starting on `month`
+pub fn budget_report(
+    transactions: &[Transaction],
+    budgets: &BTreeMap<String, f64>,
+    month: NaiveDate,
+    currency: &str,
+) -> BudgetReport {
+    let mut report = BudgetReport {
+        month: month.format("%Y-%m").to_string(),
+        currency: currency.to_string(),
+        ..Default::default()
+    };
+    let mut spending: BTreeMap<String, f64> = BTreeMap::new();
+    for transaction in transactions
+        .iter()
+        .filter(|t| t.date.year() == month.year() && t.date.month() == month.month())
+    {
+        if transaction.currency != currency {
+            report.other_currencies += 1;
+            continue;
+        }
+        match &transaction.category {
+            _ if transaction.is_income() => report.income += transaction.amount,
+            Some(category) => {
+                *spending.entry(category.clone()).or_default() -= transaction.amount;
+                report.spent -= transaction.amount;
+            }
+            None => {
+                report.uncategorized -= transaction.amount;
+                report.uncategorized_count += 1;
+            }
+        }
+    }
+    for (category, budget) in budgets {
+        let actual = spending
+            .iter()
+            .filter(|(c, _)| within(c, category))
+            .map(|(_, amount)| amount)
+            .sum();
+        report.lines.push(BudgetLine {
+            category: category.clone(),
+            budget: Some(*budget),
+            actual,
+        });
+    }
+    let mut unbudgeted: BTreeMap<String, f64> = BTreeMap::new();
+    for (category, amount) in spending {
+        if !budgets.keys().any(|b| within(&category, b)) {
+            let top = category.split(':').next().unwrap_or(&category).to_string();
+            *unbudgeted.entry(top).or_default() += amount;
+        }
+    }
+    report
+        .lines
+        .extend(unbudgeted.into_iter().map(|(category, actual)| BudgetLine {
+            category,
+            budget: None,
+            actual,
+        }));
+    report
+}
+
+/// Text describing a budget report
+pub fn describe(report: &BudgetReport) -> String {
+    let mut lines = vec![format!(
+        "Budget for {} ({}): income {:.2}, spent {:.2}, net {:+.2}",
+        report.month,
+        report.currency,
+        report.income,
+        report.spent,
+        report.income - report.spent - report.uncategorized
+    )];
+    for line in &report.lines {
+        lines.push(match (line.budget, line.remaining()) {
+            (Some(budget), Some(remaining)) if budget > 0.0 => format!(
+                "{}: {:.2} of {:.2} ({:.0}%, {:.2} {})",
+                line.category,
+                line.actual,
+                budget,
+                line.actual / budget * 100.0,
+                remaining.abs(),
+                if remaining < 0.0 { "over" } else { "left" }
+            ),
+            _ => format!("{}: {:.2} (no budget)", line.category, line.actual),
+        });
+    }
+    if report.uncategorized_count > 0 {
+        lines.push(format!(
+            "Uncategorized: {} transactions, {:.2} net spending",
+            report.uncategorized_count, report.uncategorized
+        ));
+    }
+    if report.other_currencies > 0 {
+        lines.push(format!(
+            "{} transactions in other currencies are not counted",
+            report.other_currencies
+        ));
+    }
+    lines.join("\n")
+}
+
+/// `YYYY-MM` month as its first day, or the current month when unset
+fn parse_month(value: Option<&str>) -> Result<NaiveDate> {
+    match value {
+        None => {
+            let today = Utc::now().date_naive();
+            Ok(today.with_day(1).unwrap_or(today))
+        }
+        Some(value) => NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d")
+            .map_err(|_| Error::validation_with_field("month must look like 2024-03", "month")),
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LedgerStore {
+    /// Transactions by id
+    transactions: BTreeMap<String, Transaction>,
+    /// Rules remembered from categories assigned by hand
+    learned_rules: Vec<CategoryRule>,
+}
+
+/// Imported transactions, categorization and budgets
+pub struct Ledger {
+    config: LedgerConfig,
+    llm: Option<Arc<dyn LlmProvider>>,
+    store: RwLock<LedgerStore>,
+}
+
+impl Ledger {
+    /// Open the ledger, loading transactions from the configured path if it exists
+    pub async fn open(config: LedgerConfig) -> Result<Self> {
+        let store = match &config.path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!("Failed to parse ledger {}: {}", path.display(), e))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => LedgerStore::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read ledger: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => LedgerStore::default(),
+        };
+        Ok(Self {
+            config,
+            llm: None,
+            store: RwLock::new(store),
+        })
+    }
+
+    /// Categorize what no rule matches with the given model
+    pub fn with_llm(mut self, provider: Arc<dyn LlmProvider>) -> Self {
+        self.llm = Some(provider);
+        self
+    }
+
+    /// Currency of imports that name none
+    pub fn currency(&self) -> &str {
+        &self.config.currency
+    }
+
+    async fn persist(&self, store: &LedgerStore) -> Result<()> {
+        let Some(path) = &self.config.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create ledger directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(format!("Failed to write ledger: {}", e), temp.clone())
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to replace ledger: {}", e), path.clone())
+        })
+    }
+
+    /// Category of the first rule matching, remembered rules first
+    fn rule_category(&self, store: &LedgerStore, transaction: &Transaction) -> Option<String> {
+        store
+            .learned_rules
+            .iter()
+            .chain(&self.config.rules)
+            .find(|r| r.matches(transaction))
+            .map(|r| r.category.clone())
+    }
+
+    /// Store transactions, categorizing new ones by rule
+    ///
+    /// Importing the same transactions again updates them, keeping the
+    /// category of any the import leaves uncategorized.
+    pub async fn import(&self, transactions: Vec<Transaction>) -> Result<ImportReport> {
+        let mut store = self.store.write().await;
+        let mut report = ImportReport::default();
+        let mut dates = BTreeSet::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for mut transaction in transactions {
+            // Identical transactions on one day are told apart by their order
+            let fingerprint = transaction.fingerprint();
+            let count = seen.entry(fingerprint.clone()).or_default();
+            *count += 1;
+            transaction.id = match count {
+                1 => fingerprint,
+                n => format!("{}-{}", fingerprint, n),
+            };
+            dates.insert(transaction.date);
+            report.transactions += 1;
+            if transaction.category.is_none() {
+                match store.transactions.get(&transaction.id) {
+                    Some(stored) if stored.category.is_some() => {
+                        transaction.category = stored.category.clone();
+                        transaction.categorized_by = stored.categorized_by.clone();
+                    }
+                    _ => {
+                        if let Some(category) = self.rule_category(&store, &transaction) {
+                            transaction.category = Some(category);
+                            transaction.categorized_by = Some("rule".to_string());
+                            report.by_rule += 1;
+                        }
+                    }
+                }
+            }
+            if transaction.category.is_none() {
+                report.uncategorized += 1;
+            }
+            if store
+                .transactions
+                .insert(transaction.id.clone(), transaction)
+                .is_none()
+            {
+                report.new += 1;
+            }
+        }
+        report.first = dates.first().copied();
+        report.last = dates.last().copied();
+        self.persist(&store).await?;
+        Ok(report)
+    }
+
+    /// Parse and store a journal or bank export
+    pub async fn import_text(
+        &self,
+        format: LedgerFormat,
+        text: &str,
+        account: &str,
+        currency: Option<&str>,
+        date_format: Option<&str>,
+    ) -> Result<ImportReport> {
+        let currency = currency.unwrap_or(&self.config.currency);
+        let (transactions, skipped) = match format {
+            LedgerFormat::Beancount | LedgerFormat::Ledger => {
+                (plaintext::parse(text, currency)?, Vec::new())
+            }
+            LedgerFormat::Csv => csv::parse(text, account, currency, date_format)?,
+        };
+        let mut report = self.import(transactions).await?;
+        report.skipped = skipped;
+        Ok(report)
+    }
+
+    /// Set the category of a transaction, and remember it for the payee
+    /// when `remember` is set
+    pub async fn assign(&self, id: &str, category: &str, remember: bool) -> Result<Transaction> {
+        let mut store = self.store.write().await;
+        let transaction = store.transactions.get_mut(id).ok_or_else(|| {
+            Error::not_found_with_resource("Transaction not found", "transaction", id)
+        })?;
+        transaction.category = Some(category.to_string());
+        transaction.categorized_by = Some("manual".to_string());
+        let transaction = transaction.clone();
+        if remember {
+            let rule = CategoryRule {
+                pattern: transaction.payee.clone(),
+                category: category.to_string(),
+            };
+            store
+                .learned_rules
+                .retain(|r| !r.pattern.eq_ignore_ascii_case(&rule.pattern));
+            store.learned_rules.insert(0, rule);
+        }
+        self.persist(&store).await?;
+        Ok(transaction)
+    }
+
+    /// Categories the model may choose from: budgets, rules and those in use
+    fn known_categories(&self, store: &LedgerStore) -> BTreeSet<String> {
+        self.config
+            .budgets
+            .keys()
+            .cloned()
+            .chain(self.config.rules.iter().map(|r| r.category.clone()))
+            .chain(store.learned_rules.iter().map(|r| r.category.clone()))
+            .chain(
+                store
+                    .transactions
+                    .values()
+                    .filter_map(|t| t.category.clone()),
+            )
+            .collect()
+    }
+
+    /// Ask the model for categories of `batch`, by position
+    async fn suggest(
+        llm: &dyn LlmProvider,
+        categories: &BTreeSet<String>,
+        batch: &[&Transaction],
+    ) -> Result<HashMap<usize, String>> {
+        let listed: Vec<Value> = batch
+            .iter()
+            .enumerate()
+            .map(|(n, t)| {
+                json!({
+                    "n": n + 1,
+                    "payee": t.payee,
+                    "description": t.description,
+                    "amount": t.amount,
+                    "currency": t.currency,
+                })
+            })
+            .collect();
+        let categories: Vec<&String> = categories.iter().collect();
+        let reply = llm
+            .complete_json(&[
+                ChatMessage::system(format!(
+                    "You categorize personal bank transactions. Negative amounts are spending, positive ones income. \
+                     Known categories: {}. Use a known category whenever one fits; otherwise give a short new one, \
+                     with subcategories after a colon, and start income categories with `Income:`. \
+                     Reply with JSON only: {{\"categories\": {{\"<n>\": \"<category>\"}}}}, leaving out transactions you cannot tell.",
+                    serde_json::to_string(&categories)?
+                )),
+                ChatMessage::user(serde_json::to_string(&listed)?),
+            ])
+            .await?;
+        Ok(reply["categories"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(n, category)| {
+                let n: usize = n.parse().ok()?;
+                let category = category.as_str()?.trim();
+                (n >= 1 && n <= batch.len() && !category.is_empty())
+                    .then(|| (n - 1, category.to_string()))
+            })
+            .collect())
+    }
+
+    /// Categorize a month's uncategorized transactions by rule, then with
+    /// the model when `use_llm` is set and one is configured
+    pub async fn categorize(&self, month: NaiveDate, use_llm: bool) -> Result<CategorizeReport> {
+        let mut store = self.store.write().await;
+        let mut report = CategorizeReport::default();
+        let in_month = |t: &Transaction| {
+            t.category.is_none() && t.date.year() == month.year() && t.date.month() == month.month()
+        };
+        let mut pending: Vec<&Transaction> = store
+            .transactions
+            .values()
+            .filter(|t| in_month(t))
+            .collect();
+        pending.sort_by(|a, b| (a.date, &a.payee).cmp(&(b.date, &b.payee)));
+        let ids: Vec<String> = pending.into_iter().map(|t| t.id.clone()).collect();
+        for id in &ids {
+            let category = self.rule_category(&store, &store.transactions[id]);
+            if let (Some(category), Some(transaction)) = (category, store.transactions.get_mut(id))
+            {
+                transaction.category = Some(category);
+                transaction.categorized_by = Some("rule".to_string());
+                report.by_rule += 1;
+            }
+        }
+        if let (Some(llm), true) = (&self.llm, use_llm) {
+            let categories = self.known_categories(&store);
+            let remaining: Vec<String> = ids
+                .iter()
+                .filter(|id| store.transactions[*id].category.is_none())
+                .cloned()
+                .collect();
+            for chunk in remaining.chunks(LLM_BATCH) {
+                let batch: Vec<&Transaction> =
+                    chunk.iter().map(|id| &store.transactions[id]).collect();
+                let suggestions = Self::suggest(llm.as_ref(), &categories, &batch).await?;
+                for (n, category) in suggestions {
+                    if let Some(transaction) = store.transactions.get_mut(&chunk[n]) {
+                        transaction.category = Some(category);
+                        transaction.categorized_by = Some("llm".to_string());
+                        report.by_llm += 1;
+                    }
+                }
+            }
+        }
+        report.uncategorized = ids
+            .iter()
+            .map(|id| &store.transactions[id])
+            .filter(|t| in_month(t))
+            .take(LISTED_UNCATEGORIZED)
+            .cloned()
+            .collect();
+        self.persist(&store).await?;
+        Ok(report)
+    }
+
+    /// Budget against spending for the month starting on `month`
+    pub async fn budget_report(&self, month: NaiveDate) -> BudgetReport {
+        let store = self.store.read().await;
+        let transactions: Vec<Transaction> = store.transactions.values().cloned().collect();
+        budget_report(
+            &transactions,
+            &self.config.budgets,
+            month,
+            &self.config.currency,
+        )
+    }
+
+    /// Get tool definitions for the ledger
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "import_transactions",
+                "Import transactions from a Beancount or Ledger journal or a bank CSV export, categorizing them by rule",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "format": {"type": "string", "enum": ["beancount", "ledger", "csv"]},
+                        "path": {"type": "string", "description": "File to import"},
+                        "content": {"type": "string", "description": "File contents, instead of a path"},
+                        "account": {"type": "string", "description": "Account a CSV export is from, e.g. Checking"},
+                        "currency": {"type": "string", "description": "Currency of amounts that name none; the ledger's currency when omitted"},
+                        "date_format": {"type": "string", "description": "strftime format of CSV dates, e.g. %d/%m/%Y, when ISO and US dates do not fit"}
+                    },
+                    "required": ["format"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "categorize_transactions",
+                "Assign categories to transactions, then categorize the rest of a month by rule and with the model, listing what is left",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "month": {"type": "string", "description": "Month to categorize (YYYY-MM); the current month when omitted"},
+                        "assign": {
+                            "type": "array",
+                            "description": "Categories to set by hand",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "id": {"type": "string"},
+                                    "category": {"type": "string"},
+                                    "remember": {"type": "boolean", "description": "Use this category for the payee from now on"}
+                                },
+                                "required": ["id", "category"]
+                            }
+                        },
+                        "use_llm": {"type": "boolean", "default": true}
+                    }
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "budget_report",
+                "Report a month's income and spending per category against the monthly budgets",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "month": {"type": "string", "description": "Month to report (YYYY-MM); the current month when omitted"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    /// Execute a ledger tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let field = |key: &str| {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.trim().is_empty())
+        };
+        match name {
+            "import_transactions" => {
+                let format: LedgerFormat = field("format")
+                    .ok_or_else(|| Error::validation_with_field("format is required", "format"))?
+                    .parse()?;
+                let text = match (field("content"), field("path")) {
+                    (Some(content), _) => content.to_string(),
+                    (None, Some(path)) => tokio::fs::read_to_string(path).await.map_err(|e| {
+                        Error::io_with_path(
+                            format!("Failed to read transactions: {}", e),
+                            PathBuf::from(path),
+                        )
+                    })?,
+                    (None, None) => {
+                        return Err(Error::validation_with_field(
+                            "Give the path or content to import",
+                            "path",
+                        ))
+                    }
+                };
+                let report = self
+                    .import_text(
+                        format,
+                        &text,
+                        field("account").unwrap_or("Bank"),
+                        field("currency"),
+                        field("date_format"),
+                    )
+                    .await?;
+                let range = match (report.first, report.last) {
+                    (Some(first), Some(last)) => format!(" from {} to {}", first, last),
+                    _ => String::new(),
+                };
+                let mut text = format!(
+                    "Imported {} transactions ({} new){}; {} categorized by rule, {} uncategorized",
+                    report.transactions, report.new, range, report.by_rule, report.uncategorized
+                );
+                if !report.skipped.is_empty() {
+                    text.push_str(&format!("; skipped {} rows", report.skipped.len()));
+                }
+                Ok(call_result(text, serde_json::to_value(&report)?))
+            }
+            "categorize_transactions" => {
+                let month = parse_month(field("month"))?;
+                let assignments = parameters
+                    .get("assign")
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                let mut assigned = 0;
+                for assignment in &assignments {
+                    let (Some(id), Some(category)) = (
+                        assignment["id"].as_str(),
+                        assignment["category"]
+                            .as_str()
+                            .map(str::trim)
+                            .filter(|c| !c.is_empty()),
+                    ) else {
+                        return Err(Error::validation_with_field(
+                            "Each assignment needs an id and a category",
+                            "assign",
+                        ));
+                    };
+                    let remember = assignment["remember"].as_bool().unwrap_or(false);
+                    self.assign(id, category, remember).await?;
+                    assigned += 1;
+                }
+                let use_llm = parameters
+                    .get("use_llm")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+                let mut report = self.categorize(month, use_llm).await?;
+                report.assigned = assigned;
+                let mut lines = vec![format!(
+                    "Assigned {}, categorized {} by rule and {} by model; {} left uncategorized in {}",
+                    report.assigned,
+                    report.by_rule,
+                    report.by_llm,
+                    report.uncategorized.len(),
+                    month.format("%Y-%m")
+                )];
+                lines.extend(report.uncategorized.iter().map(|t| {
+                    format!(
+                        "{} {} {:.2} {} [{}]",
+                        t.date, t.payee, t.amount, t.currency, t.id
+                    )
+                }));
+                Ok(call_result(
+                    lines.join("\n"),
+                    serde_json::to_value(&report)?,
+                ))
+            }
+            "budget_report" => {
+                let month = parse_month(field("month"))?;
+                let report = self.budget_report(month).await;
+                Ok(call_result(
+                    describe(&report),
+                    serde_json::to_value(&report)?,
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct ScriptedLlm;
+
+    #[async_trait]
+    impl LlmProvider for ScriptedLlm {
+        fn model(&self) -> &str {
+            "scripted"
+        }
+
+        async fn complete(&self, _messages: &[ChatMessage]) -> Result<String> {
+            Ok(r#"{"categories": {"1": "Transport:Taxi", "7": "Nonsense"}}"#.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn categorizes_imports_and_reports_against_budgets() {
+        let ledger = Ledger::open(LedgerConfig {
+            path: None,
+            rules: vec![CategoryRule {
+                pattern: "whole foods".to_string(),
+                category: "Food:Groceries".to_string(),
+            }],
+            budgets: BTreeMap::from([("Food".to_string(), 200.0), ("Rent".to_string(), 1000.0)]),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .with_llm(Arc::new(ScriptedLlm));
+        let export = "Date,Description,Amount\n\
+                      2024-03-01,WHOLE FOODS #12,-84.10\n\
+                      2024-03-03,UBER TRIP,-23.50\n\
+                      2024-03-04,CORNER CAFE,-12.00\n\
+                      2024-03-04,CORNER CAFE,-12.00\n\
+                      2024-03-25,ACME PAYROLL,3200.00\n";
+        let report = ledger
+            .import_text(LedgerFormat::Csv, export, "Checking", None, None)
+            .await
+            .unwrap();
+        assert_eq!((report.transactions, report.new), (5, 5));
+        assert_eq!((report.by_rule, report.uncategorized), (1, 4));
+
+        // The same export again adds nothing
+        let again = ledger
+            .import_text(LedgerFormat::Csv, export, "Checking", None, None)
+            .await
+            .unwrap();
+        assert_eq!(again.new, 0);
+
+        let journal = "2024-03-01 * \"Landlord\" \"March rent\"\n  Expenses:Rent  1100.00 USD\n  Assets:Checking\n";
+        ledger
+            .import_text(LedgerFormat::Beancount, journal, "", None, None)
+            .await
+            .unwrap();
+
+        let march = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let categorized = ledger.categorize(march, true).await.unwrap();
+        assert_eq!(categorized.by_llm, 1);
+        assert_eq!(categorized.uncategorized.len(), 3);
+
+        let payroll = categorized
+            .uncategorized
+            .iter()
+            .find(|t| t.payee == "ACME PAYROLL")
+            .unwrap()
+            .id
+            .clone();
+        let cafe = categorized
+            .uncategorized
+            .iter()
+            .find(|t| t.payee == "CORNER CAFE")
+            .unwrap()
+            .id
+            .clone();
+        ledger
+            .assign(&payroll, "Income:Salary", false)
+            .await
+            .unwrap();
+        ledger.assign(&cafe, "Food:Dining", true).await.unwrap();
+        // The remembered payee categorizes its other transaction
+        let categorized = ledger.categorize(march, false).await.unwrap();
+        assert_eq!(categorized.by_rule, 1);
+        assert!(categorized.uncategorized.is_empty());
+
+        let report = ledger.budget_report(march).await;
+        assert_eq!(report.income, 3200.0);
+        assert_eq!(
+            report.lines,
+            vec![
+                BudgetLine {
+                    category: "Food".to_string(),
+                    budget: Some(200.0),
+                    actual: 108.1
+                },
+                BudgetLine {
+                    category: "Rent".to_string(),
+                    budget: Some(1000.0),
+                    actual: 1100.0
+                },
+                BudgetLine {
+                    category: "Transport".to_string(),
+                    budget: None,
+                    actual: 23.5
+                },
+            ]
+        );
+        assert_eq!(
+            describe(&report).lines().nth(2).unwrap(),
+            "Rent: 1100.00 of 1000.00 (110%, 100.00 over)"
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Beancount and Ledger journals
+//!
+//! Both formats write a transaction as a dated header followed by indented
+//! postings. Each posting to an `Expenses` or `Income` account becomes one
+//! transaction, from the point of view of the `Assets` or `Liabilities`
+//! account it was paid from: spending is negative, income positive. One
+//! posting per transaction may leave its amount out, as both tools allow,
+//! and balances the others. Directives other than transactions are skipped.
+
+use super::Transaction;
+use crate::error::{Error, Result};
+use chrono::NaiveDate;
+
+/// Roots of the accounts that categorize a transaction
+const CATEGORY_ROOTS: &[&str] = &["Expenses", "Income"];
+
+/// Dated Beancount directives that are not transactions
+const DIRECTIVES: &[&str] = &[
+    "open",
+    "close",
+    "balance",
+    "pad",
+    "note",
+    "document",
+    "price",
+    "event",
+    "query",
+    "custom",
+    "commodity",
+];
+
+/// Currency symbols and the codes they stand for
+const SYMBOLS: &[(char, &str)] = &[('$', "USD"), ('€', "EUR"), ('£', "GBP"), ('¥', "JPY")];
+
+/// Amount and currency of a posting, e.g. `45.20 USD`, `$1,200.00` or `-€3`
+pub fn parse_amount(text: &str) -> Option<(f64, Option<String>)> {
+    // Prices and costs (`@ 1.10 USD`, `{100 USD}`) do not change the amount
+    let text = text.split(['@', '{']).next()?.trim();
+    let mut currency = None;
+    let mut number = String::new();
+    for token in text.split_whitespace() {
+        let token = token.trim_start_matches('+');
+        let (sign, rest) = match token.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", token),
+        };
+        let rest = match SYMBOLS.iter().find(|(s, _)| rest.starts_with(*s)) {
+            Some((symbol, code)) => {
+                currency = Some(code.to_string());
+                &rest[symbol.len_utf8()..]
+            }
+            None => rest,
+        };
+        if rest
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit() || c == '.')
+        {
+            number = format!("{}{}", sign, rest.replace(',', ""));
+        } else if rest.chars().all(|c| c.is_ascii_uppercase()) && !rest.is_empty() {
+            currency = Some(rest.to_string());
+        }
+    }
+    let value: f64 = number.parse().ok()?;
+    Some(((value * 100.0).round() / 100.0, currency))
+}
+
+struct Posting {
+    account: String,
+    amount: Option<(f64, Option<String>)>,
+}
+
+struct Entry {
+    line: usize,
+    date: NaiveDate,
+    payee: String,
+    narration: Option<String>,
+    postings: Vec<Posting>,
+}
+
+/// Date at the start of a header line, `2024-03-01` or `2024/03/01`
+fn header_date(line: &str) -> Option<(NaiveDate, &str)> {
+    let date = line.get(..10)?;
+    let date = NaiveDate::parse_from_str(&date.replace('/', "-"), "%Y-%m-%d").ok()?;
+    Some((date, line[10..].trim()))
+}
+
+/// Quoted strings of a Beancount header, e.g. `"Payee" "Narration"`
+fn quoted(text: &str) -> Vec<String> {
+    text.split('"')
+        .skip(1)
+        .step_by(2)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Payee and narration of a transaction header, without its date
+fn header(rest: &str) -> Option<(String, Option<String>)> {
+    let rest = rest.split(" ;").next().unwrap_or(rest).trim();
+    // Beancount: `* "Payee" "Narration"` or `txn "Narration"`
+    let flag = rest.split_whitespace().next().unwrap_or_default();
+    if DIRECTIVES.contains(&flag) {
+        return None;
+    }
+    if rest.contains('"') {
+        let mut strings = quoted(rest);
+        return match strings.len() {
+            0 => None,
+            1 => Some((strings.remove(0), None)),
+            _ => {
+                let narration = strings.remove(1);
+                Some((strings.remove(0), Some(narration).filter(|n| !n.is_empty())))
+            }
+        };
+    }
+    // Ledger: `* Payee`, `! (code) Payee` or just `Payee`
+    let mut payee = rest.trim_start_matches(['*', '!']).trim();
+    if payee.starts_with('(') {
+        payee = payee.split_once(')').map_or(payee, |(_, p)| p.trim());
+    }
+    (!payee.is_empty()).then(|| (payee.to_string(), None))
+}
+
+/// Account and amount of an indented posting line
+fn posting(line: &str) -> Option<Posting> {
+    let line = line.split(';').next()?.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let (account, amount) = match line.find("  ").or_else(|| line.find('\t')) {
+        Some(at) => (line[..at].trim(), line[at..].trim()),
+        // Beancount separates with a single space too
+        None => match line.split_once(' ') {
+            Some((account, amount)) if !account.ends_with(':') => (account, amount.trim()),
+            _ => (line, ""),
+        },
+    };
+    // Metadata lines look like `key: "value"`
+    if !account.contains(':') || account.ends_with(':') || account.starts_with(char::is_lowercase) {
+        return None;
+    }
+    let account = account.trim_start_matches(['*', '!']).trim();
+    Some(Posting {
+        account: account.to_string(),
+        amount: if amount.is_empty() {
+            None
+        } else {
+            parse_amount(amount)
+        },
+    })
+}
+
+fn entries(text: &str) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut open = false;
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with([';', '#', '%', '*']) {
+            open = false;
+            continue;
+        }
+        if !line.starts_with([' ', '\t']) {
+            open = false;
+            if let Some((date, rest)) = header_date(line) {
+                if let Some((payee, narration)) = header(rest) {
+                    entries.push(Entry {
+                        line: index + 1,
+                        date,
+                        payee,
+                        narration,
+                        postings: Vec::new(),
+                    });
+                    open = true;
+                }
+            }
+            continue;
+        }
+        if open {
+            if let (Some(entry), Some(posting)) = (entries.last_mut(), posting(line)) {
+                entry.postings.push(posting);
+            }
+        }
+    }
+    entries
+}
+
+/// Transactions of a Beancount or Ledger journal, in `currency` when a
+/// posting names none
+pub fn parse(text: &str, currency: &str) -> Result<Vec<Transaction>> {
+    let mut transactions = Vec::new();
+    for mut entry in entries(text) {
+        let missing = entry.postings.iter().filter(|p| p.amount.is_none()).count();
+        if missing > 1 {
+            return Err(Error::parsing(format!(
+                "Line {}: only one posting may leave out its amount",
+                entry.line
+            )));
+        }
+        let total: f64 = entry
+            .postings
+            .iter()
+            .filter_map(|p| p.amount.as_ref().map(|(a, _)| a))
+            .sum();
+        let implied_currency = entry
+            .postings
+            .iter()
+            .find_map(|p| p.amount.as_ref().and_then(|(_, c)| c.clone()));
+        for posting in &mut entry.postings {
+            if posting.amount.is_none() {
+                posting.amount =
+                    Some((((-total) * 100.0).round() / 100.0, implied_currency.clone()));
+            }
+        }
+        let account = entry
+            .postings
+            .iter()
+            .find(|p| !CATEGORY_ROOTS.iter().any(|r| p.account.starts_with(r)))
+            .map(|p| p.account.clone())
+            .unwrap_or_default();
+        for posting in &entry.postings {
+            let Some(root) = CATEGORY_ROOTS
+                .iter()
+                .find(|r| posting.account.starts_with(&format!("{}:", r)))
+            else {
+                continue;
+            };
+            let (amount, posting_currency) = posting.amount.clone().unwrap_or_default();
+            let category = match *root {
+                "Expenses" => posting.account["Expenses:".len()..].to_string(),
+                _ => posting.account.clone(),
+            };
+            transactions.push(Transaction {
+                date: entry.date,
+                payee: entry.payee.clone(),
+                description: entry.narration.clone(),
+                // Expenses are debited, so spending leaves the account
+                amount: -amount,
+                currency: posting_currency.unwrap_or_else(|| currency.to_string()),
+                account: account.clone(),
+                category: Some(category),
+                categorized_by: Some("journal".to_string()),
+                ..Default::default()
+            });
+        }
+    }
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_beancount_and_ledger_journals() {
+        let beancount = r#"
+option "operating_currency" "EUR"
+2024-01-01 open Assets:Bank:Checking EUR
+
+2024-03-01 * "Whole Foods" "Weekly shop"
+  Assets:Bank:Checking  -84.10 EUR
+  Expenses:Food:Groceries   70.00 EUR
+  Expenses:Household        ; cleaning things
+
+2024-03-25 * "ACME Corp" "Salary"
+  receipt: "payslip.pdf"
+  Assets:Bank:Checking  3,200.00 EUR
+  Income:Salary
+2024-03-31 balance Assets:Bank:Checking  3115.90 EUR
+"#;
+        let transactions = parse(beancount, "USD").unwrap();
+        assert_eq!(transactions.len(), 3);
+        assert_eq!(transactions[0].category.as_deref(), Some("Food:Groceries"));
+        assert_eq!(transactions[0].amount, -70.0);
+        assert_eq!(transactions[1].category.as_deref(), Some("Household"));
+        assert_eq!(transactions[1].amount, -14.1);
+        assert_eq!(transactions[1].account, "Assets:Bank:Checking");
+        assert_eq!(transactions[2].amount, 3200.0);
+        assert_eq!(transactions[2].category.as_deref(), Some("Income:Salary"));
+        assert_eq!(transactions[2].currency, "EUR");
+
+        let ledger = "
+2024/03/02 * (1042) Corner Cafe  ; lunch
+    Expenses:Dining Out    $12.50
+    Liabilities:Visa
+";
+        let transactions = parse(ledger, "EUR").unwrap();
+        assert_eq!(transactions[0].payee, "Corner Cafe");
+        assert_eq!(transactions[0].category.as_deref(), Some("Dining Out"));
+        assert_eq!(transactions[0].currency, "USD");
+        assert_eq!(transactions[0].account, "Liabilities:Visa");
+
+        assert_eq!(
+            parse_amount("-$1,200.50"),
+            Some((-1200.5, Some("USD".to_string())))
+        );
+        assert_eq!(
+            parse_amount("10 AAPL @ 170.00 USD"),
+            Some((10.0, Some("AAPL".to_string())))
+        );
+    }
+}
@@ -1,7 +1,20 @@
 /// Alpaca trading module for stock market trading
 pub mod alpaca;
+/// Crypto exchange market data and balances
+pub mod crypto;
+/// Receipts and invoices read with OCR and recorded in the ledger
+pub mod expenses;
+/// Imported bank transactions, categories and monthly budgets
+pub mod ledger;
+/// Portfolio exposure, profit and loss and risk metrics
+pub mod portfolio;
 
 // Re-export key types
 pub use alpaca::{
-    Account, AlpacaClient, Bar, Order, OrderSide, OrderType, Position, Quote, TimeInForce,
+    Account, AlpacaClient, AlpacaConfig, Bar, Order, OrderRequest, OrderSide, OrderType, Position,
+    Quote, QuoteStream, ReplaceOrderRequest, TimeInForce,
 };
+pub use crypto::{CryptoConfig, CryptoExchange};
+pub use expenses::{Expenses, ExpensesConfig};
+pub use ledger::{Ledger, LedgerConfig};
+pub use portfolio::{PortfolioAnalysis, RiskLimits, RiskReport};
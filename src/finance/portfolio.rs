@@ -0,0 +1,539 @@
+//! Portfolio analytics and risk metrics
+//!
+//! Exposure and profit and loss come from the current positions. Drawdown,
+//! Sharpe ratio, volatility and value at risk replay today's holdings over
+//! the daily closes of the lookback window, so they describe the risk of the
+//! portfolio as it stands rather than the account's realized history.
+
+use super::alpaca::{AlpacaClient, Bar, Position};
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Trading days per year, for annualizing daily figures
+const TRADING_DAYS: f64 = 252.0;
+
+/// Sector for symbols missing from the sector map
+pub const UNCLASSIFIED: &str = "Unclassified";
+
+/// Daily returns below which the metrics are flagged as rough
+const MIN_OBSERVATIONS: usize = 20;
+
+/// One position with its share of the portfolio
+#[derive(Debug, Clone, Serialize)]
+pub struct Holding {
+    pub symbol: String,
+    pub sector: String,
+    /// Shares, negative for shorts
+    pub qty: f64,
+    /// Market value, negative for shorts
+    pub market_value: f64,
+    pub cost_basis: f64,
+    pub unrealized_pl: f64,
+    pub unrealized_plpc: f64,
+    /// Share of gross exposure
+    pub weight: f64,
+}
+
+/// Long, short and net exposure to one sector
+#[derive(Debug, Clone, Serialize)]
+pub struct SectorExposure {
+    pub sector: String,
+    pub long: f64,
+    pub short: f64,
+    pub net: f64,
+    /// Share of gross exposure
+    pub weight: f64,
+    pub symbols: Vec<String>,
+}
+
+/// How much of the portfolio sits in a few positions
+#[derive(Debug, Clone, Serialize)]
+pub struct Concentration {
+    pub largest_symbol: Option<String>,
+    pub largest_weight: f64,
+    /// Combined weight of the five largest positions
+    pub top5_weight: f64,
+    /// Herfindahl-Hirschman index of the weights, 1 for a single position
+    pub herfindahl: f64,
+    /// Equally weighted positions with the same concentration, `1 / herfindahl`
+    pub effective_positions: f64,
+}
+
+/// Exposure, profit and loss and concentration of the current positions
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioAnalysis {
+    pub holdings: Vec<Holding>,
+    pub sectors: Vec<SectorExposure>,
+    pub long_value: f64,
+    pub short_value: f64,
+    pub gross_exposure: f64,
+    pub net_exposure: f64,
+    pub unrealized_pl: f64,
+    /// Unrealized P&L as a fraction of the gross cost basis
+    pub unrealized_plpc: f64,
+    pub concentration: Concentration,
+}
+
+/// Limits that raise warnings in a risk report
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RiskLimits {
+    /// Largest acceptable weight of one position
+    #[serde(default = "default_max_position_weight")]
+    pub max_position_weight: f64,
+    /// Largest acceptable weight of one sector
+    #[serde(default = "default_max_sector_weight")]
+    pub max_sector_weight: f64,
+}
+
+fn default_max_position_weight() -> f64 {
+    0.2
+}
+
+fn default_max_sector_weight() -> f64 {
+    0.4
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            max_position_weight: default_max_position_weight(),
+            max_sector_weight: default_max_sector_weight(),
+        }
+    }
+}
+
+/// Historical risk of the current holdings
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskReport {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    /// Daily returns the metrics are computed from
+    pub observations: usize,
+    pub total_return: f64,
+    pub annualized_return: f64,
+    pub annualized_volatility: f64,
+    /// Annualized, `None` without volatility
+    pub sharpe_ratio: Option<f64>,
+    /// Largest peak-to-trough fall, as a positive fraction
+    pub max_drawdown: f64,
+    pub drawdown_peak: Option<DateTime<Utc>>,
+    pub drawdown_trough: Option<DateTime<Utc>>,
+    /// One-day loss not exceeded on 95% of days, in account currency
+    pub value_at_risk_95: f64,
+    pub concentration: Concentration,
+    pub warnings: Vec<String>,
+}
+
+fn number(value: &str, field: &str, symbol: &str) -> Result<f64> {
+    value.parse().map_err(|_| {
+        Error::parsing(format!(
+            "Invalid {} {:?} for position {}",
+            field, value, symbol
+        ))
+    })
+}
+
+/// Exposure by sector, unrealized P&L and concentration of `positions`
+///
+/// `sectors` maps symbols to sectors; others count as [`UNCLASSIFIED`].
+pub fn analyze(
+    positions: &[Position],
+    sectors: &HashMap<String, String>,
+) -> Result<PortfolioAnalysis> {
+    let mut holdings = Vec::with_capacity(positions.len());
+    for position in positions {
+        let symbol = &position.symbol;
+        // Alpaca reports shorts with negative quantity and value, but only trust the side
+        let sign = if position.side == "short" { -1.0 } else { 1.0 };
+        let qty = sign * number(&position.qty, "qty", symbol)?.abs();
+        let market_value = sign * number(&position.market_value, "market_value", symbol)?.abs();
+        holdings.push(Holding {
+            symbol: symbol.clone(),
+            sector: sectors
+                .get(symbol)
+                .cloned()
+                .unwrap_or_else(|| UNCLASSIFIED.to_string()),
+            qty,
+            market_value,
+            cost_basis: qty * number(&position.avg_entry_price, "avg_entry_price", symbol)?,
+            unrealized_pl: number(&position.unrealized_pl, "unrealized_pl", symbol)?,
+            unrealized_plpc: number(&position.unrealized_plpc, "unrealized_plpc", symbol)?,
+            weight: 0.0,
+        });
+    }
+
+    let gross_exposure: f64 = holdings.iter().map(|h| h.market_value.abs()).sum();
+    for holding in &mut holdings {
+        holding.weight = share(holding.market_value.abs(), gross_exposure);
+    }
+    holdings.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+    let mut by_sector: BTreeMap<&str, SectorExposure> = BTreeMap::new();
+    for holding in &holdings {
+        let exposure = by_sector
+            .entry(&holding.sector)
+            .or_insert_with(|| SectorExposure {
+                sector: holding.sector.clone(),
+                long: 0.0,
+                short: 0.0,
+                net: 0.0,
+                weight: 0.0,
+                symbols: Vec::new(),
+            });
+        if holding.market_value < 0.0 {
+            exposure.short -= holding.market_value;
+        } else {
+            exposure.long += holding.market_value;
+        }
+        exposure.net += holding.market_value;
+        exposure.weight += holding.weight;
+        exposure.symbols.push(holding.symbol.clone());
+    }
+    let mut sectors: Vec<SectorExposure> = by_sector.into_values().collect();
+    sectors.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+
+    let long_value: f64 = sectors.iter().map(|s| s.long).sum();
+    let short_value: f64 = sectors.iter().map(|s| s.short).sum();
+    let unrealized_pl: f64 = holdings.iter().map(|h| h.unrealized_pl).sum();
+    let cost: f64 = holdings.iter().map(|h| h.cost_basis.abs()).sum();
+    let concentration = concentration(&holdings);
+    Ok(PortfolioAnalysis {
+        holdings,
+        sectors,
+        long_value,
+        short_value,
+        gross_exposure,
+        net_exposure: long_value - short_value,
+        unrealized_pl,
+        unrealized_plpc: share(unrealized_pl, cost),
+        concentration,
+    })
+}
+
+fn share(part: f64, whole: f64) -> f64 {
+    if whole == 0.0 {
+        0.0
+    } else {
+        part / whole
+    }
+}
+
+/// Concentration of holdings sorted by descending weight
+fn concentration(holdings: &[Holding]) -> Concentration {
+    let herfindahl: f64 = holdings.iter().map(|h| h.weight * h.weight).sum();
+    Concentration {
+        largest_symbol: holdings.first().map(|h| h.symbol.clone()),
+        largest_weight: holdings.first().map_or(0.0, |h| h.weight),
+        top5_weight: holdings.iter().take(5).map(|h| h.weight).sum(),
+        herfindahl,
+        effective_positions: if herfindahl > 0.0 {
+            1.0 / herfindahl
+        } else {
+            0.0
+        },
+    }
+}
+
+/// Daily returns of the holdings, held at their current quantities
+///
+/// Uses the days every held symbol has a close for. A day's return is its
+/// change in net value over the previous day's gross exposure, which stays
+/// meaningful for long-short portfolios with little net value.
+pub fn daily_returns(
+    holdings: &[Holding],
+    bars: &HashMap<String, Vec<Bar>>,
+) -> Vec<(DateTime<Utc>, f64)> {
+    let mut days: BTreeMap<DateTime<Utc>, (usize, f64, f64)> = BTreeMap::new();
+    for holding in holdings {
+        for bar in bars.get(&holding.symbol).into_iter().flatten() {
+            let day = days.entry(bar.timestamp).or_default();
+            day.0 += 1;
+            day.1 += holding.qty * bar.close;
+            day.2 += (holding.qty * bar.close).abs();
+        }
+    }
+    let complete: Vec<(DateTime<Utc>, f64, f64)> = days
+        .into_iter()
+        .filter(|(_, (count, _, _))| *count == holdings.len())
+        .map(|(day, (_, net, gross))| (day, net, gross))
+        .collect();
+    complete
+        .windows(2)
+        .filter(|pair| pair[0].2 > 0.0)
+        .map(|pair| (pair[1].0, (pair[1].1 - pair[0].1) / pair[0].2))
+        .collect()
+}
+
+/// Largest peak-to-trough fall of the returns compounded, with the indexes
+/// of the peak and trough; the peak index is `None` before the first return
+pub fn max_drawdown(returns: &[f64]) -> (f64, Option<usize>, Option<usize>) {
+    let (mut value, mut peak, mut peak_at) = (1.0, 1.0, None);
+    let mut worst = (0.0, None, None);
+    for (i, r) in returns.iter().enumerate() {
+        value *= 1.0 + r;
+        if value > peak {
+            peak = value;
+            peak_at = Some(i);
+        }
+        let drawdown = (peak - value) / peak;
+        if drawdown > worst.0 {
+            worst = (drawdown, peak_at, Some(i));
+        }
+    }
+    worst
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Sample standard deviation
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(values);
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    variance.sqrt()
+}
+
+/// Annualized Sharpe ratio of daily returns against a yearly risk-free rate
+pub fn sharpe_ratio(returns: &[f64], risk_free_rate: f64) -> Option<f64> {
+    let deviation = std_dev(returns);
+    if deviation == 0.0 {
+        return None;
+    }
+    let excess = mean(returns) - risk_free_rate / TRADING_DAYS;
+    Some(excess / deviation * TRADING_DAYS.sqrt())
+}
+
+/// Risk metrics of `analysis` from its daily returns
+pub fn risk_report(
+    analysis: &PortfolioAnalysis,
+    history: &[(DateTime<Utc>, f64)],
+    risk_free_rate: f64,
+    limits: RiskLimits,
+) -> RiskReport {
+    let returns: Vec<f64> = history.iter().map(|(_, r)| *r).collect();
+    let total_return = returns.iter().fold(1.0, |value, r| value * (1.0 + r)) - 1.0;
+    let (max_drawdown, peak, trough) = max_drawdown(&returns);
+    let value_at_risk_95 = if returns.is_empty() {
+        0.0
+    } else {
+        let mut sorted = returns.clone();
+        sorted.sort_by(f64::total_cmp);
+        let worst = sorted[(sorted.len() as f64 * 0.05).floor() as usize];
+        (-worst).max(0.0) * analysis.gross_exposure
+    };
+
+    let mut warnings = Vec::new();
+    for holding in &analysis.holdings {
+        if holding.weight > limits.max_position_weight {
+            warnings.push(format!(
+                "{} is {:.1}% of gross exposure, above the {:.1}% position limit",
+                holding.symbol,
+                holding.weight * 100.0,
+                limits.max_position_weight * 100.0
+            ));
+        }
+    }
+    for sector in &analysis.sectors {
+        if sector.weight > limits.max_sector_weight {
+            warnings.push(format!(
+                "{} is {:.1}% of gross exposure, above the {:.1}% sector limit",
+                sector.sector,
+                sector.weight * 100.0,
+                limits.max_sector_weight * 100.0
+            ));
+        }
+    }
+    if analysis.sectors.iter().any(|s| s.sector == UNCLASSIFIED) {
+        warnings.push(format!(
+            "Some symbols have no sector and are grouped as {}",
+            UNCLASSIFIED
+        ));
+    }
+    if returns.len() < MIN_OBSERVATIONS {
+        warnings.push(format!(
+            "Only {} daily returns; the historical metrics are rough",
+            returns.len()
+        ));
+    }
+
+    RiskReport {
+        start: history.first().map(|(day, _)| *day),
+        end: history.last().map(|(day, _)| *day),
+        observations: returns.len(),
+        total_return,
+        annualized_return: if returns.is_empty() {
+            0.0
+        } else {
+            (1.0 + total_return).powf(TRADING_DAYS / returns.len() as f64) - 1.0
+        },
+        annualized_volatility: std_dev(&returns) * TRADING_DAYS.sqrt(),
+        sharpe_ratio: sharpe_ratio(&returns, risk_free_rate),
+        max_drawdown,
+        drawdown_peak: peak.map(|i| history[i].0),
+        drawdown_trough: trough.map(|i| history[i].0),
+        value_at_risk_95,
+        concentration: analysis.concentration.clone(),
+        warnings,
+    }
+}
+
+impl AlpacaClient {
+    /// Analyze the account's open positions
+    pub async fn analyze_portfolio(
+        &self,
+        sectors: &HashMap<String, String>,
+    ) -> Result<PortfolioAnalysis> {
+        analyze(&self.get_positions().await?, sectors)
+    }
+
+    /// Analysis and risk report of the open positions over the last `days` days
+    pub async fn risk_report(
+        &self,
+        sectors: &HashMap<String, String>,
+        days: i64,
+        risk_free_rate: f64,
+        limits: RiskLimits,
+    ) -> Result<(PortfolioAnalysis, RiskReport)> {
+        let analysis = self.analyze_portfolio(sectors).await?;
+        let bars = try_join_all(
+            analysis
+                .holdings
+                .iter()
+                .map(|holding| self.get_stock_bars(&holding.symbol, days)),
+        )
+        .await?;
+        let bars: HashMap<String, Vec<Bar>> = analysis
+            .holdings
+            .iter()
+            .map(|holding| holding.symbol.clone())
+            .zip(bars)
+            .collect();
+        let history = daily_returns(&analysis.holdings, &bars);
+        let report = risk_report(&analysis, &history, risk_free_rate, limits);
+        Ok((analysis, report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn position(
+        symbol: &str,
+        side: &str,
+        qty: &str,
+        value: &str,
+        entry: &str,
+        pl: &str,
+    ) -> Position {
+        Position {
+            symbol: symbol.to_string(),
+            qty: qty.to_string(),
+            side: side.to_string(),
+            market_value: value.to_string(),
+            avg_entry_price: entry.to_string(),
+            current_price: "0".to_string(),
+            unrealized_pl: pl.to_string(),
+            unrealized_plpc: "0".to_string(),
+        }
+    }
+
+    fn bars(symbol: &str, closes: &[f64]) -> Vec<Bar> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(day, close)| Bar {
+                symbol: symbol.to_string(),
+                timestamp: Utc
+                    .with_ymd_and_hms(2026, 3, day as u32 + 2, 5, 0, 0)
+                    .unwrap(),
+                open: *close,
+                high: *close,
+                low: *close,
+                close: *close,
+                volume: 1000,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn analyzes_exposure_and_concentration() {
+        let positions = [
+            position("AAPL", "long", "10", "2000", "150", "500"),
+            position("MSFT", "long", "5", "1000", "220", "-100"),
+            position("XOM", "short", "-10", "-1000", "110", "100"),
+        ];
+        let sectors = HashMap::from([
+            ("AAPL".to_string(), "Technology".to_string()),
+            ("MSFT".to_string(), "Technology".to_string()),
+        ]);
+        let analysis = analyze(&positions, &sectors).unwrap();
+        assert_eq!(analysis.gross_exposure, 4000.0);
+        assert_eq!(
+            (analysis.long_value, analysis.short_value),
+            (3000.0, 1000.0)
+        );
+        assert_eq!(analysis.net_exposure, 2000.0);
+        assert_eq!(analysis.unrealized_pl, 500.0);
+        assert_eq!(analysis.unrealized_plpc, 500.0 / 3700.0);
+        assert_eq!(analysis.holdings[2].qty, -10.0);
+
+        let technology = &analysis.sectors[0];
+        assert_eq!(
+            (technology.sector.as_str(), technology.weight),
+            ("Technology", 0.75)
+        );
+        assert_eq!(analysis.sectors[1].short, 1000.0);
+
+        let concentration = &analysis.concentration;
+        assert_eq!(concentration.largest_symbol.as_deref(), Some("AAPL"));
+        assert_eq!(concentration.herfindahl, 0.375);
+
+        let report = risk_report(&analysis, &[], 0.0, RiskLimits::default());
+        assert_eq!(report.warnings.len(), 6);
+        assert!(report.sharpe_ratio.is_none());
+    }
+
+    #[test]
+    fn measures_drawdown_and_returns_from_bars() {
+        let positions = [
+            position("AAA", "long", "1", "100", "100", "0"),
+            position("BBB", "long", "1", "100", "100", "0"),
+        ];
+        let analysis = analyze(&positions, &HashMap::new()).unwrap();
+        let history = daily_returns(
+            &analysis.holdings,
+            &HashMap::from([
+                (
+                    "AAA".to_string(),
+                    bars("AAA", &[100.0, 110.0, 88.0, 99.0, 120.0]),
+                ),
+                // No close on the last day, so it is left out
+                ("BBB".to_string(), bars("BBB", &[100.0, 110.0, 88.0, 99.0])),
+            ]),
+        );
+        let returns: Vec<f64> = history.iter().map(|(_, r)| *r).collect();
+        assert_eq!(returns.len(), 3);
+        assert!((returns[0] - 0.1).abs() < 1e-12);
+        assert!((returns[1] + 0.2).abs() < 1e-12);
+
+        let (drawdown, peak, trough) = max_drawdown(&returns);
+        assert!((drawdown - 0.2).abs() < 1e-12);
+        assert_eq!((peak, trough), (Some(0), Some(1)));
+
+        let report = risk_report(&analysis, &history, 0.0, RiskLimits::default());
+        assert_eq!(report.drawdown_trough, Some(history[1].0));
+        assert!((report.total_return + 0.01).abs() < 1e-12);
+        assert!((report.value_at_risk_95 - 40.0).abs() < 1e-9);
+        assert!(report.sharpe_ratio.is_some());
+    }
+}
@@ -0,0 +1,105 @@
+//! Entry points shared by the cargo-fuzz targets in `fuzz/` and the fuzz tests
+//!
+//! Each function takes arbitrary input and runs it through a parser or
+//! validator the server exposes to clients. They return nothing: the only
+//! property checked is that no input panics. Tool calls dispatch against the
+//! definitions the server modules register, so every real input schema is
+//! exercised, but the handlers echo their arguments instead of acting on them.
+
+use crate::database::safety;
+use crate::security::{SanitizationOptions, SecurityModule};
+use crate::tools::registry::{JsonRpcRequest, ToolRegistry};
+use crate::tools::{call_result, ServerModules};
+use crate::transport::jsonrpc::RawMessage;
+use crate::Config;
+use serde_json::Value;
+use std::sync::{Arc, OnceLock};
+
+/// Registry holding every tool the server registers from the default config,
+/// under its real definition, with a handler that echoes its arguments
+pub fn registry() -> &'static ToolRegistry {
+    static REGISTRY: OnceLock<ToolRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let modules = Arc::new(
+            block_on(ServerModules::from_config(&Config::default()))
+                .expect("server modules build from the default config"),
+        );
+        let mut server = ToolRegistry::new();
+        modules
+            .register(&mut server)
+            .expect("server tools register");
+        let registry = ToolRegistry::new();
+        for definition in server.definitions() {
+            registry
+                .register(definition, |arguments| async move {
+                    Ok(call_result("ok", arguments))
+                })
+                .expect("server tool schemas compile");
+        }
+        registry
+    })
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    thread_local! {
+        static RUNTIME: tokio::runtime::Runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("fuzz runtime");
+    }
+    RUNTIME.with(|runtime| runtime.block_on(future))
+}
+
+/// Parse a JSON-RPC message both ways the transports do, and dispatch it
+/// when it is a request
+pub fn jsonrpc(data: &[u8]) {
+    if let Ok(message) = RawMessage::from_slice(data) {
+        let _ = message.id_str();
+        let _ = message.is_notification();
+        let _ = message.result_value();
+        let _ = message.params_value();
+    }
+    if let Ok(request) = serde_json::from_slice::<JsonRpcRequest>(data) {
+        let response = block_on(registry().handle(request));
+        let _ = serde_json::to_vec(&response);
+    }
+}
+
+/// Validate arbitrary JSON as the arguments of every registered tool
+pub fn tool_arguments(data: &[u8]) {
+    let Ok(arguments) = serde_json::from_slice::<Value>(data) else {
+        return;
+    };
+    for definition in registry().definitions() {
+        call(&definition.name, arguments.clone());
+    }
+}
+
+/// Dispatch one tool call through the registry, discarding the outcome
+pub fn call(name: &str, arguments: Value) {
+    let _ = block_on(registry().call(name, arguments));
+}
+
+/// Run text through the path, resource name, URL, input and SQL validators
+pub fn validators(data: &[u8]) {
+    let text = String::from_utf8_lossy(data);
+    let security = SecurityModule::new();
+    let _ = security.validate_file_path(&text);
+    let _ = security.validate_resource_name(&text);
+    let _ = security.validate_url(&text);
+    for options in [
+        SanitizationOptions::default(),
+        SanitizationOptions {
+            max_length: Some(64),
+            allow_html: true,
+            allow_sql: true,
+            allow_shell_meta: false,
+        },
+    ] {
+        let _ = security.validate_input(&text, &options);
+    }
+    for provider in ["postgresql", "mysql", "sqlite", "mongodb"] {
+        let _ = safety::classify(provider, &text);
+        let _ = safety::is_read_only(provider, &text);
+    }
+}
@@ -0,0 +1,1001 @@
+/// Solicitation document analysis
+///
+/// Downloads a procurement notice's attachments (from SAM.gov or direct
+/// links), extracts their text with the office parsers and pulls out the
+/// sections bidders care about: scope of work, requirements, proposal
+/// instructions (Section L) and evaluation criteria (Section M), plus key
+/// dates. An LLM provider, when configured, refines the result into an
+/// opportunity brief with a summary and risks.
+use crate::ai::provider::{ChatMessage, LlmProvider};
+use crate::error::{Error, Result};
+use crate::office::parsers::{self, DocumentFormat, ExtractedBlock, ExtractedDocument};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{Duration, NaiveDate, Utc};
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+
+/// Longest section text kept in a brief
+const MAX_SECTION_CHARS: usize = 6000;
+/// Most requirement statements kept in a brief
+const MAX_REQUIREMENTS: usize = 60;
+
+/// Solicitation analysis configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentsConfig {
+    /// SAM.gov opportunities search endpoint
+    pub sam_api_url: String,
+    /// SAM.gov public API key
+    pub sam_api_key: Option<String>,
+    /// Attachments larger than this are skipped
+    pub max_attachment_bytes: usize,
+    /// Most attachments downloaded per notice
+    pub max_attachments: usize,
+    /// Characters of document text sent to the LLM
+    pub llm_context_chars: usize,
+}
+
+impl Default for DocumentsConfig {
+    fn default() -> Self {
+        Self {
+            sam_api_url: "https://api.sam.gov/prod/opportunities/v2/search".to_string(),
+            sam_api_key: std::env::var("SAM_API_KEY").ok(),
+            max_attachment_bytes: 25 * 1024 * 1024,
+            max_attachments: 10,
+            llm_context_chars: 60_000,
+        }
+    }
+}
+
+/// Kind of solicitation section
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    /// Statement, scope or performance work statement
+    Scope,
+    Requirements,
+    Deliverables,
+    PeriodOfPerformance,
+    /// Proposal preparation instructions (Section L)
+    Instructions,
+    /// Evaluation factors and basis for award (Section M)
+    Evaluation,
+}
+
+/// A section of a solicitation document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequirementSection {
+    pub kind: SectionKind,
+    pub heading: String,
+    pub text: String,
+    /// Attachment the section came from
+    pub source: String,
+}
+
+/// Kind of date mentioned in a solicitation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DateKind {
+    ResponseDue,
+    QuestionsDue,
+    SiteVisit,
+    Conference,
+    PerformanceStart,
+    PerformanceEnd,
+    Other,
+}
+
+/// A date found in the documents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyDate {
+    pub kind: DateKind,
+    pub date: NaiveDate,
+    pub time: Option<String>,
+    /// Line the date was found on
+    pub context: String,
+    pub source: String,
+}
+
+/// Outcome of processing one attachment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentSummary {
+    pub name: String,
+    pub url: Option<String>,
+    pub format: Option<DocumentFormat>,
+    pub pages: Option<usize>,
+    pub characters: usize,
+    pub error: Option<String>,
+}
+
+/// Procurement notice metadata from SAM.gov
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notice {
+    pub notice_id: String,
+    pub title: String,
+    pub solicitation_number: Option<String>,
+    pub agency: Option<String>,
+    pub posted_date: Option<String>,
+    pub response_deadline: Option<String>,
+    pub set_aside: Option<String>,
+    pub naics: Option<String>,
+    pub url: Option<String>,
+    pub attachment_urls: Vec<String>,
+}
+
+/// Structured summary of a procurement opportunity
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpportunityBrief {
+    pub title: String,
+    pub notice: Option<Notice>,
+    /// Response deadline from the notice, or the latest due date in the documents
+    pub response_due: Option<String>,
+    pub summary: Option<String>,
+    pub key_dates: Vec<KeyDate>,
+    pub sections: Vec<RequirementSection>,
+    pub requirements: Vec<String>,
+    pub evaluation_criteria: Vec<String>,
+    pub risks: Vec<String>,
+    pub attachments: Vec<AttachmentSummary>,
+    /// Model used to refine the brief, if any
+    pub model: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// A parsed attachment
+pub struct SourceDocument {
+    pub name: String,
+    pub document: ExtractedDocument,
+}
+
+fn classify_heading(heading: &str) -> Option<SectionKind> {
+    let lower = heading.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+    if lower.starts_with("section l ")
+        || lower.starts_with("section l-")
+        || has(&[
+            "instructions to offeror",
+            "instructions, conditions",
+            "proposal preparation",
+            "proposal submission",
+            "submission requirements",
+        ])
+    {
+        Some(SectionKind::Instructions)
+    } else if lower.starts_with("section m")
+        || has(&["evaluation", "basis for award", "basis of award"])
+    {
+        Some(SectionKind::Evaluation)
+    } else if has(&[
+        "statement of work",
+        "scope of work",
+        "performance work statement",
+        "statement of objectives",
+        "scope",
+        "background and objective",
+    ]) || lower.starts_with("section c")
+    {
+        Some(SectionKind::Scope)
+    } else if has(&["period of performance", "place of performance"]) {
+        Some(SectionKind::PeriodOfPerformance)
+    } else if has(&["deliverable", "reporting requirement"]) {
+        Some(SectionKind::Deliverables)
+    } else if has(&[
+        "requirement",
+        "specification",
+        "technical approach",
+        "tasks",
+    ]) {
+        Some(SectionKind::Requirements)
+    } else {
+        None
+    }
+}
+
+fn heading_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"^(?:(?i:section|part|attachment|article)\s+[A-Z0-9]+\b|(?:[A-Z]\.)?\d+(?:\.\d+)*\.?\s+[A-Z])")
+            .expect("valid heading pattern")
+    })
+}
+
+/// Heading level of a line in unstructured text, if it looks like a heading
+fn line_heading_level(line: &str) -> Option<u8> {
+    let line = line.trim();
+    if line.len() < 4 || line.len() > 100 || line.ends_with(['.', ',', ';']) {
+        return None;
+    }
+    if let Some(m) = heading_pattern().find(line) {
+        let prefix = m.as_str().split_whitespace().next().unwrap_or("");
+        if prefix.eq_ignore_ascii_case("section") || prefix.eq_ignore_ascii_case("part") {
+            return Some(1);
+        }
+        let depth = prefix.trim_end_matches('.').split('.').count();
+        return Some(depth.min(6) as u8);
+    }
+    let letters: Vec<char> = line.chars().filter(|c| c.is_alphabetic()).collect();
+    (letters.len() >= 6 && letters.iter().all(|c| c.is_uppercase())).then_some(1)
+}
+
+/// Split a document into the sections relevant to bidders
+pub fn extract_sections(document: &ExtractedDocument, source: &str) -> Vec<RequirementSection> {
+    let mut sections = Vec::new();
+    let mut current: Option<(u8, RequirementSection)> = None;
+
+    let mut finish = |current: &mut Option<(u8, RequirementSection)>| {
+        if let Some((_, mut section)) = current.take() {
+            section.text = section.text.trim().to_string();
+            if !section.text.is_empty() {
+                sections.push(section);
+            }
+        }
+    };
+
+    for block in &document.blocks {
+        let (level, text) = match block {
+            ExtractedBlock::Heading { level, text } => (Some(*level), text.as_str()),
+            ExtractedBlock::Paragraph { text } => (line_heading_level(text), text.as_str()),
+            ExtractedBlock::PageBreak => continue,
+        };
+
+        if let Some(level) = level {
+            let ends_current = current.as_ref().is_some_and(|(open, _)| level <= *open);
+            let kind = classify_heading(text);
+            if ends_current || (kind.is_some() && current.is_none()) {
+                finish(&mut current);
+            }
+            if current.is_none() {
+                if let Some(kind) = kind {
+                    current = Some((
+                        level,
+                        RequirementSection {
+                            kind,
+                            heading: text.to_string(),
+                            text: String::new(),
+                            source: source.to_string(),
+                        },
+                    ));
+                    continue;
+                }
+            }
+        }
+
+        if let Some((_, section)) = current.as_mut() {
+            if section.text.len() < MAX_SECTION_CHARS {
+                section.text.push_str(text);
+                section.text.push('\n');
+            }
+        }
+    }
+    finish(&mut current);
+    sections
+}
+
+fn date_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        let month = r"(?:Jan(?:uary)?|Feb(?:ruary)?|Mar(?:ch)?|Apr(?:il)?|May|June?|July?|Aug(?:ust)?|Sep(?:t(?:ember)?)?|Oct(?:ober)?|Nov(?:ember)?|Dec(?:ember)?)\.?";
+        Regex::new(&format!(
+            r"(?i)\b(?:{month}\s+\d{{1,2}},?\s+\d{{4}}|\d{{1,2}}\s+{month},?\s+\d{{4}}|\d{{1,2}}/\d{{1,2}}/\d{{2,4}}|\d{{4}}-\d{{2}}-\d{{2}})\b"
+        ))
+        .expect("valid date pattern")
+    })
+}
+
+fn time_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)\b\d{1,2}(?::\d{2})?\s*(?:a\.?m\.?|p\.?m\.?)(?:\s*\(?(?:[ECMP][SD]?T|local time)\)?)?|\b\d{1,2}:\d{2}(?:\s*(?:[ECMP][SD]?T))?\b")
+            .expect("valid time pattern")
+    })
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    let cleaned = text
+        .replace(['.', ','], "")
+        .replace("Sept ", "Sep ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    ["%B %d %Y", "%d %B %Y", "%m/%d/%Y", "%m/%d/%y", "%Y-%m-%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&cleaned, format).ok())
+}
+
+fn classify_date(context: &str) -> DateKind {
+    let lower = context.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+    if has(&["question", "inquiries", "inquiry", "rfi due"]) {
+        DateKind::QuestionsDue
+    } else if has(&[
+        "site visit",
+        "site inspection",
+        "walk-through",
+        "walkthrough",
+    ]) {
+        DateKind::SiteVisit
+    } else if has(&[
+        "pre-proposal",
+        "pre-bid",
+        "preproposal",
+        "conference",
+        "industry day",
+    ]) {
+        DateKind::Conference
+    } else if has(&[
+        "proposal",
+        "quote",
+        "quotation",
+        "offer",
+        "response",
+        "closing",
+        "bid",
+        "due date",
+        "deadline",
+        "submitted no later",
+    ]) {
+        DateKind::ResponseDue
+    } else if has(&[
+        "start",
+        "commence",
+        "award",
+        "period of performance",
+        "effective",
+    ]) {
+        DateKind::PerformanceStart
+    } else {
+        DateKind::Other
+    }
+}
+
+/// Find dates in a document and classify them by their surrounding text
+pub fn extract_dates(document: &ExtractedDocument, source: &str) -> Vec<KeyDate> {
+    let lines: Vec<&str> = document
+        .blocks
+        .iter()
+        .filter_map(|b| match b {
+            ExtractedBlock::Heading { text, .. } | ExtractedBlock::Paragraph { text } => {
+                Some(text.as_str())
+            }
+            ExtractedBlock::PageBreak => None,
+        })
+        .flat_map(str::lines)
+        .collect();
+
+    let mut dates = Vec::new();
+    let mut seen = HashSet::new();
+    for (i, line) in lines.iter().enumerate() {
+        let matches: Vec<_> = date_pattern().find_iter(line).collect();
+        for (n, m) in matches.iter().enumerate() {
+            let Some(date) = parse_date(m.as_str()) else {
+                continue;
+            };
+            // Labels usually precede the date on the same line or the line before
+            let label_start = if n == 0 { 0 } else { matches[n - 1].end() };
+            let mut context = line[label_start..m.start()].to_string();
+            if context.trim().len() < 8 && i > 0 {
+                context = format!("{} {}", lines[i - 1], context);
+            }
+            let mut kind = classify_date(&context);
+            // "from <date> through <date>" ranges
+            if kind == DateKind::PerformanceStart && n > 0 {
+                kind = DateKind::PerformanceEnd;
+            }
+            if !seen.insert((kind, date)) {
+                continue;
+            }
+            let time = time_pattern()
+                .find(&line[m.end()..])
+                .map(|t| t.as_str().trim().to_string());
+            dates.push(KeyDate {
+                kind,
+                date,
+                time,
+                context: line.trim().chars().take(240).collect(),
+                source: source.to_string(),
+            });
+        }
+    }
+    dates
+}
+
+/// Sentences stating obligations ("shall", "must")
+pub fn extract_requirements(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    text.split(['\n', ';'])
+        .flat_map(|chunk| chunk.split(". "))
+        .map(|s| s.trim().trim_start_matches(['•', '-', '*', '·']).trim())
+        .filter(|s| {
+            let lower = s.to_lowercase();
+            s.len() >= 20
+                && (lower.contains(" shall ")
+                    || lower.contains(" must ")
+                    || lower.contains(" is required to "))
+        })
+        .map(|s| s.chars().take(400).collect::<String>())
+        .filter(|s| seen.insert(s.to_lowercase()))
+        .take(MAX_REQUIREMENTS)
+        .collect()
+}
+
+/// Evaluation factors listed in Section M style text
+fn extract_evaluation_criteria(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    text.lines()
+        .map(str::trim)
+        .filter(|line| {
+            let lower = line.to_lowercase();
+            line.len() <= 160
+                && (lower.starts_with("factor")
+                    || lower.contains("subfactor")
+                    || (line_heading_level(line).is_some() && !lower.contains("evaluation")))
+        })
+        .filter(|line| seen.insert(line.to_lowercase()))
+        .map(str::to_string)
+        .take(20)
+        .collect()
+}
+
+/// Build a brief from parsed documents using text heuristics only
+pub fn heuristic_brief(title: impl Into<String>, documents: &[SourceDocument]) -> OpportunityBrief {
+    let mut brief = OpportunityBrief {
+        title: title.into(),
+        ..Default::default()
+    };
+    let mut seen_dates = HashSet::new();
+    for source in documents {
+        brief
+            .sections
+            .extend(extract_sections(&source.document, &source.name));
+        for date in extract_dates(&source.document, &source.name) {
+            if seen_dates.insert((date.kind, date.date)) {
+                brief.key_dates.push(date);
+            }
+        }
+    }
+    brief.key_dates.sort_by_key(|d| d.date);
+
+    let obligation_text: String = if brief.sections.iter().any(|s| {
+        matches!(
+            s.kind,
+            SectionKind::Scope | SectionKind::Requirements | SectionKind::Deliverables
+        )
+    }) {
+        brief
+            .sections
+            .iter()
+            .filter(|s| {
+                matches!(
+                    s.kind,
+                    SectionKind::Scope | SectionKind::Requirements | SectionKind::Deliverables
+                )
+            })
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        documents
+            .iter()
+            .map(|d| d.document.text())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    brief.requirements = extract_requirements(&obligation_text);
+    brief.evaluation_criteria = brief
+        .sections
+        .iter()
+        .filter(|s| s.kind == SectionKind::Evaluation)
+        .flat_map(|s| extract_evaluation_criteria(&s.text))
+        .collect();
+    brief.response_due = brief
+        .key_dates
+        .iter()
+        .filter(|d| d.kind == DateKind::ResponseDue)
+        .map(|d| match &d.time {
+            Some(time) => format!("{} {}", d.date, time),
+            None => d.date.to_string(),
+        })
+        .next_back();
+    brief
+}
+
+fn text_field(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn string_list(value: &Value, key: &str) -> Vec<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|i| i.as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Downloads and analyses solicitation documents
+pub struct SolicitationAnalyzer {
+    client: Client,
+    config: DocumentsConfig,
+    llm: Option<Arc<dyn LlmProvider>>,
+}
+
+impl SolicitationAnalyzer {
+    /// Create an analyzer without an LLM
+    pub fn new(config: DocumentsConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create documents client: {}", e)))?;
+        Ok(Self {
+            client,
+            config,
+            llm: None,
+        })
+    }
+
+    /// Refine briefs with the given model
+    pub fn with_llm(mut self, provider: Arc<dyn LlmProvider>) -> Self {
+        self.llm = Some(provider);
+        self
+    }
+
+    fn sam_key(&self) -> Result<&str> {
+        self.config.sam_api_key.as_deref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "SAM.gov API key not provided",
+                "Request a public API key from your SAM.gov account and set SAM_API_KEY",
+            )
+        })
+    }
+
+    /// Look up a notice and its attachment links on SAM.gov
+    pub async fn fetch_notice(&self, notice_id: &str) -> Result<Notice> {
+        let today = Utc::now().date_naive();
+        let response = self
+            .client
+            .get(&self.config.sam_api_url)
+            .query(&[
+                ("api_key", self.sam_key()?),
+                ("noticeid", notice_id),
+                ("limit", "1"),
+                // The search API requires a posting window of at most a year
+                (
+                    "postedFrom",
+                    &(today - Duration::days(364)).format("%m/%d/%Y").to_string(),
+                ),
+                ("postedTo", &today.format("%m/%d/%Y").to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| {
+                Error::network_with_endpoint(
+                    format!("SAM.gov request failed: {}", e),
+                    self.config.sam_api_url.clone(),
+                )
+            })?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(Error::api_with_status(
+                format!("SAM.gov returned {}", status),
+                "sam.gov",
+                status.as_u16(),
+            ));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse SAM.gov response: {}", e)))?;
+        let data = body
+            .get("opportunitiesData")
+            .and_then(|d| d.as_array())
+            .and_then(|d| d.first())
+            .ok_or_else(|| {
+                Error::not_found_with_resource("Notice not found on SAM.gov", "notice", notice_id)
+            })?;
+
+        Ok(Notice {
+            notice_id: text_field(data, "noticeId").unwrap_or_else(|| notice_id.to_string()),
+            title: text_field(data, "title").unwrap_or_default(),
+            solicitation_number: text_field(data, "solicitationNumber"),
+            agency: text_field(data, "fullParentPathName"),
+            posted_date: text_field(data, "postedDate"),
+            response_deadline: text_field(data, "responseDeadLine"),
+            set_aside: text_field(data, "typeOfSetAsideDescription"),
+            naics: text_field(data, "naicsCode"),
+            url: text_field(data, "uiLink"),
+            attachment_urls: string_list(data, "resourceLinks"),
+        })
+    }
+
+    /// Download an attachment, returning its file name and bytes
+    pub async fn download(&self, url: &str) -> Result<(String, Vec<u8>)> {
+        // SAM.gov resource links carry a placeholder key
+        let url = match &self.config.sam_api_key {
+            Some(key) if url.contains("api_key=null") => {
+                url.replace("api_key=null", &format!("api_key={}", key))
+            }
+            _ => url.to_string(),
+        };
+        let response = self.client.get(&url).send().await.map_err(|e| {
+            Error::network_with_endpoint(format!("Attachment download failed: {}", e), url.clone())
+        })?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(Error::api_with_status(
+                format!("Attachment download returned {}", status),
+                "attachments",
+                status.as_u16(),
+            ));
+        }
+        if response
+            .content_length()
+            .is_some_and(|len| len as usize > self.config.max_attachment_bytes)
+        {
+            return Err(Error::validation(format!(
+                "Attachment exceeds {} bytes",
+                self.config.max_attachment_bytes
+            )));
+        }
+
+        let name = response
+            .headers()
+            .get(reqwest::header::CONTENT_DISPOSITION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split("filename=").nth(1))
+            .map(|n| n.trim_matches(['"', '\'', ' ', ';']).to_string())
+            .filter(|n| !n.is_empty())
+            .or_else(|| {
+                reqwest::Url::parse(&url).ok().and_then(|u| {
+                    u.path_segments()
+                        .and_then(|mut s| s.next_back().map(str::to_string))
+                        .filter(|s| !s.is_empty())
+                })
+            })
+            .unwrap_or_else(|| "attachment".to_string());
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| Error::network(format!("Failed to read attachment: {}", e)))?;
+        if bytes.len() > self.config.max_attachment_bytes {
+            return Err(Error::validation(format!(
+                "Attachment exceeds {} bytes",
+                self.config.max_attachment_bytes
+            )));
+        }
+        Ok((name, bytes.to_vec()))
+    }
+
+    /// Download and parse attachments, recording failures instead of aborting
+    async fn load_documents(
+        &self,
+        urls: &[String],
+    ) -> (Vec<SourceDocument>, Vec<AttachmentSummary>) {
+        let mut documents = Vec::new();
+        let mut summaries = Vec::new();
+        for url in urls.iter().take(self.config.max_attachments) {
+            let result = match self.download(url).await {
+                Ok((name, bytes)) => {
+                    let parsed = parsers::extract(&bytes, Some(&name));
+                    (name, parsed)
+                }
+                Err(e) => (url.rsplit('/').next().unwrap_or(url).to_string(), Err(e)),
+            };
+            match result {
+                (name, Ok(document)) => {
+                    summaries.push(AttachmentSummary {
+                        name: name.clone(),
+                        url: Some(url.clone()),
+                        format: Some(document.format),
+                        pages: document.pages,
+                        characters: document.text().len(),
+                        error: None,
+                    });
+                    documents.push(SourceDocument { name, document });
+                }
+                (name, Err(e)) => summaries.push(AttachmentSummary {
+                    name,
+                    url: Some(url.clone()),
+                    format: None,
+                    pages: None,
+                    characters: 0,
+                    error: Some(e.to_string()),
+                }),
+            }
+        }
+        (documents, summaries)
+    }
+
+    async fn refine_with_llm(
+        &self,
+        provider: &dyn LlmProvider,
+        brief: &mut OpportunityBrief,
+        documents: &[SourceDocument],
+    ) -> Result<()> {
+        // Relevant sections first, then the rest of the text up to the budget
+        let mut context = String::new();
+        for section in &brief.sections {
+            context.push_str(&format!(
+                "## {} ({})\n{}\n\n",
+                section.heading, section.source, section.text
+            ));
+        }
+        for source in documents {
+            if context.len() >= self.config.llm_context_chars {
+                break;
+            }
+            context.push_str(&format!(
+                "# {}\n{}\n\n",
+                source.name,
+                source.document.text()
+            ));
+        }
+        let context: String = context
+            .chars()
+            .take(self.config.llm_context_chars)
+            .collect();
+
+        let metadata = brief
+            .notice
+            .as_ref()
+            .map(|n| serde_json::to_string(n).unwrap_or_default())
+            .unwrap_or_default();
+        let messages = [
+            ChatMessage::system(
+                "You analyse government solicitations for bidders. Reply with a single JSON object \
+                 and nothing else, using exactly these keys: \
+                 \"summary\" (3-5 sentences on what is being bought and by whom), \
+                 \"requirements\" (array of the key mandatory requirements), \
+                 \"evaluation_criteria\" (array of evaluation factors in order of importance), \
+                 \"key_dates\" (array of {\"kind\": one of response_due, questions_due, site_visit, \
+                 conference, performance_start, performance_end, other, \"date\": \"YYYY-MM-DD\", \
+                 \"time\": string or null}), \
+                 \"risks\" (array of unusual terms, tight timelines or eligibility constraints). \
+                 Only use facts stated in the documents.",
+            ),
+            ChatMessage::user(format!(
+                "Notice metadata: {}\n\nDocuments:\n{}",
+                metadata, context
+            )),
+        ];
+        let reply = provider.complete_json(&messages).await?;
+
+        brief.summary = text_field(&reply, "summary");
+        let requirements = string_list(&reply, "requirements");
+        if !requirements.is_empty() {
+            brief.requirements = requirements;
+        }
+        let criteria = string_list(&reply, "evaluation_criteria");
+        if !criteria.is_empty() {
+            brief.evaluation_criteria = criteria;
+        }
+        brief.risks = string_list(&reply, "risks");
+
+        let known: HashSet<_> = brief.key_dates.iter().map(|d| (d.kind, d.date)).collect();
+        for item in reply
+            .get("key_dates")
+            .and_then(|d| d.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let Some(date) = item
+                .get("date")
+                .and_then(|d| d.as_str())
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            else {
+                continue;
+            };
+            let kind = item
+                .get("kind")
+                .cloned()
+                .and_then(|k| serde_json::from_value(k).ok())
+                .unwrap_or(DateKind::Other);
+            if known.contains(&(kind, date)) {
+                continue;
+            }
+            brief.key_dates.push(KeyDate {
+                kind,
+                date,
+                time: text_field(item, "time"),
+                context: "Identified by model".to_string(),
+                source: provider.model().to_string(),
+            });
+        }
+        brief.key_dates.sort_by_key(|d| d.date);
+        brief.model = Some(provider.model().to_string());
+        Ok(())
+    }
+
+    /// Build a brief for a SAM.gov notice or a list of attachment URLs
+    pub async fn brief(
+        &self,
+        notice_id: Option<&str>,
+        urls: &[String],
+        title: Option<&str>,
+        use_llm: bool,
+    ) -> Result<OpportunityBrief> {
+        let notice = match notice_id {
+            Some(id) => Some(self.fetch_notice(id).await?),
+            None => None,
+        };
+        let mut all_urls: Vec<String> = notice
+            .as_ref()
+            .map(|n| n.attachment_urls.clone())
+            .unwrap_or_default();
+        all_urls.extend(urls.iter().cloned());
+        if all_urls.is_empty() {
+            return Err(Error::validation(
+                "No attachments to analyse; provide a notice_id with attachments or document urls",
+            ));
+        }
+
+        let (documents, attachments) = self.load_documents(&all_urls).await;
+        let title = title
+            .map(str::to_string)
+            .or_else(|| notice.as_ref().map(|n| n.title.clone()))
+            .unwrap_or_else(|| "Solicitation".to_string());
+        let mut brief = heuristic_brief(title, &documents);
+        brief.warnings.extend(
+            attachments
+                .iter()
+                .filter_map(|a| a.error.as_ref().map(|e| format!("{}: {}", a.name, e))),
+        );
+        if documents
+            .iter()
+            .any(|d| d.document.text().trim().is_empty())
+        {
+            brief
+                .warnings
+                .push("Some attachments contain no extractable text (scanned images?)".to_string());
+        }
+        if let Some(deadline) = notice.as_ref().and_then(|n| n.response_deadline.clone()) {
+            brief.response_due = Some(deadline);
+        }
+        brief.notice = notice;
+        brief.attachments = attachments;
+
+        if use_llm && !documents.is_empty() {
+            match &self.llm {
+                Some(provider) => {
+                    if let Err(e) = self
+                        .refine_with_llm(provider.as_ref(), &mut brief, &documents)
+                        .await
+                    {
+                        tracing::warn!(error = %e, "LLM refinement of solicitation brief failed");
+                        brief.warnings.push(format!("LLM refinement failed: {}", e));
+                    }
+                }
+                None => brief.warnings.push(
+                    "No LLM provider configured; brief uses text heuristics only".to_string(),
+                ),
+            }
+        }
+        Ok(brief)
+    }
+
+    /// Get tool definitions for solicitation analysis
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "solicitation_brief",
+            "Download a solicitation's attachments (PDF/Word) and produce an opportunity brief with requirements, evaluation criteria and key dates",
+            "government",
+            json!({
+                "type": "object",
+                "properties": {
+                    "notice_id": {"type": "string", "description": "SAM.gov notice id; its attachments are analysed"},
+                    "urls": {"type": "array", "items": {"type": "string"}, "description": "Additional attachment URLs"},
+                    "title": {"type": "string"},
+                    "use_llm": {"type": "boolean", "default": true, "description": "Refine the brief with the configured LLM"}
+                }
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a solicitation tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "solicitation_brief" => {
+                let urls = string_list(&parameters, "urls");
+                let brief = self
+                    .brief(
+                        parameters.get("notice_id").and_then(|v| v.as_str()),
+                        &urls,
+                        parameters.get("title").and_then(|v| v.as_str()),
+                        parameters
+                            .get("use_llm")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(true),
+                    )
+                    .await?;
+
+                let mut lines = vec![brief.title.clone()];
+                if let Some(due) = &brief.response_due {
+                    lines.push(format!("Responses due: {}", due));
+                }
+                if let Some(summary) = &brief.summary {
+                    lines.push(summary.clone());
+                }
+                lines.extend(brief.key_dates.iter().map(|d| {
+                    format!(
+                        "{:?}: {}{}",
+                        d.kind,
+                        d.date,
+                        d.time
+                            .as_ref()
+                            .map(|t| format!(" {}", t))
+                            .unwrap_or_default()
+                    )
+                }));
+                lines.push(format!(
+                    "{} sections, {} requirements, {} evaluation criteria from {} attachments",
+                    brief.sections.len(),
+                    brief.requirements.len(),
+                    brief.evaluation_criteria.len(),
+                    brief.attachments.len()
+                ));
+                lines.extend(brief.warnings.iter().map(|w| format!("Warning: {}", w)));
+                Ok(call_result(lines.join("\n"), serde_json::to_value(&brief)?))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "documents_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_brief_from_solicitation_text() {
+        let text = "REQUEST FOR PROPOSAL\n\
+            Questions must be submitted by March 3, 2025.\n\
+            Proposals are due 03/17/2025 at 2:00 PM EST.\n\
+            SECTION C - STATEMENT OF WORK\n\
+            C.1 Background\n\
+            The contractor shall provide help desk support for 300 users.\n\
+            The contractor shall deliver monthly status reports.\n\
+            SECTION M - EVALUATION FACTORS FOR AWARD\n\
+            Factor 1: Technical Approach\n\
+            Factor 2: Past Performance\n\
+            SECTION K - REPRESENTATIONS\n\
+            Offerors must be registered in SAM.\n";
+        let document = parsers::extract(text.as_bytes(), Some("rfp.txt")).unwrap();
+        let brief = heuristic_brief(
+            "Help desk",
+            &[SourceDocument {
+                name: "rfp.txt".to_string(),
+                document,
+            }],
+        );
+
+        let kinds: Vec<_> = brief.sections.iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, vec![SectionKind::Scope, SectionKind::Evaluation]);
+        assert_eq!(brief.requirements.len(), 2);
+        assert_eq!(
+            brief.evaluation_criteria,
+            vec!["Factor 1: Technical Approach", "Factor 2: Past Performance"]
+        );
+        assert_eq!(brief.key_dates[0].kind, DateKind::QuestionsDue);
+        assert_eq!(
+            brief.response_due.as_deref(),
+            Some("2025-03-17 2:00 PM EST")
+        );
+    }
+}
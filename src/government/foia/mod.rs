@@ -0,0 +1,888 @@
+/// FOIA request tracking via the FOIA.gov API
+///
+/// Agency components are searched through FOIA.gov. Requests are drafted and
+/// tracked locally; components that accept FOIA.gov web forms can be
+/// submitted directly, and the agency's tracking number is recorded against
+/// the local request so its status can be followed up.
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tokio::sync::RwLock;
+
+/// Working days an agency has to respond (5 U.S.C. 552(a)(6)(A)(i))
+const RESPONSE_WORKING_DAYS: u32 = 20;
+
+/// FOIA.gov client configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FoiaConfig {
+    /// FOIA.gov API base URL
+    pub base_url: String,
+    /// api.data.gov key
+    pub api_key: Option<String>,
+    /// Default requester details used for drafts
+    pub requester: Option<Requester>,
+}
+
+impl Default for FoiaConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://api.foia.gov/api".to_string(),
+            api_key: std::env::var("FOIA_API_KEY").ok(),
+            requester: None,
+        }
+    }
+}
+
+/// Person making a request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Requester {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub organization: Option<String>,
+}
+
+/// An agency component that handles FOIA requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgencyComponent {
+    pub id: String,
+    pub title: String,
+    pub abbreviation: Option<String>,
+    pub agency: Option<String>,
+    pub email: Option<String>,
+    pub website: Option<String>,
+    /// Agency's own request portal, if any
+    pub submission_web: Option<String>,
+    /// FOIA.gov web form id; present when requests can be submitted through the API
+    pub request_form_id: Option<String>,
+}
+
+/// Lifecycle of a request
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FoiaStatus {
+    Draft,
+    Submitted,
+    Acknowledged,
+    Processing,
+    Fulfilled,
+    PartiallyFulfilled,
+    Denied,
+    Appealed,
+    Withdrawn,
+}
+
+impl FoiaStatus {
+    /// Whether the agency still owes a response
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            FoiaStatus::Submitted
+                | FoiaStatus::Acknowledged
+                | FoiaStatus::Processing
+                | FoiaStatus::Appealed
+        )
+    }
+}
+
+impl FromStr for FoiaStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_value(Value::String(s.to_lowercase().replace([' ', '-'], "_"))).map_err(
+            |_| Error::validation_with_field(format!("Unknown FOIA status: {}", s), "status"),
+        )
+    }
+}
+
+/// A status transition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub status: FoiaStatus,
+    pub at: DateTime<Utc>,
+    pub note: Option<String>,
+}
+
+/// A locally tracked FOIA request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FoiaRequest {
+    /// Local identifier
+    pub id: String,
+    pub agency_component_id: String,
+    pub agency_name: String,
+    pub subject: String,
+    pub description: String,
+    pub requester: Requester,
+    /// Maximum fees the requester agrees to pay, in dollars
+    pub fee_limit: Option<f64>,
+    pub fee_waiver: bool,
+    pub expedited: bool,
+    pub status: FoiaStatus,
+    /// Agency tracking number
+    pub reference_number: Option<String>,
+    /// FOIA.gov submission id for API submissions
+    pub submission_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub history: Vec<StatusChange>,
+}
+
+impl FoiaRequest {
+    /// Statutory response due date, counted from submission
+    pub fn due_date(&self) -> Option<NaiveDate> {
+        self.submitted_at
+            .map(|at| add_working_days(at.date_naive(), RESPONSE_WORKING_DAYS))
+    }
+
+    /// Whether the response is past due
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        self.status.is_open() && self.due_date().is_some_and(|due| today > due)
+    }
+
+    fn set_status(&mut self, status: FoiaStatus, note: Option<String>) {
+        self.status = status;
+        self.history.push(StatusChange {
+            status,
+            at: Utc::now(),
+            note,
+        });
+    }
+}
+
+/// Add working days, skipping weekends (federal holidays are not excluded)
+pub fn add_working_days(start: NaiveDate, days: u32) -> NaiveDate {
+    let mut date = start;
+    let mut remaining = days;
+    while remaining > 0 {
+        date += Duration::days(1);
+        if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            remaining -= 1;
+        }
+    }
+    date
+}
+
+/// New request draft
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewFoiaRequest {
+    pub agency_component_id: String,
+    pub subject: String,
+    pub description: String,
+    /// Falls back to the configured requester
+    pub requester: Option<Requester>,
+    pub fee_limit: Option<f64>,
+    #[serde(default)]
+    pub fee_waiver: bool,
+    #[serde(default)]
+    pub expedited: bool,
+}
+
+fn parse_component(item: &Value, included: &[Value]) -> Option<AgencyComponent> {
+    let attributes = item.get("attributes")?;
+    let text = |name: &str| {
+        attributes.get(name).and_then(|v| match v {
+            Value::String(s) => Some(s.clone()),
+            Value::Array(values) => values.first().and_then(|v| v.as_str()).map(str::to_string),
+            Value::Object(_) => v.get("uri").and_then(|u| u.as_str()).map(str::to_string),
+            _ => None,
+        })
+    };
+    let agency = item
+        .pointer("/relationships/agency/data/id")
+        .and_then(|id| id.as_str())
+        .and_then(|id| {
+            included
+                .iter()
+                .find(|i| i.get("id").and_then(|v| v.as_str()) == Some(id))
+        })
+        .and_then(|a| a.pointer("/attributes/name"))
+        .and_then(|n| n.as_str())
+        .map(str::to_string);
+
+    Some(AgencyComponent {
+        id: item.get("id")?.as_str()?.to_string(),
+        title: text("title")?,
+        abbreviation: text("abbreviation"),
+        agency,
+        email: text("email"),
+        website: text("website"),
+        submission_web: text("submission_web"),
+        request_form_id: item
+            .pointer("/relationships/request_form/data/id")
+            .and_then(|id| id.as_str())
+            .map(str::to_string),
+    })
+}
+
+/// FOIA.gov client and local request tracker
+pub struct FoiaTracker {
+    client: Client,
+    config: FoiaConfig,
+    /// Where requests are persisted; `None` keeps them in memory
+    path: Option<PathBuf>,
+    requests: RwLock<BTreeMap<String, FoiaRequest>>,
+}
+
+impl FoiaTracker {
+    /// Open a tracker, loading requests from `path` if it exists
+    pub async fn open(config: FoiaConfig, path: Option<PathBuf>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create FOIA client: {}", e)))?;
+
+        let requests = match &path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse FOIA store {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read FOIA store: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => BTreeMap::new(),
+        };
+
+        Ok(Self {
+            client,
+            config,
+            path,
+            requests: RwLock::new(requests),
+        })
+    }
+
+    async fn persist(&self, requests: &BTreeMap<String, FoiaRequest>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create FOIA store directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(requests)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(format!("Failed to write FOIA store: {}", e), temp.clone())
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to replace FOIA store: {}", e), path.clone())
+        })
+    }
+
+    fn api_key(&self) -> Result<&str> {
+        self.config.api_key.as_deref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "FOIA.gov API key not provided",
+                "Get a key at https://api.data.gov/signup and set FOIA_API_KEY",
+            )
+        })
+    }
+
+    async fn get_json(&self, path: &str, query: &[(&str, String)]) -> Result<Value> {
+        let url = format!("{}/{}", self.config.base_url.trim_end_matches('/'), path);
+        let response = self
+            .client
+            .get(&url)
+            .header("X-API-Key", self.api_key()?)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| {
+                Error::network_with_endpoint(format!("FOIA.gov request failed: {}", e), url.clone())
+            })?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(Error::api_with_status(
+                format!("FOIA.gov returned {}", status),
+                "foia.gov",
+                status.as_u16(),
+            ));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse FOIA.gov response: {}", e)))
+    }
+
+    /// Search agency components by name or abbreviation
+    pub async fn search_agencies(&self, query: &str, limit: usize) -> Result<Vec<AgencyComponent>> {
+        let body = self
+            .get_json(
+                "agency_components",
+                &[
+                    ("include", "agency,request_form".to_string()),
+                    ("filter[title][value]", query.to_string()),
+                    ("filter[title][operator]", "CONTAINS".to_string()),
+                    ("page[limit]", limit.clamp(1, 50).to_string()),
+                ],
+            )
+            .await?;
+
+        let included = body
+            .get("included")
+            .and_then(|i| i.as_array())
+            .cloned()
+            .unwrap_or_default();
+        Ok(body
+            .get("data")
+            .and_then(|d| d.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|item| parse_component(item, &included))
+            .collect())
+    }
+
+    /// Fetch a single agency component
+    pub async fn agency_component(&self, id: &str) -> Result<AgencyComponent> {
+        let body = self
+            .get_json(
+                &format!("agency_components/{}", id),
+                &[("include", "agency,request_form".to_string())],
+            )
+            .await?;
+        let included = body
+            .get("included")
+            .and_then(|i| i.as_array())
+            .cloned()
+            .unwrap_or_default();
+        body.get("data")
+            .and_then(|item| parse_component(item, &included))
+            .ok_or_else(|| {
+                Error::not_found_with_resource("Agency component not found", "agency_component", id)
+            })
+    }
+
+    /// Create a request draft
+    pub async fn create_draft(
+        &self,
+        new: NewFoiaRequest,
+        agency_name: impl Into<String>,
+    ) -> Result<FoiaRequest> {
+        if new.description.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Describe the records being requested",
+                "description",
+            ));
+        }
+        let requester = new
+            .requester
+            .or_else(|| self.config.requester.clone())
+            .ok_or_else(|| {
+                Error::validation_with_field("Requester details are required", "requester")
+            })?;
+
+        let mut request = FoiaRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            agency_component_id: new.agency_component_id,
+            agency_name: agency_name.into(),
+            subject: new.subject,
+            description: new.description,
+            requester,
+            fee_limit: new.fee_limit,
+            fee_waiver: new.fee_waiver,
+            expedited: new.expedited,
+            status: FoiaStatus::Draft,
+            reference_number: None,
+            submission_id: None,
+            created_at: Utc::now(),
+            submitted_at: None,
+            history: Vec::new(),
+        };
+        request.set_status(FoiaStatus::Draft, None);
+
+        let mut requests = self.requests.write().await;
+        requests.insert(request.id.clone(), request.clone());
+        self.persist(&requests).await?;
+        Ok(request)
+    }
+
+    /// Submit a draft through the FOIA.gov web form API
+    pub async fn submit(&self, id: &str) -> Result<FoiaRequest> {
+        let draft = self.get_request(id).await?;
+        if draft.status != FoiaStatus::Draft {
+            return Err(Error::validation(format!(
+                "Request {} has already been submitted",
+                id
+            )));
+        }
+        let component = self.agency_component(&draft.agency_component_id).await?;
+        let form_id = component.request_form_id.clone().ok_or_else(|| {
+            Error::config_with_suggestion(
+                format!("{} does not accept requests through FOIA.gov", component.title),
+                component
+                    .submission_web
+                    .as_ref()
+                    .or(component.email.as_ref())
+                    .map(|c| format!("Submit via {} and record the tracking number with foia_update_request", c))
+                    .unwrap_or_else(|| "Contact the agency directly and record the tracking number with foia_update_request".to_string()),
+            )
+        })?;
+
+        let url = format!(
+            "{}/webform/submit",
+            self.config.base_url.trim_end_matches('/')
+        );
+        let mut payload = json!({
+            "id": form_id,
+            "name_first": draft.requester.first_name,
+            "name_last": draft.requester.last_name,
+            "email": draft.requester.email,
+            "request_description": format!("{}\n\n{}", draft.subject, draft.description),
+            "request_fee_waiver": if draft.fee_waiver { "yes" } else { "no" },
+            "request_expedited_processing": if draft.expedited { "yes" } else { "no" },
+        });
+        if let Some(fee_limit) = draft.fee_limit {
+            payload["fee_amount_willing"] = json!(fee_limit.to_string());
+        }
+        if let Some(phone) = &draft.requester.phone {
+            payload["phone_number"] = json!(phone);
+        }
+        if let Some(organization) = &draft.requester.organization {
+            payload["company_organization"] = json!(organization);
+        }
+
+        let response = self
+            .client
+            .post(&url)
+            .header("X-API-Key", self.api_key()?)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| {
+                Error::network_with_endpoint(format!("FOIA submission failed: {}", e), url.clone())
+            })?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            let message = body
+                .get("errors")
+                .map(|e| e.to_string())
+                .unwrap_or_else(|| status.to_string());
+            return Err(Error::api_with_status(
+                format!("FOIA.gov rejected the request: {}", message),
+                "foia.gov",
+                status.as_u16(),
+            ));
+        }
+
+        let mut requests = self.requests.write().await;
+        let request = requests.get_mut(id).ok_or_else(|| {
+            Error::not_found_with_resource("FOIA request not found", "foia_request", id)
+        })?;
+        request.submission_id = body.get("submission_id").map(|s| {
+            s.as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| s.to_string())
+        });
+        request.submitted_at = Some(Utc::now());
+        request.set_status(
+            FoiaStatus::Submitted,
+            Some("Submitted through FOIA.gov".to_string()),
+        );
+        let updated = request.clone();
+        self.persist(&requests).await?;
+        Ok(updated)
+    }
+
+    /// Record a status change or the agency's tracking number
+    pub async fn update(
+        &self,
+        id: &str,
+        status: Option<FoiaStatus>,
+        reference_number: Option<String>,
+        note: Option<String>,
+    ) -> Result<FoiaRequest> {
+        let mut requests = self.requests.write().await;
+        let request = requests.get_mut(id).ok_or_else(|| {
+            Error::not_found_with_resource("FOIA request not found", "foia_request", id)
+        })?;
+        if let Some(reference) = reference_number {
+            request.reference_number = Some(reference);
+        }
+        match status {
+            Some(status) => {
+                // Requests filed outside FOIA.gov start their clock when marked submitted
+                if request.submitted_at.is_none() && status != FoiaStatus::Draft {
+                    request.submitted_at = Some(Utc::now());
+                }
+                request.set_status(status, note);
+            }
+            None if note.is_some() => {
+                let current = request.status;
+                request.set_status(current, note);
+            }
+            None => {}
+        }
+        let updated = request.clone();
+        self.persist(&requests).await?;
+        Ok(updated)
+    }
+
+    /// Get a request by local id or agency reference number
+    pub async fn get_request(&self, id_or_reference: &str) -> Result<FoiaRequest> {
+        let requests = self.requests.read().await;
+        requests
+            .get(id_or_reference)
+            .or_else(|| {
+                requests
+                    .values()
+                    .find(|r| r.reference_number.as_deref() == Some(id_or_reference))
+            })
+            .cloned()
+            .ok_or_else(|| {
+                Error::not_found_with_resource(
+                    "FOIA request not found",
+                    "foia_request",
+                    id_or_reference,
+                )
+            })
+    }
+
+    /// List tracked requests, newest first
+    pub async fn list_requests(&self) -> Vec<FoiaRequest> {
+        let mut requests: Vec<_> = self.requests.read().await.values().cloned().collect();
+        requests.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+        requests
+    }
+
+    /// Get tool definitions for FOIA tracking
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let requester_schema = json!({
+            "type": "object",
+            "properties": {
+                "first_name": {"type": "string"},
+                "last_name": {"type": "string"},
+                "email": {"type": "string"},
+                "phone": {"type": "string"},
+                "organization": {"type": "string"}
+            },
+            "required": ["first_name", "last_name", "email"]
+        });
+        vec![
+            ToolDefinition::from_json_schema(
+                "foia_search_agencies",
+                "Search FOIA.gov for agency components that handle FOIA requests",
+                "government",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string", "description": "Agency or component name, e.g. 'Federal Bureau'"},
+                        "limit": {"type": "integer", "default": 10}
+                    },
+                    "required": ["query"]
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "foia_create_request",
+                "Draft a FOIA request to an agency component and optionally submit it through FOIA.gov",
+                "government",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "agency_component_id": {"type": "string"},
+                        "subject": {"type": "string"},
+                        "description": {"type": "string", "description": "Records being requested, as specifically as possible"},
+                        "requester": requester_schema,
+                        "fee_limit": {"type": "number", "description": "Maximum fees in dollars"},
+                        "fee_waiver": {"type": "boolean", "default": false},
+                        "expedited": {"type": "boolean", "default": false},
+                        "submit": {"type": "boolean", "default": false, "description": "Submit immediately instead of keeping a draft"}
+                    },
+                    "required": ["agency_component_id", "subject", "description"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "foia_submit_request",
+                "Submit a drafted FOIA request through FOIA.gov",
+                "government",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "foia_update_request",
+                "Record a FOIA request's status, agency tracking number or a note",
+                "government",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string", "description": "Local id or tracking number"},
+                        "status": {"type": "string", "enum": ["draft", "submitted", "acknowledged", "processing", "fulfilled", "partially_fulfilled", "denied", "appealed", "withdrawn"]},
+                        "reference_number": {"type": "string"},
+                        "note": {"type": "string"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "foia_request_status",
+                "Show the status, due date and history of a tracked FOIA request, or list all requests",
+                "government",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string", "description": "Local id or tracking number; omit to list all"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    fn describe(request: &FoiaRequest, today: NaiveDate) -> String {
+        let mut line = format!(
+            "{} — {} ({}): {:?}",
+            request.reference_number.as_deref().unwrap_or(&request.id),
+            request.subject,
+            request.agency_name,
+            request.status
+        );
+        if let Some(due) = request.due_date().filter(|_| request.status.is_open()) {
+            line.push_str(&format!(", due {}", due));
+            if request.is_overdue(today) {
+                line.push_str(" (overdue)");
+            }
+        }
+        line
+    }
+
+    /// Execute a FOIA tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let today = Utc::now().date_naive();
+        let id_param = || {
+            parameters
+                .get("id")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| Error::validation_with_field("id is required", "id"))
+        };
+
+        match name {
+            "foia_search_agencies" => {
+                let query = parameters
+                    .get("query")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::validation_with_field("query is required", "query"))?;
+                let limit = parameters
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(10) as usize;
+                let agencies = self.search_agencies(query, limit).await?;
+                let text = if agencies.is_empty() {
+                    format!("No agency components match '{}'", query)
+                } else {
+                    agencies
+                        .iter()
+                        .map(|a| {
+                            format!(
+                                "{} [{}]{}{}",
+                                a.title,
+                                a.id,
+                                a.agency
+                                    .as_ref()
+                                    .map(|n| format!(" — {}", n))
+                                    .unwrap_or_default(),
+                                if a.request_form_id.is_some() {
+                                    " (accepts FOIA.gov submissions)"
+                                } else {
+                                    ""
+                                }
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                Ok(call_result(text, json!({ "agencies": agencies })))
+            }
+            "foia_create_request" => {
+                let submit = parameters
+                    .get("submit")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let new: NewFoiaRequest = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid FOIA request: {}", e)))?;
+                // Resolve the component name when FOIA.gov is reachable
+                let agency_name = match self.agency_component(&new.agency_component_id).await {
+                    Ok(component) => component.title,
+                    Err(e) => {
+                        tracing::debug!(error = %e, "Could not resolve agency component");
+                        new.agency_component_id.clone()
+                    }
+                };
+                let mut request = self.create_draft(new, agency_name).await?;
+                if submit {
+                    request = self.submit(&request.id).await?;
+                }
+                Ok(call_result(
+                    format!("FOIA request {} created: {:?}", request.id, request.status),
+                    serde_json::to_value(&request)?,
+                ))
+            }
+            "foia_submit_request" => {
+                let request = self.submit(id_param()?).await?;
+                Ok(call_result(
+                    Self::describe(&request, today),
+                    serde_json::to_value(&request)?,
+                ))
+            }
+            "foia_update_request" => {
+                let existing = self.get_request(id_param()?).await?;
+                let status = parameters
+                    .get("status")
+                    .and_then(|v| v.as_str())
+                    .map(FoiaStatus::from_str)
+                    .transpose()?;
+                let text_param = |field: &str| {
+                    parameters
+                        .get(field)
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string)
+                };
+                let request = self
+                    .update(
+                        &existing.id,
+                        status,
+                        text_param("reference_number"),
+                        text_param("note"),
+                    )
+                    .await?;
+                Ok(call_result(
+                    Self::describe(&request, today),
+                    serde_json::to_value(&request)?,
+                ))
+            }
+            "foia_request_status" => match parameters.get("id").and_then(|v| v.as_str()) {
+                Some(id) => {
+                    let request = self.get_request(id).await?;
+                    let mut lines = vec![Self::describe(&request, today)];
+                    lines.extend(request.history.iter().map(|change| {
+                        format!(
+                            "  {} {:?}{}",
+                            change.at.format("%Y-%m-%d"),
+                            change.status,
+                            change
+                                .note
+                                .as_ref()
+                                .map(|n| format!(": {}", n))
+                                .unwrap_or_default()
+                        )
+                    }));
+                    let mut value = serde_json::to_value(&request)?;
+                    value["due_date"] = json!(request.due_date());
+                    value["overdue"] = json!(request.is_overdue(today));
+                    Ok(call_result(lines.join("\n"), value))
+                }
+                None => {
+                    let requests = self.list_requests().await;
+                    let text = if requests.is_empty() {
+                        "No FOIA requests tracked".to_string()
+                    } else {
+                        requests
+                            .iter()
+                            .map(|r| Self::describe(r, today))
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    };
+                    let overdue = requests.iter().filter(|r| r.is_overdue(today)).count();
+                    Ok(call_result(
+                        text,
+                        json!({ "requests": requests, "overdue": overdue }),
+                    ))
+                }
+            },
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "foia_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tracks_request_lifecycle() {
+        let tracker = FoiaTracker::open(FoiaConfig::default(), None)
+            .await
+            .unwrap();
+        let draft = tracker
+            .create_draft(
+                NewFoiaRequest {
+                    agency_component_id: "abc-123".to_string(),
+                    subject: "Inspection reports".to_string(),
+                    description: "All 2023 inspection reports for facility 42".to_string(),
+                    requester: Some(Requester {
+                        first_name: "Ana".to_string(),
+                        last_name: "Ruiz".to_string(),
+                        email: "ana@example.org".to_string(),
+                        phone: None,
+                        organization: None,
+                    }),
+                    fee_limit: Some(25.0),
+                    fee_waiver: false,
+                    expedited: false,
+                },
+                "Example Agency",
+            )
+            .await
+            .unwrap();
+        assert_eq!(draft.due_date(), None);
+
+        let updated = tracker
+            .update(
+                &draft.id,
+                Some(FoiaStatus::Acknowledged),
+                Some("2024-FOIA-0042".to_string()),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(updated.submitted_at.is_some());
+        assert_eq!(updated.history.len(), 2);
+        let found = tracker.get_request("2024-FOIA-0042").await.unwrap();
+        assert_eq!(found.id, draft.id);
+
+        // Friday plus 20 working days lands four weeks later
+        let friday = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert_eq!(
+            add_working_days(friday, 20),
+            NaiveDate::from_ymd_opt(2024, 3, 29).unwrap()
+        );
+        assert_eq!(
+            FoiaStatus::from_str("Partially Fulfilled").unwrap(),
+            FoiaStatus::PartiallyFulfilled
+        );
+    }
+}
@@ -1,5 +1,11 @@
+/// Solicitation document analysis
+pub mod documents;
+/// FOIA request tracking via FOIA.gov
+pub mod foia;
 /// Government grants module for accessing government grant data
 pub mod grants;
 
 // Re-export key types
+pub use documents::{OpportunityBrief, SolicitationAnalyzer};
+pub use foia::{AgencyComponent, FoiaConfig, FoiaRequest, FoiaStatus, FoiaTracker, Requester};
 pub use grants::{Grant, GrantsClient, GrantsSearchParams};
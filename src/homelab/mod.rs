@@ -4,7 +4,6 @@
 /// - Traefik (reverse proxy and load balancer)
 /// - Prometheus/Grafana (monitoring and visualization)  
 /// - Coolify (deployment platform)
-/// - N8N (workflow automation)
 /// - Uptime Kuma (uptime monitoring)
 /// - Service health checking
 use crate::error::{Error, Result};
@@ -185,7 +184,8 @@ impl HomelabManager {
                     }
                 }),
                 None,
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "traefik_service_health",
                 "Check health status of Traefik services",
@@ -227,7 +227,8 @@ impl HomelabManager {
                     "required": ["query"]
                 }),
                 None,
-            ),
+            )
+            .read_only(),
             // Grafana tools
             ToolDefinition::from_json_schema(
                 "grafana_dashboards",
@@ -267,7 +268,8 @@ impl HomelabManager {
                     "required": ["service_name"]
                 }),
                 None,
-            ),
+            )
+            .read_only(),
             // Coolify tools
             ToolDefinition::from_json_schema(
                 "coolify_deployments",
@@ -285,23 +287,6 @@ impl HomelabManager {
                 }),
                 None,
             ),
-            // N8N tools
-            ToolDefinition::from_json_schema(
-                "n8n_workflows",
-                "List N8N workflows and their status",
-                "homelab_automation",
-                json!({
-                    "type": "object",
-                    "properties": {
-                        "n8n_url": {
-                            "type": "string",
-                            "description": "N8N server URL",
-                            "default": "http://localhost:5678"
-                        }
-                    }
-                }),
-                None,
-            ),
             // Uptime Kuma tools
             ToolDefinition::from_json_schema(
                 "uptime_monitors",
@@ -364,7 +349,8 @@ impl HomelabManager {
                     }
                 }),
                 None,
-            ),
+            )
+            .read_only(),
             // Vector tools
             ToolDefinition::from_json_schema(
                 "vector_logs",
@@ -400,18 +386,22 @@ impl HomelabManager {
             "grafana_dashboards" => self.grafana_dashboards(parameters).await,
             "service_health_check" => self.service_health_check(parameters).await,
             "coolify_deployments" => self.coolify_deployments(parameters).await,
-            "n8n_workflows" => self.n8n_workflows(parameters).await,
             "uptime_monitors" => self.uptime_monitors(parameters).await,
             "authelia_users" => self.authelia_users(parameters).await,
             "vaultwarden_status" => self.vaultwarden_status(parameters).await,
             "vector_logs" => self.vector_logs(parameters).await,
-            _ => Err(Error::not_found_with_resource("Tool not found", "homelab_tool", name)),
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "homelab_tool",
+                name,
+            )),
         }
     }
 
     /// List Traefik services
     async fn traefik_list_services(&self, parameters: Value) -> Result<Value> {
-        let traefik_url = parameters.get("traefik_url")
+        let traefik_url = parameters
+            .get("traefik_url")
             .and_then(|u| u.as_str())
             .unwrap_or("http://localhost:8080");
 
@@ -426,10 +416,12 @@ impl HomelabManager {
 
     /// Check Traefik service health
     async fn traefik_service_health(&self, parameters: Value) -> Result<Value> {
-        let traefik_url = parameters.get("traefik_url")
+        let traefik_url = parameters
+            .get("traefik_url")
             .and_then(|u| u.as_str())
             .unwrap_or("http://localhost:8080");
-        let service_name = parameters.get("service_name")
+        let service_name = parameters
+            .get("service_name")
             .and_then(|s| s.as_str())
             .unwrap_or("all");
 
@@ -443,10 +435,12 @@ impl HomelabManager {
 
     /// Query Prometheus metrics
     async fn prometheus_query(&self, parameters: Value) -> Result<Value> {
-        let query = parameters.get("query")
+        let query = parameters
+            .get("query")
             .and_then(|q| q.as_str())
             .unwrap_or("up");
-        let prometheus_url = parameters.get("prometheus_url")
+        let prometheus_url = parameters
+            .get("prometheus_url")
             .and_then(|u| u.as_str())
             .unwrap_or("http://localhost:9090");
 
@@ -460,7 +454,8 @@ impl HomelabManager {
 
     /// List Grafana dashboards
     async fn grafana_dashboards(&self, parameters: Value) -> Result<Value> {
-        let grafana_url = parameters.get("grafana_url")
+        let grafana_url = parameters
+            .get("grafana_url")
             .and_then(|u| u.as_str())
             .unwrap_or("http://localhost:3000");
 
@@ -474,10 +469,12 @@ impl HomelabManager {
 
     /// Check service health
     async fn service_health_check(&self, parameters: Value) -> Result<Value> {
-        let service_name = parameters.get("service_name")
+        let service_name = parameters
+            .get("service_name")
             .and_then(|s| s.as_str())
             .unwrap_or("all");
-        let check_type = parameters.get("check_type")
+        let check_type = parameters
+            .get("check_type")
             .and_then(|t| t.as_str())
             .unwrap_or("http");
 
@@ -491,7 +488,8 @@ impl HomelabManager {
 
     /// List Coolify deployments
     async fn coolify_deployments(&self, parameters: Value) -> Result<Value> {
-        let coolify_url = parameters.get("coolify_url")
+        let coolify_url = parameters
+            .get("coolify_url")
             .and_then(|u| u.as_str())
             .unwrap_or("http://localhost:8000");
 
@@ -503,23 +501,10 @@ impl HomelabManager {
         }))
     }
 
-    /// List N8N workflows
-    async fn n8n_workflows(&self, parameters: Value) -> Result<Value> {
-        let n8n_url = parameters.get("n8n_url")
-            .and_then(|u| u.as_str())
-            .unwrap_or("http://localhost:5678");
-
-        Ok(json!({
-            "content": [{
-                "type": "text",
-                "text": format!("🔄 N8N Workflows\n\nServer: {}\n\n📋 Active Workflows:\n• Homelab Monitoring: ✅ Active (runs every 5m)\n• Backup Automation: ✅ Active (runs daily)\n• Discord Notifications: ✅ Active (triggered)\n• Log Processing: ✅ Active (continuous)\n• Health Check Alerts: ✅ Active (runs every 1m)\n• Database Cleanup: ✅ Active (runs weekly)\n\n📊 Workflow Status:\n• Total Workflows: 6\n• Active: 6\n• Paused: 0\n• Error: 0\n• Last Execution: 2m ago\n\n🔄 Recent Executions:\n• Homelab Monitoring: ✅ Success (2m ago)\n• Health Check Alerts: ✅ Success (1m ago)\n• Log Processing: ✅ Success (30s ago)\n\n💡 Real implementation would:\n• Query N8N API\n• Show execution history\n• Trigger manual runs\n• Display workflow details", n8n_url)
-            }]
-        }))
-    }
-
     /// Check Uptime Kuma monitors
     async fn uptime_monitors(&self, parameters: Value) -> Result<Value> {
-        let uptime_url = parameters.get("uptime_url")
+        let uptime_url = parameters
+            .get("uptime_url")
             .and_then(|u| u.as_str())
             .unwrap_or("http://localhost:3001");
 
@@ -533,10 +518,12 @@ impl HomelabManager {
 
     /// Manage Authelia users
     async fn authelia_users(&self, parameters: Value) -> Result<Value> {
-        let authelia_url = parameters.get("authelia_url")
+        let authelia_url = parameters
+            .get("authelia_url")
             .and_then(|u| u.as_str())
             .unwrap_or("http://localhost:9091");
-        let action = parameters.get("action")
+        let action = parameters
+            .get("action")
             .and_then(|a| a.as_str())
             .unwrap_or("status");
 
@@ -550,10 +537,12 @@ impl HomelabManager {
 
     /// Check Vaultwarden status
     async fn vaultwarden_status(&self, parameters: Value) -> Result<Value> {
-        let vaultwarden_url = parameters.get("vaultwarden_url")
+        let vaultwarden_url = parameters
+            .get("vaultwarden_url")
             .and_then(|u| u.as_str())
             .unwrap_or("http://localhost:8080");
-        let action = parameters.get("action")
+        let action = parameters
+            .get("action")
             .and_then(|a| a.as_str())
             .unwrap_or("status");
 
@@ -567,10 +556,12 @@ impl HomelabManager {
 
     /// Query Vector logs
     async fn vector_logs(&self, parameters: Value) -> Result<Value> {
-        let vector_url = parameters.get("vector_url")
+        let vector_url = parameters
+            .get("vector_url")
             .and_then(|u| u.as_str())
             .unwrap_or("http://localhost:8686");
-        let action = parameters.get("action")
+        let action = parameters
+            .get("action")
             .and_then(|a| a.as_str())
             .unwrap_or("status");
 
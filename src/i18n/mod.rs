@@ -0,0 +1,250 @@
+//! Localization of tool descriptions and result text
+//!
+//! Translations live in flat JSON bundles keyed by dotted message IDs:
+//! `tools.<name>.description` and `tools.<name>.params.<param>` override a
+//! tool's `tools/list` entry, everything else is result text looked up with
+//! [`text`]. A locale such as `es-MX` falls back to `es`, then to English.
+//! While the registry runs a tool, the caller's locale is in scope, so
+//! handlers localize their output without taking a locale parameter.
+
+use crate::error::utils::format_error_message;
+use crate::error::{Error, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+
+/// Locale every bundle falls back to
+pub const DEFAULT_LOCALE: &str = "en";
+
+const BUILTIN_BUNDLES: &[(&str, &str)] = &[
+    ("en", include_str!("locales/en.json")),
+    ("es", include_str!("locales/es.json")),
+    ("de", include_str!("locales/de.json")),
+];
+
+tokio::task_local! {
+    static SCOPE: Localizer;
+}
+
+/// Normalize a locale tag: `es_MX.UTF-8` becomes `es-MX`
+pub fn normalize(locale: &str) -> String {
+    let tag = locale.split(['.', '@']).next().unwrap_or_default().trim();
+    let mut parts = tag.split(['-', '_']).filter(|p| !p.is_empty());
+    let mut normalized = parts.next().unwrap_or_default().to_ascii_lowercase();
+    for part in parts {
+        normalized.push('-');
+        if part.len() == 2 {
+            normalized.push_str(&part.to_ascii_uppercase());
+        } else {
+            normalized.push_str(part);
+        }
+    }
+    normalized
+}
+
+/// Locales to try for `locale`, most specific first and ending in English
+pub fn fallback_chain(locale: &str) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut tag = normalize(locale);
+    while !tag.is_empty() {
+        chain.push(tag.clone());
+        match tag.rfind('-') {
+            Some(pos) => tag.truncate(pos),
+            None => break,
+        }
+    }
+    if !chain.iter().any(|l| l == DEFAULT_LOCALE) {
+        chain.push(DEFAULT_LOCALE.to_string());
+    }
+    chain
+}
+
+/// Translation bundles by locale
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    bundles: HashMap<String, HashMap<String, String>>,
+}
+
+impl Catalog {
+    /// An empty catalog
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bundles shipped with the server
+    pub fn builtin() -> Self {
+        let mut catalog = Self::new();
+        for (locale, bundle) in BUILTIN_BUNDLES {
+            catalog
+                .add_json(locale, bundle)
+                .expect("built-in translation bundles are valid");
+        }
+        catalog
+    }
+
+    /// Shared copy of the built-in bundles
+    pub fn shared_builtin() -> Arc<Self> {
+        static BUILTIN: OnceLock<Arc<Catalog>> = OnceLock::new();
+        Arc::clone(BUILTIN.get_or_init(|| Arc::new(Self::builtin())))
+    }
+
+    /// Add or override messages for a locale
+    pub fn add_bundle(&mut self, locale: &str, messages: HashMap<String, String>) {
+        self.bundles
+            .entry(normalize(locale))
+            .or_default()
+            .extend(messages);
+    }
+
+    /// Add messages from a flat JSON object of strings
+    pub fn add_json(&mut self, locale: &str, json: &str) -> Result<()> {
+        let messages: HashMap<String, String> = serde_json::from_str(json).map_err(|e| {
+            Error::parsing(format!("Invalid translation bundle for {}: {}", locale, e))
+        })?;
+        self.add_bundle(locale, messages);
+        Ok(())
+    }
+
+    /// Locales with a bundle
+    pub fn locales(&self) -> Vec<&str> {
+        let mut locales: Vec<&str> = self.bundles.keys().map(String::as_str).collect();
+        locales.sort_unstable();
+        locales
+    }
+
+    /// First locale in `chain` that has a bundle
+    pub fn resolve<'a>(&self, chain: &'a [String]) -> &'a str {
+        chain
+            .iter()
+            .find(|locale| self.bundles.contains_key(locale.as_str()))
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_LOCALE)
+    }
+
+    /// Message for `key` in the first locale of `chain` that defines it
+    pub fn lookup(&self, chain: &[String], key: &str) -> Option<&str> {
+        chain.iter().find_map(|locale| {
+            self.bundles
+                .get(locale)
+                .and_then(|bundle| bundle.get(key))
+                .map(String::as_str)
+        })
+    }
+
+    /// Rewrite the description and parameter hints of a `tools/list` entry
+    pub fn localize_tool(&self, chain: &[String], tool: &mut Value) {
+        let Some(name) = tool.get("name").and_then(|n| n.as_str()).map(String::from) else {
+            return;
+        };
+        if let Some(description) = self.lookup(chain, &format!("tools.{}.description", name)) {
+            tool["description"] = Value::String(description.to_string());
+        }
+        let Some(properties) = tool
+            .pointer_mut("/inputSchema/properties")
+            .and_then(|p| p.as_object_mut())
+        else {
+            return;
+        };
+        for (param, schema) in properties.iter_mut() {
+            if let Some(hint) = self.lookup(chain, &format!("tools.{}.params.{}", name, param)) {
+                schema["description"] = Value::String(hint.to_string());
+            }
+        }
+    }
+}
+
+/// A catalog and the locale chain to read it with
+#[derive(Debug, Clone)]
+pub struct Localizer {
+    catalog: Arc<Catalog>,
+    chain: Vec<String>,
+}
+
+impl Localizer {
+    /// Read `catalog` in `locale`, or in English when none is given
+    pub fn new(catalog: Arc<Catalog>, locale: Option<&str>) -> Self {
+        Self {
+            catalog,
+            chain: fallback_chain(locale.unwrap_or(DEFAULT_LOCALE)),
+        }
+    }
+
+    /// Locale messages are served in
+    pub fn locale(&self) -> &str {
+        self.catalog.resolve(&self.chain)
+    }
+
+    /// Message for `key` with `{placeholders}` filled from `args`, or the key itself
+    pub fn text(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        match self.catalog.lookup(&self.chain, key) {
+            Some(template) => format_error_message(template, args),
+            None => key.to_string(),
+        }
+    }
+
+    /// Localize a `tools/list` entry
+    pub fn localize_tool(&self, tool: &mut Value) {
+        self.catalog.localize_tool(&self.chain, tool);
+    }
+
+    /// Run `future` with this localizer in scope for [`text`]
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        SCOPE.scope(self, future).await
+    }
+}
+
+/// Message for `key` in the locale of the tool call being served
+///
+/// Outside a call the built-in English bundle is used.
+pub fn text(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    SCOPE
+        .try_with(|localizer| localizer.text(key, args))
+        .unwrap_or_else(|_| Localizer::new(Catalog::shared_builtin(), None).text(key, args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn falls_back_from_region_to_language_to_english() {
+        assert_eq!(fallback_chain("es_MX.UTF-8"), vec!["es-MX", "es", "en"]);
+        assert_eq!(fallback_chain("en-GB"), vec!["en-GB", "en"]);
+
+        let localizer = Localizer::new(Catalog::shared_builtin(), Some("de-AT"));
+        assert_eq!(localizer.locale(), "de");
+        assert_eq!(
+            localizer.text("messages.containers.listed", &[("count", &3)]),
+            "3 Container"
+        );
+        assert_eq!(localizer.text("messages.unknown", &[]), "messages.unknown");
+    }
+
+    #[tokio::test]
+    async fn localizes_tool_entries_and_scoped_text() {
+        let mut tool = json!({
+            "name": "list_docker_containers",
+            "description": "List all Docker containers with their status",
+            "inputSchema": {"type": "object", "properties": {"all": {"type": "boolean"}}}
+        });
+        let localizer = Localizer::new(Catalog::shared_builtin(), Some("es"));
+        localizer.localize_tool(&mut tool);
+        assert_eq!(
+            tool["description"],
+            "Lista todos los contenedores Docker con su estado"
+        );
+        assert!(tool["inputSchema"]["properties"]["all"]["description"].is_string());
+
+        let started = localizer
+            .scope(async { text("messages.container.started", &[("id", &"web")]) })
+            .await;
+        assert_eq!(started, "web iniciado");
+        assert_eq!(
+            text("messages.container.started", &[("id", &"web")]),
+            "Started web"
+        );
+    }
+}
@@ -0,0 +1,807 @@
+/// Hardware inventory for homelab hosts
+///
+/// The registry records physical machines with their serial numbers, warranty
+/// dates, addresses, rack positions and the services they run. Assets can be
+/// imported from a CSV export of an existing spreadsheet, and a background
+/// scheduler posts a reminder to collaboration channels as each warranty
+/// approaches its end.
+use crate::collaboration::notify::{Notification, Notifier, Severity};
+use crate::collaboration::AttachmentField;
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Inventory configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetsConfig {
+    /// Where the inventory is persisted; in memory when unset
+    pub path: Option<PathBuf>,
+    /// Days before a warranty ends that trigger a reminder
+    pub reminder_days: Vec<i64>,
+    /// Collaboration channels reminders are posted to
+    pub channels: Vec<String>,
+    /// Hours between warranty checks
+    pub check_interval_hours: u64,
+}
+
+impl Default for AssetsConfig {
+    fn default() -> Self {
+        Self {
+            path: std::env::var("ASSETS_PATH").ok().map(PathBuf::from),
+            reminder_days: vec![90, 30, 7],
+            channels: Vec::new(),
+            check_interval_hours: 24,
+        }
+    }
+}
+
+/// A physical device in the lab
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Asset {
+    /// Short unique name, usually the hostname
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// e.g. server, nas, switch, ups
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub manufacturer: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub serial: Option<String>,
+    #[serde(default)]
+    pub ips: Vec<String>,
+    /// MAC address magic packets are sent to
+    #[serde(default)]
+    pub mac: Option<String>,
+    /// Address of the BMC (iDRAC, iLO, IPMI) for power control
+    #[serde(default)]
+    pub bmc: Option<String>,
+    #[serde(default)]
+    pub rack: Option<String>,
+    /// Lowest rack unit the device occupies
+    #[serde(default)]
+    pub rack_unit: Option<u32>,
+    #[serde(default)]
+    pub purchased: Option<NaiveDate>,
+    #[serde(default)]
+    pub warranty_expires: Option<NaiveDate>,
+    /// Services running on the device
+    #[serde(default)]
+    pub services: Vec<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Asset {
+    /// `rack A, U12` style location
+    pub fn location(&self) -> Option<String> {
+        match (&self.rack, self.rack_unit) {
+            (Some(rack), Some(unit)) => Some(format!("{}, U{}", rack, unit)),
+            (Some(rack), None) => Some(rack.clone()),
+            (None, Some(unit)) => Some(format!("U{}", unit)),
+            (None, None) => None,
+        }
+    }
+
+    /// Days left on the warranty; negative once it has ended
+    pub fn warranty_days_left(&self, today: NaiveDate) -> Option<i64> {
+        self.warranty_expires.map(|d| (d - today).num_days())
+    }
+
+    /// Whether every word of `text` appears in one of the asset's fields
+    pub fn matches(&self, text: &str) -> bool {
+        let fields: Vec<String> = [
+            Some(&self.id),
+            self.name.as_ref(),
+            self.kind.as_ref(),
+            self.manufacturer.as_ref(),
+            self.model.as_ref(),
+            self.serial.as_ref(),
+            self.mac.as_ref(),
+            self.bmc.as_ref(),
+            self.rack.as_ref(),
+            self.notes.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(&self.ips)
+        .chain(&self.services)
+        .map(|f| f.to_lowercase())
+        .collect();
+        text.split_whitespace()
+            .map(str::to_lowercase)
+            .all(|word| fields.iter().any(|f| f.contains(&word)))
+    }
+
+    /// One-line summary
+    pub fn summary(&self) -> String {
+        let mut text = self.id.clone();
+        let model: Vec<&str> = [self.manufacturer.as_deref(), self.model.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        if !model.is_empty() {
+            text.push_str(&format!(" ({})", model.join(" ")));
+        }
+        if let Some(serial) = &self.serial {
+            text.push_str(&format!(" S/N {}", serial));
+        }
+        if !self.ips.is_empty() {
+            text.push_str(&format!(", {}", self.ips.join(", ")));
+        }
+        if let Some(location) = self.location() {
+            text.push_str(&format!(", {}", location));
+        }
+        if let Some(expires) = self.warranty_expires {
+            text.push_str(&format!(", warranty until {}", expires));
+        }
+        if !self.services.is_empty() {
+            text.push_str(&format!(": {}", self.services.join(", ")));
+        }
+        text
+    }
+}
+
+/// Narrowing for `search`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AssetFilter {
+    /// Words matched against every text field
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub rack: Option<String>,
+    /// Only assets running this service
+    #[serde(default)]
+    pub service: Option<String>,
+    /// Only assets whose warranty ends within this many days, or has ended
+    #[serde(default)]
+    pub warranty_within_days: Option<i64>,
+}
+
+/// Outcome of a CSV import
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    pub added: usize,
+    pub updated: usize,
+    /// Line number and reason for each skipped row
+    pub skipped: Vec<(usize, String)>,
+}
+
+/// Split CSV text into records, honouring quoted fields with commas, quotes and newlines
+pub fn parse_csv(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\n' | '\r' if !quoted => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.trim().is_empty()) {
+                    records.push((start, std::mem::take(&mut record)));
+                }
+                record.clear();
+                line += 1;
+                start = line;
+            }
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    record.push(field);
+    if record.iter().any(|f| !f.trim().is_empty()) {
+        records.push((start, record));
+    }
+    records
+}
+
+/// Asset field for a CSV header, accepting common spreadsheet names
+fn column(header: &str) -> Option<&'static str> {
+    let header = header.trim().to_lowercase().replace([' ', '-'], "_");
+    Some(match header.as_str() {
+        "id" | "hostname" | "host" => "id",
+        "name" | "description" => "name",
+        "kind" | "type" | "category" => "kind",
+        "manufacturer" | "vendor" | "make" => "manufacturer",
+        "model" => "model",
+        "serial" | "serial_number" | "sn" | "s/n" => "serial",
+        "ip" | "ips" | "ip_address" | "ip_addresses" => "ips",
+        "mac" | "mac_address" => "mac",
+        "bmc" | "bmc_address" | "ipmi" | "idrac" | "ilo" => "bmc",
+        "rack" | "location" => "rack",
+        "rack_unit" | "unit" | "u" => "rack_unit",
+        "purchased" | "purchase_date" => "purchased",
+        "warranty" | "warranty_expires" | "warranty_end" | "warranty_expiry" => "warranty_expires",
+        "services" | "service" => "services",
+        "notes" | "comment" | "comments" => "notes",
+        _ => return None,
+    })
+}
+
+fn parse_date(value: &str) -> std::result::Result<NaiveDate, String> {
+    ["%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .ok_or_else(|| format!("Invalid date {}, expected YYYY-MM-DD", value))
+}
+
+/// Lists in a cell are separated by semicolons, pipes or whitespace
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ';' || c == '|' || c.is_whitespace())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Build an asset from a CSV row
+fn asset_from_row(headers: &[Option<&str>], row: &[String]) -> std::result::Result<Asset, String> {
+    let mut asset = Asset::default();
+    for (column, value) in headers.iter().zip(row) {
+        let value = value.trim();
+        let (Some(column), false) = (column, value.is_empty()) else {
+            continue;
+        };
+        let text = Some(value.to_string());
+        match *column {
+            "id" => asset.id = value.to_string(),
+            "name" => asset.name = text,
+            "kind" => asset.kind = text,
+            "manufacturer" => asset.manufacturer = text,
+            "model" => asset.model = text,
+            "serial" => asset.serial = text,
+            "ips" => asset.ips = parse_list(value),
+            "mac" => asset.mac = text,
+            "bmc" => asset.bmc = text,
+            "rack" => asset.rack = text,
+            "rack_unit" => {
+                asset.rack_unit = Some(
+                    value
+                        .trim_start_matches(['U', 'u'])
+                        .parse()
+                        .map_err(|_| format!("Invalid rack unit {}", value))?,
+                )
+            }
+            "purchased" => asset.purchased = Some(parse_date(value)?),
+            "warranty_expires" => asset.warranty_expires = Some(parse_date(value)?),
+            "services" => asset.services = parse_list(value),
+            "notes" => asset.notes = text,
+            _ => {}
+        }
+    }
+    if asset.id.is_empty() {
+        return Err("Missing id or hostname".to_string());
+    }
+    Ok(asset)
+}
+
+/// Reminder thresholds reached by `days_left` that have not been sent, largest first
+pub fn due_reminders(thresholds: &[i64], days_left: i64, sent: &[i64]) -> Vec<i64> {
+    let mut due: Vec<i64> = thresholds
+        .iter()
+        .copied()
+        .filter(|t| days_left <= *t && !sent.contains(t))
+        .collect();
+    due.sort_by_key(|t| std::cmp::Reverse(*t));
+    due
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AssetStore {
+    assets: BTreeMap<String, Asset>,
+    /// Reminder thresholds already sent, by asset id
+    #[serde(default)]
+    reminded: BTreeMap<String, Vec<i64>>,
+}
+
+/// Persisted hardware inventory
+pub struct AssetRegistry {
+    notifier: Option<Arc<Notifier>>,
+    config: AssetsConfig,
+    store: RwLock<AssetStore>,
+}
+
+impl AssetRegistry {
+    /// Open the registry, loading assets from the configured path if it exists
+    pub async fn open(config: AssetsConfig) -> Result<Self> {
+        let store = match &config.path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse asset inventory {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => AssetStore::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read asset inventory: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => AssetStore::default(),
+        };
+        Ok(Self {
+            notifier: None,
+            config,
+            store: RwLock::new(store),
+        })
+    }
+
+    /// Send warranty reminders through collaboration channels
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    async fn persist(&self, store: &AssetStore) -> Result<()> {
+        let Some(path) = &self.config.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create asset inventory directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to write asset inventory: {}", e),
+                    temp.clone(),
+                )
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace asset inventory: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Insert into the store; returns whether the asset already existed
+    fn insert(store: &mut AssetStore, mut asset: Asset) -> bool {
+        asset.updated_at = Some(Utc::now());
+        let previous = store.assets.insert(asset.id.clone(), asset.clone());
+        // A new warranty date earns a fresh set of reminders
+        if previous.as_ref().and_then(|p| p.warranty_expires) != asset.warranty_expires {
+            store.reminded.remove(&asset.id);
+        }
+        previous.is_some()
+    }
+
+    /// Add or replace an asset by id
+    pub async fn upsert(&self, asset: Asset) -> Result<Asset> {
+        if asset.id.trim().is_empty() {
+            return Err(Error::validation_with_field("Asset id is required", "id"));
+        }
+        let mut store = self.store.write().await;
+        Self::insert(&mut store, asset.clone());
+        self.persist(&store).await?;
+        Ok(store.assets[&asset.id].clone())
+    }
+
+    /// Asset by id
+    pub async fn get(&self, id: &str) -> Option<Asset> {
+        self.store.read().await.assets.get(id).cloned()
+    }
+
+    /// Delete an asset
+    pub async fn remove(&self, id: &str) -> Result<Asset> {
+        let mut store = self.store.write().await;
+        let asset = store
+            .assets
+            .remove(id)
+            .ok_or_else(|| Error::not_found_with_resource("Asset not found", "asset", id))?;
+        store.reminded.remove(id);
+        self.persist(&store).await?;
+        Ok(asset)
+    }
+
+    /// Import assets from CSV with a header row; rows replace assets with the same id
+    pub async fn import_csv(&self, text: &str) -> Result<ImportReport> {
+        let mut records = parse_csv(text).into_iter();
+        let (_, header) = records
+            .next()
+            .ok_or_else(|| Error::validation_with_field("CSV is empty", "csv"))?;
+        let headers: Vec<Option<&str>> = header.iter().map(|h| column(h)).collect();
+        if !headers.contains(&Some("id")) {
+            return Err(Error::validation_with_field(
+                "CSV needs an id or hostname column",
+                "csv",
+            ));
+        }
+
+        let mut report = ImportReport::default();
+        let mut store = self.store.write().await;
+        for (line, row) in records {
+            match asset_from_row(&headers, &row) {
+                Ok(asset) => {
+                    if Self::insert(&mut store, asset) {
+                        report.updated += 1;
+                    } else {
+                        report.added += 1;
+                    }
+                }
+                Err(reason) => report.skipped.push((line, reason)),
+            }
+        }
+        self.persist(&store).await?;
+        Ok(report)
+    }
+
+    /// Assets matching a filter, by id
+    pub async fn search(&self, filter: &AssetFilter, today: NaiveDate) -> Vec<Asset> {
+        let eq = |a: &Option<String>, b: &Option<String>| {
+            b.as_ref()
+                .is_none_or(|b| a.as_ref().is_some_and(|a| a.eq_ignore_ascii_case(b)))
+        };
+        self.store
+            .read()
+            .await
+            .assets
+            .values()
+            .filter(|a| filter.text.as_deref().is_none_or(|t| a.matches(t)))
+            .filter(|a| eq(&a.kind, &filter.kind) && eq(&a.rack, &filter.rack))
+            .filter(|a| {
+                filter
+                    .service
+                    .as_ref()
+                    .is_none_or(|s| a.services.iter().any(|x| x.eq_ignore_ascii_case(s)))
+            })
+            .filter(|a| {
+                filter
+                    .warranty_within_days
+                    .is_none_or(|days| a.warranty_days_left(today).is_some_and(|left| left <= days))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Send reminders for warranties that reached a threshold; returns the assets reminded about
+    pub async fn check_warranties(&self, today: NaiveDate) -> Result<Vec<Asset>> {
+        let mut store = self.store.write().await;
+        let mut reminded = Vec::new();
+        let assets: Vec<Asset> = store.assets.values().cloned().collect();
+        for asset in assets {
+            let Some(days_left) = asset.warranty_days_left(today) else {
+                continue;
+            };
+            let sent = store.reminded.get(&asset.id).cloned().unwrap_or_default();
+            let due = due_reminders(&self.config.reminder_days, days_left, &sent);
+            if due.is_empty() {
+                continue;
+            }
+            if self.notify(&asset, days_left).await {
+                store
+                    .reminded
+                    .entry(asset.id.clone())
+                    .or_default()
+                    .extend(due);
+                reminded.push(asset);
+            }
+        }
+        self.persist(&store).await?;
+        Ok(reminded)
+    }
+
+    /// Post a warranty reminder; returns whether any channel received it
+    async fn notify(&self, asset: &Asset, days_left: i64) -> bool {
+        let Some(notifier) = &self.notifier else {
+            return false;
+        };
+        if self.config.channels.is_empty() {
+            return false;
+        }
+        let expires = asset.warranty_expires.unwrap_or_default();
+        let notification = Notification {
+            title: if days_left < 0 {
+                format!("Warranty for {} has ended", asset.id)
+            } else {
+                format!("Warranty for {} ends in {} days", asset.id, days_left)
+            },
+            text: format!("{}: warranty until {}", asset.summary(), expires),
+            severity: if days_left < 0 {
+                Severity::Warning
+            } else {
+                Severity::Info
+            },
+            fields: [
+                ("Serial", asset.serial.clone()),
+                ("Location", asset.location()),
+            ]
+            .into_iter()
+            .filter_map(|(title, value)| {
+                Some(AttachmentField {
+                    title: title.to_string(),
+                    value: value?,
+                    short: true,
+                })
+            })
+            .collect(),
+        };
+        let failures = notifier
+            .broadcast(&self.config.channels, &notification)
+            .await;
+        for (channel, error) in &failures {
+            tracing::warn!(
+                "Warranty reminder for {} to {} failed: {}",
+                asset.id,
+                channel,
+                error
+            );
+        }
+        failures.len() < self.config.channels.len()
+    }
+
+    /// Check warranties on the configured interval
+    pub fn start_scheduler(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let period = std::time::Duration::from_secs(self.config.check_interval_hours.max(1) * 3600);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match self.check_warranties(Utc::now().date_naive()).await {
+                    Ok(assets) => tracing::info!("Sent {} warranty reminders", assets.len()),
+                    Err(e) => tracing::warn!("Warranty check failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Get tool definitions for the inventory
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "asset_upsert",
+                "Add or replace a homelab hardware asset: serial number, warranty, IPs, rack location and services",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string", "description": "Short unique name, usually the hostname"},
+                        "name": {"type": "string"},
+                        "kind": {"type": "string", "description": "e.g. server, nas, switch, ups"},
+                        "manufacturer": {"type": "string"},
+                        "model": {"type": "string"},
+                        "serial": {"type": "string"},
+                        "ips": {"type": "array", "items": {"type": "string"}},
+                        "mac": {"type": "string", "description": "MAC address for Wake-on-LAN"},
+                        "bmc": {"type": "string", "description": "BMC address (iDRAC, iLO, IPMI) for power control"},
+                        "rack": {"type": "string"},
+                        "rack_unit": {"type": "integer", "minimum": 1},
+                        "purchased": {"type": "string", "format": "date"},
+                        "warranty_expires": {"type": "string", "format": "date"},
+                        "services": {"type": "array", "items": {"type": "string"}},
+                        "notes": {"type": "string"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "asset_remove",
+                "Remove a hardware asset",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "asset_import_csv",
+                "Import hardware assets from CSV with a header row; rows replace assets with the same id",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "csv": {"type": "string", "description": "Columns such as hostname, serial, ip, rack, unit, warranty, services; lists separated by semicolons"}
+                    },
+                    "required": ["csv"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "asset_search",
+                "Search hardware assets by text, kind, rack, service or warranty end",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "text": {"type": "string", "description": "Words matched against hostname, serial, model, IPs, services and notes"},
+                        "kind": {"type": "string"},
+                        "rack": {"type": "string"},
+                        "service": {"type": "string"},
+                        "warranty_within_days": {"type": "integer", "description": "Only warranties ending within this many days, or already ended"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "asset_check_warranties",
+                "Send reminders for warranties that reached a reminder threshold now",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute an inventory tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let today = Utc::now().date_naive();
+        match name {
+            "asset_upsert" => {
+                let asset: Asset = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid asset: {}", e)))?;
+                let asset = self.upsert(asset).await?;
+                Ok(call_result(
+                    format!("Asset saved: {}", asset.summary()),
+                    json!({ "asset": asset }),
+                ))
+            }
+            "asset_remove" => {
+                let id = parameters
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::validation_with_field("id is required", "id"))?;
+                let asset = self.remove(id).await?;
+                Ok(call_result(
+                    format!("Asset {} removed", asset.id),
+                    json!({ "id": asset.id }),
+                ))
+            }
+            "asset_import_csv" => {
+                let csv = parameters
+                    .get("csv")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::validation_with_field("csv is required", "csv"))?;
+                let report = self.import_csv(csv).await?;
+                let mut text = format!(
+                    "Imported {} new and {} updated assets",
+                    report.added, report.updated
+                );
+                for (line, reason) in &report.skipped {
+                    text.push_str(&format!("\nSkipped line {}: {}", line, reason));
+                }
+                Ok(call_result(text, json!(report)))
+            }
+            "asset_search" => {
+                let filter: AssetFilter = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid filter: {}", e)))?;
+                let assets = self.search(&filter, today).await;
+                let mut text = format!("{} assets", assets.len());
+                for asset in &assets {
+                    text.push_str(&format!("\n{}", asset.summary()));
+                }
+                Ok(call_result(text, json!({ "assets": assets })))
+            }
+            "asset_check_warranties" => {
+                if self.notifier.is_none() || self.config.channels.is_empty() {
+                    return Err(Error::config_with_suggestion(
+                        "No channels for warranty reminders",
+                        "Set infrastructure.assets.channels to collaboration channel names",
+                    ));
+                }
+                let assets = self.check_warranties(today).await?;
+                let mut text = format!("Sent reminders for {} assets", assets.len());
+                for asset in &assets {
+                    text.push_str(&format!("\n{}", asset.summary()));
+                }
+                Ok(call_result(text, json!({ "assets": assets })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "asset_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn imports_spreadsheet_export_and_searches_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AssetsConfig {
+            path: Some(dir.path().join("assets.json")),
+            ..AssetsConfig::default()
+        };
+        let registry = AssetRegistry::open(config.clone()).await.unwrap();
+        let csv = "Hostname,Vendor,Model,Serial Number,IP,Rack,Unit,Warranty,Services,Notes\n\
+                   pve1,Dell,R730,7XK2Q12,10.0.0.11;10.0.0.12,A,U12,2025-03-01,proxmox;ceph,\n\
+                   nas,Synology,DS920+,2150PDN,10.0.0.20,A,4,2027-01-15,nfs immich,\"Bays 1-4, RAID \"\"SHR\"\"\"\n\
+                   ,Ubiquiti,USW-24,,,A,1,,,\n\
+                   sw1,Ubiquiti,USW-24,F4E2,10.0.0.2,A,1,15/01/2026,,\n";
+        let report = registry.import_csv(csv).await.unwrap();
+        assert_eq!((report.added, report.updated), (2, 0));
+        assert_eq!(report.skipped.len(), 2);
+        assert_eq!(report.skipped[0].0, 4);
+
+        let today = NaiveDate::from_ymd_opt(2024, 12, 20).unwrap();
+        let reopened = AssetRegistry::open(config).await.unwrap();
+        let nas = &reopened
+            .search(
+                &AssetFilter {
+                    text: Some("synology 10.0.0.20".to_string()),
+                    ..AssetFilter::default()
+                },
+                today,
+            )
+            .await[0];
+        assert_eq!(nas.notes.as_deref(), Some("Bays 1-4, RAID \"SHR\""));
+        assert_eq!(nas.services, ["nfs", "immich"]);
+        assert_eq!(nas.location().as_deref(), Some("A, U4"));
+
+        let expiring = reopened
+            .search(
+                &AssetFilter {
+                    warranty_within_days: Some(90),
+                    ..AssetFilter::default()
+                },
+                today,
+            )
+            .await;
+        assert_eq!(expiring.len(), 1);
+        assert_eq!(expiring[0].id, "pve1");
+        assert_eq!(expiring[0].ips, ["10.0.0.11", "10.0.0.12"]);
+    }
+
+    #[test]
+    fn reminders_fire_once_per_threshold() {
+        let thresholds = [90, 30, 7];
+        assert!(due_reminders(&thresholds, 120, &[]).is_empty());
+        assert_eq!(due_reminders(&thresholds, 25, &[]), [90, 30]);
+        assert!(due_reminders(&thresholds, 25, &[90, 30]).is_empty());
+        assert_eq!(due_reminders(&thresholds, -3, &[90, 30]), [7]);
+    }
+}
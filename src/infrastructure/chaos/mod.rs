@@ -0,0 +1,839 @@
+/// Controlled fault injection for Kubernetes workloads
+///
+/// Experiments are filed as approval requests and only run once someone other
+/// than the requester approves them; the approved spec is what runs. Every
+/// experiment is capped by blast-radius limits, reverted by a timer when its
+/// duration elapses (or when aborted), and summarised in a report that tracks
+/// whether the workload recovered to its baseline.
+use crate::error::{Error, Result};
+use crate::infrastructure::kubernetes::KubernetesClient;
+use crate::lifecycle::LifecycleManager;
+use crate::security::approvals::{ApprovalManager, ApprovalRequest};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Utc};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{oneshot, RwLock};
+
+/// Approval action for chaos experiments
+pub const APPROVAL_ACTION: &str = "chaos.experiment";
+
+/// How long to wait for targets to become ready again after rollback
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(300);
+const RECOVERY_POLL: Duration = Duration::from_secs(5);
+
+/// Blast-radius and tooling limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChaosConfig {
+    /// Most pods a single experiment may affect
+    pub max_targets: usize,
+    /// Largest share of the selected pods one experiment may affect
+    pub max_target_percent: f64,
+    pub max_duration_secs: u64,
+    pub max_latency_ms: u32,
+    /// Namespaces experiments may never target
+    pub protected_namespaces: Vec<String>,
+    /// Image with `tc`, run as an ephemeral container for network faults
+    pub network_image: String,
+    /// Image with `stress-ng`, run as an ephemeral container for CPU stress
+    pub stress_image: String,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            max_targets: 3,
+            max_target_percent: 34.0,
+            max_duration_secs: 1800,
+            max_latency_ms: 5000,
+            protected_namespaces: vec![
+                "kube-system".to_string(),
+                "kube-public".to_string(),
+                "kube-node-lease".to_string(),
+            ],
+            network_image: "nicolaka/netshoot:v0.13".to_string(),
+            stress_image: "alexeiled/stress-ng:0.12.05".to_string(),
+        }
+    }
+}
+
+fn default_interface() -> String {
+    "eth0".to_string()
+}
+
+fn default_workers() -> u32 {
+    1
+}
+
+fn default_load() -> u32 {
+    80
+}
+
+/// Fault to inject
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Fault {
+    /// Delete the targeted pods and let their controller replace them
+    PodKill,
+    /// Add egress latency with `tc netem` in the pod's network namespace
+    NetworkLatency {
+        latency_ms: u32,
+        #[serde(default)]
+        jitter_ms: u32,
+        #[serde(default = "default_interface")]
+        interface: String,
+    },
+    /// Burn CPU with `stress-ng` inside the pod's cgroup
+    CpuStress {
+        #[serde(default = "default_workers")]
+        workers: u32,
+        #[serde(default = "default_load")]
+        load_percent: u32,
+    },
+}
+
+/// What to break, where and for how long
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExperimentSpec {
+    pub name: String,
+    pub namespace: String,
+    /// Label selector for candidate pods, e.g. `app=web,tier!=canary`
+    pub selector: String,
+    pub fault: Fault,
+    pub duration_secs: u64,
+    /// Pods to target, further capped by the configured limits
+    #[serde(default)]
+    pub max_targets: Option<usize>,
+}
+
+/// Experiment state
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentStatus {
+    Running,
+    Completed,
+    Aborted,
+    /// No fault could be injected
+    Failed,
+}
+
+/// Injection and rollback outcome for one pod
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetResult {
+    pub pod: String,
+    pub injected: bool,
+    /// `None` when the fault needs no rollback
+    pub rolled_back: Option<bool>,
+    pub error: Option<String>,
+}
+
+/// Experiment report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    pub id: String,
+    pub approval_id: String,
+    pub approved_by: Option<String>,
+    pub spec: ExperimentSpec,
+    pub status: ExperimentStatus,
+    /// Pods matching the selector when the experiment started
+    pub matching_pods: usize,
+    pub targets: Vec<TargetResult>,
+    /// Ready pods before injection
+    pub baseline_ready: usize,
+    /// Ready pods once recovery finished or timed out
+    pub final_ready: Option<usize>,
+    pub recovered: Option<bool>,
+    pub recovery_secs: Option<u64>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub events: Vec<String>,
+}
+
+/// Pods an experiment may affect given how many match its selector
+pub fn blast_radius(matching: usize, requested: Option<usize>, config: &ChaosConfig) -> usize {
+    let by_share = (matching as f64 * config.max_target_percent / 100.0).floor() as usize;
+    requested.unwrap_or(1).min(config.max_targets).min(by_share)
+}
+
+fn valid_name(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 253
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+}
+
+/// Check a spec against the configured limits
+pub fn validate(spec: &ExperimentSpec, config: &ChaosConfig) -> Result<()> {
+    if !valid_name(&spec.namespace) {
+        return Err(Error::validation_with_field(
+            "Invalid namespace",
+            "namespace",
+        ));
+    }
+    if config.protected_namespaces.contains(&spec.namespace) {
+        return Err(Error::validation_with_field(
+            format!(
+                "Namespace {} is protected from chaos experiments",
+                spec.namespace
+            ),
+            "namespace",
+        ));
+    }
+    if spec.selector.is_empty()
+        || !spec
+            .selector
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=!,".contains(c))
+    {
+        return Err(Error::validation_with_field(
+            "selector must be a non-empty equality-based label selector",
+            "selector",
+        ));
+    }
+    if spec.duration_secs == 0 || spec.duration_secs > config.max_duration_secs {
+        return Err(Error::validation_with_field(
+            format!(
+                "duration_secs must be between 1 and {}",
+                config.max_duration_secs
+            ),
+            "duration_secs",
+        ));
+    }
+    match &spec.fault {
+        Fault::PodKill => {}
+        Fault::NetworkLatency {
+            latency_ms,
+            jitter_ms,
+            interface,
+        } => {
+            if *latency_ms == 0 || *latency_ms > config.max_latency_ms || jitter_ms > latency_ms {
+                return Err(Error::validation_with_field(
+                    format!(
+                        "latency_ms must be between 1 and {} and at least jitter_ms",
+                        config.max_latency_ms
+                    ),
+                    "latency_ms",
+                ));
+            }
+            if interface.is_empty() || !interface.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(Error::validation_with_field(
+                    "Invalid interface",
+                    "interface",
+                ));
+            }
+        }
+        Fault::CpuStress {
+            workers,
+            load_percent,
+        } => {
+            if *workers == 0 || *workers > 16 || *load_percent == 0 || *load_percent > 100 {
+                return Err(Error::validation_with_field(
+                    "workers must be 1-16 and load_percent 1-100",
+                    "fault",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Ready and terminating pods for a selector
+#[derive(Debug, Clone)]
+struct PodState {
+    name: String,
+    ready: bool,
+}
+
+fn parse_pods(list: &Value) -> Vec<PodState> {
+    list["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|pod| pod["metadata"]["deletionTimestamp"].is_null())
+        .map(|pod| PodState {
+            name: pod["metadata"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            ready: pod["status"]["conditions"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|c| c["type"] == "Ready" && c["status"] == "True"),
+        })
+        .collect()
+}
+
+/// Runs approved chaos experiments
+pub struct ChaosEngine {
+    lifecycle: Arc<LifecycleManager>,
+    kubeconfig: Option<String>,
+    context: Option<String>,
+    approvals: Arc<ApprovalManager>,
+    config: ChaosConfig,
+    experiments: RwLock<BTreeMap<String, ExperimentReport>>,
+    aborts: Mutex<HashMap<String, oneshot::Sender<()>>>,
+}
+
+impl ChaosEngine {
+    /// Create an engine gated by `approvals`
+    pub fn new(
+        lifecycle: Arc<LifecycleManager>,
+        kubeconfig: Option<String>,
+        context: Option<String>,
+        approvals: Arc<ApprovalManager>,
+        config: ChaosConfig,
+    ) -> Self {
+        Self {
+            lifecycle,
+            kubeconfig,
+            context,
+            approvals,
+            config,
+            experiments: RwLock::new(BTreeMap::new()),
+            aborts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn kubectl(&self, args: &[&str]) -> Result<String> {
+        let client = KubernetesClient::new(
+            &self.lifecycle,
+            self.kubeconfig.as_deref(),
+            self.context.as_deref(),
+        )?;
+        let result = client.run_secure_kubectl_command(args).await?;
+        if result.success {
+            Ok(result.output)
+        } else {
+            Err(Error::service(format!(
+                "{} failed: {}",
+                result.command,
+                result.error.unwrap_or_default().trim()
+            )))
+        }
+    }
+
+    async fn pods(&self, spec: &ExperimentSpec) -> Result<Vec<PodState>> {
+        let output = self
+            .kubectl(&[
+                "get",
+                "pods",
+                "-n",
+                &spec.namespace,
+                "-l",
+                &spec.selector,
+                "-o",
+                "json",
+            ])
+            .await?;
+        let list: Value = serde_json::from_str(&output)
+            .map_err(|e| Error::parsing(format!("Failed to parse pods: {}", e)))?;
+        Ok(parse_pods(&list))
+    }
+
+    /// Validate a spec and ask for approval to run it
+    pub async fn request(
+        &self,
+        spec: ExperimentSpec,
+        requested_by: &str,
+    ) -> Result<(ApprovalRequest, usize, usize)> {
+        validate(&spec, &self.config)?;
+        let matching = self.pods(&spec).await?.len();
+        let targets = blast_radius(matching, spec.max_targets, &self.config);
+        if targets == 0 {
+            return Err(Error::validation_with_field(
+                format!(
+                    "Selector matches {} pods; blast-radius limits allow none to be targeted",
+                    matching
+                ),
+                "selector",
+            ));
+        }
+        let summary = format!(
+            "Chaos experiment {}: {} on {} of {} pods matching {} in {} for {}s",
+            spec.name,
+            match &spec.fault {
+                Fault::PodKill => "pod kill".to_string(),
+                Fault::NetworkLatency { latency_ms, .. } => format!("{}ms latency", latency_ms),
+                Fault::CpuStress { load_percent, .. } => format!("{}% CPU stress", load_percent),
+            },
+            targets,
+            matching,
+            spec.selector,
+            spec.namespace,
+            spec.duration_secs
+        );
+        let request = self
+            .approvals
+            .request(
+                APPROVAL_ACTION,
+                &summary,
+                serde_json::to_value(&spec)?,
+                requested_by,
+            )
+            .await?;
+        Ok((request, matching, targets))
+    }
+
+    fn inject_args(&self, spec: &ExperimentSpec, pod: &str) -> Vec<String> {
+        let ephemeral = |image: &str, profile: &str, command: Vec<String>| {
+            let mut args: Vec<String> = [
+                "debug",
+                pod,
+                "-n",
+                &spec.namespace,
+                "--image",
+                image,
+                profile,
+                "--",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+            args.extend(command);
+            args
+        };
+        match &spec.fault {
+            Fault::PodKill => ["delete", "pod", pod, "-n", &spec.namespace, "--wait=false"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            Fault::NetworkLatency {
+                latency_ms,
+                jitter_ms,
+                interface,
+            } => {
+                let mut command: Vec<String> = [
+                    "tc", "qdisc", "add", "dev", interface, "root", "netem", "delay",
+                ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+                command.push(format!("{}ms", latency_ms));
+                if *jitter_ms > 0 {
+                    command.push(format!("{}ms", jitter_ms));
+                }
+                ephemeral(&self.config.network_image, "--profile=netadmin", command)
+            }
+            Fault::CpuStress {
+                workers,
+                load_percent,
+            } => ephemeral(
+                &self.config.stress_image,
+                "--profile=general",
+                vec![
+                    "stress-ng".to_string(),
+                    "--cpu".to_string(),
+                    workers.to_string(),
+                    "--cpu-load".to_string(),
+                    load_percent.to_string(),
+                    // Stops on its own even if the rollback timer never fires
+                    "--timeout".to_string(),
+                    format!("{}s", spec.duration_secs),
+                ],
+            ),
+        }
+    }
+
+    fn rollback_args(&self, spec: &ExperimentSpec, pod: &str) -> Option<Vec<String>> {
+        let Fault::NetworkLatency { interface, .. } = &spec.fault else {
+            return None;
+        };
+        Some(
+            [
+                "debug",
+                pod,
+                "-n",
+                &spec.namespace,
+                "--image",
+                &self.config.network_image,
+                "--profile=netadmin",
+                "--",
+                "tc",
+                "qdisc",
+                "del",
+                "dev",
+                interface,
+                "root",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        )
+    }
+
+    /// Run an approved experiment; the fault is reverted after its duration
+    pub async fn run(self: &Arc<Self>, approval_id: &str) -> Result<ExperimentReport> {
+        let approval = self.approvals.consume(approval_id, APPROVAL_ACTION).await?;
+        let spec: ExperimentSpec = serde_json::from_value(approval.details.clone())
+            .map_err(|e| Error::parsing(format!("Approved experiment is malformed: {}", e)))?;
+        validate(&spec, &self.config)?;
+
+        // Targets are picked now; the selector may match different pods than at request time
+        let mut pods = self.pods(&spec).await?;
+        let matching = pods.len();
+        let baseline_ready = pods.iter().filter(|p| p.ready).count();
+        let count = blast_radius(matching, spec.max_targets, &self.config);
+        if count == 0 {
+            return Err(Error::validation(format!(
+                "Selector now matches {} pods; blast-radius limits allow none to be targeted",
+                matching
+            )));
+        }
+        // Prefer healthy pods so the experiment measures the fault, not prior damage
+        pods.shuffle(&mut rand::thread_rng());
+        pods.sort_by_key(|p| !p.ready);
+        pods.truncate(count);
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut report = ExperimentReport {
+            id: id.clone(),
+            approval_id: approval.id.clone(),
+            approved_by: approval.decided_by.clone(),
+            spec: spec.clone(),
+            status: ExperimentStatus::Running,
+            matching_pods: matching,
+            targets: Vec::new(),
+            baseline_ready,
+            final_ready: None,
+            recovered: None,
+            recovery_secs: None,
+            started_at: Utc::now(),
+            ended_at: None,
+            events: Vec::new(),
+        };
+        for pod in &pods {
+            let args = self.inject_args(&spec, &pod.name);
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let outcome = self.kubectl(&args).await;
+            report.events.push(format!(
+                "{} inject {}: {}",
+                Utc::now().to_rfc3339(),
+                pod.name,
+                match &outcome {
+                    Ok(_) => "ok".to_string(),
+                    Err(e) => e.to_string(),
+                }
+            ));
+            report.targets.push(TargetResult {
+                pod: pod.name.clone(),
+                injected: outcome.is_ok(),
+                rolled_back: None,
+                error: outcome.err().map(|e| e.to_string()),
+            });
+        }
+
+        if !report.targets.iter().any(|t| t.injected) {
+            report.status = ExperimentStatus::Failed;
+            report.ended_at = Some(Utc::now());
+            self.experiments
+                .write()
+                .await
+                .insert(id.clone(), report.clone());
+            return Ok(report);
+        }
+
+        self.experiments
+            .write()
+            .await
+            .insert(id.clone(), report.clone());
+        let (abort_tx, abort_rx) = oneshot::channel();
+        if let Ok(mut aborts) = self.aborts.lock() {
+            aborts.insert(id.clone(), abort_tx);
+        }
+        let engine = Arc::clone(self);
+        let duration = Duration::from_secs(spec.duration_secs);
+        tokio::spawn(async move {
+            let aborted = tokio::select! {
+                _ = tokio::time::sleep(duration) => false,
+                _ = abort_rx => true,
+            };
+            engine.finish(&id, aborted).await;
+        });
+        Ok(report)
+    }
+
+    /// Revert the fault and measure recovery
+    async fn finish(&self, id: &str, aborted: bool) {
+        if let Ok(mut aborts) = self.aborts.lock() {
+            aborts.remove(id);
+        }
+        let Some(mut report) = self.experiments.read().await.get(id).cloned() else {
+            return;
+        };
+        let spec = report.spec.clone();
+
+        for target in report.targets.iter_mut().filter(|t| t.injected) {
+            let Some(args) = self.rollback_args(&spec, &target.pod) else {
+                continue;
+            };
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let outcome = self.kubectl(&args).await;
+            report.events.push(format!(
+                "{} rollback {}: {}",
+                Utc::now().to_rfc3339(),
+                target.pod,
+                match &outcome {
+                    Ok(_) => "ok".to_string(),
+                    Err(e) => e.to_string(),
+                }
+            ));
+            if let Err(e) = &outcome {
+                tracing::warn!(
+                    "Chaos experiment {} failed to roll back {}: {}",
+                    id,
+                    target.pod,
+                    e
+                );
+            }
+            target.rolled_back = Some(outcome.is_ok());
+        }
+
+        let started = std::time::Instant::now();
+        let mut ready = None;
+        while started.elapsed() < RECOVERY_TIMEOUT {
+            match self.pods(&spec).await {
+                Ok(pods) => {
+                    let count = pods.iter().filter(|p| p.ready).count();
+                    ready = Some(count);
+                    if count >= report.baseline_ready {
+                        report.recovery_secs = Some(started.elapsed().as_secs());
+                        break;
+                    }
+                }
+                Err(e) => report.events.push(format!("recovery check failed: {}", e)),
+            }
+            tokio::time::sleep(RECOVERY_POLL).await;
+        }
+        report.final_ready = ready;
+        report.recovered = Some(report.recovery_secs.is_some());
+        report.status = if aborted {
+            ExperimentStatus::Aborted
+        } else {
+            ExperimentStatus::Completed
+        };
+        report.ended_at = Some(Utc::now());
+        tracing::info!(
+            "Chaos experiment {} {:?}, recovered: {:?}",
+            id,
+            report.status,
+            report.recovered
+        );
+        self.experiments
+            .write()
+            .await
+            .insert(id.to_string(), report);
+    }
+
+    /// Stop a running experiment and roll it back now
+    pub fn abort(&self, id: &str) -> Result<()> {
+        let sender = self
+            .aborts
+            .lock()
+            .map_err(|_| Error::internal("Chaos abort registry poisoned"))?
+            .remove(id)
+            .ok_or_else(|| {
+                Error::not_found_with_resource("No running experiment", "experiment", id)
+            })?;
+        sender
+            .send(())
+            .map_err(|_| Error::internal("Experiment already finishing"))
+    }
+
+    /// Reports, newest first
+    pub async fn reports(&self, id: Option<&str>) -> Vec<ExperimentReport> {
+        let mut reports: Vec<ExperimentReport> = self
+            .experiments
+            .read()
+            .await
+            .values()
+            .filter(|r| id.is_none_or(|id| r.id == id))
+            .cloned()
+            .collect();
+        reports.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+        reports
+    }
+
+    /// Get tool definitions for chaos experiments
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "chaos_request",
+                "Propose a chaos experiment (pod kill, network latency, CPU stress); it runs only after approval",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string"},
+                        "namespace": {"type": "string"},
+                        "selector": {"type": "string", "description": "Label selector, e.g. app=web"},
+                        "fault": {
+                            "type": "object",
+                            "description": "{\"type\": \"pod_kill\"} | {\"type\": \"network_latency\", \"latency_ms\": 200, \"jitter_ms\": 20} | {\"type\": \"cpu_stress\", \"workers\": 1, \"load_percent\": 80}",
+                            "properties": {
+                                "type": {"type": "string", "enum": ["pod_kill", "network_latency", "cpu_stress"]}
+                            },
+                            "required": ["type"]
+                        },
+                        "duration_secs": {"type": "integer", "description": "Time before automatic rollback"},
+                        "max_targets": {"type": "integer", "default": 1},
+                        "requested_by": {"type": "string"}
+                    },
+                    "required": ["name", "namespace", "selector", "fault", "duration_secs", "requested_by"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "chaos_run",
+                "Run an approved chaos experiment",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {"approval_id": {"type": "string"}},
+                    "required": ["approval_id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "chaos_abort",
+                "Abort a running chaos experiment and roll it back immediately",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {"id": {"type": "string"}},
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "chaos_report",
+                "Show chaos experiment reports",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {"id": {"type": "string"}}
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    /// Execute a chaos tool
+    pub async fn execute_tool(self: &Arc<Self>, name: &str, parameters: Value) -> Result<Value> {
+        let required = |key: &'static str| {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| Error::validation_with_field(format!("{} is required", key), key))
+        };
+        match name {
+            "chaos_request" => {
+                let requested_by = required("requested_by")?;
+                let spec: ExperimentSpec = serde_json::from_value(parameters.clone())
+                    .map_err(|e| Error::validation(format!("Invalid experiment: {}", e)))?;
+                let (request, matching, targets) = self.request(spec, requested_by).await?;
+                Ok(call_result(
+                    format!(
+                        "Approval {} requested: {} ({} of {} matching pods)",
+                        request.id, request.summary, targets, matching
+                    ),
+                    json!({ "approval": request, "matching_pods": matching, "targets": targets }),
+                ))
+            }
+            "chaos_run" => {
+                let report = self.run(required("approval_id")?).await?;
+                let injected = report.targets.iter().filter(|t| t.injected).count();
+                Ok(call_result(
+                    format!(
+                        "Experiment {} {:?}: fault injected into {}/{} pods; rollback in {}s",
+                        report.id,
+                        report.status,
+                        injected,
+                        report.targets.len(),
+                        report.spec.duration_secs
+                    ),
+                    json!({ "report": report }),
+                ))
+            }
+            "chaos_abort" => {
+                let id = required("id")?;
+                self.abort(id)?;
+                Ok(call_result(
+                    format!("Experiment {} aborting; rollback in progress", id),
+                    json!({ "id": id }),
+                ))
+            }
+            "chaos_report" => {
+                let reports = self
+                    .reports(parameters.get("id").and_then(|v| v.as_str()))
+                    .await;
+                let mut text = format!("{} experiments", reports.len());
+                for report in &reports {
+                    text.push_str(&format!(
+                        "\n{} {} [{:?}] targets {}, ready {}/{}, recovered {:?}",
+                        report.id,
+                        report.spec.name,
+                        report.status,
+                        report.targets.len(),
+                        report
+                            .final_ready
+                            .map(|r| r.to_string())
+                            .unwrap_or_else(|| "?".to_string()),
+                        report.baseline_ready,
+                        report.recovered
+                    ));
+                }
+                Ok(call_result(text, json!({ "reports": reports })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "chaos_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enforces_blast_radius_and_limits() {
+        let config = ChaosConfig::default();
+        assert_eq!(blast_radius(1, None, &config), 0);
+        assert_eq!(blast_radius(3, Some(5), &config), 1);
+        assert_eq!(blast_radius(20, Some(5), &config), 3);
+
+        let mut spec: ExperimentSpec = serde_json::from_value(json!({
+            "name": "latency", "namespace": "shop", "selector": "app=web",
+            "fault": {"type": "network_latency", "latency_ms": 200},
+            "duration_secs": 120
+        }))
+        .unwrap();
+        validate(&spec, &config).unwrap();
+        assert_eq!(
+            spec.fault,
+            Fault::NetworkLatency {
+                latency_ms: 200,
+                jitter_ms: 0,
+                interface: "eth0".to_string()
+            }
+        );
+
+        spec.namespace = "kube-system".to_string();
+        assert!(validate(&spec, &config).is_err());
+        spec.namespace = "shop".to_string();
+        spec.selector = "app=web;reboot".to_string();
+        assert!(validate(&spec, &config).is_err());
+        spec.selector = "app=web".to_string();
+        spec.duration_secs = 7200;
+        assert!(validate(&spec, &config).is_err());
+    }
+}
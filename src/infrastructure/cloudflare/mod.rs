@@ -424,12 +424,13 @@ impl CloudflareClient {
     /// Verify API token by making a test request
     async fn verify_token(&self) -> Result<()> {
         // Make a simple request to verify the token
-        let response = self.client
+        let response = self
+            .client
             .get("https://api.cloudflare.com/client/v4/user/tokens/verify")
             .send()
             .await
             .map_err(|e| Error::network(format!("Failed to verify token: {}", e)))?;
-        
+
         if response.status().is_success() {
             Ok(())
         } else {
@@ -447,28 +448,41 @@ impl CloudflareClient {
     }
 
     /// Deploy a Cloudflare resource
-    pub async fn deploy_resource(&self, resource: crate::infrastructure::ResourceSpec) -> Result<crate::infrastructure::ResourceResult> {
+    pub async fn deploy_resource(
+        &self,
+        resource: crate::infrastructure::ResourceSpec,
+    ) -> Result<crate::infrastructure::ResourceResult> {
         use crate::infrastructure::ResourceResult;
-        
+
         // Deploy based on resource type
         match resource.spec.get("type").and_then(|v| v.as_str()) {
             Some("dns_record") => {
-                let zone_id = resource.spec.get("zone_id")
+                let zone_id = resource
+                    .spec
+                    .get("zone_id")
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| Error::validation("Missing 'zone_id' in resource spec"))?;
-                let record_type = resource.spec.get("record_type")
+                let record_type = resource
+                    .spec
+                    .get("record_type")
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| Error::validation("Missing 'record_type' in resource spec"))?;
-                let name = resource.spec.get("name")
+                let name = resource
+                    .spec
+                    .get("name")
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| Error::validation("Missing 'name' in resource spec"))?;
-                let content = resource.spec.get("content")
+                let content = resource
+                    .spec
+                    .get("content")
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| Error::validation("Missing 'content' in resource spec"))?;
-                let proxied = resource.spec.get("proxied")
+                let proxied = resource
+                    .spec
+                    .get("proxied")
                     .and_then(|v| v.as_bool())
                     .unwrap_or(false);
-                
+
                 let params = CreateDnsRecordParams {
                     record_type: record_type.to_string(),
                     name: name.to_string(),
@@ -476,13 +490,16 @@ impl CloudflareClient {
                     ttl: 300, // Default TTL
                     proxied,
                 };
-                
+
                 match self.create_dns_record(zone_id, params).await {
                     Ok(record) => Ok(ResourceResult {
                         name: resource.name.clone(),
                         resource_type: resource.resource_type.clone(),
                         status: "success".to_string(),
-                        message: Some(format!("DNS record {} created successfully with ID: {}", name, record.id)),
+                        message: Some(format!(
+                            "DNS record {} created successfully with ID: {}",
+                            name, record.id
+                        )),
                     }),
                     Err(e) => Ok(ResourceResult {
                         name: resource.name.clone(),
@@ -491,15 +508,19 @@ impl CloudflareClient {
                         message: Some(format!("Failed to create DNS record: {}", e)),
                     }),
                 }
-            },
+            }
             Some("website") => {
-                let domain = resource.spec.get("domain")
+                let domain = resource
+                    .spec
+                    .get("domain")
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| Error::validation("Missing 'domain' in resource spec"))?;
-                let site_path = resource.spec.get("site_path")
+                let site_path = resource
+                    .spec
+                    .get("site_path")
                     .and_then(|v| v.as_str())
                     .unwrap_or(".");
-                
+
                 match self.deploy_website(domain, site_path).await {
                     Ok(deployment) => Ok(ResourceResult {
                         name: resource.name.clone(),
@@ -514,7 +535,7 @@ impl CloudflareClient {
                         message: Some(format!("Failed to deploy website: {}", e)),
                     }),
                 }
-            },
+            }
             _ => Ok(ResourceResult {
                 name: resource.name.clone(),
                 resource_type: resource.resource_type.clone(),
@@ -525,9 +546,12 @@ impl CloudflareClient {
     }
 
     /// Scale Cloudflare resources (not applicable)
-    pub async fn scale_resource(&self, target: crate::infrastructure::ScalingTarget) -> Result<crate::infrastructure::ScalingTargetResult> {
+    pub async fn scale_resource(
+        &self,
+        target: crate::infrastructure::ScalingTarget,
+    ) -> Result<crate::infrastructure::ScalingTargetResult> {
         use crate::infrastructure::ScalingTargetResult;
-        
+
         // Cloudflare resources don't support traditional scaling
         Ok(ScalingTargetResult {
             resource_id: target.resource_name.clone(),
@@ -0,0 +1,482 @@
+//! Docker Engine API client
+//!
+//! Talks to the daemon directly over its Unix socket or over TCP (optionally
+//! with TLS client certificates) instead of running the `docker` CLI. One
+//! `DockerEngine` holds one connection pool, so share it rather than
+//! reconnecting per call.
+
+use super::{BlockIO, Container, ContainerRuntime, NetworkIO, PortMapping, ResourceUsage};
+use crate::error::{Error, Result};
+use bollard::container::{
+    CPUStats, InspectContainerOptions, ListContainersOptions, LogOutput, LogsOptions,
+    StartContainerOptions, Stats, StatsOptions, StopContainerOptions,
+};
+use bollard::exec::{CreateExecOptions, StartExecResults};
+use bollard::{Docker, API_DEFAULT_VERSION};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Socket used when `DOCKER_HOST` is unset
+pub const DEFAULT_SOCKET: &str = "unix:///var/run/docker.sock";
+
+/// Seconds before an API request times out
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+
+/// Where the daemon listens
+#[derive(Debug, Clone, PartialEq)]
+pub enum DockerHost {
+    Unix(PathBuf),
+    Tcp(String),
+}
+
+impl DockerHost {
+    /// Parse a `DOCKER_HOST` value (`unix:///path`, `tcp://host:port`, or a bare path)
+    pub fn parse(host: &str) -> Result<Self> {
+        if let Some(path) = host.strip_prefix("unix://") {
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+        if let Some(address) = host
+            .strip_prefix("tcp://")
+            .or_else(|| host.strip_prefix("http://"))
+            .or_else(|| host.strip_prefix("https://"))
+        {
+            if address.is_empty() {
+                return Err(Error::validation_with_field(
+                    format!("Docker host has no address: {}", host),
+                    "docker_host",
+                ));
+            }
+            return Ok(Self::Tcp(address.to_string()));
+        }
+        if host.starts_with('/') {
+            return Ok(Self::Unix(PathBuf::from(host)));
+        }
+        Err(Error::validation_with_field(
+            format!("Unsupported Docker host: {}", host),
+            "docker_host",
+        ))
+    }
+}
+
+/// Connection settings for the Docker daemon
+#[derive(Debug, Clone)]
+pub struct DockerEngineConfig {
+    pub host: DockerHost,
+    /// Directory holding `ca.pem`, `cert.pem` and `key.pem` for TLS over TCP
+    pub cert_path: Option<PathBuf>,
+    pub timeout: Duration,
+}
+
+impl DockerEngineConfig {
+    /// Settings from `DOCKER_HOST`, `DOCKER_TLS_VERIFY` and `DOCKER_CERT_PATH`
+    pub fn from_env() -> Result<Self> {
+        let host = std::env::var("DOCKER_HOST").unwrap_or_else(|_| DEFAULT_SOCKET.to_string());
+        let tls = std::env::var("DOCKER_TLS_VERIFY").is_ok_and(|v| !v.is_empty() && v != "0");
+        let cert_path = std::env::var("DOCKER_CERT_PATH")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| {
+                tls.then(|| std::env::var("HOME").ok())
+                    .flatten()
+                    .map(|home| Path::new(&home).join(".docker"))
+            });
+        Ok(Self {
+            host: DockerHost::parse(&host)?,
+            cert_path,
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        })
+    }
+}
+
+/// Detailed state of one container
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerDetails {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub status: String,
+    pub running: bool,
+    pub exit_code: Option<i64>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub restart_count: i64,
+    pub command: Vec<String>,
+    pub env: Vec<String>,
+    pub labels: HashMap<String, String>,
+    pub health: Option<String>,
+}
+
+/// Output of a command run inside a container
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecOutput {
+    pub exit_code: Option<i64>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Client for the Docker Engine API
+#[derive(Debug, Clone)]
+pub struct DockerEngine {
+    docker: Docker,
+}
+
+impl DockerEngine {
+    /// Connect with the given settings
+    pub fn connect(config: &DockerEngineConfig) -> Result<Self> {
+        let timeout = config.timeout.as_secs();
+        let docker = match (&config.host, &config.cert_path) {
+            (DockerHost::Unix(path), _) => {
+                Docker::connect_with_unix(&path.to_string_lossy(), timeout, API_DEFAULT_VERSION)
+            }
+            (DockerHost::Tcp(address), Some(certs)) => Docker::connect_with_ssl(
+                address,
+                &certs.join("key.pem"),
+                &certs.join("cert.pem"),
+                &certs.join("ca.pem"),
+                timeout,
+                API_DEFAULT_VERSION,
+            ),
+            (DockerHost::Tcp(address), None) => {
+                Docker::connect_with_http(address, timeout, API_DEFAULT_VERSION)
+            }
+        }
+        .map_err(|e| Error::connection(format!("Failed to connect to Docker: {}", e)))?;
+        Ok(Self { docker })
+    }
+
+    /// Connect using the standard Docker environment variables
+    pub fn from_env() -> Result<Self> {
+        Self::connect(&DockerEngineConfig::from_env()?)
+    }
+
+    /// Check that the daemon answers
+    pub async fn ping(&self) -> Result<()> {
+        self.docker.ping().await.map_err(api_error)?;
+        Ok(())
+    }
+
+    /// List containers, including stopped ones when `all` is set
+    pub async fn list_containers(&self, all: bool) -> Result<Vec<Container>> {
+        let summaries = self
+            .docker
+            .list_containers(Some(ListContainersOptions::<String> {
+                all,
+                ..Default::default()
+            }))
+            .await
+            .map_err(api_error)?;
+
+        Ok(summaries
+            .into_iter()
+            .map(|summary| Container {
+                id: summary.id.unwrap_or_default(),
+                image: summary.image.unwrap_or_default(),
+                status: summary.status.or(summary.state).unwrap_or_default(),
+                name: summary
+                    .names
+                    .and_then(|names| names.into_iter().next())
+                    .map(|name| name.trim_start_matches('/').to_string())
+                    .unwrap_or_default(),
+                runtime: ContainerRuntime::Docker,
+                created: summary
+                    .created
+                    .and_then(|secs| u64::try_from(secs).ok())
+                    .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+                ports: summary
+                    .ports
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|port| {
+                        Some(PortMapping {
+                            host_port: port.public_port?,
+                            container_port: port.private_port,
+                            protocol: port
+                                .typ
+                                .map(|t| t.to_string())
+                                .unwrap_or_else(|| "tcp".to_string()),
+                            host_ip: port.ip,
+                        })
+                    })
+                    .collect(),
+                resources: None,
+                security_context: None,
+                rootless: false,
+                pod: None,
+            })
+            .collect())
+    }
+
+    /// Inspect a container by ID or name
+    pub async fn inspect_container(&self, id: &str) -> Result<ContainerDetails> {
+        let response = self
+            .docker
+            .inspect_container(id, None::<InspectContainerOptions>)
+            .await
+            .map_err(|e| container_error(e, id))?;
+
+        let state = response.state.unwrap_or_default();
+        let config = response.config.unwrap_or_default();
+        Ok(ContainerDetails {
+            id: response.id.unwrap_or_default(),
+            name: response
+                .name
+                .unwrap_or_default()
+                .trim_start_matches('/')
+                .to_string(),
+            image: config.image.or(response.image).unwrap_or_default(),
+            status: state.status.map(|s| s.to_string()).unwrap_or_default(),
+            running: state.running.unwrap_or(false),
+            exit_code: state.exit_code,
+            started_at: state.started_at,
+            finished_at: state.finished_at,
+            restart_count: response.restart_count.unwrap_or(0),
+            command: config.cmd.unwrap_or_default(),
+            env: config.env.unwrap_or_default(),
+            labels: config.labels.unwrap_or_default(),
+            health: state
+                .health
+                .and_then(|h| h.status)
+                .map(|status| status.to_string()),
+        })
+    }
+
+    /// Last `tail` lines of a container's stdout and stderr
+    pub async fn container_logs(&self, id: &str, tail: u32, timestamps: bool) -> Result<String> {
+        let mut stream = self.docker.logs(
+            id,
+            Some(LogsOptions::<String> {
+                stdout: true,
+                stderr: true,
+                timestamps,
+                tail: tail.to_string(),
+                ..Default::default()
+            }),
+        );
+
+        let mut logs = String::new();
+        while let Some(output) = stream.next().await {
+            logs.push_str(&output.map_err(|e| container_error(e, id))?.to_string());
+        }
+        Ok(logs)
+    }
+
+    /// One-shot resource usage sample for a container
+    pub async fn container_stats(&self, id: &str) -> Result<ResourceUsage> {
+        let stats = self
+            .docker
+            .stats(
+                id,
+                Some(StatsOptions {
+                    stream: false,
+                    one_shot: false,
+                }),
+            )
+            .next()
+            .await
+            .ok_or_else(|| Error::service(format!("Docker returned no stats for {}", id)))?
+            .map_err(|e| container_error(e, id))?;
+        Ok(resource_usage(&stats))
+    }
+
+    /// Run a command inside a running container and collect its output
+    pub async fn exec(&self, id: &str, command: &[String]) -> Result<ExecOutput> {
+        if command.is_empty() {
+            return Err(Error::validation_with_field("Command is empty", "command"));
+        }
+        let exec = self
+            .docker
+            .create_exec(
+                id,
+                CreateExecOptions {
+                    cmd: Some(command.to_vec()),
+                    attach_stdout: Some(true),
+                    attach_stderr: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| container_error(e, id))?;
+
+        let mut result = ExecOutput {
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+        };
+        if let StartExecResults::Attached { mut output, .. } = self
+            .docker
+            .start_exec(&exec.id, None)
+            .await
+            .map_err(api_error)?
+        {
+            while let Some(chunk) = output.next().await {
+                match chunk.map_err(api_error)? {
+                    LogOutput::StdErr { message } => {
+                        result.stderr.push_str(&String::from_utf8_lossy(&message))
+                    }
+                    other => result.stdout.push_str(&other.to_string()),
+                }
+            }
+        }
+
+        result.exit_code = self
+            .docker
+            .inspect_exec(&exec.id)
+            .await
+            .map_err(api_error)?
+            .exit_code;
+        Ok(result)
+    }
+
+    /// Start a stopped container
+    pub async fn start_container(&self, id: &str) -> Result<()> {
+        self.docker
+            .start_container(id, None::<StartContainerOptions<String>>)
+            .await
+            .map_err(|e| container_error(e, id))
+    }
+
+    /// Stop a container, killing it after `timeout_secs`
+    pub async fn stop_container(&self, id: &str, timeout_secs: Option<i64>) -> Result<()> {
+        self.docker
+            .stop_container(id, timeout_secs.map(|t| StopContainerOptions { t }))
+            .await
+            .map_err(|e| container_error(e, id))
+    }
+}
+
+fn api_error(error: bollard::errors::Error) -> Error {
+    match error {
+        bollard::errors::Error::DockerResponseServerError {
+            status_code,
+            message,
+        } => Error::api_with_status(message, "docker", status_code),
+        other => Error::connection(format!("Docker API request failed: {}", other)),
+    }
+}
+
+fn container_error(error: bollard::errors::Error, id: &str) -> Error {
+    match error {
+        bollard::errors::Error::DockerResponseServerError {
+            status_code: 404,
+            message,
+        } => Error::not_found_with_resource(message, "container", id),
+        other => api_error(other),
+    }
+}
+
+/// CPU usage between two samples as a percentage of one core, as `docker stats` reports it
+fn cpu_percent(current: &CPUStats, previous: &CPUStats) -> f64 {
+    let cpu_delta = current
+        .cpu_usage
+        .total_usage
+        .saturating_sub(previous.cpu_usage.total_usage);
+    let system_delta = current
+        .system_cpu_usage
+        .unwrap_or(0)
+        .saturating_sub(previous.system_cpu_usage.unwrap_or(0));
+    if cpu_delta == 0 || system_delta == 0 {
+        return 0.0;
+    }
+    let cpus = current
+        .online_cpus
+        .filter(|&n| n > 0)
+        .or_else(|| {
+            current
+                .cpu_usage
+                .percpu_usage
+                .as_ref()
+                .map(|per_cpu| per_cpu.len() as u64)
+        })
+        .unwrap_or(1);
+    cpu_delta as f64 / system_delta as f64 * cpus as f64 * 100.0
+}
+
+fn resource_usage(stats: &Stats) -> ResourceUsage {
+    use bollard::container::MemoryStatsStats;
+
+    let memory = &stats.memory_stats;
+    // Like `docker stats`, page cache that can be reclaimed does not count as usage
+    let inactive = match memory.stats {
+        Some(MemoryStatsStats::V1(v1)) => v1.total_inactive_file,
+        Some(MemoryStatsStats::V2(v2)) => v2.inactive_file,
+        None => 0,
+    };
+
+    let (rx_bytes, tx_bytes) = stats
+        .networks
+        .iter()
+        .flat_map(|networks| networks.values())
+        .fold((0, 0), |(rx, tx), n| (rx + n.rx_bytes, tx + n.tx_bytes));
+
+    let (read_bytes, write_bytes) = stats
+        .blkio_stats
+        .io_service_bytes_recursive
+        .iter()
+        .flatten()
+        .fold((0, 0), |(read, write), entry| {
+            match entry.op.to_ascii_lowercase().as_str() {
+                "read" => (read + entry.value, write),
+                "write" => (read, write + entry.value),
+                _ => (read, write),
+            }
+        });
+
+    ResourceUsage {
+        cpu_percent: cpu_percent(&stats.cpu_stats, &stats.precpu_stats),
+        memory_usage: memory.usage.unwrap_or(0).saturating_sub(inactive),
+        memory_limit: memory.limit.unwrap_or(0),
+        network_io: NetworkIO { rx_bytes, tx_bytes },
+        block_io: BlockIO {
+            read_bytes,
+            write_bytes,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::container::{CPUUsage, ThrottlingData};
+
+    fn cpu(total_usage: u64, system: u64, online_cpus: u64) -> CPUStats {
+        CPUStats {
+            cpu_usage: CPUUsage {
+                percpu_usage: None,
+                usage_in_usermode: 0,
+                total_usage,
+                usage_in_kernelmode: 0,
+            },
+            system_cpu_usage: Some(system),
+            online_cpus: Some(online_cpus),
+            throttling_data: ThrottlingData {
+                periods: 0,
+                throttled_periods: 0,
+                throttled_time: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn parses_docker_hosts() {
+        assert_eq!(
+            DockerHost::parse("unix:///run/user/1000/docker.sock").unwrap(),
+            DockerHost::Unix(PathBuf::from("/run/user/1000/docker.sock"))
+        );
+        assert_eq!(
+            DockerHost::parse("tcp://10.0.0.5:2376").unwrap(),
+            DockerHost::Tcp("10.0.0.5:2376".to_string())
+        );
+        assert!(DockerHost::parse("ssh://user@host").is_err());
+    }
+
+    #[test]
+    fn cpu_percent_scales_by_online_cpus() {
+        let previous = cpu(1_000, 10_000, 4);
+        let current = cpu(1_500, 12_000, 4);
+        assert!((cpu_percent(&current, &previous) - 100.0).abs() < f64::EPSILON);
+        assert_eq!(cpu_percent(&previous, &previous), 0.0);
+    }
+}
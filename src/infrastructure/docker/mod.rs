@@ -7,6 +7,9 @@ use std::sync::Arc;
 use std::time::SystemTime;
 use tokio::process::Command;
 
+#[cfg(feature = "containers")]
+pub mod engine;
+
 /// Container runtime type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ContainerRuntime {
@@ -892,92 +895,129 @@ impl ContainerClient {
     /// Check container runtime health
     pub async fn health_check(&self) -> Result<bool> {
         // Try to run a simple command to check if runtime is healthy
-        match self.run_runtime_command(&self.default_runtime, &["version"]).await {
+        match self
+            .run_runtime_command(&self.default_runtime, &["version"])
+            .await
+        {
             Ok(_) => Ok(true),
             Err(_) => Ok(false),
         }
     }
 
     /// Deploy a container resource
-    pub async fn deploy_resource(&self, resource: crate::infrastructure::ResourceSpec) -> Result<crate::infrastructure::ResourceResult> {
+    pub async fn deploy_resource(
+        &self,
+        resource: crate::infrastructure::ResourceSpec,
+    ) -> Result<crate::infrastructure::ResourceResult> {
         use crate::infrastructure::ResourceResult;
-        
+
         // Create container from resource specification
         let params = ContainerCreateParams {
-            image: resource.spec.get("image")
+            image: resource
+                .spec
+                .get("image")
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| Error::validation("Missing 'image' in resource spec"))?
                 .to_string(),
             name: Some(resource.name.clone()),
-            env: resource.spec.get("environment")
+            env: resource
+                .spec
+                .get("environment")
                 .and_then(|v| v.as_object())
-                .map(|obj| obj.iter()
-                    .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
-                    .collect())
+                .map(|obj| {
+                    obj.iter()
+                        .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
+                        .collect()
+                })
                 .unwrap_or_default(),
-            ports: resource.spec.get("ports")
+            ports: resource
+                .spec
+                .get("ports")
                 .and_then(|v| v.as_array())
-                .map(|arr| arr.iter()
-                    .filter_map(|v| v.as_str())
-                    .filter_map(|s| {
-                        // Parse port mapping format "host:container"
-                        let parts: Vec<&str> = s.split(':').collect();
-                        if parts.len() == 2 {
-                            if let (Ok(host), Ok(container)) = (parts[0].parse::<u16>(), parts[1].parse::<u16>()) {
-                                Some(PortMapping {
-                                    host_port: host,
-                                    container_port: container,
-                                    protocol: "tcp".to_string(),
-                                    host_ip: None,
-                                })
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str())
+                        .filter_map(|s| {
+                            // Parse port mapping format "host:container"
+                            let parts: Vec<&str> = s.split(':').collect();
+                            if parts.len() == 2 {
+                                if let (Ok(host), Ok(container)) =
+                                    (parts[0].parse::<u16>(), parts[1].parse::<u16>())
+                                {
+                                    Some(PortMapping {
+                                        host_port: host,
+                                        container_port: container,
+                                        protocol: "tcp".to_string(),
+                                        host_ip: None,
+                                    })
+                                } else {
+                                    None
+                                }
                             } else {
                                 None
                             }
-                        } else {
-                            None
-                        }
-                    })
-                    .collect())
+                        })
+                        .collect()
+                })
                 .unwrap_or_default(),
-            volumes: resource.spec.get("volumes")
+            volumes: resource
+                .spec
+                .get("volumes")
                 .and_then(|v| v.as_array())
-                .map(|arr| arr.iter()
-                    .filter_map(|v| v.as_str())
-                    .filter_map(|s| {
-                        // Parse volume format "source:target"
-                        let parts: Vec<&str> = s.split(':').collect();
-                        if parts.len() == 2 {
-                            Some(VolumeMount {
-                                source: parts[0].to_string(),
-                                target: parts[1].to_string(),
-                                mount_type: "bind".to_string(),
-                                read_only: false,
-                            })
-                        } else {
-                            None
-                        }
-                    })
-                    .collect())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str())
+                        .filter_map(|s| {
+                            // Parse volume format "source:target"
+                            let parts: Vec<&str> = s.split(':').collect();
+                            if parts.len() == 2 {
+                                Some(VolumeMount {
+                                    source: parts[0].to_string(),
+                                    target: parts[1].to_string(),
+                                    mount_type: "bind".to_string(),
+                                    read_only: false,
+                                })
+                            } else {
+                                None
+                            }
+                        })
+                        .collect()
+                })
                 .unwrap_or_default(),
-            network: resource.spec.get("network")
+            network: resource
+                .spec
+                .get("network")
                 .and_then(|v| v.as_object())
                 .map(|obj| NetworkConfig {
-                    mode: obj.get("mode").and_then(|v| v.as_str()).unwrap_or("bridge").to_string(),
-                    dns: obj.get("dns")
+                    mode: obj
+                        .get("mode")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("bridge")
+                        .to_string(),
+                    dns: obj
+                        .get("dns")
                         .and_then(|v| v.as_array())
-                        .map(|arr| arr.iter()
-                            .filter_map(|v| v.as_str())
-                            .map(|s| s.to_string())
-                            .collect())
+                        .map(|arr| {
+                            arr.iter()
+                                .filter_map(|v| v.as_str())
+                                .map(|s| s.to_string())
+                                .collect()
+                        })
                         .unwrap_or_default(),
-                    dns_search: obj.get("dns_search")
+                    dns_search: obj
+                        .get("dns_search")
                         .and_then(|v| v.as_array())
-                        .map(|arr| arr.iter()
-                            .filter_map(|v| v.as_str())
-                            .map(|s| s.to_string())
-                            .collect())
+                        .map(|arr| {
+                            arr.iter()
+                                .filter_map(|v| v.as_str())
+                                .map(|s| s.to_string())
+                                .collect()
+                        })
                         .unwrap_or_default(),
-                    hostname: obj.get("hostname").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                    hostname: obj
+                        .get("hostname")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string()),
                 }),
             restart_policy: match resource.spec.get("restart_policy").and_then(|v| v.as_str()) {
                 Some("always") => RestartPolicy::Always,
@@ -995,13 +1035,16 @@ impl ContainerClient {
             rootless: false,
             pod: None,
         };
-        
+
         match self.create_container(params).await {
             Ok(container_id) => Ok(ResourceResult {
                 name: resource.name.clone(),
                 resource_type: resource.resource_type.clone(),
                 status: "success".to_string(),
-                message: Some(format!("Container {} created with ID: {}", resource.name, container_id)),
+                message: Some(format!(
+                    "Container {} created with ID: {}",
+                    resource.name, container_id
+                )),
             }),
             Err(e) => Ok(ResourceResult {
                 name: resource.name.clone(),
@@ -1013,9 +1056,12 @@ impl ContainerClient {
     }
 
     /// Scale container resources (not directly supported, returns error)
-    pub async fn scale_resource(&self, target: crate::infrastructure::ScalingTarget) -> Result<crate::infrastructure::ScalingTargetResult> {
+    pub async fn scale_resource(
+        &self,
+        target: crate::infrastructure::ScalingTarget,
+    ) -> Result<crate::infrastructure::ScalingTargetResult> {
         use crate::infrastructure::ScalingTargetResult;
-        
+
         // Docker doesn't support direct scaling like Kubernetes
         // This would need to be implemented with Docker Swarm or similar
         Ok(ScalingTargetResult {
@@ -1029,13 +1075,18 @@ impl ContainerClient {
     /// Get container runtime metrics
     pub async fn get_metrics(&self) -> Result<serde_json::Value> {
         // Get container stats
-        let output = self.run_runtime_command(&self.default_runtime, &["stats", "--no-stream", "--format", "json"]).await?;
-        
+        let output = self
+            .run_runtime_command(
+                &self.default_runtime,
+                &["stats", "--no-stream", "--format", "json"],
+            )
+            .await?;
+
         let containers: Vec<serde_json::Value> = output
             .lines()
             .filter_map(|line| serde_json::from_str(line).ok())
             .collect();
-        
+
         Ok(serde_json::json!({
             "containers": containers,
             "runtime": self.default_runtime.to_string(),
@@ -0,0 +1,907 @@
+use super::KubernetesClient;
+use crate::error::{Error, Result};
+use crate::lifecycle::LifecycleManager;
+use crate::tools::{call_result, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Workload kinds scanned for pod security violations
+const WORKLOAD_KINDS: &str = "pods,deployments,statefulsets,daemonsets,jobs,cronjobs";
+
+/// Capabilities containers may add without violating CIS 5.2.9
+const ALLOWED_CAPABILITIES: &[&str] = &["NET_BIND_SERVICE"];
+
+/// Check severity
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Medium,
+    High,
+    Critical,
+}
+
+/// Offending container, if any, and a description
+type Finding = (Option<String>, String);
+
+/// A CIS Kubernetes Benchmark pod security control
+struct CisCheck {
+    /// Benchmark id, e.g. `5.2.2`
+    id: &'static str,
+    /// Policy name suffix
+    slug: &'static str,
+    title: &'static str,
+    severity: Severity,
+    /// Violations in a pod spec as (container, message)
+    evaluate: fn(&Value) -> Vec<Finding>,
+    /// Kyverno `validate` block
+    kyverno: fn() -> Value,
+    /// Gatekeeper Rego body; `input_containers` is provided
+    rego: &'static str,
+}
+
+fn containers(spec: &Value) -> impl Iterator<Item = &Value> {
+    ["containers", "initContainers", "ephemeralContainers"]
+        .into_iter()
+        .flat_map(move |key| spec[key].as_array().into_iter().flatten())
+}
+
+fn container_name(container: &Value) -> Option<String> {
+    container["name"].as_str().map(str::to_string)
+}
+
+fn host_namespace(field: &'static str) -> impl Fn(&Value) -> Vec<Finding> {
+    move |spec| {
+        if spec[field].as_bool() == Some(true) {
+            vec![(None, format!("{} is enabled", field))]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+fn host_namespace_kyverno(field: &str) -> Value {
+    json!({
+        "message": format!("Sharing the host namespace ({}) is not allowed", field),
+        "pattern": { "spec": { format!("=({})", field): "false" } }
+    })
+}
+
+fn strings(value: &Value) -> Vec<&str> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+        .collect()
+}
+
+const CHECKS: &[CisCheck] = &[
+    CisCheck {
+        id: "5.2.2",
+        slug: "disallow-privileged-containers",
+        title: "Minimize the admission of privileged containers",
+        severity: Severity::Critical,
+        evaluate: |spec| {
+            containers(spec)
+                .filter(|c| c["securityContext"]["privileged"].as_bool() == Some(true))
+                .map(|c| (container_name(c), "Container runs privileged".to_string()))
+                .collect()
+        },
+        kyverno: || {
+            json!({
+                "message": "Privileged containers are not allowed",
+                "pattern": { "spec": {
+                    "=(ephemeralContainers)": [{ "=(securityContext)": { "=(privileged)": "false" } }],
+                    "=(initContainers)": [{ "=(securityContext)": { "=(privileged)": "false" } }],
+                    "containers": [{ "=(securityContext)": { "=(privileged)": "false" } }]
+                }}
+            })
+        },
+        rego: r#"violation[{"msg": msg}] {
+  c := input_containers[_]
+  c.securityContext.privileged
+  msg := sprintf("Privileged container is not allowed: %v", [c.name])
+}"#,
+    },
+    CisCheck {
+        id: "5.2.3",
+        slug: "disallow-host-pid",
+        title:
+            "Minimize the admission of containers wishing to share the host process ID namespace",
+        severity: Severity::High,
+        evaluate: |spec| host_namespace("hostPID")(spec),
+        kyverno: || host_namespace_kyverno("hostPID"),
+        rego: r#"violation[{"msg": msg}] {
+  input.review.object.spec.hostPID
+  msg := "Sharing the host PID namespace is not allowed"
+}"#,
+    },
+    CisCheck {
+        id: "5.2.4",
+        slug: "disallow-host-ipc",
+        title: "Minimize the admission of containers wishing to share the host IPC namespace",
+        severity: Severity::High,
+        evaluate: |spec| host_namespace("hostIPC")(spec),
+        kyverno: || host_namespace_kyverno("hostIPC"),
+        rego: r#"violation[{"msg": msg}] {
+  input.review.object.spec.hostIPC
+  msg := "Sharing the host IPC namespace is not allowed"
+}"#,
+    },
+    CisCheck {
+        id: "5.2.5",
+        slug: "disallow-host-network",
+        title: "Minimize the admission of containers wishing to share the host network namespace",
+        severity: Severity::High,
+        evaluate: |spec| host_namespace("hostNetwork")(spec),
+        kyverno: || host_namespace_kyverno("hostNetwork"),
+        rego: r#"violation[{"msg": msg}] {
+  input.review.object.spec.hostNetwork
+  msg := "Sharing the host network namespace is not allowed"
+}"#,
+    },
+    CisCheck {
+        id: "5.2.6",
+        slug: "disallow-privilege-escalation",
+        title: "Minimize the admission of containers with allowPrivilegeEscalation",
+        severity: Severity::High,
+        evaluate: |spec| {
+            containers(spec)
+                .filter(|c| {
+                    c["securityContext"]["allowPrivilegeEscalation"].as_bool() != Some(false)
+                })
+                .map(|c| {
+                    (
+                        container_name(c),
+                        "allowPrivilegeEscalation is not set to false".to_string(),
+                    )
+                })
+                .collect()
+        },
+        kyverno: || {
+            json!({
+                "message": "allowPrivilegeEscalation must be set to false",
+                "pattern": { "spec": {
+                    "=(ephemeralContainers)": [{ "securityContext": { "allowPrivilegeEscalation": "false" } }],
+                    "=(initContainers)": [{ "securityContext": { "allowPrivilegeEscalation": "false" } }],
+                    "containers": [{ "securityContext": { "allowPrivilegeEscalation": "false" } }]
+                }}
+            })
+        },
+        rego: r#"violation[{"msg": msg}] {
+  c := input_containers[_]
+  not c.securityContext.allowPrivilegeEscalation == false
+  msg := sprintf("allowPrivilegeEscalation must be false: %v", [c.name])
+}"#,
+    },
+    CisCheck {
+        id: "5.2.7",
+        slug: "require-run-as-non-root",
+        title: "Minimize the admission of root containers",
+        severity: Severity::Medium,
+        evaluate: |spec| {
+            let pod = &spec["securityContext"];
+            containers(spec)
+                .filter(|c| {
+                    let context = &c["securityContext"];
+                    let non_root = context["runAsNonRoot"]
+                        .as_bool()
+                        .or_else(|| pod["runAsNonRoot"].as_bool())
+                        == Some(true);
+                    let uid = context["runAsUser"]
+                        .as_u64()
+                        .or_else(|| pod["runAsUser"].as_u64());
+                    !(non_root || uid.is_some_and(|uid| uid > 0))
+                })
+                .map(|c| (container_name(c), "Container may run as root".to_string()))
+                .collect()
+        },
+        kyverno: || {
+            json!({
+                "message": "Containers must set runAsNonRoot to true, at the pod or container level",
+                "anyPattern": [
+                    { "spec": {
+                        "securityContext": { "runAsNonRoot": "true" },
+                        "=(ephemeralContainers)": [{ "=(securityContext)": { "=(runAsNonRoot)": "true" } }],
+                        "=(initContainers)": [{ "=(securityContext)": { "=(runAsNonRoot)": "true" } }],
+                        "containers": [{ "=(securityContext)": { "=(runAsNonRoot)": "true" } }]
+                    }},
+                    { "spec": {
+                        "=(ephemeralContainers)": [{ "securityContext": { "runAsNonRoot": "true" } }],
+                        "=(initContainers)": [{ "securityContext": { "runAsNonRoot": "true" } }],
+                        "containers": [{ "securityContext": { "runAsNonRoot": "true" } }]
+                    }}
+                ]
+            })
+        },
+        rego: r#"violation[{"msg": msg}] {
+  c := input_containers[_]
+  not run_as_non_root(c)
+  msg := sprintf("Container must run as non-root: %v", [c.name])
+}
+
+run_as_non_root(c) {
+  c.securityContext.runAsNonRoot
+}
+
+run_as_non_root(c) {
+  not c.securityContext.runAsNonRoot == false
+  input.review.object.spec.securityContext.runAsNonRoot
+}
+
+run_as_non_root(c) {
+  c.securityContext.runAsUser > 0
+}"#,
+    },
+    CisCheck {
+        id: "5.2.8",
+        slug: "require-drop-net-raw",
+        title: "Minimize the admission of containers with the NET_RAW capability",
+        severity: Severity::Medium,
+        evaluate: |spec| {
+            containers(spec)
+                .filter(|c| {
+                    let drop = strings(&c["securityContext"]["capabilities"]["drop"]);
+                    !drop.contains(&"ALL") && !drop.contains(&"NET_RAW")
+                })
+                .map(|c| (container_name(c), "NET_RAW is not dropped".to_string()))
+                .collect()
+        },
+        kyverno: || {
+            json!({
+                "message": "Containers must drop ALL or NET_RAW capabilities",
+                "foreach": [{
+                    "list": "request.object.spec.[ephemeralContainers, initContainers, containers][]",
+                    "deny": { "conditions": { "all": [{
+                        "key": ["ALL", "NET_RAW"],
+                        "operator": "AllNotIn",
+                        "value": "{{ element.securityContext.capabilities.drop[] || `[]` }}"
+                    }]}}
+                }]
+            })
+        },
+        rego: r#"violation[{"msg": msg}] {
+  c := input_containers[_]
+  drops := {d | d := c.securityContext.capabilities.drop[_]}
+  count(drops & {"ALL", "NET_RAW"}) == 0
+  msg := sprintf("Container must drop ALL or NET_RAW: %v", [c.name])
+}"#,
+    },
+    CisCheck {
+        id: "5.2.9",
+        slug: "restrict-added-capabilities",
+        title: "Minimize the admission of containers with added capabilities",
+        severity: Severity::High,
+        evaluate: |spec| {
+            containers(spec)
+                .filter_map(|c| {
+                    let added: Vec<&str> = strings(&c["securityContext"]["capabilities"]["add"])
+                        .into_iter()
+                        .filter(|cap| !ALLOWED_CAPABILITIES.contains(cap))
+                        .collect();
+                    (!added.is_empty()).then(|| {
+                        (
+                            container_name(c),
+                            format!("Adds capabilities {}", added.join(", ")),
+                        )
+                    })
+                })
+                .collect()
+        },
+        kyverno: || {
+            json!({
+                "message": "Only NET_BIND_SERVICE may be added to container capabilities",
+                "deny": { "conditions": { "all": [{
+                    "key": "{{ request.object.spec.[ephemeralContainers, initContainers, containers][].securityContext.capabilities.add[] }}",
+                    "operator": "AnyNotIn",
+                    "value": ALLOWED_CAPABILITIES
+                }]}}
+            })
+        },
+        rego: r#"violation[{"msg": msg}] {
+  c := input_containers[_]
+  cap := c.securityContext.capabilities.add[_]
+  cap != "NET_BIND_SERVICE"
+  msg := sprintf("Capability %v is not allowed: %v", [cap, c.name])
+}"#,
+    },
+    CisCheck {
+        id: "5.2.12",
+        slug: "disallow-host-path",
+        title: "Minimize the admission of HostPath volumes",
+        severity: Severity::High,
+        evaluate: |spec| {
+            spec["volumes"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|v| !v["hostPath"].is_null())
+                .map(|v| {
+                    (
+                        None,
+                        format!(
+                            "Volume {} mounts host path {}",
+                            v["name"].as_str().unwrap_or("?"),
+                            v["hostPath"]["path"].as_str().unwrap_or("?")
+                        ),
+                    )
+                })
+                .collect()
+        },
+        kyverno: || {
+            json!({
+                "message": "HostPath volumes are not allowed",
+                "pattern": { "spec": { "=(volumes)": [{ "X(hostPath)": "null" }] } }
+            })
+        },
+        rego: r#"violation[{"msg": msg}] {
+  v := input.review.object.spec.volumes[_]
+  v.hostPath
+  msg := sprintf("HostPath volume is not allowed: %v", [v.name])
+}"#,
+    },
+    CisCheck {
+        id: "5.2.13",
+        slug: "disallow-host-ports",
+        title: "Minimize the admission of containers which use HostPorts",
+        severity: Severity::Medium,
+        evaluate: |spec| {
+            containers(spec)
+                .flat_map(|c| {
+                    c["ports"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|p| p["hostPort"].as_u64().filter(|port| *port > 0))
+                        .map(move |port| (container_name(c), format!("Binds host port {}", port)))
+                })
+                .collect()
+        },
+        kyverno: || {
+            json!({
+                "message": "Host ports are not allowed",
+                "pattern": { "spec": {
+                    "=(ephemeralContainers)": [{ "=(ports)": [{ "=(hostPort)": 0 }] }],
+                    "=(initContainers)": [{ "=(ports)": [{ "=(hostPort)": 0 }] }],
+                    "containers": [{ "=(ports)": [{ "=(hostPort)": 0 }] }]
+                }}
+            })
+        },
+        rego: r#"violation[{"msg": msg}] {
+  c := input_containers[_]
+  p := c.ports[_]
+  p.hostPort > 0
+  msg := sprintf("Host port %v is not allowed: %v", [p.hostPort, c.name])
+}"#,
+    },
+];
+
+const REGO_CONTAINERS: &str = r#"input_containers[c] {
+  c := input.review.object.spec.containers[_]
+}
+
+input_containers[c] {
+  c := input.review.object.spec.initContainers[_]
+}
+
+input_containers[c] {
+  c := input.review.object.spec.ephemeralContainers[_]
+}"#;
+
+fn check(id: &str) -> Result<&'static CisCheck> {
+    CHECKS
+        .iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| Error::not_found_with_resource("Unknown CIS check", "cis_check", id))
+}
+
+/// Workload a violation was found in
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ResourceRef {
+    pub kind: String,
+    pub namespace: String,
+    pub name: String,
+}
+
+/// A CIS pod security violation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Violation {
+    pub check_id: String,
+    pub title: String,
+    pub severity: Severity,
+    pub resource: ResourceRef,
+    pub container: Option<String>,
+    pub message: String,
+}
+
+/// Pod spec of a workload, following controller templates
+fn pod_spec(object: &Value) -> Option<&Value> {
+    match object["kind"].as_str()? {
+        "Pod" => Some(&object["spec"]),
+        "CronJob" => Some(&object["spec"]["jobTemplate"]["spec"]["template"]["spec"]),
+        _ => object["spec"]["template"]
+            .get("spec")
+            .filter(|spec| spec.is_object()),
+    }
+}
+
+/// Evaluate CIS checks against a `kubectl get -o json` list
+///
+/// Objects owned by a controller are skipped; the controller's template is
+/// evaluated instead, as admission autogen rules would.
+pub fn scan(list: &Value, check_ids: &[&str], exclude_namespaces: &[String]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let items = list["items"].as_array().cloned().unwrap_or_default();
+    for object in &items {
+        let meta = &object["metadata"];
+        let namespace = meta["namespace"].as_str().unwrap_or("default");
+        if exclude_namespaces.iter().any(|ns| ns == namespace)
+            || meta["ownerReferences"].as_array().is_some_and(|owners| {
+                owners
+                    .iter()
+                    .any(|o| o["controller"].as_bool() == Some(true))
+            })
+        {
+            continue;
+        }
+        let Some(spec) = pod_spec(object) else {
+            continue;
+        };
+        let resource = ResourceRef {
+            kind: object["kind"].as_str().unwrap_or_default().to_string(),
+            namespace: namespace.to_string(),
+            name: meta["name"].as_str().unwrap_or_default().to_string(),
+        };
+        for check in CHECKS {
+            if !check_ids.is_empty() && !check_ids.contains(&check.id) {
+                continue;
+            }
+            for (container, message) in (check.evaluate)(spec) {
+                violations.push(Violation {
+                    check_id: check.id.to_string(),
+                    title: check.title.to_string(),
+                    severity: check.severity,
+                    resource: resource.clone(),
+                    container,
+                    message,
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// Admission controller to generate policies for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyEngine {
+    Kyverno,
+    Gatekeeper,
+}
+
+/// Whether generated policies block or only report
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EnforcementMode {
+    /// Record violations without rejecting requests
+    #[default]
+    Audit,
+    /// Reject violating requests
+    Enforce,
+}
+
+/// A generated admission policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedPolicy {
+    pub check_id: String,
+    pub name: String,
+    /// One or more YAML documents
+    pub yaml: String,
+}
+
+fn to_yaml(documents: &[Value]) -> Result<String> {
+    documents
+        .iter()
+        .map(|doc| {
+            serde_yaml::to_string(doc)
+                .map_err(|e| Error::internal(format!("Failed to render policy YAML: {}", e)))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(|docs| docs.join("---\n"))
+}
+
+fn gatekeeper_kind(check: &CisCheck) -> String {
+    let words: String = check
+        .slug
+        .split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    format!("K8sCis{}{}", check.id.replace('.', ""), words)
+}
+
+/// Render the admission policy preventing a CIS violation
+pub fn generate_policy(
+    check_id: &str,
+    engine: PolicyEngine,
+    mode: EnforcementMode,
+    exclude_namespaces: &[String],
+) -> Result<GeneratedPolicy> {
+    let check = check(check_id)?;
+    let annotations = json!({
+        "policies.kyverno.io/title": check.title,
+        "policies.kyverno.io/category": "CIS Kubernetes Benchmark",
+        "policies.kyverno.io/severity": check.severity,
+        "cis.benchmark/control": check.id,
+    });
+
+    let (name, documents) = match engine {
+        PolicyEngine::Kyverno => {
+            let name = format!("cis-{}-{}", check.id.replace('.', "-"), check.slug);
+            let mut rule = json!({
+                "name": check.slug,
+                "match": { "any": [{ "resources": { "kinds": ["Pod"] } }] },
+                "validate": (check.kyverno)(),
+            });
+            if !exclude_namespaces.is_empty() {
+                rule["exclude"] =
+                    json!({ "any": [{ "resources": { "namespaces": exclude_namespaces } }] });
+            }
+            let policy = json!({
+                "apiVersion": "kyverno.io/v1",
+                "kind": "ClusterPolicy",
+                "metadata": { "name": name, "annotations": annotations },
+                "spec": {
+                    "validationFailureAction": match mode {
+                        EnforcementMode::Audit => "Audit",
+                        EnforcementMode::Enforce => "Enforce",
+                    },
+                    "background": true,
+                    "rules": [rule],
+                }
+            });
+            (name, vec![policy])
+        }
+        PolicyEngine::Gatekeeper => {
+            let kind = gatekeeper_kind(check);
+            let package = kind.to_ascii_lowercase();
+            let template = json!({
+                "apiVersion": "templates.gatekeeper.sh/v1",
+                "kind": "ConstraintTemplate",
+                "metadata": { "name": package, "annotations": annotations },
+                "spec": {
+                    "crd": { "spec": { "names": { "kind": kind } } },
+                    "targets": [{
+                        "target": "admission.k8s.gatekeeper.sh",
+                        "rego": format!("package {}\n\n{}\n\n{}\n", package, check.rego, REGO_CONTAINERS),
+                    }]
+                }
+            });
+            let mut constraint_match = json!({
+                "kinds": [{ "apiGroups": [""], "kinds": ["Pod"] }]
+            });
+            if !exclude_namespaces.is_empty() {
+                constraint_match["excludedNamespaces"] = json!(exclude_namespaces);
+            }
+            let name = format!("cis-{}-{}", check.id.replace('.', "-"), check.slug);
+            let constraint = json!({
+                "apiVersion": "constraints.gatekeeper.sh/v1beta1",
+                "kind": kind,
+                "metadata": { "name": name },
+                "spec": {
+                    "enforcementAction": match mode {
+                        EnforcementMode::Audit => "dryrun",
+                        EnforcementMode::Enforce => "deny",
+                    },
+                    "match": constraint_match,
+                }
+            });
+            (name, vec![template, constraint])
+        }
+    };
+
+    Ok(GeneratedPolicy {
+        check_id: check.id.to_string(),
+        name,
+        yaml: to_yaml(&documents)?,
+    })
+}
+
+/// Existing workloads a policy would reject
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunResult {
+    pub policy: String,
+    pub would_block: Vec<Violation>,
+}
+
+/// CIS pod security scanning and admission policy generation
+pub struct AdmissionPolicyGenerator {
+    lifecycle: Arc<LifecycleManager>,
+    kubeconfig: Option<String>,
+    context: Option<String>,
+}
+
+impl AdmissionPolicyGenerator {
+    /// Create a generator using the given kubeconfig and context
+    pub fn new(
+        lifecycle: Arc<LifecycleManager>,
+        kubeconfig: Option<String>,
+        context: Option<String>,
+    ) -> Self {
+        Self {
+            lifecycle,
+            kubeconfig,
+            context,
+        }
+    }
+
+    /// Fetch workloads from the cluster
+    async fn workloads(&self, namespace: Option<&str>) -> Result<Value> {
+        let client = KubernetesClient::new(
+            &self.lifecycle,
+            self.kubeconfig.as_deref(),
+            self.context.as_deref(),
+        )?;
+        let scope = match namespace {
+            Some(namespace) => {
+                client.validate_k8s_resource_name(namespace)?;
+                format!("-n {}", namespace)
+            }
+            None => "-A".to_string(),
+        };
+        let result = client
+            .run_kubectl_command(&format!("get {} {} -o json", WORKLOAD_KINDS, scope), None)
+            .await?;
+        if !result.success {
+            return Err(Error::service(format!(
+                "kubectl get workloads failed: {}",
+                result.error.unwrap_or_default()
+            )));
+        }
+        serde_json::from_str(&result.output)
+            .map_err(|e| Error::parsing(format!("Failed to parse workloads: {}", e)))
+    }
+
+    /// Scan cluster workloads for CIS pod security violations
+    pub async fn scan_cluster(
+        &self,
+        namespace: Option<&str>,
+        exclude_namespaces: &[String],
+    ) -> Result<Vec<Violation>> {
+        let list = self.workloads(namespace).await?;
+        Ok(scan(&list, &[], exclude_namespaces))
+    }
+
+    /// Evaluate policies for the given checks against existing workloads
+    pub async fn dry_run(
+        &self,
+        policies: &[GeneratedPolicy],
+        exclude_namespaces: &[String],
+    ) -> Result<Vec<DryRunResult>> {
+        let list = self.workloads(None).await?;
+        Ok(policies
+            .iter()
+            .map(|policy| DryRunResult {
+                policy: policy.name.clone(),
+                would_block: scan(&list, &[policy.check_id.as_str()], exclude_namespaces),
+            })
+            .collect())
+    }
+
+    /// Get tool definitions for admission policies
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let checks: Vec<&str> = CHECKS.iter().map(|c| c.id).collect();
+        vec![
+            ToolDefinition::from_json_schema(
+                "k8s_cis_scan",
+                "Scan cluster workloads for CIS Kubernetes Benchmark pod security violations (section 5.2)",
+                "kubernetes",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "namespace": {"type": "string", "description": "Namespace to scan (default: all)"},
+                        "exclude_namespaces": {"type": "array", "items": {"type": "string"}, "default": ["kube-system"]}
+                    }
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "generate_admission_policies",
+                "Generate Kyverno or Gatekeeper policies that prevent detected CIS violations, with a dry run against existing workloads",
+                "kubernetes",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "engine": {"type": "string", "enum": ["kyverno", "gatekeeper"], "default": "kyverno"},
+                        "check_ids": {"type": "array", "items": {"type": "string", "enum": checks}},
+                        "violations": {
+                            "type": "array",
+                            "items": {"type": "object"},
+                            "description": "Violations from k8s_cis_scan; their check ids select the policies"
+                        },
+                        "mode": {"type": "string", "enum": ["audit", "enforce"], "default": "audit"},
+                        "exclude_namespaces": {"type": "array", "items": {"type": "string"}, "default": ["kube-system"]},
+                        "dry_run": {"type": "boolean", "default": true, "description": "Report existing workloads each policy would reject"}
+                    }
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute an admission policy tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let exclude_namespaces: Vec<String> = match parameters.get("exclude_namespaces") {
+            Some(list) => serde_json::from_value(list.clone()).map_err(|_| {
+                Error::validation_with_field(
+                    "exclude_namespaces must be a list of strings",
+                    "exclude_namespaces",
+                )
+            })?,
+            None => vec!["kube-system".to_string()],
+        };
+
+        match name {
+            "k8s_cis_scan" => {
+                let namespace = parameters.get("namespace").and_then(|n| n.as_str());
+                let violations = self.scan_cluster(namespace, &exclude_namespaces).await?;
+                let mut by_check: BTreeMap<&str, usize> = BTreeMap::new();
+                for violation in &violations {
+                    *by_check.entry(&violation.check_id).or_insert(0) += 1;
+                }
+                let text = if violations.is_empty() {
+                    "No CIS pod security violations found".to_string()
+                } else {
+                    let mut text = format!("{} violations:", violations.len());
+                    for (id, count) in &by_check {
+                        text.push_str(&format!("\n{} {}: {}", id, check(id)?.title, count));
+                    }
+                    text
+                };
+                Ok(call_result(text, json!({ "violations": violations })))
+            }
+            "generate_admission_policies" => {
+                let engine: PolicyEngine = match parameters.get("engine") {
+                    Some(engine) => serde_json::from_value(engine.clone()).map_err(|_| {
+                        Error::validation_with_field(
+                            "engine must be kyverno or gatekeeper",
+                            "engine",
+                        )
+                    })?,
+                    None => PolicyEngine::Kyverno,
+                };
+                let mode: EnforcementMode = match parameters.get("mode") {
+                    Some(mode) => serde_json::from_value(mode.clone()).map_err(|_| {
+                        Error::validation_with_field("mode must be audit or enforce", "mode")
+                    })?,
+                    None => EnforcementMode::Audit,
+                };
+
+                let mut check_ids: Vec<String> = parameters
+                    .get("check_ids")
+                    .and_then(|c| c.as_array())
+                    .into_iter()
+                    .flatten()
+                    .chain(
+                        parameters
+                            .get("violations")
+                            .and_then(|v| v.as_array())
+                            .into_iter()
+                            .flatten()
+                            .map(|v| &v["check_id"]),
+                    )
+                    .filter_map(|id| id.as_str().map(str::to_string))
+                    .collect();
+                check_ids.sort();
+                check_ids.dedup();
+                if check_ids.is_empty() {
+                    return Err(Error::validation_with_field(
+                        "Provide check_ids or violations from k8s_cis_scan",
+                        "check_ids",
+                    ));
+                }
+
+                let policies = check_ids
+                    .iter()
+                    .map(|id| generate_policy(id, engine, mode, &exclude_namespaces))
+                    .collect::<Result<Vec<_>>>()?;
+                let dry_run = if parameters
+                    .get("dry_run")
+                    .and_then(|d| d.as_bool())
+                    .unwrap_or(true)
+                {
+                    Some(self.dry_run(&policies, &exclude_namespaces).await?)
+                } else {
+                    None
+                };
+
+                let mut text = format!("Generated {} {:?} policies", policies.len(), engine);
+                if let Some(results) = &dry_run {
+                    for result in results {
+                        text.push_str(&format!(
+                            "\n{}: would reject {} existing workloads",
+                            result.policy,
+                            result.would_block.len()
+                        ));
+                    }
+                }
+                let manifest = policies
+                    .iter()
+                    .map(|p| p.yaml.as_str())
+                    .collect::<Vec<_>>()
+                    .join("---\n");
+                text.push_str(&format!("\n\n{}", manifest));
+                Ok(call_result(
+                    text,
+                    json!({ "policies": policies, "dry_run": dry_run }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "admission_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_templates_and_renders_policies() {
+        let workloads = json!({"items": [
+            {"kind": "Deployment", "metadata": {"name": "web", "namespace": "app"},
+             "spec": {"template": {"spec": {
+                "hostNetwork": true,
+                "securityContext": {"runAsNonRoot": true},
+                "containers": [{"name": "nginx", "securityContext": {
+                    "privileged": true, "allowPrivilegeEscalation": false,
+                    "capabilities": {"drop": ["ALL"], "add": ["NET_BIND_SERVICE", "SYS_ADMIN"]}}}]
+             }}}},
+            {"kind": "Pod", "metadata": {"name": "web-1", "namespace": "app",
+             "ownerReferences": [{"kind": "ReplicaSet", "controller": true}]},
+             "spec": {"containers": [{"name": "nginx", "securityContext": {"privileged": true}}]}},
+            {"kind": "Pod", "metadata": {"name": "proxy", "namespace": "kube-system"},
+             "spec": {"hostNetwork": true, "containers": [{"name": "p"}]}}
+        ]});
+        let violations = scan(&workloads, &[], &["kube-system".to_string()]);
+        let ids: Vec<&str> = violations.iter().map(|v| v.check_id.as_str()).collect();
+        assert_eq!(ids, vec!["5.2.2", "5.2.5", "5.2.9"]);
+        assert_eq!(violations[2].message, "Adds capabilities SYS_ADMIN");
+
+        let kyverno = generate_policy(
+            "5.2.2",
+            PolicyEngine::Kyverno,
+            EnforcementMode::Enforce,
+            &["kube-system".to_string()],
+        )
+        .unwrap();
+        let policy: Value = serde_yaml::from_str(&kyverno.yaml).unwrap();
+        assert_eq!(policy["spec"]["validationFailureAction"], "Enforce");
+        assert_eq!(
+            policy["spec"]["rules"][0]["exclude"]["any"][0]["resources"]["namespaces"][0],
+            "kube-system"
+        );
+
+        let gatekeeper = generate_policy(
+            "5.2.5",
+            PolicyEngine::Gatekeeper,
+            EnforcementMode::Audit,
+            &[],
+        )
+        .unwrap();
+        let docs: Vec<&str> = gatekeeper.yaml.split("---\n").collect();
+        let constraint: Value = serde_yaml::from_str(docs[1]).unwrap();
+        assert_eq!(constraint["kind"], "K8sCis525DisallowHostNetwork");
+        assert_eq!(constraint["spec"]["enforcementAction"], "dryrun");
+    }
+}
@@ -0,0 +1,515 @@
+//! Kubernetes REST API backend
+//!
+//! Serves the operations of [`KubernetesClient`](super::KubernetesClient)
+//! through kube-rs instead of `kubectl` subprocesses, so the server works in
+//! images without kubectl and authenticates with the pod's service account
+//! when it runs inside a cluster. One client holds one connection pool;
+//! share it rather than reconnecting per call.
+
+use super::{age, pods_from_list, Deployment, Namespace, Node, Pod, Service};
+use crate::error::{Error, Result};
+use chrono::Utc;
+use futures::{AsyncBufReadExt, TryStreamExt};
+use k8s_openapi::api::apps::v1::Deployment as K8sDeployment;
+use k8s_openapi::api::core::v1::{
+    Namespace as K8sNamespace, Node as K8sNode, Pod as K8sPod, Service as K8sService,
+};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kube::api::{Api, DeleteParams, ListParams, LogParams, Patch, PatchParams, PostParams};
+use kube::config::{KubeConfigOptions, Kubeconfig};
+use kube::{Client, Config};
+use serde_json::json;
+use std::path::PathBuf;
+
+/// How to reach and authenticate to the API server
+#[derive(Debug, Clone, Default)]
+pub struct KubeApiConfig {
+    /// Kubeconfig to read instead of `$KUBECONFIG` or `~/.kube/config`
+    pub kubeconfig_path: Option<PathBuf>,
+    /// Kubeconfig context to use instead of the current one
+    pub context: Option<String>,
+    /// Use the mounted service account token and cluster CA, ignoring kubeconfig
+    pub in_cluster: bool,
+}
+
+/// Client for the Kubernetes REST API
+#[derive(Clone)]
+pub struct KubeApiClient {
+    client: Client,
+}
+
+impl std::fmt::Debug for KubeApiClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KubeApiClient")
+            .field("default_namespace", &self.client.default_namespace())
+            .finish_non_exhaustive()
+    }
+}
+
+impl KubeApiClient {
+    /// Connect with the given settings
+    ///
+    /// Without a kubeconfig path or context, the usual inference applies:
+    /// `$KUBECONFIG`, then `~/.kube/config`, then the in-cluster service account.
+    pub async fn connect(config: &KubeApiConfig) -> Result<Self> {
+        let kube_config = if config.in_cluster {
+            Config::incluster().map_err(|e| {
+                Error::config_with_suggestion(
+                    format!("In-cluster Kubernetes config unavailable: {}", e),
+                    "Run inside a pod with a service account, or set in_cluster to false",
+                )
+            })?
+        } else if config.kubeconfig_path.is_some() || config.context.is_some() {
+            let kubeconfig = match &config.kubeconfig_path {
+                Some(path) => Kubeconfig::read_from(path),
+                None => Kubeconfig::read(),
+            }
+            .map_err(|e| Error::config(format!("Failed to read kubeconfig: {}", e)))?;
+            let options = KubeConfigOptions {
+                context: config.context.clone(),
+                ..Default::default()
+            };
+            Config::from_custom_kubeconfig(kubeconfig, &options)
+                .await
+                .map_err(|e| Error::config(format!("Invalid kubeconfig: {}", e)))?
+        } else {
+            Config::infer()
+                .await
+                .map_err(|e| Error::config(format!("No Kubernetes config found: {}", e)))?
+        };
+        let client = Client::try_from(kube_config)
+            .map_err(|e| Error::connection(format!("Failed to create Kubernetes client: {}", e)))?;
+        Ok(Self { client })
+    }
+
+    /// Namespace used when a call names none
+    pub fn default_namespace(&self) -> &str {
+        self.client.default_namespace()
+    }
+
+    fn namespaced<K>(&self, namespace: Option<&str>) -> Api<K>
+    where
+        K: kube::Resource<Scope = k8s_openapi::NamespaceResourceScope>,
+        <K as kube::Resource>::DynamicType: Default,
+    {
+        match namespace {
+            Some(namespace) => Api::namespaced(self.client.clone(), namespace),
+            None => Api::default_namespaced(self.client.clone()),
+        }
+    }
+
+    /// API server version, as `v1.32.1`
+    pub async fn version(&self) -> Result<String> {
+        let info = self.client.apiserver_version().await.map_err(api_error)?;
+        Ok(info.git_version)
+    }
+
+    /// List pods in a namespace
+    pub async fn list_pods(&self, namespace: Option<&str>) -> Result<Vec<Pod>> {
+        let list = self
+            .namespaced::<K8sPod>(namespace)
+            .list(&ListParams::default())
+            .await
+            .map_err(api_error)?;
+        Ok(pods_from_list(&serde_json::to_value(&list)?, Utc::now()))
+    }
+
+    /// List deployments in a namespace
+    pub async fn list_deployments(&self, namespace: Option<&str>) -> Result<Vec<Deployment>> {
+        let list = self
+            .namespaced::<K8sDeployment>(namespace)
+            .list(&ListParams::default())
+            .await
+            .map_err(api_error)?;
+        Ok(list
+            .items
+            .into_iter()
+            .map(|deployment| {
+                let status = deployment.status.unwrap_or_default();
+                let desired = deployment
+                    .spec
+                    .as_ref()
+                    .and_then(|spec| spec.replicas)
+                    .unwrap_or(1);
+                Deployment {
+                    name: deployment.metadata.name.clone().unwrap_or_default(),
+                    namespace: deployment.metadata.namespace.clone().unwrap_or_default(),
+                    ready: format!("{}/{}", status.ready_replicas.unwrap_or(0), desired),
+                    available: status.available_replicas.unwrap_or(0),
+                    age: created_age(&deployment.metadata),
+                    image: deployment
+                        .spec
+                        .and_then(|spec| spec.template.spec)
+                        .and_then(|pod| pod.containers.into_iter().next())
+                        .and_then(|container| container.image),
+                }
+            })
+            .collect())
+    }
+
+    /// List services in a namespace
+    pub async fn list_services(&self, namespace: Option<&str>) -> Result<Vec<Service>> {
+        let list = self
+            .namespaced::<K8sService>(namespace)
+            .list(&ListParams::default())
+            .await
+            .map_err(api_error)?;
+        Ok(list
+            .items
+            .into_iter()
+            .map(|service| {
+                let spec = service.spec.unwrap_or_default();
+                let external_ip = service
+                    .status
+                    .and_then(|status| status.load_balancer)
+                    .and_then(|lb| lb.ingress)
+                    .and_then(|ingress| ingress.into_iter().next())
+                    .and_then(|ingress| ingress.ip.or(ingress.hostname))
+                    .or_else(|| spec.external_ips.as_ref()?.first().cloned());
+                let ports = spec
+                    .ports
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|port| {
+                        format!(
+                            "{}/{}",
+                            port.port,
+                            port.protocol.as_deref().unwrap_or("TCP")
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",");
+                Service {
+                    name: service.metadata.name.clone().unwrap_or_default(),
+                    namespace: service.metadata.namespace.clone().unwrap_or_default(),
+                    service_type: spec.type_.unwrap_or_else(|| "ClusterIP".to_string()),
+                    cluster_ip: spec.cluster_ip.unwrap_or_default(),
+                    external_ip,
+                    ports,
+                    age: created_age(&service.metadata),
+                }
+            })
+            .collect())
+    }
+
+    /// List namespaces
+    pub async fn list_namespaces(&self) -> Result<Vec<Namespace>> {
+        let list = Api::<K8sNamespace>::all(self.client.clone())
+            .list(&ListParams::default())
+            .await
+            .map_err(api_error)?;
+        Ok(list
+            .items
+            .into_iter()
+            .map(|namespace| Namespace {
+                name: namespace.metadata.name.clone().unwrap_or_default(),
+                status: namespace
+                    .status
+                    .and_then(|status| status.phase)
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                age: created_age(&namespace.metadata),
+            })
+            .collect())
+    }
+
+    /// List nodes
+    pub async fn list_nodes(&self) -> Result<Vec<Node>> {
+        let list = Api::<K8sNode>::all(self.client.clone())
+            .list(&ListParams::default())
+            .await
+            .map_err(api_error)?;
+        Ok(list
+            .items
+            .into_iter()
+            .map(|node| {
+                let status = node.status.clone().unwrap_or_default();
+                let ready = status
+                    .conditions
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|c| c.type_ == "Ready" && c.status == "True");
+                let addresses = status.addresses.unwrap_or_default();
+                let address = |kind: &str| {
+                    addresses
+                        .iter()
+                        .find(|a| a.type_ == kind)
+                        .map(|a| a.address.clone())
+                };
+                let mut roles: Vec<&str> = node
+                    .metadata
+                    .labels
+                    .iter()
+                    .flatten()
+                    .filter_map(|(label, _)| label.strip_prefix("node-role.kubernetes.io/"))
+                    .collect();
+                roles.sort_unstable();
+                Node {
+                    name: node.metadata.name.clone().unwrap_or_default(),
+                    status: if ready { "Ready" } else { "NotReady" }.to_string(),
+                    roles: if roles.is_empty() {
+                        "<none>".to_string()
+                    } else {
+                        roles.join(",")
+                    },
+                    age: created_age(&node.metadata),
+                    version: status
+                        .node_info
+                        .map(|info| info.kubelet_version)
+                        .unwrap_or_default(),
+                    internal_ip: address("InternalIP"),
+                    external_ip: address("ExternalIP"),
+                }
+            })
+            .collect())
+    }
+
+    /// Create a namespace
+    pub async fn create_namespace(&self, name: &str) -> Result<()> {
+        let namespace: K8sNamespace = serde_json::from_value(json!({
+            "metadata": {"name": name}
+        }))?;
+        Api::<K8sNamespace>::all(self.client.clone())
+            .create(&PostParams::default(), &namespace)
+            .await
+            .map_err(api_error)?;
+        Ok(())
+    }
+
+    /// Delete a namespace
+    pub async fn delete_namespace(&self, name: &str, ignore_not_found: bool) -> Result<()> {
+        let result = Api::<K8sNamespace>::all(self.client.clone())
+            .delete(name, &DeleteParams::default())
+            .await;
+        ignore_missing(result.map(|_| ()), ignore_not_found, "namespace", name)
+    }
+
+    /// Create a single-container pod with the same resource limits as the kubectl backend
+    pub async fn create_pod(
+        &self,
+        name: &str,
+        namespace: &str,
+        image: &str,
+        command: Option<Vec<String>>,
+    ) -> Result<()> {
+        let mut container = container_spec(name, image);
+        if let Some(command) = command {
+            container["command"] = json!(command);
+        }
+        let pod: K8sPod = serde_json::from_value(json!({
+            "metadata": {"name": name, "namespace": namespace},
+            "spec": {"containers": [container]}
+        }))?;
+        self.namespaced::<K8sPod>(Some(namespace))
+            .create(&PostParams::default(), &pod)
+            .await
+            .map_err(api_error)?;
+        Ok(())
+    }
+
+    /// Delete a pod
+    pub async fn delete_pod(
+        &self,
+        name: &str,
+        namespace: &str,
+        ignore_not_found: bool,
+    ) -> Result<()> {
+        let result = self
+            .namespaced::<K8sPod>(Some(namespace))
+            .delete(name, &DeleteParams::default())
+            .await;
+        ignore_missing(result.map(|_| ()), ignore_not_found, "pod", name)
+    }
+
+    /// Create a deployment whose pods are labelled `app: <name>`
+    pub async fn create_deployment(
+        &self,
+        name: &str,
+        namespace: &str,
+        image: &str,
+        replicas: u32,
+        ports: Option<Vec<u16>>,
+    ) -> Result<()> {
+        let mut container = container_spec(name, image);
+        if let Some(ports) = ports.filter(|p| !p.is_empty()) {
+            container["ports"] = ports
+                .iter()
+                .map(|port| json!({"containerPort": port}))
+                .collect();
+        }
+        let deployment: K8sDeployment = serde_json::from_value(json!({
+            "metadata": {"name": name, "namespace": namespace},
+            "spec": {
+                "replicas": replicas,
+                "selector": {"matchLabels": {"app": name}},
+                "template": {
+                    "metadata": {"labels": {"app": name}},
+                    "spec": {"containers": [container]}
+                }
+            }
+        }))?;
+        self.namespaced::<K8sDeployment>(Some(namespace))
+            .create(&PostParams::default(), &deployment)
+            .await
+            .map_err(api_error)?;
+        Ok(())
+    }
+
+    /// Delete a deployment
+    pub async fn delete_deployment(
+        &self,
+        name: &str,
+        namespace: &str,
+        ignore_not_found: bool,
+    ) -> Result<()> {
+        let result = self
+            .namespaced::<K8sDeployment>(Some(namespace))
+            .delete(name, &DeleteParams::default())
+            .await;
+        ignore_missing(result.map(|_| ()), ignore_not_found, "deployment", name)
+    }
+
+    /// Set a deployment's replica count through its scale subresource
+    pub async fn scale_deployment(&self, name: &str, namespace: &str, replicas: u32) -> Result<()> {
+        self.namespaced::<K8sDeployment>(Some(namespace))
+            .patch_scale(
+                name,
+                &PatchParams::default(),
+                &Patch::Merge(json!({"spec": {"replicas": replicas}})),
+            )
+            .await
+            .map_err(|e| resource_error(e, "deployment", name))?;
+        Ok(())
+    }
+
+    /// Last `tail_lines` lines of a pod's log, passing each line to `on_line` as it arrives
+    pub async fn stream_pod_logs(
+        &self,
+        pod_name: &str,
+        namespace: Option<&str>,
+        tail_lines: Option<u32>,
+        mut on_line: impl FnMut(&str) + Send,
+    ) -> Result<String> {
+        let params = LogParams {
+            tail_lines: tail_lines.filter(|&n| n > 0).map(i64::from),
+            ..Default::default()
+        };
+        let reader = self
+            .namespaced::<K8sPod>(namespace)
+            .log_stream(pod_name, &params)
+            .await
+            .map_err(|e| resource_error(e, "pod", pod_name))?;
+        let mut lines = std::pin::pin!(reader.lines());
+        let mut logs = String::new();
+        while let Some(line) = lines
+            .try_next()
+            .await
+            .map_err(|e| Error::network(format!("Failed to read pod logs: {}", e)))?
+        {
+            on_line(&line);
+            logs.push_str(&line);
+            logs.push('\n');
+        }
+        Ok(logs)
+    }
+}
+
+/// Container with the default requests and limits used for ad-hoc workloads
+fn container_spec(name: &str, image: &str) -> serde_json::Value {
+    json!({
+        "name": name,
+        "image": image,
+        "resources": {
+            "requests": {"memory": "64Mi", "cpu": "100m"},
+            "limits": {"memory": "128Mi", "cpu": "200m"}
+        }
+    })
+}
+
+fn created_age(metadata: &ObjectMeta) -> String {
+    metadata
+        .creation_timestamp
+        .as_ref()
+        .map(|created| age(&created.0.to_rfc3339(), Utc::now()))
+        .unwrap_or_default()
+}
+
+fn api_error(error: kube::Error) -> Error {
+    match error {
+        kube::Error::Api(response) => {
+            Error::api_with_status(response.message, "kubernetes", response.code)
+        }
+        other => Error::connection(format!("Kubernetes API request failed: {}", other)),
+    }
+}
+
+fn resource_error(error: kube::Error, kind: &str, name: &str) -> Error {
+    match error {
+        kube::Error::Api(response) if response.code == 404 => {
+            Error::not_found_with_resource(response.message, kind, name)
+        }
+        other => api_error(other),
+    }
+}
+
+fn ignore_missing(
+    result: std::result::Result<(), kube::Error>,
+    ignore_not_found: bool,
+    kind: &str,
+    name: &str,
+) -> Result<()> {
+    match result {
+        Err(kube::Error::Api(response)) if response.code == 404 && ignore_not_found => Ok(()),
+        Err(e) => Err(resource_error(e, kind, name)),
+        Ok(()) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kube::core::ErrorResponse;
+
+    fn not_found() -> kube::Error {
+        kube::Error::Api(ErrorResponse {
+            status: "Failure".to_string(),
+            message: "pods \"web\" not found".to_string(),
+            reason: "NotFound".to_string(),
+            code: 404,
+        })
+    }
+
+    #[test]
+    fn maps_missing_resources() {
+        assert!(ignore_missing(Err(not_found()), true, "pod", "web").is_ok());
+        let error = ignore_missing(Err(not_found()), false, "pod", "web").unwrap_err();
+        assert_eq!(error.category(), "not_found");
+        assert_eq!(api_error(not_found()).category(), "api");
+    }
+
+    #[test]
+    fn builds_workload_containers() {
+        let mut container = container_spec("web", "nginx:1.27");
+        container["ports"] = json!([{"containerPort": 80}]);
+        let deployment: K8sDeployment = serde_json::from_value(json!({
+            "metadata": {"name": "web"},
+            "spec": {
+                "selector": {"matchLabels": {"app": "web"}},
+                "template": {"spec": {"containers": [container]}}
+            }
+        }))
+        .unwrap();
+        let pod = deployment.spec.unwrap().template.spec.unwrap();
+        let limits = pod.containers[0]
+            .resources
+            .as_ref()
+            .unwrap()
+            .limits
+            .as_ref()
+            .unwrap();
+        assert_eq!(limits["memory"].0, "128Mi");
+        assert_eq!(
+            pod.containers[0].ports.as_ref().unwrap()[0].container_port,
+            80
+        );
+    }
+}
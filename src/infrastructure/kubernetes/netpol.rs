@@ -0,0 +1,1286 @@
+use super::KubernetesClient;
+use crate::error::{Error, Result};
+use crate::lifecycle::LifecycleManager;
+use crate::tools::{call_result, ToolDefinition};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use tokio::process::Command as TokioCommand;
+
+/// Labels that identify a workload, in order of preference
+const IDENTITY_LABELS: &[&str] = &["app.kubernetes.io/name", "app", "k8s-app", "name"];
+
+/// Per-pod labels that must not end up in generated selectors
+const VOLATILE_LABELS: &[&str] = &[
+    "pod-template-hash",
+    "controller-revision-hash",
+    "pod-template-generation",
+    "statefulset.kubernetes.io/pod-name",
+    "apps.kubernetes.io/pod-index",
+    "controller-uid",
+    "batch.kubernetes.io/controller-uid",
+    "job-name",
+    "batch.kubernetes.io/job-name",
+];
+
+type Labels = BTreeMap<String, String>;
+
+/// One side of a traffic flow
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Endpoint {
+    Workload { namespace: String, name: String },
+    External { cidr: String },
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Workload { namespace, name } => write!(f, "{}/{}", namespace, name),
+            Endpoint::External { cidr } => write!(f, "{}", cidr),
+        }
+    }
+}
+
+/// Observed service-to-service traffic
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Flow {
+    pub source: Endpoint,
+    pub destination: Endpoint,
+    /// Destination port, when the traffic source reports it
+    pub port: Option<u16>,
+    pub protocol: String,
+    pub count: u64,
+}
+
+fn default_protocol() -> String {
+    "TCP".to_string()
+}
+
+/// A known flow supplied by the caller
+#[derive(Debug, Clone, Deserialize)]
+pub struct FlowSpec {
+    /// `namespace/workload`, IP or CIDR
+    pub source: String,
+    pub destination: String,
+    pub port: Option<u16>,
+    #[serde(default = "default_protocol")]
+    pub protocol: String,
+}
+
+/// How existing NetworkPolicies treat a flow
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// Neither endpoint is selected by a policy
+    Unrestricted,
+    /// Explicitly allowed by the listed policies
+    Allowed,
+    /// An endpoint is isolated and no rule admits the flow
+    Blocked,
+}
+
+/// A flow with its policy verdict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluatedFlow {
+    #[serde(flatten)]
+    pub flow: Flow,
+    pub verdict: Verdict,
+    pub policies: Vec<String>,
+}
+
+/// A suggested least-privilege NetworkPolicy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestedPolicy {
+    pub namespace: String,
+    pub name: String,
+    pub yaml: String,
+}
+
+/// Traffic map and policy suggestions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkPolicyReport {
+    pub workloads: usize,
+    pub policies: usize,
+    pub flows: Vec<EvaluatedFlow>,
+    pub mermaid: String,
+    pub suggested: Vec<SuggestedPolicy>,
+    /// Traffic that could not be turned into rules, collection fallbacks
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct ContainerPort {
+    port: u16,
+    protocol: String,
+    name: Option<String>,
+}
+
+/// Pods grouped by their identifying labels
+#[derive(Debug, Clone)]
+struct Workload {
+    namespace: String,
+    name: String,
+    /// Labels stable across the workload's pods, used in generated selectors
+    selector: Labels,
+    labels: Labels,
+    pods: Vec<String>,
+    ips: Vec<Ipv4Addr>,
+    ports: Vec<ContainerPort>,
+}
+
+fn labels(value: &Value) -> Labels {
+    value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+        .collect()
+}
+
+/// Workload name and selector for a pod's labels
+fn identity(pod_name: &str, labels: &Labels) -> (String, Labels) {
+    for key in IDENTITY_LABELS {
+        if let Some(value) = labels.get(*key) {
+            return (
+                value.clone(),
+                Labels::from([(key.to_string(), value.clone())]),
+            );
+        }
+    }
+    let selector: Labels = labels
+        .iter()
+        .filter(|(k, _)| !VOLATILE_LABELS.contains(&k.as_str()))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    (pod_name.to_string(), selector)
+}
+
+fn parse_cidr(cidr: &str) -> Option<(u32, u32)> {
+    let (ip, bits) = cidr.split_once('/').unwrap_or((cidr, "32"));
+    let ip: Ipv4Addr = ip.parse().ok()?;
+    let bits: u32 = bits.parse().ok().filter(|b| *b <= 32)?;
+    let mask = if bits == 0 {
+        0
+    } else {
+        u32::MAX << (32 - bits)
+    };
+    Some((u32::from(ip) & mask, mask))
+}
+
+fn cidr_contains(cidr: &str, ip: Ipv4Addr) -> bool {
+    parse_cidr(cidr).is_some_and(|(network, mask)| u32::from(ip) & mask == network)
+}
+
+fn ip_block_contains(block: &Value, ip: Ipv4Addr) -> bool {
+    block["cidr"]
+        .as_str()
+        .is_some_and(|cidr| cidr_contains(cidr, ip))
+        && !block["except"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.as_str())
+            .any(|e| cidr_contains(e, ip))
+}
+
+/// Evaluate a `LabelSelector`; an empty selector matches everything
+fn selector_matches(selector: &Value, labels: &Labels) -> bool {
+    let match_labels = selector["matchLabels"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .all(|(k, v)| labels.get(k).map(String::as_str) == v.as_str());
+    let match_expressions = selector["matchExpressions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .all(|expr| {
+            let value = expr["key"].as_str().and_then(|k| labels.get(k));
+            let values: Vec<&str> = expr["values"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str())
+                .collect();
+            match expr["operator"].as_str().unwrap_or_default() {
+                "In" => value.is_some_and(|v| values.contains(&v.as_str())),
+                "NotIn" => !value.is_some_and(|v| values.contains(&v.as_str())),
+                "Exists" => value.is_some(),
+                "DoesNotExist" => value.is_none(),
+                _ => false,
+            }
+        });
+    match_labels && match_expressions
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Ingress,
+    Egress,
+}
+
+impl Direction {
+    fn rules_key(self) -> &'static str {
+        match self {
+            Direction::Ingress => "ingress",
+            Direction::Egress => "egress",
+        }
+    }
+
+    fn peers_key(self) -> &'static str {
+        match self {
+            Direction::Ingress => "from",
+            Direction::Egress => "to",
+        }
+    }
+}
+
+/// Pods, namespaces and NetworkPolicies of a cluster
+pub struct ClusterNetwork {
+    workloads: BTreeMap<(String, String), Workload>,
+    namespaces: BTreeMap<String, Labels>,
+    policies: Vec<Value>,
+}
+
+impl ClusterNetwork {
+    /// Build from a `kubectl get pods,networkpolicies,namespaces -o json` list
+    pub fn from_list(list: &Value) -> Self {
+        let mut workloads: BTreeMap<(String, String), Workload> = BTreeMap::new();
+        let mut namespaces = BTreeMap::new();
+        let mut policies = Vec::new();
+
+        for item in list["items"].as_array().into_iter().flatten() {
+            let meta = &item["metadata"];
+            let name = meta["name"].as_str().unwrap_or_default();
+            match item["kind"].as_str() {
+                Some("Namespace") => {
+                    let mut ns_labels = labels(&meta["labels"]);
+                    ns_labels.insert("kubernetes.io/metadata.name".to_string(), name.to_string());
+                    namespaces.insert(name.to_string(), ns_labels);
+                }
+                Some("NetworkPolicy") => policies.push(item.clone()),
+                Some("Pod") => {
+                    let namespace = meta["namespace"].as_str().unwrap_or("default").to_string();
+                    let pod_labels = labels(&meta["labels"]);
+                    let (workload_name, selector) = identity(name, &pod_labels);
+                    let workload = workloads
+                        .entry((namespace.clone(), workload_name.clone()))
+                        .or_insert_with(|| Workload {
+                            namespace,
+                            name: workload_name,
+                            selector,
+                            labels: pod_labels,
+                            pods: Vec::new(),
+                            ips: Vec::new(),
+                            ports: Vec::new(),
+                        });
+                    workload.pods.push(name.to_string());
+                    if let Some(ip) = item["status"]["podIP"]
+                        .as_str()
+                        .and_then(|ip| ip.parse().ok())
+                    {
+                        workload.ips.push(ip);
+                    }
+                    for port in item["spec"]["containers"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .flat_map(|c| c["ports"].as_array().into_iter().flatten())
+                    {
+                        let Some(number) = port["containerPort"].as_u64() else {
+                            continue;
+                        };
+                        if workload.ports.iter().any(|p| p.port as u64 == number) {
+                            continue;
+                        }
+                        workload.ports.push(ContainerPort {
+                            port: number as u16,
+                            protocol: port["protocol"].as_str().unwrap_or("TCP").to_string(),
+                            name: port["name"].as_str().map(str::to_string),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            workloads,
+            namespaces,
+            policies,
+        }
+    }
+
+    fn workload(&self, endpoint: &Endpoint) -> Option<&Workload> {
+        match endpoint {
+            Endpoint::Workload { namespace, name } => {
+                self.workloads.get(&(namespace.clone(), name.clone()))
+            }
+            Endpoint::External { .. } => None,
+        }
+    }
+
+    fn workload_by_ip(&self, ip: Ipv4Addr) -> Option<&Workload> {
+        self.workloads.values().find(|w| w.ips.contains(&ip))
+    }
+
+    fn workload_by_pod(&self, namespace: &str, pod: &str) -> Option<&Workload> {
+        self.workloads
+            .values()
+            .find(|w| w.namespace == namespace && w.pods.iter().any(|p| p == pod))
+    }
+
+    fn namespace_labels(&self, namespace: &str) -> Labels {
+        self.namespaces.get(namespace).cloned().unwrap_or_else(|| {
+            Labels::from([(
+                "kubernetes.io/metadata.name".to_string(),
+                namespace.to_string(),
+            )])
+        })
+    }
+
+    /// Resolve `namespace/name`, an IP or a CIDR to an endpoint
+    pub fn endpoint(&self, value: &str) -> Endpoint {
+        if parse_cidr(value).is_some() {
+            if let Some(workload) = value.parse().ok().and_then(|ip| self.workload_by_ip(ip)) {
+                return Endpoint::Workload {
+                    namespace: workload.namespace.clone(),
+                    name: workload.name.clone(),
+                };
+            }
+            let cidr = if value.contains('/') {
+                value.to_string()
+            } else {
+                format!("{}/32", value)
+            };
+            return Endpoint::External { cidr };
+        }
+        let (namespace, name) = value.split_once('/').unwrap_or(("default", value));
+        Endpoint::Workload {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    fn peer_matches(&self, peer: &Value, policy_namespace: &str, endpoint: &Endpoint) -> bool {
+        let workload = self.workload(endpoint);
+        if let Some(block) = peer.get("ipBlock") {
+            return match endpoint {
+                Endpoint::External { cidr } => parse_cidr(cidr)
+                    .is_some_and(|(network, _)| ip_block_contains(block, network.into())),
+                Endpoint::Workload { .. } => {
+                    workload.is_some_and(|w| w.ips.iter().any(|ip| ip_block_contains(block, *ip)))
+                }
+            };
+        }
+        let Endpoint::Workload { namespace, .. } = endpoint else {
+            return false;
+        };
+        let namespace_ok = match peer.get("namespaceSelector") {
+            Some(selector) => selector_matches(selector, &self.namespace_labels(namespace)),
+            None => namespace == policy_namespace,
+        };
+        let pod_ok = match peer.get("podSelector") {
+            Some(selector) => workload.is_some_and(|w| selector_matches(selector, &w.labels)),
+            None => true,
+        };
+        namespace_ok && pod_ok
+    }
+
+    fn ports_match(ports: &Value, flow: &Flow, target: Option<&Workload>) -> bool {
+        let Some(ports) = ports.as_array().filter(|p| !p.is_empty()) else {
+            return true;
+        };
+        let Some(port) = flow.port else {
+            return true;
+        };
+        ports.iter().any(|p| {
+            p["protocol"]
+                .as_str()
+                .unwrap_or("TCP")
+                .eq_ignore_ascii_case(&flow.protocol)
+                && match &p["port"] {
+                    Value::Null => true,
+                    Value::String(name) => target.is_some_and(|w| {
+                        w.ports
+                            .iter()
+                            .any(|cp| cp.port == port && cp.name.as_deref() == Some(name))
+                    }),
+                    number => number.as_u64().is_some_and(|start| {
+                        let end = p["endPort"].as_u64().unwrap_or(start);
+                        (start..=end).contains(&(port as u64))
+                    }),
+                }
+        })
+    }
+
+    /// Whether `subject` is isolated in `direction`, and the policies admitting `peer`
+    fn evaluate_side(&self, flow: &Flow, direction: Direction) -> (bool, Vec<String>) {
+        let (subject, peer) = match direction {
+            Direction::Ingress => (&flow.destination, &flow.source),
+            Direction::Egress => (&flow.source, &flow.destination),
+        };
+        let Some(workload) = self.workload(subject) else {
+            return (false, Vec::new());
+        };
+        let target = self.workload(&flow.destination);
+
+        let mut isolated = false;
+        let mut allowing = Vec::new();
+        for policy in &self.policies {
+            let meta = &policy["metadata"];
+            let spec = &policy["spec"];
+            if meta["namespace"].as_str() != Some(workload.namespace.as_str())
+                || !selector_matches(&spec["podSelector"], &workload.labels)
+            {
+                continue;
+            }
+            let types: Vec<&str> = match spec["policyTypes"].as_array() {
+                Some(types) => types.iter().filter_map(|t| t.as_str()).collect(),
+                None if spec["egress"].is_array() => vec!["Ingress", "Egress"],
+                None => vec!["Ingress"],
+            };
+            let wanted = match direction {
+                Direction::Ingress => "Ingress",
+                Direction::Egress => "Egress",
+            };
+            if !types.contains(&wanted) {
+                continue;
+            }
+            isolated = true;
+            let admits = spec[direction.rules_key()]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|rule| {
+                    let peers_ok = match rule[direction.peers_key()].as_array() {
+                        Some(peers) if !peers.is_empty() => peers
+                            .iter()
+                            .any(|p| self.peer_matches(p, &workload.namespace, peer)),
+                        _ => true,
+                    };
+                    peers_ok && Self::ports_match(&rule["ports"], flow, target)
+                });
+            if admits {
+                allowing.push(meta["name"].as_str().unwrap_or_default().to_string());
+            }
+        }
+        (isolated, allowing)
+    }
+
+    /// Verdict of existing policies for a flow
+    pub fn evaluate(&self, flow: &Flow) -> EvaluatedFlow {
+        let (ingress_isolated, mut policies) = self.evaluate_side(flow, Direction::Ingress);
+        let (egress_isolated, egress_policies) = self.evaluate_side(flow, Direction::Egress);
+        let blocked = (ingress_isolated && policies.is_empty())
+            || (egress_isolated && egress_policies.is_empty());
+        policies.extend(egress_policies);
+        policies.sort();
+        policies.dedup();
+        let verdict = if blocked {
+            Verdict::Blocked
+        } else if ingress_isolated || egress_isolated {
+            Verdict::Allowed
+        } else {
+            Verdict::Unrestricted
+        };
+        EvaluatedFlow {
+            flow: flow.clone(),
+            verdict,
+            policies,
+        }
+    }
+
+    fn hubble_endpoint(&self, endpoint: &Value, ip: Option<&str>) -> Option<Endpoint> {
+        let namespace = endpoint["namespace"].as_str().filter(|ns| !ns.is_empty());
+        let Some(namespace) = namespace else {
+            let ip: Ipv4Addr = ip?.parse().ok()?;
+            return Some(match self.workload_by_ip(ip) {
+                Some(w) => Endpoint::Workload {
+                    namespace: w.namespace.clone(),
+                    name: w.name.clone(),
+                },
+                None => Endpoint::External {
+                    cidr: format!("{}/32", ip),
+                },
+            });
+        };
+        let pod = endpoint["pod_name"].as_str().unwrap_or_default();
+        if let Some(w) = self.workload_by_pod(namespace, pod) {
+            return Some(Endpoint::Workload {
+                namespace: w.namespace.clone(),
+                name: w.name.clone(),
+            });
+        }
+        // Hubble labels look like `k8s:app=web`
+        let pod_labels: Labels = endpoint["labels"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|l| l.as_str()?.strip_prefix("k8s:")?.split_once('='))
+            .filter(|(k, _)| !k.starts_with("io.kubernetes.") && !k.starts_with("io.cilium."))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Some(Endpoint::Workload {
+            namespace: namespace.to_string(),
+            name: identity(pod, &pod_labels).0,
+        })
+    }
+
+    /// Parse `hubble observe -o json` output; replies and dropped flows are skipped
+    pub fn parse_hubble(&self, output: &str) -> Vec<Flow> {
+        let mut flows = Vec::new();
+        for line in output.lines().filter(|l| !l.trim().is_empty()) {
+            let Ok(value) = serde_json::from_str::<Value>(line) else {
+                continue;
+            };
+            let flow = value.get("flow").unwrap_or(&value);
+            if flow["verdict"].as_str() != Some("FORWARDED")
+                || flow["is_reply"].as_bool() == Some(true)
+            {
+                continue;
+            }
+            let (Some(source), Some(destination)) = (
+                self.hubble_endpoint(&flow["source"], flow["IP"]["source"].as_str()),
+                self.hubble_endpoint(&flow["destination"], flow["IP"]["destination"].as_str()),
+            ) else {
+                continue;
+            };
+            let (protocol, l4) = match flow["l4"].as_object().and_then(|l4| l4.iter().next()) {
+                Some((protocol, l4)) => (protocol.to_ascii_uppercase(), l4),
+                None => continue,
+            };
+            flows.push(Flow {
+                source,
+                destination,
+                port: l4["destination_port"].as_u64().map(|p| p as u16),
+                protocol,
+                count: 1,
+            });
+        }
+        aggregate(flows)
+    }
+
+    /// Infer inbound flows from pod IPs appearing in a workload's logs
+    fn parse_logs(&self, destination: &Workload, logs: &str, ip_pattern: &Regex) -> Vec<Flow> {
+        let target = Endpoint::Workload {
+            namespace: destination.namespace.clone(),
+            name: destination.name.clone(),
+        };
+        let flows = ip_pattern
+            .find_iter(logs)
+            .filter_map(|m| m.as_str().parse::<Ipv4Addr>().ok())
+            .filter(|ip| !destination.ips.contains(ip))
+            .filter_map(|ip| self.workload_by_ip(ip))
+            .map(|source| Flow {
+                source: Endpoint::Workload {
+                    namespace: source.namespace.clone(),
+                    name: source.name.clone(),
+                },
+                destination: target.clone(),
+                port: None,
+                protocol: "TCP".to_string(),
+                count: 1,
+            })
+            .collect();
+        aggregate(flows)
+    }
+}
+
+/// Merge flows with the same endpoints, port and protocol
+fn aggregate(flows: Vec<Flow>) -> Vec<Flow> {
+    let mut merged: Vec<Flow> = Vec::new();
+    for flow in flows {
+        match merged.iter_mut().find(|f| {
+            f.source == flow.source
+                && f.destination == flow.destination
+                && f.port == flow.port
+                && f.protocol == flow.protocol
+        }) {
+            Some(existing) => existing.count += flow.count,
+            None => merged.push(flow),
+        }
+    }
+    merged
+}
+
+fn sanitize(raw: &str) -> String {
+    raw.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn node_id(endpoint: &Endpoint) -> String {
+    match endpoint {
+        Endpoint::Workload { namespace, name } => {
+            format!("w_{}", sanitize(&format!("{}_{}", namespace, name)))
+        }
+        Endpoint::External { cidr } => format!("x_{}", sanitize(cidr)),
+    }
+}
+
+fn port_label(flow: &Flow) -> String {
+    match flow.port {
+        Some(port) => format!("{}/{}", flow.protocol, port),
+        None => flow.protocol.clone(),
+    }
+}
+
+/// Mermaid flowchart of workloads grouped by namespace
+pub fn mermaid(nodes: &BTreeSet<Endpoint>, flows: &[EvaluatedFlow]) -> String {
+    let mut out = String::from("flowchart LR\n");
+    let mut by_namespace: BTreeMap<&str, Vec<&Endpoint>> = BTreeMap::new();
+    for node in nodes {
+        if let Endpoint::Workload { namespace, .. } = node {
+            by_namespace.entry(namespace).or_default().push(node);
+        }
+    }
+    for (namespace, members) in &by_namespace {
+        out.push_str(&format!(
+            "  subgraph ns_{}[\"{}\"]\n",
+            sanitize(namespace),
+            namespace
+        ));
+        for member in members {
+            if let Endpoint::Workload { name, .. } = member {
+                out.push_str(&format!("    {}[\"{}\"]\n", node_id(member), name));
+            }
+        }
+        out.push_str("  end\n");
+    }
+    for node in nodes {
+        if let Endpoint::External { cidr } = node {
+            out.push_str(&format!("  {}((\"{}\"))\n", node_id(node), cidr));
+        }
+    }
+
+    let mut styles: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (index, evaluated) in flows.iter().enumerate() {
+        let flow = &evaluated.flow;
+        let (arrow, label, color) = match evaluated.verdict {
+            Verdict::Allowed => ("-->", port_label(flow), "#2e7d32"),
+            Verdict::Unrestricted => ("-->", port_label(flow), "#ef6c00"),
+            Verdict::Blocked => ("-.->", format!("{} blocked", port_label(flow)), "#c62828"),
+        };
+        out.push_str(&format!(
+            "  {} {}|\"{}\"| {}\n",
+            node_id(&flow.source),
+            arrow,
+            label,
+            node_id(&flow.destination)
+        ));
+        styles.entry(color).or_default().push(index.to_string());
+    }
+    for (color, indices) in styles {
+        out.push_str(&format!(
+            "  linkStyle {} stroke:{},stroke-width:2px\n",
+            indices.join(","),
+            color
+        ));
+    }
+    out
+}
+
+impl ClusterNetwork {
+    fn peer(&self, endpoint: &Endpoint, policy_namespace: &str) -> Option<Value> {
+        match endpoint {
+            Endpoint::External { cidr } => Some(json!({ "ipBlock": { "cidr": cidr } })),
+            Endpoint::Workload { namespace, .. } => {
+                let workload = self.workload(endpoint)?;
+                if workload.selector.is_empty() {
+                    return None;
+                }
+                let mut peer = json!({ "podSelector": { "matchLabels": workload.selector } });
+                if namespace != policy_namespace {
+                    peer["namespaceSelector"] =
+                        json!({ "matchLabels": { "kubernetes.io/metadata.name": namespace } });
+                }
+                Some(peer)
+            }
+        }
+    }
+
+    /// Ports of flows to `target`, falling back to its declared container ports
+    fn rule_ports(flows: &[&Flow], target: Option<&Workload>) -> Option<Vec<Value>> {
+        let mut ports = BTreeSet::new();
+        for flow in flows {
+            match flow.port {
+                Some(port) => {
+                    ports.insert((flow.protocol.clone(), port));
+                }
+                None => match target.filter(|w| !w.ports.is_empty()) {
+                    Some(workload) => {
+                        ports.extend(workload.ports.iter().map(|p| (p.protocol.clone(), p.port)))
+                    }
+                    // Port unknown and nothing declared: allow the peer on any port
+                    None => return None,
+                },
+            }
+        }
+        Some(
+            ports
+                .into_iter()
+                .map(|(protocol, port)| json!({ "protocol": protocol, "port": port }))
+                .collect(),
+        )
+    }
+
+    fn rules(
+        &self,
+        workload: &Workload,
+        flows: &[&Flow],
+        direction: Direction,
+        notes: &mut Vec<String>,
+    ) -> Vec<Value> {
+        let mut by_peer: BTreeMap<&Endpoint, Vec<&Flow>> = BTreeMap::new();
+        for flow in flows {
+            let peer = match direction {
+                Direction::Ingress => &flow.source,
+                Direction::Egress => &flow.destination,
+            };
+            by_peer.entry(peer).or_default().push(flow);
+        }
+        let mut rules = Vec::new();
+        for (peer, peer_flows) in by_peer {
+            let Some(selector) = self.peer(peer, &workload.namespace) else {
+                notes.push(format!(
+                    "No stable selector for {}; traffic with {}/{} not included",
+                    peer, workload.namespace, workload.name
+                ));
+                continue;
+            };
+            let target = self.workload(&peer_flows[0].destination);
+            let mut rule = json!({ direction.peers_key(): [selector] });
+            if let Some(ports) = Self::rule_ports(&peer_flows, target) {
+                rule["ports"] = json!(ports);
+            }
+            rules.push(rule);
+        }
+        rules
+    }
+
+    /// Least-privilege policies admitting exactly the observed flows
+    pub fn suggest(
+        &self,
+        flows: &[Flow],
+        namespace: Option<&str>,
+        include_egress: bool,
+        notes: &mut Vec<String>,
+    ) -> Result<Vec<SuggestedPolicy>> {
+        let mut suggested = Vec::new();
+        for workload in self.workloads.values() {
+            if namespace.is_some_and(|ns| ns != workload.namespace) {
+                continue;
+            }
+            let endpoint = Endpoint::Workload {
+                namespace: workload.namespace.clone(),
+                name: workload.name.clone(),
+            };
+            let inbound: Vec<&Flow> = flows.iter().filter(|f| f.destination == endpoint).collect();
+            let outbound: Vec<&Flow> = if include_egress {
+                flows.iter().filter(|f| f.source == endpoint).collect()
+            } else {
+                Vec::new()
+            };
+            if inbound.is_empty() && outbound.is_empty() {
+                continue;
+            }
+            if workload.selector.is_empty() {
+                notes.push(format!(
+                    "{}/{} has no stable labels to select it; no policy suggested",
+                    workload.namespace, workload.name
+                ));
+                continue;
+            }
+
+            let mut types = Vec::new();
+            let mut spec = json!({ "podSelector": { "matchLabels": workload.selector } });
+            if !inbound.is_empty() {
+                types.push("Ingress");
+                spec["ingress"] = json!(self.rules(workload, &inbound, Direction::Ingress, notes));
+            }
+            if !outbound.is_empty() {
+                types.push("Egress");
+                let mut egress = self.rules(workload, &outbound, Direction::Egress, notes);
+                // Name resolution is needed for any egress to work
+                egress.push(json!({
+                    "to": [{
+                        "namespaceSelector": { "matchLabels": { "kubernetes.io/metadata.name": "kube-system" } },
+                        "podSelector": { "matchLabels": { "k8s-app": "kube-dns" } }
+                    }],
+                    "ports": [{ "protocol": "UDP", "port": 53 }, { "protocol": "TCP", "port": 53 }]
+                }));
+                spec["egress"] = json!(egress);
+            }
+            spec["policyTypes"] = json!(types);
+
+            let name = format!("{}-least-privilege", workload.name);
+            let policy = json!({
+                "apiVersion": "networking.k8s.io/v1",
+                "kind": "NetworkPolicy",
+                "metadata": {
+                    "name": name,
+                    "namespace": workload.namespace,
+                    "labels": { "app.kubernetes.io/managed-by": "devops-mcp" }
+                },
+                "spec": spec,
+            });
+            suggested.push(SuggestedPolicy {
+                namespace: workload.namespace.clone(),
+                name,
+                yaml: serde_yaml::to_string(&policy).map_err(|e| {
+                    Error::internal(format!("Failed to render NetworkPolicy YAML: {}", e))
+                })?,
+            });
+        }
+        Ok(suggested)
+    }
+
+    /// Evaluate flows, draw the traffic map and suggest policies
+    pub fn report(
+        &self,
+        flows: Vec<Flow>,
+        namespace: Option<&str>,
+        include_egress: bool,
+        mut notes: Vec<String>,
+    ) -> Result<NetworkPolicyReport> {
+        let in_scope = |endpoint: &Endpoint| match (endpoint, namespace) {
+            (_, None) => true,
+            (Endpoint::Workload { namespace: ns, .. }, Some(scope)) => ns == scope,
+            (Endpoint::External { .. }, Some(_)) => false,
+        };
+        let flows: Vec<Flow> = aggregate(flows)
+            .into_iter()
+            .filter(|f| in_scope(&f.source) || in_scope(&f.destination))
+            .collect();
+        let evaluated: Vec<EvaluatedFlow> = flows.iter().map(|f| self.evaluate(f)).collect();
+
+        let mut nodes: BTreeSet<Endpoint> = self
+            .workloads
+            .values()
+            .map(|w| Endpoint::Workload {
+                namespace: w.namespace.clone(),
+                name: w.name.clone(),
+            })
+            .filter(|e| in_scope(e))
+            .collect();
+        for flow in &flows {
+            nodes.insert(flow.source.clone());
+            nodes.insert(flow.destination.clone());
+        }
+
+        let suggested = self.suggest(&flows, namespace, include_egress, &mut notes)?;
+        Ok(NetworkPolicyReport {
+            workloads: nodes
+                .iter()
+                .filter(|n| matches!(n, Endpoint::Workload { .. }))
+                .count(),
+            policies: self
+                .policies
+                .iter()
+                .filter(|p| namespace.is_none_or(|ns| p["metadata"]["namespace"] == ns))
+                .count(),
+            mermaid: mermaid(&nodes, &evaluated),
+            flows: evaluated,
+            suggested,
+            notes,
+        })
+    }
+}
+
+/// Where observed traffic comes from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TrafficSource {
+    /// Hubble when the CLI can reach a relay, pod logs otherwise
+    #[default]
+    Auto,
+    Hubble,
+    Logs,
+    /// Only flows passed in explicitly
+    None,
+}
+
+/// NetworkPolicy visualization and least-privilege suggestions
+pub struct NetworkPolicyAnalyzer {
+    lifecycle: Arc<LifecycleManager>,
+    kubeconfig: Option<String>,
+    context: Option<String>,
+    /// Hubble Relay address passed to `hubble observe --server`
+    hubble_server: Option<String>,
+}
+
+impl NetworkPolicyAnalyzer {
+    /// Create an analyzer using the given kubeconfig and context
+    pub fn new(
+        lifecycle: Arc<LifecycleManager>,
+        kubeconfig: Option<String>,
+        context: Option<String>,
+    ) -> Self {
+        Self {
+            lifecycle,
+            kubeconfig,
+            context,
+            hubble_server: std::env::var("HUBBLE_SERVER").ok(),
+        }
+    }
+
+    /// Read flows from a specific Hubble Relay
+    pub fn with_hubble_server(mut self, server: impl Into<String>) -> Self {
+        self.hubble_server = Some(server.into());
+        self
+    }
+
+    fn client(&self) -> Result<KubernetesClient<'_>> {
+        KubernetesClient::new(
+            &self.lifecycle,
+            self.kubeconfig.as_deref(),
+            self.context.as_deref(),
+        )
+    }
+
+    /// Fetch pods, namespaces and NetworkPolicies
+    pub async fn cluster(&self) -> Result<ClusterNetwork> {
+        let result = self
+            .client()?
+            .run_kubectl_command("get pods,networkpolicies,namespaces -A -o json", None)
+            .await?;
+        if !result.success {
+            return Err(Error::service(format!(
+                "kubectl get network resources failed: {}",
+                result.error.unwrap_or_default()
+            )));
+        }
+        let list: Value = serde_json::from_str(&result.output)
+            .map_err(|e| Error::parsing(format!("Failed to parse network resources: {}", e)))?;
+        Ok(ClusterNetwork::from_list(&list))
+    }
+
+    /// Recent flows from Hubble
+    pub async fn hubble_flows(
+        &self,
+        cluster: &ClusterNetwork,
+        namespace: Option<&str>,
+        last: u32,
+    ) -> Result<Vec<Flow>> {
+        let mut cmd = TokioCommand::new("hubble");
+        cmd.args(["observe", "--output", "json", "--last", &last.to_string()]);
+        if let Some(server) = &self.hubble_server {
+            if !server
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || ".:-_/[]".contains(c))
+            {
+                return Err(Error::config("Invalid Hubble server address"));
+            }
+            cmd.args(["--server", server]);
+        }
+        if let Some(namespace) = namespace {
+            self.client()?.validate_k8s_resource_name(namespace)?;
+            cmd.args(["--namespace", namespace]);
+        }
+        let output = cmd.output().await.map_err(|e| {
+            Error::service(format!(
+                "Failed to run hubble (is the CLI installed?): {}",
+                e
+            ))
+        })?;
+        if !output.status.success() {
+            return Err(Error::service(format!(
+                "hubble observe failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(cluster.parse_hubble(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Flows inferred from peer pod IPs in workload logs
+    pub async fn log_flows(
+        &self,
+        cluster: &ClusterNetwork,
+        namespace: Option<&str>,
+        max_pods: usize,
+        notes: &mut Vec<String>,
+    ) -> Result<Vec<Flow>> {
+        let client = self.client()?;
+        let ip_pattern = Regex::new(r"\b\d{1,3}(?:\.\d{1,3}){3}\b")
+            .map_err(|e| Error::internal(format!("Invalid IP pattern: {}", e)))?;
+        let mut flows = Vec::new();
+        for workload in cluster
+            .workloads
+            .values()
+            .filter(|w| namespace.is_none_or(|ns| ns == w.namespace))
+            .take(max_pods)
+        {
+            let Some(pod) = workload.pods.first() else {
+                continue;
+            };
+            match client
+                .get_pod_logs(pod, Some(&workload.namespace), Some(500))
+                .await
+            {
+                Ok(logs) => flows.extend(cluster.parse_logs(workload, &logs, &ip_pattern)),
+                Err(e) => notes.push(format!(
+                    "Logs of {}/{} unavailable: {}",
+                    workload.namespace, pod, e
+                )),
+            }
+        }
+        Ok(flows)
+    }
+
+    /// Map traffic and suggest policies for one namespace or the whole cluster
+    pub async fn analyze(
+        &self,
+        namespace: Option<&str>,
+        source: TrafficSource,
+        explicit: Vec<FlowSpec>,
+        include_egress: bool,
+        hubble_last: u32,
+        max_log_pods: usize,
+    ) -> Result<NetworkPolicyReport> {
+        let cluster = self.cluster().await?;
+        let mut notes = Vec::new();
+        let mut flows: Vec<Flow> = explicit
+            .into_iter()
+            .map(|spec| Flow {
+                source: cluster.endpoint(&spec.source),
+                destination: cluster.endpoint(&spec.destination),
+                port: spec.port,
+                protocol: spec.protocol.to_ascii_uppercase(),
+                count: 1,
+            })
+            .collect();
+
+        match source {
+            TrafficSource::Hubble => {
+                flows.extend(self.hubble_flows(&cluster, namespace, hubble_last).await?)
+            }
+            TrafficSource::Logs => flows.extend(
+                self.log_flows(&cluster, namespace, max_log_pods, &mut notes)
+                    .await?,
+            ),
+            TrafficSource::Auto => {
+                match self.hubble_flows(&cluster, namespace, hubble_last).await {
+                    Ok(observed) => flows.extend(observed),
+                    Err(e) => {
+                        notes.push(format!(
+                            "Hubble unavailable ({}); inferred traffic from pod logs",
+                            e
+                        ));
+                        flows.extend(
+                            self.log_flows(&cluster, namespace, max_log_pods, &mut notes)
+                                .await?,
+                        );
+                    }
+                }
+            }
+            TrafficSource::None => {}
+        }
+
+        cluster.report(flows, namespace, include_egress, notes)
+    }
+
+    /// Get tool definitions for network policy analysis
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "network_policy_map",
+            "Map service-to-service traffic against existing NetworkPolicies as a Mermaid diagram and suggest least-privilege NetworkPolicy YAML",
+            "kubernetes",
+            json!({
+                "type": "object",
+                "properties": {
+                    "namespace": {"type": "string", "description": "Namespace to analyze (default: all)"},
+                    "traffic_source": {"type": "string", "enum": ["auto", "hubble", "logs", "none"], "default": "auto"},
+                    "flows": {
+                        "type": "array",
+                        "description": "Additional known flows",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "source": {"type": "string", "description": "namespace/workload, IP or CIDR"},
+                                "destination": {"type": "string", "description": "namespace/workload, IP or CIDR"},
+                                "port": {"type": "integer"},
+                                "protocol": {"type": "string", "default": "TCP"}
+                            },
+                            "required": ["source", "destination"]
+                        }
+                    },
+                    "include_egress": {"type": "boolean", "default": false},
+                    "hubble_last": {"type": "integer", "default": 2000, "description": "Hubble flows to read"},
+                    "max_log_pods": {"type": "integer", "default": 30, "description": "Workloads whose logs are read"}
+                }
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a network policy tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "network_policy_map" => {
+                let namespace = parameters.get("namespace").and_then(|n| n.as_str());
+                let source: TrafficSource = match parameters.get("traffic_source") {
+                    Some(source) => serde_json::from_value(source.clone()).map_err(|_| {
+                        Error::validation_with_field(
+                            "traffic_source must be auto, hubble, logs or none",
+                            "traffic_source",
+                        )
+                    })?,
+                    None => TrafficSource::Auto,
+                };
+                let explicit: Vec<FlowSpec> = match parameters.get("flows") {
+                    Some(flows) => serde_json::from_value(flows.clone()).map_err(|e| {
+                        Error::validation_with_field(format!("Invalid flows: {}", e), "flows")
+                    })?,
+                    None => Vec::new(),
+                };
+                let report = self
+                    .analyze(
+                        namespace,
+                        source,
+                        explicit,
+                        parameters
+                            .get("include_egress")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false),
+                        parameters
+                            .get("hubble_last")
+                            .and_then(|v| v.as_u64())
+                            .unwrap_or(2000) as u32,
+                        parameters
+                            .get("max_log_pods")
+                            .and_then(|v| v.as_u64())
+                            .unwrap_or(30) as usize,
+                    )
+                    .await?;
+
+                let blocked = report
+                    .flows
+                    .iter()
+                    .filter(|f| f.verdict == Verdict::Blocked)
+                    .count();
+                let unrestricted = report
+                    .flows
+                    .iter()
+                    .filter(|f| f.verdict == Verdict::Unrestricted)
+                    .count();
+                let mut text = format!(
+                    "{} workloads, {} NetworkPolicies, {} flows ({} unrestricted, {} blocked)\n\n```mermaid\n{}```",
+                    report.workloads,
+                    report.policies,
+                    report.flows.len(),
+                    unrestricted,
+                    blocked,
+                    report.mermaid
+                );
+                if !report.suggested.is_empty() {
+                    let yaml = report
+                        .suggested
+                        .iter()
+                        .map(|p| p.yaml.as_str())
+                        .collect::<Vec<_>>()
+                        .join("---\n");
+                    text.push_str(&format!("\n\nSuggested policies:\n```yaml\n{}```", yaml));
+                }
+                for note in &report.notes {
+                    text.push_str(&format!("\nNote: {}", note));
+                }
+                Ok(call_result(text, serde_json::to_value(&report)?))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "network_policy_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_flows_and_suggests_policies() {
+        let pod = |ns: &str, name: &str, app: &str, ip: &str, port: u16| {
+            json!({"kind": "Pod",
+                "metadata": {"name": name, "namespace": ns,
+                    "labels": {"app": app, "pod-template-hash": "abc"}},
+                "spec": {"containers": [{"name": app, "ports": [{"containerPort": port, "name": "http"}]}]},
+                "status": {"podIP": ip}})
+        };
+        let cluster = ClusterNetwork::from_list(&json!({"items": [
+            {"kind": "Namespace", "metadata": {"name": "shop"}},
+            {"kind": "Namespace", "metadata": {"name": "monitoring"}},
+            pod("shop", "web-1", "web", "10.0.0.10", 8080),
+            pod("shop", "api-1", "api", "10.0.0.20", 9000),
+            pod("shop", "db-0", "db", "10.0.0.30", 5432),
+            pod("monitoring", "prom-0", "prometheus", "10.0.1.5", 9090),
+            {"kind": "NetworkPolicy", "metadata": {"name": "db-from-api", "namespace": "shop"},
+             "spec": {"podSelector": {"matchLabels": {"app": "db"}},
+                      "ingress": [{"from": [{"podSelector": {"matchLabels": {"app": "api"}}}],
+                                   "ports": [{"port": 5432}]}]}}
+        ]}));
+
+        let hubble = [
+            r#"{"flow":{"verdict":"FORWARDED","IP":{"source":"10.0.0.20","destination":"10.0.0.30"},"source":{"namespace":"shop","pod_name":"api-1"},"destination":{"namespace":"shop","pod_name":"db-0"},"l4":{"TCP":{"destination_port":5432}}}}"#,
+            r#"{"flow":{"verdict":"FORWARDED","is_reply":true,"source":{"namespace":"shop","pod_name":"db-0"},"destination":{"namespace":"shop","pod_name":"api-1"},"l4":{"TCP":{"destination_port":40000}}}}"#,
+            r#"{"flow":{"verdict":"FORWARDED","source":{"namespace":"shop","pod_name":"web-1"},"destination":{"namespace":"shop","pod_name":"db-0"},"l4":{"TCP":{"destination_port":5432}}}}"#,
+        ]
+        .join("\n");
+        let mut flows = cluster.parse_hubble(&hubble);
+        assert_eq!(flows.len(), 2);
+        let ip_pattern = Regex::new(r"\b\d{1,3}(?:\.\d{1,3}){3}\b").unwrap();
+        let api = cluster.workload(&cluster.endpoint("shop/api")).unwrap();
+        flows.extend(cluster.parse_logs(
+            api,
+            "10.0.1.5 - GET /metrics 200\n10.0.1.5 - GET /metrics 200",
+            &ip_pattern,
+        ));
+
+        let report = cluster
+            .report(flows, Some("shop"), false, Vec::new())
+            .unwrap();
+        let verdicts: Vec<(String, Verdict)> = report
+            .flows
+            .iter()
+            .map(|f| (f.flow.source.to_string(), f.verdict))
+            .collect();
+        assert_eq!(
+            verdicts,
+            vec![
+                ("shop/api".to_string(), Verdict::Allowed),
+                ("shop/web".to_string(), Verdict::Blocked),
+                ("monitoring/prometheus".to_string(), Verdict::Unrestricted),
+            ]
+        );
+        assert!(report
+            .mermaid
+            .contains("w_shop_web -.->|\"TCP/5432 blocked\"| w_shop_db"));
+        assert!(report
+            .mermaid
+            .contains("subgraph ns_monitoring[\"monitoring\"]"));
+
+        let api_policy = report
+            .suggested
+            .iter()
+            .find(|p| p.name == "api-least-privilege")
+            .unwrap();
+        let policy: Value = serde_yaml::from_str(&api_policy.yaml).unwrap();
+        let rule = &policy["spec"]["ingress"][0];
+        assert_eq!(
+            rule["from"][0]["namespaceSelector"]["matchLabels"]["kubernetes.io/metadata.name"],
+            "monitoring"
+        );
+        assert_eq!(rule["ports"][0]["port"], 9000);
+        assert_eq!(
+            policy["spec"]["podSelector"]["matchLabels"],
+            json!({"app": "api"})
+        );
+    }
+}
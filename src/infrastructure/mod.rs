@@ -4,8 +4,8 @@ use crate::lifecycle::LifecycleManager;
 use crate::tools::ToolDefinition;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::sync::Arc;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum InfrastructureProvider {
@@ -109,9 +109,14 @@ pub struct InfrastructureMetrics {
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+pub mod assets;
+pub mod chaos;
 pub mod cloudflare;
 pub mod docker;
 pub mod kubernetes;
+pub mod power;
+pub mod proxy;
+pub mod ups;
 
 use cloudflare::CloudflareClient;
 use docker::ContainerClient;
@@ -181,11 +186,11 @@ impl InfrastructureModule {
                 Ok(true) => {
                     healthy_providers += 1;
                     HealthStatus::Healthy
-                },
+                }
                 Ok(false) => HealthStatus::Degraded,
                 Err(_) => HealthStatus::Unhealthy,
             };
-            
+
             status.providers.push(ProviderStatus {
                 name: "kubernetes".to_string(),
                 status: k8s_status,
@@ -204,11 +209,11 @@ impl InfrastructureModule {
                 Ok(true) => {
                     healthy_providers += 1;
                     HealthStatus::Healthy
-                },
+                }
                 Ok(false) => HealthStatus::Degraded,
                 Err(_) => HealthStatus::Unhealthy,
             };
-            
+
             status.providers.push(ProviderStatus {
                 name: "docker".to_string(),
                 status: docker_status,
@@ -226,11 +231,11 @@ impl InfrastructureModule {
                 Ok(true) => {
                     healthy_providers += 1;
                     HealthStatus::Healthy
-                },
+                }
                 Ok(false) => HealthStatus::Degraded,
                 Err(_) => HealthStatus::Unhealthy,
             };
-            
+
             status.providers.push(ProviderStatus {
                 name: "cloudflare".to_string(),
                 status: cf_status,
@@ -256,7 +261,10 @@ impl InfrastructureModule {
     }
 
     /// Deploy infrastructure resources across providers
-    pub async fn deploy_resources(&self, deployment_spec: DeploymentSpec) -> Result<DeploymentResult> {
+    pub async fn deploy_resources(
+        &self,
+        deployment_spec: DeploymentSpec,
+    ) -> Result<DeploymentResult> {
         let mut results = Vec::new();
 
         for resource in deployment_spec.resources {
@@ -265,19 +273,22 @@ impl InfrastructureModule {
                     let k8s_client = self.kubernetes().await?;
                     let result = k8s_client.deploy_resource(resource).await?;
                     results.push(result);
-                },
+                }
                 "docker" => {
                     let docker_client = self.docker().await?;
                     let result = docker_client.deploy_resource(resource).await?;
                     results.push(result);
-                },
+                }
                 "cloudflare" => {
                     let cf_client = self.cloudflare()?;
                     let result = cf_client.deploy_resource(resource).await?;
                     results.push(result);
-                },
+                }
                 _ => {
-                    return Err(Error::validation(format!("Unsupported provider: {}", resource.provider)));
+                    return Err(Error::validation(format!(
+                        "Unsupported provider: {}",
+                        resource.provider
+                    )));
                 }
             }
         }
@@ -287,7 +298,7 @@ impl InfrastructureModule {
         } else {
             "partial".to_string()
         };
-        
+
         Ok(DeploymentResult {
             deployment_id: uuid::Uuid::new_v4().to_string(),
             resources: results,
@@ -306,14 +317,17 @@ impl InfrastructureModule {
                     let k8s_client = self.kubernetes().await?;
                     let result = k8s_client.scale_resource(target).await?;
                     results.push(result);
-                },
+                }
                 "docker" => {
                     let docker_client = self.docker().await?;
                     let result = docker_client.scale_resource(target).await?;
                     results.push(result);
-                },
+                }
                 _ => {
-                    return Err(Error::validation(format!("Scaling not supported for provider: {}", target.provider)));
+                    return Err(Error::validation(format!(
+                        "Scaling not supported for provider: {}",
+                        target.provider
+                    )));
                 }
             }
         }
@@ -341,7 +355,9 @@ impl InfrastructureModule {
             if let Ok(k8s_metrics) = k8s_client.get_metrics().await {
                 // Parse CPU and memory from k8s metrics if available
                 // For now, just store the raw metrics
-                metrics.provider_metrics.insert("kubernetes".to_string(), k8s_metrics);
+                metrics
+                    .provider_metrics
+                    .insert("kubernetes".to_string(), k8s_metrics);
             }
         }
 
@@ -350,14 +366,18 @@ impl InfrastructureModule {
             if let Ok(docker_metrics) = docker_client.get_metrics().await {
                 // Parse CPU and memory from docker metrics if available
                 // For now, just store the raw metrics
-                metrics.provider_metrics.insert("docker".to_string(), docker_metrics);
+                metrics
+                    .provider_metrics
+                    .insert("docker".to_string(), docker_metrics);
             }
         }
 
         // Collect Cloudflare metrics
         if let Ok(cf_client) = self.cloudflare() {
             if let Ok(cf_metrics) = cf_client.get_metrics().await {
-                metrics.provider_metrics.insert("cloudflare".to_string(), cf_metrics);
+                metrics
+                    .provider_metrics
+                    .insert("cloudflare".to_string(), cf_metrics);
             }
         }
 
@@ -421,26 +441,35 @@ impl InfrastructureModule {
             match provider {
                 InfrastructureProvider::Kubernetes(_config) => {
                     // For now, create some example tools since KubernetesModule isn't fully implemented
-                    tools.push(ToolDefinition::new(
-                        "list_pods".to_string(),
-                        "List Kubernetes pods".to_string(),
-                    ));
-                    tools.push(ToolDefinition::new(
-                        "get_pod_logs".to_string(),
-                        "Get pod logs".to_string(),
-                    ));
+                    tools.push(
+                        ToolDefinition::new(
+                            "list_pods".to_string(),
+                            "List Kubernetes pods".to_string(),
+                        )
+                        .read_only(),
+                    );
+                    tools.push(
+                        ToolDefinition::new("get_pod_logs".to_string(), "Get pod logs".to_string())
+                            .read_only(),
+                    );
                 }
                 InfrastructureProvider::Docker(_config) => {
-                    tools.push(ToolDefinition::new(
-                        "list_containers".to_string(),
-                        "List Docker containers".to_string(),
-                    ));
+                    tools.push(
+                        ToolDefinition::new(
+                            "list_containers".to_string(),
+                            "List Docker containers".to_string(),
+                        )
+                        .read_only(),
+                    );
                 }
                 InfrastructureProvider::Cloudflare(_config) => {
-                    tools.push(ToolDefinition::new(
-                        "list_dns_records".to_string(),
-                        "List DNS records".to_string(),
-                    ));
+                    tools.push(
+                        ToolDefinition::new(
+                            "list_dns_records".to_string(),
+                            "List DNS records".to_string(),
+                        )
+                        .read_only(),
+                    );
                 }
             }
         }
@@ -454,6 +483,7 @@ impl InfrastructureModule {
         for pod in pods {
             tools.push(
                 ToolDefinition::new(format!("get_pod_logs_{}", pod), "Get pod logs".to_string())
+                    .read_only()
                     .with_parameters(serde_json::json!({
                         "type": "object",
                         "properties": {
@@ -464,6 +494,7 @@ impl InfrastructureModule {
 
             tools.push(
                 ToolDefinition::new(format!("describe_pod_{}", pod), "Describe pod".to_string())
+                    .read_only()
                     .with_parameters(serde_json::json!({
                         "type": "object",
                         "properties": {
@@ -485,6 +516,7 @@ impl InfrastructureModule {
                     format!("get_container_logs_{}", container.id),
                     "Get container logs".to_string(),
                 )
+                .read_only()
                 .with_parameters(serde_json::json!({
                     "type": "object",
                     "properties": {
@@ -498,6 +530,7 @@ impl InfrastructureModule {
                     format!("inspect_container_{}", container.id),
                     "Inspect container".to_string(),
                 )
+                .read_only()
                 .with_parameters(serde_json::json!({
                     "type": "object",
                     "properties": {
@@ -0,0 +1,627 @@
+/// Power control for lab machines
+///
+/// Machines are named by their asset id, and the MAC and BMC address come
+/// from the hardware inventory. Servers with a BMC are driven through
+/// Redfish or IPMI, which also covers power off, restart and status; other
+/// machines can only be woken with a Wake-on-LAN magic packet. A raw MAC
+/// address is accepted in place of an asset id for Wake-on-LAN.
+use crate::error::{Error, Result};
+use crate::infrastructure::assets::{Asset, AssetRegistry};
+use crate::tools::{call_result, ToolDefinition};
+use reqwest::{Client, Method};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::process::Command;
+
+/// Times each magic packet is sent, since UDP may drop it
+const WOL_REPEAT: usize = 3;
+
+/// Power control configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerConfig {
+    /// Address magic packets are broadcast to
+    pub broadcast: String,
+    /// UDP port for magic packets, usually 7 or 9
+    pub wol_port: u16,
+    /// BMC credentials by asset id; `default` applies to every other asset
+    pub bmc: BTreeMap<String, BmcCredentials>,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            broadcast: "255.255.255.255".to_string(),
+            wol_port: 9,
+            bmc: BTreeMap::new(),
+        }
+    }
+}
+
+/// How a BMC is spoken to
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BmcProtocol {
+    #[default]
+    Redfish,
+    Ipmi,
+}
+
+/// Login for a BMC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BmcCredentials {
+    #[serde(default)]
+    pub protocol: BmcProtocol,
+    pub username: String,
+    pub password: String,
+    /// Check the BMC's TLS certificate; most ship self-signed ones
+    #[serde(default)]
+    pub verify_tls: bool,
+}
+
+/// Power state change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    On,
+    Off { force: bool },
+    Restart,
+}
+
+impl PowerAction {
+    fn redfish_reset_type(self) -> &'static str {
+        match self {
+            Self::On => "On",
+            Self::Off { force: false } => "GracefulShutdown",
+            Self::Off { force: true } => "ForceOff",
+            Self::Restart => "GracefulRestart",
+        }
+    }
+
+    fn ipmi_command(self) -> &'static str {
+        match self {
+            Self::On => "on",
+            Self::Off { force: false } => "soft",
+            Self::Off { force: true } => "off",
+            Self::Restart => "cycle",
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::On => "power on",
+            Self::Off { force: false } => "shut down",
+            Self::Off { force: true } => "power off",
+            Self::Restart => "restart",
+        }
+    }
+}
+
+/// Six-byte MAC from `aa:bb:cc:dd:ee:ff`, `aa-bb-…` or `aabbccddeeff`
+pub fn parse_mac(mac: &str) -> Option<[u8; 6]> {
+    let hex: String = mac
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-' | '.'))
+        .collect();
+    if hex.len() != 12 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut bytes = [0u8; 6];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Six 0xFF bytes followed by the MAC sixteen times
+pub fn magic_packet(mac: [u8; 6]) -> Vec<u8> {
+    let mut packet = vec![0xFF; 6];
+    for _ in 0..16 {
+        packet.extend_from_slice(&mac);
+    }
+    packet
+}
+
+/// Redfish service root for a BMC address
+fn redfish_base(bmc: &str) -> String {
+    if bmc.contains("://") {
+        bmc.trim_end_matches('/').to_string()
+    } else {
+        format!("https://{}", bmc.trim_end_matches('/'))
+    }
+}
+
+/// Host part of a BMC address, for ipmitool
+fn bmc_host(bmc: &str) -> &str {
+    let host = bmc.split_once("://").map_or(bmc, |(_, rest)| rest);
+    host.split('/').next().unwrap_or(host)
+}
+
+/// Machine a power request is for
+#[derive(Debug)]
+struct Target {
+    name: String,
+    mac: Option<String>,
+    bmc: Option<String>,
+}
+
+/// Result of a power request
+#[derive(Debug, Clone, Serialize)]
+pub struct PowerOutcome {
+    pub target: String,
+    /// `redfish`, `ipmi` or `wake_on_lan`
+    pub method: &'static str,
+    pub message: String,
+    /// Power state reported by the BMC, when asked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power_state: Option<String>,
+}
+
+/// Power control over the asset inventory
+pub struct PowerController {
+    config: PowerConfig,
+    assets: Arc<AssetRegistry>,
+    client: Client,
+    /// Client for BMCs with self-signed certificates
+    insecure_client: Client,
+}
+
+impl PowerController {
+    pub fn new(config: PowerConfig, assets: Arc<AssetRegistry>) -> Result<Self> {
+        let build = |verify: bool| {
+            Client::builder()
+                .timeout(Duration::from_secs(30))
+                .danger_accept_invalid_certs(!verify)
+                .build()
+                .map_err(|e| Error::network(format!("Failed to create Redfish client: {}", e)))
+        };
+        Ok(Self {
+            config,
+            assets,
+            client: build(true)?,
+            insecure_client: build(false)?,
+        })
+    }
+
+    async fn target(&self, name: &str) -> Result<Target> {
+        match self.assets.get(name).await {
+            Some(Asset { id, mac, bmc, .. }) => Ok(Target { name: id, mac, bmc }),
+            None if parse_mac(name).is_some() => Ok(Target {
+                name: name.to_string(),
+                mac: Some(name.to_string()),
+                bmc: None,
+            }),
+            None => Err(Error::not_found_with_resource(
+                format!("No asset named {}", name),
+                "asset",
+                name,
+            )),
+        }
+    }
+
+    fn credentials(&self, target: &Target) -> Option<&BmcCredentials> {
+        self.config
+            .bmc
+            .get(&target.name)
+            .or_else(|| self.config.bmc.get("default"))
+    }
+
+    /// BMC address and login, or an error naming what is missing
+    fn bmc<'a>(&'a self, target: &'a Target) -> Result<(&'a str, &'a BmcCredentials)> {
+        let bmc = target.bmc.as_deref().ok_or_else(|| {
+            Error::validation_with_field(
+                format!("{} has no BMC address in the asset inventory", target.name),
+                "bmc",
+            )
+        })?;
+        let credentials = self.credentials(target).ok_or_else(|| {
+            Error::config_with_suggestion(
+                format!("No BMC credentials for {}", target.name),
+                "Add them under infrastructure.power.bmc, keyed by asset id or `default`",
+            )
+        })?;
+        Ok((bmc, credentials))
+    }
+
+    /// Send a magic packet for `mac`
+    pub async fn wake(&self, mac: &str) -> Result<()> {
+        let bytes = parse_mac(mac).ok_or_else(|| {
+            Error::validation_with_field(format!("{} is not a MAC address", mac), "mac")
+        })?;
+        let packet = magic_packet(bytes);
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .await
+            .map_err(|e| Error::network(format!("Failed to open UDP socket: {}", e)))?;
+        socket
+            .set_broadcast(true)
+            .map_err(|e| Error::network(format!("Failed to enable broadcast: {}", e)))?;
+        let destination = (self.config.broadcast.as_str(), self.config.wol_port);
+        for _ in 0..WOL_REPEAT {
+            socket.send_to(&packet, destination).await.map_err(|e| {
+                Error::network(format!(
+                    "Failed to send magic packet to {}: {}",
+                    self.config.broadcast, e
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
+    async fn redfish(
+        &self,
+        bmc: &str,
+        credentials: &BmcCredentials,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let client = if credentials.verify_tls {
+            &self.client
+        } else {
+            &self.insecure_client
+        };
+        let url = format!("{}{}", redfish_base(bmc), path);
+        let mut request = client
+            .request(method, &url)
+            .basic_auth(&credentials.username, Some(&credentials.password));
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Redfish request to {} failed: {}", bmc, e)))?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            let message = serde_json::from_str::<Value>(&text)
+                .ok()
+                .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+                .unwrap_or(text);
+            return Err(Error::api_with_status(
+                format!("Redfish {} failed: {}", path, message),
+                "redfish",
+                status.as_u16(),
+            ));
+        }
+        if text.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&text)
+            .map_err(|e| Error::parsing(format!("Invalid Redfish response: {}", e)))
+    }
+
+    /// Path of the first computer system the BMC manages
+    async fn redfish_system(&self, bmc: &str, credentials: &BmcCredentials) -> Result<String> {
+        let systems = self
+            .redfish(bmc, credentials, Method::GET, "/redfish/v1/Systems", None)
+            .await?;
+        systems["Members"][0]["@odata.id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| Error::parsing(format!("{} lists no Redfish systems", bmc)))
+    }
+
+    async fn ipmi(&self, bmc: &str, credentials: &BmcCredentials, command: &str) -> Result<String> {
+        let output = Command::new("ipmitool")
+            .args(["-I", "lanplus", "-H", bmc_host(bmc), "-U"])
+            .arg(&credentials.username)
+            .args(["-E", "chassis", "power", command])
+            .env("IPMI_PASSWORD", &credentials.password)
+            .output()
+            .await
+            .map_err(|e| Error::service(format!("Failed to run ipmitool: {}", e)))?;
+        if !output.status.success() {
+            return Err(Error::service(format!(
+                "ipmitool chassis power {} on {} failed: {}",
+                command,
+                bmc,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Change the power state of an asset
+    pub async fn set_power(&self, name: &str, action: PowerAction) -> Result<PowerOutcome> {
+        let target = self.target(name).await?;
+        if action == PowerAction::On
+            && (target.bmc.is_none() || self.credentials(&target).is_none())
+        {
+            let mac = target.mac.as_deref().ok_or_else(|| {
+                Error::validation_with_field(
+                    format!("{} has neither a BMC nor a MAC address", target.name),
+                    "mac",
+                )
+            })?;
+            self.wake(mac).await?;
+            return Ok(PowerOutcome {
+                message: format!("Sent Wake-on-LAN to {} ({})", target.name, mac),
+                target: target.name,
+                method: "wake_on_lan",
+                power_state: None,
+            });
+        }
+        let (bmc, credentials) = self.bmc(&target)?;
+        let method = match credentials.protocol {
+            BmcProtocol::Redfish => {
+                let system = self.redfish_system(bmc, credentials).await?;
+                self.redfish(
+                    bmc,
+                    credentials,
+                    Method::POST,
+                    &format!("{}/Actions/ComputerSystem.Reset", system),
+                    Some(json!({ "ResetType": action.redfish_reset_type() })),
+                )
+                .await?;
+                "redfish"
+            }
+            BmcProtocol::Ipmi => {
+                self.ipmi(bmc, credentials, action.ipmi_command()).await?;
+                "ipmi"
+            }
+        };
+        Ok(PowerOutcome {
+            message: format!("Asked {} to {}", target.name, action.describe()),
+            target: target.name,
+            method,
+            power_state: None,
+        })
+    }
+
+    /// Power state reported by an asset's BMC
+    pub async fn power_state(&self, name: &str) -> Result<PowerOutcome> {
+        let target = self.target(name).await?;
+        let (bmc, credentials) = self.bmc(&target)?;
+        let (method, state) = match credentials.protocol {
+            BmcProtocol::Redfish => {
+                let system = self.redfish_system(bmc, credentials).await?;
+                let details = self
+                    .redfish(bmc, credentials, Method::GET, &system, None)
+                    .await?;
+                let state = details["PowerState"].as_str().unwrap_or("Unknown");
+                ("redfish", state.to_string())
+            }
+            BmcProtocol::Ipmi => {
+                let output = self.ipmi(bmc, credentials, "status").await?;
+                // "Chassis Power is on"
+                let state = output.rsplit(' ').next().unwrap_or(&output);
+                ("ipmi", capitalize(state))
+            }
+        };
+        Ok(PowerOutcome {
+            message: format!("{} is {}", target.name, state.to_lowercase()),
+            target: target.name,
+            method,
+            power_state: Some(state),
+        })
+    }
+
+    /// Get tool definitions for power control
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let target = json!({
+            "type": "string",
+            "description": "Asset id from the hardware inventory"
+        });
+        vec![
+            ToolDefinition::from_json_schema(
+                "power_on",
+                "Power on a machine through its BMC (Redfish or IPMI), or with Wake-on-LAN when it has none",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "target": {
+                            "type": "string",
+                            "description": "Asset id from the hardware inventory, or a MAC address to wake"
+                        }
+                    },
+                    "required": ["target"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "power_off",
+                "Shut down a machine through its BMC; graceful unless forced",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "target": target,
+                        "force": {"type": "boolean", "description": "Cut power instead of asking the OS to shut down", "default": false}
+                    },
+                    "required": ["target"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "power_restart",
+                "Restart a machine through its BMC",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {"target": target},
+                    "required": ["target"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "power_status",
+                "Read a machine's power state from its BMC",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {"target": target},
+                    "required": ["target"]
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    /// Execute a power tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let target = parameters
+            .get("target")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::validation_with_field("target is required", "target"))?;
+        let outcome = match name {
+            "power_on" => self.set_power(target, PowerAction::On).await?,
+            "power_off" => {
+                let force = parameters
+                    .get("force")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                self.set_power(target, PowerAction::Off { force }).await?
+            }
+            "power_restart" => self.set_power(target, PowerAction::Restart).await?,
+            "power_status" => self.power_state(target).await?,
+            _ => {
+                return Err(Error::not_found_with_resource(
+                    "Tool not found",
+                    "power_tool",
+                    name,
+                ))
+            }
+        };
+        Ok(call_result(outcome.message.clone(), json!(outcome)))
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::assets::AssetsConfig;
+    use axum::extract::State;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use tokio::net::TcpListener;
+
+    async fn registry(assets: Vec<Asset>) -> Arc<AssetRegistry> {
+        let registry = AssetRegistry::open(AssetsConfig {
+            path: None,
+            ..AssetsConfig::default()
+        })
+        .await
+        .unwrap();
+        for asset in assets {
+            registry.upsert(asset).await.unwrap();
+        }
+        Arc::new(registry)
+    }
+
+    #[tokio::test]
+    async fn wakes_machines_by_asset_or_mac() {
+        assert_eq!(
+            parse_mac("00-11-22-aa-bb-CC"),
+            Some([0x00, 0x11, 0x22, 0xaa, 0xbb, 0xcc])
+        );
+        assert_eq!(parse_mac("00:11:22:aa:bb"), None);
+        let packet = magic_packet([1, 2, 3, 4, 5, 6]);
+        assert_eq!(packet.len(), 102);
+        assert_eq!(&packet[..6], &[0xFF; 6]);
+        assert_eq!(&packet[96..], &[1, 2, 3, 4, 5, 6]);
+
+        let listener = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let assets = registry(vec![Asset {
+            id: "nas-01".to_string(),
+            mac: Some("00:11:22:33:44:55".to_string()),
+            ..Asset::default()
+        }])
+        .await;
+        let power = PowerController::new(
+            PowerConfig {
+                broadcast: "127.0.0.1".to_string(),
+                wol_port: listener.local_addr().unwrap().port(),
+                ..PowerConfig::default()
+            },
+            assets,
+        )
+        .unwrap();
+
+        let outcome = power.set_power("nas-01", PowerAction::On).await.unwrap();
+        assert_eq!(outcome.method, "wake_on_lan");
+        let mut buf = [0u8; 128];
+        let (len, _) = listener.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..len], magic_packet([0, 0x11, 0x22, 0x33, 0x44, 0x55]));
+
+        assert!(power
+            .set_power("aa:bb:cc:dd:ee:ff", PowerAction::On)
+            .await
+            .is_ok());
+        assert!(power.set_power("missing", PowerAction::On).await.is_err());
+        assert!(power.power_state("nas-01").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn drives_redfish_bmc_from_asset() {
+        let resets = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let app = Router::new()
+            .route(
+                "/redfish/v1/Systems",
+                get(|| async {
+                    Json(json!({"Members": [{"@odata.id": "/redfish/v1/Systems/System.Embedded.1"}]}))
+                }),
+            )
+            .route(
+                "/redfish/v1/Systems/System.Embedded.1",
+                get(|| async { Json(json!({"PowerState": "On"})) }),
+            )
+            .route(
+                "/redfish/v1/Systems/System.Embedded.1/Actions/ComputerSystem.Reset",
+                post(
+                    |State(resets): State<Arc<std::sync::Mutex<Vec<String>>>>,
+                     Json(body): Json<Value>| async move {
+                        resets
+                            .lock()
+                            .unwrap()
+                            .push(body["ResetType"].as_str().unwrap().to_string());
+                        axum::http::StatusCode::NO_CONTENT
+                    },
+                ),
+            )
+            .with_state(Arc::clone(&resets));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let assets = registry(vec![Asset {
+            id: "pve1".to_string(),
+            bmc: Some(format!("http://{}", address)),
+            mac: Some("00:11:22:33:44:55".to_string()),
+            ..Asset::default()
+        }])
+        .await;
+        let config: PowerConfig = serde_json::from_value(json!({
+            "bmc": {"default": {"username": "root", "password": "calvin"}}
+        }))
+        .unwrap();
+        let power = PowerController::new(config, assets).unwrap();
+
+        let on = power.set_power("pve1", PowerAction::On).await.unwrap();
+        assert_eq!(on.method, "redfish");
+        power
+            .set_power("pve1", PowerAction::Off { force: true })
+            .await
+            .unwrap();
+        assert_eq!(*resets.lock().unwrap(), ["On", "ForceOff"]);
+
+        let result = power
+            .execute_tool("power_status", json!({"target": "pve1"}))
+            .await
+            .unwrap();
+        assert_eq!(result["structuredContent"]["power_state"], "On");
+    }
+}
@@ -0,0 +1,379 @@
+//! Caddy through its admin API
+//!
+//! Routes are read from the live JSON config, including those loaded from
+//! a Caddyfile, and proxy hosts are inserted or replaced in place, so the
+//! change is lost on restart unless Caddy runs with `--resume` or config
+//! persistence. HTTPS follows the server a route is added to; Caddy
+//! issues certificates for it automatically, and they are read from its
+//! storage directory.
+
+use super::{pem_expiry, HostSpec, ProxyCertificate, ProxyKind, ProxyRoute, ReverseProxy};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use reqwest::{Client, Method};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn default_admin_url() -> String {
+    "http://localhost:2019".to_string()
+}
+
+/// Caddy instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaddyConfig {
+    #[serde(default = "default_admin_url")]
+    pub admin_url: String,
+    /// HTTP server new routes go to; by default the one on port 443, or
+    /// one without it for plain HTTP hosts
+    #[serde(default)]
+    pub server: Option<String>,
+    /// Caddy's data directory, e.g. `/data/caddy`; needed to list certificates
+    #[serde(default)]
+    pub storage_dir: Option<PathBuf>,
+}
+
+fn serves_https(server: &Value) -> bool {
+    server["listen"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|l| l.as_str())
+        .any(|l| l.ends_with(":443"))
+}
+
+fn route_hosts(route: &Value, key: &str) -> Vec<String> {
+    route["match"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m[key].as_array())
+        .flatten()
+        .filter_map(|h| h.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Upstream URLs of `reverse_proxy` handlers, including inside subroutes
+fn upstreams(handlers: &Value, found: &mut Vec<String>) {
+    for handler in handlers.as_array().into_iter().flatten() {
+        match handler["handler"].as_str() {
+            Some("reverse_proxy") => {
+                let scheme = if handler["transport"].get("tls").is_some() {
+                    "https"
+                } else {
+                    "http"
+                };
+                found.extend(
+                    handler["upstreams"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|u| u["dial"].as_str())
+                        .map(|dial| format!("{}://{}", scheme, dial)),
+                );
+            }
+            Some("subroute") => {
+                for route in handler["routes"].as_array().into_iter().flatten() {
+                    upstreams(&route["handle"], found);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Caddy instance
+pub struct Caddy {
+    client: Client,
+    config: CaddyConfig,
+}
+
+impl Caddy {
+    pub fn new(config: CaddyConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Caddy client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    async fn request(&self, method: Method, path: &str, body: Option<&Value>) -> Result<Value> {
+        let url = format!("{}{}", self.config.admin_url.trim_end_matches('/'), path);
+        let mut request = self.client.request(method, &url);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Caddy request failed: {}", e)))?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            let message = serde_json::from_str::<Value>(&text)
+                .ok()
+                .and_then(|e| e["error"].as_str().map(str::to_string))
+                .unwrap_or(text);
+            return Err(Error::api_with_status(
+                format!("Caddy {} failed: {}", path, message.trim()),
+                "caddy",
+                status.as_u16(),
+            ));
+        }
+        Ok(serde_json::from_str(&text).unwrap_or(Value::Null))
+    }
+
+    async fn servers(&self) -> Result<Map<String, Value>> {
+        Ok(self
+            .request(Method::GET, "/config/apps/http/servers", None)
+            .await?
+            .as_object()
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl ReverseProxy for Caddy {
+    fn kind(&self) -> ProxyKind {
+        ProxyKind::Caddy
+    }
+
+    async fn routes(&self) -> Result<Vec<ProxyRoute>> {
+        let mut routes = Vec::new();
+        for (name, server) in self.servers().await? {
+            let tls = serves_https(&server);
+            for (index, route) in server["routes"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .enumerate()
+            {
+                let mut backends = Vec::new();
+                upstreams(&route["handle"], &mut backends);
+                routes.push(ProxyRoute {
+                    proxy: ProxyKind::Caddy,
+                    id: route["@id"]
+                        .as_str()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("{}/{}", name, index)),
+                    hosts: route_hosts(route, "host"),
+                    paths: route_hosts(route, "path"),
+                    backends,
+                    tls,
+                    enabled: true,
+                    source: Some(name.clone()),
+                });
+            }
+        }
+        Ok(routes)
+    }
+
+    async fn certificates(&self) -> Result<Vec<ProxyCertificate>> {
+        let Some(storage) = &self.config.storage_dir else {
+            return Ok(Vec::new());
+        };
+        // certificates/<issuer>/<domain>/<domain>.crt
+        let mut certificates = Vec::new();
+        let Ok(mut issuers) = tokio::fs::read_dir(storage.join("certificates")).await else {
+            return Ok(certificates);
+        };
+        while let Ok(Some(issuer)) = issuers.next_entry().await {
+            let Ok(mut domains) = tokio::fs::read_dir(issuer.path()).await else {
+                continue;
+            };
+            while let Ok(Some(domain)) = domains.next_entry().await {
+                let name = domain.file_name().to_string_lossy().to_string();
+                let Ok(pem) = tokio::fs::read(domain.path().join(format!("{}.crt", name))).await
+                else {
+                    continue;
+                };
+                certificates.push(ProxyCertificate {
+                    proxy: ProxyKind::Caddy,
+                    // Wildcards are stored with the asterisk spelled out
+                    domains: vec![name.replace("wildcard_", "*")],
+                    issuer: Some(issuer.file_name().to_string_lossy().to_string()),
+                    expires: pem_expiry(&pem),
+                });
+            }
+        }
+        Ok(certificates)
+    }
+
+    async fn upsert_host(&self, spec: &HostSpec) -> Result<ProxyRoute> {
+        let servers = self.servers().await?;
+        let (name, server) = match &self.config.server {
+            Some(name) => servers.get_key_value(name),
+            None => servers
+                .iter()
+                .find(|(_, s)| serves_https(s) == spec.tls)
+                .or_else(|| servers.iter().next()),
+        }
+        .ok_or_else(|| {
+            Error::not_found_with_resource(
+                "Caddy has no HTTP server to add the host to",
+                "caddy_server",
+                self.config.server.as_deref().unwrap_or_default(),
+            )
+        })?;
+        let existing = server["routes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .find(|(_, r)| route_hosts(r, "host").contains(&spec.domain));
+
+        let (scheme, host, port) = spec.upstream_parts()?;
+        let mut handler = json!({
+            "handler": "reverse_proxy",
+            "upstreams": [{ "dial": format!("{}:{}", host, port) }]
+        });
+        if scheme == "https" {
+            handler["transport"] = json!({ "protocol": "http", "tls": {} });
+        }
+        let id = existing
+            .and_then(|(_, r)| r["@id"].as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("mcp-{}", spec.slug()));
+        let route = json!({
+            "@id": id,
+            "match": [{ "host": [spec.domain] }],
+            "handle": [handler],
+            "terminal": true
+        });
+        let routes = format!("/config/apps/http/servers/{}/routes", name);
+        match existing {
+            Some((_, r)) if r.get("@id").is_some() => {
+                self.request(Method::PATCH, &format!("/id/{}", id), Some(&route))
+                    .await?
+            }
+            Some((index, _)) => {
+                self.request(
+                    Method::PATCH,
+                    &format!("{}/{}", routes, index),
+                    Some(&route),
+                )
+                .await?
+            }
+            // Put new hosts first so catch-all routes keep working
+            None => {
+                self.request(Method::PUT, &format!("{}/0", routes), Some(&route))
+                    .await?
+            }
+        };
+        Ok(ProxyRoute {
+            proxy: ProxyKind::Caddy,
+            id,
+            hosts: vec![spec.domain.clone()],
+            paths: Vec::new(),
+            backends: vec![format!("{}://{}:{}", scheme, host, port)],
+            tls: serves_https(server),
+            enabled: true,
+            source: Some(name.clone()),
+        })
+    }
+
+    async fn reported_status(&self) -> Result<HashMap<String, String>> {
+        let upstreams = self
+            .request(Method::GET, "/reverse_proxy/upstreams", None)
+            .await?;
+        let mut status = HashMap::new();
+        for upstream in upstreams.as_array().into_iter().flatten() {
+            let Some(address) = upstream["address"].as_str() else {
+                continue;
+            };
+            let fails = upstream["fails"].as_u64().unwrap_or(0);
+            let state = if fails == 0 {
+                "UP".to_string()
+            } else {
+                format!("{} recent failures", fails)
+            };
+            // Caddy reports dial addresses; match both schemes used for backends
+            status.insert(format!("http://{}", address), state.clone());
+            status.insert(format!("https://{}", address), state);
+        }
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, State};
+    use axum::routing::{get, patch};
+    use axum::{Json, Router};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    type Log = Arc<std::sync::Mutex<Vec<Value>>>;
+
+    #[tokio::test]
+    async fn lists_nested_upstreams_and_replaces_existing_routes() {
+        let changes: Log = Arc::default();
+        let app = Router::new()
+            .route(
+                "/config/apps/http/servers",
+                get(|| async {
+                    Json(json!({
+                        "srv0": {
+                            "listen": [":443"],
+                            "routes": [{
+                                "match": [{"host": ["cloud.example.com"]}],
+                                "handle": [{"handler": "subroute", "routes": [{"handle": [
+                                    {"handler": "reverse_proxy", "upstreams": [{"dial": "10.0.0.4:11000"}]}
+                                ]}]}]
+                            }]
+                        }
+                    }))
+                }),
+            )
+            .route(
+                "/config/apps/http/servers/:server/routes/:index",
+                patch(
+                    |State(changes): State<Log>,
+                     Path((server, index)): Path<(String, usize)>,
+                     Json(body): Json<Value>| async move {
+                        changes
+                            .lock()
+                            .unwrap()
+                            .push(json!({"server": server, "index": index, "route": body}));
+                        Json(Value::Null)
+                    },
+                ),
+            )
+            .with_state(Arc::clone(&changes));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let caddy = Caddy::new(CaddyConfig {
+            admin_url: format!("http://{}", address),
+            server: None,
+            storage_dir: None,
+        })
+        .unwrap();
+        let routes = caddy.routes().await.unwrap();
+        assert_eq!(routes[0].id, "srv0/0");
+        assert_eq!(routes[0].hosts, ["cloud.example.com"]);
+        assert_eq!(routes[0].backends, ["http://10.0.0.4:11000"]);
+        assert!(routes[0].tls);
+
+        let route = caddy
+            .upsert_host(&HostSpec {
+                domain: "cloud.example.com".to_string(),
+                upstream: "https://10.0.0.5:11443".to_string(),
+                tls: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(route.id, "mcp-cloud-example-com");
+        let changes = changes.lock().unwrap();
+        assert_eq!(changes[0]["server"], "srv0");
+        assert_eq!(changes[0]["index"], 0);
+        let handler = &changes[0]["route"]["handle"][0];
+        assert_eq!(handler["upstreams"][0]["dial"], "10.0.0.5:11443");
+        assert_eq!(handler["transport"]["tls"], json!({}));
+    }
+}
@@ -0,0 +1,602 @@
+//! Reverse proxy management for Traefik, Caddy and Nginx Proxy Manager
+//!
+//! Every configured proxy is reduced to the same routes (hosts and paths to
+//! backends) and certificates, so one call lists what is published and
+//! which certificates are close to expiry. Proxy hosts are added or
+//! changed through each proxy's own mechanism: Traefik's file provider,
+//! Caddy's admin API and the Nginx Proxy Manager API. Backend health is
+//! checked by requesting each backend directly, next to the status the
+//! proxy itself reports where it has one.
+
+pub mod caddy;
+pub mod npm;
+pub mod traefik;
+
+pub use caddy::{Caddy, CaddyConfig};
+pub use npm::{NginxProxyManager, NpmConfig};
+pub use traefik::{Traefik, TraefikConfig};
+
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Longest wait for one backend to answer a health check
+const BACKEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn default_expiry_warning_days() -> i64 {
+    21
+}
+
+/// Reverse proxies, under `infrastructure.proxy`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    #[serde(default)]
+    pub traefik: Option<TraefikConfig>,
+    #[serde(default)]
+    pub caddy: Option<CaddyConfig>,
+    /// Nginx Proxy Manager
+    #[serde(default)]
+    pub npm: Option<NpmConfig>,
+    /// Certificates ending within this many days are flagged
+    #[serde(default = "default_expiry_warning_days")]
+    pub expiry_warning_days: i64,
+}
+
+impl ProxyConfig {
+    pub fn is_empty(&self) -> bool {
+        self.traefik.is_none() && self.caddy.is_none() && self.npm.is_none()
+    }
+}
+
+/// Proxy software
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyKind {
+    Traefik,
+    Caddy,
+    Npm,
+}
+
+impl ProxyKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Traefik => "traefik",
+            Self::Caddy => "caddy",
+            Self::Npm => "npm",
+        }
+    }
+}
+
+/// Hosts and paths a proxy sends to a set of backends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyRoute {
+    pub proxy: ProxyKind,
+    /// Router name, route id or proxy host id
+    pub id: String,
+    pub hosts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Backend URLs
+    pub backends: Vec<String>,
+    pub tls: bool,
+    pub enabled: bool,
+    /// Where the route was defined, e.g. Traefik's `docker` or `file` provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// TLS certificate a proxy serves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyCertificate {
+    pub proxy: ProxyKind,
+    pub domains: Vec<String>,
+    /// ACME resolver, issuer directory or provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl ProxyCertificate {
+    /// Days until expiry; negative once expired
+    pub fn days_left(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.expires.map(|e| (e - now).num_days())
+    }
+}
+
+/// Proxy host to add or change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostSpec {
+    pub domain: String,
+    /// Backend URL, e.g. `http://10.0.0.20:8096`
+    pub upstream: String,
+    /// Serve HTTPS with an automatically issued certificate
+    #[serde(default = "default_tls")]
+    pub tls: bool,
+}
+
+fn default_tls() -> bool {
+    true
+}
+
+impl HostSpec {
+    /// Name derived from the domain for routers, files and route ids
+    pub fn slug(&self) -> String {
+        self.domain
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect()
+    }
+
+    /// Scheme, host and port of the upstream
+    pub fn upstream_parts(&self) -> Result<(String, String, u16)> {
+        let url = url::Url::parse(&self.upstream).map_err(|e| {
+            Error::validation_with_field(
+                format!("{} is not a URL: {}", self.upstream, e),
+                "upstream",
+            )
+        })?;
+        let host = url.host_str().ok_or_else(|| {
+            Error::validation_with_field(format!("{} has no host", self.upstream), "upstream")
+        })?;
+        let port = url.port_or_known_default().unwrap_or(80);
+        Ok((url.scheme().to_string(), host.to_string(), port))
+    }
+}
+
+/// Result of requesting one backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendHealth {
+    pub proxy: ProxyKind,
+    pub route: String,
+    pub backend: String,
+    pub healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Status the proxy reports for the backend, e.g. Traefik's `UP`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reported: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A reverse proxy
+#[async_trait]
+pub trait ReverseProxy: Send + Sync {
+    fn kind(&self) -> ProxyKind;
+
+    async fn routes(&self) -> Result<Vec<ProxyRoute>>;
+
+    async fn certificates(&self) -> Result<Vec<ProxyCertificate>>;
+
+    /// Add a proxy host for `spec.domain`, or point the existing one at the new upstream
+    async fn upsert_host(&self, spec: &HostSpec) -> Result<ProxyRoute>;
+
+    /// Backend status as the proxy sees it, keyed by backend URL
+    async fn reported_status(&self) -> Result<HashMap<String, String>> {
+        Ok(HashMap::new())
+    }
+}
+
+/// Reading a DER element: tag, contents and what follows
+fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let len = rest[..count]
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        (len, &rest[count..])
+    };
+    (rest.len() >= len).then(|| (tag, &rest[..len], &rest[len..]))
+}
+
+/// `notAfter` of a DER-encoded X.509 certificate
+pub fn certificate_expiry(der: &[u8]) -> Option<DateTime<Utc>> {
+    let (_, certificate, _) = der_element(der)?;
+    let (_, tbs, _) = der_element(certificate)?;
+    let mut fields = tbs;
+    // Optional explicit version
+    if fields.first() == Some(&0xa0) {
+        fields = der_element(fields)?.2;
+    }
+    // Serial number, signature algorithm, issuer
+    for _ in 0..3 {
+        fields = der_element(fields)?.2;
+    }
+    let (_, validity, _) = der_element(fields)?;
+    let (_, _, rest) = der_element(validity)?;
+    let (tag, time, _) = der_element(rest)?;
+    let time = std::str::from_utf8(time).ok()?.trim_end_matches('Z');
+    let time = match tag {
+        // UTCTime: two-digit years from 1950 to 2049
+        0x17 => {
+            let year: u32 = time.get(..2)?.parse().ok()?;
+            format!("{}{}", if year < 50 { "20" } else { "19" }, time)
+        }
+        0x18 => time.to_string(),
+        _ => return None,
+    };
+    let parsed = NaiveDateTime::parse_from_str(&time, "%Y%m%d%H%M%S").ok()?;
+    Some(Utc.from_utc_datetime(&parsed))
+}
+
+/// Expiry of the first certificate in a PEM bundle
+pub fn pem_expiry(pem: &[u8]) -> Option<DateTime<Utc>> {
+    let certificates = rustls_pemfile::certs(&mut std::io::BufReader::new(pem)).ok()?;
+    certificate_expiry(certificates.first()?)
+}
+
+/// Every configured reverse proxy
+pub struct ProxyManager {
+    proxies: Vec<Box<dyn ReverseProxy>>,
+    expiry_warning_days: i64,
+    /// Client for backend checks; backends often have self-signed certificates
+    probe: Client,
+}
+
+impl ProxyManager {
+    pub fn new(config: ProxyConfig) -> Result<Self> {
+        let mut proxies: Vec<Box<dyn ReverseProxy>> = Vec::new();
+        if let Some(traefik) = config.traefik {
+            proxies.push(Box::new(Traefik::new(traefik)?));
+        }
+        if let Some(caddy) = config.caddy {
+            proxies.push(Box::new(Caddy::new(caddy)?));
+        }
+        if let Some(npm) = config.npm {
+            proxies.push(Box::new(NginxProxyManager::new(npm)?));
+        }
+        let probe = Client::builder()
+            .timeout(BACKEND_TIMEOUT)
+            .danger_accept_invalid_certs(true)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create health check client: {}", e)))?;
+        Ok(Self {
+            proxies,
+            expiry_warning_days: config.expiry_warning_days,
+            probe,
+        })
+    }
+
+    /// The named proxy, or all of them
+    fn select(&self, proxy: Option<&str>) -> Result<Vec<&dyn ReverseProxy>> {
+        let selected: Vec<&dyn ReverseProxy> = self
+            .proxies
+            .iter()
+            .map(|p| p.as_ref())
+            .filter(|p| proxy.is_none_or(|name| p.kind().as_str() == name))
+            .collect();
+        if selected.is_empty() {
+            return Err(Error::not_found_with_resource(
+                format!("{} is not configured", proxy.unwrap_or("A reverse proxy")),
+                "proxy",
+                proxy.unwrap_or_default(),
+            ));
+        }
+        Ok(selected)
+    }
+
+    /// Routes of the selected proxies, optionally only those serving `host`
+    pub async fn routes(&self, proxy: Option<&str>, host: Option<&str>) -> Result<Vec<ProxyRoute>> {
+        let mut routes = Vec::new();
+        for proxy in self.select(proxy)? {
+            routes.extend(proxy.routes().await?.into_iter().filter(|r| {
+                host.is_none_or(|h| r.hosts.iter().any(|rh| rh.eq_ignore_ascii_case(h)))
+            }));
+        }
+        Ok(routes)
+    }
+
+    /// Request each backend of the selected routes
+    pub async fn backend_health(
+        &self,
+        proxy: Option<&str>,
+        host: Option<&str>,
+    ) -> Result<Vec<BackendHealth>> {
+        let mut reported = HashMap::new();
+        for selected in self.select(proxy)? {
+            match selected.reported_status().await {
+                Ok(status) => {
+                    reported.insert(selected.kind(), status);
+                }
+                Err(e) => tracing::debug!(
+                    "{} backend status unavailable: {}",
+                    selected.kind().as_str(),
+                    e
+                ),
+            }
+        }
+        let reported = &reported;
+        let routes = self.routes(proxy, host).await?;
+        let checks = routes.iter().flat_map(|route| {
+            route.backends.iter().map(move |backend| async move {
+                let started = Instant::now();
+                let (healthy, status, error) = match self.probe.get(backend).send().await {
+                    Ok(response) => {
+                        let status = response.status();
+                        (!status.is_server_error(), Some(status.as_u16()), None)
+                    }
+                    Err(e) => (false, None, Some(e.to_string())),
+                };
+                BackendHealth {
+                    proxy: route.proxy,
+                    route: route.id.clone(),
+                    backend: backend.clone(),
+                    healthy,
+                    status,
+                    latency_ms: status.map(|_| started.elapsed().as_millis() as u64),
+                    reported: reported
+                        .get(&route.proxy)
+                        .and_then(|r| r.get(backend))
+                        .cloned(),
+                    error,
+                }
+            })
+        });
+        Ok(futures::future::join_all(checks).await)
+    }
+
+    /// Get tool definitions for reverse proxies
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let proxy = json!({
+            "type": "string",
+            "enum": ["traefik", "caddy", "npm"],
+            "description": "Only this proxy; all configured proxies when omitted"
+        });
+        vec![
+            ToolDefinition::from_json_schema(
+                "proxy_routes",
+                "List reverse proxy routes in Traefik, Caddy and Nginx Proxy Manager: hosts, paths, backends and TLS",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "proxy": proxy,
+                        "host": {"type": "string", "description": "Only routes serving this host name"}
+                    }
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "proxy_certificates",
+                "List the TLS certificates the reverse proxies serve and when they expire",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "proxy": proxy,
+                        "expiring_within_days": {"type": "integer", "minimum": 0, "description": "Only certificates expiring within this many days"}
+                    }
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "proxy_upsert_host",
+                "Publish a service on a domain through a reverse proxy, or point an existing proxy host at a new backend",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "domain": {"type": "string", "description": "Host name to serve, e.g. jellyfin.example.com"},
+                        "upstream": {"type": "string", "description": "Backend URL, e.g. http://10.0.0.20:8096"},
+                        "tls": {"type": "boolean", "description": "Serve HTTPS with an automatically issued certificate", "default": true},
+                        "proxy": {"type": "string", "enum": ["traefik", "caddy", "npm"], "description": "Proxy to change; required when several are configured"}
+                    },
+                    "required": ["domain", "upstream"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "proxy_backend_health",
+                "Check that the backends behind reverse proxy routes answer, with response time and the proxy's own view",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "proxy": proxy,
+                        "host": {"type": "string", "description": "Only backends of routes serving this host name"}
+                    }
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a reverse proxy tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let field = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let proxy = field("proxy");
+        match name {
+            "proxy_routes" => {
+                let routes = self.routes(proxy, field("host")).await?;
+                let mut text = format!("{} routes", routes.len());
+                for route in &routes {
+                    text.push_str(&format!(
+                        "\n{} [{}] {}{} -> {}{}",
+                        route.id,
+                        route.proxy.as_str(),
+                        route.hosts.join(", "),
+                        route.paths.join(","),
+                        route.backends.join(", "),
+                        if route.enabled { "" } else { " (disabled)" }
+                    ));
+                }
+                Ok(call_result(text, json!({ "routes": routes })))
+            }
+            "proxy_certificates" => {
+                let now = Utc::now();
+                let within = parameters
+                    .get("expiring_within_days")
+                    .and_then(|v| v.as_i64());
+                let mut certificates = Vec::new();
+                for selected in self.select(proxy)? {
+                    certificates.extend(selected.certificates().await?);
+                }
+                certificates.retain(|c| {
+                    within.is_none_or(|days| c.days_left(now).is_some_and(|left| left <= days))
+                });
+                certificates.sort_by_key(|c| c.expires);
+                let mut text = format!("{} certificates", certificates.len());
+                for certificate in &certificates {
+                    let left = certificate.days_left(now);
+                    text.push_str(&format!(
+                        "\n{} [{}]: {}{}",
+                        certificate.domains.join(", "),
+                        certificate.proxy.as_str(),
+                        match left {
+                            Some(days) if days < 0 => "expired".to_string(),
+                            Some(days) => format!("{} days left", days),
+                            None => "expiry unknown".to_string(),
+                        },
+                        if left.is_some_and(|d| d <= self.expiry_warning_days) {
+                            " (renew soon)"
+                        } else {
+                            ""
+                        }
+                    ));
+                }
+                Ok(call_result(
+                    text,
+                    json!({
+                        "certificates": certificates,
+                        "expiry_warning_days": self.expiry_warning_days,
+                    }),
+                ))
+            }
+            "proxy_upsert_host" => {
+                let spec = HostSpec {
+                    domain: field("domain")
+                        .ok_or_else(|| {
+                            Error::validation_with_field("domain is required", "domain")
+                        })?
+                        .to_string(),
+                    upstream: field("upstream")
+                        .ok_or_else(|| {
+                            Error::validation_with_field("upstream is required", "upstream")
+                        })?
+                        .to_string(),
+                    tls: parameters
+                        .get("tls")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(true),
+                };
+                spec.upstream_parts()?;
+                let selected = self.select(proxy)?;
+                let [target] = selected.as_slice() else {
+                    return Err(Error::validation_with_field(
+                        "Several reverse proxies are configured; name one",
+                        "proxy",
+                    ));
+                };
+                let route = target.upsert_host(&spec).await?;
+                Ok(call_result(
+                    format!(
+                        "{} now proxies {} to {}",
+                        target.kind().as_str(),
+                        spec.domain,
+                        spec.upstream
+                    ),
+                    json!({ "route": route }),
+                ))
+            }
+            "proxy_backend_health" => {
+                let checks = self.backend_health(proxy, field("host")).await?;
+                let down: Vec<&BackendHealth> = checks.iter().filter(|c| !c.healthy).collect();
+                let mut text = format!(
+                    "{} of {} backends healthy",
+                    checks.len() - down.len(),
+                    checks.len()
+                );
+                for check in &down {
+                    text.push_str(&format!(
+                        "\n{} ({}): {}",
+                        check.backend,
+                        check.route,
+                        check
+                            .error
+                            .clone()
+                            .or(check.status.map(|s| format!("HTTP {}", s)))
+                            .unwrap_or_default()
+                    ));
+                }
+                Ok(call_result(text, json!({ "backends": checks })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "proxy_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        if content.len() < 0x80 {
+            out.push(content.len() as u8);
+        } else {
+            out.extend([0x82, (content.len() >> 8) as u8, content.len() as u8]);
+        }
+        out.extend_from_slice(content);
+        out
+    }
+
+    #[test]
+    fn reads_certificate_expiry_from_der() {
+        let validity = [tlv(0x17, b"240101000000Z"), tlv(0x18, b"20250330120000Z")].concat();
+        let tbs = [
+            tlv(0xa0, &tlv(0x02, &[2])),
+            tlv(0x02, &[0x01; 16]),
+            tlv(0x30, &tlv(0x06, &[0x2a, 0x86, 0x48])),
+            tlv(0x30, &[0x31; 200]),
+            tlv(0x30, &validity),
+            tlv(0x30, &[]),
+        ]
+        .concat();
+        let certificate = tlv(
+            0x30,
+            &[tlv(0x30, &tbs), tlv(0x30, &[]), tlv(0x03, &[0])].concat(),
+        );
+        assert_eq!(
+            certificate_expiry(&certificate),
+            Some("2025-03-30T12:00:00Z".parse().unwrap())
+        );
+        assert_eq!(certificate_expiry(&certificate[..40]), None);
+
+        let spec = HostSpec {
+            domain: "Jellyfin.example.com".to_string(),
+            upstream: "https://10.0.0.20".to_string(),
+            tls: true,
+        };
+        assert_eq!(spec.slug(), "jellyfin-example-com");
+        assert_eq!(
+            spec.upstream_parts().unwrap(),
+            ("https".to_string(), "10.0.0.20".to_string(), 443)
+        );
+    }
+}
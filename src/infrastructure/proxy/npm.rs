@@ -0,0 +1,411 @@
+//! Nginx Proxy Manager through its API
+//!
+//! Requests carry a token from `/api/tokens`, requested with the login of
+//! an NPM user and renewed when it expires. New HTTPS hosts reuse a
+//! certificate already covering the domain, wildcards included, or ask NPM
+//! to request one from Let's Encrypt with the user's email.
+
+use super::{HostSpec, ProxyCertificate, ProxyKind, ProxyRoute, ReverseProxy};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use reqwest::{Client, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Nginx Proxy Manager instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NpmConfig {
+    /// Admin address, e.g. `http://npm.lan:81`
+    pub url: String,
+    pub email: String,
+    pub password: String,
+}
+
+fn enabled(value: &Value) -> bool {
+    value.as_bool().unwrap_or_else(|| value.as_u64() == Some(1))
+}
+
+fn backend(target: &Value) -> Option<String> {
+    Some(format!(
+        "{}://{}:{}",
+        target["forward_scheme"].as_str().unwrap_or("http"),
+        target["forward_host"].as_str()?,
+        target["forward_port"].as_u64()?
+    ))
+}
+
+fn domain_names(value: &Value) -> Vec<String> {
+    value["domain_names"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|d| d.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Whether a certificate name such as `*.example.com` covers `domain`
+fn covers(name: &str, domain: &str) -> bool {
+    match name.strip_prefix("*.") {
+        Some(parent) => domain
+            .split_once('.')
+            .is_some_and(|(_, rest)| rest.eq_ignore_ascii_case(parent)),
+        None => name.eq_ignore_ascii_case(domain),
+    }
+}
+
+fn route(host: &Value) -> ProxyRoute {
+    let locations = host["locations"].as_array().into_iter().flatten();
+    ProxyRoute {
+        proxy: ProxyKind::Npm,
+        id: host["id"].to_string(),
+        hosts: domain_names(host),
+        paths: locations
+            .clone()
+            .filter_map(|l| l["path"].as_str().map(str::to_string))
+            .collect(),
+        backends: backend(host)
+            .into_iter()
+            .chain(locations.filter_map(backend))
+            .collect(),
+        tls: host["certificate_id"].as_u64().is_some_and(|id| id > 0),
+        enabled: enabled(&host["enabled"]),
+        source: None,
+    }
+}
+
+/// Nginx Proxy Manager instance
+pub struct NginxProxyManager {
+    client: Client,
+    config: NpmConfig,
+    token: Mutex<Option<String>>,
+}
+
+impl NginxProxyManager {
+    pub fn new(config: NpmConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| {
+                Error::network(format!(
+                    "Failed to create Nginx Proxy Manager client: {}",
+                    e
+                ))
+            })?;
+        Ok(Self {
+            client,
+            config,
+            token: Mutex::new(None),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api{}", self.config.url.trim_end_matches('/'), path)
+    }
+
+    async fn token(&self) -> Result<String> {
+        let mut token = self.token.lock().await;
+        if let Some(token) = token.as_ref() {
+            return Ok(token.clone());
+        }
+        let response = self
+            .client
+            .post(self.url("/tokens"))
+            .json(&json!({ "identity": self.config.email, "secret": self.config.password }))
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Nginx Proxy Manager login failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::api_with_status(
+                "Nginx Proxy Manager rejected the login",
+                "npm",
+                status.as_u16(),
+            ));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Invalid Nginx Proxy Manager token: {}", e)))?;
+        let issued = body["token"]
+            .as_str()
+            .ok_or_else(|| Error::parsing("Nginx Proxy Manager returned no token"))?
+            .to_string();
+        *token = Some(issued.clone());
+        Ok(issued)
+    }
+
+    async fn request(&self, method: Method, path: &str, body: Option<&Value>) -> Result<Value> {
+        for attempt in 0..2 {
+            let mut request = self
+                .client
+                .request(method.clone(), self.url(path))
+                .bearer_auth(self.token().await?);
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            let response = request.send().await.map_err(|e| {
+                Error::network(format!("Nginx Proxy Manager request failed: {}", e))
+            })?;
+            let status = response.status();
+            // Tokens expire; log in again once
+            if status == StatusCode::UNAUTHORIZED && attempt == 0 {
+                *self.token.lock().await = None;
+                continue;
+            }
+            let text = response.text().await.unwrap_or_default();
+            if !status.is_success() {
+                let message = serde_json::from_str::<Value>(&text)
+                    .ok()
+                    .and_then(|e| e["error"]["message"].as_str().map(str::to_string))
+                    .unwrap_or(text);
+                return Err(Error::api_with_status(
+                    format!("Nginx Proxy Manager {} failed: {}", path, message.trim()),
+                    "npm",
+                    status.as_u16(),
+                ));
+            }
+            return serde_json::from_str(&text).map_err(|e| {
+                Error::parsing(format!("Invalid Nginx Proxy Manager response: {}", e))
+            });
+        }
+        Err(Error::api_with_status(
+            "Nginx Proxy Manager rejected the token",
+            "npm",
+            StatusCode::UNAUTHORIZED.as_u16(),
+        ))
+    }
+
+    async fn list(&self, path: &str) -> Result<Vec<Value>> {
+        Ok(self
+            .request(Method::GET, path, None)
+            .await?
+            .as_array()
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl ReverseProxy for NginxProxyManager {
+    fn kind(&self) -> ProxyKind {
+        ProxyKind::Npm
+    }
+
+    async fn routes(&self) -> Result<Vec<ProxyRoute>> {
+        Ok(self
+            .list("/nginx/proxy-hosts")
+            .await?
+            .iter()
+            .map(route)
+            .collect())
+    }
+
+    async fn certificates(&self) -> Result<Vec<ProxyCertificate>> {
+        Ok(self
+            .list("/nginx/certificates")
+            .await?
+            .into_iter()
+            .map(|certificate| ProxyCertificate {
+                proxy: ProxyKind::Npm,
+                domains: domain_names(&certificate),
+                issuer: certificate["provider"].as_str().map(str::to_string),
+                // "2025-03-30 12:00:00" in UTC, or RFC 3339 in newer releases
+                expires: certificate["expires_on"].as_str().and_then(|e| {
+                    DateTime::parse_from_rfc3339(e)
+                        .map(|e| e.with_timezone(&Utc))
+                        .ok()
+                        .or_else(|| {
+                            NaiveDateTime::parse_from_str(e, "%Y-%m-%d %H:%M:%S")
+                                .ok()
+                                .map(|e| Utc.from_utc_datetime(&e))
+                        })
+                }),
+            })
+            .collect())
+    }
+
+    async fn upsert_host(&self, spec: &HostSpec) -> Result<ProxyRoute> {
+        let (scheme, host, port) = spec.upstream_parts()?;
+        let existing = self
+            .list("/nginx/proxy-hosts")
+            .await?
+            .into_iter()
+            .find(|h| {
+                domain_names(h)
+                    .iter()
+                    .any(|d| d.eq_ignore_ascii_case(&spec.domain))
+            });
+        let certificate = if !spec.tls {
+            json!(0)
+        } else if let Some(id) = existing
+            .as_ref()
+            .and_then(|h| h["certificate_id"].as_u64())
+            .filter(|id| *id > 0)
+        {
+            json!(id)
+        } else {
+            self.list("/nginx/certificates")
+                .await?
+                .into_iter()
+                .find(|c| {
+                    domain_names(c)
+                        .iter()
+                        .any(|name| covers(name, &spec.domain))
+                })
+                .map(|c| c["id"].clone())
+                .unwrap_or_else(|| json!("new"))
+        };
+        let mut body = json!({
+            "forward_scheme": scheme,
+            "forward_host": host,
+            "forward_port": port,
+            "certificate_id": certificate,
+            "ssl_forced": spec.tls,
+            "http2_support": spec.tls,
+            "meta": {
+                "letsencrypt_agree": true,
+                "letsencrypt_email": self.config.email,
+                "dns_challenge": false
+            }
+        });
+        let host = match existing {
+            Some(existing) => {
+                self.request(
+                    Method::PUT,
+                    &format!("/nginx/proxy-hosts/{}", existing["id"]),
+                    Some(&body),
+                )
+                .await?
+            }
+            None => {
+                body["domain_names"] = json!([spec.domain]);
+                body["access_list_id"] = json!(0);
+                body["block_exploits"] = json!(true);
+                body["allow_websocket_upgrade"] = json!(true);
+                body["caching_enabled"] = json!(false);
+                body["locations"] = json!([]);
+                self.request(Method::POST, "/nginx/proxy-hosts", Some(&body))
+                    .await?
+            }
+        };
+        Ok(route(&host))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, State};
+    use axum::http::{HeaderMap, StatusCode as HttpStatus};
+    use axum::routing::{get, post, put};
+    use axum::{Json, Router};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    type Log = Arc<std::sync::Mutex<Vec<Value>>>;
+
+    #[tokio::test]
+    async fn reuses_wildcard_certificates_and_updates_hosts() {
+        let changes: Log = Arc::default();
+        let app = Router::new()
+            .route(
+                "/api/tokens",
+                post(|Json(body): Json<Value>| async move {
+                    assert_eq!(body["identity"], "admin@example.com");
+                    Json(json!({"token": "t1", "expires": "2030-01-01T00:00:00Z"}))
+                }),
+            )
+            .route(
+                "/api/nginx/proxy-hosts",
+                get(|headers: HeaderMap| async move {
+                    if headers["authorization"] != "Bearer t1" {
+                        return Err(HttpStatus::UNAUTHORIZED);
+                    }
+                    Ok(Json(json!([{
+                        "id": 7, "domain_names": ["ha.example.com"], "enabled": 1,
+                        "forward_scheme": "http", "forward_host": "10.0.0.30", "forward_port": 8123,
+                        "certificate_id": 0, "locations": []
+                    }])))
+                })
+                .post(
+                    |State(changes): State<Log>, Json(body): Json<Value>| async move {
+                        changes.lock().unwrap().push(body.clone());
+                        Json(json!({"id": 8, "enabled": true, "domain_names": body["domain_names"],
+                            "forward_scheme": body["forward_scheme"], "forward_host": body["forward_host"],
+                            "forward_port": body["forward_port"], "certificate_id": body["certificate_id"]}))
+                    },
+                ),
+            )
+            .route(
+                "/api/nginx/proxy-hosts/:id",
+                put(
+                    |State(changes): State<Log>,
+                     Path(id): Path<u64>,
+                     Json(body): Json<Value>| async move {
+                        changes.lock().unwrap().push(json!({"id": id, "body": body}));
+                        Json(json!({"id": id, "enabled": 1, "domain_names": ["ha.example.com"],
+                            "forward_host": body["forward_host"], "forward_port": body["forward_port"],
+                            "certificate_id": body["certificate_id"]}))
+                    },
+                ),
+            )
+            .route(
+                "/api/nginx/certificates",
+                get(|| async {
+                    Json(json!([{"id": 3, "provider": "letsencrypt", "domain_names": ["*.example.com"],
+                        "expires_on": "2025-03-30 12:00:00"}]))
+                }),
+            )
+            .with_state(Arc::clone(&changes));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let npm = NginxProxyManager::new(NpmConfig {
+            url: format!("http://{}", address),
+            email: "admin@example.com".to_string(),
+            password: "secret".to_string(),
+        })
+        .unwrap();
+        let routes = npm.routes().await.unwrap();
+        assert_eq!(routes[0].backends, ["http://10.0.0.30:8123"]);
+        assert!(routes[0].enabled && !routes[0].tls);
+
+        let certificates = npm.certificates().await.unwrap();
+        assert_eq!(
+            certificates[0].expires,
+            Some("2025-03-30T12:00:00Z".parse().unwrap())
+        );
+
+        let updated = npm
+            .upsert_host(&HostSpec {
+                domain: "ha.example.com".to_string(),
+                upstream: "http://10.0.0.31:8123".to_string(),
+                tls: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.id, "7");
+        assert!(updated.tls);
+        let created = npm
+            .upsert_host(&HostSpec {
+                domain: "photos.example.com".to_string(),
+                upstream: "http://10.0.0.32:2283".to_string(),
+                tls: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(created.backends, ["http://10.0.0.32:2283"]);
+
+        let changes = changes.lock().unwrap();
+        assert_eq!(changes[0]["id"], 7);
+        assert_eq!(changes[0]["body"]["forward_host"], "10.0.0.31");
+        assert_eq!(changes[0]["body"]["certificate_id"], 3);
+        assert_eq!(changes[1]["domain_names"], json!(["photos.example.com"]));
+        assert_eq!(changes[1]["certificate_id"], 3);
+        assert!(!covers("*.example.com", "a.b.example.com"));
+    }
+}
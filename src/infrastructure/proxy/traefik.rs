@@ -0,0 +1,345 @@
+//! Traefik through its API and file provider
+//!
+//! Routers and services are read from the read-only `/api`, which requires
+//! `api.insecure` or a router to `api@internal`. Traefik has no write API,
+//! so proxy hosts are written as YAML files into the directory its file
+//! provider watches. Certificates are read from the ACME storage file
+//! (`acme.json`), since the API does not list them.
+
+use super::{pem_expiry, HostSpec, ProxyCertificate, ProxyKind, ProxyRoute, ReverseProxy};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+fn default_entry_point() -> String {
+    "websecure".to_string()
+}
+
+fn default_http_entry_point() -> String {
+    "web".to_string()
+}
+
+fn default_cert_resolver() -> String {
+    "letsencrypt".to_string()
+}
+
+/// Traefik instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraefikConfig {
+    /// API address, e.g. `http://traefik.lan:8080`
+    pub api_url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Directory watched by the file provider; needed to add proxy hosts
+    #[serde(default)]
+    pub dynamic_config_dir: Option<PathBuf>,
+    /// ACME storage file; needed to list certificates
+    #[serde(default)]
+    pub acme_storage: Option<PathBuf>,
+    /// Entry point for HTTPS hosts
+    #[serde(default = "default_entry_point")]
+    pub entry_point: String,
+    /// Entry point for plain HTTP hosts
+    #[serde(default = "default_http_entry_point")]
+    pub http_entry_point: String,
+    #[serde(default = "default_cert_resolver")]
+    pub cert_resolver: String,
+}
+
+/// Host names and paths in a router rule, e.g. ``Host(`a`) && PathPrefix(`/b`)``
+fn rule_matchers(rule: &str) -> (Vec<String>, Vec<String>) {
+    static MATCHER: OnceLock<Regex> = OnceLock::new();
+    static VALUE: OnceLock<Regex> = OnceLock::new();
+    let matcher = MATCHER.get_or_init(|| {
+        Regex::new(r"\b(Host|PathPrefix|Path)\(([^)]*)\)").expect("valid matcher regex")
+    });
+    let value = VALUE.get_or_init(|| Regex::new(r"`([^`]*)`").expect("valid value regex"));
+    let (mut hosts, mut paths) = (Vec::new(), Vec::new());
+    for matched in matcher.captures_iter(rule) {
+        let target = if &matched[1] == "Host" {
+            &mut hosts
+        } else {
+            &mut paths
+        };
+        target.extend(value.captures_iter(&matched[2]).map(|v| v[1].to_string()));
+    }
+    (hosts, paths)
+}
+
+/// Traefik instance
+pub struct Traefik {
+    client: Client,
+    config: TraefikConfig,
+}
+
+impl Traefik {
+    pub fn new(config: TraefikConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Traefik client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    async fn get(&self, path: &str) -> Result<Vec<Value>> {
+        let url = format!("{}/api{}", self.config.api_url.trim_end_matches('/'), path);
+        let mut request = self.client.get(&url);
+        if let Some(username) = &self.config.username {
+            request = request.basic_auth(username, self.config.password.as_ref());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Traefik request failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::api_with_status(
+                format!("Traefik {} failed: {}", path, text.trim()),
+                "traefik",
+                status.as_u16(),
+            ));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Invalid Traefik response: {}", e)))
+    }
+
+    /// HTTP services by qualified name (`name@provider`)
+    async fn services(&self) -> Result<HashMap<String, Value>> {
+        Ok(self
+            .get("/http/services")
+            .await?
+            .into_iter()
+            .filter_map(|s| Some((s["name"].as_str()?.to_string(), s)))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl ReverseProxy for Traefik {
+    fn kind(&self) -> ProxyKind {
+        ProxyKind::Traefik
+    }
+
+    async fn routes(&self) -> Result<Vec<ProxyRoute>> {
+        let routers = self.get("/http/routers").await?;
+        let services = self.services().await?;
+        Ok(routers
+            .into_iter()
+            .map(|router| {
+                let provider = router["provider"].as_str().unwrap_or_default();
+                let (hosts, paths) = rule_matchers(router["rule"].as_str().unwrap_or_default());
+                // Services are referenced without a provider when it is the router's own
+                let service = match router["service"].as_str().unwrap_or_default() {
+                    s if s.contains('@') => s.to_string(),
+                    s => format!("{}@{}", s, provider),
+                };
+                ProxyRoute {
+                    proxy: ProxyKind::Traefik,
+                    id: router["name"].as_str().unwrap_or_default().to_string(),
+                    hosts,
+                    paths,
+                    backends: services
+                        .get(&service)
+                        .and_then(|s| s["loadBalancer"]["servers"].as_array())
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|s| s["url"].as_str().map(str::to_string))
+                        .collect(),
+                    tls: router.get("tls").is_some(),
+                    enabled: router["status"].as_str() == Some("enabled"),
+                    source: Some(provider.to_string()),
+                }
+            })
+            .collect())
+    }
+
+    async fn certificates(&self) -> Result<Vec<ProxyCertificate>> {
+        let Some(path) = &self.config.acme_storage else {
+            return Ok(Vec::new());
+        };
+        let data = tokio::fs::read(path)
+            .await
+            .map_err(|e| Error::service(format!("Cannot read {}: {}", path.display(), e)))?;
+        let storage: HashMap<String, Value> = serde_json::from_slice(&data).map_err(|e| {
+            Error::parsing(format!("Invalid ACME storage {}: {}", path.display(), e))
+        })?;
+        let mut certificates = Vec::new();
+        for (resolver, store) in storage {
+            for certificate in store["Certificates"].as_array().into_iter().flatten() {
+                let domain = &certificate["domain"];
+                let domains = domain["main"]
+                    .as_str()
+                    .into_iter()
+                    .chain(
+                        domain["sans"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|s| s.as_str()),
+                    )
+                    .map(str::to_string)
+                    .collect();
+                let expires = certificate["certificate"]
+                    .as_str()
+                    .and_then(|pem| base64::engine::general_purpose::STANDARD.decode(pem).ok())
+                    .and_then(|pem| pem_expiry(&pem));
+                certificates.push(ProxyCertificate {
+                    proxy: ProxyKind::Traefik,
+                    domains,
+                    issuer: Some(resolver.clone()),
+                    expires,
+                });
+            }
+        }
+        Ok(certificates)
+    }
+
+    async fn upsert_host(&self, spec: &HostSpec) -> Result<ProxyRoute> {
+        let directory = self.config.dynamic_config_dir.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Traefik has no file provider directory",
+                "Set infrastructure.proxy.traefik.dynamic_config_dir to the directory the file provider watches",
+            )
+        })?;
+        let name = spec.slug();
+        let mut router = json!({
+            "rule": format!("Host(`{}`)", spec.domain),
+            "service": name,
+            "entryPoints": [if spec.tls { &self.config.entry_point } else { &self.config.http_entry_point }],
+        });
+        if spec.tls {
+            router["tls"] = json!({ "certResolver": self.config.cert_resolver });
+        }
+        let dynamic = json!({
+            "http": {
+                "routers": { &name: router },
+                "services": { &name: { "loadBalancer": { "servers": [{ "url": spec.upstream }] } } }
+            }
+        });
+        let yaml = serde_yaml::to_string(&dynamic)
+            .map_err(|e| Error::internal(format!("Failed to write Traefik config: {}", e)))?;
+        let path = directory.join(format!("{}.yml", name));
+        tokio::fs::write(&path, yaml)
+            .await
+            .map_err(|e| Error::service(format!("Cannot write {}: {}", path.display(), e)))?;
+        Ok(ProxyRoute {
+            proxy: ProxyKind::Traefik,
+            id: format!("{}@file", name),
+            hosts: vec![spec.domain.clone()],
+            paths: Vec::new(),
+            backends: vec![spec.upstream.clone()],
+            tls: spec.tls,
+            enabled: true,
+            source: Some("file".to_string()),
+        })
+    }
+
+    async fn reported_status(&self) -> Result<HashMap<String, String>> {
+        Ok(self
+            .services()
+            .await?
+            .into_values()
+            .filter_map(|s| s["serverStatus"].as_object().cloned())
+            .flatten()
+            .filter_map(|(url, status)| Some((url, status.as_str()?.to_string())))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn reads_routes_and_writes_file_provider_hosts() {
+        let app = Router::new()
+            .route(
+                "/api/http/routers",
+                get(|| async {
+                    Json(json!([
+                        {"name": "jellyfin@docker", "provider": "docker", "service": "jellyfin",
+                         "rule": "Host(`media.example.com`) || Host(`tv.example.com`)",
+                         "tls": {"certResolver": "letsencrypt"}, "status": "enabled"},
+                        {"name": "api@file", "provider": "file", "service": "backend@docker",
+                         "rule": "Host(`example.com`) && PathPrefix(`/api`)", "status": "disabled"}
+                    ]))
+                }),
+            )
+            .route(
+                "/api/http/services",
+                get(|| async {
+                    Json(json!([
+                        {"name": "jellyfin@docker", "loadBalancer": {"servers": [{"url": "http://172.18.0.5:8096"}]},
+                         "serverStatus": {"http://172.18.0.5:8096": "UP"}},
+                        {"name": "backend@docker", "loadBalancer": {"servers": [{"url": "http://172.18.0.6:3000"}]},
+                         "serverStatus": {"http://172.18.0.6:3000": "DOWN"}}
+                    ]))
+                }),
+            );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let directory = tempfile::tempdir().unwrap();
+        let traefik = Traefik::new(TraefikConfig {
+            api_url: format!("http://{}", address),
+            username: None,
+            password: None,
+            dynamic_config_dir: Some(directory.path().to_path_buf()),
+            acme_storage: None,
+            entry_point: default_entry_point(),
+            http_entry_point: default_http_entry_point(),
+            cert_resolver: default_cert_resolver(),
+        })
+        .unwrap();
+
+        let routes = traefik.routes().await.unwrap();
+        assert_eq!(routes[0].hosts, ["media.example.com", "tv.example.com"]);
+        assert_eq!(routes[0].backends, ["http://172.18.0.5:8096"]);
+        assert!(routes[0].tls && routes[0].enabled);
+        assert_eq!(routes[1].paths, ["/api"]);
+        assert_eq!(routes[1].backends, ["http://172.18.0.6:3000"]);
+        assert!(!routes[1].enabled);
+        let status = traefik.reported_status().await.unwrap();
+        assert_eq!(status["http://172.18.0.6:3000"], "DOWN");
+        assert!(traefik.certificates().await.unwrap().is_empty());
+
+        let route = traefik
+            .upsert_host(&HostSpec {
+                domain: "grafana.example.com".to_string(),
+                upstream: "http://10.0.0.9:3000".to_string(),
+                tls: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(route.id, "grafana-example-com@file");
+        let written: Value = serde_yaml::from_str(
+            &std::fs::read_to_string(directory.path().join("grafana-example-com.yml")).unwrap(),
+        )
+        .unwrap();
+        let router = &written["http"]["routers"]["grafana-example-com"];
+        assert_eq!(router["rule"], "Host(`grafana.example.com`)");
+        assert_eq!(router["entryPoints"], json!(["websecure"]));
+        assert_eq!(router["tls"]["certResolver"], "letsencrypt");
+        assert_eq!(
+            written["http"]["services"]["grafana-example-com"]["loadBalancer"]["servers"][0]["url"],
+            "http://10.0.0.9:3000"
+        );
+    }
+}
@@ -0,0 +1,793 @@
+/// UPS monitoring through Network UPS Tools
+///
+/// Each configured UPS is read from its `upsd` server over the NUT network
+/// protocol. While a UPS runs on battery, the monitor shuts down the
+/// configured hosts and VMs in order once the battery is low, by NUT's own
+/// low-battery flag or the configured charge and runtime thresholds, and does
+/// so once per outage. Power events go to collaboration channels, and every
+/// reading can be pushed as gauges to the OpenTelemetry collector.
+use crate::collaboration::notify::{Notification, Notifier, Severity};
+use crate::collaboration::AttachmentField;
+use crate::error::{Error, Result};
+use crate::monitoring::{MonitoringModule, OtelDataPoint, OtelMetric, OtelMetricType};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+/// Default `upsd` port
+const NUT_PORT: u16 = 3493;
+
+/// Longest wait for one `upsd` conversation
+const NUT_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn default_poll_interval() -> u64 {
+    30
+}
+
+fn default_shutdown_charge() -> f64 {
+    20.0
+}
+
+fn default_shutdown_runtime() -> u64 {
+    300
+}
+
+fn default_shutdown_timeout() -> u64 {
+    120
+}
+
+fn default_shutdown_command() -> String {
+    "sudo shutdown -h now".to_string()
+}
+
+/// UPS monitoring configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsConfig {
+    /// UPS units to watch
+    pub units: Vec<UpsUnit>,
+    /// Seconds between readings
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval_secs: u64,
+    /// Shut down once the charge on battery is at or below this percentage
+    #[serde(default = "default_shutdown_charge")]
+    pub shutdown_charge_percent: f64,
+    /// Shut down once the runtime left on battery is at or below this many seconds
+    #[serde(default = "default_shutdown_runtime")]
+    pub shutdown_runtime_secs: u64,
+    /// Hosts and VMs to shut down, in order
+    #[serde(default)]
+    pub shutdown_targets: Vec<ShutdownTarget>,
+    /// Collaboration channels power events are posted to
+    #[serde(default)]
+    pub channels: Vec<String>,
+}
+
+impl UpsConfig {
+    /// One UPS from `NUT_UPS` (`name@host[:port]`), with `NUT_USERNAME` and `NUT_PASSWORD`
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            units: vec![UpsUnit {
+                ups: std::env::var("NUT_UPS").ok()?,
+                username: std::env::var("NUT_USERNAME").ok(),
+                password: std::env::var("NUT_PASSWORD").ok(),
+            }],
+            poll_interval_secs: default_poll_interval(),
+            shutdown_charge_percent: default_shutdown_charge(),
+            shutdown_runtime_secs: default_shutdown_runtime(),
+            shutdown_targets: Vec::new(),
+            channels: Vec::new(),
+        })
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for unit in &self.units {
+            unit.address()?;
+        }
+        for target in &self.shutdown_targets {
+            if let Some(ups) = &target.ups {
+                if !self.units.iter().any(|u| u.name() == ups) {
+                    return Err(Error::validation_with_field(
+                        format!("Shutdown target {} names unknown UPS {}", target.name, ups),
+                        "shutdown_targets",
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A UPS served by `upsd`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsUnit {
+    /// NUT identifier, `name@host[:port]`; `localhost` when no host is given
+    pub ups: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl UpsUnit {
+    /// UPS name, host and port
+    pub fn address(&self) -> Result<(&str, &str, u16)> {
+        let (name, server) = self.ups.split_once('@').unwrap_or((&self.ups, "localhost"));
+        let (host, port) = match server.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse().map_err(|_| {
+                    Error::validation_with_field(format!("Invalid port in {}", self.ups), "ups")
+                })?,
+            ),
+            None => (server, NUT_PORT),
+        };
+        if name.is_empty() || host.is_empty() {
+            return Err(Error::validation_with_field(
+                format!("{} is not a NUT UPS identifier like myups@nas", self.ups),
+                "ups",
+            ));
+        }
+        Ok((name, host, port))
+    }
+
+    pub fn name(&self) -> &str {
+        self.ups.split('@').next().unwrap_or_default()
+    }
+}
+
+/// A host or VM shut down when the battery runs low
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownTarget {
+    pub name: String,
+    /// Only when this UPS runs low; any configured UPS otherwise
+    #[serde(default)]
+    pub ups: Option<String>,
+    pub action: ShutdownAction,
+    /// Seconds to wait for the action to finish
+    #[serde(default = "default_shutdown_timeout")]
+    pub timeout_secs: u64,
+}
+
+/// How a target is shut down
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ShutdownAction {
+    /// Run a command on the host over SSH with key authentication
+    Ssh {
+        host: String,
+        #[serde(default)]
+        user: Option<String>,
+        #[serde(default)]
+        port: Option<u16>,
+        #[serde(default = "default_shutdown_command")]
+        command: String,
+    },
+    /// Run a local command, e.g. `virsh shutdown web01` or `qm shutdown 101`
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl ShutdownAction {
+    fn command(&self) -> Command {
+        match self {
+            ShutdownAction::Ssh {
+                host,
+                user,
+                port,
+                command,
+            } => {
+                let mut ssh = Command::new("ssh");
+                ssh.args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=10"]);
+                if let Some(port) = port {
+                    ssh.args(["-p", &port.to_string()]);
+                }
+                ssh.arg(match user {
+                    Some(user) => format!("{}@{}", user, host),
+                    None => host.clone(),
+                });
+                ssh.arg(command);
+                ssh
+            }
+            ShutdownAction::Command { program, args } => {
+                let mut command = Command::new(program);
+                command.args(args);
+                command
+            }
+        }
+    }
+}
+
+/// One reading of a UPS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsStatus {
+    pub ups: String,
+    /// `ups.status` flags, e.g. `OL`, `OB`, `LB`, `CHRG`
+    pub flags: Vec<String>,
+    pub on_battery: bool,
+    pub low_battery: bool,
+    pub battery_charge: Option<f64>,
+    pub runtime_secs: Option<f64>,
+    pub load_percent: Option<f64>,
+    pub input_voltage: Option<f64>,
+    pub model: Option<String>,
+    pub read_at: DateTime<Utc>,
+    /// Every variable `upsd` reported
+    pub variables: BTreeMap<String, String>,
+}
+
+impl UpsStatus {
+    pub fn from_variables(ups: &str, variables: BTreeMap<String, String>) -> Self {
+        let number = |key: &str| variables.get(key).and_then(|v| v.trim().parse().ok());
+        let flags: Vec<String> = variables
+            .get("ups.status")
+            .map(|s| s.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+        let model = [variables.get("ups.mfr"), variables.get("ups.model")]
+            .into_iter()
+            .flatten()
+            .map(|s| s.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self {
+            ups: ups.to_string(),
+            on_battery: flags.iter().any(|f| f == "OB"),
+            low_battery: flags.iter().any(|f| f == "LB"),
+            flags,
+            battery_charge: number("battery.charge"),
+            runtime_secs: number("battery.runtime"),
+            load_percent: number("ups.load"),
+            input_voltage: number("input.voltage"),
+            model: (!model.is_empty()).then_some(model),
+            read_at: Utc::now(),
+            variables,
+        }
+    }
+
+    /// Whether the battery is too low to keep the targets running
+    pub fn needs_shutdown(&self, config: &UpsConfig) -> bool {
+        self.on_battery
+            && (self.low_battery
+                || self
+                    .battery_charge
+                    .is_some_and(|c| c <= config.shutdown_charge_percent)
+                || self
+                    .runtime_secs
+                    .is_some_and(|r| r <= config.shutdown_runtime_secs as f64))
+    }
+
+    /// One-line summary
+    pub fn summary(&self) -> String {
+        let mut text = format!(
+            "{}: {}",
+            self.ups,
+            if self.on_battery {
+                "on battery"
+            } else {
+                "on mains"
+            }
+        );
+        if self.low_battery {
+            text.push_str(", battery low");
+        }
+        if let Some(charge) = self.battery_charge {
+            text.push_str(&format!(", {:.0}% charged", charge));
+        }
+        if let Some(runtime) = self.runtime_secs {
+            text.push_str(&format!(", {:.0} min left", runtime / 60.0));
+        }
+        if let Some(load) = self.load_percent {
+            text.push_str(&format!(", {:.0}% load", load));
+        }
+        text
+    }
+
+    /// Gauges for the OpenTelemetry collector
+    pub fn metrics(&self) -> Vec<OtelMetric> {
+        let labels = HashMap::from([("ups".to_string(), self.ups.clone())]);
+        let on_battery = if self.on_battery { 1.0 } else { 0.0 };
+        [
+            (
+                "ups.battery.charge",
+                "Battery charge",
+                "%",
+                self.battery_charge,
+            ),
+            (
+                "ups.battery.runtime",
+                "Runtime left on battery",
+                "s",
+                self.runtime_secs,
+            ),
+            ("ups.load", "Load", "%", self.load_percent),
+            (
+                "ups.input.voltage",
+                "Input voltage",
+                "V",
+                self.input_voltage,
+            ),
+            (
+                "ups.on_battery",
+                "1 while running on battery",
+                "1",
+                Some(on_battery),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, description, unit, value)| {
+            Some(OtelMetric {
+                name: name.to_string(),
+                description: description.to_string(),
+                unit: unit.to_string(),
+                metric_type: OtelMetricType::Gauge,
+                data_points: vec![OtelDataPoint {
+                    timestamp: self.read_at,
+                    value: value?,
+                    labels: labels.clone(),
+                }],
+            })
+        })
+        .collect()
+    }
+}
+
+/// Value of a `VAR <ups> <name> "<value>"` line
+fn parse_var(line: &str) -> Option<(String, String)> {
+    let rest = line.strip_prefix("VAR ")?;
+    let (_, rest) = rest.split_once(' ')?;
+    let (name, quoted) = rest.split_once(' ')?;
+    let quoted = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            value.extend(chars.next());
+        } else {
+            value.push(c);
+        }
+    }
+    Some((name.to_string(), value))
+}
+
+async fn send(writer: &mut OwnedWriteHalf, command: &str) -> std::io::Result<()> {
+    writer.write_all(format!("{}\n", command).as_bytes()).await
+}
+
+/// All variables of a UPS from its `upsd`
+pub async fn read_variables(unit: &UpsUnit) -> Result<BTreeMap<String, String>> {
+    let (name, host, port) = unit.address()?;
+    let conversation = async {
+        let stream = TcpStream::connect((host, port))
+            .await
+            .map_err(|e| Error::network(format!("Failed to reach upsd at {}: {}", host, e)))?;
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let io = |e: std::io::Error| Error::network(format!("upsd connection failed: {}", e));
+
+        let mut login = Vec::new();
+        if let Some(username) = &unit.username {
+            login.push(format!("USERNAME {}", username));
+        }
+        if let Some(password) = &unit.password {
+            login.push(format!("PASSWORD {}", password));
+        }
+        for command in login {
+            send(&mut writer, &command).await.map_err(io)?;
+            let reply = lines.next_line().await.map_err(io)?.unwrap_or_default();
+            if !reply.starts_with("OK") {
+                return Err(Error::protocol(format!("upsd refused login: {}", reply)));
+            }
+        }
+
+        send(&mut writer, &format!("LIST VAR {}", name))
+            .await
+            .map_err(io)?;
+        let mut variables = BTreeMap::new();
+        let end = format!("END LIST VAR {}", name);
+        while let Some(line) = lines.next_line().await.map_err(io)? {
+            if let Some(code) = line.strip_prefix("ERR ") {
+                return Err(match code.trim() {
+                    "UNKNOWN-UPS" => {
+                        Error::not_found_with_resource("UPS not found on upsd", "ups", name)
+                    }
+                    code => Error::protocol(format!("upsd error for {}: {}", name, code)),
+                });
+            }
+            if line == end {
+                break;
+            }
+            if let Some((key, value)) = parse_var(&line) {
+                variables.insert(key, value);
+            }
+        }
+        send(&mut writer, "LOGOUT").await.ok();
+        Ok(variables)
+    };
+    tokio::time::timeout(NUT_TIMEOUT, conversation)
+        .await
+        .map_err(|_| {
+            Error::timeout_with_duration(format!("upsd at {} did not answer", host), NUT_TIMEOUT)
+        })?
+}
+
+/// Result of shutting down one target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownResult {
+    pub target: String,
+    pub ok: bool,
+    pub message: String,
+}
+
+/// What the monitor remembers between readings of a UPS
+#[derive(Debug, Default)]
+struct UnitState {
+    on_battery: bool,
+    /// Shutdown already run during the current outage
+    shut_down: bool,
+}
+
+/// Watches UPS units and runs the shutdown workflow
+pub struct UpsMonitor {
+    config: UpsConfig,
+    notifier: Option<Arc<Notifier>>,
+    metrics: Option<MonitoringModule>,
+    state: Mutex<HashMap<String, UnitState>>,
+}
+
+impl UpsMonitor {
+    pub fn new(config: UpsConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            notifier: None,
+            metrics: None,
+            state: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Post power events through collaboration channels
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Push each reading to the OpenTelemetry collector configured on `monitoring`
+    pub fn with_metrics(mut self, monitoring: MonitoringModule) -> Self {
+        self.metrics = Some(monitoring);
+        self
+    }
+
+    fn unit(&self, name: &str) -> Result<&UpsUnit> {
+        self.config
+            .units
+            .iter()
+            .find(|u| u.name() == name || u.ups == name)
+            .ok_or_else(|| Error::not_found_with_resource("UPS not configured", "ups", name))
+    }
+
+    /// Current status of one UPS, or of all of them
+    pub async fn status(&self, ups: Option<&str>) -> Result<Vec<UpsStatus>> {
+        let units = match ups {
+            Some(name) => vec![self.unit(name)?],
+            None => self.config.units.iter().collect(),
+        };
+        let mut statuses = Vec::with_capacity(units.len());
+        for unit in units {
+            let variables = read_variables(unit).await?;
+            statuses.push(UpsStatus::from_variables(unit.name(), variables));
+        }
+        Ok(statuses)
+    }
+
+    /// Read every UPS, report power events and shut targets down when a battery runs low
+    pub async fn check(&self) -> Vec<ShutdownResult> {
+        let mut results = Vec::new();
+        for unit in &self.config.units {
+            let status = match read_variables(unit).await {
+                Ok(variables) => UpsStatus::from_variables(unit.name(), variables),
+                Err(e) => {
+                    tracing::warn!("Reading UPS {} failed: {}", unit.ups, e);
+                    continue;
+                }
+            };
+            if let Some(monitoring) = &self.metrics {
+                if let Err(e) = monitoring.otel_send_metrics(status.metrics()).await {
+                    tracing::warn!("Sending UPS metrics failed: {}", e);
+                }
+            }
+
+            let (was_on_battery, shut_down) = {
+                let mut state = self.state.lock().await;
+                let entry = state.entry(status.ups.clone()).or_default();
+                let previous = (entry.on_battery, entry.shut_down);
+                entry.on_battery = status.on_battery;
+                if !status.on_battery {
+                    entry.shut_down = false;
+                }
+                previous
+            };
+            if status.on_battery && !was_on_battery {
+                self.notify(&status, "is on battery", Severity::Warning, &[])
+                    .await;
+            } else if !status.on_battery && was_on_battery {
+                self.notify(&status, "is back on mains", Severity::Info, &[])
+                    .await;
+            }
+            if status.needs_shutdown(&self.config) && !shut_down {
+                let done = self.shut_down(&status.ups).await;
+                if let Some(entry) = self.state.lock().await.get_mut(&status.ups) {
+                    entry.shut_down = true;
+                }
+                self.notify(
+                    &status,
+                    "is running low; shut down dependent hosts",
+                    Severity::Critical,
+                    &done,
+                )
+                .await;
+                results.extend(done);
+            }
+        }
+        results
+    }
+
+    /// Shut down, in order, the targets that depend on `ups`
+    pub async fn shut_down(&self, ups: &str) -> Vec<ShutdownResult> {
+        let mut results = Vec::new();
+        for target in self
+            .config
+            .shutdown_targets
+            .iter()
+            .filter(|t| t.ups.as_deref().is_none_or(|u| u == ups))
+        {
+            let timeout = Duration::from_secs(target.timeout_secs);
+            let outcome =
+                tokio::time::timeout(timeout, target.action.command().kill_on_drop(true).output())
+                    .await;
+            let (ok, message) = match outcome {
+                Ok(Ok(output)) if output.status.success() => (true, "shutdown started".to_string()),
+                Ok(Ok(output)) => (
+                    false,
+                    format!(
+                        "exited with {}: {}",
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ),
+                ),
+                Ok(Err(e)) => (false, format!("could not run: {}", e)),
+                Err(_) => (
+                    false,
+                    format!("no answer within {} seconds", target.timeout_secs),
+                ),
+            };
+            if ok {
+                tracing::info!("Shut down {} for UPS {}", target.name, ups);
+            } else {
+                tracing::error!("Shutting down {} failed: {}", target.name, message);
+            }
+            results.push(ShutdownResult {
+                target: target.name.clone(),
+                ok,
+                message,
+            });
+        }
+        results
+    }
+
+    async fn notify(
+        &self,
+        status: &UpsStatus,
+        event: &str,
+        severity: Severity,
+        results: &[ShutdownResult],
+    ) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+        if self.config.channels.is_empty() {
+            return;
+        }
+        let notification = Notification {
+            title: format!("UPS {} {}", status.ups, event),
+            text: status.summary(),
+            severity,
+            fields: results
+                .iter()
+                .map(|r| AttachmentField {
+                    title: r.target.clone(),
+                    value: r.message.clone(),
+                    short: true,
+                })
+                .collect(),
+        };
+        for (channel, error) in notifier
+            .broadcast(&self.config.channels, &notification)
+            .await
+        {
+            tracing::warn!("UPS notice to {} failed: {}", channel, error);
+        }
+    }
+
+    /// Read the UPS units on the configured interval
+    pub fn start_scheduler(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let period = Duration::from_secs(self.config.poll_interval_secs.max(5));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                self.check().await;
+            }
+        })
+    }
+
+    /// Get tool definitions for UPS monitoring
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "ups_status",
+            "Read UPS status from Network UPS Tools: mains or battery, charge, runtime left and load",
+            "infrastructure",
+            json!({
+                "type": "object",
+                "properties": {
+                    "ups": {"type": "string", "description": "UPS name; all configured UPS units when omitted"}
+                }
+            }),
+            None,
+        )
+        .read_only()]
+    }
+
+    /// Execute a UPS tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "ups_status" => {
+                let ups = parameters.get("ups").and_then(|v| v.as_str());
+                let statuses = self.status(ups).await?;
+                let mut text = String::new();
+                for status in &statuses {
+                    if !text.is_empty() {
+                        text.push('\n');
+                    }
+                    text.push_str(&status.summary());
+                    if status.needs_shutdown(&self.config) {
+                        text.push_str(" (below the shutdown threshold)");
+                    }
+                }
+                Ok(call_result(
+                    text,
+                    json!({
+                        "units": statuses,
+                        "shutdown_charge_percent": self.config.shutdown_charge_percent,
+                        "shutdown_runtime_secs": self.config.shutdown_runtime_secs,
+                    }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "ups_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn parses_variables_and_decides_on_shutdown() {
+        assert_eq!(
+            parse_var(r#"VAR ups ups.model "Back-UPS \"XS\" 1400U""#),
+            Some(("ups.model".to_string(), "Back-UPS \"XS\" 1400U".to_string()))
+        );
+        let unit = UpsUnit {
+            ups: "rack@10.0.0.5:3494".to_string(),
+            username: None,
+            password: None,
+        };
+        assert_eq!(unit.address().unwrap(), ("rack", "10.0.0.5", 3494));
+
+        let config: UpsConfig = serde_json::from_value(json!({"units": []})).unwrap();
+        let status = |flags: &str, charge: &str, runtime: &str| {
+            UpsStatus::from_variables(
+                "rack",
+                BTreeMap::from([
+                    ("ups.status".to_string(), flags.to_string()),
+                    ("battery.charge".to_string(), charge.to_string()),
+                    ("battery.runtime".to_string(), runtime.to_string()),
+                ]),
+            )
+        };
+        assert!(!status("OL CHRG", "10", "60").needs_shutdown(&config));
+        assert!(!status("OB DISCHRG", "80", "1500").needs_shutdown(&config));
+        assert!(status("OB DISCHRG", "19", "1500").needs_shutdown(&config));
+        assert!(status("OB DISCHRG", "80", "240").needs_shutdown(&config));
+        assert!(status("OB LB", "80", "1500").needs_shutdown(&config));
+        assert_eq!(status("OB LB", "80", "1500").metrics().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn shuts_targets_down_once_per_outage() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let charge = Arc::new(std::sync::Mutex::new("15"));
+        let served = Arc::clone(&charge);
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let charge = *served.lock().unwrap();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut lines = BufReader::new(reader).lines();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        let reply = match line.as_str() {
+                            "USERNAME monitor" | "PASSWORD secret" => "OK\n".to_string(),
+                            "LIST VAR rack" => format!(
+                                "BEGIN LIST VAR rack\nVAR rack ups.status \"{}\"\nVAR rack battery.charge \"{}\"\nEND LIST VAR rack\n",
+                                if charge == "100" { "OL" } else { "OB DISCHRG" },
+                                charge
+                            ),
+                            "LIST VAR missing" => "ERR UNKNOWN-UPS\n".to_string(),
+                            _ => "OK Goodbye\n".to_string(),
+                        };
+                        writer.write_all(reply.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        let unit = |name: &str| UpsUnit {
+            ups: format!("{}@127.0.0.1:{}", name, port),
+            username: Some("monitor".to_string()),
+            password: Some("secret".to_string()),
+        };
+        let monitor = UpsMonitor::new(UpsConfig {
+            units: vec![unit("rack")],
+            poll_interval_secs: 30,
+            shutdown_charge_percent: 20.0,
+            shutdown_runtime_secs: 300,
+            shutdown_targets: vec![ShutdownTarget {
+                name: "pve1".to_string(),
+                ups: Some("rack".to_string()),
+                action: ShutdownAction::Command {
+                    program: "true".to_string(),
+                    args: Vec::new(),
+                },
+                timeout_secs: 10,
+            }],
+            channels: Vec::new(),
+        })
+        .unwrap();
+
+        let status = &monitor.status(Some("rack")).await.unwrap()[0];
+        assert!(status.on_battery);
+        assert_eq!(status.battery_charge, Some(15.0));
+
+        let results = monitor.check().await;
+        assert_eq!(results.len(), 1);
+        assert!(results[0].ok, "{}", results[0].message);
+        assert!(monitor.check().await.is_empty());
+
+        *charge.lock().unwrap() = "100";
+        assert!(monitor.check().await.is_empty());
+        *charge.lock().unwrap() = "15";
+        assert_eq!(monitor.check().await.len(), 1);
+
+        let missing = read_variables(&unit("missing")).await.unwrap_err();
+        assert!(missing.to_string().contains("not found"), "{}", missing);
+    }
+}
@@ -6,15 +6,22 @@
 // Core modules with performance optimizations
 pub mod client;
 pub mod config;
+pub mod entity;
 pub mod error;
 pub mod lifecycle;
 pub mod transport;
 
+// Fuzzing entry points for parsers and validators that see client input
+#[doc(hidden)]
+pub mod fuzz;
+
 // Authentication and security with zero-copy where possible
 pub mod auth;
 pub mod security;
 
 // Tools and capabilities
+pub mod i18n;
+pub mod search;
 pub mod tools;
 
 // Infrastructure and DevOps modules with efficient resource management
@@ -22,6 +29,7 @@ pub mod cicd;
 pub mod cloud;
 pub mod database;
 pub mod infrastructure;
+pub mod messaging;
 pub mod monitoring;
 
 // Collaboration and development
@@ -52,7 +60,7 @@ pub use client::Mcp;
 pub use config::Config;
 pub use error::{Error, Result};
 pub use lifecycle::LifecycleManager;
-pub use transport::{StdioTransport, Transport, WebSocketTransport};
+pub use transport::{SseTransport, StdioTransport, Transport, WebSocketTransport};
 
 // Re-export key functionality
 pub use auth::{AuthManager, Credentials};
@@ -110,6 +118,12 @@ pub async fn connect_websocket(url: &str) -> Result<LifecycleManager> {
     connect_to_server(transport).await
 }
 
+/// Connect using the SSE transport, given the server's event stream URL
+pub async fn connect_sse(url: &str) -> Result<LifecycleManager> {
+    let transport = SseTransport::new(url.to_string())?;
+    connect_to_server(transport).await
+}
+
 /// Connect using stdio transport with efficient process management
 pub async fn connect_command(command: &str, args: &[&str]) -> Result<LifecycleManager> {
     let args_vec = args.iter().map(|s| s.to_string()).collect();
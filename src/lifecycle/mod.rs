@@ -1,4 +1,5 @@
 use crate::error::{Error, Result};
+use crate::tools::stream::{ToolResultChunk, ToolResultStream, PROGRESS_METHOD};
 use crate::tools::{
     ContentBlock, ProgressInfo, SchemaValidator, ToolDefinition, ToolExecutionResult,
 };
@@ -13,7 +14,10 @@ use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, oneshot, OnceCell, RwLock};
+
+/// Chunk senders of in-flight streaming calls, by progress token
+type ProgressRoutes = Arc<RwLock<HashMap<String, mpsc::UnboundedSender<ToolResultChunk>>>>;
 
 /// Client capabilities for MCP 2025-06-18
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -132,6 +136,8 @@ pub struct LifecycleManager {
     server_capabilities: Option<ServerCapabilities>,
     elicitation_sessions: Arc<RwLock<HashMap<String, ElicitationSession>>>,
     schema_validator: Arc<RwLock<SchemaValidator>>,
+    progress_routes: ProgressRoutes,
+    progress_handler: Arc<OnceCell<()>>,
 }
 
 impl LifecycleManager {
@@ -143,13 +149,80 @@ impl LifecycleManager {
             server_capabilities: None,
             elicitation_sessions: Arc::new(RwLock::new(HashMap::with_capacity(16))), // Pre-allocate
             schema_validator: Arc::new(RwLock::new(SchemaValidator::new())),
+            progress_routes: Arc::new(RwLock::new(HashMap::new())),
+            progress_handler: Arc::new(OnceCell::new()),
         }
     }
 
     /// Initialize the lifecycle with server handshake
-    pub async fn initialize(&mut self) -> Result<()> {
-        // Simplified initialization for performance
-        Ok(())
+    ///
+    /// Sends `initialize` with the client capabilities, records the protocol
+    /// version and capabilities the server answers with, and confirms with
+    /// `notifications/initialized`. Returns the server's answer, which also
+    /// carries its name, version and locale.
+    pub async fn initialize(&mut self) -> Result<Value> {
+        let result = self
+            .call_method(
+                "initialize",
+                Some(serde_json::json!({
+                    "protocolVersion": self.client_capabilities.protocol_version,
+                    "capabilities": {
+                        "experimental": {"features": self.client_capabilities.features}
+                    },
+                    "clientInfo": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION")
+                    }
+                })),
+            )
+            .await?;
+        let protocol_version = result
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::protocol("initialize result has no protocolVersion"))?;
+        let capabilities = result.get("capabilities").cloned().unwrap_or_default();
+        self.server_capabilities = Some(ServerCapabilities {
+            protocol_version: protocol_version.to_string(),
+            tools: None,
+            prompts: Some(capabilities.get("prompts").is_some()),
+            resources: Some(capabilities.get("resources").is_some()),
+            logging: None,
+            elicitation: None,
+            auth: None,
+        });
+        self.notify("notifications/initialized", None).await?;
+        Ok(result)
+    }
+
+    /// Names, descriptions and input schemas of the server's tools
+    pub async fn list_tools(&self) -> Result<Vec<Value>> {
+        let mut result = self.call_method("tools/list", None).await?;
+        match result.get_mut("tools").map(Value::take) {
+            Some(Value::Array(tools)) => Ok(tools),
+            _ => Err(Error::protocol("tools/list result has no tools")),
+        }
+    }
+
+    /// Call a tool on the server and return its result
+    ///
+    /// The result keeps the `content` blocks and the `structuredContent` the
+    /// tool returned. Results the server marks with `isError` become errors
+    /// carrying the tool's message.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value> {
+        let result = self
+            .call_method(
+                "tools/call",
+                Some(serde_json::json!({"name": name, "arguments": arguments})),
+            )
+            .await?;
+        if result.get("isError").and_then(|e| e.as_bool()) == Some(true) {
+            let message = result
+                .pointer("/content/0/text")
+                .and_then(|t| t.as_str())
+                .unwrap_or("Tool call failed");
+            return Err(Error::service(format!("{}: {}", name, message)));
+        }
+        Ok(result)
     }
 
     /// Call a method on the transport layer (MCP protocol)
@@ -211,53 +284,118 @@ impl LifecycleManager {
         Ok(ToolExecutionResult::success(content))
     }
 
+    /// Call a tool on the server, receiving its partial output as it is produced
+    ///
+    /// The call carries a progress token; progress notifications with that
+    /// token become chunks on the returned stream, which ends when the final
+    /// result arrives. Servers that do not stream just send the result.
+    pub async fn call_tool_streaming(
+        &self,
+        name: &str,
+        arguments: Value,
+    ) -> Result<ToolResultStream> {
+        self.progress_handler
+            .get_or_try_init(|| async {
+                let routes = Arc::clone(&self.progress_routes);
+                self.register_notification_handler(Arc::new(move |method: String, params| {
+                    let routes = Arc::clone(&routes);
+                    Box::pin(async move {
+                        if method != PROGRESS_METHOD {
+                            return;
+                        }
+                        if let Some((token, chunk)) = ToolResultChunk::from_notification(&params) {
+                            if let Some(route) = routes.read().await.get(&token) {
+                                let _ = route.send(chunk);
+                            }
+                        }
+                    }) as Pin<Box<dyn Future<Output = ()> + Send>>
+                }))
+                .await
+            })
+            .await?;
+
+        let token = uuid::Uuid::new_v4().to_string();
+        let (chunks, chunk_receiver) = mpsc::unbounded_channel();
+        self.progress_routes
+            .write()
+            .await
+            .insert(token.clone(), chunks);
+        let (result, result_receiver) = oneshot::channel();
+        let lifecycle = self.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            let response = lifecycle
+                .call_method(
+                    "tools/call",
+                    Some(serde_json::json!({
+                        "name": name,
+                        "arguments": arguments,
+                        "_meta": {"progressToken": token}
+                    })),
+                )
+                .await;
+            // Dropping the route ends the chunk stream
+            lifecycle.progress_routes.write().await.remove(&token);
+            let parsed = match response {
+                Ok(response) => {
+                    lifecycle
+                        .parse_tool_result(response, &ToolDefinition::new(name, ""))
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            let _ = result.send(parsed);
+        });
+        Ok(ToolResultStream::new(chunk_receiver, result_receiver))
+    }
+
     /// Parse tool result with optimized allocations
     pub async fn parse_tool_result(
         &self,
-        response: Value,
+        mut response: Value,
         _tool_def: &ToolDefinition,
     ) -> Result<ToolExecutionResult> {
+        // Fields are moved out of the response rather than cloned, so large
+        // text blocks are never copied on their way to the caller
+
         // Check for elicitation request
-        if let Some(elicitation) = response.get("elicitationRequest") {
-            let elicitation_req: ElicitationRequest = serde_json::from_value(elicitation.clone())
+        if let Some(elicitation) = response.get_mut("elicitationRequest") {
+            let elicitation_req: ElicitationRequest = serde_json::from_value(elicitation.take())
                 .map_err(|e| {
-                Error::protocol(format!("Failed to parse elicitation request: {}", e))
-            })?;
+                    Error::protocol(format!("Failed to parse elicitation request: {}", e))
+                })?;
 
             return Ok(ToolExecutionResult::needs_elicitation(elicitation_req));
         }
 
         // Parse content blocks with pre-allocation
-        let content =
-            if let Some(content_array) = response.get("content").and_then(|c| c.as_array()) {
-                let mut content_blocks = Vec::with_capacity(content_array.len());
-                content_blocks.extend(
-                    content_array.iter().filter_map(|item| {
-                        serde_json::from_value::<ContentBlock>(item.clone()).ok()
-                    }),
-                );
+        let content = match response.get_mut("content").map(Value::take) {
+            Some(Value::Array(blocks)) => {
+                let mut content_blocks = Vec::with_capacity(blocks.len());
+                content_blocks.extend(blocks.into_iter().filter_map(ContentBlock::from_mcp_owned));
                 content_blocks
-            } else {
-                Vec::with_capacity(0)
-            };
+            }
+            _ => Vec::with_capacity(0),
+        };
 
         // Parse structured output efficiently
         let _structured_output = response
-            .get("structuredOutput")
-            .and_then(|so| serde_json::from_value::<StructuredContent>(so.clone()).ok());
+            .get_mut("structuredOutput")
+            .and_then(|so| serde_json::from_value::<StructuredContent>(so.take()).ok());
 
         // Parse resource links with pre-allocation
-        let _resource_links =
-            response
-                .get("resourceLinks")
-                .and_then(|rl| rl.as_array())
-                .map(|array| {
-                    let mut links = Vec::with_capacity(array.len());
-                    links.extend(array.iter().filter_map(|item| {
-                        serde_json::from_value::<ResourceLink>(item.clone()).ok()
-                    }));
-                    links
-                });
+        let _resource_links = match response.get_mut("resourceLinks").map(Value::take) {
+            Some(Value::Array(array)) => {
+                let mut links = Vec::with_capacity(array.len());
+                links.extend(
+                    array
+                        .into_iter()
+                        .filter_map(|item| serde_json::from_value::<ResourceLink>(item).ok()),
+                );
+                Some(links)
+            }
+            _ => None,
+        };
 
         // Check for errors with efficient string handling
         if let Some(error) = response.get("error") {
@@ -282,8 +420,8 @@ impl LifecycleManager {
         }
 
         // Check if it's a progress update
-        if let Some(progress_info) = response.get("progress") {
-            let progress: ProgressInfo = serde_json::from_value(progress_info.clone())
+        if let Some(progress_info) = response.get_mut("progress") {
+            let progress: ProgressInfo = serde_json::from_value(progress_info.take())
                 .map_err(|e| Error::protocol(format!("Failed to parse progress info: {}", e)))?;
             return Ok(ToolExecutionResult::progress(progress));
         }
@@ -485,7 +623,7 @@ impl Default for ClientCapabilities {
             tools: Some(ToolCapabilities {
                 structured_output: true,
                 resource_links: false,
-                progress_tracking: false,
+                progress_tracking: true,
                 cancellation: false,
             }),
             elicitation: None,
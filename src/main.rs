@@ -1,36 +1,24 @@
+use axum::routing::get;
+use axum::Router;
+use devops_mcp::config::ConfigWatcher;
 use devops_mcp::error::Result;
-use tracing_subscriber::EnvFilter;
-use axum::{Router, routing::{get, post}, extract::Json, response::Json as ResponseJson};
-use serde::{Deserialize, Serialize};
-use serde_json::{Value, json};
-use std::net::SocketAddr;
+use devops_mcp::homelab::{HomelabConfig, HomelabManager};
+use devops_mcp::security::PermissionPolicy;
+use devops_mcp::tools::favorites::{self, FavoriteStore};
+use devops_mcp::tools::preferences::{self, PreferenceStore};
+use devops_mcp::tools::snapshot::{self, SnapshotManager};
+use devops_mcp::tools::{
+    bulk, help, ReloadableModules, ServerModules, ToolDefinition, ToolRegistry,
+};
+use devops_mcp::transport::buffer::{BufferPool, PoolStats};
+use devops_mcp::transport::framing::{self, CompressionConfig};
+use devops_mcp::transport::tenancy::{self, TenantConfig, Tenants};
+use devops_mcp::{config, Config};
+use serde_json::{json, Value};
 use std::env;
-
-#[derive(Debug, Deserialize)]
-struct JsonRpcRequest {
-    jsonrpc: String,
-    id: Option<Value>,
-    method: String,
-    params: Option<Value>,
-}
-
-#[derive(Debug, Serialize)]
-struct JsonRpcResponse {
-    jsonrpc: String,
-    id: Option<Value>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    result: Option<Value>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<JsonRpcError>,
-}
-
-#[derive(Debug, Serialize)]
-struct JsonRpcError {
-    code: i32,
-    message: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    data: Option<Value>,
-}
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -38,10 +26,20 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter(
             EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| EnvFilter::new("devops_mcp=info,tower_http=debug"))
+                .unwrap_or_else(|_| EnvFilter::new("devops_mcp=info,audit=info,tower_http=debug")),
         )
         .init();
 
+    // `--check-config [file]` reports every problem in the config and exits
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--check-config") {
+        let path = args
+            .get(index + 1)
+            .cloned()
+            .or_else(|| env::var("MCP_CONFIG").ok());
+        std::process::exit(check_config(path));
+    }
+
     tracing::info!("Starting MCP Modules Rust server...");
 
     // Get configuration from environment
@@ -50,25 +48,60 @@ async fn main() -> Result<()> {
         .unwrap_or_else(|_| "8080".to_string())
         .parse()
         .unwrap_or(8080);
-    
-    // Create router with MCP JSON-RPC endpoint
-    let app = Router::new()
-        .route("/health", get(health_check))
-        .route("/", post(mcp_handler))
-        .route("/", get(root_handler));
+
+    let config_path = env::var("MCP_CONFIG").ok();
+    let config = match &config_path {
+        Some(path) => Config::load(path).await?,
+        None => Config::default(),
+    };
+    let mut reloadable = Vec::new();
+    let compression = config.compression.clone().unwrap_or_default();
+    // Create router with MCP JSON-RPC endpoint, one set per tenant when configured
+    let app = match config.tenants.as_ref().filter(|t| !t.is_empty()) {
+        Some(tenants) => {
+            let mut served = Tenants::new();
+            for (id, tenant) in tenants {
+                let (registry, modules) =
+                    build_registry(&tenant.module_config(id, &config), Some((id, tenant))).await?;
+                tracing::info!("Registered {} tools for tenant {}", registry.len(), id);
+                reloadable.push(modules);
+                served.add(id, tenant, transport_router(registry, &compression))?;
+            }
+            tenancy::router(Arc::new(served))
+        }
+        None => {
+            let (registry, modules) = build_registry(&config, None).await?;
+            tracing::info!("Registered {} tools", registry.len());
+            reloadable.push(modules);
+            transport_router(registry, &compression)
+        }
+    }
+    .route("/health", get(health_check))
+    .route("/health/buffers", get(buffer_stats))
+    .route("/", get(root_handler));
+
+    // Module configs follow the file without dropping connections
+    if let Some(path) = config_path {
+        let watcher = reloadable
+            .into_iter()
+            .fold(ConfigWatcher::new(path, &config), |watcher, modules| {
+                watcher.with_target(modules)
+            });
+        Arc::new(watcher).start();
+    }
 
     // Bind to address
     let addr: SocketAddr = format!("{}:{}", host, port)
         .parse()
         .map_err(|e| devops_mcp::error::Error::network(format!("Invalid address: {}", e)))?;
-    
+
     tracing::info!("MCP server listening on {}", addr);
-    
+
     // Run server
     let listener = tokio::net::TcpListener::bind(addr)
         .await
         .map_err(|e| devops_mcp::error::Error::network(format!("Failed to bind: {}", e)))?;
-    
+
     axum::serve(listener, app)
         .await
         .map_err(|e| devops_mcp::error::Error::network(format!("Server error: {}", e)))?;
@@ -76,163 +109,209 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Print the config file's problems; exit code 0 when it is valid, 1 when
+/// it has errors and 2 when it cannot be read
+fn check_config(path: Option<String>) -> i32 {
+    let Some(path) = path else {
+        eprintln!("Usage: devops-mcp --check-config <file>, or set MCP_CONFIG");
+        return 2;
+    };
+    let value = match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str::<Value>(&contents).map_err(|e| e.to_string()))
+    {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return 2;
+        }
+    };
+    let report = config::schema::check(&value);
+    println!("{}: {}", path, report);
+    if report.is_valid() {
+        0
+    } else {
+        1
+    }
+}
+
 async fn health_check() -> &'static str {
     "OK"
 }
 
+/// Transport buffer pool counters, to check the hit rate under load
+async fn buffer_stats() -> axum::Json<PoolStats> {
+    axum::Json(BufferPool::shared().stats())
+}
+
 async fn root_handler() -> &'static str {
-    "MCP Modules Rust Server - Use POST for JSON-RPC requests"
+    "MCP Modules Rust Server - Use POST for JSON-RPC requests, GET /sse for the SSE transport, /ws for WebSocket, or gRPC (mcp.v1.Mcp) over HTTP/2"
 }
 
-async fn mcp_handler(Json(request): Json<JsonRpcRequest>) -> ResponseJson<JsonRpcResponse> {
-    tracing::info!("Received MCP request: method={}, id={:?}", request.method, request.id);
-    
-    let response = match request.method.as_str() {
-        "initialize" => handle_initialize(request.id, request.params),
-        "tools/list" => handle_tools_list(request.id),
-        "tools/call" => handle_tools_call(request.id, request.params).await,
-        _ => JsonRpcResponse {
-            jsonrpc: "2.0".to_string(),
-            id: request.id,
-            result: None,
-            error: Some(JsonRpcError {
-                code: -32601,
-                message: format!("Method not found: {}", request.method),
-                data: None,
-            }),
-        },
-    };
-    
-    ResponseJson(response)
+/// JSON-RPC, SSE, WebSocket and gRPC endpoints for a registry
+fn transport_router(registry: Arc<ToolRegistry>, compression: &CompressionConfig) -> Router {
+    Arc::clone(&registry)
+        .router()
+        .layer(axum::middleware::from_fn_with_state(
+            compression.http.clone(),
+            framing::http_compression,
+        ))
+        .merge(devops_mcp::transport::sse::router(Arc::clone(&registry)))
+        .merge(devops_mcp::transport::websocket::router_with_framing(
+            Arc::clone(&registry),
+            compression.websocket.clone(),
+        ))
+        .merge(devops_mcp::transport::grpc::router(registry))
 }
 
-fn handle_initialize(id: Option<Value>, _params: Option<Value>) -> JsonRpcResponse {
-    JsonRpcResponse {
-        jsonrpc: "2.0".to_string(),
-        id,
-        result: Some(json!({
-            "protocolVersion": "2025-06-18",
-            "capabilities": {
-                "tools": {}
-            },
-            "serverInfo": {
-                "name": "devops-mcp-rust",
-                "version": "0.1.0"
-            }
-        })),
-        error: None,
+/// Register every tool served by the binary, limited to a tenant's tools if given,
+/// along with the modules behind them for reloading
+async fn build_registry(
+    config: &Config,
+    tenant: Option<(&String, &TenantConfig)>,
+) -> Result<(Arc<ToolRegistry>, Arc<ReloadableModules>)> {
+    let mut registry = ToolRegistry::new();
+    let modules = Arc::new(ServerModules::from_config(config).await?);
+    modules.register(&mut registry)?;
+    let module_tools = registry.tool_set();
+    register_demo_tools(&mut registry)?;
+
+    // Per-client defaults for arguments calls leave out
+    let preference_store =
+        Arc::new(PreferenceStore::open(config.preferences.clone().unwrap_or_default()).await?);
+    preferences::register(&mut registry, Arc::clone(&preference_store))?;
+    let favorite_store =
+        Arc::new(FavoriteStore::open(config.favorites.clone().unwrap_or_default()).await?);
+    favorites::register(&mut registry, Arc::clone(&favorite_store))?;
+
+    // Disaster recovery archives of the state above
+    let mut snapshots = SnapshotManager::new(config.snapshot.clone().unwrap_or_default())?;
+    snapshots.add_source(preference_store)?;
+    snapshots.add_source(favorite_store)?;
+    if let Some(memory) = modules.memory() {
+        snapshots.add_source(memory)?;
     }
+    snapshot::register(&registry, Arc::new(snapshots))?;
+
+    // Homelab Infrastructure Tools, except those a configured integration above serves
+    let homelab = Arc::new(HomelabManager::new(HomelabConfig::default()));
+    let homelab_tools = homelab
+        .get_tool_definitions()
+        .into_iter()
+        .filter(|definition| !registry.contains(&definition.name))
+        .collect();
+    registry.register_all(homelab_tools, move |name, arguments| {
+        let homelab = Arc::clone(&homelab);
+        async move { homelab.execute_tool(&name, arguments).await }
+    })?;
+
+    // Config checks of the file the server was started with
+    config::schema::register(&registry, env::var("MCP_CONFIG").ok().map(Into::into))?;
+
+    // Bulk tools call back into the shared registry
+    let registry = Arc::new(registry);
+    bulk::register(&registry)?;
+
+    if let Some((_, tenant)) = tenant {
+        tenant.restrict(&registry);
+    }
+
+    // Help covers everything registered above
+    help::register(&registry)?;
+
+    // Roles and per-tool permissions of the callers
+    registry.set_policy(PermissionPolicy::from_config(config)?.map(Arc::new));
+
+    let reloadable = Arc::new(ReloadableModules::new(
+        Arc::clone(&registry),
+        modules,
+        module_tools,
+        tenant.map(|(id, _)| id.clone()),
+    ));
+    Ok((registry, reloadable))
 }
 
-fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
-    let mut all_tools = Vec::new();
-    
-    // Infrastructure tools
-    all_tools.extend([
-        json!({
-            "name": "list_docker_containers",
-            "description": "List all Docker containers with their status",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "all": {
-                        "type": "boolean",
-                        "description": "Include stopped containers",
-                        "default": false
-                    }
-                }
-            }
-        }),
-        json!({
-            "name": "get_container_logs",
-            "description": "Get logs from a Docker container",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "container_id": {"type": "string", "description": "Container ID or name"},
-                    "lines": {"type": "integer", "description": "Number of lines to fetch", "default": 100}
-                },
-                "required": ["container_id"]
-            }
-        }),
-        json!({
-            "name": "list_k8s_pods",
-            "description": "List Kubernetes pods in a namespace",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "namespace": {"type": "string", "description": "Kubernetes namespace", "default": "default"}
-                }
-            }
-        }),
-        json!({
-            "name": "get_pod_logs",
-            "description": "Get logs from a Kubernetes pod",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "pod_name": {"type": "string", "description": "Pod name"},
-                    "namespace": {"type": "string", "description": "Kubernetes namespace", "default": "default"},
-                    "lines": {"type": "integer", "description": "Number of lines to fetch", "default": 100}
-                },
-                "required": ["pod_name"]
-            }
-        })
-    ]);
+/// Register a demo tool from its `tools/list` entry and a synchronous handler
+fn demo_tool(
+    registry: &mut ToolRegistry,
+    category: &str,
+    spec: Value,
+    handler: fn(&Value) -> Value,
+) -> Result<()> {
+    let definition = ToolDefinition::from_json_schema(
+        spec["name"].as_str().unwrap_or_default(),
+        spec["description"].as_str().unwrap_or_default(),
+        category,
+        spec["inputSchema"].clone(),
+        None,
+    );
+    registry.register(definition, move |arguments| async move {
+        Ok(handler(&arguments))
+    })
+}
 
-    // Database tools
-    all_tools.extend([
+fn register_demo_tools(registry: &mut ToolRegistry) -> Result<()> {
+    // Core system tools
+    demo_tool(
+        registry,
+        "core",
         json!({
-            "name": "list_databases",
-            "description": "List all available databases",
+            "name": "health_check",
+            "description": "Check system health status",
             "inputSchema": {
                 "type": "object",
-                "properties": {
-                    "provider": {
-                        "type": "string",
-                        "enum": ["postgresql", "mongodb", "supabase"],
-                        "description": "Database provider"
-                    }
-                }
+                "properties": {}
             }
         }),
+        |_| {
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": "✅ MCP Server Status: Healthy\n✅ All 25+ modules loaded successfully\n✅ Database connections available\n✅ Security module active\n✅ Infrastructure monitoring ready\n✅ Office automation available\n✅ Smart home integration active\n✅ Financial tools loaded\n✅ Research capabilities enabled"
+                }]
+            })
+        },
+    )?;
+    demo_tool(
+        registry,
+        "core",
         json!({
-            "name": "execute_query",
-            "description": "Execute a database query",
+            "name": "security_validate",
+            "description": "Validate input for security issues",
             "inputSchema": {
                 "type": "object",
                 "properties": {
-                    "provider": {
-                        "type": "string",
-                        "enum": ["postgresql", "mongodb", "supabase"],
-                        "description": "Database provider"
-                    },
-                    "database": {"type": "string", "description": "Database name"},
-                    "query": {"type": "string", "description": "Query to execute"}
+                    "input": {"type": "string", "description": "Input to validate"}
                 },
-                "required": ["provider", "database", "query"]
+                "required": ["input"]
             }
         }),
-        json!({
-            "name": "list_tables",
-            "description": "List tables in a database",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "provider": {
-                        "type": "string", 
-                        "enum": ["postgresql", "mongodb", "supabase"],
-                        "description": "Database provider"
-                    },
-                    "database": {"type": "string", "description": "Database name"}
-                },
-                "required": ["provider", "database"]
-            }
-        })
-    ]);
+        |arguments| {
+            let input = arguments
+                .get("input")
+                .and_then(|i| i.as_str())
+                .unwrap_or("");
+            let is_safe = !input.contains("<script")
+                && !input.contains("DROP TABLE")
+                && !input.contains("rm -rf")
+                && !input.contains("../");
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("🔒 Security Validation Result\n\nInput: \"{}\"\nStatus: {}\n\n🔍 Security Checks:\n✅ XSS Prevention\n✅ SQL Injection Detection\n✅ Command Injection Protection\n✅ Path Traversal Check\n\nValidation: {}",
+                        input,
+                        if is_safe { "✅ SAFE" } else { "⚠️ POTENTIAL THREAT DETECTED" },
+                        if is_safe { "Input appears safe for processing" } else { "Input contains potentially dangerous patterns" }
+                    )
+                }]
+            })
+        },
+    )?;
 
     // Office automation tools
-    all_tools.extend([
+    demo_tool(
+        registry,
+        "office",
         json!({
             "name": "create_presentation",
             "description": "Create a PowerPoint presentation",
@@ -256,6 +335,26 @@ fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
                 "required": ["title"]
             }
         }),
+        |arguments| {
+            let title = arguments
+                .get("title")
+                .and_then(|t| t.as_str())
+                .unwrap_or("Untitled Presentation");
+            let template = arguments
+                .get("template")
+                .and_then(|t| t.as_str())
+                .unwrap_or("default");
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("📊 PowerPoint Presentation Created\n\nTitle: \"{}\"\nTemplate: {}\n\n✅ Presentation structure:\n• Title slide\n• Content slides\n• Summary slide\n\n💡 Features available:\n• Custom templates\n• Dynamic content\n• Chart generation\n• Image insertion\n\nNote: Full Office integration requires Microsoft Graph API setup", title, template)
+                }]
+            })
+        },
+    )?;
+    demo_tool(
+        registry,
+        "office",
         json!({
             "name": "create_document",
             "description": "Create a Word document",
@@ -269,6 +368,26 @@ fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
                 "required": ["title", "content"]
             }
         }),
+        |arguments| {
+            let title = arguments
+                .get("title")
+                .and_then(|t| t.as_str())
+                .unwrap_or("Untitled Document");
+            let author = arguments
+                .get("author")
+                .and_then(|a| a.as_str())
+                .unwrap_or("Anonymous");
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("📄 Word Document Created\n\nTitle: \"{}\"\nAuthor: {}\n\n✅ Document features:\n• Professional formatting\n• Table of contents\n• Headers and footers\n• Style templates\n\n💡 Capabilities:\n• Rich text formatting\n• Tables and charts\n• Image insertion\n• Mail merge\n\nNote: Full Word integration requires Microsoft Graph API", title, author)
+                }]
+            })
+        },
+    )?;
+    demo_tool(
+        registry,
+        "office",
         json!({
             "name": "create_workbook",
             "description": "Create an Excel workbook",
@@ -285,11 +404,29 @@ fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
                 },
                 "required": ["title"]
             }
-        })
-    ]);
+        }),
+        |arguments| {
+            let title = arguments
+                .get("title")
+                .and_then(|t| t.as_str())
+                .unwrap_or("Untitled Workbook");
+            let author = arguments
+                .get("author")
+                .and_then(|a| a.as_str())
+                .unwrap_or("Anonymous");
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("📊 Excel Workbook Created\n\nTitle: \"{}\"\nAuthor: {}\n\n✅ Workbook structure:\n• Data worksheets\n• Charts and graphs\n• Formulas and calculations\n• Pivot tables\n\n💡 Features:\n• Data analysis\n• Statistical functions\n• Conditional formatting\n• Macro support\n\nNote: Full Excel integration requires Microsoft Graph API", title, author)
+                }]
+            })
+        },
+    )?;
 
     // Memory and AI tools
-    all_tools.extend([
+    demo_tool(
+        registry,
+        "memory",
         json!({
             "name": "create_memory",
             "description": "Create a new memory in the knowledge graph",
@@ -312,6 +449,30 @@ fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
                 "required": ["memory_type", "title", "content"]
             }
         }),
+        |arguments| {
+            let memory_type = arguments
+                .get("memory_type")
+                .and_then(|t| t.as_str())
+                .unwrap_or("knowledge");
+            let title = arguments
+                .get("title")
+                .and_then(|t| t.as_str())
+                .unwrap_or("Untitled Memory");
+            let content = arguments
+                .get("content")
+                .and_then(|c| c.as_str())
+                .unwrap_or("");
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("🧠 Memory Created\n\nType: {}\nTitle: \"{}\"\nContent: {}\nTimestamp: {}\n\n✅ Memory stored in knowledge graph\n💡 Features:\n• Semantic search\n• Relationship mapping\n• Version history\n• Tag-based organization", memory_type, title, content, std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs())
+                }]
+            })
+        },
+    )?;
+    demo_tool(
+        registry,
+        "memory",
         json!({
             "name": "search_memory",
             "description": "Search through stored memories",
@@ -328,6 +489,23 @@ fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
                 "required": ["query"]
             }
         }),
+        |arguments| {
+            let query = arguments
+                .get("query")
+                .and_then(|q| q.as_str())
+                .unwrap_or("");
+            let memory_type = arguments.get("memory_type").and_then(|t| t.as_str());
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("🔍 Memory Search Results\n\nQuery: \"{}\"\nFilter: {}\n\n📋 Found memories:\n• Related memory 1\n• Related memory 2\n• Related memory 3\n\n💡 Search features:\n• Semantic matching\n• Relevance scoring\n• Context understanding\n• Multi-type filtering", query, memory_type.unwrap_or("all types"))
+                }]
+            })
+        },
+    )?;
+    demo_tool(
+        registry,
+        "memory",
         json!({
             "name": "store_llm_response",
             "description": "Store an LLM response for future reference",
@@ -340,89 +518,33 @@ fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
                 },
                 "required": ["response"]
             }
-        })
-    ]);
-
-    // Smart Home tools
-    all_tools.extend([
-        json!({
-            "name": "ha_turn_on",
-            "description": "Turn on a Home Assistant device",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "entity_id": {"type": "string", "description": "Entity ID of the device"},
-                    "brightness": {"type": "integer", "description": "Brightness level (0-255)"},
-                    "color": {"type": "string", "description": "Color name or hex code"}
-                },
-                "required": ["entity_id"]
-            }
-        }),
-        json!({
-            "name": "ha_turn_off",
-            "description": "Turn off a Home Assistant device",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "entity_id": {"type": "string", "description": "Entity ID of the device"}
-                },
-                "required": ["entity_id"]
-            }
-        }),
-        json!({
-            "name": "ha_set_temperature",
-            "description": "Set climate control temperature",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "entity_id": {"type": "string", "description": "Climate entity ID"},
-                    "temperature": {"type": "number", "description": "Target temperature"}
-                },
-                "required": ["entity_id", "temperature"]
-            }
-        })
-    ]);
-
-    // Finance tools
-    all_tools.extend([
-        json!({
-            "name": "get_account_info",
-            "description": "Get Alpaca trading account information",
-            "inputSchema": {
-                "type": "object",
-                "properties": {}
-            }
-        }),
-        json!({
-            "name": "get_stock_quote",
-            "description": "Get real-time stock quote",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "symbol": {"type": "string", "description": "Stock symbol (e.g., AAPL)"}
-                },
-                "required": ["symbol"]
-            }
         }),
-        json!({
-            "name": "place_order",
-            "description": "Place a stock order",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "symbol": {"type": "string", "description": "Stock symbol"},
-                    "quantity": {"type": "integer", "description": "Number of shares"},
-                    "side": {"type": "string", "enum": ["buy", "sell"], "description": "Order side"},
-                    "type": {"type": "string", "enum": ["market", "limit"], "description": "Order type"},
-                    "limit_price": {"type": "number", "description": "Limit price (for limit orders)"}
-                },
-                "required": ["symbol", "quantity", "side", "type"]
-            }
-        })
-    ]);
+        |arguments| {
+            let response = arguments
+                .get("response")
+                .and_then(|r| r.as_str())
+                .unwrap_or("");
+            let context = arguments
+                .get("context")
+                .and_then(|c| c.as_str())
+                .unwrap_or("general");
+            let model = arguments
+                .get("model")
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown");
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("🤖 LLM Response Stored\n\nModel: {}\nContext: {}\nResponse: {}\nTimestamp: {}\n\n✅ Stored for future reference\n💡 Features:\n• Response analytics\n• Context preservation\n• Model comparison\n• Quality tracking", model, context, if response.len() > 100 { format!("{}...", &response[..100] )} else { response.to_string() }, std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs())
+                }]
+            })
+        },
+    )?;
 
     // Research tools
-    all_tools.extend([
+    demo_tool(
+        registry,
+        "research",
         json!({
             "name": "deep_research",
             "description": "Conduct deep research on a topic",
@@ -439,11 +561,29 @@ fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
                 },
                 "required": ["topic"]
             }
-        })
-    ]);
+        }),
+        |arguments| {
+            let topic = arguments
+                .get("topic")
+                .and_then(|t| t.as_str())
+                .unwrap_or("AI");
+            let depth = arguments
+                .get("depth")
+                .and_then(|d| d.as_str())
+                .unwrap_or("medium");
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("🔬 Deep Research: {}\n\nDepth: {}\n\n📚 Research Progress:\n✅ Gathering sources\n✅ Analyzing content\n✅ Cross-referencing\n✅ Synthesizing findings\n\n📋 Key Findings:\n• Finding 1: Important insight about {}\n• Finding 2: Current trends and developments\n• Finding 3: Future implications\n\n💡 Research complete!\nNote: Full implementation includes web scraping, academic sources, and AI analysis", topic, depth, topic)
+                }]
+            })
+        },
+    )?;
 
     // Maps and location tools
-    all_tools.extend([
+    demo_tool(
+        registry,
+        "maps",
         json!({
             "name": "query_overpass",
             "description": "Query OpenStreetMap data using Overpass QL",
@@ -456,6 +596,22 @@ fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
                 "required": ["query"]
             }
         }),
+        |arguments| {
+            let query = arguments
+                .get("query")
+                .and_then(|q| q.as_str())
+                .unwrap_or("amenity=restaurant");
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("🗺️ OpenStreetMap Query\n\nQuery: {}\n\n📍 Results:\n• Location 1: Example Restaurant\n• Location 2: Another Place\n• Location 3: Third Result\n\n✅ Query executed successfully\n💡 Real implementation provides:\n• Full Overpass QL support\n• Geospatial analysis\n• Data export options\n• Visualization tools", query)
+                }]
+            })
+        },
+    )?;
+    demo_tool(
+        registry,
+        "maps",
         json!({
             "name": "find_places",
             "description": "Find places near a location",
@@ -469,11 +625,37 @@ fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
                 },
                 "required": ["latitude", "longitude", "place_type"]
             }
-        })
-    ]);
+        }),
+        |arguments| {
+            let lat = arguments
+                .get("latitude")
+                .and_then(|l| l.as_f64())
+                .unwrap_or(40.7128);
+            let lon = arguments
+                .get("longitude")
+                .and_then(|l| l.as_f64())
+                .unwrap_or(-74.0060);
+            let place_type = arguments
+                .get("place_type")
+                .and_then(|p| p.as_str())
+                .unwrap_or("restaurant");
+            let radius = arguments
+                .get("radius")
+                .and_then(|r| r.as_f64())
+                .unwrap_or(1000.0);
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!("📍 Places Near Location\n\nCoordinates: {}, {}\nType: {}\nRadius: {}m\n\n🏪 Found places:\n• Place 1: 0.2km away\n• Place 2: 0.5km away\n• Place 3: 0.8km away\n\n✅ Search complete\n💡 Features:\n• Distance calculation\n• Rating integration\n• Route planning\n• Real-time data", lat, lon, place_type, radius)
+                }]
+            })
+        },
+    )?;
 
     // Government tools
-    all_tools.extend([
+    demo_tool(
+        registry,
+        "government",
         json!({
             "name": "search_grants",
             "description": "Search for government grants",
@@ -486,377 +668,21 @@ fn handle_tools_list(id: Option<Value>) -> JsonRpcResponse {
                 },
                 "required": ["query"]
             }
-        })
-    ]);
-
-    // Homelab Infrastructure Tools
-    let homelab_config = devops_mcp::homelab::HomelabConfig::default();
-    let homelab_manager = devops_mcp::homelab::HomelabManager::new(homelab_config);
-    let homelab_tools: Vec<_> = homelab_manager.get_tool_definitions()
-        .into_iter()
-        .map(|tool| {
+        }),
+        |arguments| {
+            let query = arguments
+                .get("query")
+                .and_then(|q| q.as_str())
+                .unwrap_or("technology");
+            let category = arguments.get("category").and_then(|c| c.as_str());
             json!({
-                "name": tool.name,
-                "description": tool.description,
-                "inputSchema": tool.parameters.unwrap_or_else(|| json!({"type": "object", "properties": {}}))
+                "content": [{
+                    "type": "text",
+                    "text": format!("🏛️ Government Grants Search\n\nQuery: \"{}\"\nCategory: {}\n\n💰 Available grants:\n• Grant 1: Technology Innovation Fund ($50,000)\n• Grant 2: Research Development Grant ($25,000)\n• Grant 3: Small Business Support ($15,000)\n\n📋 Application requirements:\n• Eligibility criteria\n• Required documentation\n• Deadline information\n\n💡 Real implementation includes:\n• Live grant databases\n• Application tracking\n• Deadline alerts\n• Eligibility matching", query, category.unwrap_or("all categories"))
+                }]
             })
-        })
-        .collect();
-    all_tools.extend(homelab_tools);
+        },
+    )?;
 
-    // Core system tools
-    all_tools.extend([
-        json!({
-            "name": "health_check",
-            "description": "Check system health status",
-            "inputSchema": {
-                "type": "object",
-                "properties": {}
-            }
-        }),
-        json!({
-            "name": "security_validate",
-            "description": "Validate input for security issues",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "input": {"type": "string", "description": "Input to validate"}
-                },
-                "required": ["input"]
-            }
-        })
-    ]);
-
-    JsonRpcResponse {
-        jsonrpc: "2.0".to_string(),
-        id,
-        result: Some(json!({"tools": all_tools})),
-        error: None,
-    }
+    Ok(())
 }
-
-async fn handle_tools_call(id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
-    if let Some(params) = params {
-        if let Some(tool_name) = params.get("name").and_then(|n| n.as_str()) {
-            let empty_args = json!({});
-            let arguments = params.get("arguments").unwrap_or(&empty_args);
-            
-            let result = match tool_name {
-                // Core system tools
-                "health_check" => json!({
-                    "content": [{
-                        "type": "text",
-                        "text": "✅ MCP Server Status: Healthy\n✅ All 25+ modules loaded successfully\n✅ Database connections available\n✅ Security module active\n✅ Infrastructure monitoring ready\n✅ Office automation available\n✅ Smart home integration active\n✅ Financial tools loaded\n✅ Research capabilities enabled"
-                    }]
-                }),
-                "security_validate" => {
-                    let input = arguments.get("input").and_then(|i| i.as_str()).unwrap_or("");
-                    let is_safe = !input.contains("<script") && !input.contains("DROP TABLE") && !input.contains("rm -rf") && !input.contains("../");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🔒 Security Validation Result\n\nInput: \"{}\"\nStatus: {}\n\n🔍 Security Checks:\n✅ XSS Prevention\n✅ SQL Injection Detection\n✅ Command Injection Protection\n✅ Path Traversal Check\n\nValidation: {}", 
-                                input, 
-                                if is_safe { "✅ SAFE" } else { "⚠️ POTENTIAL THREAT DETECTED" },
-                                if is_safe { "Input appears safe for processing" } else { "Input contains potentially dangerous patterns" }
-                            )
-                        }]
-                    })
-                },
-
-                // Infrastructure tools
-                "list_docker_containers" => {
-                    let include_all = arguments.get("all").and_then(|a| a.as_bool()).unwrap_or(false);
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🐳 Docker Containers ({})\n\n📋 Found containers from your homelab:\n• neon-postgres-leopaska (running)\n• redis-nd-leopaska (running)\n• adminer-leopaska (running)\n• coolify-leopaska (running)\n• homeassistant-leopaska (running)\n• jellyfin-leopaska (running)\n• n8n-leopaska (running)\n• spacedrive-leopaska (running)\n\n✅ Total containers: 20+\n💡 Use get_container_logs to view specific container logs", 
-                                if include_all { "all containers" } else { "running containers" }
-                            )
-                        }]
-                    })
-                },
-                "get_container_logs" => {
-                    let container_id = arguments.get("container_id").and_then(|c| c.as_str()).unwrap_or("unknown");
-                    let lines = arguments.get("lines").and_then(|l| l.as_i64()).unwrap_or(100);
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("📋 Container Logs: {}\n\n🔍 Last {} lines:\n\n[Recent log entries would appear here]\n\n💡 This is a demo response. Full implementation would:\n• Connect to Docker API\n• Fetch real container logs\n• Apply filtering and formatting", container_id, lines)
-                        }]
-                    })
-                },
-                "list_k8s_pods" => {
-                    let namespace = arguments.get("namespace").and_then(|n| n.as_str()).unwrap_or("default");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("☸️ Kubernetes Pods (namespace: {})\n\n📋 Pods in cluster:\n• example-app-deployment-123 (Running)\n• nginx-ingress-456 (Running)\n• monitoring-pod-789 (Running)\n\n✅ All pods healthy\n💡 Full K8s integration requires cluster configuration", namespace)
-                        }]
-                    })
-                },
-                "get_pod_logs" => {
-                    let pod_name = arguments.get("pod_name").and_then(|p| p.as_str()).unwrap_or("unknown");
-                    let namespace = arguments.get("namespace").and_then(|n| n.as_str()).unwrap_or("default");
-                    let lines = arguments.get("lines").and_then(|l| l.as_i64()).unwrap_or(100);
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("📋 Pod Logs: {}/{}\n\n🔍 Last {} lines:\n\n[Pod log entries would appear here]\n\n💡 This is a demo response. Full implementation would:\n• Connect to Kubernetes API\n• Fetch real pod logs\n• Apply namespace filtering", namespace, pod_name, lines)
-                        }]
-                    })
-                },
-
-                // Database tools
-                "list_databases" => {
-                    let provider = arguments.get("provider").and_then(|p| p.as_str()).unwrap_or("all");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🗄️ Available Databases ({})\n\n📋 Your homelab databases:\n• PostgreSQL (neon-postgres-leopaska:5432)\n  - Available databases: postgres, mcp_db\n• Redis (redis-nd-leopaska:6379)\n  - Key-value store active\n\n💡 Detected connections:\n✅ PostgreSQL: Ready\n✅ Redis: Active\n\nNote: MongoDB and Supabase require additional configuration", provider)
-                        }]
-                    })
-                },
-                "execute_query" => {
-                    let provider = arguments.get("provider").and_then(|p| p.as_str()).unwrap_or("postgresql");
-                    let database = arguments.get("database").and_then(|d| d.as_str()).unwrap_or("postgres");
-                    let query = arguments.get("query").and_then(|q| q.as_str()).unwrap_or("SELECT 1");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🗄️ Database Query Execution\n\nProvider: {}\nDatabase: {}\nQuery: {}\n\n📊 Results:\n[Query results would appear here]\n\n⚠️ Demo mode: Real implementation would:\n• Validate query safety\n• Connect to actual database\n• Execute and return real results\n• Apply proper formatting", provider, database, query)
-                        }]
-                    })
-                },
-                "list_tables" => {
-                    let provider = arguments.get("provider").and_then(|p| p.as_str()).unwrap_or("postgresql");
-                    let database = arguments.get("database").and_then(|d| d.as_str()).unwrap_or("postgres");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("📋 Database Tables\n\nProvider: {}\nDatabase: {}\n\n📊 Available tables:\n• users\n• sessions\n• configurations\n• logs\n• metrics\n\n💡 Demo mode: Real implementation would query actual database schema", provider, database)
-                        }]
-                    })
-                },
-
-                // Office automation tools
-                "create_presentation" => {
-                    let title = arguments.get("title").and_then(|t| t.as_str()).unwrap_or("Untitled Presentation");
-                    let template = arguments.get("template").and_then(|t| t.as_str()).unwrap_or("default");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("📊 PowerPoint Presentation Created\n\nTitle: \"{}\"\nTemplate: {}\n\n✅ Presentation structure:\n• Title slide\n• Content slides\n• Summary slide\n\n💡 Features available:\n• Custom templates\n• Dynamic content\n• Chart generation\n• Image insertion\n\nNote: Full Office integration requires Microsoft Graph API setup", title, template)
-                        }]
-                    })
-                },
-                "create_document" => {
-                    let title = arguments.get("title").and_then(|t| t.as_str()).unwrap_or("Untitled Document");
-                    let author = arguments.get("author").and_then(|a| a.as_str()).unwrap_or("Anonymous");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("📄 Word Document Created\n\nTitle: \"{}\"\nAuthor: {}\n\n✅ Document features:\n• Professional formatting\n• Table of contents\n• Headers and footers\n• Style templates\n\n💡 Capabilities:\n• Rich text formatting\n• Tables and charts\n• Image insertion\n• Mail merge\n\nNote: Full Word integration requires Microsoft Graph API", title, author)
-                        }]
-                    })
-                },
-                "create_workbook" => {
-                    let title = arguments.get("title").and_then(|t| t.as_str()).unwrap_or("Untitled Workbook");
-                    let author = arguments.get("author").and_then(|a| a.as_str()).unwrap_or("Anonymous");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("📊 Excel Workbook Created\n\nTitle: \"{}\"\nAuthor: {}\n\n✅ Workbook structure:\n• Data worksheets\n• Charts and graphs\n• Formulas and calculations\n• Pivot tables\n\n💡 Features:\n• Data analysis\n• Statistical functions\n• Conditional formatting\n• Macro support\n\nNote: Full Excel integration requires Microsoft Graph API", title, author)
-                        }]
-                    })
-                },
-
-                // Memory and AI tools
-                "create_memory" => {
-                    let memory_type = arguments.get("memory_type").and_then(|t| t.as_str()).unwrap_or("knowledge");
-                    let title = arguments.get("title").and_then(|t| t.as_str()).unwrap_or("Untitled Memory");
-                    let content = arguments.get("content").and_then(|c| c.as_str()).unwrap_or("");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🧠 Memory Created\n\nType: {}\nTitle: \"{}\"\nContent: {}\nTimestamp: {}\n\n✅ Memory stored in knowledge graph\n💡 Features:\n• Semantic search\n• Relationship mapping\n• Version history\n• Tag-based organization", memory_type, title, content, std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs())
-                        }]
-                    })
-                },
-                "search_memory" => {
-                    let query = arguments.get("query").and_then(|q| q.as_str()).unwrap_or("");
-                    let memory_type = arguments.get("memory_type").and_then(|t| t.as_str());
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🔍 Memory Search Results\n\nQuery: \"{}\"\nFilter: {}\n\n📋 Found memories:\n• Related memory 1\n• Related memory 2\n• Related memory 3\n\n💡 Search features:\n• Semantic matching\n• Relevance scoring\n• Context understanding\n• Multi-type filtering", query, memory_type.unwrap_or("all types"))
-                        }]
-                    })
-                },
-                "store_llm_response" => {
-                    let response = arguments.get("response").and_then(|r| r.as_str()).unwrap_or("");
-                    let context = arguments.get("context").and_then(|c| c.as_str()).unwrap_or("general");
-                    let model = arguments.get("model").and_then(|m| m.as_str()).unwrap_or("unknown");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🤖 LLM Response Stored\n\nModel: {}\nContext: {}\nResponse: {}\nTimestamp: {}\n\n✅ Stored for future reference\n💡 Features:\n• Response analytics\n• Context preservation\n• Model comparison\n• Quality tracking", model, context, if response.len() > 100 { format!("{}...", &response[..100] )} else { response.to_string() }, std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs())
-                        }]
-                    })
-                },
-
-                // Smart Home tools (Home Assistant)
-                "ha_turn_on" => {
-                    let entity_id = arguments.get("entity_id").and_then(|e| e.as_str()).unwrap_or("unknown");
-                    let brightness = arguments.get("brightness").and_then(|b| b.as_i64());
-                    let color = arguments.get("color").and_then(|c| c.as_str());
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🏠 Home Assistant: Turn On\n\nEntity: {}\nBrightness: {}\nColor: {}\n\n✅ Command sent to Home Assistant\n💡 Your HA instance (homeassistant-leopaska:8123)\n\nNote: Requires Home Assistant API configuration for real control", entity_id, brightness.map_or("default".to_string(), |b| b.to_string()), color.unwrap_or("default"))
-                        }]
-                    })
-                },
-                "ha_turn_off" => {
-                    let entity_id = arguments.get("entity_id").and_then(|e| e.as_str()).unwrap_or("unknown");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🏠 Home Assistant: Turn Off\n\nEntity: {}\n\n✅ Command sent to Home Assistant\n💡 Your HA instance (homeassistant-leopaska:8123)\n\nNote: Requires Home Assistant API configuration for real control", entity_id)
-                        }]
-                    })
-                },
-                "ha_set_temperature" => {
-                    let entity_id = arguments.get("entity_id").and_then(|e| e.as_str()).unwrap_or("unknown");
-                    let temperature = arguments.get("temperature").and_then(|t| t.as_f64()).unwrap_or(20.0);
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🌡️ Home Assistant: Set Temperature\n\nEntity: {}\nTarget: {}°C\n\n✅ Temperature command sent\n💡 Your HA instance (homeassistant-leopaska:8123)\n\nNote: Requires Home Assistant API configuration for real control", entity_id, temperature)
-                        }]
-                    })
-                },
-
-                // Finance tools (Alpaca)
-                "get_account_info" => json!({
-                    "content": [{
-                        "type": "text",
-                        "text": "💰 Alpaca Trading Account\n\n📊 Account Status:\n• Account ID: [Demo Account]\n• Status: Active\n• Buying Power: $10,000.00\n• Cash: $5,000.00\n• Portfolio Value: $15,000.00\n\n⚠️ Demo mode: Real implementation requires Alpaca API keys\n💡 Features available:\n• Real-time quotes\n• Order execution\n• Portfolio management\n• Risk analytics"
-                    }]
-                }),
-                "get_stock_quote" => {
-                    let symbol = arguments.get("symbol").and_then(|s| s.as_str()).unwrap_or("AAPL");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("📈 Stock Quote: {}\n\n💰 Current Price: $150.25\n📊 Day Change: +$2.35 (+1.59%)\n📈 Day High: $151.20\n📉 Day Low: $148.50\n🔢 Volume: 45,234,567\n\n⚠️ Demo data - Real implementation requires market data feed\n💡 Features:\n• Real-time quotes\n• Historical data\n• Technical indicators\n• Market analysis", symbol.to_uppercase())
-                        }]
-                    })
-                },
-                "place_order" => {
-                    let symbol = arguments.get("symbol").and_then(|s| s.as_str()).unwrap_or("AAPL");
-                    let quantity = arguments.get("quantity").and_then(|q| q.as_i64()).unwrap_or(1);
-                    let side = arguments.get("side").and_then(|s| s.as_str()).unwrap_or("buy");
-                    let order_type = arguments.get("type").and_then(|t| t.as_str()).unwrap_or("market");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("📝 Order Placed\n\nSymbol: {}\nQuantity: {} shares\nSide: {}\nType: {}\n\n✅ Order submitted\n🆔 Order ID: DEMO-123456\n💰 Estimated Value: $1,502.50\n\n⚠️ Demo mode - No real trades executed\n💡 Real implementation requires:\n• Alpaca API authentication\n• Account verification\n• Compliance checks", symbol.to_uppercase(), quantity, side.to_uppercase(), order_type.to_uppercase())
-                        }]
-                    })
-                },
-
-                // Research tools
-                "deep_research" => {
-                    let topic = arguments.get("topic").and_then(|t| t.as_str()).unwrap_or("AI");
-                    let depth = arguments.get("depth").and_then(|d| d.as_str()).unwrap_or("medium");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🔬 Deep Research: {}\n\nDepth: {}\n\n📚 Research Progress:\n✅ Gathering sources\n✅ Analyzing content\n✅ Cross-referencing\n✅ Synthesizing findings\n\n📋 Key Findings:\n• Finding 1: Important insight about {}\n• Finding 2: Current trends and developments\n• Finding 3: Future implications\n\n💡 Research complete!\nNote: Full implementation includes web scraping, academic sources, and AI analysis", topic, depth, topic)
-                        }]
-                    })
-                },
-
-                // Maps and location tools
-                "query_overpass" => {
-                    let query = arguments.get("query").and_then(|q| q.as_str()).unwrap_or("amenity=restaurant");
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🗺️ OpenStreetMap Query\n\nQuery: {}\n\n📍 Results:\n• Location 1: Example Restaurant\n• Location 2: Another Place\n• Location 3: Third Result\n\n✅ Query executed successfully\n💡 Real implementation provides:\n• Full Overpass QL support\n• Geospatial analysis\n• Data export options\n• Visualization tools", query)
-                        }]
-                    })
-                },
-                "find_places" => {
-                    let lat = arguments.get("latitude").and_then(|l| l.as_f64()).unwrap_or(40.7128);
-                    let lon = arguments.get("longitude").and_then(|l| l.as_f64()).unwrap_or(-74.0060);
-                    let place_type = arguments.get("place_type").and_then(|p| p.as_str()).unwrap_or("restaurant");
-                    let radius = arguments.get("radius").and_then(|r| r.as_f64()).unwrap_or(1000.0);
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("📍 Places Near Location\n\nCoordinates: {}, {}\nType: {}\nRadius: {}m\n\n🏪 Found places:\n• Place 1: 0.2km away\n• Place 2: 0.5km away\n• Place 3: 0.8km away\n\n✅ Search complete\n💡 Features:\n• Distance calculation\n• Rating integration\n• Route planning\n• Real-time data", lat, lon, place_type, radius)
-                        }]
-                    })
-                },
-
-                // Government tools
-                "search_grants" => {
-                    let query = arguments.get("query").and_then(|q| q.as_str()).unwrap_or("technology");
-                    let category = arguments.get("category").and_then(|c| c.as_str());
-                    json!({
-                        "content": [{
-                            "type": "text",
-                            "text": format!("🏛️ Government Grants Search\n\nQuery: \"{}\"\nCategory: {}\n\n💰 Available grants:\n• Grant 1: Technology Innovation Fund ($50,000)\n• Grant 2: Research Development Grant ($25,000)\n• Grant 3: Small Business Support ($15,000)\n\n📋 Application requirements:\n• Eligibility criteria\n• Required documentation\n• Deadline information\n\n💡 Real implementation includes:\n• Live grant databases\n• Application tracking\n• Deadline alerts\n• Eligibility matching", query, category.unwrap_or("all categories"))
-                        }]
-                    })
-                },
-
-                // Homelab Infrastructure Tools - delegate to homelab manager
-                "traefik_list_services" | "traefik_service_health" | "prometheus_query" | 
-                "grafana_dashboards" | "service_health_check" | "coolify_deployments" | 
-                "n8n_workflows" | "uptime_monitors" | "authelia_users" | 
-                "vaultwarden_status" | "vector_logs" => {
-                    let homelab_config = devops_mcp::homelab::HomelabConfig::default();
-                    let homelab_manager = devops_mcp::homelab::HomelabManager::new(homelab_config);
-                    
-                    match homelab_manager.execute_tool(tool_name, arguments.clone()).await {
-                        Ok(result) => result,
-                        Err(e) => json!({
-                            "content": [{
-                                "type": "text",
-                                "text": format!("❌ Homelab Tool Error: {}\n\nTool: {}\nError: {}", tool_name, tool_name, e)
-                            }]
-                        })
-                    }
-                },
-
-                _ => json!({
-                    "content": [{
-                        "type": "text",
-                        "text": format!("❌ Tool Not Implemented: {}\n\n🔧 Available tool categories:\n• Infrastructure (Docker, Kubernetes)\n• Database (PostgreSQL, MongoDB, Supabase)\n• Office (PowerPoint, Word, Excel)\n• Smart Home (Home Assistant)\n• Finance (Alpaca Trading)\n• Research (Deep Research)\n• Maps (OpenStreetMap)\n• Government (Grants)\n• Memory (Knowledge Graph)\n• AI (LLM Storage)\n• Security (Validation)\n\n💡 Total tools available: 25+", tool_name)
-                    }]
-                })
-            };
-            
-            return JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                result: Some(result),
-                error: None,
-            };
-        }
-    }
-    
-    JsonRpcResponse {
-        jsonrpc: "2.0".to_string(),
-        id,
-        result: None,
-        error: Some(JsonRpcError {
-            code: -32602,
-            message: "Invalid params".to_string(),
-            data: None,
-        }),
-    }
-}
\ No newline at end of file
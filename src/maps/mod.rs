@@ -1,5 +1,20 @@
 /// OpenStreetMap module for geographic data access
 pub mod osm;
+/// Static map image rendering
+pub mod render;
+/// Travel-time routing
+pub mod routing;
+/// Timezone, sun time and meeting scheduling utilities
+pub mod time;
+/// GPX/KML track import and geofencing
+pub mod tracks;
+/// Weather forecasts and alerts
+pub mod weather;
 
 // Re-export key types
 pub use osm::{BoundingBox, Node, OsmClient, OsmQueryResult, Point, Relation, Way};
+pub use render::{MapRenderConfig, MapRequest, RenderedMap, StaticMapRenderer, TileConfig};
+pub use routing::{Route, RoutingClient, RoutingConfig, TravelMode};
+pub use time::{MeetingRequest, MeetingSlot, Participant, SunTimes, TimeUtils, TimezoneInfo};
+pub use tracks::{Geofence, GeofenceManager, Track, TrackFile, TrackStats, TrackTools, Waypoint};
+pub use weather::{Forecast, Severity, WeatherAlert, WeatherClient, WeatherConfig};
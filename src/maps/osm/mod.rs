@@ -1,5 +1,6 @@
 use crate::error::{Error, Result};
 use crate::lifecycle::LifecycleManager;
+use crate::maps::render::{MapRenderConfig, MapRequest, StaticMapRenderer};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -417,7 +418,7 @@ impl<'a> OsmClient<'a> {
         )))
     }
 
-    /// Generate an image of a map
+    /// Generate a PNG image of a map
     pub async fn generate_map_image(
         &self,
         center: Point,
@@ -425,14 +426,15 @@ impl<'a> OsmClient<'a> {
         width: u32,
         height: u32,
     ) -> Result<Vec<u8>> {
-        // This would typically use a service like Mapbox or OpenStreetMap tiles
-        // For now, we'll return a placeholder
-        Err(Error::service(format!(
-            "Map image generation requires a map rendering service. 
-            Consider using the center point ({}, {}) with zoom level {} 
-            and dimensions {}x{} to query an external map service.",
-            center.lon, center.lat, zoom, width, height
-        )))
+        let renderer = StaticMapRenderer::new(MapRenderConfig::default())?;
+        let request = MapRequest {
+            width,
+            height,
+            center: Some(center),
+            zoom: Some(zoom),
+            ..Default::default()
+        };
+        Ok(renderer.render(&request).await?.png)
     }
 
     /// Get registered tools
@@ -0,0 +1,226 @@
+//! RGBA raster canvas with anti-aliased drawing primitives
+
+use crate::error::{Error, Result};
+
+/// An RGBA colour
+pub type Color = [u8; 4];
+
+/// Parse `#rrggbb`, `#rrggbbaa` or a basic colour name
+pub fn parse_color(value: &str) -> Result<Color> {
+    let named = match value.to_lowercase().as_str() {
+        "red" => Some([220, 38, 38, 255]),
+        "blue" => Some([37, 99, 235, 255]),
+        "green" => Some([22, 163, 74, 255]),
+        "orange" => Some([234, 88, 12, 255]),
+        "purple" => Some([147, 51, 234, 255]),
+        "black" => Some([0, 0, 0, 255]),
+        "white" => Some([255, 255, 255, 255]),
+        "gray" | "grey" => Some([107, 114, 128, 255]),
+        _ => None,
+    };
+    if let Some(color) = named {
+        return Ok(color);
+    }
+
+    let invalid = || Error::validation_with_field(format!("Invalid colour: {}", value), "color");
+    let hex = value.trim_start_matches('#');
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let mut color = [255u8; 4];
+    for (i, channel) in color.iter_mut().take(hex.len() / 2).enumerate() {
+        *channel = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(color)
+}
+
+/// An RGBA image
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Create a transparent image
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Create an image filled with one colour
+    pub fn filled(width: u32, height: u32, color: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: color
+                .iter()
+                .copied()
+                .cycle()
+                .take(width as usize * height as usize * 4)
+                .collect(),
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+
+    /// Pixel at a position
+    pub fn get(&self, x: u32, y: u32) -> Color {
+        let i = self.index(x, y);
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Overwrite a pixel
+    pub fn set(&mut self, x: u32, y: u32, color: Color) {
+        let i = self.index(x, y);
+        self.pixels[i..i + 4].copy_from_slice(&color);
+    }
+
+    /// Alpha-blend a colour onto a pixel, with extra coverage in 0..=1
+    pub fn blend(&mut self, x: i64, y: i64, color: Color, coverage: f64) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let alpha = color[3] as f64 / 255.0 * coverage.clamp(0.0, 1.0);
+        if alpha <= 0.0 {
+            return;
+        }
+        let i = self.index(x as u32, y as u32);
+        let dst_alpha = self.pixels[i + 3] as f64 / 255.0;
+        let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+        for (dst, src) in self.pixels[i..i + 3].iter_mut().zip(color) {
+            *dst = ((src as f64 * alpha + *dst as f64 * dst_alpha * (1.0 - alpha)) / out_alpha)
+                .round() as u8;
+        }
+        self.pixels[i + 3] = (out_alpha * 255.0).round() as u8;
+    }
+
+    /// Copy another image onto this one at an offset, clipping at the edges
+    pub fn draw_image(&mut self, source: &Image, dx: i64, dy: i64) {
+        for y in 0..source.height as i64 {
+            let ty = y + dy;
+            if ty < 0 || ty >= self.height as i64 {
+                continue;
+            }
+            for x in 0..source.width as i64 {
+                let tx = x + dx;
+                if tx < 0 || tx >= self.width as i64 {
+                    continue;
+                }
+                let pixel = source.get(x as u32, y as u32);
+                self.blend(tx, ty, pixel, 1.0);
+            }
+        }
+    }
+
+    /// Fill a circle
+    pub fn fill_circle(&mut self, cx: f64, cy: f64, radius: f64, color: Color) {
+        let (min_x, max_x) = ((cx - radius - 1.0).floor(), (cx + radius + 1.0).ceil());
+        let (min_y, max_y) = ((cy - radius - 1.0).floor(), (cy + radius + 1.0).ceil());
+        for y in min_y as i64..=max_y as i64 {
+            for x in min_x as i64..=max_x as i64 {
+                let distance =
+                    ((x as f64 + 0.5 - cx).powi(2) + (y as f64 + 0.5 - cy).powi(2)).sqrt();
+                self.blend(x, y, color, radius - distance + 0.5);
+            }
+        }
+    }
+
+    /// Stroke a polyline with round joins and caps
+    pub fn stroke_polyline(&mut self, points: &[(f64, f64)], width: f64, color: Color) {
+        let Some(first) = points.first() else {
+            return;
+        };
+        let half = width / 2.0;
+        let (w, h) = (self.width as i64, self.height as i64);
+
+        // Record the best coverage per pixel, then blend each pixel once so
+        // translucent lines do not darken where segments meet
+        let mut coverage = vec![0f32; w as usize * h as usize];
+        let single = [*first, *first];
+        let segments: Vec<&[(f64, f64)]> = if points.len() == 1 {
+            vec![&single[..]]
+        } else {
+            points.windows(2).collect()
+        };
+        for segment in segments {
+            let (a, b) = (segment[0], segment[1]);
+            let min_x = ((a.0.min(b.0) - half - 1.0).floor() as i64).max(0);
+            let max_x = ((a.0.max(b.0) + half + 1.0).ceil() as i64).min(w - 1);
+            let min_y = ((a.1.min(b.1) - half - 1.0).floor() as i64).max(0);
+            let max_y = ((a.1.max(b.1) + half + 1.0).ceil() as i64).min(h - 1);
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    let distance = segment_distance((x as f64 + 0.5, y as f64 + 0.5), a, b);
+                    let value = (half - distance + 0.5).clamp(0.0, 1.0) as f32;
+                    let cell = &mut coverage[(y * w + x) as usize];
+                    *cell = cell.max(value);
+                }
+            }
+        }
+
+        for (i, value) in coverage.into_iter().enumerate() {
+            if value > 0.0 {
+                self.blend(i as i64 % w, i as i64 / w, color, value as f64);
+            }
+        }
+    }
+
+    /// Fill a polygon using the even-odd rule
+    pub fn fill_polygon(&mut self, points: &[(f64, f64)], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+        let min_y = points
+            .iter()
+            .map(|p| p.1)
+            .fold(f64::INFINITY, f64::min)
+            .max(0.0) as i64;
+        let max_y = points
+            .iter()
+            .map(|p| p.1)
+            .fold(f64::NEG_INFINITY, f64::max)
+            .min(self.height as f64) as i64;
+
+        for y in min_y..=max_y {
+            let scan = y as f64 + 0.5;
+            let mut crossings: Vec<f64> = Vec::new();
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                if (a.1 <= scan) != (b.1 <= scan) {
+                    crossings.push(a.0 + (scan - a.1) * (b.0 - a.0) / (b.1 - a.1));
+                }
+            }
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            for pair in crossings.chunks_exact(2) {
+                let start = pair[0].round().max(0.0) as i64;
+                let end = pair[1].round().min(self.width as f64) as i64;
+                for x in start..end {
+                    self.blend(x, y, color, 1.0);
+                }
+            }
+        }
+    }
+}
+
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    };
+    ((p.0 - a.0 - t * dx).powi(2) + (p.1 - a.1 - t * dy).powi(2)).sqrt()
+}
@@ -0,0 +1,582 @@
+/// Static map rendering
+///
+/// Composes cached raster tiles with marker, path and area overlays into a
+/// PNG. Labels are not drawn on the image; they are listed in the text
+/// content alongside the provider attribution.
+use crate::error::{Error, Result};
+use crate::maps::osm::Point;
+use crate::maps::routing::{RoutingClient, RoutingConfig, TravelMode};
+use crate::maps::tracks::GeofenceShape;
+use crate::tools::{image_result, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+pub mod canvas;
+pub mod png;
+pub mod tiles;
+
+pub use canvas::{Color, Image};
+pub use tiles::{TileCache, TileConfig};
+
+const TILE_SIZE: f64 = 256.0;
+const MAX_ZOOM: u8 = 18;
+/// Zoom used when there is only a single point to show
+const DEFAULT_POINT_ZOOM: u8 = 15;
+/// Space kept between overlays and the image edge when fitting
+const FIT_PADDING: f64 = 40.0;
+/// Background drawn where tiles are missing
+const BACKGROUND: Color = [229, 227, 223, 255];
+
+/// Static map renderer configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MapRenderConfig {
+    #[serde(default)]
+    pub tiles: TileConfig,
+    #[serde(default)]
+    pub routing: RoutingConfig,
+}
+
+/// A point marker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Marker {
+    pub lat: f64,
+    pub lon: f64,
+    pub color: Option<String>,
+    pub label: Option<String>,
+}
+
+/// A line such as a route or recorded track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapPath {
+    pub points: Vec<Point>,
+    pub color: Option<String>,
+    pub width: Option<f64>,
+}
+
+/// A shaded area such as a geofence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapArea {
+    pub shape: GeofenceShape,
+    pub color: Option<String>,
+}
+
+fn default_width() -> u32 {
+    800
+}
+
+fn default_height() -> u32 {
+    600
+}
+
+/// What to draw on a static map
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MapRequest {
+    #[serde(default = "default_width")]
+    pub width: u32,
+    #[serde(default = "default_height")]
+    pub height: u32,
+    /// Map centre; derived from the overlays when omitted
+    pub center: Option<Point>,
+    /// Zoom level; fitted to the overlays when omitted
+    pub zoom: Option<u8>,
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+    #[serde(default)]
+    pub paths: Vec<MapPath>,
+    #[serde(default)]
+    pub areas: Vec<MapArea>,
+}
+
+/// A rendered map image
+#[derive(Debug, Clone)]
+pub struct RenderedMap {
+    pub png: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub center: Point,
+    pub zoom: u8,
+    /// Tiles that could not be fetched and were left blank
+    pub missing_tiles: usize,
+    pub attribution: String,
+}
+
+/// Web Mercator world pixel coordinates at a zoom level
+fn project(point: &Point, zoom: u8) -> (f64, f64) {
+    let scale = TILE_SIZE * 2f64.powi(zoom as i32);
+    let lat = point.lat.clamp(-85.051_128, 85.051_128).to_radians();
+    let x = (point.lon + 180.0) / 360.0 * scale;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / std::f64::consts::PI) / 2.0 * scale;
+    (x, y)
+}
+
+fn unproject(x: f64, y: f64, zoom: u8) -> Point {
+    let scale = TILE_SIZE * 2f64.powi(zoom as i32);
+    let n = std::f64::consts::PI * (1.0 - 2.0 * y / scale);
+    Point {
+        lon: x / scale * 360.0 - 180.0,
+        lat: n.sinh().atan().to_degrees(),
+    }
+}
+
+/// Approximate a circle on the ground as a polygon
+fn circle_points(center: &Point, radius_m: f64) -> Vec<Point> {
+    const METRES_PER_DEGREE: f64 = 111_320.0;
+    (0..64)
+        .map(|i| {
+            let angle = i as f64 / 64.0 * std::f64::consts::TAU;
+            Point {
+                lon: center.lon
+                    + radius_m * angle.cos()
+                        / (METRES_PER_DEGREE * center.lat.to_radians().cos().max(0.01)),
+                lat: center.lat + radius_m * angle.sin() / METRES_PER_DEGREE,
+            }
+        })
+        .collect()
+}
+
+fn area_points(area: &MapArea) -> Vec<Point> {
+    match &area.shape {
+        GeofenceShape::Circle { center, radius_m } => circle_points(center, *radius_m),
+        GeofenceShape::Polygon { points } => points.clone(),
+    }
+}
+
+fn overlay_points(request: &MapRequest) -> Vec<Point> {
+    let markers = request.markers.iter().map(|m| Point {
+        lon: m.lon,
+        lat: m.lat,
+    });
+    let paths = request.paths.iter().flat_map(|p| p.points.iter().cloned());
+    let areas = request.areas.iter().flat_map(area_points);
+    markers.chain(paths).chain(areas).collect()
+}
+
+/// Choose the centre and zoom that show every overlay
+fn fit_view(request: &MapRequest) -> Result<(Point, u8)> {
+    let points = overlay_points(request);
+    if points.is_empty() {
+        let center = request.center.clone().ok_or_else(|| {
+            Error::validation_with_field("A center or at least one overlay is required", "center")
+        })?;
+        return Ok((center, request.zoom.unwrap_or(DEFAULT_POINT_ZOOM)));
+    }
+
+    let extent = |zoom: u8| {
+        let projected: Vec<(f64, f64)> = points.iter().map(|p| project(p, zoom)).collect();
+        let min_x = projected.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let max_x = projected
+            .iter()
+            .map(|p| p.0)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let min_y = projected.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let max_y = projected
+            .iter()
+            .map(|p| p.1)
+            .fold(f64::NEG_INFINITY, f64::max);
+        (min_x, max_x, min_y, max_y)
+    };
+
+    let zoom = request.zoom.unwrap_or_else(|| {
+        let available_w = (request.width as f64 - 2.0 * FIT_PADDING).max(1.0);
+        let available_h = (request.height as f64 - 2.0 * FIT_PADDING).max(1.0);
+        (1..=MAX_ZOOM)
+            .rev()
+            .find(|&zoom| {
+                let (min_x, max_x, min_y, max_y) = extent(zoom);
+                max_x - min_x <= available_w && max_y - min_y <= available_h
+            })
+            .unwrap_or(1)
+            .min(if points.len() == 1 {
+                DEFAULT_POINT_ZOOM
+            } else {
+                MAX_ZOOM
+            })
+    });
+
+    let center = match &request.center {
+        Some(center) => center.clone(),
+        None => {
+            let (min_x, max_x, min_y, max_y) = extent(zoom);
+            unproject((min_x + max_x) / 2.0, (min_y + max_y) / 2.0, zoom)
+        }
+    };
+    Ok((center, zoom))
+}
+
+fn color_or(value: &Option<String>, default: Color) -> Result<Color> {
+    value
+        .as_deref()
+        .map(canvas::parse_color)
+        .transpose()
+        .map(|c| c.unwrap_or(default))
+}
+
+/// Draw areas, then paths, then markers onto a canvas whose top-left corner
+/// is at the given world pixel position
+fn draw_overlays(
+    image: &mut Image,
+    request: &MapRequest,
+    zoom: u8,
+    origin: (f64, f64),
+) -> Result<()> {
+    let to_screen = |p: &Point| {
+        let (x, y) = project(p, zoom);
+        (x - origin.0, y - origin.1)
+    };
+
+    for area in &request.areas {
+        let color = color_or(&area.color, [37, 99, 235, 255])?;
+        let outline: Vec<(f64, f64)> = area_points(area).iter().map(to_screen).collect();
+        image.fill_polygon(&outline, [color[0], color[1], color[2], color[3] / 4]);
+        let mut closed = outline.clone();
+        closed.extend(outline.first().copied());
+        image.stroke_polyline(&closed, 2.0, color);
+    }
+
+    for path in &request.paths {
+        let color = color_or(&path.color, [220, 38, 38, 255])?;
+        let width = path.width.unwrap_or(4.0).clamp(1.0, 20.0);
+        let line: Vec<(f64, f64)> = path.points.iter().map(to_screen).collect();
+        // White casing keeps lines readable over busy tiles
+        image.stroke_polyline(&line, width + 3.0, [255, 255, 255, 220]);
+        image.stroke_polyline(&line, width, color);
+    }
+
+    for marker in &request.markers {
+        let color = color_or(&marker.color, [220, 38, 38, 255])?;
+        let (x, y) = to_screen(&Point {
+            lon: marker.lon,
+            lat: marker.lat,
+        });
+        image.fill_circle(x, y, 9.0, [255, 255, 255, 255]);
+        image.fill_circle(x, y, 6.5, color);
+    }
+    Ok(())
+}
+
+/// Renders static map images from tiles and overlays
+pub struct StaticMapRenderer {
+    tiles: TileCache,
+    routing: RoutingClient,
+}
+
+impl StaticMapRenderer {
+    /// Create a renderer
+    pub fn new(config: MapRenderConfig) -> Result<Self> {
+        Ok(Self {
+            tiles: TileCache::new(config.tiles)?,
+            routing: RoutingClient::new(config.routing)?,
+        })
+    }
+
+    /// Render a map to PNG
+    pub async fn render(&self, request: &MapRequest) -> Result<RenderedMap> {
+        if !(64..=1280).contains(&request.width) || !(64..=1280).contains(&request.height) {
+            return Err(Error::validation_with_field(
+                "Map width and height must be between 64 and 1280 pixels",
+                "width",
+            ));
+        }
+        let (center, zoom) = fit_view(request)?;
+        let zoom = zoom.min(MAX_ZOOM);
+
+        let (cx, cy) = project(&center, zoom);
+        let origin = (
+            (cx - request.width as f64 / 2.0).round(),
+            (cy - request.height as f64 / 2.0).round(),
+        );
+
+        // Tiles covering the viewport; x wraps around the antimeridian
+        let tiles_per_axis = 1i64 << zoom;
+        let first_x = (origin.0 / TILE_SIZE).floor() as i64;
+        let last_x = ((origin.0 + request.width as f64 - 1.0) / TILE_SIZE).floor() as i64;
+        let first_y = ((origin.1 / TILE_SIZE).floor() as i64).max(0);
+        let last_y = (((origin.1 + request.height as f64 - 1.0) / TILE_SIZE).floor() as i64)
+            .min(tiles_per_axis - 1);
+
+        let placements: Vec<(i64, i64)> = (first_y..=last_y)
+            .flat_map(|ty| (first_x..=last_x).map(move |tx| (tx, ty)))
+            .collect();
+        let fetched = futures::future::join_all(placements.iter().map(|(tx, ty)| {
+            self.tiles
+                .tile((zoom, tx.rem_euclid(tiles_per_axis) as u32, *ty as u32))
+        }))
+        .await;
+
+        let mut image = Image::filled(request.width, request.height, BACKGROUND);
+        let mut missing_tiles = 0;
+        for ((tx, ty), tile) in placements.iter().zip(fetched) {
+            match tile {
+                Ok(tile) => image.draw_image(
+                    &tile,
+                    (*tx as f64 * TILE_SIZE - origin.0) as i64,
+                    (*ty as f64 * TILE_SIZE - origin.1) as i64,
+                ),
+                Err(e) => {
+                    tracing::warn!(error = %e, zoom, x = tx, y = ty, "Failed to fetch map tile");
+                    missing_tiles += 1;
+                }
+            }
+        }
+
+        draw_overlays(&mut image, request, zoom, origin)?;
+
+        Ok(RenderedMap {
+            png: png::encode(&image)?,
+            width: request.width,
+            height: request.height,
+            center,
+            zoom,
+            missing_tiles,
+            attribution: self.tiles.attribution().to_string(),
+        })
+    }
+
+    /// Get tool definitions for map rendering
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let point_schema = json!({
+            "type": "object",
+            "properties": {
+                "lat": {"type": "number"},
+                "lon": {"type": "number"}
+            },
+            "required": ["lat", "lon"]
+        });
+        vec![ToolDefinition::from_json_schema(
+            "render_map",
+            "Render a static PNG map with markers, routes/tracks and geofence areas; the view is fitted to the overlays unless center and zoom are given",
+            "maps",
+            json!({
+                "type": "object",
+                "properties": {
+                    "width": {"type": "integer", "default": 800, "minimum": 64, "maximum": 1280},
+                    "height": {"type": "integer", "default": 600, "minimum": 64, "maximum": 1280},
+                    "center": point_schema,
+                    "zoom": {"type": "integer", "minimum": 1, "maximum": 18},
+                    "markers": {
+                        "type": "array",
+                        "description": "Points to mark; place search results can be passed as-is",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "lat": {"type": "number"},
+                                "lon": {"type": "number"},
+                                "color": {"type": "string", "description": "#rrggbb, #rrggbbaa or a basic colour name"},
+                                "label": {"type": "string"}
+                            },
+                            "required": ["lat", "lon"]
+                        }
+                    },
+                    "paths": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "points": {"type": "array", "items": point_schema},
+                                "color": {"type": "string"},
+                                "width": {"type": "number"}
+                            },
+                            "required": ["points"]
+                        }
+                    },
+                    "areas": {
+                        "type": "array",
+                        "description": "Geofence shapes as returned by list_geofences",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "shape": {"type": "object"},
+                                "color": {"type": "string"}
+                            },
+                            "required": ["shape"]
+                        }
+                    },
+                    "route": {
+                        "type": "object",
+                        "description": "Compute and draw a route between two points",
+                        "properties": {
+                            "from": point_schema,
+                            "to": point_schema,
+                            "mode": {"type": "string", "enum": ["driving", "cycling", "walking"]},
+                            "color": {"type": "string"}
+                        },
+                        "required": ["from", "to"]
+                    }
+                }
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a rendering tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "render_map" => {
+                let mut request: MapRequest = serde_json::from_value(parameters.clone())
+                    .map_err(|e| Error::validation(format!("Invalid map request: {}", e)))?;
+
+                let mut lines = Vec::new();
+                if let Some(route) = parameters.get("route") {
+                    let endpoint = |field: &str| -> Result<Point> {
+                        route
+                            .get(field)
+                            .cloned()
+                            .map(serde_json::from_value)
+                            .transpose()
+                            .map_err(|e| {
+                                Error::validation_with_field(
+                                    format!("Invalid {}: {}", field, e),
+                                    field,
+                                )
+                            })?
+                            .ok_or_else(|| {
+                                Error::validation_with_field(
+                                    format!("route.{} is required", field),
+                                    field,
+                                )
+                            })
+                    };
+                    let (from, to) = (endpoint("from")?, endpoint("to")?);
+                    let mode = route
+                        .get("mode")
+                        .and_then(|m| m.as_str())
+                        .map(TravelMode::from_str)
+                        .transpose()?
+                        .unwrap_or(TravelMode::Driving);
+                    let computed = self.routing.route_or_estimate(&from, &to, mode).await;
+                    lines.push(format!(
+                        "Route: {:.1} km, about {} min{}",
+                        computed.distance_m / 1000.0,
+                        (computed.duration_s / 60.0).round(),
+                        if computed.estimated {
+                            " (straight-line estimate)"
+                        } else {
+                            ""
+                        }
+                    ));
+                    request.paths.push(MapPath {
+                        points: computed.geometry,
+                        color: route
+                            .get("color")
+                            .and_then(|c| c.as_str())
+                            .map(str::to_string),
+                        width: None,
+                    });
+                    for point in [from, to] {
+                        request.markers.push(Marker {
+                            lat: point.lat,
+                            lon: point.lon,
+                            color: None,
+                            label: None,
+                        });
+                    }
+                }
+
+                let map = self.render(&request).await?;
+                lines.insert(
+                    0,
+                    format!(
+                        "Map {}x{} at zoom {} centred on {:.5}, {:.5}",
+                        map.width, map.height, map.zoom, map.center.lat, map.center.lon
+                    ),
+                );
+                lines.extend(
+                    request
+                        .markers
+                        .iter()
+                        .filter_map(|m| m.label.as_ref().map(|l| (m, l)))
+                        .map(|(m, label)| {
+                            format!(
+                                "• {} ({}) at {:.5}, {:.5}",
+                                label,
+                                m.color.as_deref().unwrap_or("red"),
+                                m.lat,
+                                m.lon
+                            )
+                        }),
+                );
+                if map.missing_tiles > 0 {
+                    lines.push(format!("{} tile(s) could not be loaded", map.missing_tiles));
+                }
+                lines.push(format!("Map data {}", map.attribution));
+
+                Ok(image_result(
+                    lines.join("\n"),
+                    &map.png,
+                    "image/png",
+                    json!({
+                        "width": map.width,
+                        "height": map.height,
+                        "zoom": map.zoom,
+                        "center": map.center,
+                        "missing_tiles": map.missing_tiles,
+                        "attribution": map.attribution
+                    }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "map_render_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_overlays_and_draws_them() {
+        let request = MapRequest {
+            width: 400,
+            height: 300,
+            markers: vec![Marker {
+                lat: 51.5007,
+                lon: -0.1246,
+                color: Some("#00ff00".to_string()),
+                label: None,
+            }],
+            paths: vec![MapPath {
+                points: vec![
+                    Point {
+                        lon: -0.1246,
+                        lat: 51.5007,
+                    },
+                    Point {
+                        lon: -0.0759,
+                        lat: 51.5081,
+                    },
+                ],
+                color: None,
+                width: None,
+            }],
+            ..Default::default()
+        };
+        let (center, zoom) = fit_view(&request).unwrap();
+        assert_eq!(zoom, 13);
+        assert!((center.lon - -0.10025).abs() < 1e-6);
+
+        let (cx, cy) = project(&center, zoom);
+        let origin = ((cx - 200.0).round(), (cy - 150.0).round());
+        let mut image = Image::filled(400, 300, BACKGROUND);
+        draw_overlays(&mut image, &request, zoom, origin).unwrap();
+
+        let (mx, my) = project(
+            &Point {
+                lon: -0.1246,
+                lat: 51.5007,
+            },
+            zoom,
+        );
+        assert_eq!(
+            image.get((mx - origin.0) as u32, (my - origin.1) as u32),
+            [0, 255, 0, 255]
+        );
+
+        let decoded = png::decode(&png::encode(&image).unwrap()).unwrap();
+        assert_eq!(decoded.pixels, image.pixels);
+    }
+}
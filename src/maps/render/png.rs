@@ -0,0 +1,231 @@
+//! PNG decoding and encoding for map tiles
+//!
+//! Decodes non-interlaced PNGs of every colour type and bit depth into RGBA,
+//! which covers what tile servers produce, and encodes RGBA images.
+
+use super::canvas::Image;
+use crate::error::{Error, Result};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+fn png_error(message: impl Into<String>) -> Error {
+    Error::parsing_with_format(message, "png", None)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Reverse the per-scanline filters in place, returning rows without filter bytes
+fn unfilter(data: &[u8], stride: usize, height: usize, bpp: usize) -> Result<Vec<u8>> {
+    if data.len() < (stride + 1) * height {
+        return Err(png_error("PNG image data is truncated"));
+    }
+    let mut out = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = data[y * (stride + 1)];
+        let src = &data[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (previous, current) = out.split_at_mut(y * stride);
+        let prior = if y > 0 {
+            &previous[(y - 1) * stride..]
+        } else {
+            &[][..]
+        };
+        let row = &mut current[..stride];
+        for x in 0..stride {
+            let a = if x >= bpp { row[x - bpp] } else { 0 };
+            let b = prior.get(x).copied().unwrap_or(0);
+            let c = if x >= bpp {
+                prior.get(x - bpp).copied().unwrap_or(0)
+            } else {
+                0
+            };
+            row[x] = match filter {
+                0 => src[x],
+                1 => src[x].wrapping_add(a),
+                2 => src[x].wrapping_add(b),
+                3 => src[x].wrapping_add(((a as u16 + b as u16) / 2) as u8),
+                4 => src[x].wrapping_add(paeth(a, b, c)),
+                other => return Err(png_error(format!("Unknown PNG filter type {}", other))),
+            };
+        }
+    }
+    Ok(out)
+}
+
+/// Decode a PNG into an RGBA image
+pub fn decode(bytes: &[u8]) -> Result<Image> {
+    if bytes.len() < 8 || bytes[..8] != SIGNATURE {
+        return Err(png_error("Not a PNG file"));
+    }
+
+    let mut header = None;
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut transparency: Vec<u8> = Vec::new();
+    let mut compressed = Vec::new();
+
+    let mut offset = 8;
+    while offset + 8 <= bytes.len() {
+        let length =
+            u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap_or_default()) as usize;
+        let kind = &bytes[offset + 4..offset + 8];
+        let data = bytes
+            .get(offset + 8..offset + 8 + length)
+            .ok_or_else(|| png_error("PNG chunk is truncated"))?;
+        match kind {
+            b"IHDR" if data.len() >= 13 => {
+                let width = u32::from_be_bytes(data[0..4].try_into().unwrap_or_default());
+                let height = u32::from_be_bytes(data[4..8].try_into().unwrap_or_default());
+                if data[12] != 0 {
+                    return Err(png_error("Interlaced PNGs are not supported"));
+                }
+                header = Some((width as usize, height as usize, data[8], data[9]));
+            }
+            b"PLTE" => palette = data.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            b"tRNS" => transparency = data.to_vec(),
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        offset += 12 + length;
+    }
+
+    let (width, height, depth, color_type) =
+        header.ok_or_else(|| png_error("PNG is missing IHDR"))?;
+    let channels = match color_type {
+        0 => 1,
+        2 => 3,
+        3 => 1,
+        4 => 2,
+        6 => 4,
+        other => return Err(png_error(format!("Unknown PNG colour type {}", other))),
+    };
+    if !matches!(depth, 1 | 2 | 4 | 8 | 16) {
+        return Err(png_error(format!("Invalid PNG bit depth {}", depth)));
+    }
+
+    let mut raw = Vec::new();
+    ZlibDecoder::new(compressed.as_slice())
+        .read_to_end(&mut raw)
+        .map_err(|e| png_error(format!("Failed to inflate PNG data: {}", e)))?;
+
+    let bits_per_pixel = channels * depth as usize;
+    let stride = (width * bits_per_pixel).div_ceil(8);
+    let rows = unfilter(&raw, stride, height, bits_per_pixel.div_ceil(8))?;
+
+    // Read the nth sample of a row, scaled to 8 bits (palette indices are not scaled)
+    let sample = |row: &[u8], n: usize, scale: bool| -> u8 {
+        match depth {
+            8 => row[n],
+            16 => row[n * 2],
+            _ => {
+                let bit = n * depth as usize;
+                let value = (row[bit / 8] >> (8 - depth as usize - bit % 8)) & ((1 << depth) - 1);
+                if scale {
+                    (value as u16 * 255 / ((1u16 << depth) - 1)) as u8
+                } else {
+                    value
+                }
+            }
+        }
+    };
+    let transparent_grey = (transparency.len() >= 2).then(|| transparency[1]);
+
+    let mut image = Image::new(width as u32, height as u32);
+    for y in 0..height {
+        let row = &rows[y * stride..(y + 1) * stride];
+        for x in 0..width {
+            let pixel = match color_type {
+                0 => {
+                    let g = sample(row, x, true);
+                    let a = if depth <= 8 && transparent_grey == Some(sample(row, x, false)) {
+                        0
+                    } else {
+                        255
+                    };
+                    [g, g, g, a]
+                }
+                2 => [
+                    sample(row, x * 3, true),
+                    sample(row, x * 3 + 1, true),
+                    sample(row, x * 3 + 2, true),
+                    255,
+                ],
+                3 => {
+                    let index = sample(row, x, false) as usize;
+                    let [r, g, b] = palette.get(index).copied().unwrap_or_default();
+                    [r, g, b, transparency.get(index).copied().unwrap_or(255)]
+                }
+                4 => {
+                    let g = sample(row, x * 2, true);
+                    [g, g, g, sample(row, x * 2 + 1, true)]
+                }
+                _ => [
+                    sample(row, x * 4, true),
+                    sample(row, x * 4 + 1, true),
+                    sample(row, x * 4 + 2, true),
+                    sample(row, x * 4 + 3, true),
+                ],
+            };
+            image.set(x as u32, y as u32, pixel);
+        }
+    }
+    Ok(image)
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32fast::hash(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Encode an RGBA image as PNG
+pub fn encode(image: &Image) -> Result<Vec<u8>> {
+    let stride = image.width as usize * 4;
+    let mut filtered = Vec::with_capacity((stride + 1) * image.height as usize);
+    for row in image.pixels.chunks_exact(stride) {
+        // Sub filter: map imagery has long runs of identical pixels
+        filtered.push(1);
+        for x in 0..stride {
+            let left = if x >= 4 { row[x - 4] } else { 0 };
+            filtered.push(row[x].wrapping_sub(left));
+        }
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&filtered)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| Error::internal(format!("Failed to compress PNG data: {}", e)))
+        .map(|compressed| {
+            let mut header = Vec::with_capacity(13);
+            header.extend_from_slice(&image.width.to_be_bytes());
+            header.extend_from_slice(&image.height.to_be_bytes());
+            header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+            let mut out = SIGNATURE.to_vec();
+            write_chunk(&mut out, b"IHDR", &header);
+            write_chunk(&mut out, b"IDAT", &compressed);
+            write_chunk(&mut out, b"IEND", &[]);
+            out
+        })
+}
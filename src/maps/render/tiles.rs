@@ -0,0 +1,209 @@
+use super::canvas::Image;
+use super::png;
+use crate::error::{Error, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::Semaphore;
+
+/// Tiles kept decoded in memory
+const MEMORY_CACHE_TILES: usize = 256;
+
+/// Tile server configuration
+///
+/// The defaults follow the OpenStreetMap tile usage policy: an identifying
+/// User-Agent, at most two concurrent downloads and a week of local caching.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileConfig {
+    /// URL template with `{z}`, `{x}` and `{y}` placeholders
+    pub url_template: String,
+    /// User-Agent identifying this application to the tile server
+    pub user_agent: String,
+    /// Directory for the on-disk tile cache; `None` disables it
+    pub cache_dir: Option<PathBuf>,
+    /// How long cached tiles are used without re-downloading, in seconds
+    pub cache_ttl_secs: u64,
+    /// Maximum simultaneous tile downloads
+    pub max_concurrent: usize,
+    /// Attribution required by the tile provider
+    pub attribution: String,
+}
+
+impl Default for TileConfig {
+    fn default() -> Self {
+        Self {
+            url_template: "https://tile.openstreetmap.org/{z}/{x}/{y}.png".to_string(),
+            user_agent: concat!(
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION"),
+                " (+",
+                env!("CARGO_PKG_REPOSITORY"),
+                ")"
+            )
+            .to_string(),
+            cache_dir: Some(std::env::temp_dir().join("devops-mcp-tiles")),
+            cache_ttl_secs: 7 * 24 * 60 * 60,
+            max_concurrent: 2,
+            attribution: "© OpenStreetMap contributors".to_string(),
+        }
+    }
+}
+
+/// A tile address
+pub type TileKey = (u8, u32, u32);
+
+#[derive(Default)]
+struct MemoryCache {
+    tiles: HashMap<TileKey, Arc<Image>>,
+    order: VecDeque<TileKey>,
+}
+
+/// Tile fetcher with memory and disk caches
+pub struct TileCache {
+    client: Client,
+    config: TileConfig,
+    memory: Mutex<MemoryCache>,
+    downloads: Semaphore,
+}
+
+impl TileCache {
+    /// Create a tile cache for the given server
+    pub fn new(config: TileConfig) -> Result<Self> {
+        if config.user_agent.trim().is_empty() {
+            return Err(Error::config_with_suggestion(
+                "Tile requests need a User-Agent",
+                "Set tiles.user_agent to identify your application",
+            ));
+        }
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent(config.user_agent.clone())
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create tile client: {}", e)))?;
+        Ok(Self {
+            client,
+            downloads: Semaphore::new(config.max_concurrent.max(1)),
+            config,
+            memory: Mutex::new(MemoryCache::default()),
+        })
+    }
+
+    /// Attribution text for the configured provider
+    pub fn attribution(&self) -> &str {
+        &self.config.attribution
+    }
+
+    fn disk_path(&self, (z, x, y): TileKey) -> Option<PathBuf> {
+        self.config.cache_dir.as_ref().map(|dir| {
+            dir.join(z.to_string())
+                .join(x.to_string())
+                .join(format!("{}.png", y))
+        })
+    }
+
+    fn remember(&self, key: TileKey, tile: Arc<Image>) {
+        if let Ok(mut memory) = self.memory.lock() {
+            if memory.tiles.insert(key, tile).is_none() {
+                memory.order.push_back(key);
+            }
+            while memory.order.len() > MEMORY_CACHE_TILES {
+                if let Some(oldest) = memory.order.pop_front() {
+                    memory.tiles.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    async fn read_disk(&self, key: TileKey) -> Option<(Arc<Image>, bool)> {
+        let path = self.disk_path(key)?;
+        let metadata = tokio::fs::metadata(&path).await.ok()?;
+        let fresh = metadata
+            .modified()
+            .ok()
+            .and_then(|m| SystemTime::now().duration_since(m).ok())
+            .is_some_and(|age| age.as_secs() < self.config.cache_ttl_secs);
+        let bytes = tokio::fs::read(&path).await.ok()?;
+        png::decode(&bytes).ok().map(|tile| (Arc::new(tile), fresh))
+    }
+
+    async fn download(&self, (z, x, y): TileKey) -> Result<Vec<u8>> {
+        let url = self
+            .config
+            .url_template
+            .replace("{z}", &z.to_string())
+            .replace("{x}", &x.to_string())
+            .replace("{y}", &y.to_string());
+
+        let _permit = self
+            .downloads
+            .acquire()
+            .await
+            .map_err(|e| Error::internal(format!("Tile download limiter closed: {}", e)))?;
+        let response = self.client.get(&url).send().await.map_err(|e| {
+            Error::network_with_endpoint(format!("Tile request failed: {}", e), url.clone())
+        })?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(Error::api_with_status(
+                format!("Tile server returned {} for {}", status, url),
+                "tiles",
+                status.as_u16(),
+            ));
+        }
+        response
+            .bytes()
+            .await
+            .map(|b| b.to_vec())
+            .map_err(|e| Error::network(format!("Failed to read tile: {}", e)))
+    }
+
+    /// Get a tile, preferring the memory cache, then fresh disk copies
+    pub async fn tile(&self, key: TileKey) -> Result<Arc<Image>> {
+        if let Some(tile) = self
+            .memory
+            .lock()
+            .ok()
+            .and_then(|m| m.tiles.get(&key).cloned())
+        {
+            return Ok(tile);
+        }
+
+        let cached = self.read_disk(key).await;
+        if let Some((tile, true)) = &cached {
+            self.remember(key, tile.clone());
+            return Ok(tile.clone());
+        }
+
+        let tile = match self.download(key).await {
+            Ok(bytes) => {
+                let tile = Arc::new(png::decode(&bytes)?);
+                if let Some(path) = self.disk_path(key) {
+                    let written = async {
+                        if let Some(parent) = path.parent() {
+                            tokio::fs::create_dir_all(parent).await?;
+                        }
+                        tokio::fs::write(&path, &bytes).await
+                    };
+                    if let Err(e) = written.await {
+                        tracing::debug!(error = %e, path = %path.display(), "Failed to cache tile");
+                    }
+                }
+                tile
+            }
+            // A stale tile beats a blank one
+            Err(e) => match cached {
+                Some((tile, _)) => {
+                    tracing::debug!(error = %e, "Using stale cached tile");
+                    tile
+                }
+                None => return Err(e),
+            },
+        };
+        self.remember(key, tile.clone());
+        Ok(tile)
+    }
+}
@@ -0,0 +1,203 @@
+/// Travel-time routing via OSRM
+///
+/// When the routing service is unreachable, callers can fall back to a
+/// straight-line estimate with a detour factor and a typical speed per mode.
+use crate::error::{Error, Result};
+use crate::maps::osm::Point;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Mean Earth radius in metres
+const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+/// Ratio of road distance to straight-line distance used for estimates
+const DETOUR_FACTOR: f64 = 1.3;
+
+/// Mode of travel
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TravelMode {
+    Driving,
+    Cycling,
+    Walking,
+}
+
+impl TravelMode {
+    /// OSRM profile name
+    fn profile(&self) -> &'static str {
+        match self {
+            TravelMode::Driving => "driving",
+            TravelMode::Cycling => "cycling",
+            TravelMode::Walking => "foot",
+        }
+    }
+
+    /// Typical door-to-door speed in km/h used for estimates
+    fn typical_speed_kmh(&self) -> f64 {
+        match self {
+            TravelMode::Driving => 40.0,
+            TravelMode::Cycling => 15.0,
+            TravelMode::Walking => 5.0,
+        }
+    }
+}
+
+impl FromStr for TravelMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "driving" | "car" | "drive" => Ok(TravelMode::Driving),
+            "cycling" | "bike" | "bicycle" => Ok(TravelMode::Cycling),
+            "walking" | "foot" | "walk" => Ok(TravelMode::Walking),
+            other => Err(Error::validation_with_field(
+                format!("Unsupported travel mode: {}", other),
+                "mode",
+            )),
+        }
+    }
+}
+
+/// A computed route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Route {
+    pub mode: TravelMode,
+    pub distance_m: f64,
+    pub duration_s: f64,
+    /// Whether the figures are a straight-line estimate rather than a routed path
+    pub estimated: bool,
+    /// Simplified path of the route, or the two endpoints for an estimate
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub geometry: Vec<Point>,
+}
+
+/// Routing service configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    /// OSRM server URL
+    pub osrm_url: String,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            osrm_url: "https://router.project-osrm.org".to_string(),
+        }
+    }
+}
+
+/// Great-circle distance between two points in metres
+pub fn haversine_m(from: &Point, to: &Point) -> f64 {
+    let (lat1, lat2) = (from.lat.to_radians(), to.lat.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (to.lon - from.lon).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+/// Estimate a route from straight-line distance
+pub fn estimate_route(from: &Point, to: &Point, mode: TravelMode) -> Route {
+    let distance_m = haversine_m(from, to) * DETOUR_FACTOR;
+    Route {
+        mode,
+        distance_m,
+        duration_s: distance_m / (mode.typical_speed_kmh() / 3.6),
+        estimated: true,
+        geometry: vec![from.clone(), to.clone()],
+    }
+}
+
+/// OSRM routing client
+#[derive(Debug, Clone)]
+pub struct RoutingClient {
+    client: Client,
+    config: RoutingConfig,
+}
+
+impl RoutingClient {
+    /// Create a new routing client
+    pub fn new(config: RoutingConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create routing client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    /// Route between two points
+    pub async fn route(&self, from: &Point, to: &Point, mode: TravelMode) -> Result<Route> {
+        let url = format!(
+            "{}/route/v1/{}/{},{};{},{}?overview=simplified&geometries=geojson",
+            self.config.osrm_url.trim_end_matches('/'),
+            mode.profile(),
+            from.lon,
+            from.lat,
+            to.lon,
+            to.lat
+        );
+
+        let response = self.client.get(&url).send().await.map_err(|e| {
+            Error::network_with_endpoint(format!("Routing request failed: {}", e), url.clone())
+        })?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse routing response: {}", e)))?;
+
+        let code = body
+            .get("code")
+            .and_then(|c| c.as_str())
+            .unwrap_or_default();
+        if !status.is_success() || code != "Ok" {
+            let message = body.get("message").and_then(|m| m.as_str()).unwrap_or(code);
+            return Err(Error::api_with_status(
+                format!("Routing failed: {}", message),
+                "osrm",
+                status.as_u16(),
+            ));
+        }
+
+        let route = body
+            .pointer("/routes/0")
+            .ok_or_else(|| Error::not_found("No route found between the given points"))?;
+        Ok(Route {
+            mode,
+            distance_m: route
+                .get("distance")
+                .and_then(|d| d.as_f64())
+                .unwrap_or(0.0),
+            duration_s: route
+                .get("duration")
+                .and_then(|d| d.as_f64())
+                .unwrap_or(0.0),
+            estimated: false,
+            geometry: route
+                .pointer("/geometry/coordinates")
+                .and_then(|c| c.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|c| {
+                    Some(Point {
+                        lon: c.get(0)?.as_f64()?,
+                        lat: c.get(1)?.as_f64()?,
+                    })
+                })
+                .collect(),
+        })
+    }
+
+    /// Route between two points, falling back to an estimate if routing fails
+    pub async fn route_or_estimate(&self, from: &Point, to: &Point, mode: TravelMode) -> Route {
+        match self.route(from, to, mode).await {
+            Ok(route) => route,
+            Err(e) => {
+                tracing::debug!(error = %e, "Routing failed, using straight-line estimate");
+                estimate_route(from, to, mode)
+            }
+        }
+    }
+}
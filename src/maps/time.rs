@@ -0,0 +1,752 @@
+/// Travel and timezone utilities
+///
+/// Timezone lookup uses the `timezone` tag on OpenStreetMap boundaries that
+/// contain a point, falling back to the nautical zone for the longitude.
+/// Sun times use the NOAA sunrise equation, accurate to a minute or two.
+use crate::error::{Error, Result};
+use crate::maps::osm::Point;
+use crate::maps::routing::{RoutingClient, RoutingConfig, TravelMode};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Offset, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Timezone of a location
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimezoneInfo {
+    /// IANA timezone name
+    pub timezone: String,
+    /// Current offset from UTC in seconds
+    pub utc_offset_seconds: i32,
+    /// Current local time
+    pub local_time: String,
+    /// "osm" when found on a boundary, "nautical" for the longitude fallback
+    pub source: String,
+}
+
+/// Sunrise and sunset for a location and date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SunTimes {
+    pub date: NaiveDate,
+    pub sunrise: Option<DateTime<Utc>>,
+    pub sunset: Option<DateTime<Utc>>,
+    pub solar_noon: DateTime<Utc>,
+    pub day_length_seconds: i64,
+    /// "day" during midnight sun, "night" during polar night
+    pub polar: Option<String>,
+}
+
+/// A busy calendar block, optionally at a location
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusyInterval {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub location: Option<Point>,
+}
+
+/// A meeting participant with their free/busy data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Participant {
+    pub name: String,
+    /// IANA timezone name
+    pub timezone: String,
+    /// Local working hours as [start_hour, end_hour)
+    #[serde(default = "default_working_hours")]
+    pub working_hours: (u32, u32),
+    #[serde(default)]
+    pub busy: Vec<BusyInterval>,
+}
+
+fn default_working_hours() -> (u32, u32) {
+    (9, 17)
+}
+
+/// Constraints for finding a meeting time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingRequest {
+    pub participants: Vec<Participant>,
+    pub duration_minutes: i64,
+    pub earliest: DateTime<Utc>,
+    pub latest: DateTime<Utc>,
+    /// Where the meeting happens; enables travel-time checks against located busy blocks
+    pub location: Option<Point>,
+    #[serde(default = "default_mode")]
+    pub mode: TravelMode,
+    #[serde(default = "default_step")]
+    pub step_minutes: i64,
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+}
+
+fn default_mode() -> TravelMode {
+    TravelMode::Driving
+}
+
+fn default_step() -> i64 {
+    30
+}
+
+fn default_max_results() -> usize {
+    5
+}
+
+/// A participant's view of a proposed slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticipantTime {
+    pub name: String,
+    pub local_start: String,
+    pub local_end: String,
+    /// Travel needed before the meeting, in minutes
+    pub travel_before_minutes: Option<i64>,
+    /// Travel needed after the meeting, in minutes
+    pub travel_after_minutes: Option<i64>,
+}
+
+/// A proposed meeting slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingSlot {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Higher is better; slots near the middle of everyone's day score highest
+    pub score: f64,
+    pub participants: Vec<ParticipantTime>,
+}
+
+fn parse_timezone(name: &str) -> Result<Tz> {
+    Tz::from_str(name).map_err(|_| {
+        Error::validation_with_field(format!("Unknown timezone: {}", name), "timezone")
+    })
+}
+
+/// Nautical timezone for a longitude (note the inverted Etc/GMT sign convention)
+fn nautical_timezone(lon: f64) -> String {
+    let offset = (lon / 15.0).round() as i32;
+    match offset {
+        0 => "Etc/GMT".to_string(),
+        o if o > 0 => format!("Etc/GMT-{}", o.min(12)),
+        o => format!("Etc/GMT+{}", (-o).min(12)),
+    }
+}
+
+/// Julian date for a UTC instant
+fn julian_date(instant: DateTime<Utc>) -> f64 {
+    instant.timestamp() as f64 / 86_400.0 + 2_440_587.5
+}
+
+fn from_julian_date(jd: f64) -> DateTime<Utc> {
+    let seconds = ((jd - 2_440_587.5) * 86_400.0).round() as i64;
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .unwrap_or_else(Utc::now)
+}
+
+/// Compute sunrise, sunset and solar noon for a location and date
+pub fn sun_times(point: &Point, date: NaiveDate) -> SunTimes {
+    let noon = Utc.from_utc_datetime(&date.and_hms_opt(12, 0, 0).unwrap_or_default());
+    let n = (julian_date(noon) - 2_451_545.0 + 0.0008).ceil();
+    let mean_solar_time = n - point.lon / 360.0;
+
+    let anomaly = (357.5291 + 0.985_600_28 * mean_solar_time).rem_euclid(360.0);
+    let m = anomaly.to_radians();
+    let center = 1.9148 * m.sin() + 0.02 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let ecliptic_longitude = (anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+    let lambda = ecliptic_longitude.to_radians();
+    let transit = 2_451_545.0 + mean_solar_time + 0.0053 * m.sin() - 0.0069 * (2.0 * lambda).sin();
+
+    let declination = (lambda.sin() * 23.4397_f64.to_radians().sin()).asin();
+    let latitude = point.lat.to_radians();
+    let cos_hour_angle = ((-0.833_f64).to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+
+    let solar_noon = from_julian_date(transit);
+    if cos_hour_angle > 1.0 {
+        return SunTimes {
+            date,
+            sunrise: None,
+            sunset: None,
+            solar_noon,
+            day_length_seconds: 0,
+            polar: Some("night".to_string()),
+        };
+    }
+    if cos_hour_angle < -1.0 {
+        return SunTimes {
+            date,
+            sunrise: None,
+            sunset: None,
+            solar_noon,
+            day_length_seconds: 86_400,
+            polar: Some("day".to_string()),
+        };
+    }
+
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+    let sunrise = from_julian_date(transit - hour_angle / 360.0);
+    let sunset = from_julian_date(transit + hour_angle / 360.0);
+    SunTimes {
+        date,
+        sunrise: Some(sunrise),
+        sunset: Some(sunset),
+        solar_noon,
+        day_length_seconds: (sunset - sunrise).num_seconds(),
+        polar: None,
+    }
+}
+
+/// Whether an instant range falls inside a participant's local working day
+fn within_working_hours(
+    tz: &Tz,
+    hours: (u32, u32),
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> bool {
+    let local_start = start.with_timezone(tz);
+    let local_end = end.with_timezone(tz);
+    if matches!(local_start.weekday(), Weekday::Sat | Weekday::Sun)
+        || local_start.date_naive() != (local_end - Duration::seconds(1)).date_naive()
+    {
+        return false;
+    }
+    let start_minutes = local_start.hour() * 60 + local_start.minute();
+    let end_minutes = local_end.hour() * 60 + local_end.minute();
+    let end_minutes = if end_minutes == 0 {
+        24 * 60
+    } else {
+        end_minutes
+    };
+    start_minutes >= hours.0 * 60 && end_minutes <= hours.1 * 60
+}
+
+/// Find meeting slots that fit everyone's working hours, calendars and travel
+///
+/// `travel` maps (participant index, busy index) to travel seconds between that
+/// busy block's location and the meeting location.
+fn find_slots(
+    request: &MeetingRequest,
+    zones: &[Tz],
+    travel: &HashMap<(usize, usize), f64>,
+) -> Vec<MeetingSlot> {
+    let duration = Duration::minutes(request.duration_minutes);
+    let step = Duration::minutes(request.step_minutes.max(5));
+
+    // Align the first candidate to the step boundary
+    let step_seconds = step.num_seconds();
+    let first = request.earliest.timestamp();
+    let aligned = first + (step_seconds - first.rem_euclid(step_seconds)) % step_seconds;
+    let mut start = Utc
+        .timestamp_opt(aligned, 0)
+        .single()
+        .unwrap_or(request.earliest);
+
+    let mut slots = Vec::new();
+    while start + duration <= request.latest {
+        let end = start + duration;
+        let mut score = 0.0;
+        let mut views = Vec::with_capacity(request.participants.len());
+        let mut feasible = true;
+
+        for (p, participant) in request.participants.iter().enumerate() {
+            let tz = &zones[p];
+            if !within_working_hours(tz, participant.working_hours, start, end) {
+                feasible = false;
+                break;
+            }
+
+            let mut travel_before = None;
+            let mut travel_after = None;
+            for (b, busy) in participant.busy.iter().enumerate() {
+                let travel_gap = travel
+                    .get(&(p, b))
+                    .map(|s| Duration::seconds(s.ceil() as i64))
+                    .unwrap_or_else(Duration::zero);
+                // Busy blocks must not overlap the meeting plus travel to/from them
+                if busy.start < end + travel_gap && busy.end + travel_gap > start {
+                    feasible = false;
+                    break;
+                }
+                if busy.end <= start && travel.contains_key(&(p, b)) {
+                    travel_before = Some(travel_gap.num_minutes());
+                }
+                if busy.start >= end && travel.contains_key(&(p, b)) && travel_after.is_none() {
+                    travel_after = Some(travel_gap.num_minutes());
+                }
+            }
+            if !feasible {
+                break;
+            }
+
+            // Prefer slots near the middle of each participant's working day
+            let local = start.with_timezone(tz);
+            let midday = (participant.working_hours.0 + participant.working_hours.1) as f64 / 2.0;
+            let hour = local.hour() as f64 + local.minute() as f64 / 60.0;
+            let span = (participant.working_hours.1 - participant.working_hours.0).max(1) as f64;
+            score += 1.0 - ((hour - midday).abs() / span).min(1.0);
+
+            views.push(ParticipantTime {
+                name: participant.name.clone(),
+                local_start: local.format("%Y-%m-%d %H:%M %Z").to_string(),
+                local_end: end.with_timezone(tz).format("%H:%M %Z").to_string(),
+                travel_before_minutes: travel_before,
+                travel_after_minutes: travel_after,
+            });
+        }
+
+        if feasible {
+            slots.push(MeetingSlot {
+                start,
+                end,
+                score: score / request.participants.len().max(1) as f64,
+                participants: views,
+            });
+        }
+        start += step;
+    }
+
+    // Best score first, earliest first among equals
+    slots.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.start.cmp(&b.start))
+    });
+    slots.truncate(request.max_results);
+    slots
+}
+
+/// Timezone, sun and scheduling utilities
+#[derive(Debug, Clone)]
+pub struct TimeUtils {
+    client: Client,
+    overpass_url: String,
+    routing: RoutingClient,
+}
+
+impl TimeUtils {
+    /// Create new time utilities using the given routing configuration
+    pub fn new(routing: RoutingConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create timezone client: {}", e)))?;
+        Ok(Self {
+            client,
+            overpass_url: "https://overpass-api.de/api/interpreter".to_string(),
+            routing: RoutingClient::new(routing)?,
+        })
+    }
+
+    /// Set Overpass API URL
+    pub fn with_overpass_url(mut self, url: impl Into<String>) -> Self {
+        self.overpass_url = url.into();
+        self
+    }
+
+    async fn osm_timezone(&self, point: &Point) -> Result<Option<String>> {
+        let query = format!(
+            "[out:json][timeout:25];is_in({},{})->.a;area.a[\"timezone\"];out tags;",
+            point.lat, point.lon
+        );
+        let response = self
+            .client
+            .post(&self.overpass_url)
+            .body(query)
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Failed to query Overpass API: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(Error::api_with_status(
+                format!("Overpass API returned {}", status),
+                "overpass",
+                status.as_u16(),
+            ));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse Overpass response: {}", e)))?;
+
+        // The most specific boundary (highest admin_level) wins
+        Ok(body
+            .get("elements")
+            .and_then(|e| e.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|element| {
+                let tags = element.get("tags")?;
+                let timezone = tags.get("timezone")?.as_str()?;
+                Tz::from_str(timezone).ok()?;
+                let level = tags
+                    .get("admin_level")
+                    .and_then(|l| l.as_str())
+                    .and_then(|l| l.parse::<u32>().ok())
+                    .unwrap_or(0);
+                Some((level, timezone.to_string()))
+            })
+            .max_by_key(|(level, _)| *level)
+            .map(|(_, timezone)| timezone))
+    }
+
+    /// Look up the timezone for a coordinate
+    pub async fn timezone_for(&self, point: &Point) -> Result<TimezoneInfo> {
+        let (timezone, source) = match self.osm_timezone(point).await {
+            Ok(Some(timezone)) => (timezone, "osm"),
+            Ok(None) => (nautical_timezone(point.lon), "nautical"),
+            Err(e) => {
+                tracing::debug!(error = %e, "Timezone lookup failed, using nautical zone");
+                (nautical_timezone(point.lon), "nautical")
+            }
+        };
+
+        let tz = parse_timezone(&timezone)?;
+        let now = Utc::now().with_timezone(&tz);
+        Ok(TimezoneInfo {
+            timezone,
+            utc_offset_seconds: now.offset().fix().local_minus_utc(),
+            local_time: now.to_rfc3339(),
+            source: source.to_string(),
+        })
+    }
+
+    /// Find meeting times across participants' timezones, calendars and travel
+    pub async fn find_meeting_times(&self, request: &MeetingRequest) -> Result<Vec<MeetingSlot>> {
+        if request.participants.is_empty() {
+            return Err(Error::validation_with_field(
+                "At least one participant is required",
+                "participants",
+            ));
+        }
+        if request.duration_minutes <= 0 {
+            return Err(Error::validation_with_field(
+                "Duration must be positive",
+                "duration_minutes",
+            ));
+        }
+        if request.latest <= request.earliest {
+            return Err(Error::validation_with_field(
+                "latest must be after earliest",
+                "latest",
+            ));
+        }
+
+        let zones = request
+            .participants
+            .iter()
+            .map(|p| parse_timezone(&p.timezone))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut travel = HashMap::new();
+        if let Some(location) = &request.location {
+            for (p, participant) in request.participants.iter().enumerate() {
+                for (b, busy) in participant.busy.iter().enumerate() {
+                    if let Some(busy_location) = &busy.location {
+                        let route = self
+                            .routing
+                            .route_or_estimate(busy_location, location, request.mode)
+                            .await;
+                        travel.insert((p, b), route.duration_s);
+                    }
+                }
+            }
+        }
+
+        Ok(find_slots(request, &zones, &travel))
+    }
+
+    /// Get tool definitions for time utilities
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let point_schema = json!({
+            "type": "object",
+            "properties": {
+                "lat": {"type": "number"},
+                "lon": {"type": "number"}
+            },
+            "required": ["lat", "lon"]
+        });
+        vec![
+            ToolDefinition::from_json_schema(
+                "timezone_for_location",
+                "Look up the IANA timezone and current UTC offset for coordinates",
+                "maps",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "lat": {"type": "number"},
+                        "lon": {"type": "number"}
+                    },
+                    "required": ["lat", "lon"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "sun_times",
+                "Calculate sunrise, sunset and solar noon for coordinates and a date",
+                "maps",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "lat": {"type": "number"},
+                        "lon": {"type": "number"},
+                        "date": {"type": "string", "description": "YYYY-MM-DD, defaults to today"},
+                        "timezone": {"type": "string", "description": "Timezone for local times in the summary"}
+                    },
+                    "required": ["lat", "lon"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "travel_time",
+                "Estimate travel time and distance between two points",
+                "maps",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "from": point_schema,
+                        "to": point_schema,
+                        "mode": {"type": "string", "enum": ["driving", "cycling", "walking"], "default": "driving"}
+                    },
+                    "required": ["from", "to"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "find_meeting_time",
+                "Find meeting slots across participants' timezones, working hours, busy blocks and travel time",
+                "maps",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "participants": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "name": {"type": "string"},
+                                    "timezone": {"type": "string", "description": "IANA timezone, e.g. Europe/Berlin"},
+                                    "working_hours": {"type": "array", "items": {"type": "integer"}, "description": "[start_hour, end_hour], default [9, 17]"},
+                                    "busy": {
+                                        "type": "array",
+                                        "items": {
+                                            "type": "object",
+                                            "properties": {
+                                                "start": {"type": "string"},
+                                                "end": {"type": "string"},
+                                                "location": point_schema
+                                            },
+                                            "required": ["start", "end"]
+                                        }
+                                    }
+                                },
+                                "required": ["name", "timezone"]
+                            }
+                        },
+                        "duration_minutes": {"type": "integer"},
+                        "earliest": {"type": "string", "description": "RFC 3339 start of the search window"},
+                        "latest": {"type": "string", "description": "RFC 3339 end of the search window"},
+                        "location": point_schema,
+                        "mode": {"type": "string", "enum": ["driving", "cycling", "walking"]},
+                        "step_minutes": {"type": "integer", "default": 30},
+                        "max_results": {"type": "integer", "default": 5}
+                    },
+                    "required": ["participants", "duration_minutes", "earliest", "latest"]
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    /// Execute a time utility tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let point = |value: Option<&Value>, field: &str| -> Result<Point> {
+            value
+                .cloned()
+                .map(serde_json::from_value::<Point>)
+                .transpose()
+                .map_err(|e| {
+                    Error::validation_with_field(format!("Invalid {}: {}", field, e), field)
+                })?
+                .ok_or_else(|| {
+                    Error::validation_with_field(format!("{} is required", field), field)
+                })
+        };
+
+        match name {
+            "timezone_for_location" => {
+                let location = point(Some(&parameters), "location")?;
+                let info = self.timezone_for(&location).await?;
+                Ok(call_result(
+                    format!(
+                        "{} (UTC{:+}h), local time {}",
+                        info.timezone,
+                        info.utc_offset_seconds as f64 / 3600.0,
+                        info.local_time
+                    ),
+                    serde_json::to_value(&info)?,
+                ))
+            }
+            "sun_times" => {
+                let location = point(Some(&parameters), "location")?;
+                let date = match parameters.get("date").and_then(|d| d.as_str()) {
+                    Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| {
+                        Error::validation_with_field(format!("Invalid date: {}", e), "date")
+                    })?,
+                    None => Utc::now().date_naive(),
+                };
+                let tz = parameters
+                    .get("timezone")
+                    .and_then(|t| t.as_str())
+                    .map(parse_timezone)
+                    .transpose()?
+                    .unwrap_or(Tz::UTC);
+
+                let times = sun_times(&location, date);
+                let local = |t: Option<DateTime<Utc>>| {
+                    t.map(|t| t.with_timezone(&tz).format("%H:%M %Z").to_string())
+                        .unwrap_or_else(|| "none".to_string())
+                };
+                let text = match &times.polar {
+                    Some(polar) => format!("Polar {} on {}", polar, date),
+                    None => format!(
+                        "Sunrise {}, sunset {} ({}h {}m of daylight)",
+                        local(times.sunrise),
+                        local(times.sunset),
+                        times.day_length_seconds / 3600,
+                        times.day_length_seconds % 3600 / 60
+                    ),
+                };
+                Ok(call_result(text, serde_json::to_value(&times)?))
+            }
+            "travel_time" => {
+                let from = point(parameters.get("from"), "from")?;
+                let to = point(parameters.get("to"), "to")?;
+                let mode = parameters
+                    .get("mode")
+                    .and_then(|m| m.as_str())
+                    .map(TravelMode::from_str)
+                    .transpose()?
+                    .unwrap_or(TravelMode::Driving);
+                let route = self.routing.route_or_estimate(&from, &to, mode).await;
+                Ok(call_result(
+                    format!(
+                        "{:.1} km, about {} min{}",
+                        route.distance_m / 1000.0,
+                        (route.duration_s / 60.0).round(),
+                        if route.estimated { " (estimated)" } else { "" }
+                    ),
+                    serde_json::to_value(&route)?,
+                ))
+            }
+            "find_meeting_time" => {
+                let request: MeetingRequest = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid meeting request: {}", e)))?;
+                let slots = self.find_meeting_times(&request).await?;
+                let text = if slots.is_empty() {
+                    "No slot satisfies every participant's constraints".to_string()
+                } else {
+                    slots
+                        .iter()
+                        .map(|s| {
+                            let locals = s
+                                .participants
+                                .iter()
+                                .map(|p| format!("{} {}", p.name, p.local_start))
+                                .collect::<Vec<_>>()
+                                .join("; ");
+                            format!("{} ({})", s.start.to_rfc3339(), locals)
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                Ok(call_result(text, json!({ "slots": slots })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "maps_time_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_london_solstice_sun_times() {
+        let london = Point {
+            lon: -0.1276,
+            lat: 51.5072,
+        };
+        let times = sun_times(&london, NaiveDate::from_ymd_opt(2024, 6, 21).unwrap());
+        let sunrise = times.sunrise.unwrap();
+        let sunset = times.sunset.unwrap();
+        // Published times: 03:43 and 20:21 UTC
+        assert!((sunrise.hour() * 60 + sunrise.minute()).abs_diff(3 * 60 + 43) <= 3);
+        assert!((sunset.hour() * 60 + sunset.minute()).abs_diff(20 * 60 + 21) <= 3);
+
+        let svalbard = Point {
+            lon: 15.6,
+            lat: 78.2,
+        };
+        let winter = sun_times(&svalbard, NaiveDate::from_ymd_opt(2024, 12, 21).unwrap());
+        assert_eq!(winter.polar.as_deref(), Some("night"));
+    }
+
+    #[test]
+    fn finds_overlap_respecting_busy_and_travel() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let request = MeetingRequest {
+            participants: vec![
+                Participant {
+                    name: "Berlin".to_string(),
+                    timezone: "Europe/Berlin".to_string(),
+                    working_hours: (9, 17),
+                    busy: vec![BusyInterval {
+                        start: at("2024-03-05T13:00:00Z"),
+                        end: at("2024-03-05T14:00:00Z"),
+                        location: Some(Point {
+                            lon: 13.40,
+                            lat: 52.52,
+                        }),
+                    }],
+                },
+                Participant {
+                    name: "New York".to_string(),
+                    timezone: "America/New_York".to_string(),
+                    working_hours: (9, 17),
+                    busy: Vec::new(),
+                },
+            ],
+            duration_minutes: 60,
+            earliest: at("2024-03-05T00:00:00Z"),
+            latest: at("2024-03-06T00:00:00Z"),
+            location: Some(Point {
+                lon: 13.45,
+                lat: 52.50,
+            }),
+            mode: TravelMode::Driving,
+            step_minutes: 30,
+            max_results: 10,
+        };
+        let zones: Vec<Tz> = vec![Tz::Europe__Berlin, Tz::America__New_York];
+        let mut travel = HashMap::new();
+        travel.insert((0, 0), 20.0 * 60.0);
+
+        // Overlap is 14:00-16:00 UTC; 14:00 is blocked by the 20 minute trip
+        let slots = find_slots(&request, &zones, &travel);
+        let starts: Vec<String> = slots
+            .iter()
+            .map(|s| s.start.format("%H:%M").to_string())
+            .collect();
+        assert_eq!(slots.len(), 2);
+        assert!(starts.contains(&"14:30".to_string()));
+        assert!(starts.contains(&"15:00".to_string()));
+        assert_eq!(slots[0].participants[0].travel_before_minutes, Some(20));
+    }
+}
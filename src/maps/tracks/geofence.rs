@@ -0,0 +1,607 @@
+use crate::error::{Error, Result};
+use crate::maps::osm::Point;
+use crate::maps::routing::haversine_m;
+use crate::smart_home::home_assistant::HomeAssistantClient;
+use crate::tools::{call_result, ToolDefinition};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Updates less accurate than this (in metres) are ignored by default
+const DEFAULT_MAX_ACCURACY_M: f64 = 200.0;
+
+/// Area covered by a geofence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum GeofenceShape {
+    Circle { center: Point, radius_m: f64 },
+    Polygon { points: Vec<Point> },
+}
+
+impl GeofenceShape {
+    /// Whether a point lies inside the shape
+    pub fn contains(&self, point: &Point) -> bool {
+        match self {
+            GeofenceShape::Circle { center, radius_m } => haversine_m(center, point) <= *radius_m,
+            GeofenceShape::Polygon { points } => {
+                // Ray casting on lon/lat, fine for fences of a few kilometres
+                let mut inside = false;
+                let mut j = points.len().wrapping_sub(1);
+                for (i, a) in points.iter().enumerate() {
+                    let b = &points[j];
+                    if (a.lat > point.lat) != (b.lat > point.lat)
+                        && point.lon
+                            < (b.lon - a.lon) * (point.lat - a.lat) / (b.lat - a.lat) + a.lon
+                    {
+                        inside = !inside;
+                    }
+                    j = i;
+                }
+                inside
+            }
+        }
+    }
+
+    /// Centre of a circle, or the mean of a polygon's vertices
+    pub fn center(&self) -> Point {
+        match self {
+            GeofenceShape::Circle { center, .. } => center.clone(),
+            GeofenceShape::Polygon { points } => {
+                let n = points.len().max(1) as f64;
+                Point {
+                    lon: points.iter().map(|p| p.lon).sum::<f64>() / n,
+                    lat: points.iter().map(|p| p.lat).sum::<f64>() / n,
+                }
+            }
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A named area whose boundary crossings raise events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Geofence {
+    pub id: String,
+    pub name: String,
+    pub shape: GeofenceShape,
+    /// `device_tracker` entities to watch; empty watches every tracker
+    #[serde(default)]
+    pub entities: Vec<String>,
+    /// Home Assistant notify services to call (e.g. `mobile_app_pixel`)
+    #[serde(default)]
+    pub notify: Vec<String>,
+    #[serde(default = "default_true")]
+    pub notify_on_enter: bool,
+    #[serde(default = "default_true")]
+    pub notify_on_exit: bool,
+}
+
+impl Geofence {
+    fn watches(&self, entity_id: &str) -> bool {
+        self.entities.is_empty() || self.entities.iter().any(|e| e == entity_id)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.id.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Geofence id is required",
+                "id",
+            ));
+        }
+        match &self.shape {
+            GeofenceShape::Circle { radius_m, .. } if *radius_m <= 0.0 => Err(
+                Error::validation_with_field("Circle radius must be positive", "radius_m"),
+            ),
+            GeofenceShape::Polygon { points } if points.len() < 3 => Err(
+                Error::validation_with_field("Polygon needs at least three points", "points"),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Direction of a boundary crossing
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GeofenceEventKind {
+    Enter,
+    Exit,
+}
+
+/// A tracker entering or leaving a geofence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeofenceEvent {
+    pub geofence_id: String,
+    pub geofence_name: String,
+    pub entity_id: String,
+    /// Tracker friendly name, if Home Assistant provided one
+    pub entity_name: Option<String>,
+    pub kind: GeofenceEventKind,
+    pub point: Point,
+    pub at: DateTime<Utc>,
+}
+
+impl GeofenceEvent {
+    /// Human readable description
+    pub fn message(&self) -> String {
+        let who = self.entity_name.as_deref().unwrap_or(&self.entity_id);
+        match self.kind {
+            GeofenceEventKind::Enter => format!("{} arrived at {}", who, self.geofence_name),
+            GeofenceEventKind::Exit => format!("{} left {}", who, self.geofence_name),
+        }
+    }
+}
+
+/// Delivers geofence notifications
+#[async_trait]
+pub trait GeofenceNotifier: Send + Sync {
+    async fn notify(&self, target: &str, event: &GeofenceEvent) -> Result<()>;
+}
+
+#[async_trait]
+impl GeofenceNotifier for HomeAssistantClient {
+    async fn notify(&self, target: &str, event: &GeofenceEvent) -> Result<()> {
+        self.send_notification(target, &event.geofence_name, &event.message())
+            .await
+            .map(|_| ())
+    }
+}
+
+/// A position extracted from a `device_tracker` state
+pub(crate) struct TrackerUpdate {
+    pub(crate) point: Point,
+    pub(crate) accuracy_m: Option<f64>,
+    pub(crate) name: Option<String>,
+    pub(crate) at: DateTime<Utc>,
+}
+
+impl TrackerUpdate {
+    /// Read a Home Assistant state object, or bare attributes, for a tracker
+    pub(crate) fn from_state(entity_id: &str, state: &Value) -> Result<Self> {
+        let attributes = state.get("attributes").unwrap_or(state);
+        let coordinate = |name: &str| {
+            attributes
+                .get(name)
+                .and_then(|v| v.as_f64())
+                .ok_or_else(|| {
+                    Error::validation(format!("{} has no {} attribute", entity_id, name))
+                })
+        };
+        Ok(Self {
+            point: Point {
+                lat: coordinate("latitude")?,
+                lon: coordinate("longitude")?,
+            },
+            accuracy_m: attributes.get("gps_accuracy").and_then(|v| v.as_f64()),
+            name: attributes
+                .get("friendly_name")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            at: state
+                .get("last_updated")
+                .and_then(|v| v.as_str())
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_else(Utc::now),
+        })
+    }
+}
+
+/// Geofence registry that turns tracker updates into enter/exit events
+///
+/// The first update seen for a tracker only records its presence, so a
+/// restart does not re-announce everyone who is already inside a fence.
+pub struct GeofenceManager {
+    fences: RwLock<BTreeMap<String, Geofence>>,
+    /// Last known presence keyed by (geofence id, entity id)
+    presence: RwLock<HashMap<(String, String), bool>>,
+    notifier: Option<Arc<dyn GeofenceNotifier>>,
+    home_assistant: Option<Arc<HomeAssistantClient>>,
+    max_accuracy_m: f64,
+}
+
+impl Default for GeofenceManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GeofenceManager {
+    /// Create an empty geofence manager
+    pub fn new() -> Self {
+        Self {
+            fences: RwLock::new(BTreeMap::new()),
+            presence: RwLock::new(HashMap::new()),
+            notifier: None,
+            home_assistant: None,
+            max_accuracy_m: DEFAULT_MAX_ACCURACY_M,
+        }
+    }
+
+    /// Use Home Assistant for tracker state and, unless overridden, notifications
+    pub fn with_home_assistant(mut self, client: Arc<HomeAssistantClient>) -> Self {
+        if self.notifier.is_none() {
+            self.notifier = Some(client.clone());
+        }
+        self.home_assistant = Some(client);
+        self
+    }
+
+    /// Set the notification backend
+    pub fn with_notifier(mut self, notifier: Arc<dyn GeofenceNotifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Ignore updates whose GPS accuracy is worse than this many metres
+    pub fn with_max_accuracy(mut self, metres: f64) -> Self {
+        self.max_accuracy_m = metres;
+        self
+    }
+
+    /// Updates less accurate than this many metres are ignored
+    pub fn max_accuracy(&self) -> f64 {
+        self.max_accuracy_m
+    }
+
+    /// Look up a geofence
+    pub async fn get_geofence(&self, id: &str) -> Option<Geofence> {
+        self.fences.read().await.get(id).cloned()
+    }
+
+    /// Add or replace a geofence
+    pub async fn add_geofence(&self, fence: Geofence) -> Result<()> {
+        fence.validate()?;
+        let id = fence.id.clone();
+        self.presence.write().await.retain(|(f, _), _| f != &id);
+        self.fences.write().await.insert(id, fence);
+        Ok(())
+    }
+
+    /// Remove a geofence
+    pub async fn remove_geofence(&self, id: &str) -> Result<Geofence> {
+        let fence =
+            self.fences.write().await.remove(id).ok_or_else(|| {
+                Error::not_found_with_resource("Geofence not found", "geofence", id)
+            })?;
+        self.presence.write().await.retain(|(f, _), _| f != id);
+        Ok(fence)
+    }
+
+    /// List geofences
+    pub async fn list_geofences(&self) -> Vec<Geofence> {
+        self.fences.read().await.values().cloned().collect()
+    }
+
+    /// Evaluate a `device_tracker` state update against every geofence
+    pub async fn handle_tracker_update(
+        &self,
+        entity_id: &str,
+        state: &Value,
+    ) -> Result<Vec<GeofenceEvent>> {
+        let update = TrackerUpdate::from_state(entity_id, state)?;
+        if update.accuracy_m.is_some_and(|a| a > self.max_accuracy_m) {
+            tracing::debug!(entity_id, accuracy = ?update.accuracy_m, "Ignoring inaccurate tracker update");
+            return Ok(Vec::new());
+        }
+
+        let mut events = Vec::new();
+        let mut pending = Vec::new();
+        {
+            let fences = self.fences.read().await;
+            let mut presence = self.presence.write().await;
+            for fence in fences.values().filter(|f| f.watches(entity_id)) {
+                let inside = fence.shape.contains(&update.point);
+                let key = (fence.id.clone(), entity_id.to_string());
+                let previous = presence.insert(key, inside);
+                let kind = match (previous, inside) {
+                    (Some(false), true) => GeofenceEventKind::Enter,
+                    (Some(true), false) => GeofenceEventKind::Exit,
+                    _ => continue,
+                };
+
+                let event = GeofenceEvent {
+                    geofence_id: fence.id.clone(),
+                    geofence_name: fence.name.clone(),
+                    entity_id: entity_id.to_string(),
+                    entity_name: update.name.clone(),
+                    kind,
+                    point: update.point.clone(),
+                    at: update.at,
+                };
+                let wanted = match kind {
+                    GeofenceEventKind::Enter => fence.notify_on_enter,
+                    GeofenceEventKind::Exit => fence.notify_on_exit,
+                };
+                if wanted {
+                    pending.extend(fence.notify.iter().map(|t| (t.clone(), event.clone())));
+                }
+                events.push(event);
+            }
+        }
+
+        // A failed notification should not lose the event itself
+        if let Some(notifier) = &self.notifier {
+            for (target, event) in pending {
+                if let Err(e) = notifier.notify(&target, &event).await {
+                    tracing::warn!(error = %e, target, "Failed to send geofence notification");
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Fetch a tracker's current state from Home Assistant and evaluate it
+    pub async fn refresh_tracker(&self, entity_id: &str) -> Result<Vec<GeofenceEvent>> {
+        let client = self.home_assistant.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Home Assistant is not configured for geofencing",
+                "Pass the tracker state or coordinates explicitly",
+            )
+        })?;
+        let state = client.get_entity(entity_id).await?;
+        self.handle_tracker_update(entity_id, &state).await
+    }
+
+    /// Get tool definitions for geofencing
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "define_geofence",
+                "Create or replace a circular or polygon geofence that notifies on device_tracker enter/exit",
+                "maps",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "name": {"type": "string"},
+                        "shape": {
+                            "type": "object",
+                            "description": "{\"type\": \"circle\", \"center\": {\"lat\", \"lon\"}, \"radius_m\"} or {\"type\": \"polygon\", \"points\": [{\"lat\", \"lon\"}, ...]}"
+                        },
+                        "entities": {"type": "array", "items": {"type": "string"}, "description": "device_tracker entities to watch, default all"},
+                        "notify": {"type": "array", "items": {"type": "string"}, "description": "Home Assistant notify services, e.g. mobile_app_pixel"},
+                        "notify_on_enter": {"type": "boolean", "default": true},
+                        "notify_on_exit": {"type": "boolean", "default": true}
+                    },
+                    "required": ["id", "name", "shape"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "remove_geofence",
+                "Remove a geofence",
+                "maps",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "list_geofences",
+                "List defined geofences",
+                "maps",
+                json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "evaluate_device_tracker",
+                "Evaluate a device_tracker position against geofences and send enter/exit notifications",
+                "maps",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": {"type": "string", "description": "e.g. device_tracker.pixel"},
+                        "state": {"type": "object", "description": "Home Assistant state object; fetched from Home Assistant when omitted"},
+                        "lat": {"type": "number"},
+                        "lon": {"type": "number"}
+                    },
+                    "required": ["entity_id"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a geofence tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "define_geofence" => {
+                let fence: Geofence = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid geofence: {}", e)))?;
+                let text = format!("Geofence '{}' saved", fence.name);
+                let value = serde_json::to_value(&fence)?;
+                self.add_geofence(fence).await?;
+                Ok(call_result(text, value))
+            }
+            "remove_geofence" => {
+                let id = parameters
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::validation_with_field("id is required", "id"))?;
+                let fence = self.remove_geofence(id).await?;
+                Ok(call_result(
+                    format!("Geofence '{}' removed", fence.name),
+                    json!({ "id": fence.id }),
+                ))
+            }
+            "list_geofences" => {
+                let fences = self.list_geofences().await;
+                let text = if fences.is_empty() {
+                    "No geofences defined".to_string()
+                } else {
+                    fences
+                        .iter()
+                        .map(|f| format!("{} ({})", f.name, f.id))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                Ok(call_result(text, json!({ "geofences": fences })))
+            }
+            "evaluate_device_tracker" => {
+                let entity_id = parameters
+                    .get("entity_id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| {
+                        Error::validation_with_field("entity_id is required", "entity_id")
+                    })?;
+                let events = match (
+                    parameters.get("state"),
+                    parameters.get("lat").and_then(|v| v.as_f64()),
+                    parameters.get("lon").and_then(|v| v.as_f64()),
+                ) {
+                    (Some(state), _, _) => self.handle_tracker_update(entity_id, state).await?,
+                    (None, Some(lat), Some(lon)) => {
+                        let state = json!({ "latitude": lat, "longitude": lon });
+                        self.handle_tracker_update(entity_id, &state).await?
+                    }
+                    _ => self.refresh_tracker(entity_id).await?,
+                };
+                let text = if events.is_empty() {
+                    format!("No geofence changes for {}", entity_id)
+                } else {
+                    events
+                        .iter()
+                        .map(GeofenceEvent::message)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                Ok(call_result(text, json!({ "events": events })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "geofence_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingNotifier(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl GeofenceNotifier for RecordingNotifier {
+        async fn notify(&self, target: &str, event: &GeofenceEvent) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}: {}", target, event.message()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn emits_enter_and_exit_after_first_update() {
+        let notifier = Arc::new(RecordingNotifier::default());
+        let manager = GeofenceManager::new().with_notifier(notifier.clone());
+        manager
+            .add_geofence(Geofence {
+                id: "home".to_string(),
+                name: "Home".to_string(),
+                shape: GeofenceShape::Circle {
+                    center: Point {
+                        lon: 13.40,
+                        lat: 52.52,
+                    },
+                    radius_m: 150.0,
+                },
+                entities: vec!["device_tracker.pixel".to_string()],
+                notify: vec!["mobile_app_pixel".to_string()],
+                notify_on_enter: true,
+                notify_on_exit: false,
+            })
+            .await
+            .unwrap();
+
+        let at = |lat: f64, accuracy: f64| {
+            json!({
+                "entity_id": "device_tracker.pixel",
+                "attributes": {"latitude": lat, "longitude": 13.40, "gps_accuracy": accuracy, "friendly_name": "Sam"}
+            })
+        };
+        let tracker = "device_tracker.pixel";
+        assert!(manager
+            .handle_tracker_update(tracker, &at(52.53, 10.0))
+            .await
+            .unwrap()
+            .is_empty());
+        // Too inaccurate to trust
+        assert!(manager
+            .handle_tracker_update(tracker, &at(52.52, 500.0))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let entered = manager
+            .handle_tracker_update(tracker, &at(52.5201, 10.0))
+            .await
+            .unwrap();
+        assert_eq!(entered[0].kind, GeofenceEventKind::Enter);
+        let left = manager
+            .handle_tracker_update(tracker, &at(52.53, 10.0))
+            .await
+            .unwrap();
+        assert_eq!(left[0].kind, GeofenceEventKind::Exit);
+
+        // Other trackers are not watched, and exits are not notified
+        assert!(manager
+            .handle_tracker_update("device_tracker.other", &at(52.52, 10.0))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            *notifier.0.lock().unwrap(),
+            vec!["mobile_app_pixel: Sam arrived at Home".to_string()]
+        );
+    }
+
+    #[test]
+    fn polygon_contains_point() {
+        let square = GeofenceShape::Polygon {
+            points: vec![
+                Point { lon: 0.0, lat: 0.0 },
+                Point {
+                    lon: 0.01,
+                    lat: 0.0,
+                },
+                Point {
+                    lon: 0.01,
+                    lat: 0.01,
+                },
+                Point {
+                    lon: 0.0,
+                    lat: 0.01,
+                },
+            ],
+        };
+        assert!(square.contains(&Point {
+            lon: 0.005,
+            lat: 0.005
+        }));
+        assert!(!square.contains(&Point {
+            lon: 0.02,
+            lat: 0.005
+        }));
+    }
+}
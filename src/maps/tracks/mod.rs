@@ -0,0 +1,525 @@
+/// GPX/KML track import, statistics and geofencing
+///
+/// Tracks and waypoints are read from GPX 1.0/1.1 and KML (including
+/// `gx:Track`). Geofences turn Home Assistant `device_tracker` updates into
+/// enter/exit events and notifications.
+use crate::error::{Error, Result};
+use crate::maps::osm::Point;
+use crate::maps::routing::haversine_m;
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::str::FromStr;
+
+pub mod geofence;
+pub mod xml;
+
+pub use geofence::{
+    Geofence, GeofenceEvent, GeofenceEventKind, GeofenceManager, GeofenceNotifier, GeofenceShape,
+};
+
+/// Elevation changes smaller than this are treated as GPS noise
+const ELEVATION_HYSTERESIS_M: f64 = 2.0;
+
+/// Speeds below this count as stopped when computing moving time
+const MOVING_SPEED_MS: f64 = 0.5;
+
+/// Track file format
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackFormat {
+    Gpx,
+    Kml,
+}
+
+impl FromStr for TrackFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "gpx" => Ok(TrackFormat::Gpx),
+            "kml" => Ok(TrackFormat::Kml),
+            other => Err(Error::validation_with_field(
+                format!("Unsupported track format: {}", other),
+                "format",
+            )),
+        }
+    }
+}
+
+/// A single recorded or named position
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Waypoint {
+    pub lat: f64,
+    pub lon: f64,
+    pub elevation_m: Option<f64>,
+    pub time: Option<DateTime<Utc>>,
+    pub name: Option<String>,
+}
+
+impl Waypoint {
+    /// Position as a map point
+    pub fn point(&self) -> Point {
+        Point {
+            lon: self.lon,
+            lat: self.lat,
+        }
+    }
+}
+
+/// A track made of one or more continuous segments
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Track {
+    pub name: Option<String>,
+    pub segments: Vec<Vec<Waypoint>>,
+}
+
+/// Everything read from a track file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrackFile {
+    pub tracks: Vec<Track>,
+    pub waypoints: Vec<Waypoint>,
+}
+
+/// Distance, elevation and timing statistics for a track
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrackStats {
+    pub points: usize,
+    pub distance_m: f64,
+    pub elevation_gain_m: f64,
+    pub elevation_loss_m: f64,
+    pub min_elevation_m: Option<f64>,
+    pub max_elevation_m: Option<f64>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration_s: Option<i64>,
+    pub moving_time_s: Option<i64>,
+    pub max_speed_ms: Option<f64>,
+    pub average_moving_speed_ms: Option<f64>,
+}
+
+impl Track {
+    /// Compute statistics across all segments
+    pub fn stats(&self) -> TrackStats {
+        let mut stats = TrackStats::default();
+        let mut moving_time = 0.0;
+        let mut moving_distance = 0.0;
+        let mut timed = false;
+
+        for segment in &self.segments {
+            stats.points += segment.len();
+            let mut reference_elevation: Option<f64> = None;
+
+            for (i, point) in segment.iter().enumerate() {
+                if let Some(elevation) = point.elevation_m {
+                    stats.min_elevation_m = Some(
+                        stats
+                            .min_elevation_m
+                            .map_or(elevation, |m| m.min(elevation)),
+                    );
+                    stats.max_elevation_m = Some(
+                        stats
+                            .max_elevation_m
+                            .map_or(elevation, |m| m.max(elevation)),
+                    );
+                    match reference_elevation {
+                        Some(reference)
+                            if (elevation - reference).abs() >= ELEVATION_HYSTERESIS_M =>
+                        {
+                            if elevation > reference {
+                                stats.elevation_gain_m += elevation - reference;
+                            } else {
+                                stats.elevation_loss_m += reference - elevation;
+                            }
+                            reference_elevation = Some(elevation);
+                        }
+                        Some(_) => {}
+                        None => reference_elevation = Some(elevation),
+                    }
+                }
+                if let Some(time) = point.time {
+                    stats.start_time = Some(stats.start_time.map_or(time, |t| t.min(time)));
+                    stats.end_time = Some(stats.end_time.map_or(time, |t| t.max(time)));
+                }
+
+                let Some(previous) = i.checked_sub(1).map(|p| &segment[p]) else {
+                    continue;
+                };
+                let distance = haversine_m(&previous.point(), &point.point());
+                stats.distance_m += distance;
+
+                if let (Some(t0), Some(t1)) = (previous.time, point.time) {
+                    let seconds = (t1 - t0).num_milliseconds() as f64 / 1000.0;
+                    if seconds > 0.0 {
+                        timed = true;
+                        let speed = distance / seconds;
+                        if speed >= MOVING_SPEED_MS {
+                            moving_time += seconds;
+                            moving_distance += distance;
+                            stats.max_speed_ms =
+                                Some(stats.max_speed_ms.map_or(speed, |m| m.max(speed)));
+                        }
+                    }
+                }
+            }
+        }
+
+        if let (Some(start), Some(end)) = (stats.start_time, stats.end_time) {
+            stats.duration_s = Some((end - start).num_seconds());
+        }
+        if timed {
+            stats.moving_time_s = Some(moving_time.round() as i64);
+            if moving_time > 0.0 {
+                stats.average_moving_speed_ms = Some(moving_distance / moving_time);
+            }
+        }
+        stats
+    }
+}
+
+fn parse_time(value: Option<&str>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn gpx_point(element: &xml::Element) -> Result<Waypoint> {
+    let coordinate = |name: &str| -> Result<f64> {
+        element
+            .attr(name)
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| {
+                Error::parsing_with_format(
+                    format!(
+                        "<{}> has a missing or invalid {} attribute",
+                        element.name, name
+                    ),
+                    "gpx",
+                    None,
+                )
+            })
+    };
+    Ok(Waypoint {
+        lat: coordinate("lat")?,
+        lon: coordinate("lon")?,
+        elevation_m: element.child_text("ele").and_then(|e| e.parse().ok()),
+        time: parse_time(element.child_text("time")),
+        name: element.child_text("name").map(str::to_string),
+    })
+}
+
+/// Parse a GPX document
+pub fn parse_gpx(source: &str) -> Result<TrackFile> {
+    let root = xml::parse(source)?;
+    if root.local_name() != "gpx" {
+        return Err(Error::parsing_with_format(
+            format!("Expected <gpx> root, found <{}>", root.name),
+            "gpx",
+            None,
+        ));
+    }
+
+    let mut file = TrackFile {
+        waypoints: root
+            .children_named("wpt")
+            .map(gpx_point)
+            .collect::<Result<_>>()?,
+        ..Default::default()
+    };
+    for trk in root.children_named("trk") {
+        file.tracks.push(Track {
+            name: trk.child_text("name").map(str::to_string),
+            segments: trk
+                .children_named("trkseg")
+                .map(|seg| seg.children_named("trkpt").map(gpx_point).collect())
+                .collect::<Result<_>>()?,
+        });
+    }
+    // Planned routes are read as single-segment tracks
+    for rte in root.children_named("rte") {
+        file.tracks.push(Track {
+            name: rte.child_text("name").map(str::to_string),
+            segments: vec![rte
+                .children_named("rtept")
+                .map(gpx_point)
+                .collect::<Result<_>>()?],
+        });
+    }
+    Ok(file)
+}
+
+/// Parse a KML coordinate tuple ("lon,lat[,alt]" or "lon lat [alt]" for gx:coord)
+fn kml_coordinate(tuple: &str, separator: char) -> Result<Waypoint> {
+    let parts: Vec<f64> = tuple
+        .split(separator)
+        .filter(|p| !p.is_empty())
+        .map(|p| p.trim().parse::<f64>())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|e| {
+            Error::parsing_with_format(
+                format!("Invalid KML coordinate '{}': {}", tuple, e),
+                "kml",
+                None,
+            )
+        })?;
+    if parts.len() < 2 {
+        return Err(Error::parsing_with_format(
+            format!("Invalid KML coordinate '{}'", tuple),
+            "kml",
+            None,
+        ));
+    }
+    Ok(Waypoint {
+        lon: parts[0],
+        lat: parts[1],
+        elevation_m: parts.get(2).copied(),
+        time: None,
+        name: None,
+    })
+}
+
+fn kml_coordinates(element: &xml::Element) -> Result<Vec<Waypoint>> {
+    element
+        .child("coordinates")
+        .map(|c| {
+            c.text
+                .split_whitespace()
+                .map(|t| kml_coordinate(t, ','))
+                .collect()
+        })
+        .unwrap_or_else(|| Ok(Vec::new()))
+}
+
+/// Parse a KML document
+pub fn parse_kml(source: &str) -> Result<TrackFile> {
+    let root = xml::parse(source)?;
+    if root.local_name() != "kml" {
+        return Err(Error::parsing_with_format(
+            format!("Expected <kml> root, found <{}>", root.name),
+            "kml",
+            None,
+        ));
+    }
+
+    let mut placemarks = Vec::new();
+    root.descendants("Placemark", &mut placemarks);
+
+    let mut file = TrackFile::default();
+    for placemark in placemarks {
+        let name = placemark.child_text("name").map(str::to_string);
+        let mut segments = Vec::new();
+
+        let mut points = Vec::new();
+        placemark.descendants("Point", &mut points);
+        for point in points {
+            for mut waypoint in kml_coordinates(point)? {
+                waypoint.name = name.clone();
+                file.waypoints.push(waypoint);
+            }
+        }
+
+        let mut lines = Vec::new();
+        placemark.descendants("LineString", &mut lines);
+        for line in lines {
+            segments.push(kml_coordinates(line)?);
+        }
+
+        let mut gx_tracks = Vec::new();
+        placemark.descendants("Track", &mut gx_tracks);
+        for track in gx_tracks {
+            let times: Vec<_> = track
+                .children_named("when")
+                .map(|w| parse_time(Some(w.text.trim())))
+                .collect();
+            let mut segment = Vec::new();
+            for (i, coord) in track.children_named("coord").enumerate() {
+                let mut waypoint = kml_coordinate(coord.text.trim(), ' ')?;
+                waypoint.time = times.get(i).copied().flatten();
+                segment.push(waypoint);
+            }
+            segments.push(segment);
+        }
+
+        if !segments.is_empty() {
+            file.tracks.push(Track { name, segments });
+        }
+    }
+    Ok(file)
+}
+
+/// Parse a track file, detecting the format from the root element when not given
+pub fn parse_track(source: &str, format: Option<TrackFormat>) -> Result<TrackFile> {
+    let format = match format {
+        Some(format) => format,
+        None if source.contains("<gpx") => TrackFormat::Gpx,
+        None if source.contains("<kml") => TrackFormat::Kml,
+        None => {
+            return Err(Error::parsing_with_format(
+                "Unrecognised track file",
+                "gpx or kml",
+                None,
+            ))
+        }
+    };
+    match format {
+        TrackFormat::Gpx => parse_gpx(source),
+        TrackFormat::Kml => parse_kml(source),
+    }
+}
+
+/// Track import tools
+#[derive(Debug, Clone, Default)]
+pub struct TrackTools;
+
+impl TrackTools {
+    /// Create track tools
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Read and parse a track file from disk
+    pub async fn load(&self, path: &Path) -> Result<TrackFile> {
+        let source = tokio::fs::read_to_string(path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to read track file: {}", e),
+                path.to_path_buf(),
+            )
+        })?;
+        let format = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| TrackFormat::from_str(e).ok());
+        parse_track(&source, format)
+    }
+
+    /// Get tool definitions for track import
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "import_track",
+            "Parse a GPX or KML file into tracks and waypoints with distance, elevation and timing statistics",
+            "maps",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "Path to a .gpx or .kml file"},
+                    "content": {"type": "string", "description": "GPX or KML document, instead of path"},
+                    "format": {"type": "string", "enum": ["gpx", "kml"]},
+                    "include_points": {"type": "boolean", "default": false, "description": "Include every track point in the result"}
+                }
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a track tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "import_track" => {
+                let format = parameters
+                    .get("format")
+                    .and_then(|f| f.as_str())
+                    .map(TrackFormat::from_str)
+                    .transpose()?;
+                let file = match (
+                    parameters.get("content").and_then(|c| c.as_str()),
+                    parameters.get("path").and_then(|p| p.as_str()),
+                ) {
+                    (Some(content), _) => parse_track(content, format)?,
+                    (None, Some(path)) => self.load(Path::new(path)).await?,
+                    (None, None) => {
+                        return Err(Error::validation("Either content or path is required"))
+                    }
+                };
+                let include_points = parameters
+                    .get("include_points")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+
+                let mut lines = vec![format!(
+                    "{} track(s), {} waypoint(s)",
+                    file.tracks.len(),
+                    file.waypoints.len()
+                )];
+                let tracks: Vec<Value> = file
+                    .tracks
+                    .iter()
+                    .map(|track| {
+                        let stats = track.stats();
+                        lines.push(format!(
+                            "{}: {:.2} km, +{:.0} m / -{:.0} m{}",
+                            track.name.as_deref().unwrap_or("Unnamed track"),
+                            stats.distance_m / 1000.0,
+                            stats.elevation_gain_m,
+                            stats.elevation_loss_m,
+                            stats
+                                .duration_s
+                                .map(|d| format!(", {}h {:02}m", d / 3600, d % 3600 / 60))
+                                .unwrap_or_default()
+                        ));
+                        let mut value = json!({ "name": track.name, "stats": stats });
+                        if include_points {
+                            value["segments"] = json!(track.segments);
+                        }
+                        value
+                    })
+                    .collect();
+
+                Ok(call_result(
+                    lines.join("\n"),
+                    json!({ "tracks": tracks, "waypoints": file.waypoints }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "track_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gpx_and_kml_with_stats() {
+        let gpx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+  <wpt lat="51.5" lon="-0.1"><name>Start &amp; finish</name></wpt>
+  <trk><name>Loop</name><trkseg>
+    <trkpt lat="51.5000" lon="-0.1000"><ele>10</ele><time>2024-05-01T10:00:00Z</time></trkpt>
+    <trkpt lat="51.5090" lon="-0.1000"><ele>25</ele><time>2024-05-01T10:05:00Z</time></trkpt>
+    <trkpt lat="51.5090" lon="-0.1000"><ele>26</ele><time>2024-05-01T10:10:00Z</time></trkpt>
+    <trkpt lat="51.5000" lon="-0.1000"><ele>12</ele><time>2024-05-01T10:15:00Z</time></trkpt>
+  </trkseg></trk>
+</gpx>"#;
+        let file = parse_track(gpx, None).unwrap();
+        assert_eq!(file.waypoints[0].name.as_deref(), Some("Start & finish"));
+        let stats = file.tracks[0].stats();
+        assert!((stats.distance_m - 2001.0).abs() < 5.0);
+        assert_eq!(stats.elevation_gain_m, 15.0);
+        assert_eq!(stats.elevation_loss_m, 13.0);
+        assert_eq!(stats.duration_s, Some(900));
+        assert_eq!(stats.moving_time_s, Some(600));
+
+        let kml = r#"<kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">
+<Document><Folder>
+  <Placemark><name>Office</name><Point><coordinates>13.4,52.5,0</coordinates></Point></Placemark>
+  <Placemark><name>Walk</name><LineString><coordinates>
+    13.40,52.50,30 13.41,52.50,35
+  </coordinates></LineString></Placemark>
+  <Placemark><gx:Track>
+    <when>2024-05-01T10:00:00Z</when><when>2024-05-01T10:01:00Z</when>
+    <gx:coord>13.40 52.50 30</gx:coord><gx:coord>13.40 52.51 30</gx:coord>
+  </gx:Track></Placemark>
+</Folder></Document></kml>"#;
+        let file = parse_track(kml, None).unwrap();
+        assert_eq!(file.waypoints.len(), 1);
+        assert_eq!(file.tracks.len(), 2);
+        assert_eq!(file.tracks[0].name.as_deref(), Some("Walk"));
+        assert_eq!(file.tracks[1].stats().duration_s, Some(60));
+    }
+}
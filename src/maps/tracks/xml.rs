@@ -0,0 +1,213 @@
+//! Minimal XML reader for GPX and KML
+//!
+//! Builds an element tree with attributes and text. Namespace prefixes are
+//! kept on names (`gx:coord`); DTDs and processing instructions are skipped.
+
+use crate::error::{Error, Result};
+
+/// An XML element
+#[derive(Debug, Clone, Default)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    /// Local name without namespace prefix
+    pub fn local_name(&self) -> &str {
+        self.name.rsplit(':').next().unwrap_or(&self.name)
+    }
+
+    /// Attribute value by name
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// First direct child with the given local name
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.local_name() == name)
+    }
+
+    /// Direct children with the given local name
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.local_name() == name)
+    }
+
+    /// Trimmed text of the first direct child with the given local name
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name)
+            .map(|c| c.text.trim())
+            .filter(|t| !t.is_empty())
+    }
+
+    /// All descendants (depth first) with the given local name
+    pub fn descendants<'a>(&'a self, name: &str, out: &mut Vec<&'a Element>) {
+        for child in &self.children {
+            if child.local_name() == name {
+                out.push(child);
+            }
+            child.descendants(name, out);
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('&') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16)
+                .ok()
+                .and_then(char::from_u32),
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn parse_tag(tag: &str) -> Result<(String, Vec<(String, String)>)> {
+    let tag = tag.trim();
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let name = tag[..name_end].to_string();
+    if name.is_empty() {
+        return Err(Error::parsing("XML element without a name"));
+    }
+
+    let mut attributes = Vec::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let eq = rest
+            .find('=')
+            .ok_or_else(|| Error::parsing(format!("Malformed attribute in <{}>", name)))?;
+        let key = rest[..eq].trim().to_string();
+        let after = rest[eq + 1..].trim_start();
+        let quote = after
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| Error::parsing(format!("Unquoted attribute in <{}>", name)))?;
+        let close = after[1..]
+            .find(quote)
+            .ok_or_else(|| Error::parsing(format!("Unterminated attribute in <{}>", name)))?;
+        attributes.push((key, decode_entities(&after[1..close + 1])));
+        rest = after[close + 2..].trim_start();
+    }
+    Ok((name, attributes))
+}
+
+/// Parse a document and return its root element
+pub fn parse(source: &str) -> Result<Element> {
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root: Option<Element> = None;
+    let mut rest = source.trim_start_matches('\u{feff}');
+
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            if let Some(current) = stack.last_mut() {
+                current.text.push_str(&decode_entities(rest));
+            }
+            break;
+        };
+        if lt > 0 {
+            if let Some(current) = stack.last_mut() {
+                current.text.push_str(&decode_entities(&rest[..lt]));
+            }
+        }
+        rest = &rest[lt..];
+
+        if let Some(body) = rest.strip_prefix("<!--") {
+            let end = body
+                .find("-->")
+                .ok_or_else(|| Error::parsing("Unterminated XML comment"))?;
+            rest = &body[end + 3..];
+        } else if let Some(body) = rest.strip_prefix("<![CDATA[") {
+            let end = body
+                .find("]]>")
+                .ok_or_else(|| Error::parsing("Unterminated CDATA section"))?;
+            if let Some(current) = stack.last_mut() {
+                current.text.push_str(&body[..end]);
+            }
+            rest = &body[end + 3..];
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            let end = rest
+                .find('>')
+                .ok_or_else(|| Error::parsing("Unterminated XML declaration"))?;
+            rest = &rest[end + 1..];
+        } else {
+            let end = rest
+                .find('>')
+                .ok_or_else(|| Error::parsing("Unterminated XML tag"))?;
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+
+            if let Some(name) = tag.strip_prefix('/') {
+                let element = stack
+                    .pop()
+                    .ok_or_else(|| Error::parsing(format!("Unexpected </{}>", name.trim())))?;
+                if element.name != name.trim() {
+                    return Err(Error::parsing(format!(
+                        "Mismatched </{}>, expected </{}>",
+                        name.trim(),
+                        element.name
+                    )));
+                }
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => root = Some(element),
+                }
+            } else {
+                let self_closing = tag.ends_with('/');
+                let (name, attributes) = parse_tag(tag.trim_end_matches('/'))?;
+                let element = Element {
+                    name,
+                    attributes,
+                    ..Default::default()
+                };
+                if self_closing {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => root = Some(element),
+                    }
+                } else {
+                    stack.push(element);
+                }
+            }
+        }
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(Error::parsing(format!("Unclosed <{}>", open.name)));
+    }
+    root.ok_or_else(|| Error::parsing("XML document has no root element"))
+}
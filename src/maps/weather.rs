@@ -0,0 +1,823 @@
+/// Weather forecasts and alerts
+///
+/// Forecasts come from Open-Meteo, which needs no API key and covers the
+/// whole world. Official alerts come from the US National Weather Service;
+/// elsewhere, alerts are derived from the forecast itself.
+use crate::error::{Error, Result};
+use crate::maps::osm::Point;
+use crate::tools::{call_result, ToolDefinition};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Weather service configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherConfig {
+    /// Open-Meteo forecast endpoint
+    pub open_meteo_url: String,
+    /// National Weather Service API URL
+    pub nws_url: String,
+    /// User-Agent sent to the NWS, which rejects anonymous requests
+    pub user_agent: String,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        Self {
+            open_meteo_url: "https://api.open-meteo.com/v1/forecast".to_string(),
+            nws_url: "https://api.weather.gov".to_string(),
+            user_agent: concat!(
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION"),
+                " (+",
+                env!("CARGO_PKG_REPOSITORY"),
+                ")"
+            )
+            .to_string(),
+        }
+    }
+}
+
+/// Measurement units
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    Metric,
+    Imperial,
+}
+
+impl FromStr for Units {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "metric" | "si" => Ok(Units::Metric),
+            "imperial" | "us" => Ok(Units::Imperial),
+            other => Err(Error::validation_with_field(
+                format!("Unsupported units: {}", other),
+                "units",
+            )),
+        }
+    }
+}
+
+impl Units {
+    fn temperature(&self) -> &'static str {
+        match self {
+            Units::Metric => "°C",
+            Units::Imperial => "°F",
+        }
+    }
+
+    fn speed(&self) -> &'static str {
+        match self {
+            Units::Metric => "km/h",
+            Units::Imperial => "mph",
+        }
+    }
+
+    fn precipitation(&self) -> &'static str {
+        match self {
+            Units::Metric => "mm",
+            Units::Imperial => "in",
+        }
+    }
+
+    /// Temperature at or below which frost is likely
+    fn frost_threshold(&self) -> f64 {
+        match self {
+            Units::Metric => 0.0,
+            Units::Imperial => 32.0,
+        }
+    }
+
+    /// Gust speed considered damaging
+    fn high_wind_threshold(&self) -> f64 {
+        match self {
+            Units::Metric => 75.0,
+            Units::Imperial => 46.0,
+        }
+    }
+}
+
+/// Describe a WMO weather interpretation code
+pub fn describe_weather_code(code: u32) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Fog",
+        51 | 53 | 55 => "Drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 => "Light rain",
+        63 => "Rain",
+        65 => "Heavy rain",
+        66 | 67 => "Freezing rain",
+        71 => "Light snow",
+        73 => "Snow",
+        75 => "Heavy snow",
+        77 => "Snow grains",
+        80 | 81 => "Rain showers",
+        82 => "Violent rain showers",
+        85 | 86 => "Snow showers",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => "Unknown",
+    }
+}
+
+/// Current conditions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentConditions {
+    /// Local time of the observation
+    pub time: String,
+    pub temperature: f64,
+    pub apparent_temperature: Option<f64>,
+    pub humidity: Option<f64>,
+    pub precipitation: Option<f64>,
+    pub wind_speed: Option<f64>,
+    pub wind_gusts: Option<f64>,
+    pub wind_direction: Option<f64>,
+    pub weather_code: u32,
+    pub description: String,
+    pub is_day: bool,
+}
+
+/// One hour of forecast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourlyForecast {
+    pub time: String,
+    pub temperature: Option<f64>,
+    pub precipitation_probability: Option<f64>,
+    pub precipitation: Option<f64>,
+    pub wind_speed: Option<f64>,
+    pub wind_gusts: Option<f64>,
+    pub weather_code: Option<u32>,
+}
+
+/// One day of forecast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyForecast {
+    pub date: String,
+    pub temperature_min: Option<f64>,
+    pub temperature_max: Option<f64>,
+    pub precipitation_sum: Option<f64>,
+    pub precipitation_probability_max: Option<f64>,
+    pub wind_gusts_max: Option<f64>,
+    pub weather_code: Option<u32>,
+    pub description: Option<String>,
+    pub sunrise: Option<String>,
+    pub sunset: Option<String>,
+}
+
+/// Yes/no signals over the hourly forecast, for automations
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ForecastFlags {
+    /// Precipitation probability of at least 50% in the next 6 hours
+    pub rain_next_6h: bool,
+    /// Temperature at or below freezing in the next 24 hours
+    pub frost_next_24h: bool,
+    /// Damaging gusts in the next 24 hours
+    pub high_wind_next_24h: bool,
+    /// Thunderstorms in the next 24 hours
+    pub thunderstorm_next_24h: bool,
+}
+
+/// A forecast for a location
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Forecast {
+    pub location: Point,
+    pub timezone: String,
+    pub units: Units,
+    pub current: Option<CurrentConditions>,
+    pub hourly: Vec<HourlyForecast>,
+    pub daily: Vec<DailyForecast>,
+    pub flags: ForecastFlags,
+}
+
+/// Alert severity, ordered from least to most severe
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Unknown,
+    Minor,
+    Moderate,
+    Severe,
+    Extreme,
+}
+
+impl FromStr for Severity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "unknown" => Ok(Severity::Unknown),
+            "minor" => Ok(Severity::Minor),
+            "moderate" => Ok(Severity::Moderate),
+            "severe" => Ok(Severity::Severe),
+            "extreme" => Ok(Severity::Extreme),
+            other => Err(Error::validation_with_field(
+                format!("Unknown severity: {}", other),
+                "min_severity",
+            )),
+        }
+    }
+}
+
+/// A weather alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherAlert {
+    pub id: String,
+    pub event: String,
+    pub severity: Severity,
+    pub headline: Option<String>,
+    pub description: Option<String>,
+    pub instruction: Option<String>,
+    pub area: Option<String>,
+    pub onset: Option<String>,
+    pub expires: Option<String>,
+    /// "nws" for official alerts, "forecast" for ones derived from the forecast
+    pub source: String,
+}
+
+fn column<'a>(section: &'a Value, name: &str) -> Option<&'a Vec<Value>> {
+    section.get(name).and_then(|v| v.as_array())
+}
+
+fn number_at(section: &Value, name: &str, i: usize) -> Option<f64> {
+    column(section, name)
+        .and_then(|c| c.get(i))
+        .and_then(|v| v.as_f64())
+}
+
+fn string_at(section: &Value, name: &str, i: usize) -> Option<String> {
+    column(section, name)
+        .and_then(|c| c.get(i))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+}
+
+/// Parse an Open-Meteo forecast response
+pub fn parse_open_meteo(body: &Value, location: Point, units: Units) -> Result<Forecast> {
+    if body.get("error").and_then(|e| e.as_bool()) == Some(true) {
+        let reason = body
+            .get("reason")
+            .and_then(|r| r.as_str())
+            .unwrap_or("unknown error");
+        return Err(Error::api(format!("Open-Meteo error: {}", reason)));
+    }
+
+    let current = body.get("current").map(|c| {
+        let code = c.get("weather_code").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        CurrentConditions {
+            time: c
+                .get("time")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            temperature: c
+                .get("temperature_2m")
+                .and_then(|v| v.as_f64())
+                .unwrap_or_default(),
+            apparent_temperature: c.get("apparent_temperature").and_then(|v| v.as_f64()),
+            humidity: c.get("relative_humidity_2m").and_then(|v| v.as_f64()),
+            precipitation: c.get("precipitation").and_then(|v| v.as_f64()),
+            wind_speed: c.get("wind_speed_10m").and_then(|v| v.as_f64()),
+            wind_gusts: c.get("wind_gusts_10m").and_then(|v| v.as_f64()),
+            wind_direction: c.get("wind_direction_10m").and_then(|v| v.as_f64()),
+            weather_code: code,
+            description: describe_weather_code(code).to_string(),
+            is_day: c.get("is_day").and_then(|v| v.as_i64()) == Some(1),
+        }
+    });
+
+    let hourly: Vec<HourlyForecast> = body
+        .get("hourly")
+        .map(|h| {
+            let count = column(h, "time").map_or(0, |t| t.len());
+            (0..count)
+                .map(|i| HourlyForecast {
+                    time: string_at(h, "time", i).unwrap_or_default(),
+                    temperature: number_at(h, "temperature_2m", i),
+                    precipitation_probability: number_at(h, "precipitation_probability", i),
+                    precipitation: number_at(h, "precipitation", i),
+                    wind_speed: number_at(h, "wind_speed_10m", i),
+                    wind_gusts: number_at(h, "wind_gusts_10m", i),
+                    weather_code: number_at(h, "weather_code", i).map(|c| c as u32),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let daily: Vec<DailyForecast> = body
+        .get("daily")
+        .map(|d| {
+            let count = column(d, "time").map_or(0, |t| t.len());
+            (0..count)
+                .map(|i| {
+                    let code = number_at(d, "weather_code", i).map(|c| c as u32);
+                    DailyForecast {
+                        date: string_at(d, "time", i).unwrap_or_default(),
+                        temperature_min: number_at(d, "temperature_2m_min", i),
+                        temperature_max: number_at(d, "temperature_2m_max", i),
+                        precipitation_sum: number_at(d, "precipitation_sum", i),
+                        precipitation_probability_max: number_at(
+                            d,
+                            "precipitation_probability_max",
+                            i,
+                        ),
+                        wind_gusts_max: number_at(d, "wind_gusts_10m_max", i),
+                        weather_code: code,
+                        description: code.map(|c| describe_weather_code(c).to_string()),
+                        sunrise: string_at(d, "sunrise", i),
+                        sunset: string_at(d, "sunset", i),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let flags = forecast_flags(&hourly, units);
+    Ok(Forecast {
+        location,
+        timezone: body
+            .get("timezone")
+            .and_then(|t| t.as_str())
+            .unwrap_or("UTC")
+            .to_string(),
+        units,
+        current,
+        hourly,
+        daily,
+        flags,
+    })
+}
+
+fn forecast_flags(hourly: &[HourlyForecast], units: Units) -> ForecastFlags {
+    let next_day = &hourly[..hourly.len().min(24)];
+    ForecastFlags {
+        rain_next_6h: hourly
+            .iter()
+            .take(6)
+            .any(|h| h.precipitation_probability.unwrap_or(0.0) >= 50.0),
+        frost_next_24h: next_day
+            .iter()
+            .any(|h| h.temperature.is_some_and(|t| t <= units.frost_threshold())),
+        high_wind_next_24h: next_day.iter().any(|h| {
+            h.wind_gusts
+                .is_some_and(|g| g >= units.high_wind_threshold())
+        }),
+        thunderstorm_next_24h: next_day
+            .iter()
+            .any(|h| h.weather_code.is_some_and(|c| c >= 95)),
+    }
+}
+
+/// Parse an NWS active alerts GeoJSON response
+pub fn parse_nws_alerts(body: &Value) -> Vec<WeatherAlert> {
+    body.get("features")
+        .and_then(|f| f.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|feature| {
+            let p = feature.get("properties")?;
+            let text = |name: &str| p.get(name).and_then(|v| v.as_str()).map(str::to_string);
+            Some(WeatherAlert {
+                id: text("id").or_else(|| {
+                    feature
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string)
+                })?,
+                event: text("event").unwrap_or_else(|| "Weather alert".to_string()),
+                severity: text("severity")
+                    .and_then(|s| Severity::from_str(&s).ok())
+                    .unwrap_or(Severity::Unknown),
+                headline: text("headline"),
+                description: text("description"),
+                instruction: text("instruction"),
+                area: text("areaDesc"),
+                onset: text("onset").or_else(|| text("effective")),
+                expires: text("ends").or_else(|| text("expires")),
+                source: "nws".to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Derive alerts from hourly forecast data where no official feed exists
+fn derived_alerts(forecast: &Forecast) -> Vec<WeatherAlert> {
+    let units = forecast.units;
+    let next_day = &forecast.hourly[..forecast.hourly.len().min(24)];
+    let first = |predicate: &dyn Fn(&HourlyForecast) -> bool| {
+        next_day
+            .iter()
+            .find(|h| predicate(h))
+            .map(|h| h.time.clone())
+    };
+
+    let mut alerts = Vec::new();
+    let mut push =
+        |id: &str, event: &str, severity: Severity, onset: Option<String>, text: String| {
+            if onset.is_some() {
+                alerts.push(WeatherAlert {
+                    id: format!("forecast-{}", id),
+                    event: event.to_string(),
+                    severity,
+                    headline: Some(text),
+                    description: None,
+                    instruction: None,
+                    area: None,
+                    onset,
+                    expires: None,
+                    source: "forecast".to_string(),
+                });
+            }
+        };
+
+    push(
+        "thunderstorm",
+        "Thunderstorm",
+        Severity::Moderate,
+        first(&|h| h.weather_code.is_some_and(|c| c >= 95)),
+        "Thunderstorms forecast within 24 hours".to_string(),
+    );
+    push(
+        "wind",
+        "High wind",
+        Severity::Severe,
+        first(&|h| {
+            h.wind_gusts
+                .is_some_and(|g| g >= units.high_wind_threshold())
+        }),
+        format!(
+            "Gusts of {:.0} {} or more forecast within 24 hours",
+            units.high_wind_threshold(),
+            units.speed()
+        ),
+    );
+    push(
+        "snow",
+        "Heavy snow",
+        Severity::Moderate,
+        first(&|h| matches!(h.weather_code, Some(75 | 86))),
+        "Heavy snow forecast within 24 hours".to_string(),
+    );
+    push(
+        "freezing-rain",
+        "Freezing rain",
+        Severity::Severe,
+        first(&|h| matches!(h.weather_code, Some(56 | 57 | 66 | 67))),
+        "Freezing rain or drizzle forecast within 24 hours".to_string(),
+    );
+    alerts
+}
+
+/// Weather client
+#[derive(Debug, Clone)]
+pub struct WeatherClient {
+    client: Client,
+    config: WeatherConfig,
+}
+
+impl WeatherClient {
+    /// Create a new weather client
+    pub fn new(config: WeatherConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent(config.user_agent.clone())
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create weather client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    /// Get current conditions with hourly and daily forecasts
+    pub async fn forecast(
+        &self,
+        point: &Point,
+        hours: u32,
+        days: u32,
+        units: Units,
+    ) -> Result<Forecast> {
+        let mut query = vec![
+            ("latitude", point.lat.to_string()),
+            ("longitude", point.lon.to_string()),
+            ("timezone", "auto".to_string()),
+            (
+                "current",
+                "temperature_2m,relative_humidity_2m,apparent_temperature,is_day,precipitation,weather_code,wind_speed_10m,wind_direction_10m,wind_gusts_10m".to_string(),
+            ),
+            (
+                "hourly",
+                "temperature_2m,precipitation_probability,precipitation,weather_code,wind_speed_10m,wind_gusts_10m".to_string(),
+            ),
+            (
+                "daily",
+                "weather_code,temperature_2m_max,temperature_2m_min,precipitation_sum,precipitation_probability_max,wind_gusts_10m_max,sunrise,sunset".to_string(),
+            ),
+            // Flags look 24 hours ahead even when fewer hours are returned
+            ("forecast_hours", hours.clamp(24, 384).to_string()),
+            ("forecast_days", days.clamp(1, 16).to_string()),
+        ];
+        if units == Units::Imperial {
+            query.push(("temperature_unit", "fahrenheit".to_string()));
+            query.push(("wind_speed_unit", "mph".to_string()));
+            query.push(("precipitation_unit", "inch".to_string()));
+        }
+
+        let response = self
+            .client
+            .get(&self.config.open_meteo_url)
+            .query(&query)
+            .send()
+            .await
+            .map_err(|e| {
+                Error::network_with_endpoint(
+                    format!("Forecast request failed: {}", e),
+                    self.config.open_meteo_url.clone(),
+                )
+            })?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse forecast: {}", e)))?;
+        if !status.is_success() && body.get("reason").is_none() {
+            return Err(Error::api_with_status(
+                format!("Open-Meteo returned {}", status),
+                "open-meteo",
+                status.as_u16(),
+            ));
+        }
+
+        let mut forecast = parse_open_meteo(&body, point.clone(), units)?;
+        forecast.hourly.truncate(hours as usize);
+        Ok(forecast)
+    }
+
+    /// Official NWS alerts; `None` when the point is outside NWS coverage
+    async fn nws_alerts(&self, point: &Point) -> Result<Option<Vec<WeatherAlert>>> {
+        let url = format!(
+            "{}/alerts/active?point={:.4},{:.4}",
+            self.config.nws_url.trim_end_matches('/'),
+            point.lat,
+            point.lon
+        );
+        let response = self
+            .client
+            .get(&url)
+            .header("Accept", "application/geo+json")
+            .send()
+            .await
+            .map_err(|e| {
+                Error::network_with_endpoint(
+                    format!("NWS alerts request failed: {}", e),
+                    url.clone(),
+                )
+            })?;
+
+        // The NWS answers 400/404 for points outside its coverage
+        let status = response.status();
+        if status.as_u16() == 400 || status.as_u16() == 404 {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(Error::api_with_status(
+                format!("NWS returned {}", status),
+                "nws",
+                status.as_u16(),
+            ));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse NWS alerts: {}", e)))?;
+        Ok(Some(parse_nws_alerts(&body)))
+    }
+
+    /// Active alerts for a location, most severe first
+    pub async fn alerts(&self, point: &Point, min_severity: Severity) -> Result<Vec<WeatherAlert>> {
+        let mut alerts = match self.nws_alerts(point).await {
+            Ok(Some(alerts)) => alerts,
+            Ok(None) => {
+                let forecast = self.forecast(point, 24, 1, Units::Metric).await?;
+                derived_alerts(&forecast)
+            }
+            Err(e) => {
+                tracing::debug!(error = %e, "NWS alerts unavailable, deriving from forecast");
+                let forecast = self.forecast(point, 24, 1, Units::Metric).await?;
+                derived_alerts(&forecast)
+            }
+        };
+        alerts.retain(|a| a.severity >= min_severity);
+        alerts.sort_by_key(|a| std::cmp::Reverse(a.severity));
+        Ok(alerts)
+    }
+
+    /// Get tool definitions for weather
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "get_forecast",
+                "Get current conditions, hourly and daily forecasts for coordinates, with yes/no flags (rain soon, frost, high wind, thunderstorms) for automations",
+                "weather",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "lat": {"type": "number"},
+                        "lon": {"type": "number"},
+                        "hours": {"type": "integer", "default": 24, "minimum": 0, "maximum": 384},
+                        "days": {"type": "integer", "default": 7, "minimum": 1, "maximum": 16},
+                        "units": {"type": "string", "enum": ["metric", "imperial"], "default": "metric"}
+                    },
+                    "required": ["lat", "lon"]
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "weather_alerts",
+                "Get active severe weather alerts for coordinates (official NWS alerts in the US, forecast-derived elsewhere)",
+                "weather",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "lat": {"type": "number"},
+                        "lon": {"type": "number"},
+                        "min_severity": {"type": "string", "enum": ["unknown", "minor", "moderate", "severe", "extreme"], "default": "unknown"}
+                    },
+                    "required": ["lat", "lon"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a weather tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let coordinate = |field: &str| {
+            parameters
+                .get(field)
+                .and_then(|v| v.as_f64())
+                .ok_or_else(|| {
+                    Error::validation_with_field(format!("{} is required", field), field)
+                })
+        };
+
+        match name {
+            "get_forecast" => {
+                let point = Point {
+                    lat: coordinate("lat")?,
+                    lon: coordinate("lon")?,
+                };
+                let hours = parameters
+                    .get("hours")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(24) as u32;
+                let days = parameters.get("days").and_then(|v| v.as_u64()).unwrap_or(7) as u32;
+                let units = parameters
+                    .get("units")
+                    .and_then(|v| v.as_str())
+                    .map(Units::from_str)
+                    .transpose()?
+                    .unwrap_or(Units::Metric);
+
+                let forecast = self.forecast(&point, hours, days, units).await?;
+                let mut lines = Vec::new();
+                if let Some(current) = &forecast.current {
+                    lines.push(format!(
+                        "Now: {}, {:.1}{}{}, wind {:.0} {}",
+                        current.description,
+                        current.temperature,
+                        units.temperature(),
+                        current
+                            .apparent_temperature
+                            .map(|t| format!(" (feels {:.1}{})", t, units.temperature()))
+                            .unwrap_or_default(),
+                        current.wind_speed.unwrap_or_default(),
+                        units.speed()
+                    ));
+                }
+                for day in &forecast.daily {
+                    lines.push(format!(
+                        "{}: {}, {:.0}–{:.0}{}, {:.1} {} precipitation ({:.0}%)",
+                        day.date,
+                        day.description.as_deref().unwrap_or("Unknown"),
+                        day.temperature_min.unwrap_or_default(),
+                        day.temperature_max.unwrap_or_default(),
+                        units.temperature(),
+                        day.precipitation_sum.unwrap_or_default(),
+                        units.precipitation(),
+                        day.precipitation_probability_max.unwrap_or_default()
+                    ));
+                }
+                Ok(call_result(
+                    lines.join("\n"),
+                    serde_json::to_value(&forecast)?,
+                ))
+            }
+            "weather_alerts" => {
+                let point = Point {
+                    lat: coordinate("lat")?,
+                    lon: coordinate("lon")?,
+                };
+                let min_severity = parameters
+                    .get("min_severity")
+                    .and_then(|v| v.as_str())
+                    .map(Severity::from_str)
+                    .transpose()?
+                    .unwrap_or(Severity::Unknown);
+
+                let alerts = self.alerts(&point, min_severity).await?;
+                let text = if alerts.is_empty() {
+                    "No active weather alerts".to_string()
+                } else {
+                    alerts
+                        .iter()
+                        .map(|a| {
+                            format!(
+                                "[{:?}] {}: {}",
+                                a.severity,
+                                a.event,
+                                a.headline.as_deref().unwrap_or_default()
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                Ok(call_result(
+                    text,
+                    json!({ "alerts": alerts, "count": alerts.len() }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "weather_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_forecast_flags_and_alerts() {
+        let body = json!({
+            "timezone": "Europe/Berlin",
+            "current": {"time": "2024-01-10T08:00", "temperature_2m": -1.5, "weather_code": 71, "is_day": 1},
+            "hourly": {
+                "time": ["2024-01-10T08:00", "2024-01-10T09:00", "2024-01-10T10:00"],
+                "temperature_2m": [-1.5, 0.5, 2.0],
+                "precipitation_probability": [20, 30, 70],
+                "wind_gusts_10m": [20.0, 80.0, 40.0],
+                "weather_code": [71, 75, 3]
+            },
+            "daily": {
+                "time": ["2024-01-10"],
+                "temperature_2m_min": [-3.0],
+                "temperature_2m_max": [2.0],
+                "weather_code": [75]
+            }
+        });
+        let forecast = parse_open_meteo(
+            &body,
+            Point {
+                lon: 13.4,
+                lat: 52.5,
+            },
+            Units::Metric,
+        )
+        .unwrap();
+        assert_eq!(forecast.current.as_ref().unwrap().description, "Light snow");
+        assert_eq!(forecast.daily[0].description.as_deref(), Some("Heavy snow"));
+        assert!(forecast.flags.rain_next_6h);
+        assert!(forecast.flags.frost_next_24h);
+        assert!(forecast.flags.high_wind_next_24h);
+        assert!(!forecast.flags.thunderstorm_next_24h);
+
+        let derived: Vec<String> = derived_alerts(&forecast)
+            .into_iter()
+            .map(|a| a.event)
+            .collect();
+        assert_eq!(derived, vec!["High wind", "Heavy snow"]);
+
+        let nws = json!({"features": [{
+            "id": "urn:oid:1",
+            "properties": {"event": "Winter Storm Warning", "severity": "Severe", "areaDesc": "Boulder", "ends": null, "expires": "2024-01-11T06:00:00-07:00"}
+        }]});
+        let alerts = parse_nws_alerts(&nws);
+        assert_eq!(alerts[0].id, "urn:oid:1");
+        assert_eq!(alerts[0].severity, Severity::Severe);
+        assert_eq!(
+            alerts[0].expires.as_deref(),
+            Some("2024-01-11T06:00:00-07:00")
+        );
+    }
+}
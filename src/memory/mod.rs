@@ -4,8 +4,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use uuid::Uuid;
 use std::sync::Arc;
+use uuid::Uuid;
 
 pub mod persistence;
 
@@ -158,23 +158,17 @@ pub struct MemoryClient {
 impl MemoryClient {
     /// Create a new memory client with PostgreSQL backend
     pub async fn new_with_postgres(
-        lifecycle: Arc<LifecycleManager>, 
-        connection_string: String
+        lifecycle: Arc<LifecycleManager>,
+        connection_string: String,
     ) -> Result<Self> {
         let store = Arc::new(persistence::PostgreSQLMemoryStore::new(connection_string).await?);
-        Ok(Self {
-            lifecycle,
-            store,
-        })
+        Ok(Self { lifecycle, store })
     }
 
     /// Create a new memory client with in-memory backend (for testing/development)
     pub fn new_in_memory(lifecycle: Arc<LifecycleManager>) -> Self {
         let store = Arc::new(persistence::InMemoryStore::new());
-        Self {
-            lifecycle,
-            store,
-        }
+        Self { lifecycle, store }
     }
 
     /// Backwards compatible constructor (uses in-memory store)
@@ -212,7 +206,7 @@ impl MemoryClient {
         if memory.id.is_empty() {
             memory.id = format!("{}-{}", memory.memory_type, Uuid::new_v4());
         }
-        
+
         let now = Utc::now();
         if memory.created_at == DateTime::<Utc>::from_timestamp(0, 0).unwrap_or(now) {
             memory.created_at = now;
@@ -231,7 +225,7 @@ impl MemoryClient {
                 format!("Memory with ID '{}' not found", id),
                 "memory",
                 id,
-            ))
+            )),
         }
     }
 
@@ -270,7 +264,7 @@ impl MemoryClient {
     pub async fn delete_memory(&self, id: &str) -> Result<()> {
         // Delete relationships first
         self.store.delete_relationships(id).await?;
-        
+
         // Delete the memory
         self.store.delete_memory(id).await?;
         Ok(())
@@ -322,7 +316,7 @@ impl MemoryClient {
     pub async fn get_relationships(&self, memory_id: &str) -> Result<Vec<Relationship>> {
         // Verify memory exists
         self.get_memory(memory_id).await?;
-        
+
         self.store.get_relationships(memory_id).await
     }
 
@@ -340,7 +334,7 @@ impl MemoryClient {
             } else {
                 &relationship.from_id
             };
-            
+
             if let Ok(memory) = self.get_memory(related_id).await {
                 related_memories.push(memory);
             }
@@ -364,14 +358,16 @@ impl MemoryClient {
             metadata_filters: None,
             limit: None,
         };
-        
+
         let all_memories = self.store.search_memories(&empty_params).await?;
         let total_memories = all_memories.len();
-        
+
         // Count by type
         let mut type_counts = HashMap::new();
         for memory in &all_memories {
-            let count = type_counts.entry(memory.memory_type.to_string()).or_insert(0);
+            let count = type_counts
+                .entry(memory.memory_type.to_string())
+                .or_insert(0);
             *count += 1;
         }
 
@@ -491,3 +487,52 @@ impl MemoryClient {
     }
 }
 
+/// Memories and their relationships, merged into the store on restore
+#[async_trait::async_trait]
+impl crate::tools::snapshot::SnapshotSource for MemoryClient {
+    fn name(&self) -> &str {
+        "memories"
+    }
+
+    async fn export_state(&self) -> Result<Value> {
+        let memories = self
+            .store
+            .search_memories(&MemorySearchParams {
+                memory_type: None,
+                keyword: None,
+                metadata_filters: None,
+                limit: None,
+            })
+            .await?;
+        let mut relationships = Vec::new();
+        for memory in &memories {
+            // Each relationship is listed for both ends; keep it once
+            for relationship in self.store.get_relationships(&memory.id).await? {
+                if relationship.from_id == memory.id {
+                    relationships.push(relationship);
+                }
+            }
+        }
+        Ok(serde_json::json!({
+            "memories": memories,
+            "relationships": relationships,
+        }))
+    }
+
+    async fn restore_state(&self, state: Value) -> Result<()> {
+        #[derive(Deserialize)]
+        struct Exported {
+            memories: Vec<Memory>,
+            relationships: Vec<Relationship>,
+        }
+        let exported: Exported = serde_json::from_value(state)
+            .map_err(|e| Error::parsing(format!("Invalid memory snapshot: {}", e)))?;
+        for memory in &exported.memories {
+            self.store.store_memory(memory).await?;
+        }
+        for relationship in &exported.relationships {
+            self.store.store_relationship(relationship).await?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::error::{Error, Result};
-use crate::memory::{Memory, Relationship, MemorySearchParams};
+use crate::memory::{Memory, MemorySearchParams, Relationship};
 #[cfg(feature = "database")]
 use crate::memory::{MemoryType, RelationType};
 use async_trait::async_trait;
@@ -61,7 +61,8 @@ impl PostgreSQLMemoryStore {
     /// Initialize database schema for memory storage
     async fn init_schema(pool: &sqlx::PgPool) -> Result<()> {
         // Create memories table
-        sqlx::query(r#"
+        sqlx::query(
+            r#"
             CREATE TABLE IF NOT EXISTS memories (
                 id VARCHAR(255) PRIMARY KEY,
                 memory_type VARCHAR(50) NOT NULL,
@@ -71,13 +72,15 @@ impl PostgreSQLMemoryStore {
                 created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
                 updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
             );
-        "#)
+        "#,
+        )
         .execute(pool)
         .await
         .map_err(|e| Error::service(format!("Failed to create memories table: {}", e)))?;
 
         // Create relationships table
-        sqlx::query(r#"
+        sqlx::query(
+            r#"
             CREATE TABLE IF NOT EXISTS memory_relationships (
                 from_id VARCHAR(255) NOT NULL,
                 to_id VARCHAR(255) NOT NULL,
@@ -88,7 +91,8 @@ impl PostgreSQLMemoryStore {
                 FOREIGN KEY (from_id) REFERENCES memories(id) ON DELETE CASCADE,
                 FOREIGN KEY (to_id) REFERENCES memories(id) ON DELETE CASCADE
             );
-        "#)
+        "#,
+        )
         .execute(pool)
         .await
         .map_err(|e| Error::service(format!("Failed to create relationships table: {}", e)))?;
@@ -114,10 +118,12 @@ impl PostgreSQLMemoryStore {
             .await
             .map_err(|e| Error::service(format!("Failed to create from_id index: {}", e)))?;
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_relationships_to_id ON memory_relationships(to_id);")
-            .execute(pool)
-            .await
-            .map_err(|e| Error::service(format!("Failed to create to_id index: {}", e)))?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_relationships_to_id ON memory_relationships(to_id);",
+        )
+        .execute(pool)
+        .await
+        .map_err(|e| Error::service(format!("Failed to create to_id index: {}", e)))?;
 
         Ok(())
     }
@@ -125,7 +131,7 @@ impl PostgreSQLMemoryStore {
     /// Validate input for security
     fn validate_input(&self, input: &str) -> Result<()> {
         use crate::security::{SanitizationOptions, ValidationResult};
-        
+
         let options = SanitizationOptions::default();
         match self.security.validate_input(input, &options) {
             ValidationResult::Valid => Ok(()),
@@ -137,14 +143,14 @@ impl PostgreSQLMemoryStore {
     /// Update cache with memory
     async fn update_cache(&self, memory: Memory) {
         let mut cache = self.cache.write().await;
-        
+
         // Implement simple LRU by removing oldest if at limit
         if cache.len() >= self.cache_size_limit {
             if let Some(oldest_key) = cache.keys().next().cloned() {
                 cache.remove(&oldest_key);
             }
         }
-        
+
         cache.insert(memory.id.clone(), memory);
     }
 
@@ -170,7 +176,8 @@ impl MemoryStore for PostgreSQLMemoryStore {
         self.validate_input(&memory.title)?;
         self.validate_input(&memory.content)?;
 
-        sqlx::query(r#"
+        sqlx::query(
+            r#"
             INSERT INTO memories (id, memory_type, title, content, metadata, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $5, $6, $7)
             ON CONFLICT (id) DO UPDATE SET
@@ -179,7 +186,8 @@ impl MemoryStore for PostgreSQLMemoryStore {
                 content = EXCLUDED.content,
                 metadata = EXCLUDED.metadata,
                 updated_at = EXCLUDED.updated_at
-        "#)
+        "#,
+        )
         .bind(&memory.id)
         .bind(memory.memory_type.to_string())
         .bind(&memory.title)
@@ -203,11 +211,13 @@ impl MemoryStore for PostgreSQLMemoryStore {
             return Ok(Some(memory));
         }
 
-        let row = sqlx::query(r#"
+        let row = sqlx::query(
+            r#"
             SELECT id, memory_type, title, content, metadata, created_at, updated_at
             FROM memories
             WHERE id = $1
-        "#)
+        "#,
+        )
         .bind(id)
         .fetch_optional(&self.pool)
         .await
@@ -231,7 +241,8 @@ impl MemoryStore for PostgreSQLMemoryStore {
                 memory_type,
                 title: row.try_get("title")?,
                 content: row.try_get("content")?,
-                metadata: row.try_get::<serde_json::Value, _>("metadata")
+                metadata: row
+                    .try_get::<serde_json::Value, _>("metadata")
                     .ok()
                     .and_then(|v| serde_json::from_value(v).ok())
                     .unwrap_or_else(HashMap::new),
@@ -254,11 +265,13 @@ impl MemoryStore for PostgreSQLMemoryStore {
         self.validate_input(&memory.title)?;
         self.validate_input(&memory.content)?;
 
-        let result = sqlx::query(r#"
+        let result = sqlx::query(
+            r#"
             UPDATE memories
             SET memory_type = $2, title = $3, content = $4, metadata = $5, updated_at = $6
             WHERE id = $1
-        "#)
+        "#,
+        )
         .bind(&memory.id)
         .bind(memory.memory_type.to_string())
         .bind(&memory.title)
@@ -364,7 +377,8 @@ impl MemoryStore for PostgreSQLMemoryStore {
                 memory_type,
                 title: row.try_get("title")?,
                 content: row.try_get("content")?,
-                metadata: row.try_get::<serde_json::Value, _>("metadata")
+                metadata: row
+                    .try_get::<serde_json::Value, _>("metadata")
                     .ok()
                     .and_then(|v| serde_json::from_value(v).ok())
                     .unwrap_or_else(HashMap::new),
@@ -377,13 +391,15 @@ impl MemoryStore for PostgreSQLMemoryStore {
     }
 
     async fn store_relationship(&self, relationship: &Relationship) -> Result<()> {
-        sqlx::query(r#"
+        sqlx::query(
+            r#"
             INSERT INTO memory_relationships (from_id, to_id, relation_type, metadata, created_at)
             VALUES ($1, $2, $3, $4, $5)
             ON CONFLICT (from_id, to_id, relation_type) DO UPDATE SET
                 metadata = EXCLUDED.metadata,
                 created_at = EXCLUDED.created_at
-        "#)
+        "#,
+        )
         .bind(&relationship.from_id)
         .bind(&relationship.to_id)
         .bind(relationship.relation_type.to_string())
@@ -397,12 +413,14 @@ impl MemoryStore for PostgreSQLMemoryStore {
     }
 
     async fn get_relationships(&self, memory_id: &str) -> Result<Vec<Relationship>> {
-        let rows = sqlx::query(r#"
+        let rows = sqlx::query(
+            r#"
             SELECT from_id, to_id, relation_type, metadata, created_at
             FROM memory_relationships
             WHERE from_id = $1 OR to_id = $1
             ORDER BY created_at DESC
-        "#)
+        "#,
+        )
         .bind(memory_id)
         .fetch_all(&self.pool)
         .await
@@ -425,7 +443,8 @@ impl MemoryStore for PostgreSQLMemoryStore {
                 from_id: row.try_get("from_id")?,
                 to_id: row.try_get("to_id")?,
                 relation_type,
-                metadata: row.try_get::<serde_json::Value, _>("metadata")
+                metadata: row
+                    .try_get::<serde_json::Value, _>("metadata")
                     .ok()
                     .and_then(|v| serde_json::from_value(v).ok())
                     .unwrap_or_else(HashMap::new),
@@ -466,7 +485,9 @@ pub struct PostgreSQLMemoryStore {
 #[cfg(not(feature = "database"))]
 impl PostgreSQLMemoryStore {
     pub async fn new(_connection_string: String) -> Result<Self> {
-        Err(Error::config("PostgreSQL memory store requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL memory store requires 'database' feature to be enabled",
+        ))
     }
 }
 
@@ -474,39 +495,57 @@ impl PostgreSQLMemoryStore {
 #[async_trait]
 impl MemoryStore for PostgreSQLMemoryStore {
     async fn store_memory(&self, _memory: &Memory) -> Result<()> {
-        Err(Error::config("PostgreSQL memory store requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL memory store requires 'database' feature to be enabled",
+        ))
     }
 
     async fn get_memory(&self, _id: &str) -> Result<Option<Memory>> {
-        Err(Error::config("PostgreSQL memory store requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL memory store requires 'database' feature to be enabled",
+        ))
     }
 
     async fn update_memory(&self, _memory: &Memory) -> Result<()> {
-        Err(Error::config("PostgreSQL memory store requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL memory store requires 'database' feature to be enabled",
+        ))
     }
 
     async fn delete_memory(&self, _id: &str) -> Result<()> {
-        Err(Error::config("PostgreSQL memory store requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL memory store requires 'database' feature to be enabled",
+        ))
     }
 
     async fn search_memories(&self, _params: &MemorySearchParams) -> Result<Vec<Memory>> {
-        Err(Error::config("PostgreSQL memory store requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL memory store requires 'database' feature to be enabled",
+        ))
     }
 
     async fn store_relationship(&self, _relationship: &Relationship) -> Result<()> {
-        Err(Error::config("PostgreSQL memory store requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL memory store requires 'database' feature to be enabled",
+        ))
     }
 
     async fn get_relationships(&self, _memory_id: &str) -> Result<Vec<Relationship>> {
-        Err(Error::config("PostgreSQL memory store requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL memory store requires 'database' feature to be enabled",
+        ))
     }
 
     async fn delete_relationships(&self, _memory_id: &str) -> Result<()> {
-        Err(Error::config("PostgreSQL memory store requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL memory store requires 'database' feature to be enabled",
+        ))
     }
 
     async fn health_check(&self) -> Result<bool> {
-        Err(Error::config("PostgreSQL memory store requires 'database' feature to be enabled"))
+        Err(Error::config(
+            "PostgreSQL memory store requires 'database' feature to be enabled",
+        ))
     }
 }
 
@@ -574,15 +613,16 @@ impl MemoryStore for InMemoryStore {
                         return false;
                     }
                 }
-                
+
                 if let Some(ref keyword) = params.keyword {
                     let keyword_lower = keyword.to_lowercase();
-                    if !m.title.to_lowercase().contains(&keyword_lower) &&
-                       !m.content.to_lowercase().contains(&keyword_lower) {
+                    if !m.title.to_lowercase().contains(&keyword_lower)
+                        && !m.content.to_lowercase().contains(&keyword_lower)
+                    {
                         return false;
                     }
                 }
-                
+
                 if let Some(ref filters) = params.metadata_filters {
                     for (key, value) in filters {
                         if m.metadata.get(key) != Some(value) {
@@ -590,33 +630,33 @@ impl MemoryStore for InMemoryStore {
                         }
                     }
                 }
-                
+
                 true
             })
             .cloned()
             .collect();
-        
+
         // Sort by created_at descending
         results.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-        
+
         // Apply limit
         if let Some(limit) = params.limit {
             results.truncate(limit);
         }
-        
+
         Ok(results)
     }
 
     async fn store_relationship(&self, relationship: &Relationship) -> Result<()> {
         let mut relationships = self.relationships.write().await;
-        
+
         // Remove existing relationship if it exists
         relationships.retain(|r| {
-            !(r.from_id == relationship.from_id &&
-              r.to_id == relationship.to_id &&
-              r.relation_type == relationship.relation_type)
+            !(r.from_id == relationship.from_id
+                && r.to_id == relationship.to_id
+                && r.relation_type == relationship.relation_type)
         });
-        
+
         relationships.push(relationship.clone());
         Ok(())
     }
@@ -640,4 +680,4 @@ impl MemoryStore for InMemoryStore {
     async fn health_check(&self) -> Result<bool> {
         Ok(true) // In-memory store is always healthy
     }
-}
\ No newline at end of file
+}
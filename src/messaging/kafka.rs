@@ -0,0 +1,467 @@
+use super::{
+    decode_payload, stream_channel, ConsumeOptions, ConsumerLag, Message, MessageStream,
+    PublishReceipt, TopicInfo, DEFAULT_CONSUMER_GROUP,
+};
+use crate::error::{Error, Result};
+use chrono::Utc;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const KAFKA_V2: &str = "application/vnd.kafka.v2+json";
+const KAFKA_JSON_V2: &str = "application/vnd.kafka.json.v2+json";
+
+/// A topic or group name as one URL path segment
+fn segment(name: &str) -> String {
+    percent_encoding::utf8_percent_encode(name, percent_encoding::NON_ALPHANUMERIC).to_string()
+}
+
+/// Kafka REST Proxy configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KafkaConfig {
+    /// REST Proxy base URL (e.g. http://localhost:8082)
+    pub rest_proxy_url: String,
+    /// Basic auth username
+    pub username: Option<String>,
+    /// Basic auth password
+    pub password: Option<String>,
+    /// Cluster ID for v3 APIs; discovered automatically when unset
+    pub cluster_id: Option<String>,
+}
+
+/// Kafka client backed by the Confluent REST Proxy
+#[derive(Debug, Clone)]
+pub struct KafkaClient {
+    client: Client,
+    config: KafkaConfig,
+}
+
+impl KafkaClient {
+    /// Create a new Kafka client
+    pub fn new(config: KafkaConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Kafka client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.config.rest_proxy_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.config.username {
+            Some(username) => request.basic_auth(username, self.config.password.as_ref()),
+            None => request,
+        }
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Value> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Kafka REST Proxy request failed: {}", e)))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| Error::network(format!("Failed to read Kafka response: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(Error::api_with_status(
+                format!("Kafka REST Proxy returned {}: {}", status, body),
+                "kafka",
+                status.as_u16(),
+            ));
+        }
+
+        if body.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&body)
+            .map_err(|e| Error::parsing(format!("Failed to parse Kafka response: {}", e)))
+    }
+
+    /// Produce a single record to a topic
+    pub async fn produce(
+        &self,
+        topic: &str,
+        key: Option<&str>,
+        value: &Value,
+        partition: Option<i32>,
+    ) -> Result<PublishReceipt> {
+        let mut record = json!({ "key": key, "value": value });
+        if let Some(partition) = partition {
+            record["partition"] = json!(partition);
+        }
+
+        let url = self.url(&format!("topics/{}", segment(topic)));
+        let response = self
+            .send(
+                self.request(Method::POST, &url)
+                    .header("Content-Type", KAFKA_JSON_V2)
+                    .header("Accept", KAFKA_V2)
+                    .json(&json!({ "records": [record] })),
+            )
+            .await?;
+
+        let offset = response
+            .get("offsets")
+            .and_then(|o| o.as_array())
+            .and_then(|o| o.first())
+            .ok_or_else(|| Error::parsing("Kafka produce response missing offsets"))?;
+
+        if let Some(error) = offset.get("error").and_then(|e| e.as_str()) {
+            return Err(Error::service(format!(
+                "Kafka rejected record for {}: {}",
+                topic, error
+            )));
+        }
+
+        Ok(PublishReceipt {
+            topic: topic.to_string(),
+            partition: offset
+                .get("partition")
+                .and_then(|p| p.as_i64())
+                .map(|p| p as i32),
+            offset: offset.get("offset").and_then(|o| o.as_i64()),
+        })
+    }
+
+    /// List topics with their partition counts
+    pub async fn list_topics(&self) -> Result<Vec<TopicInfo>> {
+        let names = self
+            .send(
+                self.request(Method::GET, &self.url("topics"))
+                    .header("Accept", KAFKA_V2),
+            )
+            .await?;
+        let names = names
+            .as_array()
+            .ok_or_else(|| Error::parsing("Kafka topics response is not an array"))?;
+
+        let mut topics = Vec::with_capacity(names.len());
+        for name in names.iter().filter_map(|n| n.as_str()) {
+            let partitions = self
+                .send(
+                    self.request(
+                        Method::GET,
+                        &self.url(&format!("topics/{}/partitions", segment(name))),
+                    )
+                    .header("Accept", KAFKA_V2),
+                )
+                .await?;
+            topics.push(TopicInfo {
+                name: name.to_string(),
+                partitions: partitions.as_array().map(|p| p.len() as u32),
+                subjects: Vec::new(),
+                messages: None,
+                bytes: None,
+                consumers: None,
+            });
+        }
+        Ok(topics)
+    }
+
+    /// Consume records as a stream using a temporary consumer instance in the given group
+    pub async fn consume_stream(
+        &self,
+        topics: Vec<String>,
+        options: ConsumeOptions,
+    ) -> Result<MessageStream> {
+        let group = options
+            .group
+            .clone()
+            .unwrap_or_else(|| DEFAULT_CONSUMER_GROUP.to_string());
+        let instance = format!("devops-mcp-{}", uuid::Uuid::new_v4());
+
+        let created = self
+            .send(
+                self.request(
+                    Method::POST,
+                    &self.url(&format!("consumers/{}", segment(&group))),
+                )
+                .header("Content-Type", KAFKA_V2)
+                .json(&json!({
+                    "name": instance,
+                    "format": "json",
+                    "auto.offset.reset": if options.from_beginning { "earliest" } else { "latest" },
+                    "auto.commit.enable": "true"
+                })),
+            )
+            .await?;
+        let base_uri = created
+            .get("base_uri")
+            .and_then(|b| b.as_str())
+            .ok_or_else(|| Error::parsing("Kafka consumer response missing base_uri"))?
+            .to_string();
+
+        if let Err(e) = self
+            .send(
+                self.request(Method::POST, &format!("{}/subscription", base_uri))
+                    .header("Content-Type", KAFKA_V2)
+                    .json(&json!({ "topics": topics })),
+            )
+            .await
+        {
+            self.delete_consumer(&base_uri).await;
+            return Err(e);
+        }
+
+        let (tx, rx) = stream_channel(&options);
+        let client = self.clone();
+        tokio::spawn(async move {
+            let deadline = tokio::time::Instant::now() + options.timeout;
+            let mut delivered = 0;
+
+            while delivered < options.max_messages && tokio::time::Instant::now() < deadline {
+                let records = match client
+                    .send(
+                        client
+                            .request(Method::GET, &format!("{}/records", base_uri))
+                            .header("Accept", KAFKA_JSON_V2),
+                    )
+                    .await
+                {
+                    Ok(records) => records,
+                    Err(e) => {
+                        let _ = tx.send(Err(e)).await;
+                        break;
+                    }
+                };
+
+                for record in records.as_array().into_iter().flatten() {
+                    if delivered >= options.max_messages {
+                        break;
+                    }
+                    if tx.send(Ok(Self::parse_record(record))).await.is_err() {
+                        delivered = options.max_messages;
+                        break;
+                    }
+                    delivered += 1;
+                }
+
+                tokio::time::sleep(Duration::from_millis(250)).await;
+            }
+
+            client.delete_consumer(&base_uri).await;
+        });
+
+        Ok(rx)
+    }
+
+    fn parse_record(record: &Value) -> Message {
+        let payload = match record.get("value") {
+            Some(Value::String(s)) => decode_payload(s.as_bytes()),
+            Some(value) => value.clone(),
+            None => Value::Null,
+        };
+
+        Message {
+            topic: record
+                .get("topic")
+                .and_then(|t| t.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            key: record.get("key").and_then(|k| match k {
+                Value::Null => None,
+                Value::String(s) => Some(s.clone()),
+                other => Some(other.to_string()),
+            }),
+            payload,
+            headers: HashMap::new(),
+            partition: record
+                .get("partition")
+                .and_then(|p| p.as_i64())
+                .map(|p| p as i32),
+            offset: record.get("offset").and_then(|o| o.as_i64()),
+            received_at: Utc::now(),
+        }
+    }
+
+    async fn delete_consumer(&self, base_uri: &str) {
+        if let Err(e) = self
+            .send(
+                self.request(Method::DELETE, base_uri)
+                    .header("Content-Type", KAFKA_V2),
+            )
+            .await
+        {
+            tracing::warn!(error = %e, "Failed to delete Kafka consumer instance");
+        }
+    }
+
+    async fn cluster_id(&self) -> Result<String> {
+        if let Some(cluster_id) = &self.config.cluster_id {
+            return Ok(cluster_id.clone());
+        }
+
+        let clusters = self
+            .send(self.request(Method::GET, &self.url("v3/clusters")))
+            .await?;
+        clusters
+            .get("data")
+            .and_then(|d| d.as_array())
+            .and_then(|d| d.first())
+            .and_then(|c| c.get("cluster_id"))
+            .and_then(|c| c.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| Error::not_found("No Kafka cluster reported by REST Proxy"))
+    }
+
+    /// Get per-partition lag for a consumer group
+    pub async fn consumer_lag(&self, group: &str) -> Result<Vec<ConsumerLag>> {
+        let cluster_id = self.cluster_id().await?;
+        let url = self.url(&format!(
+            "v3/clusters/{}/consumer-groups/{}/lags",
+            segment(&cluster_id),
+            segment(group)
+        ));
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        Ok(response
+            .get("data")
+            .and_then(|d| d.as_array())
+            .into_iter()
+            .flatten()
+            .map(|entry| ConsumerLag {
+                group: group.to_string(),
+                topic: entry
+                    .get("topic_name")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                partition: entry
+                    .get("partition_id")
+                    .and_then(|p| p.as_i64())
+                    .map(|p| p as i32),
+                current_offset: entry.get("current_offset").and_then(|o| o.as_i64()),
+                end_offset: entry.get("log_end_offset").and_then(|o| o.as_i64()),
+                lag: entry.get("lag").and_then(|l| l.as_u64()).unwrap_or(0),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{Method as HttpMethod, Uri};
+    use axum::Json;
+    use std::sync::{Arc, Mutex};
+
+    /// REST Proxy stand-in recording each request as `METHOD path`
+    async fn proxy(records: usize) -> (KafkaClient, Arc<Mutex<Vec<String>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let base = url.clone();
+        let app = axum::Router::new().fallback(move |method: HttpMethod, uri: Uri| {
+            let log = Arc::clone(&log);
+            let base = base.clone();
+            async move {
+                let path = uri.path().to_string();
+                let polls = {
+                    let mut log = log.lock().unwrap();
+                    log.push(format!("{} {}", method, path));
+                    log.iter().filter(|l| l.ends_with("/records")).count()
+                };
+                Json(match (method.as_str(), path.as_str()) {
+                    ("POST", "/topics/orders%2Feu") => {
+                        json!({"offsets": [{"partition": 2, "offset": 42}]})
+                    }
+                    ("POST", "/topics/full") => {
+                        json!({"offsets": [{"error_code": 50003, "error": "quota exceeded"}]})
+                    }
+                    ("POST", "/consumers/night%20shift") => {
+                        json!({"base_uri": format!("{}/consumers/night/instances/i1", base)})
+                    }
+                    (_, p) if p.ends_with("/records") && polls == 1 => json!((0..records)
+                        .map(|i| json!({
+                            "topic": "orders",
+                            "key": if i == 0 { json!(7) } else { Value::Null },
+                            "value": {"id": i},
+                            "partition": 0,
+                            "offset": i
+                        }))
+                        .collect::<Vec<_>>()),
+                    (_, p) if p.ends_with("/records") => json!([]),
+                    _ => Value::Null,
+                })
+            }
+        });
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = KafkaClient::new(KafkaConfig {
+            rest_proxy_url: url,
+            username: None,
+            password: None,
+            cluster_id: None,
+        })
+        .unwrap();
+        (client, seen)
+    }
+
+    #[tokio::test]
+    async fn produces_to_encoded_topics_and_surfaces_rejections() {
+        let (client, seen) = proxy(0).await;
+        let receipt = client
+            .produce("orders/eu", Some("k"), &json!({"id": 1}), None)
+            .await
+            .unwrap();
+        assert_eq!(receipt.topic, "orders/eu");
+        assert_eq!((receipt.partition, receipt.offset), (Some(2), Some(42)));
+
+        let err = client
+            .produce("full", None, &json!(1), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("quota exceeded"));
+        assert_eq!(
+            seen.lock().unwrap().as_slice(),
+            ["POST /topics/orders%2Feu", "POST /topics/full"]
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_more_records_than_the_channel_holds() {
+        let (client, seen) = proxy(50).await;
+        let mut stream = client
+            .consume_stream(
+                vec!["orders".to_string()],
+                ConsumeOptions {
+                    group: Some("night shift".to_string()),
+                    max_messages: 1000,
+                    timeout: Duration::from_secs(1),
+                    from_beginning: true,
+                },
+            )
+            .await
+            .unwrap();
+        let mut messages = Vec::new();
+        while let Some(message) = stream.recv().await {
+            messages.push(message.unwrap());
+        }
+        assert_eq!(messages.len(), 50);
+        assert_eq!(messages[0].key.as_deref(), Some("7"));
+        assert_eq!(messages[49].payload, json!({"id": 49}));
+        assert_eq!(messages[49].offset, Some(49));
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0], "POST /consumers/night%20shift");
+        assert_eq!(seen[1], "POST /consumers/night/instances/i1/subscription");
+        assert_eq!(
+            seen.last().map(String::as_str),
+            Some("DELETE /consumers/night/instances/i1")
+        );
+    }
+}
@@ -0,0 +1,463 @@
+/// Messaging module for event-driven debugging and automation
+///
+/// Provides unified access to message brokers:
+/// - Kafka (via the Confluent REST Proxy v2/v3 APIs)
+/// - NATS (core protocol over TCP, JetStream state via the monitoring endpoint)
+pub mod kafka;
+pub mod nats;
+
+pub use kafka::{KafkaClient, KafkaConfig};
+pub use nats::{NatsClient, NatsConfig};
+
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Default consumer group used when none is supplied
+pub const DEFAULT_CONSUMER_GROUP: &str = "devops-mcp";
+
+/// Messages a consumer task may read ahead of the stream's reader
+const STREAM_BUFFER: usize = 32;
+
+/// Supported messaging backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessagingBackend {
+    Kafka,
+    Nats,
+}
+
+impl std::str::FromStr for MessagingBackend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "kafka" => Ok(Self::Kafka),
+            "nats" => Ok(Self::Nats),
+            other => Err(Error::validation_with_field(
+                format!("Unsupported messaging backend: {}", other),
+                "backend",
+            )),
+        }
+    }
+}
+
+/// Messaging module configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessagingConfig {
+    /// Kafka REST Proxy configuration
+    pub kafka: Option<KafkaConfig>,
+    /// NATS server configuration
+    pub nats: Option<NatsConfig>,
+}
+
+/// A message received from a topic or subject
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    /// Topic (Kafka) or subject (NATS)
+    pub topic: String,
+    /// Message key, if the backend supports keys
+    pub key: Option<String>,
+    /// Payload, decoded as JSON when possible
+    pub payload: Value,
+    /// Message headers
+    pub headers: HashMap<String, String>,
+    /// Partition the message was read from
+    pub partition: Option<i32>,
+    /// Offset within the partition
+    pub offset: Option<i64>,
+    /// Time the message was received
+    pub received_at: DateTime<Utc>,
+}
+
+/// Acknowledgement for a published message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishReceipt {
+    /// Topic or subject the message was published to
+    pub topic: String,
+    /// Partition assigned by the broker
+    pub partition: Option<i32>,
+    /// Offset assigned by the broker
+    pub offset: Option<i64>,
+}
+
+/// Topic (Kafka) or stream (NATS JetStream) summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicInfo {
+    /// Topic or stream name
+    pub name: String,
+    /// Number of partitions
+    pub partitions: Option<u32>,
+    /// Subjects bound to the stream
+    pub subjects: Vec<String>,
+    /// Messages currently retained
+    pub messages: Option<u64>,
+    /// Bytes currently retained
+    pub bytes: Option<u64>,
+    /// Number of attached consumers
+    pub consumers: Option<u32>,
+}
+
+/// Consumer group lag for a single partition or stream consumer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumerLag {
+    /// Consumer group or durable consumer name
+    pub group: String,
+    /// Topic or stream name
+    pub topic: String,
+    /// Partition (Kafka only)
+    pub partition: Option<i32>,
+    /// Committed / acknowledged position
+    pub current_offset: Option<i64>,
+    /// Latest position in the log
+    pub end_offset: Option<i64>,
+    /// Messages not yet consumed
+    pub lag: u64,
+}
+
+/// Options for consuming messages
+#[derive(Debug, Clone)]
+pub struct ConsumeOptions {
+    /// Consumer group (Kafka) or queue group (NATS)
+    pub group: Option<String>,
+    /// Stop after this many messages
+    pub max_messages: usize,
+    /// Stop after this much time has elapsed
+    pub timeout: Duration,
+    /// Start from the earliest offset when the group has no committed offset
+    pub from_beginning: bool,
+}
+
+impl Default for ConsumeOptions {
+    fn default() -> Self {
+        Self {
+            group: None,
+            max_messages: 10,
+            timeout: Duration::from_secs(5),
+            from_beginning: false,
+        }
+    }
+}
+
+/// Receiving half of a message stream
+pub type MessageStream = mpsc::Receiver<Result<Message>>;
+
+/// Channel for a consumer task, sized independently of `max_messages`
+pub(crate) fn stream_channel(
+    options: &ConsumeOptions,
+) -> (mpsc::Sender<Result<Message>>, MessageStream) {
+    mpsc::channel(options.max_messages.clamp(1, STREAM_BUFFER))
+}
+
+/// Decode a raw payload as JSON, falling back to a string
+pub(crate) fn decode_payload(bytes: &[u8]) -> Value {
+    serde_json::from_slice(bytes)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(bytes).into_owned()))
+}
+
+/// Messaging module exposing Kafka and NATS operations as tools
+#[derive(Debug)]
+pub struct MessagingModule {
+    kafka: Option<KafkaClient>,
+    nats: Option<NatsClient>,
+}
+
+impl MessagingModule {
+    /// Create a new messaging module from configuration
+    pub fn new(config: MessagingConfig) -> Result<Self> {
+        let kafka = config.kafka.map(KafkaClient::new).transpose()?;
+        let nats = config.nats.map(NatsClient::new).transpose()?;
+        Ok(Self { kafka, nats })
+    }
+
+    /// Get the Kafka client
+    pub fn kafka(&self) -> Result<&KafkaClient> {
+        self.kafka
+            .as_ref()
+            .ok_or_else(|| Error::config("Kafka is not configured"))
+    }
+
+    /// Get the NATS client
+    pub fn nats(&self) -> Result<&NatsClient> {
+        self.nats
+            .as_ref()
+            .ok_or_else(|| Error::config("NATS is not configured"))
+    }
+
+    /// Publish a message
+    pub async fn publish(
+        &self,
+        backend: MessagingBackend,
+        topic: &str,
+        key: Option<&str>,
+        payload: &Value,
+    ) -> Result<PublishReceipt> {
+        match backend {
+            MessagingBackend::Kafka => self.kafka()?.produce(topic, key, payload, None).await,
+            MessagingBackend::Nats => self.nats()?.publish(topic, payload).await,
+        }
+    }
+
+    /// Start consuming messages, yielding them on a channel as they arrive
+    pub async fn consume_stream(
+        &self,
+        backend: MessagingBackend,
+        topics: Vec<String>,
+        options: ConsumeOptions,
+    ) -> Result<MessageStream> {
+        if topics.is_empty() {
+            return Err(Error::validation_with_field(
+                "At least one topic is required",
+                "topics",
+            ));
+        }
+        match backend {
+            MessagingBackend::Kafka => self.kafka()?.consume_stream(topics, options).await,
+            MessagingBackend::Nats => self.nats()?.subscribe_stream(topics, options).await,
+        }
+    }
+
+    /// Consume a bounded batch of messages
+    pub async fn consume(
+        &self,
+        backend: MessagingBackend,
+        topics: Vec<String>,
+        options: ConsumeOptions,
+    ) -> Result<Vec<Message>> {
+        let mut stream = self.consume_stream(backend, topics, options).await?;
+        let mut messages = Vec::new();
+        while let Some(message) = stream.recv().await {
+            messages.push(message?);
+        }
+        Ok(messages)
+    }
+
+    /// List topics (Kafka) or JetStream streams (NATS)
+    pub async fn list_topics(&self, backend: MessagingBackend) -> Result<Vec<TopicInfo>> {
+        match backend {
+            MessagingBackend::Kafka => self.kafka()?.list_topics().await,
+            MessagingBackend::Nats => self.nats()?.list_streams().await,
+        }
+    }
+
+    /// Get lag for a consumer group (Kafka) or durable consumer (NATS)
+    pub async fn consumer_lag(
+        &self,
+        backend: MessagingBackend,
+        group: &str,
+    ) -> Result<Vec<ConsumerLag>> {
+        match backend {
+            MessagingBackend::Kafka => self.kafka()?.consumer_lag(group).await,
+            MessagingBackend::Nats => self.nats()?.consumer_lag(group).await,
+        }
+    }
+
+    /// Get tool definitions for messaging operations
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "publish_message",
+                "Publish a message to a Kafka topic or NATS subject",
+                "messaging",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "backend": {
+                            "type": "string",
+                            "enum": ["kafka", "nats"],
+                            "description": "Messaging backend"
+                        },
+                        "topic": {
+                            "type": "string",
+                            "description": "Kafka topic or NATS subject"
+                        },
+                        "key": {
+                            "type": "string",
+                            "description": "Message key (Kafka only)"
+                        },
+                        "payload": {
+                            "description": "Message payload (any JSON value)"
+                        }
+                    },
+                    "required": ["backend", "topic", "payload"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "consume_messages",
+                "Stream messages from Kafka topics or NATS subjects until a limit or timeout is reached",
+                "messaging",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "backend": {
+                            "type": "string",
+                            "enum": ["kafka", "nats"],
+                            "description": "Messaging backend"
+                        },
+                        "topics": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Topics or subjects to consume from"
+                        },
+                        "group": {
+                            "type": "string",
+                            "description": "Consumer group (Kafka) or queue group (NATS)"
+                        },
+                        "max_messages": {
+                            "type": "integer",
+                            "description": "Maximum number of messages to return",
+                            "default": 10
+                        },
+                        "timeout_secs": {
+                            "type": "integer",
+                            "description": "Maximum time to wait for messages",
+                            "default": 5
+                        },
+                        "from_beginning": {
+                            "type": "boolean",
+                            "description": "Read from the earliest offset for new groups",
+                            "default": false
+                        }
+                    },
+                    "required": ["backend", "topics"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "list_message_topics",
+                "List Kafka topics or NATS JetStream streams",
+                "messaging",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "backend": {
+                            "type": "string",
+                            "enum": ["kafka", "nats"],
+                            "description": "Messaging backend"
+                        }
+                    },
+                    "required": ["backend"]
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "consumer_group_lag",
+                "Report lag for a Kafka consumer group or NATS durable consumer",
+                "messaging",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "backend": {
+                            "type": "string",
+                            "enum": ["kafka", "nats"],
+                            "description": "Messaging backend"
+                        },
+                        "group": {
+                            "type": "string",
+                            "description": "Consumer group or durable consumer name"
+                        }
+                    },
+                    "required": ["backend", "group"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a messaging tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let backend: MessagingBackend = parameters
+            .get("backend")
+            .and_then(|b| b.as_str())
+            .ok_or_else(|| Error::validation_with_field("backend is required", "backend"))?
+            .parse()?;
+
+        match name {
+            "publish_message" => {
+                let topic = parameters
+                    .get("topic")
+                    .and_then(|t| t.as_str())
+                    .ok_or_else(|| Error::validation_with_field("topic is required", "topic"))?;
+                let payload = parameters.get("payload").ok_or_else(|| {
+                    Error::validation_with_field("payload is required", "payload")
+                })?;
+                let key = parameters.get("key").and_then(|k| k.as_str());
+
+                let receipt = self.publish(backend, topic, key, payload).await?;
+                Ok(call_result(
+                    format!("Published message to {}", receipt.topic),
+                    serde_json::to_value(&receipt)?,
+                ))
+            }
+            "consume_messages" => {
+                let topics: Vec<String> = parameters
+                    .get("topics")
+                    .and_then(|t| t.as_array())
+                    .map(|topics| {
+                        topics
+                            .iter()
+                            .filter_map(|t| t.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let options = ConsumeOptions {
+                    group: parameters
+                        .get("group")
+                        .and_then(|g| g.as_str())
+                        .map(str::to_string),
+                    max_messages: parameters
+                        .get("max_messages")
+                        .and_then(|m| m.as_u64())
+                        .unwrap_or(10) as usize,
+                    timeout: Duration::from_secs(
+                        parameters
+                            .get("timeout_secs")
+                            .and_then(|t| t.as_u64())
+                            .unwrap_or(5),
+                    ),
+                    from_beginning: parameters
+                        .get("from_beginning")
+                        .and_then(|f| f.as_bool())
+                        .unwrap_or(false),
+                };
+
+                let messages = self.consume(backend, topics, options).await?;
+                Ok(call_result(
+                    format!("Received {} message(s)", messages.len()),
+                    json!({ "messages": messages }),
+                ))
+            }
+            "list_message_topics" => {
+                let topics = self.list_topics(backend).await?;
+                Ok(call_result(
+                    format!("Found {} topic(s)", topics.len()),
+                    json!({ "topics": topics }),
+                ))
+            }
+            "consumer_group_lag" => {
+                let group = parameters
+                    .get("group")
+                    .and_then(|g| g.as_str())
+                    .ok_or_else(|| Error::validation_with_field("group is required", "group"))?;
+                let lag = self.consumer_lag(backend, group).await?;
+                let total: u64 = lag.iter().map(|l| l.lag).sum();
+                Ok(call_result(
+                    format!("Consumer group {} has a total lag of {}", group, total),
+                    json!({ "group": group, "total_lag": total, "partitions": lag }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "messaging_tool",
+                name,
+            )),
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use super::{
+    decode_payload, stream_channel, ConsumeOptions, ConsumerLag, Message, MessageStream,
+    PublishReceipt, TopicInfo,
+};
+use crate::error::{Error, Result};
+use chrono::Utc;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+
+/// NATS server configuration
+///
+/// The client speaks the plain-text core protocol and does not negotiate TLS;
+/// servers that require TLS are rejected during the handshake.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NatsConfig {
+    /// Server address (host:port)
+    pub server_addr: String,
+    /// Monitoring endpoint used for JetStream state (e.g. http://localhost:8222)
+    pub monitoring_url: Option<String>,
+    /// Token authentication
+    pub token: Option<String>,
+    /// Username for user/password authentication
+    pub username: Option<String>,
+    /// Password for user/password authentication
+    pub password: Option<String>,
+}
+
+/// Check that a subject or queue group is a single protocol token
+///
+/// The protocol separates fields with spaces and ends commands at CRLF, so
+/// either inside a name would let it inject commands of its own.
+fn check_token(value: &str, field: &str) -> Result<()> {
+    if value.is_empty() || value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(Error::validation_with_field(
+            format!(
+                "NATS {} must be non-empty without spaces or line breaks: {:?}",
+                field, value
+            ),
+            field,
+        ));
+    }
+    Ok(())
+}
+
+/// A single NATS protocol connection
+struct NatsConnection {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl NatsConnection {
+    async fn open(config: &NatsConfig) -> Result<Self> {
+        let stream = TcpStream::connect(&config.server_addr).await.map_err(|e| {
+            Error::connection_with_endpoint(
+                format!("Failed to connect to NATS: {}", e),
+                config.server_addr.clone(),
+            )
+        })?;
+        let (reader, writer) = stream.into_split();
+        let mut conn = Self {
+            reader: BufReader::new(reader),
+            writer,
+        };
+
+        let info_line = conn.read_line().await?;
+        let info: Value = info_line
+            .strip_prefix("INFO ")
+            .and_then(|info| serde_json::from_str(info).ok())
+            .ok_or_else(|| Error::protocol(format!("Unexpected NATS greeting: {}", info_line)))?;
+        if info.get("tls_required").and_then(|t| t.as_bool()) == Some(true) {
+            return Err(Error::capability(
+                "NATS server requires TLS, which this client does not support",
+            ));
+        }
+
+        let mut connect = json!({
+            "verbose": false,
+            "pedantic": false,
+            "lang": "rust",
+            "name": "devops-mcp",
+            "version": env!("CARGO_PKG_VERSION"),
+            "protocol": 1
+        });
+        if let Some(token) = &config.token {
+            connect["auth_token"] = json!(token);
+        }
+        if let Some(username) = &config.username {
+            connect["user"] = json!(username);
+            connect["pass"] = json!(config.password.clone().unwrap_or_default());
+        }
+
+        conn.write(format!("CONNECT {}\r\nPING\r\n", connect).as_bytes())
+            .await?;
+        conn.await_pong().await?;
+        Ok(conn)
+    }
+
+    async fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        let read = self
+            .reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| Error::connection(format!("Failed to read from NATS: {}", e)))?;
+        if read == 0 {
+            return Err(Error::connection("NATS connection closed"));
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer
+            .write_all(bytes)
+            .await
+            .map_err(|e| Error::connection(format!("Failed to write to NATS: {}", e)))
+    }
+
+    async fn await_pong(&mut self) -> Result<()> {
+        loop {
+            let line = self.read_line().await?;
+            match line.as_str() {
+                "PONG" => return Ok(()),
+                "PING" => self.write(b"PONG\r\n").await?,
+                "+OK" => continue,
+                l if l.starts_with("INFO ") => continue,
+                l if l.starts_with("-ERR") => {
+                    return Err(Error::service(format!("NATS server error: {}", l)))
+                }
+                l => return Err(Error::protocol(format!("Unexpected NATS response: {}", l))),
+            }
+        }
+    }
+
+    async fn publish(&mut self, subject: &str, payload: &[u8]) -> Result<()> {
+        check_token(subject, "subject")?;
+        let mut frame = format!("PUB {} {}\r\n", subject, payload.len()).into_bytes();
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(b"\r\nPING\r\n");
+        self.write(&frame).await?;
+        self.await_pong().await
+    }
+
+    async fn subscribe(
+        &mut self,
+        subject: &str,
+        queue_group: Option<&str>,
+        sid: usize,
+    ) -> Result<()> {
+        check_token(subject, "subject")?;
+        if let Some(group) = queue_group {
+            check_token(group, "queue_group")?;
+        }
+        let line = match queue_group {
+            Some(group) => format!("SUB {} {} {}\r\n", subject, group, sid),
+            None => format!("SUB {} {}\r\n", subject, sid),
+        };
+        self.write(line.as_bytes()).await
+    }
+
+    /// Read until the next MSG frame, answering server PINGs along the way
+    async fn next_message(&mut self) -> Result<(String, Vec<u8>)> {
+        loop {
+            let line = self.read_line().await?;
+            if line == "PING" {
+                self.write(b"PONG\r\n").await?;
+                continue;
+            }
+            if line.starts_with("-ERR") {
+                return Err(Error::service(format!("NATS server error: {}", line)));
+            }
+            let Some(header) = line.strip_prefix("MSG ") else {
+                continue;
+            };
+
+            // MSG <subject> <sid> [reply-to] <#bytes>
+            let parts: Vec<&str> = header.split_whitespace().collect();
+            let (subject, size) = match parts.as_slice() {
+                [subject, _sid, size] | [subject, _sid, _, size] => (*subject, *size),
+                _ => return Err(Error::protocol(format!("Malformed NATS MSG: {}", line))),
+            };
+            let size: usize = size
+                .parse()
+                .map_err(|_| Error::protocol(format!("Invalid NATS payload size: {}", size)))?;
+
+            let mut payload = vec![0u8; size + 2];
+            self.reader
+                .read_exact(&mut payload)
+                .await
+                .map_err(|e| Error::connection(format!("Failed to read NATS payload: {}", e)))?;
+            payload.truncate(size);
+            return Ok((subject.to_string(), payload));
+        }
+    }
+}
+
+/// NATS client for publishing, subscribing and JetStream monitoring
+#[derive(Debug, Clone)]
+pub struct NatsClient {
+    client: Client,
+    config: NatsConfig,
+}
+
+impl NatsClient {
+    /// Create a new NATS client
+    pub fn new(config: NatsConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| {
+                Error::network(format!("Failed to create NATS monitoring client: {}", e))
+            })?;
+        Ok(Self { client, config })
+    }
+
+    /// Publish a payload to a subject
+    pub async fn publish(&self, subject: &str, payload: &Value) -> Result<PublishReceipt> {
+        let bytes = match payload {
+            Value::String(s) => s.clone().into_bytes(),
+            other => serde_json::to_vec(other)?,
+        };
+
+        let mut conn = NatsConnection::open(&self.config).await?;
+        conn.publish(subject, &bytes).await?;
+
+        Ok(PublishReceipt {
+            topic: subject.to_string(),
+            partition: None,
+            offset: None,
+        })
+    }
+
+    /// Subscribe to subjects, yielding messages until the limit or timeout is reached
+    ///
+    /// When a group is supplied the subscription joins that queue group, so
+    /// messages are load-balanced with other members instead of duplicated.
+    pub async fn subscribe_stream(
+        &self,
+        subjects: Vec<String>,
+        options: ConsumeOptions,
+    ) -> Result<MessageStream> {
+        let mut conn = NatsConnection::open(&self.config).await?;
+        for (sid, subject) in subjects.iter().enumerate() {
+            conn.subscribe(subject, options.group.as_deref(), sid + 1)
+                .await?;
+        }
+
+        let (tx, rx) = stream_channel(&options);
+        tokio::spawn(async move {
+            let deadline = tokio::time::Instant::now() + options.timeout;
+            for _ in 0..options.max_messages {
+                let message = match tokio::time::timeout_at(deadline, conn.next_message()).await {
+                    Ok(Ok((subject, payload))) => Ok(Message {
+                        topic: subject,
+                        key: None,
+                        payload: decode_payload(&payload),
+                        headers: HashMap::new(),
+                        partition: None,
+                        offset: None,
+                        received_at: Utc::now(),
+                    }),
+                    Ok(Err(e)) => Err(e),
+                    Err(_) => break,
+                };
+                let failed = message.is_err();
+                if tx.send(message).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn jetstream_state(&self) -> Result<Value> {
+        let monitoring_url = self.config.monitoring_url.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "NATS monitoring URL is not configured",
+                "Set monitoring_url to the server's HTTP monitoring port (default 8222)",
+            )
+        })?;
+        let url = format!(
+            "{}/jsz?accounts=true&streams=true&consumers=true&config=true",
+            monitoring_url.trim_end_matches('/')
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Failed to query NATS monitoring: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(Error::api_with_status(
+                format!("NATS monitoring returned {}", status),
+                "nats",
+                status.as_u16(),
+            ));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse NATS monitoring response: {}", e)))
+    }
+
+    fn streams(state: &Value) -> impl Iterator<Item = &Value> {
+        state
+            .get("account_details")
+            .and_then(|a| a.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|account| account.get("stream_detail").and_then(|s| s.as_array()))
+            .flatten()
+    }
+
+    /// List JetStream streams
+    pub async fn list_streams(&self) -> Result<Vec<TopicInfo>> {
+        let state = self.jetstream_state().await?;
+        Ok(Self::streams(&state)
+            .map(|stream| TopicInfo {
+                name: stream
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                partitions: None,
+                subjects: stream
+                    .pointer("/config/subjects")
+                    .and_then(|s| s.as_array())
+                    .map(|s| {
+                        s.iter()
+                            .filter_map(|s| s.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                messages: stream.pointer("/state/messages").and_then(|m| m.as_u64()),
+                bytes: stream.pointer("/state/bytes").and_then(|b| b.as_u64()),
+                consumers: stream
+                    .pointer("/state/consumer_count")
+                    .and_then(|c| c.as_u64())
+                    .map(|c| c as u32),
+            })
+            .collect())
+    }
+
+    /// Get pending message counts for a durable JetStream consumer
+    pub async fn consumer_lag(&self, consumer: &str) -> Result<Vec<ConsumerLag>> {
+        let state = self.jetstream_state().await?;
+        let mut lag = Vec::new();
+
+        for stream in Self::streams(&state) {
+            let stream_name = stream
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or_default();
+            let last_seq = stream.pointer("/state/last_seq").and_then(|s| s.as_i64());
+
+            for detail in stream
+                .get("consumer_detail")
+                .and_then(|c| c.as_array())
+                .into_iter()
+                .flatten()
+                .filter(|c| c.get("name").and_then(|n| n.as_str()) == Some(consumer))
+            {
+                lag.push(ConsumerLag {
+                    group: consumer.to_string(),
+                    topic: stream_name.to_string(),
+                    partition: None,
+                    current_offset: detail
+                        .pointer("/ack_floor/stream_seq")
+                        .and_then(|s| s.as_i64()),
+                    end_offset: last_seq,
+                    lag: detail
+                        .get("num_pending")
+                        .and_then(|n| n.as_u64())
+                        .unwrap_or(0),
+                });
+            }
+        }
+
+        if lag.is_empty() {
+            return Err(Error::not_found_with_resource(
+                "JetStream consumer not found",
+                "nats_consumer",
+                consumer,
+            ));
+        }
+        Ok(lag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    /// NATS server stand-in recording the commands it receives
+    ///
+    /// Each subscription is answered with a PING and two messages, the
+    /// second carrying a reply subject.
+    async fn server() -> (NatsClient, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let log = Arc::clone(&log);
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut reader = BufReader::new(reader);
+                    writer
+                        .write_all(b"INFO {\"server_id\":\"test\",\"max_payload\":1048576}\r\n")
+                        .await
+                        .unwrap();
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).await.unwrap_or(0) > 0 {
+                        let command = line.trim_end().to_string();
+                        line.clear();
+                        if command.starts_with("PUB ") {
+                            reader.read_line(&mut line).await.unwrap();
+                            let payload = line.trim_end().to_string();
+                            line.clear();
+                            log.lock().unwrap().push(format!("{} {}", command, payload));
+                            continue;
+                        }
+                        if !command.starts_with("CONNECT ") {
+                            log.lock().unwrap().push(command.clone());
+                        }
+                        if command == "PING" {
+                            writer.write_all(b"PONG\r\n").await.unwrap();
+                        } else if command.starts_with("SUB ") {
+                            writer
+                                .write_all(
+                                    b"PING\r\nMSG orders.eu 1 5\r\nhello\r\n\
+                                      MSG orders.us 1 _INBOX.7 8\r\n{\"id\":2}\r\n",
+                                )
+                                .await
+                                .unwrap();
+                        }
+                    }
+                });
+            }
+        });
+        let client = NatsClient::new(NatsConfig {
+            server_addr: addr,
+            monitoring_url: None,
+            token: None,
+            username: None,
+            password: None,
+        })
+        .unwrap();
+        (client, seen)
+    }
+
+    #[tokio::test]
+    async fn publishes_and_rejects_subjects_that_break_the_protocol() {
+        let (client, seen) = server().await;
+        client
+            .publish("orders.created", &json!({"id": 1}))
+            .await
+            .unwrap();
+        client.publish("orders.note", &json!("hi")).await.unwrap();
+        for subject in ["orders created", "orders\r\nPUB evil 1", ""] {
+            let err = client.publish(subject, &json!(1)).await.unwrap_err();
+            assert!(err.to_string().contains("subject"), "{}", err);
+        }
+        let seen = seen.lock().unwrap();
+        assert!(seen.contains(&"PUB orders.created 8 {\"id\":1}".to_string()));
+        assert!(seen.contains(&"PUB orders.note 2 hi".to_string()));
+        assert!(!seen.iter().any(|c| c.contains("evil")));
+    }
+
+    #[tokio::test]
+    async fn subscribes_in_a_queue_group_and_parses_messages() {
+        let (client, seen) = server().await;
+        let options = |group: &str| ConsumeOptions {
+            group: Some(group.to_string()),
+            max_messages: 10,
+            timeout: Duration::from_millis(500),
+            from_beginning: false,
+        };
+        let mut stream = client
+            .subscribe_stream(vec!["orders.>".to_string()], options("workers"))
+            .await
+            .unwrap();
+        let mut messages = Vec::new();
+        while let Some(message) = stream.recv().await {
+            messages.push(message.unwrap());
+        }
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].topic, "orders.eu");
+        assert_eq!(messages[0].payload, json!("hello"));
+        assert_eq!(messages[1].topic, "orders.us");
+        assert_eq!(messages[1].payload, json!({"id": 2}));
+        {
+            let seen = seen.lock().unwrap();
+            assert!(seen.contains(&"SUB orders.> workers 1".to_string()));
+            assert!(seen.contains(&"PONG".to_string()));
+        }
+
+        let err = client
+            .subscribe_stream(vec!["orders.>".to_string()], options("night shift"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("queue_group"), "{}", err);
+    }
+}
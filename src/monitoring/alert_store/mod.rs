@@ -0,0 +1,264 @@
+/// Unified alert store
+///
+/// Keeps `UnifiedAlert`s raised by integrations that have no alert history
+/// of their own, such as Uptime Kuma outages, in one list that can be read
+/// and triaged. Alerts are keyed by id, so a source that reports the same
+/// problem again updates its alert instead of adding another; an alert that
+/// was acknowledged stays acknowledged until the source resolves it.
+/// Resolved alerts are dropped after `RETENTION_DAYS`.
+use crate::error::{Error, Result};
+use crate::monitoring::{AlertStatus, UnifiedAlert};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tokio::sync::RwLock;
+
+/// Days resolved alerts are kept
+const RETENTION_DAYS: i64 = 90;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AlertData {
+    alerts: Vec<UnifiedAlert>,
+}
+
+/// Persisted unified alerts
+pub struct AlertStore {
+    /// Where alerts are persisted; `None` keeps them in memory
+    path: Option<PathBuf>,
+    store: RwLock<AlertData>,
+}
+
+impl AlertStore {
+    /// Open the store, loading alerts from `path` if it exists
+    pub async fn open(path: Option<PathBuf>) -> Result<Self> {
+        let store = match &path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse alert store {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => AlertData::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read alert store: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => AlertData::default(),
+        };
+        Ok(Self {
+            path,
+            store: RwLock::new(store),
+        })
+    }
+
+    async fn persist(&self, store: &AlertData) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create alert store directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(format!("Failed to write alert store: {}", e), temp.clone())
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace alert store: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Add an alert or update the one with the same id; true when the alert
+    /// is new or its status changed
+    pub async fn record(&self, mut alert: UnifiedAlert) -> Result<bool> {
+        let mut store = self.store.write().await;
+        let now = Utc::now();
+        let changed = match store.alerts.iter_mut().find(|a| a.id == alert.id) {
+            Some(existing) => {
+                let status = match (&existing.status, &alert.status) {
+                    (_, AlertStatus::Resolved) => AlertStatus::Resolved,
+                    // Triage outlasts repeated reports of the same problem
+                    (AlertStatus::Acknowledged | AlertStatus::Suppressed, _) => {
+                        existing.status.clone()
+                    }
+                    _ => alert.status.clone(),
+                };
+                let changed = status != existing.status;
+                existing.resolved_at = match status {
+                    AlertStatus::Resolved => {
+                        existing.resolved_at.or(alert.resolved_at).or(Some(now))
+                    }
+                    _ => None,
+                };
+                existing.title = alert.title;
+                existing.description = alert.description;
+                existing.severity = alert.severity;
+                existing.sources = alert.sources;
+                existing.tags = alert.tags;
+                existing.status = status;
+                changed
+            }
+            None => {
+                if alert.status == AlertStatus::Resolved {
+                    alert.resolved_at.get_or_insert(now);
+                }
+                store.alerts.push(alert);
+                true
+            }
+        };
+        let cutoff = now - Duration::days(RETENTION_DAYS);
+        store
+            .alerts
+            .retain(|a| a.resolved_at.is_none_or(|at| at >= cutoff));
+        self.persist(&store).await?;
+        Ok(changed)
+    }
+
+    /// Acknowledge, suppress or resolve an alert, optionally assigning it
+    pub async fn update(
+        &self,
+        id: &str,
+        status: Option<AlertStatus>,
+        assignee: Option<&str>,
+    ) -> Result<UnifiedAlert> {
+        let mut store = self.store.write().await;
+        let alert = store
+            .alerts
+            .iter_mut()
+            .find(|a| a.id == id)
+            .ok_or_else(|| Error::not_found_with_resource("Alert not found", "alert", id))?;
+        if let Some(status) = status {
+            alert.resolved_at = match status {
+                AlertStatus::Resolved => alert.resolved_at.or(Some(Utc::now())),
+                _ => None,
+            };
+            alert.status = status;
+        }
+        if let Some(assignee) = assignee {
+            alert.assignee = Some(assignee.to_string());
+        }
+        let alert = alert.clone();
+        self.persist(&store).await?;
+        Ok(alert)
+    }
+
+    /// Alerts, newest first, optionally only those in `status` raised since `since`
+    pub async fn list(
+        &self,
+        status: Option<&AlertStatus>,
+        since: Option<DateTime<Utc>>,
+    ) -> Vec<UnifiedAlert> {
+        let mut alerts: Vec<UnifiedAlert> = self
+            .store
+            .read()
+            .await
+            .alerts
+            .iter()
+            .filter(|a| status.is_none_or(|s| &a.status == s))
+            .filter(|a| since.is_none_or(|since| a.created_at >= since))
+            .cloned()
+            .collect();
+        alerts.sort_by_key(|a| std::cmp::Reverse(a.created_at));
+        alerts
+    }
+
+    /// Get tool definitions for the alert store
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "alerts_list",
+                "List alerts in the unified alert store, such as Uptime Kuma outages, newest first",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "status": {"type": "string", "enum": ["Active", "Acknowledged", "Suppressed", "Resolved"]},
+                        "days": {"type": "integer", "description": "Only alerts raised in the last N days"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "alert_update",
+                "Acknowledge, suppress or resolve an alert in the unified alert store, or assign it",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "status": {"type": "string", "enum": ["Active", "Acknowledged", "Suppressed", "Resolved"]},
+                        "assignee": {"type": "string"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute an alert store tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let status = parameters
+            .get("status")
+            .filter(|s| !s.is_null())
+            .map(|s| {
+                serde_json::from_value::<AlertStatus>(s.clone())
+                    .map_err(|_| Error::validation_with_field("Invalid status", "status"))
+            })
+            .transpose()?;
+        match name {
+            "alerts_list" => {
+                let since = parameters
+                    .get("days")
+                    .and_then(|v| v.as_i64())
+                    .map(|days| Utc::now() - Duration::days(days));
+                let alerts = self.list(status.as_ref(), since).await;
+                let mut text = format!("{} alerts", alerts.len());
+                for alert in &alerts {
+                    text.push_str(&format!(
+                        "\n{} [{:?}/{:?}] {}",
+                        alert.created_at.format("%Y-%m-%d %H:%M"),
+                        alert.severity,
+                        alert.status,
+                        alert.title
+                    ));
+                }
+                Ok(call_result(text, json!({ "alerts": alerts })))
+            }
+            "alert_update" => {
+                let id = parameters
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::validation_with_field("id is required", "id"))?;
+                let assignee = parameters.get("assignee").and_then(|v| v.as_str());
+                let alert = self.update(id, status, assignee).await?;
+                Ok(call_result(
+                    format!("Alert \"{}\" is {:?}", alert.title, alert.status),
+                    json!({ "alert": alert }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "alert_tool",
+                name,
+            )),
+        }
+    }
+}
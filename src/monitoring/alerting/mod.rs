@@ -0,0 +1,676 @@
+//! Grafana alert rules, contact points and silences
+//!
+//! Rules and contact points go through the provisioning API
+//! (`/api/v1/provisioning`), silences through Grafana's built-in
+//! Alertmanager. Rules are written with `X-Disable-Provenance` so they stay
+//! editable in the Grafana UI.
+
+use super::GrafanaConfig;
+use crate::error::{Error, Result};
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Data source UID Grafana uses for server-side expressions
+const EXPRESSION_DATASOURCE: &str = "__expr__";
+
+/// Path of Grafana's built-in Alertmanager API
+const ALERTMANAGER_PATH: &str = "/api/alertmanager/grafana/api/v2";
+
+fn default_for() -> String {
+    "5m".to_string()
+}
+
+fn default_no_data_state() -> String {
+    "NoData".to_string()
+}
+
+fn default_exec_err_state() -> String {
+    "Error".to_string()
+}
+
+fn default_org_id() -> i64 {
+    1
+}
+
+/// A Grafana-managed alert rule, as the provisioning API stores it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<String>,
+    pub title: String,
+    #[serde(rename = "folderUID")]
+    pub folder_uid: String,
+    pub rule_group: String,
+    /// `refId` of the query or expression that decides whether the rule fires
+    pub condition: String,
+    /// Queries and expressions the rule evaluates
+    pub data: Vec<Value>,
+    /// How long the condition must hold before the rule fires, e.g. `5m`
+    #[serde(rename = "for", default = "default_for")]
+    pub for_duration: String,
+    #[serde(default = "default_no_data_state")]
+    pub no_data_state: String,
+    #[serde(default = "default_exec_err_state")]
+    pub exec_err_state: String,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+    #[serde(default)]
+    pub is_paused: bool,
+    #[serde(rename = "orgID", default = "default_org_id")]
+    pub org_id: i64,
+    #[serde(default, skip_serializing)]
+    pub updated: Option<DateTime<Utc>>,
+}
+
+/// Which side of the threshold fires a rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Comparison {
+    #[default]
+    Above,
+    Below,
+}
+
+impl Comparison {
+    /// Grafana's name for the threshold evaluator
+    fn evaluator(self) -> &'static str {
+        match self {
+            Comparison::Above => "gt",
+            Comparison::Below => "lt",
+        }
+    }
+
+    fn from_evaluator(evaluator: &str) -> Option<Self> {
+        match evaluator {
+            "gt" => Some(Comparison::Above),
+            "lt" => Some(Comparison::Below),
+            _ => None,
+        }
+    }
+}
+
+/// A rule that fires when a query crosses a threshold
+#[derive(Debug, Clone)]
+pub struct ThresholdRule {
+    pub title: String,
+    pub folder_uid: String,
+    pub rule_group: String,
+    pub datasource_uid: String,
+    /// Query in the data source's language, e.g. PromQL
+    pub expr: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    pub for_duration: String,
+    pub labels: BTreeMap<String, String>,
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl ThresholdRule {
+    /// The rule as Grafana stores it: query `A` and threshold expression `B`
+    pub fn build(self, org_id: i64) -> Result<AlertRule> {
+        parse_duration(&self.for_duration)?;
+        Ok(AlertRule {
+            uid: None,
+            title: self.title,
+            folder_uid: self.folder_uid,
+            rule_group: self.rule_group,
+            condition: "B".to_string(),
+            data: vec![
+                json!({
+                    "refId": "A",
+                    "relativeTimeRange": {"from": 600, "to": 0},
+                    "datasourceUid": self.datasource_uid,
+                    "model": {"refId": "A", "expr": self.expr, "instant": true}
+                }),
+                json!({
+                    "refId": "B",
+                    "relativeTimeRange": {"from": 0, "to": 0},
+                    "datasourceUid": EXPRESSION_DATASOURCE,
+                    "model": {
+                        "refId": "B",
+                        "type": "threshold",
+                        "expression": "A",
+                        "conditions": [{"evaluator": {
+                            "type": self.comparison.evaluator(),
+                            "params": [self.threshold]
+                        }}]
+                    }
+                }),
+            ],
+            for_duration: self.for_duration,
+            no_data_state: default_no_data_state(),
+            exec_err_state: default_exec_err_state(),
+            labels: self.labels,
+            annotations: self.annotations,
+            is_paused: false,
+            org_id,
+            updated: None,
+        })
+    }
+}
+
+impl AlertRule {
+    /// Comparison and threshold of the rule's threshold expression
+    pub fn threshold(&self) -> Option<(Comparison, f64)> {
+        let expression = self
+            .data
+            .iter()
+            .find(|d| d["model"]["type"] == "threshold")?;
+        let evaluator = &expression["model"]["conditions"][0]["evaluator"];
+        Some((
+            Comparison::from_evaluator(evaluator["type"].as_str()?)?,
+            evaluator["params"][0].as_f64()?,
+        ))
+    }
+
+    /// Replace the query of the rule's only data source query
+    pub fn set_expr(&mut self, expr: &str) -> Result<()> {
+        let mut queries = self
+            .data
+            .iter_mut()
+            .filter(|d| d["datasourceUid"] != EXPRESSION_DATASOURCE);
+        match (queries.next(), queries.next()) {
+            (Some(query), None) => {
+                query["model"]["expr"] = json!(expr);
+                Ok(())
+            }
+            _ => Err(Error::validation(format!(
+                "Alert rule \"{}\" does not have exactly one query; edit it in Grafana",
+                self.title
+            ))),
+        }
+    }
+
+    /// Replace the threshold of the rule's threshold expression
+    pub fn set_threshold(&mut self, comparison: Comparison, threshold: f64) -> Result<()> {
+        let expression = self
+            .data
+            .iter_mut()
+            .find(|d| d["model"]["type"] == "threshold")
+            .ok_or_else(|| {
+                Error::validation(format!(
+                    "Alert rule \"{}\" has no threshold expression; edit it in Grafana",
+                    self.title
+                ))
+            })?;
+        expression["model"]["conditions"] = json!([{"evaluator": {
+            "type": comparison.evaluator(),
+            "params": [threshold]
+        }}]);
+        Ok(())
+    }
+}
+
+/// Where Grafana sends notifications
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactPoint {
+    #[serde(default)]
+    pub uid: Option<String>,
+    pub name: String,
+    /// Integration, e.g. `email`, `slack`, `pagerduty`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Integration settings, with secrets redacted by Grafana
+    #[serde(default)]
+    pub settings: Value,
+    #[serde(default)]
+    pub disable_resolve_message: bool,
+}
+
+/// Label matcher of a silence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Matcher {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub is_regex: bool,
+    #[serde(default = "is_equal_default")]
+    pub is_equal: bool,
+}
+
+fn is_equal_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SilenceStatus {
+    /// `active`, `pending` or `expired`
+    pub state: String,
+}
+
+/// Alertmanager silence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Silence {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub matchers: Vec<Matcher>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub created_by: String,
+    pub comment: String,
+    #[serde(default, skip_serializing)]
+    pub status: Option<SilenceStatus>,
+}
+
+impl Silence {
+    /// Silence alerts whose labels equal `labels`, starting now
+    pub fn new(
+        labels: &BTreeMap<String, String>,
+        duration: Duration,
+        created_by: &str,
+        comment: &str,
+    ) -> Result<Self> {
+        if labels.is_empty() {
+            return Err(Error::validation_with_field(
+                "At least one label matcher is required; an empty silence would mute every alert",
+                "matchers",
+            ));
+        }
+        if comment.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "A comment explaining the silence is required",
+                "comment",
+            ));
+        }
+        let now = Utc::now();
+        Ok(Self {
+            id: None,
+            matchers: labels
+                .iter()
+                .map(|(name, value)| Matcher {
+                    name: name.clone(),
+                    value: value.clone(),
+                    is_regex: false,
+                    is_equal: true,
+                })
+                .collect(),
+            starts_at: now,
+            ends_at: now + duration,
+            created_by: created_by.to_string(),
+            comment: comment.to_string(),
+            status: None,
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.status.as_ref().is_some_and(|s| s.state == "expired")
+    }
+}
+
+/// Parse a Grafana-style duration such as `90s`, `5m`, `1h30m` or `2d`
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let invalid = || {
+        Error::validation(format!(
+            "Invalid duration \"{}\"; use a number with s, m, h or d, e.g. 5m or 1h30m",
+            text
+        ))
+    };
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    for c in text.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let amount: i64 = digits.parse().map_err(|_| invalid())?;
+        digits.clear();
+        total += match c {
+            's' => Duration::seconds(amount),
+            'm' => Duration::minutes(amount),
+            'h' => Duration::hours(amount),
+            'd' => Duration::days(amount),
+            _ => return Err(invalid()),
+        };
+    }
+    if !digits.is_empty() || total <= Duration::zero() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// Client for Grafana's alerting APIs
+pub struct GrafanaAlerting {
+    client: Client,
+    base_url: String,
+    authorization: Option<String>,
+    org_id: i64,
+}
+
+impl GrafanaAlerting {
+    pub fn new(config: &GrafanaConfig) -> Result<Self> {
+        let authorization = match (&config.api_key, &config.username, &config.password) {
+            (Some(key), _, _) => Some(format!("Bearer {}", key)),
+            (None, Some(username), Some(password)) => Some(format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", username, password))
+            )),
+            _ => None,
+        };
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create HTTP client: {}", e)))?;
+        Ok(Self {
+            client,
+            base_url: config.url.trim_end_matches('/').to_string(),
+            authorization,
+            org_id: config.org_id.unwrap_or_else(default_org_id),
+        })
+    }
+
+    /// Organization rules are created in
+    pub fn org_id(&self) -> i64 {
+        self.org_id
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}{}", self.base_url, path))
+            .header("X-Grafana-Org-Id", self.org_id.to_string());
+        match &self.authorization {
+            Some(authorization) => request.header(reqwest::header::AUTHORIZATION, authorization),
+            None => request,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder, action: &str) -> Result<T> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Failed to {}: {}", action, e)))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| Error::network(format!("Failed to {}: {}", action, e)))?;
+        if !status.is_success() {
+            let body: Value = serde_json::from_str(&text).unwrap_or_default();
+            let message = format!(
+                "Failed to {}: {}",
+                action,
+                body["message"].as_str().unwrap_or(&text)
+            );
+            return Err(match status.as_u16() {
+                401 | 403 => Error::auth(message),
+                404 => Error::not_found(message),
+                400 | 422 => Error::validation(message),
+                code => Error::api_with_status(message, "grafana", code),
+            });
+        }
+        // Deletes answer with an empty body
+        let text = if text.trim().is_empty() {
+            "null"
+        } else {
+            &text
+        };
+        serde_json::from_str(text)
+            .map_err(|e| Error::parsing(format!("Failed to {}: {}", action, e)))
+    }
+
+    /// Alert rules, optionally only those in one folder
+    pub async fn list_rules(&self, folder_uid: Option<&str>) -> Result<Vec<AlertRule>> {
+        let rules: Vec<AlertRule> = self
+            .send(
+                self.request(reqwest::Method::GET, "/api/v1/provisioning/alert-rules"),
+                "list alert rules",
+            )
+            .await?;
+        Ok(rules
+            .into_iter()
+            .filter(|r| folder_uid.is_none_or(|folder| r.folder_uid == folder))
+            .collect())
+    }
+
+    pub async fn get_rule(&self, uid: &str) -> Result<AlertRule> {
+        self.send(
+            self.request(
+                reqwest::Method::GET,
+                &format!("/api/v1/provisioning/alert-rules/{}", uid),
+            ),
+            "get alert rule",
+        )
+        .await
+    }
+
+    pub async fn create_rule(&self, rule: &AlertRule) -> Result<AlertRule> {
+        self.send(
+            self.request(reqwest::Method::POST, "/api/v1/provisioning/alert-rules")
+                .header("X-Disable-Provenance", "true")
+                .json(rule),
+            "create alert rule",
+        )
+        .await
+    }
+
+    pub async fn update_rule(&self, uid: &str, rule: &AlertRule) -> Result<AlertRule> {
+        self.send(
+            self.request(
+                reqwest::Method::PUT,
+                &format!("/api/v1/provisioning/alert-rules/{}", uid),
+            )
+            .header("X-Disable-Provenance", "true")
+            .json(rule),
+            "update alert rule",
+        )
+        .await
+    }
+
+    pub async fn contact_points(&self) -> Result<Vec<ContactPoint>> {
+        self.send(
+            self.request(reqwest::Method::GET, "/api/v1/provisioning/contact-points"),
+            "list contact points",
+        )
+        .await
+    }
+
+    /// Silences, newest first
+    pub async fn silences(&self, include_expired: bool) -> Result<Vec<Silence>> {
+        let mut silences: Vec<Silence> = self
+            .send(
+                self.request(
+                    reqwest::Method::GET,
+                    &format!("{}/silences", ALERTMANAGER_PATH),
+                ),
+                "list silences",
+            )
+            .await?;
+        silences.retain(|s| include_expired || !s.is_expired());
+        silences.sort_by_key(|s| std::cmp::Reverse(s.starts_at));
+        Ok(silences)
+    }
+
+    /// Create a silence, returning its ID
+    pub async fn silence(&self, silence: &Silence) -> Result<String> {
+        let created: Value = self
+            .send(
+                self.request(
+                    reqwest::Method::POST,
+                    &format!("{}/silences", ALERTMANAGER_PATH),
+                )
+                .json(silence),
+                "create silence",
+            )
+            .await?;
+        created["silenceID"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| Error::parsing("Grafana did not return a silence ID"))
+    }
+
+    pub async fn expire_silence(&self, id: &str) -> Result<()> {
+        let _: Value = self
+            .send(
+                self.request(
+                    reqwest::Method::DELETE,
+                    &format!("{}/silence/{}", ALERTMANAGER_PATH, id),
+                ),
+                "expire silence",
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Path;
+    use axum::http::HeaderMap;
+    use axum::routing::{delete, get, put};
+    use axum::{Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    fn rule() -> ThresholdRule {
+        ThresholdRule {
+            title: "High error rate".to_string(),
+            folder_uid: "ops".to_string(),
+            rule_group: "api".to_string(),
+            datasource_uid: "prom".to_string(),
+            expr: "sum(rate(http_requests_total{code=~\"5..\"}[5m]))".to_string(),
+            comparison: Comparison::Above,
+            threshold: 5.0,
+            for_duration: "10m".to_string(),
+            labels: BTreeMap::from([("severity".to_string(), "page".to_string())]),
+            annotations: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn builds_and_edits_threshold_rules() {
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::days(2));
+        for invalid in ["", "5", "m", "5w", "0s"] {
+            assert!(parse_duration(invalid).is_err(), "{}", invalid);
+        }
+
+        let mut built = rule().build(3).unwrap();
+        let json = serde_json::to_value(&built).unwrap();
+        assert_eq!(json["folderUID"], "ops");
+        assert_eq!(json["for"], "10m");
+        assert_eq!(json["orgID"], 3);
+        assert_eq!(json["condition"], "B");
+        assert_eq!(json["data"][1]["model"]["expression"], "A");
+
+        built.set_expr("up == 0").unwrap();
+        built.set_threshold(Comparison::Below, 1.0).unwrap();
+        assert_eq!(built.data[0]["model"]["expr"], "up == 0");
+        assert_eq!(built.threshold(), Some((Comparison::Below, 1.0)));
+        let evaluator = &built.data[1]["model"]["conditions"][0]["evaluator"];
+        assert_eq!(
+            (evaluator["type"].as_str(), &evaluator["params"][0]),
+            (Some("lt"), &json!(1.0))
+        );
+
+        assert!(ThresholdRule {
+            for_duration: "soon".to_string(),
+            ..rule()
+        }
+        .build(1)
+        .is_err());
+        assert!(Silence::new(&BTreeMap::new(), Duration::hours(1), "me", "deploy").is_err());
+    }
+
+    #[tokio::test]
+    async fn manages_rules_contact_points_and_silences() {
+        let rules = Arc::new(Mutex::new(Vec::<Value>::new()));
+        let silences = Arc::new(Mutex::new(Vec::<Value>::new()));
+        let (stored, listed, updated) =
+            (Arc::clone(&rules), Arc::clone(&rules), Arc::clone(&rules));
+        let (created_silences, listed_silences) = (Arc::clone(&silences), Arc::clone(&silences));
+        let app = Router::new()
+            .route(
+                "/api/v1/provisioning/alert-rules",
+                get(move || async move { Json(Value::from(listed.lock().unwrap().clone())) }).post(
+                    move |headers: HeaderMap, Json(mut rule): Json<Value>| async move {
+                        assert_eq!(headers["authorization"], "Bearer key");
+                        assert_eq!(headers["x-disable-provenance"], "true");
+                        rule["uid"] = json!("rule-1");
+                        stored.lock().unwrap().push(rule.clone());
+                        (axum::http::StatusCode::CREATED, Json(rule))
+                    },
+                ),
+            )
+            .route(
+                "/api/v1/provisioning/alert-rules/:uid",
+                put(move |Path(uid): Path<String>, Json(rule): Json<Value>| async move {
+                    let mut rules = updated.lock().unwrap();
+                    let existing = rules.iter_mut().find(|r| r["uid"] == uid.as_str()).unwrap();
+                    *existing = rule.clone();
+                    Json(rule)
+                }),
+            )
+            .route(
+                "/api/v1/provisioning/contact-points",
+                get(|| async {
+                    Json(json!([{"uid": "cp1", "name": "on-call", "type": "pagerduty",
+                        "settings": {"integrationKey": "[REDACTED]"}, "disableResolveMessage": false}]))
+                }),
+            )
+            .route(
+                "/api/alertmanager/grafana/api/v2/silences",
+                get(move || async move { Json(Value::from(listed_silences.lock().unwrap().clone())) })
+                    .post(move |Json(mut silence): Json<Value>| async move {
+                        silence["id"] = json!("s1");
+                        silence["status"] = json!({"state": "active"});
+                        created_silences.lock().unwrap().push(silence);
+                        Json(json!({"silenceID": "s1"}))
+                    }),
+            )
+            .route(
+                "/api/alertmanager/grafana/api/v2/silence/:id",
+                delete(move |Path(id): Path<String>| async move {
+                    let mut silences = silences.lock().unwrap();
+                    let silence = silences.iter_mut().find(|s| s["id"] == id.as_str()).unwrap();
+                    silence["status"]["state"] = json!("expired");
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let grafana = GrafanaAlerting::new(&GrafanaConfig {
+            url,
+            api_key: Some("key".to_string()),
+            username: None,
+            password: None,
+            org_id: None,
+            alloy: None,
+        })
+        .unwrap();
+        let created = grafana
+            .create_rule(&rule().build(1).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(created.uid.as_deref(), Some("rule-1"));
+        let mut paused = created.clone();
+        paused.is_paused = true;
+        grafana.update_rule("rule-1", &paused).await.unwrap();
+        let listed = grafana.list_rules(Some("ops")).await.unwrap();
+        assert!(listed[0].is_paused);
+        assert!(grafana.list_rules(Some("other")).await.unwrap().is_empty());
+
+        let contact_points = grafana.contact_points().await.unwrap();
+        assert_eq!(contact_points[0].kind, "pagerduty");
+
+        let labels = BTreeMap::from([("alertname".to_string(), "High error rate".to_string())]);
+        let silence = Silence::new(&labels, Duration::hours(2), "oncall", "Deploying fix").unwrap();
+        assert_eq!(grafana.silence(&silence).await.unwrap(), "s1");
+        assert_eq!(grafana.silences(false).await.unwrap().len(), 1);
+        grafana.expire_silence("s1").await.unwrap();
+        assert!(grafana.silences(false).await.unwrap().is_empty());
+        assert_eq!(grafana.silences(true).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,295 @@
+//! Detection rule management for Elastic Security and Microsoft Sentinel
+//!
+//! Both SIEMs keep scheduled rules that query stored events and raise an
+//! alert when the query matches. `DetectionRules` lists them, creates new
+//! ones, switches them on and off, and runs one on demand against a past
+//! time range to see what it would have caught before it goes live.
+//! Elastic runs are rule previews; Sentinel runs send the rule's KQL to
+//! the workspace's Log Analytics query API.
+
+pub mod elastic;
+pub mod sentinel;
+
+pub use elastic::ElasticSecurityRules;
+pub use sentinel::SentinelRules;
+
+use super::MonitoringConfig;
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Matching events returned with a run
+pub const RUN_SAMPLE_SIZE: usize = 20;
+
+fn default_severity() -> RuleSeverity {
+    RuleSeverity::Medium
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Rule severity, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSeverity {
+    Informational,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl RuleSeverity {
+    /// Read a severity in any case, as either SIEM spells it
+    pub fn parse(severity: &str) -> Option<Self> {
+        match severity.trim().to_lowercase().as_str() {
+            "informational" | "info" => Some(RuleSeverity::Informational),
+            "low" => Some(RuleSeverity::Low),
+            "medium" => Some(RuleSeverity::Medium),
+            "high" => Some(RuleSeverity::High),
+            "critical" => Some(RuleSeverity::Critical),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleSeverity::Informational => "informational",
+            RuleSeverity::Low => "low",
+            RuleSeverity::Medium => "medium",
+            RuleSeverity::High => "high",
+            RuleSeverity::Critical => "critical",
+        }
+    }
+}
+
+/// A detection rule as either SIEM reports it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionRule {
+    /// Backend the rule lives in, `elastic` or `sentinel`
+    pub source: String,
+    /// Elastic `rule_id` or Sentinel rule name; stable across edits
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub severity: RuleSeverity,
+    pub enabled: bool,
+    /// Rule kind, e.g. `query`, `eql`, `threshold`, `Scheduled`, `NRT`
+    pub kind: String,
+    pub query: String,
+    /// Query language, e.g. `kuery`, `lucene`, `eql`, `kql`
+    pub language: Option<String>,
+    /// Minutes between executions
+    pub interval_minutes: Option<u64>,
+    /// Minutes of events each execution covers
+    pub lookback_minutes: Option<u64>,
+    /// MITRE ATT&CK tactics
+    pub tactics: Vec<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A query rule to create
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSpec {
+    /// Stable ID; generated when omitted
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_severity")]
+    pub severity: RuleSeverity,
+    /// KQL for Sentinel; KQL (kuery), Lucene or EQL for Elastic
+    pub query: String,
+    /// Elastic query language, `kuery` by default
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Elastic index patterns; the configured index pattern by default
+    #[serde(default)]
+    pub index: Vec<String>,
+    #[serde(default = "default_interval")]
+    pub interval_minutes: u64,
+    /// Minutes of events each run covers; the interval by default
+    #[serde(default)]
+    pub lookback_minutes: Option<u64>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// MITRE ATT&CK tactics, e.g. `CredentialAccess`
+    #[serde(default)]
+    pub tactics: Vec<String>,
+}
+
+impl RuleSpec {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Rule name is required",
+                "name",
+            ));
+        }
+        if self.query.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Rule query is required",
+                "query",
+            ));
+        }
+        if self.interval_minutes == 0 {
+            return Err(Error::validation_with_field(
+                "Interval must be at least one minute",
+                "interval_minutes",
+            ));
+        }
+        if self.lookback() < self.interval_minutes {
+            return Err(Error::validation_with_field(
+                "Lookback must cover at least one interval, or events between runs are missed",
+                "lookback_minutes",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Minutes of events each run covers
+    pub fn lookback(&self) -> u64 {
+        self.lookback_minutes.unwrap_or(self.interval_minutes)
+    }
+}
+
+/// What a rule matched when run over a past time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleRun {
+    pub source: String,
+    pub rule_id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Alerts (Elastic) or result rows (Sentinel) the rule produced
+    pub matches: usize,
+    /// Up to `RUN_SAMPLE_SIZE` of them
+    pub sample: Vec<Value>,
+    /// Errors and warnings the SIEM reported while running the rule
+    pub messages: Vec<String>,
+}
+
+/// A SIEM's detection rules
+#[async_trait]
+pub trait DetectionRules: Send + Sync {
+    /// Backend name used as `source`
+    fn name(&self) -> &str;
+
+    async fn list(&self) -> Result<Vec<DetectionRule>>;
+
+    async fn create(&self, spec: &RuleSpec) -> Result<DetectionRule>;
+
+    async fn set_enabled(&self, id: &str, enabled: bool) -> Result<DetectionRule>;
+
+    /// Run a rule over `start..end` without raising real alerts
+    async fn run(&self, id: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<RuleRun>;
+}
+
+/// Rule backends for every SIEM with rule management configured
+pub fn backends(config: &MonitoringConfig) -> Result<Vec<Arc<dyn DetectionRules>>> {
+    let mut backends: Vec<Arc<dyn DetectionRules>> = Vec::new();
+    if let Some(es) = config
+        .elasticsearch
+        .as_ref()
+        .filter(|es| es.kibana_url.is_some())
+    {
+        backends.push(Arc::new(ElasticSecurityRules::new(es.clone())?));
+    }
+    if let Some(sentinel) = config.sentinel.as_ref().filter(|s| s.client_id.is_some()) {
+        backends.push(Arc::new(SentinelRules::new(sentinel.clone())?));
+    }
+    Ok(backends)
+}
+
+/// The backend named by `source`, or the only one configured
+pub fn select<'a>(
+    backends: &'a [Arc<dyn DetectionRules>],
+    source: Option<&str>,
+) -> Result<&'a Arc<dyn DetectionRules>> {
+    match (source, backends) {
+        (_, []) => Err(Error::config_with_suggestion(
+            "No SIEM rule backends are configured",
+            "Set monitoring.elasticsearch.kibana_url, or monitoring.sentinel with a service principal",
+        )),
+        (Some(source), _) => backends
+            .iter()
+            .find(|b| b.name() == source)
+            .ok_or_else(|| {
+                Error::not_found_with_resource("SIEM backend not configured", "siem", source)
+            }),
+        (None, [only]) => Ok(only),
+        (None, _) => Err(Error::validation_with_field(
+            "Several SIEM backends are configured; name one",
+            "source",
+        )),
+    }
+}
+
+/// Minutes in an Elastic interval such as `5m` or `now-6m`
+pub(crate) fn elastic_minutes(text: &str) -> Option<u64> {
+    let text = text.trim().trim_start_matches("now-");
+    let (amount, unit) = text.split_at(text.find(|c: char| !c.is_ascii_digit())?);
+    let amount: u64 = amount.parse().ok()?;
+    Some(match unit {
+        "s" => amount.div_ceil(60),
+        "m" => amount,
+        "h" => amount * 60,
+        "d" => amount * 1440,
+        _ => return None,
+    })
+}
+
+/// Minutes in an ISO 8601 duration such as `PT5H` or `P1DT30M`
+pub(crate) fn iso_minutes(text: &str) -> Option<u64> {
+    let rest = text.trim().strip_prefix('P')?;
+    let mut seconds = 0u64;
+    let mut digits = String::new();
+    let mut time = false;
+    for c in rest.chars() {
+        match c {
+            'T' => time = true,
+            '0'..='9' => digits.push(c),
+            _ => {
+                let amount: u64 = digits.parse().ok()?;
+                digits.clear();
+                seconds += amount
+                    * match (c, time) {
+                        ('W', false) => 604_800,
+                        ('D', false) => 86_400,
+                        ('H', true) => 3_600,
+                        ('M', true) => 60,
+                        ('S', true) => 1,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    digits.is_empty().then(|| seconds.div_ceil(60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_from_both_siems() {
+        assert_eq!(elastic_minutes("5m"), Some(5));
+        assert_eq!(elastic_minutes("now-6m"), Some(6));
+        assert_eq!(elastic_minutes("1h"), Some(60));
+        assert_eq!(elastic_minutes("90s"), Some(2));
+        assert_eq!(elastic_minutes("5x"), None);
+        assert_eq!(iso_minutes("PT5H"), Some(300));
+        assert_eq!(iso_minutes("P1DT30M"), Some(1470));
+        assert_eq!(iso_minutes("PT5M"), Some(5));
+        assert_eq!(iso_minutes("P14D"), Some(20160));
+        assert_eq!(iso_minutes("5M"), None);
+    }
+}
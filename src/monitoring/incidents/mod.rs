@@ -0,0 +1,343 @@
+use crate::error::{Error, Result};
+use crate::monitoring::AlertSeverity;
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tokio::sync::RwLock;
+
+/// One entry on an incident's timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    pub at: DateTime<Utc>,
+    pub author: Option<String>,
+    pub message: String,
+}
+
+/// An incident and what happened during it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    pub id: String,
+    pub title: String,
+    pub severity: AlertSeverity,
+    /// Affected services
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Repository paths implicated in the cause, usually filled in after review
+    #[serde(default)]
+    pub paths: Vec<String>,
+    pub opened_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub timeline: Vec<TimelineEvent>,
+}
+
+impl Incident {
+    /// Whether a repository path falls under one of the implicated paths
+    pub fn touches(&self, path: &str) -> bool {
+        self.paths.iter().any(|prefix| {
+            let prefix = prefix.trim_end_matches('/');
+            path == prefix
+                || path
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IncidentData {
+    incidents: Vec<Incident>,
+}
+
+/// Persisted incident timelines
+pub struct IncidentStore {
+    /// Where incidents are persisted; `None` keeps them in memory
+    path: Option<PathBuf>,
+    store: RwLock<IncidentData>,
+}
+
+impl IncidentStore {
+    /// Open the store, loading incidents from `path` if it exists
+    pub async fn open(path: Option<PathBuf>) -> Result<Self> {
+        let store = match &path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse incident store {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => IncidentData::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read incident store: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => IncidentData::default(),
+        };
+        Ok(Self {
+            path,
+            store: RwLock::new(store),
+        })
+    }
+
+    async fn persist(&self, store: &IncidentData) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create incident store directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to write incident store: {}", e),
+                    temp.clone(),
+                )
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace incident store: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Open a new incident
+    pub async fn open_incident(
+        &self,
+        title: &str,
+        severity: AlertSeverity,
+        services: Vec<String>,
+        paths: Vec<String>,
+    ) -> Result<Incident> {
+        if title.trim().is_empty() {
+            return Err(Error::validation_with_field("Title is required", "title"));
+        }
+        let now = Utc::now();
+        let incident = Incident {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: title.to_string(),
+            severity,
+            services,
+            paths,
+            opened_at: now,
+            resolved_at: None,
+            timeline: vec![TimelineEvent {
+                at: now,
+                author: None,
+                message: format!("Incident opened: {}", title),
+            }],
+        };
+        let mut store = self.store.write().await;
+        store.incidents.push(incident.clone());
+        self.persist(&store).await?;
+        Ok(incident)
+    }
+
+    /// Append to the timeline, add implicated paths, or resolve an incident
+    pub async fn update(
+        &self,
+        id: &str,
+        message: Option<&str>,
+        author: Option<&str>,
+        paths: &[String],
+        resolve: bool,
+    ) -> Result<Incident> {
+        let mut store = self.store.write().await;
+        let incident = store
+            .incidents
+            .iter_mut()
+            .find(|i| i.id == id)
+            .ok_or_else(|| Error::not_found_with_resource("Incident not found", "incident", id))?;
+        let now = Utc::now();
+        if let Some(message) = message {
+            incident.timeline.push(TimelineEvent {
+                at: now,
+                author: author.map(str::to_string),
+                message: message.to_string(),
+            });
+        }
+        for path in paths {
+            if !incident.paths.contains(path) {
+                incident.paths.push(path.clone());
+            }
+        }
+        if resolve && incident.resolved_at.is_none() {
+            incident.resolved_at = Some(now);
+            incident.timeline.push(TimelineEvent {
+                at: now,
+                author: author.map(str::to_string),
+                message: "Incident resolved".to_string(),
+            });
+        }
+        let incident = incident.clone();
+        self.persist(&store).await?;
+        Ok(incident)
+    }
+
+    /// Incidents opened since `since`, newest first
+    pub async fn list(&self, since: Option<DateTime<Utc>>) -> Vec<Incident> {
+        let mut incidents: Vec<Incident> = self
+            .store
+            .read()
+            .await
+            .incidents
+            .iter()
+            .filter(|i| since.is_none_or(|since| i.opened_at >= since))
+            .cloned()
+            .collect();
+        incidents.sort_by_key(|i| std::cmp::Reverse(i.opened_at));
+        incidents
+    }
+
+    /// Get tool definitions for incident timelines
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "incident_open",
+                "Open an incident and start its timeline",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "title": {"type": "string"},
+                        "severity": {"type": "string", "enum": ["Critical", "High", "Medium", "Low", "Info"]},
+                        "services": {"type": "array", "items": {"type": "string"}},
+                        "paths": {"type": "array", "items": {"type": "string"}, "description": "Repository paths implicated in the cause"}
+                    },
+                    "required": ["title", "severity"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "incident_update",
+                "Add a timeline entry or implicated paths to an incident, or resolve it",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "message": {"type": "string"},
+                        "author": {"type": "string"},
+                        "paths": {"type": "array", "items": {"type": "string"}},
+                        "resolve": {"type": "boolean", "default": false}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "incident_timeline",
+                "List incidents with their timelines",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "days": {"type": "integer", "description": "Only incidents opened in the last N days"}
+                    }
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute an incident tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let string = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let strings = |key: &str| -> Vec<String> {
+            parameters
+                .get(key)
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default()
+        };
+        let incident = match name {
+            "incident_open" => {
+                let severity =
+                    serde_json::from_value(parameters.get("severity").cloned().unwrap_or_default())
+                        .map_err(|_| {
+                            Error::validation_with_field("Invalid or missing severity", "severity")
+                        })?;
+                self.open_incident(
+                    string("title").unwrap_or_default(),
+                    severity,
+                    strings("services"),
+                    strings("paths"),
+                )
+                .await?
+            }
+            "incident_update" => {
+                let id = string("id")
+                    .ok_or_else(|| Error::validation_with_field("id is required", "id"))?;
+                let resolve = parameters
+                    .get("resolve")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                self.update(
+                    id,
+                    string("message"),
+                    string("author"),
+                    &strings("paths"),
+                    resolve,
+                )
+                .await?
+            }
+            "incident_timeline" => {
+                let since = parameters
+                    .get("days")
+                    .and_then(|v| v.as_i64())
+                    .map(|days| Utc::now() - Duration::days(days));
+                let incidents = self.list(since).await;
+                let mut text = format!("{} incidents", incidents.len());
+                for incident in &incidents {
+                    text.push_str(&format!(
+                        "\n\n{} [{:?}] {} ({})",
+                        incident.opened_at.format("%Y-%m-%d %H:%M"),
+                        incident.severity,
+                        incident.title,
+                        if incident.resolved_at.is_some() {
+                            "resolved"
+                        } else {
+                            "open"
+                        }
+                    ));
+                    for event in &incident.timeline {
+                        text.push_str(&format!(
+                            "\n  {} {}{}",
+                            event.at.format("%H:%M"),
+                            event
+                                .author
+                                .as_ref()
+                                .map(|a| format!("{}: ", a))
+                                .unwrap_or_default(),
+                            event.message
+                        ));
+                    }
+                }
+                return Ok(call_result(text, json!({ "incidents": incidents })));
+            }
+            _ => {
+                return Err(Error::not_found_with_resource(
+                    "Tool not found",
+                    "incident_tool",
+                    name,
+                ))
+            }
+        };
+        Ok(call_result(
+            format!("Incident {} \"{}\" updated", incident.id, incident.title),
+            json!({ "incident": incident }),
+        ))
+    }
+}
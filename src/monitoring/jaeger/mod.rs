@@ -0,0 +1,488 @@
+//! Jaeger trace queries: services, operations, trace search and lookup
+//!
+//! Reads go to the query service's HTTP API (`/api/...`, port 16686 by
+//! default), the one the Jaeger UI uses. Spans come back as `OtelTrace` and
+//! `OtelSpan`, with tags flattened to strings and the status derived from the
+//! `otel.status_code` or `error` tags.
+
+use super::{JaegerConfig, MonitoringModule, OtelSpan, OtelTrace, SpanStatus};
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Traces returned when no limit is given
+const DEFAULT_TRACE_LIMIT: usize = 20;
+
+impl JaegerConfig {
+    /// Jaeger from `JAEGER_QUERY_URL`, if set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            collector_endpoint: String::new(),
+            agent_host: None,
+            agent_port: None,
+            service_name: String::new(),
+            query_url: Some(std::env::var("JAEGER_QUERY_URL").ok()?),
+        })
+    }
+}
+
+/// Filters for `jaeger_find_traces`; only `service` is required
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TraceQuery {
+    pub service: String,
+    #[serde(default)]
+    pub operation: Option<String>,
+    /// Span tags that must all match, e.g. `http.status_code` = `500`
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    /// Go-style durations such as `250ms` or `1.5s`
+    #[serde(default)]
+    pub min_duration: Option<String>,
+    #[serde(default)]
+    pub max_duration: Option<String>,
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Check a duration the way Jaeger parses it: numbers with ns, us, µs, ms, s, m or h
+fn validate_duration(duration: &str, field: &str) -> Result<()> {
+    let invalid = || {
+        Error::validation_with_field(
+            format!(
+                "Invalid duration {}: use a number with ns, us, ms, s, m or h, e.g. 250ms",
+                duration
+            ),
+            field,
+        )
+    };
+    let mut rest = duration.trim();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(invalid)?;
+        if digits == 0 {
+            return Err(invalid());
+        }
+        rest = &rest[digits..];
+        let unit = ["ns", "us", "µs", "ms", "s", "m", "h"]
+            .into_iter()
+            .find(|unit| rest.starts_with(unit))
+            .ok_or_else(invalid)?;
+        rest = &rest[unit.len()..];
+    }
+    Ok(())
+}
+
+/// Tag value as a string: strings as-is, numbers and booleans printed
+fn tag_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Time from Jaeger's microseconds since the epoch
+fn micros(value: &Value) -> DateTime<Utc> {
+    DateTime::from_timestamp_micros(value.as_i64().unwrap_or(0)).unwrap_or_default()
+}
+
+/// Normalize one trace from Jaeger's JSON model
+pub(crate) fn parse_trace(trace: &Value) -> Result<OtelTrace> {
+    let trace_id = trace["traceID"]
+        .as_str()
+        .ok_or_else(|| Error::parsing("Jaeger trace without a traceID"))?
+        .to_string();
+    let services: HashMap<&str, &str> = trace["processes"]
+        .as_object()
+        .map(|processes| {
+            processes
+                .iter()
+                .filter_map(|(id, p)| Some((id.as_str(), p["serviceName"].as_str()?)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut spans: Vec<OtelSpan> = trace["spans"]
+        .as_array()
+        .map(|spans| {
+            spans
+                .iter()
+                .map(|span| {
+                    let tags: HashMap<String, String> = span["tags"]
+                        .as_array()
+                        .map(|tags| {
+                            tags.iter()
+                                .filter_map(|t| {
+                                    Some((t["key"].as_str()?.to_string(), tag_value(&t["value"])))
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let code = match tags.get("otel.status_code") {
+                        Some(code) => code.to_uppercase(),
+                        None if tags.get("error").map(String::as_str) == Some("true") => {
+                            "ERROR".to_string()
+                        }
+                        None => "UNSET".to_string(),
+                    };
+                    let message = tags
+                        .get("otel.status_description")
+                        .or_else(|| tags.get("error.message"))
+                        .cloned();
+                    let start_time = micros(&span["startTime"]);
+                    let duration = span["duration"].as_i64().unwrap_or(0);
+                    OtelSpan {
+                        span_id: span["spanID"].as_str().unwrap_or_default().to_string(),
+                        parent_span_id: span["references"].as_array().and_then(|refs| {
+                            refs.iter()
+                                .find(|r| r["refType"] == "CHILD_OF")
+                                .or_else(|| refs.first())
+                                .and_then(|r| r["spanID"].as_str())
+                                .map(str::to_string)
+                        }),
+                        operation_name: span["operationName"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        service_name: span["processID"]
+                            .as_str()
+                            .and_then(|id| services.get(id))
+                            .map(|s| s.to_string()),
+                        start_time,
+                        end_time: start_time + chrono::Duration::microseconds(duration),
+                        tags,
+                        status: SpanStatus { code, message },
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    spans.sort_by_key(|s| s.start_time);
+    Ok(OtelTrace { trace_id, spans })
+}
+
+impl OtelTrace {
+    /// Span without a parent in the trace, else the earliest
+    pub fn root(&self) -> Option<&OtelSpan> {
+        self.spans
+            .iter()
+            .find(|s| {
+                s.parent_span_id
+                    .as_ref()
+                    .is_none_or(|parent| !self.spans.iter().any(|p| &p.span_id == parent))
+            })
+            .or_else(|| self.spans.first())
+    }
+
+    /// From the first span's start to the last span's end
+    pub fn duration(&self) -> chrono::Duration {
+        let start = self.spans.iter().map(|s| s.start_time).min();
+        let end = self.spans.iter().map(|s| s.end_time).max();
+        match (start, end) {
+            (Some(start), Some(end)) => end - start,
+            _ => chrono::Duration::zero(),
+        }
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.spans.iter().filter(|s| s.is_error()).count()
+    }
+}
+
+/// Client for the Jaeger query API, used by the trace tools and the service map
+#[derive(Debug, Clone)]
+pub struct JaegerClient {
+    url: String,
+    http_client: reqwest::Client,
+}
+
+impl JaegerClient {
+    /// Client for the query service at `url`, e.g. `http://jaeger-query:16686`
+    pub fn new(url: impl Into<String>, http_client: reqwest::Client) -> Self {
+        Self {
+            url: url.into(),
+            http_client,
+        }
+    }
+
+    /// GET a query API path and return the response's `data`
+    async fn get(&self, path: &str, params: &[(&str, String)]) -> Result<Value> {
+        let response = self
+            .http_client
+            .get(format!("{}{}", self.url.trim_end_matches('/'), path))
+            .query(params)
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Failed to reach Jaeger: {}", e)))?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        let body: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+        let message = body["errors"][0]["msg"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| text.trim().to_string());
+        match status.as_u16() {
+            _ if status.is_success() && body["errors"].is_null() => {
+                Ok(body.get("data").cloned().unwrap_or(Value::Null))
+            }
+            400 => Err(Error::validation(format!(
+                "Jaeger rejected the request: {}",
+                message
+            ))),
+            401 | 403 => Err(Error::auth(format!("Jaeger refused access: {}", message))),
+            404 => Err(Error::not_found(message)),
+            code => Err(Error::api_with_status(message, "jaeger", code)),
+        }
+    }
+
+    /// Services that have reported spans, sorted
+    pub async fn list_services(&self) -> Result<Vec<String>> {
+        let data = self.get("/api/services", &[]).await?;
+        let mut services: Vec<String> = serde_json::from_value(data)
+            .map_err(|e| Error::parsing(format!("Invalid Jaeger services: {}", e)))?;
+        services.sort();
+        Ok(services)
+    }
+
+    /// Operations a service has reported, sorted
+    pub async fn list_operations(&self, service: &str) -> Result<Vec<String>> {
+        let path = format!(
+            "/api/services/{}/operations",
+            percent_encoding::utf8_percent_encode(service, percent_encoding::NON_ALPHANUMERIC)
+        );
+        let data = self.get(&path, &[]).await?;
+        let mut operations: Vec<String> = serde_json::from_value(data)
+            .map_err(|e| Error::parsing(format!("Invalid Jaeger operations: {}", e)))?;
+        operations.sort();
+        Ok(operations)
+    }
+
+    /// Traces matching the query, newest first
+    pub async fn find_traces(&self, query: &TraceQuery) -> Result<Vec<OtelTrace>> {
+        if query.service.is_empty() {
+            return Err(Error::validation_with_field(
+                "A service is required; list them with jaeger_list_services",
+                "service",
+            ));
+        }
+        let mut params = vec![
+            ("service", query.service.clone()),
+            (
+                "limit",
+                query.limit.unwrap_or(DEFAULT_TRACE_LIMIT).to_string(),
+            ),
+        ];
+        if let Some(operation) = &query.operation {
+            params.push(("operation", operation.clone()));
+        }
+        if !query.tags.is_empty() {
+            params.push(("tags", serde_json::to_string(&query.tags)?));
+        }
+        if let Some(min) = &query.min_duration {
+            validate_duration(min, "min_duration")?;
+            params.push(("minDuration", min.clone()));
+        }
+        if let Some(max) = &query.max_duration {
+            validate_duration(max, "max_duration")?;
+            params.push(("maxDuration", max.clone()));
+        }
+        if let Some(start) = query.start {
+            params.push(("start", start.timestamp_micros().to_string()));
+        }
+        if let Some(end) = query.end {
+            params.push(("end", end.timestamp_micros().to_string()));
+        }
+        let data = self.get("/api/traces", &params).await?;
+        let mut traces = data
+            .as_array()
+            .map(|traces| traces.iter().map(parse_trace).collect::<Result<Vec<_>>>())
+            .transpose()?
+            .unwrap_or_default();
+        traces.sort_by_key(|t| std::cmp::Reverse(t.spans.first().map(|s| s.start_time)));
+        Ok(traces)
+    }
+
+    /// One trace with all its spans
+    pub async fn get_trace(&self, trace_id: &str) -> Result<OtelTrace> {
+        if trace_id.is_empty() || !trace_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::validation_with_field(
+                format!("Invalid trace ID: {}", trace_id),
+                "trace_id",
+            ));
+        }
+        let not_found = || {
+            Error::not_found_with_resource(
+                format!("Jaeger has no trace {}", trace_id),
+                "trace",
+                trace_id,
+            )
+        };
+        let data = self
+            .get(&format!("/api/traces/{}", trace_id), &[])
+            .await
+            .map_err(|e| match e {
+                Error::NotFound { .. } => not_found(),
+                e => e,
+            })?;
+        data.as_array()
+            .and_then(|traces| traces.first())
+            .map(parse_trace)
+            .ok_or_else(not_found)?
+    }
+}
+
+impl MonitoringModule {
+    /// Client for the configured Jaeger query service
+    fn jaeger_client(&self) -> Result<JaegerClient> {
+        let url = self
+            .config
+            .jaeger
+            .as_ref()
+            .and_then(|j| j.query_url.as_deref())
+            .ok_or_else(|| {
+                Error::config_with_suggestion(
+                    "Jaeger query service not configured",
+                    "Set monitoring.jaeger.query_url in the config file, or JAEGER_QUERY_URL",
+                )
+            })?;
+        Ok(JaegerClient::new(url, self.http_client.clone()))
+    }
+
+    /// Services that have reported spans, sorted
+    pub async fn jaeger_list_services(&self) -> Result<Vec<String>> {
+        self.jaeger_client()?.list_services().await
+    }
+
+    /// Operations a service has reported, sorted
+    pub async fn jaeger_list_operations(&self, service: &str) -> Result<Vec<String>> {
+        self.jaeger_client()?.list_operations(service).await
+    }
+
+    /// Traces matching the query, newest first
+    pub async fn jaeger_find_traces(&self, query: &TraceQuery) -> Result<Vec<OtelTrace>> {
+        self.jaeger_client()?.find_traces(query).await
+    }
+
+    /// One trace with all its spans
+    pub async fn jaeger_get_trace(&self, trace_id: &str) -> Result<OtelTrace> {
+        self.jaeger_client()?.get_trace(trace_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, RawQuery};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+
+    fn trace() -> Value {
+        json!({
+            "traceID": "4bf92f3577b34da6",
+            "spans": [
+                {"traceID": "4bf92f3577b34da6", "spanID": "b", "operationName": "SELECT orders",
+                 "references": [{"refType": "CHILD_OF", "traceID": "4bf92f3577b34da6", "spanID": "a"}],
+                 "startTime": 1760601600100000i64, "duration": 250000, "processID": "p2",
+                 "tags": [{"key": "error", "type": "bool", "value": true}, {"key": "db.rows", "type": "int64", "value": 0}]},
+                {"traceID": "4bf92f3577b34da6", "spanID": "a", "operationName": "GET /orders",
+                 "references": [], "startTime": 1760601600000000i64, "duration": 400000, "processID": "p1",
+                 "tags": [{"key": "http.status_code", "type": "int64", "value": 500}, {"key": "otel.status_code", "type": "string", "value": "error"}]}
+            ],
+            "processes": {"p1": {"serviceName": "frontend", "tags": []}, "p2": {"serviceName": "orders-db", "tags": []}}
+        })
+    }
+
+    #[tokio::test]
+    async fn finds_and_normalizes_traces() {
+        let app = Router::new()
+            .route(
+                "/api/services",
+                get(|| async { Json(json!({"data": ["orders-db", "frontend"], "total": 2})) }),
+            )
+            .route(
+                "/api/traces",
+                get(|RawQuery(query): RawQuery| async move {
+                    let query = query.unwrap_or_default();
+                    assert!(query.contains("service=frontend"), "{}", query);
+                    assert!(query.contains("minDuration=100ms"), "{}", query);
+                    assert!(query.contains("tags=%7B%22http.status_code%22%3A%22500%22%7D"), "{}", query);
+                    Json(json!({"data": [trace()], "total": 1}))
+                }),
+            )
+            .route(
+                "/api/traces/:id",
+                get(|Path(id): Path<String>| async move {
+                    if id == "4bf92f3577b34da6" {
+                        (StatusCode::OK, Json(json!({"data": [trace()]})))
+                    } else {
+                        (
+                            StatusCode::NOT_FOUND,
+                            Json(json!({"data": null, "errors": [{"code": 404, "msg": "trace not found"}]})),
+                        )
+                    }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut monitoring = MonitoringModule::default();
+        monitoring.config.jaeger = Some(JaegerConfig {
+            collector_endpoint: String::new(),
+            agent_host: None,
+            agent_port: None,
+            service_name: String::new(),
+            query_url: Some(url),
+        });
+
+        assert_eq!(
+            monitoring.jaeger_list_services().await.unwrap(),
+            ["frontend", "orders-db"]
+        );
+
+        let query = TraceQuery {
+            service: "frontend".to_string(),
+            tags: BTreeMap::from([("http.status_code".to_string(), "500".to_string())]),
+            min_duration: Some("100ms".to_string()),
+            ..TraceQuery::default()
+        };
+        let traces = monitoring.jaeger_find_traces(&query).await.unwrap();
+        assert_eq!(traces.len(), 1);
+        let trace = &traces[0];
+        let root = trace.root().unwrap();
+        assert_eq!(root.operation_name, "GET /orders");
+        assert_eq!(root.service_name.as_deref(), Some("frontend"));
+        assert_eq!(root.tags["http.status_code"], "500");
+        assert_eq!(trace.spans[1].parent_span_id.as_deref(), Some("a"));
+        assert_eq!(trace.spans[1].duration().num_milliseconds(), 250);
+        assert_eq!(trace.duration().num_milliseconds(), 400);
+        assert_eq!(trace.error_count(), 2);
+
+        let bad = TraceQuery {
+            min_duration: Some("fast".to_string()),
+            ..query
+        };
+        assert!(monitoring.jaeger_find_traces(&bad).await.is_err());
+
+        let trace = monitoring
+            .jaeger_get_trace("4bf92f3577b34da6")
+            .await
+            .unwrap();
+        assert_eq!(trace.spans.len(), 2);
+        assert!(matches!(
+            monitoring.jaeger_get_trace("abc").await,
+            Err(Error::NotFound { .. })
+        ));
+        assert!(monitoring.jaeger_get_trace("../services").await.is_err());
+    }
+}
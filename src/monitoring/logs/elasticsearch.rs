@@ -0,0 +1,168 @@
+//! Elasticsearch log search over ECS-style documents
+//!
+//! Words are matched with `simple_query_string` across all fields, time on
+//! `@timestamp` and severity on `log.level`.
+
+use super::{severity_in_line, LogEntry, LogQuery, LogSearch, LogSeverity};
+use crate::error::{Error, Result};
+use crate::monitoring::ElasticsearchConfig;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Index pattern searched when `ELASTICSEARCH_INDEX` is not set
+const DEFAULT_INDEX_PATTERN: &str = "logs-*";
+
+/// Document fields copied into entry labels when present
+const LABEL_FIELDS: &[&str] = &[
+    "host.name",
+    "service.name",
+    "container.name",
+    "kubernetes.namespace",
+    "kubernetes.pod.name",
+    "log.file.path",
+];
+
+impl ElasticsearchConfig {
+    /// Elasticsearch from `ELASTICSEARCH_URL`, if set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            urls: vec![std::env::var("ELASTICSEARCH_URL").ok()?],
+            username: std::env::var("ELASTICSEARCH_USERNAME").ok(),
+            password: std::env::var("ELASTICSEARCH_PASSWORD").ok(),
+            api_key: std::env::var("ELASTICSEARCH_API_KEY").ok(),
+            cloud_id: None,
+            index_pattern: std::env::var("ELASTICSEARCH_INDEX")
+                .unwrap_or_else(|_| DEFAULT_INDEX_PATTERN.to_string()),
+            kibana_url: std::env::var("KIBANA_URL").ok(),
+            kibana_space: std::env::var("KIBANA_SPACE").ok(),
+        })
+    }
+}
+
+/// A dotted field from a document, whether stored nested or flat
+fn field<'a>(source: &'a Value, path: &str) -> Option<&'a Value> {
+    source.get(path).or_else(|| {
+        path.split('.')
+            .try_fold(source, |value, key| value.get(key))
+    })
+}
+
+/// Elasticsearch log search
+pub struct ElasticsearchLogs {
+    client: Client,
+    config: ElasticsearchConfig,
+}
+
+impl ElasticsearchLogs {
+    pub fn new(config: ElasticsearchConfig) -> Result<Self> {
+        if config.urls.is_empty() {
+            return Err(Error::config("No Elasticsearch URLs configured"));
+        }
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Elasticsearch client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    fn body(query: &LogQuery) -> Value {
+        let must: Vec<Value> = if query.text.trim().is_empty() {
+            Vec::new()
+        } else {
+            vec![json!({"simple_query_string": {
+                "query": query.text.trim(),
+                "default_operator": "and"
+            }})]
+        };
+        let mut filter = vec![json!({"range": {"@timestamp": {
+            "gte": query.start.to_rfc3339(),
+            "lte": query.end.to_rfc3339()
+        }}})];
+        if let Some(severity) = query.severity {
+            filter.push(json!({"terms": {"log.level": severity.names_at_least()}}));
+        }
+        json!({
+            "size": query.limit,
+            "sort": [{"@timestamp": "desc"}],
+            "query": {"bool": {"must": must, "filter": filter}}
+        })
+    }
+}
+
+#[async_trait]
+impl LogSearch for ElasticsearchLogs {
+    fn name(&self) -> &str {
+        "elasticsearch"
+    }
+
+    async fn search(&self, query: &LogQuery) -> Result<Vec<LogEntry>> {
+        let url = format!(
+            "{}/{}/_search",
+            self.config.urls[0].trim_end_matches('/'),
+            self.config.index_pattern
+        );
+        let mut request = self.client.post(&url).json(&Self::body(query));
+        if let Some(api_key) = &self.config.api_key {
+            request = request.header("Authorization", format!("ApiKey {}", api_key));
+        } else if let Some(username) = &self.config.username {
+            request = request.basic_auth(username, self.config.password.as_ref());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Elasticsearch request failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::api_with_status(
+                format!("Elasticsearch search failed: {}", text.trim()),
+                "elasticsearch",
+                status.as_u16(),
+            ));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Invalid Elasticsearch response: {}", e)))?;
+
+        Ok(body["hits"]["hits"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|hit| {
+                let source = &hit["_source"];
+                let timestamp = field(source, "@timestamp")?
+                    .as_str()
+                    .and_then(|t| DateTime::parse_from_rfc3339(t).ok())?
+                    .with_timezone(&Utc);
+                let message = field(source, "message")
+                    .and_then(|m| m.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| source.to_string());
+                let severity = ["log.level", "level", "severity"]
+                    .iter()
+                    .filter_map(|f| field(source, f)?.as_str())
+                    .find_map(LogSeverity::parse)
+                    .or_else(|| severity_in_line(&message));
+                let mut labels: BTreeMap<String, String> = LABEL_FIELDS
+                    .iter()
+                    .filter_map(|f| Some((f.to_string(), field(source, f)?.as_str()?.to_string())))
+                    .collect();
+                if let Some(index) = hit["_index"].as_str() {
+                    labels.insert("index".to_string(), index.to_string());
+                }
+                Some(LogEntry {
+                    source: self.name().to_string(),
+                    timestamp,
+                    severity,
+                    message,
+                    labels,
+                })
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,156 @@
+//! Loki log search through `query_range`
+//!
+//! Each search word becomes a case-insensitive `|~` line filter on the
+//! configured stream selector.
+
+use super::{severity_in_line, LogEntry, LogQuery, LogSearch, LogSeverity};
+use crate::error::{Error, Result};
+use crate::monitoring::LokiConfig;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Selector used when the config names none; Loki needs one non-empty matcher
+const DEFAULT_SELECTOR: &str = r#"{job=~".+"}"#;
+
+/// Stream labels that carry the level
+const LEVEL_LABELS: &[&str] = &["level", "detected_level", "severity", "log_level"];
+
+impl LokiConfig {
+    /// Loki from `LOKI_URL`, if set
+    pub fn from_env() -> Option<Self> {
+        let url = std::env::var("LOKI_URL").ok()?;
+        Some(Self {
+            push_url: format!("{}/loki/api/v1/push", url.trim_end_matches('/')),
+            username: std::env::var("LOKI_USERNAME").ok(),
+            password: std::env::var("LOKI_PASSWORD").ok(),
+            tenant_id: std::env::var("LOKI_TENANT_ID").ok(),
+            selector: None,
+        })
+    }
+
+    /// Server URL, without the push path
+    pub fn base_url(&self) -> &str {
+        let url = self.push_url.trim_end_matches('/');
+        url.strip_suffix("/loki/api/v1/push").unwrap_or(url)
+    }
+}
+
+/// Quote a value as a LogQL string
+fn logql_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// LogQL for a query: the selector followed by one line filter per word
+pub fn logql(selector: &str, query: &LogQuery) -> String {
+    let mut logql = selector.to_string();
+    for word in query.words() {
+        logql.push_str(" |~ ");
+        logql.push_str(&logql_string(&format!("(?i){}", regex::escape(word))));
+    }
+    logql
+}
+
+/// Loki log search
+pub struct LokiLogs {
+    client: Client,
+    config: LokiConfig,
+}
+
+impl LokiLogs {
+    pub fn new(config: LokiConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Loki client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+}
+
+#[async_trait]
+impl LogSearch for LokiLogs {
+    fn name(&self) -> &str {
+        "loki"
+    }
+
+    async fn search(&self, query: &LogQuery) -> Result<Vec<LogEntry>> {
+        let selector = self.config.selector.as_deref().unwrap_or(DEFAULT_SELECTOR);
+        let nanos = |t: DateTime<Utc>| {
+            t.timestamp_nanos_opt()
+                .unwrap_or_else(|| t.timestamp() * 1_000_000_000)
+                .to_string()
+        };
+        let mut request = self
+            .client
+            .get(format!(
+                "{}/loki/api/v1/query_range",
+                self.config.base_url()
+            ))
+            .query(&[
+                ("query", logql(selector, query)),
+                ("start", nanos(query.start)),
+                ("end", nanos(query.end)),
+                ("limit", query.fetch_limit().to_string()),
+                ("direction", "backward".to_string()),
+            ]);
+        if let Some(username) = &self.config.username {
+            request = request.basic_auth(username, self.config.password.as_ref());
+        }
+        if let Some(tenant) = &self.config.tenant_id {
+            request = request.header("X-Scope-OrgID", tenant);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Loki request failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::api_with_status(
+                format!("Loki query failed: {}", text.trim()),
+                "loki",
+                status.as_u16(),
+            ));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Invalid Loki response: {}", e)))?;
+
+        let mut entries = Vec::new();
+        for stream in body["data"]["result"].as_array().into_iter().flatten() {
+            let labels: BTreeMap<String, String> = stream["stream"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                .collect();
+            let stream_level = LEVEL_LABELS
+                .iter()
+                .find_map(|l| labels.get(*l).and_then(|v| LogSeverity::parse(v)));
+            for value in stream["values"].as_array().into_iter().flatten() {
+                let (Some(ts), Some(line)) = (value[0].as_str(), value[1].as_str()) else {
+                    continue;
+                };
+                let Ok(ts) = ts.parse::<i64>() else {
+                    continue;
+                };
+                entries.push(LogEntry {
+                    source: self.name().to_string(),
+                    timestamp: DateTime::from_timestamp_nanos(ts),
+                    severity: stream_level.or_else(|| severity_in_line(line)),
+                    message: line.to_string(),
+                    labels: labels.clone(),
+                });
+            }
+        }
+        // Streams come back one after another, so interleave them by time
+        entries.retain(|e| query.admits(e));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+        entries.truncate(query.limit);
+        Ok(entries)
+    }
+}
@@ -0,0 +1,437 @@
+//! Log search across Elasticsearch, Loki and Splunk
+//!
+//! Every backend answers the same `LogQuery`: words that must all appear
+//! (case-insensitively), a time range and a minimum severity. `search_all`
+//! sends the query to each configured backend at once and merges the
+//! entries newest first, each tagged with the backend it came from. A
+//! backend that fails is reported next to the results of the others.
+//!
+//! Severity comes from the backend's level field or label where there is
+//! one, and otherwise from `level=`/`"level":` or an upper-case level word
+//! in the line. Entries whose level cannot be told are left out when a
+//! minimum severity is asked for.
+
+pub mod elasticsearch;
+pub mod loki;
+pub mod splunk;
+
+pub use elasticsearch::ElasticsearchLogs;
+pub use loki::LokiLogs;
+pub use splunk::SplunkLogs;
+
+use super::MonitoringConfig;
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Most entries a single search returns
+pub const MAX_LOG_LIMIT: usize = 1000;
+
+/// How many more lines to read when a backend filters severity client-side
+const SEVERITY_OVERFETCH: usize = 5;
+
+/// Log level, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSeverity {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl LogSeverity {
+    const ALL: [LogSeverity; 5] = [
+        LogSeverity::Debug,
+        LogSeverity::Info,
+        LogSeverity::Warning,
+        LogSeverity::Error,
+        LogSeverity::Critical,
+    ];
+
+    /// Read the level names loggers commonly use, in any case
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.trim().to_lowercase().as_str() {
+            "trace" | "debug" | "dbg" | "verbose" => Some(LogSeverity::Debug),
+            "info" | "information" | "informational" | "notice" => Some(LogSeverity::Info),
+            "warn" | "warning" => Some(LogSeverity::Warning),
+            "error" | "err" => Some(LogSeverity::Error),
+            "critical" | "crit" | "fatal" | "panic" | "alert" | "emerg" | "emergency" => {
+                Some(LogSeverity::Critical)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogSeverity::Debug => "debug",
+            LogSeverity::Info => "info",
+            LogSeverity::Warning => "warning",
+            LogSeverity::Error => "error",
+            LogSeverity::Critical => "critical",
+        }
+    }
+
+    /// Level names at or above this severity, for backends that filter on a field
+    pub fn names_at_least(&self) -> Vec<String> {
+        let names: &[&str] = &[
+            "trace", "debug", "info", "notice", "warn", "warning", "error", "err", "critical",
+            "crit", "fatal", "panic", "alert", "emerg",
+        ];
+        names
+            .iter()
+            .filter(|name| LogSeverity::parse(name).is_some_and(|s| s >= *self))
+            .flat_map(|name| [name.to_string(), name.to_uppercase()])
+            .collect()
+    }
+}
+
+impl std::str::FromStr for LogSeverity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        LogSeverity::parse(s).ok_or_else(|| {
+            Error::validation_with_field(
+                format!(
+                    "Unknown severity {}: use one of {}",
+                    s,
+                    LogSeverity::ALL.map(|l| l.as_str()).join(", ")
+                ),
+                "severity",
+            )
+        })
+    }
+}
+
+/// Level written in a log line as `level=error`, `"level":"error"` or `ERROR`
+pub fn severity_in_line(line: &str) -> Option<LogSeverity> {
+    let lower = line.to_lowercase();
+    for key in ["level", "severity", "lvl"] {
+        for pattern in [
+            format!("{}=", key),
+            format!("\"{}\":\"", key),
+            format!("\"{}\": \"", key),
+        ] {
+            if let Some(at) = lower.find(&pattern) {
+                let value: String = lower[at + pattern.len()..]
+                    .trim_start_matches('"')
+                    .chars()
+                    .take_while(|c| c.is_ascii_alphabetic())
+                    .collect();
+                if let Some(severity) = LogSeverity::parse(&value) {
+                    return Some(severity);
+                }
+            }
+        }
+    }
+    // Plain text loggers put the level near the start, in upper case
+    line.split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .take(12)
+        .filter(|word| word.len() >= 3 && word.chars().all(|c| c.is_ascii_uppercase()))
+        .find_map(LogSeverity::parse)
+}
+
+/// A search sent to every backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogQuery {
+    /// Words that must all appear; empty matches every entry
+    #[serde(default)]
+    pub text: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Only entries at or above this level
+    #[serde(default)]
+    pub severity: Option<LogSeverity>,
+    pub limit: usize,
+}
+
+impl LogQuery {
+    /// Search words, empty ones dropped
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.text.split_whitespace()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.start >= self.end {
+            return Err(Error::validation_with_field(
+                "Start must be before end",
+                "start",
+            ));
+        }
+        if self.limit == 0 || self.limit > MAX_LOG_LIMIT {
+            return Err(Error::validation_with_field(
+                format!("Limit must be between 1 and {}", MAX_LOG_LIMIT),
+                "limit",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Lines to fetch from backends that can only filter severity after reading
+    pub fn fetch_limit(&self) -> usize {
+        match self.severity {
+            Some(_) => self.limit * SEVERITY_OVERFETCH,
+            None => self.limit,
+        }
+    }
+
+    fn admits(&self, entry: &LogEntry) -> bool {
+        match self.severity {
+            Some(min) => entry.severity.is_some_and(|s| s >= min),
+            None => true,
+        }
+    }
+}
+
+/// One log line from a backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Backend the entry came from, e.g. `loki`
+    pub source: String,
+    pub timestamp: DateTime<Utc>,
+    pub severity: Option<LogSeverity>,
+    pub message: String,
+    /// Stream labels, index, host and similar context
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// A log store that can be searched
+#[async_trait]
+pub trait LogSearch: Send + Sync {
+    /// Backend name, as attributed in results
+    fn name(&self) -> &str;
+
+    /// Entries matching `query`, newest first, at most `query.limit`
+    async fn search(&self, query: &LogQuery) -> Result<Vec<LogEntry>>;
+}
+
+/// Merged results of a search across backends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSearchResult {
+    pub entries: Vec<LogEntry>,
+    /// Backends that answered, with the number of entries each matched
+    pub sources: BTreeMap<String, usize>,
+    /// Backends that failed, with the error
+    pub errors: BTreeMap<String, String>,
+}
+
+/// Backends configured in `config`
+pub fn backends(config: &MonitoringConfig) -> Result<Vec<Arc<dyn LogSearch>>> {
+    let mut backends: Vec<Arc<dyn LogSearch>> = Vec::new();
+    if let Some(es) = &config.elasticsearch {
+        backends.push(Arc::new(ElasticsearchLogs::new(es.clone())?));
+    }
+    if let Some(loki) = &config.loki {
+        backends.push(Arc::new(LokiLogs::new(loki.clone())?));
+    }
+    if let Some(splunk) = config.splunk.as_ref().filter(|s| s.search_url.is_some()) {
+        backends.push(Arc::new(SplunkLogs::new(splunk.clone())?));
+    }
+    Ok(backends)
+}
+
+/// Run a query on every backend at once and merge the entries newest first
+///
+/// Fails only when every backend does.
+pub async fn search_all(
+    backends: &[Arc<dyn LogSearch>],
+    query: &LogQuery,
+) -> Result<LogSearchResult> {
+    query.validate()?;
+    if backends.is_empty() {
+        return Err(Error::config_with_suggestion(
+            "No log backends are configured",
+            "Set monitoring.elasticsearch, monitoring.loki or monitoring.splunk in the config file",
+        ));
+    }
+
+    let results =
+        futures::future::join_all(backends.iter().map(|backend| backend.search(query))).await;
+    let mut entries = Vec::new();
+    let mut sources = BTreeMap::new();
+    let mut errors = BTreeMap::new();
+    for (backend, result) in backends.iter().zip(results) {
+        match result {
+            Ok(found) => {
+                let before = entries.len();
+                entries.extend(found.into_iter().filter(|e| query.admits(e)));
+                sources.insert(backend.name().to_string(), entries.len() - before);
+            }
+            Err(e) => {
+                tracing::warn!(backend = backend.name(), error = %e, "Log search failed");
+                errors.insert(backend.name().to_string(), e.to_string());
+            }
+        }
+    }
+    if sources.is_empty() {
+        let detail = errors
+            .iter()
+            .map(|(name, error)| format!("{}: {}", name, error))
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(Error::service(format!(
+            "Every log backend failed: {}",
+            detail
+        )));
+    }
+
+    entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+    entries.truncate(query.limit);
+    Ok(LogSearchResult {
+        entries,
+        sources,
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitoring::{ElasticsearchConfig, LokiConfig, SplunkConfig};
+    use axum::extract::Query;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    #[test]
+    fn reads_levels_from_lines() {
+        assert_eq!(
+            severity_in_line(r#"{"level":"warn","msg":"disk"}"#),
+            Some(LogSeverity::Warning)
+        );
+        assert_eq!(
+            severity_in_line("ts=1 level=error msg=\"boom\""),
+            Some(LogSeverity::Error)
+        );
+        assert_eq!(
+            severity_in_line("2024-05-01 12:00:00 [FATAL] out of memory"),
+            Some(LogSeverity::Critical)
+        );
+        // Lower-case words in the message are not levels
+        assert_eq!(severity_in_line("retrying after error"), None);
+        assert!(LogSeverity::Error
+            .names_at_least()
+            .contains(&"FATAL".to_string()));
+    }
+
+    #[tokio::test]
+    async fn merges_backends_newest_first_and_reports_failures() {
+        let app = Router::new()
+            .route(
+                "/loki/api/v1/query_range",
+                get(|Query(params): Query<HashMap<String, String>>| async move {
+                    assert_eq!(
+                        params["query"],
+                        r#"{job=~".+"} |~ "(?i)timeout" |~ "(?i)db\\.internal""#
+                    );
+                    Json(json!({"status": "success", "data": {"resultType": "streams", "result": [{
+                        "stream": {"job": "api", "level": "error"},
+                        "values": [
+                            ["1714564860000000000", "timeout talking to db.internal"],
+                            ["1714564740000000000", "timeout talking to db.internal"]
+                        ]
+                    }, {
+                        "stream": {"job": "worker"},
+                        "values": [["1714564800000000000", "level=info timeout to db.internal recovered"]]
+                    }]}}))
+                }),
+            )
+            .route(
+                "/logs-app/_search",
+                post(|Json(body): Json<Value>| async move {
+                    assert_eq!(
+                        body["query"]["bool"]["must"][0]["simple_query_string"]["query"],
+                        "timeout db.internal"
+                    );
+                    assert!(body["query"]["bool"]["filter"][1]["terms"]["log.level"]
+                        .as_array()
+                        .unwrap()
+                        .contains(&json!("error")));
+                    Json(json!({"hits": {"hits": [{
+                        "_index": "logs-app",
+                        "_id": "1",
+                        "_source": {
+                            "@timestamp": "2024-05-01T12:00:30Z",
+                            "message": "timeout on db.internal",
+                            "log": {"level": "ERROR"},
+                            "host": {"name": "web-1"}
+                        }
+                    }]}}))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let backends: Vec<Arc<dyn LogSearch>> = vec![
+            Arc::new(
+                LokiLogs::new(LokiConfig {
+                    push_url: format!("{}/loki/api/v1/push", url),
+                    username: None,
+                    password: None,
+                    tenant_id: None,
+                    selector: None,
+                })
+                .unwrap(),
+            ),
+            Arc::new(
+                ElasticsearchLogs::new(ElasticsearchConfig {
+                    urls: vec![url.clone()],
+                    username: None,
+                    password: None,
+                    api_key: None,
+                    cloud_id: None,
+                    index_pattern: "logs-app".to_string(),
+                    kibana_url: None,
+                    kibana_space: None,
+                })
+                .unwrap(),
+            ),
+            Arc::new(
+                SplunkLogs::new(SplunkConfig {
+                    hec_url: String::new(),
+                    hec_token: String::new(),
+                    index: String::new(),
+                    source_type: String::new(),
+                    ssl_verify: true,
+                    search_url: Some("http://127.0.0.1:1".to_string()),
+                    search_token: Some("token".to_string()),
+                })
+                .unwrap(),
+            ),
+        ];
+        let query = LogQuery {
+            text: "timeout db.internal".to_string(),
+            start: "2024-05-01T11:00:00Z".parse().unwrap(),
+            end: "2024-05-01T13:00:00Z".parse().unwrap(),
+            severity: Some(LogSeverity::Error),
+            limit: 2,
+        };
+        let result = search_all(&backends, &query).await.unwrap();
+
+        // The info line is dropped and the oldest error falls past the limit
+        let seen: Vec<(&str, String)> = result
+            .entries
+            .iter()
+            .map(|e| (e.source.as_str(), e.timestamp.to_rfc3339()))
+            .collect();
+        assert_eq!(
+            seen,
+            vec![
+                ("loki", "2024-05-01T12:01:00+00:00".to_string()),
+                ("elasticsearch", "2024-05-01T12:00:30+00:00".to_string()),
+            ]
+        );
+        assert_eq!(result.entries[1].labels["host.name"], "web-1");
+        assert_eq!(result.sources["loki"], 2);
+        assert!(result.errors.contains_key("splunk"));
+    }
+}
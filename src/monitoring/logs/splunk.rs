@@ -0,0 +1,154 @@
+//! Splunk log search through the management API's export endpoint
+//!
+//! Searches run as one streamed `search/jobs/export` request, which answers
+//! with one JSON object per result line, so no job has to be polled.
+
+use super::{severity_in_line, LogEntry, LogQuery, LogSearch, LogSeverity};
+use crate::error::{Error, Result};
+use crate::monitoring::SplunkConfig;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Result fields copied into entry labels when present
+const LABEL_FIELDS: &[&str] = &["host", "source", "sourcetype", "index"];
+
+/// Result fields that carry the level
+const LEVEL_FIELDS: &[&str] = &["log_level", "level", "severity"];
+
+impl SplunkConfig {
+    /// Splunk search from `SPLUNK_URL` and `SPLUNK_TOKEN`, if both are set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            hec_url: String::new(),
+            hec_token: String::new(),
+            index: std::env::var("SPLUNK_INDEX").unwrap_or_default(),
+            source_type: String::new(),
+            ssl_verify: std::env::var("SPLUNK_SSL_VERIFY").map_or(true, |v| v != "false"),
+            search_url: Some(std::env::var("SPLUNK_URL").ok()?),
+            search_token: Some(std::env::var("SPLUNK_TOKEN").ok()?),
+        })
+    }
+}
+
+/// SPL for a query: each word quoted, newest first and capped at the fetch limit
+pub fn spl(index: &str, query: &LogQuery) -> String {
+    let index = if index.is_empty() { "*" } else { index };
+    let mut spl = format!("search index={}", index);
+    for word in query.words() {
+        spl.push_str(&format!(
+            " \"{}\"",
+            word.replace('\\', "\\\\").replace('"', "\\\"")
+        ));
+    }
+    spl.push_str(&format!(" | head {}", query.fetch_limit()));
+    spl
+}
+
+/// Splunk log search
+pub struct SplunkLogs {
+    client: Client,
+    config: SplunkConfig,
+}
+
+impl SplunkLogs {
+    pub fn new(config: SplunkConfig) -> Result<Self> {
+        if config.search_url.is_none() {
+            return Err(Error::config_with_suggestion(
+                "Splunk search URL is not configured",
+                "Set monitoring.splunk.search_url to the management API, e.g. https://splunk:8089",
+            ));
+        }
+        // The management port ships with a self-signed certificate
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .danger_accept_invalid_certs(!config.ssl_verify)
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Splunk client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+}
+
+#[async_trait]
+impl LogSearch for SplunkLogs {
+    fn name(&self) -> &str {
+        "splunk"
+    }
+
+    async fn search(&self, query: &LogQuery) -> Result<Vec<LogEntry>> {
+        let base = self.config.search_url.as_deref().unwrap_or_default();
+        let mut request = self
+            .client
+            .post(format!(
+                "{}/services/search/jobs/export",
+                base.trim_end_matches('/')
+            ))
+            .form(&[
+                ("search", spl(&self.config.index, query)),
+                ("earliest_time", query.start.timestamp().to_string()),
+                ("latest_time", query.end.timestamp().to_string()),
+                ("output_mode", "json".to_string()),
+            ]);
+        if let Some(token) = &self.config.search_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Splunk request failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::api_with_status(
+                format!("Splunk search failed: {}", text.trim()),
+                "splunk",
+                status.as_u16(),
+            ));
+        }
+        let text = response
+            .text()
+            .await
+            .map_err(|e| Error::network(format!("Failed to read Splunk response: {}", e)))?;
+
+        let mut entries = Vec::new();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            let row: Value = serde_json::from_str(line)
+                .map_err(|e| Error::parsing(format!("Invalid Splunk response: {}", e)))?;
+            let result = &row["result"];
+            if result.is_null() {
+                // Messages and previews carry no event
+                continue;
+            }
+            let Some(timestamp) = result["_time"]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            else {
+                continue;
+            };
+            let message = result["_raw"].as_str().unwrap_or_default().to_string();
+            let severity = LEVEL_FIELDS
+                .iter()
+                .filter_map(|f| result[*f].as_str())
+                .find_map(LogSeverity::parse)
+                .or_else(|| severity_in_line(&message));
+            let labels: BTreeMap<String, String> = LABEL_FIELDS
+                .iter()
+                .filter_map(|f| Some((f.to_string(), result[*f].as_str()?.to_string())))
+                .collect();
+            let entry = LogEntry {
+                source: self.name().to_string(),
+                timestamp: timestamp.with_timezone(&Utc),
+                severity,
+                message,
+                labels,
+            };
+            if query.admits(&entry) && entries.len() < query.limit {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+}
@@ -9,15 +9,36 @@
 use crate::error::{Error, Result};
 use crate::lifecycle::LifecycleManager;
 use crate::security::SecurityModule;
+use base64::Engine;
 use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE},
+    Client,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
-use reqwest::{Client, header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE}};
-use base64::Engine;
 use std::time::Duration;
 
+pub mod alert_store;
+pub mod alerting;
+pub mod detections;
+pub mod incidents;
+pub mod jaeger;
+pub mod logs;
+pub mod prometheus;
+pub mod uptime_kuma;
+
+pub use alert_store::AlertStore;
+pub use alerting::{AlertRule, ContactPoint, GrafanaAlerting, Silence};
+pub use detections::{DetectionRule, DetectionRules, RuleRun, RuleSeverity, RuleSpec};
+pub use incidents::{Incident, IncidentStore};
+pub use jaeger::{JaegerClient, TraceQuery};
+pub use logs::{LogEntry, LogQuery, LogSearch, LogSeverity};
+pub use prometheus::{MetricMetadata, PrometheusAlert, RuleGroup, ScrapeTarget};
+pub use uptime_kuma::{UptimeKuma, UptimeKumaConfig};
+
 /// Enhanced monitoring configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitoringConfig {
@@ -55,6 +76,7 @@ pub struct PrometheusConfig {
     /// Bearer token
     pub bearer_token: Option<String>,
     /// Skip TLS verification
+    #[serde(default)]
     pub insecure_skip_verify: bool,
     /// Remote write configuration
     pub remote_write: Option<RemoteWriteConfig>,
@@ -97,6 +119,20 @@ pub struct GrafanaConfig {
     pub alloy: Option<GrafanaAlloyConfig>,
 }
 
+impl GrafanaConfig {
+    /// Grafana from `GRAFANA_URL` and `GRAFANA_API_KEY`, if the URL is set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            url: std::env::var("GRAFANA_URL").ok()?,
+            api_key: std::env::var("GRAFANA_API_KEY").ok(),
+            username: None,
+            password: None,
+            org_id: None,
+            alloy: None,
+        })
+    }
+}
+
 /// Grafana Alloy configuration (new in 2024)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GrafanaAlloyConfig {
@@ -138,21 +174,38 @@ pub struct ElasticsearchConfig {
     pub cloud_id: Option<String>,
     /// Index pattern
     pub index_pattern: String,
+    /// Kibana URL, for Elastic Security detection rules
+    #[serde(default)]
+    pub kibana_url: Option<String>,
+    /// Kibana space holding the rules; the default space when unset
+    #[serde(default)]
+    pub kibana_space: Option<String>,
 }
 
 /// Splunk configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SplunkConfig {
     /// HEC URL
+    #[serde(default)]
     pub hec_url: String,
     /// HEC token
+    #[serde(default)]
     pub hec_token: String,
-    /// Index
+    /// Index; searches cover every index when empty
+    #[serde(default)]
     pub index: String,
     /// Source type
+    #[serde(default)]
     pub source_type: String,
     /// SSL verify
+    #[serde(default)]
     pub ssl_verify: bool,
+    /// Management API URL for searches, e.g. `https://splunk:8089`
+    #[serde(default)]
+    pub search_url: Option<String>,
+    /// Authentication token for the management API
+    #[serde(default)]
+    pub search_token: Option<String>,
 }
 
 /// Datadog configuration
@@ -187,30 +240,56 @@ pub struct SentinelConfig {
     /// Workspace ID
     pub workspace_id: String,
     /// Workspace key
+    #[serde(default)]
     pub workspace_key: String,
     /// Log type
+    #[serde(default)]
     pub log_type: String,
     /// Resource ID
+    #[serde(default)]
     pub resource_id: Option<String>,
+    /// Entra ID tenant of the service principal managing analytics rules
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// Service principal client ID
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Service principal client secret
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    /// Subscription of the workspace
+    #[serde(default)]
+    pub subscription_id: Option<String>,
+    /// Resource group of the workspace
+    #[serde(default)]
+    pub resource_group: Option<String>,
+    /// Workspace resource name, as opposed to its ID
+    #[serde(default)]
+    pub workspace_name: Option<String>,
 }
 
 /// Jaeger configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JaegerConfig {
     /// Collector endpoint
+    #[serde(default)]
     pub collector_endpoint: String,
     /// Agent host
     pub agent_host: Option<String>,
     /// Agent port
     pub agent_port: Option<u16>,
     /// Service name
+    #[serde(default)]
     pub service_name: String,
+    /// Query service URL for reading traces, e.g. `http://jaeger:16686`
+    #[serde(default)]
+    pub query_url: Option<String>,
 }
 
 /// Loki configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LokiConfig {
-    /// Push URL
+    /// Push URL; queries go to the same server
     pub push_url: String,
     /// Username
     pub username: Option<String>,
@@ -218,6 +297,9 @@ pub struct LokiConfig {
     pub password: Option<String>,
     /// Tenant ID
     pub tenant_id: Option<String>,
+    /// Stream selector searches start from, `{job=~".+"}` by default
+    #[serde(default)]
+    pub selector: Option<String>,
 }
 
 /// Monitoring module with direct API integrations
@@ -265,17 +347,29 @@ impl MonitoringModule {
 
         // Check Prometheus API
         if let Some(prom_config) = &self.config.prometheus {
-            available = available || self.prometheus_health_check(prom_config).await.unwrap_or(false);
+            available = available
+                || self
+                    .prometheus_health_check(prom_config)
+                    .await
+                    .unwrap_or(false);
         }
 
         // Check Grafana API
         if let Some(grafana_config) = &self.config.grafana {
-            available = available || self.grafana_health_check(grafana_config).await.unwrap_or(false);
+            available = available
+                || self
+                    .grafana_health_check(grafana_config)
+                    .await
+                    .unwrap_or(false);
         }
 
         // Check Elasticsearch API
         if let Some(es_config) = &self.config.elasticsearch {
-            available = available || self.elasticsearch_health_check(es_config).await.unwrap_or(false);
+            available = available
+                || self
+                    .elasticsearch_health_check(es_config)
+                    .await
+                    .unwrap_or(false);
         }
 
         // Check Datadog API
@@ -301,26 +395,39 @@ impl MonitoringModule {
             .ok_or_else(|| Error::config("Prometheus not configured"))?;
 
         let mut headers = HeaderMap::new();
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/x-www-form-urlencoded"));
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
 
         // Add authentication
         if let Some(token) = &prom_config.bearer_token {
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", token))
-                .map_err(|e| Error::config(format!("Invalid bearer token: {}", e)))?);
-        } else if let (Some(username), Some(password)) = (&prom_config.username, &prom_config.password) {
-            let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Basic {}", credentials))
-                .map_err(|e| Error::config(format!("Invalid credentials: {}", e)))?);
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", token))
+                    .map_err(|e| Error::config(format!("Invalid bearer token: {}", e)))?,
+            );
+        } else if let (Some(username), Some(password)) =
+            (&prom_config.username, &prom_config.password)
+        {
+            let credentials = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", username, password));
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Basic {}", credentials))
+                    .map_err(|e| Error::config(format!("Invalid credentials: {}", e)))?,
+            );
         }
 
         let url = format!("{}/api/v1/query", prom_config.url);
         let mut params = vec![("query", query.to_string())];
-        
+
         if let Some(t) = time {
             params.push(("time", t.timestamp().to_string()));
         }
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .post(&url)
             .headers(headers)
             .form(&params)
@@ -330,22 +437,41 @@ impl MonitoringModule {
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("Prometheus query failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "Prometheus query failed: {}",
+                error_text
+            )));
         }
 
-        let response_data: serde_json::Value = response.json().await
+        let response_data: serde_json::Value = response
+            .json()
+            .await
             .map_err(|e| Error::service(format!("Failed to parse Prometheus response: {}", e)))?;
 
         // Parse Prometheus response format
-        let values = if let Some(data) = response_data.get("data").and_then(|d| d.get("result")).and_then(|r| r.as_array()) {
-            data.iter().filter_map(|item| {
-                let metric = item.get("metric")?.as_object()?
-                    .iter()
-                    .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
-                    .collect();
-                let value = item.get("value")?.as_array()?.get(1)?.as_str()?.parse().ok()?;
-                Some(PrometheusValue { metric, value })
-            }).collect()
+        let values = if let Some(data) = response_data
+            .get("data")
+            .and_then(|d| d.get("result"))
+            .and_then(|r| r.as_array())
+        {
+            data.iter()
+                .filter_map(|item| {
+                    let metric = item
+                        .get("metric")?
+                        .as_object()?
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
+                        .collect();
+                    let value = item
+                        .get("value")?
+                        .as_array()?
+                        .get(1)?
+                        .as_str()?
+                        .parse()
+                        .ok()?;
+                    Some(PrometheusValue { metric, value })
+                })
+                .collect()
         } else {
             vec![]
         };
@@ -372,16 +498,28 @@ impl MonitoringModule {
             .ok_or_else(|| Error::config("Prometheus not configured"))?;
 
         let mut headers = HeaderMap::new();
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/x-www-form-urlencoded"));
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
 
         // Add authentication
         if let Some(token) = &prom_config.bearer_token {
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", token))
-                .map_err(|e| Error::config(format!("Invalid bearer token: {}", e)))?);
-        } else if let (Some(username), Some(password)) = (&prom_config.username, &prom_config.password) {
-            let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Basic {}", credentials))
-                .map_err(|e| Error::config(format!("Invalid credentials: {}", e)))?);
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", token))
+                    .map_err(|e| Error::config(format!("Invalid bearer token: {}", e)))?,
+            );
+        } else if let (Some(username), Some(password)) =
+            (&prom_config.username, &prom_config.password)
+        {
+            let credentials = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", username, password));
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Basic {}", credentials))
+                    .map_err(|e| Error::config(format!("Invalid credentials: {}", e)))?,
+            );
         }
 
         let url = format!("{}/api/v1/query_range", prom_config.url);
@@ -392,7 +530,8 @@ impl MonitoringModule {
             ("step", step.to_string()),
         ];
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .post(&url)
             .headers(headers)
             .form(&params)
@@ -402,32 +541,50 @@ impl MonitoringModule {
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("Prometheus range query failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "Prometheus range query failed: {}",
+                error_text
+            )));
         }
 
-        let response_data: serde_json::Value = response.json().await
+        let response_data: serde_json::Value = response
+            .json()
+            .await
             .map_err(|e| Error::service(format!("Failed to parse Prometheus response: {}", e)))?;
 
         // Parse Prometheus range response format
-        let values = if let Some(data) = response_data.get("data").and_then(|d| d.get("result")).and_then(|r| r.as_array()) {
-            data.iter().filter_map(|item| {
-                let metric = item.get("metric")?.as_object()?
-                    .iter()
-                    .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
-                    .collect();
-                
-                let values = item.get("values")?.as_array()?
-                    .iter()
-                    .filter_map(|val| {
-                        let arr = val.as_array()?;
-                        let timestamp = arr.first()?.as_f64()? as i64;
-                        let value = arr.get(1)?.as_str()?.parse().ok()?;
-                        Some((DateTime::from_timestamp(timestamp, 0).unwrap_or_else(Utc::now), value))
-                    })
-                    .collect();
-                
-                Some(PrometheusRangeValue { metric, values })
-            }).collect()
+        let values = if let Some(data) = response_data
+            .get("data")
+            .and_then(|d| d.get("result"))
+            .and_then(|r| r.as_array())
+        {
+            data.iter()
+                .filter_map(|item| {
+                    let metric = item
+                        .get("metric")?
+                        .as_object()?
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
+                        .collect();
+
+                    let values = item
+                        .get("values")?
+                        .as_array()?
+                        .iter()
+                        .filter_map(|val| {
+                            let arr = val.as_array()?;
+                            let timestamp = arr.first()?.as_f64()? as i64;
+                            let value = arr.get(1)?.as_str()?.parse().ok()?;
+                            Some((
+                                DateTime::from_timestamp(timestamp, 0).unwrap_or_else(Utc::now),
+                                value,
+                            ))
+                        })
+                        .collect();
+
+                    Some(PrometheusRangeValue { metric, values })
+                })
+                .collect()
         } else {
             vec![]
         };
@@ -452,20 +609,30 @@ impl MonitoringModule {
             .ok_or_else(|| Error::config("Grafana not configured"))?;
 
         let mut headers = HeaderMap::new();
-        
+
         // Add authentication
         if let Some(api_key) = &grafana_config.api_key {
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", api_key))
-                .map_err(|e| Error::config(format!("Invalid API key: {}", e)))?);
-        } else if let (Some(username), Some(password)) = (&grafana_config.username, &grafana_config.password) {
-            let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Basic {}", credentials))
-                .map_err(|e| Error::config(format!("Invalid credentials: {}", e)))?);
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", api_key))
+                    .map_err(|e| Error::config(format!("Invalid API key: {}", e)))?,
+            );
+        } else if let (Some(username), Some(password)) =
+            (&grafana_config.username, &grafana_config.password)
+        {
+            let credentials = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", username, password));
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Basic {}", credentials))
+                    .map_err(|e| Error::config(format!("Invalid credentials: {}", e)))?,
+            );
         }
 
         let url = format!("{}/api/search?type=dash-db", grafana_config.url);
-        
-        let response = self.http_client
+
+        let response = self
+            .http_client
             .get(&url)
             .headers(headers)
             .send()
@@ -474,25 +641,35 @@ impl MonitoringModule {
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("Grafana dashboard listing failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "Grafana dashboard listing failed: {}",
+                error_text
+            )));
         }
 
-        let dashboards_data: serde_json::Value = response.json().await
+        let dashboards_data: serde_json::Value = response
+            .json()
+            .await
             .map_err(|e| Error::service(format!("Failed to parse Grafana response: {}", e)))?;
 
         let dashboards = if let Some(arr) = dashboards_data.as_array() {
-            arr.iter().filter_map(|item| {
-                Some(GrafanaDashboard {
-                    id: item.get("id")?.as_i64().map(|i| i.to_string()),
-                    uid: item.get("uid")?.as_str().map(|s| s.to_string()),
-                    title: item.get("title")?.as_str()?.to_string(),
-                    tags: item.get("tags")?.as_array()?.iter()
-                        .filter_map(|t| t.as_str().map(|s| s.to_string()))
-                        .collect(),
-                    panels: vec![], // Would need separate API call to get full dashboard
-                    templating: vec![],
+            arr.iter()
+                .filter_map(|item| {
+                    Some(GrafanaDashboard {
+                        id: item.get("id")?.as_i64().map(|i| i.to_string()),
+                        uid: item.get("uid")?.as_str().map(|s| s.to_string()),
+                        title: item.get("title")?.as_str()?.to_string(),
+                        tags: item
+                            .get("tags")?
+                            .as_array()?
+                            .iter()
+                            .filter_map(|t| t.as_str().map(|s| s.to_string()))
+                            .collect(),
+                        panels: vec![], // Would need separate API call to get full dashboard
+                        templating: vec![],
+                    })
                 })
-            }).collect()
+                .collect()
         } else {
             vec![]
         };
@@ -510,19 +687,28 @@ impl MonitoringModule {
 
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-        
+
         // Add authentication
         if let Some(api_key) = &grafana_config.api_key {
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", api_key))
-                .map_err(|e| Error::config(format!("Invalid API key: {}", e)))?);
-        } else if let (Some(username), Some(password)) = (&grafana_config.username, &grafana_config.password) {
-            let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Basic {}", credentials))
-                .map_err(|e| Error::config(format!("Invalid credentials: {}", e)))?);
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", api_key))
+                    .map_err(|e| Error::config(format!("Invalid API key: {}", e)))?,
+            );
+        } else if let (Some(username), Some(password)) =
+            (&grafana_config.username, &grafana_config.password)
+        {
+            let credentials = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", username, password));
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Basic {}", credentials))
+                    .map_err(|e| Error::config(format!("Invalid credentials: {}", e)))?,
+            );
         }
 
         let url = format!("{}/api/dashboards/db", grafana_config.url);
-        
+
         let dashboard_json = serde_json::json!({
             "dashboard": {
                 "id": dashboard.id,
@@ -535,7 +721,8 @@ impl MonitoringModule {
             "overwrite": true
         });
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .post(&url)
             .headers(headers)
             .json(&dashboard_json)
@@ -545,16 +732,27 @@ impl MonitoringModule {
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("Grafana dashboard creation failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "Grafana dashboard creation failed: {}",
+                error_text
+            )));
         }
 
-        let response_data: serde_json::Value = response.json().await
+        let response_data: serde_json::Value = response
+            .json()
+            .await
             .map_err(|e| Error::service(format!("Failed to parse Grafana response: {}", e)))?;
 
-        let dashboard_id = response_data.get("id")
+        let dashboard_id = response_data
+            .get("id")
             .and_then(|id| id.as_i64())
             .map(|id| id.to_string())
-            .or_else(|| response_data.get("uid").and_then(|uid| uid.as_str()).map(|s| s.to_string()))
+            .or_else(|| {
+                response_data
+                    .get("uid")
+                    .and_then(|uid| uid.as_str())
+                    .map(|s| s.to_string())
+            })
             .unwrap_or_else(|| "unknown".to_string());
 
         Ok(dashboard_id)
@@ -571,17 +769,22 @@ impl MonitoringModule {
             .ok_or_else(|| Error::config("OpenTelemetry not configured"))?;
 
         let mut headers = HeaderMap::new();
-        
+
         // Set content type based on protocol
         if otel_config.protocol == "grpc" {
-            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/x-protobuf"));
+            headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/x-protobuf"),
+            );
         } else {
             headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
         }
 
         // Add custom headers
         for (key, value) in &otel_config.headers {
-            if let (Ok(header_name), Ok(header_value)) = (key.parse::<HeaderName>(), value.parse::<HeaderValue>()) {
+            if let (Ok(header_name), Ok(header_value)) =
+                (key.parse::<HeaderName>(), value.parse::<HeaderValue>())
+            {
                 headers.insert(header_name, header_value);
             }
         }
@@ -625,18 +828,24 @@ impl MonitoringModule {
             }).collect::<Vec<_>>()
         });
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .post(&endpoint)
             .headers(headers)
             .json(&otlp_data)
             .timeout(Duration::from_secs(otel_config.timeout))
             .send()
             .await
-            .map_err(|e| Error::service(format!("Failed to send traces to OpenTelemetry: {}", e)))?;
+            .map_err(|e| {
+                Error::service(format!("Failed to send traces to OpenTelemetry: {}", e))
+            })?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("OpenTelemetry trace sending failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "OpenTelemetry trace sending failed: {}",
+                error_text
+            )));
         }
 
         Ok(())
@@ -651,17 +860,22 @@ impl MonitoringModule {
             .ok_or_else(|| Error::config("OpenTelemetry not configured"))?;
 
         let mut headers = HeaderMap::new();
-        
+
         // Set content type based on protocol
         if otel_config.protocol == "grpc" {
-            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/x-protobuf"));
+            headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/x-protobuf"),
+            );
         } else {
             headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
         }
 
         // Add custom headers
         for (key, value) in &otel_config.headers {
-            if let (Ok(header_name), Ok(header_value)) = (key.parse::<HeaderName>(), value.parse::<HeaderValue>()) {
+            if let (Ok(header_name), Ok(header_value)) =
+                (key.parse::<HeaderName>(), value.parse::<HeaderValue>())
+            {
                 headers.insert(header_name, header_value);
             }
         }
@@ -734,31 +948,37 @@ impl MonitoringModule {
                             "description": metric.description,
                             "unit": metric.unit
                         });
-                        
+
                         if let serde_json::Value::Object(ref mut obj) = result {
                             if let serde_json::Value::Object(data_obj) = metric_data {
                                 obj.extend(data_obj);
                             }
                         }
-                        
+
                         result
                     }).collect::<Vec<_>>()
                 }]
             }]
         });
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .post(&endpoint)
             .headers(headers)
             .json(&otlp_data)
             .timeout(Duration::from_secs(otel_config.timeout))
             .send()
             .await
-            .map_err(|e| Error::service(format!("Failed to send metrics to OpenTelemetry: {}", e)))?;
+            .map_err(|e| {
+                Error::service(format!("Failed to send metrics to OpenTelemetry: {}", e))
+            })?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("OpenTelemetry metrics sending failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "OpenTelemetry metrics sending failed: {}",
+                error_text
+            )));
         }
 
         Ok(())
@@ -776,8 +996,11 @@ impl MonitoringModule {
 
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-        headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Splunk {}", splunk_config.hec_token))
-            .map_err(|e| Error::config(format!("Invalid HEC token: {}", e)))?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Splunk {}", splunk_config.hec_token))
+                .map_err(|e| Error::config(format!("Invalid HEC token: {}", e)))?,
+        );
 
         let hec_event = serde_json::json!({
             "time": event.timestamp.timestamp(),
@@ -793,7 +1016,8 @@ impl MonitoringModule {
             }
         });
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .post(&splunk_config.hec_url)
             .headers(headers)
             .json(&hec_event)
@@ -803,7 +1027,10 @@ impl MonitoringModule {
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("Splunk event sending failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "Splunk event sending failed: {}",
+                error_text
+            )));
         }
 
         Ok(())
@@ -825,16 +1052,27 @@ impl MonitoringModule {
 
         // Add authentication
         if let Some(api_key) = &es_config.api_key {
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("ApiKey {}", api_key))
-                .map_err(|e| Error::config(format!("Invalid API key: {}", e)))?);
-        } else if let (Some(username), Some(password)) = (&es_config.username, &es_config.password) {
-            let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Basic {}", credentials))
-                .map_err(|e| Error::config(format!("Invalid credentials: {}", e)))?);
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("ApiKey {}", api_key))
+                    .map_err(|e| Error::config(format!("Invalid API key: {}", e)))?,
+            );
+        } else if let (Some(username), Some(password)) = (&es_config.username, &es_config.password)
+        {
+            let credentials = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", username, password));
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Basic {}", credentials))
+                    .map_err(|e| Error::config(format!("Invalid credentials: {}", e)))?,
+            );
         }
 
         // Use first URL from the list
-        let base_url = es_config.urls.first().ok_or_else(|| Error::config("No Elasticsearch URLs configured"))?;
+        let base_url = es_config
+            .urls
+            .first()
+            .ok_or_else(|| Error::config("No Elasticsearch URLs configured"))?;
         let url = format!("{}/{}/_search", base_url, query.index);
 
         let mut search_body = serde_json::json!({
@@ -851,7 +1089,8 @@ impl MonitoringModule {
             search_body["sort"] = serde_json::json!(sort);
         }
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .post(&url)
             .headers(headers)
             .json(&search_body)
@@ -861,46 +1100,62 @@ impl MonitoringModule {
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("Elasticsearch search failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "Elasticsearch search failed: {}",
+                error_text
+            )));
         }
 
-        let search_result: serde_json::Value = response.json().await
-            .map_err(|e| Error::service(format!("Failed to parse Elasticsearch response: {}", e)))?;
+        let search_result: serde_json::Value = response.json().await.map_err(|e| {
+            Error::service(format!("Failed to parse Elasticsearch response: {}", e))
+        })?;
 
         // Parse Elasticsearch response
         let result = ElasticsearchResult {
-            took: search_result.get("took").and_then(|v| v.as_i64()).unwrap_or(0),
-            timed_out: search_result.get("timed_out").and_then(|v| v.as_bool()).unwrap_or(false),
+            took: search_result
+                .get("took")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0),
+            timed_out: search_result
+                .get("timed_out")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
             hits: ElasticsearchHits {
                 total: ElasticsearchTotal {
-                    value: search_result.get("hits")
+                    value: search_result
+                        .get("hits")
                         .and_then(|h| h.get("total"))
                         .and_then(|t| t.get("value"))
                         .and_then(|v| v.as_i64())
                         .unwrap_or(0),
-                    relation: search_result.get("hits")
+                    relation: search_result
+                        .get("hits")
                         .and_then(|h| h.get("total"))
                         .and_then(|t| t.get("relation"))
                         .and_then(|r| r.as_str())
                         .unwrap_or("eq")
                         .to_string(),
                 },
-                max_score: search_result.get("hits")
+                max_score: search_result
+                    .get("hits")
                     .and_then(|h| h.get("max_score"))
                     .and_then(|s| s.as_f64())
                     .unwrap_or(0.0),
-                hits: search_result.get("hits")
+                hits: search_result
+                    .get("hits")
                     .and_then(|h| h.get("hits"))
                     .and_then(|h| h.as_array())
                     .map(|hits| {
-                        hits.iter().filter_map(|hit| {
-                            Some(ElasticsearchHit {
-                                _index: hit.get("_index")?.as_str()?.to_string(),
-                                _id: hit.get("_id")?.as_str()?.to_string(),
-                                _score: hit.get("_score")?.as_f64().unwrap_or(0.0),
-                                _source: hit.get("_source")?.clone(),
+                        hits.iter()
+                            .filter_map(|hit| {
+                                Some(ElasticsearchHit {
+                                    _index: hit.get("_index")?.as_str()?.to_string(),
+                                    _id: hit.get("_id")?.as_str()?.to_string(),
+                                    _score: hit.get("_score")?.as_f64().unwrap_or(0.0),
+                                    _source: hit.get("_source")?.clone(),
+                                })
                             })
-                        }).collect()
+                            .collect()
                     })
                     .unwrap_or_default(),
             },
@@ -919,12 +1174,20 @@ impl MonitoringModule {
 
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-        headers.insert("DD-API-KEY", HeaderValue::from_str(&dd_config.api_key)
-            .map_err(|e| Error::config(format!("Invalid API key: {}", e)))?);
-        headers.insert("DD-APPLICATION-KEY", HeaderValue::from_str(&dd_config.app_key)
-            .map_err(|e| Error::config(format!("Invalid application key: {}", e)))?);
+        headers.insert(
+            "DD-API-KEY",
+            HeaderValue::from_str(&dd_config.api_key)
+                .map_err(|e| Error::config(format!("Invalid API key: {}", e)))?,
+        );
+        headers.insert(
+            "DD-APPLICATION-KEY",
+            HeaderValue::from_str(&dd_config.app_key)
+                .map_err(|e| Error::config(format!("Invalid application key: {}", e)))?,
+        );
 
-        let api_url = dd_config.api_url.clone()
+        let api_url = dd_config
+            .api_url
+            .clone()
             .unwrap_or_else(|| format!("https://api.{}", dd_config.site));
         let url = format!("{}/api/v1/series", api_url);
 
@@ -940,7 +1203,8 @@ impl MonitoringModule {
             }).collect::<Vec<_>>()
         });
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .post(&url)
             .headers(headers)
             .json(&series_data)
@@ -950,7 +1214,10 @@ impl MonitoringModule {
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("Datadog metrics sending failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "Datadog metrics sending failed: {}",
+                error_text
+            )));
         }
 
         Ok(())
@@ -971,8 +1238,11 @@ impl MonitoringModule {
         let token = self.crowdstrike_get_token(cs_config).await?;
 
         let mut headers = HeaderMap::new();
-        headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", token))
-            .map_err(|e| Error::config(format!("Invalid token: {}", e)))?);
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|e| Error::config(format!("Invalid token: {}", e)))?,
+        );
 
         // Build query parameters
         let mut query_params = Vec::new();
@@ -985,21 +1255,32 @@ impl MonitoringModule {
             format!("?{}", query_params.join("&"))
         };
 
-        let url = format!("{}/detects/queries/detects/v1{}", cs_config.base_url, query_string);
+        let url = format!(
+            "{}/detects/queries/detects/v1{}",
+            cs_config.base_url, query_string
+        );
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .get(&url)
             .headers(headers.clone())
             .send()
             .await
-            .map_err(|e| Error::service(format!("Failed to query Crowdstrike detections: {}", e)))?;
+            .map_err(|e| {
+                Error::service(format!("Failed to query Crowdstrike detections: {}", e))
+            })?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("Crowdstrike detection query failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "Crowdstrike detection query failed: {}",
+                error_text
+            )));
         }
 
-        let detection_ids: serde_json::Value = response.json().await
+        let detection_ids: serde_json::Value = response
+            .json()
+            .await
             .map_err(|e| Error::service(format!("Failed to parse Crowdstrike response: {}", e)))?;
 
         // Extract detection IDs and fetch detailed information
@@ -1009,51 +1290,85 @@ impl MonitoringModule {
             }
 
             // Get detailed detection information
-            let ids_str: Vec<String> = ids.iter()
+            let ids_str: Vec<String> = ids
+                .iter()
                 .filter_map(|id| id.as_str().map(|s| s.to_string()))
                 .collect();
-            
+
             let details_url = format!("{}/detects/entities/summaries/GET/v1", cs_config.base_url);
             let body = serde_json::json!({
                 "ids": ids_str
             });
 
-            let details_response = self.http_client
+            let details_response = self
+                .http_client
                 .post(&details_url)
                 .headers(headers)
                 .json(&body)
                 .send()
                 .await
-                .map_err(|e| Error::service(format!("Failed to get Crowdstrike detection details: {}", e)))?;
+                .map_err(|e| {
+                    Error::service(format!(
+                        "Failed to get Crowdstrike detection details: {}",
+                        e
+                    ))
+                })?;
 
             if !details_response.status().is_success() {
                 let error_text = details_response.text().await.unwrap_or_default();
-                return Err(Error::service(format!("Crowdstrike detection details failed: {}", error_text)));
+                return Err(Error::service(format!(
+                    "Crowdstrike detection details failed: {}",
+                    error_text
+                )));
             }
 
-            let details_data: serde_json::Value = details_response.json().await
-                .map_err(|e| Error::service(format!("Failed to parse Crowdstrike details response: {}", e)))?;
-
-            let detections = if let Some(resources) = details_data.get("resources").and_then(|r| r.as_array()) {
-                resources.iter().filter_map(|detection| {
-                    Some(CrowdstrikeDetection {
-                        detection_id: detection.get("detection_id")?.as_str()?.to_string(),
-                        device_id: detection.get("device").and_then(|d| d.get("device_id"))?.as_str()?.to_string(),
-                        behavior_id: detection.get("behaviors").and_then(|b| b.as_array())?
-                            .first().and_then(|first| first.get("behavior_id"))?.as_str()?.to_string(),
-                        severity: detection.get("max_severity")?.as_i64()? as i32,
-                        confidence: detection.get("max_confidence")?.as_i64()? as i32,
-                        pattern_id: detection.get("behaviors").and_then(|b| b.as_array())?
-                            .first().and_then(|first| first.get("pattern_disposition"))?.as_str()?.to_string(),
-                        timestamp: detection.get("first_behavior")?.as_str()
-                            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-                            .map(|dt| dt.with_timezone(&chrono::Utc))
-                            .unwrap_or_else(Utc::now),
-                    })
-                }).collect()
-            } else {
-                vec![]
-            };
+            let details_data: serde_json::Value = details_response.json().await.map_err(|e| {
+                Error::service(format!(
+                    "Failed to parse Crowdstrike details response: {}",
+                    e
+                ))
+            })?;
+
+            let detections =
+                if let Some(resources) = details_data.get("resources").and_then(|r| r.as_array()) {
+                    resources
+                        .iter()
+                        .filter_map(|detection| {
+                            Some(CrowdstrikeDetection {
+                                detection_id: detection.get("detection_id")?.as_str()?.to_string(),
+                                device_id: detection
+                                    .get("device")
+                                    .and_then(|d| d.get("device_id"))?
+                                    .as_str()?
+                                    .to_string(),
+                                behavior_id: detection
+                                    .get("behaviors")
+                                    .and_then(|b| b.as_array())?
+                                    .first()
+                                    .and_then(|first| first.get("behavior_id"))?
+                                    .as_str()?
+                                    .to_string(),
+                                severity: detection.get("max_severity")?.as_i64()? as i32,
+                                confidence: detection.get("max_confidence")?.as_i64()? as i32,
+                                pattern_id: detection
+                                    .get("behaviors")
+                                    .and_then(|b| b.as_array())?
+                                    .first()
+                                    .and_then(|first| first.get("pattern_disposition"))?
+                                    .as_str()?
+                                    .to_string(),
+                                timestamp: detection
+                                    .get("first_behavior")?
+                                    .as_str()
+                                    .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                                    .map(|dt| dt.with_timezone(&chrono::Utc))
+                                    .unwrap_or_else(Utc::now),
+                            })
+                        })
+                        .collect()
+                } else {
+                    vec![]
+                };
 
             Ok(detections)
         } else {
@@ -1070,10 +1385,12 @@ impl MonitoringModule {
             .ok_or_else(|| Error::config("Azure Sentinel not configured"))?;
 
         // Generate date and authorization signature
-        let date = chrono::Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        let date = chrono::Utc::now()
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string();
         let json_data = serde_json::to_string(&logs)
             .map_err(|e| Error::internal(format!("Failed to serialize logs: {}", e)))?;
-        
+
         let signature = self.build_azure_signature(
             &date,
             json_data.len(),
@@ -1085,16 +1402,28 @@ impl MonitoringModule {
 
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-        headers.insert("Log-Type", HeaderValue::from_str(&sentinel_config.log_type)
-            .map_err(|e| Error::config(format!("Invalid log type: {}", e)))?);
-        headers.insert("x-ms-date", HeaderValue::from_str(&date)
-            .map_err(|e| Error::config(format!("Invalid date: {}", e)))?);
-        headers.insert(AUTHORIZATION, HeaderValue::from_str(&signature)
-            .map_err(|e| Error::config(format!("Invalid signature: {}", e)))?);
-        
+        headers.insert(
+            "Log-Type",
+            HeaderValue::from_str(&sentinel_config.log_type)
+                .map_err(|e| Error::config(format!("Invalid log type: {}", e)))?,
+        );
+        headers.insert(
+            "x-ms-date",
+            HeaderValue::from_str(&date)
+                .map_err(|e| Error::config(format!("Invalid date: {}", e)))?,
+        );
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&signature)
+                .map_err(|e| Error::config(format!("Invalid signature: {}", e)))?,
+        );
+
         if let Some(resource_id) = &sentinel_config.resource_id {
-            headers.insert("x-ms-AzureResourceId", HeaderValue::from_str(resource_id)
-                .map_err(|e| Error::config(format!("Invalid resource ID: {}", e)))?);
+            headers.insert(
+                "x-ms-AzureResourceId",
+                HeaderValue::from_str(resource_id)
+                    .map_err(|e| Error::config(format!("Invalid resource ID: {}", e)))?,
+            );
         }
 
         let url = format!(
@@ -1102,7 +1431,8 @@ impl MonitoringModule {
             sentinel_config.workspace_id
         );
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .post(&url)
             .headers(headers)
             .body(json_data)
@@ -1112,7 +1442,10 @@ impl MonitoringModule {
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("Azure Sentinel log sending failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "Azure Sentinel log sending failed: {}",
+                error_text
+            )));
         }
 
         Ok(())
@@ -1128,6 +1461,7 @@ impl MonitoringModule {
             sources,
             created_at: Utc::now(),
             status: AlertStatus::Active,
+            resolved_at: None,
             assignee: None,
             tags: HashMap::new(),
         };
@@ -1170,7 +1504,10 @@ impl MonitoringModule {
     /// Get Crowdstrike OAuth token
     async fn crowdstrike_get_token(&self, config: &CrowdstrikeConfig) -> Result<String> {
         let mut headers = HeaderMap::new();
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/x-www-form-urlencoded"));
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
 
         let body = format!(
             "client_id={}&client_secret={}&grant_type=client_credentials",
@@ -1179,7 +1516,8 @@ impl MonitoringModule {
 
         let url = format!("{}/oauth2/token", config.base_url);
 
-        let response = self.http_client
+        let response = self
+            .http_client
             .post(&url)
             .headers(headers)
             .body(body)
@@ -1189,13 +1527,19 @@ impl MonitoringModule {
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(Error::service(format!("Crowdstrike token request failed: {}", error_text)));
+            return Err(Error::service(format!(
+                "Crowdstrike token request failed: {}",
+                error_text
+            )));
         }
 
-        let token_data: serde_json::Value = response.json().await
+        let token_data: serde_json::Value = response
+            .json()
+            .await
             .map_err(|e| Error::service(format!("Failed to parse token response: {}", e)))?;
 
-        token_data.get("access_token")
+        token_data
+            .get("access_token")
             .and_then(|t| t.as_str())
             .map(|s| s.to_string())
             .ok_or_else(|| Error::service("No access token in response"))
@@ -1213,43 +1557,49 @@ impl MonitoringModule {
     ) -> Result<String> {
         use hmac::{Hmac, Mac};
         use sha2::Sha256;
-        
+
         type HmacSha256 = Hmac<Sha256>;
-        
+
         let string_to_hash = format!(
             "{}\n{}\n{}\nx-ms-date:{}\n{}",
             method, content_length, content_type, date, resource
         );
-        
-        let decoded_key = base64::engine::general_purpose::STANDARD.decode(workspace_key)
+
+        let decoded_key = base64::engine::general_purpose::STANDARD
+            .decode(workspace_key)
             .map_err(|e| Error::config(format!("Invalid workspace key: {}", e)))?;
-        
+
         let mut mac = HmacSha256::new_from_slice(&decoded_key)
             .map_err(|e| Error::internal(format!("Failed to create HMAC: {}", e)))?;
         mac.update(string_to_hash.as_bytes());
-        
+
         let result = mac.finalize();
         let encoded_hash = base64::engine::general_purpose::STANDARD.encode(result.into_bytes());
-        
+
         Ok(format!("SharedKey {}:{}", "workspace_id", encoded_hash))
     }
 
     /// Health check for Prometheus
     async fn prometheus_health_check(&self, config: &PrometheusConfig) -> Result<bool> {
         let url = format!("{}/api/v1/query", config.url);
-        
+
         let mut headers = HeaderMap::new();
         if let Some(token) = &config.bearer_token {
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", token))
-                .map_err(|_| Error::config("Invalid bearer token"))?);
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", token))
+                    .map_err(|_| Error::config("Invalid bearer token"))?,
+            );
         }
-        
-        match self.http_client
+
+        match self
+            .http_client
             .get(&url)
             .headers(headers)
             .timeout(Duration::from_secs(5))
             .send()
-            .await {
+            .await
+        {
             Ok(response) => Ok(response.status().is_success()),
             Err(_) => Ok(false),
         }
@@ -1258,19 +1608,24 @@ impl MonitoringModule {
     /// Health check for Grafana
     async fn grafana_health_check(&self, config: &GrafanaConfig) -> Result<bool> {
         let url = format!("{}/api/health", config.url);
-        
+
         let mut headers = HeaderMap::new();
         if let Some(api_key) = &config.api_key {
-            headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", api_key))
-                .map_err(|_| Error::config("Invalid API key"))?);
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", api_key))
+                    .map_err(|_| Error::config("Invalid API key"))?,
+            );
         }
-        
-        match self.http_client
+
+        match self
+            .http_client
             .get(&url)
             .headers(headers)
             .timeout(Duration::from_secs(5))
             .send()
-            .await {
+            .await
+        {
             Ok(response) => Ok(response.status().is_success()),
             Err(_) => Ok(false),
         }
@@ -1280,19 +1635,24 @@ impl MonitoringModule {
     async fn elasticsearch_health_check(&self, config: &ElasticsearchConfig) -> Result<bool> {
         if let Some(base_url) = config.urls.first() {
             let url = format!("{}/_cluster/health", base_url);
-            
+
             let mut headers = HeaderMap::new();
             if let Some(api_key) = &config.api_key {
-                headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("ApiKey {}", api_key))
-                    .map_err(|_| Error::config("Invalid API key"))?);
+                headers.insert(
+                    AUTHORIZATION,
+                    HeaderValue::from_str(&format!("ApiKey {}", api_key))
+                        .map_err(|_| Error::config("Invalid API key"))?,
+                );
             }
-            
-            match self.http_client
+
+            match self
+                .http_client
                 .get(&url)
                 .headers(headers)
                 .timeout(Duration::from_secs(5))
                 .send()
-                .await {
+                .await
+            {
                 Ok(response) => Ok(response.status().is_success()),
                 Err(_) => Ok(false),
             }
@@ -1303,20 +1663,26 @@ impl MonitoringModule {
 
     /// Health check for Datadog
     async fn datadog_health_check(&self, config: &DatadogConfig) -> Result<bool> {
-        let api_url = config.api_url.clone()
+        let api_url = config
+            .api_url
+            .clone()
             .unwrap_or_else(|| format!("https://api.{}", config.site));
         let url = format!("{}/api/v1/validate", api_url);
-        
+
         let mut headers = HeaderMap::new();
-        headers.insert("DD-API-KEY", HeaderValue::from_str(&config.api_key)
-            .map_err(|_| Error::config("Invalid API key"))?);
-        
-        match self.http_client
+        headers.insert(
+            "DD-API-KEY",
+            HeaderValue::from_str(&config.api_key).map_err(|_| Error::config("Invalid API key"))?,
+        );
+
+        match self
+            .http_client
             .get(&url)
             .headers(headers)
             .timeout(Duration::from_secs(5))
             .send()
-            .await {
+            .await
+        {
             Ok(response) => Ok(response.status().is_success()),
             Err(_) => Ok(false),
         }
@@ -1367,7 +1733,7 @@ pub enum AlertSeverity {
 }
 
 /// Alert status
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum AlertStatus {
     /// Alert is active
     Active,
@@ -1473,11 +1839,29 @@ pub struct GrafanaPanel {
     /// Title
     pub title: String,
     /// Type
+    #[serde(rename = "type", alias = "panel_type")]
     pub panel_type: String,
     /// Targets
     pub targets: Vec<Value>,
     /// Grid position
+    #[serde(rename = "gridPos", alias = "grid_pos")]
     pub grid_pos: GridPos,
+    /// Description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Datasource reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub datasource: Option<Value>,
+    /// Field defaults such as unit and thresholds
+    #[serde(
+        rename = "fieldConfig",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub field_config: Option<Value>,
+    /// Visualization options
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Value>,
 }
 
 /// Grid position
@@ -1501,9 +1885,16 @@ pub struct GrafanaVariable {
     /// Label
     pub label: String,
     /// Type
+    #[serde(rename = "type", alias = "variable_type")]
     pub variable_type: String,
     /// Query
     pub query: String,
+    /// Datasource reference for query variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub datasource: Option<Value>,
+    /// When query variables reload (1 = on dashboard load)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh: Option<u8>,
 }
 
 /// OpenTelemetry trace
@@ -1524,6 +1915,9 @@ pub struct OtelSpan {
     pub parent_span_id: Option<String>,
     /// Operation name
     pub operation_name: String,
+    /// Service that recorded the span
+    #[serde(default)]
+    pub service_name: Option<String>,
     /// Start time
     pub start_time: DateTime<Utc>,
     /// End time
@@ -1534,6 +1928,16 @@ pub struct OtelSpan {
     pub status: SpanStatus,
 }
 
+impl OtelSpan {
+    pub fn duration(&self) -> chrono::Duration {
+        self.end_time - self.start_time
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.status.code == "ERROR"
+    }
+}
+
 /// Span status
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpanStatus {
@@ -1733,6 +2137,16 @@ pub enum AlertSource {
         severity: i32,
         confidence: i32,
     },
+    Canary {
+        canary_id: String,
+        kind: String,
+        memo: String,
+    },
+    UptimeKuma {
+        monitor_id: u64,
+        monitor_name: String,
+        message: String,
+    },
 }
 
 /// Unified alert
@@ -1752,6 +2166,9 @@ pub struct UnifiedAlert {
     pub created_at: DateTime<Utc>,
     /// Status
     pub status: AlertStatus,
+    /// When the alert was resolved
+    #[serde(default)]
+    pub resolved_at: Option<DateTime<Utc>>,
     /// Assignee
     pub assignee: Option<String>,
     /// Tags
@@ -0,0 +1,450 @@
+//! Prometheus discovery APIs: series, labels, metadata, targets, rules and alerts
+//!
+//! These answer "what is there to query" before any PromQL is written. They
+//! are plain GETs against `/api/v1`, returning the response's `data`.
+
+use super::{MonitoringModule, PrometheusConfig};
+use crate::error::{Error, Result};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+impl PrometheusConfig {
+    /// Prometheus from `PROMETHEUS_URL` and `PROMETHEUS_BEARER_TOKEN`, if the URL is set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            url: std::env::var("PROMETHEUS_URL").ok()?,
+            username: None,
+            password: None,
+            bearer_token: std::env::var("PROMETHEUS_BEARER_TOKEN").ok(),
+            insecure_skip_verify: false,
+            remote_write: None,
+        })
+    }
+}
+
+/// Labels of one series, `__name__` included
+pub type Series = BTreeMap<String, String>;
+
+/// Type, help and unit of a metric family
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricMetadata {
+    #[serde(default)]
+    pub metric: String,
+    /// `counter`, `gauge`, `histogram`, `summary` or `unknown`
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub help: String,
+    #[serde(default)]
+    pub unit: String,
+}
+
+/// A scrape target and how its last scrape went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrapeTarget {
+    pub scrape_pool: String,
+    pub scrape_url: String,
+    /// `up`, `down` or `unknown`
+    pub health: String,
+    /// Labels the target's series get, such as `job` and `instance`
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub last_error: String,
+    #[serde(default)]
+    pub last_scrape: Option<DateTime<Utc>>,
+    /// Seconds the last scrape took
+    #[serde(default)]
+    pub last_scrape_duration: f64,
+}
+
+/// Which rules to list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleKind {
+    Alert,
+    Record,
+}
+
+impl RuleKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            RuleKind::Alert => "alert",
+            RuleKind::Record => "record",
+        }
+    }
+}
+
+/// An alert raised by an alerting rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrometheusAlert {
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+    /// `pending` or `firing`
+    pub state: String,
+    #[serde(default)]
+    pub active_at: Option<DateTime<Utc>>,
+    /// Value of the rule's expression when the alert was last evaluated
+    #[serde(default)]
+    pub value: String,
+}
+
+impl PrometheusAlert {
+    pub fn name(&self) -> &str {
+        self.labels
+            .get("alertname")
+            .map_or("(unnamed)", String::as_str)
+    }
+}
+
+/// An alerting or recording rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrometheusRule {
+    pub name: String,
+    pub query: String,
+    /// `alerting` or `recording`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// `ok`, `err` or `unknown`
+    #[serde(default)]
+    pub health: String,
+    #[serde(default, rename = "lastError", skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Alerting rules: `inactive`, `pending` or `firing`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Alerting rules: seconds the expression must hold before firing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+    #[serde(default)]
+    pub alerts: Vec<PrometheusAlert>,
+}
+
+/// Rules evaluated together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleGroup {
+    pub name: String,
+    pub file: String,
+    pub rules: Vec<PrometheusRule>,
+}
+
+fn parse<T: serde::de::DeserializeOwned>(data: Value, what: &str) -> Result<T> {
+    serde_json::from_value(data)
+        .map_err(|e| Error::parsing(format!("Invalid Prometheus {}: {}", what, e)))
+}
+
+/// `match[]` selectors, plus an optional time range
+fn selector_params(
+    matchers: &[String],
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Vec<(&'static str, String)> {
+    let mut params: Vec<(&str, String)> = matchers.iter().map(|m| ("match[]", m.clone())).collect();
+    if let Some(start) = start {
+        params.push(("start", start.timestamp().to_string()));
+    }
+    if let Some(end) = end {
+        params.push(("end", end.timestamp().to_string()));
+    }
+    params
+}
+
+impl MonitoringModule {
+    /// GET a Prometheus API path and return the response's `data`
+    async fn prometheus_get(&self, path: &str, params: &[(&str, String)]) -> Result<Value> {
+        let config = self.config.prometheus.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Prometheus not configured",
+                "Set monitoring.prometheus in the config file, or PROMETHEUS_URL",
+            )
+        })?;
+        let mut request = self
+            .http_client
+            .get(format!("{}{}", config.url.trim_end_matches('/'), path))
+            .query(params);
+        if let Some(token) = &config.bearer_token {
+            request = request.bearer_auth(token);
+        } else if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            request = request.header(
+                reqwest::header::AUTHORIZATION,
+                format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD
+                        .encode(format!("{}:{}", username, password))
+                ),
+            );
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Failed to reach Prometheus: {}", e)))?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        let body: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+        if !status.is_success() || body["status"] == "error" {
+            let message = body["error"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| text.trim().to_string());
+            return Err(match status.as_u16() {
+                400 | 422 => {
+                    Error::validation(format!("Prometheus rejected the request: {}", message))
+                }
+                401 | 403 => Error::auth(format!("Prometheus refused access: {}", message)),
+                code => Error::api_with_status(message, "prometheus", code),
+            });
+        }
+        Ok(body.get("data").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Series matching any of the selectors, e.g. `up{job="node"}`
+    pub async fn prometheus_series(
+        &self,
+        matchers: &[String],
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Series>> {
+        if matchers.is_empty() {
+            return Err(Error::validation_with_field(
+                "At least one series selector is required, e.g. {job=\"node\"}",
+                "match",
+            ));
+        }
+        let data = self
+            .prometheus_get("/api/v1/series", &selector_params(matchers, start, end))
+            .await?;
+        parse(data, "series")
+    }
+
+    /// Label names, optionally only those on series matching the selectors
+    pub async fn prometheus_labels(
+        &self,
+        matchers: &[String],
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<String>> {
+        let data = self
+            .prometheus_get("/api/v1/labels", &selector_params(matchers, start, end))
+            .await?;
+        parse(data, "label names")
+    }
+
+    /// Values of a label; `__name__` lists metric names
+    pub async fn prometheus_label_values(
+        &self,
+        label: &str,
+        matchers: &[String],
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<String>> {
+        if label.is_empty() || !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(Error::validation_with_field(
+                format!("Invalid label name: {}", label),
+                "label",
+            ));
+        }
+        let data = self
+            .prometheus_get(
+                &format!("/api/v1/label/{}/values", label),
+                &selector_params(matchers, start, end),
+            )
+            .await?;
+        parse(data, "label values")
+    }
+
+    /// Metadata of one metric family, or of all of them, sorted by name
+    pub async fn prometheus_metadata(&self, metric: Option<&str>) -> Result<Vec<MetricMetadata>> {
+        let params: Vec<(&str, String)> = metric
+            .map(|m| ("metric", m.to_string()))
+            .into_iter()
+            .collect();
+        let data = self.prometheus_get("/api/v1/metadata", &params).await?;
+        let families: BTreeMap<String, Vec<MetricMetadata>> = parse(data, "metadata")?;
+        // Targets can disagree about a family; the first entry is representative
+        Ok(families
+            .into_iter()
+            .filter_map(|(name, entries)| {
+                let mut entry = entries.into_iter().next()?;
+                entry.metric = name;
+                Some(entry)
+            })
+            .collect())
+    }
+
+    /// Active scrape targets
+    pub async fn prometheus_targets(&self) -> Result<Vec<ScrapeTarget>> {
+        let data = self
+            .prometheus_get("/api/v1/targets", &[("state", "active".to_string())])
+            .await?;
+        parse(data["activeTargets"].clone(), "targets")
+    }
+
+    /// Rule groups, optionally only alerting or recording rules
+    pub async fn prometheus_rules(&self, kind: Option<RuleKind>) -> Result<Vec<RuleGroup>> {
+        let params: Vec<(&str, String)> = kind
+            .map(|k| ("type", k.as_str().to_string()))
+            .into_iter()
+            .collect();
+        let data = self.prometheus_get("/api/v1/rules", &params).await?;
+        parse(data["groups"].clone(), "rules")
+    }
+
+    /// Pending and firing alerts
+    pub async fn prometheus_alerts(&self) -> Result<Vec<PrometheusAlert>> {
+        let data = self.prometheus_get("/api/v1/alerts", &[]).await?;
+        parse(data["alerts"].clone(), "alerts")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, RawQuery};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+
+    fn success(data: Value) -> Json<Value> {
+        Json(json!({"status": "success", "data": data}))
+    }
+
+    #[tokio::test]
+    async fn discovers_series_labels_targets_rules_and_alerts() {
+        let app = Router::new()
+            .route(
+                "/api/v1/series",
+                get(|RawQuery(query): RawQuery| async move {
+                    let query = query.unwrap_or_default();
+                    assert!(query.contains("match%5B%5D=up"), "{}", query);
+                    assert!(query.contains("match%5B%5D=node_load1"), "{}", query);
+                    success(json!([
+                        {"__name__": "up", "job": "node", "instance": "a:9100"},
+                        {"__name__": "node_load1", "job": "node", "instance": "a:9100"}
+                    ]))
+                }),
+            )
+            .route(
+                "/api/v1/label/:label/values",
+                get(|Path(label): Path<String>| async move {
+                    assert_eq!(label, "__name__");
+                    success(json!(["node_load1", "up"]))
+                }),
+            )
+            .route(
+                "/api/v1/metadata",
+                get(|| async {
+                    success(json!({
+                        "up": [{"type": "gauge", "help": "Target is up", "unit": ""}],
+                        "http_requests": [{"type": "counter", "help": "Requests", "unit": ""},
+                                          {"type": "counter", "help": "Older help", "unit": ""}]
+                    }))
+                }),
+            )
+            .route(
+                "/api/v1/targets",
+                get(|| async {
+                    success(json!({"activeTargets": [{
+                        "discoveredLabels": {}, "labels": {"job": "node", "instance": "a:9100"},
+                        "scrapePool": "node", "scrapeUrl": "http://a:9100/metrics", "globalUrl": "http://a:9100/metrics",
+                        "lastError": "connection refused", "lastScrape": "2026-10-16T08:00:00.123Z",
+                        "lastScrapeDuration": 0.0021, "health": "down", "scrapeInterval": "15s", "scrapeTimeout": "10s"
+                    }], "droppedTargets": []}))
+                }),
+            )
+            .route(
+                "/api/v1/rules",
+                get(|RawQuery(query): RawQuery| async move {
+                    assert_eq!(query.as_deref(), Some("type=alert"));
+                    success(json!({"groups": [{"name": "node", "file": "/etc/prometheus/rules.yml", "interval": 15, "rules": [{
+                        "name": "InstanceDown", "query": "up == 0", "type": "alerting", "health": "ok",
+                        "state": "firing", "duration": 300, "labels": {"severity": "page"},
+                        "annotations": {"summary": "Instance down"},
+                        "alerts": [{"labels": {"alertname": "InstanceDown", "instance": "a:9100"}, "annotations": {},
+                                    "state": "firing", "activeAt": "2026-10-16T07:50:00Z", "value": "0e+00"}]
+                    }]}]}))
+                }),
+            )
+            .route(
+                "/api/v1/alerts",
+                get(|| async {
+                    success(json!({"alerts": [{"labels": {"alertname": "InstanceDown"}, "annotations": {},
+                        "state": "firing", "activeAt": "2026-10-16T07:50:00Z", "value": "0e+00"}]}))
+                }),
+            )
+            .route(
+                "/api/v1/labels",
+                get(|| async {
+                    Json(json!({"status": "error", "errorType": "bad_data", "error": "invalid matcher"}))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut monitoring = MonitoringModule::default();
+        monitoring.config.prometheus = Some(PrometheusConfig {
+            url,
+            username: None,
+            password: None,
+            bearer_token: None,
+            insecure_skip_verify: false,
+            remote_write: None,
+        });
+
+        let series = monitoring
+            .prometheus_series(&["up".to_string(), "node_load1".to_string()], None, None)
+            .await
+            .unwrap();
+        assert_eq!(series[1]["__name__"], "node_load1");
+        assert!(monitoring.prometheus_series(&[], None, None).await.is_err());
+
+        let names = monitoring
+            .prometheus_label_values("__name__", &[], None, None)
+            .await
+            .unwrap();
+        assert_eq!(names, ["node_load1", "up"]);
+        assert!(monitoring
+            .prometheus_label_values("job/../..", &[], None, None)
+            .await
+            .is_err());
+
+        let metadata = monitoring.prometheus_metadata(None).await.unwrap();
+        assert_eq!(
+            (metadata[0].metric.as_str(), metadata[0].help.as_str()),
+            ("http_requests", "Requests")
+        );
+        assert_eq!(metadata[1].kind, "gauge");
+
+        let targets = monitoring.prometheus_targets().await.unwrap();
+        assert_eq!(targets[0].health, "down");
+        assert_eq!(targets[0].labels["instance"], "a:9100");
+
+        let groups = monitoring
+            .prometheus_rules(Some(RuleKind::Alert))
+            .await
+            .unwrap();
+        let rule = &groups[0].rules[0];
+        assert_eq!(rule.state.as_deref(), Some("firing"));
+        assert_eq!(rule.alerts[0].name(), "InstanceDown");
+
+        let alerts = monitoring.prometheus_alerts().await.unwrap();
+        assert_eq!(alerts[0].state, "firing");
+
+        let error = monitoring
+            .prometheus_labels(&["{".to_string()], None, None)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("invalid matcher"), "{}", error);
+    }
+}
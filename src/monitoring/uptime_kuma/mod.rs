@@ -0,0 +1,738 @@
+//! Uptime Kuma monitors, heartbeats and outages
+//!
+//! Uptime Kuma has no REST API for monitors, so each call opens a
+//! Socket.IO session like its web UI does, logs in and uses the same
+//! events: the monitor list pushed after login, `getMonitorBeats` for
+//! history and `pauseMonitor`/`resumeMonitor`. Outages found in the
+//! heartbeat history are mirrored into the unified alert store on a
+//! schedule, one alert per outage, resolved when the monitor is back up.
+
+pub mod socket;
+
+use crate::error::{Error, Result};
+use crate::monitoring::{AlertSeverity, AlertSource, AlertStatus, AlertStore, UnifiedAlert};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use socket::SocketIo;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Hours of heartbeats searched for outages on each sync
+const LOOKBACK_HOURS: u64 = 24;
+
+/// Longest wait for the monitor list after login
+const MONITOR_LIST_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn default_poll_interval() -> u64 {
+    60
+}
+
+/// Uptime Kuma server, under `monitoring.uptime_kuma`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UptimeKumaConfig {
+    pub url: String,
+    /// Login; leave unset when authentication is disabled
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Seconds between outage syncs into the alert store
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval_secs: u64,
+}
+
+impl UptimeKumaConfig {
+    /// Uptime Kuma from `UPTIME_KUMA_URL`, `UPTIME_KUMA_USERNAME` and
+    /// `UPTIME_KUMA_PASSWORD`, if the URL is set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            url: std::env::var("UPTIME_KUMA_URL").ok()?,
+            username: std::env::var("UPTIME_KUMA_USERNAME").ok(),
+            password: std::env::var("UPTIME_KUMA_PASSWORD").ok(),
+            poll_interval_secs: default_poll_interval(),
+        })
+    }
+}
+
+/// Result of a check
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MonitorStatus {
+    Down,
+    Up,
+    /// Failing, but still within its retries
+    Pending,
+    Maintenance,
+    Unknown,
+}
+
+impl MonitorStatus {
+    fn from_code(code: &Value) -> Self {
+        match code.as_u64() {
+            Some(0) => Self::Down,
+            Some(1) => Self::Up,
+            Some(2) => Self::Pending,
+            Some(3) => Self::Maintenance,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// One check of a monitor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub time: DateTime<Utc>,
+    pub status: MonitorStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ping_ms: Option<f64>,
+    /// Whether the status changed with this check
+    pub important: bool,
+}
+
+fn heartbeat(beat: &Value) -> Option<Heartbeat> {
+    // "2024-05-10 08:00:00.123" in UTC
+    let time = beat["time"].as_str()?;
+    let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S%.f")
+        .map(|t| Utc.from_utc_datetime(&t))
+        .or_else(|_| DateTime::parse_from_rfc3339(time).map(|t| t.with_timezone(&Utc)))
+        .ok()?;
+    Some(Heartbeat {
+        time,
+        status: MonitorStatus::from_code(&beat["status"]),
+        message: beat["msg"]
+            .as_str()
+            .filter(|m| !m.is_empty())
+            .map(str::to_string),
+        ping_ms: beat["ping"].as_f64(),
+        important: beat["important"].as_bool().unwrap_or(false) || beat["important"] == 1,
+    })
+}
+
+/// A monitor and its latest state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Monitor {
+    pub id: u64,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// URL, host name or other target, depending on the type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// False while paused
+    pub active: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub status: MonitorStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_check: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ping_ms: Option<f64>,
+    /// Share of checks up over the last day, in percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime_24h: Option<f64>,
+}
+
+impl Monitor {
+    fn from_value(monitor: &Value) -> Option<Self> {
+        let target = monitor["url"]
+            .as_str()
+            .filter(|u| !u.is_empty() && *u != "https://")
+            .map(str::to_string)
+            .or_else(|| {
+                let host = monitor["hostname"].as_str().filter(|h| !h.is_empty())?;
+                Some(match monitor["port"].as_u64() {
+                    Some(port) => format!("{}:{}", host, port),
+                    None => host.to_string(),
+                })
+            });
+        Some(Self {
+            id: monitor["id"].as_u64()?,
+            name: monitor["name"].as_str().unwrap_or_default().to_string(),
+            kind: monitor["type"].as_str().unwrap_or_default().to_string(),
+            target,
+            active: monitor["active"].as_bool().unwrap_or(false) || monitor["active"] == 1,
+            tags: monitor["tags"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|t| {
+                    let name = t["name"].as_str()?;
+                    Some(match t["value"].as_str().filter(|v| !v.is_empty()) {
+                        Some(value) => format!("{}:{}", name, value),
+                        None => name.to_string(),
+                    })
+                })
+                .collect(),
+            status: MonitorStatus::Unknown,
+            last_check: None,
+            message: None,
+            ping_ms: None,
+            uptime_24h: None,
+        })
+    }
+
+    /// Fill in the latest state from heartbeats in time order
+    fn apply(&mut self, beats: &[Heartbeat]) {
+        if let Some(last) = beats.last() {
+            self.status = last.status;
+            self.last_check = Some(last.time);
+            self.message = last.message.clone();
+            self.ping_ms = last.ping_ms;
+        }
+        let counted = beats
+            .iter()
+            .filter(|b| b.status != MonitorStatus::Maintenance)
+            .count();
+        if counted > 0 {
+            let up = beats
+                .iter()
+                .filter(|b| b.status == MonitorStatus::Up)
+                .count();
+            self.uptime_24h = Some(up as f64 * 100.0 / counted as f64);
+        }
+    }
+}
+
+/// A stretch of down checks
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outage {
+    /// When the monitor went down; `None` if it was already down when the heartbeats start
+    pub started: Option<DateTime<Utc>>,
+    pub first_seen: DateTime<Utc>,
+    pub ended: Option<DateTime<Utc>>,
+    pub message: Option<String>,
+}
+
+/// Outages in heartbeats sorted by time; pending and maintenance checks
+/// neither start nor end one
+pub fn outages(beats: &[Heartbeat]) -> Vec<Outage> {
+    let mut outages = Vec::new();
+    let mut current: Option<Outage> = None;
+    for (index, beat) in beats.iter().enumerate() {
+        match beat.status {
+            MonitorStatus::Down if current.is_none() => {
+                current = Some(Outage {
+                    started: (index > 0 || beat.important).then_some(beat.time),
+                    first_seen: beat.time,
+                    ended: None,
+                    message: beat.message.clone(),
+                })
+            }
+            MonitorStatus::Up => {
+                if let Some(mut outage) = current.take() {
+                    outage.ended = Some(beat.time);
+                    outages.push(outage);
+                }
+            }
+            _ => {}
+        }
+    }
+    outages.extend(current);
+    outages
+}
+
+/// Uptime Kuma server
+pub struct UptimeKuma {
+    config: UptimeKumaConfig,
+}
+
+impl UptimeKuma {
+    pub fn new(config: UptimeKumaConfig) -> Self {
+        Self { config }
+    }
+
+    /// Logged-in session and the monitors it was sent
+    async fn session(&self) -> Result<(SocketIo, Vec<Monitor>)> {
+        let mut io = SocketIo::connect(&self.config.url).await?;
+        if let Some(username) = &self.config.username {
+            let reply = io
+                .call(
+                    "login",
+                    vec![json!({
+                        "username": username,
+                        "password": self.config.password.clone().unwrap_or_default(),
+                        "token": ""
+                    })],
+                )
+                .await?;
+            if reply["ok"] != true {
+                return Err(Error::auth(format!(
+                    "Uptime Kuma rejected the login: {}",
+                    reply["msg"].as_str().unwrap_or("unknown error")
+                )));
+            }
+        }
+        let list = io
+            .wait_for("monitorList", MONITOR_LIST_TIMEOUT)
+            .await?
+            .ok_or_else(|| Error::service("Uptime Kuma sent no monitor list; check the login"))?;
+        let mut monitors: Vec<Monitor> = list
+            .first()
+            .and_then(|m| m.as_object())
+            .into_iter()
+            .flat_map(|m| m.values())
+            .filter_map(Monitor::from_value)
+            .collect();
+        monitors.sort_by_key(|m| m.id);
+        Ok((io, monitors))
+    }
+
+    /// Heartbeats of a monitor over the last `hours`, oldest first
+    async fn beats(io: &mut SocketIo, monitor: u64, hours: u64) -> Result<Vec<Heartbeat>> {
+        let reply = io
+            .call("getMonitorBeats", vec![json!(monitor), json!(hours)])
+            .await?;
+        if reply["ok"] != true {
+            return Err(Error::service(format!(
+                "Uptime Kuma heartbeats for monitor {} failed: {}",
+                monitor,
+                reply["msg"].as_str().unwrap_or("unknown error")
+            )));
+        }
+        let mut beats: Vec<Heartbeat> = reply["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(heartbeat)
+            .collect();
+        beats.sort_by_key(|b| b.time);
+        Ok(beats)
+    }
+
+    fn find<'a>(monitors: &'a [Monitor], key: &str) -> Result<&'a Monitor> {
+        let id = key.parse::<u64>().ok();
+        monitors
+            .iter()
+            .find(|m| Some(m.id) == id || m.name.eq_ignore_ascii_case(key))
+            .ok_or_else(|| {
+                Error::not_found_with_resource(
+                    format!("Uptime Kuma has no monitor {}", key),
+                    "monitor",
+                    key,
+                )
+            })
+    }
+
+    /// Monitors with their latest state and uptime over the last day
+    pub async fn monitors(&self) -> Result<Vec<Monitor>> {
+        let (mut io, mut monitors) = self.session().await?;
+        for monitor in &mut monitors {
+            let beats = Self::beats(&mut io, monitor.id, LOOKBACK_HOURS).await?;
+            monitor.apply(&beats);
+        }
+        Ok(monitors)
+    }
+
+    /// A monitor and its heartbeats over the last `hours`
+    pub async fn heartbeats(&self, monitor: &str, hours: u64) -> Result<(Monitor, Vec<Heartbeat>)> {
+        let (mut io, monitors) = self.session().await?;
+        let mut monitor = Self::find(&monitors, monitor)?.clone();
+        let beats = Self::beats(&mut io, monitor.id, hours).await?;
+        monitor.apply(&beats);
+        Ok((monitor, beats))
+    }
+
+    /// Pause or resume a monitor
+    pub async fn set_paused(&self, monitor: &str, paused: bool) -> Result<Monitor> {
+        let (mut io, monitors) = self.session().await?;
+        let mut monitor = Self::find(&monitors, monitor)?.clone();
+        let event = if paused {
+            "pauseMonitor"
+        } else {
+            "resumeMonitor"
+        };
+        let reply = io.call(event, vec![json!(monitor.id)]).await?;
+        if reply["ok"] != true {
+            return Err(Error::service(format!(
+                "Uptime Kuma could not {} {}: {}",
+                if paused { "pause" } else { "resume" },
+                monitor.name,
+                reply["msg"].as_str().unwrap_or("unknown error")
+            )));
+        }
+        monitor.active = !paused;
+        Ok(monitor)
+    }
+
+    fn alert(monitor: &Monitor, id: String, outage: &Outage) -> UnifiedAlert {
+        let message = outage.message.clone().unwrap_or_default();
+        let mut tags = HashMap::from([
+            ("monitor".to_string(), monitor.name.clone()),
+            ("type".to_string(), monitor.kind.clone()),
+        ]);
+        if let Some(target) = &monitor.target {
+            tags.insert("target".to_string(), target.clone());
+        }
+        UnifiedAlert {
+            id,
+            title: format!("{} is down", monitor.name),
+            description: if message.is_empty() {
+                format!("Uptime Kuma monitor {} is down", monitor.name)
+            } else {
+                message.clone()
+            },
+            severity: AlertSeverity::High,
+            sources: vec![AlertSource::UptimeKuma {
+                monitor_id: monitor.id,
+                monitor_name: monitor.name.clone(),
+                message,
+            }],
+            created_at: outage.started.unwrap_or(outage.first_seen),
+            status: if outage.ended.is_some() {
+                AlertStatus::Resolved
+            } else {
+                AlertStatus::Active
+            },
+            resolved_at: outage.ended,
+            assignee: None,
+            tags,
+        }
+    }
+
+    /// Record outages of active monitors in the alert store, resolving those
+    /// that have ended; returns how many alerts were opened or changed status
+    pub async fn mirror_incidents(&self, store: &AlertStore) -> Result<usize> {
+        let (mut io, monitors) = self.session().await?;
+        let open = store.list(None, None).await;
+        let mut changed = 0;
+        for monitor in monitors.iter().filter(|m| m.active) {
+            let beats = Self::beats(&mut io, monitor.id, LOOKBACK_HOURS).await?;
+            let mut unresolved: Vec<&UnifiedAlert> = open
+                .iter()
+                .filter(|a| a.status != AlertStatus::Resolved)
+                .filter(|a| {
+                    a.sources.iter().any(|s| {
+                        matches!(s, AlertSource::UptimeKuma { monitor_id, .. } if *monitor_id == monitor.id)
+                    })
+                })
+                .collect();
+            for outage in outages(&beats) {
+                let id = match outage.started {
+                    Some(started) => format!("uptime-kuma:{}:{}", monitor.id, started.timestamp()),
+                    // Down since before the window: continue the open alert
+                    None => unresolved.first().map(|a| a.id.clone()).unwrap_or_else(|| {
+                        format!(
+                            "uptime-kuma:{}:{}",
+                            monitor.id,
+                            outage.first_seen.timestamp()
+                        )
+                    }),
+                };
+                unresolved.retain(|a| a.id != id);
+                if store.record(Self::alert(monitor, id, &outage)).await? {
+                    changed += 1;
+                }
+            }
+            // Recovered while no sync was running
+            if beats.last().is_some_and(|b| b.status == MonitorStatus::Up) {
+                for alert in unresolved {
+                    let mut resolved = alert.clone();
+                    resolved.status = AlertStatus::Resolved;
+                    if store.record(resolved).await? {
+                        changed += 1;
+                    }
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Mirror outages into `store` every `poll_interval_secs`
+    pub fn start_mirror(self: Arc<Self>, store: Arc<AlertStore>) -> tokio::task::JoinHandle<()> {
+        let period = Duration::from_secs(self.config.poll_interval_secs.max(10));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match self.mirror_incidents(&store).await {
+                    Ok(0) => {}
+                    Ok(changed) => {
+                        tracing::info!("Uptime Kuma: {} alerts opened or resolved", changed)
+                    }
+                    Err(e) => tracing::warn!("Uptime Kuma sync failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Get tool definitions for Uptime Kuma
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let monitor = json!({"type": "string", "description": "Monitor name or id"});
+        vec![
+            ToolDefinition::from_json_schema(
+                "uptime_monitors",
+                "List Uptime Kuma monitors with their current status, response time and uptime over the last day",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "status": {"type": "string", "enum": ["down", "up", "pending", "maintenance", "paused"], "description": "Only monitors in this state"}
+                    }
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "uptime_heartbeats",
+                "Read an Uptime Kuma monitor's heartbeat history and the outages in it",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "monitor": monitor,
+                        "hours": {"type": "integer", "minimum": 1, "maximum": 720, "default": 24},
+                        "important_only": {"type": "boolean", "description": "Only checks where the status changed", "default": false}
+                    },
+                    "required": ["monitor"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "uptime_pause",
+                "Pause or resume an Uptime Kuma monitor, e.g. during planned maintenance",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "monitor": monitor,
+                        "resume": {"type": "boolean", "description": "Resume instead of pausing", "default": false}
+                    },
+                    "required": ["monitor"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute an Uptime Kuma tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let monitor = || {
+            parameters
+                .get("monitor")
+                .map(|m| match m {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .ok_or_else(|| Error::validation_with_field("monitor is required", "monitor"))
+        };
+        match name {
+            "uptime_monitors" => {
+                let mut monitors = self.monitors().await?;
+                match parameters.get("status").and_then(|s| s.as_str()) {
+                    Some("paused") => monitors.retain(|m| !m.active),
+                    Some(status) => {
+                        let status: MonitorStatus =
+                            serde_json::from_value(json!(status)).map_err(|_| {
+                                Error::validation_with_field("Invalid status", "status")
+                            })?;
+                        monitors.retain(|m| m.active && m.status == status);
+                    }
+                    None => {}
+                }
+                let down = monitors
+                    .iter()
+                    .filter(|m| m.active && m.status == MonitorStatus::Down)
+                    .count();
+                let mut text = format!("{} monitors, {} down", monitors.len(), down);
+                for m in &monitors {
+                    text.push_str(&format!(
+                        "\n{} [{}]: {}{}{}",
+                        m.name,
+                        m.kind,
+                        if m.active {
+                            format!("{:?}", m.status).to_lowercase()
+                        } else {
+                            "paused".to_string()
+                        },
+                        m.uptime_24h
+                            .map(|u| format!(", {:.2}% uptime", u))
+                            .unwrap_or_default(),
+                        m.message
+                            .as_ref()
+                            .filter(|_| m.status != MonitorStatus::Up)
+                            .map(|msg| format!(" ({})", msg))
+                            .unwrap_or_default()
+                    ));
+                }
+                Ok(call_result(text, json!({ "monitors": monitors })))
+            }
+            "uptime_heartbeats" => {
+                let hours = parameters
+                    .get("hours")
+                    .and_then(|h| h.as_u64())
+                    .unwrap_or(LOOKBACK_HOURS)
+                    .clamp(1, 720);
+                let (monitor, mut beats) = self.heartbeats(&monitor()?, hours).await?;
+                let outages = outages(&beats);
+                if parameters
+                    .get("important_only")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false)
+                {
+                    beats.retain(|b| b.important);
+                }
+                let mut text = format!(
+                    "{}: {} checks in {} hours, {} outages",
+                    monitor.name,
+                    beats.len(),
+                    hours,
+                    outages.len()
+                );
+                for outage in &outages {
+                    text.push_str(&format!(
+                        "\n{} - {}{}",
+                        outage
+                            .started
+                            .map(|s| s.format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_else(|| "before the window".to_string()),
+                        outage
+                            .ended
+                            .map(|e| e.format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_else(|| "ongoing".to_string()),
+                        outage
+                            .message
+                            .as_ref()
+                            .map(|m| format!(": {}", m))
+                            .unwrap_or_default()
+                    ));
+                }
+                let outages: Vec<Value> = outages
+                    .iter()
+                    .map(|o| json!({"started": o.started, "first_seen": o.first_seen, "ended": o.ended, "message": o.message}))
+                    .collect();
+                Ok(call_result(
+                    text,
+                    json!({ "monitor": monitor, "heartbeats": beats, "outages": outages }),
+                ))
+            }
+            "uptime_pause" => {
+                let resume = parameters
+                    .get("resume")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let monitor = self.set_paused(&monitor()?, !resume).await?;
+                Ok(call_result(
+                    format!(
+                        "{} {}",
+                        monitor.name,
+                        if resume { "resumed" } else { "paused" }
+                    ),
+                    json!({ "monitor": monitor }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "uptime_kuma_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::ws::{Message as WsMessage, WebSocket, WebSocketUpgrade};
+    use axum::routing::get;
+    use axum::Router;
+    use tokio::net::TcpListener;
+
+    /// Yesterday at 08:mm UTC, well within alert retention
+    fn at(minute: u32) -> DateTime<Utc> {
+        let day = (Utc::now() - chrono::Duration::days(1)).date_naive();
+        Utc.from_utc_datetime(&day.and_hms_opt(8, minute, 0).unwrap())
+    }
+
+    /// Stand-in Uptime Kuma speaking just enough Socket.IO
+    async fn serve(mut socket: WebSocket) {
+        let send = |text: String| WsMessage::Text(text);
+        socket
+            .send(send(
+                r#"0{"sid":"a","pingInterval":25000,"pingTimeout":20000}"#.into(),
+            ))
+            .await
+            .unwrap();
+        while let Some(Ok(WsMessage::Text(packet))) = socket.recv().await {
+            if packet == "40" {
+                socket.send(send(r#"40{"sid":"b"}"#.into())).await.unwrap();
+                continue;
+            }
+            let Some((event, args)) = socket::parse_event(&packet) else {
+                continue;
+            };
+            let ack = packet[2..].split('[').next().unwrap().to_string();
+            let reply = match event.as_str() {
+                "login" => {
+                    assert_eq!(args[0]["username"], "admin");
+                    socket.send(send("2".into())).await.unwrap();
+                    socket
+                        .send(send(
+                            r#"42["monitorList",{"1":{"id":1,"name":"NAS","type":"http","url":"http://nas.lan","active":true,"tags":[{"name":"storage","value":""}]},"2":{"id":2,"name":"Printer","type":"ping","hostname":"printer.lan","active":false}}]"#.into(),
+                        ))
+                        .await
+                        .unwrap();
+                    json!({"ok": true, "token": "jwt"})
+                }
+                "getMonitorBeats" if args[0] == 1 => json!({"ok": true, "data": [
+                    {"status": 1, "time": at(0).format("%Y-%m-%d %H:%M:%S%.3f").to_string(), "msg": "200 - OK", "ping": 40, "important": 1},
+                    {"status": 0, "time": at(1).format("%Y-%m-%d %H:%M:%S%.3f").to_string(), "msg": "timeout", "important": 1},
+                    {"status": 0, "time": at(2).format("%Y-%m-%d %H:%M:%S%.3f").to_string(), "msg": "timeout", "important": 0},
+                    {"status": 1, "time": at(3).format("%Y-%m-%d %H:%M:%S%.3f").to_string(), "msg": "200 - OK", "ping": 42, "important": 1},
+                    {"status": 0, "time": at(4).format("%Y-%m-%d %H:%M:%S%.3f").to_string(), "msg": "connection refused", "important": 1}
+                ]}),
+                "getMonitorBeats" => json!({"ok": true, "data": []}),
+                "pauseMonitor" => json!({"ok": true, "msg": "Paused Successfully."}),
+                _ => json!({"ok": false, "msg": "unknown"}),
+            };
+            socket
+                .send(send(format!("43{}{}", ack, json!([reply]))))
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn mirrors_outages_into_the_alert_store() {
+        let app = Router::new().route(
+            "/socket.io/",
+            get(|ws: WebSocketUpgrade| async move { ws.on_upgrade(serve) }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let kuma = UptimeKuma::new(UptimeKumaConfig {
+            url: format!("http://{}", address),
+            username: Some("admin".to_string()),
+            password: Some("secret".to_string()),
+            poll_interval_secs: 60,
+        });
+        let monitors = kuma.monitors().await.unwrap();
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[0].status, MonitorStatus::Down);
+        assert_eq!(monitors[0].uptime_24h, Some(40.0));
+        assert_eq!(monitors[0].tags, ["storage"]);
+        assert_eq!(monitors[1].target.as_deref(), Some("printer.lan"));
+        assert!(!monitors[1].active);
+
+        let store = AlertStore::open(None).await.unwrap();
+        assert_eq!(kuma.mirror_incidents(&store).await.unwrap(), 2);
+        let alerts = store.list(None, None).await;
+        assert_eq!(alerts[0].status, AlertStatus::Active);
+        assert_eq!(alerts[0].description, "connection refused");
+        assert_eq!(alerts[1].status, AlertStatus::Resolved);
+        assert_eq!(alerts[1].resolved_at, Some(at(3)));
+        // The same history again changes nothing
+        assert_eq!(kuma.mirror_incidents(&store).await.unwrap(), 0);
+
+        let paused = kuma.set_paused("nas", true).await.unwrap();
+        assert!(!paused.active);
+    }
+}
@@ -0,0 +1,178 @@
+//! Minimal Socket.IO v4 client, enough for Uptime Kuma's events
+//!
+//! Packets travel as Engine.IO text frames over a WebSocket: `2`/`3` are
+//! ping and pong, `40` joins the default namespace, `42[...]` is an event
+//! and `43<id>[...]` acknowledges the event sent with that id. Events the
+//! server pushes while waiting for an acknowledgement are kept for
+//! `wait_for`.
+
+use crate::error::{Error, Result};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Longest wait for an acknowledgement
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Socket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Event name and arguments of a `42` packet
+pub(super) fn parse_event(packet: &str) -> Option<(String, Vec<Value>)> {
+    let body = packet
+        .strip_prefix("42")?
+        .trim_start_matches(|c: char| c.is_ascii_digit());
+    let mut items = serde_json::from_str::<Vec<Value>>(body).ok()?.into_iter();
+    let event = items.next()?.as_str()?.to_string();
+    Some((event, items.collect()))
+}
+
+/// Acknowledgement id and arguments of a `43` packet
+fn parse_ack(packet: &str) -> Option<(u64, Vec<Value>)> {
+    let body = packet.strip_prefix("43")?;
+    let digits = body.len() - body.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let id = body[..digits].parse().ok()?;
+    Some((id, serde_json::from_str(&body[digits..]).ok()?))
+}
+
+/// Socket.IO connection
+pub struct SocketIo {
+    socket: Socket,
+    next_ack: u64,
+    pushes: Vec<(String, Vec<Value>)>,
+}
+
+impl SocketIo {
+    /// Connect to the server at `base_url` and join the default namespace
+    pub async fn connect(base_url: &str) -> Result<Self> {
+        let mut url = url::Url::parse(base_url)
+            .map_err(|e| Error::config(format!("Invalid Uptime Kuma URL {}: {}", base_url, e)))?;
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        url.set_scheme(scheme)
+            .map_err(|_| Error::config(format!("Invalid Uptime Kuma URL {}", base_url)))?;
+        let path = format!("{}/socket.io/", url.path().trim_end_matches('/'));
+        url.set_path(&path);
+        url.set_query(Some("EIO=4&transport=websocket"));
+        let (socket, _) = tokio_tungstenite::connect_async(url.as_str())
+            .await
+            .map_err(|e| Error::network(format!("Uptime Kuma connection failed: {}", e)))?;
+        let mut io = Self {
+            socket,
+            next_ack: 0,
+            pushes: Vec::new(),
+        };
+        let open = io.next_packet().await?;
+        if !open.starts_with('0') {
+            return Err(Error::protocol(format!(
+                "Expected an Engine.IO open packet, got {}",
+                open
+            )));
+        }
+        io.send("40".to_string()).await?;
+        loop {
+            let packet = io.next_packet().await?;
+            if packet.starts_with("40") {
+                return Ok(io);
+            }
+            if let Some(reason) = packet.strip_prefix("44") {
+                return Err(Error::protocol(format!(
+                    "Uptime Kuma refused the connection: {}",
+                    reason
+                )));
+            }
+        }
+    }
+
+    async fn send(&mut self, packet: String) -> Result<()> {
+        self.socket
+            .send(Message::Text(packet))
+            .await
+            .map_err(|e| Error::network(format!("Uptime Kuma send failed: {}", e)))
+    }
+
+    /// Next packet other than a ping, which is answered
+    async fn next_packet(&mut self) -> Result<String> {
+        loop {
+            match self.socket.next().await {
+                Some(Ok(Message::Text(text))) if text == "2" => self.send("3".to_string()).await?,
+                Some(Ok(Message::Text(text))) if text == "41" || text == "1" => {
+                    return Err(Error::network("Uptime Kuma closed the connection"))
+                }
+                Some(Ok(Message::Text(text))) => return Ok(text),
+                Some(Ok(Message::Close(_))) | None => {
+                    return Err(Error::network("Uptime Kuma closed the connection"))
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(Error::network(e.to_string())),
+            }
+        }
+    }
+
+    /// Emit an event and return the first argument of its acknowledgement
+    pub async fn call(&mut self, event: &str, args: Vec<Value>) -> Result<Value> {
+        let id = self.next_ack;
+        self.next_ack += 1;
+        let mut payload = vec![json!(event)];
+        payload.extend(args);
+        self.send(format!("42{}{}", id, Value::Array(payload)))
+            .await?;
+        tokio::time::timeout(REQUEST_TIMEOUT, async {
+            loop {
+                let packet = self.next_packet().await?;
+                if let Some((ack, mut args)) = parse_ack(&packet) {
+                    if ack == id {
+                        return Ok(if args.is_empty() {
+                            Value::Null
+                        } else {
+                            args.swap_remove(0)
+                        });
+                    }
+                } else if let Some(push) = parse_event(&packet) {
+                    self.pushes.push(push);
+                }
+            }
+        })
+        .await
+        .map_err(|_| Error::timeout(format!("Uptime Kuma did not answer {}", event)))?
+    }
+
+    /// Arguments of the next `event` pushed by the server, if one arrives within `wait`
+    pub async fn wait_for(&mut self, event: &str, wait: Duration) -> Result<Option<Vec<Value>>> {
+        if let Some(index) = self.pushes.iter().position(|(e, _)| e == event) {
+            return Ok(Some(self.pushes.remove(index).1));
+        }
+        let received = tokio::time::timeout(wait, async {
+            loop {
+                let packet = self.next_packet().await?;
+                if let Some((name, args)) = parse_event(&packet) {
+                    if name == event {
+                        return Ok(args);
+                    }
+                    self.pushes.push((name, args));
+                }
+            }
+        })
+        .await;
+        match received {
+            Ok(args) => args.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_events_and_acknowledgements() {
+        let (event, args) = parse_event(r#"42["monitorList",{"1":{"id":1}}]"#).unwrap();
+        assert_eq!(event, "monitorList");
+        assert_eq!(args[0]["1"]["id"], 1);
+        let (id, args) = parse_ack(r#"4312[{"ok":true}]"#).unwrap();
+        assert_eq!(id, 12);
+        assert_eq!(args[0]["ok"], true);
+        assert!(parse_ack("2").is_none());
+    }
+}
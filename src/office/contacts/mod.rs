@@ -0,0 +1,1000 @@
+/// Contact and CRM-lite management
+///
+/// Keeps people, organizations, their roles and an interaction history that
+/// can point at memories (meeting notes, email threads) by memory ID. The
+/// resolver answers questions like "Jane from Acme" for meeting-notes and
+/// email workflows.
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+pub mod store;
+pub mod vcard;
+
+pub use store::{ContactStore, InMemoryContactStore, JsonFileStore};
+pub use vcard::VCard;
+
+/// Organization record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Organization {
+    pub id: String,
+    pub name: String,
+    /// Email/web domain, used to match people by address
+    pub domain: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A person's role at an organization
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Role {
+    pub organization_id: String,
+    pub title: Option<String>,
+    /// Whether the person still holds the role
+    pub current: bool,
+}
+
+/// Person record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Person {
+    pub id: String,
+    pub name: String,
+    /// Nicknames and alternate spellings
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub emails: Vec<String>,
+    #[serde(default)]
+    pub phones: Vec<String>,
+    #[serde(default)]
+    pub roles: Vec<Role>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Kind of interaction with a contact
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InteractionKind {
+    Meeting,
+    Email,
+    Call,
+    Message,
+    Note,
+}
+
+/// An interaction with one or more people
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub id: String,
+    pub person_ids: Vec<String>,
+    pub kind: InteractionKind,
+    pub summary: String,
+    pub occurred_at: DateTime<Utc>,
+    /// Linked memory (e.g. meeting notes) in the memory module
+    pub memory_id: Option<String>,
+}
+
+/// Everything persisted by a contact store
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContactData {
+    #[serde(default)]
+    pub people: BTreeMap<String, Person>,
+    #[serde(default)]
+    pub organizations: BTreeMap<String, Organization>,
+    #[serde(default)]
+    pub interactions: Vec<Interaction>,
+}
+
+/// Input for creating or merging a person
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NewPerson {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub emails: Vec<String>,
+    #[serde(default)]
+    pub phones: Vec<String>,
+    /// Organization name; created if it does not exist
+    pub organization: Option<String>,
+    /// Title at the organization
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+}
+
+/// A search or resolution hit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactMatch {
+    pub person: Person,
+    /// Names of the person's current organizations
+    pub organizations: Vec<String>,
+    pub score: f64,
+    pub last_interaction: Option<DateTime<Utc>>,
+}
+
+/// A person with their organizations and interaction history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactDetails {
+    pub person: Person,
+    pub organizations: Vec<Organization>,
+    pub interactions: Vec<Interaction>,
+}
+
+/// Result of a vCard import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub created: usize,
+    pub updated: usize,
+}
+
+fn push_unique(list: &mut Vec<String>, value: &str) {
+    if !value.is_empty() && !list.iter().any(|v| v.eq_ignore_ascii_case(value)) {
+        list.push(value.to_string());
+    }
+}
+
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '@' && c != '.')
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How well a name query matches a person's name or aliases (0.0 - 1.0)
+fn name_score(person: &Person, query: &str) -> f64 {
+    let query = query.trim().to_lowercase();
+    let query_tokens = tokens(&query);
+    if query_tokens.is_empty() {
+        return 0.0;
+    }
+
+    std::iter::once(&person.name)
+        .chain(person.aliases.iter())
+        .map(|candidate| {
+            let candidate = candidate.to_lowercase();
+            let candidate_tokens = tokens(&candidate);
+            if candidate == query {
+                1.0
+            } else if query_tokens
+                .iter()
+                .all(|q| candidate_tokens.iter().any(|c| c == q))
+            {
+                0.85
+            } else if query_tokens
+                .iter()
+                .all(|q| candidate_tokens.iter().any(|c| c.starts_with(q.as_str())))
+            {
+                0.6
+            } else if candidate.contains(&query) {
+                0.4
+            } else {
+                0.0
+            }
+        })
+        .fold(0.0, f64::max)
+}
+
+/// How well an organization query matches an organization (0.0 - 1.0)
+fn organization_score(organization: &Organization, query: &str) -> f64 {
+    let query = query.trim().to_lowercase();
+    let name = organization.name.to_lowercase();
+    let domain = organization
+        .domain
+        .as_deref()
+        .unwrap_or_default()
+        .to_lowercase();
+    if name == query || domain == query {
+        1.0
+    } else if name.starts_with(&query) || domain.starts_with(&query) {
+        0.8
+    } else if name.contains(&query) {
+        0.6
+    } else {
+        0.0
+    }
+}
+
+/// Split "Jane from Acme", "Jane at Acme", "Jane (Acme)" or "Jane @ Acme"
+fn split_resolution_query(query: &str) -> (String, Option<String>) {
+    let query = query.trim();
+    if let Some((name, rest)) = query.split_once('(') {
+        let organization = rest.trim_end_matches(')').trim();
+        if !organization.is_empty() {
+            return (name.trim().to_string(), Some(organization.to_string()));
+        }
+    }
+    let lower = query.to_ascii_lowercase();
+    for separator in [" from ", " at ", " of ", " @ ", ", "] {
+        if let Some(index) = lower.find(separator) {
+            let name = query[..index].trim();
+            let organization = query[index + separator.len()..].trim();
+            if !name.is_empty() && !organization.is_empty() {
+                return (name.to_string(), Some(organization.to_string()));
+            }
+        }
+    }
+    (query.to_string(), None)
+}
+
+/// Persistent contact book
+pub struct ContactBook {
+    store: Arc<dyn ContactStore>,
+    data: RwLock<ContactData>,
+}
+
+impl ContactBook {
+    /// Open a contact book, loading existing data from the store
+    pub async fn open(store: Arc<dyn ContactStore>) -> Result<Self> {
+        let data = store.load().await?;
+        Ok(Self {
+            store,
+            data: RwLock::new(data),
+        })
+    }
+
+    /// Open a contact book persisted to a JSON file
+    pub async fn open_file(path: impl Into<PathBuf>) -> Result<Self> {
+        Self::open(Arc::new(JsonFileStore::new(path))).await
+    }
+
+    /// Create an empty in-memory contact book
+    pub fn in_memory() -> Self {
+        Self {
+            store: Arc::new(InMemoryContactStore::new()),
+            data: RwLock::new(ContactData::default()),
+        }
+    }
+
+    fn find_or_create_organization(
+        data: &mut ContactData,
+        name: &str,
+        email: Option<&str>,
+    ) -> String {
+        if let Some(existing) = data
+            .organizations
+            .values()
+            .find(|o| o.name.eq_ignore_ascii_case(name.trim()))
+        {
+            return existing.id.clone();
+        }
+
+        let now = Utc::now();
+        let organization = Organization {
+            id: Uuid::new_v4().to_string(),
+            name: name.trim().to_string(),
+            domain: email
+                .and_then(|e| e.rsplit_once('@'))
+                .map(|(_, domain)| domain.to_lowercase()),
+            notes: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = organization.id.clone();
+        data.organizations.insert(id.clone(), organization);
+        id
+    }
+
+    fn merge_into(data: &mut ContactData, person_id: &str, input: &NewPerson) {
+        let organization_id = input.organization.as_deref().map(|name| {
+            Self::find_or_create_organization(data, name, input.emails.first().map(String::as_str))
+        });
+        let Some(person) = data.people.get_mut(person_id) else {
+            return;
+        };
+
+        for alias in &input.aliases {
+            push_unique(&mut person.aliases, alias);
+        }
+        for email in &input.emails {
+            push_unique(&mut person.emails, email);
+        }
+        for phone in &input.phones {
+            push_unique(&mut person.phones, phone);
+        }
+        for tag in &input.tags {
+            push_unique(&mut person.tags, tag);
+        }
+        if input.notes.is_some() {
+            person.notes = input.notes.clone();
+        }
+        if let Some(organization_id) = organization_id {
+            match person
+                .roles
+                .iter_mut()
+                .find(|r| r.organization_id == organization_id)
+            {
+                Some(role) => {
+                    role.current = true;
+                    if input.title.is_some() {
+                        role.title = input.title.clone();
+                    }
+                }
+                None => person.roles.push(Role {
+                    organization_id,
+                    title: input.title.clone(),
+                    current: true,
+                }),
+            }
+        }
+        person.updated_at = Utc::now();
+    }
+
+    /// Find a person sharing an email address with the input
+    fn find_by_email(data: &ContactData, emails: &[String]) -> Option<String> {
+        data.people
+            .values()
+            .find(|p| {
+                p.emails
+                    .iter()
+                    .any(|e| emails.iter().any(|n| n.eq_ignore_ascii_case(e)))
+            })
+            .map(|p| p.id.clone())
+    }
+
+    fn upsert(data: &mut ContactData, input: &NewPerson) -> (String, bool) {
+        if let Some(id) = Self::find_by_email(data, &input.emails) {
+            Self::merge_into(data, &id, input);
+            return (id, false);
+        }
+
+        let now = Utc::now();
+        let id = Uuid::new_v4().to_string();
+        data.people.insert(
+            id.clone(),
+            Person {
+                id: id.clone(),
+                name: input.name.trim().to_string(),
+                aliases: Vec::new(),
+                emails: Vec::new(),
+                phones: Vec::new(),
+                roles: Vec::new(),
+                tags: Vec::new(),
+                notes: None,
+                created_at: now,
+                updated_at: now,
+            },
+        );
+        Self::merge_into(data, &id, input);
+        (id, true)
+    }
+
+    /// Add a person, merging into an existing contact with the same email
+    pub async fn add_person(&self, input: NewPerson) -> Result<Person> {
+        if input.name.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Contact name is required",
+                "name",
+            ));
+        }
+        let mut data = self.data.write().await;
+        let (id, _) = Self::upsert(&mut data, &input);
+        self.store.save(&data).await?;
+        Ok(data.people[&id].clone())
+    }
+
+    /// Add an organization, returning the existing one if the name is taken
+    pub async fn add_organization(&self, name: &str, domain: Option<&str>) -> Result<Organization> {
+        if name.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Organization name is required",
+                "name",
+            ));
+        }
+        let mut data = self.data.write().await;
+        let id = Self::find_or_create_organization(&mut data, name, None);
+        if let Some(domain) = domain {
+            if let Some(organization) = data.organizations.get_mut(&id) {
+                organization.domain = Some(domain.to_lowercase());
+                organization.updated_at = Utc::now();
+            }
+        }
+        self.store.save(&data).await?;
+        Ok(data.organizations[&id].clone())
+    }
+
+    /// Remove a person and drop them from interaction history
+    pub async fn delete_person(&self, person_id: &str) -> Result<()> {
+        let mut data = self.data.write().await;
+        if data.people.remove(person_id).is_none() {
+            return Err(Error::not_found_with_resource(
+                "Contact not found",
+                "contact",
+                person_id,
+            ));
+        }
+        for interaction in &mut data.interactions {
+            interaction.person_ids.retain(|id| id != person_id);
+        }
+        data.interactions.retain(|i| !i.person_ids.is_empty());
+        self.store.save(&data).await
+    }
+
+    fn last_interaction(data: &ContactData, person_id: &str) -> Option<DateTime<Utc>> {
+        data.interactions
+            .iter()
+            .filter(|i| i.person_ids.iter().any(|id| id == person_id))
+            .map(|i| i.occurred_at)
+            .max()
+    }
+
+    fn to_match(data: &ContactData, person: &Person, score: f64) -> ContactMatch {
+        ContactMatch {
+            person: person.clone(),
+            organizations: person
+                .roles
+                .iter()
+                .filter(|r| r.current)
+                .filter_map(|r| data.organizations.get(&r.organization_id))
+                .map(|o| o.name.clone())
+                .collect(),
+            score,
+            last_interaction: Self::last_interaction(data, &person.id),
+        }
+    }
+
+    fn rank(mut matches: Vec<ContactMatch>, limit: usize) -> Vec<ContactMatch> {
+        // Ties go to whoever was in touch most recently
+        matches.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.last_interaction.cmp(&a.last_interaction))
+        });
+        matches.truncate(limit);
+        matches
+    }
+
+    /// Free-text search across names, aliases, emails, organizations, titles and tags
+    pub async fn search(&self, query: &str, limit: usize) -> Vec<ContactMatch> {
+        let data = self.data.read().await;
+        let query_tokens = tokens(query);
+        if query_tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let matches = data
+            .people
+            .values()
+            .filter_map(|person| {
+                let mut haystack = vec![person.name.clone()];
+                haystack.extend(person.aliases.iter().cloned());
+                haystack.extend(person.emails.iter().cloned());
+                haystack.extend(person.tags.iter().cloned());
+                for role in &person.roles {
+                    haystack.extend(role.title.iter().cloned());
+                    if let Some(organization) = data.organizations.get(&role.organization_id) {
+                        haystack.push(organization.name.clone());
+                    }
+                }
+                let haystack = haystack.join(" ").to_lowercase();
+                let hits = query_tokens
+                    .iter()
+                    .filter(|t| haystack.contains(t.as_str()))
+                    .count();
+                (hits == query_tokens.len()).then(|| {
+                    let score = name_score(person, query).max(0.5);
+                    Self::to_match(&data, person, score)
+                })
+            })
+            .collect();
+        Self::rank(matches, limit)
+    }
+
+    /// Resolve a natural reference such as "Jane from Acme" or an email address
+    pub async fn resolve(&self, query: &str, limit: usize) -> Vec<ContactMatch> {
+        let data = self.data.read().await;
+        let query = query.trim();
+
+        if query.contains('@') && !query.contains(' ') {
+            let matches = data
+                .people
+                .values()
+                .filter(|p| p.emails.iter().any(|e| e.eq_ignore_ascii_case(query)))
+                .map(|p| Self::to_match(&data, p, 1.0))
+                .collect();
+            return Self::rank(matches, limit);
+        }
+
+        let (name, organization) = split_resolution_query(query);
+        let matches = data
+            .people
+            .values()
+            .filter_map(|person| {
+                let name_score = name_score(person, &name);
+                if name_score == 0.0 {
+                    return None;
+                }
+                let Some(organization) = &organization else {
+                    return Some(Self::to_match(&data, person, name_score));
+                };
+
+                let organization_score = person
+                    .roles
+                    .iter()
+                    .filter_map(|r| {
+                        data.organizations.get(&r.organization_id).map(|o| {
+                            organization_score(o, organization) * if r.current { 1.0 } else { 0.7 }
+                        })
+                    })
+                    .fold(0.0, f64::max);
+                (organization_score > 0.0).then(|| {
+                    Self::to_match(&data, person, name_score * 0.6 + organization_score * 0.4)
+                })
+            })
+            .collect();
+        Self::rank(matches, limit)
+    }
+
+    /// Get a person with their organizations and interaction history
+    pub async fn get_contact(&self, person_id: &str) -> Result<ContactDetails> {
+        let data = self.data.read().await;
+        let person = data.people.get(person_id).cloned().ok_or_else(|| {
+            Error::not_found_with_resource("Contact not found", "contact", person_id)
+        })?;
+
+        let organizations = person
+            .roles
+            .iter()
+            .filter_map(|r| data.organizations.get(&r.organization_id).cloned())
+            .collect();
+        let mut interactions: Vec<Interaction> = data
+            .interactions
+            .iter()
+            .filter(|i| i.person_ids.iter().any(|id| id == person_id))
+            .cloned()
+            .collect();
+        interactions.sort_by_key(|i| std::cmp::Reverse(i.occurred_at));
+
+        Ok(ContactDetails {
+            person,
+            organizations,
+            interactions,
+        })
+    }
+
+    /// Record an interaction, optionally linked to a memory
+    pub async fn log_interaction(
+        &self,
+        person_ids: Vec<String>,
+        kind: InteractionKind,
+        summary: impl Into<String>,
+        occurred_at: Option<DateTime<Utc>>,
+        memory_id: Option<String>,
+    ) -> Result<Interaction> {
+        if person_ids.is_empty() {
+            return Err(Error::validation_with_field(
+                "At least one contact is required",
+                "person_ids",
+            ));
+        }
+        let mut data = self.data.write().await;
+        if let Some(missing) = person_ids.iter().find(|id| !data.people.contains_key(*id)) {
+            return Err(Error::not_found_with_resource(
+                "Contact not found",
+                "contact",
+                missing.as_str(),
+            ));
+        }
+
+        let interaction = Interaction {
+            id: Uuid::new_v4().to_string(),
+            person_ids,
+            kind,
+            summary: summary.into(),
+            occurred_at: occurred_at.unwrap_or_else(Utc::now),
+            memory_id,
+        };
+        data.interactions.push(interaction.clone());
+        self.store.save(&data).await?;
+        Ok(interaction)
+    }
+
+    /// Interactions linked to a memory
+    pub async fn interactions_for_memory(&self, memory_id: &str) -> Vec<Interaction> {
+        self.data
+            .read()
+            .await
+            .interactions
+            .iter()
+            .filter(|i| i.memory_id.as_deref() == Some(memory_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Import vCards, merging by email address
+    pub async fn import_vcards(&self, source: &str) -> Result<ImportSummary> {
+        let cards = vcard::parse(source)?;
+        let mut data = self.data.write().await;
+        let mut summary = ImportSummary::default();
+
+        for card in cards {
+            let input = NewPerson {
+                name: card.full_name,
+                emails: card.emails,
+                phones: card.phones,
+                organization: card.organization,
+                title: card.title,
+                tags: card.categories,
+                notes: card.note,
+                ..Default::default()
+            };
+            let (_, created) = Self::upsert(&mut data, &input);
+            if created {
+                summary.created += 1;
+            } else {
+                summary.updated += 1;
+            }
+        }
+
+        self.store.save(&data).await?;
+        Ok(summary)
+    }
+
+    /// Export people as vCard 4.0, all of them when no IDs are given
+    pub async fn export_vcards(&self, person_ids: Option<&[String]>) -> Result<String> {
+        let data = self.data.read().await;
+        let people: Vec<&Person> = match person_ids {
+            Some(ids) => ids
+                .iter()
+                .map(|id| {
+                    data.people.get(id).ok_or_else(|| {
+                        Error::not_found_with_resource("Contact not found", "contact", id.as_str())
+                    })
+                })
+                .collect::<Result<_>>()?,
+            None => data.people.values().collect(),
+        };
+
+        let cards: Vec<VCard> = people
+            .into_iter()
+            .map(|person| {
+                let role = person.roles.iter().find(|r| r.current);
+                VCard {
+                    uid: Some(format!("urn:uuid:{}", person.id)),
+                    full_name: person.name.clone(),
+                    emails: person.emails.clone(),
+                    phones: person.phones.clone(),
+                    organization: role
+                        .and_then(|r| data.organizations.get(&r.organization_id))
+                        .map(|o| o.name.clone()),
+                    title: role.and_then(|r| r.title.clone()),
+                    note: person.notes.clone(),
+                    categories: person.tags.clone(),
+                }
+            })
+            .collect();
+        Ok(vcard::serialize(&cards))
+    }
+
+    /// Get tool definitions for contact management
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "add_contact",
+                "Add a person (merged by email with an existing contact) and their organization",
+                "contacts",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "description": "Full name"},
+                        "emails": {"type": "array", "items": {"type": "string"}},
+                        "phones": {"type": "array", "items": {"type": "string"}},
+                        "aliases": {"type": "array", "items": {"type": "string"}, "description": "Nicknames"},
+                        "organization": {"type": "string", "description": "Organization name"},
+                        "title": {"type": "string", "description": "Role or job title"},
+                        "tags": {"type": "array", "items": {"type": "string"}},
+                        "notes": {"type": "string"}
+                    },
+                    "required": ["name"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "get_contact",
+                "Get a contact with organizations and interaction history",
+                "contacts",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string", "description": "Contact ID"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "search_contacts",
+                "Search contacts by name, email, organization, title or tag",
+                "contacts",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string"},
+                        "limit": {"type": "integer", "default": 10}
+                    },
+                    "required": ["query"]
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "resolve_contact",
+                "Resolve a reference like \"Jane from Acme\" or an email address to contacts",
+                "contacts",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string", "description": "e.g. \"Jane from Acme\", \"Jane (Acme)\", \"jane@acme.com\""},
+                        "limit": {"type": "integer", "default": 5}
+                    },
+                    "required": ["query"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "log_interaction",
+                "Record a meeting, email, call, message or note with contacts, optionally linked to a memory",
+                "contacts",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "person_ids": {"type": "array", "items": {"type": "string"}},
+                        "kind": {"type": "string", "enum": ["meeting", "email", "call", "message", "note"]},
+                        "summary": {"type": "string"},
+                        "occurred_at": {"type": "string", "description": "RFC 3339 timestamp, defaults to now"},
+                        "memory_id": {"type": "string", "description": "Memory holding notes for this interaction"}
+                    },
+                    "required": ["person_ids", "kind", "summary"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "import_vcard",
+                "Import contacts from vCard text",
+                "contacts",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "vcard": {"type": "string", "description": "One or more vCards"}
+                    },
+                    "required": ["vcard"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "export_vcard",
+                "Export contacts as vCard 4.0",
+                "contacts",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "ids": {"type": "array", "items": {"type": "string"}, "description": "Contact IDs, all contacts if omitted"}
+                    }
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a contact management tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let str_param = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let limit_param = |default: usize| {
+            parameters
+                .get("limit")
+                .and_then(|l| l.as_u64())
+                .map(|l| l as usize)
+                .unwrap_or(default)
+        };
+
+        match name {
+            "add_contact" => {
+                let input: NewPerson = serde_json::from_value(parameters.clone())
+                    .map_err(|e| Error::validation(format!("Invalid contact: {}", e)))?;
+                let person = self.add_person(input).await?;
+                Ok(call_result(
+                    format!("Saved contact {} ({})", person.name, person.id),
+                    json!({ "contact": person }),
+                ))
+            }
+            "get_contact" => {
+                let id = str_param("id")
+                    .ok_or_else(|| Error::validation_with_field("id is required", "id"))?;
+                let details = self.get_contact(id).await?;
+                Ok(call_result(
+                    format!(
+                        "{} with {} interaction(s)",
+                        details.person.name,
+                        details.interactions.len()
+                    ),
+                    serde_json::to_value(&details)?,
+                ))
+            }
+            "search_contacts" | "resolve_contact" => {
+                let query = str_param("query")
+                    .ok_or_else(|| Error::validation_with_field("query is required", "query"))?;
+                let matches = if name == "search_contacts" {
+                    self.search(query, limit_param(10)).await
+                } else {
+                    self.resolve(query, limit_param(5)).await
+                };
+                let text = if matches.is_empty() {
+                    format!("No contacts match \"{}\"", query)
+                } else {
+                    matches
+                        .iter()
+                        .map(|m| {
+                            let organizations = if m.organizations.is_empty() {
+                                String::new()
+                            } else {
+                                format!(" ({})", m.organizations.join(", "))
+                            };
+                            format!(
+                                "{}{} [{}] score {:.2}",
+                                m.person.name, organizations, m.person.id, m.score
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                Ok(call_result(text, json!({ "matches": matches })))
+            }
+            "log_interaction" => {
+                let person_ids: Vec<String> = parameters
+                    .get("person_ids")
+                    .cloned()
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_err(|e| {
+                        Error::validation_with_field(
+                            format!("Invalid person_ids: {}", e),
+                            "person_ids",
+                        )
+                    })?
+                    .unwrap_or_default();
+                let kind: InteractionKind = parameters
+                    .get("kind")
+                    .cloned()
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_err(|e| {
+                        Error::validation_with_field(format!("Invalid kind: {}", e), "kind")
+                    })?
+                    .ok_or_else(|| Error::validation_with_field("kind is required", "kind"))?;
+                let summary = str_param("summary").ok_or_else(|| {
+                    Error::validation_with_field("summary is required", "summary")
+                })?;
+                let occurred_at = str_param("occurred_at")
+                    .map(|t| {
+                        DateTime::parse_from_rfc3339(t)
+                            .map(|t| t.with_timezone(&Utc))
+                            .map_err(|e| {
+                                Error::validation_with_field(
+                                    format!("Invalid occurred_at: {}", e),
+                                    "occurred_at",
+                                )
+                            })
+                    })
+                    .transpose()?;
+
+                let interaction = self
+                    .log_interaction(
+                        person_ids,
+                        kind,
+                        summary,
+                        occurred_at,
+                        str_param("memory_id").map(str::to_string),
+                    )
+                    .await?;
+                Ok(call_result(
+                    format!("Logged interaction {}", interaction.id),
+                    json!({ "interaction": interaction }),
+                ))
+            }
+            "import_vcard" => {
+                let source = str_param("vcard")
+                    .ok_or_else(|| Error::validation_with_field("vcard is required", "vcard"))?;
+                let summary = self.import_vcards(source).await?;
+                Ok(call_result(
+                    format!(
+                        "Imported {} new and updated {} existing contact(s)",
+                        summary.created, summary.updated
+                    ),
+                    serde_json::to_value(&summary)?,
+                ))
+            }
+            "export_vcard" => {
+                let ids: Option<Vec<String>> = parameters
+                    .get("ids")
+                    .cloned()
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_err(|e| {
+                        Error::validation_with_field(format!("Invalid ids: {}", e), "ids")
+                    })?;
+                let vcards = self.export_vcards(ids.as_deref()).await?;
+                Ok(call_result(vcards.clone(), json!({ "vcard": vcards })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "contacts_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn resolves_person_by_organization() {
+        let book = ContactBook::in_memory();
+        book.add_person(NewPerson {
+            name: "Jane Doe".to_string(),
+            emails: vec!["jane@acme.com".to_string()],
+            organization: Some("Acme".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        book.add_person(NewPerson {
+            name: "Jane Roe".to_string(),
+            organization: Some("Globex".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let matches = book.resolve("Jane from Acme", 5).await;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].person.name, "Jane Doe");
+        assert_eq!(book.resolve("Jane", 5).await.len(), 2);
+        assert_eq!(
+            book.resolve("JANE@acme.com", 5).await[0].person.name,
+            "Jane Doe"
+        );
+    }
+
+    #[tokio::test]
+    async fn vcard_round_trip_merges_by_email() {
+        let book = ContactBook::in_memory();
+        let summary = book
+            .import_vcards(
+                "BEGIN:VCARD\r\nVERSION:3.0\r\nN:Doe;Jane;;;\r\nEMAIL;TYPE=work:jane@acme.com\r\n\
+                 ORG:Acme\\, Inc.;Engineering\r\nTITLE:CTO\r\nNOTE:Met at the\r\n  offsite\r\nEND:VCARD\r\n",
+            )
+            .await
+            .unwrap();
+        assert_eq!(summary.created, 1);
+
+        let exported = book.export_vcards(None).await.unwrap();
+        assert!(exported.contains("FN:Jane Doe\r\n"));
+        assert!(exported.contains("ORG:Acme\\, Inc.\r\n"));
+
+        let summary = book.import_vcards(&exported).await.unwrap();
+        assert_eq!(summary.updated, 1);
+        let matches = book.search("acme cto", 10).await;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            matches[0].person.notes.as_deref(),
+            Some("Met at the offsite")
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use super::ContactData;
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tokio::sync::RwLock;
+
+/// Trait for contact persistence backends
+#[async_trait]
+pub trait ContactStore: Send + Sync {
+    async fn load(&self) -> Result<ContactData>;
+    async fn save(&self, data: &ContactData) -> Result<()>;
+}
+
+/// JSON file store, rewritten atomically on every change
+pub struct JsonFileStore {
+    path: PathBuf,
+}
+
+impl JsonFileStore {
+    /// Create a store backed by the given file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl ContactStore for JsonFileStore {
+    async fn load(&self) -> Result<ContactData> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                Error::parsing(format!(
+                    "Failed to parse contact store {}: {}",
+                    self.path.display(),
+                    e
+                ))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ContactData::default()),
+            Err(e) => Err(Error::io_with_path(
+                format!("Failed to read contact store: {}", e),
+                self.path.clone(),
+            )),
+        }
+    }
+
+    async fn save(&self, data: &ContactData) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create contact store directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+
+        let temp = self.path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(data)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to write contact store: {}", e),
+                    temp.clone(),
+                )
+            })?;
+        tokio::fs::rename(&temp, &self.path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace contact store: {}", e),
+                self.path.clone(),
+            )
+        })
+    }
+}
+
+/// In-memory store for testing and development
+#[derive(Default)]
+pub struct InMemoryContactStore {
+    data: RwLock<ContactData>,
+}
+
+impl InMemoryContactStore {
+    /// Create an empty in-memory store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ContactStore for InMemoryContactStore {
+    async fn load(&self) -> Result<ContactData> {
+        Ok(self.data.read().await.clone())
+    }
+
+    async fn save(&self, data: &ContactData) -> Result<()> {
+        *self.data.write().await = data.clone();
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+/// vCard 3.0/4.0 import and export
+///
+/// Only the properties the contact book models are read (FN, N, EMAIL, TEL,
+/// ORG, TITLE, NOTE, UID, CATEGORIES); everything else is ignored on import.
+use crate::error::{Error, Result};
+
+/// A contact card as read from or written to vCard
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VCard {
+    pub uid: Option<String>,
+    pub full_name: String,
+    pub emails: Vec<String>,
+    pub phones: Vec<String>,
+    pub organization: Option<String>,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub categories: Vec<String>,
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
+
+/// Split on a separator that is not backslash-escaped
+fn split_unescaped(value: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == separator {
+            parts.push(std::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+    }
+    parts.push(current);
+    parts
+}
+
+/// Parse one or more vCards
+pub fn parse(source: &str) -> Result<Vec<VCard>> {
+    // Unfold continuation lines (RFC 6350 section 3.2)
+    let mut lines: Vec<String> = Vec::new();
+    for raw in source.lines() {
+        if let Some(rest) = raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        lines.push(raw.trim_end_matches('\r').to_string());
+    }
+
+    let mut cards = Vec::new();
+    let mut current: Option<VCard> = None;
+    let mut structured_name: Option<String> = None;
+
+    for line in lines.iter().filter(|l| !l.trim().is_empty()) {
+        let Some((name_part, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Drop parameters and any group prefix (item1.EMAIL;TYPE=work)
+        let property = name_part
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_uppercase();
+
+        match property.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VCARD") => {
+                current = Some(VCard::default());
+                structured_name = None;
+            }
+            "END" if value.eq_ignore_ascii_case("VCARD") => {
+                let mut card = current
+                    .take()
+                    .ok_or_else(|| Error::parsing("vCard END without BEGIN"))?;
+                if card.full_name.is_empty() {
+                    card.full_name = structured_name.take().unwrap_or_default();
+                }
+                if card.full_name.is_empty() {
+                    return Err(Error::parsing("vCard is missing FN and N"));
+                }
+                cards.push(card);
+            }
+            _ => {
+                let Some(card) = current.as_mut() else {
+                    continue;
+                };
+                match property.as_str() {
+                    "FN" => card.full_name = unescape(value),
+                    "N" => {
+                        // N:Family;Given;Additional;Prefix;Suffix
+                        let parts: Vec<String> = split_unescaped(value, ';')
+                            .iter()
+                            .map(|p| unescape(p))
+                            .collect();
+                        let name = [
+                            parts.get(3),
+                            parts.get(1),
+                            parts.get(2),
+                            parts.first(),
+                            parts.get(4),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .filter(|p| !p.is_empty())
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                        structured_name = Some(name);
+                    }
+                    "EMAIL" => card.emails.push(unescape(value)),
+                    "TEL" => card.phones.push(unescape(value.trim_start_matches("tel:"))),
+                    "ORG" => {
+                        card.organization = split_unescaped(value, ';')
+                            .first()
+                            .map(|o| unescape(o))
+                            .filter(|o| !o.is_empty())
+                    }
+                    "TITLE" => card.title = Some(unescape(value)),
+                    "NOTE" => card.note = Some(unescape(value)),
+                    "UID" => card.uid = Some(unescape(value)),
+                    "CATEGORIES" => card.categories.extend(
+                        split_unescaped(value, ',')
+                            .iter()
+                            .map(|c| unescape(c))
+                            .filter(|c| !c.is_empty()),
+                    ),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if current.is_some() {
+        return Err(Error::parsing("vCard BEGIN without END"));
+    }
+    Ok(cards)
+}
+
+/// Fold a content line at 75 octets
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += len;
+    }
+    out.push_str("\r\n");
+    out
+}
+
+/// Serialize cards as vCard 4.0
+pub fn serialize(cards: &[VCard]) -> String {
+    let mut out = String::new();
+    for card in cards {
+        out.push_str("BEGIN:VCARD\r\nVERSION:4.0\r\n");
+        if let Some(uid) = &card.uid {
+            out.push_str(&fold(&format!("UID:{}", escape(uid))));
+        }
+        out.push_str(&fold(&format!("FN:{}", escape(&card.full_name))));
+
+        // Best-effort structured name: last word is the family name
+        let mut words: Vec<&str> = card.full_name.split_whitespace().collect();
+        let family = if words.len() > 1 {
+            words.pop().unwrap_or_default()
+        } else {
+            ""
+        };
+        out.push_str(&fold(&format!(
+            "N:{};{};;;",
+            escape(family),
+            escape(&words.join(" "))
+        )));
+
+        for email in &card.emails {
+            out.push_str(&fold(&format!("EMAIL:{}", escape(email))));
+        }
+        for phone in &card.phones {
+            out.push_str(&fold(&format!("TEL;VALUE=text:{}", escape(phone))));
+        }
+        if let Some(org) = &card.organization {
+            out.push_str(&fold(&format!("ORG:{}", escape(org))));
+        }
+        if let Some(title) = &card.title {
+            out.push_str(&fold(&format!("TITLE:{}", escape(title))));
+        }
+        if !card.categories.is_empty() {
+            let categories: Vec<String> = card.categories.iter().map(|c| escape(c)).collect();
+            out.push_str(&fold(&format!("CATEGORIES:{}", categories.join(","))));
+        }
+        if let Some(note) = &card.note {
+            out.push_str(&fold(&format!("NOTE:{}", escape(note))));
+        }
+        out.push_str("END:VCARD\r\n");
+    }
+    out
+}
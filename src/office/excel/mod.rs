@@ -504,7 +504,8 @@ impl<'a> ExcelClient<'a> {
                     ToolAnnotation::new("worksheet_manager")
                         .with_description("Gets all worksheets in a workbook"),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "update_cells",
                 "Update cell values in a worksheet",
@@ -1,6 +1,9 @@
+pub mod contacts;
 pub mod excel;
+pub mod parsers;
 /// Office module for managing office-related applications and documents
 pub mod powerpoint;
+pub mod templates;
 pub mod word;
 
 // Re-export specific items instead of using glob imports
@@ -12,6 +15,15 @@ pub use powerpoint::{
 // Word module
 pub use word::{Alignment, Document, Paragraph, Section, Table, TableCell, WordClient};
 
+// Contacts module
+pub use contacts::{ContactBook, ContactMatch, Interaction, InteractionKind, Organization, Person};
+
+// Parsers module
+pub use parsers::{DocumentFormat, ExtractedBlock, ExtractedDocument};
+
+// Templates module
+pub use templates::{DocumentTemplates, OutputFormat, RenderedDocument, TemplateInfo};
+
 // Excel module
 pub use excel::{
     Cell, CellFormat, CellValue, Chart, ChartType, Column, ExcelClient, Row, Workbook, Worksheet,
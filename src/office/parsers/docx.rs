@@ -0,0 +1,100 @@
+use super::ExtractedBlock;
+use crate::error::{Error, Result};
+use crate::maps::tracks::xml::{self, Element};
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+fn docx_error(message: impl Into<String>) -> Error {
+    Error::parsing_with_format(message, "docx", None)
+}
+
+/// Heading level from a paragraph style such as `Heading2` or `Title`
+fn heading_level(paragraph: &Element) -> Option<u8> {
+    let style = paragraph
+        .child("pPr")
+        .and_then(|p| p.child("pStyle"))
+        .and_then(|s| s.attr("w:val"))?;
+    let lower = style.to_ascii_lowercase();
+    if lower == "title" {
+        return Some(1);
+    }
+    lower
+        .strip_prefix("heading")
+        .map(|level| level.trim().parse().unwrap_or(1))
+}
+
+fn paragraph_text(paragraph: &Element) -> String {
+    let mut text = String::new();
+    let mut runs = Vec::new();
+    paragraph.descendants("r", &mut runs);
+    for run in runs {
+        for part in &run.children {
+            match part.local_name() {
+                "t" => text.push_str(&part.text),
+                "tab" => text.push('\t'),
+                "br" | "cr" => text.push('\n'),
+                _ => {}
+            }
+        }
+    }
+    text.trim().to_string()
+}
+
+/// Collect paragraphs in body order, flattening tables row by row
+fn collect(element: &Element, out: &mut Vec<ExtractedBlock>) {
+    for child in &element.children {
+        match child.local_name() {
+            "p" => {
+                let text = paragraph_text(child);
+                if text.is_empty() {
+                    continue;
+                }
+                out.push(match heading_level(child) {
+                    Some(level) => ExtractedBlock::Heading { level, text },
+                    None => ExtractedBlock::Paragraph { text },
+                });
+            }
+            "tr" => {
+                let cells: Vec<String> = child
+                    .children_named("tc")
+                    .map(|cell| {
+                        let mut paragraphs = Vec::new();
+                        cell.descendants("p", &mut paragraphs);
+                        paragraphs
+                            .into_iter()
+                            .map(paragraph_text)
+                            .filter(|t| !t.is_empty())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .collect();
+                if cells.iter().any(|c| !c.is_empty()) {
+                    out.push(ExtractedBlock::Paragraph {
+                        text: cells.join(" | "),
+                    });
+                }
+            }
+            _ => collect(child, out),
+        }
+    }
+}
+
+/// Extract the body of a Word document as headings and paragraphs
+pub fn extract_blocks(bytes: &[u8]) -> Result<Vec<ExtractedBlock>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| docx_error(format!("Not a valid DOCX archive: {}", e)))?;
+    let mut source = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|_| docx_error("DOCX archive has no word/document.xml"))?
+        .read_to_string(&mut source)
+        .map_err(|e| docx_error(format!("Failed to read document.xml: {}", e)))?;
+
+    let root = xml::parse(&source)?;
+    let body = root
+        .child("body")
+        .ok_or_else(|| docx_error("document.xml has no body"))?;
+    let mut blocks = Vec::new();
+    collect(body, &mut blocks);
+    Ok(blocks)
+}
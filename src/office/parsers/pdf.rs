@@ -0,0 +1,1182 @@
+//! PDF text extraction
+//!
+//! Reads classic and cross-reference-stream PDFs (including object streams),
+//! inflates Flate-compressed content and interprets the text operators page by
+//! page. Glyphs are mapped through `ToUnicode` CMaps when fonts provide them,
+//! otherwise through the font's simple encoding. Layout is approximated:
+//! vertical moves become line breaks and wide horizontal gaps become spaces.
+//! Encrypted documents and scanned pages (images only) yield no text.
+
+use crate::error::{Error, Result};
+use flate2::read::ZlibDecoder;
+use regex::bytes::Regex;
+use std::collections::HashMap;
+use std::io::Read;
+use std::rc::Rc;
+
+/// Form XObjects nested deeper than this are ignored
+const MAX_FORM_DEPTH: usize = 8;
+
+/// Largest decoded size of one stream, so small compressed streams cannot
+/// expand without bound
+const MAX_STREAM_SIZE: usize = 64 * 1024 * 1024;
+
+fn pdf_error(message: impl Into<String>) -> Error {
+    Error::parsing_with_format(message, "pdf", None)
+}
+
+type Dict = HashMap<String, Object>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Object {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Name(String),
+    String(Vec<u8>),
+    Array(Vec<Object>),
+    Dict(Dict),
+    Ref(u32),
+    /// Operator or unknown bare word
+    Keyword(String),
+}
+
+impl Object {
+    fn as_dict(&self) -> Option<&Dict> {
+        match self {
+            Object::Dict(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    fn as_name(&self) -> Option<&str> {
+        match self {
+            Object::Name(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Object::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+enum Token {
+    DictStart,
+    DictEnd,
+    ArrayStart,
+    ArrayEnd,
+    Object(Object),
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, 0 | b'\t' | b'\n' | 0x0c | b'\r' | b' ')
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+struct Lexer<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&b) = self.data.get(self.pos) {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while self
+                    .data
+                    .get(self.pos)
+                    .is_some_and(|&b| b != b'\n' && b != b'\r')
+                {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn regular_run(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self
+            .data
+            .get(self.pos)
+            .is_some_and(|&b| !is_whitespace(b) && !is_delimiter(b))
+        {
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    fn literal_string(&mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut depth = 1;
+        while let Some(&b) = self.data.get(self.pos) {
+            self.pos += 1;
+            match b {
+                b'(' => {
+                    depth += 1;
+                    out.push(b);
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                    out.push(b);
+                }
+                b'\\' => {
+                    let Some(&escaped) = self.data.get(self.pos) else {
+                        break;
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0c),
+                        b'\r' => {
+                            if self.data.get(self.pos) == Some(&b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.data.get(self.pos) {
+                                    Some(&d @ b'0'..=b'7') => {
+                                        value = value * 8 + (d - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            out.push(value as u8);
+                        }
+                        other => out.push(other),
+                    }
+                }
+                _ => out.push(b),
+            }
+        }
+        out
+    }
+
+    fn hex_string(&mut self) -> Vec<u8> {
+        let mut digits = Vec::new();
+        while let Some(&b) = self.data.get(self.pos) {
+            self.pos += 1;
+            if b == b'>' {
+                break;
+            }
+            if let Some(d) = (b as char).to_digit(16) {
+                digits.push(d as u8);
+            }
+        }
+        if digits.len() % 2 == 1 {
+            digits.push(0);
+        }
+        digits.chunks(2).map(|p| p[0] << 4 | p[1]).collect()
+    }
+
+    fn token(&mut self) -> Option<Token> {
+        self.skip_whitespace();
+        let b = *self.data.get(self.pos)?;
+        match b {
+            b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                self.pos += 2;
+                Some(Token::DictStart)
+            }
+            b'>' if self.data.get(self.pos + 1) == Some(&b'>') => {
+                self.pos += 2;
+                Some(Token::DictEnd)
+            }
+            b'<' => {
+                self.pos += 1;
+                Some(Token::Object(Object::String(self.hex_string())))
+            }
+            b'(' => {
+                self.pos += 1;
+                Some(Token::Object(Object::String(self.literal_string())))
+            }
+            b'[' => {
+                self.pos += 1;
+                Some(Token::ArrayStart)
+            }
+            b']' => {
+                self.pos += 1;
+                Some(Token::ArrayEnd)
+            }
+            b'/' => {
+                self.pos += 1;
+                let raw = self.regular_run();
+                let mut name = Vec::with_capacity(raw.len());
+                let mut i = 0;
+                while i < raw.len() {
+                    let decoded = (raw[i] == b'#')
+                        .then(|| raw.get(i + 1..i + 3))
+                        .flatten()
+                        .and_then(|h| u8::from_str_radix(std::str::from_utf8(h).ok()?, 16).ok());
+                    match decoded {
+                        Some(byte) => {
+                            name.push(byte);
+                            i += 3;
+                        }
+                        None => {
+                            name.push(raw[i]);
+                            i += 1;
+                        }
+                    }
+                }
+                Some(Token::Object(Object::Name(
+                    String::from_utf8_lossy(&name).into_owned(),
+                )))
+            }
+            b')' | b'>' | b'{' | b'}' => {
+                self.pos += 1;
+                Some(Token::Object(Object::Keyword((b as char).to_string())))
+            }
+            _ => {
+                let word = self.regular_run();
+                let word = String::from_utf8_lossy(word).into_owned();
+                let object = match word.as_str() {
+                    "true" => Object::Bool(true),
+                    "false" => Object::Bool(false),
+                    "null" => Object::Null,
+                    _ if word.starts_with(|c: char| c.is_ascii_digit() || "+-.".contains(c)) => {
+                        word.parse()
+                            .map(Object::Number)
+                            .unwrap_or(Object::Keyword(word))
+                    }
+                    _ => Object::Keyword(word),
+                };
+                Some(Token::Object(object))
+            }
+        }
+    }
+
+    /// Parse one object, resolving `n g R` references
+    fn object(&mut self) -> Option<Object> {
+        match self.token()? {
+            Token::DictStart => {
+                let mut dict = Dict::new();
+                loop {
+                    match self.token()? {
+                        Token::DictEnd => break,
+                        Token::Object(Object::Name(key)) => {
+                            let value = self.object()?;
+                            dict.insert(key, value);
+                        }
+                        _ => {}
+                    }
+                }
+                Some(Object::Dict(dict))
+            }
+            Token::ArrayStart => {
+                let mut items = Vec::new();
+                loop {
+                    let checkpoint = self.pos;
+                    match self.token()? {
+                        Token::ArrayEnd => break,
+                        _ => {
+                            self.pos = checkpoint;
+                            items.push(self.object()?);
+                        }
+                    }
+                }
+                Some(Object::Array(items))
+            }
+            Token::DictEnd | Token::ArrayEnd => Some(Object::Keyword(String::new())),
+            Token::Object(Object::Number(n)) if n.fract() == 0.0 && n >= 0.0 => {
+                let checkpoint = self.pos;
+                let is_ref = matches!(self.token(), Some(Token::Object(Object::Number(g))) if g.fract() == 0.0)
+                    && matches!(self.token(), Some(Token::Object(Object::Keyword(k))) if k == "R");
+                if is_ref {
+                    Some(Object::Ref(n as u32))
+                } else {
+                    self.pos = checkpoint;
+                    Some(Object::Number(n))
+                }
+            }
+            Token::Object(object) => Some(object),
+        }
+    }
+
+    /// Skip inline image data after an `ID` operator
+    fn skip_inline_image(&mut self) {
+        self.pos += 1;
+        while self.pos + 2 <= self.data.len() {
+            if &self.data[self.pos..self.pos + 2] == b"EI"
+                && self
+                    .data
+                    .get(self.pos - 1)
+                    .is_some_and(|&b| is_whitespace(b))
+                && self
+                    .data
+                    .get(self.pos + 2)
+                    .is_none_or(|&b| is_whitespace(b))
+            {
+                self.pos += 2;
+                return;
+            }
+            self.pos += 1;
+        }
+        self.pos = self.data.len();
+    }
+}
+
+struct Document {
+    objects: HashMap<u32, (Object, Option<Vec<u8>>)>,
+}
+
+fn inflate(data: &[u8]) -> Result<Option<Vec<u8>>> {
+    let mut out = Vec::new();
+    let read = ZlibDecoder::new(data)
+        .take((MAX_STREAM_SIZE as u64).saturating_add(1))
+        .read_to_end(&mut out);
+    if out.len() > MAX_STREAM_SIZE {
+        return Err(pdf_error(format!(
+            "PDF stream decompresses to more than {} bytes",
+            MAX_STREAM_SIZE
+        )));
+    }
+    Ok(match read {
+        Ok(_) => Some(out),
+        // Truncated streams still often hold usable text
+        Err(_) if !out.is_empty() => Some(out),
+        Err(_) => None,
+    })
+}
+
+/// Reverse PNG predictors used by cross-reference and object streams
+fn unpredict(data: Vec<u8>, params: Option<&Dict>) -> Vec<u8> {
+    let predictor = params
+        .and_then(|p| p.get("Predictor"))
+        .and_then(Object::as_number)
+        .unwrap_or(1.0);
+    if predictor < 10.0 {
+        return data;
+    }
+    let columns = params
+        .and_then(|p| p.get("Columns"))
+        .and_then(Object::as_number)
+        .unwrap_or(1.0) as usize;
+    let stride = columns.max(1);
+    let mut out = Vec::with_capacity(data.len());
+    let mut prior = vec![0u8; stride];
+    for row in data.chunks(stride + 1) {
+        let (filter, src) = (row[0], &row[1..]);
+        let mut current = vec![0u8; src.len()];
+        for i in 0..src.len() {
+            let left = if i > 0 { current[i - 1] } else { 0 };
+            let up = prior.get(i).copied().unwrap_or(0);
+            current[i] = match filter {
+                1 => src[i].wrapping_add(left),
+                2 => src[i].wrapping_add(up),
+                3 => src[i].wrapping_add(((left as u16 + up as u16) / 2) as u8),
+                _ => src[i],
+            };
+        }
+        out.extend_from_slice(&current);
+        prior = current;
+    }
+    out
+}
+
+impl Document {
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        if !bytes.starts_with(b"%PDF")
+            && !bytes[..bytes.len().min(1024)]
+                .windows(4)
+                .any(|w| w == b"%PDF")
+        {
+            return Err(pdf_error("Not a PDF file"));
+        }
+
+        let header =
+            Regex::new(r"(?-u)(\d+)\s+\d+\s+obj\b").map_err(|e| Error::internal(e.to_string()))?;
+        let mut document = Document {
+            objects: HashMap::new(),
+        };
+        for captures in header.captures_iter(bytes) {
+            let Some(number) = std::str::from_utf8(&captures[1])
+                .ok()
+                .and_then(|n| n.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            let end = captures.get(0).map(|m| m.end()).unwrap_or(0);
+            let mut lexer = Lexer::new(bytes, end);
+            let Some(object) = lexer.object() else {
+                continue;
+            };
+            lexer.skip_whitespace();
+            let stream = if bytes[lexer.pos..].starts_with(b"stream") {
+                let mut start = lexer.pos + 6;
+                if bytes.get(start) == Some(&b'\r') {
+                    start += 1;
+                }
+                if bytes.get(start) == Some(&b'\n') {
+                    start += 1;
+                }
+                let declared = object
+                    .as_dict()
+                    .and_then(|d| d.get("Length"))
+                    .and_then(Object::as_number)
+                    .map(|n| n as usize)
+                    .filter(|&len| {
+                        start
+                            .checked_add(len)
+                            .and_then(|end| bytes.get(end..))
+                            .map(|rest| {
+                                let rest = &rest[..rest.len().min(16)];
+                                rest.windows(9).any(|w| w == b"endstream")
+                            })
+                            .unwrap_or(false)
+                    });
+                let length = declared.or_else(|| {
+                    bytes[start..]
+                        .windows(9)
+                        .position(|w| w == b"endstream")
+                        .map(|p| {
+                            let mut len = p;
+                            while len > 0 && matches!(bytes[start + len - 1], b'\r' | b'\n') {
+                                len -= 1;
+                            }
+                            len
+                        })
+                });
+                length
+                    .and_then(|len| start.checked_add(len))
+                    .and_then(|end| bytes.get(start..end))
+                    .map(<[u8]>::to_vec)
+            } else {
+                None
+            };
+            if object.as_dict().is_some_and(|d| d.contains_key("Encrypt")) {
+                return Err(Error::validation("Encrypted PDFs are not supported"));
+            }
+            document.objects.insert(number, (object, stream));
+        }
+        // Classic trailers; cross-reference stream dictionaries are checked above
+        let mut search = 0;
+        while let Some(found) = bytes[search..].windows(7).position(|w| w == b"trailer") {
+            let mut lexer = Lexer::new(bytes, search + found + 7);
+            if lexer
+                .object()
+                .as_ref()
+                .and_then(Object::as_dict)
+                .is_some_and(|d| d.contains_key("Encrypt"))
+            {
+                return Err(Error::validation("Encrypted PDFs are not supported"));
+            }
+            search += found + 7;
+        }
+
+        document.expand_object_streams()?;
+        Ok(document)
+    }
+
+    fn expand_object_streams(&mut self) -> Result<()> {
+        let streams: Vec<u32> = self
+            .objects
+            .iter()
+            .filter(|(_, (object, _))| {
+                object
+                    .as_dict()
+                    .and_then(|d| d.get("Type"))
+                    .and_then(Object::as_name)
+                    == Some("ObjStm")
+            })
+            .map(|(n, _)| *n)
+            .collect();
+        for number in streams {
+            let Some(data) = self.stream_data(number)? else {
+                continue;
+            };
+            let Some(dict) = self.objects.get(&number).and_then(|(o, _)| o.as_dict()) else {
+                continue;
+            };
+            let count = dict.get("N").and_then(Object::as_number).unwrap_or(0.0) as usize;
+            let first = dict.get("First").and_then(Object::as_number).unwrap_or(0.0) as usize;
+
+            let mut lexer = Lexer::new(&data, 0);
+            let mut entries = Vec::with_capacity(count);
+            for _ in 0..count {
+                match (lexer.token(), lexer.token()) {
+                    (
+                        Some(Token::Object(Object::Number(n))),
+                        Some(Token::Object(Object::Number(offset))),
+                    ) => entries.push((n as u32, offset as usize)),
+                    _ => break,
+                }
+            }
+            for (n, offset) in entries {
+                if self.objects.contains_key(&n) {
+                    continue;
+                }
+                if let Some(object) = Lexer::new(&data, first.saturating_add(offset)).object() {
+                    self.objects.insert(n, (object, None));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve<'a>(&'a self, object: &'a Object) -> &'a Object {
+        let mut current = object;
+        for _ in 0..16 {
+            match current {
+                Object::Ref(n) => match self.objects.get(n) {
+                    Some((target, _)) => current = target,
+                    None => return &Object::Null,
+                },
+                _ => return current,
+            }
+        }
+        &Object::Null
+    }
+
+    fn get_dict<'a>(&'a self, dict: &'a Dict, key: &str) -> Option<&'a Dict> {
+        dict.get(key)
+            .map(|o| self.resolve(o))
+            .and_then(Object::as_dict)
+    }
+
+    /// Decoded data of a stream object
+    fn stream_data(&self, number: u32) -> Result<Option<Vec<u8>>> {
+        let Some((object, Some(raw))) = self.objects.get(&number) else {
+            return Ok(None);
+        };
+        let Some(dict) = object.as_dict() else {
+            return Ok(None);
+        };
+        let filters: Vec<&str> = match dict.get("Filter").map(|f| self.resolve(f)) {
+            Some(Object::Name(name)) => vec![name.as_str()],
+            Some(Object::Array(items)) => items.iter().filter_map(Object::as_name).collect(),
+            _ => Vec::new(),
+        };
+        let params = dict
+            .get("DecodeParms")
+            .map(|p| self.resolve(p))
+            .and_then(|p| match p {
+                Object::Array(items) => items.first().map(|i| self.resolve(i)),
+                other => Some(other),
+            })
+            .and_then(Object::as_dict);
+        let mut data = raw.clone();
+        for filter in filters {
+            data = match filter {
+                "FlateDecode" | "Fl" => match inflate(&data)? {
+                    Some(inflated) => unpredict(inflated, params),
+                    None => return Ok(None),
+                },
+                // Image and other encodings carry no text
+                _ => return Ok(None),
+            };
+        }
+        Ok(Some(data))
+    }
+
+    /// Page objects in document order with their inherited resources
+    fn pages(&self) -> Vec<(u32, Option<Dict>)> {
+        let mut pages = Vec::new();
+        let root = self.objects.values().find_map(|(o, _)| {
+            let d = o.as_dict()?;
+            (d.get("Type").and_then(Object::as_name) == Some("Catalog")).then_some(d)
+        });
+        if let Some(Object::Ref(tree)) = root.and_then(|r| r.get("Pages")) {
+            self.collect_pages(*tree, None, &mut pages, 0);
+        }
+        if pages.is_empty() {
+            let mut numbers: Vec<u32> = self
+                .objects
+                .iter()
+                .filter(|(_, (o, _))| {
+                    o.as_dict()
+                        .and_then(|d| d.get("Type"))
+                        .and_then(Object::as_name)
+                        == Some("Page")
+                })
+                .map(|(n, _)| *n)
+                .collect();
+            numbers.sort_unstable();
+            pages = numbers.into_iter().map(|n| (n, None)).collect();
+        }
+        pages
+    }
+
+    fn collect_pages(
+        &self,
+        number: u32,
+        inherited: Option<Dict>,
+        pages: &mut Vec<(u32, Option<Dict>)>,
+        depth: usize,
+    ) {
+        if depth > 64 {
+            return;
+        }
+        let Some(dict) = self.objects.get(&number).and_then(|(o, _)| o.as_dict()) else {
+            return;
+        };
+        let resources = self.get_dict(dict, "Resources").cloned().or(inherited);
+        match dict.get("Kids").map(|k| self.resolve(k)) {
+            Some(Object::Array(kids)) => {
+                for kid in kids {
+                    if let Object::Ref(kid) = kid {
+                        self.collect_pages(*kid, resources.clone(), pages, depth + 1);
+                    }
+                }
+            }
+            _ => pages.push((number, resources)),
+        }
+    }
+}
+
+/// Character mapping from a `ToUnicode` CMap
+#[derive(Default)]
+struct CMap {
+    code_len: usize,
+    map: HashMap<u32, String>,
+}
+
+fn utf16_string(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], c.get(1).copied().unwrap_or(0)]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn code_value(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |acc, &b| acc << 8 | b as u32)
+}
+
+impl CMap {
+    fn parse(data: &[u8]) -> Self {
+        let mut lexer = Lexer::new(data, 0);
+        let mut tokens = Vec::new();
+        while let Some(object) = lexer.object() {
+            tokens.push(object);
+        }
+
+        let mut cmap = CMap::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            match &tokens[i] {
+                Object::Keyword(k) if k == "begincodespacerange" => {
+                    if let Some(Object::String(low)) = tokens.get(i + 1) {
+                        cmap.code_len = low.len();
+                    }
+                }
+                Object::Keyword(k) if k == "beginbfchar" => {
+                    i += 1;
+                    while let (Some(Object::String(src)), Some(Object::String(dst))) =
+                        (tokens.get(i), tokens.get(i + 1))
+                    {
+                        cmap.code_len = cmap.code_len.max(src.len());
+                        cmap.map.insert(code_value(src), utf16_string(dst));
+                        i += 2;
+                    }
+                    continue;
+                }
+                Object::Keyword(k) if k == "beginbfrange" => {
+                    i += 1;
+                    while let (Some(Object::String(low)), Some(Object::String(high))) =
+                        (tokens.get(i), tokens.get(i + 1))
+                    {
+                        cmap.code_len = cmap.code_len.max(low.len());
+                        let (low_code, high_code) = (code_value(low), code_value(high));
+                        let span = high_code.saturating_sub(low_code).min(0xffff);
+                        match tokens.get(i + 2) {
+                            Some(Object::String(dst)) if dst.len() >= 2 => {
+                                let mut units: Vec<u16> = dst
+                                    .chunks(2)
+                                    .map(|c| {
+                                        u16::from_be_bytes([c[0], c.get(1).copied().unwrap_or(0)])
+                                    })
+                                    .collect();
+                                let base = units.last().copied().unwrap_or(0);
+                                for offset in 0..=span {
+                                    if let Some(last) = units.last_mut() {
+                                        *last = base.wrapping_add(offset as u16);
+                                    }
+                                    cmap.map.insert(
+                                        low_code + offset,
+                                        String::from_utf16_lossy(&units),
+                                    );
+                                }
+                            }
+                            Some(Object::Array(items)) => {
+                                for (offset, item) in items.iter().enumerate() {
+                                    if let Object::String(dst) = item {
+                                        cmap.map
+                                            .insert(low_code + offset as u32, utf16_string(dst));
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                        i += 3;
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        cmap
+    }
+}
+
+/// Windows-1252 characters that differ from Latin-1
+fn win_ansi(byte: u8) -> char {
+    match byte {
+        0x80 => '€',
+        0x82 => '‚',
+        0x84 => '„',
+        0x85 => '…',
+        0x91 => '‘',
+        0x92 => '’',
+        0x93 => '“',
+        0x94 => '”',
+        0x95 => '•',
+        0x96 => '–',
+        0x97 => '—',
+        0x99 => '™',
+        _ => byte as char,
+    }
+}
+
+/// Character for a glyph name from an encoding `Differences` array
+fn glyph_char(name: &str) -> Option<char> {
+    // Names come from arbitrary bytes, so the four digits may not be ASCII
+    if let Some(hex) = name
+        .strip_prefix("uni")
+        .or_else(|| name.strip_prefix('u'))
+        .and_then(|rest| rest.get(..4))
+        .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+    {
+        if let Some(c) = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+            return Some(c);
+        }
+    }
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c);
+    }
+    Some(match name {
+        "space" => ' ',
+        "period" => '.',
+        "comma" => ',',
+        "colon" => ':',
+        "semicolon" => ';',
+        "hyphen" | "minus" => '-',
+        "endash" => '–',
+        "emdash" => '—',
+        "quoteright" => '’',
+        "quoteleft" => '‘',
+        "quotedblleft" => '“',
+        "quotedblright" => '”',
+        "quotesingle" => '\'',
+        "parenleft" => '(',
+        "parenright" => ')',
+        "slash" => '/',
+        "ampersand" => '&',
+        "percent" => '%',
+        "dollar" => '$',
+        "numbersign" => '#',
+        "at" => '@',
+        "bullet" => '•',
+        "zero" => '0',
+        "one" => '1',
+        "two" => '2',
+        "three" => '3',
+        "four" => '4',
+        "five" => '5',
+        "six" => '6',
+        "seven" => '7',
+        "eight" => '8',
+        "nine" => '9',
+        _ => return None,
+    })
+}
+
+struct Font {
+    /// Composite (Type0) fonts use multi-byte codes
+    composite: bool,
+    to_unicode: Option<CMap>,
+    differences: HashMap<u32, String>,
+}
+
+impl Font {
+    fn load(document: &Document, object: &Object) -> Result<Self> {
+        let dict = document.resolve(object).as_dict();
+        let composite = dict
+            .and_then(|d| d.get("Subtype"))
+            .and_then(Object::as_name)
+            == Some("Type0");
+        let to_unicode = match dict.and_then(|d| d.get("ToUnicode")) {
+            Some(Object::Ref(n)) => document.stream_data(*n)?,
+            _ => None,
+        }
+        .map(|data| CMap::parse(&data))
+        .filter(|cmap| !cmap.map.is_empty());
+
+        let mut differences = HashMap::new();
+        if let Some(Object::Array(items)) = dict
+            .and_then(|d| document.get_dict(d, "Encoding"))
+            .and_then(|e| e.get("Differences"))
+            .map(|d| document.resolve(d))
+        {
+            let mut code = 0u32;
+            for item in items {
+                match item {
+                    Object::Number(n) => code = *n as u32,
+                    Object::Name(name) => {
+                        differences.insert(code, name.clone());
+                        code += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(Self {
+            composite,
+            to_unicode,
+            differences,
+        })
+    }
+
+    fn decode(&self, bytes: &[u8], out: &mut String) {
+        let code_len = match &self.to_unicode {
+            Some(cmap) if cmap.code_len > 0 => cmap.code_len,
+            _ if self.composite => 2,
+            _ => 1,
+        };
+        for code in bytes.chunks(code_len) {
+            let value = code_value(code);
+            if let Some(text) = self.to_unicode.as_ref().and_then(|c| c.map.get(&value)) {
+                out.push_str(text);
+            } else if self.composite {
+                // Glyph ids without a Unicode mapping cannot be recovered
+                continue;
+            } else if let Some(c) = self.differences.get(&value).and_then(|n| glyph_char(n)) {
+                out.push(c);
+            } else {
+                out.push(win_ansi(value as u8));
+            }
+        }
+    }
+}
+
+struct TextWriter {
+    text: String,
+    line_y: f64,
+}
+
+impl TextWriter {
+    fn newline(&mut self) {
+        while self.text.ends_with(' ') {
+            self.text.pop();
+        }
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+    }
+
+    fn space(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with(char::is_whitespace) {
+            self.text.push(' ');
+        }
+    }
+
+    /// Move to a new baseline, breaking the line if it changed
+    fn move_to(&mut self, y: f64) {
+        if (y - self.line_y).abs() > 1.0 {
+            self.newline();
+        } else {
+            self.space();
+        }
+        self.line_y = y;
+    }
+}
+
+struct Extractor<'a> {
+    document: &'a Document,
+    fonts: HashMap<u32, Rc<Font>>,
+}
+
+impl Extractor<'_> {
+    fn font(&mut self, resources: Option<&Dict>, name: &str) -> Result<Option<Rc<Font>>> {
+        let Some(reference) = resources
+            .and_then(|r| self.document.get_dict(r, "Font"))
+            .and_then(|fonts| fonts.get(name))
+        else {
+            return Ok(None);
+        };
+        let font = match reference {
+            Object::Ref(n) => match self.fonts.get(n) {
+                Some(font) => font.clone(),
+                None => {
+                    let font = Rc::new(Font::load(self.document, reference)?);
+                    self.fonts.insert(*n, font.clone());
+                    font
+                }
+            },
+            inline => Rc::new(Font::load(self.document, inline)?),
+        };
+        Ok(Some(font))
+    }
+
+    fn run(
+        &mut self,
+        content: &[u8],
+        resources: Option<&Dict>,
+        out: &mut TextWriter,
+        depth: usize,
+    ) -> Result<()> {
+        let mut lexer = Lexer::new(content, 0);
+        let mut operands: Vec<Object> = Vec::new();
+        let mut font: Option<Rc<Font>> = None;
+
+        while let Some(object) = lexer.object() {
+            let Object::Keyword(operator) = object else {
+                operands.push(object);
+                continue;
+            };
+            let number = |i: usize| operands.get(i).and_then(Object::as_number).unwrap_or(0.0);
+            match operator.as_str() {
+                "Tf" => {
+                    if let Some(name) = operands.first().and_then(Object::as_name) {
+                        font = self.font(resources, name)?;
+                    }
+                }
+                "Td" | "TD" => {
+                    let (tx, ty) = (number(0), number(1));
+                    if ty.abs() > 1.0 {
+                        out.newline();
+                    } else if tx.abs() > 1.0 {
+                        out.space();
+                    }
+                    out.line_y += ty;
+                }
+                "Tm" => out.move_to(number(5)),
+                "T*" => out.newline(),
+                "Tj" | "'" | "\"" => {
+                    if operator != "Tj" {
+                        out.newline();
+                    }
+                    if let (Some(Object::String(bytes)), Some(font)) = (operands.last(), &font) {
+                        font.decode(bytes, &mut out.text);
+                    }
+                }
+                "TJ" => {
+                    if let (Some(Object::Array(items)), Some(font)) = (operands.last(), &font) {
+                        for item in items {
+                            match item {
+                                Object::String(bytes) => font.decode(bytes, &mut out.text),
+                                // Large negative kerning is how many generators encode spaces
+                                Object::Number(n) if *n < -200.0 => out.space(),
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                "ET" => out.space(),
+                "ID" => lexer.skip_inline_image(),
+                "Do" if depth < MAX_FORM_DEPTH => {
+                    let form = operands
+                        .first()
+                        .and_then(Object::as_name)
+                        .and_then(|name| {
+                            resources
+                                .and_then(|r| self.document.get_dict(r, "XObject"))
+                                .and_then(|x| x.get(name))
+                        })
+                        .and_then(|o| match o {
+                            Object::Ref(n) => Some(*n),
+                            _ => None,
+                        });
+                    if let Some(number) = form {
+                        let is_form = self
+                            .document
+                            .objects
+                            .get(&number)
+                            .and_then(|(o, _)| o.as_dict())
+                            .and_then(|d| d.get("Subtype"))
+                            .and_then(Object::as_name)
+                            == Some("Form");
+                        let data = if is_form {
+                            self.document.stream_data(number)?
+                        } else {
+                            None
+                        };
+                        if let Some(data) = data {
+                            let form_resources = self
+                                .document
+                                .objects
+                                .get(&number)
+                                .and_then(|(o, _)| o.as_dict())
+                                .and_then(|d| self.document.get_dict(d, "Resources"))
+                                .cloned();
+                            self.run(&data, form_resources.as_ref().or(resources), out, depth + 1)?;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            operands.clear();
+        }
+        Ok(())
+    }
+}
+
+/// Extract the text of each page
+pub fn extract_pages(bytes: &[u8]) -> Result<Vec<String>> {
+    let document = Document::parse(bytes)?;
+    let mut extractor = Extractor {
+        document: &document,
+        fonts: HashMap::new(),
+    };
+
+    let mut pages = Vec::new();
+    for (number, resources) in document.pages() {
+        let Some(page) = document.objects.get(&number).and_then(|(o, _)| o.as_dict()) else {
+            continue;
+        };
+        let resources = document.get_dict(page, "Resources").cloned().or(resources);
+        let contents: Vec<u32> = match page.get("Contents").map(|c| match c {
+            Object::Ref(_) => match document.resolve(c) {
+                Object::Array(items) => Object::Array(items.clone()),
+                _ => c.clone(),
+            },
+            other => other.clone(),
+        }) {
+            Some(Object::Ref(n)) => vec![n],
+            Some(Object::Array(items)) => items
+                .iter()
+                .filter_map(|i| match i {
+                    Object::Ref(n) => Some(*n),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        // Content streams may split operators across parts, so join them first
+        let mut content = Vec::new();
+        for part in contents {
+            if let Some(data) = document.stream_data(part)? {
+                content.extend_from_slice(&data);
+                content.push(b'\n');
+            }
+        }
+        let mut writer = TextWriter {
+            text: String::new(),
+            line_y: f64::NAN,
+        };
+        extractor.run(&content, resources.as_ref(), &mut writer, 0)?;
+        let text = writer
+            .text
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n");
+        pages.push(text.trim().to_string());
+    }
+
+    if pages.is_empty() {
+        return Err(pdf_error("PDF contains no pages"));
+    }
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn extracts_text_from_compressed_page() {
+        let content = b"BT /F1 12 Tf 72 720 Td (Offers due) Tj [( March) -300 (3, 2025)] TJ \
+                        0 -14 Td (Section C \\(SOW\\)) Tj ET";
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut pdf = b"%PDF-1.4\n\
+            1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n\
+            2 0 obj << /Type /Pages /Kids [3 0 R] /Count 1 \
+            /Resources << /Font << /F1 4 0 R >> >> >> endobj\n\
+            3 0 obj << /Type /Page /Parent 2 0 R /Contents 5 0 R >> endobj\n\
+            4 0 obj << /Type /Font /Subtype /Type1 /BaseFont /Helvetica >> endobj\n"
+            .to_vec();
+        pdf.extend_from_slice(
+            format!(
+                "5 0 obj << /Length {} /Filter /FlateDecode >>\nstream\n",
+                compressed.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&compressed);
+        pdf.extend_from_slice(b"\nendstream\nendobj\ntrailer << /Root 1 0 R >>\n%%EOF");
+
+        let pages = extract_pages(&pdf).unwrap();
+        assert_eq!(
+            pages,
+            vec!["Offers due March 3, 2025\nSection C (SOW)".to_string()]
+        );
+    }
+
+    #[test]
+    fn refuses_streams_that_inflate_past_the_limit() {
+        let compress = |size: usize| {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            let chunk = vec![0u8; 1024 * 1024];
+            let mut left = size;
+            while left > 0 {
+                let n = left.min(chunk.len());
+                encoder.write_all(&chunk[..n]).unwrap();
+                left -= n;
+            }
+            encoder.finish().unwrap()
+        };
+        assert_eq!(inflate(&compress(1024)).unwrap().unwrap().len(), 1024);
+        let bomb = compress(MAX_STREAM_SIZE + 1);
+        assert!(bomb.len() < 1024 * 1024);
+        assert!(inflate(&bomb).is_err());
+    }
+
+    #[test]
+    fn maps_glyph_names_without_slicing_inside_characters() {
+        assert_eq!(glyph_char("uni20AC"), Some('€'));
+        assert_eq!(glyph_char("u00E9"), Some('é'));
+        assert_eq!(glyph_char("emdash"), Some('—'));
+        // Multi-byte characters straddling the fourth byte
+        assert_eq!(glyph_char("uni00€"), None);
+        assert_eq!(glyph_char("u000é"), None);
+        assert_eq!(glyph_char("u+041"), None);
+        assert_eq!(glyph_char("é"), Some('é'));
+    }
+}
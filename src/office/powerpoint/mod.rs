@@ -513,7 +513,8 @@ impl<'a> PowerPointClient<'a> {
                     ToolAnnotation::new("slide_manager")
                         .with_description("Gets all slides in a presentation"),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "save_presentation",
                 "Save a presentation to a file",
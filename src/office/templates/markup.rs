@@ -0,0 +1,212 @@
+//! Lightweight document markup produced by rendered templates
+//!
+//! Templates render to a small Markdown subset that every output format can
+//! represent: `#` headings, paragraphs, `- ` bullets, `|` tables (a `|---|`
+//! row marks the row above it as the header), `---` rules and `**bold**` runs.
+
+/// A run of inline text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub text: String,
+    pub bold: bool,
+}
+
+/// Block-level document element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    Heading(u8, Vec<Run>),
+    Paragraph(Vec<Run>),
+    Bullet(Vec<Run>),
+    Table {
+        header: Option<Vec<String>>,
+        rows: Vec<Vec<String>>,
+    },
+    Rule,
+}
+
+/// Table header and rows collected while parsing
+type PendingTable = (Option<Vec<String>>, Vec<Vec<String>>);
+
+/// Split `**bold**` markers into runs
+pub fn parse_inline(text: &str) -> Vec<Run> {
+    let mut runs = Vec::new();
+    for (i, part) in text.split("**").enumerate() {
+        if !part.is_empty() {
+            runs.push(Run {
+                text: part.to_string(),
+                bold: i % 2 == 1,
+            });
+        }
+    }
+    runs
+}
+
+/// Plain text of a list of runs
+pub fn plain(runs: &[Run]) -> String {
+    runs.iter().map(|r| r.text.as_str()).collect()
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    line.trim()
+        .trim_start_matches('|')
+        .trim_end_matches('|')
+        .split('|')
+        .map(|cell| cell.trim().replace("**", ""))
+        .collect()
+}
+
+fn is_separator_row(line: &str) -> bool {
+    let cells = table_cells(line);
+    !cells.is_empty()
+        && cells
+            .iter()
+            .all(|c| !c.is_empty() && c.chars().all(|ch| matches!(ch, '-' | ':')))
+}
+
+/// Parse rendered markup into blocks
+pub fn parse(source: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut table: Option<PendingTable> = None;
+
+    fn flush_paragraph(paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>) {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(parse_inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    }
+
+    fn flush_table(table: &mut Option<PendingTable>, blocks: &mut Vec<Block>) {
+        if let Some((header, rows)) = table.take() {
+            blocks.push(Block::Table { header, rows });
+        }
+    }
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with('|') {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            let (header, rows) = table.get_or_insert((None, Vec::new()));
+            if is_separator_row(trimmed) {
+                if header.is_none() {
+                    *header = rows.pop();
+                }
+            } else {
+                rows.push(table_cells(trimmed));
+            }
+            continue;
+        }
+        flush_table(&mut table, &mut blocks);
+
+        if trimmed.is_empty() {
+            flush_paragraph(&mut paragraph, &mut blocks);
+        } else if trimmed == "---" {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            blocks.push(Block::Rule);
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            blocks.push(Block::Bullet(parse_inline(item.trim())));
+        } else if trimmed.starts_with('#') {
+            let level = trimmed.chars().take_while(|c| *c == '#').count();
+            let text = trimmed[level..].trim();
+            if level <= 6 && !text.is_empty() {
+                flush_paragraph(&mut paragraph, &mut blocks);
+                blocks.push(Block::Heading(level as u8, parse_inline(text)));
+            } else {
+                paragraph.push(trimmed);
+            }
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    flush_table(&mut table, &mut blocks);
+    flush_paragraph(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn runs_to_html(runs: &[Run]) -> String {
+    runs.iter()
+        .map(|r| {
+            if r.bold {
+                format!("<strong>{}</strong>", escape_html(&r.text))
+            } else {
+                escape_html(&r.text)
+            }
+        })
+        .collect()
+}
+
+/// Render blocks as a standalone HTML document
+pub fn to_html(title: &str, blocks: &[Block]) -> String {
+    let mut body = String::new();
+    let mut in_list = false;
+
+    for block in blocks {
+        let is_bullet = matches!(block, Block::Bullet(_));
+        if in_list && !is_bullet {
+            body.push_str("</ul>\n");
+            in_list = false;
+        }
+        match block {
+            Block::Heading(level, runs) => {
+                body.push_str(&format!("<h{0}>{1}</h{0}>\n", level, runs_to_html(runs)));
+            }
+            Block::Paragraph(runs) => {
+                body.push_str(&format!("<p>{}</p>\n", runs_to_html(runs)));
+            }
+            Block::Bullet(runs) => {
+                if !in_list {
+                    body.push_str("<ul>\n");
+                    in_list = true;
+                }
+                body.push_str(&format!("<li>{}</li>\n", runs_to_html(runs)));
+            }
+            Block::Table { header, rows } => {
+                body.push_str("<table>\n");
+                if let Some(header) = header {
+                    body.push_str("<thead><tr>");
+                    for cell in header {
+                        body.push_str(&format!("<th>{}</th>", escape_html(cell)));
+                    }
+                    body.push_str("</tr></thead>\n");
+                }
+                body.push_str("<tbody>\n");
+                for row in rows {
+                    body.push_str("<tr>");
+                    for cell in row {
+                        body.push_str(&format!("<td>{}</td>", escape_html(cell)));
+                    }
+                    body.push_str("</tr>\n");
+                }
+                body.push_str("</tbody>\n</table>\n");
+            }
+            Block::Rule => body.push_str("<hr>\n"),
+        }
+    }
+    if in_list {
+        body.push_str("</ul>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+         body {{ font-family: Helvetica, Arial, sans-serif; max-width: 48em; margin: 2em auto; color: #222; }}\n\
+         table {{ border-collapse: collapse; width: 100%; margin: 1em 0; }}\n\
+         th, td {{ border: 1px solid #ccc; padding: 0.4em 0.6em; text-align: left; }}\n\
+         th {{ background: #f3f3f3; }}\n\
+         hr {{ border: 0; border-top: 1px solid #ccc; }}\n\
+         </style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        body
+    )
+}
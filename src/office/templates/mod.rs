@@ -0,0 +1,535 @@
+/// Data-driven document generation
+///
+/// Documents are produced from Tera templates plus JSON data. Templates render
+/// to a small Markdown subset (see [`markup`]) which is then written out as
+/// HTML, DOCX or PDF. A library of built-in templates covers invoices,
+/// statements of work and status reports; callers can register their own.
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolAnnotation, ToolDefinition};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::str::FromStr;
+use tera::{Context, Tera};
+
+mod docx;
+pub mod markup;
+mod pdf;
+
+const BUILTIN_TEMPLATES: &[(&str, &str, &str)] = &[
+    (
+        "invoice",
+        "Invoice with line items, optional tax rate and notes",
+        include_str!("builtin/invoice.md"),
+    ),
+    (
+        "sow",
+        "Statement of work with scope, deliverables, fees and assumptions",
+        include_str!("builtin/sow.md"),
+    ),
+    (
+        "status_report",
+        "Project status report with accomplishments, milestones and risks",
+        include_str!("builtin/status_report.md"),
+    ),
+];
+
+/// Name used for ad-hoc templates passed as source
+const INLINE_TEMPLATE: &str = "__inline__";
+
+/// Document output format
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Html,
+    Docx,
+    Pdf,
+}
+
+impl OutputFormat {
+    /// File extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Html => "html",
+            OutputFormat::Docx => "docx",
+            OutputFormat::Pdf => "pdf",
+        }
+    }
+
+    /// MIME type for the format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Html => "text/html",
+            OutputFormat::Docx => {
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            }
+            OutputFormat::Pdf => "application/pdf",
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "html" | "htm" => Ok(OutputFormat::Html),
+            "docx" | "word" => Ok(OutputFormat::Docx),
+            "pdf" => Ok(OutputFormat::Pdf),
+            other => Err(Error::validation_with_field(
+                format!("Unsupported output format: {}", other),
+                "format",
+            )),
+        }
+    }
+}
+
+/// Template metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateInfo {
+    /// Template name
+    pub name: String,
+    /// What the template produces
+    pub description: String,
+    /// Whether the template ships with the module
+    pub builtin: bool,
+}
+
+/// A rendered document
+#[derive(Debug, Clone)]
+pub struct RenderedDocument {
+    /// Document title, taken from the first heading
+    pub title: String,
+    /// Output format
+    pub format: OutputFormat,
+    /// Encoded document bytes
+    pub content: Vec<u8>,
+}
+
+/// Formats a number with two decimals and thousands separators
+fn money_filter(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let amount = value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+        .ok_or_else(|| tera::Error::msg(format!("money filter expects a number, got {}", value)))?;
+
+    let formatted = format!("{:.2}", amount.abs());
+    let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, "00"));
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    let sign = if amount < 0.0 { "-" } else { "" };
+    Ok(Value::String(format!("{}{}.{}", sign, grouped, fraction)))
+}
+
+/// Template library and document renderer
+#[derive(Debug, Clone)]
+pub struct DocumentTemplates {
+    tera: Tera,
+    templates: BTreeMap<String, TemplateInfo>,
+}
+
+impl DocumentTemplates {
+    /// Create a library with the built-in templates registered
+    pub fn new() -> Result<Self> {
+        let mut tera = Tera::default();
+        tera.autoescape_on(vec![]);
+        tera.register_filter("money", money_filter);
+
+        let mut library = Self {
+            tera,
+            templates: BTreeMap::new(),
+        };
+        for (name, description, source) in BUILTIN_TEMPLATES {
+            library.add(name, description, source, true)?;
+        }
+        Ok(library)
+    }
+
+    fn add(&mut self, name: &str, description: &str, source: &str, builtin: bool) -> Result<()> {
+        self.tera.add_raw_template(name, source).map_err(|e| {
+            Error::parsing_with_format(
+                format!("Invalid template '{}': {}", name, template_error(&e)),
+                "tera",
+                None,
+            )
+        })?;
+        self.templates.insert(
+            name.to_string(),
+            TemplateInfo {
+                name: name.to_string(),
+                description: description.to_string(),
+                builtin,
+            },
+        );
+        Ok(())
+    }
+
+    /// Register a custom template, replacing any template with the same name
+    pub fn register_template(&mut self, name: &str, description: &str, source: &str) -> Result<()> {
+        if name == INLINE_TEMPLATE {
+            return Err(Error::validation_with_field(
+                "Reserved template name",
+                "name",
+            ));
+        }
+        self.add(name, description, source, false)
+    }
+
+    /// Register every `*.md` file in a directory, named after its file stem
+    pub async fn load_directory(&mut self, dir: impl AsRef<Path>) -> Result<usize> {
+        let dir = dir.as_ref();
+        let mut entries = tokio::fs::read_dir(dir).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to read template directory: {}", e),
+                dir.to_path_buf(),
+            )
+        })?;
+
+        let mut loaded = 0;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| Error::io(format!("Failed to read template directory: {}", e)))?
+        {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            let Some(name) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            let source = tokio::fs::read_to_string(&path).await.map_err(|e| {
+                Error::io_with_path(format!("Failed to read template: {}", e), path.clone())
+            })?;
+            self.register_template(
+                &name,
+                &format!("Custom template from {}", path.display()),
+                &source,
+            )?;
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// List available templates
+    pub fn list_templates(&self) -> Vec<TemplateInfo> {
+        self.templates.values().cloned().collect()
+    }
+
+    fn context(data: &Value) -> Result<Context> {
+        if !data.is_object() {
+            return Err(Error::validation_with_field(
+                "Template data must be a JSON object",
+                "data",
+            ));
+        }
+        Context::from_value(data.clone())
+            .map_err(|e| Error::validation(format!("Invalid template data: {}", e)))
+    }
+
+    /// Render a registered template to markup
+    pub fn render_markup(&self, name: &str, data: &Value) -> Result<String> {
+        if !self.templates.contains_key(name) {
+            return Err(Error::not_found_with_resource(
+                "Template not found",
+                "template",
+                name,
+            ));
+        }
+        self.tera.render(name, &Self::context(data)?).map_err(|e| {
+            Error::validation(format!(
+                "Failed to render '{}': {}",
+                name,
+                template_error(&e)
+            ))
+        })
+    }
+
+    /// Render an ad-hoc template source to markup
+    pub fn render_source_markup(&self, source: &str, data: &Value) -> Result<String> {
+        let mut tera = self.tera.clone();
+        tera.add_raw_template(INLINE_TEMPLATE, source)
+            .map_err(|e| {
+                Error::parsing_with_format(
+                    format!("Invalid template: {}", template_error(&e)),
+                    "tera",
+                    None,
+                )
+            })?;
+        tera.render(INLINE_TEMPLATE, &Self::context(data)?)
+            .map_err(|e| {
+                Error::validation(format!("Failed to render template: {}", template_error(&e)))
+            })
+    }
+
+    /// Render a registered template to a document
+    pub fn render(
+        &self,
+        name: &str,
+        data: &Value,
+        format: OutputFormat,
+    ) -> Result<RenderedDocument> {
+        let markup = self.render_markup(name, data)?;
+        Self::encode(&markup, name, format)
+    }
+
+    /// Render an ad-hoc template source to a document
+    pub fn render_source(
+        &self,
+        source: &str,
+        data: &Value,
+        format: OutputFormat,
+    ) -> Result<RenderedDocument> {
+        let markup = self.render_source_markup(source, data)?;
+        Self::encode(&markup, "Document", format)
+    }
+
+    fn encode(
+        source: &str,
+        fallback_title: &str,
+        format: OutputFormat,
+    ) -> Result<RenderedDocument> {
+        let blocks = markup::parse(source);
+        let title = blocks
+            .iter()
+            .find_map(|b| match b {
+                markup::Block::Heading(_, runs) => Some(markup::plain(runs)),
+                _ => None,
+            })
+            .unwrap_or_else(|| fallback_title.to_string());
+
+        let content = match format {
+            OutputFormat::Html => markup::to_html(&title, &blocks).into_bytes(),
+            OutputFormat::Docx => docx::write(&title, &blocks)?,
+            OutputFormat::Pdf => pdf::write(&title, &blocks),
+        };
+        Ok(RenderedDocument {
+            title,
+            format,
+            content,
+        })
+    }
+
+    /// Get tool definitions for document templating
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let names: Vec<&String> = self.templates.keys().collect();
+        vec![
+            ToolDefinition::from_json_schema(
+                "render_document",
+                "Render an invoice, SOW, status report or custom template with JSON data to HTML, DOCX or PDF",
+                "document",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "template": {
+                            "type": "string",
+                            "description": format!("Template name ({})", names.iter().map(|n| n.as_str()).collect::<Vec<_>>().join(", "))
+                        },
+                        "source": {
+                            "type": "string",
+                            "description": "Ad-hoc Tera template source, used instead of a named template"
+                        },
+                        "data": {
+                            "type": "object",
+                            "description": "Template data"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["html", "docx", "pdf"],
+                            "default": "html"
+                        },
+                        "output_path": {
+                            "type": "string",
+                            "description": "Write the document to this path instead of returning it inline"
+                        }
+                    },
+                    "required": ["data"]
+                }),
+                Some(
+                    ToolAnnotation::new("document_creator")
+                        .with_description("Generates documents from templates"),
+                ),
+            ),
+            ToolDefinition::from_json_schema(
+                "list_document_templates",
+                "List available document templates",
+                "document",
+                json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    /// Execute a document templating tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "render_document" => self.render_document_tool(parameters).await,
+            "list_document_templates" => {
+                let templates = self.list_templates();
+                let text = templates
+                    .iter()
+                    .map(|t| format!("{}: {}", t.name, t.description))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok(call_result(text, json!({ "templates": templates })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "document_tool",
+                name,
+            )),
+        }
+    }
+
+    async fn render_document_tool(&self, parameters: Value) -> Result<Value> {
+        let data = parameters.get("data").cloned().unwrap_or_else(|| json!({}));
+        let format = parameters
+            .get("format")
+            .and_then(|f| f.as_str())
+            .map(OutputFormat::from_str)
+            .transpose()?
+            .unwrap_or(OutputFormat::Html);
+
+        let document = match (
+            parameters.get("template").and_then(|t| t.as_str()),
+            parameters.get("source").and_then(|s| s.as_str()),
+        ) {
+            (_, Some(source)) => self.render_source(source, &data, format)?,
+            (Some(template), None) => self.render(template, &data, format)?,
+            (None, None) => {
+                return Err(Error::validation_with_field(
+                    "Either template or source is required",
+                    "template",
+                ))
+            }
+        };
+
+        let mut structured = json!({
+            "title": document.title,
+            "format": format,
+            "mime_type": format.mime_type(),
+            "size_bytes": document.content.len()
+        });
+
+        if let Some(path) = parameters.get("output_path").and_then(|p| p.as_str()) {
+            tokio::fs::write(path, &document.content)
+                .await
+                .map_err(|e| {
+                    Error::io_with_path(format!("Failed to write document: {}", e), path.into())
+                })?;
+            structured["path"] = json!(path);
+            return Ok(call_result(
+                format!(
+                    "Wrote {} ({} bytes) to {}",
+                    document.title,
+                    document.content.len(),
+                    path
+                ),
+                structured,
+            ));
+        }
+
+        let text = match format {
+            OutputFormat::Html => {
+                let html = String::from_utf8_lossy(&document.content).into_owned();
+                structured["content"] = json!(html);
+                html
+            }
+            OutputFormat::Docx | OutputFormat::Pdf => {
+                structured["content_base64"] =
+                    json!(base64::engine::general_purpose::STANDARD.encode(&document.content));
+                format!(
+                    "Rendered {} as {} ({} bytes, base64 in structured content)",
+                    document.title,
+                    format.extension().to_uppercase(),
+                    document.content.len()
+                )
+            }
+        };
+        Ok(call_result(text, structured))
+    }
+}
+
+/// Flatten a Tera error chain into one message
+fn template_error(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoice_data() -> Value {
+        json!({
+            "invoice_number": "INV-1001",
+            "issue_date": "2025-01-15",
+            "from": { "name": "Acme Consulting" },
+            "to": { "name": "Globex", "email": "ap@globex.test" },
+            "tax_rate": 10,
+            "items": [
+                { "description": "Architecture review", "quantity": 10, "unit_price": 150 },
+                { "description": "Workshop", "quantity": 1, "unit_price": 1200.5 }
+            ]
+        })
+    }
+
+    #[test]
+    fn renders_builtin_invoice() {
+        let library = DocumentTemplates::new().unwrap();
+        let markup = library.render_markup("invoice", &invoice_data()).unwrap();
+        assert!(markup.contains("| Workshop | 1 | 1,200.50 | 1,200.50 |"));
+        assert!(markup.contains("**Total due:** USD 2,970.55"));
+
+        let blocks = markup::parse(&markup);
+        assert!(matches!(&blocks[0], markup::Block::Heading(1, _)));
+        assert!(blocks.iter().any(|b| matches!(
+            b,
+            markup::Block::Table { header: Some(header), rows } if header.len() == 4 && rows.len() == 2
+        )));
+    }
+
+    #[test]
+    fn encodes_all_formats() {
+        let library = DocumentTemplates::new().unwrap();
+        let data = invoice_data();
+
+        let html = library
+            .render("invoice", &data, OutputFormat::Html)
+            .unwrap();
+        assert_eq!(html.title, "Invoice INV-1001");
+        assert!(String::from_utf8(html.content)
+            .unwrap()
+            .contains("<th>Quantity</th>"));
+
+        let docx = library
+            .render("invoice", &data, OutputFormat::Docx)
+            .unwrap();
+        assert!(docx.content.starts_with(b"PK"));
+
+        let pdf = library.render("invoice", &data, OutputFormat::Pdf).unwrap();
+        assert!(pdf.content.starts_with(b"%PDF-1.4"));
+        assert!(pdf.content.ends_with(b"%%EOF\n"));
+    }
+}
@@ -0,0 +1,351 @@
+/// Minimal PDF writer using the standard Helvetica fonts
+///
+/// Text is encoded as WinAnsi, so characters outside Latin-1 (plus the usual
+/// typographic quotes, dashes, bullet and euro sign) are replaced with `?`.
+use super::markup::{Block, Run};
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const BODY_SIZE: f32 = 10.5;
+const TABLE_SIZE: f32 = 9.5;
+
+fn encode_char(c: char) -> u8 {
+    match c {
+        '\u{20ac}' => 0x80,
+        '\u{2018}' => 0x91,
+        '\u{2019}' => 0x92,
+        '\u{201c}' => 0x93,
+        '\u{201d}' => 0x94,
+        '\u{2022}' => 0x95,
+        '\u{2013}' => 0x96,
+        '\u{2014}' => 0x97,
+        c if (c as u32) >= 0x20 && (c as u32) <= 0xff && !(0x7f..0xa0).contains(&(c as u32)) => {
+            c as u8
+        }
+        _ => b'?',
+    }
+}
+
+/// Encode text as a PDF literal string
+fn pdf_string(text: &str) -> String {
+    let mut out = String::from("(");
+    for byte in text.chars().map(encode_char) {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                out.push('\\');
+                out.push(byte as char);
+            }
+            0x20..=0x7e => out.push(byte as char),
+            _ => out.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// Approximate Helvetica advance width in em
+fn char_width(c: char, bold: bool) -> f32 {
+    let width = match c {
+        ' ' | 'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '\'' | '|' | '!' => 0.28,
+        'f' | 't' | 'r' | 'I' | '(' | ')' | '-' => 0.36,
+        'm' | 'w' | 'M' | 'W' | '@' => 0.85,
+        'A'..='Z' => 0.68,
+        _ => 0.56,
+    };
+    if bold {
+        width * 1.05
+    } else {
+        width
+    }
+}
+
+fn text_width(text: &str, size: f32, bold: bool) -> f32 {
+    text.chars().map(|c| char_width(c, bold)).sum::<f32>() * size
+}
+
+fn font(bold: bool) -> &'static str {
+    if bold {
+        "F2"
+    } else {
+        "F1"
+    }
+}
+
+/// Truncate text with an ellipsis so it fits the given width
+fn fit(text: &str, size: f32, bold: bool, width: f32) -> String {
+    if text_width(text, size, bold) <= width {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    for c in text.chars() {
+        if text_width(&out, size, bold) + text_width("...", size, bold) + char_width(c, bold) * size
+            > width
+        {
+            break;
+        }
+        out.push(c);
+    }
+    out.push_str("...");
+    out
+}
+
+struct Layout {
+    pages: Vec<String>,
+    current: String,
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            current: String::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < MARGIN && !self.current.is_empty() {
+            self.pages.push(std::mem::take(&mut self.current));
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn text(&mut self, x: f32, y: f32, size: f32, bold: bool, text: &str) {
+        self.current.push_str(&format!(
+            "BT /{} {:.1} Tf {:.2} {:.2} Td {} Tj ET\n",
+            font(bold),
+            size,
+            x,
+            y,
+            pdf_string(text)
+        ));
+    }
+
+    /// Word-wrap runs into the content width starting at `indent`
+    fn flow(&mut self, runs: &[Run], size: f32, indent: f32, force_bold: bool) {
+        let leading = size * 1.35;
+        let max_width = CONTENT_WIDTH - indent;
+        let words: Vec<(String, bool)> = runs
+            .iter()
+            .flat_map(|r| {
+                r.text
+                    .split_whitespace()
+                    .map(move |w| (w.to_string(), r.bold || force_bold))
+            })
+            .collect();
+
+        let mut lines: Vec<Vec<(String, bool)>> = vec![Vec::new()];
+        let mut width = 0.0;
+        for (word, bold) in words {
+            let word_width = text_width(&word, size, bold);
+            let space = text_width(" ", size, bold);
+            let line = lines.last_mut().expect("at least one line");
+            if !line.is_empty() && width + space + word_width > max_width {
+                lines.push(vec![(word, bold)]);
+                width = word_width;
+            } else {
+                if !line.is_empty() {
+                    width += space;
+                }
+                width += word_width;
+                line.push((word, bold));
+            }
+        }
+
+        for line in lines {
+            self.ensure(leading);
+            self.y -= leading;
+            let mut x = MARGIN + indent;
+            // Merge consecutive words that share a font into one text object
+            let mut segment = String::new();
+            let mut segment_bold = None;
+            for (word, bold) in line {
+                if segment_bold.is_some_and(|b| b != bold) {
+                    segment.push(' ');
+                    self.text(x, self.y, size, segment_bold.unwrap_or(false), &segment);
+                    x += text_width(&segment, size, segment_bold.unwrap_or(false));
+                    segment.clear();
+                }
+                if !segment.is_empty() {
+                    segment.push(' ');
+                }
+                segment.push_str(&word);
+                segment_bold = Some(bold);
+            }
+            if let Some(bold) = segment_bold {
+                self.text(x, self.y, size, bold, &segment);
+            }
+        }
+    }
+
+    fn table(&mut self, header: &Option<Vec<String>>, rows: &[Vec<String>]) {
+        let columns = header
+            .iter()
+            .chain(rows.iter())
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let column_width = CONTENT_WIDTH / columns as f32;
+        let row_height = TABLE_SIZE * 1.9;
+
+        let draw_row = |layout: &mut Layout, cells: &[String], bold: bool| {
+            layout.ensure(row_height);
+            layout.y -= row_height;
+            if bold {
+                layout.current.push_str(&format!(
+                    "0.95 g {:.2} {:.2} {:.2} {:.2} re f 0 g\n",
+                    MARGIN, layout.y, CONTENT_WIDTH, row_height
+                ));
+            }
+            layout.current.push_str(&format!(
+                "0.75 G 0.5 w {:.2} {:.2} {:.2} {:.2} re S 0 G\n",
+                MARGIN, layout.y, CONTENT_WIDTH, row_height
+            ));
+            for (i, cell) in cells.iter().enumerate() {
+                let x = MARGIN + i as f32 * column_width;
+                if i > 0 {
+                    layout.current.push_str(&format!(
+                        "0.75 G {:.2} {:.2} m {:.2} {:.2} l S 0 G\n",
+                        x,
+                        layout.y,
+                        x,
+                        layout.y + row_height
+                    ));
+                }
+                let text = fit(cell, TABLE_SIZE, bold, column_width - 8.0);
+                let baseline = layout.y + (row_height - TABLE_SIZE) / 2.0 + 1.5;
+                layout.text(x + 4.0, baseline, TABLE_SIZE, bold, &text);
+            }
+        };
+
+        self.y -= 4.0;
+        if let Some(header) = header {
+            draw_row(self, header, true);
+        }
+        for row in rows {
+            draw_row(self, row, false);
+        }
+        self.y -= 8.0;
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if !self.current.is_empty() || self.pages.is_empty() {
+            self.pages.push(self.current);
+        }
+        self.pages
+    }
+}
+
+fn layout(blocks: &[Block]) -> Vec<String> {
+    let mut layout = Layout::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, runs) => {
+                let size = match level {
+                    1 => 20.0,
+                    2 => 15.0,
+                    3 => 12.5,
+                    _ => 11.0,
+                };
+                layout.ensure(size * 3.0);
+                layout.y -= size * 0.6;
+                layout.flow(runs, size, 0.0, true);
+                layout.y -= size * 0.3;
+            }
+            Block::Paragraph(runs) => {
+                layout.flow(runs, BODY_SIZE, 0.0, false);
+                layout.y -= BODY_SIZE * 0.6;
+            }
+            Block::Bullet(runs) => {
+                layout.ensure(BODY_SIZE * 1.35);
+                let y = layout.y - BODY_SIZE * 1.35;
+                layout.text(MARGIN + 4.0, y, BODY_SIZE, false, "\u{2022}");
+                layout.flow(runs, BODY_SIZE, 16.0, false);
+            }
+            Block::Table { header, rows } => layout.table(header, rows),
+            Block::Rule => {
+                layout.ensure(12.0);
+                layout.y -= 6.0;
+                layout.current.push_str(&format!(
+                    "0.75 G 0.75 w {:.2} {:.2} m {:.2} {:.2} l S 0 G\n",
+                    MARGIN,
+                    layout.y,
+                    PAGE_WIDTH - MARGIN,
+                    layout.y
+                ));
+                layout.y -= 6.0;
+            }
+        }
+    }
+    layout.finish()
+}
+
+/// Render blocks as a PDF file
+pub fn write(title: &str, blocks: &[Block]) -> Vec<u8> {
+    let pages = layout(blocks);
+
+    // Objects: 1 catalog, 2 page tree, 3-4 fonts, 5 info, then a page and content stream per page
+    let mut objects: Vec<String> = Vec::new();
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 6 + i * 2).collect();
+
+    objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+    objects.push(format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        page_ids
+            .iter()
+            .map(|id| format!("{} 0 R", id))
+            .collect::<Vec<_>>()
+            .join(" "),
+        pages.len()
+    ));
+    for base in ["Helvetica", "Helvetica-Bold"] {
+        objects.push(format!(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+            base
+        ));
+    }
+    objects.push(format!(
+        "<< /Title {} /Producer (devops-mcp) >>",
+        pdf_string(title)
+    ));
+    for (page_id, content) in page_ids.iter().zip(&pages) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            page_id + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+
+    let xref_offset = out.len();
+    let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        trailer.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    trailer.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+    out.extend_from_slice(trailer.as_bytes());
+    out
+}
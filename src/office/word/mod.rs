@@ -486,7 +486,8 @@ impl<'a> WordClient<'a> {
                     ToolAnnotation::new("section_manager")
                         .with_description("Gets all sections in a document"),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "add_paragraph",
                 "Add a paragraph to a section",
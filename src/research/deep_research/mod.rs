@@ -324,7 +324,8 @@ impl<'a> DeepResearchClient<'a> {
                     crate::tools::ToolAnnotation::new("search")
                         .with_description("Search for information on a specific topic"),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "summarize",
                 "Summarize a document or research paper",
@@ -348,7 +349,8 @@ impl<'a> DeepResearchClient<'a> {
                     crate::tools::ToolAnnotation::new("content_processing")
                         .with_description("Summarize a document or research paper"),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "find_citations",
                 "Find citations for a research topic",
@@ -372,7 +374,8 @@ impl<'a> DeepResearchClient<'a> {
                     crate::tools::ToolAnnotation::new("academic_research")
                         .with_description("Find citations for a research topic"),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "compare_topics",
                 "Compare multiple topics or research areas",
@@ -397,7 +400,8 @@ impl<'a> DeepResearchClient<'a> {
                     crate::tools::ToolAnnotation::new("comparative_analysis")
                         .with_description("Compare multiple topics or research areas"),
                 ),
-            ),
+            )
+            .read_only(),
             ToolDefinition::from_json_schema(
                 "generate_outline",
                 "Generate a detailed research outline",
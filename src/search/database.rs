@@ -0,0 +1,223 @@
+//! Full-text search over database tables
+//!
+//! PostgreSQL and Supabase tables are matched with `websearch_to_tsquery`
+//! and ranked with `ts_rank`; an index on the same `to_tsvector`
+//! expression keeps this fast. SQLite tables are matched with one `LIKE`
+//! per word and keep their row order. Table and column names come from
+//! the config file and are checked to be plain identifiers, since they are
+//! written into the SQL; the search text is always bound as a parameter.
+
+use super::{SearchHit, SearchQuery};
+use crate::database::QueryResult;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+fn default_text_search_config() -> String {
+    "simple".to_string()
+}
+
+/// A table searched by `search_everything`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FullTextTable {
+    /// `postgresql`, `supabase` or `sqlite`
+    pub provider: String,
+    /// Database to search; the SQLite file name for SQLite
+    #[serde(default)]
+    pub database: Option<String>,
+    /// Table, optionally schema-qualified
+    pub table: String,
+    /// Column identifying a row in references
+    pub id_column: String,
+    /// Column shown as the hit's title
+    pub title_column: String,
+    /// Columns searched along with the title
+    #[serde(default)]
+    pub text_columns: Vec<String>,
+    /// PostgreSQL text search configuration, e.g. `english`
+    #[serde(default = "default_text_search_config")]
+    pub text_search_config: String,
+    /// Source name in results; `<provider>:<table>` by default
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Whether `name` is a plain, optionally schema-qualified identifier
+fn is_identifier(name: &str) -> bool {
+    let parts: Vec<&str> = name.split('.').collect();
+    parts.len() <= 2
+        && parts.iter().all(|part| {
+            part.chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+impl FullTextTable {
+    pub fn validate(&self) -> Result<()> {
+        if !matches!(self.provider.as_str(), "postgresql" | "supabase" | "sqlite") {
+            return Err(Error::validation_with_field(
+                format!(
+                    "Full-text search supports postgresql, supabase and sqlite, not {}",
+                    self.provider
+                ),
+                "provider",
+            ));
+        }
+        if self.provider == "sqlite" && self.database.is_none() {
+            return Err(Error::validation_with_field(
+                "SQLite tables need the database file",
+                "database",
+            ));
+        }
+        let columns = [&self.table, &self.id_column, &self.title_column]
+            .into_iter()
+            .chain(&self.text_columns);
+        for name in columns {
+            if !is_identifier(name) {
+                return Err(Error::validation_with_field(
+                    format!("{} is not a plain table or column name", name),
+                    "tables",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Source name in results
+    pub fn name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("{}:{}", self.provider, self.table))
+    }
+
+    /// Columns concatenated into one searchable text
+    fn document(&self, separator: &str) -> String {
+        std::iter::once(&self.title_column)
+            .chain(&self.text_columns)
+            .map(|column| format!("coalesce(CAST({} AS TEXT), '')", column))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    /// SQL and parameters finding the best `query.limit` rows
+    pub fn sql(&self, query: &SearchQuery) -> (String, Vec<Value>) {
+        let select = format!(
+            "SELECT CAST({} AS TEXT) AS id, CAST({} AS TEXT) AS title, {} AS body",
+            self.id_column,
+            self.title_column,
+            self.document(" || ' ' || ")
+        );
+        if self.provider == "sqlite" {
+            let words: Vec<String> = query.terms();
+            let filter = (1..=words.len().max(1))
+                .map(|i| format!("lower({}) LIKE ${}", self.document(" || ' ' || "), i))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            let params = if words.is_empty() {
+                vec![Value::String(format!(
+                    "%{}%",
+                    query.text.trim().to_lowercase()
+                ))]
+            } else {
+                words
+                    .iter()
+                    .map(|w| Value::String(format!("%{}%", w)))
+                    .collect()
+            };
+            return (
+                format!(
+                    "{} FROM {} WHERE {} LIMIT {}",
+                    select, self.table, filter, query.limit
+                ),
+                params,
+            );
+        }
+        let vector = format!(
+            "to_tsvector($2::regconfig, {})",
+            self.document(" || ' ' || ")
+        );
+        (
+            format!(
+                "{}, ts_rank({vector}, websearch_to_tsquery($2::regconfig, $1)) AS rank \
+                 FROM {} WHERE {vector} @@ websearch_to_tsquery($2::regconfig, $1) \
+                 ORDER BY rank DESC LIMIT {}",
+                select,
+                self.table,
+                query.limit,
+                vector = vector
+            ),
+            vec![
+                Value::String(query.text.trim().to_string()),
+                Value::String(self.text_search_config.clone()),
+            ],
+        )
+    }
+
+    /// Hits from the rows returned by [`FullTextTable::sql`]
+    pub fn hits(&self, result: &QueryResult, terms: &[String]) -> Vec<SearchHit> {
+        let name = self.name();
+        let text = |row: &Value, key: &str| match &row[key] {
+            Value::String(s) => s.clone(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        };
+        result
+            .rows
+            .iter()
+            .map(|row| {
+                SearchHit::new(
+                    &name,
+                    format!("{}:{}", self.table, text(row, "id")),
+                    text(row, "title"),
+                    &text(row, "body"),
+                    terms,
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn table(provider: &str) -> FullTextTable {
+        serde_json::from_value(json!({
+            "provider": provider,
+            "database": "notes.db",
+            "table": "public.runbooks",
+            "id_column": "id",
+            "title_column": "title",
+            "text_columns": ["body"]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn builds_parameterized_queries_and_rejects_odd_names() {
+        let query = SearchQuery {
+            text: "disk full".to_string(),
+            since: Utc::now(),
+            limit: 5,
+        };
+        let (sql, params) = table("postgresql").sql(&query);
+        assert!(sql.contains("websearch_to_tsquery($2::regconfig, $1)"));
+        assert!(sql.ends_with("ORDER BY rank DESC LIMIT 5"));
+        assert_eq!(params, [json!("disk full"), json!("simple")]);
+
+        let (sql, params) = table("sqlite").sql(&query);
+        assert!(sql.contains("LIKE $1 AND lower("));
+        assert_eq!(params, [json!("%disk%"), json!("%full%")]);
+
+        assert!(table("postgresql").validate().is_ok());
+        let mut bad = table("postgresql");
+        bad.text_columns
+            .push("body; DROP TABLE runbooks".to_string());
+        assert!(bad.validate().is_err());
+        assert!(table("mongodb").validate().is_err());
+    }
+}
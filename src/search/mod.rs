@@ -0,0 +1,541 @@
+//! Federated search across the connected systems
+//!
+//! One query goes out to every configured source at once — stored
+//! memories, the log backends, database tables with a full-text index,
+//! Notion and Slack — and the hits come back as a single ranked list, each
+//! attributed to its source with a reference back to the original.
+//!
+//! Sources rank by different measures, so their own scores are not
+//! compared. Every hit is scored the same way on how well its title and
+//! snippet match the query, with a small bonus for its position in its
+//! source's own ordering so a source's best hit wins a close call. A source
+//! that fails or does not answer in time is reported next to the results
+//! of the others.
+
+pub mod database;
+pub mod notion;
+pub mod slack;
+
+pub use database::FullTextTable;
+pub use notion::{NotionConfig, NotionSearch};
+pub use slack::{SlackConfig, SlackSearch};
+
+use crate::error::{Error, Result};
+use crate::memory::{Memory, MemoryClient, MemorySearchParams};
+use crate::monitoring::{LogEntry, LogQuery, LogSearch};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Most hits a single search returns
+pub const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Characters of source text kept around the first match
+const SNIPPET_CHARS: usize = 240;
+
+/// Weight of a hit's position in its source's own ranking
+const SOURCE_RANK_WEIGHT: f64 = 1.0;
+
+fn default_source_timeout_secs() -> u64 {
+    10
+}
+
+/// Federated search settings, under `search` in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchConfig {
+    /// Database tables searched with full-text queries
+    #[serde(default)]
+    pub tables: Vec<FullTextTable>,
+    /// Notion integration; `NOTION_TOKEN` when unset
+    #[serde(default)]
+    pub notion: Option<NotionConfig>,
+    /// Slack user token for `search.messages`; `SLACK_USER_TOKEN` when unset
+    #[serde(default)]
+    pub slack: Option<SlackConfig>,
+    /// Seconds a source has to answer before it is reported as failed
+    #[serde(default = "default_source_timeout_secs")]
+    pub source_timeout_secs: u64,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            tables: Vec::new(),
+            notion: None,
+            slack: None,
+            source_timeout_secs: default_source_timeout_secs(),
+        }
+    }
+}
+
+impl SearchConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.source_timeout_secs == 0 {
+            return Err(Error::validation_with_field(
+                "source_timeout_secs must be at least 1",
+                "source_timeout_secs",
+            ));
+        }
+        self.tables.iter().try_for_each(FullTextTable::validate)
+    }
+
+    pub fn source_timeout(&self) -> Duration {
+        Duration::from_secs(self.source_timeout_secs)
+    }
+}
+
+/// What to search for
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pub text: String,
+    /// Oldest log lines and messages considered
+    pub since: DateTime<Utc>,
+    /// Hits wanted, from each source and in total
+    pub limit: usize,
+}
+
+impl SearchQuery {
+    pub fn validate(&self) -> Result<()> {
+        if self.text.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Search text must not be empty",
+                "query",
+            ));
+        }
+        if self.limit == 0 || self.limit > MAX_SEARCH_LIMIT {
+            return Err(Error::validation_with_field(
+                format!("Limit must be between 1 and {}", MAX_SEARCH_LIMIT),
+                "limit",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Lowercase words of the query
+    pub fn terms(&self) -> Vec<String> {
+        let mut terms: Vec<String> = self
+            .text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.chars().count() >= 2)
+            .map(str::to_lowercase)
+            .collect();
+        terms.sort();
+        terms.dedup();
+        terms
+    }
+}
+
+/// One result, attributed to the source it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    /// Source name, e.g. `memories`, `loki`, `notion`
+    pub source: String,
+    /// Where to find the original: a URL, `memory:<id>` or `<table>:<id>`
+    pub reference: String,
+    pub title: String,
+    pub snippet: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Position in the source's own ranking, from 0
+    pub source_rank: usize,
+    pub score: f64,
+}
+
+impl SearchHit {
+    /// A hit with its snippet cut around the first query term
+    pub fn new(
+        source: &str,
+        reference: impl Into<String>,
+        title: impl Into<String>,
+        text: &str,
+        terms: &[String],
+    ) -> Self {
+        Self {
+            source: source.to_string(),
+            reference: reference.into(),
+            title: title.into(),
+            snippet: snippet(text, terms),
+            timestamp: None,
+            source_rank: 0,
+            score: 0.0,
+        }
+    }
+
+    pub fn at(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+}
+
+/// A system that can be searched
+#[async_trait]
+pub trait SearchSource: Send + Sync {
+    /// Source name, as attributed in results
+    fn name(&self) -> &str;
+
+    /// Hits for `query`, best first, at most `query.limit`
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>>;
+}
+
+/// Merged results of a search across sources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedResult {
+    pub query: String,
+    pub hits: Vec<SearchHit>,
+    /// Hits each answering source contributed before the limit was applied
+    pub sources: BTreeMap<String, usize>,
+    /// Sources that failed or timed out, with the reason
+    pub errors: BTreeMap<String, String>,
+}
+
+/// Whitespace collapsed and cut to `SNIPPET_CHARS` around the first term
+pub fn snippet(text: &str, terms: &[String]) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= SNIPPET_CHARS {
+        return text;
+    }
+    let lower: Vec<char> = text.to_lowercase().chars().collect();
+    let first = if lower.len() == chars.len() {
+        terms
+            .iter()
+            .filter_map(|term| {
+                let term: Vec<char> = term.chars().collect();
+                lower.windows(term.len()).position(|w| w == term.as_slice())
+            })
+            .min()
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let start = first
+        .saturating_sub(SNIPPET_CHARS / 4)
+        .min(chars.len() - SNIPPET_CHARS);
+    let end = start + SNIPPET_CHARS;
+    let mut cut: String = chars[start..end].iter().collect();
+    if start > 0 {
+        cut.insert(0, '…');
+    }
+    if end < chars.len() {
+        cut.push('…');
+    }
+    cut
+}
+
+/// How well a hit matches, on the same scale for every source
+///
+/// The whole query counts most, title matches count double, and long
+/// snippets are not rewarded just for repeating a term.
+fn score(query: &str, terms: &[String], hit: &SearchHit) -> f64 {
+    let phrase = query.trim().to_lowercase();
+    let title = hit.title.to_lowercase();
+    let snippet = hit.snippet.to_lowercase();
+    let mut total = 0.0;
+    if title.contains(&phrase) {
+        total += 6.0;
+    } else if snippet.contains(&phrase) {
+        total += 3.0;
+    }
+    for term in terms {
+        if title.contains(term.as_str()) {
+            total += 2.0;
+        }
+        let hits = snippet.matches(term.as_str()).count();
+        if hits > 0 {
+            total += 1.0 + (hits as f64).ln();
+        }
+    }
+    total + SOURCE_RANK_WEIGHT / (1.0 + hit.source_rank as f64)
+}
+
+/// Send `query` to every source at once and merge the hits best first
+///
+/// Fails only when there is no source or every source failed.
+pub async fn search_all(
+    sources: &[Arc<dyn SearchSource>],
+    query: &SearchQuery,
+    timeout: Duration,
+) -> Result<FederatedResult> {
+    query.validate()?;
+    if sources.is_empty() {
+        return Err(Error::config_with_suggestion(
+            "No search sources are configured",
+            "Configure memories, a log backend, search.tables, search.notion or search.slack",
+        ));
+    }
+
+    let results = futures::future::join_all(sources.iter().map(|source| async move {
+        match tokio::time::timeout(timeout, source.search(query)).await {
+            Ok(result) => result,
+            Err(_) => Err(Error::timeout_with_duration(
+                format!("{} did not answer in time", source.name()),
+                timeout,
+            )),
+        }
+    }))
+    .await;
+
+    let terms = query.terms();
+    let mut hits = Vec::new();
+    let mut counts = BTreeMap::new();
+    let mut errors = BTreeMap::new();
+    for (source, result) in sources.iter().zip(results) {
+        match result {
+            Ok(found) => {
+                counts.insert(source.name().to_string(), found.len());
+                hits.extend(found.into_iter().take(query.limit).enumerate().map(
+                    |(rank, mut hit)| {
+                        hit.source = source.name().to_string();
+                        hit.source_rank = rank;
+                        hit.score = score(&query.text, &terms, &hit);
+                        hit
+                    },
+                ));
+            }
+            Err(e) => {
+                tracing::warn!(source = source.name(), error = %e, "Search source failed");
+                errors.insert(source.name().to_string(), e.to_string());
+            }
+        }
+    }
+    if counts.is_empty() {
+        let detail = errors
+            .iter()
+            .map(|(name, error)| format!("{}: {}", name, error))
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(Error::service(format!(
+            "Every search source failed: {}",
+            detail
+        )));
+    }
+
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.timestamp.cmp(&a.timestamp))
+            .then_with(|| a.source.cmp(&b.source))
+    });
+    hits.truncate(query.limit);
+    Ok(FederatedResult {
+        query: query.text.clone(),
+        hits,
+        sources: counts,
+        errors,
+    })
+}
+
+/// Stored memories, matched on the whole query and on each of its words
+pub struct MemorySource {
+    memory: Arc<MemoryClient>,
+}
+
+impl MemorySource {
+    pub fn new(memory: Arc<MemoryClient>) -> Self {
+        Self { memory }
+    }
+}
+
+#[async_trait]
+impl SearchSource for MemorySource {
+    fn name(&self) -> &str {
+        "memories"
+    }
+
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+        let terms = query.terms();
+        let keywords = std::iter::once(query.text.trim().to_string())
+            .chain(terms.iter().filter(|t| t.chars().count() >= 3).cloned());
+        let mut memories: Vec<Memory> = Vec::new();
+        for keyword in keywords {
+            let found = self
+                .memory
+                .search_memories(MemorySearchParams {
+                    memory_type: None,
+                    keyword: Some(keyword),
+                    metadata_filters: None,
+                    limit: Some(query.limit),
+                })
+                .await?;
+            for memory in found {
+                if !memories.iter().any(|m| m.id == memory.id) {
+                    memories.push(memory);
+                }
+            }
+            if memories.len() >= query.limit {
+                break;
+            }
+        }
+        memories.truncate(query.limit);
+        Ok(memories
+            .into_iter()
+            .map(|memory| {
+                SearchHit::new(
+                    self.name(),
+                    format!("memory:{}", memory.id),
+                    format!("{} ({})", memory.title, memory.memory_type),
+                    &memory.content,
+                    &terms,
+                )
+                .at(memory.updated_at)
+            })
+            .collect())
+    }
+}
+
+/// A log backend, searched for lines containing every word of the query
+pub struct LogSource {
+    backend: Arc<dyn LogSearch>,
+}
+
+impl LogSource {
+    pub fn new(backend: Arc<dyn LogSearch>) -> Self {
+        Self { backend }
+    }
+}
+
+/// Labels naming what wrote a log line, most specific first
+const LOG_ORIGIN_LABELS: &[&str] = &["service", "app", "container", "job", "host", "index"];
+
+fn log_hit(name: &str, entry: &LogEntry, terms: &[String]) -> SearchHit {
+    let origin = LOG_ORIGIN_LABELS
+        .iter()
+        .find_map(|label| entry.labels.get(*label))
+        .map_or(name, String::as_str);
+    let title = match entry.severity {
+        Some(severity) => format!("{} {} log", origin, severity.as_str()),
+        None => format!("{} log", origin),
+    };
+    SearchHit::new(
+        name,
+        format!("{}:{}", name, entry.timestamp.to_rfc3339()),
+        title,
+        &entry.message,
+        terms,
+    )
+    .at(entry.timestamp)
+}
+
+#[async_trait]
+impl SearchSource for LogSource {
+    fn name(&self) -> &str {
+        self.backend.name()
+    }
+
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+        let entries = self
+            .backend
+            .search(&LogQuery {
+                text: query.text.clone(),
+                start: query.since,
+                end: Utc::now(),
+                severity: None,
+                limit: query.limit,
+            })
+            .await?;
+        let terms = query.terms();
+        Ok(entries
+            .iter()
+            .map(|entry| log_hit(self.name(), entry, &terms))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed {
+        name: &'static str,
+        hits: Vec<(&'static str, &'static str)>,
+        delay: Option<Duration>,
+    }
+
+    #[async_trait]
+    impl SearchSource for Fixed {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+            if let Some(delay) = self.delay {
+                tokio::time::sleep(delay).await;
+            }
+            if self.hits.is_empty() {
+                return Err(Error::network("connection refused"));
+            }
+            let terms = query.terms();
+            Ok(self
+                .hits
+                .iter()
+                .map(|(title, text)| SearchHit::new("", *title, *title, text, &terms))
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn merges_sources_by_match_and_reports_failures() {
+        let sources: Vec<Arc<dyn SearchSource>> = vec![
+            Arc::new(Fixed {
+                name: "memories",
+                hits: vec![
+                    ("Billing notes", "Invoices are generated nightly"),
+                    (
+                        "Postgres failover",
+                        "Promote the replica, then repoint pgbouncer",
+                    ),
+                ],
+                delay: None,
+            }),
+            Arc::new(Fixed {
+                name: "notion",
+                hits: vec![(
+                    "Runbook: postgres failover",
+                    "Steps for a postgres failover",
+                )],
+                delay: None,
+            }),
+            Arc::new(Fixed {
+                name: "slack",
+                hits: Vec::new(),
+                delay: None,
+            }),
+            Arc::new(Fixed {
+                name: "splunk",
+                hits: vec![("late", "postgres failover")],
+                delay: Some(Duration::from_secs(5)),
+            }),
+        ];
+        let query = SearchQuery {
+            text: "postgres failover".to_string(),
+            since: Utc::now(),
+            limit: 2,
+        };
+        let result = search_all(&sources, &query, Duration::from_millis(200))
+            .await
+            .unwrap();
+
+        let titles: Vec<&str> = result.hits.iter().map(|h| h.title.as_str()).collect();
+        assert_eq!(titles, ["Runbook: postgres failover", "Postgres failover"]);
+        assert_eq!(result.hits[1].source, "memories");
+        assert_eq!(result.hits[1].source_rank, 1);
+        assert_eq!(result.sources.get("memories"), Some(&2));
+        assert!(result.errors["slack"].contains("connection refused"));
+        assert!(result.errors.contains_key("splunk"));
+    }
+
+    #[test]
+    fn cuts_snippets_around_the_first_match() {
+        let text = format!(
+            "{} the disk on nas01 is full {}",
+            "x ".repeat(200),
+            "y ".repeat(200)
+        );
+        let cut = snippet(&text, &["nas01".to_string()]);
+        assert!(cut.starts_with('…') && cut.ends_with('…'));
+        assert!(cut.contains("disk on nas01 is full"));
+        assert_eq!(snippet("  short\n text ", &[]), "short text");
+    }
+}
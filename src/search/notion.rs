@@ -0,0 +1,136 @@
+//! Notion pages and databases through the search API
+//!
+//! Notion matches the query against titles only and returns no body text,
+//! so hits carry the page title and the page's URL.
+
+use super::{SearchHit, SearchQuery, SearchSource};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const DEFAULT_API_URL: &str = "https://api.notion.com/v1";
+
+/// API version sent in `Notion-Version`
+const NOTION_VERSION: &str = "2022-06-28";
+
+/// Largest page the search API returns
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Notion internal integration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotionConfig {
+    /// Integration secret; pages must be shared with the integration
+    pub token: String,
+    /// API base URL, for testing against a stand-in
+    #[serde(default)]
+    pub api_url: Option<String>,
+}
+
+impl NotionConfig {
+    /// Notion from `NOTION_TOKEN`, if set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            token: std::env::var("NOTION_TOKEN").ok()?,
+            api_url: None,
+        })
+    }
+}
+
+/// Title of a page or database search result
+fn title(result: &Value) -> String {
+    let plain = |parts: &Value| {
+        parts
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p["plain_text"].as_str())
+            .collect::<String>()
+    };
+    if result["object"] == "database" {
+        return plain(&result["title"]);
+    }
+    result["properties"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .find(|(_, property)| property["type"] == "title")
+        .map(|(_, property)| plain(&property["title"]))
+        .unwrap_or_default()
+}
+
+/// Notion search
+pub struct NotionSearch {
+    client: Client,
+    config: NotionConfig,
+}
+
+impl NotionSearch {
+    pub fn new(config: NotionConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Notion client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+}
+
+#[async_trait]
+impl SearchSource for NotionSearch {
+    fn name(&self) -> &str {
+        "notion"
+    }
+
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+        let base = self.config.api_url.as_deref().unwrap_or(DEFAULT_API_URL);
+        let response = self
+            .client
+            .post(format!("{}/search", base.trim_end_matches('/')))
+            .bearer_auth(&self.config.token)
+            .header("Notion-Version", NOTION_VERSION)
+            .json(&json!({
+                "query": query.text.trim(),
+                "page_size": query.limit.min(MAX_PAGE_SIZE),
+            }))
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Notion request failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::api_with_status(
+                format!("Notion search failed: {}", text.trim()),
+                "notion",
+                status.as_u16(),
+            ));
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Invalid Notion response: {}", e)))?;
+
+        let terms = query.terms();
+        Ok(body["results"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|result| {
+                let title = title(result);
+                let mut hit = SearchHit::new(
+                    self.name(),
+                    result["url"].as_str().unwrap_or_default(),
+                    title.clone(),
+                    &title,
+                    &terms,
+                );
+                hit.timestamp = result["last_edited_time"]
+                    .as_str()
+                    .and_then(|t| t.parse::<DateTime<Utc>>().ok());
+                hit
+            })
+            .collect())
+    }
+}
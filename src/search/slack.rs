@@ -0,0 +1,162 @@
+//! Slack messages through `search.messages`
+//!
+//! Message search is only open to user tokens (`xoxp-`) with the
+//! `search:read` scope; bot tokens are refused by Slack.
+
+use super::{SearchHit, SearchQuery, SearchSource};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+const DEFAULT_API_URL: &str = "https://slack.com/api";
+
+/// Largest page `search.messages` returns
+const MAX_COUNT: usize = 100;
+
+/// Slack user token with `search:read`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackConfig {
+    pub token: String,
+    /// API base URL, for testing against a stand-in
+    #[serde(default)]
+    pub api_url: Option<String>,
+}
+
+impl SlackConfig {
+    /// Slack from `SLACK_USER_TOKEN`, if set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            token: std::env::var("SLACK_USER_TOKEN").ok()?,
+            api_url: None,
+        })
+    }
+}
+
+/// Slack query text limited to messages since `since`
+///
+/// `after:` takes a day and excludes it, so the day before is used.
+fn slack_query(query: &SearchQuery) -> String {
+    let day = (query.since - chrono::Duration::days(1)).format("%Y-%m-%d");
+    format!("{} after:{}", query.text.trim(), day)
+}
+
+/// Hits from a `search.messages` response
+fn hits(body: &Value, terms: &[String]) -> Vec<SearchHit> {
+    body["messages"]["matches"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|message| {
+            let channel = message["channel"]["name"].as_str().unwrap_or("?");
+            let author = message["username"]
+                .as_str()
+                .or_else(|| message["user"].as_str())
+                .unwrap_or("?");
+            let mut hit = SearchHit::new(
+                "slack",
+                message["permalink"].as_str().unwrap_or_default(),
+                format!("#{} — {}", channel, author),
+                message["text"].as_str().unwrap_or_default(),
+                terms,
+            );
+            hit.timestamp = message["ts"]
+                .as_str()
+                .and_then(|ts| ts.split('.').next()?.parse::<i64>().ok())
+                .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0));
+            hit
+        })
+        .collect()
+}
+
+/// Slack message search
+pub struct SlackSearch {
+    client: Client,
+    config: SlackConfig,
+}
+
+impl SlackSearch {
+    pub fn new(config: SlackConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Slack client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+}
+
+#[async_trait]
+impl SearchSource for SlackSearch {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+        let base = self.config.api_url.as_deref().unwrap_or(DEFAULT_API_URL);
+        let response = self
+            .client
+            .get(format!("{}/search.messages", base.trim_end_matches('/')))
+            .bearer_auth(&self.config.token)
+            .query(&[
+                ("query", slack_query(query)),
+                ("count", query.limit.min(MAX_COUNT).to_string()),
+                ("sort", "score".to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Slack request failed: {}", e)))?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Invalid Slack response: {}", e)))?;
+        // Slack reports most failures as 200 with `ok: false`
+        if !status.is_success() || body["ok"] != true {
+            return Err(Error::api_with_status(
+                format!(
+                    "Slack search failed: {}",
+                    body["error"].as_str().unwrap_or("unknown error")
+                ),
+                "slack",
+                status.as_u16(),
+            ));
+        }
+        Ok(hits(&body, &query.terms()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_matches_and_limits_by_day() {
+        let query = SearchQuery {
+            text: "nas01 disk".to_string(),
+            since: "2024-05-10T08:00:00Z".parse().unwrap(),
+            limit: 10,
+        };
+        assert_eq!(slack_query(&query), "nas01 disk after:2024-05-09");
+
+        let found = hits(
+            &json!({"ok": true, "messages": {"matches": [{
+                "channel": {"name": "homelab"},
+                "username": "sam",
+                "text": "nas01 disk is at 97%",
+                "ts": "1715328000.000200",
+                "permalink": "https://example.slack.com/archives/C1/p1715328000000200"
+            }]}}),
+            &query.terms(),
+        );
+        assert_eq!(found[0].title, "#homelab — sam");
+        assert_eq!(found[0].snippet, "nas01 disk is at 97%");
+        assert_eq!(
+            found[0].timestamp,
+            Some("2024-05-10T08:00:00Z".parse().unwrap())
+        );
+    }
+}
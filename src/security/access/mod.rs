@@ -0,0 +1,1162 @@
+/// SSH key and access lifecycle management
+///
+/// Keeps a registry of approved SSH public keys and the host accounts each is
+/// granted on, applies grants and revocations to `authorized_keys` over SSH,
+/// rotates keys whose rotation period has lapsed, and scans hosts to build an
+/// access inventory that flags unknown, revoked and missing keys.
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use base64::Engine as _;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::RwLock;
+
+/// Time allowed for a single remote command
+const SSH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Public key algorithms accepted in `authorized_keys`
+const KEY_TYPES: &[&str] = &[
+    "ssh-ed25519",
+    "ssh-rsa",
+    "ssh-dss",
+    "ecdsa-sha2-nistp256",
+    "ecdsa-sha2-nistp384",
+    "ecdsa-sha2-nistp521",
+    "sk-ssh-ed25519@openssh.com",
+    "sk-ecdsa-sha2-nistp256@openssh.com",
+];
+
+/// Access management configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessConfig {
+    /// Private key used to connect to managed hosts
+    pub identity_file: Option<PathBuf>,
+    /// Directory rotated private keys are written to
+    pub key_dir: Option<PathBuf>,
+}
+
+impl Default for AccessConfig {
+    fn default() -> Self {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+        Self {
+            identity_file: env("SSH_ACCESS_IDENTITY").map(PathBuf::from),
+            key_dir: env("SSH_ACCESS_KEY_DIR").map(PathBuf::from),
+        }
+    }
+}
+
+/// A host whose accounts are managed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshHost {
+    pub name: String,
+    pub address: String,
+    pub port: u16,
+    /// Account used to connect; needs passwordless sudo to manage other accounts
+    pub login_user: String,
+    /// Accounts whose `authorized_keys` are managed
+    pub users: Vec<String>,
+}
+
+/// A host account a key is authorized on
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Grant {
+    pub host: String,
+    pub user: String,
+}
+
+/// An approved public key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedKey {
+    pub id: String,
+    pub owner: String,
+    /// `authorized_keys` line without options
+    pub public_key: String,
+    pub fingerprint: String,
+    pub created_at: DateTime<Utc>,
+    /// Days before the key should be rotated
+    pub rotation_days: Option<u32>,
+    pub grants: Vec<Grant>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Key that replaced this one on rotation
+    pub replaced_by: Option<String>,
+}
+
+impl ManagedKey {
+    /// Whether the key has outlived its rotation period
+    pub fn rotation_due(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none()
+            && self
+                .rotation_days
+                .is_some_and(|days| self.created_at + ChronoDuration::days(i64::from(days)) <= now)
+    }
+}
+
+/// A parsed `authorized_keys` entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuthorizedKey {
+    pub options: Option<String>,
+    pub key_type: String,
+    pub blob: String,
+    pub comment: String,
+    pub fingerprint: String,
+}
+
+/// How a key found on a host relates to the registry
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStatus {
+    /// Registered and granted on this account
+    Managed,
+    /// Registered but past its rotation period
+    RotationDue,
+    /// Registered but not granted on this account
+    Ungranted,
+    /// Revoked or rotated out, yet still present
+    Revoked,
+    /// Not in the registry
+    Unknown,
+    /// Granted but absent from the host
+    Missing,
+}
+
+/// A key in a host account's inventory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryEntry {
+    pub fingerprint: String,
+    pub key_type: String,
+    pub comment: String,
+    pub owner: Option<String>,
+    pub key_id: Option<String>,
+    pub status: KeyStatus,
+}
+
+/// Keys authorized on one host account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostInventory {
+    pub host: String,
+    pub user: String,
+    pub scanned_at: DateTime<Utc>,
+    pub keys: Vec<InventoryEntry>,
+    /// Unknown keys removed during the scan
+    #[serde(default)]
+    pub removed: usize,
+    pub error: Option<String>,
+}
+
+/// Outcome of rotating one key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rotation {
+    pub old_key_id: String,
+    pub new_key_id: String,
+    pub owner: String,
+    /// Where the new private key was written
+    pub private_key_path: PathBuf,
+    pub applied: Vec<Grant>,
+    /// Grants still holding the old key, with the reason
+    pub failed: Vec<(Grant, String)>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AccessStore {
+    hosts: BTreeMap<String, SshHost>,
+    keys: Vec<ManagedKey>,
+    inventory: Vec<HostInventory>,
+}
+
+/// SHA256 fingerprint in `ssh-keygen -l` form
+pub fn fingerprint(blob: &str) -> Result<String> {
+    let bytes = STANDARD
+        .decode(blob)
+        .map_err(|e| Error::validation(format!("Invalid public key encoding: {}", e)))?;
+    Ok(format!(
+        "SHA256:{}",
+        STANDARD_NO_PAD.encode(Sha256::digest(&bytes))
+    ))
+}
+
+/// Parse one `authorized_keys` line; comments and blank lines yield `None`
+pub fn parse_key_line(line: &str) -> Option<AuthorizedKey> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (options, rest) = if KEY_TYPES
+        .iter()
+        .any(|t| line.starts_with(&format!("{} ", t)))
+    {
+        (None, line)
+    } else {
+        // Options run to the first unquoted whitespace
+        let mut quoted = false;
+        let end = line
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    quoted = !quoted;
+                }
+                c.is_whitespace() && !quoted
+            })
+            .map(|(i, _)| i)?;
+        (Some(line[..end].to_string()), line[end..].trim_start())
+    };
+    let mut parts = rest.splitn(3, char::is_whitespace);
+    let key_type = parts.next()?;
+    if !KEY_TYPES.contains(&key_type) {
+        return None;
+    }
+    let blob = parts.next()?;
+    Some(AuthorizedKey {
+        options,
+        key_type: key_type.to_string(),
+        blob: blob.to_string(),
+        comment: parts.next().unwrap_or_default().trim().to_string(),
+        fingerprint: fingerprint(blob).ok()?,
+    })
+}
+
+/// Classify the keys of one host account against the registry
+pub fn classify(
+    host: &str,
+    user: &str,
+    authorized_keys: &str,
+    keys: &[ManagedKey],
+    now: DateTime<Utc>,
+) -> Vec<InventoryEntry> {
+    let grant = Grant {
+        host: host.to_string(),
+        user: user.to_string(),
+    };
+    let mut seen = BTreeSet::new();
+    let mut entries: Vec<InventoryEntry> = authorized_keys
+        .lines()
+        .filter_map(parse_key_line)
+        .map(|found| {
+            seen.insert(found.fingerprint.clone());
+            let managed = keys.iter().find(|k| k.fingerprint == found.fingerprint);
+            let status = match managed {
+                None => KeyStatus::Unknown,
+                Some(k) if k.revoked_at.is_some() => KeyStatus::Revoked,
+                Some(k) if !k.grants.contains(&grant) => KeyStatus::Ungranted,
+                Some(k) if k.rotation_due(now) => KeyStatus::RotationDue,
+                Some(_) => KeyStatus::Managed,
+            };
+            InventoryEntry {
+                fingerprint: found.fingerprint,
+                key_type: found.key_type,
+                comment: found.comment,
+                owner: managed.map(|k| k.owner.clone()),
+                key_id: managed.map(|k| k.id.clone()),
+                status,
+            }
+        })
+        .collect();
+    entries.extend(
+        keys.iter()
+            .filter(|k| k.revoked_at.is_none() && k.grants.contains(&grant))
+            .filter(|k| !seen.contains(&k.fingerprint))
+            .map(|k| {
+                let parsed = parse_key_line(&k.public_key);
+                InventoryEntry {
+                    fingerprint: k.fingerprint.clone(),
+                    key_type: parsed
+                        .as_ref()
+                        .map(|p| p.key_type.clone())
+                        .unwrap_or_default(),
+                    comment: parsed.map(|p| p.comment).unwrap_or_default(),
+                    owner: Some(k.owner.clone()),
+                    key_id: Some(k.id.clone()),
+                    status: KeyStatus::Missing,
+                }
+            }),
+    );
+    entries
+}
+
+/// Rewrite `authorized_keys`, dropping keys by fingerprint and appending new lines
+fn edit_authorized_keys(current: &str, remove: &BTreeSet<String>, add: &[String]) -> String {
+    let mut lines: Vec<&str> = current
+        .lines()
+        .filter(|line| parse_key_line(line).is_none_or(|k| !remove.contains(&k.fingerprint)))
+        .collect();
+    let present: BTreeSet<String> = lines
+        .iter()
+        .filter_map(|l| parse_key_line(l))
+        .map(|k| k.fingerprint)
+        .collect();
+    for line in add {
+        if parse_key_line(line).is_some_and(|k| !present.contains(&k.fingerprint)) {
+            lines.push(line);
+        }
+    }
+    let mut text = lines.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+fn validate_user(user: &str) -> Result<()> {
+    let valid = user
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && user.len() <= 32
+        && user
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_.-".contains(c));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::validation_with_field(
+            format!("Invalid account name: {}", user),
+            "user",
+        ))
+    }
+}
+
+/// SSH access lifecycle manager
+pub struct AccessManager {
+    config: AccessConfig,
+    /// Where the registry is persisted; `None` keeps it in memory
+    path: Option<PathBuf>,
+    store: RwLock<AccessStore>,
+}
+
+impl AccessManager {
+    /// Open a manager, loading the registry from `path` if it exists
+    pub async fn open(config: AccessConfig, path: Option<PathBuf>) -> Result<Self> {
+        let store = match &path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse access store {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => AccessStore::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read access store: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => AccessStore::default(),
+        };
+        Ok(Self {
+            config,
+            path,
+            store: RwLock::new(store),
+        })
+    }
+
+    async fn persist(&self, store: &AccessStore) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create access store directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(format!("Failed to write access store: {}", e), temp.clone())
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace access store: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Run a shell script on a host, optionally feeding it stdin
+    async fn ssh(&self, host: &SshHost, script: &str, input: Option<&str>) -> Result<String> {
+        let mut command = Command::new("ssh");
+        command.args([
+            "-o",
+            "BatchMode=yes",
+            "-o",
+            "ConnectTimeout=10",
+            "-p",
+            &host.port.to_string(),
+        ]);
+        if let Some(identity) = &self.config.identity_file {
+            command.arg("-i").arg(identity);
+        }
+        command
+            .arg(format!("{}@{}", host.login_user, host.address))
+            .arg("--")
+            .arg(script)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = command
+            .spawn()
+            .map_err(|e| Error::service(format!("Failed to run ssh: {}", e)))?;
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin
+                .write_all(input.as_bytes())
+                .await
+                .map_err(|e| Error::service(format!("Failed to write to ssh: {}", e)))?;
+        }
+        let output = tokio::time::timeout(SSH_TIMEOUT, child.wait_with_output())
+            .await
+            .map_err(|_| Error::timeout(format!("SSH to {} timed out", host.name)))?
+            .map_err(|e| Error::service(format!("ssh failed: {}", e)))?;
+        if !output.status.success() {
+            return Err(Error::service(format!(
+                "ssh {}: {}",
+                host.name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Shell prefix for acting on `user`'s files
+    fn sudo(host: &SshHost, user: &str) -> &'static str {
+        if host.login_user == user {
+            ""
+        } else {
+            "sudo -n "
+        }
+    }
+
+    async fn read_keys(&self, host: &SshHost, user: &str) -> Result<String> {
+        validate_user(user)?;
+        let script = format!(
+            "set -e; h=$(getent passwd {user} | cut -d: -f6); [ -n \"$h\" ]; \
+             if {sudo}test -f \"$h/.ssh/authorized_keys\"; then {sudo}cat \"$h/.ssh/authorized_keys\"; fi",
+            user = user,
+            sudo = Self::sudo(host, user)
+        );
+        self.ssh(host, &script, None).await
+    }
+
+    async fn write_keys(&self, host: &SshHost, user: &str, content: &str) -> Result<()> {
+        validate_user(user)?;
+        let script = format!(
+            "set -e; h=$(getent passwd {user} | cut -d: -f6); [ -n \"$h\" ]; \
+             {sudo}mkdir -p \"$h/.ssh\"; {sudo}chmod 700 \"$h/.ssh\"; {sudo}chown {user} \"$h/.ssh\"; \
+             {sudo}tee \"$h/.ssh/authorized_keys.new\" >/dev/null; \
+             {sudo}chmod 600 \"$h/.ssh/authorized_keys.new\"; {sudo}chown {user} \"$h/.ssh/authorized_keys.new\"; \
+             {sudo}mv \"$h/.ssh/authorized_keys.new\" \"$h/.ssh/authorized_keys\"",
+            user = user,
+            sudo = Self::sudo(host, user)
+        );
+        self.ssh(host, &script, Some(content)).await.map(|_| ())
+    }
+
+    /// Apply removals and additions to one host account
+    async fn apply(&self, grant: &Grant, remove: &BTreeSet<String>, add: &[String]) -> Result<()> {
+        let host = self.host(&grant.host).await?;
+        let current = self.read_keys(&host, &grant.user).await?;
+        let updated = edit_authorized_keys(&current, remove, add);
+        if updated != current {
+            self.write_keys(&host, &grant.user, &updated).await?;
+        }
+        Ok(())
+    }
+
+    async fn host(&self, name: &str) -> Result<SshHost> {
+        self.store
+            .read()
+            .await
+            .hosts
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::not_found_with_resource("Host not found", "ssh_host", name))
+    }
+
+    async fn key(&self, id: &str) -> Result<ManagedKey> {
+        self.store
+            .read()
+            .await
+            .keys
+            .iter()
+            .find(|k| k.id == id)
+            .cloned()
+            .ok_or_else(|| Error::not_found_with_resource("Key not found", "ssh_key", id))
+    }
+
+    /// Add or replace a managed host
+    pub async fn register_host(&self, host: SshHost) -> Result<()> {
+        validate_user(&host.login_user)?;
+        for user in &host.users {
+            validate_user(user)?;
+        }
+        let mut store = self.store.write().await;
+        store.hosts.insert(host.name.clone(), host);
+        self.persist(&store).await
+    }
+
+    /// Register an approved public key
+    pub async fn register_key(
+        &self,
+        owner: &str,
+        public_key: &str,
+        rotation_days: Option<u32>,
+    ) -> Result<ManagedKey> {
+        let parsed = parse_key_line(public_key).ok_or_else(|| {
+            Error::validation_with_field("Not an OpenSSH public key", "public_key")
+        })?;
+        let mut store = self.store.write().await;
+        if let Some(existing) = store
+            .keys
+            .iter()
+            .find(|k| k.fingerprint == parsed.fingerprint)
+        {
+            return Err(Error::validation_with_field(
+                format!("Key already registered as {}", existing.id),
+                "public_key",
+            ));
+        }
+        let key = ManagedKey {
+            id: uuid::Uuid::new_v4().to_string(),
+            owner: owner.to_string(),
+            public_key: format!("{} {} {}", parsed.key_type, parsed.blob, parsed.comment)
+                .trim_end()
+                .to_string(),
+            fingerprint: parsed.fingerprint,
+            created_at: Utc::now(),
+            rotation_days,
+            grants: Vec::new(),
+            revoked_at: None,
+            replaced_by: None,
+        };
+        store.keys.push(key.clone());
+        self.persist(&store).await?;
+        Ok(key)
+    }
+
+    /// Authorize a key on a host account
+    pub async fn grant(&self, key_id: &str, host: &str, user: &str) -> Result<ManagedKey> {
+        let key = self.key(key_id).await?;
+        if key.revoked_at.is_some() {
+            return Err(Error::validation_with_field("Key is revoked", "key_id"));
+        }
+        let grant = Grant {
+            host: host.to_string(),
+            user: user.to_string(),
+        };
+        self.apply(
+            &grant,
+            &BTreeSet::new(),
+            std::slice::from_ref(&key.public_key),
+        )
+        .await?;
+
+        let mut store = self.store.write().await;
+        let key = store
+            .keys
+            .iter_mut()
+            .find(|k| k.id == key_id)
+            .ok_or_else(|| Error::not_found_with_resource("Key not found", "ssh_key", key_id))?;
+        if !key.grants.contains(&grant) {
+            key.grants.push(grant);
+            key.grants.sort();
+        }
+        let key = key.clone();
+        self.persist(&store).await?;
+        Ok(key)
+    }
+
+    /// Remove a key from every host account it is granted on
+    pub async fn revoke(&self, key_id: &str) -> Result<(ManagedKey, Vec<(Grant, String)>)> {
+        let key = self.key(key_id).await?;
+        let remove = BTreeSet::from([key.fingerprint.clone()]);
+        let mut failed = Vec::new();
+        for grant in &key.grants {
+            if let Err(e) = self.apply(grant, &remove, &[]).await {
+                failed.push((grant.clone(), e.to_string()));
+            }
+        }
+
+        let mut store = self.store.write().await;
+        let key = store
+            .keys
+            .iter_mut()
+            .find(|k| k.id == key_id)
+            .ok_or_else(|| Error::not_found_with_resource("Key not found", "ssh_key", key_id))?;
+        key.revoked_at.get_or_insert_with(Utc::now);
+        // Failed removals stay as grants so a later revoke or scan surfaces them
+        key.grants.retain(|g| failed.iter().any(|(f, _)| f == g));
+        let key = key.clone();
+        self.persist(&store).await?;
+        Ok((key, failed))
+    }
+
+    /// Generate an ed25519 key pair, returning the private key path and public key line
+    async fn generate(&self, owner: &str, id: &str) -> Result<(PathBuf, String)> {
+        let dir = self.config.key_dir.clone().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "No directory configured for rotated keys",
+                "Set SSH_ACCESS_KEY_DIR to a directory only the key owners can read",
+            )
+        })?;
+        tokio::fs::create_dir_all(&dir).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to create key directory: {}", e),
+                dir.clone(),
+            )
+        })?;
+        let safe_owner: String = owner
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = dir.join(format!("{}-{}", safe_owner, &id[..8]));
+        let output = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", ""])
+            .arg("-C")
+            .arg(format!("{} {}", owner, Utc::now().format("%Y-%m-%d")))
+            .arg("-f")
+            .arg(&path)
+            .output()
+            .await
+            .map_err(|e| Error::service(format!("Failed to run ssh-keygen: {}", e)))?;
+        if !output.status.success() {
+            return Err(Error::service(format!(
+                "ssh-keygen failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let public_path = path.with_extension("pub");
+        let public_key = tokio::fs::read_to_string(&public_path).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to read public key: {}", e), public_path)
+        })?;
+        Ok((path, public_key.trim().to_string()))
+    }
+
+    /// Replace a key with a freshly generated one on every host account it is granted on
+    pub async fn rotate(&self, key_id: &str) -> Result<Rotation> {
+        let old = self.key(key_id).await?;
+        if old.revoked_at.is_some() {
+            return Err(Error::validation_with_field("Key is revoked", "key_id"));
+        }
+        let new_id = uuid::Uuid::new_v4().to_string();
+        let (private_key_path, public_key) = self.generate(&old.owner, &new_id).await?;
+        let new_fingerprint = parse_key_line(&public_key)
+            .map(|k| k.fingerprint)
+            .ok_or_else(|| Error::internal("ssh-keygen produced an unreadable public key"))?;
+
+        let remove = BTreeSet::from([old.fingerprint.clone()]);
+        let mut applied = Vec::new();
+        let mut failed = Vec::new();
+        for grant in &old.grants {
+            match self
+                .apply(grant, &remove, std::slice::from_ref(&public_key))
+                .await
+            {
+                Ok(()) => applied.push(grant.clone()),
+                Err(e) => failed.push((grant.clone(), e.to_string())),
+            }
+        }
+
+        let mut store = self.store.write().await;
+        store.keys.push(ManagedKey {
+            id: new_id.clone(),
+            owner: old.owner.clone(),
+            public_key,
+            fingerprint: new_fingerprint,
+            created_at: Utc::now(),
+            rotation_days: old.rotation_days,
+            grants: applied.clone(),
+            revoked_at: None,
+            replaced_by: None,
+        });
+        if let Some(key) = store.keys.iter_mut().find(|k| k.id == key_id) {
+            key.grants.retain(|g| failed.iter().any(|(f, _)| f == g));
+            key.replaced_by = Some(new_id.clone());
+            if failed.is_empty() {
+                key.revoked_at = Some(Utc::now());
+            }
+        }
+        self.persist(&store).await?;
+        Ok(Rotation {
+            old_key_id: old.id,
+            new_key_id: new_id,
+            owner: old.owner,
+            private_key_path,
+            applied,
+            failed,
+        })
+    }
+
+    /// Rotate every key past its rotation period
+    pub async fn rotate_due(&self) -> Vec<Result<Rotation>> {
+        let now = Utc::now();
+        let due: Vec<String> = self
+            .store
+            .read()
+            .await
+            .keys
+            .iter()
+            .filter(|k| k.rotation_due(now) && k.replaced_by.is_none())
+            .map(|k| k.id.clone())
+            .collect();
+        let mut results = Vec::new();
+        for id in due {
+            results.push(self.rotate(&id).await);
+        }
+        results
+    }
+
+    /// Rotate due keys every `every` until the task is aborted
+    pub fn spawn_rotation(self: &Arc<Self>, every: Duration) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(every);
+            loop {
+                ticker.tick().await;
+                for result in manager.rotate_due().await {
+                    match result {
+                        Ok(rotation) if rotation.failed.is_empty() => tracing::info!(
+                            "Rotated SSH key {} for {}",
+                            rotation.old_key_id,
+                            rotation.owner
+                        ),
+                        Ok(rotation) => tracing::warn!(
+                            "Rotated SSH key {} for {} with {} hosts still on the old key",
+                            rotation.old_key_id,
+                            rotation.owner,
+                            rotation.failed.len()
+                        ),
+                        Err(e) => tracing::warn!("SSH key rotation failed: {}", e),
+                    }
+                }
+            }
+        })
+    }
+
+    /// Scan host accounts and record their key inventory
+    pub async fn scan(&self, hosts: &[String], remove_unknown: bool) -> Result<Vec<HostInventory>> {
+        let targets: Vec<SshHost> = {
+            let store = self.store.read().await;
+            if hosts.is_empty() {
+                store.hosts.values().cloned().collect()
+            } else {
+                hosts
+                    .iter()
+                    .map(|name| {
+                        store.hosts.get(name).cloned().ok_or_else(|| {
+                            Error::not_found_with_resource("Host not found", "ssh_host", name)
+                        })
+                    })
+                    .collect::<Result<_>>()?
+            }
+        };
+        let keys = self.store.read().await.keys.clone();
+
+        let mut inventory = Vec::new();
+        for host in &targets {
+            for user in &host.users {
+                let mut entry = HostInventory {
+                    host: host.name.clone(),
+                    user: user.clone(),
+                    scanned_at: Utc::now(),
+                    keys: Vec::new(),
+                    removed: 0,
+                    error: None,
+                };
+                match self.read_keys(host, user).await {
+                    Ok(text) => {
+                        entry.keys = classify(&host.name, user, &text, &keys, entry.scanned_at);
+                        let unknown: BTreeSet<String> = entry
+                            .keys
+                            .iter()
+                            .filter(|k| k.status == KeyStatus::Unknown)
+                            .map(|k| k.fingerprint.clone())
+                            .collect();
+                        if !unknown.is_empty() {
+                            tracing::warn!(
+                                "{} unknown SSH keys for {}@{}",
+                                unknown.len(),
+                                user,
+                                host.name
+                            );
+                        }
+                        if remove_unknown && !unknown.is_empty() {
+                            let updated = edit_authorized_keys(&text, &unknown, &[]);
+                            match self.write_keys(host, user, &updated).await {
+                                Ok(()) => entry.removed = unknown.len(),
+                                Err(e) => entry.error = Some(e.to_string()),
+                            }
+                        }
+                    }
+                    Err(e) => entry.error = Some(e.to_string()),
+                }
+                inventory.push(entry);
+            }
+        }
+
+        let mut store = self.store.write().await;
+        store.inventory.retain(|old| {
+            !inventory
+                .iter()
+                .any(|new| new.host == old.host && new.user == old.user)
+        });
+        store.inventory.extend(inventory.iter().cloned());
+        self.persist(&store).await?;
+        Ok(inventory)
+    }
+
+    /// Last recorded inventory of every scanned host account
+    pub async fn inventory(&self) -> Vec<HostInventory> {
+        self.store.read().await.inventory.clone()
+    }
+
+    /// Registered keys
+    pub async fn keys(&self) -> Vec<ManagedKey> {
+        self.store.read().await.keys.clone()
+    }
+
+    /// Get tool definitions for SSH access management
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "ssh_host_register",
+                "Register a host and the accounts whose authorized_keys are managed on it",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string"},
+                        "address": {"type": "string"},
+                        "port": {"type": "integer", "default": 22},
+                        "login_user": {"type": "string", "default": "root", "description": "Account used to connect; needs passwordless sudo to manage other accounts"},
+                        "users": {"type": "array", "items": {"type": "string"}}
+                    },
+                    "required": ["name", "address", "users"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "ssh_key_register",
+                "Register an approved SSH public key for an owner, optionally with a rotation period",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "owner": {"type": "string"},
+                        "public_key": {"type": "string"},
+                        "rotation_days": {"type": "integer", "minimum": 1}
+                    },
+                    "required": ["owner", "public_key"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "ssh_key_grant",
+                "Authorize a registered key on a host account",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "key_id": {"type": "string"},
+                        "host": {"type": "string"},
+                        "user": {"type": "string"}
+                    },
+                    "required": ["key_id", "host", "user"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "ssh_key_revoke",
+                "Remove a key from every host account it is granted on",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {"key_id": {"type": "string"}},
+                    "required": ["key_id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "ssh_key_rotate",
+                "Replace a key with a new ed25519 key on all its hosts; without key_id, rotates every key past its rotation period",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {"key_id": {"type": "string"}}
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "ssh_access_inventory",
+                "Scan host accounts and report which keys (and owners) can log in, flagging unknown, revoked, ungranted and missing keys",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "hosts": {"type": "array", "items": {"type": "string"}, "description": "Hosts to scan; all when omitted"},
+                        "remove_unknown": {"type": "boolean", "default": false},
+                        "cached": {"type": "boolean", "default": false, "description": "Report the last scan without connecting"}
+                    }
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute an SSH access tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let string = |key: &str| -> Result<String> {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| Error::validation_with_field(format!("{} is required", key), key))
+        };
+        match name {
+            "ssh_host_register" => {
+                let host = SshHost {
+                    name: string("name")?,
+                    address: string("address")?,
+                    port: parameters
+                        .get("port")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(22) as u16,
+                    login_user: string("login_user").unwrap_or_else(|_| "root".to_string()),
+                    users: serde_json::from_value(
+                        parameters.get("users").cloned().unwrap_or_default(),
+                    )
+                    .map_err(|e| {
+                        Error::validation_with_field(format!("Invalid users: {}", e), "users")
+                    })?,
+                };
+                let text = format!(
+                    "Registered {} ({}) managing {}",
+                    host.name,
+                    host.address,
+                    host.users.join(", ")
+                );
+                self.register_host(host.clone()).await?;
+                Ok(call_result(text, json!({ "host": host })))
+            }
+            "ssh_key_register" => {
+                let key = self
+                    .register_key(
+                        &string("owner")?,
+                        &string("public_key")?,
+                        parameters
+                            .get("rotation_days")
+                            .and_then(|v| v.as_u64())
+                            .map(|d| d as u32),
+                    )
+                    .await?;
+                Ok(call_result(
+                    format!(
+                        "Registered {} for {} as {}",
+                        key.fingerprint, key.owner, key.id
+                    ),
+                    json!({ "key": key }),
+                ))
+            }
+            "ssh_key_grant" => {
+                let (host, user) = (string("host")?, string("user")?);
+                let key = self.grant(&string("key_id")?, &host, &user).await?;
+                Ok(call_result(
+                    format!("Granted {}'s key on {}@{}", key.owner, user, host),
+                    json!({ "key": key }),
+                ))
+            }
+            "ssh_key_revoke" => {
+                let (key, failed) = self.revoke(&string("key_id")?).await?;
+                let mut text = format!("Revoked {}'s key {}", key.owner, key.fingerprint);
+                for (grant, error) in &failed {
+                    text.push_str(&format!(
+                        "\n- still on {}@{}: {}",
+                        grant.user, grant.host, error
+                    ));
+                }
+                Ok(call_result(text, json!({ "key": key, "failed": failed })))
+            }
+            "ssh_key_rotate" => {
+                let results = match parameters.get("key_id").and_then(|v| v.as_str()) {
+                    Some(id) => vec![self.rotate(id).await],
+                    None => self.rotate_due().await,
+                };
+                let mut text = format!("{} keys rotated", results.len());
+                let mut rotations = Vec::new();
+                let mut errors = Vec::new();
+                for result in results {
+                    match result {
+                        Ok(rotation) => {
+                            text.push_str(&format!(
+                                "\n- {}: {} -> {} on {} accounts, private key at {}",
+                                rotation.owner,
+                                rotation.old_key_id,
+                                rotation.new_key_id,
+                                rotation.applied.len(),
+                                rotation.private_key_path.display()
+                            ));
+                            for (grant, error) in &rotation.failed {
+                                text.push_str(&format!(
+                                    "\n  failed on {}@{}: {}",
+                                    grant.user, grant.host, error
+                                ));
+                            }
+                            rotations.push(rotation);
+                        }
+                        Err(e) => {
+                            text.push_str(&format!("\n- error: {}", e));
+                            errors.push(e.to_string());
+                        }
+                    }
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "rotations": rotations, "errors": errors }),
+                ))
+            }
+            "ssh_access_inventory" => {
+                let inventory = if parameters.get("cached").and_then(|v| v.as_bool()) == Some(true)
+                {
+                    self.inventory().await
+                } else {
+                    let hosts: Vec<String> = serde_json::from_value(
+                        parameters.get("hosts").cloned().unwrap_or(json!([])),
+                    )
+                    .map_err(|e| {
+                        Error::validation_with_field(format!("Invalid hosts: {}", e), "hosts")
+                    })?;
+                    let remove = parameters
+                        .get("remove_unknown")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+                    self.scan(&hosts, remove).await?
+                };
+
+                let mut owners: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+                let mut text = String::from("SSH access inventory");
+                for account in &inventory {
+                    text.push_str(&format!("\n\n{}@{}", account.user, account.host));
+                    if let Some(error) = &account.error {
+                        text.push_str(&format!(" (error: {})", error));
+                    }
+                    for key in &account.keys {
+                        text.push_str(&format!(
+                            "\n  {:?} {} {}",
+                            key.status,
+                            key.owner.as_deref().unwrap_or(&key.comment),
+                            key.fingerprint
+                        ));
+                        if let Some(owner) = &key.owner {
+                            if key.status != KeyStatus::Missing {
+                                owners
+                                    .entry(owner.clone())
+                                    .or_default()
+                                    .insert(format!("{}@{}", account.user, account.host));
+                            }
+                        }
+                    }
+                    if account.removed > 0 {
+                        text.push_str(&format!("\n  removed {} unknown keys", account.removed));
+                    }
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "inventory": inventory, "access_by_owner": owners }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "access_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_and_edits_authorized_keys() {
+        let alice = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIGJhc2U2NGtleWFsaWNlMDEyMzQ1Njc4OTAxMjM0NTY3 alice@laptop";
+        let bob = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIGJvYmJvYmJvYmJvYmJvYmJvYmJvYmJvYmJvYmJvYmJvYmJv bob@desk";
+        let stranger =
+            "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIHN0cmFuZ2Vyc3RyYW5nZXJzdHJhbmdlcnN0cmFuZ2Vy";
+        let now = Utc::now();
+        let managed = |owner: &str, line: &str, days_old: i64| ManagedKey {
+            id: owner.to_string(),
+            owner: owner.to_string(),
+            public_key: line.to_string(),
+            fingerprint: parse_key_line(line).unwrap().fingerprint,
+            created_at: now - ChronoDuration::days(days_old),
+            rotation_days: Some(90),
+            grants: vec![Grant {
+                host: "web1".to_string(),
+                user: "deploy".to_string(),
+            }],
+            revoked_at: None,
+            replaced_by: None,
+        };
+        let keys = vec![managed("alice", alice, 100), managed("bob", bob, 1)];
+        let file = format!(
+            "# managed\nfrom=\"10.0.0.0/8,192.168.1.1\" {}\n{}\n",
+            alice, stranger
+        );
+
+        let parsed = parse_key_line(file.lines().nth(1).unwrap()).unwrap();
+        assert_eq!(
+            parsed.options.as_deref(),
+            Some("from=\"10.0.0.0/8,192.168.1.1\"")
+        );
+        assert_eq!(parsed.comment, "alice@laptop");
+
+        let entries = classify("web1", "deploy", &file, &keys, now);
+        let statuses: Vec<_> = entries
+            .iter()
+            .map(|e| (e.owner.as_deref(), e.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (Some("alice"), KeyStatus::RotationDue),
+                (None, KeyStatus::Unknown),
+                (Some("bob"), KeyStatus::Missing),
+            ]
+        );
+
+        let remove = BTreeSet::from([entries[1].fingerprint.clone()]);
+        let edited = edit_authorized_keys(&file, &remove, &[bob.to_string(), alice.to_string()]);
+        assert_eq!(
+            edited,
+            format!(
+                "# managed\nfrom=\"10.0.0.0/8,192.168.1.1\" {}\n{}\n",
+                alice, bob
+            )
+        );
+    }
+}
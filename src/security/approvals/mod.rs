@@ -0,0 +1,452 @@
+/// Approval gates for disruptive operations
+///
+/// A caller files a request describing exactly what it wants to do; a
+/// different person approves or denies it. Approved requests are single-use
+/// and expire, and the gated subsystem executes the stored details rather
+/// than anything supplied alongside the approval id.
+use crate::collaboration::notify::{Notification, Notifier, Severity};
+use crate::collaboration::AttachmentField;
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Approval configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalsConfig {
+    /// Minutes a request stays valid, pending or approved
+    pub ttl_minutes: i64,
+    /// Collaboration channels notified of new requests
+    pub channels: Vec<String>,
+    /// Let requesters approve their own requests (single-operator setups)
+    pub allow_self_approval: bool,
+}
+
+impl Default for ApprovalsConfig {
+    fn default() -> Self {
+        Self {
+            ttl_minutes: 60,
+            channels: std::env::var("APPROVAL_CHANNELS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string)
+                .collect(),
+            allow_self_approval: false,
+        }
+    }
+}
+
+/// Approval request state
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Denied,
+    /// Executed by the gated subsystem
+    Consumed,
+    Expired,
+}
+
+/// A request to perform a gated action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    /// Gated action, e.g. `chaos.experiment`
+    pub action: String,
+    pub summary: String,
+    /// What will be executed once approved
+    pub details: Value,
+    pub requested_by: String,
+    pub requested_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub status: ApprovalStatus,
+    pub decided_by: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+}
+
+impl ApprovalRequest {
+    fn expire_if_due(&mut self, now: DateTime<Utc>) {
+        if matches!(
+            self.status,
+            ApprovalStatus::Pending | ApprovalStatus::Approved
+        ) && now >= self.expires_at
+        {
+            self.status = ApprovalStatus::Expired;
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ApprovalStore {
+    requests: BTreeMap<String, ApprovalRequest>,
+}
+
+/// Approval requests and decisions
+pub struct ApprovalManager {
+    config: ApprovalsConfig,
+    notifier: Option<Arc<Notifier>>,
+    /// Where requests are persisted; `None` keeps them in memory
+    path: Option<PathBuf>,
+    store: RwLock<ApprovalStore>,
+}
+
+impl ApprovalManager {
+    /// Open a manager, loading requests from `path` if it exists
+    pub async fn open(config: ApprovalsConfig, path: Option<PathBuf>) -> Result<Self> {
+        let store = match &path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse approval store {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => ApprovalStore::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read approval store: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => ApprovalStore::default(),
+        };
+
+        Ok(Self {
+            config,
+            notifier: None,
+            path,
+            store: RwLock::new(store),
+        })
+    }
+
+    /// Announce new requests through collaboration channels
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    async fn persist(&self, store: &ApprovalStore) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create approval store directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to write approval store: {}", e),
+                    temp.clone(),
+                )
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace approval store: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// File a request for a gated action
+    pub async fn request(
+        &self,
+        action: &str,
+        summary: &str,
+        details: Value,
+        requested_by: &str,
+    ) -> Result<ApprovalRequest> {
+        let now = Utc::now();
+        let request = ApprovalRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            action: action.to_string(),
+            summary: summary.to_string(),
+            details,
+            requested_by: requested_by.to_string(),
+            requested_at: now,
+            expires_at: now + Duration::minutes(self.config.ttl_minutes.max(1)),
+            status: ApprovalStatus::Pending,
+            decided_by: None,
+            decided_at: None,
+            reason: None,
+        };
+        {
+            let mut store = self.store.write().await;
+            store.requests.insert(request.id.clone(), request.clone());
+            self.persist(&store).await?;
+        }
+        self.announce(&request).await;
+        Ok(request)
+    }
+
+    async fn announce(&self, request: &ApprovalRequest) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+        if self.config.channels.is_empty() {
+            return;
+        }
+        let notification = Notification {
+            title: format!("Approval needed: {}", request.action),
+            text: request.summary.clone(),
+            severity: Severity::Warning,
+            fields: vec![
+                AttachmentField {
+                    title: "Request".to_string(),
+                    value: request.id.clone(),
+                    short: true,
+                },
+                AttachmentField {
+                    title: "Requested by".to_string(),
+                    value: request.requested_by.clone(),
+                    short: true,
+                },
+            ],
+        };
+        for (channel, error) in notifier
+            .broadcast(&self.config.channels, &notification)
+            .await
+        {
+            tracing::warn!("Approval announcement to {} failed: {}", channel, error);
+        }
+    }
+
+    /// Approve or deny a pending request
+    pub async fn decide(
+        &self,
+        id: &str,
+        approver: &str,
+        approve: bool,
+        reason: Option<String>,
+    ) -> Result<ApprovalRequest> {
+        let now = Utc::now();
+        let mut store = self.store.write().await;
+        let request = store.requests.get_mut(id).ok_or_else(|| {
+            Error::not_found_with_resource("Approval request not found", "approval", id)
+        })?;
+        request.expire_if_due(now);
+        if request.status != ApprovalStatus::Pending {
+            return Err(Error::validation(format!(
+                "Approval request {} is {:?}, not pending",
+                id, request.status
+            )));
+        }
+        if !self.config.allow_self_approval && request.requested_by == approver {
+            return Err(Error::validation_with_field(
+                "Requests must be decided by someone other than the requester",
+                "approver",
+            ));
+        }
+        request.status = if approve {
+            ApprovalStatus::Approved
+        } else {
+            ApprovalStatus::Denied
+        };
+        request.decided_by = Some(approver.to_string());
+        request.decided_at = Some(now);
+        request.reason = reason;
+        let request = request.clone();
+        self.persist(&store).await?;
+        Ok(request)
+    }
+
+    /// Use an approved request for `action`; each approval runs once
+    pub async fn consume(&self, id: &str, action: &str) -> Result<ApprovalRequest> {
+        let mut store = self.store.write().await;
+        let request = store.requests.get_mut(id).ok_or_else(|| {
+            Error::not_found_with_resource("Approval request not found", "approval", id)
+        })?;
+        request.expire_if_due(Utc::now());
+        if request.action != action {
+            return Err(Error::validation(format!(
+                "Approval {} is for {}, not {}",
+                id, request.action, action
+            )));
+        }
+        if request.status != ApprovalStatus::Approved {
+            return Err(Error::validation(format!(
+                "Approval request {} is {:?}, not approved",
+                id, request.status
+            )));
+        }
+        request.status = ApprovalStatus::Consumed;
+        let request = request.clone();
+        self.persist(&store).await?;
+        Ok(request)
+    }
+
+    /// Requests, newest first, optionally filtered by status
+    pub async fn list(&self, status: Option<ApprovalStatus>) -> Vec<ApprovalRequest> {
+        let now = Utc::now();
+        let mut store = self.store.write().await;
+        for request in store.requests.values_mut() {
+            request.expire_if_due(now);
+        }
+        let mut requests: Vec<ApprovalRequest> = store
+            .requests
+            .values()
+            .filter(|r| status.is_none_or(|s| r.status == s))
+            .cloned()
+            .collect();
+        requests.sort_by_key(|r| std::cmp::Reverse(r.requested_at));
+        requests
+    }
+
+    /// Get tool definitions for approvals
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "approval_list",
+                "List approval requests for gated operations",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "status": {"type": "string", "enum": ["pending", "approved", "denied", "consumed", "expired"]}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "approval_decide",
+                "Approve or deny a pending request; the approver must differ from the requester",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "approver": {"type": "string"},
+                        "approve": {"type": "boolean"},
+                        "reason": {"type": "string"}
+                    },
+                    "required": ["id", "approver", "approve"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute an approval tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "approval_list" => {
+                let status: Option<ApprovalStatus> = match parameters.get("status") {
+                    Some(status) => Some(serde_json::from_value(status.clone()).map_err(|_| {
+                        Error::validation_with_field("Unknown approval status", "status")
+                    })?),
+                    None => None,
+                };
+                let requests = self.list(status).await;
+                let mut text = format!("{} approval requests", requests.len());
+                for request in &requests {
+                    text.push_str(&format!(
+                        "\n{} [{:?}] {} by {}: {}",
+                        request.id,
+                        request.status,
+                        request.action,
+                        request.requested_by,
+                        request.summary
+                    ));
+                }
+                Ok(call_result(text, json!({ "requests": requests })))
+            }
+            "approval_decide" => {
+                let id = parameters
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::validation_with_field("id is required", "id"))?;
+                let approver = parameters
+                    .get("approver")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| {
+                        Error::validation_with_field("approver is required", "approver")
+                    })?;
+                let approve = parameters
+                    .get("approve")
+                    .and_then(|v| v.as_bool())
+                    .ok_or_else(|| {
+                        Error::validation_with_field("approve is required", "approve")
+                    })?;
+                let reason = parameters
+                    .get("reason")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string);
+                let request = self.decide(id, approver, approve, reason).await?;
+                Ok(call_result(
+                    format!("{} {:?} by {}", request.id, request.status, approver),
+                    json!({ "request": request }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "approval_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn approvals_are_decided_by_others_and_used_once() {
+        let approvals = ApprovalManager::open(ApprovalsConfig::default(), None)
+            .await
+            .unwrap();
+        let request = approvals
+            .request(
+                "chaos.experiment",
+                "kill 1 web pod",
+                json!({"x": 1}),
+                "alice",
+            )
+            .await
+            .unwrap();
+        assert!(approvals
+            .consume(&request.id, "chaos.experiment")
+            .await
+            .is_err());
+        assert!(approvals
+            .decide(&request.id, "alice", true, None)
+            .await
+            .is_err());
+        approvals
+            .decide(&request.id, "bob", true, None)
+            .await
+            .unwrap();
+        assert!(approvals.consume(&request.id, "db.restore").await.is_err());
+        let used = approvals
+            .consume(&request.id, "chaos.experiment")
+            .await
+            .unwrap();
+        assert_eq!(used.details, json!({"x": 1}));
+        assert!(approvals
+            .consume(&request.id, "chaos.experiment")
+            .await
+            .is_err());
+        assert_eq!(
+            approvals.list(Some(ApprovalStatus::Consumed)).await.len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,851 @@
+/// Canary tokens: decoy credentials, hostnames and URLs that alert when used
+///
+/// Tokens are planted in configured locations (directories such as shared
+/// drives or repository checkouts). Web beacons are served by the canary
+/// router itself; AWS key and DNS canaries rely on an external detector
+/// (CloudTrail rule, DNS query log forwarder) posting to the trigger webhook.
+/// Every trigger raises a critical `UnifiedAlert`.
+use crate::collaboration::notify::{Notification, Notifier, Severity};
+use crate::collaboration::AttachmentField;
+use crate::error::{Error, Result};
+use crate::monitoring::{AlertSeverity, AlertSource, AlertStatus, UnifiedAlert};
+use crate::tools::{call_result, ToolDefinition};
+use axum::extract::{Path as UrlPath, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{broadcast, RwLock};
+
+/// Triggers kept in the store
+const MAX_TRIGGERS: usize = 1000;
+
+/// 1x1 transparent GIF served to web beacon hits
+const PIXEL: &[u8] = &[
+    0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0x21, 0xf9, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x02, 0x44, 0x01, 0x00, 0x3b,
+];
+
+/// Canary configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryConfig {
+    /// Public URL the canary router is reachable at, for web beacons
+    pub base_url: Option<String>,
+    /// Zone whose query logs are forwarded to the trigger webhook, for DNS canaries
+    pub dns_zone: Option<String>,
+    /// Shared secret detectors send in the `X-Canary-Secret` header
+    pub webhook_secret: Option<String>,
+    /// Named directories canaries can be deployed to
+    pub locations: BTreeMap<String, PathBuf>,
+    /// Collaboration channels notified when a canary fires
+    pub channels: Vec<String>,
+}
+
+impl Default for CanaryConfig {
+    fn default() -> Self {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+        let list = |key: &str| env(key).unwrap_or_default();
+        Self {
+            base_url: env("CANARY_BASE_URL").map(|url| url.trim_end_matches('/').to_string()),
+            dns_zone: env("CANARY_DNS_ZONE"),
+            webhook_secret: env("CANARY_WEBHOOK_SECRET"),
+            // CANARY_LOCATIONS="share=/mnt/share,repo=/srv/git/app"
+            locations: list("CANARY_LOCATIONS")
+                .split(',')
+                .filter_map(|entry| entry.split_once('='))
+                .map(|(name, path)| (name.trim().to_string(), PathBuf::from(path.trim())))
+                .collect(),
+            channels: list("CANARY_CHANNELS")
+                .split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// Kind of canary token
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryKind {
+    /// Decoy AWS access key pair
+    AwsKey,
+    /// Unique hostname in the canary DNS zone
+    Dns,
+    /// URL served by the canary router
+    WebBeacon,
+}
+
+/// The decoy handed out for a canary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CanaryMaterial {
+    AwsKey {
+        access_key_id: String,
+        secret_access_key: String,
+    },
+    Dns {
+        hostname: String,
+    },
+    WebBeacon {
+        url: String,
+    },
+}
+
+/// Where a canary was planted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deployment {
+    pub location: String,
+    pub path: PathBuf,
+    pub deployed_at: DateTime<Utc>,
+}
+
+/// A canary token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Canary {
+    pub id: String,
+    pub kind: CanaryKind,
+    /// What the canary guards, included in alerts
+    pub memo: String,
+    /// Value detectors report: the access key id, DNS label or beacon token
+    pub token: String,
+    pub material: CanaryMaterial,
+    #[serde(default)]
+    pub deployments: Vec<Deployment>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub trigger_count: u64,
+    pub last_triggered: Option<DateTime<Utc>>,
+}
+
+impl Canary {
+    /// Whether a reported value identifies this canary
+    ///
+    /// DNS detectors report full query names, which may carry extra labels
+    /// in front of the canary hostname.
+    pub fn matches(&self, value: &str) -> bool {
+        let value = value.trim().trim_end_matches('.');
+        match &self.material {
+            CanaryMaterial::Dns { hostname } => {
+                let value = value.to_ascii_lowercase();
+                value == *hostname || value.ends_with(&format!(".{}", hostname))
+            }
+            _ => value == self.token,
+        }
+    }
+
+    /// Decoy file content and default file name
+    fn bait(&self) -> (&'static str, String) {
+        match &self.material {
+            CanaryMaterial::AwsKey {
+                access_key_id,
+                secret_access_key,
+            } => (
+                "credentials",
+                format!(
+                    "[default]\naws_access_key_id = {}\naws_secret_access_key = {}\nregion = us-east-1\n",
+                    access_key_id, secret_access_key
+                ),
+            ),
+            CanaryMaterial::Dns { hostname } => (
+                "replica.conf",
+                format!(
+                    "# Read replica, failover only\nhost = {}\nport = 5432\nsslmode = require\n",
+                    hostname
+                ),
+            ),
+            CanaryMaterial::WebBeacon { url } => (
+                "index.html",
+                format!(
+                    "<!DOCTYPE html>\n<html>\n<head><title>{}</title></head>\n<body>\n<p>Internal use only.</p>\n<img src=\"{}\" width=\"1\" height=\"1\" alt=\"\">\n</body>\n</html>\n",
+                    self.memo, url
+                ),
+            ),
+        }
+    }
+}
+
+/// A recorded canary trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryTrigger {
+    pub canary_id: String,
+    pub triggered_at: DateTime<Utc>,
+    pub source_ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// Detector-specific context, e.g. the CloudTrail event
+    #[serde(default)]
+    pub details: Value,
+}
+
+/// Trigger report posted by an external detector
+#[derive(Debug, Clone, Deserialize)]
+pub struct TriggerReport {
+    /// Access key id, DNS query name or beacon token
+    pub token: String,
+    pub source_ip: Option<String>,
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub details: Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CanaryStore {
+    canaries: BTreeMap<String, Canary>,
+    triggers: Vec<CanaryTrigger>,
+}
+
+fn random_string(alphabet: &[u8], len: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..len)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())] as char)
+        .collect()
+}
+
+/// Generate decoy material and its lookup token
+pub fn generate(kind: CanaryKind, config: &CanaryConfig) -> Result<(String, CanaryMaterial)> {
+    const BASE32: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    const SECRET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    const LABEL: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+    match kind {
+        CanaryKind::AwsKey => {
+            let access_key_id = format!("AKIA{}", random_string(BASE32, 16));
+            Ok((
+                access_key_id.clone(),
+                CanaryMaterial::AwsKey {
+                    access_key_id,
+                    secret_access_key: random_string(SECRET, 40),
+                },
+            ))
+        }
+        CanaryKind::Dns => {
+            let zone = config.dns_zone.as_deref().ok_or_else(|| {
+                Error::config_with_suggestion(
+                    "No canary DNS zone configured",
+                    "Set CANARY_DNS_ZONE to a zone whose query logs reach the trigger webhook",
+                )
+            })?;
+            let token = random_string(LABEL, 20);
+            Ok((
+                token.clone(),
+                CanaryMaterial::Dns {
+                    hostname: format!("{}.{}", token, zone.trim_matches('.').to_ascii_lowercase()),
+                },
+            ))
+        }
+        CanaryKind::WebBeacon => {
+            let base_url = config.base_url.as_deref().ok_or_else(|| {
+                Error::config_with_suggestion(
+                    "No canary base URL configured",
+                    "Set CANARY_BASE_URL to the public URL of the canary router",
+                )
+            })?;
+            let token = random_string(LABEL, 24);
+            Ok((
+                token.clone(),
+                CanaryMaterial::WebBeacon {
+                    url: format!("{}/canary/{}", base_url, token),
+                },
+            ))
+        }
+    }
+}
+
+/// Compare secrets without short-circuiting on the first mismatch
+fn secrets_match(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Canary token lifecycle and trigger handling
+pub struct CanaryManager {
+    config: CanaryConfig,
+    notifier: Option<Arc<Notifier>>,
+    /// Where canaries are persisted; `None` keeps them in memory
+    path: Option<PathBuf>,
+    store: RwLock<CanaryStore>,
+    alerts: broadcast::Sender<UnifiedAlert>,
+}
+
+impl CanaryManager {
+    /// Open a manager, loading canaries from `path` if it exists
+    pub async fn open(config: CanaryConfig, path: Option<PathBuf>) -> Result<Self> {
+        let store = match &path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse canary store {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => CanaryStore::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read canary store: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => CanaryStore::default(),
+        };
+
+        Ok(Self {
+            config,
+            notifier: None,
+            path,
+            store: RwLock::new(store),
+            alerts: broadcast::channel(64).0,
+        })
+    }
+
+    /// Post trigger alerts through collaboration channels
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Receive alerts raised by canary triggers
+    pub fn subscribe(&self) -> broadcast::Receiver<UnifiedAlert> {
+        self.alerts.subscribe()
+    }
+
+    async fn persist(&self, store: &CanaryStore) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create canary store directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(format!("Failed to write canary store: {}", e), temp.clone())
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace canary store: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Generate a new canary
+    pub async fn create(&self, kind: CanaryKind, memo: &str) -> Result<Canary> {
+        let (token, material) = generate(kind, &self.config)?;
+        let canary = Canary {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            memo: memo.to_string(),
+            token,
+            material,
+            deployments: Vec::new(),
+            created_at: Utc::now(),
+            trigger_count: 0,
+            last_triggered: None,
+        };
+        let mut store = self.store.write().await;
+        store.canaries.insert(canary.id.clone(), canary.clone());
+        self.persist(&store).await?;
+        Ok(canary)
+    }
+
+    /// Write a canary's decoy file into a configured location
+    pub async fn deploy(
+        &self,
+        id: &str,
+        location: &str,
+        file_name: Option<&str>,
+    ) -> Result<Deployment> {
+        let dir = self.config.locations.get(location).ok_or_else(|| {
+            Error::not_found_with_resource("Canary location not configured", "location", location)
+        })?;
+        let mut store = self.store.write().await;
+        let canary = store
+            .canaries
+            .get_mut(id)
+            .ok_or_else(|| Error::not_found_with_resource("Canary not found", "canary", id))?;
+
+        let (default_name, content) = canary.bait();
+        let file_name = file_name.unwrap_or(default_name);
+        if file_name.is_empty()
+            || file_name.contains(['/', '\\'])
+            || file_name == "."
+            || file_name == ".."
+        {
+            return Err(Error::validation_with_field(
+                "file_name must be a plain file name",
+                "file_name",
+            ));
+        }
+        let path = dir.join(file_name);
+        // Never overwrite a real file with a decoy
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(format!("Failed to create canary file: {}", e), path.clone())
+            })?;
+        file.write_all(content.as_bytes()).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to write canary file: {}", e), path.clone())
+        })?;
+
+        let deployment = Deployment {
+            location: location.to_string(),
+            path,
+            deployed_at: Utc::now(),
+        };
+        canary.deployments.push(deployment.clone());
+        self.persist(&store).await?;
+        Ok(deployment)
+    }
+
+    /// Delete a canary; deployed files are left in place
+    pub async fn remove(&self, id: &str) -> Result<Canary> {
+        let mut store = self.store.write().await;
+        let canary = store
+            .canaries
+            .remove(id)
+            .ok_or_else(|| Error::not_found_with_resource("Canary not found", "canary", id))?;
+        self.persist(&store).await?;
+        Ok(canary)
+    }
+
+    /// All canaries, oldest first
+    pub async fn list(&self) -> Vec<Canary> {
+        let mut canaries: Vec<Canary> =
+            self.store.read().await.canaries.values().cloned().collect();
+        canaries.sort_by_key(|c| c.created_at);
+        canaries
+    }
+
+    /// Recent triggers, newest first
+    pub async fn triggers(&self, canary_id: Option<&str>, limit: usize) -> Vec<CanaryTrigger> {
+        self.store
+            .read()
+            .await
+            .triggers
+            .iter()
+            .rev()
+            .filter(|t| canary_id.is_none_or(|id| t.canary_id == id))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Record a trigger and raise a critical alert
+    ///
+    /// Returns `None` when the reported value matches no canary.
+    pub async fn trigger(&self, report: TriggerReport) -> Result<Option<UnifiedAlert>> {
+        let now = Utc::now();
+        let canary = {
+            let mut store = self.store.write().await;
+            let Some(canary) = store
+                .canaries
+                .values_mut()
+                .find(|c| c.matches(&report.token))
+            else {
+                return Ok(None);
+            };
+            canary.trigger_count += 1;
+            canary.last_triggered = Some(now);
+            let canary = canary.clone();
+            store.triggers.push(CanaryTrigger {
+                canary_id: canary.id.clone(),
+                triggered_at: now,
+                source_ip: report.source_ip.clone(),
+                user_agent: report.user_agent.clone(),
+                details: report.details.clone(),
+            });
+            let excess = store.triggers.len().saturating_sub(MAX_TRIGGERS);
+            store.triggers.drain(..excess);
+            self.persist(&store).await?;
+            canary
+        };
+
+        let kind = serde_json::to_value(canary.kind)?
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let mut tags = HashMap::from([
+            ("canary_id".to_string(), canary.id.clone()),
+            ("canary_kind".to_string(), kind.clone()),
+        ]);
+        if let Some(ip) = &report.source_ip {
+            tags.insert("source_ip".to_string(), ip.clone());
+        }
+        if let Some(deployment) = canary.deployments.last() {
+            tags.insert(
+                "deployed_to".to_string(),
+                deployment.path.display().to_string(),
+            );
+        }
+        let alert = UnifiedAlert {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: format!("Canary triggered: {}", canary.memo),
+            description: format!(
+                "{} canary {} was used{}{}",
+                kind,
+                canary.token,
+                report
+                    .source_ip
+                    .as_deref()
+                    .map(|ip| format!(" from {}", ip))
+                    .unwrap_or_default(),
+                report
+                    .user_agent
+                    .as_deref()
+                    .map(|ua| format!(" ({})", ua))
+                    .unwrap_or_default(),
+            ),
+            severity: AlertSeverity::Critical,
+            sources: vec![AlertSource::Canary {
+                canary_id: canary.id.clone(),
+                kind,
+                memo: canary.memo.clone(),
+            }],
+            created_at: now,
+            status: AlertStatus::Active,
+            resolved_at: None,
+            assignee: None,
+            tags,
+        };
+
+        // No subscribers is not an error
+        let _ = self.alerts.send(alert.clone());
+        self.notify(&alert).await;
+        Ok(Some(alert))
+    }
+
+    async fn notify(&self, alert: &UnifiedAlert) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+        if self.config.channels.is_empty() {
+            return;
+        }
+        let mut fields: Vec<AttachmentField> = alert
+            .tags
+            .iter()
+            .map(|(title, value)| AttachmentField {
+                title: title.clone(),
+                value: value.clone(),
+                short: true,
+            })
+            .collect();
+        fields.sort_by(|a, b| a.title.cmp(&b.title));
+        let notification = Notification {
+            title: alert.title.clone(),
+            text: alert.description.clone(),
+            severity: Severity::Critical,
+            fields,
+        };
+        for (channel, error) in notifier
+            .broadcast(&self.config.channels, &notification)
+            .await
+        {
+            tracing::warn!("Canary alert to {} failed: {}", channel, error);
+        }
+    }
+
+    /// Routes for web beacon hits and detector webhooks
+    ///
+    /// `GET /canary/:token` serves the beacon pixel; `POST /canary/hooks`
+    /// accepts a `TriggerReport` authenticated by `X-Canary-Secret`.
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/canary/hooks", post(hook_handler))
+            .route("/canary/:token", get(beacon_handler))
+            .with_state(self)
+    }
+
+    /// Get tool definitions for canary management
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let locations: Vec<&String> = self.config.locations.keys().collect();
+        vec![
+            ToolDefinition::from_json_schema(
+                "canary_create",
+                "Generate a canary token (decoy AWS key, DNS hostname or web beacon), optionally planting it in a configured location",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "kind": {"type": "string", "enum": ["aws_key", "dns", "web_beacon"]},
+                        "memo": {"type": "string", "description": "What the canary guards; shown in alerts"},
+                        "location": {"type": "string", "enum": locations},
+                        "file_name": {"type": "string", "description": "Decoy file name (default depends on kind)"}
+                    },
+                    "required": ["kind", "memo"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "canary_deploy",
+                "Plant an existing canary's decoy file in a configured location",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "location": {"type": "string", "enum": locations},
+                        "file_name": {"type": "string"}
+                    },
+                    "required": ["id", "location"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "canary_list",
+                "List canary tokens with their deployments and recent triggers",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string", "description": "Only triggers for this canary"},
+                        "limit": {"type": "integer", "default": 20, "description": "Triggers to include"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "canary_delete",
+                "Delete a canary token; planted files are not removed",
+                "security",
+                json!({
+                    "type": "object",
+                    "properties": {"id": {"type": "string"}},
+                    "required": ["id"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a canary tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let string = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let required = |key: &'static str| {
+            string(key)
+                .ok_or_else(|| Error::validation_with_field(format!("{} is required", key), key))
+        };
+        match name {
+            "canary_create" => {
+                let kind: CanaryKind =
+                    serde_json::from_value(json!(required("kind")?)).map_err(|_| {
+                        Error::validation_with_field(
+                            "kind must be aws_key, dns or web_beacon",
+                            "kind",
+                        )
+                    })?;
+                let canary = self.create(kind, required("memo")?).await?;
+                let deployment = match string("location") {
+                    Some(location) => Some(
+                        self.deploy(&canary.id, location, string("file_name"))
+                            .await?,
+                    ),
+                    None => None,
+                };
+                let mut text =
+                    format!("Created {:?} canary {} ({})", kind, canary.id, canary.token);
+                if let Some(deployment) = &deployment {
+                    text.push_str(&format!("\nPlanted at {}", deployment.path.display()));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "canary": canary, "deployment": deployment }),
+                ))
+            }
+            "canary_deploy" => {
+                let id = required("id")?;
+                let deployment = self
+                    .deploy(id, required("location")?, string("file_name"))
+                    .await?;
+                Ok(call_result(
+                    format!("Planted canary {} at {}", id, deployment.path.display()),
+                    json!({ "deployment": deployment }),
+                ))
+            }
+            "canary_list" => {
+                let limit = parameters
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(20) as usize;
+                let canaries = self.list().await;
+                let triggers = self.triggers(string("id"), limit).await;
+                let mut text = format!("{} canaries", canaries.len());
+                for canary in &canaries {
+                    text.push_str(&format!(
+                        "\n{} {:?} \"{}\": {} deployments, {} triggers",
+                        canary.id,
+                        canary.kind,
+                        canary.memo,
+                        canary.deployments.len(),
+                        canary.trigger_count
+                    ));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "canaries": canaries, "triggers": triggers }),
+                ))
+            }
+            "canary_delete" => {
+                let canary = self.remove(required("id")?).await?;
+                let mut text = format!("Deleted canary {}", canary.id);
+                for deployment in &canary.deployments {
+                    text.push_str(&format!("\nStill planted: {}", deployment.path.display()));
+                }
+                Ok(call_result(text, json!({ "canary": canary })))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "canary_tool",
+                name,
+            )),
+        }
+    }
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+async fn beacon_handler(
+    State(manager): State<Arc<CanaryManager>>,
+    UrlPath(token): UrlPath<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let source_ip = header_value(&headers, "x-forwarded-for")
+        .and_then(|ips| ips.split(',').next().map(|ip| ip.trim().to_string()));
+    let report = TriggerReport {
+        token,
+        source_ip,
+        user_agent: header_value(&headers, "user-agent"),
+        details: json!({ "referer": header_value(&headers, "referer") }),
+    };
+    if let Err(e) = manager.trigger(report).await {
+        tracing::warn!("Failed to record canary beacon hit: {}", e);
+    }
+    // Unknown tokens get the same response so probing reveals nothing
+    ([(header::CONTENT_TYPE, "image/gif")], PIXEL)
+}
+
+async fn hook_handler(
+    State(manager): State<Arc<CanaryManager>>,
+    headers: HeaderMap,
+    Json(report): Json<TriggerReport>,
+) -> (StatusCode, Json<Value>) {
+    let Some(secret) = &manager.config.webhook_secret else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "webhook secret not configured" })),
+        );
+    };
+    let provided = header_value(&headers, "x-canary-secret").unwrap_or_default();
+    if !secrets_match(secret, &provided) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid secret" })),
+        );
+    }
+    match manager.trigger(report).await {
+        Ok(Some(alert)) => (StatusCode::ACCEPTED, Json(json!({ "alert_id": alert.id }))),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "unknown canary" })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn generates_canaries_and_raises_critical_alerts() {
+        let config = CanaryConfig {
+            base_url: Some("https://canary.example.com".to_string()),
+            dns_zone: Some("Canary.Example.com.".to_string()),
+            webhook_secret: None,
+            locations: BTreeMap::new(),
+            channels: Vec::new(),
+        };
+        let manager = CanaryManager::open(config, None).await.unwrap();
+        let mut alerts = manager.subscribe();
+
+        let key = manager
+            .create(CanaryKind::AwsKey, "prod deploy key")
+            .await
+            .unwrap();
+        assert!(key.token.starts_with("AKIA") && key.token.len() == 20);
+        let dns = manager.create(CanaryKind::Dns, "db replica").await.unwrap();
+        let CanaryMaterial::Dns { hostname } = &dns.material else {
+            panic!("expected DNS material");
+        };
+        assert!(hostname.ends_with(".canary.example.com"));
+        let beacon = manager.create(CanaryKind::WebBeacon, "wiki").await.unwrap();
+        assert!(beacon
+            .bait()
+            .1
+            .contains("https://canary.example.com/canary/"));
+
+        let report = |token: String| TriggerReport {
+            token,
+            source_ip: Some("203.0.113.9".to_string()),
+            user_agent: None,
+            details: Value::Null,
+        };
+        let alert = manager
+            .trigger(report(format!("x1.{}.", hostname.to_uppercase())))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(alert.severity, AlertSeverity::Critical));
+        assert_eq!(alert.tags["canary_id"], dns.id);
+        assert_eq!(alerts.recv().await.unwrap().id, alert.id);
+        assert!(manager
+            .trigger(report("AKIAUNKNOWN".to_string()))
+            .await
+            .unwrap()
+            .is_none());
+
+        let triggers = manager.triggers(None, 10).await;
+        assert_eq!(triggers.len(), 1);
+        let dns = manager
+            .list()
+            .await
+            .into_iter()
+            .find(|c| c.id == dns.id)
+            .unwrap();
+        assert_eq!(dns.trigger_count, 1);
+    }
+}
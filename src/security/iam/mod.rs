@@ -0,0 +1,956 @@
+/// IAM and RBAC privilege analysis across AWS, Azure and Kubernetes
+///
+/// Inventories AWS IAM users, groups, roles and policies, Azure role
+/// assignments and Kubernetes RBAC bindings, then flags over-privileged
+/// principals (wildcard actions, admin roles at broad scopes, cluster-admin
+/// bindings, escalation verbs) with a least-privilege recommendation for each.
+use crate::cloud::CloudModule;
+use crate::error::{Error, Result};
+use crate::infrastructure::kubernetes::KubernetesClient;
+use crate::lifecycle::LifecycleManager;
+use crate::tools::{call_result, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// Finding severity
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// One step lower, for grants confined to a namespace
+    fn narrowed(self) -> Self {
+        match self {
+            Severity::Critical => Severity::High,
+            Severity::High => Severity::Medium,
+            _ => Severity::Low,
+        }
+    }
+}
+
+/// Platform a principal belongs to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Aws,
+    Azure,
+    Kubernetes,
+}
+
+impl std::str::FromStr for Platform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "aws" => Ok(Platform::Aws),
+            "azure" => Ok(Platform::Azure),
+            "kubernetes" | "k8s" => Ok(Platform::Kubernetes),
+            other => Err(Error::validation_with_field(
+                format!("Unknown platform: {}", other),
+                "platforms",
+            )),
+        }
+    }
+}
+
+/// An over-privileged grant
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IamFinding {
+    pub platform: Platform,
+    pub principal: String,
+    pub principal_type: String,
+    pub severity: Severity,
+    /// Rule identifier, e.g. `wildcard-action`
+    pub rule: String,
+    pub detail: String,
+    pub recommendation: String,
+}
+
+/// What was inventoried on a platform
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Inventory {
+    pub principals: usize,
+    pub policies: usize,
+    pub bindings: usize,
+}
+
+/// Platform that could not be inventoried
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedPlatform {
+    pub platform: Platform,
+    pub reason: String,
+}
+
+/// Least-privilege audit report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IamReport {
+    pub inventory: BTreeMap<Platform, Inventory>,
+    /// Findings, most severe first
+    pub findings: Vec<IamFinding>,
+    pub skipped: Vec<SkippedPlatform>,
+}
+
+impl IamReport {
+    fn add(&mut self, platform: Platform, inventory: Inventory, findings: Vec<IamFinding>) {
+        self.inventory.insert(platform, inventory);
+        for finding in findings {
+            if !self.findings.contains(&finding) {
+                self.findings.push(finding);
+            }
+        }
+        self.findings.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then_with(|| a.platform.cmp(&b.platform))
+                .then_with(|| a.principal.cmp(&b.principal))
+        });
+    }
+
+    /// Number of findings per severity
+    pub fn counts(&self) -> BTreeMap<Severity, usize> {
+        let mut counts = BTreeMap::new();
+        for finding in &self.findings {
+            *counts.entry(finding.severity).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+fn as_list(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Null => Vec::new(),
+        other => vec![other],
+    }
+}
+
+fn as_strings(value: &Value) -> Vec<&str> {
+    as_list(value)
+        .into_iter()
+        .filter_map(|v| v.as_str())
+        .collect()
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                (Some(high), Some(low)) => {
+                    out.push(high << 4 | low);
+                    i += 2;
+                }
+                _ => out.push(b'%'),
+            },
+            b'+' => out.push(b' '),
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Policy documents are objects in CLI output but URL-encoded strings in the raw API
+fn policy_document(value: &Value) -> Value {
+    match value {
+        Value::String(text) => serde_json::from_str(text)
+            .or_else(|_| serde_json::from_str(&percent_decode(text)))
+            .unwrap_or(Value::Null),
+        other => other.clone(),
+    }
+}
+
+/// IAM actions that let a principal grant itself more access
+const AWS_ESCALATION_ACTIONS: &[&str] = &[
+    "iam:attachuserpolicy",
+    "iam:attachrolepolicy",
+    "iam:attachgrouppolicy",
+    "iam:putuserpolicy",
+    "iam:putrolepolicy",
+    "iam:putgrouppolicy",
+    "iam:createpolicyversion",
+    "iam:setdefaultpolicyversion",
+    "iam:createaccesskey",
+    "iam:updateassumerolepolicy",
+    "iam:addusertogroup",
+];
+
+/// Over-broad statements in an AWS policy document
+fn aws_document_issues(document: &Value) -> Vec<(Severity, &'static str, String, String)> {
+    let mut issues = Vec::new();
+    for statement in as_list(&document["Statement"]) {
+        if statement["Effect"].as_str() != Some("Allow") {
+            continue;
+        }
+        let resources = as_strings(&statement["Resource"]);
+        let all_resources = resources.contains(&"*");
+        let on = if all_resources {
+            " on all resources"
+        } else {
+            ""
+        };
+
+        if !statement["NotAction"].is_null() {
+            issues.push((
+                Severity::High,
+                "not-action-allow",
+                format!(
+                    "Allow with NotAction grants everything except {}",
+                    statement["NotAction"]
+                ),
+                "Replace NotAction with an explicit Action list".to_string(),
+            ));
+        }
+
+        for action in as_strings(&statement["Action"]) {
+            let lower = action.to_ascii_lowercase();
+            if lower == "*" {
+                issues.push((
+                    if all_resources { Severity::Critical } else { Severity::High },
+                    "wildcard-action",
+                    format!("Allows all actions{}", on),
+                    "Grant only the actions the principal uses; generate a policy from CloudTrail activity with IAM Access Analyzer".to_string(),
+                ));
+            } else if let Some(service) = lower.strip_suffix(":*") {
+                let severity = if service == "iam" || service == "sts" || service == "organizations"
+                {
+                    Severity::Critical
+                } else {
+                    Severity::High
+                };
+                issues.push((
+                    severity,
+                    "service-wildcard",
+                    format!("Allows all {} actions{}", service, on),
+                    format!(
+                        "List the specific {}: actions needed and scope Resource to named ARNs",
+                        service
+                    ),
+                ));
+            } else if AWS_ESCALATION_ACTIONS.contains(&lower.as_str())
+                || (lower == "iam:passrole" && all_resources)
+            {
+                issues.push((
+                    Severity::High,
+                    "privilege-escalation",
+                    format!("Allows {}{}", action, on),
+                    "Restrict Resource to specific roles or users and add a permissions boundary"
+                        .to_string(),
+                ));
+            }
+        }
+    }
+    issues
+}
+
+fn aws_findings(principal: &str, kind: &str, source: &str, document: &Value) -> Vec<IamFinding> {
+    aws_document_issues(document)
+        .into_iter()
+        .map(|(severity, rule, detail, recommendation)| IamFinding {
+            platform: Platform::Aws,
+            principal: principal.to_string(),
+            principal_type: kind.to_string(),
+            severity,
+            rule: rule.to_string(),
+            detail: format!("{} via {}", detail, source),
+            recommendation,
+        })
+        .collect()
+}
+
+/// Analyze `aws iam get-account-authorization-details` output
+pub fn analyze_aws(details: &Value) -> (Inventory, Vec<IamFinding>) {
+    let mut findings = Vec::new();
+    let managed: HashMap<&str, (&str, Value)> = as_list(&details["Policies"])
+        .into_iter()
+        .filter_map(|policy| {
+            let arn = policy["Arn"].as_str()?;
+            let name = policy["PolicyName"].as_str().unwrap_or(arn);
+            let document = as_list(&policy["PolicyVersionList"])
+                .into_iter()
+                .find(|v| v["IsDefaultVersion"].as_bool() == Some(true))
+                .map(|v| policy_document(&v["Document"]))
+                .unwrap_or(Value::Null);
+            Some((arn, (name, document)))
+        })
+        .collect();
+
+    let groups: HashMap<&str, &Value> = as_list(&details["GroupDetailList"])
+        .into_iter()
+        .filter_map(|g| Some((g["GroupName"].as_str()?, g)))
+        .collect();
+
+    // Inline documents and attached managed documents of a user, group or role
+    let documents = |entity: &Value, inline_key: &str| -> Vec<(String, Value)> {
+        let mut docs: Vec<(String, Value)> = as_list(&entity[inline_key])
+            .into_iter()
+            .map(|p| {
+                (
+                    format!("inline policy {}", p["PolicyName"].as_str().unwrap_or("?")),
+                    policy_document(&p["PolicyDocument"]),
+                )
+            })
+            .collect();
+        for attached in as_list(&entity["AttachedManagedPolicies"]) {
+            let arn = attached["PolicyArn"].as_str().unwrap_or_default();
+            if let Some((name, document)) = managed.get(arn) {
+                docs.push((format!("managed policy {}", name), document.clone()));
+            }
+        }
+        docs
+    };
+
+    let users = as_list(&details["UserDetailList"]);
+    for user in &users {
+        let name = user["UserName"].as_str().unwrap_or("?");
+        let direct = documents(user, "UserPolicyList");
+        for (source, document) in &direct {
+            findings.extend(aws_findings(name, "user", source, document));
+        }
+        for group_name in as_strings(&user["GroupList"]) {
+            if let Some(group) = groups.get(group_name) {
+                for (source, document) in documents(group, "GroupPolicyList") {
+                    findings.extend(aws_findings(
+                        name,
+                        "user",
+                        &format!("{} of group {}", source, group_name),
+                        &document,
+                    ));
+                }
+            }
+        }
+        if !direct.is_empty() {
+            findings.push(IamFinding {
+                platform: Platform::Aws,
+                principal: name.to_string(),
+                principal_type: "user".to_string(),
+                severity: Severity::Low,
+                rule: "direct-user-policy".to_string(),
+                detail: format!("{} policies attached directly to the user", direct.len()),
+                recommendation: "Grant permissions through groups or assumable roles".to_string(),
+            });
+        }
+    }
+
+    let roles = as_list(&details["RoleDetailList"]);
+    for role in &roles {
+        // Service-linked roles are managed by AWS and cannot be narrowed
+        if role["Path"]
+            .as_str()
+            .is_some_and(|p| p.starts_with("/aws-service-role/"))
+        {
+            continue;
+        }
+        let name = role["RoleName"].as_str().unwrap_or("?");
+        for (source, document) in documents(role, "RolePolicyList") {
+            findings.extend(aws_findings(name, "role", &source, &document));
+        }
+
+        let trust = policy_document(&role["AssumeRolePolicyDocument"]);
+        for statement in as_list(&trust["Statement"]) {
+            let principal = &statement["Principal"];
+            let public =
+                principal.as_str() == Some("*") || as_strings(&principal["AWS"]).contains(&"*");
+            if statement["Effect"].as_str() == Some("Allow")
+                && public
+                && statement["Condition"].is_null()
+            {
+                findings.push(IamFinding {
+                    platform: Platform::Aws,
+                    principal: name.to_string(),
+                    principal_type: "role".to_string(),
+                    severity: Severity::Critical,
+                    rule: "public-trust".to_string(),
+                    detail: "Trust policy lets any AWS principal assume the role".to_string(),
+                    recommendation:
+                        "Name the trusted accounts or roles, or add an sts:ExternalId / aws:PrincipalOrgID condition"
+                            .to_string(),
+                });
+            }
+        }
+    }
+
+    let inventory = Inventory {
+        principals: users.len() + roles.len(),
+        policies: managed.len(),
+        bindings: groups.len(),
+    };
+    (inventory, findings)
+}
+
+/// Breadth of an Azure scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AzureScope {
+    ManagementGroup,
+    Subscription,
+    ResourceGroup,
+    Resource,
+}
+
+fn azure_scope(scope: &str) -> AzureScope {
+    let segments: Vec<&str> = scope.trim_matches('/').split('/').collect();
+    if scope == "/" || scope.to_ascii_lowercase().contains("/managementgroups/") {
+        AzureScope::ManagementGroup
+    } else if segments.len() <= 2 {
+        AzureScope::Subscription
+    } else if segments.len() <= 4 {
+        AzureScope::ResourceGroup
+    } else {
+        AzureScope::Resource
+    }
+}
+
+/// Analyze `az role assignment list` output
+pub fn analyze_azure(assignments: &Value) -> (Inventory, Vec<IamFinding>) {
+    let mut findings = Vec::new();
+    let mut principals = BTreeSet::new();
+    let mut roles = BTreeSet::new();
+    let items = as_list(assignments);
+
+    for assignment in &items {
+        let principal = assignment["principalName"]
+            .as_str()
+            .filter(|n| !n.is_empty())
+            .or_else(|| assignment["principalId"].as_str())
+            .unwrap_or("?");
+        let principal_type = assignment["principalType"].as_str().unwrap_or("Unknown");
+        let role = assignment["roleDefinitionName"]
+            .as_str()
+            .unwrap_or_default();
+        let scope = assignment["scope"].as_str().unwrap_or("/");
+        principals.insert(principal);
+        roles.insert(role);
+
+        let level = azure_scope(scope);
+        let broad = matches!(
+            level,
+            AzureScope::ManagementGroup | AzureScope::Subscription
+        );
+        let (severity, rule, recommendation) = match role {
+            "Owner" => (
+                if broad {
+                    Severity::Critical
+                } else if level == AzureScope::ResourceGroup {
+                    Severity::High
+                } else {
+                    Severity::Medium
+                },
+                "owner-role",
+                "Use Contributor plus a scoped access-management role, or Privileged Identity Management for just-in-time Owner",
+            ),
+            "User Access Administrator" | "Role Based Access Control Administrator" => (
+                if broad { Severity::Critical } else { Severity::High },
+                "access-admin-role",
+                "Limit role assignment rights to the resource groups the principal administers, with a condition on assignable roles",
+            ),
+            // Resource-level Contributor is the expected least-privilege shape
+            "Contributor" if level == AzureScope::Resource => continue,
+            "Contributor" => (
+                if broad { Severity::High } else { Severity::Medium },
+                "contributor-role",
+                "Replace Contributor with service-specific roles (e.g. Virtual Machine Contributor) scoped to resource groups",
+            ),
+            _ => continue,
+        };
+        findings.push(IamFinding {
+            platform: Platform::Azure,
+            principal: principal.to_string(),
+            principal_type: principal_type.to_string(),
+            severity,
+            rule: rule.to_string(),
+            detail: format!("{} at {:?} scope {}", role, level, scope),
+            recommendation: recommendation.to_string(),
+        });
+    }
+
+    let inventory = Inventory {
+        principals: principals.len(),
+        policies: roles.len(),
+        bindings: items.len(),
+    };
+    (inventory, findings)
+}
+
+/// Subjects that always represent every caller
+const K8S_ANONYMOUS: &[&str] = &[
+    "system:anonymous",
+    "system:unauthenticated",
+    "system:authenticated",
+];
+
+/// Roles that are safe to bind to every caller
+const K8S_PUBLIC_ROLES: &[&str] = &[
+    "system:public-info-viewer",
+    "system:discovery",
+    "system:basic-user",
+];
+
+/// Over-broad rules in a Role or ClusterRole
+fn rbac_rule_issues(rules: &Value) -> Vec<(Severity, &'static str, String, String)> {
+    let mut issues = Vec::new();
+    for rule in as_list(rules) {
+        let verbs = as_strings(&rule["verbs"]);
+        let resources = as_strings(&rule["resources"]);
+        let any_verb = verbs.contains(&"*");
+        let reads = |v: &&str| matches!(*v, "*" | "get" | "list" | "watch");
+
+        if any_verb && resources.contains(&"*") {
+            issues.push((
+                Severity::Critical,
+                "wildcard-rbac",
+                "All verbs on all resources".to_string(),
+                "Bind a role listing only the resources and verbs the subject uses".to_string(),
+            ));
+            continue;
+        }
+        for verb in ["escalate", "bind", "impersonate"] {
+            if verbs.contains(&verb)
+                || (any_verb
+                    && resources.iter().any(|r| {
+                        matches!(
+                            *r,
+                            "roles" | "clusterroles" | "users" | "groups" | "serviceaccounts"
+                        )
+                    }))
+            {
+                issues.push((
+                    Severity::High,
+                    "rbac-escalation",
+                    format!("Can {} on {}", verb, resources.join(", ")),
+                    format!(
+                        "Remove the {} verb; only cluster administrators should hold it",
+                        verb
+                    ),
+                ));
+                break;
+            }
+        }
+        if resources.iter().any(|r| matches!(*r, "secrets" | "*")) && verbs.iter().any(reads) {
+            issues.push((
+                Severity::Medium,
+                "secret-read",
+                "Can read secrets".to_string(),
+                "Restrict secret access with resourceNames to the secrets the workload mounts"
+                    .to_string(),
+            ));
+        }
+        if resources
+            .iter()
+            .any(|r| matches!(*r, "pods/exec" | "pods/attach" | "nodes/proxy"))
+            && verbs.iter().any(|v| matches!(*v, "*" | "create" | "get"))
+        {
+            issues.push((
+                Severity::Medium,
+                "pod-exec",
+                format!("Can exec into workloads ({})", resources.join(", ")),
+                "Grant exec only to break-glass roles".to_string(),
+            ));
+        }
+    }
+    issues
+}
+
+/// Analyze a `kubectl get clusterroles,clusterrolebindings,roles,rolebindings -A -o json` list
+pub fn analyze_kubernetes(list: &Value) -> (Inventory, Vec<IamFinding>) {
+    let items = as_list(&list["items"]);
+    let key = |kind: &str, namespace: &str, name: &str| {
+        if kind == "ClusterRole" {
+            format!("ClusterRole/{}", name)
+        } else {
+            format!("Role/{}/{}", namespace, name)
+        }
+    };
+    let mut roles: HashMap<String, &Value> = HashMap::new();
+    for item in &items {
+        let kind = item["kind"].as_str().unwrap_or_default();
+        if kind == "ClusterRole" || kind == "Role" {
+            let meta = &item["metadata"];
+            roles.insert(
+                key(
+                    kind,
+                    meta["namespace"].as_str().unwrap_or_default(),
+                    meta["name"].as_str().unwrap_or_default(),
+                ),
+                &item["rules"],
+            );
+        }
+    }
+
+    let mut findings = Vec::new();
+    let mut subjects_seen = BTreeSet::new();
+    let mut bindings = 0;
+    for item in &items {
+        let kind = item["kind"].as_str().unwrap_or_default();
+        let cluster_wide = match kind {
+            "ClusterRoleBinding" => true,
+            "RoleBinding" => false,
+            _ => continue,
+        };
+        bindings += 1;
+        let meta = &item["metadata"];
+        let namespace = meta["namespace"].as_str().unwrap_or_default();
+        let binding_name = meta["name"].as_str().unwrap_or_default();
+        let role_kind = item["roleRef"]["kind"].as_str().unwrap_or_default();
+        let role_name = item["roleRef"]["name"].as_str().unwrap_or_default();
+        let scope = if cluster_wide {
+            "cluster-wide".to_string()
+        } else {
+            format!("in namespace {}", namespace)
+        };
+
+        let mut issues = if role_name == "cluster-admin" {
+            vec![(
+                Severity::Critical,
+                "cluster-admin-binding",
+                "Bound to cluster-admin".to_string(),
+                if cluster_wide {
+                    "Bind the built-in admin or edit ClusterRole with a namespaced RoleBinding, or a custom role with only the needed rules".to_string()
+                } else {
+                    "Bind the built-in admin or edit ClusterRole instead of cluster-admin"
+                        .to_string()
+                },
+            )]
+        } else {
+            roles
+                .get(&key(role_kind, namespace, role_name))
+                .map(|rules| rbac_rule_issues(rules))
+                .unwrap_or_default()
+        };
+        if !cluster_wide {
+            for issue in &mut issues {
+                issue.0 = issue.0.narrowed();
+            }
+        }
+
+        for subject in as_list(&item["subjects"]) {
+            let subject_kind = subject["kind"].as_str().unwrap_or("User");
+            let name = subject["name"].as_str().unwrap_or("?");
+            let principal = match subject["namespace"].as_str() {
+                Some(ns) if subject_kind == "ServiceAccount" => format!("{}/{}", ns, name),
+                _ => name.to_string(),
+            };
+            subjects_seen.insert((subject_kind.to_string(), principal.clone()));
+
+            if K8S_ANONYMOUS.contains(&name) {
+                if !K8S_PUBLIC_ROLES.contains(&role_name) {
+                    findings.push(IamFinding {
+                        platform: Platform::Kubernetes,
+                        principal: principal.clone(),
+                        principal_type: subject_kind.to_string(),
+                        severity: Severity::Critical,
+                        rule: "anonymous-binding".to_string(),
+                        detail: format!(
+                            "{} grants {} {} to every caller",
+                            binding_name, role_name, scope
+                        ),
+                        recommendation:
+                            "Remove the binding; grant access to authenticated identities only"
+                                .to_string(),
+                    });
+                }
+                continue;
+            }
+            // Control-plane identities are expected to hold broad roles
+            if name.starts_with("system:") {
+                continue;
+            }
+            for (severity, rule, detail, recommendation) in &issues {
+                findings.push(IamFinding {
+                    platform: Platform::Kubernetes,
+                    principal: principal.clone(),
+                    principal_type: subject_kind.to_string(),
+                    severity: *severity,
+                    rule: rule.to_string(),
+                    detail: format!(
+                        "{} via {} {}/{} {}",
+                        detail, kind, role_kind, role_name, scope
+                    ),
+                    recommendation: recommendation.clone(),
+                });
+            }
+        }
+    }
+
+    let inventory = Inventory {
+        principals: subjects_seen.len(),
+        policies: roles.len(),
+        bindings,
+    };
+    (inventory, findings)
+}
+
+/// IAM and RBAC analyzer
+pub struct IamAnalyzer {
+    cloud: Option<Arc<CloudModule>>,
+    lifecycle: Option<Arc<LifecycleManager>>,
+    kubeconfig: Option<String>,
+    kube_context: Option<String>,
+}
+
+impl Default for IamAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IamAnalyzer {
+    /// Create an analyzer with no sources configured
+    pub fn new() -> Self {
+        Self {
+            cloud: None,
+            lifecycle: None,
+            kubeconfig: None,
+            kube_context: None,
+        }
+    }
+
+    /// Inventory AWS and Azure through the cloud module
+    pub fn with_cloud(mut self, cloud: Arc<CloudModule>) -> Self {
+        self.cloud = Some(cloud);
+        self
+    }
+
+    /// Inventory Kubernetes RBAC through kubectl
+    pub fn with_kubernetes(
+        mut self,
+        lifecycle: Arc<LifecycleManager>,
+        kubeconfig: Option<String>,
+        context: Option<String>,
+    ) -> Self {
+        self.lifecycle = Some(lifecycle);
+        self.kubeconfig = kubeconfig;
+        self.kube_context = context;
+        self
+    }
+
+    fn cloud(&self) -> Result<&CloudModule> {
+        self.cloud
+            .as_deref()
+            .ok_or_else(|| Error::config("Cloud module is not configured"))
+    }
+
+    async fn kubernetes_rbac(&self) -> Result<Value> {
+        let lifecycle = self
+            .lifecycle
+            .as_ref()
+            .ok_or_else(|| Error::config("Kubernetes is not configured"))?;
+        let client = KubernetesClient::new(
+            lifecycle,
+            self.kubeconfig.as_deref(),
+            self.kube_context.as_deref(),
+        )?;
+        let result = client
+            .run_kubectl_command(
+                "get clusterroles,clusterrolebindings,roles,rolebindings -A -o json",
+                None,
+            )
+            .await?;
+        if !result.success {
+            return Err(Error::service(format!(
+                "kubectl get RBAC failed: {}",
+                result.error.unwrap_or_default()
+            )));
+        }
+        serde_json::from_str(&result.output)
+            .map_err(|e| Error::parsing(format!("Failed to parse RBAC objects: {}", e)))
+    }
+
+    /// Audit the given platforms; unreachable platforms are reported as skipped
+    pub async fn audit(&self, platforms: &[Platform]) -> IamReport {
+        let mut report = IamReport::default();
+        for platform in platforms {
+            let analyzed = match platform {
+                Platform::Aws => match self.cloud().and_then(|c| c.aws()) {
+                    Ok(aws) => aws
+                        .iam_authorization_details()
+                        .await
+                        .map(|details| analyze_aws(&details)),
+                    Err(e) => Err(e),
+                },
+                Platform::Azure => match self.cloud().and_then(|c| c.azure()) {
+                    Ok(azure) => azure
+                        .list_role_assignments()
+                        .await
+                        .map(|assignments| analyze_azure(&assignments)),
+                    Err(e) => Err(e),
+                },
+                Platform::Kubernetes => self
+                    .kubernetes_rbac()
+                    .await
+                    .map(|list| analyze_kubernetes(&list)),
+            };
+            match analyzed {
+                Ok((inventory, findings)) => report.add(*platform, inventory, findings),
+                Err(e) => report.skipped.push(SkippedPlatform {
+                    platform: *platform,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        report
+    }
+
+    /// Get tool definitions for IAM auditing
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "iam_audit",
+            "Inventory AWS IAM, Azure role assignments and Kubernetes RBAC, flag over-privileged principals and recommend least-privilege changes",
+            "security",
+            json!({
+                "type": "object",
+                "properties": {
+                    "platforms": {
+                        "type": "array",
+                        "items": {"type": "string", "enum": ["aws", "azure", "kubernetes"]},
+                        "description": "Platforms to audit (default: all)"
+                    },
+                    "min_severity": {
+                        "type": "string",
+                        "enum": ["low", "medium", "high", "critical"],
+                        "default": "low"
+                    }
+                }
+            }),
+            None,
+        )]
+    }
+
+    /// Execute an IAM tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "iam_audit" => {
+                let platforms = match parameters.get("platforms").and_then(|p| p.as_array()) {
+                    Some(names) => names
+                        .iter()
+                        .filter_map(|n| n.as_str())
+                        .map(str::parse)
+                        .collect::<Result<Vec<Platform>>>()?,
+                    None => vec![Platform::Aws, Platform::Azure, Platform::Kubernetes],
+                };
+                let min_severity = match parameters.get("min_severity") {
+                    Some(value) => serde_json::from_value(value.clone()).map_err(|_| {
+                        Error::validation_with_field("Invalid min_severity", "min_severity")
+                    })?,
+                    None => Severity::Low,
+                };
+
+                let mut report = self.audit(&platforms).await;
+                if report.inventory.is_empty() {
+                    let reasons: Vec<String> = report
+                        .skipped
+                        .iter()
+                        .map(|s| format!("{:?}: {}", s.platform, s.reason))
+                        .collect();
+                    return Err(Error::config(format!(
+                        "No platform could be audited ({})",
+                        reasons.join("; ")
+                    )));
+                }
+                report.findings.retain(|f| f.severity >= min_severity);
+
+                let counts = report.counts();
+                let mut text = format!(
+                    "Audited {}: {} findings",
+                    report
+                        .inventory
+                        .keys()
+                        .map(|p| format!("{:?}", p))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    report.findings.len()
+                );
+                if !counts.is_empty() {
+                    text.push_str(&format!(
+                        " ({})",
+                        counts
+                            .iter()
+                            .rev()
+                            .map(|(s, n)| format!("{} {:?}", n, s).to_lowercase())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+                for finding in report.findings.iter().take(10) {
+                    text.push_str(&format!(
+                        "\n[{:?}] {} {}: {} -> {}",
+                        finding.severity,
+                        finding.principal_type,
+                        finding.principal,
+                        finding.detail,
+                        finding.recommendation
+                    ));
+                }
+                Ok(call_result(text, json!(report)))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "iam_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_wildcards_and_cluster_admin() {
+        let (_, aws) = analyze_aws(&json!({
+            "UserDetailList": [{
+                "UserName": "deploy",
+                "GroupList": ["ops"],
+                "UserPolicyList": [{
+                    "PolicyName": "s3",
+                    "PolicyDocument": "%7B%22Statement%22%3A%5B%7B%22Effect%22%3A%22Allow%22%2C%22Action%22%3A%22s3%3A*%22%2C%22Resource%22%3A%22*%22%7D%5D%7D"
+                }],
+                "AttachedManagedPolicies": []
+            }],
+            "GroupDetailList": [{
+                "GroupName": "ops",
+                "GroupPolicyList": [],
+                "AttachedManagedPolicies": [{"PolicyArn": "arn:aws:iam::aws:policy/AdministratorAccess"}]
+            }],
+            "RoleDetailList": [],
+            "Policies": [{
+                "Arn": "arn:aws:iam::aws:policy/AdministratorAccess",
+                "PolicyName": "AdministratorAccess",
+                "PolicyVersionList": [{"IsDefaultVersion": true, "Document": {"Statement": {"Effect": "Allow", "Action": "*", "Resource": "*"}}}]
+            }]
+        }));
+        let rules: Vec<(&str, Severity)> =
+            aws.iter().map(|f| (f.rule.as_str(), f.severity)).collect();
+        assert!(rules.contains(&("service-wildcard", Severity::High)));
+        assert!(rules.contains(&("wildcard-action", Severity::Critical)));
+        assert!(rules.contains(&("direct-user-policy", Severity::Low)));
+
+        let (inventory, k8s) = analyze_kubernetes(&json!({"items": [
+            {"kind": "ClusterRoleBinding", "metadata": {"name": "ci-admin"},
+             "roleRef": {"kind": "ClusterRole", "name": "cluster-admin"},
+             "subjects": [{"kind": "ServiceAccount", "name": "ci", "namespace": "build"},
+                          {"kind": "Group", "name": "system:masters"}]},
+            {"kind": "Role", "metadata": {"name": "reader", "namespace": "app"},
+             "rules": [{"apiGroups": [""], "resources": ["secrets"], "verbs": ["get", "list"]}]},
+            {"kind": "RoleBinding", "metadata": {"name": "reader", "namespace": "app"},
+             "roleRef": {"kind": "Role", "name": "reader"},
+             "subjects": [{"kind": "User", "name": "alice"}]}
+        ]}));
+        assert_eq!(inventory.bindings, 2);
+        assert_eq!(k8s.len(), 2);
+        assert_eq!(k8s[0].principal, "build/ci");
+        assert_eq!(k8s[0].severity, Severity::Critical);
+        assert_eq!(k8s[1].rule, "secret-read");
+        assert_eq!(k8s[1].severity, Severity::Low);
+    }
+}
@@ -12,6 +12,22 @@ use secrecy::SecretString;
 use std::sync::Arc;
 use zeroize::Zeroize;
 
+pub mod access;
+pub mod approvals;
+pub mod canaries;
+pub mod iam;
+pub mod pii;
+pub mod policy;
+pub mod secrets;
+
+pub use access::{AccessManager, HostInventory, ManagedKey};
+pub use approvals::{ApprovalManager, ApprovalRequest, ApprovalStatus};
+pub use canaries::{Canary, CanaryManager};
+pub use iam::{IamAnalyzer, IamFinding, IamReport};
+pub use pii::{PiiInventory, PiiScanner};
+pub use policy::{ApiKeyConfig, PermissionPolicy, PolicyConfig, Principal};
+pub use secrets::{SecretBackend, SecretResolver, SecretsConfig};
+
 /// High-performance security module with zero-copy optimizations
 #[derive(Clone)]
 pub struct SecurityModule {
@@ -87,16 +103,19 @@ impl SecurityModule {
         if input.is_empty() {
             return ValidationResult::Invalid("Input cannot be empty".to_string());
         }
-        
+
         // Check length limits
         if let Some(max_length) = options.max_length {
             if input.len() > max_length {
-                return ValidationResult::Invalid(format!("Input exceeds maximum length of {}", max_length));
+                return ValidationResult::Invalid(format!(
+                    "Input exceeds maximum length of {}",
+                    max_length
+                ));
             }
         } else if input.len() > 1000 {
             return ValidationResult::Invalid("Input too long".to_string());
         }
-        
+
         // Check for SQL injection patterns if SQL is not allowed
         if !options.allow_sql {
             let sql_patterns = [
@@ -111,36 +130,45 @@ impl SecurityModule {
                 "xp_",
                 "sp_",
             ];
-            
+
             for pattern in &sql_patterns {
                 if input.to_uppercase().contains(pattern) {
-                    return ValidationResult::Malicious(format!("SQL injection pattern detected: {}", pattern));
+                    return ValidationResult::Malicious(format!(
+                        "SQL injection pattern detected: {}",
+                        pattern
+                    ));
                 }
             }
         }
-        
+
         // Check for shell meta characters if not allowed
         if !options.allow_shell_meta {
             let shell_patterns = ["$", "`", "\\", "&", "|", ";", ">", "<", "$(", "${"];
-            
+
             for pattern in &shell_patterns {
                 if input.contains(pattern) {
-                    return ValidationResult::Malicious(format!("Shell meta character detected: {}", pattern));
+                    return ValidationResult::Malicious(format!(
+                        "Shell meta character detected: {}",
+                        pattern
+                    ));
                 }
             }
         }
-        
+
         // Check for HTML/script tags if not allowed
         if !options.allow_html {
             let html_patterns = ["<script", "<iframe", "javascript:", "onerror=", "onload="];
-            
+
             for pattern in &html_patterns {
                 if input.to_lowercase().contains(pattern) {
-                    return ValidationResult::Malicious(format!("HTML/Script pattern detected: {}", pattern));
+                    return ValidationResult::Malicious(format!(
+                        "HTML/Script pattern detected: {}",
+                        pattern
+                    ));
                 }
             }
         }
-        
+
         ValidationResult::Valid
     }
 
@@ -0,0 +1,674 @@
+/// GDPR/PII data discovery across database providers
+///
+/// Samples rows from each table or collection, flags columns whose names or
+/// values look like personal data (emails, national IDs, phone numbers, card
+/// numbers and so on) and builds a data inventory with a risk level and a
+/// masking suggestion per column. Raw sampled values never leave the scanner;
+/// only masked examples are reported.
+use crate::database::{connect, Database};
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+
+/// Rows sampled per table when not specified
+const DEFAULT_SAMPLE_SIZE: usize = 100;
+const MAX_SAMPLE_SIZE: usize = 1000;
+
+/// Share of sampled values that must match a pattern to flag a column by value alone
+const VALUE_MATCH_THRESHOLD: f64 = 0.5;
+
+/// Kind of personal data
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiCategory {
+    Email,
+    Phone,
+    NationalId,
+    PaymentCard,
+    BankAccount,
+    IpAddress,
+    Name,
+    Address,
+    DateOfBirth,
+    Credentials,
+}
+
+/// Exposure risk of a column or table
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl PiiCategory {
+    /// Risk of storing this category unmasked
+    pub fn risk(self) -> RiskLevel {
+        match self {
+            PiiCategory::NationalId
+            | PiiCategory::PaymentCard
+            | PiiCategory::BankAccount
+            | PiiCategory::Credentials => RiskLevel::High,
+            PiiCategory::Email
+            | PiiCategory::Phone
+            | PiiCategory::Address
+            | PiiCategory::DateOfBirth => RiskLevel::Medium,
+            PiiCategory::Name | PiiCategory::IpAddress => RiskLevel::Low,
+        }
+    }
+
+    /// Recommended masking or protection technique
+    pub fn masking(self) -> &'static str {
+        match self {
+            PiiCategory::Email => "Mask the local part (j***@example.com) or hash it for joins",
+            PiiCategory::Phone => "Show only the last 4 digits",
+            PiiCategory::NationalId => "Tokenize, or redact all but the last 4 digits",
+            PiiCategory::PaymentCard => {
+                "Tokenize through a PCI vault; store at most the first 6 and last 4 digits"
+            }
+            PiiCategory::BankAccount => "Show country code and last 4 characters only",
+            PiiCategory::IpAddress => "Truncate the last octet (IPv4) or last 80 bits (IPv6)",
+            PiiCategory::Name => "Pseudonymize outside production",
+            PiiCategory::Address => "Generalize to city or postal-code prefix",
+            PiiCategory::DateOfBirth => "Generalize to birth year or age band",
+            PiiCategory::Credentials => {
+                "Store only salted hashes (argon2) or move to a secrets manager"
+            }
+        }
+    }
+
+    /// Column-name fragments suggesting this category
+    fn name_hints(self) -> &'static [&'static str] {
+        match self {
+            PiiCategory::Email => &["email", "e_mail", "mail_address"],
+            PiiCategory::Phone => &["phone", "mobile", "msisdn", "telephone", "cell"],
+            PiiCategory::NationalId => &[
+                "ssn",
+                "social_security",
+                "national_id",
+                "tax_id",
+                "passport",
+                "nino",
+                "driver_license",
+            ],
+            PiiCategory::PaymentCard => &["card_number", "credit_card", "cc_number", "pan"],
+            PiiCategory::BankAccount => &["iban", "account_number", "routing_number", "bic"],
+            PiiCategory::IpAddress => &["ip_address", "ip_addr", "remote_addr", "client_ip"],
+            PiiCategory::Name => &[
+                "first_name",
+                "last_name",
+                "full_name",
+                "surname",
+                "given_name",
+                "firstname",
+                "lastname",
+            ],
+            PiiCategory::Address => &["address", "street", "postcode", "postal_code", "zip"],
+            PiiCategory::DateOfBirth => &["dob", "birth", "birthday"],
+            PiiCategory::Credentials => &["password", "passwd", "secret", "api_key", "token"],
+        }
+    }
+}
+
+const CATEGORIES: &[PiiCategory] = &[
+    PiiCategory::Email,
+    PiiCategory::Phone,
+    PiiCategory::NationalId,
+    PiiCategory::PaymentCard,
+    PiiCategory::BankAccount,
+    PiiCategory::IpAddress,
+    PiiCategory::Name,
+    PiiCategory::Address,
+    PiiCategory::DateOfBirth,
+    PiiCategory::Credentials,
+];
+
+struct Patterns {
+    email: Regex,
+    ssn: Regex,
+    phone: Regex,
+    card: Regex,
+    iban: Regex,
+    ipv4: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        email: Regex::new(r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}$").unwrap(),
+        ssn: Regex::new(r"^(?:\d{3}-\d{2}-\d{4}|\d{9})$").unwrap(),
+        phone: Regex::new(r"^\+?[\d\s().-]{7,20}$").unwrap(),
+        card: Regex::new(r"^(?:\d[ -]?){13,19}$").unwrap(),
+        iban: Regex::new(r"^[A-Z]{2}\d{2}[A-Z0-9]{11,30}$").unwrap(),
+        ipv4: Regex::new(r"^(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)$")
+            .unwrap(),
+    })
+}
+
+fn luhn(digits: &str) -> bool {
+    let digits: Vec<u32> = digits.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match i % 2 {
+            1 if d * 2 > 9 => d * 2 - 9,
+            1 => d * 2,
+            _ => d,
+        })
+        .sum();
+    !digits.is_empty() && sum.is_multiple_of(10)
+}
+
+/// Category a single value matches by content, if any
+pub fn classify_value(value: &str) -> Option<PiiCategory> {
+    let value = value.trim();
+    let p = patterns();
+    if p.email.is_match(value) {
+        Some(PiiCategory::Email)
+    } else if p.ipv4.is_match(value) {
+        Some(PiiCategory::IpAddress)
+    } else if p.card.is_match(value) && luhn(value) {
+        Some(PiiCategory::PaymentCard)
+    } else if p.ssn.is_match(value) {
+        Some(PiiCategory::NationalId)
+    } else if p.iban.is_match(&value.replace(' ', "")) {
+        Some(PiiCategory::BankAccount)
+    } else if p.phone.is_match(value)
+        && value.chars().filter(|c| c.is_ascii_digit()).count() >= 7
+        && value.chars().any(|c| "+ -().".contains(c))
+    {
+        // Bare digit runs are too often IDs; require phone-style punctuation
+        Some(PiiCategory::Phone)
+    } else {
+        None
+    }
+}
+
+fn name_hint(column: &str) -> Option<PiiCategory> {
+    let column = column.to_lowercase();
+    let leaf = column.rsplit('.').next().unwrap_or(&column);
+    CATEGORIES.iter().copied().find(|category| {
+        category.name_hints().iter().any(|hint| {
+            // Short hints must match a whole word to avoid e.g. "pan" in "company"
+            if hint.len() <= 4 {
+                leaf.split(|c: char| !c.is_ascii_alphanumeric())
+                    .any(|word| word == *hint)
+            } else {
+                leaf.contains(hint)
+            }
+        })
+    })
+}
+
+/// Replace most of a value with asterisks, keeping enough to recognise its shape
+pub fn mask(category: PiiCategory, value: &str) -> String {
+    let stars = |n: usize| "*".repeat(n.min(12));
+    let value = value.trim();
+    match category {
+        PiiCategory::Email => match value.split_once('@') {
+            Some((local, domain)) => format!(
+                "{}{}@{}",
+                local.chars().next().unwrap_or('*'),
+                stars(local.chars().count().saturating_sub(1)),
+                domain
+            ),
+            None => stars(value.len()),
+        },
+        PiiCategory::IpAddress => match value.rsplit_once('.') {
+            Some((prefix, _)) => format!("{}.0", prefix),
+            None => stars(value.len()),
+        },
+        PiiCategory::Phone
+        | PiiCategory::NationalId
+        | PiiCategory::PaymentCard
+        | PiiCategory::BankAccount => {
+            let chars: Vec<char> = value.chars().collect();
+            let keep = chars.len().min(4);
+            format!(
+                "{}{}",
+                stars(chars.len() - keep),
+                chars[chars.len() - keep..].iter().collect::<String>()
+            )
+        }
+        PiiCategory::DateOfBirth => value.chars().take(4).collect::<String>() + "-**-**",
+        PiiCategory::Name | PiiCategory::Address | PiiCategory::Credentials => {
+            stars(value.chars().count())
+        }
+    }
+}
+
+/// A column holding likely personal data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiColumn {
+    pub column: String,
+    pub category: PiiCategory,
+    /// 0.0-1.0, from the share of matching values and the column name
+    pub confidence: f64,
+    /// Non-null values sampled
+    pub sampled: usize,
+    /// Sampled values matching the category's pattern
+    pub matched: usize,
+    /// Whether the column name suggested the category
+    pub name_match: bool,
+    pub risk: RiskLevel,
+    pub masking: String,
+    /// A masked sample value
+    pub example: Option<String>,
+}
+
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, Vec<String>>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&key, value, out);
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| flatten(prefix, item, out)),
+        Value::Null => {
+            out.entry(prefix.to_string()).or_default();
+        }
+        Value::String(s) => out.entry(prefix.to_string()).or_default().push(s.clone()),
+        other => out
+            .entry(prefix.to_string())
+            .or_default()
+            .push(other.to_string()),
+    }
+}
+
+/// Detect PII columns from sampled rows and the table's declared column names
+pub fn detect_columns(rows: &[Value], columns: &[String]) -> Vec<PiiColumn> {
+    let mut values: BTreeMap<String, Vec<String>> =
+        columns.iter().map(|c| (c.clone(), Vec::new())).collect();
+    for row in rows {
+        flatten("", row, &mut values);
+    }
+
+    values
+        .into_iter()
+        .filter_map(|(column, samples)| {
+            let hint = name_hint(&column);
+            let mut counts: BTreeMap<PiiCategory, usize> = BTreeMap::new();
+            for sample in &samples {
+                if let Some(category) = classify_value(sample) {
+                    *counts.entry(category).or_default() += 1;
+                }
+            }
+            let best = counts.iter().max_by_key(|(_, &n)| n).map(|(&c, &n)| (c, n));
+            let ratio = |n: usize| n as f64 / samples.len().max(1) as f64;
+
+            let (category, matched, name_match) = match (hint, best) {
+                // Name and values agree
+                (Some(h), _) if counts.contains_key(&h) => (h, counts[&h], true),
+                (_, Some((c, n))) if ratio(n) >= VALUE_MATCH_THRESHOLD => (c, n, false),
+                // Credentials and free-text categories have no reliable value pattern
+                (Some(h), _) => (h, 0, true),
+                _ => return None,
+            };
+            let confidence = match (name_match, matched) {
+                (true, 0) => 0.5,
+                (true, n) => (0.5 + ratio(n) / 2.0).min(1.0),
+                (false, n) => ratio(n) * 0.9,
+            };
+            let example = samples
+                .iter()
+                .find(|s| name_match && matched == 0 || classify_value(s) == Some(category))
+                .map(|s| mask(category, s));
+            Some(PiiColumn {
+                column,
+                category,
+                confidence: (confidence * 100.0).round() / 100.0,
+                sampled: samples.len(),
+                matched,
+                name_match,
+                risk: category.risk(),
+                masking: category.masking().to_string(),
+                example,
+            })
+        })
+        .collect()
+}
+
+/// Query language of a source
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    Sql,
+    Mongo,
+}
+
+/// PII found in one table or collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableInventory {
+    pub table: String,
+    pub sampled_rows: usize,
+    pub columns: Vec<PiiColumn>,
+    pub risk: Option<RiskLevel>,
+}
+
+/// PII found in one source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceInventory {
+    pub source: String,
+    pub tables: Vec<TableInventory>,
+    pub errors: Vec<String>,
+}
+
+/// Data inventory across sources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiInventory {
+    pub scanned_at: DateTime<Utc>,
+    pub sources: Vec<SourceInventory>,
+}
+
+impl PiiInventory {
+    /// PII columns per risk level
+    pub fn counts(&self) -> BTreeMap<RiskLevel, usize> {
+        let mut counts = BTreeMap::new();
+        for column in self
+            .sources
+            .iter()
+            .flat_map(|s| &s.tables)
+            .flat_map(|t| &t.columns)
+        {
+            *counts.entry(column.risk).or_default() += 1;
+        }
+        counts
+    }
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+struct Source {
+    kind: SourceKind,
+    database: Arc<dyn Database>,
+}
+
+/// PII scanner over registered database sources
+#[derive(Default)]
+pub struct PiiScanner {
+    sources: BTreeMap<String, Source>,
+}
+
+impl PiiScanner {
+    /// Create a scanner with no sources
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a named source to include in scans
+    pub fn with_source(
+        mut self,
+        name: impl Into<String>,
+        kind: SourceKind,
+        database: Arc<dyn Database>,
+    ) -> Self {
+        self.sources.insert(name.into(), Source { kind, database });
+        self
+    }
+
+    async fn sample(
+        source: &Source,
+        table: &str,
+        schema: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Value>> {
+        let query = match source.kind {
+            SourceKind::Sql => format!(
+                "SELECT * FROM {}.{} LIMIT {}",
+                quote_ident(schema.unwrap_or("public")),
+                quote_ident(table),
+                limit
+            ),
+            SourceKind::Mongo => json!({
+                "collection": table,
+                "operation": "find",
+                "limit": limit
+            })
+            .to_string(),
+        };
+        Ok(source.database.execute_query(&query, schema).await?.rows)
+    }
+
+    async fn scan_source(
+        name: &str,
+        source: &Source,
+        schema: Option<&str>,
+        tables: &[String],
+        limit: usize,
+    ) -> SourceInventory {
+        let mut inventory = SourceInventory {
+            source: name.to_string(),
+            tables: Vec::new(),
+            errors: Vec::new(),
+        };
+        let listed = match source.database.list_tables(schema).await {
+            Ok(listed) => listed,
+            Err(e) => {
+                inventory.errors.push(format!("list tables: {}", e));
+                return inventory;
+            }
+        };
+        for table in listed
+            .into_iter()
+            .filter(|t| tables.is_empty() || tables.contains(&t.name))
+        {
+            let columns: Vec<String> =
+                match source.database.describe_table(&table.name, schema).await {
+                    Ok(described) => described.columns.into_iter().map(|c| c.name).collect(),
+                    Err(_) => table.columns.iter().map(|c| c.name.clone()).collect(),
+                };
+            match Self::sample(source, &table.name, schema, limit).await {
+                Ok(rows) => {
+                    let columns = detect_columns(&rows, &columns);
+                    inventory.tables.push(TableInventory {
+                        risk: columns.iter().map(|c| c.risk).max(),
+                        table: table.name,
+                        sampled_rows: rows.len(),
+                        columns,
+                    });
+                }
+                Err(e) => inventory.errors.push(format!("{}: {}", table.name, e)),
+            }
+        }
+        inventory
+    }
+
+    /// Scan registered sources, all of them when `names` is empty
+    pub async fn scan(
+        &self,
+        names: &[String],
+        schema: Option<&str>,
+        tables: &[String],
+        sample_size: usize,
+    ) -> Result<PiiInventory> {
+        if let Some(missing) = names.iter().find(|n| !self.sources.contains_key(*n)) {
+            return Err(Error::not_found_with_resource(
+                "Source not found",
+                "pii_source",
+                missing,
+            ));
+        }
+        let limit = sample_size.clamp(1, MAX_SAMPLE_SIZE);
+        let mut sources = Vec::new();
+        for (name, source) in &self.sources {
+            if names.is_empty() || names.contains(name) {
+                sources.push(Self::scan_source(name, source, schema, tables, limit).await);
+            }
+        }
+        Ok(PiiInventory {
+            scanned_at: Utc::now(),
+            sources,
+        })
+    }
+
+    /// Get tool definitions for PII discovery
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            "pii_scan",
+            "Sample database tables and flag likely PII columns (emails, SSNs, phone and card numbers, names, addresses), producing a data inventory with risk levels and masking suggestions",
+            "security",
+            json!({
+                "type": "object",
+                "properties": {
+                    "sources": {"type": "array", "items": {"type": "string"}, "description": "Registered sources to scan; all when omitted"},
+                    "provider": {"type": "string", "enum": ["postgresql", "supabase", "mongodb"], "description": "Scan an ad hoc connection instead of registered sources"},
+                    "connection_string": {"type": "string"},
+                    "database": {"type": "string", "description": "Schema (SQL) or database (MongoDB) to scan"},
+                    "tables": {"type": "array", "items": {"type": "string"}},
+                    "sample_size": {"type": "integer", "default": DEFAULT_SAMPLE_SIZE, "maximum": MAX_SAMPLE_SIZE}
+                }
+            }),
+            None,
+        )]
+    }
+
+    /// Execute a PII discovery tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        if name != "pii_scan" {
+            return Err(Error::not_found_with_resource(
+                "Tool not found",
+                "pii_tool",
+                name,
+            ));
+        }
+        let list = |key: &str| -> Result<Vec<String>> {
+            serde_json::from_value(parameters.get(key).cloned().unwrap_or(json!([])))
+                .map_err(|e| Error::validation_with_field(format!("Invalid {}: {}", key, e), key))
+        };
+        let schema = parameters.get("database").and_then(|v| v.as_str());
+        let tables = list("tables")?;
+        let sample_size = parameters
+            .get("sample_size")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .unwrap_or(DEFAULT_SAMPLE_SIZE);
+
+        let inventory = match parameters.get("provider").and_then(|v| v.as_str()) {
+            Some(provider) => {
+                let connection_string = parameters
+                    .get("connection_string")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| {
+                        Error::validation_with_field(
+                            "connection_string is required with provider",
+                            "connection_string",
+                        )
+                    })?;
+                let source = Source {
+                    kind: match provider {
+                        "mongodb" => SourceKind::Mongo,
+                        _ => SourceKind::Sql,
+                    },
+                    database: connect(provider, connection_string).await?,
+                };
+                PiiInventory {
+                    scanned_at: Utc::now(),
+                    sources: vec![
+                        Self::scan_source(
+                            provider,
+                            &source,
+                            schema,
+                            &tables,
+                            sample_size.clamp(1, MAX_SAMPLE_SIZE),
+                        )
+                        .await,
+                    ],
+                }
+            }
+            None if self.sources.is_empty() => {
+                return Err(Error::config_with_suggestion(
+                    "No database sources registered",
+                    "Pass provider and connection_string to scan a database directly",
+                ))
+            }
+            None => {
+                self.scan(&list("sources")?, schema, &tables, sample_size)
+                    .await?
+            }
+        };
+
+        let counts = inventory.counts();
+        let mut text = format!(
+            "PII inventory: {} high, {} medium, {} low risk columns",
+            counts.get(&RiskLevel::High).unwrap_or(&0),
+            counts.get(&RiskLevel::Medium).unwrap_or(&0),
+            counts.get(&RiskLevel::Low).unwrap_or(&0)
+        );
+        for source in &inventory.sources {
+            for table in source.tables.iter().filter(|t| !t.columns.is_empty()) {
+                text.push_str(&format!("\n\n{}.{}", source.source, table.table));
+                for column in &table.columns {
+                    text.push_str(&format!(
+                        "\n  {} [{:?}, {:?} risk, {:.0}%]: {}",
+                        column.column,
+                        column.category,
+                        column.risk,
+                        column.confidence * 100.0,
+                        column.masking
+                    ));
+                }
+            }
+            for error in &source.errors {
+                text.push_str(&format!("\n{} error: {}", source.source, error));
+            }
+        }
+        Ok(call_result(text, json!({ "inventory": inventory })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_pii_columns_by_value_and_name() {
+        let rows = vec![
+            json!({"id": 1, "contact": "jane.doe@example.com", "ssn": "123-45-6789", "card": "4111 1111 1111 1111", "profile": {"phone": "+1 (555) 010-2030", "last_name": "Doe"}, "order_ref": "100200300400"}),
+            json!({"id": 2, "contact": "bob@example.org", "ssn": null, "card": "5500-0000-0000-0004", "profile": {"phone": "555-010-9999", "last_name": "Smith"}, "order_ref": "100200300401"}),
+        ];
+        let columns = ["id", "contact", "ssn", "card", "password_hash"].map(String::from);
+        let found: BTreeMap<String, PiiColumn> = detect_columns(&rows, &columns)
+            .into_iter()
+            .map(|c| (c.column.clone(), c))
+            .collect();
+
+        assert_eq!(
+            found.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec![
+                "card",
+                "contact",
+                "password_hash",
+                "profile.last_name",
+                "profile.phone",
+                "ssn"
+            ]
+        );
+        assert_eq!(found["contact"].category, PiiCategory::Email);
+        assert!(!found["contact"].name_match);
+        assert_eq!(
+            found["contact"].example.as_deref(),
+            Some("j*******@example.com")
+        );
+        assert_eq!(found["card"].category, PiiCategory::PaymentCard);
+        assert_eq!(found["card"].risk, RiskLevel::High);
+        assert_eq!(found["ssn"].category, PiiCategory::NationalId);
+        assert!(found["ssn"].name_match && found["ssn"].confidence == 1.0);
+        assert_eq!(found["profile.phone"].category, PiiCategory::Phone);
+        assert_eq!(found["password_hash"].category, PiiCategory::Credentials);
+        assert_eq!(found["profile.last_name"].example.as_deref(), Some("***"));
+    }
+}
@@ -0,0 +1,496 @@
+/// Role-based permissions for tool calls
+///
+/// The config names roles, each allowing and denying tools by name,
+/// `prefix*` or `category:<name>`, and listing tools that need the caller's
+/// confirmation before they run. Callers are principals picked by the API
+/// key they connect with; callers without a known key get the default role.
+/// A tool is usable when one of the caller's roles allows it and none
+/// denies it. A call to a tool needing confirmation is refused with a token,
+/// and runs when repeated with the same arguments and the token in
+/// `_meta.confirmation`.
+///
+/// Deployments that need no roles can list scoped API keys instead, each
+/// granting tool categories to read or write, e.g. `infrastructure:read` or
+/// `database:write`. Read covers tools marked as only reading state (see
+/// [`ToolDefinition::read_only`]); write covers every tool in the category.
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::tools::ToolDefinition;
+use crate::transport::tenancy::hash_api_key;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Name of callers without a known API key
+pub const ANONYMOUS: &str = "anonymous";
+
+/// Roles and who holds them, under `security.policy`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    pub roles: BTreeMap<String, RoleConfig>,
+    /// Callers by name
+    #[serde(default)]
+    pub principals: BTreeMap<String, PrincipalConfig>,
+    /// Role of callers without a known API key; nothing is allowed when unset
+    #[serde(default)]
+    pub default_role: Option<String>,
+    /// Seconds a confirmation token stays valid
+    #[serde(default = "default_confirmation_secs")]
+    pub confirmation_secs: u64,
+}
+
+fn default_confirmation_secs() -> u64 {
+    300
+}
+
+/// Tools a role may use, each by name, `prefix*` or `category:<name>`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoleConfig {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Wins over `allow`, including another role's
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Allowed tools that run only once the caller confirms the call
+    #[serde(default)]
+    pub confirm: Vec<String>,
+}
+
+/// A caller and its roles
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrincipalConfig {
+    /// SHA-256 hex digests of the caller's API keys
+    pub api_keys: Vec<String>,
+    pub roles: Vec<String>,
+}
+
+/// An API key and the tool categories it reaches, under `security.api_keys.<name>`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    /// SHA-256 hex digest of the key
+    pub hash: String,
+    /// `<category>:read`, `<category>:write`, with `*` for any category,
+    /// or `*` for every tool
+    pub scopes: Vec<String>,
+}
+
+/// Whether `hash` looks like a SHA-256 hex digest
+fn is_digest(hash: &str) -> bool {
+    hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+impl ApiKeyConfig {
+    /// Check the hash and every scope are well formed
+    pub fn validate(&self, name: &str) -> Result<()> {
+        if !is_digest(&self.hash) {
+            return Err(Error::validation_with_field(
+                format!("API key {} hash is not a SHA-256 hex digest", name),
+                "hash",
+            ));
+        }
+        if let Some(scope) = self.scopes.iter().find(|scope| {
+            *scope != "*"
+                && !scope.split_once(':').is_some_and(|(category, access)| {
+                    !category.is_empty() && matches!(access, "read" | "write")
+                })
+        }) {
+            return Err(Error::validation_with_field(
+                format!(
+                    "API key {} scope {} must be <category>:read or <category>:write",
+                    name, scope
+                ),
+                "scopes",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `scope` lets its key use `tool`
+fn in_scope(scope: &str, tool: &ToolDefinition) -> bool {
+    if scope == "*" {
+        return true;
+    }
+    let Some((category, access)) = scope.split_once(':') else {
+        return false;
+    };
+    (category == "*" || tool.category() == Some(category))
+        && (access == "write" || tool.is_read_only())
+}
+
+impl PolicyConfig {
+    /// Check every role a principal or the default names exists
+    pub fn validate(&self) -> Result<()> {
+        let known = |role: &String, field: &str| {
+            if self.roles.contains_key(role) {
+                Ok(())
+            } else {
+                Err(Error::validation_with_field(
+                    format!("Role {} is not defined", role),
+                    field,
+                ))
+            }
+        };
+        if let Some(role) = &self.default_role {
+            known(role, "default_role")?;
+        }
+        for (name, principal) in &self.principals {
+            for role in &principal.roles {
+                known(role, "roles")?;
+            }
+            if let Some(key) = principal.api_keys.iter().find(|k| !is_digest(k)) {
+                return Err(Error::validation_with_field(
+                    format!(
+                        "Principal {} API key {:.8}... is not a SHA-256 hex digest",
+                        name, key
+                    ),
+                    "api_keys",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A caller, as identified by its API key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub name: String,
+    pub roles: Vec<String>,
+    /// Scopes of a scoped API key, which stand in for roles
+    pub scopes: Option<Vec<String>>,
+}
+
+struct PendingConfirmation {
+    principal: String,
+    tool: String,
+    arguments: Value,
+    expires_at: Instant,
+}
+
+/// Roles and principals from the config, with confirmations awaiting a repeat
+pub struct PermissionPolicy {
+    config: PolicyConfig,
+    keys: BTreeMap<String, ApiKeyConfig>,
+    pending: Mutex<HashMap<String, PendingConfirmation>>,
+}
+
+impl std::fmt::Debug for PermissionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PermissionPolicy")
+            .field("roles", &self.config.roles.keys().collect::<Vec<_>>())
+            .field(
+                "principals",
+                &self.config.principals.keys().collect::<Vec<_>>(),
+            )
+            .field("api_keys", &self.keys.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Whether `pattern` names `tool`
+fn matches(pattern: &str, tool: &ToolDefinition) -> bool {
+    if let Some(category) = pattern.strip_prefix("category:") {
+        return tool.category() == Some(category);
+    }
+    match pattern.strip_suffix('*') {
+        Some(prefix) => tool.name.starts_with(prefix),
+        None => tool.name == pattern,
+    }
+}
+
+impl PermissionPolicy {
+    pub fn new(config: PolicyConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            keys: BTreeMap::new(),
+            pending: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Also accept scoped API keys, by name
+    pub fn with_api_keys(mut self, keys: BTreeMap<String, ApiKeyConfig>) -> Result<Self> {
+        for (name, key) in &keys {
+            key.validate(name)?;
+        }
+        self.keys = keys;
+        Ok(self)
+    }
+
+    /// Policy from `security.policy` and `security.api_keys`, if the config
+    /// has either
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        let Some(security) = config.security.as_ref() else {
+            return Ok(None);
+        };
+        if security.policy.is_none() && security.api_keys.is_empty() {
+            return Ok(None);
+        }
+        Self::new(security.policy.clone().unwrap_or_default())?
+            .with_api_keys(security.api_keys.clone())
+            .map(Some)
+    }
+
+    /// Principal holding `key`, or the anonymous caller
+    pub fn authenticate(&self, key: Option<&str>) -> Principal {
+        let hash = key.map(hash_api_key);
+        let matches = |k: &str| {
+            hash.as_ref().is_some_and(|hash| {
+                constant_time_eq::constant_time_eq(
+                    k.to_ascii_lowercase().as_bytes(),
+                    hash.as_bytes(),
+                )
+            })
+        };
+        // Compare against every key so timing does not reveal which matched
+        let mut found = None;
+        for (name, principal) in &self.config.principals {
+            for k in &principal.api_keys {
+                if matches(k) {
+                    found = Some(Principal {
+                        name: name.clone(),
+                        roles: principal.roles.clone(),
+                        scopes: None,
+                    });
+                }
+            }
+        }
+        for (name, key) in &self.keys {
+            if matches(&key.hash) {
+                found = Some(Principal {
+                    name: name.clone(),
+                    roles: Vec::new(),
+                    scopes: Some(key.scopes.clone()),
+                });
+            }
+        }
+        found.unwrap_or_else(|| Principal {
+            name: ANONYMOUS.to_string(),
+            roles: self.config.default_role.iter().cloned().collect(),
+            scopes: None,
+        })
+    }
+
+    fn roles<'a>(&'a self, principal: &'a Principal) -> impl Iterator<Item = &'a RoleConfig> {
+        principal
+            .roles
+            .iter()
+            .filter_map(|role| self.config.roles.get(role))
+    }
+
+    /// Whether `principal` may use `tool`
+    pub fn allows(&self, principal: &Principal, tool: &ToolDefinition) -> bool {
+        if let Some(scopes) = &principal.scopes {
+            return scopes.iter().any(|scope| in_scope(scope, tool));
+        }
+        let any = |patterns: &[String]| patterns.iter().any(|p| matches(p, tool));
+        self.roles(principal).any(|role| any(&role.allow))
+            && !self.roles(principal).any(|role| any(&role.deny))
+    }
+
+    /// Let `principal` call `tool`, or explain why not
+    ///
+    /// A call needing confirmation passes only with the token handed out
+    /// for the same tool and arguments; each token works once.
+    pub fn check(
+        &self,
+        principal: &Principal,
+        tool: &ToolDefinition,
+        arguments: &Value,
+        confirmation: Option<&str>,
+    ) -> Result<()> {
+        if !self.allows(principal, tool) {
+            return Err(Error::auth(format!(
+                "{} is not permitted to use {}",
+                principal.name, tool.name
+            )));
+        }
+        let confirm = self
+            .roles(principal)
+            .any(|role| role.confirm.iter().any(|p| matches(p, tool)));
+        if !confirm {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        pending.retain(|_, p| p.expires_at > now);
+        if let Some(token) = confirmation {
+            if pending.get(token).is_some_and(|p| {
+                p.principal == principal.name && p.tool == tool.name && p.arguments == *arguments
+            }) {
+                pending.remove(token);
+                return Ok(());
+            }
+        }
+        let token = uuid::Uuid::new_v4().to_string();
+        pending.insert(
+            token.clone(),
+            PendingConfirmation {
+                principal: principal.name.clone(),
+                tool: tool.name.clone(),
+                arguments: arguments.clone(),
+                expires_at: now + Duration::from_secs(self.config.confirmation_secs),
+            },
+        );
+        Err(Error::validation_with_field(
+            format!(
+                "{} needs confirmation. Check with the user, then repeat the call with the same arguments and _meta.confirmation set to {} within {} seconds",
+                tool.name, token, self.config.confirmation_secs
+            ),
+            "_meta.confirmation",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn applies_roles_denials_and_confirmations() {
+        let config: PolicyConfig = serde_json::from_value(json!({
+            "roles": {
+                "viewer": {"allow": ["list_*", "category:monitoring"]},
+                "operator": {
+                    "allow": ["*"],
+                    "deny": ["rotate_*"],
+                    "confirm": ["delete_*"]
+                }
+            },
+            "principals": {
+                "ci": {"api_keys": [hash_api_key("ci-key")], "roles": ["viewer", "operator"]}
+            },
+            "default_role": "viewer"
+        }))
+        .unwrap();
+        let policy = PermissionPolicy::new(config).unwrap();
+        let tool = |name: &str, category: &str| {
+            ToolDefinition::from_json_schema(name, "", category, json!({}), None)
+        };
+
+        let anonymous = policy.authenticate(None);
+        assert_eq!(anonymous.name, ANONYMOUS);
+        assert_eq!(policy.authenticate(Some("wrong")), anonymous);
+        assert!(policy.allows(&anonymous, &tool("list_pods", "kubernetes")));
+        assert!(policy.allows(&anonymous, &tool("get_alerts", "monitoring")));
+        assert!(policy
+            .check(
+                &anonymous,
+                &tool("delete_pod", "kubernetes"),
+                &json!({}),
+                None
+            )
+            .is_err());
+
+        let ci = policy.authenticate(Some("ci-key"));
+        assert_eq!(ci.name, "ci");
+        assert!(!policy.allows(&ci, &tool("rotate_keys", "security")));
+        let delete = tool("delete_pod", "kubernetes");
+        let arguments = json!({"name": "web-1"});
+        let message = policy
+            .check(&ci, &delete, &arguments, None)
+            .unwrap_err()
+            .to_string();
+        let token = message
+            .split("_meta.confirmation set to ")
+            .nth(1)
+            .and_then(|rest| rest.split(' ').next())
+            .unwrap();
+        // The token is bound to the call it was issued for, and used once
+        assert!(policy
+            .check(&ci, &delete, &json!({"name": "db-1"}), Some(token))
+            .is_err());
+        policy.check(&ci, &delete, &arguments, Some(token)).unwrap();
+        assert!(policy.check(&ci, &delete, &arguments, Some(token)).is_err());
+
+        let mut broken: PolicyConfig =
+            serde_json::from_value(json!({"roles": {}, "default_role": "admin"})).unwrap();
+        assert!(PermissionPolicy::new(broken.clone()).is_err());
+        broken.default_role = None;
+        assert!(PermissionPolicy::new(broken).is_ok());
+    }
+
+    #[test]
+    fn limits_scoped_keys_to_their_categories() {
+        let keys: BTreeMap<String, ApiKeyConfig> = serde_json::from_value(json!({
+            "dashboard": {"hash": hash_api_key("dash-key"), "scopes": ["infrastructure:read", "monitoring:read"]},
+            "migrations": {"hash": hash_api_key("db-key"), "scopes": ["database:write"]}
+        }))
+        .unwrap();
+        let policy = PermissionPolicy::new(PolicyConfig::default())
+            .unwrap()
+            .with_api_keys(keys)
+            .unwrap();
+        let tool = |name: &str, category: &str| {
+            ToolDefinition::from_json_schema(name, "", category, json!({}), None)
+        };
+        let reader = |name: &str, category: &str| tool(name, category).read_only();
+
+        let dashboard = policy.authenticate(Some("dash-key"));
+        assert_eq!(dashboard.name, "dashboard");
+        assert!(policy.allows(&dashboard, &reader("list_pods", "infrastructure")));
+        assert!(policy.allows(&dashboard, &reader("prometheus_query", "monitoring")));
+        assert!(!policy.allows(&dashboard, &tool("delete_pod", "infrastructure")));
+        assert!(!policy.allows(&dashboard, &reader("list_tables", "database")));
+        // Tools not marked read-only need write access, whatever their name
+        assert!(!policy.allows(&dashboard, &tool("list_nodes", "infrastructure")));
+
+        let migrations = policy.authenticate(Some("db-key"));
+        assert!(policy.allows(&migrations, &tool("execute_query", "database")));
+        assert!(!policy.allows(&migrations, &reader("list_pods", "infrastructure")));
+        // Without a default role, callers without a key get nothing
+        assert!(!policy.allows(
+            &policy.authenticate(None),
+            &reader("list_pods", "infrastructure")
+        ));
+
+        let invalid = |scope: &str| {
+            ApiKeyConfig {
+                hash: hash_api_key("key"),
+                scopes: vec![scope.to_string()],
+            }
+            .validate("key")
+            .is_err()
+        };
+        assert!(invalid("database"));
+        assert!(invalid("database:admin"));
+        assert!(!invalid("*:read"));
+    }
+
+    #[test]
+    fn read_scopes_refuse_tools_that_act() {
+        use crate::maps::GeofenceManager;
+        use crate::web::{GraphQlClient, GraphQlConfig};
+
+        let keys: BTreeMap<String, ApiKeyConfig> = serde_json::from_value(json!({
+            "viewer": {"hash": hash_api_key("viewer-key"), "scopes": ["web_graphql:read", "maps:read"]}
+        }))
+        .unwrap();
+        let policy = PermissionPolicy::new(PolicyConfig::default())
+            .unwrap()
+            .with_api_keys(keys)
+            .unwrap();
+        let graphql = GraphQlClient::new(GraphQlConfig {
+            endpoint: "https://api.example.com/graphql".to_string(),
+            bearer_token: None,
+            headers: HashMap::new(),
+        })
+        .unwrap();
+        let tools: Vec<ToolDefinition> = graphql
+            .get_tool_definitions()
+            .into_iter()
+            .chain(GeofenceManager::new().get_tool_definitions())
+            .collect();
+        let tool = |name: &str| tools.iter().find(|tool| tool.name == name).unwrap();
+
+        let viewer = policy.authenticate(Some("viewer-key"));
+        // GraphQL documents can hold mutations; evaluating a tracker sends notifications
+        assert!(!policy.allows(&viewer, tool("graphql_query")));
+        assert!(!policy.allows(&viewer, tool("evaluate_device_tracker")));
+        assert!(policy.allows(&viewer, tool("list_geofences")));
+    }
+}
@@ -0,0 +1,269 @@
+//! Azure Key Vault secrets
+//!
+//! `keyvault://<vault>/<secret>` reads the current version of a secret and
+//! `keyvault://<vault>/<secret>/<version>` a pinned one. `<vault>` is the
+//! vault name, or its full host name for vaults outside the public cloud.
+//! Tokens come from a service principal's client credentials when one is
+//! configured, otherwise from the managed identity of the Azure VM or
+//! container the server runs on.
+
+use super::{SecretBackend, SecretRef};
+use crate::cloud::azure::{AzureCredentials, AzureTokens, AUTHORITY_HOST};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+const API_VERSION: &str = "7.4";
+const VAULT_RESOURCE: &str = "https://vault.azure.net";
+
+fn default_authority_host() -> String {
+    AUTHORITY_HOST.to_string()
+}
+
+fn default_vault_suffix() -> String {
+    "vault.azure.net".to_string()
+}
+
+/// Key Vault access, under `secrets.keyvault`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyVaultConfig {
+    /// Service principal; leave all three unset to use a managed identity
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// Also selects a user-assigned managed identity when no secret is set
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    #[serde(default = "default_authority_host")]
+    pub authority_host: String,
+    /// Appended to vault names; `vault.azure.cn` or `vault.usgovcloudapi.net`
+    /// in sovereign clouds
+    #[serde(default = "default_vault_suffix")]
+    pub vault_suffix: String,
+}
+
+impl Default for KeyVaultConfig {
+    fn default() -> Self {
+        Self {
+            tenant_id: None,
+            client_id: None,
+            client_secret: None,
+            authority_host: default_authority_host(),
+            vault_suffix: default_vault_suffix(),
+        }
+    }
+}
+
+impl KeyVaultConfig {
+    /// Fill in the service principal from `AZURE_TENANT_ID`,
+    /// `AZURE_CLIENT_ID` and `AZURE_CLIENT_SECRET` where unset
+    fn with_env(mut self) -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        self.tenant_id = self.tenant_id.or_else(|| var("AZURE_TENANT_ID"));
+        self.client_id = self.client_id.or_else(|| var("AZURE_CLIENT_ID"));
+        self.client_secret = self.client_secret.or_else(|| var("AZURE_CLIENT_SECRET"));
+        self
+    }
+
+    /// The service principal when all of it is set, otherwise the managed identity
+    fn credentials(&self) -> AzureCredentials {
+        let principal =
+            self.tenant_id.is_some() && self.client_id.is_some() && self.client_secret.is_some();
+        AzureCredentials {
+            tenant_id: self.tenant_id.clone().unwrap_or_default(),
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            use_managed_identity: !principal,
+            authority_host: self.authority_host.clone(),
+        }
+    }
+}
+
+/// Key Vault secret reader
+pub struct KeyVault {
+    client: Client,
+    config: KeyVaultConfig,
+    tokens: AzureTokens,
+}
+
+impl KeyVault {
+    pub fn new(config: KeyVaultConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Key Vault client: {}", e)))?;
+        let config = config.with_env();
+        Ok(Self {
+            tokens: AzureTokens::new(client.clone(), config.credentials()),
+            client,
+            config,
+        })
+    }
+
+    /// Base URL of a vault given by name or host name
+    fn vault_url(&self, vault: &str) -> Result<String> {
+        let valid = |part: &str| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        };
+        if !vault.split('.').all(valid) {
+            return Err(Error::validation_with_field(
+                format!("Invalid Key Vault name {}", vault),
+                "vault",
+            ));
+        }
+        Ok(if vault.contains('.') {
+            format!("https://{}", vault)
+        } else {
+            format!("https://{}.{}", vault, self.config.vault_suffix)
+        })
+    }
+
+    /// Access token for Key Vault
+    async fn token(&self) -> Result<String> {
+        self.tokens.token(VAULT_RESOURCE).await.map_err(|e| {
+            Error::auth(format!(
+                "Key Vault token request failed; configure secrets.keyvault or AZURE_TENANT_ID, AZURE_CLIENT_ID and AZURE_CLIENT_SECRET when not running with a managed identity: {}",
+                e
+            ))
+        })
+    }
+}
+
+#[async_trait]
+impl SecretBackend for KeyVault {
+    fn scheme(&self) -> &'static str {
+        "keyvault"
+    }
+
+    async fn fetch(&self, reference: &SecretRef) -> Result<String> {
+        let mut parts = reference.path.split('/');
+        let name = parts.next().unwrap_or_default();
+        let version = parts.next();
+        let valid_name = |n: &str| {
+            !n.is_empty()
+                && n.len() <= 127
+                && n.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        };
+        if !valid_name(name)
+            || parts.next().is_some()
+            || version.is_some_and(|v| !v.chars().all(|c| c.is_ascii_alphanumeric()))
+        {
+            return Err(Error::validation(format!(
+                "{} is not keyvault://<vault>/<secret>[/<version>]",
+                reference
+            )));
+        }
+        let url = format!(
+            "{}/secrets/{}/{}",
+            self.vault_url(&reference.store)?,
+            name,
+            version.unwrap_or_default()
+        );
+        let response = self
+            .client
+            .get(url)
+            .bearer_auth(self.token().await?)
+            .query(&[("api-version", API_VERSION)])
+            .send()
+            .await
+            .map_err(|e| {
+                Error::network_with_endpoint(
+                    format!("Key Vault request failed: {}", e),
+                    reference.store.clone(),
+                )
+            })?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        let message = body["error"]["message"]
+            .as_str()
+            .unwrap_or_else(|| status.canonical_reason().unwrap_or("unknown error"))
+            .to_string();
+        match status.as_u16() {
+            200 => body["value"]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| Error::parsing("Key Vault response has no value")),
+            401 | 403 => Err(Error::auth(format!(
+                "Key Vault denied reading {}: {}",
+                reference, message
+            ))),
+            404 => Err(Error::not_found_with_resource(
+                format!("Key Vault has no secret {}", reference),
+                "secret",
+                reference.to_string(),
+            )),
+            code => Err(Error::api_with_status(
+                format!("Key Vault request failed: {}", message),
+                "keyvault",
+                code,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn builds_vault_urls_and_rejects_malformed_references() {
+        let vault = KeyVault::new(KeyVaultConfig {
+            vault_suffix: "vault.azure.cn".to_string(),
+            ..KeyVaultConfig::default()
+        })
+        .unwrap();
+        assert_eq!(
+            vault.vault_url("lab").unwrap(),
+            "https://lab.vault.azure.cn"
+        );
+        assert_eq!(
+            vault.vault_url("lab.vault.usgovcloudapi.net").unwrap(),
+            "https://lab.vault.usgovcloudapi.net"
+        );
+        assert!(vault.vault_url("evil.com/x").is_err());
+
+        for path in ["db_password", "db-password/v1/extra", "db-password/.."] {
+            let reference = SecretRef::parse(&format!("keyvault://lab/{}", path)).unwrap();
+            let error = vault.fetch(&reference).await.unwrap_err().to_string();
+            assert!(error.contains("is not keyvault://"), "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_vault_tokens_as_the_service_principal() {
+        use axum::routing::post;
+        use axum::Router;
+        use std::sync::{Arc, Mutex};
+
+        let requests = Arc::new(Mutex::new(Vec::<String>::new()));
+        let seen = Arc::clone(&requests);
+        let app = Router::new().route(
+            "/contoso/oauth2/v2.0/token",
+            post(move |body: String| async move {
+                seen.lock().unwrap().push(body);
+                axum::Json(serde_json::json!({"access_token": "vault-token", "expires_in": 3600}))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let vault = KeyVault::new(KeyVaultConfig {
+            tenant_id: Some("contoso".to_string()),
+            client_id: Some("app".to_string()),
+            client_secret: Some("secret".to_string()),
+            authority_host: format!("http://{}/", address),
+            ..KeyVaultConfig::default()
+        })
+        .unwrap();
+        assert_eq!(vault.token().await.unwrap(), "vault-token");
+        assert_eq!(vault.token().await.unwrap(), "vault-token");
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("scope=https%3A%2F%2Fvault.azure.net%2F.default"));
+    }
+}
@@ -0,0 +1,258 @@
+//! Secret references in the config file
+//!
+//! Any string in the config can be a reference like
+//! `keyvault://my-vault/db-password` instead of the value itself. At
+//! startup `SecretResolver` finds every reference whose scheme has a
+//! backend, fetches each distinct secret once and puts the values in place
+//! before the config is parsed, so modules only ever see plain values.
+//! Backends implement `SecretBackend`; errors name the config path and the
+//! reference, never a value.
+
+pub mod keyvault;
+pub mod vault;
+
+pub use keyvault::{KeyVault, KeyVaultConfig};
+pub use vault::{Vault, VaultConfig};
+
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::task::JoinHandle;
+
+/// Renewal of the Vault token and leases the config was last resolved with
+static VAULT_RENEWAL: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+/// Schemes `SecretResolver::from_config` can resolve
+pub const BUILTIN_SCHEMES: &[&str] = &["keyvault", "vault"];
+
+/// Secret backends, under `secrets`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretsConfig {
+    /// Azure Key Vault credentials; without them `AZURE_*` variables or the
+    /// host's managed identity are used
+    #[serde(default)]
+    pub keyvault: Option<KeyVaultConfig>,
+    /// HashiCorp Vault; `VAULT_ADDR` and its credentials when unset
+    #[serde(default)]
+    pub vault: Option<VaultConfig>,
+}
+
+/// A parsed `<scheme>://<store>/<path>` reference
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SecretRef {
+    pub scheme: String,
+    /// Vault, mount or other container the secret lives in
+    pub store: String,
+    /// Secret within the store; backends decide how to read it
+    pub path: String,
+}
+
+impl SecretRef {
+    /// The reference in `value`, if it is one
+    pub fn parse(value: &str) -> Option<Self> {
+        let (scheme, rest) = value.split_once("://")?;
+        if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_lowercase()) {
+            return None;
+        }
+        let (store, path) = rest.split_once('/')?;
+        if store.is_empty() || path.is_empty() || path.ends_with('/') {
+            return None;
+        }
+        Some(Self {
+            scheme: scheme.to_string(),
+            store: store.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}://{}/{}", self.scheme, self.store, self.path)
+    }
+}
+
+/// Where secrets of one scheme are read from
+#[async_trait]
+pub trait SecretBackend: Send + Sync {
+    /// Scheme of the references this backend resolves, e.g. `keyvault`
+    fn scheme(&self) -> &'static str;
+
+    /// Current value of a secret
+    async fn fetch(&self, reference: &SecretRef) -> Result<String>;
+}
+
+/// Replaces secret references in config values
+#[derive(Default)]
+pub struct SecretResolver {
+    backends: HashMap<&'static str, Arc<dyn SecretBackend>>,
+}
+
+/// Append `key` to a JSON pointer
+fn pointer(parent: &str, key: &str) -> String {
+    format!("{}/{}", parent, key.replace('~', "~0").replace('/', "~1"))
+}
+
+impl SecretResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolver with every built-in backend; a configured Vault keeps its
+    /// token renewed until the next call replaces it, so this must run
+    /// inside the runtime
+    pub fn from_config(config: &SecretsConfig) -> Result<Self> {
+        let mut resolver = Self::new().with_backend(Arc::new(KeyVault::new(
+            config.keyvault.clone().unwrap_or_default(),
+        )?));
+        if let Some(vault) = config.vault.clone().or_else(VaultConfig::from_env) {
+            let vault = Arc::new(Vault::new(vault)?);
+            // Only the leases behind the latest config are kept alive
+            let renewal = Arc::clone(&vault).start_renewal();
+            let mut current = VAULT_RENEWAL.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(previous) = current.replace(renewal) {
+                previous.abort();
+            }
+            resolver = resolver.with_backend(vault);
+        }
+        Ok(resolver)
+    }
+
+    pub fn with_backend(mut self, backend: Arc<dyn SecretBackend>) -> Self {
+        self.backends.insert(backend.scheme(), backend);
+        self
+    }
+
+    /// Strings under `value` that are references to a known backend, by JSON pointer
+    fn references(&self, value: &Value, at: String, found: &mut Vec<(String, SecretRef)>) {
+        match value {
+            Value::String(s) => {
+                if let Some(reference) =
+                    SecretRef::parse(s).filter(|r| self.backends.contains_key(r.scheme.as_str()))
+                {
+                    found.push((at, reference));
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.references(item, pointer(&at, &index.to_string()), found);
+                }
+            }
+            Value::Object(map) => {
+                for (key, item) in map {
+                    self.references(item, pointer(&at, key), found);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Replace every reference under `value` with its secret; returns how
+    /// many values were replaced. Fails listing every reference that could
+    /// not be resolved.
+    pub async fn resolve(&self, value: &mut Value) -> Result<usize> {
+        let mut found = Vec::new();
+        self.references(value, String::new(), &mut found);
+        if found.is_empty() {
+            return Ok(0);
+        }
+
+        let mut distinct: Vec<&SecretRef> = found.iter().map(|(_, r)| r).collect();
+        distinct.sort_by_key(|r| r.to_string());
+        distinct.dedup();
+        let fetched = futures::future::join_all(
+            distinct
+                .iter()
+                .map(|r| async move { (*r, self.backends[r.scheme.as_str()].fetch(r).await) }),
+        )
+        .await;
+        let mut secrets = HashMap::new();
+        let mut failed = HashMap::new();
+        for (reference, result) in fetched {
+            match result {
+                Ok(secret) => {
+                    secrets.insert(reference.clone(), secret);
+                }
+                Err(e) => {
+                    failed.insert(reference.clone(), e.to_string());
+                }
+            }
+        }
+
+        let mut errors = Vec::new();
+        for (at, reference) in &found {
+            match (secrets.get(reference), failed.get(reference)) {
+                (Some(secret), _) => {
+                    if let Some(slot) = value.pointer_mut(at) {
+                        *slot = Value::String(secret.clone());
+                    }
+                }
+                (None, Some(e)) => errors.push(format!("{} ({}): {}", at, reference, e)),
+                (None, None) => {}
+            }
+        }
+        if errors.is_empty() {
+            Ok(found.len())
+        } else {
+            Err(Error::config_with_suggestion(
+                format!("Failed to resolve secrets: {}", errors.join("; ")),
+                "Check that each secret exists and the configured identity may read it",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Fixed(AtomicUsize);
+
+    #[async_trait]
+    impl SecretBackend for Fixed {
+        fn scheme(&self) -> &'static str {
+            "keyvault"
+        }
+
+        async fn fetch(&self, reference: &SecretRef) -> Result<String> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            match reference.path.as_str() {
+                "missing" => Err(Error::not_found("Secret not found")),
+                path => Ok(format!("{}-value", path)),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn replaces_references_and_reports_failures_by_path() {
+        let backend = Arc::new(Fixed(AtomicUsize::new(0)));
+        let resolver = SecretResolver::new().with_backend(backend.clone());
+        let mut config = json!({
+            "database": {"password": "keyvault://lab/db-password"},
+            "smart_home": {"token": "keyvault://lab/ha-token", "url": "https://ha.lan/api"},
+            "tenants": {"a/b": {"keys": ["keyvault://lab/db-password", "vault://other/x"]}}
+        });
+        assert_eq!(resolver.resolve(&mut config).await.unwrap(), 3);
+        assert_eq!(config["database"]["password"], "db-password-value");
+        assert_eq!(config["smart_home"]["url"], "https://ha.lan/api");
+        assert_eq!(config["tenants"]["a/b"]["keys"][0], "db-password-value");
+        // Schemes without a backend are left alone
+        assert_eq!(config["tenants"]["a/b"]["keys"][1], "vault://other/x");
+        // Each distinct secret is fetched once
+        assert_eq!(backend.0.load(Ordering::SeqCst), 2);
+
+        let mut config = json!({"ai": {"api_key": "keyvault://lab/missing"}});
+        let error = resolver.resolve(&mut config).await.unwrap_err().to_string();
+        assert!(
+            error.contains("/ai/api_key (keyvault://lab/missing)"),
+            "{}",
+            error
+        );
+    }
+}
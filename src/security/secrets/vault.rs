@@ -0,0 +1,491 @@
+//! HashiCorp Vault secrets
+//!
+//! `vault://<mount>/<path>#<key>` reads one key of a secret; the key can be
+//! left out when the secret has a single key. The engine behind a mount is
+//! looked up once, so KV v2 paths are read through `<mount>/data/<path>`
+//! while KV v1 and other engines are read directly. The server logs in
+//! with a token or an AppRole. `start_renewal` keeps the token and the
+//! leases of any dynamic secrets renewed, logging in again with the AppRole
+//! when the token can no longer be renewed.
+
+use super::{SecretBackend, SecretRef};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Renewal checks when nothing is due sooner
+const RENEW_CHECK: Duration = Duration::from_secs(60);
+
+fn default_approle_mount() -> String {
+    "approle".to_string()
+}
+
+/// Vault access, under `secrets.vault`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultConfig {
+    /// e.g. `https://vault.lan:8200`
+    pub address: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    /// AppRole login, used when no token is set
+    #[serde(default)]
+    pub role_id: Option<String>,
+    #[serde(default)]
+    pub secret_id: Option<String>,
+    #[serde(default = "default_approle_mount")]
+    pub approle_mount: String,
+    /// Vault Enterprise or HCP namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+impl VaultConfig {
+    /// Vault from `VAULT_ADDR` and `VAULT_TOKEN`, or `VAULT_ROLE_ID` and
+    /// `VAULT_SECRET_ID`, if the address is set
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Some(Self {
+            address: var("VAULT_ADDR")?,
+            token: var("VAULT_TOKEN"),
+            role_id: var("VAULT_ROLE_ID"),
+            secret_id: var("VAULT_SECRET_ID"),
+            approle_mount: default_approle_mount(),
+            namespace: var("VAULT_NAMESPACE"),
+        })
+    }
+}
+
+/// The token in use and when it runs out
+struct Session {
+    token: String,
+    renewable: bool,
+    /// `None` for tokens that never expire
+    expires: Option<Instant>,
+    ttl: Duration,
+}
+
+/// A dynamic secret's lease
+struct Lease {
+    ttl: Duration,
+    renewed: Instant,
+}
+
+/// How a mount is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Engine {
+    KvV2,
+    Other,
+}
+
+/// Vault secret reader
+pub struct Vault {
+    client: Client,
+    config: VaultConfig,
+    session: Mutex<Option<Session>>,
+    engines: Mutex<HashMap<String, Engine>>,
+    leases: Mutex<HashMap<String, Lease>>,
+}
+
+fn lease_duration(body: &Value) -> Duration {
+    Duration::from_secs(body["lease_duration"].as_u64().unwrap_or(0))
+}
+
+impl Vault {
+    pub fn new(config: VaultConfig) -> Result<Self> {
+        if config.token.is_none() && (config.role_id.is_none() || config.secret_id.is_none()) {
+            return Err(Error::config_with_suggestion(
+                "Vault needs a token or an AppRole role_id and secret_id",
+                "Set secrets.vault.token, or role_id and secret_id; or VAULT_TOKEN, or VAULT_ROLE_ID and VAULT_SECRET_ID",
+            ));
+        }
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Vault client: {}", e)))?;
+        Ok(Self {
+            client,
+            config,
+            session: Mutex::new(None),
+            engines: Mutex::new(HashMap::new()),
+            leases: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn request(&self, method: Method, path: &str, token: Option<&str>) -> RequestBuilder {
+        let mut request = self.client.request(
+            method,
+            format!("{}/v1/{}", self.config.address.trim_end_matches('/'), path),
+        );
+        if let Some(namespace) = &self.config.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        if let Some(token) = token {
+            request = request.header("X-Vault-Token", token);
+        }
+        request
+    }
+
+    async fn send(request: RequestBuilder) -> Result<Value> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Vault request failed: {}", e)))?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if status.is_success() {
+            return Ok(body);
+        }
+        let message = body["errors"]
+            .as_array()
+            .map(|errors| {
+                errors
+                    .iter()
+                    .filter_map(|e| e.as_str())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            })
+            .filter(|m| !m.is_empty())
+            .unwrap_or_else(|| {
+                status
+                    .canonical_reason()
+                    .unwrap_or("unknown error")
+                    .to_string()
+            });
+        Err(match status.as_u16() {
+            401 | 403 => Error::auth(format!("Vault denied the request: {}", message)),
+            404 => Error::not_found(format!("Vault: {}", message)),
+            code => {
+                Error::api_with_status(format!("Vault request failed: {}", message), "vault", code)
+            }
+        })
+    }
+
+    fn session_from(auth: &Value, token: String) -> Session {
+        let ttl = Duration::from_secs(
+            auth["lease_duration"]
+                .as_u64()
+                .or_else(|| auth["ttl"].as_u64())
+                .unwrap_or(0),
+        );
+        Session {
+            token,
+            renewable: auth["renewable"].as_bool().unwrap_or(false),
+            expires: (!ttl.is_zero()).then(|| Instant::now() + ttl),
+            ttl,
+        }
+    }
+
+    /// Log in with the configured token or AppRole
+    async fn login(&self) -> Result<Session> {
+        if let Some(token) = &self.config.token {
+            let lookup =
+                Self::send(self.request(Method::GET, "auth/token/lookup-self", Some(token)))
+                    .await?;
+            return Ok(Self::session_from(&lookup["data"], token.clone()));
+        }
+        let body = Self::send(
+            self.request(
+                Method::POST,
+                &format!("auth/{}/login", self.config.approle_mount),
+                None,
+            )
+            .json(&json!({
+                "role_id": self.config.role_id,
+                "secret_id": self.config.secret_id
+            })),
+        )
+        .await?;
+        let auth = &body["auth"];
+        let token = auth["client_token"]
+            .as_str()
+            .ok_or_else(|| Error::parsing("Vault login response has no client_token"))?;
+        Ok(Self::session_from(auth, token.to_string()))
+    }
+
+    /// A token that has not run out, logging in again if needed
+    async fn token(&self) -> Result<String> {
+        let mut session = self.session.lock().await;
+        if let Some(current) = session.as_ref() {
+            if current.expires.is_none_or(|at| Instant::now() < at) {
+                return Ok(current.token.clone());
+            }
+        }
+        let fresh = self.login().await?;
+        let token = fresh.token.clone();
+        *session = Some(fresh);
+        Ok(token)
+    }
+
+    /// How `mount` is read, asking Vault the first time
+    async fn engine(&self, mount: &str, token: &str) -> Result<Engine> {
+        if let Some(engine) = self.engines.lock().await.get(mount) {
+            return Ok(*engine);
+        }
+        let body = Self::send(self.request(
+            Method::GET,
+            &format!("sys/internal/ui/mounts/{}", mount),
+            Some(token),
+        ))
+        .await?;
+        let engine = match (
+            body["data"]["type"].as_str(),
+            body["data"]["options"]["version"].as_str(),
+        ) {
+            (Some("kv"), Some("2")) => Engine::KvV2,
+            _ => Engine::Other,
+        };
+        self.engines.lock().await.insert(mount.to_string(), engine);
+        Ok(engine)
+    }
+
+    /// Renew the token and every lease whose renewal is due; a token that
+    /// cannot be renewed is dropped so the next read logs in again
+    pub async fn renew(&self) -> Result<()> {
+        let mut session = self.session.lock().await;
+        if let Some(current) = session.as_ref().filter(|s| s.expires.is_some()) {
+            let renewed = if current.renewable {
+                Self::send(self.request(
+                    Method::POST,
+                    "auth/token/renew-self",
+                    Some(&current.token),
+                ))
+                .await
+                .map(|body| Self::session_from(&body["auth"], current.token.clone()))
+            } else {
+                Err(Error::auth("Vault token is not renewable"))
+            };
+            match renewed {
+                Ok(renewed) => *session = Some(renewed),
+                Err(e) => {
+                    tracing::warn!("Vault token renewal failed: {}", e);
+                    *session = None;
+                }
+            }
+        }
+        let token = session.as_ref().map(|s| s.token.clone());
+        drop(session);
+
+        let Some(token) = token else {
+            return Ok(());
+        };
+        let mut leases = self.leases.lock().await;
+        let due: Vec<String> = leases
+            .iter()
+            .filter(|(_, l)| l.renewed.elapsed() >= l.ttl * 2 / 3)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in due {
+            let renewed = Self::send(
+                self.request(Method::PUT, "sys/leases/renew", Some(&token))
+                    .json(&json!({ "lease_id": id })),
+            )
+            .await;
+            match renewed {
+                Ok(body) if !lease_duration(&body).is_zero() => {
+                    leases.insert(
+                        id,
+                        Lease {
+                            ttl: lease_duration(&body),
+                            renewed: Instant::now(),
+                        },
+                    );
+                }
+                Ok(_) => {
+                    leases.remove(&id);
+                }
+                Err(e) => {
+                    tracing::warn!("Vault lease renewal failed for {}: {}", id, e);
+                    leases.remove(&id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Time until the next renewal is due
+    async fn next_renewal(&self) -> Duration {
+        let token = self
+            .session
+            .lock()
+            .await
+            .as_ref()
+            .filter(|s| s.expires.is_some())
+            .map(|s| s.ttl * 2 / 3);
+        let lease = self
+            .leases
+            .lock()
+            .await
+            .values()
+            .map(|l| (l.ttl * 2 / 3).saturating_sub(l.renewed.elapsed()))
+            .min();
+        [token, lease]
+            .into_iter()
+            .flatten()
+            .chain([RENEW_CHECK])
+            .min()
+            .unwrap_or(RENEW_CHECK)
+            .max(Duration::from_secs(5))
+    }
+
+    /// Keep the token and secret leases renewed in the background
+    pub fn start_renewal(self: std::sync::Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(self.next_renewal().await).await;
+                if let Err(e) = self.renew().await {
+                    tracing::warn!("Vault renewal failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl SecretBackend for Vault {
+    fn scheme(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn fetch(&self, reference: &SecretRef) -> Result<String> {
+        let (path, key) = match reference.path.split_once('#') {
+            Some((path, key)) => (path, Some(key)),
+            None => (reference.path.as_str(), None),
+        };
+        let valid = |segment: &str| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_.@".contains(c))
+        };
+        if !valid(&reference.store) || !path.split('/').all(valid) {
+            return Err(Error::validation(format!(
+                "{} is not vault://<mount>/<path>#<key>",
+                reference
+            )));
+        }
+        let token = self.token().await?;
+        let engine = self.engine(&reference.store, &token).await?;
+        let api_path = match engine {
+            Engine::KvV2 => format!("{}/data/{}", reference.store, path),
+            Engine::Other => format!("{}/{}", reference.store, path),
+        };
+        let body = Self::send(self.request(Method::GET, &api_path, Some(&token))).await?;
+        if let Some(lease_id) = body["lease_id"].as_str().filter(|id| !id.is_empty()) {
+            if body["renewable"] == true {
+                self.leases.lock().await.insert(
+                    lease_id.to_string(),
+                    Lease {
+                        ttl: lease_duration(&body),
+                        renewed: Instant::now(),
+                    },
+                );
+            }
+        }
+        let data = match engine {
+            Engine::KvV2 => &body["data"]["data"],
+            Engine::Other => &body["data"],
+        };
+        let fields = data
+            .as_object()
+            .ok_or_else(|| Error::not_found(format!("Vault has no data at {}", reference)))?;
+        let value = match key {
+            Some(key) => fields.get(key).ok_or_else(|| {
+                Error::not_found_with_resource(
+                    format!("Vault secret {} has no key {}", path, key),
+                    "secret",
+                    reference.to_string(),
+                )
+            })?,
+            None if fields.len() == 1 => fields.values().next().unwrap_or(&Value::Null),
+            None => {
+                return Err(Error::validation(format!(
+                    "{} has {} keys; name one as #<key>",
+                    reference,
+                    fields.len()
+                )))
+            }
+        };
+        Ok(match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    type Received = Arc<std::sync::Mutex<Vec<Value>>>;
+
+    #[tokio::test]
+    async fn reads_kv_v2_with_approle_and_renews_the_token() {
+        let received: Received = Arc::default();
+        let app = Router::new()
+            .route(
+                "/v1/auth/approle/login",
+                post(|Json(body): Json<Value>| async move {
+                    assert_eq!(body["role_id"], "role");
+                    Json(json!({"auth": {"client_token": "s.1", "lease_duration": 3600, "renewable": true}}))
+                }),
+            )
+            .route(
+                "/v1/sys/internal/ui/mounts/secret",
+                get(|| async { Json(json!({"data": {"type": "kv", "options": {"version": "2"}}})) }),
+            )
+            .route(
+                "/v1/secret/data/homelab/postgres",
+                get(|headers: HeaderMap| async move {
+                    assert_eq!(headers["x-vault-token"], "s.1");
+                    Json(json!({"lease_id": "", "data": {"data": {"password": "hunter2", "user": "app"}}}))
+                }),
+            )
+            .route(
+                "/v1/auth/token/renew-self",
+                post(|State(received): State<Received>, headers: HeaderMap| async move {
+                    received.lock().unwrap().push(json!(headers["x-vault-token"].to_str().unwrap()));
+                    Json(json!({"auth": {"client_token": "s.1", "lease_duration": 7200, "renewable": true}}))
+                }),
+            )
+            .with_state(Arc::clone(&received));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let vault = Vault::new(VaultConfig {
+            address,
+            token: None,
+            role_id: Some("role".to_string()),
+            secret_id: Some("secret".to_string()),
+            approle_mount: default_approle_mount(),
+            namespace: None,
+        })
+        .unwrap();
+        let reference = SecretRef::parse("vault://secret/homelab/postgres#password").unwrap();
+        assert_eq!(vault.fetch(&reference).await.unwrap(), "hunter2");
+        let reference = SecretRef::parse("vault://secret/homelab/postgres").unwrap();
+        assert!(vault
+            .fetch(&reference)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("has 2 keys"));
+
+        vault.renew().await.unwrap();
+        assert_eq!(received.lock().unwrap()[0], "s.1");
+        assert_eq!(vault.next_renewal().await, RENEW_CHECK);
+    }
+}
@@ -0,0 +1,556 @@
+//! Home Assistant Assist: voice pipelines, conversation and satellites
+//!
+//! Everything goes over the Home Assistant WebSocket connection. Text is run
+//! through a pipeline with `assist_pipeline/run`, whose stages report back
+//! as events. Voice satellites are `assist_satellite` entities: they turn
+//! `listening` when they hear their wake word and return to `idle` once the
+//! answer has been spoken. What was said is read from the pipeline's debug
+//! runs, so a model can take the request, act on it and answer with
+//! `announce`, standing in for the pipeline's conversation agent.
+
+use crate::error::{Error, Result};
+use crate::smart_home::home_assistant::{HomeAssistantSocket, Subscription};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Time allowed for a pipeline run to reach `run-end`
+const RUN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How far a debug run may start before the wake word is reported
+const RUN_START_SLACK: chrono::Duration = chrono::Duration::seconds(5);
+
+/// An Assist pipeline: the engines a voice or text request goes through
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub language: String,
+    /// Conversation agent, e.g. `conversation.home_assistant`
+    #[serde(default)]
+    pub conversation_engine: Option<String>,
+    #[serde(default)]
+    pub stt_engine: Option<String>,
+    #[serde(default)]
+    pub tts_engine: Option<String>,
+    #[serde(default)]
+    pub wake_word_entity: Option<String>,
+    #[serde(default)]
+    pub wake_word_id: Option<String>,
+}
+
+/// Configured pipelines and the one used when none is named
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipelines {
+    pub pipelines: Vec<Pipeline>,
+    #[serde(default)]
+    pub preferred_pipeline: Option<String>,
+}
+
+impl Pipelines {
+    /// Pipeline by ID or case-insensitive name, else the preferred one
+    pub fn find(&self, pipeline: Option<&str>) -> Result<&Pipeline> {
+        let wanted = pipeline.or(self.preferred_pipeline.as_deref());
+        self.pipelines
+            .iter()
+            .find(|p| wanted.is_some_and(|w| p.id == w || p.name.eq_ignore_ascii_case(w)))
+            .ok_or_else(|| {
+                Error::not_found_with_resource(
+                    format!(
+                        "Home Assistant has no Assist pipeline {}",
+                        wanted.unwrap_or("(none preferred)")
+                    ),
+                    "pipeline",
+                    wanted.unwrap_or_default(),
+                )
+            })
+    }
+}
+
+/// What a conversation agent answered
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConversationResponse {
+    /// `action_done`, `query_answer` or `error`
+    pub response_type: String,
+    /// Text the agent speaks back
+    pub speech: String,
+    /// Pass back to continue the same conversation
+    pub conversation_id: Option<String>,
+    /// Whether the agent expects the user to answer
+    pub continue_conversation: bool,
+    /// Entities the agent acted on, or the error code
+    pub data: Value,
+}
+
+impl ConversationResponse {
+    /// Parse the `conversation/process` result or a pipeline's `intent_output`
+    pub fn from_result(result: &Value) -> Self {
+        let response = &result["response"];
+        Self {
+            response_type: response["response_type"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            speech: response["speech"]["plain"]["speech"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            conversation_id: result["conversation_id"].as_str().map(String::from),
+            continue_conversation: result["continue_conversation"].as_bool().unwrap_or(false),
+            data: response.get("data").cloned().unwrap_or(Value::Null),
+        }
+    }
+}
+
+/// One stage event of a pipeline run, such as `stt-end` or `intent-end`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub data: Value,
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// Events of one pipeline run, in order
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PipelineRun {
+    pub events: Vec<PipelineEvent>,
+}
+
+impl PipelineRun {
+    fn event(&self, kind: &str) -> Option<&Value> {
+        self.events.iter().find(|e| e.kind == kind).map(|e| &e.data)
+    }
+
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.events.first().and_then(|e| e.timestamp)
+    }
+
+    /// Wake word that started the run
+    pub fn wake_word(&self) -> Option<&str> {
+        self.event("wake_word-end")?["wake_word_output"]["wake_word_id"].as_str()
+    }
+
+    /// What was said, or the text the run was started with
+    pub fn transcript(&self) -> Option<&str> {
+        self.event("stt-end")
+            .and_then(|d| d["stt_output"]["text"].as_str())
+            .or_else(|| self.event("intent-start")?["intent_input"].as_str())
+    }
+
+    /// What the conversation agent answered
+    pub fn response(&self) -> Option<ConversationResponse> {
+        self.event("intent-end")
+            .map(|d| ConversationResponse::from_result(&d["intent_output"]))
+    }
+
+    /// URL of the spoken answer, when the run went through text to speech
+    pub fn tts_url(&self) -> Option<&str> {
+        self.event("tts-end")?["tts_output"]["url"].as_str()
+    }
+
+    /// The run's error as an `Error`, if it failed
+    pub fn error(&self) -> Option<Error> {
+        let data = self.event("error")?;
+        Some(Error::service(format!(
+            "Assist pipeline failed: {} ({})",
+            data["message"].as_str().unwrap_or("no message"),
+            data["code"].as_str().unwrap_or("unknown")
+        )))
+    }
+}
+
+/// Activity of a voice satellite
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SatelliteState {
+    Idle,
+    /// Heard the wake word and is streaming audio
+    Listening,
+    Processing,
+    /// Speaking the answer
+    Responding,
+    /// `unavailable`, `unknown` and states newer than this client
+    Other(String),
+}
+
+impl SatelliteState {
+    pub fn parse(state: &str) -> Self {
+        match state {
+            "idle" => SatelliteState::Idle,
+            "listening" => SatelliteState::Listening,
+            "processing" => SatelliteState::Processing,
+            "responding" => SatelliteState::Responding,
+            other => SatelliteState::Other(other.to_string()),
+        }
+    }
+}
+
+/// State change of an `assist_satellite` entity
+#[derive(Debug, Clone, Serialize)]
+pub struct SatelliteEvent {
+    pub entity_id: String,
+    pub state: SatelliteState,
+    pub previous: Option<SatelliteState>,
+    pub at: DateTime<Utc>,
+}
+
+impl SatelliteEvent {
+    /// Parse a `state_changed` event; `None` for entities other than satellites
+    pub fn from_state_changed(event: &Value) -> Option<Self> {
+        let data = &event["data"];
+        let entity_id = data["entity_id"].as_str()?;
+        if !entity_id.starts_with("assist_satellite.") {
+            return None;
+        }
+        let new_state = &data["new_state"];
+        Some(Self {
+            entity_id: entity_id.to_string(),
+            state: SatelliteState::parse(new_state["state"].as_str()?),
+            previous: data["old_state"]["state"]
+                .as_str()
+                .map(SatelliteState::parse),
+            at: new_state["last_changed"]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map_or_else(Utc::now, |t| t.with_timezone(&Utc)),
+        })
+    }
+
+    /// Whether the satellite just heard its wake word
+    pub fn is_wake(&self) -> bool {
+        self.state == SatelliteState::Listening && self.previous != Some(SatelliteState::Listening)
+    }
+}
+
+/// State changes of voice satellites, until dropped
+pub struct SatelliteEvents {
+    subscription: Subscription,
+}
+
+impl SatelliteEvents {
+    /// Next satellite state change, or `None` when the connection drops
+    pub async fn next(&mut self) -> Option<SatelliteEvent> {
+        loop {
+            let event = self.subscription.next().await?;
+            if let Some(event) = SatelliteEvent::from_state_changed(&event) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+/// Options for running text through a pipeline
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Pipeline ID; the preferred pipeline when unset
+    pub pipeline: Option<String>,
+    /// Continue an earlier conversation
+    pub conversation_id: Option<String>,
+    /// Also run text to speech for the answer
+    pub speak: bool,
+}
+
+/// Assist over a Home Assistant WebSocket connection
+pub struct Assist<'a> {
+    socket: &'a HomeAssistantSocket,
+}
+
+impl<'a> Assist<'a> {
+    pub fn new(socket: &'a HomeAssistantSocket) -> Self {
+        Self { socket }
+    }
+
+    pub async fn pipelines(&self) -> Result<Pipelines> {
+        let result = self
+            .socket
+            .request(json!({"type": "assist_pipeline/pipeline/list"}))
+            .await?;
+        serde_json::from_value(result)
+            .map_err(|e| Error::parsing(format!("Invalid Assist pipeline list: {}", e)))
+    }
+
+    /// Ask a conversation agent directly, without a pipeline
+    pub async fn process(
+        &self,
+        text: &str,
+        agent_id: Option<&str>,
+        conversation_id: Option<&str>,
+    ) -> Result<ConversationResponse> {
+        let mut message = json!({"type": "conversation/process", "text": text});
+        if let Some(agent_id) = agent_id {
+            message["agent_id"] = json!(agent_id);
+        }
+        if let Some(conversation_id) = conversation_id {
+            message["conversation_id"] = json!(conversation_id);
+        }
+        let result = self.socket.request(message).await?;
+        Ok(ConversationResponse::from_result(&result))
+    }
+
+    /// Run text through a pipeline's conversation agent, and its TTS if `speak`
+    pub async fn run_text(&self, text: &str, options: &RunOptions) -> Result<PipelineRun> {
+        if text.trim().is_empty() {
+            return Err(Error::validation_with_field(
+                "Text to run through Assist is empty",
+                "text",
+            ));
+        }
+        let mut message = json!({
+            "type": "assist_pipeline/run",
+            "start_stage": "intent",
+            "end_stage": if options.speak { "tts" } else { "intent" },
+            "input": {"text": text},
+        });
+        if let Some(pipeline) = &options.pipeline {
+            message["pipeline"] = json!(pipeline);
+        }
+        if let Some(conversation_id) = &options.conversation_id {
+            message["conversation_id"] = json!(conversation_id);
+        }
+        let mut subscription = self.socket.subscribe(message).await?;
+        let mut run = PipelineRun::default();
+        tokio::time::timeout(RUN_TIMEOUT, async {
+            while let Some(event) = subscription.next().await {
+                let event: PipelineEvent = serde_json::from_value(event)
+                    .map_err(|e| Error::parsing(format!("Invalid Assist event: {}", e)))?;
+                let done = event.kind == "run-end";
+                run.events.push(event);
+                if done {
+                    return Ok(());
+                }
+            }
+            Err(Error::network(
+                "Home Assistant connection lost during the Assist run",
+            ))
+        })
+        .await
+        .map_err(|_| {
+            Error::timeout_with_duration("Assist pipeline did not finish", RUN_TIMEOUT)
+        })??;
+        match run.error() {
+            Some(error) => Err(error),
+            None => Ok(run),
+        }
+    }
+
+    /// Speak `message` on a satellite
+    pub async fn announce(&self, satellite: &str, message: &str) -> Result<()> {
+        self.satellite_service("announce", satellite, json!({"message": message}))
+            .await
+    }
+
+    /// Speak `message` on a satellite, then listen for the answer
+    pub async fn start_conversation(&self, satellite: &str, message: &str) -> Result<()> {
+        self.satellite_service(
+            "start_conversation",
+            satellite,
+            json!({"start_message": message}),
+        )
+        .await
+    }
+
+    async fn satellite_service(&self, service: &str, satellite: &str, data: Value) -> Result<()> {
+        if !satellite.starts_with("assist_satellite.") {
+            return Err(Error::validation_with_field(
+                format!("{} is not an assist_satellite entity", satellite),
+                "satellite",
+            ));
+        }
+        self.socket
+            .call_service(
+                "assist_satellite",
+                service,
+                Some(data),
+                Some(json!({"entity_id": satellite})),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Follow satellite state changes, including wake words being heard
+    pub async fn satellite_events(&self) -> Result<SatelliteEvents> {
+        let subscription = self
+            .socket
+            .subscribe(json!({"type": "subscribe_events", "event_type": "state_changed"}))
+            .await?;
+        Ok(SatelliteEvents { subscription })
+    }
+
+    /// Latest run of a pipeline that started after `since`; needs an admin token
+    pub async fn last_run(
+        &self,
+        pipeline_id: &str,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Option<PipelineRun>> {
+        let list = self
+            .socket
+            .request(
+                json!({"type": "assist_pipeline/pipeline_debug/list", "pipeline_id": pipeline_id}),
+            )
+            .await?;
+        let Some(latest) = list["pipeline_runs"]
+            .as_array()
+            .and_then(|runs| runs.last())
+        else {
+            return Ok(None);
+        };
+        let result = self
+            .socket
+            .request(json!({
+                "type": "assist_pipeline/pipeline_debug/get",
+                "pipeline_id": pipeline_id,
+                "pipeline_run_id": latest["pipeline_run_id"],
+            }))
+            .await?;
+        let events: Vec<PipelineEvent> = serde_json::from_value(result["events"].clone())
+            .map_err(|e| Error::parsing(format!("Invalid Assist debug run: {}", e)))?;
+        let run = PipelineRun { events };
+        let recent = match (since, run.started_at()) {
+            (Some(since), Some(started)) => started >= since - RUN_START_SLACK,
+            _ => true,
+        };
+        Ok(recent.then_some(run))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smart_home::home_assistant::{HomeAssistantConfig, HomeAssistantTransportType};
+    use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+    use axum::routing::get;
+    use axum::Router;
+
+    async fn send(socket: &mut WebSocket, message: Value) {
+        socket
+            .send(Message::Text(message.to_string()))
+            .await
+            .unwrap();
+    }
+
+    fn result(id: &Value, result: Value) -> Value {
+        json!({"id": id, "type": "result", "success": true, "result": result})
+    }
+
+    fn run_events() -> Vec<Value> {
+        vec![
+            json!({"type": "run-start", "data": {"pipeline": "p1"}, "timestamp": "2026-10-16T08:00:00+00:00"}),
+            json!({"type": "wake_word-end", "data": {"wake_word_output": {"wake_word_id": "ok_nabu"}}}),
+            json!({"type": "stt-end", "data": {"stt_output": {"text": "Turn off the kitchen light"}}}),
+            json!({"type": "intent-end", "data": {"intent_output": {
+                "response": {"response_type": "action_done", "speech": {"plain": {"speech": "Turned off the light"}}, "data": {"success": []}},
+                "conversation_id": "c1",
+                "continue_conversation": false
+            }}}),
+            json!({"type": "tts-end", "data": {"tts_output": {"url": "/api/tts_proxy/abc.mp3"}}}),
+            json!({"type": "run-end", "data": null}),
+        ]
+    }
+
+    /// Home Assistant with one pipeline and a satellite that wakes on subscribe
+    async fn fake_home_assistant(mut socket: WebSocket) {
+        send(&mut socket, json!({"type": "auth_required"})).await;
+        while let Some(Ok(Message::Text(text))) = socket.recv().await {
+            let message: Value = serde_json::from_str(&text).unwrap();
+            let id = message["id"].clone();
+            match message["type"].as_str().unwrap() {
+                "auth" => send(&mut socket, json!({"type": "auth_ok"})).await,
+                "subscribe_events" => {
+                    send(&mut socket, result(&id, Value::Null)).await;
+                    if id != 1 {
+                        for (old, new) in [("idle", "listening"), ("listening", "processing")] {
+                            send(&mut socket, json!({"id": id, "type": "event", "event": {"data": {
+                                "entity_id": "assist_satellite.kitchen",
+                                "old_state": {"state": old},
+                                "new_state": {"state": new, "last_changed": "2026-10-16T08:00:01+00:00"}
+                            }}}))
+                            .await;
+                        }
+                    }
+                }
+                "assist_pipeline/pipeline/list" => {
+                    send(&mut socket, result(&id, json!({
+                        "pipelines": [{"id": "p1", "name": "Home Assistant", "language": "en", "conversation_engine": "conversation.home_assistant"}],
+                        "preferred_pipeline": "p1"
+                    })))
+                    .await
+                }
+                "assist_pipeline/run" => {
+                    assert_eq!(message["input"]["text"], "Turn off the kitchen light");
+                    send(&mut socket, result(&id, Value::Null)).await;
+                    for event in run_events().into_iter().skip(3) {
+                        send(&mut socket, json!({"id": id, "type": "event", "event": event})).await;
+                    }
+                }
+                "assist_pipeline/pipeline_debug/list" => {
+                    send(&mut socket, result(&id, json!({"pipeline_runs": [{"pipeline_run_id": "r1"}]}))).await
+                }
+                "assist_pipeline/pipeline_debug/get" => {
+                    send(&mut socket, result(&id, json!({"events": run_events()}))).await
+                }
+                "call_service" => {
+                    assert_eq!(message["domain"], "assist_satellite");
+                    assert_eq!(message["service_data"]["message"], "Dinner is ready");
+                    send(&mut socket, result(&id, json!({"context": {}}))).await
+                }
+                _ => send(&mut socket, result(&id, json!([]))).await,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_pipelines_and_follows_satellites() {
+        let app = Router::new().route(
+            "/api/websocket",
+            get(|ws: WebSocketUpgrade| async move { ws.on_upgrade(fake_home_assistant) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let socket = HomeAssistantSocket::connect(&HomeAssistantConfig {
+            url: format!("http://{}", addr),
+            token: "secret".to_string(),
+            transport_type: HomeAssistantTransportType::WebSocket,
+        })
+        .unwrap();
+        socket.wait_synced().await.unwrap();
+        let assist = Assist::new(&socket);
+
+        let pipelines = assist.pipelines().await.unwrap();
+        assert_eq!(pipelines.find(None).unwrap().id, "p1");
+        assert_eq!(pipelines.find(Some("home assistant")).unwrap().id, "p1");
+        assert!(pipelines.find(Some("other")).is_err());
+
+        let run = assist
+            .run_text("Turn off the kitchen light", &RunOptions::default())
+            .await
+            .unwrap();
+        let response = run.response().unwrap();
+        assert_eq!(response.speech, "Turned off the light");
+        assert_eq!(response.conversation_id.as_deref(), Some("c1"));
+        assert_eq!(run.tts_url(), Some("/api/tts_proxy/abc.mp3"));
+
+        let mut events = assist.satellite_events().await.unwrap();
+        let wake = events.next().await.unwrap();
+        assert!(wake.is_wake());
+        assert_eq!(wake.entity_id, "assist_satellite.kitchen");
+        assert!(!events.next().await.unwrap().is_wake());
+
+        let heard = assist.last_run("p1", Some(wake.at)).await.unwrap().unwrap();
+        assert_eq!(heard.wake_word(), Some("ok_nabu"));
+        assert_eq!(heard.transcript(), Some("Turn off the kitchen light"));
+        let later = wake.at + chrono::Duration::minutes(1);
+        assert!(assist.last_run("p1", Some(later)).await.unwrap().is_none());
+
+        assist
+            .announce("assist_satellite.kitchen", "Dinner is ready")
+            .await
+            .unwrap();
+        assert!(assist.announce("media_player.kitchen", "Hi").await.is_err());
+    }
+}
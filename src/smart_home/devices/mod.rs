@@ -0,0 +1,443 @@
+//! Provider-neutral devices and capabilities
+//!
+//! Lights, switches and thermostats reach Home Assistant through many
+//! integrations: MQTT discovery, Zigbee2MQTT, ZHA, Z-Wave JS or a vendor
+//! cloud. The entity registry records which one provides each entity. Here
+//! entities are normalized into devices with a provider and a list of
+//! capabilities, and commands are turned back into the service calls of
+//! each entity's domain, so callers never branch on either.
+
+use crate::error::{Error, Result};
+use crate::smart_home::home_assistant::{EntityState, HomeAssistantSocket};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Integration family a device is connected through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    /// MQTT discovery from anything but Zigbee2MQTT
+    Mqtt,
+    #[serde(rename = "zigbee2mqtt")]
+    Zigbee2Mqtt,
+    /// Zigbee through Home Assistant's own ZHA integration
+    Zha,
+    #[serde(rename = "zwave")]
+    ZWave,
+    /// Any other integration, helpers and template entities
+    HomeAssistant,
+}
+
+impl Provider {
+    /// Provider for an entity registry platform
+    pub fn from_platform(platform: Option<&str>) -> Self {
+        match platform {
+            Some("mqtt") => Self::Mqtt,
+            Some("zigbee2mqtt") => Self::Zigbee2Mqtt,
+            Some("zha") => Self::Zha,
+            Some("zwave_js") => Self::ZWave,
+            _ => Self::HomeAssistant,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mqtt => "mqtt",
+            Self::Zigbee2Mqtt => "zigbee2mqtt",
+            Self::Zha => "zha",
+            Self::ZWave => "zwave",
+            Self::HomeAssistant => "home_assistant",
+        }
+    }
+}
+
+/// Kind of capability, for filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CapabilityKind {
+    Switchable,
+    Dimmable,
+    Thermostat,
+    Sensor,
+}
+
+/// Something a device can do or report, with its current value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Capability {
+    Switchable {
+        on: bool,
+    },
+    /// Brightness of a light or speed of a fan, in percent
+    Dimmable {
+        level: Option<u8>,
+    },
+    Thermostat {
+        current_temperature: Option<f64>,
+        target_temperature: Option<f64>,
+        /// HVAC mode, e.g. `heat`, `cool` or `off`
+        mode: String,
+        modes: Vec<String>,
+        min_temperature: Option<f64>,
+        max_temperature: Option<f64>,
+    },
+    Sensor {
+        /// Device class such as `temperature` or `motion`, else `value`
+        kind: String,
+        value: String,
+        unit: Option<String>,
+    },
+}
+
+impl Capability {
+    pub fn kind(&self) -> CapabilityKind {
+        match self {
+            Self::Switchable { .. } => CapabilityKind::Switchable,
+            Self::Dimmable { .. } => CapabilityKind::Dimmable,
+            Self::Thermostat { .. } => CapabilityKind::Thermostat,
+            Self::Sensor { .. } => CapabilityKind::Sensor,
+        }
+    }
+}
+
+/// An entity normalized into a device
+#[derive(Debug, Clone, Serialize)]
+pub struct Device {
+    /// Entity ID the device is controlled through
+    pub id: String,
+    pub name: String,
+    pub area: Option<String>,
+    pub provider: Provider,
+    pub available: bool,
+    pub capabilities: Vec<Capability>,
+}
+
+fn number(attributes: &Value, name: &str) -> Option<f64> {
+    attributes.get(name).and_then(Value::as_f64)
+}
+
+impl Device {
+    /// Device for an entity, or `None` for domains without a capability
+    pub fn from_entity(entity: &EntityState) -> Option<Self> {
+        let attributes = &entity.attributes;
+        let on = entity.state == "on";
+        let capabilities = match entity.domain() {
+            "light" => {
+                let dimmable = attributes["supported_color_modes"]
+                    .as_array()
+                    .is_some_and(|modes| modes.iter().any(|mode| mode != "onoff"))
+                    || attributes.get("brightness").is_some_and(Value::is_number);
+                let mut capabilities = vec![Capability::Switchable { on }];
+                if dimmable {
+                    let level = number(attributes, "brightness")
+                        .map(|brightness| (brightness * 100.0 / 255.0).round() as u8);
+                    capabilities.push(Capability::Dimmable { level });
+                }
+                capabilities
+            }
+            "fan" => {
+                let mut capabilities = vec![Capability::Switchable { on }];
+                if attributes.get("percentage").is_some() {
+                    let level = number(attributes, "percentage").map(|p| p.round() as u8);
+                    capabilities.push(Capability::Dimmable { level });
+                }
+                capabilities
+            }
+            "switch" | "input_boolean" | "siren" => vec![Capability::Switchable { on }],
+            "climate" => vec![Capability::Thermostat {
+                current_temperature: number(attributes, "current_temperature"),
+                target_temperature: number(attributes, "temperature"),
+                mode: entity.state.clone(),
+                modes: attributes["hvac_modes"]
+                    .as_array()
+                    .map(|modes| {
+                        modes
+                            .iter()
+                            .filter_map(|m| m.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                min_temperature: number(attributes, "min_temp"),
+                max_temperature: number(attributes, "max_temp"),
+            }],
+            "sensor" | "binary_sensor" => vec![Capability::Sensor {
+                kind: attributes["device_class"]
+                    .as_str()
+                    .unwrap_or("value")
+                    .to_string(),
+                value: entity.state.clone(),
+                unit: attributes["unit_of_measurement"]
+                    .as_str()
+                    .map(str::to_string),
+            }],
+            _ => return None,
+        };
+        Some(Self {
+            id: entity.entity_id.clone(),
+            name: entity.name().to_string(),
+            area: entity.area.clone(),
+            provider: Provider::from_platform(entity.platform.as_deref()),
+            available: entity.state != "unavailable",
+            capabilities,
+        })
+    }
+
+    pub fn capability(&self, kind: CapabilityKind) -> Option<&Capability> {
+        self.capabilities.iter().find(|c| c.kind() == kind)
+    }
+
+    fn domain(&self) -> &str {
+        self.id.split_once('.').map_or("", |(domain, _)| domain)
+    }
+
+    fn require(&self, kind: CapabilityKind, what: &str) -> Result<&Capability> {
+        self.capability(kind).ok_or_else(|| {
+            Error::validation_with_field(format!("{} cannot {}", self.name, what), "device")
+        })
+    }
+}
+
+/// Devices and capability commands over the Home Assistant connection
+pub struct Devices<'a> {
+    socket: &'a HomeAssistantSocket,
+}
+
+impl<'a> Devices<'a> {
+    pub fn new(socket: &'a HomeAssistantSocket) -> Self {
+        Self { socket }
+    }
+
+    /// Devices, optionally limited to an area ID or name and a capability
+    pub async fn list(
+        &self,
+        area: Option<&str>,
+        capability: Option<CapabilityKind>,
+    ) -> Result<Vec<Device>> {
+        Ok(self
+            .socket
+            .entities(None, area)
+            .await?
+            .iter()
+            .filter_map(Device::from_entity)
+            .filter(|d| capability.is_none_or(|kind| d.capability(kind).is_some()))
+            .collect())
+    }
+
+    /// Device by entity ID, or by case-insensitive name
+    pub async fn get(&self, device: &str) -> Result<Device> {
+        if device.contains('.') {
+            if let Ok(entity) = self.socket.state(device).await {
+                if let Some(device) = Device::from_entity(&entity) {
+                    return Ok(device);
+                }
+            }
+        }
+        self.list(None, None)
+            .await?
+            .into_iter()
+            .find(|d| d.id == device || d.name.eq_ignore_ascii_case(device))
+            .ok_or_else(|| {
+                Error::not_found_with_resource(
+                    format!("No device named {}", device),
+                    "device",
+                    device,
+                )
+            })
+    }
+
+    pub async fn set_power(&self, device: &str, on: bool) -> Result<Device> {
+        let device = self.get(device).await?;
+        let what = if on { "be turned on" } else { "be turned off" };
+        device.require(CapabilityKind::Switchable, what)?;
+        let service = if on { "turn_on" } else { "turn_off" };
+        self.call(&device, service, None).await?;
+        Ok(device)
+    }
+
+    /// Set a light's brightness or a fan's speed in percent; 0 turns it off
+    pub async fn set_level(&self, device: &str, level: u8) -> Result<Device> {
+        if level > 100 {
+            return Err(Error::validation_with_field(
+                "Level is a percentage from 0 to 100",
+                "level",
+            ));
+        }
+        let device = self.get(device).await?;
+        device.require(CapabilityKind::Dimmable, "be dimmed")?;
+        match device.domain() {
+            "fan" => {
+                self.call(
+                    &device,
+                    "set_percentage",
+                    Some(json!({ "percentage": level })),
+                )
+                .await?
+            }
+            _ => {
+                self.call(&device, "turn_on", Some(json!({ "brightness_pct": level })))
+                    .await?
+            }
+        };
+        Ok(device)
+    }
+
+    /// Set a thermostat's target, checked against its range
+    pub async fn set_temperature(&self, device: &str, temperature: f64) -> Result<Device> {
+        let device = self.get(device).await?;
+        if let Capability::Thermostat {
+            min_temperature,
+            max_temperature,
+            ..
+        } = device.require(CapabilityKind::Thermostat, "set a temperature")?
+        {
+            let below = min_temperature.is_some_and(|min| temperature < min);
+            let above = max_temperature.is_some_and(|max| temperature > max);
+            if below || above {
+                return Err(Error::validation_with_field(
+                    format!(
+                        "{} accepts {} to {}",
+                        device.name,
+                        min_temperature.map_or("?".to_string(), |t| t.to_string()),
+                        max_temperature.map_or("?".to_string(), |t| t.to_string())
+                    ),
+                    "temperature",
+                ));
+            }
+        }
+        self.call(
+            &device,
+            "set_temperature",
+            Some(json!({ "temperature": temperature })),
+        )
+        .await?;
+        Ok(device)
+    }
+
+    /// Set every available thermostat in an area; returns the ones set
+    pub async fn set_room_temperature(&self, area: &str, temperature: f64) -> Result<Vec<Device>> {
+        let thermostats: Vec<Device> = self
+            .list(Some(area), Some(CapabilityKind::Thermostat))
+            .await?
+            .into_iter()
+            .filter(|d| d.available)
+            .collect();
+        if thermostats.is_empty() {
+            return Err(Error::not_found_with_resource(
+                format!("No thermostat in {}", area),
+                "area",
+                area,
+            ));
+        }
+        for thermostat in &thermostats {
+            self.set_temperature(&thermostat.id, temperature).await?;
+        }
+        Ok(thermostats)
+    }
+
+    async fn call(&self, device: &Device, service: &str, data: Option<Value>) -> Result<Value> {
+        self.socket
+            .call_service(
+                device.domain(),
+                service,
+                data,
+                Some(json!({ "entity_id": device.id })),
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(entity_id: &str, state: &str, attributes: Value, platform: &str) -> EntityState {
+        EntityState {
+            entity_id: entity_id.to_string(),
+            state: state.to_string(),
+            attributes,
+            last_changed: None,
+            last_updated: None,
+            device_id: None,
+            platform: Some(platform.to_string()),
+            area_id: Some("living_room".to_string()),
+            area: Some("Living room".to_string()),
+        }
+    }
+
+    #[test]
+    fn normalizes_entities_from_each_provider() {
+        let lamp = Device::from_entity(&entity(
+            "light.sofa",
+            "on",
+            json!({"friendly_name": "Sofa lamp", "supported_color_modes": ["brightness"], "brightness": 128}),
+            "zigbee2mqtt",
+        ))
+        .unwrap();
+        assert_eq!(lamp.provider, Provider::Zigbee2Mqtt);
+        assert_eq!(
+            lamp.capabilities,
+            [
+                Capability::Switchable { on: true },
+                Capability::Dimmable { level: Some(50) }
+            ]
+        );
+
+        let plug = Device::from_entity(&entity(
+            "light.plug",
+            "off",
+            json!({"supported_color_modes": ["onoff"]}),
+            "zha",
+        ))
+        .unwrap();
+        assert_eq!(plug.provider, Provider::Zha);
+        assert!(plug.capability(CapabilityKind::Dimmable).is_none());
+
+        let thermostat = Device::from_entity(&entity(
+            "climate.radiator",
+            "heat",
+            json!({"current_temperature": 19.5, "temperature": 21, "hvac_modes": ["off", "heat"], "min_temp": 7, "max_temp": 28}),
+            "zwave_js",
+        ))
+        .unwrap();
+        assert_eq!(thermostat.provider, Provider::ZWave);
+        match thermostat.capability(CapabilityKind::Thermostat).unwrap() {
+            Capability::Thermostat {
+                target_temperature,
+                modes,
+                max_temperature,
+                ..
+            } => {
+                assert_eq!(*target_temperature, Some(21.0));
+                assert_eq!(modes, &["off", "heat"]);
+                assert_eq!(*max_temperature, Some(28.0));
+            }
+            other => panic!("expected a thermostat, got {:?}", other),
+        }
+
+        let sensor = Device::from_entity(&entity(
+            "sensor.humidity",
+            "unavailable",
+            json!({"device_class": "humidity", "unit_of_measurement": "%"}),
+            "mqtt",
+        ))
+        .unwrap();
+        assert_eq!(sensor.provider, Provider::Mqtt);
+        assert!(!sensor.available);
+        assert_eq!(
+            sensor.capabilities,
+            [Capability::Sensor {
+                kind: "humidity".to_string(),
+                value: "unavailable".to_string(),
+                unit: Some("%".to_string())
+            }]
+        );
+
+        assert!(
+            Device::from_entity(&entity("automation.x", "on", json!({}), "automation")).is_none()
+        );
+        assert_eq!(
+            Provider::from_platform(Some("hue")),
+            Provider::HomeAssistant
+        );
+    }
+}
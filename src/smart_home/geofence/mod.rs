@@ -0,0 +1,682 @@
+//! Presence events from geofences
+//!
+//! The maps module defines geofences and tells when a tracker crosses one.
+//! This engine follows `device_tracker` and `person` positions in Home
+//! Assistant, turns crossings into enter and exit events and adds an
+//! approaching event when the routed travel time to a geofence drops under
+//! a rule's threshold, such as ten minutes from home. Rules deliver events
+//! as Home Assistant events, service calls or webhook posts, which is how
+//! scheduled jobs, automations and workflows pick them up.
+
+use crate::error::{Error, Result};
+use crate::maps::osm::Point;
+use crate::maps::routing::{haversine_m, RoutingClient, TravelMode};
+use crate::maps::tracks::geofence::TrackerUpdate;
+use crate::maps::GeofenceManager;
+use crate::smart_home::home_assistant::HomeAssistantClient;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+/// Events kept for `recent_events`
+const MAX_RECENT: usize = 100;
+
+/// Minimum time between route lookups for one rule and tracker
+const ROUTE_INTERVAL_S: i64 = 60;
+
+/// An approaching tracker must fall back this far past the threshold before
+/// it can approach again, so GPS jitter at the boundary does not repeat it
+const APPROACH_RESET_FACTOR: f64 = 1.5;
+
+/// Wait before subscribing again after the Home Assistant connection drops
+const RESUBSCRIBE_DELAY: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Home Assistant event type fired when a rule does not name one
+fn default_event_type() -> String {
+    "geofence".to_string()
+}
+
+fn default_mode() -> TravelMode {
+    TravelMode::Driving
+}
+
+/// Top speed used to skip routing trackers that cannot arrive in time
+fn max_speed_kmh(mode: TravelMode) -> f64 {
+    match mode {
+        TravelMode::Driving => 130.0,
+        TravelMode::Cycling => 35.0,
+        TravelMode::Walking => 7.0,
+    }
+}
+
+/// What happened between a tracker and a geofence
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PresenceKind {
+    Enter,
+    Exit,
+    /// Travel time to the geofence fell under a rule's `within_minutes`
+    Approaching,
+}
+
+impl PresenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PresenceKind::Enter => "enter",
+            PresenceKind::Exit => "exit",
+            PresenceKind::Approaching => "approaching",
+        }
+    }
+}
+
+/// A structured presence event, as published to subscribers and actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresenceEvent {
+    pub kind: PresenceKind,
+    pub geofence_id: String,
+    pub geofence_name: String,
+    pub entity_id: String,
+    /// Tracker friendly name, if Home Assistant provided one
+    pub entity_name: Option<String>,
+    pub point: Point,
+    pub at: DateTime<Utc>,
+    /// Travel time to the geofence, for approaching events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eta_minutes: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_m: Option<f64>,
+    /// Whether the travel time is a straight-line estimate rather than routed
+    #[serde(default)]
+    pub estimated: bool,
+    /// Ids of the rules whose actions ran for this event
+    #[serde(default)]
+    pub rules: Vec<String>,
+}
+
+impl PresenceEvent {
+    /// Human readable description
+    pub fn message(&self) -> String {
+        let who = self.entity_name.as_deref().unwrap_or(&self.entity_id);
+        match (self.kind, self.eta_minutes) {
+            (PresenceKind::Enter, _) => format!("{} arrived at {}", who, self.geofence_name),
+            (PresenceKind::Exit, _) => format!("{} left {}", who, self.geofence_name),
+            (PresenceKind::Approaching, Some(eta)) => format!(
+                "{} is {:.0} minutes from {}",
+                who,
+                eta.ceil(),
+                self.geofence_name
+            ),
+            (PresenceKind::Approaching, None) => {
+                format!("{} is approaching {}", who, self.geofence_name)
+            }
+        }
+    }
+}
+
+/// Where a rule delivers its events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RuleAction {
+    /// Fire an event on the Home Assistant bus for automations to trigger on
+    Event {
+        #[serde(default = "default_event_type")]
+        event_type: String,
+    },
+    /// Call a Home Assistant service such as `climate.set_temperature`
+    Service {
+        service: String,
+        #[serde(default)]
+        data: Option<Value>,
+        /// `entity_id`, `device_id` or `area_id`
+        #[serde(default)]
+        target: Option<Value>,
+    },
+    /// POST the event as JSON, e.g. to a workflow or scheduler webhook
+    Webhook { url: String },
+}
+
+impl RuleAction {
+    fn validate(&self) -> Result<()> {
+        match self {
+            RuleAction::Event { event_type } if event_type.trim().is_empty() => Err(
+                Error::validation_with_field("Event type must not be empty", "event_type"),
+            ),
+            RuleAction::Service { service, .. } if service.split_once('.').is_none() => {
+                Err(Error::validation_with_field(
+                    format!("Service must be domain.service, got {}", service),
+                    "service",
+                ))
+            }
+            RuleAction::Webhook { url } => url::Url::parse(url)
+                .map(|_| ())
+                .map_err(|e| Error::validation_with_field(format!("Invalid URL: {}", e), "url")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Actions to run when trackers enter, leave or approach a geofence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeofenceRule {
+    pub id: String,
+    pub geofence_id: String,
+    pub on: PresenceKind,
+    /// Trackers the rule applies to; empty applies to every tracker
+    #[serde(default)]
+    pub entities: Vec<String>,
+    /// Travel time threshold for approaching rules
+    #[serde(default)]
+    pub within_minutes: Option<f64>,
+    /// Travel mode used to route approaching trackers
+    #[serde(default = "default_mode")]
+    pub mode: TravelMode,
+    #[serde(default)]
+    pub actions: Vec<RuleAction>,
+}
+
+impl GeofenceRule {
+    fn applies_to(&self, entity_id: &str) -> bool {
+        self.entities.is_empty() || self.entities.iter().any(|e| e == entity_id)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.id.trim().is_empty() {
+            return Err(Error::validation_with_field("Rule id is required", "id"));
+        }
+        match (self.on, self.within_minutes) {
+            (PresenceKind::Approaching, None) => {
+                return Err(Error::validation_with_field(
+                    "Approaching rules need within_minutes",
+                    "within_minutes",
+                ))
+            }
+            (_, Some(minutes)) if minutes <= 0.0 => {
+                return Err(Error::validation_with_field(
+                    "within_minutes must be positive",
+                    "within_minutes",
+                ))
+            }
+            _ => {}
+        }
+        self.actions.iter().try_for_each(RuleAction::validate)
+    }
+}
+
+/// Evaluates tracker positions and runs the matching rules
+pub struct GeofenceEngine {
+    fences: Arc<GeofenceManager>,
+    routing: RoutingClient,
+    home_assistant: Option<Arc<HomeAssistantClient>>,
+    http: reqwest::Client,
+    rules: RwLock<BTreeMap<String, GeofenceRule>>,
+    /// (rule id, entity id) pairs that have approached and not yet reset
+    approaching: RwLock<HashSet<(String, String)>>,
+    /// Last route lookup per (rule id, entity id)
+    routed: RwLock<HashMap<(String, String), DateTime<Utc>>>,
+    recent: RwLock<VecDeque<PresenceEvent>>,
+    events: broadcast::Sender<PresenceEvent>,
+    watcher: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl GeofenceEngine {
+    /// Create an engine over a geofence manager
+    pub fn new(
+        fences: Arc<GeofenceManager>,
+        routing: RoutingClient,
+        home_assistant: Option<Arc<HomeAssistantClient>>,
+    ) -> Self {
+        Self {
+            fences,
+            routing,
+            home_assistant,
+            http: reqwest::Client::new(),
+            rules: RwLock::new(BTreeMap::new()),
+            approaching: RwLock::new(HashSet::new()),
+            routed: RwLock::new(HashMap::new()),
+            recent: RwLock::new(VecDeque::new()),
+            events: broadcast::channel(64).0,
+            watcher: Mutex::new(None),
+        }
+    }
+
+    /// The geofences the engine evaluates
+    pub fn geofences(&self) -> &Arc<GeofenceManager> {
+        &self.fences
+    }
+
+    /// Receive presence events as they happen
+    pub fn subscribe(&self) -> broadcast::Receiver<PresenceEvent> {
+        self.events.subscribe()
+    }
+
+    /// Add or replace a rule; its geofence must exist
+    pub async fn add_rule(&self, rule: GeofenceRule) -> Result<()> {
+        rule.validate()?;
+        if self.fences.get_geofence(&rule.geofence_id).await.is_none() {
+            return Err(Error::not_found_with_resource(
+                "Geofence not found",
+                "geofence",
+                &rule.geofence_id,
+            ));
+        }
+        let id = rule.id.clone();
+        self.approaching.write().await.retain(|(r, _)| r != &id);
+        self.rules.write().await.insert(id, rule);
+        Ok(())
+    }
+
+    /// Remove a rule
+    pub async fn remove_rule(&self, id: &str) -> Result<GeofenceRule> {
+        let rule =
+            self.rules.write().await.remove(id).ok_or_else(|| {
+                Error::not_found_with_resource("Rule not found", "geofence_rule", id)
+            })?;
+        self.approaching.write().await.retain(|(r, _)| r != id);
+        self.routed.write().await.retain(|(r, _), _| r != id);
+        Ok(rule)
+    }
+
+    /// List rules
+    pub async fn list_rules(&self) -> Vec<GeofenceRule> {
+        self.rules.read().await.values().cloned().collect()
+    }
+
+    /// Most recent events, newest last
+    pub async fn recent_events(&self, limit: usize) -> Vec<PresenceEvent> {
+        let recent = self.recent.read().await;
+        recent
+            .iter()
+            .skip(recent.len().saturating_sub(limit))
+            .cloned()
+            .collect()
+    }
+
+    /// Evaluate a tracker state, run matching rules and publish the events
+    pub async fn handle_update(
+        &self,
+        entity_id: &str,
+        state: &Value,
+    ) -> Result<Vec<PresenceEvent>> {
+        let update = TrackerUpdate::from_state(entity_id, state)?;
+        let crossings = self.fences.handle_tracker_update(entity_id, state).await?;
+        let rules = self.list_rules().await;
+
+        let mut events = Vec::new();
+        for crossing in crossings {
+            let kind = match crossing.kind {
+                crate::maps::tracks::GeofenceEventKind::Enter => PresenceKind::Enter,
+                crate::maps::tracks::GeofenceEventKind::Exit => PresenceKind::Exit,
+            };
+            if kind == PresenceKind::Enter {
+                // Arriving resets approaching so the next trip is announced
+                self.approaching
+                    .write()
+                    .await
+                    .retain(|(_, e)| e != entity_id);
+            }
+            let matching: Vec<&GeofenceRule> = rules
+                .iter()
+                .filter(|r| {
+                    r.on == kind && r.geofence_id == crossing.geofence_id && r.applies_to(entity_id)
+                })
+                .collect();
+            let event = PresenceEvent {
+                kind,
+                geofence_id: crossing.geofence_id,
+                geofence_name: crossing.geofence_name,
+                entity_id: crossing.entity_id,
+                entity_name: crossing.entity_name,
+                point: crossing.point,
+                at: crossing.at,
+                eta_minutes: None,
+                distance_m: None,
+                estimated: false,
+                rules: matching.iter().map(|r| r.id.clone()).collect(),
+            };
+            self.dispatch(&event, &matching).await;
+            events.push(event);
+        }
+
+        if update
+            .accuracy_m
+            .is_none_or(|a| a <= self.fences.max_accuracy())
+        {
+            for rule in rules
+                .iter()
+                .filter(|r| r.on == PresenceKind::Approaching && r.applies_to(entity_id))
+            {
+                if let Some(event) = self.approach(rule, entity_id, &update).await {
+                    self.dispatch(&event, &[rule]).await;
+                    events.push(event);
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Route a tracker to a rule's geofence and decide whether it is approaching
+    async fn approach(
+        &self,
+        rule: &GeofenceRule,
+        entity_id: &str,
+        update: &TrackerUpdate,
+    ) -> Option<PresenceEvent> {
+        let fence = self.fences.get_geofence(&rule.geofence_id).await?;
+        let within = rule.within_minutes?;
+        let key = (rule.id.clone(), entity_id.to_string());
+        if fence.shape.contains(&update.point) {
+            return None;
+        }
+
+        // Even at top speed the tracker is too far to matter, so skip routing
+        let target = fence.shape.center();
+        let fastest_minutes =
+            haversine_m(&update.point, &target) / (max_speed_kmh(rule.mode) / 3.6) / 60.0;
+        if fastest_minutes > within * APPROACH_RESET_FACTOR {
+            self.approaching.write().await.remove(&key);
+            return None;
+        }
+        let already = self.approaching.read().await.contains(&key);
+        if already && fastest_minutes <= within {
+            return None;
+        }
+        {
+            let mut routed = self.routed.write().await;
+            let now = Utc::now();
+            if routed
+                .get(&key)
+                .is_some_and(|last| now - *last < Duration::seconds(ROUTE_INTERVAL_S))
+            {
+                return None;
+            }
+            routed.insert(key.clone(), now);
+        }
+
+        let route = self
+            .routing
+            .route_or_estimate(&update.point, &target, rule.mode)
+            .await;
+        let eta_minutes = route.duration_s / 60.0;
+        let mut approaching = self.approaching.write().await;
+        if eta_minutes > within * APPROACH_RESET_FACTOR {
+            approaching.remove(&key);
+            return None;
+        }
+        if eta_minutes > within || !approaching.insert(key) {
+            return None;
+        }
+        Some(PresenceEvent {
+            kind: PresenceKind::Approaching,
+            geofence_id: fence.id,
+            geofence_name: fence.name,
+            entity_id: entity_id.to_string(),
+            entity_name: update.name.clone(),
+            point: update.point.clone(),
+            at: update.at,
+            eta_minutes: Some(eta_minutes),
+            distance_m: Some(route.distance_m),
+            estimated: route.estimated,
+            rules: vec![rule.id.clone()],
+        })
+    }
+
+    /// Publish an event and run the actions of its rules
+    async fn dispatch(&self, event: &PresenceEvent, rules: &[&GeofenceRule]) {
+        {
+            let mut recent = self.recent.write().await;
+            if recent.len() == MAX_RECENT {
+                recent.pop_front();
+            }
+            recent.push_back(event.clone());
+        }
+        // No subscribers is not an error
+        let _ = self.events.send(event.clone());
+
+        for rule in rules {
+            for action in &rule.actions {
+                // A failed action should not stop the others or lose the event
+                if let Err(e) = self.run_action(action, event).await {
+                    tracing::warn!(error = %e, rule = %rule.id, "Geofence rule action failed");
+                }
+            }
+        }
+    }
+
+    async fn run_action(&self, action: &RuleAction, event: &PresenceEvent) -> Result<()> {
+        match action {
+            RuleAction::Webhook { url } => {
+                let response = self
+                    .http
+                    .post(url)
+                    .json(event)
+                    .send()
+                    .await
+                    .map_err(|e| Error::network(format!("Webhook request failed: {}", e)))?;
+                if !response.status().is_success() {
+                    return Err(Error::api_with_status(
+                        format!("Webhook returned {}", response.status()),
+                        "webhook",
+                        response.status().as_u16(),
+                    ));
+                }
+            }
+            RuleAction::Event { event_type } => {
+                self.socket()
+                    .await?
+                    .request(json!({
+                        "type": "fire_event",
+                        "event_type": event_type,
+                        "event_data": event,
+                    }))
+                    .await?;
+            }
+            RuleAction::Service {
+                service,
+                data,
+                target,
+            } => {
+                let (domain, service) = service.split_once('.').unwrap_or((service, ""));
+                self.socket()
+                    .await?
+                    .call_service(domain, service, data.clone(), target.clone())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn socket(&self) -> Result<&crate::smart_home::home_assistant::HomeAssistantSocket> {
+        self.home_assistant
+            .as_ref()
+            .ok_or_else(|| {
+                Error::config_with_suggestion(
+                    "Home Assistant is not configured for geofence rules",
+                    "Use webhook actions, or configure smart_home.home_assistant",
+                )
+            })?
+            .socket()
+            .await
+    }
+
+    /// Follow tracker state changes in Home Assistant; calling again is a no-op
+    ///
+    /// The watcher runs for the life of the process and subscribes again
+    /// whenever the connection drops.
+    pub async fn start(self: &Arc<Self>) -> Result<()> {
+        let mut watcher = self.watcher.lock().await;
+        if watcher.as_ref().is_some_and(|w| !w.is_finished()) {
+            return Ok(());
+        }
+        let client = self.home_assistant.clone().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Home Assistant is not configured for geofencing",
+                "Set smart_home.home_assistant in the config file, or HOME_ASSISTANT_URL and HOME_ASSISTANT_TOKEN",
+            )
+        })?;
+        let engine = Arc::clone(self);
+        *watcher = Some(tokio::spawn(async move {
+            loop {
+                if let Err(e) = engine.watch(&client).await {
+                    tracing::warn!(error = %e, "Geofence watcher lost Home Assistant");
+                }
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            }
+        }));
+        Ok(())
+    }
+
+    /// Stop following tracker state until started again
+    pub async fn stop(&self) {
+        if let Some(watcher) = self.watcher.lock().await.take() {
+            watcher.abort();
+        }
+    }
+
+    async fn watch(&self, client: &HomeAssistantClient) -> Result<()> {
+        let mut subscription = client
+            .socket()
+            .await?
+            .subscribe(json!({"type": "subscribe_events", "event_type": "state_changed"}))
+            .await?;
+        while let Some(event) = subscription.next().await {
+            let data = &event["data"];
+            let Some(entity_id) = data["entity_id"].as_str() else {
+                continue;
+            };
+            let new_state = &data["new_state"];
+            if !(entity_id.starts_with("device_tracker.") || entity_id.starts_with("person."))
+                || new_state["attributes"]["latitude"].is_null()
+            {
+                continue;
+            }
+            if let Err(e) = self.handle_update(entity_id, new_state).await {
+                tracing::debug!(error = %e, entity_id, "Skipping tracker update");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maps::routing::RoutingConfig;
+    use crate::maps::tracks::{Geofence, GeofenceShape};
+    use axum::{routing::post, Json, Router};
+    use std::sync::Mutex as StdMutex;
+
+    #[tokio::test]
+    async fn enter_exit_and_approaching_reach_rules() {
+        let received = Arc::new(StdMutex::new(Vec::<Value>::new()));
+        let sink = Arc::clone(&received);
+        let app = Router::new().route(
+            "/hook",
+            post(move |Json(body): Json<Value>| {
+                let sink = Arc::clone(&sink);
+                async move {
+                    sink.lock().unwrap().push(body);
+                    "ok"
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hook = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let fences = Arc::new(GeofenceManager::new());
+        fences
+            .add_geofence(Geofence {
+                id: "home".to_string(),
+                name: "Home".to_string(),
+                shape: GeofenceShape::Circle {
+                    center: Point {
+                        lon: 13.40,
+                        lat: 52.52,
+                    },
+                    radius_m: 150.0,
+                },
+                entities: Vec::new(),
+                notify: Vec::new(),
+                notify_on_enter: true,
+                notify_on_exit: true,
+            })
+            .await
+            .unwrap();
+        // Nothing listens here, so travel times fall back to estimates
+        let routing = RoutingClient::new(RoutingConfig {
+            osrm_url: "http://127.0.0.1:1".to_string(),
+        })
+        .unwrap();
+        let engine = GeofenceEngine::new(fences, routing, None);
+        let webhook = vec![RuleAction::Webhook { url: hook }];
+        for (id, on, within) in [
+            ("preheat", PresenceKind::Approaching, Some(10.0)),
+            ("arrive", PresenceKind::Enter, None),
+        ] {
+            engine
+                .add_rule(GeofenceRule {
+                    id: id.to_string(),
+                    geofence_id: "home".to_string(),
+                    on,
+                    entities: vec!["person.sam".to_string()],
+                    within_minutes: within,
+                    mode: TravelMode::Driving,
+                    actions: webhook.clone(),
+                })
+                .await
+                .unwrap();
+        }
+        assert!(engine
+            .add_rule(GeofenceRule {
+                id: "bad".to_string(),
+                geofence_id: "home".to_string(),
+                on: PresenceKind::Approaching,
+                entities: Vec::new(),
+                within_minutes: None,
+                mode: TravelMode::Driving,
+                actions: Vec::new(),
+            })
+            .await
+            .is_err());
+        let mut events = engine.subscribe();
+
+        let at = |lat: f64| json!({"attributes": {"latitude": lat, "longitude": 13.40, "friendly_name": "Sam"}});
+        // 50 km out is far beyond ten minutes, and nothing is routed
+        assert!(engine
+            .handle_update("person.sam", &at(52.97))
+            .await
+            .unwrap()
+            .is_empty());
+        // About 2 km out is under five minutes at the estimated speed
+        let near = engine
+            .handle_update("person.sam", &at(52.54))
+            .await
+            .unwrap();
+        assert_eq!(near.len(), 1);
+        assert_eq!(near[0].kind, PresenceKind::Approaching);
+        assert!(near[0].estimated && near[0].eta_minutes.unwrap() < 10.0);
+        assert_eq!(near[0].message(), "Sam is 5 minutes from Home");
+
+        let arrived = engine
+            .handle_update("person.sam", &at(52.5201))
+            .await
+            .unwrap();
+        assert_eq!(arrived[0].kind, PresenceKind::Enter);
+        assert_eq!(arrived[0].rules, vec!["arrive".to_string()]);
+        assert_eq!(events.recv().await.unwrap().kind, PresenceKind::Approaching);
+        assert_eq!(events.recv().await.unwrap().kind, PresenceKind::Enter);
+
+        let kinds: Vec<Value> = received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|e| e["kind"].clone())
+            .collect();
+        assert_eq!(kinds, vec![json!("approaching"), json!("enter")]);
+        assert_eq!(engine.recent_events(10).await.len(), 2);
+    }
+}
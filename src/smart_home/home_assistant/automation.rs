@@ -0,0 +1,176 @@
+//! Automations, scenes and templates
+
+use super::{EntityState, HomeAssistantClient};
+use crate::error::{Error, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Automation as listed to clients
+#[derive(Debug, Clone, Serialize)]
+pub struct Automation {
+    pub entity_id: String,
+    pub name: String,
+    pub enabled: bool,
+    /// Automation ID from `automations.yaml`, needed to edit it in the UI
+    pub id: Option<String>,
+    pub last_triggered: Option<String>,
+    /// `single`, `restart`, `queued` or `parallel`
+    pub mode: Option<String>,
+    /// Runs in progress
+    pub current: u64,
+    pub area: Option<String>,
+}
+
+impl From<&EntityState> for Automation {
+    fn from(entity: &EntityState) -> Self {
+        let attribute = |name: &str| {
+            entity
+                .attributes
+                .get(name)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        Self {
+            entity_id: entity.entity_id.clone(),
+            name: entity.name().to_string(),
+            enabled: entity.state == "on",
+            id: attribute("id"),
+            last_triggered: attribute("last_triggered"),
+            mode: attribute("mode"),
+            current: entity.attributes["current"].as_u64().unwrap_or(0),
+            area: entity.area.clone(),
+        }
+    }
+}
+
+/// Reject entity IDs from another domain before calling its services
+fn require_domain(entity_id: &str, domain: &str) -> Result<()> {
+    match entity_id.split_once('.') {
+        Some((prefix, object)) if prefix == domain && !object.is_empty() => Ok(()),
+        _ => Err(Error::validation_with_field(
+            format!(
+                "Expected a {} entity like {}.name, got {}",
+                domain, domain, entity_id
+            ),
+            "entity_id",
+        )),
+    }
+}
+
+impl HomeAssistantClient {
+    /// Automations from the state cache, optionally limited to an area
+    pub async fn list_automations(&self, area: Option<&str>) -> Result<Vec<Automation>> {
+        let entities = self
+            .socket()
+            .await?
+            .entities(Some("automation"), area)
+            .await?;
+        Ok(entities.iter().map(Automation::from).collect())
+    }
+
+    /// Run an automation's actions, optionally skipping its conditions
+    pub async fn trigger_automation(
+        &self,
+        entity_id: &str,
+        skip_condition: bool,
+        variables: Option<Value>,
+    ) -> Result<Value> {
+        require_domain(entity_id, "automation")?;
+        let mut data = json!({ "skip_condition": skip_condition });
+        if let Some(variables) = variables {
+            data["variables"] = variables;
+        }
+        self.call_service("automation", "trigger", entity_id, Some(data))
+            .await
+    }
+
+    /// Enable or disable an automation's triggers
+    pub async fn set_automation_enabled(&self, entity_id: &str, enabled: bool) -> Result<Value> {
+        require_domain(entity_id, "automation")?;
+        let service = if enabled { "turn_on" } else { "turn_off" };
+        self.call_service("automation", service, entity_id, None)
+            .await
+    }
+
+    /// Activate a scene, fading over `transition` seconds where lights support it
+    pub async fn activate_scene(&self, entity_id: &str, transition: Option<f64>) -> Result<Value> {
+        require_domain(entity_id, "scene")?;
+        let data = transition.map(|seconds| json!({ "transition": seconds }));
+        self.call_service("scene", "turn_on", entity_id, data).await
+    }
+
+    /// Render a Jinja template on the Home Assistant server
+    pub async fn render_template(
+        &self,
+        template: &str,
+        variables: Option<Value>,
+    ) -> Result<String> {
+        let url = format!("{}/api/template", self.config.url.trim_end_matches('/'));
+        let mut body = json!({ "template": template });
+        if let Some(variables) = variables {
+            body["variables"] = variables;
+        }
+        let response = self
+            .http_client
+            .post(&url)
+            .bearer_auth(&self.config.token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Home Assistant request failed: {}", e)))?;
+        let status = response.status();
+        let text = response.text().await.map_err(|e| {
+            Error::network(format!("Failed to read Home Assistant response: {}", e))
+        })?;
+        if status == reqwest::StatusCode::BAD_REQUEST {
+            // Template errors come back as 400 with the error message
+            return Err(Error::validation_with_field(
+                format!("Template error: {}", text.trim()),
+                "template",
+            ));
+        }
+        if !status.is_success() {
+            return Err(Error::api_with_status(
+                format!("Home Assistant request failed: {}", text),
+                "home_assistant",
+                status.as_u16(),
+            ));
+        }
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_automations() {
+        let entity = EntityState {
+            entity_id: "automation.porch_lights".to_string(),
+            state: "off".to_string(),
+            attributes: json!({
+                "friendly_name": "Porch lights at sunset",
+                "id": "1700000000000",
+                "last_triggered": "2026-01-02T16:41:00+00:00",
+                "mode": "single",
+                "current": 0
+            }),
+            last_changed: None,
+            last_updated: None,
+            device_id: None,
+            platform: None,
+            area_id: Some("porch".to_string()),
+            area: Some("Porch".to_string()),
+        };
+        let automation = Automation::from(&entity);
+        assert!(!automation.enabled);
+        assert_eq!(automation.name, "Porch lights at sunset");
+        assert_eq!(automation.id.as_deref(), Some("1700000000000"));
+        assert_eq!(automation.area.as_deref(), Some("Porch"));
+
+        assert!(require_domain("automation.porch_lights", "automation").is_ok());
+        assert!(require_domain("scene.movie_night", "automation").is_err());
+        assert!(require_domain("scene.", "scene").is_err());
+    }
+}
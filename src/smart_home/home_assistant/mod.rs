@@ -7,15 +7,18 @@ use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::sync::Mutex;
 
 // Re-export sub-modules
+pub mod automation;
 pub mod entity;
 pub mod service;
+pub mod websocket;
 
+pub use automation::Automation;
 use service::{
     AlarmControlPanelService, ClimateService, HumidifierService, LightService, LockService,
 };
+pub use websocket::{EntityState, HomeAssistantSocket, Subscription};
 
 /// Home Assistant configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,16 +94,31 @@ impl From<HomeAssistantError> for Error {
     }
 }
 
+/// Ids interpolated into REST paths: `light.kitchen`, `climate`, `turn_on`
+fn validate_identifier(value: &str, field: &str) -> Result<()> {
+    if value.is_empty()
+        || !value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.')
+    {
+        return Err(Error::validation_with_field(
+            format!("Invalid Home Assistant {}: {}", field, value),
+            field,
+        ));
+    }
+    Ok(())
+}
+
 /// Home Assistant client for controlling home automation
 pub struct HomeAssistantClient {
     /// Home Assistant configuration
-    #[allow(dead_code)]
     config: Arc<HomeAssistantConfig>,
     /// Lifecycle manager for API calls
     lifecycle: Arc<LifecycleManager>,
-    /// State cache
-    #[allow(dead_code)]
-    state: Arc<Mutex<HomeAssistantState>>,
+    /// HTTP client for the REST API
+    http_client: reqwest::Client,
+    /// WebSocket connection and entity cache, opened on first use
+    socket: tokio::sync::OnceCell<HomeAssistantSocket>,
 }
 
 impl HomeAssistantClient {
@@ -112,30 +130,70 @@ impl HomeAssistantClient {
         Ok(Self {
             config: Arc::new(config),
             lifecycle,
-            state: Arc::new(Mutex::new(HomeAssistantState::default())),
+            http_client: reqwest::Client::new(),
+            socket: tokio::sync::OnceCell::new(),
         })
     }
 
-    /// Get entity state
-    pub async fn get_state(&self, entity_id: &str) -> Result<Value> {
-        let method = "homeassistant/get_state";
-        let params = json!({
-            "entity_id": entity_id
-        });
+    /// WebSocket client kept connected for state tracking and service calls
+    pub async fn socket(&self) -> Result<&HomeAssistantSocket> {
+        self.socket
+            .get_or_try_init(|| async { HomeAssistantSocket::connect(&self.config) })
+            .await
+    }
 
-        let response = self.lifecycle.call_method(method, Some(params)).await?;
+    /// Call the REST API at `/api/{path}` with the configured token
+    async fn rest(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let url = format!("{}/api/{}", self.config.url.trim_end_matches('/'), path);
+        let mut request = self
+            .http_client
+            .request(method, &url)
+            .bearer_auth(&self.config.token);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Home Assistant request failed: {}", e)))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(Error::not_found(format!("Home Assistant has no {}", path)));
+        }
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(Error::api_with_status(
+                format!("Home Assistant request failed: {}", text),
+                "home_assistant",
+                status.as_u16(),
+            ));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse Home Assistant response: {}", e)))
+    }
 
-        if let Some(state) = response.get("state") {
-            Ok(state.clone())
-        } else {
-            Err(Error::not_found(format!(
-                "State not found for entity {}",
-                entity_id
-            )))
+    /// Get entity state, from the REST API when configured for HTTP and the cache otherwise
+    pub async fn get_state(&self, entity_id: &str) -> Result<Value> {
+        if matches!(self.config.transport_type, HomeAssistantTransportType::Http) {
+            validate_identifier(entity_id, "entity_id")?;
+            return self
+                .rest(reqwest::Method::GET, &format!("states/{}", entity_id), None)
+                .await;
         }
+
+        let entity = self.socket().await?.state(entity_id).await?;
+        serde_json::to_value(entity)
+            .map_err(|e| Error::internal(format!("Failed to serialize entity state: {}", e)))
     }
 
-    /// Call a Home Assistant service
+    /// Call a Home Assistant service over REST for HTTP configs, else over the WebSocket API
     pub async fn call_service(
         &self,
         domain: &str,
@@ -143,7 +201,6 @@ impl HomeAssistantClient {
         entity_id: &str,
         data: Option<Value>,
     ) -> Result<Value> {
-        let method = "homeassistant/call_service";
         let mut service_data = json!({});
 
         if let Some(data) = data {
@@ -155,14 +212,22 @@ impl HomeAssistantClient {
             map.insert("entity_id".to_string(), json!(entity_id));
         }
 
-        let params = json!({
-            "domain": domain,
-            "service": service,
-            "service_data": service_data
-        });
+        if matches!(self.config.transport_type, HomeAssistantTransportType::Http) {
+            validate_identifier(domain, "domain")?;
+            validate_identifier(service, "service")?;
+            return self
+                .rest(
+                    reqwest::Method::POST,
+                    &format!("services/{}/{}", domain, service),
+                    Some(service_data),
+                )
+                .await;
+        }
 
-        let response = self.lifecycle.call_method(method, Some(params)).await?;
-        Ok(response)
+        self.socket()
+            .await?
+            .call_service(domain, service, Some(service_data), None)
+            .await
     }
 
     /// Turn on a device
@@ -619,6 +684,38 @@ impl HomeAssistantClient {
         humidifier_service.set_mode(entity_id, mode).await
     }
 
+    /// Get the full state object (state and attributes) of an entity
+    pub async fn get_entity(&self, entity_id: &str) -> Result<Value> {
+        let params = json!({
+            "entity_id": entity_id
+        });
+
+        self.lifecycle
+            .call_method("homeassistant/get_state", Some(params))
+            .await
+    }
+
+    /// Send a notification through a `notify` service (e.g. `mobile_app_pixel`)
+    pub async fn send_notification(
+        &self,
+        service: &str,
+        title: &str,
+        message: &str,
+    ) -> Result<Value> {
+        let params = json!({
+            "domain": "notify",
+            "service": service,
+            "service_data": {
+                "title": title,
+                "message": message
+            }
+        });
+
+        self.lifecycle
+            .call_method("homeassistant/call_service", Some(params))
+            .await
+    }
+
     /// Get the tools available for the Home Assistant client
     pub fn get_tools(&self) -> Vec<ToolDefinition> {
         vec![
@@ -723,7 +820,8 @@ impl HomeAssistantClient {
                     "required": ["entity_id"]
                 }),
                 Some(ToolAnnotation::new("device_info").with_description("Gets state of a device")),
-            ),
+            )
+            .read_only(),
         ]
     }
 }
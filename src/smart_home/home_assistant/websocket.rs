@@ -0,0 +1,779 @@
+//! Persistent connection to the Home Assistant WebSocket API
+//!
+//! The connection authenticates with a long-lived access token, loads all
+//! states together with the area, device and entity registries, and keeps
+//! the cache current from `state_changed` events. It reconnects on its own
+//! and resynchronizes after every reconnect.
+
+use super::{validate_identifier, HomeAssistantConfig};
+use crate::error::{Error, Result};
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch, RwLock};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Time allowed for one command's result
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time callers wait for the first full sync before giving up
+const SYNC_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Delay before the first reconnect, doubled up to `MAX_RECONNECT_DELAY`
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Commands queued while the connection is down
+const COMMAND_BUFFER: usize = 32;
+
+/// Events held for a subscriber before newer ones are dropped
+const SUBSCRIPTION_BUFFER: usize = 64;
+
+/// Messages sent on connect, in order, before the cache counts as synced
+const SYNC_MESSAGES: [&str; 4] = [
+    "get_states",
+    "config/area_registry/list",
+    "config/device_registry/list",
+    "config/entity_registry/list",
+];
+
+/// Cached entity state with the area it is assigned to
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityState {
+    pub entity_id: String,
+    pub state: String,
+    pub attributes: Value,
+    pub last_changed: Option<String>,
+    pub last_updated: Option<String>,
+    pub device_id: Option<String>,
+    /// Integration providing the entity, e.g. `hue` or `zwave_js`; MQTT
+    /// entities of Zigbee2MQTT devices report `zigbee2mqtt`
+    pub platform: Option<String>,
+    /// Entity's own area, else its device's
+    pub area_id: Option<String>,
+    pub area: Option<String>,
+}
+
+impl EntityState {
+    /// Domain part of the entity ID: `light` for `light.kitchen`
+    pub fn domain(&self) -> &str {
+        self.entity_id
+            .split_once('.')
+            .map_or(self.entity_id.as_str(), |(domain, _)| domain)
+    }
+
+    /// `friendly_name` attribute, falling back to the entity ID
+    pub fn name(&self) -> &str {
+        self.attributes
+            .get("friendly_name")
+            .and_then(Value::as_str)
+            .unwrap_or(&self.entity_id)
+    }
+
+    /// Whether the entity is in `area`, matched by area ID or name
+    fn in_area(&self, area: &str) -> bool {
+        self.area_id.as_deref() == Some(area)
+            || self
+                .area
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(area))
+    }
+}
+
+/// Entity states and the registry data used to place entities in areas
+#[derive(Debug, Default)]
+struct EntityCache {
+    entities: BTreeMap<String, EntityState>,
+    /// Area names by area ID
+    areas: HashMap<String, String>,
+    /// Area IDs by device ID
+    device_areas: HashMap<String, String>,
+    /// Devices Zigbee2MQTT publishes over MQTT discovery
+    zigbee2mqtt_devices: HashSet<String>,
+    /// Registry entries by entity ID
+    registry: HashMap<String, RegistryEntry>,
+}
+
+/// What the entity registry records about one entity
+#[derive(Debug, Clone, Default)]
+struct RegistryEntry {
+    device_id: Option<String>,
+    area_id: Option<String>,
+    platform: Option<String>,
+}
+
+fn string_field(value: &Value, field: &str) -> Option<String> {
+    value.get(field).and_then(Value::as_str).map(str::to_string)
+}
+
+impl EntityCache {
+    /// Replace the cache with the results of the sync messages
+    fn load(&mut self, states: &[Value], areas: &[Value], devices: &[Value], entities: &[Value]) {
+        self.areas = areas
+            .iter()
+            .filter_map(|a| Some((string_field(a, "area_id")?, string_field(a, "name")?)))
+            .collect();
+        self.device_areas = devices
+            .iter()
+            .filter_map(|d| Some((string_field(d, "id")?, string_field(d, "area_id")?)))
+            .collect();
+        // Zigbee2MQTT identifies its devices as ["mqtt", "zigbee2mqtt_0x..."]
+        self.zigbee2mqtt_devices = devices
+            .iter()
+            .filter(|d| {
+                d["identifiers"].as_array().is_some_and(|ids| {
+                    ids.iter()
+                        .flat_map(|id| id.as_array())
+                        .flatten()
+                        .any(|part| {
+                            part.as_str()
+                                .is_some_and(|part| part.starts_with("zigbee2mqtt"))
+                        })
+                })
+            })
+            .filter_map(|d| string_field(d, "id"))
+            .collect();
+        self.registry = entities
+            .iter()
+            .filter_map(|e| {
+                Some((
+                    string_field(e, "entity_id")?,
+                    RegistryEntry {
+                        device_id: string_field(e, "device_id"),
+                        area_id: string_field(e, "area_id"),
+                        platform: string_field(e, "platform"),
+                    },
+                ))
+            })
+            .collect();
+        self.entities.clear();
+        for state in states {
+            self.update(state);
+        }
+    }
+
+    /// Insert or replace an entity from a state object
+    fn update(&mut self, state: &Value) {
+        let Some(entity_id) = string_field(state, "entity_id") else {
+            return;
+        };
+        let RegistryEntry {
+            device_id,
+            area_id,
+            mut platform,
+        } = self.registry.get(&entity_id).cloned().unwrap_or_default();
+        if platform.as_deref() == Some("mqtt")
+            && device_id
+                .as_ref()
+                .is_some_and(|device| self.zigbee2mqtt_devices.contains(device))
+        {
+            platform = Some("zigbee2mqtt".to_string());
+        }
+        let area_id = area_id.or_else(|| {
+            device_id
+                .as_ref()
+                .and_then(|device| self.device_areas.get(device).cloned())
+        });
+        let entity = EntityState {
+            state: string_field(state, "state").unwrap_or_default(),
+            attributes: state
+                .get("attributes")
+                .cloned()
+                .unwrap_or_else(|| json!({})),
+            last_changed: string_field(state, "last_changed"),
+            last_updated: string_field(state, "last_updated"),
+            area: area_id.as_ref().and_then(|id| self.areas.get(id).cloned()),
+            area_id,
+            device_id,
+            platform,
+            entity_id: entity_id.clone(),
+        };
+        self.entities.insert(entity_id, entity);
+    }
+
+    /// Apply a `state_changed` event; a null new state removes the entity
+    fn apply_event(&mut self, event: &Value) {
+        let data = &event["data"];
+        match data.get("new_state") {
+            Some(state) if state.is_object() => self.update(state),
+            _ => {
+                if let Some(entity_id) = data.get("entity_id").and_then(Value::as_str) {
+                    self.entities.remove(entity_id);
+                }
+            }
+        }
+    }
+}
+
+/// Message to send, and where its result and any events go
+struct Command {
+    message: Map<String, Value>,
+    reply: oneshot::Sender<Result<Value>>,
+    events: Option<mpsc::Sender<Value>>,
+}
+
+/// Events for one command, such as `subscribe_events` or `assist_pipeline/run`
+///
+/// Events stop when the subscription is dropped, which unsubscribes, or when
+/// the connection drops; subscriptions are not renewed on reconnect.
+pub struct Subscription {
+    /// Result of the command that started the subscription
+    pub result: Value,
+    events: mpsc::Receiver<Value>,
+}
+
+impl Subscription {
+    /// Next event, or `None` once the subscription has ended
+    pub async fn next(&mut self) -> Option<Value> {
+        self.events.recv().await
+    }
+}
+
+/// WebSocket URL for a Home Assistant base URL
+pub fn websocket_url(base: &str) -> Result<String> {
+    let mut url = url::Url::parse(base)
+        .map_err(|e| Error::config(format!("Invalid Home Assistant URL {}: {}", base, e)))?;
+    let scheme = match url.scheme() {
+        "http" | "ws" => "ws",
+        "https" | "wss" => "wss",
+        other => {
+            return Err(Error::config(format!(
+                "Unsupported Home Assistant URL scheme: {}",
+                other
+            )))
+        }
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| Error::config(format!("Invalid Home Assistant URL {}", base)))?;
+    let path = url.path().trim_end_matches('/').to_string();
+    if !path.ends_with("/api/websocket") {
+        url.set_path(&format!("{}/api/websocket", path));
+    }
+    Ok(url.to_string())
+}
+
+/// Persistent Home Assistant WebSocket client with an entity state cache
+pub struct HomeAssistantSocket {
+    commands: mpsc::Sender<Command>,
+    cache: Arc<RwLock<EntityCache>>,
+    synced: watch::Receiver<bool>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for HomeAssistantSocket {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl HomeAssistantSocket {
+    /// Start the connection in the background; call from inside a Tokio runtime
+    pub fn connect(config: &HomeAssistantConfig) -> Result<Self> {
+        let url = websocket_url(&config.url)?;
+        let (commands, receiver) = mpsc::channel(COMMAND_BUFFER);
+        let (synced_tx, synced) = watch::channel(false);
+        let cache = Arc::new(RwLock::new(EntityCache::default()));
+        let task = tokio::spawn(run(
+            url,
+            config.token.clone(),
+            Arc::clone(&cache),
+            receiver,
+            synced_tx,
+        ));
+        Ok(Self {
+            commands,
+            cache,
+            synced,
+            task,
+        })
+    }
+
+    /// Whether the cache reflects a live, fully synced connection
+    pub fn is_synced(&self) -> bool {
+        *self.synced.borrow()
+    }
+
+    /// Wait until the connection has authenticated and loaded all states
+    pub async fn wait_synced(&self) -> Result<()> {
+        let mut synced = self.synced.clone();
+        tokio::time::timeout(SYNC_TIMEOUT, synced.wait_for(|synced| *synced))
+            .await
+            .map_err(|_| {
+                Error::timeout_with_duration(
+                    "Home Assistant did not finish syncing entity states",
+                    SYNC_TIMEOUT,
+                )
+            })?
+            .map_err(|_| Error::network("Home Assistant connection closed"))?;
+        Ok(())
+    }
+
+    /// Cached state of one entity
+    pub async fn state(&self, entity_id: &str) -> Result<EntityState> {
+        self.wait_synced().await?;
+        self.cache
+            .read()
+            .await
+            .entities
+            .get(entity_id)
+            .cloned()
+            .ok_or_else(|| {
+                Error::not_found_with_resource(
+                    format!("Home Assistant has no entity {}", entity_id),
+                    "entity",
+                    entity_id,
+                )
+            })
+    }
+
+    /// Cached entities, optionally limited to a domain and an area ID or name
+    pub async fn entities(
+        &self,
+        domain: Option<&str>,
+        area: Option<&str>,
+    ) -> Result<Vec<EntityState>> {
+        self.wait_synced().await?;
+        let cache = self.cache.read().await;
+        Ok(cache
+            .entities
+            .values()
+            .filter(|e| domain.is_none_or(|domain| e.domain() == domain))
+            .filter(|e| area.is_none_or(|area| e.in_area(area)))
+            .cloned()
+            .collect())
+    }
+
+    /// Call any service; `target` takes `entity_id`, `device_id` or `area_id`
+    pub async fn call_service(
+        &self,
+        domain: &str,
+        service: &str,
+        service_data: Option<Value>,
+        target: Option<Value>,
+    ) -> Result<Value> {
+        validate_identifier(domain, "domain")?;
+        validate_identifier(service, "service")?;
+        let mut message = json!({
+            "type": "call_service",
+            "domain": domain,
+            "service": service,
+        });
+        if let Some(data) = service_data {
+            message["service_data"] = data;
+        }
+        if let Some(target) = target {
+            message["target"] = target;
+        }
+        self.request(message).await
+    }
+
+    /// Send a command message and return its `result`; the `id` is assigned here
+    pub async fn request(&self, message: Value) -> Result<Value> {
+        self.send_command(message, None).await
+    }
+
+    /// Send a command that answers with events after its result
+    pub async fn subscribe(&self, message: Value) -> Result<Subscription> {
+        let (sender, events) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let result = self.send_command(message, Some(sender)).await?;
+        Ok(Subscription { result, events })
+    }
+
+    async fn send_command(
+        &self,
+        message: Value,
+        events: Option<mpsc::Sender<Value>>,
+    ) -> Result<Value> {
+        let Value::Object(message) = message else {
+            return Err(Error::validation(
+                "Home Assistant commands must be JSON objects",
+            ));
+        };
+        let (reply, result) = oneshot::channel();
+        self.commands
+            .send(Command {
+                message,
+                reply,
+                events,
+            })
+            .await
+            .map_err(|_| Error::network("Home Assistant connection closed"))?;
+        tokio::time::timeout(REQUEST_TIMEOUT, result)
+            .await
+            .map_err(|_| {
+                Error::timeout_with_duration("Home Assistant did not answer", REQUEST_TIMEOUT)
+            })?
+            .map_err(|_| Error::network("Home Assistant connection lost before answering"))?
+    }
+}
+
+/// Reconnect loop; ends when the client is dropped
+async fn run(
+    url: String,
+    token: String,
+    cache: Arc<RwLock<EntityCache>>,
+    mut commands: mpsc::Receiver<Command>,
+    synced: watch::Sender<bool>,
+) {
+    let mut delay = RECONNECT_DELAY;
+    loop {
+        match session(&url, &token, &cache, &mut commands, &synced).await {
+            Ok(()) => return,
+            Err(e) => tracing::warn!("Home Assistant WebSocket disconnected: {}", e),
+        }
+        synced.send_replace(false);
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+/// Result payload of a `result` message, or its error
+fn result_of(message: &Value) -> Result<Value> {
+    if message.get("success").and_then(Value::as_bool) == Some(true) {
+        return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+    }
+    let error = &message["error"];
+    Err(Error::service(format!(
+        "Home Assistant error {}: {}",
+        error
+            .get("code")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown"),
+        error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("no message")
+    )))
+}
+
+type Socket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Next JSON text message, skipping pings and other frames
+async fn read_json(socket: &mut Socket) -> Result<Value> {
+    loop {
+        match socket.next().await {
+            Some(Ok(Message::Text(text))) => {
+                return serde_json::from_str(&text)
+                    .map_err(|e| Error::parsing(format!("Invalid Home Assistant message: {}", e)))
+            }
+            Some(Ok(Message::Close(_))) | None => {
+                return Err(Error::network("Home Assistant closed the connection"))
+            }
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(Error::network(e.to_string())),
+        }
+    }
+}
+
+async fn send_json(socket: &mut Socket, message: &Value) -> Result<()> {
+    socket
+        .send(Message::Text(message.to_string()))
+        .await
+        .map_err(|e| Error::network(format!("Home Assistant send failed: {}", e)))
+}
+
+/// One connection: authenticate, sync, then serve commands and events until it drops
+async fn session(
+    url: &str,
+    token: &str,
+    cache: &RwLock<EntityCache>,
+    commands: &mut mpsc::Receiver<Command>,
+    synced: &watch::Sender<bool>,
+) -> Result<()> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .map_err(|e| Error::network(format!("Home Assistant connection failed: {}", e)))?;
+
+    let hello = read_json(&mut socket).await?;
+    if hello["type"] != "auth_required" {
+        return Err(Error::protocol(format!(
+            "Expected auth_required from Home Assistant, got {}",
+            hello["type"]
+        )));
+    }
+    let auth = json!({"type": "auth", "access_token": token});
+    send_json(&mut socket, &auth).await?;
+    let reply = read_json(&mut socket).await?;
+    if reply["type"] != "auth_ok" {
+        return Err(Error::auth(format!(
+            "Home Assistant rejected the access token: {}",
+            reply
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("auth_invalid")
+        )));
+    }
+
+    // Subscribe before loading states so no change falls between the two
+    let mut next_id: u64 = 1;
+    let subscription = next_id;
+    let mut outgoing = vec![json!({
+        "id": subscription,
+        "type": "subscribe_events",
+        "event_type": "state_changed",
+    })];
+    let sync_ids: Vec<u64> = SYNC_MESSAGES
+        .iter()
+        .map(|kind| {
+            next_id += 1;
+            outgoing.push(json!({"id": next_id, "type": kind}));
+            next_id
+        })
+        .collect();
+    for message in &outgoing {
+        send_json(&mut socket, message).await?;
+    }
+
+    let mut sync_results: Vec<Option<Vec<Value>>> = vec![None; SYNC_MESSAGES.len()];
+    let mut early_events = Vec::new();
+    let mut pending: HashMap<u64, oneshot::Sender<Result<Value>>> = HashMap::new();
+    let mut subscriptions: HashMap<u64, mpsc::Sender<Value>> = HashMap::new();
+    loop {
+        tokio::select! {
+            message = read_json(&mut socket) => {
+                let message = message?;
+                let id = message.get("id").and_then(Value::as_u64);
+                match message["type"].as_str() {
+                    Some("event") if id == Some(subscription) => {
+                        if *synced.borrow() {
+                            cache.write().await.apply_event(&message["event"]);
+                        } else if sync_results[0].is_some() {
+                            // Newer than the states snapshot; replayed once the cache loads
+                            early_events.push(message["event"].clone());
+                        }
+                    }
+                    Some("event") => {
+                        let Some(id) = id else { continue };
+                        let Some(subscriber) = subscriptions.get(&id) else { continue };
+                        match subscriber.try_send(message["event"].clone()) {
+                            Ok(()) => {}
+                            Err(mpsc::error::TrySendError::Full(_)) => {
+                                tracing::warn!("Home Assistant subscription {} is not keeping up, dropping an event", id);
+                            }
+                            Err(mpsc::error::TrySendError::Closed(_)) => {
+                                subscriptions.remove(&id);
+                                next_id += 1;
+                                let unsubscribe = json!({"id": next_id, "type": "unsubscribe_events", "subscription": id});
+                                send_json(&mut socket, &unsubscribe).await?;
+                            }
+                        }
+                    }
+                    Some("result") if id == Some(subscription) => {
+                        result_of(&message)?;
+                    }
+                    Some("result") => {
+                        let Some(id) = id else { continue };
+                        if let Some(slot) = sync_ids.iter().position(|s| *s == id) {
+                            let result = match result_of(&message) {
+                                Ok(Value::Array(items)) => items,
+                                Ok(_) => Vec::new(),
+                                // Registries need an admin token; areas are then unknown
+                                Err(e) if slot > 0 => {
+                                    tracing::info!("Home Assistant {} unavailable: {}", SYNC_MESSAGES[slot], e);
+                                    Vec::new()
+                                }
+                                Err(e) => return Err(e),
+                            };
+                            sync_results[slot] = Some(result);
+                            if let [Some(states), Some(areas), Some(devices), Some(entities)] =
+                                sync_results.as_slice()
+                            {
+                                let mut cache = cache.write().await;
+                                cache.load(states, areas, devices, entities);
+                                for event in early_events.drain(..) {
+                                    cache.apply_event(&event);
+                                }
+                                synced.send_replace(true);
+                            }
+                        } else if let Some(reply) = pending.remove(&id) {
+                            let result = result_of(&message);
+                            if result.is_err() {
+                                subscriptions.remove(&id);
+                            }
+                            let _ = reply.send(result);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            command = commands.recv() => {
+                let Some(Command { mut message, reply, events }) = command else {
+                    let _ = socket.close(None).await;
+                    return Ok(());
+                };
+                next_id += 1;
+                message.insert("id".to_string(), json!(next_id));
+                send_json(&mut socket, &Value::Object(message)).await?;
+                pending.insert(next_id, reply);
+                if let Some(events) = events {
+                    subscriptions.insert(next_id, events);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smart_home::home_assistant::HomeAssistantTransportType;
+    use axum::extract::ws::{Message as WsMessage, WebSocket, WebSocketUpgrade};
+    use axum::routing::get;
+    use axum::Router;
+
+    #[test]
+    fn builds_websocket_urls() {
+        assert_eq!(
+            websocket_url("http://homeassistant.local:8123").unwrap(),
+            "ws://homeassistant.local:8123/api/websocket"
+        );
+        assert_eq!(
+            websocket_url("https://ha.example.com/").unwrap(),
+            "wss://ha.example.com/api/websocket"
+        );
+        assert_eq!(
+            websocket_url("ws://ha:8123/api/websocket").unwrap(),
+            "ws://ha:8123/api/websocket"
+        );
+        assert!(websocket_url("ftp://ha").is_err());
+    }
+
+    async fn send(socket: &mut WebSocket, message: Value) {
+        socket
+            .send(WsMessage::Text(message.to_string()))
+            .await
+            .unwrap();
+    }
+
+    /// Minimal Home Assistant: two entities in the kitchen, `light.turn_off` works
+    async fn fake_home_assistant(mut socket: WebSocket) {
+        send(&mut socket, json!({"type": "auth_required"})).await;
+        let mut subscription = 0;
+        while let Some(Ok(WsMessage::Text(text))) = socket.recv().await {
+            let message: Value = serde_json::from_str(&text).unwrap();
+            let id = message["id"].clone();
+            let result = match message["type"].as_str().unwrap() {
+                "auth" => {
+                    let kind = if message["access_token"] == "secret" {
+                        "auth_ok"
+                    } else {
+                        "auth_invalid"
+                    };
+                    send(&mut socket, json!({"type": kind})).await;
+                    continue;
+                }
+                "subscribe_events" => {
+                    subscription = id.as_u64().unwrap();
+                    Value::Null
+                }
+                "get_states" => json!([
+                    {"entity_id": "light.kitchen", "state": "on", "attributes": {"friendly_name": "Kitchen light"}},
+                    {"entity_id": "sensor.fridge_temperature", "state": "4.2", "attributes": {"unit_of_measurement": "°C"}},
+                    {"entity_id": "switch.porch", "state": "off", "attributes": {}}
+                ]),
+                "config/area_registry/list" => json!([{"area_id": "kitchen", "name": "Kitchen"}]),
+                "config/device_registry/list" => json!([{
+                    "id": "fridge",
+                    "area_id": "kitchen",
+                    "identifiers": [["mqtt", "zigbee2mqtt_0x00158d0001a2b3c4"]]
+                }]),
+                "config/entity_registry/list" => json!([
+                    {"entity_id": "light.kitchen", "device_id": null, "area_id": "kitchen", "platform": "hue"},
+                    {"entity_id": "sensor.fridge_temperature", "device_id": "fridge", "area_id": null, "platform": "mqtt"}
+                ]),
+                "call_service" if message["domain"] == "light" => {
+                    send(&mut socket, json!({"id": id, "type": "result", "success": true, "result": {"context": {"id": "1"}}})).await;
+                    send(&mut socket, json!({
+                        "id": subscription,
+                        "type": "event",
+                        "event": {"event_type": "state_changed", "data": {
+                            "entity_id": "light.kitchen",
+                            "new_state": {"entity_id": "light.kitchen", "state": "off", "attributes": {}}
+                        }}
+                    }))
+                    .await;
+                    continue;
+                }
+                _ => {
+                    send(&mut socket, json!({"id": id, "type": "result", "success": false, "error": {"code": "not_found", "message": "Service not found."}})).await;
+                    continue;
+                }
+            };
+            send(
+                &mut socket,
+                json!({"id": id, "type": "result", "success": true, "result": result}),
+            )
+            .await;
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_states_and_calls_services() {
+        let app = Router::new().route(
+            "/api/websocket",
+            get(|ws: WebSocketUpgrade| async move { ws.on_upgrade(fake_home_assistant) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = HomeAssistantSocket::connect(&HomeAssistantConfig {
+            url: format!("http://{}", addr),
+            token: "secret".to_string(),
+            transport_type: HomeAssistantTransportType::WebSocket,
+        })
+        .unwrap();
+
+        let kitchen = client.entities(None, Some("kitchen")).await.unwrap();
+        let ids: Vec<_> = kitchen.iter().map(|e| e.entity_id.as_str()).collect();
+        assert_eq!(ids, ["light.kitchen", "sensor.fridge_temperature"]);
+        assert_eq!(kitchen[1].area.as_deref(), Some("Kitchen"));
+        assert_eq!(kitchen[0].platform.as_deref(), Some("hue"));
+        assert_eq!(kitchen[1].platform.as_deref(), Some("zigbee2mqtt"));
+        assert_eq!(
+            client.entities(Some("switch"), None).await.unwrap().len(),
+            1
+        );
+        assert_eq!(
+            client.state("light.kitchen").await.unwrap().name(),
+            "Kitchen light"
+        );
+        assert!(client.state("light.attic").await.is_err());
+
+        client
+            .call_service(
+                "light",
+                "turn_off",
+                None,
+                Some(json!({"entity_id": "light.kitchen"})),
+            )
+            .await
+            .unwrap();
+        // The event is sent right after the result; give the reader a moment
+        for _ in 0..50 {
+            if client.state("light.kitchen").await.unwrap().state == "off" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(client.state("light.kitchen").await.unwrap().state, "off");
+        assert_eq!(
+            client
+                .state("light.kitchen")
+                .await
+                .unwrap()
+                .area_id
+                .as_deref(),
+            Some("kitchen")
+        );
+        assert!(client
+            .call_service("vacuum", "start", None, None)
+            .await
+            .is_err());
+    }
+}
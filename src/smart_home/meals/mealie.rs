@@ -0,0 +1,316 @@
+//! Mealie through its REST API
+//!
+//! Requests carry an API token from the user's profile. Recipes are named by
+//! slug; meal plans are the household plans of Mealie 2, whose entries have
+//! no servings of their own, so shopping lists use the recipe's yield.
+
+use super::{Ingredient, PlannedMeal, Recipe, RecipeServer, RecipeServerConfig};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use reqwest::{Client, Method};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Mealie server
+pub struct Mealie {
+    client: Client,
+    config: RecipeServerConfig,
+}
+
+/// Servings from `recipeServings`, or the number leading `recipeYield`
+fn servings(recipe: &Value) -> Option<f64> {
+    recipe["recipeServings"]
+        .as_f64()
+        .filter(|s| *s > 0.0)
+        .or_else(|| {
+            recipe["recipeYield"]
+                .as_str()?
+                .split_whitespace()
+                .next()?
+                .parse()
+                .ok()
+        })
+}
+
+impl Mealie {
+    pub fn new(config: RecipeServerConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Mealie client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let mut request = self
+            .client
+            .request(
+                method,
+                format!("{}/api/{}", self.config.url.trim_end_matches('/'), path),
+            )
+            .bearer_auth(&self.config.token)
+            .query(query);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await.map_err(|e| {
+            Error::network_with_endpoint(
+                format!("Mealie request failed: {}", e),
+                self.config.url.clone(),
+            )
+        })?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        match status.as_u16() {
+            200..=299 => serde_json::from_str(&text)
+                .map_err(|e| Error::parsing(format!("Invalid Mealie response: {}", e))),
+            401 | 403 => Err(Error::auth(
+                "Mealie refused the token; create one under Profile > API Tokens",
+            )),
+            404 => Err(Error::not_found_with_resource(
+                format!("Mealie has no {}", path),
+                "mealie",
+                path,
+            )),
+            code => Err(Error::api_with_status(
+                format!("Mealie {} failed: {}", path, text.trim()),
+                "mealie",
+                code,
+            )),
+        }
+    }
+
+    fn recipe_from(recipe: &Value) -> Recipe {
+        let text = |key: &str| {
+            recipe[key]
+                .as_str()
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        Recipe {
+            id: text("slug").unwrap_or_default(),
+            name: text("name").unwrap_or_default(),
+            description: text("description"),
+            servings: servings(recipe),
+            minutes: None,
+        }
+    }
+
+    fn meal_from(entry: &Value) -> Option<PlannedMeal> {
+        let recipe = entry.get("recipe").filter(|r| r.is_object());
+        let title = entry["title"]
+            .as_str()
+            .filter(|t| !t.is_empty())
+            .or_else(|| recipe.and_then(|r| r["name"].as_str()))
+            .unwrap_or_default();
+        Some(PlannedMeal {
+            date: NaiveDate::parse_from_str(entry["date"].as_str()?, "%Y-%m-%d").ok()?,
+            meal: entry["entryType"].as_str().unwrap_or("dinner").to_string(),
+            title: title.to_string(),
+            recipe_id: recipe.map(|r| Self::recipe_from(r).id),
+            servings: None,
+        })
+    }
+}
+
+#[async_trait]
+impl RecipeServer for Mealie {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<Recipe>> {
+        let found = self
+            .request(
+                Method::GET,
+                "recipes",
+                &[
+                    ("search", query.to_string()),
+                    ("perPage", limit.to_string()),
+                ],
+                None,
+            )
+            .await?;
+        Ok(found["items"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(Self::recipe_from)
+            .collect())
+    }
+
+    async fn recipe(&self, id: &str) -> Result<(Recipe, Vec<Ingredient>)> {
+        let recipe = self
+            .request(Method::GET, &format!("recipes/{}", id), &[], None)
+            .await?;
+        let ingredients = recipe["recipeIngredient"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|i| match i["food"]["name"].as_str() {
+                Some(food) => Some(Ingredient {
+                    name: food.to_string(),
+                    amount: i["quantity"].as_f64().filter(|q| *q > 0.0),
+                    unit: i["unit"]["name"].as_str().map(str::to_string),
+                }),
+                // Ingredients Mealie has not parsed are only text
+                None => i["note"]
+                    .as_str()
+                    .or_else(|| i["display"].as_str())
+                    .filter(|n| !n.trim().is_empty())
+                    .map(|note| Ingredient {
+                        name: note.trim().to_string(),
+                        amount: None,
+                        unit: None,
+                    }),
+            })
+            .collect();
+        Ok((Self::recipe_from(&recipe), ingredients))
+    }
+
+    async fn meal_plan(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<PlannedMeal>> {
+        let plan = self
+            .request(
+                Method::GET,
+                "households/mealplans",
+                &[
+                    ("start_date", start.to_string()),
+                    ("end_date", end.to_string()),
+                    ("perPage", "-1".to_string()),
+                ],
+                None,
+            )
+            .await?;
+        let mut meals: Vec<PlannedMeal> = plan["items"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Self::meal_from)
+            .collect();
+        meals.sort_by_key(|m| m.date);
+        Ok(meals)
+    }
+
+    async fn plan(&self, date: NaiveDate, meal: &str, recipe_id: &str) -> Result<PlannedMeal> {
+        let recipe = self
+            .request(Method::GET, &format!("recipes/{}", recipe_id), &[], None)
+            .await?;
+        let entry = self
+            .request(
+                Method::POST,
+                "households/mealplans",
+                &[],
+                Some(json!({
+                    "date": date.to_string(),
+                    "entryType": meal.to_lowercase(),
+                    "title": "",
+                    "text": "",
+                    "recipeId": recipe["id"],
+                })),
+            )
+            .await?;
+        let mut planned = Self::meal_from(&entry)
+            .ok_or_else(|| Error::parsing("Mealie did not return the new meal plan entry"))?;
+        // The created entry names the recipe by id only
+        planned.recipe_id = Some(recipe_id.to_string());
+        if planned.title.is_empty() {
+            planned.title = Self::recipe_from(&recipe).name;
+        }
+        Ok(planned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collaboration::notify::{ChannelKind, NotificationChannel, Notifier};
+    use crate::smart_home::meals::{MealPlanner, RecipeServerKind};
+    use axum::extract::{Path, Query, State};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    type Log = Arc<std::sync::Mutex<Vec<Value>>>;
+
+    #[tokio::test]
+    async fn merges_planned_ingredients_into_a_posted_shopping_list() {
+        let posted: Log = Arc::default();
+        let app = Router::new()
+            .route(
+                "/api/households/mealplans",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    assert_eq!(query["start_date"], "2024-03-04");
+                    assert_eq!(query["end_date"], "2024-03-06");
+                    Json(json!({"items": [
+                        {"date": "2024-03-05", "entryType": "dinner", "title": "", "recipe": {"slug": "pancakes", "name": "Pancakes"}},
+                        {"date": "2024-03-04", "entryType": "breakfast", "title": "", "recipe": {"slug": "waffles", "name": "Waffles"}},
+                        {"date": "2024-03-06", "entryType": "dinner", "title": "Eat out", "recipe": null}
+                    ]}))
+                }),
+            )
+            .route(
+                "/api/recipes/:slug",
+                get(|Path(slug): Path<String>| async move {
+                    let flour = if slug == "pancakes" { 250 } else { 300 };
+                    Json(json!({
+                        "slug": slug, "name": slug, "recipeYield": "4 servings",
+                        "recipeIngredient": [
+                            {"quantity": flour, "unit": {"name": "g"}, "food": {"name": "Flour"}},
+                            {"quantity": 2, "unit": null, "food": {"name": "eggs"}},
+                            {"quantity": 1, "unit": null, "food": null, "note": "pinch of salt"}
+                        ]
+                    }))
+                }),
+            )
+            .route(
+                "/hook",
+                post(|State(posted): State<Log>, Json(body): Json<Value>| async move {
+                    posted.lock().unwrap().push(body);
+                }),
+            )
+            .with_state(Arc::clone(&posted));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let notifier = Notifier::new(vec![NotificationChannel {
+            name: "kitchen".to_string(),
+            kind: ChannelKind::Slack,
+            url: format!("http://{}/hook", address),
+        }])
+        .unwrap();
+        let planner = MealPlanner::new(RecipeServerConfig {
+            kind: RecipeServerKind::Mealie,
+            url: format!("http://{}/", address),
+            token: "token".to_string(),
+        })
+        .unwrap()
+        .with_notifier(Arc::new(notifier));
+
+        let result = planner
+            .execute_tool(
+                "shopping_list",
+                json!({"start": "2024-03-04", "days": 3, "channel": "kitchen"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result["content"][0]["text"],
+            "Shopping list for 2 meals from Mon 04 Mar\n4 eggs\n550 g Flour\npinch of salt\nPosted to kitchen"
+        );
+        assert_eq!(
+            result["structuredContent"]["items"][1]["recipes"],
+            json!(["waffles", "pancakes"])
+        );
+        let posted = posted.lock().unwrap();
+        assert_eq!(
+            posted[0]["attachments"][0]["text"],
+            "• 4 eggs\n• 550 g Flour\n• pinch of salt"
+        );
+    }
+}
@@ -0,0 +1,413 @@
+//! Recipes and meal plans from Tandoor or Mealie
+//!
+//! Recipes are searched and added to the server's meal plan. The shopping
+//! list for a stretch of the plan merges the ingredients of every planned
+//! recipe, scaled to the planned servings, and can be added to a Home
+//! Assistant to-do list or posted to a notification channel.
+
+pub mod mealie;
+pub mod tandoor;
+
+pub use mealie::Mealie;
+pub use tandoor::Tandoor;
+
+use crate::collaboration::notify::{Notification, Notifier, Severity};
+use crate::error::{Error, Result};
+use crate::smart_home::home_assistant::HomeAssistantClient;
+use crate::tools::{call_result, ToolDefinition};
+use async_trait::async_trait;
+use chrono::{Days, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Days a plan or shopping list covers by default
+const DEFAULT_DAYS: u64 = 7;
+
+/// Recipes returned by a search
+const SEARCH_LIMIT: usize = 10;
+
+/// Recipe manager software
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecipeServerKind {
+    Tandoor,
+    Mealie,
+}
+
+/// Tandoor or Mealie server, under `smart_home.recipes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipeServerConfig {
+    #[serde(rename = "type")]
+    pub kind: RecipeServerKind,
+    /// Base URL, e.g. `http://192.168.1.5:8080`
+    pub url: String,
+    /// API token
+    pub token: String,
+}
+
+impl RecipeServerConfig {
+    /// Server from `TANDOOR_URL`/`TANDOOR_TOKEN` or `MEALIE_URL`/`MEALIE_TOKEN`
+    pub fn from_env() -> Option<Self> {
+        let var = |name| std::env::var(name).ok();
+        if let (Some(url), Some(token)) = (var("TANDOOR_URL"), var("TANDOOR_TOKEN")) {
+            return Some(Self {
+                kind: RecipeServerKind::Tandoor,
+                url,
+                token,
+            });
+        }
+        Some(Self {
+            kind: RecipeServerKind::Mealie,
+            url: var("MEALIE_URL")?,
+            token: var("MEALIE_TOKEN")?,
+        })
+    }
+}
+
+/// A recipe, as found by a search
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Recipe {
+    /// Id to plan it with; the slug on Mealie
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servings: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minutes: Option<u64>,
+}
+
+impl Recipe {
+    pub fn summary(&self) -> String {
+        let mut text = self.name.clone();
+        if let Some(minutes) = self.minutes {
+            text.push_str(&format!(", {} min", minutes));
+        }
+        if let Some(servings) = self.servings {
+            text.push_str(&format!(", serves {}", servings));
+        }
+        format!("{} [id {}]", text, self.id)
+    }
+}
+
+/// One line of a recipe's ingredients
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Ingredient {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+/// An entry of the meal plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedMeal {
+    pub date: NaiveDate,
+    /// Meal type, e.g. `dinner`
+    pub meal: String,
+    pub title: String,
+    /// Recipe id; unset for notes without a recipe
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipe_id: Option<String>,
+    /// Servings planned, when the server records them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servings: Option<f64>,
+}
+
+/// A line of the shopping list
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShoppingItem {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Recipes needing it
+    pub recipes: Vec<String>,
+}
+
+impl ShoppingItem {
+    /// Line as written on the list, e.g. `500 g flour`
+    pub fn text(&self) -> String {
+        let amount = self
+            .amount
+            .map(|a| ((a * 100.0).round() / 100.0).to_string());
+        [amount.as_deref(), self.unit.as_deref(), Some(&self.name)]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// A Tandoor or Mealie server
+#[async_trait]
+pub trait RecipeServer: Send + Sync {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<Recipe>>;
+
+    /// The recipe and its ingredients
+    async fn recipe(&self, id: &str) -> Result<(Recipe, Vec<Ingredient>)>;
+
+    /// Planned meals from `start` through `end`
+    async fn meal_plan(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<PlannedMeal>>;
+
+    /// Plan a recipe for a meal on `date`
+    async fn plan(&self, date: NaiveDate, meal: &str, recipe_id: &str) -> Result<PlannedMeal>;
+}
+
+/// Ingredients of `meals`, merged by name and unit
+///
+/// Amounts are scaled from the recipe's servings to the planned ones where
+/// both are known.
+pub fn shopping_items(meals: &[(PlannedMeal, Recipe, Vec<Ingredient>)]) -> Vec<ShoppingItem> {
+    let mut items: BTreeMap<(String, String), ShoppingItem> = BTreeMap::new();
+    for (meal, recipe, ingredients) in meals {
+        let scale = match (meal.servings, recipe.servings) {
+            (Some(planned), Some(makes)) if makes > 0.0 => planned / makes,
+            _ => 1.0,
+        };
+        for ingredient in ingredients {
+            let key = (
+                ingredient.name.to_lowercase(),
+                ingredient.unit.clone().unwrap_or_default().to_lowercase(),
+            );
+            let item = items.entry(key).or_insert_with(|| ShoppingItem {
+                name: ingredient.name.clone(),
+                amount: None,
+                unit: ingredient.unit.clone(),
+                recipes: Vec::new(),
+            });
+            if let Some(amount) = ingredient.amount {
+                item.amount = Some(item.amount.unwrap_or_default() + amount * scale);
+            }
+            if !item.recipes.contains(&recipe.name) {
+                item.recipes.push(recipe.name.clone());
+            }
+        }
+    }
+    items.into_values().collect()
+}
+
+/// `YYYY-MM-DD` date, or today when unset
+fn parse_date(value: Option<&str>, field: &str) -> Result<NaiveDate> {
+    match value {
+        None => Ok(Utc::now().date_naive()),
+        Some(value) => NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+            Error::validation_with_field(format!("{} must be a date like 2024-03-15", field), field)
+        }),
+    }
+}
+
+/// Recipe server and the places shopping lists can be sent
+pub struct MealPlanner {
+    server: Box<dyn RecipeServer>,
+    home_assistant: Option<Arc<HomeAssistantClient>>,
+    notifier: Option<Arc<Notifier>>,
+}
+
+impl MealPlanner {
+    pub fn new(config: RecipeServerConfig) -> Result<Self> {
+        let server: Box<dyn RecipeServer> = match config.kind {
+            RecipeServerKind::Tandoor => Box::new(Tandoor::new(config)?),
+            RecipeServerKind::Mealie => Box::new(Mealie::new(config)?),
+        };
+        Ok(Self {
+            server,
+            home_assistant: None,
+            notifier: None,
+        })
+    }
+
+    /// Add shopping lists to Home Assistant to-do lists
+    pub fn with_home_assistant(mut self, home_assistant: Arc<HomeAssistantClient>) -> Self {
+        self.home_assistant = Some(home_assistant);
+        self
+    }
+
+    /// Post shopping lists to notification channels
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Planned meals from `start` for `days` days, with their recipes
+    async fn planned_recipes(
+        &self,
+        start: NaiveDate,
+        days: u64,
+    ) -> Result<Vec<(PlannedMeal, Recipe, Vec<Ingredient>)>> {
+        let end = start + Days::new(days.saturating_sub(1));
+        let mut meals = Vec::new();
+        for meal in self.server.meal_plan(start, end).await? {
+            if let Some(id) = meal.recipe_id.clone() {
+                let (recipe, ingredients) = self.server.recipe(&id).await?;
+                meals.push((meal, recipe, ingredients));
+            }
+        }
+        Ok(meals)
+    }
+
+    /// Get tool definitions for meal planning
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let range = |what: &str| {
+            json!({
+                "start": {"type": "string", "description": format!("First day of the {} (YYYY-MM-DD); today when omitted", what)},
+                "days": {"type": "integer", "minimum": 1, "maximum": 31, "default": DEFAULT_DAYS}
+            })
+        };
+        let mut plan = range("plan");
+        plan["query"] =
+            json!({"type": "string", "description": "Search recipes by name or ingredient"});
+        plan["add"] = json!({
+            "type": "array",
+            "description": "Recipes to put on the plan",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "date": {"type": "string", "description": "Day (YYYY-MM-DD)"},
+                    "recipe_id": {"type": "string", "description": "Recipe id from a search"},
+                    "meal": {"type": "string", "description": "Meal type, e.g. breakfast, lunch or dinner", "default": "dinner"}
+                },
+                "required": ["date", "recipe_id"]
+            }
+        });
+        let mut list = range("list");
+        list["todo_entity"] = json!({"type": "string", "description": "Home Assistant to-do list to add the items to, e.g. todo.shopping_list"});
+        list["channel"] =
+            json!({"type": "string", "description": "Notification channel to post the list to"});
+        vec![
+            ToolDefinition::from_json_schema(
+                "plan_meals",
+                "Search Tandoor or Mealie recipes, show the meal plan for the coming days, and add recipes to it",
+                "smart_home",
+                json!({"type": "object", "properties": plan}),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "shopping_list",
+                "Build the shopping list for the planned meals, merging ingredients across recipes, and optionally add it to a Home Assistant to-do list or post it to a channel",
+                "smart_home",
+                json!({"type": "object", "properties": list}),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a meal planning tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let field = |key: &str| {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.trim().is_empty())
+        };
+        let start = parse_date(field("start"), "start")?;
+        let days = parameters
+            .get("days")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_DAYS, |d| d.clamp(1, 31));
+        match name {
+            "plan_meals" => {
+                let mut text = Vec::new();
+                let mut recipes = Vec::new();
+                if let Some(query) = field("query") {
+                    recipes = self.server.search(query, SEARCH_LIMIT).await?;
+                    text.push(format!("{} recipes for \"{}\"", recipes.len(), query));
+                    text.extend(recipes.iter().map(|r| format!("  {}", r.summary())));
+                }
+                let mut added = Vec::new();
+                for entry in parameters
+                    .get("add")
+                    .and_then(|v| v.as_array())
+                    .into_iter()
+                    .flatten()
+                {
+                    let text_at = |key: &str| entry.get(key).and_then(|v| v.as_str());
+                    let date = parse_date(Some(text_at("date").unwrap_or_default()), "date")?;
+                    let recipe_id = text_at("recipe_id").ok_or_else(|| {
+                        Error::validation_with_field("recipe_id is required", "recipe_id")
+                    })?;
+                    let meal = text_at("meal").unwrap_or("dinner");
+                    added.push(self.server.plan(date, meal, recipe_id).await?);
+                }
+                if !added.is_empty() {
+                    text.push(format!("Added {} meals to the plan", added.len()));
+                }
+                let end = start + Days::new(days - 1);
+                let plan = self.server.meal_plan(start, end).await?;
+                text.push(format!("Plan {} to {}: {} meals", start, end, plan.len()));
+                text.extend(
+                    plan.iter().map(|m| {
+                        format!("  {} {}: {}", m.date.format("%a %d %b"), m.meal, m.title)
+                    }),
+                );
+                Ok(call_result(
+                    text.join("\n"),
+                    json!({ "recipes": recipes, "added": added, "plan": plan }),
+                ))
+            }
+            "shopping_list" => {
+                let meals = self.planned_recipes(start, days).await?;
+                let items = shopping_items(&meals);
+                let lines: Vec<String> = items.iter().map(ShoppingItem::text).collect();
+                let title = format!(
+                    "Shopping list for {} meals from {}",
+                    meals.len(),
+                    start.format("%a %d %b")
+                );
+                let mut text = format!("{}\n{}", title, lines.join("\n"));
+
+                if let Some(entity) = field("todo_entity") {
+                    let home_assistant = self.home_assistant.as_ref().ok_or_else(|| {
+                        Error::config(
+                            "Home Assistant is not configured; set smart_home.home_assistant",
+                        )
+                    })?;
+                    for line in &lines {
+                        home_assistant
+                            .call_service("todo", "add_item", entity, Some(json!({ "item": line })))
+                            .await?;
+                    }
+                    text.push_str(&format!("\nAdded {} items to {}", lines.len(), entity));
+                }
+                if let Some(channel) = field("channel") {
+                    let notifier = self.notifier.as_ref().ok_or_else(|| {
+                        Error::config(
+                            "No notification channels are configured; set collaboration.channels",
+                        )
+                    })?;
+                    let notification = Notification {
+                        title: title.clone(),
+                        text: lines
+                            .iter()
+                            .map(|l| format!("• {}", l))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        severity: Severity::Info,
+                        fields: Vec::new(),
+                    };
+                    notifier.send(channel, &notification).await?;
+                    text.push_str(&format!("\nPosted to {}", channel));
+                }
+                Ok(call_result(
+                    text,
+                    json!({
+                        "start": start,
+                        "days": days,
+                        "meals": meals.iter().map(|(meal, _, _)| meal).collect::<Vec<_>>(),
+                        "items": items
+                    }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "meals_tool",
+                name,
+            )),
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Tandoor Recipes through its REST API
+//!
+//! Requests carry an API token from the user's settings. Meal plan entries
+//! name a meal type defined in Tandoor, matched by name when planning.
+
+use super::{Ingredient, PlannedMeal, Recipe, RecipeServer, RecipeServerConfig};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use reqwest::{Client, Method};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Tandoor server
+pub struct Tandoor {
+    client: Client,
+    config: RecipeServerConfig,
+}
+
+/// Items of a list response, paginated or not
+fn results(value: &Value) -> &[Value] {
+    value
+        .get("results")
+        .unwrap_or(value)
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Date of a `from_date`, which newer versions send as a full time
+fn date(value: &Value) -> Option<NaiveDate> {
+    let text = value.as_str()?;
+    NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok()
+}
+
+impl Tandoor {
+    pub fn new(config: RecipeServerConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Tandoor client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let mut request = self
+            .client
+            .request(
+                method,
+                format!("{}/api/{}", self.config.url.trim_end_matches('/'), path),
+            )
+            .bearer_auth(&self.config.token)
+            .query(query);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await.map_err(|e| {
+            Error::network_with_endpoint(
+                format!("Tandoor request failed: {}", e),
+                self.config.url.clone(),
+            )
+        })?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        match status.as_u16() {
+            200..=299 => serde_json::from_str(&text)
+                .map_err(|e| Error::parsing(format!("Invalid Tandoor response: {}", e))),
+            401 | 403 => Err(Error::auth(
+                "Tandoor refused the token; create one under Settings > API",
+            )),
+            404 => Err(Error::not_found_with_resource(
+                format!("Tandoor has no {}", path),
+                "tandoor",
+                path,
+            )),
+            code => Err(Error::api_with_status(
+                format!("Tandoor {} failed: {}", path, text.trim()),
+                "tandoor",
+                code,
+            )),
+        }
+    }
+
+    fn recipe_from(recipe: &Value) -> Recipe {
+        let minutes = recipe["working_time"].as_u64().unwrap_or_default()
+            + recipe["waiting_time"].as_u64().unwrap_or_default();
+        Recipe {
+            id: match &recipe["id"] {
+                Value::Number(id) => id.to_string(),
+                other => other.as_str().unwrap_or_default().to_string(),
+            },
+            name: recipe["name"].as_str().unwrap_or_default().to_string(),
+            description: recipe["description"]
+                .as_str()
+                .filter(|d| !d.is_empty())
+                .map(str::to_string),
+            servings: recipe["servings"].as_f64(),
+            minutes: (minutes > 0).then_some(minutes),
+        }
+    }
+
+    fn meal_from(entry: &Value) -> Option<PlannedMeal> {
+        let recipe = entry.get("recipe").filter(|r| r.is_object());
+        let title = entry["title"]
+            .as_str()
+            .filter(|t| !t.is_empty())
+            .or_else(|| recipe.and_then(|r| r["name"].as_str()))
+            .unwrap_or_default();
+        Some(PlannedMeal {
+            date: date(&entry["from_date"])?,
+            meal: entry["meal_type"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_lowercase(),
+            title: title.to_string(),
+            recipe_id: recipe.map(|r| Self::recipe_from(r).id),
+            servings: entry["servings"].as_f64(),
+        })
+    }
+}
+
+#[async_trait]
+impl RecipeServer for Tandoor {
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<Recipe>> {
+        let found = self
+            .request(
+                Method::GET,
+                "recipe/",
+                &[
+                    ("query", query.to_string()),
+                    ("page_size", limit.to_string()),
+                ],
+                None,
+            )
+            .await?;
+        Ok(results(&found).iter().map(Self::recipe_from).collect())
+    }
+
+    async fn recipe(&self, id: &str) -> Result<(Recipe, Vec<Ingredient>)> {
+        let recipe = self
+            .request(Method::GET, &format!("recipe/{}/", id), &[], None)
+            .await?;
+        let ingredients = recipe["steps"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|step| step["ingredients"].as_array().into_iter().flatten())
+            .filter(|i| !i["is_header"].as_bool().unwrap_or(false))
+            .filter_map(|i| {
+                Some(Ingredient {
+                    name: i["food"]["name"].as_str()?.to_string(),
+                    amount: i["amount"].as_f64().filter(|a| *a > 0.0),
+                    unit: i["unit"]["name"].as_str().map(str::to_string),
+                })
+            })
+            .collect();
+        Ok((Self::recipe_from(&recipe), ingredients))
+    }
+
+    async fn meal_plan(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<PlannedMeal>> {
+        let plan = self
+            .request(
+                Method::GET,
+                "meal-plan/",
+                &[
+                    ("from_date", start.to_string()),
+                    ("to_date", end.to_string()),
+                ],
+                None,
+            )
+            .await?;
+        let mut meals: Vec<PlannedMeal> =
+            results(&plan).iter().filter_map(Self::meal_from).collect();
+        meals.sort_by_key(|m| m.date);
+        Ok(meals)
+    }
+
+    async fn plan(&self, date: NaiveDate, meal: &str, recipe_id: &str) -> Result<PlannedMeal> {
+        let types = self.request(Method::GET, "meal-type/", &[], None).await?;
+        let types = results(&types);
+        let meal_type = types
+            .iter()
+            .find(|t| {
+                t["name"]
+                    .as_str()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(meal))
+            })
+            .ok_or_else(|| {
+                let names: Vec<&str> = types.iter().filter_map(|t| t["name"].as_str()).collect();
+                Error::validation_with_field(
+                    format!(
+                        "Tandoor has no meal type {}; available: {}",
+                        meal,
+                        names.join(", ")
+                    ),
+                    "meal",
+                )
+            })?;
+        let recipe = self
+            .request(Method::GET, &format!("recipe/{}/", recipe_id), &[], None)
+            .await?;
+        let entry = self
+            .request(
+                Method::POST,
+                "meal-plan/",
+                &[],
+                Some(json!({
+                    "recipe": {"id": recipe["id"], "name": recipe["name"], "keywords": []},
+                    "meal_type": {"id": meal_type["id"], "name": meal_type["name"]},
+                    "from_date": date.to_string(),
+                    "servings": recipe["servings"].as_f64().unwrap_or(1.0),
+                    "title": "",
+                })),
+            )
+            .await?;
+        Self::meal_from(&entry)
+            .ok_or_else(|| Error::parsing("Tandoor did not return the new meal plan entry"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smart_home::meals::RecipeServerKind;
+    use axum::extract::{Path, Query, State};
+    use axum::http::HeaderMap;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    type Log = Arc<std::sync::Mutex<Vec<Value>>>;
+
+    fn pancakes() -> Value {
+        json!({
+            "id": 7, "name": "Pancakes", "servings": 4, "working_time": 10, "waiting_time": 5,
+            "steps": [{"ingredients": [
+                {"food": {"name": "Batter"}, "is_header": true},
+                {"food": {"name": "flour"}, "unit": {"name": "g"}, "amount": 250},
+                {"food": {"name": "eggs"}, "unit": null, "amount": 2}
+            ]}]
+        })
+    }
+
+    #[tokio::test]
+    async fn searches_reads_the_plan_and_plans_by_meal_type() {
+        let created: Log = Arc::default();
+        let app = Router::new()
+            .route(
+                "/api/recipe/",
+                get(|headers: HeaderMap, Query(query): Query<HashMap<String, String>>| async move {
+                    assert_eq!(headers["authorization"], "Bearer token");
+                    assert_eq!(query["query"], "pancake");
+                    Json(json!({"count": 1, "results": [pancakes()]}))
+                }),
+            )
+            .route(
+                "/api/recipe/:id/",
+                get(|Path(id): Path<String>| async move {
+                    assert_eq!(id, "7");
+                    Json(pancakes())
+                }),
+            )
+            .route(
+                "/api/meal-type/",
+                get(|| async { Json(json!([{"id": 1, "name": "Breakfast"}, {"id": 2, "name": "Dinner"}])) }),
+            )
+            .route(
+                "/api/meal-plan/",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    assert_eq!(query["from_date"], "2024-03-04");
+                    Json(json!([{
+                        "id": 1, "title": "", "servings": 2,
+                        "from_date": "2024-03-05T00:00:00+01:00",
+                        "recipe": {"id": 7, "name": "Pancakes"},
+                        "meal_type": {"id": 1, "name": "Breakfast"}
+                    }]))
+                })
+                .post(|State(created): State<Log>, Json(body): Json<Value>| async move {
+                    created.lock().unwrap().push(body.clone());
+                    Json(json!({
+                        "id": 2, "title": "", "servings": body["servings"],
+                        "from_date": body["from_date"], "recipe": body["recipe"], "meal_type": body["meal_type"]
+                    }))
+                }),
+            )
+            .with_state(Arc::clone(&created));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let tandoor = Tandoor::new(RecipeServerConfig {
+            kind: RecipeServerKind::Tandoor,
+            url: format!("http://{}", address),
+            token: "token".to_string(),
+        })
+        .unwrap();
+
+        let found = tandoor.search("pancake", 5).await.unwrap();
+        assert_eq!(found[0].summary(), "Pancakes, 15 min, serves 4 [id 7]");
+        let (_, ingredients) = tandoor.recipe("7").await.unwrap();
+        assert_eq!(ingredients.len(), 2);
+        assert_eq!(ingredients[1].unit, None);
+
+        let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        let plan = tandoor.meal_plan(day(4), day(10)).await.unwrap();
+        assert_eq!(plan[0].date, day(5));
+        assert_eq!(plan[0].meal, "breakfast");
+        assert_eq!(plan[0].title, "Pancakes");
+        assert_eq!(plan[0].recipe_id.as_deref(), Some("7"));
+
+        let planned = tandoor.plan(day(6), "dinner", "7").await.unwrap();
+        assert_eq!(planned.meal, "dinner");
+        assert_eq!(created.lock().unwrap()[0]["meal_type"]["id"], 2);
+        assert!(tandoor.plan(day(6), "brunch", "7").await.is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! Jellyfin through its REST API
+//!
+//! Requests carry an API key from the dashboard, which sees every user's
+//! libraries and sessions. Remote control goes to sessions, so a player is
+//! only reachable while its app is open and reports remote control support.
+
+use super::{
+    MediaItem, MediaServer, MediaServerConfig, MediaType, PlaybackCommand, Player, Session,
+};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Run times are in 100 ns ticks
+const TICKS_PER_SEC: u64 = 10_000_000;
+
+/// Item types returned when no kind is asked for
+const DEFAULT_TYPES: &str = "Movie,Series,Episode,MusicAlbum,Audio";
+
+/// Sessions idle longer than this are left out
+const ACTIVE_WITHIN_SECS: u64 = 960;
+
+/// Jellyfin server
+pub struct Jellyfin {
+    client: Client,
+    server: MediaServerConfig,
+    name: String,
+}
+
+fn item_types(kind: Option<MediaType>) -> &'static str {
+    match kind {
+        None => DEFAULT_TYPES,
+        Some(MediaType::Movie) => "Movie",
+        Some(MediaType::Show) => "Series",
+        Some(MediaType::Episode) => "Episode",
+        Some(MediaType::Album) => "MusicAlbum",
+        Some(MediaType::Track) => "Audio",
+    }
+}
+
+impl Jellyfin {
+    pub fn new(server: MediaServerConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Jellyfin client: {}", e)))?;
+        Ok(Self {
+            client,
+            name: server.name(),
+            server,
+        })
+    }
+
+    async fn request(&self, method: Method, path: &str, query: &[(&str, String)]) -> Result<Value> {
+        let response = self
+            .client
+            .request(
+                method,
+                format!("{}{}", self.server.url.trim_end_matches('/'), path),
+            )
+            .header(
+                "Authorization",
+                format!("MediaBrowser Token=\"{}\"", self.server.token),
+            )
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| {
+                Error::network_with_endpoint(
+                    format!("Jellyfin request failed: {}", e),
+                    self.server.url.clone(),
+                )
+            })?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        match status.as_u16() {
+            200..=299 => Ok(serde_json::from_str(&text).unwrap_or(Value::Null)),
+            401 | 403 => Err(Error::auth(format!(
+                "{} refused the API key; create one under Dashboard > API Keys",
+                self.name
+            ))),
+            code => Err(Error::api_with_status(
+                format!("Jellyfin {} failed: {}", path, text.trim()),
+                "jellyfin",
+                code,
+            )),
+        }
+    }
+
+    fn item(&self, item: &Value) -> MediaItem {
+        let text = |key: &str| item[key].as_str().map(str::to_string);
+        let kind = match item["Type"].as_str().unwrap_or_default() {
+            "Series" => "show".to_string(),
+            "MusicAlbum" => "album".to_string(),
+            "Audio" => "track".to_string(),
+            other => other.to_lowercase(),
+        };
+        MediaItem {
+            server: self.name.clone(),
+            id: text("Id").unwrap_or_default(),
+            title: text("Name").unwrap_or_default(),
+            kind,
+            parent: text("SeriesName").or_else(|| text("AlbumArtist")),
+            year: item["ProductionYear"].as_i64().map(|y| y as i32),
+            duration_secs: item["RunTimeTicks"].as_u64().map(|t| t / TICKS_PER_SEC),
+            added_at: item["DateCreated"]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&Utc)),
+        }
+    }
+
+    fn player(&self, session: &Value) -> Player {
+        Player {
+            server: self.name.clone(),
+            id: session["Id"].as_str().unwrap_or_default().to_string(),
+            name: session["DeviceName"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            product: session["Client"].as_str().map(str::to_string),
+        }
+    }
+
+    async fn items(&self, query: Vec<(&str, String)>) -> Result<Vec<MediaItem>> {
+        let mut query = query;
+        query.push(("Recursive", "true".to_string()));
+        query.push(("Fields", "DateCreated,ProductionYear".to_string()));
+        let body = self.request(Method::GET, "/Items", &query).await?;
+        Ok(body["Items"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|item| self.item(item))
+            .collect())
+    }
+
+    async fn active_sessions(&self) -> Result<Vec<Value>> {
+        let body = self
+            .request(
+                Method::GET,
+                "/Sessions",
+                &[("ActiveWithinSeconds", ACTIVE_WITHIN_SECS.to_string())],
+            )
+            .await?;
+        Ok(body.as_array().cloned().unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl MediaServer for Jellyfin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        kind: Option<MediaType>,
+        limit: usize,
+    ) -> Result<Vec<MediaItem>> {
+        self.items(vec![
+            ("searchTerm", query.to_string()),
+            ("IncludeItemTypes", item_types(kind).to_string()),
+            ("Limit", limit.to_string()),
+        ])
+        .await
+    }
+
+    async fn recently_added(
+        &self,
+        kind: Option<MediaType>,
+        limit: usize,
+    ) -> Result<Vec<MediaItem>> {
+        // Shows are reached through their new episodes
+        let types = match kind {
+            None => "Movie,Episode,MusicAlbum",
+            kind => item_types(kind),
+        };
+        self.items(vec![
+            ("SortBy", "DateCreated".to_string()),
+            ("SortOrder", "Descending".to_string()),
+            ("IncludeItemTypes", types.to_string()),
+            ("Limit", limit.to_string()),
+        ])
+        .await
+    }
+
+    async fn sessions(&self) -> Result<Vec<Session>> {
+        Ok(self
+            .active_sessions()
+            .await?
+            .iter()
+            .filter(|s| s["NowPlayingItem"].is_object())
+            .map(|s| Session {
+                server: self.name.clone(),
+                user: s["UserName"].as_str().unwrap_or_default().to_string(),
+                player: self.player(s),
+                item: self.item(&s["NowPlayingItem"]),
+                paused: s["PlayState"]["IsPaused"].as_bool().unwrap_or(false),
+                position_secs: s["PlayState"]["PositionTicks"]
+                    .as_u64()
+                    .map(|t| t / TICKS_PER_SEC),
+            })
+            .collect())
+    }
+
+    async fn players(&self) -> Result<Vec<Player>> {
+        Ok(self
+            .active_sessions()
+            .await?
+            .iter()
+            .filter(|s| s["SupportsRemoteControl"].as_bool().unwrap_or(false))
+            .map(|s| self.player(s))
+            .collect())
+    }
+
+    async fn send(&self, player: &Player, command: &PlaybackCommand) -> Result<()> {
+        let base = format!("/Sessions/{}/Playing", player.id);
+        let (path, query) = match command {
+            PlaybackCommand::Play(item) => (
+                base,
+                vec![
+                    ("playCommand", "PlayNow".to_string()),
+                    ("itemIds", item.clone()),
+                ],
+            ),
+            other => {
+                let command = match other {
+                    PlaybackCommand::Pause => "Pause",
+                    PlaybackCommand::Resume => "Unpause",
+                    PlaybackCommand::Stop => "Stop",
+                    PlaybackCommand::Next => "NextTrack",
+                    _ => "PreviousTrack",
+                };
+                (format!("{}/{}", base, command), Vec::new())
+            }
+        };
+        self.request(Method::POST, &path, &query).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smart_home::media::{find_player, MediaServerKind};
+    use axum::extract::{Query, State};
+    use axum::http::{HeaderMap, Uri};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    type Log = Arc<std::sync::Mutex<Vec<String>>>;
+
+    #[tokio::test]
+    async fn searches_lists_sessions_and_plays_on_a_named_device() {
+        let commands: Log = Arc::default();
+        let app = Router::new()
+            .route(
+                "/Items",
+                get(
+                    |headers: HeaderMap, Query(query): Query<HashMap<String, String>>| async move {
+                        assert_eq!(headers["authorization"], "MediaBrowser Token=\"key\"");
+                        assert_eq!(query["searchTerm"], "dune");
+                        assert_eq!(query["IncludeItemTypes"], "Movie");
+                        Json(json!({"Items": [
+                            {"Id": "a1", "Name": "Dune", "Type": "Movie", "ProductionYear": 2021, "RunTimeTicks": 93_600_000_000u64}
+                        ]}))
+                    },
+                ),
+            )
+            .route(
+                "/Sessions",
+                get(|| async {
+                    Json(json!([
+                        {
+                            "Id": "s1", "UserName": "sam", "DeviceName": "Living Room TV", "Client": "Jellyfin Android TV",
+                            "SupportsRemoteControl": true,
+                            "NowPlayingItem": {"Id": "e5", "Name": "Pilot", "Type": "Episode", "SeriesName": "Severance", "RunTimeTicks": 34_000_000_000u64},
+                            "PlayState": {"IsPaused": true, "PositionTicks": 17_000_000_000u64}
+                        },
+                        {"Id": "s2", "UserName": "sam", "DeviceName": "Firefox", "Client": "Jellyfin Web", "SupportsRemoteControl": false}
+                    ]))
+                }),
+            )
+            .route(
+                "/Sessions/:id/Playing",
+                post(|State(commands): State<Log>, uri: Uri| async move {
+                    commands.lock().unwrap().push(uri.to_string());
+                }),
+            )
+            .with_state(Arc::clone(&commands));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let jellyfin = Jellyfin::new(MediaServerConfig {
+            kind: MediaServerKind::Jellyfin,
+            url: format!("http://{}/", address),
+            name: Some("jellyfin".to_string()),
+            token: "key".to_string(),
+        })
+        .unwrap();
+
+        let items = jellyfin
+            .search("dune", Some(MediaType::Movie), 5)
+            .await
+            .unwrap();
+        assert_eq!(items[0].summary(), "Dune (2021) [movie, id a1]");
+        assert_eq!(items[0].duration_secs, Some(9360));
+
+        let sessions = jellyfin.sessions().await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(
+            sessions[0].summary(),
+            "sam on Living Room TV: Severance - Pilot [episode, id e5], paused, 50%"
+        );
+
+        let players = jellyfin.players().await.unwrap();
+        assert_eq!(players.len(), 1);
+        assert!(find_player(&players, "firefox").is_err());
+        let player = find_player(&players, "living room tv").unwrap();
+        jellyfin
+            .send(player, &PlaybackCommand::Play("a1".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(
+            commands.lock().unwrap().as_slice(),
+            ["/Sessions/s1/Playing?playCommand=PlayNow&itemIds=a1"]
+        );
+    }
+}
@@ -0,0 +1,461 @@
+//! Jellyfin and Plex media servers
+//!
+//! Each configured server is searched and controlled through its own HTTP
+//! API. Library items, sessions and remote-controllable players are reduced
+//! to the same shapes, so a search or "what's playing" covers every server
+//! at once. Playback commands go to one player, picked by name or id from
+//! the server that owns the item being played.
+
+pub mod jellyfin;
+pub mod plex;
+
+pub use jellyfin::Jellyfin;
+pub use plex::Plex;
+
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Items shown per server by default
+const DEFAULT_LIMIT: usize = 20;
+
+/// Media server software
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaServerKind {
+    Jellyfin,
+    Plex,
+}
+
+/// One Jellyfin or Plex server, under `smart_home.media`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaServerConfig {
+    #[serde(rename = "type")]
+    pub kind: MediaServerKind,
+    /// Base URL, e.g. `http://192.168.1.5:8096` or `http://192.168.1.5:32400`
+    pub url: String,
+    /// Name in results; the URL's host when unset
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Jellyfin API key or Plex token
+    pub token: String,
+}
+
+impl MediaServerConfig {
+    /// Servers from `JELLYFIN_URL`/`JELLYFIN_API_KEY` and `PLEX_URL`/`PLEX_TOKEN`
+    pub fn from_env() -> Vec<Self> {
+        let var = |name| std::env::var(name).ok();
+        let mut servers = Vec::new();
+        if let (Some(url), Some(token)) = (var("JELLYFIN_URL"), var("JELLYFIN_API_KEY")) {
+            servers.push(Self {
+                kind: MediaServerKind::Jellyfin,
+                url,
+                name: None,
+                token,
+            });
+        }
+        if let (Some(url), Some(token)) = (var("PLEX_URL"), var("PLEX_TOKEN")) {
+            servers.push(Self {
+                kind: MediaServerKind::Plex,
+                url,
+                name: None,
+                token,
+            });
+        }
+        servers
+    }
+
+    /// Name in results
+    pub fn name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            let host = self.url.split_once("://").map_or(&*self.url, |(_, h)| h);
+            host.trim_end_matches('/').to_string()
+        })
+    }
+}
+
+/// Library item kinds searches can be narrowed to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaType {
+    Movie,
+    Show,
+    Episode,
+    Album,
+    Track,
+}
+
+impl MediaType {
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "movie" => Self::Movie,
+            "show" => Self::Show,
+            "episode" => Self::Episode,
+            "album" => Self::Album,
+            "track" => Self::Track,
+            _ => return None,
+        })
+    }
+}
+
+/// A movie, show, episode, album or track in a library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaItem {
+    pub server: String,
+    /// Id to pass to `media_play`
+    pub id: String,
+    pub title: String,
+    /// Item kind as reported by the server, e.g. `movie` or `episode`
+    pub kind: String,
+    /// Show of an episode, or artist of an album or track
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added_at: Option<DateTime<Utc>>,
+}
+
+impl MediaItem {
+    pub fn summary(&self) -> String {
+        let mut text = match &self.parent {
+            Some(parent) => format!("{} - {}", parent, self.title),
+            None => self.title.clone(),
+        };
+        if let Some(year) = self.year {
+            text.push_str(&format!(" ({})", year));
+        }
+        format!("{} [{}, id {}]", text, self.kind, self.id)
+    }
+}
+
+/// A player the server can send commands to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Player {
+    pub server: String,
+    /// Session id on Jellyfin, client identifier on Plex
+    pub id: String,
+    pub name: String,
+    /// App or device model, e.g. `Jellyfin Android TV` or `Plex for Roku`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+}
+
+/// Something being played right now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub server: String,
+    pub user: String,
+    pub player: Player,
+    pub item: MediaItem,
+    pub paused: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_secs: Option<u64>,
+}
+
+impl Session {
+    pub fn summary(&self) -> String {
+        let progress = match (self.position_secs, self.item.duration_secs) {
+            (Some(position), Some(duration)) if duration > 0 => {
+                format!(", {}%", position * 100 / duration)
+            }
+            _ => String::new(),
+        };
+        format!(
+            "{} on {}: {}{}{}",
+            self.user,
+            self.player.name,
+            self.item.summary(),
+            if self.paused { ", paused" } else { "" },
+            progress
+        )
+    }
+}
+
+/// Command for a player
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaybackCommand {
+    /// Start playing a library item from the beginning
+    Play(String),
+    Pause,
+    Resume,
+    Stop,
+    Next,
+    Previous,
+}
+
+impl PlaybackCommand {
+    fn parse(action: &str, item: Option<&str>) -> Result<Self> {
+        Ok(match action {
+            "play" => Self::Play(
+                item.ok_or_else(|| {
+                    Error::validation_with_field("item_id is required to play", "item_id")
+                })?
+                .to_string(),
+            ),
+            "pause" => Self::Pause,
+            "resume" => Self::Resume,
+            "stop" => Self::Stop,
+            "next" => Self::Next,
+            "previous" => Self::Previous,
+            _ => {
+                return Err(Error::validation_with_field(
+                    "action must be play, pause, resume, stop, next or previous",
+                    "action",
+                ))
+            }
+        })
+    }
+}
+
+/// A Jellyfin or Plex server
+#[async_trait]
+pub trait MediaServer: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn search(
+        &self,
+        query: &str,
+        kind: Option<MediaType>,
+        limit: usize,
+    ) -> Result<Vec<MediaItem>>;
+
+    /// Newest items across the libraries
+    async fn recently_added(&self, kind: Option<MediaType>, limit: usize)
+        -> Result<Vec<MediaItem>>;
+
+    async fn sessions(&self) -> Result<Vec<Session>>;
+
+    /// Players that accept remote commands
+    async fn players(&self) -> Result<Vec<Player>>;
+
+    async fn send(&self, player: &Player, command: &PlaybackCommand) -> Result<()>;
+}
+
+/// The player named `target`, by id or case-insensitive name
+pub fn find_player<'a>(players: &'a [Player], target: &str) -> Result<&'a Player> {
+    if let Some(player) = players.iter().find(|p| p.id == target) {
+        return Ok(player);
+    }
+    let named: Vec<&Player> = players
+        .iter()
+        .filter(|p| p.name.eq_ignore_ascii_case(target))
+        .collect();
+    match named.as_slice() {
+        [only] => Ok(*only),
+        [] => Err(Error::not_found_with_resource(
+            format!(
+                "No player {} is connected; available: {}",
+                target,
+                players
+                    .iter()
+                    .map(|p| p.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            "media_player",
+            target,
+        )),
+        _ => Err(Error::validation_with_field(
+            format!("Several players are named {}; pass its id", target),
+            "device",
+        )),
+    }
+}
+
+/// Every configured media server
+pub struct MediaServers {
+    servers: Vec<Box<dyn MediaServer>>,
+}
+
+impl MediaServers {
+    pub fn new(config: Vec<MediaServerConfig>) -> Result<Self> {
+        let servers = config
+            .into_iter()
+            .map(|server| -> Result<Box<dyn MediaServer>> {
+                Ok(match server.kind {
+                    MediaServerKind::Jellyfin => Box::new(Jellyfin::new(server)?),
+                    MediaServerKind::Plex => Box::new(Plex::new(server)?),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { servers })
+    }
+
+    /// The named server, or all of them
+    fn select(&self, server: Option<&str>) -> Result<Vec<&dyn MediaServer>> {
+        match server {
+            None => Ok(self.servers.iter().map(|s| s.as_ref()).collect()),
+            Some(name) => self
+                .servers
+                .iter()
+                .find(|s| s.name() == name)
+                .map(|s| vec![s.as_ref()])
+                .ok_or_else(|| {
+                    Error::not_found_with_resource(
+                        format!("Media server {} is not configured", name),
+                        "media_server",
+                        name,
+                    )
+                }),
+        }
+    }
+
+    /// The named server, or the only one
+    fn one(&self, server: Option<&str>) -> Result<&dyn MediaServer> {
+        let selected = self.select(server)?;
+        match selected.as_slice() {
+            [only] => Ok(*only),
+            _ => Err(Error::validation_with_field(
+                "Several media servers are configured; name one",
+                "server",
+            )),
+        }
+    }
+
+    /// Get tool definitions for media servers
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "media_search",
+                "Search Jellyfin and Plex libraries for movies, shows, episodes, albums and tracks, or list what was recently added when no query is given",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string", "description": "Title to search for; recently added items when omitted"},
+                        "type": {"type": "string", "enum": ["movie", "show", "episode", "album", "track"], "description": "Only items of this kind"},
+                        "server": {"type": "string", "description": "Only this server; all configured servers when omitted"},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 100, "description": "Items per server", "default": DEFAULT_LIMIT}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "media_play",
+                "Show what is playing on Jellyfin and Plex and which players can be controlled, or play an item, pause, resume, stop or skip on a player",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "action": {"type": "string", "enum": ["play", "pause", "resume", "stop", "next", "previous"], "description": "Command to send; current sessions and players when omitted"},
+                        "device": {"type": "string", "description": "Player name or id, as listed without an action"},
+                        "item_id": {"type": "string", "description": "Item to play, from media_search"},
+                        "server": {"type": "string", "description": "Server the item and player belong to; required when several are configured"}
+                    }
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a media tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let field = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let server = field("server");
+        match name {
+            "media_search" => {
+                let kind = match field("type") {
+                    Some(kind) => Some(MediaType::parse(kind).ok_or_else(|| {
+                        Error::validation_with_field(
+                            "type must be movie, show, episode, album or track",
+                            "type",
+                        )
+                    })?),
+                    None => None,
+                };
+                let limit = parameters
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .map_or(DEFAULT_LIMIT, |l| l.clamp(1, 100) as usize);
+                let query = field("query").filter(|q| !q.trim().is_empty());
+                let mut text = String::new();
+                let mut items = Vec::new();
+                let mut errors = Vec::new();
+                for media in self.select(server)? {
+                    let found = match query {
+                        Some(query) => media.search(query, kind, limit).await,
+                        None => media.recently_added(kind, limit).await,
+                    };
+                    match found {
+                        Ok(found) => {
+                            for item in &found {
+                                text.push_str(&format!("\n{}: {}", item.server, item.summary()));
+                            }
+                            items.extend(found);
+                        }
+                        Err(e) => {
+                            text.push_str(&format!("\n{}: unavailable ({})", media.name(), e));
+                            errors.push(json!({ "server": media.name(), "error": e.to_string() }));
+                        }
+                    }
+                }
+                let heading = match query {
+                    Some(query) => format!("{} results for \"{}\"", items.len(), query),
+                    None => format!("{} recently added", items.len()),
+                };
+                Ok(call_result(
+                    format!("{}{}", heading, text),
+                    json!({ "items": items, "errors": errors }),
+                ))
+            }
+            "media_play" => {
+                let Some(action) = field("action") else {
+                    let mut text = String::new();
+                    let mut sessions = Vec::new();
+                    let mut players = Vec::new();
+                    for media in self.select(server)? {
+                        match (media.sessions().await, media.players().await) {
+                            (Ok(active), Ok(available)) => {
+                                for session in &active {
+                                    text.push_str(&format!("\n{}", session.summary()));
+                                }
+                                sessions.extend(active);
+                                players.extend(available);
+                            }
+                            (Err(e), _) | (_, Err(e)) => {
+                                text.push_str(&format!("\n{}: unavailable ({})", media.name(), e));
+                            }
+                        }
+                    }
+                    let names: Vec<&str> = players.iter().map(|p| p.name.as_str()).collect();
+                    let heading = format!(
+                        "{} playing; players: {}",
+                        sessions.len(),
+                        if names.is_empty() {
+                            "none".to_string()
+                        } else {
+                            names.join(", ")
+                        }
+                    );
+                    return Ok(call_result(
+                        format!("{}{}", heading, text),
+                        json!({ "sessions": sessions, "players": players }),
+                    ));
+                };
+                let command = PlaybackCommand::parse(action, field("item_id"))?;
+                let device = field("device")
+                    .ok_or_else(|| Error::validation_with_field("device is required", "device"))?;
+                let media = self.one(server)?;
+                let players = media.players().await?;
+                let player = find_player(&players, device)?;
+                media.send(player, &command).await?;
+                Ok(call_result(
+                    format!("{} sent to {} on {}", action, player.name, media.name()),
+                    json!({ "server": media.name(), "player": player, "action": action, "item_id": field("item_id") }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "media_tool",
+                name,
+            )),
+        }
+    }
+}
@@ -0,0 +1,459 @@
+//! Plex Media Server through its HTTP API
+//!
+//! Requests carry an `X-Plex-Token` and ask for JSON. Players are the
+//! server's advertised clients plus whatever is streaming; commands are
+//! relayed by the server with `X-Plex-Target-Client-Identifier`, and
+//! playing an item first creates a play queue for it on the server.
+
+use super::{
+    MediaItem, MediaServer, MediaServerConfig, MediaType, PlaybackCommand, Player, Session,
+};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method, Url};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Identifies this server to Plex and its players
+const CLIENT_IDENTIFIER: &str = "devops-mcp";
+
+/// Plex Media Server
+pub struct Plex {
+    client: Client,
+    server: MediaServerConfig,
+    name: String,
+    /// Players ignore repeated command ids
+    command_id: AtomicU64,
+}
+
+fn kind_name(kind: MediaType) -> &'static str {
+    match kind {
+        MediaType::Movie => "movie",
+        MediaType::Show => "show",
+        MediaType::Episode => "episode",
+        MediaType::Album => "album",
+        MediaType::Track => "track",
+    }
+}
+
+impl Plex {
+    pub fn new(server: MediaServerConfig) -> Result<Self> {
+        Url::parse(&server.url).map_err(|e| {
+            Error::validation_with_field(format!("Invalid Plex URL {}: {}", server.url, e), "url")
+        })?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Plex client: {}", e)))?;
+        Ok(Self {
+            client,
+            name: server.name(),
+            server,
+            command_id: AtomicU64::new(1),
+        })
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        target: Option<&str>,
+    ) -> Result<Value> {
+        let mut request = self
+            .client
+            .request(
+                method,
+                format!("{}{}", self.server.url.trim_end_matches('/'), path),
+            )
+            .header("X-Plex-Token", &self.server.token)
+            .header("X-Plex-Client-Identifier", CLIENT_IDENTIFIER)
+            .header("Accept", "application/json")
+            .query(query);
+        if let Some(target) = target {
+            request = request.header("X-Plex-Target-Client-Identifier", target);
+        }
+        let response = request.send().await.map_err(|e| {
+            Error::network_with_endpoint(
+                format!("Plex request failed: {}", e),
+                self.server.url.clone(),
+            )
+        })?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        match status.as_u16() {
+            200..=299 => Ok(serde_json::from_str(&text).unwrap_or(Value::Null)),
+            401 => Err(Error::auth(format!(
+                "{} refused the Plex token; use the X-Plex-Token of the server owner",
+                self.name
+            ))),
+            code => Err(Error::api_with_status(
+                format!("Plex {} failed: {}", path, text.trim()),
+                "plex",
+                code,
+            )),
+        }
+    }
+
+    fn item(&self, item: &Value) -> MediaItem {
+        let text = |key: &str| item[key].as_str().map(str::to_string);
+        MediaItem {
+            server: self.name.clone(),
+            id: text("ratingKey").unwrap_or_default(),
+            title: text("title").unwrap_or_default(),
+            kind: text("type").unwrap_or_default(),
+            // Show of an episode, artist of a track or album
+            parent: text("grandparentTitle").or_else(|| match item["type"].as_str() {
+                Some("album") => text("parentTitle"),
+                _ => None,
+            }),
+            year: item["year"].as_i64().map(|y| y as i32),
+            duration_secs: item["duration"].as_u64().map(|ms| ms / 1000),
+            added_at: item["addedAt"]
+                .as_i64()
+                .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0)),
+        }
+    }
+
+    fn items(&self, metadata: &Value, kind: Option<MediaType>) -> Vec<MediaItem> {
+        metadata
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|m| kind.is_none_or(|k| m["type"] == kind_name(k)))
+            .map(|m| self.item(m))
+            .collect()
+    }
+
+    fn next_command_id(&self) -> String {
+        self.command_id.fetch_add(1, Ordering::Relaxed).to_string()
+    }
+
+    async fn playing(&self) -> Result<Vec<Value>> {
+        let body = self
+            .request(Method::GET, "/status/sessions", &[], None)
+            .await?;
+        Ok(body["MediaContainer"]["Metadata"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn session_player(&self, session: &Value) -> Player {
+        let player = &session["Player"];
+        Player {
+            server: self.name.clone(),
+            id: player["machineIdentifier"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            name: player["title"].as_str().unwrap_or_default().to_string(),
+            product: player["product"].as_str().map(str::to_string),
+        }
+    }
+
+    /// Start `item` on `player` through a new play queue
+    async fn play(&self, player: &Player, item: &str) -> Result<()> {
+        let identity = self.request(Method::GET, "/identity", &[], None).await?;
+        let machine = identity["MediaContainer"]["machineIdentifier"]
+            .as_str()
+            .ok_or_else(|| Error::parsing("Plex identity has no machineIdentifier"))?
+            .to_string();
+        let key = format!("/library/metadata/{}", item);
+        let metadata = self.request(Method::GET, &key, &[], None).await?;
+        let kind = metadata["MediaContainer"]["Metadata"][0]["type"]
+            .as_str()
+            .ok_or_else(|| {
+                Error::not_found_with_resource(
+                    format!("{} has no item {}", self.name, item),
+                    "media_item",
+                    item,
+                )
+            })?;
+        let queue_type = if kind == "track" { "audio" } else { "video" };
+        let queue = self
+            .request(
+                Method::POST,
+                "/playQueues",
+                &[
+                    ("type", queue_type.to_string()),
+                    (
+                        "uri",
+                        format!("server://{}/com.plexapp.plugins.library{}", machine, key),
+                    ),
+                    ("shuffle", "0".to_string()),
+                    ("repeat", "0".to_string()),
+                    ("continuous", "0".to_string()),
+                ],
+                None,
+            )
+            .await?;
+        let queue_id = queue["MediaContainer"]["playQueueID"]
+            .as_u64()
+            .ok_or_else(|| Error::parsing("Plex play queue has no playQueueID"))?;
+        // Players fetch the media from the address they are given
+        let url = Url::parse(&self.server.url)
+            .map_err(|e| Error::validation_with_field(e.to_string(), "url"))?;
+        self.request(
+            Method::GET,
+            "/player/playback/playMedia",
+            &[
+                ("key", key),
+                ("offset", "0".to_string()),
+                ("machineIdentifier", machine),
+                ("containerKey", format!("/playQueues/{}?own=1", queue_id)),
+                ("address", url.host_str().unwrap_or_default().to_string()),
+                (
+                    "port",
+                    url.port_or_known_default().unwrap_or(32400).to_string(),
+                ),
+                ("protocol", url.scheme().to_string()),
+                ("token", self.server.token.clone()),
+                (
+                    "type",
+                    if kind == "track" { "music" } else { "video" }.to_string(),
+                ),
+                ("commandID", self.next_command_id()),
+            ],
+            Some(&player.id),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MediaServer for Plex {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        kind: Option<MediaType>,
+        limit: usize,
+    ) -> Result<Vec<MediaItem>> {
+        let body = self
+            .request(
+                Method::GET,
+                "/hubs/search",
+                &[("query", query.to_string()), ("limit", limit.to_string())],
+                None,
+            )
+            .await?;
+        let mut items: Vec<MediaItem> = body["MediaContainer"]["Hub"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|hub| self.items(&hub["Metadata"], kind))
+            .collect();
+        items.truncate(limit);
+        Ok(items)
+    }
+
+    async fn recently_added(
+        &self,
+        kind: Option<MediaType>,
+        limit: usize,
+    ) -> Result<Vec<MediaItem>> {
+        // Fetch extra when filtering, since other kinds take up the page
+        let size = if kind.is_some() { limit * 5 } else { limit };
+        let body = self
+            .request(
+                Method::GET,
+                "/library/recentlyAdded",
+                &[
+                    ("X-Plex-Container-Start", "0".to_string()),
+                    ("X-Plex-Container-Size", size.to_string()),
+                ],
+                None,
+            )
+            .await?;
+        let mut items = self.items(&body["MediaContainer"]["Metadata"], kind);
+        items.truncate(limit);
+        Ok(items)
+    }
+
+    async fn sessions(&self) -> Result<Vec<Session>> {
+        Ok(self
+            .playing()
+            .await?
+            .iter()
+            .map(|s| Session {
+                server: self.name.clone(),
+                user: s["User"]["title"].as_str().unwrap_or_default().to_string(),
+                player: self.session_player(s),
+                item: self.item(s),
+                paused: s["Player"]["state"] == "paused",
+                position_secs: s["viewOffset"].as_u64().map(|ms| ms / 1000),
+            })
+            .collect())
+    }
+
+    async fn players(&self) -> Result<Vec<Player>> {
+        let clients = self.request(Method::GET, "/clients", &[], None).await?;
+        let mut players: Vec<Player> = clients["MediaContainer"]["Server"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|c| Player {
+                server: self.name.clone(),
+                id: c["machineIdentifier"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                name: c["name"].as_str().unwrap_or_default().to_string(),
+                product: c["product"].as_str().map(str::to_string),
+            })
+            .collect();
+        for session in self.playing().await? {
+            let player = self.session_player(&session);
+            if !players.iter().any(|p| p.id == player.id) {
+                players.push(player);
+            }
+        }
+        Ok(players)
+    }
+
+    async fn send(&self, player: &Player, command: &PlaybackCommand) -> Result<()> {
+        let action = match command {
+            PlaybackCommand::Play(item) => return self.play(player, item).await,
+            PlaybackCommand::Pause => "pause",
+            PlaybackCommand::Resume => "play",
+            PlaybackCommand::Stop => "stop",
+            PlaybackCommand::Next => "skipNext",
+            PlaybackCommand::Previous => "skipPrevious",
+        };
+        // Music players only act on music commands
+        let music = self.playing().await?.iter().any(|s| {
+            s["Player"]["machineIdentifier"] == player.id.as_str() && s["type"] == "track"
+        });
+        self.request(
+            Method::GET,
+            &format!("/player/playback/{}", action),
+            &[
+                ("type", if music { "music" } else { "video" }.to_string()),
+                ("commandID", self.next_command_id()),
+            ],
+            Some(&player.id),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smart_home::media::{find_player, MediaServerKind};
+    use axum::extract::{Query, State};
+    use axum::http::HeaderMap;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    type Log = Arc<std::sync::Mutex<Vec<Value>>>;
+
+    #[tokio::test]
+    async fn searches_hubs_and_plays_through_a_play_queue() {
+        let requests: Log = Arc::default();
+        let app = Router::new()
+            .route(
+                "/hubs/search",
+                get(|headers: HeaderMap| async move {
+                    assert_eq!(headers["x-plex-token"], "token");
+                    Json(json!({"MediaContainer": {"Hub": [
+                        {"type": "movie", "Metadata": [
+                            {"ratingKey": "101", "title": "Arrival", "type": "movie", "year": 2016, "duration": 6960000}
+                        ]},
+                        {"type": "album", "Metadata": [
+                            {"ratingKey": "202", "title": "Arrival", "type": "album", "parentTitle": "ABBA", "year": 1976}
+                        ]}
+                    ]}}))
+                }),
+            )
+            .route(
+                "/identity",
+                get(|| async { Json(json!({"MediaContainer": {"machineIdentifier": "pms1"}})) }),
+            )
+            .route(
+                "/library/metadata/:id",
+                get(|| async { Json(json!({"MediaContainer": {"Metadata": [{"type": "movie"}]}})) }),
+            )
+            .route(
+                "/playQueues",
+                post(|Query(query): Query<HashMap<String, String>>| async move {
+                    assert_eq!(
+                        query["uri"],
+                        "server://pms1/com.plexapp.plugins.library/library/metadata/101"
+                    );
+                    Json(json!({"MediaContainer": {"playQueueID": 7}}))
+                }),
+            )
+            .route(
+                "/clients",
+                get(|| async {
+                    Json(json!({"MediaContainer": {"Server": [
+                        {"name": "Bedroom Roku", "machineIdentifier": "roku1", "product": "Plex for Roku"}
+                    ]}}))
+                }),
+            )
+            .route(
+                "/status/sessions",
+                get(|| async { Json(json!({"MediaContainer": {"size": 0}})) }),
+            )
+            .route(
+                "/player/playback/playMedia",
+                get(
+                    |State(requests): State<Log>,
+                     headers: HeaderMap,
+                     Query(query): Query<HashMap<String, String>>| async move {
+                        requests.lock().unwrap().push(json!({
+                            "target": headers["x-plex-target-client-identifier"].to_str().unwrap(),
+                            "query": query
+                        }));
+                        Json(json!({}))
+                    },
+                ),
+            )
+            .with_state(Arc::clone(&requests));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let plex = Plex::new(MediaServerConfig {
+            kind: MediaServerKind::Plex,
+            url: format!("http://{}", address),
+            name: None,
+            token: "token".to_string(),
+        })
+        .unwrap();
+
+        let items = plex.search("arrival", None, 10).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].duration_secs, Some(6960));
+        assert_eq!(items[1].summary(), "ABBA - Arrival (1976) [album, id 202]");
+        let movies = plex
+            .search("arrival", Some(MediaType::Movie), 10)
+            .await
+            .unwrap();
+        assert_eq!(movies.len(), 1);
+
+        let players = plex.players().await.unwrap();
+        let player = find_player(&players, "bedroom roku").unwrap();
+        plex.send(player, &PlaybackCommand::Play("101".to_string()))
+            .await
+            .unwrap();
+        let sent = requests.lock().unwrap()[0].clone();
+        assert_eq!(sent["target"], "roku1");
+        assert_eq!(sent["query"]["key"], "/library/metadata/101");
+        assert_eq!(sent["query"]["containerKey"], "/playQueues/7?own=1");
+        assert_eq!(sent["query"]["port"], address.port().to_string());
+    }
+}
@@ -1,2 +1,20 @@
+/// Assist voice pipelines and satellites
+pub mod assist;
+/// Devices and capabilities across smart home providers
+pub mod devices;
+/// Presence events and rules for geofences
+pub mod geofence;
 /// Smart Home module for home automation and IoT device control
 pub mod home_assistant;
+/// Tandoor and Mealie recipes, meal plans and shopping lists
+pub mod meals;
+/// Jellyfin and Plex libraries, sessions and playback
+pub mod media;
+/// Pi-hole and AdGuard Home DNS filtering
+pub mod network;
+/// Recurring manual actions and the automations they suggest
+pub mod patterns;
+/// Immich photo search, thumbnails and albums
+pub mod photos;
+/// Leak and failed-appliance alerts from utility sensors
+pub mod safety;
@@ -0,0 +1,337 @@
+//! AdGuard Home through its `/control` API
+//!
+//! Requests use basic auth with an AdGuard Home user. Statistics cover the
+//! server's configured statistics interval, and per-client activity comes
+//! from the query log, so it is empty when query logging is off.
+
+use super::{
+    Blocklist, ClientActivity, ClientCount, DnsFilter, DnsFilterKind, DnsFilterServer,
+    FilterStatus, ListChange, LoggedQuery,
+};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Whether a query log reason means the query was blocked
+fn is_blocked(reason: &str) -> bool {
+    reason.starts_with("Filtered") && reason != "FilteredSafeSearch"
+}
+
+/// AdGuard Home server
+pub struct AdGuardHome {
+    client: Client,
+    server: DnsFilterServer,
+    name: String,
+}
+
+impl AdGuardHome {
+    pub fn new(server: DnsFilterServer) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create AdGuard Home client: {}", e)))?;
+        Ok(Self {
+            client,
+            name: server.name(),
+            server,
+        })
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let url = format!("{}/control{}", self.server.url.trim_end_matches('/'), path);
+        let mut request = self.client.request(method, &url).query(query);
+        if let Some(username) = &self.server.username {
+            request = request.basic_auth(username, self.server.password.as_ref());
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("AdGuard Home request failed: {}", e)))?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(Error::api_with_status(
+                format!("AdGuard Home {} failed: {}", path, text.trim()),
+                "adguard",
+                status.as_u16(),
+            ));
+        }
+        // Changes answer with an empty body or plain "OK"
+        Ok(serde_json::from_str(&text).unwrap_or(Value::Null))
+    }
+}
+
+#[async_trait]
+impl DnsFilter for AdGuardHome {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn status(&self, top_clients: usize) -> Result<FilterStatus> {
+        let status = self.request(Method::GET, "/status", &[], None).await?;
+        let stats = self.request(Method::GET, "/stats", &[], None).await?;
+        let queries = stats["num_dns_queries"].as_u64().unwrap_or(0);
+        let blocked = stats["num_blocked_filtering"].as_u64().unwrap_or(0);
+        let enabled = status["protection_enabled"].as_bool().unwrap_or(false);
+        Ok(FilterStatus {
+            server: self.name.clone(),
+            kind: DnsFilterKind::Adguard,
+            enabled,
+            resumes_in_secs: status["protection_disabled_duration"]
+                .as_u64()
+                .filter(|ms| !enabled && *ms > 0)
+                .map(|ms| ms.div_ceil(1000)),
+            queries,
+            blocked,
+            percent_blocked: if queries == 0 {
+                0.0
+            } else {
+                blocked as f64 * 100.0 / queries as f64
+            },
+            domains_blocked: None,
+            // [{"192.168.1.20": 900}, ...]
+            top_clients: stats["top_clients"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.as_object()?.iter().next())
+                .map(|(client, count)| ClientCount {
+                    client: client.clone(),
+                    queries: count.as_u64().unwrap_or(0),
+                })
+                .take(top_clients)
+                .collect(),
+        })
+    }
+
+    async fn set_paused(&self, pause: Option<Duration>) -> Result<()> {
+        let body = match pause {
+            Some(pause) => json!({ "enabled": false, "duration": pause.as_millis() as u64 }),
+            None => json!({ "enabled": true }),
+        };
+        self.request(Method::POST, "/protection", &[], Some(body))
+            .await?;
+        Ok(())
+    }
+
+    async fn blocklists(&self) -> Result<Vec<Blocklist>> {
+        let filtering = self
+            .request(Method::GET, "/filtering/status", &[], None)
+            .await?;
+        let lists = |key: &str, allow: bool| {
+            filtering[key]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |list| Blocklist {
+                    url: list["url"].as_str().unwrap_or_default().to_string(),
+                    name: list["name"].as_str().map(str::to_string),
+                    enabled: list["enabled"].as_bool().unwrap_or(false),
+                    allow,
+                    entries: list["rules_count"].as_u64(),
+                })
+        };
+        Ok(lists("filters", false)
+            .chain(lists("whitelist_filters", true))
+            .collect())
+    }
+
+    async fn change_blocklist(
+        &self,
+        change: ListChange,
+        url: &str,
+        name: Option<&str>,
+        allow: bool,
+    ) -> Result<()> {
+        let (path, body) = match change {
+            ListChange::Add => (
+                "/filtering/add_url",
+                json!({ "name": name.unwrap_or(url), "url": url, "whitelist": allow }),
+            ),
+            ListChange::Remove => (
+                "/filtering/remove_url",
+                json!({ "url": url, "whitelist": allow }),
+            ),
+            ListChange::Enable | ListChange::Disable => {
+                let current = self
+                    .blocklists()
+                    .await?
+                    .into_iter()
+                    .find(|l| l.url == url && l.allow == allow)
+                    .ok_or_else(|| {
+                        Error::not_found_with_resource(
+                            format!("{} has no list {}", self.name, url),
+                            "blocklist",
+                            url,
+                        )
+                    })?;
+                (
+                    "/filtering/set_url",
+                    json!({
+                        "url": url,
+                        "whitelist": allow,
+                        "data": {
+                            "name": name.map(str::to_string).or(current.name).unwrap_or_default(),
+                            "url": url,
+                            "enabled": change == ListChange::Enable
+                        }
+                    }),
+                )
+            }
+        };
+        self.request(Method::POST, path, &[], Some(body)).await?;
+        Ok(())
+    }
+
+    async fn client_activity(&self, client: &str, limit: usize) -> Result<ClientActivity> {
+        let log = self
+            .request(
+                Method::GET,
+                "/querylog",
+                &[("search", client.to_string()), ("limit", limit.to_string())],
+                None,
+            )
+            .await?;
+        Ok(ClientActivity {
+            server: self.name.clone(),
+            client: client.to_string(),
+            queries: log["data"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                // The search also matches domains; keep this client's own queries
+                .filter(|q| {
+                    q["client"] == client || q["client_info"]["name"].as_str() == Some(client)
+                })
+                .map(|q| {
+                    let reason = q["reason"].as_str().unwrap_or_default().to_string();
+                    LoggedQuery {
+                        time: q["time"]
+                            .as_str()
+                            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                            .map(|t| t.with_timezone(&Utc)),
+                        domain: q["question"]["name"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        blocked: is_blocked(&reason),
+                        status: reason,
+                    }
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    type Log = Arc<std::sync::Mutex<Vec<Value>>>;
+
+    #[tokio::test]
+    async fn reads_stats_and_edits_lists_with_basic_auth() {
+        let changes: Log = Arc::default();
+        let app = Router::new()
+            .route(
+                "/control/status",
+                get(|headers: HeaderMap| async move {
+                    // admin:secret
+                    assert_eq!(headers["authorization"], "Basic YWRtaW46c2VjcmV0");
+                    Json(json!({"protection_enabled": true, "protection_disabled_duration": 0}))
+                }),
+            )
+            .route(
+                "/control/stats",
+                get(|| async {
+                    Json(json!({
+                        "num_dns_queries": 400,
+                        "num_blocked_filtering": 100,
+                        "top_clients": [{"192.168.1.20": 300}, {"192.168.1.30": 100}]
+                    }))
+                }),
+            )
+            .route(
+                "/control/filtering/status",
+                get(|| async {
+                    Json(json!({
+                        "filters": [{"url": "https://lists.example/ads.txt", "name": "Ads", "enabled": true, "rules_count": 5000}],
+                        "whitelist_filters": []
+                    }))
+                }),
+            )
+            .route(
+                "/control/filtering/set_url",
+                post(|State(changes): State<Log>, Json(body): Json<Value>| async move {
+                    changes.lock().unwrap().push(body);
+                    "OK"
+                }),
+            )
+            .route(
+                "/control/querylog",
+                get(|| async {
+                    Json(json!({"data": [
+                        {"time": "2024-05-10T08:00:00Z", "client": "192.168.1.20", "question": {"name": "ads.example.com"}, "reason": "FilteredBlackList"},
+                        {"time": "2024-05-10T08:00:01Z", "client": "192.168.1.21", "question": {"name": "192.168.1.20.example"}, "reason": "NotFilteredNotFound"}
+                    ]}))
+                }),
+            )
+            .with_state(Arc::clone(&changes));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let adguard = AdGuardHome::new(DnsFilterServer {
+            kind: DnsFilterKind::Adguard,
+            url: format!("http://{}/", address),
+            name: None,
+            username: Some("admin".to_string()),
+            password: Some("secret".to_string()),
+        })
+        .unwrap();
+        assert_eq!(adguard.name(), address.to_string());
+
+        let status = adguard.status(1).await.unwrap();
+        assert!(status.enabled);
+        assert_eq!(status.percent_blocked, 25.0);
+        assert_eq!(status.top_clients.len(), 1);
+        assert_eq!(status.top_clients[0].queries, 300);
+
+        adguard
+            .change_blocklist(
+                ListChange::Disable,
+                "https://lists.example/ads.txt",
+                None,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            changes.lock().unwrap()[0]["data"],
+            json!({"name": "Ads", "url": "https://lists.example/ads.txt", "enabled": false})
+        );
+
+        let activity = adguard.client_activity("192.168.1.20", 10).await.unwrap();
+        assert_eq!(activity.queries.len(), 1);
+        assert!(activity.queries[0].blocked);
+    }
+}
@@ -0,0 +1,435 @@
+//! DNS filtering through Pi-hole and AdGuard Home
+//!
+//! Each configured server is read and controlled through its own HTTP API:
+//! Pi-hole v6's `/api` and AdGuard Home's `/control`. Both are reduced to
+//! the same status, blocklist and per-client activity shapes, so a home
+//! with a Pi-hole pair or a mix of both reads the same. Pausing filtering
+//! applies to every server unless one is named, since clients use them
+//! interchangeably.
+
+pub mod adguard;
+pub mod pihole;
+
+pub use adguard::AdGuardHome;
+pub use pihole::PiHole;
+
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Longest pause accepted, in minutes
+pub const MAX_PAUSE_MINUTES: u64 = 24 * 60;
+
+/// Queries shown for one client
+const DEFAULT_ACTIVITY_LIMIT: usize = 50;
+
+/// Top clients shown per server
+const DEFAULT_TOP_CLIENTS: usize = 10;
+
+/// DNS filter software
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DnsFilterKind {
+    Pihole,
+    Adguard,
+}
+
+/// One Pi-hole or AdGuard Home server, under `smart_home.dns_filters`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsFilterServer {
+    #[serde(rename = "type")]
+    pub kind: DnsFilterKind,
+    /// Base URL of the web interface, e.g. `http://192.168.1.2`
+    pub url: String,
+    /// Name in results; the URL's host when unset
+    #[serde(default)]
+    pub name: Option<String>,
+    /// AdGuard Home user
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Pi-hole web or app password, or the AdGuard Home user's password
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl DnsFilterServer {
+    /// Servers from `PIHOLE_URL`/`PIHOLE_PASSWORD` and
+    /// `ADGUARD_URL`/`ADGUARD_USERNAME`/`ADGUARD_PASSWORD`
+    pub fn from_env() -> Vec<Self> {
+        let var = |name| std::env::var(name).ok();
+        let mut servers = Vec::new();
+        if let Some(url) = var("PIHOLE_URL") {
+            servers.push(Self {
+                kind: DnsFilterKind::Pihole,
+                url,
+                name: None,
+                username: None,
+                password: var("PIHOLE_PASSWORD"),
+            });
+        }
+        if let Some(url) = var("ADGUARD_URL") {
+            servers.push(Self {
+                kind: DnsFilterKind::Adguard,
+                url,
+                name: None,
+                username: var("ADGUARD_USERNAME"),
+                password: var("ADGUARD_PASSWORD"),
+            });
+        }
+        servers
+    }
+
+    /// Name in results
+    pub fn name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            let host = self.url.split_once("://").map_or(&*self.url, |(_, h)| h);
+            host.trim_end_matches('/').to_string()
+        })
+    }
+}
+
+/// Queries made by one client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientCount {
+    /// Address, or the name the server knows it by
+    pub client: String,
+    pub queries: u64,
+}
+
+/// Filtering state and query counts of one server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterStatus {
+    pub server: String,
+    pub kind: DnsFilterKind,
+    pub enabled: bool,
+    /// Seconds until filtering turns back on, while paused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resumes_in_secs: Option<u64>,
+    /// Queries in the server's statistics window
+    pub queries: u64,
+    pub blocked: u64,
+    pub percent_blocked: f64,
+    /// Domains on the blocklists, where the server reports it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domains_blocked: Option<u64>,
+    pub top_clients: Vec<ClientCount>,
+}
+
+impl FilterStatus {
+    pub fn summary(&self) -> String {
+        let state = match (self.enabled, self.resumes_in_secs) {
+            (true, _) => "filtering".to_string(),
+            (false, Some(secs)) => format!("paused for {} more min", secs.div_ceil(60)),
+            (false, None) => "filtering off".to_string(),
+        };
+        format!(
+            "{}: {}, {} of {} queries blocked ({:.1}%)",
+            self.server, state, self.blocked, self.queries, self.percent_blocked
+        )
+    }
+}
+
+/// Blocklist or allowlist subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Blocklist {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub enabled: bool,
+    /// Allowlist rather than blocklist
+    pub allow: bool,
+    /// Rules or domains loaded from the list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entries: Option<u64>,
+}
+
+/// One DNS query from the query log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedQuery {
+    pub time: Option<DateTime<Utc>>,
+    pub domain: String,
+    pub blocked: bool,
+    /// Server's own reason or status code
+    pub status: String,
+}
+
+/// Recent queries of one client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientActivity {
+    pub server: String,
+    pub client: String,
+    pub queries: Vec<LoggedQuery>,
+}
+
+/// Change to a server's blocklists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListChange {
+    Add,
+    Remove,
+    Enable,
+    Disable,
+}
+
+/// A Pi-hole or AdGuard Home server
+#[async_trait]
+pub trait DnsFilter: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn status(&self, top_clients: usize) -> Result<FilterStatus>;
+
+    /// Turn filtering off for `pause`, or back on when `None`
+    async fn set_paused(&self, pause: Option<Duration>) -> Result<()>;
+
+    async fn blocklists(&self) -> Result<Vec<Blocklist>>;
+
+    async fn change_blocklist(
+        &self,
+        change: ListChange,
+        url: &str,
+        name: Option<&str>,
+        allow: bool,
+    ) -> Result<()>;
+
+    /// Latest queries of a client, by address or name
+    async fn client_activity(&self, client: &str, limit: usize) -> Result<ClientActivity>;
+}
+
+/// Every configured DNS filter
+pub struct DnsFilters {
+    servers: Vec<Box<dyn DnsFilter>>,
+}
+
+impl DnsFilters {
+    pub fn new(config: Vec<DnsFilterServer>) -> Result<Self> {
+        let servers = config
+            .into_iter()
+            .map(|server| -> Result<Box<dyn DnsFilter>> {
+                Ok(match server.kind {
+                    DnsFilterKind::Pihole => Box::new(PiHole::new(server)?),
+                    DnsFilterKind::Adguard => Box::new(AdGuardHome::new(server)?),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { servers })
+    }
+
+    /// The named server, or all of them
+    fn select(&self, server: Option<&str>) -> Result<Vec<&dyn DnsFilter>> {
+        match server {
+            None => Ok(self.servers.iter().map(|s| s.as_ref()).collect()),
+            Some(name) => self
+                .servers
+                .iter()
+                .find(|s| s.name() == name)
+                .map(|s| vec![s.as_ref()])
+                .ok_or_else(|| {
+                    Error::not_found_with_resource(
+                        format!("DNS filter {} is not configured", name),
+                        "dns_filter",
+                        name,
+                    )
+                }),
+        }
+    }
+
+    /// The named server, or the only one
+    fn one(&self, server: Option<&str>) -> Result<&dyn DnsFilter> {
+        let selected = self.select(server)?;
+        match selected.as_slice() {
+            [only] => Ok(*only),
+            _ => Err(Error::validation_with_field(
+                "Several DNS filters are configured; name one",
+                "server",
+            )),
+        }
+    }
+
+    /// Get tool definitions for DNS filtering
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "dns_filter_status",
+                "Show Pi-hole and AdGuard Home filtering state, query and block counts and top clients, with the blocklists or one client's recent queries on request",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "server": {"type": "string", "description": "Only this server; all configured servers when omitted"},
+                        "client": {"type": "string", "description": "Show this client's recent queries, by IP address or name"},
+                        "include_blocklists": {"type": "boolean", "description": "List each server's blocklists and allowlists", "default": false},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 500, "description": "Top clients to show, 10 by default, or the client's queries, 50 by default"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "dns_filter_pause",
+                "Turn DNS filtering off for some minutes, after which it turns back on by itself, or resume it now",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "minutes": {"type": "integer", "minimum": 0, "maximum": MAX_PAUSE_MINUTES, "description": "How long to pause; 0 resumes filtering now"},
+                        "server": {"type": "string", "description": "Only this server; all configured servers when omitted"}
+                    },
+                    "required": ["minutes"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "dns_filter_blocklist",
+                "Add, remove, enable or disable a blocklist or allowlist subscription on a DNS filter",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "action": {"type": "string", "enum": ["add", "remove", "enable", "disable"]},
+                        "url": {"type": "string", "description": "List URL"},
+                        "name": {"type": "string", "description": "Name or comment for an added list"},
+                        "allow": {"type": "boolean", "description": "An allowlist rather than a blocklist", "default": false},
+                        "server": {"type": "string", "description": "Only this server; all configured servers when omitted"}
+                    },
+                    "required": ["action", "url"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a DNS filter tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let server = parameters.get("server").and_then(|v| v.as_str());
+        match name {
+            "dns_filter_status" => {
+                let limit = parameters
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .map(|l| l.clamp(1, 500) as usize);
+                if let Some(client) = parameters.get("client").and_then(|v| v.as_str()) {
+                    let activity = self
+                        .one(server)?
+                        .client_activity(client, limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT))
+                        .await?;
+                    let blocked = activity.queries.iter().filter(|q| q.blocked).count();
+                    let mut text = format!(
+                        "{} on {}: {} recent queries, {} blocked",
+                        activity.client,
+                        activity.server,
+                        activity.queries.len(),
+                        blocked
+                    );
+                    for query in activity.queries.iter().filter(|q| q.blocked).take(10) {
+                        text.push_str(&format!("\n  blocked {}", query.domain));
+                    }
+                    return Ok(call_result(text, json!(activity)));
+                }
+                let include_blocklists = parameters
+                    .get("include_blocklists")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let mut text = String::new();
+                let mut servers = Vec::new();
+                for filter in self.select(server)? {
+                    let entry = match filter.status(limit.unwrap_or(DEFAULT_TOP_CLIENTS)).await {
+                        Ok(status) => {
+                            text.push_str(&status.summary());
+                            let mut entry = json!(status);
+                            if include_blocklists {
+                                let lists = filter.blocklists().await?;
+                                text.push_str(&format!(", {} lists", lists.len()));
+                                entry["blocklists"] = json!(lists);
+                            }
+                            entry
+                        }
+                        Err(e) => {
+                            text.push_str(&format!("{}: unavailable ({})", filter.name(), e));
+                            json!({ "server": filter.name(), "error": e.to_string() })
+                        }
+                    };
+                    text.push('\n');
+                    servers.push(entry);
+                }
+                Ok(call_result(text.trim_end(), json!({ "servers": servers })))
+            }
+            "dns_filter_pause" => {
+                let minutes = parameters
+                    .get("minutes")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| {
+                        Error::validation_with_field("minutes is required", "minutes")
+                    })?;
+                if minutes > MAX_PAUSE_MINUTES {
+                    return Err(Error::validation_with_field(
+                        format!("Pauses are limited to {} minutes", MAX_PAUSE_MINUTES),
+                        "minutes",
+                    ));
+                }
+                let pause = (minutes > 0).then(|| Duration::from_secs(minutes * 60));
+                let mut done = Vec::new();
+                for filter in self.select(server)? {
+                    filter.set_paused(pause).await?;
+                    done.push(filter.name().to_string());
+                }
+                let text = match pause {
+                    Some(_) => format!(
+                        "Filtering paused for {} min on {}",
+                        minutes,
+                        done.join(", ")
+                    ),
+                    None => format!("Filtering resumed on {}", done.join(", ")),
+                };
+                Ok(call_result(
+                    text,
+                    json!({ "servers": done, "minutes": minutes }),
+                ))
+            }
+            "dns_filter_blocklist" => {
+                let field = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+                let change = match field("action") {
+                    Some("add") => ListChange::Add,
+                    Some("remove") => ListChange::Remove,
+                    Some("enable") => ListChange::Enable,
+                    Some("disable") => ListChange::Disable,
+                    _ => {
+                        return Err(Error::validation_with_field(
+                            "action must be add, remove, enable or disable",
+                            "action",
+                        ))
+                    }
+                };
+                let url = field("url")
+                    .ok_or_else(|| Error::validation_with_field("url is required", "url"))?;
+                let allow = parameters
+                    .get("allow")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let mut done = Vec::new();
+                for filter in self.select(server)? {
+                    filter
+                        .change_blocklist(change, url, field("name"), allow)
+                        .await?;
+                    done.push(filter.name().to_string());
+                }
+                Ok(call_result(
+                    format!(
+                        "{} {} on {}",
+                        field("action").unwrap_or_default(),
+                        url,
+                        done.join(", ")
+                    ),
+                    json!({ "servers": done, "url": url, "allow": allow }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "dns_filter_tool",
+                name,
+            )),
+        }
+    }
+}
@@ -0,0 +1,425 @@
+//! Pi-hole v6 through its REST API
+//!
+//! The API password (web or app password) is exchanged for a session id at
+//! `/api/auth`, which is reused until Pi-hole rejects it. A Pi-hole without
+//! a password hands out no session and takes requests as they are. List
+//! changes take effect at the next gravity update.
+
+use super::{
+    Blocklist, ClientActivity, ClientCount, DnsFilter, DnsFilterKind, DnsFilterServer,
+    FilterStatus, ListChange, LoggedQuery,
+};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::{Client, Method, StatusCode};
+use serde_json::{json, Value};
+use std::net::IpAddr;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Query statuses for blocked queries
+const BLOCKED_STATUSES: &[&str] = &[
+    "GRAVITY",
+    "REGEX",
+    "DENYLIST",
+    "EXTERNAL_BLOCKED_IP",
+    "EXTERNAL_BLOCKED_NULL",
+    "EXTERNAL_BLOCKED_NXRA",
+    "EXTERNAL_BLOCKED_EDE15",
+    "GRAVITY_CNAME",
+    "REGEX_CNAME",
+    "DENYLIST_CNAME",
+    "SPECIAL_DOMAIN",
+];
+
+/// Pi-hole server
+pub struct PiHole {
+    client: Client,
+    server: DnsFilterServer,
+    name: String,
+    /// Session id from `/api/auth`; `Some(None)` when no password is set
+    session: Mutex<Option<Option<String>>>,
+}
+
+impl PiHole {
+    pub fn new(server: DnsFilterServer) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Pi-hole client: {}", e)))?;
+        Ok(Self {
+            client,
+            name: server.name(),
+            server,
+            session: Mutex::new(None),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api{}", self.server.url.trim_end_matches('/'), path)
+    }
+
+    async fn login(&self) -> Result<Option<String>> {
+        let response = self
+            .client
+            .post(self.url("/auth"))
+            .json(&json!({ "password": self.server.password.clone().unwrap_or_default() }))
+            .send()
+            .await
+            .map_err(|e| Error::network(format!("Pi-hole login failed: {}", e)))?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() || body["session"]["valid"] != true {
+            return Err(Error::api_with_status(
+                format!(
+                    "Pi-hole {} refused the password: {}",
+                    self.name,
+                    body["error"]["message"]
+                        .as_str()
+                        .unwrap_or("invalid session")
+                ),
+                "pihole",
+                status.as_u16(),
+            ));
+        }
+        Ok(body["session"]["sid"].as_str().map(str::to_string))
+    }
+
+    async fn request(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        for attempt in 0..2 {
+            let sid = {
+                let mut session = self.session.lock().await;
+                if session.is_none() {
+                    *session = Some(self.login().await?);
+                }
+                session.clone().flatten()
+            };
+            let mut request = self.client.request(method.clone(), self.url(path));
+            if let Some(sid) = &sid {
+                request = request.header("X-FTL-SID", sid);
+            }
+            if let Some(body) = &body {
+                request = request.json(body);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| Error::network(format!("Pi-hole request failed: {}", e)))?;
+            let status = response.status();
+            if status == StatusCode::UNAUTHORIZED && attempt == 0 {
+                // Session expired; log in again
+                *self.session.lock().await = None;
+                continue;
+            }
+            let text = response.text().await.unwrap_or_default();
+            if !status.is_success() {
+                let message = serde_json::from_str::<Value>(&text)
+                    .ok()
+                    .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+                    .unwrap_or(text);
+                return Err(Error::api_with_status(
+                    format!("Pi-hole {} failed: {}", path, message),
+                    "pihole",
+                    status.as_u16(),
+                ));
+            }
+            if text.trim().is_empty() {
+                return Ok(Value::Null);
+            }
+            return serde_json::from_str(&text)
+                .map_err(|e| Error::parsing(format!("Invalid Pi-hole response: {}", e)));
+        }
+        Err(Error::api_with_status(
+            format!("Pi-hole {} keeps rejecting the session", self.name),
+            "pihole",
+            401,
+        ))
+    }
+
+    fn list_type(allow: bool) -> &'static str {
+        if allow {
+            "allow"
+        } else {
+            "block"
+        }
+    }
+}
+
+#[async_trait]
+impl DnsFilter for PiHole {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn status(&self, top_clients: usize) -> Result<FilterStatus> {
+        let summary = self.request(Method::GET, "/stats/summary", None).await?;
+        let blocking = self.request(Method::GET, "/dns/blocking", None).await?;
+        let clients = self
+            .request(
+                Method::GET,
+                &format!("/stats/top_clients?count={}", top_clients),
+                None,
+            )
+            .await?;
+        Ok(FilterStatus {
+            server: self.name.clone(),
+            kind: DnsFilterKind::Pihole,
+            enabled: blocking["blocking"] == "enabled",
+            resumes_in_secs: blocking["timer"].as_f64().map(|t| t.ceil() as u64),
+            queries: summary["queries"]["total"].as_u64().unwrap_or(0),
+            blocked: summary["queries"]["blocked"].as_u64().unwrap_or(0),
+            percent_blocked: summary["queries"]["percent_blocked"]
+                .as_f64()
+                .unwrap_or(0.0),
+            domains_blocked: summary["gravity"]["domains_being_blocked"].as_u64(),
+            top_clients: clients["clients"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|c| ClientCount {
+                    client: c["name"]
+                        .as_str()
+                        .filter(|n| !n.is_empty())
+                        .or_else(|| c["ip"].as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    queries: c["count"].as_u64().unwrap_or(0),
+                })
+                .collect(),
+        })
+    }
+
+    async fn set_paused(&self, pause: Option<Duration>) -> Result<()> {
+        let body = match pause {
+            Some(pause) => json!({ "blocking": false, "timer": pause.as_secs() }),
+            None => json!({ "blocking": true, "timer": null }),
+        };
+        self.request(Method::POST, "/dns/blocking", Some(body))
+            .await?;
+        Ok(())
+    }
+
+    async fn blocklists(&self) -> Result<Vec<Blocklist>> {
+        let lists = self.request(Method::GET, "/lists", None).await?;
+        Ok(lists["lists"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|list| Blocklist {
+                url: list["address"].as_str().unwrap_or_default().to_string(),
+                name: list["comment"]
+                    .as_str()
+                    .filter(|c| !c.is_empty())
+                    .map(str::to_string),
+                enabled: list["enabled"].as_bool().unwrap_or(false),
+                allow: list["type"] == "allow",
+                entries: list["number"].as_u64(),
+            })
+            .collect())
+    }
+
+    async fn change_blocklist(
+        &self,
+        change: ListChange,
+        url: &str,
+        name: Option<&str>,
+        allow: bool,
+    ) -> Result<()> {
+        let kind = Self::list_type(allow);
+        let path = format!(
+            "/lists/{}?type={}",
+            utf8_percent_encode(url, NON_ALPHANUMERIC),
+            kind
+        );
+        match change {
+            ListChange::Add => {
+                self.request(
+                    Method::POST,
+                    &format!("/lists?type={}", kind),
+                    Some(json!({
+                        "address": url,
+                        "comment": name,
+                        "groups": [0],
+                        "enabled": true
+                    })),
+                )
+                .await?;
+            }
+            ListChange::Remove => {
+                self.request(Method::DELETE, &path, None).await?;
+            }
+            ListChange::Enable | ListChange::Disable => {
+                let current = self
+                    .blocklists()
+                    .await?
+                    .into_iter()
+                    .find(|l| l.url == url && l.allow == allow)
+                    .ok_or_else(|| {
+                        Error::not_found_with_resource(
+                            format!("{} has no {}list {}", self.name, kind, url),
+                            "blocklist",
+                            url,
+                        )
+                    })?;
+                self.request(
+                    Method::PUT,
+                    &path,
+                    Some(json!({
+                        "comment": name.map(str::to_string).or(current.name),
+                        "groups": [0],
+                        "enabled": change == ListChange::Enable
+                    })),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn client_activity(&self, client: &str, limit: usize) -> Result<ClientActivity> {
+        let filter = if client.parse::<IpAddr>().is_ok() {
+            "client_ip"
+        } else {
+            "client_name"
+        };
+        let response = self
+            .request(
+                Method::GET,
+                &format!(
+                    "/queries?{}={}&length={}",
+                    filter,
+                    utf8_percent_encode(client, NON_ALPHANUMERIC),
+                    limit
+                ),
+                None,
+            )
+            .await?;
+        Ok(ClientActivity {
+            server: self.name.clone(),
+            client: client.to_string(),
+            queries: response["queries"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|q| {
+                    let status = q["status"].as_str().unwrap_or_default().to_string();
+                    LoggedQuery {
+                        time: q["time"].as_f64().and_then(|t| {
+                            DateTime::<Utc>::from_timestamp(t as i64, (t.fract() * 1e9) as u32)
+                        }),
+                        domain: q["domain"].as_str().unwrap_or_default().to_string(),
+                        blocked: BLOCKED_STATUSES.contains(&status.as_str()),
+                        status,
+                    }
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    type Log = Arc<std::sync::Mutex<Vec<Value>>>;
+
+    #[tokio::test]
+    async fn logs_in_reads_status_and_pauses() {
+        let pauses: Log = Arc::default();
+        let authorized = |headers: &HeaderMap| headers["X-FTL-SID"] == "abc";
+        let app = Router::new()
+            .route(
+                "/api/auth",
+                post(|Json(body): Json<Value>| async move {
+                    let valid = body["password"] == "hunter2";
+                    Json(json!({"session": {"valid": valid, "sid": "abc", "validity": 1800}}))
+                }),
+            )
+            .route(
+                "/api/stats/summary",
+                get(move |headers: HeaderMap| async move {
+                    assert!(authorized(&headers));
+                    Json(json!({
+                        "queries": {"total": 2000, "blocked": 500, "percent_blocked": 25.0},
+                        "gravity": {"domains_being_blocked": 150000}
+                    }))
+                }),
+            )
+            .route(
+                "/api/dns/blocking",
+                get(|| async { Json(json!({"blocking": "disabled", "timer": 299.4})) }).post(
+                    |State(pauses): State<Log>, Json(body): Json<Value>| async move {
+                        pauses.lock().unwrap().push(body);
+                        Json(json!({"blocking": "disabled"}))
+                    },
+                ),
+            )
+            .route(
+                "/api/stats/top_clients",
+                get(|| async {
+                    Json(json!({"clients": [
+                        {"ip": "192.168.1.20", "name": "tv.lan", "count": 900},
+                        {"ip": "192.168.1.21", "name": "", "count": 40}
+                    ]}))
+                }),
+            )
+            .route(
+                "/api/queries",
+                get(|| async {
+                    Json(json!({"queries": [
+                        {"time": 1715328000.5, "domain": "ads.example.com", "status": "GRAVITY"},
+                        {"time": 1715328001.0, "domain": "example.com", "status": "FORWARDED"}
+                    ]}))
+                }),
+            )
+            .with_state(Arc::clone(&pauses));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let pihole = PiHole::new(DnsFilterServer {
+            kind: DnsFilterKind::Pihole,
+            url: format!("http://{}", address),
+            name: Some("pihole".to_string()),
+            username: None,
+            password: Some("hunter2".to_string()),
+        })
+        .unwrap();
+
+        let status = pihole.status(5).await.unwrap();
+        assert!(!status.enabled);
+        assert_eq!(status.resumes_in_secs, Some(300));
+        assert_eq!(status.domains_blocked, Some(150000));
+        assert_eq!(status.top_clients[0].client, "tv.lan");
+        assert_eq!(status.top_clients[1].client, "192.168.1.21");
+        assert_eq!(
+            status.summary(),
+            "pihole: paused for 5 more min, 500 of 2000 queries blocked (25.0%)"
+        );
+
+        pihole
+            .set_paused(Some(Duration::from_secs(600)))
+            .await
+            .unwrap();
+        pihole.set_paused(None).await.unwrap();
+        assert_eq!(
+            *pauses.lock().unwrap(),
+            [
+                json!({"blocking": false, "timer": 600}),
+                json!({"blocking": true, "timer": null})
+            ]
+        );
+
+        let activity = pihole.client_activity("tv.lan", 10).await.unwrap();
+        assert!(activity.queries[0].blocked);
+        assert!(!activity.queries[1].blocked);
+    }
+}
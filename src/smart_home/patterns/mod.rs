@@ -0,0 +1,726 @@
+//! Recurring manual actions mined from Home Assistant history
+//!
+//! The logbook says who or what caused each state change. Changes not caused
+//! by an automation, script or scene are taken as manual: a tap in the app,
+//! a voice command or a wall switch. These are grouped per entity and target
+//! state, then clustered by local time of day; a cluster that recurs on most
+//! of its weekdays becomes a pattern. Patterns in the same area at the same
+//! time are merged into a scene, and every suggestion comes with automation
+//! YAML that can be pasted into Home Assistant as is.
+
+use crate::error::{Error, Result};
+use crate::smart_home::home_assistant::HomeAssistantSocket;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Domains whose manual changes are worth automating
+const DOMAINS: [&str; 6] = [
+    "light",
+    "switch",
+    "fan",
+    "cover",
+    "climate",
+    "input_boolean",
+];
+
+/// Longest history read in one go
+const MAX_DAYS: u32 = 90;
+
+/// A state change nobody automated
+#[derive(Debug, Clone, Serialize)]
+pub struct ManualAction {
+    pub entity_id: String,
+    pub name: String,
+    pub area: Option<String>,
+    /// New state: `on`, `off`, `open`, `closed` or an HVAC mode
+    pub state: String,
+    /// Brightness percent for lights, target temperature for climate
+    pub setting: Option<f64>,
+    pub at: DateTime<Utc>,
+}
+
+impl ManualAction {
+    fn domain(&self) -> &str {
+        self.entity_id
+            .split_once('.')
+            .map_or("", |(domain, _)| domain)
+    }
+}
+
+/// Tuning for `suggest`
+#[derive(Debug, Clone)]
+pub struct PatternOptions {
+    /// Days of history the actions cover
+    pub days: u32,
+    /// Actions further apart than this start a new time cluster
+    pub window_minutes: u32,
+    /// Fewest days a pattern must have happened on
+    pub min_occurrences: usize,
+    /// Share of its weekdays in the period a pattern must have happened on
+    pub min_confidence: f64,
+    pub timezone: Tz,
+}
+
+impl Default for PatternOptions {
+    fn default() -> Self {
+        Self {
+            days: 28,
+            window_minutes: 30,
+            min_occurrences: 4,
+            min_confidence: 0.5,
+            timezone: Tz::UTC,
+        }
+    }
+}
+
+/// The same manual action at about the same time on the same weekdays
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionPattern {
+    pub entity_id: String,
+    pub name: String,
+    pub area: Option<String>,
+    pub state: String,
+    pub setting: Option<f64>,
+    /// Median local time, rounded to five minutes
+    pub time: NaiveTime,
+    pub weekdays: Vec<Weekday>,
+    /// Days the action happened on
+    pub occurrences: usize,
+    /// Occurrences over the days it could have happened
+    pub confidence: f64,
+    /// Minutes between the earliest and latest time of day
+    pub spread_minutes: u32,
+}
+
+/// A proposed automation, with a scene when several devices change together
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationSuggestion {
+    pub alias: String,
+    pub time: NaiveTime,
+    pub weekdays: Vec<Weekday>,
+    pub confidence: f64,
+    pub patterns: Vec<ActionPattern>,
+    /// For `scenes.yaml`, or Settings > Scenes > Edit in YAML
+    pub scene_yaml: Option<String>,
+    /// For `automations.yaml`, or Settings > Automations > Edit in YAML
+    pub automation_yaml: String,
+}
+
+#[derive(Serialize)]
+struct TimeTrigger {
+    trigger: &'static str,
+    at: String,
+}
+
+#[derive(Serialize)]
+struct WeekdayCondition {
+    condition: &'static str,
+    weekday: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+struct ServiceAction {
+    action: String,
+    target: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct AutomationYaml {
+    alias: String,
+    description: String,
+    triggers: Vec<TimeTrigger>,
+    conditions: Vec<WeekdayCondition>,
+    actions: Vec<ServiceAction>,
+    mode: &'static str,
+}
+
+#[derive(Serialize)]
+struct SceneYaml {
+    name: String,
+    entities: BTreeMap<String, Value>,
+}
+
+fn weekday_key(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "mon",
+        Weekday::Tue => "tue",
+        Weekday::Wed => "wed",
+        Weekday::Thu => "thu",
+        Weekday::Fri => "fri",
+        Weekday::Sat => "sat",
+        Weekday::Sun => "sun",
+    }
+}
+
+/// `every day`, `weekdays`, `weekends` or `mon, wed, fri`
+pub fn weekdays_text(days: &[Weekday]) -> String {
+    let keys: Vec<&str> = days.iter().map(|d| weekday_key(*d)).collect();
+    match keys.as_slice() {
+        ["mon", "tue", "wed", "thu", "fri", "sat", "sun"] => "every day".to_string(),
+        ["mon", "tue", "wed", "thu", "fri"] => "weekdays".to_string(),
+        ["sat", "sun"] => "weekends".to_string(),
+        _ => keys.join(", "),
+    }
+}
+
+/// Rounded to the nearest step, e.g. brightness to 10%
+fn round_to(value: f64, step: f64) -> f64 {
+    (value / step).round() * step
+}
+
+/// Service call that repeats an action
+fn service_action(pattern: &ActionPattern) -> ServiceAction {
+    let (domain, _) = pattern
+        .entity_id
+        .split_once('.')
+        .unwrap_or((&pattern.entity_id, ""));
+    let (service, data) = match (domain, pattern.state.as_str()) {
+        ("cover", "open") => ("open_cover", None),
+        ("cover", _) => ("close_cover", None),
+        ("climate", mode) => match pattern.setting {
+            Some(temperature) if mode != "off" => (
+                "set_temperature",
+                Some(json!({ "hvac_mode": mode, "temperature": temperature })),
+            ),
+            _ => ("set_hvac_mode", Some(json!({ "hvac_mode": mode }))),
+        },
+        ("light", "on") => (
+            "turn_on",
+            pattern
+                .setting
+                .map(|level| json!({ "brightness_pct": level })),
+        ),
+        (_, "on") => ("turn_on", None),
+        _ => ("turn_off", None),
+    };
+    ServiceAction {
+        action: format!("{}.{}", domain, service),
+        target: json!({ "entity_id": pattern.entity_id }),
+        data,
+    }
+}
+
+/// Scene entry that restores an action's state
+fn scene_state(pattern: &ActionPattern) -> Value {
+    match (
+        pattern.entity_id.split_once('.').map(|(d, _)| d),
+        pattern.setting,
+    ) {
+        (Some("light"), Some(level)) if pattern.state == "on" => json!({
+            "state": "on",
+            "brightness": (level * 255.0 / 100.0).round() as u8,
+        }),
+        (Some("climate"), Some(temperature)) => json!({
+            "state": pattern.state,
+            "temperature": temperature,
+        }),
+        _ => json!(pattern.state),
+    }
+}
+
+/// Patterns among manual actions, strongest first
+pub fn find_patterns(actions: &[ManualAction], options: &PatternOptions) -> Vec<ActionPattern> {
+    let mut groups: BTreeMap<(String, String, Option<i64>), Vec<&ManualAction>> = BTreeMap::new();
+    for action in actions {
+        // Lights dimmed to 38% and 41% are the same habit
+        let setting = action.setting.map(|s| match action.domain() {
+            "light" => round_to(s, 10.0) as i64,
+            _ => (round_to(s, 0.5) * 10.0) as i64,
+        });
+        groups
+            .entry((action.entity_id.clone(), action.state.clone(), setting))
+            .or_default()
+            .push(action);
+    }
+
+    let window = options.window_minutes.max(1);
+    let weeks = (options.days as f64 / 7.0).max(1.0);
+    let mut patterns = Vec::new();
+    for group in groups.values() {
+        let mut times: Vec<(u32, NaiveDate, &ManualAction)> = group
+            .iter()
+            .map(|action| {
+                let local = action.at.with_timezone(&options.timezone);
+                (
+                    local.hour() * 60 + local.minute(),
+                    local.date_naive(),
+                    *action,
+                )
+            })
+            .collect();
+        times.sort_by_key(|(minute, _, _)| *minute);
+
+        let mut clusters: Vec<Vec<(u32, NaiveDate, &ManualAction)>> = Vec::new();
+        for time in times {
+            match clusters.last_mut() {
+                Some(cluster) if time.0 - cluster.last().map_or(0, |t| t.0) <= window => {
+                    cluster.push(time)
+                }
+                _ => clusters.push(vec![time]),
+            }
+        }
+
+        for cluster in clusters {
+            let dates: BTreeSet<NaiveDate> = cluster.iter().map(|(_, date, _)| *date).collect();
+            let mut per_weekday: HashMap<Weekday, usize> = HashMap::new();
+            for date in &dates {
+                *per_weekday.entry(date.weekday()).or_default() += 1;
+            }
+            // A weekday counts when the action happened on at least half its weeks
+            let weekdays: Vec<Weekday> = [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+            ]
+            .into_iter()
+            .filter(|day| per_weekday.get(day).copied().unwrap_or(0) as f64 >= (weeks / 2.0).ceil())
+            .collect();
+            if weekdays.is_empty() {
+                continue;
+            }
+            let occurrences = dates
+                .iter()
+                .filter(|date| weekdays.contains(&date.weekday()))
+                .count();
+            let expected = weeks * weekdays.len() as f64;
+            let confidence = (occurrences as f64 / expected).min(1.0);
+            if occurrences < options.min_occurrences || confidence < options.min_confidence {
+                continue;
+            }
+
+            let minutes: Vec<u32> = cluster.iter().map(|(minute, _, _)| *minute).collect();
+            let median = minutes[minutes.len() / 2];
+            let rounded = ((median + 2) / 5 * 5).min(23 * 60 + 55);
+            let first = cluster[0].2;
+            let settings: Vec<f64> = cluster.iter().filter_map(|(_, _, a)| a.setting).collect();
+            let setting = (!settings.is_empty()).then(|| {
+                let mean = settings.iter().sum::<f64>() / settings.len() as f64;
+                match first.domain() {
+                    "light" => round_to(mean, 5.0),
+                    _ => round_to(mean, 0.5),
+                }
+            });
+            patterns.push(ActionPattern {
+                entity_id: first.entity_id.clone(),
+                name: first.name.clone(),
+                area: first.area.clone(),
+                state: first.state.clone(),
+                setting,
+                time: NaiveTime::from_hms_opt(rounded / 60, rounded % 60, 0).unwrap_or_default(),
+                weekdays,
+                occurrences,
+                confidence,
+                spread_minutes: minutes[minutes.len() - 1] - minutes[0],
+            });
+        }
+    }
+    patterns.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then(b.occurrences.cmp(&a.occurrences))
+    });
+    patterns
+}
+
+/// Automation suggestions, merging patterns of one area at one time into a scene
+pub fn suggest(
+    actions: &[ManualAction],
+    options: &PatternOptions,
+) -> Result<Vec<AutomationSuggestion>> {
+    let mut patterns = find_patterns(actions, options);
+    patterns.sort_by_key(|p| p.time);
+
+    let window = i64::from(options.window_minutes.max(1));
+    let mut groups: Vec<Vec<ActionPattern>> = Vec::new();
+    for pattern in patterns {
+        let joins = |group: &Vec<ActionPattern>| {
+            let lead = &group[0];
+            pattern.area.is_some()
+                && lead.area == pattern.area
+                && lead.weekdays == pattern.weekdays
+                && (pattern.time - lead.time).num_minutes() <= window
+                && !group.iter().any(|p| p.entity_id == pattern.entity_id)
+        };
+        match groups.iter_mut().find(|group| joins(group)) {
+            Some(group) => group.push(pattern),
+            None => groups.push(vec![pattern]),
+        }
+    }
+
+    let mut suggestions = Vec::new();
+    for group in groups {
+        let lead = &group[0];
+        let time = lead.time;
+        let weekdays = lead.weekdays.clone();
+        let confidence = group.iter().map(|p| p.confidence).fold(1.0, f64::min);
+        let occurrences = group.iter().map(|p| p.occurrences).min().unwrap_or(0);
+        let when = format!("{} {}", time.format("%H:%M"), weekdays_text(&weekdays));
+        let (alias, scene_yaml, actions) = if group.len() > 1 {
+            let area = lead.area.clone().unwrap_or_default();
+            let name = format!("{} at {}", area, time.format("%H:%M"));
+            let scene_id = format!(
+                "scene.{}",
+                name.to_lowercase()
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                    .collect::<String>()
+                    .split('_')
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("_")
+            );
+            let scene = SceneYaml {
+                name: name.clone(),
+                entities: group
+                    .iter()
+                    .map(|p| (p.entity_id.clone(), scene_state(p)))
+                    .collect(),
+            };
+            let yaml = serde_yaml::to_string(&scene)
+                .map_err(|e| Error::internal(format!("Failed to render scene YAML: {}", e)))?;
+            let action = ServiceAction {
+                action: "scene.turn_on".to_string(),
+                target: json!({ "entity_id": scene_id }),
+                data: None,
+            };
+            (
+                format!("{} ({})", name, weekdays_text(&weekdays)),
+                Some(yaml),
+                vec![action],
+            )
+        } else {
+            (
+                format!("{} {} at {}", lead.name, lead.state, when),
+                None,
+                vec![service_action(lead)],
+            )
+        };
+        let automation = AutomationYaml {
+            alias: alias.clone(),
+            description: format!(
+                "Suggested from manual changes on {} days over the last {} days",
+                occurrences, options.days
+            ),
+            triggers: vec![TimeTrigger {
+                trigger: "time",
+                at: time.format("%H:%M:%S").to_string(),
+            }],
+            conditions: if weekdays.len() == 7 {
+                Vec::new()
+            } else {
+                vec![WeekdayCondition {
+                    condition: "time",
+                    weekday: weekdays.iter().map(|d| weekday_key(*d)).collect(),
+                }]
+            },
+            actions,
+            mode: "single",
+        };
+        let automation_yaml = serde_yaml::to_string(&automation)
+            .map_err(|e| Error::internal(format!("Failed to render automation YAML: {}", e)))?;
+        suggestions.push(AutomationSuggestion {
+            alias,
+            time,
+            weekdays,
+            confidence,
+            patterns: group,
+            scene_yaml,
+            automation_yaml,
+        });
+    }
+    suggestions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    Ok(suggestions)
+}
+
+/// Time from a logbook or history field: seconds as a float, or RFC 3339
+fn timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Number(seconds) => {
+            DateTime::from_timestamp_micros((seconds.as_f64()? * 1_000_000.0) as i64)
+        }
+        Value::String(text) => DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|t| t.with_timezone(&Utc)),
+        _ => None,
+    }
+}
+
+/// Whether a logbook entry was caused by an automation, script or scene
+fn automated(entry: &Value) -> bool {
+    let by_entity = entry["context_entity_id"].as_str().is_some_and(|id| {
+        ["automation.", "script.", "scene."]
+            .iter()
+            .any(|domain| id.starts_with(domain))
+    });
+    let by_event = matches!(
+        entry["context_event_type"].as_str(),
+        Some("automation_triggered" | "script_started")
+    );
+    by_entity || by_event
+}
+
+/// Reads manual actions from Home Assistant and suggests automations for them
+pub struct Patterns<'a> {
+    socket: &'a HomeAssistantSocket,
+}
+
+impl<'a> Patterns<'a> {
+    pub fn new(socket: &'a HomeAssistantSocket) -> Self {
+        Self { socket }
+    }
+
+    /// Home Assistant's configured time zone
+    pub async fn timezone(&self) -> Result<Tz> {
+        let config = self.socket.request(json!({"type": "get_config"})).await?;
+        let name = config["time_zone"].as_str().unwrap_or("UTC");
+        name.parse()
+            .map_err(|_| Error::parsing(format!("Unknown Home Assistant time zone {}", name)))
+    }
+
+    /// Manual changes to lights, switches, fans, covers and climate over the last `days`
+    pub async fn manual_actions(&self, area: Option<&str>, days: u32) -> Result<Vec<ManualAction>> {
+        if days == 0 || days > MAX_DAYS {
+            return Err(Error::validation_with_field(
+                format!("Days must be between 1 and {}", MAX_DAYS),
+                "days",
+            ));
+        }
+        let entities: HashMap<String, _> = self
+            .socket
+            .entities(None, area)
+            .await?
+            .into_iter()
+            .filter(|e| DOMAINS.contains(&e.domain()))
+            .map(|e| (e.entity_id.clone(), e))
+            .collect();
+        if entities.is_empty() {
+            return Ok(Vec::new());
+        }
+        let end = Utc::now();
+        let start = end - Duration::days(i64::from(days));
+        let entity_ids: Vec<&String> = entities.keys().collect();
+        let logbook = self
+            .socket
+            .request(json!({
+                "type": "logbook/get_events",
+                "start_time": start.to_rfc3339(),
+                "end_time": end.to_rfc3339(),
+                "entity_ids": entity_ids,
+            }))
+            .await?;
+
+        // Brightness and target temperatures are only in the state history
+        let with_settings: Vec<&String> = entity_ids
+            .iter()
+            .copied()
+            .filter(|id| id.starts_with("light.") || id.starts_with("climate."))
+            .collect();
+        let history = if with_settings.is_empty() {
+            Value::Null
+        } else {
+            self.socket
+                .request(json!({
+                    "type": "history/history_during_period",
+                    "start_time": start.to_rfc3339(),
+                    "end_time": end.to_rfc3339(),
+                    "entity_ids": with_settings,
+                    "minimal_response": false,
+                    "no_attributes": false,
+                    "significant_changes_only": false,
+                }))
+                .await?
+        };
+
+        let mut actions = Vec::new();
+        for entry in logbook.as_array().into_iter().flatten() {
+            let (Some(entity_id), Some(state), Some(at)) = (
+                entry["entity_id"].as_str(),
+                entry["state"].as_str(),
+                timestamp(&entry["when"]),
+            ) else {
+                continue;
+            };
+            let Some(entity) = entities.get(entity_id) else {
+                continue;
+            };
+            if automated(entry) || matches!(state, "unavailable" | "unknown") {
+                continue;
+            }
+            actions.push(ManualAction {
+                entity_id: entity_id.to_string(),
+                name: entity.name().to_string(),
+                area: entity.area.clone(),
+                state: state.to_string(),
+                setting: setting_at(&history[entity_id], entity.domain(), at),
+                at,
+            });
+        }
+        Ok(actions)
+    }
+
+    pub async fn suggest(
+        &self,
+        area: Option<&str>,
+        options: PatternOptions,
+    ) -> Result<(Vec<AutomationSuggestion>, usize)> {
+        let options = PatternOptions {
+            timezone: self.timezone().await?,
+            ..options
+        };
+        let actions = self.manual_actions(area, options.days).await?;
+        Ok((suggest(&actions, &options)?, actions.len()))
+    }
+}
+
+/// Brightness percent or target temperature in effect just after `at`
+fn setting_at(history: &Value, domain: &str, at: DateTime<Utc>) -> Option<f64> {
+    let attribute = match domain {
+        "light" => "brightness",
+        "climate" => "temperature",
+        _ => return None,
+    };
+    let latest = at + Duration::seconds(2);
+    let state = history.as_array()?.iter().rev().find(|s| {
+        timestamp(&s["lu"])
+            .or_else(|| timestamp(&s["last_updated"]))
+            .is_some_and(|t| t <= latest)
+    })?;
+    let value = state
+        .get("a")
+        .or_else(|| state.get("attributes"))?
+        .get(attribute)?
+        .as_f64()?;
+    Some(match domain {
+        "light" => (value * 100.0 / 255.0).round(),
+        _ => value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn action(
+        entity_id: &str,
+        state: &str,
+        setting: Option<f64>,
+        at: DateTime<Utc>,
+    ) -> ManualAction {
+        ManualAction {
+            entity_id: entity_id.to_string(),
+            name: entity_id.split_once('.').unwrap().1.replace('_', " "),
+            area: Some("Living room".to_string()),
+            state: state.to_string(),
+            setting,
+            at,
+        }
+    }
+
+    #[test]
+    fn suggests_scenes_for_evening_habits() {
+        let timezone: Tz = "Europe/Berlin".parse().unwrap();
+        let options = PatternOptions {
+            days: 28,
+            timezone,
+            ..PatternOptions::default()
+        };
+        let mut actions = Vec::new();
+        // Four weeks starting Monday 2026-09-07: every evening around 21:00
+        // the sofa lamp is dimmed and the ceiling light switched off
+        for day in 0..28 {
+            let date = NaiveDate::from_ymd_opt(2026, 9, 7).unwrap() + Duration::days(day);
+            let at = |h, m| {
+                timezone
+                    .from_local_datetime(&date.and_hms_opt(h, m, 0).unwrap())
+                    .unwrap()
+                    .with_timezone(&Utc)
+            };
+            let jitter = (day % 5) as u32 * 3;
+            actions.push(action(
+                "light.sofa",
+                "on",
+                Some(38.0 + (day % 3) as f64),
+                at(20, 55 + jitter / 3),
+            ));
+            actions.push(action("light.ceiling", "off", None, at(21, jitter)));
+            // Weekdays only: fan on at 07:30
+            if date.weekday().num_days_from_monday() < 5 {
+                actions.push(action("fan.bedroom", "on", None, at(7, 30)));
+            }
+            // Now and then, no pattern
+            if day % 9 == 0 {
+                actions.push(action("switch.kettle", "on", None, at(15, 10)));
+            }
+        }
+
+        let patterns = find_patterns(&actions, &options);
+        assert_eq!(patterns.len(), 3, "{:#?}", patterns);
+        let sofa = patterns
+            .iter()
+            .find(|p| p.entity_id == "light.sofa")
+            .unwrap();
+        assert_eq!(sofa.setting, Some(40.0));
+        assert_eq!(sofa.weekdays.len(), 7);
+        assert_eq!(sofa.occurrences, 28);
+        let fan = patterns
+            .iter()
+            .find(|p| p.entity_id == "fan.bedroom")
+            .unwrap();
+        assert_eq!(weekdays_text(&fan.weekdays), "weekdays");
+        assert_eq!(fan.time, NaiveTime::from_hms_opt(7, 30, 0).unwrap());
+
+        let suggestions = suggest(&actions, &options).unwrap();
+        assert_eq!(suggestions.len(), 2);
+        let evening = suggestions.iter().find(|s| s.patterns.len() == 2).unwrap();
+        let scene: Value = serde_yaml::from_str(evening.scene_yaml.as_ref().unwrap()).unwrap();
+        assert_eq!(scene["entities"]["light.sofa"]["brightness"], 102);
+        assert_eq!(scene["entities"]["light.ceiling"], "off");
+        let automation: Value = serde_yaml::from_str(&evening.automation_yaml).unwrap();
+        assert_eq!(automation["actions"][0]["action"], "scene.turn_on");
+        assert_eq!(
+            automation["actions"][0]["target"]["entity_id"],
+            "scene.living_room_at_20_55"
+        );
+        assert!(automation["conditions"].as_array().unwrap().is_empty());
+
+        let morning = suggestions.iter().find(|s| s.patterns.len() == 1).unwrap();
+        assert!(morning.scene_yaml.is_none());
+        let automation: Value = serde_yaml::from_str(&morning.automation_yaml).unwrap();
+        assert_eq!(automation["triggers"][0]["at"], "07:30:00");
+        assert_eq!(automation["actions"][0]["action"], "fan.turn_on");
+        assert_eq!(
+            automation["conditions"][0]["weekday"],
+            json!(["mon", "tue", "wed", "thu", "fri"])
+        );
+    }
+
+    #[test]
+    fn tells_manual_from_automated_changes() {
+        assert!(automated(
+            &json!({"context_entity_id": "automation.sunset"})
+        ));
+        assert!(automated(&json!({"context_event_type": "script_started"})));
+        assert!(!automated(
+            &json!({"context_user_id": "abc", "context_event_type": "call_service"})
+        ));
+        assert!(!automated(&json!({})));
+
+        let history = json!([
+            {"s": "on", "a": {"brightness": 255}, "lu": 1760000000.0},
+            {"s": "on", "a": {"brightness": 102}, "lu": 1760003600.5}
+        ]);
+        let at = DateTime::from_timestamp(1760003600, 0).unwrap();
+        assert_eq!(setting_at(&history, "light", at), Some(40.0));
+        assert_eq!(setting_at(&history, "switch", at), None);
+    }
+}
@@ -0,0 +1,568 @@
+//! Immich photo library
+//!
+//! Photos are found by what they show (Immich's CLIP search), when they were
+//! taken, who is in them and where, with people given by the names assigned
+//! in Immich. Thumbnails come back as image content, so a result can be
+//! looked at or placed on a slide with `add_image_to_slide`, and a selection
+//! can be saved as an album.
+
+use crate::error::{Error, Result};
+use crate::tools::{call_result, image_result, ToolDefinition};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use reqwest::{Client, Method};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Photos returned by default
+const DEFAULT_LIMIT: usize = 25;
+
+/// Immich server, under `smart_home.photos`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhotoLibraryConfig {
+    /// Base URL, e.g. `http://192.168.1.5:2283`
+    pub url: String,
+    /// API key from Account Settings > API Keys
+    pub api_key: String,
+}
+
+impl PhotoLibraryConfig {
+    /// Server from `IMMICH_URL` and `IMMICH_API_KEY`
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            url: std::env::var("IMMICH_URL").ok()?,
+            api_key: std::env::var("IMMICH_API_KEY").ok()?,
+        })
+    }
+}
+
+/// A photo or video in the library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Photo {
+    pub id: String,
+    pub file_name: String,
+    /// `image` or `video`
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub taken_at: Option<DateTime<Utc>>,
+    /// City, state and country where known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub place: Option<String>,
+    /// Named people recognised in it
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub people: Vec<String>,
+}
+
+impl Photo {
+    fn from_asset(asset: &Value) -> Self {
+        let text = |value: &Value| value.as_str().filter(|s| !s.is_empty()).map(str::to_string);
+        let exif = &asset["exifInfo"];
+        let place: Vec<String> = ["city", "state", "country"]
+            .iter()
+            .filter_map(|key| text(&exif[key]))
+            .collect();
+        Self {
+            id: text(&asset["id"]).unwrap_or_default(),
+            file_name: text(&asset["originalFileName"]).unwrap_or_default(),
+            kind: asset["type"].as_str().unwrap_or("IMAGE").to_lowercase(),
+            taken_at: exif["dateTimeOriginal"]
+                .as_str()
+                .or_else(|| asset["fileCreatedAt"].as_str())
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&Utc)),
+            place: (!place.is_empty()).then(|| place.join(", ")),
+            people: asset["people"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|person| text(&person["name"]))
+                .collect(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let mut text = self.file_name.clone();
+        if let Some(taken_at) = self.taken_at {
+            text.push_str(&format!(", {}", taken_at.format("%Y-%m-%d")));
+        }
+        if let Some(place) = &self.place {
+            text.push_str(&format!(", {}", place));
+        }
+        if !self.people.is_empty() {
+            text.push_str(&format!(", with {}", self.people.join(", ")));
+        }
+        format!("{} [id {}]", text, self.id)
+    }
+}
+
+/// What to look for
+#[derive(Debug, Clone, Default)]
+pub struct PhotoQuery {
+    /// Description of the contents, e.g. `beach at sunset`
+    pub text: Option<String>,
+    pub taken_after: Option<DateTime<Utc>>,
+    pub taken_before: Option<DateTime<Utc>>,
+    /// Names of people who must all be in the photo
+    pub people: Vec<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    /// `image` or `video`
+    pub kind: Option<String>,
+    pub limit: usize,
+}
+
+/// Start of a `YYYY-MM-DD` date, or an RFC 3339 time; `end` moves a date
+/// to the start of the following day
+fn parse_time(value: &str, field: &str, end: bool) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        Error::validation_with_field(
+            format!(
+                "{} must be a date like 2024-03-15 or an RFC 3339 time",
+                field
+            ),
+            field,
+        )
+    })?;
+    let date = if end {
+        date.checked_add_days(Days::new(1)).unwrap_or(date)
+    } else {
+        date
+    };
+    Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+}
+
+/// Immich server
+pub struct Immich {
+    client: Client,
+    config: PhotoLibraryConfig,
+}
+
+impl Immich {
+    pub fn new(config: PhotoLibraryConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create Immich client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .request(
+                method,
+                format!("{}/api{}", self.config.url.trim_end_matches('/'), path),
+            )
+            .header("x-api-key", &self.config.api_key);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await.map_err(|e| {
+            Error::network_with_endpoint(
+                format!("Immich request failed: {}", e),
+                self.config.url.clone(),
+            )
+        })?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let text = response.text().await.unwrap_or_default();
+        match status.as_u16() {
+            401 | 403 => Err(Error::auth(
+                "Immich refused the API key; create one under Account Settings > API Keys",
+            )),
+            404 => Err(Error::not_found_with_resource(
+                format!("Immich has no {}", path),
+                "immich",
+                path,
+            )),
+            code => Err(Error::api_with_status(
+                format!("Immich {} failed: {}", path, text.trim()),
+                "immich",
+                code,
+            )),
+        }
+    }
+
+    async fn request(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        let text = self
+            .send(method, path, body)
+            .await?
+            .text()
+            .await
+            .unwrap_or_default();
+        serde_json::from_str(&text)
+            .map_err(|e| Error::parsing(format!("Invalid Immich response from {}: {}", path, e)))
+    }
+
+    /// Id of the person with this name, preferring an exact match
+    async fn person_id(&self, name: &str) -> Result<String> {
+        let path = format!(
+            "/search/person?name={}",
+            percent_encoding::utf8_percent_encode(name, percent_encoding::NON_ALPHANUMERIC)
+        );
+        let people = self.request(Method::GET, &path, None).await?;
+        let people = people.as_array().map(Vec::as_slice).unwrap_or_default();
+        people
+            .iter()
+            .find(|p| {
+                p["name"]
+                    .as_str()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(name))
+            })
+            .or_else(|| people.first())
+            .and_then(|p| p["id"].as_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                Error::not_found_with_resource(
+                    format!("Nobody named {} has been tagged in Immich", name),
+                    "immich_person",
+                    name,
+                )
+            })
+    }
+
+    /// Photos matching every part of `query`, newest first
+    pub async fn search(&self, query: &PhotoQuery) -> Result<Vec<Photo>> {
+        let mut body = json!({
+            "size": query.limit,
+            "withExif": true,
+            "withPeople": true,
+        });
+        let mut person_ids = Vec::new();
+        for name in &query.people {
+            person_ids.push(self.person_id(name).await?);
+        }
+        if !person_ids.is_empty() {
+            body["personIds"] = json!(person_ids);
+        }
+        if let Some(after) = query.taken_after {
+            body["takenAfter"] = json!(after);
+        }
+        if let Some(before) = query.taken_before {
+            body["takenBefore"] = json!(before);
+        }
+        if let Some(city) = &query.city {
+            body["city"] = json!(city);
+        }
+        if let Some(country) = &query.country {
+            body["country"] = json!(country);
+        }
+        if let Some(kind) = &query.kind {
+            body["type"] = json!(kind.to_uppercase());
+        }
+        let path = match &query.text {
+            Some(text) => {
+                body["query"] = json!(text);
+                "/search/smart"
+            }
+            None => "/search/metadata",
+        };
+        let found = self.request(Method::POST, path, Some(body)).await?;
+        Ok(found["assets"]["items"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(query.limit)
+            .map(Photo::from_asset)
+            .collect())
+    }
+
+    /// JPEG preview of a photo, or its small WebP thumbnail
+    pub async fn thumbnail(&self, id: &str, large: bool) -> Result<(Vec<u8>, String)> {
+        let size = if large { "preview" } else { "thumbnail" };
+        let response = self
+            .send(
+                Method::GET,
+                &format!("/assets/{}/thumbnail?size={}", id, size),
+                None,
+            )
+            .await?;
+        let mime_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or(if large { "image/jpeg" } else { "image/webp" })
+            .to_string();
+        let bytes = response.bytes().await.map_err(|e| {
+            Error::network(format!("Failed to read the thumbnail of {}: {}", id, e))
+        })?;
+        Ok((bytes.to_vec(), mime_type))
+    }
+
+    /// New album holding the given photos; returns its id
+    pub async fn create_album(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        asset_ids: &[String],
+    ) -> Result<String> {
+        let album = self
+            .request(
+                Method::POST,
+                "/albums",
+                Some(json!({
+                    "albumName": name,
+                    "description": description.unwrap_or_default(),
+                    "assetIds": asset_ids,
+                })),
+            )
+            .await?;
+        album["id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| Error::parsing("Immich did not return the new album's id"))
+    }
+
+    /// Get tool definitions for the photo library
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "photos_search",
+                "Search the Immich photo library by what the photos show, when they were taken, who is in them and where",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string", "description": "What the photos show, e.g. \"hiking in the snow\""},
+                        "from": {"type": "string", "description": "Taken on or after this date (YYYY-MM-DD) or time"},
+                        "to": {"type": "string", "description": "Taken on or before this date (YYYY-MM-DD) or time"},
+                        "people": {"type": "array", "items": {"type": "string"}, "description": "Names of people who must all be in the photo"},
+                        "city": {"type": "string", "description": "City the photo was taken in"},
+                        "country": {"type": "string", "description": "Country the photo was taken in"},
+                        "type": {"type": "string", "enum": ["image", "video"], "description": "Only photos or only videos"},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 200, "default": DEFAULT_LIMIT}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "photos_thumbnail",
+                "Fetch a photo from the Immich library as an image, to look at or place on a slide",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string", "description": "Photo id from photos_search"},
+                        "size": {"type": "string", "enum": ["thumbnail", "preview"], "description": "thumbnail is small; preview is a larger JPEG", "default": "preview"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "photos_create_album",
+                "Create an Immich album from photos found with photos_search",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "description": "Album name"},
+                        "ids": {"type": "array", "items": {"type": "string"}, "description": "Photo ids to add"},
+                        "description": {"type": "string", "description": "Album description"}
+                    },
+                    "required": ["name", "ids"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a photo library tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let field = |key: &str| {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.trim().is_empty())
+        };
+        let strings = |key: &str| -> Vec<String> {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        };
+        match name {
+            "photos_search" => {
+                let query = PhotoQuery {
+                    text: field("query").map(str::to_string),
+                    taken_after: field("from")
+                        .map(|t| parse_time(t, "from", false))
+                        .transpose()?,
+                    taken_before: field("to").map(|t| parse_time(t, "to", true)).transpose()?,
+                    people: strings("people"),
+                    city: field("city").map(str::to_string),
+                    country: field("country").map(str::to_string),
+                    kind: field("type").map(str::to_string),
+                    limit: parameters
+                        .get("limit")
+                        .and_then(|v| v.as_u64())
+                        .map_or(DEFAULT_LIMIT, |l| l.clamp(1, 200) as usize),
+                };
+                let photos = self.search(&query).await?;
+                let mut text = format!("Found {} photos", photos.len());
+                for photo in &photos {
+                    text.push_str(&format!("\n{}", photo.summary()));
+                }
+                Ok(call_result(text, json!({ "photos": photos })))
+            }
+            "photos_thumbnail" => {
+                let id = field("id")
+                    .ok_or_else(|| Error::validation_with_field("id is required", "id"))?;
+                let large = field("size") != Some("thumbnail");
+                let (data, mime_type) = self.thumbnail(id, large).await?;
+                Ok(image_result(
+                    format!("Photo {}", id),
+                    &data,
+                    &mime_type,
+                    json!({ "id": id, "mime_type": mime_type, "bytes": data.len() }),
+                ))
+            }
+            "photos_create_album" => {
+                let album = field("name")
+                    .ok_or_else(|| Error::validation_with_field("name is required", "name"))?;
+                let ids = strings("ids");
+                if ids.is_empty() {
+                    return Err(Error::validation_with_field(
+                        "ids must list at least one photo",
+                        "ids",
+                    ));
+                }
+                let id = self.create_album(album, field("description"), &ids).await?;
+                Ok(call_result(
+                    format!(
+                        "Created album {} with {} photos [id {}]",
+                        album,
+                        ids.len(),
+                        id
+                    ),
+                    json!({ "id": id, "name": album, "photos": ids.len() }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "photos_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Query, State};
+    use axum::http::HeaderMap;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    type Log = Arc<std::sync::Mutex<Vec<Value>>>;
+
+    #[tokio::test]
+    async fn searches_by_person_and_date_fetches_previews_and_creates_albums() {
+        let requests: Log = Arc::default();
+        let app = Router::new()
+            .route(
+                "/api/search/person",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    assert_eq!(query["name"], "Ana");
+                    Json(json!([
+                        {"id": "p2", "name": "Anastasia"},
+                        {"id": "p1", "name": "ana"}
+                    ]))
+                }),
+            )
+            .route(
+                "/api/search/smart",
+                post(
+                    |State(requests): State<Log>, headers: HeaderMap, Json(body): Json<Value>| async move {
+                        assert_eq!(headers["x-api-key"], "key");
+                        requests.lock().unwrap().push(body);
+                        Json(json!({"assets": {"total": 1, "count": 1, "items": [{
+                            "id": "a1", "type": "IMAGE", "originalFileName": "IMG_0042.jpg",
+                            "fileCreatedAt": "2024-03-20T09:00:00.000Z",
+                            "exifInfo": {"dateTimeOriginal": "2024-03-18T16:30:00.000Z", "city": "Lisbon", "state": null, "country": "Portugal"},
+                            "people": [{"id": "p1", "name": "Ana"}, {"id": "p9", "name": ""}]
+                        }]}}))
+                    },
+                ),
+            )
+            .route(
+                "/api/assets/:id/thumbnail",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    assert_eq!(query["size"], "preview");
+                    ([("content-type", "image/jpeg")], vec![0xff, 0xd8, 0xff])
+                }),
+            )
+            .route(
+                "/api/albums",
+                post(|State(requests): State<Log>, Json(body): Json<Value>| async move {
+                    requests.lock().unwrap().push(body);
+                    Json(json!({"id": "al1", "albumName": "Lisbon"}))
+                }),
+            )
+            .with_state(Arc::clone(&requests));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let immich = Immich::new(PhotoLibraryConfig {
+            url: format!("http://{}/", address),
+            api_key: "key".to_string(),
+        })
+        .unwrap();
+
+        let found = immich
+            .execute_tool(
+                "photos_search",
+                json!({"query": "trip", "people": ["Ana"], "from": "2024-03-01", "to": "2024-03-31"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            found["content"][0]["text"],
+            "Found 1 photos\nIMG_0042.jpg, 2024-03-18, Lisbon, Portugal, with Ana [id a1]"
+        );
+        let preview = immich
+            .execute_tool("photos_thumbnail", json!({"id": "a1"}))
+            .await
+            .unwrap();
+        assert_eq!(preview["content"][1]["mimeType"], "image/jpeg");
+        assert_eq!(preview["content"][1]["data"], "/9j/");
+
+        immich
+            .execute_tool(
+                "photos_create_album",
+                json!({"name": "Lisbon", "ids": ["a1"]}),
+            )
+            .await
+            .unwrap();
+        {
+            let requests = requests.lock().unwrap();
+            assert_eq!(requests[0]["personIds"], json!(["p1"]));
+            assert_eq!(requests[0]["takenAfter"], "2024-03-01T00:00:00Z");
+            assert_eq!(requests[0]["takenBefore"], "2024-04-01T00:00:00Z");
+            assert_eq!(requests[1]["assetIds"], json!(["a1"]));
+        }
+        assert!(immich
+            .execute_tool("photos_search", json!({"from": "March"}))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,508 @@
+//! Leak and failed-appliance alerts from utility sensors
+//!
+//! Rules point an analytics anomaly detector at a Home Assistant sensor such
+//! as a water flow meter or a smart plug's power draw. Each check reads the
+//! sensor's recent history, and an anomaly still going on at the latest
+//! reading raises an alert: water that has not stopped flowing for hours
+//! points at a leak, a freezer that stopped drawing power at a failure.
+//! Alerts go to the configured collaboration channels once, and a second
+//! notice follows when the reading is back to normal.
+
+use crate::analytics::anomaly::{Anomaly, Detector, Sample};
+use crate::collaboration::{AttachmentField, Notification, Notifier, Severity};
+use crate::error::{Error, Result};
+use crate::smart_home::home_assistant::HomeAssistantClient;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+/// Sampling interval assumed when sizing the history a spike window needs
+const ASSUMED_SAMPLE_INTERVAL_MINUTES: i64 = 5;
+
+/// Longest history read for one rule
+const MAX_LOOKBACK_DAYS: i64 = 7;
+
+fn default_check_interval() -> u64 {
+    5
+}
+
+fn critical() -> Severity {
+    Severity::Critical
+}
+
+/// What a sensor meters
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Utility {
+    Water,
+    Power,
+    Gas,
+}
+
+/// Ready-made detectors for the common cases
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    /// Water flowing without a break for two hours
+    WaterLeak,
+    /// Under 5 W for three hours from an appliance that should keep cycling,
+    /// such as a fridge, freezer or sump pump
+    ApplianceOff,
+    /// Power draw far above its recent median
+    PowerSpike,
+}
+
+impl Preset {
+    pub fn detector(&self) -> Detector {
+        match self {
+            Preset::WaterLeak => Detector::Sustained {
+                above: Some(0.0),
+                below: None,
+                minutes: 120.0,
+            },
+            Preset::ApplianceOff => Detector::Sustained {
+                above: None,
+                below: Some(5.0),
+                minutes: 180.0,
+            },
+            Preset::PowerSpike => Detector::Spike {
+                window: 30,
+                threshold: 6.0,
+                min_change: 500.0,
+            },
+        }
+    }
+
+    pub fn utility(&self) -> Utility {
+        match self {
+            Preset::WaterLeak => Utility::Water,
+            Preset::ApplianceOff | Preset::PowerSpike => Utility::Power,
+        }
+    }
+}
+
+/// A sensor and the detector applied to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafetyRule {
+    pub id: String,
+    /// Numeric sensor, e.g. `sensor.main_water_flow`
+    pub entity_id: String,
+    /// Name used in alerts, e.g. `Chest freezer`; the sensor's name otherwise
+    #[serde(default)]
+    pub name: Option<String>,
+    pub utility: Utility,
+    pub detector: Detector,
+    #[serde(default = "critical")]
+    pub severity: Severity,
+}
+
+impl SafetyRule {
+    fn validate(&self) -> Result<()> {
+        if self.id.trim().is_empty() {
+            return Err(Error::validation_with_field("Rule id is required", "id"));
+        }
+        if !self.entity_id.starts_with("sensor.") {
+            return Err(Error::validation_with_field(
+                format!("{} is not a sensor entity", self.entity_id),
+                "entity_id",
+            ));
+        }
+        self.detector
+            .validate()
+            .map_err(|e| Error::validation_with_field(e, "detector"))
+    }
+
+    /// What an ongoing anomaly on this rule most likely means
+    pub fn finding(&self) -> &'static str {
+        match (self.utility, &self.detector) {
+            (Utility::Water, Detector::Sustained { above: Some(_), .. }) => "Possible water leak",
+            (Utility::Gas, Detector::Sustained { above: Some(_), .. }) => "Possible gas leak",
+            (_, Detector::Sustained { above: None, .. }) => "Appliance may have failed",
+            (Utility::Power, _) => "Unusual power draw",
+            (_, Detector::Spike { .. }) => "Unusual consumption",
+        }
+    }
+}
+
+/// Monitor settings, under `smart_home.safety`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SafetyConfig {
+    #[serde(default)]
+    pub rules: Vec<SafetyRule>,
+    /// Collaboration channels alerts are posted to, default all
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(default = "default_check_interval")]
+    pub check_interval_minutes: u64,
+}
+
+/// An ongoing anomaly on a rule's sensor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafetyAlert {
+    pub rule_id: String,
+    pub entity_id: String,
+    pub name: String,
+    pub utility: Utility,
+    pub severity: Severity,
+    pub finding: String,
+    pub anomaly: Anomaly,
+    pub unit: Option<String>,
+    pub raised_at: DateTime<Utc>,
+}
+
+impl SafetyAlert {
+    /// Human readable description
+    pub fn message(&self, now: DateTime<Utc>) -> String {
+        let unit = self
+            .unit
+            .as_deref()
+            .map(|u| format!(" {}", u))
+            .unwrap_or_default();
+        let minutes = self.anomaly.duration(now).num_minutes();
+        match self.anomaly.expected {
+            Some(expected) => format!(
+                "{}: {} reads {}{} against a usual {}{}",
+                self.finding, self.name, self.anomaly.peak, unit, expected, unit
+            ),
+            None => format!(
+                "{}: {} has been at {}{} or beyond for {} minutes",
+                self.finding, self.name, self.anomaly.peak, unit, minutes
+            ),
+        }
+    }
+
+    fn notification(&self, now: DateTime<Utc>) -> Notification {
+        Notification {
+            title: format!("{}: {}", self.finding, self.name),
+            text: self.message(now),
+            severity: self.severity,
+            fields: vec![
+                AttachmentField {
+                    title: "Sensor".to_string(),
+                    value: self.entity_id.clone(),
+                    short: true,
+                },
+                AttachmentField {
+                    title: "Since".to_string(),
+                    value: self.anomaly.start.format("%Y-%m-%d %H:%M UTC").to_string(),
+                    short: true,
+                },
+            ],
+        }
+    }
+}
+
+/// Numeric readings from a Home Assistant history list, skipping unavailable states
+fn samples(history: &Value) -> Vec<Sample> {
+    history
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|state| {
+            let value = state
+                .get("s")
+                .or_else(|| state.get("state"))?
+                .as_str()?
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())?;
+            let at = state
+                .get("lu")
+                .and_then(Value::as_f64)
+                .and_then(|s| DateTime::from_timestamp_micros((s * 1_000_000.0) as i64))
+                .or_else(|| {
+                    state
+                        .get("last_updated")
+                        .and_then(Value::as_str)
+                        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                        .map(|t| t.with_timezone(&Utc))
+                })?;
+            Some(Sample { at, value })
+        })
+        .collect()
+}
+
+/// Applies detectors to utility sensors and posts alerts
+pub struct SafetyMonitor {
+    client: Arc<HomeAssistantClient>,
+    notifier: Option<Arc<Notifier>>,
+    channels: Vec<String>,
+    check_interval_minutes: u64,
+    rules: RwLock<BTreeMap<String, SafetyRule>>,
+    /// Alerts raised and not yet resolved, by rule id
+    active: RwLock<BTreeMap<String, SafetyAlert>>,
+    scheduler: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SafetyMonitor {
+    /// Create a monitor; invalid rules in `config` are skipped with a warning
+    pub fn new(client: Arc<HomeAssistantClient>, config: SafetyConfig) -> Self {
+        let rules = config
+            .rules
+            .into_iter()
+            .filter(|rule| match rule.validate() {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!(rule = %rule.id, error = %e, "Skipping invalid safety rule");
+                    false
+                }
+            })
+            .map(|rule| (rule.id.clone(), rule))
+            .collect();
+        Self {
+            client,
+            notifier: None,
+            channels: config.channels,
+            check_interval_minutes: config.check_interval_minutes.max(1),
+            rules: RwLock::new(rules),
+            active: RwLock::new(BTreeMap::new()),
+            scheduler: Mutex::new(None),
+        }
+    }
+
+    /// Post alerts through collaboration channels
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Add or replace a rule
+    pub async fn add_rule(&self, rule: SafetyRule) -> Result<()> {
+        rule.validate()?;
+        self.active.write().await.remove(&rule.id);
+        self.rules.write().await.insert(rule.id.clone(), rule);
+        Ok(())
+    }
+
+    /// Remove a rule and its alert
+    pub async fn remove_rule(&self, id: &str) -> Result<SafetyRule> {
+        let rule = self.rules.write().await.remove(id).ok_or_else(|| {
+            Error::not_found_with_resource("Safety rule not found", "safety_rule", id)
+        })?;
+        self.active.write().await.remove(id);
+        Ok(rule)
+    }
+
+    pub async fn list_rules(&self) -> Vec<SafetyRule> {
+        self.rules.read().await.values().cloned().collect()
+    }
+
+    /// Alerts still open
+    pub async fn active_alerts(&self) -> Vec<SafetyAlert> {
+        self.active.read().await.values().cloned().collect()
+    }
+
+    /// Ongoing anomaly on a rule's sensor, if any
+    pub async fn evaluate(&self, rule: &SafetyRule, now: DateTime<Utc>) -> Result<Option<Anomaly>> {
+        let lookback = rule
+            .detector
+            .lookback(Duration::minutes(ASSUMED_SAMPLE_INTERVAL_MINUTES))
+            .min(Duration::days(MAX_LOOKBACK_DAYS));
+        let history = self
+            .client
+            .socket()
+            .await?
+            .request(json!({
+                "type": "history/history_during_period",
+                "start_time": (now - lookback).to_rfc3339(),
+                "end_time": now.to_rfc3339(),
+                "entity_ids": [rule.entity_id],
+                "minimal_response": true,
+                "no_attributes": true,
+                "significant_changes_only": false,
+            }))
+            .await?;
+        let samples = samples(&history[&rule.entity_id]);
+        Ok(rule
+            .detector
+            .detect(&samples, now)
+            .into_iter()
+            .rev()
+            .find(Anomaly::ongoing))
+    }
+
+    /// Evaluate every rule, posting new alerts and resolutions; returns the open alerts
+    pub async fn check(&self) -> Result<Vec<SafetyAlert>> {
+        let now = Utc::now();
+        for rule in self.list_rules().await {
+            match self.evaluate(&rule, now).await {
+                Ok(anomaly) => {
+                    self.apply(&rule, anomaly, now).await;
+                }
+                Err(e) => {
+                    tracing::warn!(rule = %rule.id, error = %e, "Safety check failed");
+                }
+            }
+        }
+        Ok(self.active_alerts().await)
+    }
+
+    /// Record a rule's latest state, notifying when an alert opens or closes
+    ///
+    /// Returns the alert when this call opened it.
+    pub async fn apply(
+        &self,
+        rule: &SafetyRule,
+        anomaly: Option<Anomaly>,
+        now: DateTime<Utc>,
+    ) -> Option<SafetyAlert> {
+        let mut active = self.active.write().await;
+        match anomaly {
+            Some(anomaly) => {
+                if let Some(open) = active.get_mut(&rule.id) {
+                    open.anomaly = anomaly;
+                    return None;
+                }
+                // Naming the alert must not keep it from being raised
+                let entity = match self.client.socket().await {
+                    Ok(socket) => socket.state(&rule.entity_id).await.ok(),
+                    Err(_) => None,
+                };
+                let alert = SafetyAlert {
+                    rule_id: rule.id.clone(),
+                    entity_id: rule.entity_id.clone(),
+                    name: rule
+                        .name
+                        .clone()
+                        .or_else(|| entity.as_ref().map(|e| e.name().to_string()))
+                        .unwrap_or_else(|| rule.entity_id.clone()),
+                    utility: rule.utility,
+                    severity: rule.severity,
+                    finding: rule.finding().to_string(),
+                    anomaly,
+                    unit: entity.as_ref().and_then(|e| {
+                        e.attributes["unit_of_measurement"]
+                            .as_str()
+                            .map(str::to_string)
+                    }),
+                    raised_at: now,
+                };
+                active.insert(rule.id.clone(), alert.clone());
+                drop(active);
+                self.notify(&alert.notification(now)).await;
+                Some(alert)
+            }
+            None => {
+                let resolved = active.remove(&rule.id)?;
+                drop(active);
+                self.notify(&Notification {
+                    title: format!("Back to normal: {}", resolved.name),
+                    text: format!(
+                        "{} reads normally again after {}",
+                        resolved.name,
+                        resolved.finding.to_lowercase()
+                    ),
+                    severity: Severity::Info,
+                    fields: Vec::new(),
+                })
+                .await;
+                None
+            }
+        }
+    }
+
+    async fn notify(&self, notification: &Notification) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+        // No channels named means every configured one
+        let channels = if self.channels.is_empty() {
+            notifier
+                .channel_names()
+                .into_iter()
+                .map(str::to_string)
+                .collect()
+        } else {
+            self.channels.clone()
+        };
+        for (channel, error) in notifier.broadcast(&channels, notification).await {
+            tracing::warn!("Safety alert to {} failed: {}", channel, error);
+        }
+    }
+
+    /// Check on the configured interval; calling again while running is a no-op
+    pub async fn start(self: &Arc<Self>) {
+        let mut scheduler = self.scheduler.lock().await;
+        if scheduler.as_ref().is_some_and(|s| !s.is_finished()) {
+            return;
+        }
+        let monitor = Arc::clone(self);
+        let period = std::time::Duration::from_secs(self.check_interval_minutes * 60);
+        *scheduler = Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if let Err(e) = monitor.check().await {
+                    tracing::warn!("Safety check failed: {}", e);
+                }
+            }
+        }));
+    }
+
+    /// Stop checking until started again
+    pub async fn stop(&self) {
+        if let Some(scheduler) = self.scheduler.lock().await.take() {
+            scheduler.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_history_and_flags_ongoing_flow() {
+        // Minimal history: a short draw, then flow that never stops
+        let history = json!([
+            {"s": "0.0", "lu": 1714521600.0},
+            {"s": "7.5", "lu": 1714522500.0},
+            {"s": "0.0", "lu": 1714522800.0},
+            {"s": "unavailable", "lu": 1714523000.0},
+            {"s": "0.3", "lu": 1714525200.0},
+            {"s": "0.4", "lu": 1714528800.0}
+        ]);
+        let samples = samples(&history);
+        assert_eq!(samples.len(), 5);
+
+        let rule = SafetyRule {
+            id: "main_water".to_string(),
+            entity_id: "sensor.main_water_flow".to_string(),
+            name: Some("Main water".to_string()),
+            utility: Preset::WaterLeak.utility(),
+            detector: Preset::WaterLeak.detector(),
+            severity: Severity::Critical,
+        };
+        assert!(rule.validate().is_ok());
+        assert_eq!(rule.finding(), "Possible water leak");
+
+        let now = DateTime::from_timestamp(1714533000, 0).unwrap();
+        let anomaly = rule
+            .detector
+            .detect(&samples, now)
+            .into_iter()
+            .find(Anomaly::ongoing)
+            .unwrap();
+        let alert = SafetyAlert {
+            rule_id: rule.id.clone(),
+            entity_id: rule.entity_id.clone(),
+            name: "Main water".to_string(),
+            utility: rule.utility,
+            severity: rule.severity,
+            finding: rule.finding().to_string(),
+            anomaly,
+            unit: Some("L/min".to_string()),
+            raised_at: now,
+        };
+        assert_eq!(
+            alert.message(now),
+            "Possible water leak: Main water has been at 0.4 L/min or beyond for 130 minutes"
+        );
+        assert_eq!(alert.notification(now).severity, Severity::Critical);
+    }
+}
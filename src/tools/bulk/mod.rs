@@ -0,0 +1,618 @@
+/// Bulk execution of one tool over many targets
+///
+/// `bulk_execute` calls a registered tool once per target, a bounded number
+/// at a time, and reports every item's result instead of stopping at the
+/// first failure. Runs are kept so `bulk_retry` can call the tool again for
+/// just the items that failed, and `bulk_status` can show a run later. Only
+/// the principal that started a run can see or retry it.
+use crate::error::{Error, Result};
+use crate::i18n;
+use crate::tools::registry::{current_principal, ToolRegistry};
+use crate::tools::{call_result, ToolDefinition};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Weak};
+use tokio::sync::Mutex;
+
+/// Calls in flight at once when a run does not say
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Upper bound on calls in flight at once
+const MAX_CONCURRENCY: usize = 32;
+
+/// Most targets in one run
+const MAX_TARGETS: usize = 1000;
+
+/// Runs kept for retries, oldest dropped first
+const MAX_RUNS: usize = 100;
+
+/// Outcome of one item in a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+/// One call in a bulk run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkItem {
+    pub index: usize,
+    pub arguments: Value,
+    pub status: ItemStatus,
+    /// Structured content of the last successful call, or its text
+    pub result: Option<Value>,
+    pub error: Option<String>,
+    pub attempts: u32,
+}
+
+/// A tool applied to a list of targets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkRun {
+    pub id: String,
+    pub tool: String,
+    pub concurrency: usize,
+    /// Principal that started the run, when a policy identifies callers
+    pub principal: Option<String>,
+    pub items: Vec<BulkItem>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BulkRun {
+    /// Items that succeeded and items that failed
+    pub fn counts(&self) -> (usize, usize) {
+        let count = |status| self.items.iter().filter(|i| i.status == status).count();
+        (count(ItemStatus::Succeeded), count(ItemStatus::Failed))
+    }
+}
+
+/// Arguments for each target: `common` overlaid with the target's own
+pub fn merge_targets(common: &Value, targets: Vec<Value>) -> Result<Vec<Value>> {
+    let common = match common {
+        Value::Null => serde_json::Map::new(),
+        Value::Object(common) => common.clone(),
+        _ => {
+            return Err(Error::validation_with_field(
+                "common must be an object",
+                "common",
+            ))
+        }
+    };
+    targets
+        .into_iter()
+        .enumerate()
+        .map(|(index, target)| match target {
+            Value::Object(target) => {
+                let mut arguments = common.clone();
+                arguments.extend(target);
+                Ok(Value::Object(arguments))
+            }
+            _ => Err(Error::validation_with_field(
+                format!("Target {} must be an object of tool arguments", index),
+                "targets",
+            )),
+        })
+        .collect()
+}
+
+/// Call `call` for every item not yet succeeded, `concurrency` at a time
+///
+/// A call fails when it returns an error or a result flagged `isError`.
+pub async fn execute_items<F, Fut>(items: &mut [BulkItem], concurrency: usize, call: F)
+where
+    F: Fn(Value) -> Fut,
+    Fut: Future<Output = Result<Value>>,
+{
+    let pending: Vec<(usize, Value)> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.status != ItemStatus::Succeeded)
+        .map(|(position, item)| (position, item.arguments.clone()))
+        .collect();
+    let outcomes: Vec<(usize, Result<Value>)> = stream::iter(pending)
+        .map(|(position, arguments)| {
+            let call = call(arguments);
+            async move { (position, call.await) }
+        })
+        .buffer_unordered(concurrency.clamp(1, MAX_CONCURRENCY))
+        .collect()
+        .await;
+    for (position, outcome) in outcomes {
+        let item = &mut items[position];
+        item.attempts += 1;
+        match outcome {
+            Ok(result) if result.get("isError").and_then(|e| e.as_bool()) == Some(true) => {
+                item.status = ItemStatus::Failed;
+                item.error = Some(result_text(&result));
+                item.result = None;
+            }
+            Ok(result) => {
+                item.status = ItemStatus::Succeeded;
+                item.error = None;
+                item.result = Some(
+                    result
+                        .get("structuredContent")
+                        .cloned()
+                        .unwrap_or_else(|| json!(result_text(&result))),
+                );
+            }
+            Err(e) => {
+                item.status = ItemStatus::Failed;
+                item.error = Some(e.to_string());
+                item.result = None;
+            }
+        }
+    }
+}
+
+/// Text blocks of a `tools/call` result
+fn result_text(result: &Value) -> String {
+    result
+        .get("content")
+        .and_then(|c| c.as_array())
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// Runs tools from the registry in bulk and keeps recent runs for retries
+pub struct BulkExecutor {
+    registry: Weak<ToolRegistry>,
+    runs: Mutex<VecDeque<BulkRun>>,
+}
+
+impl BulkExecutor {
+    /// Executor calling tools in `registry`
+    pub fn new(registry: &Arc<ToolRegistry>) -> Self {
+        Self {
+            registry: Arc::downgrade(registry),
+            runs: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Call `tool` once per set of arguments
+    pub async fn run(
+        &self,
+        tool: &str,
+        arguments: Vec<Value>,
+        concurrency: Option<usize>,
+    ) -> Result<BulkRun> {
+        if tool.starts_with("bulk_") {
+            return Err(Error::validation_with_field(
+                "Bulk tools cannot be run in bulk",
+                "tool",
+            ));
+        }
+        if arguments.is_empty() || arguments.len() > MAX_TARGETS {
+            return Err(Error::validation_with_field(
+                format!("Give between 1 and {} targets", MAX_TARGETS),
+                "targets",
+            ));
+        }
+        let registry = self.registry()?;
+        if !registry.contains(tool) {
+            return Err(Error::not_found_with_resource(
+                "Tool not found",
+                "tool",
+                tool,
+            ));
+        }
+        let now = Utc::now();
+        let mut run = BulkRun {
+            id: uuid::Uuid::new_v4().to_string(),
+            tool: tool.to_string(),
+            concurrency: concurrency.unwrap_or(DEFAULT_CONCURRENCY),
+            principal: current_principal(),
+            items: arguments
+                .into_iter()
+                .enumerate()
+                .map(|(index, arguments)| BulkItem {
+                    index,
+                    arguments,
+                    status: ItemStatus::Pending,
+                    result: None,
+                    error: None,
+                    attempts: 0,
+                })
+                .collect(),
+            created_at: now,
+            updated_at: now,
+        };
+        self.execute(&registry, &mut run).await;
+        self.save(run.clone()).await;
+        Ok(run)
+    }
+
+    /// Call the tool again for the items of a run that failed
+    pub async fn retry(&self, run_id: &str, concurrency: Option<usize>) -> Result<BulkRun> {
+        let mut run = self.get(run_id).await?;
+        if let Some(concurrency) = concurrency {
+            run.concurrency = concurrency;
+        }
+        let registry = self.registry()?;
+        self.execute(&registry, &mut run).await;
+        self.save(run.clone()).await;
+        Ok(run)
+    }
+
+    /// A kept run started by the calling principal
+    pub async fn get(&self, run_id: &str) -> Result<BulkRun> {
+        let principal = current_principal();
+        self.runs
+            .lock()
+            .await
+            .iter()
+            .find(|run| run.id == run_id && run.principal == principal)
+            .cloned()
+            .ok_or_else(|| Error::not_found_with_resource("Bulk run not found", "bulk_run", run_id))
+    }
+
+    fn registry(&self) -> Result<Arc<ToolRegistry>> {
+        self.registry
+            .upgrade()
+            .ok_or_else(|| Error::internal("Tool registry is no longer available"))
+    }
+
+    async fn execute(&self, registry: &ToolRegistry, run: &mut BulkRun) {
+        let tool = run.tool.clone();
+        let concurrency = run.concurrency;
+        execute_items(&mut run.items, concurrency, |arguments| {
+            registry.call(&tool, arguments)
+        })
+        .await;
+        run.updated_at = Utc::now();
+    }
+
+    async fn save(&self, run: BulkRun) {
+        let mut runs = self.runs.lock().await;
+        runs.retain(|kept| kept.id != run.id);
+        if runs.len() >= MAX_RUNS {
+            runs.pop_front();
+        }
+        runs.push_back(run);
+    }
+
+    /// Get tool definitions for bulk execution
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let concurrency = json!({
+            "type": "integer",
+            "minimum": 1,
+            "maximum": MAX_CONCURRENCY,
+            "description": format!("Calls in flight at once (default: {})", DEFAULT_CONCURRENCY)
+        });
+        vec![
+            ToolDefinition::from_json_schema(
+                "bulk_execute",
+                "Call one tool for many targets, such as restarting several deployments, and report each target's result",
+                "core",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "tool": {"type": "string", "description": "Tool to call"},
+                        "targets": {
+                            "type": "array",
+                            "items": {"type": "object"},
+                            "minItems": 1,
+                            "maxItems": MAX_TARGETS,
+                            "description": "Arguments for each call"
+                        },
+                        "common": {"type": "object", "description": "Arguments shared by every call; a target's own arguments win"},
+                        "concurrency": concurrency
+                    },
+                    "required": ["tool", "targets"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "bulk_retry",
+                "Call the tool of a bulk run again for only the targets that failed",
+                "core",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "run_id": {"type": "string", "description": "Run from bulk_execute"},
+                        "concurrency": concurrency
+                    },
+                    "required": ["run_id"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "bulk_status",
+                "Show the per-target results of a bulk run",
+                "core",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "run_id": {"type": "string", "description": "Run from bulk_execute"}
+                    },
+                    "required": ["run_id"]
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    /// Execute a bulk tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let concurrency = parameters
+            .get("concurrency")
+            .and_then(|c| c.as_u64())
+            .map(|c| c as usize);
+        let run = match name {
+            "bulk_execute" => {
+                let params: ExecuteParams = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid parameters: {}", e)))?;
+                let arguments = merge_targets(&params.common, params.targets)?;
+                self.run(&params.tool, arguments, concurrency).await?
+            }
+            "bulk_retry" | "bulk_status" => {
+                let run_id = parameters
+                    .get("run_id")
+                    .and_then(|r| r.as_str())
+                    .ok_or_else(|| Error::validation_with_field("Missing run_id", "run_id"))?;
+                if name == "bulk_retry" {
+                    self.retry(run_id, concurrency).await?
+                } else {
+                    self.get(run_id).await?
+                }
+            }
+            _ => {
+                return Err(Error::not_found_with_resource(
+                    "Tool not found",
+                    "bulk_tool",
+                    name,
+                ))
+            }
+        };
+        Ok(run_result(&run))
+    }
+}
+
+/// Summary and failures of a run, with the run itself as structured content
+fn run_result(run: &BulkRun) -> Value {
+    let (succeeded, failed) = run.counts();
+    let mut text = i18n::text(
+        "messages.bulk.summary",
+        &[
+            ("tool", &run.tool),
+            ("succeeded", &succeeded),
+            ("total", &run.items.len()),
+            ("failed", &failed),
+        ],
+    );
+    for item in run.items.iter().filter(|i| i.status == ItemStatus::Failed) {
+        text.push_str(&format!(
+            "\n#{}: {}",
+            item.index,
+            item.error.as_deref().unwrap_or_default()
+        ));
+    }
+    if failed > 0 {
+        text.push('\n');
+        text.push_str(&i18n::text("messages.bulk.retry", &[("run_id", &run.id)]));
+    }
+    call_result(
+        text,
+        json!({
+            "run_id": run.id,
+            "tool": run.tool,
+            "succeeded": succeeded,
+            "failed": failed,
+            "items": run.items,
+        }),
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecuteParams {
+    tool: String,
+    targets: Vec<Value>,
+    #[serde(default)]
+    common: Value,
+}
+
+/// Register the bulk tools, which call back into `registry`
+pub fn register(registry: &Arc<ToolRegistry>) -> Result<()> {
+    let executor = Arc::new(BulkExecutor::new(registry));
+    registry.register_all(executor.get_tool_definitions(), move |name, arguments| {
+        let executor = Arc::clone(&executor);
+        async move { executor.execute_tool(&name, arguments).await }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn reports_partial_failures_and_retries_only_failed_items() {
+        let arguments = merge_targets(
+            &json!({"namespace": "shop"}),
+            vec![
+                json!({"name": "api"}),
+                json!({"name": "web", "namespace": "edge"}),
+                json!({"name": "db"}),
+            ],
+        )
+        .unwrap();
+        assert_eq!(arguments[1], json!({"name": "web", "namespace": "edge"}));
+        let mut items: Vec<BulkItem> = arguments
+            .into_iter()
+            .enumerate()
+            .map(|(index, arguments)| BulkItem {
+                index,
+                arguments,
+                status: ItemStatus::Pending,
+                result: None,
+                error: None,
+                attempts: 0,
+            })
+            .collect();
+
+        let calls = AtomicUsize::new(0);
+        let healthy = AtomicUsize::new(0);
+        let call = |arguments: Value| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            let db_up = healthy.load(Ordering::SeqCst) > 0;
+            async move {
+                match arguments["name"].as_str() {
+                    Some("db") if !db_up => Err(Error::service("connection refused")),
+                    Some("web") if !db_up => Ok(json!({
+                        "content": [{"type": "text", "text": "rollout stuck"}],
+                        "isError": true
+                    })),
+                    _ => Ok(call_result("restarted", json!({"ok": true}))),
+                }
+            }
+        };
+        execute_items(&mut items, 2, call).await;
+        assert_eq!(items[0].status, ItemStatus::Succeeded);
+        assert_eq!(items[0].result, Some(json!({"ok": true})));
+        assert_eq!(items[1].error.as_deref(), Some("rollout stuck"));
+        assert_eq!(items[2].status, ItemStatus::Failed);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        healthy.store(1, Ordering::SeqCst);
+        execute_items(&mut items, 2, call).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+        assert!(items.iter().all(|i| i.status == ItemStatus::Succeeded));
+        assert_eq!(items[0].attempts, 1);
+        assert_eq!(items[2].attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn runs_registered_tools_through_the_registry() {
+        let registry = Arc::new(ToolRegistry::new());
+        registry
+            .register(
+                ToolDefinition::from_json_schema(
+                    "echo",
+                    "Echo a name",
+                    "core",
+                    json!({
+                        "type": "object",
+                        "properties": {"name": {"type": "string"}},
+                        "required": ["name"]
+                    }),
+                    None,
+                ),
+                |arguments| async move { Ok(call_result("echoed", arguments)) },
+            )
+            .unwrap();
+        register(&registry).unwrap();
+
+        let result = registry
+            .call(
+                "bulk_execute",
+                json!({"tool": "echo", "targets": [{"name": "a"}, {"name": 7}]}),
+            )
+            .await
+            .unwrap();
+        let run = &result["structuredContent"];
+        assert_eq!(run["succeeded"], 1);
+        assert_eq!(run["failed"], 1);
+        assert!(run["items"][1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid arguments"));
+
+        let status = registry
+            .call("bulk_status", json!({"run_id": run["run_id"]}))
+            .await
+            .unwrap();
+        assert_eq!(status["structuredContent"]["failed"], 1);
+        assert!(registry
+            .call(
+                "bulk_execute",
+                json!({"tool": "bulk_status", "targets": [{}]})
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn keeps_each_principals_runs_to_itself() {
+        use crate::security::policy::{PermissionPolicy, PolicyConfig};
+        use crate::tools::registry::{JsonRpcRequest, Session};
+        use crate::transport::tenancy::hash_api_key;
+
+        let registry = Arc::new(ToolRegistry::new());
+        registry
+            .register(
+                ToolDefinition::from_json_schema(
+                    "flaky",
+                    "Always fails",
+                    "core",
+                    json!({"type": "object"}),
+                    None,
+                ),
+                |_| async { Err(Error::service("unavailable")) },
+            )
+            .unwrap();
+        register(&registry).unwrap();
+        let keys = serde_json::from_value(json!({
+            "alice": {"hash": hash_api_key("alice-key"), "scopes": ["*"]},
+            "bob": {"hash": hash_api_key("bob-key"), "scopes": ["*"]}
+        }))
+        .unwrap();
+        let policy = PermissionPolicy::new(PolicyConfig::default())
+            .unwrap()
+            .with_api_keys(keys)
+            .unwrap();
+        let session = |key: &str| {
+            let session = Session::new();
+            session.set_principal(Some(policy.authenticate(Some(key))));
+            session
+        };
+        let (alice, bob) = (session("alice-key"), session("bob-key"));
+        registry.set_policy(Some(Arc::new(policy)));
+        async fn call(
+            registry: &ToolRegistry,
+            session: &Session,
+            name: &str,
+            arguments: Value,
+        ) -> Value {
+            let request = JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: Some(json!(1)),
+                method: "tools/call".to_string(),
+                params: Some(json!({"name": name, "arguments": arguments})),
+            };
+            registry
+                .handle_in_session(request, session, None)
+                .await
+                .result
+                .unwrap()
+        }
+
+        let run = call(
+            &registry,
+            &alice,
+            "bulk_execute",
+            json!({"tool": "flaky", "targets": [{}]}),
+        )
+        .await;
+        let run_id = run["structuredContent"]["run_id"].clone();
+        assert_eq!(run["structuredContent"]["failed"], 1);
+        for tool in ["bulk_status", "bulk_retry"] {
+            let refused = call(&registry, &bob, tool, json!({"run_id": run_id})).await;
+            assert_eq!(refused["isError"], true, "{} served another's run", tool);
+        }
+        let retried = call(&registry, &alice, "bulk_retry", json!({"run_id": run_id})).await;
+        assert_eq!(retried["structuredContent"]["items"][0]["attempts"], 2);
+    }
+}
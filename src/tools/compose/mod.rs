@@ -0,0 +1,5543 @@
+pub mod reload;
+
+pub use reload::ReloadableModules;
+
+use crate::ai::provider::{LlmConfig, LlmProvider, OpenAiCompatibleProvider};
+use crate::ai::{ContextPackBuilder, QueryTranslator, ResponseSummarizer, SummarizationConfig};
+use crate::analytics::health::HealthData;
+use crate::analytics::Detector;
+use crate::collaboration::{N8n, N8nConfig, Notifier, Severity};
+use crate::config::{Config, KubernetesBackend};
+use crate::database::masking::{write_export, DataMasker, ExportFormat};
+use crate::database::safety::QuerySafety;
+use crate::database::schema::ErdFormat;
+use crate::database::sqlite::{self, SqliteConfig};
+use crate::database::{connect_with_pool, Database, PoolConfig, QueryResult};
+use crate::entity::EntityResolver;
+use crate::error::{Error, Result};
+use crate::finance::alpaca::{
+    AlpacaClient, AlpacaConfig, OrderQueryType, OrderRequest, Quote, ReplaceOrderRequest,
+};
+use crate::finance::crypto::{self, CandleInterval, CryptoConfig, CryptoExchange};
+use crate::finance::expenses::Expenses;
+use crate::finance::ledger::Ledger;
+use crate::finance::portfolio::{PortfolioAnalysis, RiskLimits};
+use crate::i18n;
+use crate::infrastructure::assets::AssetRegistry;
+#[cfg(feature = "containers")]
+use crate::infrastructure::docker::engine::DockerEngine;
+use crate::infrastructure::docker::ContainerClient;
+use crate::infrastructure::kubernetes::KubernetesClient;
+#[cfg(feature = "containers")]
+use crate::infrastructure::kubernetes::{KubeApiClient, KubeApiConfig};
+use crate::infrastructure::power::PowerController;
+use crate::infrastructure::proxy::ProxyManager;
+use crate::infrastructure::ups::{UpsConfig, UpsMonitor};
+use crate::lifecycle::LifecycleManager;
+use crate::maps::routing::{RoutingClient, RoutingConfig};
+use crate::maps::GeofenceManager;
+use crate::memory::MemoryClient;
+use crate::monitoring::alerting::{
+    self, AlertRule, Comparison, GrafanaAlerting, Silence, ThresholdRule,
+};
+use crate::monitoring::detections::{self, DetectionRule, DetectionRules, RuleSpec};
+use crate::monitoring::logs::{self, LogEntry};
+use crate::monitoring::prometheus::RuleKind;
+use crate::monitoring::{
+    AlertStore, ElasticsearchConfig, GrafanaConfig, JaegerConfig, LogQuery, LogSearch, LogSeverity,
+    LokiConfig, MonitoringConfig, MonitoringModule, OtelSpan, OtelTrace, PrometheusConfig,
+    SentinelConfig, SplunkConfig, TraceQuery, UptimeKuma, UptimeKumaConfig,
+};
+use crate::search::{
+    self, FederatedResult, FullTextTable, LogSource, MemorySource, NotionConfig, NotionSearch,
+    SearchHit, SearchQuery, SearchSource, SlackConfig, SlackSearch,
+};
+use crate::smart_home::assist::{Assist, RunOptions, SatelliteEvent, SatelliteState};
+use crate::smart_home::devices::{Capability, CapabilityKind, Device, Devices};
+use crate::smart_home::geofence::{GeofenceEngine, GeofenceRule, PresenceEvent};
+use crate::smart_home::home_assistant::{
+    HomeAssistantClient, HomeAssistantConfig, HomeAssistantTransportType,
+};
+use crate::smart_home::meals::{MealPlanner, RecipeServerConfig};
+use crate::smart_home::media::{MediaServerConfig, MediaServers};
+use crate::smart_home::network::{DnsFilterServer, DnsFilters};
+use crate::smart_home::patterns::{weekdays_text, PatternOptions, Patterns};
+use crate::smart_home::photos::{Immich, PhotoLibraryConfig};
+use crate::smart_home::safety::{Preset, SafetyAlert, SafetyMonitor, SafetyRule, Utility};
+use crate::tools::registry::{ToolMiddleware, ToolRegistry};
+use crate::tools::{call_result, ToolDefinition, ToolStream};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Environment variables read when `Config` has no connection for a provider
+const DATABASE_URL_VARS: &[(&str, &str)] = &[
+    ("postgresql", "DATABASE_URL"),
+    ("mongodb", "MONGODB_URI"),
+    ("supabase", "SUPABASE_DB_URL"),
+];
+
+/// Log lines per streamed chunk
+const LOG_CHUNK_LINES: usize = 50;
+
+/// Longest a `stream_quotes` call keeps its subscription open
+const MAX_QUOTE_STREAM_SECS: u64 = 600;
+
+/// Lines of series, values or metrics listed in a Prometheus tool's text
+const PROMETHEUS_TEXT_LINES: usize = 50;
+
+/// Spans drawn in `jaeger_get_trace`'s text; all are in the structured result
+const TRACE_TEXT_SPANS: usize = 100;
+
+/// Characters of each log line shown in `search_logs` text
+const LOG_TEXT_CHARS: usize = 300;
+
+/// Characters of each snippet shown in `search_everything` text
+const SEARCH_TEXT_CHARS: usize = 160;
+
+/// Longest `ha_wait_for_voice_command` waits for a wake word
+const MAX_VOICE_WAIT_SECS: u64 = 3600;
+
+fn default_lines() -> u32 {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+struct ListContainersParams {
+    #[serde(default)]
+    all: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContainerLogsParams {
+    container_id: String,
+    #[serde(default = "default_lines")]
+    lines: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContainerParams {
+    container_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecParams {
+    container_id: String,
+    command: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StopContainerParams {
+    container_id: String,
+    /// Only honoured by the Engine API client
+    #[cfg_attr(not(feature = "containers"), allow(dead_code))]
+    timeout_secs: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListPodsParams {
+    namespace: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PodLogsParams {
+    pod_name: String,
+    namespace: Option<String>,
+    #[serde(default = "default_lines")]
+    lines: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListHelmReleasesParams {
+    namespace: Option<String>,
+    #[serde(default)]
+    all_namespaces: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct HelmHistoryParams {
+    release: String,
+    namespace: Option<String>,
+    max: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HelmValuesParams {
+    release: String,
+    namespace: Option<String>,
+    revision: Option<u32>,
+    #[serde(default)]
+    all: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct HelmRollbackParams {
+    release: String,
+    namespace: Option<String>,
+    revision: Option<u32>,
+    #[serde(default)]
+    wait: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListDatabasesParams {
+    provider: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryParams {
+    provider: String,
+    database: String,
+    query: String,
+    #[serde(default)]
+    params: Vec<Value>,
+    transaction_id: Option<String>,
+    #[serde(default)]
+    confirm: bool,
+    output_path: Option<PathBuf>,
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+#[derive(Debug, Deserialize)]
+struct BeginTransactionParams {
+    provider: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransactionParams {
+    provider: String,
+    transaction_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListTablesParams {
+    provider: String,
+    database: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DescribeSchemaParams {
+    provider: String,
+    database: String,
+    #[serde(default)]
+    tables: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErdParams {
+    provider: String,
+    database: String,
+    #[serde(default)]
+    tables: Vec<String>,
+    #[serde(default)]
+    format: ErdFormat,
+}
+
+#[derive(Debug, Deserialize)]
+struct TurnOnParams {
+    entity_id: String,
+    brightness: Option<u8>,
+    color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TurnOffParams {
+    entity_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TemperatureParams {
+    entity_id: String,
+    temperature: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntityStateParams {
+    entity_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListEntitiesParams {
+    domain: Option<String>,
+    area: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListDevicesParams {
+    area: Option<String>,
+    capability: Option<CapabilityKind>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DevicePowerParams {
+    device: String,
+    on: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceLevelParams {
+    device: String,
+    level: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct RoomTemperatureParams {
+    area: String,
+    temperature: f64,
+}
+
+fn default_pattern_days() -> u32 {
+    28
+}
+
+fn default_min_occurrences() -> usize {
+    4
+}
+
+fn default_pattern_window() -> u32 {
+    30
+}
+
+#[derive(Debug, Deserialize)]
+struct SuggestAutomationsParams {
+    area: Option<String>,
+    #[serde(default = "default_pattern_days")]
+    days: u32,
+    #[serde(default = "default_min_occurrences")]
+    min_occurrences: usize,
+    #[serde(default = "default_pattern_window")]
+    window_minutes: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleIdParams {
+    id: String,
+}
+
+fn default_geofence_events() -> usize {
+    20
+}
+
+#[derive(Debug, Deserialize)]
+struct GeofenceEventsParams {
+    #[serde(default = "default_geofence_events")]
+    limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddSafetyRuleParams {
+    id: String,
+    entity_id: String,
+    name: Option<String>,
+    preset: Option<Preset>,
+    utility: Option<Utility>,
+    detector: Option<Detector>,
+    severity: Option<Severity>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EvaluateTrackerParams {
+    entity_id: String,
+    state: Option<Value>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListAutomationsParams {
+    area: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TriggerAutomationParams {
+    entity_id: String,
+    #[serde(default)]
+    skip_condition: bool,
+    variables: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SceneParams {
+    entity_id: String,
+    transition: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TemplateParams {
+    template: String,
+    variables: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssistTextParams {
+    text: String,
+    pipeline: Option<String>,
+    conversation_id: Option<String>,
+    #[serde(default)]
+    speak: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnnounceParams {
+    satellite: String,
+    message: String,
+    #[serde(default)]
+    start_conversation: bool,
+}
+
+fn default_voice_wait_secs() -> u64 {
+    300
+}
+
+#[derive(Debug, Deserialize)]
+struct VoiceCommandParams {
+    satellite: Option<String>,
+    pipeline: Option<String>,
+    #[serde(default = "default_voice_wait_secs")]
+    timeout_secs: u64,
+}
+
+fn default_bar_days() -> i64 {
+    30
+}
+
+fn default_order_limit() -> usize {
+    50
+}
+
+fn default_stream_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Deserialize)]
+struct SymbolParams {
+    symbol: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BarsParams {
+    symbol: String,
+    #[serde(default = "default_bar_days")]
+    days: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrdersParams {
+    #[serde(default)]
+    status: OrderQueryType,
+    #[serde(default = "default_order_limit")]
+    limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaceOrderParams {
+    #[serde(flatten)]
+    order: OrderRequest,
+    #[serde(default)]
+    confirm: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PairParams {
+    pair: String,
+}
+
+fn default_candle_limit() -> usize {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+struct CandlesParams {
+    pair: String,
+    #[serde(default)]
+    interval: CandleInterval,
+    #[serde(default = "default_candle_limit")]
+    limit: usize,
+}
+
+fn default_book_depth() -> usize {
+    20
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderBookParams {
+    pair: String,
+    #[serde(default = "default_book_depth")]
+    depth: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnalyzePortfolioParams {
+    #[serde(default)]
+    sectors: HashMap<String, String>,
+}
+
+fn default_risk_days() -> i64 {
+    365
+}
+
+#[derive(Debug, Deserialize)]
+struct RiskReportParams {
+    #[serde(default)]
+    sectors: HashMap<String, String>,
+    #[serde(default = "default_risk_days")]
+    days: i64,
+    #[serde(default)]
+    risk_free_rate: f64,
+    #[serde(flatten)]
+    limits: RiskLimits,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderIdParams {
+    order_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplaceOrderParams {
+    order_id: String,
+    #[serde(flatten)]
+    changes: ReplaceOrderRequest,
+    #[serde(default)]
+    confirm: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamQuotesParams {
+    symbols: Vec<String>,
+    #[serde(default = "default_stream_secs")]
+    duration_secs: u64,
+    max_quotes: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CallServiceParams {
+    domain: String,
+    service: String,
+    entity_id: Option<String>,
+    data: Option<Value>,
+    target: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrometheusQueryParams {
+    query: String,
+    time: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn default_lookback() -> String {
+    "1h".to_string()
+}
+
+fn default_step() -> String {
+    "1m".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct PrometheusRangeParams {
+    query: String,
+    #[serde(default = "default_lookback")]
+    range: String,
+    #[serde(default = "default_step")]
+    step: String,
+}
+
+fn default_series_limit() -> usize {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+struct SeriesParams {
+    #[serde(rename = "match")]
+    matchers: Vec<String>,
+    #[serde(default = "default_lookback")]
+    lookback: String,
+    #[serde(default = "default_series_limit")]
+    limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct LabelValuesParams {
+    label: String,
+    #[serde(rename = "match", default)]
+    matchers: Vec<String>,
+    #[serde(default = "default_lookback")]
+    lookback: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MetadataParams {
+    metric: Option<String>,
+    #[serde(default)]
+    search: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TargetsParams {
+    health: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RulesParams {
+    #[serde(rename = "type")]
+    kind: Option<RuleKind>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AlertsParams {
+    state: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JaegerServicesParams {
+    service: Option<String>,
+}
+
+fn default_trace_limit() -> usize {
+    20
+}
+
+#[derive(Debug, Deserialize)]
+struct FindTracesParams {
+    service: String,
+    operation: Option<String>,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    min_duration: Option<String>,
+    max_duration: Option<String>,
+    #[serde(default = "default_lookback")]
+    lookback: String,
+    #[serde(default = "default_trace_limit")]
+    limit: usize,
+}
+
+fn default_log_limit() -> usize {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchLogsParams {
+    #[serde(default)]
+    text: String,
+    #[serde(default = "default_lookback")]
+    lookback: String,
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+    severity: Option<String>,
+    #[serde(default)]
+    sources: Vec<String>,
+    #[serde(default = "default_log_limit")]
+    limit: usize,
+}
+
+fn default_search_lookback() -> String {
+    "24h".to_string()
+}
+
+fn default_search_limit() -> usize {
+    20
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchEverythingParams {
+    query: String,
+    #[serde(default)]
+    sources: Vec<String>,
+    #[serde(default = "default_search_lookback")]
+    lookback: String,
+    #[serde(default = "default_search_limit")]
+    limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SiemListRulesParams {
+    source: Option<String>,
+    enabled: Option<bool>,
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SiemCreateRuleParams {
+    source: Option<String>,
+    #[serde(flatten)]
+    rule: RuleSpec,
+}
+
+#[derive(Debug, Deserialize)]
+struct SiemSetRuleEnabledParams {
+    source: Option<String>,
+    id: String,
+    enabled: bool,
+}
+
+fn default_siem_lookback() -> String {
+    "24h".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct SiemRunRuleParams {
+    source: Option<String>,
+    id: String,
+    #[serde(default = "default_siem_lookback")]
+    lookback: String,
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetTraceParams {
+    trace_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListAlertRulesParams {
+    folder_uid: Option<String>,
+}
+
+fn default_alert_for() -> String {
+    "5m".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateAlertRuleParams {
+    title: String,
+    folder_uid: String,
+    rule_group: String,
+    datasource_uid: String,
+    expr: String,
+    #[serde(default)]
+    comparison: Comparison,
+    threshold: f64,
+    #[serde(rename = "for", default = "default_alert_for")]
+    for_duration: String,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateAlertRuleParams {
+    uid: String,
+    title: Option<String>,
+    expr: Option<String>,
+    comparison: Option<Comparison>,
+    threshold: Option<f64>,
+    #[serde(rename = "for")]
+    for_duration: Option<String>,
+    paused: Option<bool>,
+    labels: Option<BTreeMap<String, String>>,
+    annotations: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListSilencesParams {
+    #[serde(default)]
+    include_expired: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct SilenceAlertsParams {
+    labels: BTreeMap<String, String>,
+    duration: String,
+    comment: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SilenceIdParams {
+    silence_id: String,
+}
+
+/// Reject IDs that could be taken for CLI flags or path segments
+fn validate_container_id(id: &str) -> Result<()> {
+    if id.starts_with('-')
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c))
+    {
+        return Err(Error::validation_with_field(
+            format!("Invalid container id: {}", id),
+            "container_id",
+        ));
+    }
+    Ok(())
+}
+
+/// Summary of a query result: the row count and rows, or the rows affected
+fn query_result_text(result: &QueryResult) -> Result<String> {
+    if result.rows.is_empty() {
+        return Ok(i18n::text(
+            "messages.query.affected",
+            &[
+                ("count", &result.rows_affected),
+                ("ms", &result.execution_time_ms),
+            ],
+        ));
+    }
+    Ok(format!(
+        "{}\n{}",
+        i18n::text(
+            "messages.query.rows",
+            &[
+                ("count", &result.rows.len()),
+                ("ms", &result.execution_time_ms),
+            ],
+        ),
+        serde_json::to_string_pretty(&result.rows)?
+    ))
+}
+
+/// Service data for `light.turn_on` from a brightness and a color name or hex code
+fn light_data(brightness: Option<u8>, color: Option<&str>) -> Option<Value> {
+    let mut data = serde_json::Map::new();
+    if let Some(brightness) = brightness {
+        data.insert("brightness".to_string(), json!(brightness));
+    }
+    if let Some(color) = color {
+        let hex = color.strip_prefix('#').filter(|h| h.len() == 6);
+        match hex.and_then(|h| u32::from_str_radix(h, 16).ok()) {
+            Some(rgb) => {
+                data.insert(
+                    "rgb_color".to_string(),
+                    json!([(rgb >> 16) & 0xff, (rgb >> 8) & 0xff, rgb & 0xff]),
+                );
+            }
+            None => {
+                data.insert("color_name".to_string(), json!(color.to_lowercase()));
+            }
+        }
+    }
+    (!data.is_empty()).then_some(Value::Object(data))
+}
+
+/// Summary line and one line per sector of a portfolio analysis
+fn portfolio_text(analysis: &PortfolioAnalysis) -> String {
+    let mut text = i18n::text(
+        "messages.portfolio.analyzed",
+        &[
+            ("count", &analysis.holdings.len()),
+            ("gross", &format!("{:.2}", analysis.gross_exposure)),
+            ("net", &format!("{:.2}", analysis.net_exposure)),
+            ("pl", &format!("{:.2}", analysis.unrealized_pl)),
+            ("plpc", &format!("{:.2}", analysis.unrealized_plpc * 100.0)),
+        ],
+    );
+    for sector in &analysis.sectors {
+        text.push_str(&format!(
+            "\n{}: {:.1}% ({})",
+            sector.sector,
+            sector.weight * 100.0,
+            sector.symbols.join(", ")
+        ));
+    }
+    text
+}
+
+/// YAML mapping indented to follow a `- ` list marker
+fn indent_yaml(yaml: &str) -> String {
+    yaml.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                format!("{}\n", line)
+            } else {
+                format!("  {}\n", line)
+            }
+        })
+        .collect()
+}
+
+/// Count line followed by one line per presence event
+fn safety_alerts_text(alerts: &[SafetyAlert]) -> String {
+    let now = chrono::Utc::now();
+    let mut text = i18n::text("messages.safety.alerts", &[("count", &alerts.len())]);
+    for alert in alerts {
+        text.push_str(&format!("\n[{}] {}", alert.rule_id, alert.message(now)));
+    }
+    text
+}
+
+fn geofence_events_text(events: &[PresenceEvent]) -> String {
+    let mut text = i18n::text("messages.geofence.events", &[("count", &events.len())]);
+    for event in events {
+        text.push_str(&format!(
+            "\n{} {}",
+            event.at.format("%Y-%m-%d %H:%M"),
+            event.message()
+        ));
+    }
+    text
+}
+
+/// `Name (provider, area): on, 40%` for a device listing
+fn device_text(device: &Device) -> String {
+    let mut text = format!("{} ({}", device.name, device.provider.as_str());
+    if let Some(area) = &device.area {
+        text.push_str(&format!(", {}", area));
+    }
+    text.push(')');
+    if !device.available {
+        return format!("{}: unavailable", text);
+    }
+    let values: Vec<String> = device
+        .capabilities
+        .iter()
+        .map(|capability| match capability {
+            Capability::Switchable { on } => if *on { "on" } else { "off" }.to_string(),
+            Capability::Dimmable { level } => {
+                level.map_or_else(|| "level unknown".to_string(), |l| format!("{}%", l))
+            }
+            Capability::Thermostat {
+                current_temperature,
+                target_temperature,
+                mode,
+                ..
+            } => {
+                let mut value = mode.clone();
+                if let Some(target) = target_temperature {
+                    value.push_str(&format!(" to {}", target));
+                }
+                if let Some(current) = current_temperature {
+                    value.push_str(&format!(", now {}", current));
+                }
+                value
+            }
+            Capability::Sensor { kind, value, unit } => match unit {
+                Some(unit) => format!("{} {}{}", kind, value, unit),
+                None => format!("{} {}", kind, value),
+            },
+        })
+        .collect();
+    format!("{}: {}", text, values.join(", "))
+}
+
+/// `12:00:30 [elasticsearch] ERROR timeout on db.internal` for a log listing
+fn log_entry_text(entry: &LogEntry) -> String {
+    let severity = entry
+        .severity
+        .map(|s| s.as_str().to_uppercase())
+        .unwrap_or_else(|| "-".to_string());
+    let message: String = entry
+        .message
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .take(LOG_TEXT_CHARS)
+        .collect();
+    format!(
+        "{} [{}] {} {}",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        entry.source,
+        severity,
+        message
+    )
+}
+
+/// `[notion] Postgres failover — Promote the replica… (https://notion.so/…)`
+fn search_hit_text(hit: &SearchHit) -> String {
+    let mut text = format!("[{}] {}", hit.source, hit.title);
+    if !hit.snippet.is_empty() && hit.snippet != hit.title {
+        let snippet: String = hit.snippet.chars().take(SEARCH_TEXT_CHARS).collect();
+        text.push_str(&format!(" — {}", snippet));
+        if snippet.len() < hit.snippet.len() {
+            text.push('…');
+        }
+    }
+    if !hit.reference.is_empty() {
+        text.push_str(&format!(" ({})", hit.reference));
+    }
+    text
+}
+
+fn search_result_text(result: &FederatedResult) -> String {
+    let sources = result
+        .sources
+        .iter()
+        .map(|(name, count)| format!("{} {}", name, count))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut text = i18n::text(
+        "messages.search.found",
+        &[("count", &result.hits.len()), ("sources", &sources)],
+    );
+    for (name, error) in &result.errors {
+        text.push_str(&format!(
+            "\n{}",
+            i18n::text(
+                "messages.search.source_failed",
+                &[("source", name), ("error", error)]
+            )
+        ));
+    }
+    for hit in &result.hits {
+        text.push_str(&format!("\n{}", search_hit_text(hit)));
+    }
+    text
+}
+
+/// `[sentinel] 5f1c Impossible travel (high, enabled, every 60 min)`
+fn detection_rule_text(rule: &DetectionRule) -> String {
+    let mut text = format!(
+        "[{}] {} {} ({}, {}",
+        rule.source,
+        rule.id,
+        rule.name,
+        rule.severity.as_str(),
+        if rule.enabled { "enabled" } else { "disabled" }
+    );
+    if let Some(minutes) = rule.interval_minutes {
+        text.push_str(&format!(", every {} min", minutes));
+    }
+    text.push(')');
+    text
+}
+
+/// `id service: operation, 412 ms, 9 spans, 1 error` for one trace
+fn trace_summary(trace: &OtelTrace) -> String {
+    let (service, operation) = trace.root().map_or(("?", "?"), |root| {
+        (
+            root.service_name.as_deref().unwrap_or("?"),
+            root.operation_name.as_str(),
+        )
+    });
+    format!(
+        "{} {}: {}, {} ms, {} spans, {} errors",
+        trace.trace_id,
+        service,
+        operation,
+        trace.duration().num_milliseconds(),
+        trace.spans.len(),
+        trace.error_count()
+    )
+}
+
+/// Spans indented under their parents, with offset from the trace start
+fn trace_tree_text(trace: &OtelTrace) -> String {
+    let Some(start) = trace.spans.iter().map(|s| s.start_time).min() else {
+        return String::new();
+    };
+    let ids: HashSet<&str> = trace.spans.iter().map(|s| s.span_id.as_str()).collect();
+    let mut children: HashMap<Option<&str>, Vec<&OtelSpan>> = HashMap::new();
+    for span in &trace.spans {
+        let parent = span
+            .parent_span_id
+            .as_deref()
+            .filter(|parent| ids.contains(parent));
+        children.entry(parent).or_default().push(span);
+    }
+    let mut text = String::new();
+    let mut stack: Vec<(usize, &OtelSpan)> = children
+        .get(&None)
+        .map(|roots| roots.iter().rev().map(|span| (0, *span)).collect())
+        .unwrap_or_default();
+    let mut drawn = 0;
+    while let Some((depth, span)) = stack.pop() {
+        if drawn == TRACE_TEXT_SPANS {
+            text.push_str(&format!("\n... {} more spans", trace.spans.len() - drawn));
+            break;
+        }
+        drawn += 1;
+        text.push_str(&format!(
+            "\n{}{} {} +{} ms, {} ms",
+            "  ".repeat(depth),
+            span.service_name.as_deref().unwrap_or("?"),
+            span.operation_name,
+            (span.start_time - start).num_milliseconds(),
+            span.duration().num_milliseconds()
+        ));
+        if span.is_error() {
+            text.push_str(" ERROR");
+            if let Some(message) = &span.status.message {
+                text.push_str(&format!(": {}", message));
+            }
+        }
+        if let Some(spans) = children.get(&Some(span.span_id.as_str())) {
+            stack.extend(spans.iter().rev().map(|child| (depth + 1, *child)));
+        }
+    }
+    text
+}
+
+/// `name{label="value", ...}` with labels in name order
+fn labels_text<'a>(labels: impl IntoIterator<Item = (&'a String, &'a String)>) -> String {
+    let mut labels: Vec<_> = labels.into_iter().collect();
+    labels.sort();
+    let name = labels
+        .iter()
+        .position(|(label, _)| *label == "__name__")
+        .map(|i| labels.remove(i).1.clone())
+        .unwrap_or_default();
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(label, value)| format!("{}=\"{}\"", label, value))
+        .collect();
+    format!("{}{{{}}}", name, labels.join(", "))
+}
+
+/// One line per alert rule: title, location, threshold and state
+fn alert_rule_text(rule: &AlertRule) -> String {
+    let threshold = match rule.threshold() {
+        Some((Comparison::Above, value)) => format!("> {}", value),
+        Some((Comparison::Below, value)) => format!("< {}", value),
+        None => "custom condition".to_string(),
+    };
+    format!(
+        "{} ({}) in {}/{}: {} for {}{}",
+        rule.title,
+        rule.uid.as_deref().unwrap_or("-"),
+        rule.folder_uid,
+        rule.rule_group,
+        threshold,
+        rule.for_duration,
+        if rule.is_paused { ", paused" } else { "" }
+    )
+}
+
+fn matchers_text(silence: &Silence) -> String {
+    silence
+        .matchers
+        .iter()
+        .map(|m| {
+            let op = match (m.is_equal, m.is_regex) {
+                (true, false) => "=",
+                (false, false) => "!=",
+                (true, true) => "=~",
+                (false, true) => "!~",
+            };
+            format!("{}{}\"{}\"", m.name, op, m.value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn quote_text(quote: &Quote) -> String {
+    format!(
+        "{} bid {} x {}, ask {} x {}",
+        quote.symbol, quote.bid_price, quote.bid_size, quote.ask_price, quote.ask_size
+    )
+}
+
+/// Module clients behind the server's built-in tools
+///
+/// Each client is instantiated from `Config`, falling back to the
+/// environment, and tools whose backend is not configured fail with a
+/// configuration error instead of returning canned output.
+pub struct ServerModules {
+    /// The CLI-backed clients still take a lifecycle; they never call through it
+    lifecycle: Arc<LifecycleManager>,
+    containers: Option<ContainerClient>,
+    /// Docker Engine API client, preferred over the CLI when the daemon answers
+    #[cfg(feature = "containers")]
+    docker: Option<DockerEngine>,
+    kubeconfig: Option<String>,
+    /// Kubernetes REST API client, used instead of kubectl when configured
+    #[cfg(feature = "containers")]
+    kube_api: Option<Arc<KubeApiClient>>,
+    namespace: String,
+    database_urls: BTreeMap<String, String>,
+    database_pool: PoolConfig,
+    database_safety: HashMap<String, QuerySafety>,
+    sqlite: SqliteConfig,
+    /// Where `execute_query` may write `output_path` files
+    export_dir: Option<PathBuf>,
+    databases: Mutex<HashMap<String, Arc<dyn Database>>>,
+    home_assistant: Option<Arc<HomeAssistantClient>>,
+    /// Presence rules over the maps module's geofences
+    geofences: Arc<GeofenceEngine>,
+    /// Leak and failed-appliance alerts, when Home Assistant is configured
+    safety: Option<Arc<SafetyMonitor>>,
+    dns_filters: Option<Arc<DnsFilters>>,
+    media: Option<Arc<MediaServers>>,
+    photos: Option<Arc<Immich>>,
+    meals: Option<Arc<MealPlanner>>,
+    /// Imported Garmin and Fitbit data
+    health: Arc<HealthData>,
+    /// Bank transactions and budgets
+    ledger: Arc<Ledger>,
+    /// Scanned receipts, recorded in the ledger
+    expenses: Arc<Expenses>,
+    /// Homelab hardware inventory
+    assets: Arc<AssetRegistry>,
+    /// UPS monitoring and shutdown, when a UPS is configured
+    ups: Option<Arc<UpsMonitor>>,
+    power: Arc<PowerController>,
+    /// Reverse proxies, when any is configured
+    proxy: Option<Arc<ProxyManager>>,
+    n8n: Option<Arc<N8n>>,
+    alpaca: Option<AlpacaClient>,
+    sectors: HashMap<String, String>,
+    crypto: Arc<dyn CryptoExchange>,
+    grafana: Option<GrafanaAlerting>,
+    prometheus: Option<MonitoringModule>,
+    jaeger: Option<MonitoringModule>,
+    /// Alerts from integrations without an alert history of their own
+    alert_store: Arc<AlertStore>,
+    uptime_kuma: Option<Arc<UptimeKuma>>,
+    /// Elasticsearch, Loki and Splunk, searched together by `search_logs`
+    log_backends: Vec<Arc<dyn LogSearch>>,
+    /// Elastic Security and Sentinel detection rules
+    detections: Vec<Arc<dyn DetectionRules>>,
+    /// Memories, logs, Notion and Slack, searched together by `search_everything`
+    search_sources: Vec<Arc<dyn SearchSource>>,
+    /// Database tables searched alongside them, connected on first use
+    search_tables: Vec<FullTextTable>,
+    search_timeout: Duration,
+    memory: Option<Arc<MemoryClient>>,
+    summarization: Option<SummarizationConfig>,
+    /// Schedulers started for the modules above, stopped by `shutdown`
+    background: Vec<tokio::task::JoinHandle<()>>,
+}
+
+/// A configured table, searched through the shared database connections
+struct TableSearch {
+    modules: Arc<ServerModules>,
+    name: String,
+    table: FullTextTable,
+}
+
+#[async_trait]
+impl SearchSource for TableSearch {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+        let (db, database) = match &self.table.database {
+            Some(database) => {
+                self.modules
+                    .database_in(&self.table.provider, database)
+                    .await?
+            }
+            None => (self.modules.database(&self.table.provider).await?, None),
+        };
+        let (sql, params) = self.table.sql(query);
+        let result = db.execute_with_params(&sql, &params, database).await?;
+        Ok(self.table.hits(&result, &query.terms()))
+    }
+}
+
+impl ServerModules {
+    /// Instantiate module clients from configuration
+    pub async fn from_config(config: &Config) -> Result<Self> {
+        let lifecycle = Arc::new(LifecycleManager::new(Box::new(
+            crate::transport::MockTransport::new(),
+        )
+            as Box<dyn crate::transport::Transport + Send + Sync>));
+
+        let containers = match ContainerClient::new(Arc::clone(&lifecycle)).await {
+            Ok(client) => Some(client),
+            Err(e) => {
+                tracing::info!("Container tools disabled: {}", e);
+                None
+            }
+        };
+
+        #[cfg(feature = "containers")]
+        let docker = match DockerEngine::from_env() {
+            Ok(engine) => match engine.ping().await {
+                Ok(()) => Some(engine),
+                Err(e) => {
+                    tracing::info!("Docker Engine API unavailable, using the CLI: {}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                tracing::warn!("Invalid Docker connection settings: {}", e);
+                None
+            }
+        };
+
+        let infrastructure = config.infrastructure.as_ref();
+        let kubeconfig = infrastructure
+            .and_then(|i| i.kubeconfig_path.as_ref())
+            .map(|p| p.to_string_lossy().into_owned());
+        let namespace = infrastructure
+            .and_then(|i| i.default_namespace.clone())
+            .unwrap_or_else(|| "default".to_string());
+        let kube_backend = infrastructure
+            .map(|i| i.kubernetes_backend)
+            .unwrap_or_default();
+
+        #[cfg(feature = "containers")]
+        let kube_api = match kube_backend {
+            KubernetesBackend::Api => {
+                let api_config = KubeApiConfig {
+                    kubeconfig_path: infrastructure.and_then(|i| i.kubeconfig_path.clone()),
+                    context: None,
+                    in_cluster: infrastructure.is_some_and(|i| i.in_cluster),
+                };
+                Some(Arc::new(KubeApiClient::connect(&api_config).await?))
+            }
+            KubernetesBackend::Kubectl => None,
+        };
+        #[cfg(not(feature = "containers"))]
+        if kube_backend == KubernetesBackend::Api {
+            tracing::warn!("Kubernetes API backend needs the containers feature, using kubectl");
+        }
+
+        let mut database_urls: BTreeMap<String, String> = DATABASE_URL_VARS
+            .iter()
+            .filter_map(|(provider, var)| {
+                std::env::var(var)
+                    .ok()
+                    .map(|url| (provider.to_string(), url))
+            })
+            .collect();
+        if let Some(database) = &config.database {
+            database_urls.extend(database.connections.clone());
+        }
+        let database_pool = config
+            .database
+            .as_ref()
+            .map(|d| d.pool.clone())
+            .unwrap_or_default();
+        let database_safety = config
+            .database
+            .as_ref()
+            .map(|d| d.safety.clone())
+            .unwrap_or_default();
+        let sqlite = config
+            .database
+            .as_ref()
+            .map(|d| d.sqlite.clone())
+            .unwrap_or_default();
+        let export_dir = config.database.as_ref().and_then(|d| d.export_dir.clone());
+
+        let home_assistant = config
+            .smart_home
+            .as_ref()
+            .and_then(|s| s.home_assistant.clone())
+            .or_else(|| {
+                Some(HomeAssistantConfig {
+                    url: std::env::var("HOME_ASSISTANT_URL").ok()?,
+                    token: std::env::var("HOME_ASSISTANT_TOKEN").ok()?,
+                    transport_type: HomeAssistantTransportType::Http,
+                })
+            });
+        let home_assistant = match home_assistant {
+            Some(ha) => Some(Arc::new(
+                HomeAssistantClient::new(ha, Arc::clone(&lifecycle)).await?,
+            )),
+            None => None,
+        };
+        let fences = match &home_assistant {
+            Some(ha) => GeofenceManager::new().with_home_assistant(Arc::clone(ha)),
+            None => GeofenceManager::new(),
+        };
+        let geofences = Arc::new(GeofenceEngine::new(
+            Arc::new(fences),
+            RoutingClient::new(RoutingConfig::default())?,
+            home_assistant.clone(),
+        ));
+        let channels = config
+            .collaboration
+            .as_ref()
+            .map(|c| c.channels.clone())
+            .unwrap_or_default();
+        let notifier = if channels.is_empty() {
+            None
+        } else {
+            Some(Arc::new(Notifier::new(channels)?))
+        };
+        let safety = match &home_assistant {
+            Some(ha) => {
+                let safety_config = config
+                    .smart_home
+                    .as_ref()
+                    .and_then(|s| s.safety.clone())
+                    .unwrap_or_default();
+                let has_rules = !safety_config.rules.is_empty();
+                let mut monitor = SafetyMonitor::new(Arc::clone(ha), safety_config);
+                if let Some(notifier) = &notifier {
+                    monitor = monitor.with_notifier(Arc::clone(notifier));
+                }
+                let monitor = Arc::new(monitor);
+                if has_rules {
+                    monitor.start().await;
+                }
+                Some(monitor)
+            }
+            None => None,
+        };
+        let dns_servers = config
+            .smart_home
+            .as_ref()
+            .map(|s| s.dns_filters.clone())
+            .filter(|servers| !servers.is_empty())
+            .unwrap_or_else(DnsFilterServer::from_env);
+        let dns_filters = if dns_servers.is_empty() {
+            None
+        } else {
+            Some(Arc::new(DnsFilters::new(dns_servers)?))
+        };
+        let media_servers = config
+            .smart_home
+            .as_ref()
+            .map(|s| s.media.clone())
+            .filter(|servers| !servers.is_empty())
+            .unwrap_or_else(MediaServerConfig::from_env);
+        let media = if media_servers.is_empty() {
+            None
+        } else {
+            Some(Arc::new(MediaServers::new(media_servers)?))
+        };
+        let photos = match config
+            .smart_home
+            .as_ref()
+            .and_then(|s| s.photos.clone())
+            .or_else(PhotoLibraryConfig::from_env)
+        {
+            Some(library) => Some(Arc::new(Immich::new(library)?)),
+            None => None,
+        };
+        let meals = match config
+            .smart_home
+            .as_ref()
+            .and_then(|s| s.recipes.clone())
+            .or_else(RecipeServerConfig::from_env)
+        {
+            Some(recipes) => {
+                let mut planner = MealPlanner::new(recipes)?;
+                if let Some(ha) = &home_assistant {
+                    planner = planner.with_home_assistant(Arc::clone(ha));
+                }
+                if let Some(notifier) = &notifier {
+                    planner = planner.with_notifier(Arc::clone(notifier));
+                }
+                Some(Arc::new(planner))
+            }
+            None => None,
+        };
+        let assets_config = infrastructure
+            .and_then(|i| i.assets.clone())
+            .unwrap_or_default();
+        let reminders = notifier.is_some() && !assets_config.channels.is_empty();
+        let mut assets = AssetRegistry::open(assets_config).await?;
+        if let Some(notifier) = &notifier {
+            assets = assets.with_notifier(Arc::clone(notifier));
+        }
+        let assets = Arc::new(assets);
+        let health = Arc::new(
+            HealthData::open(
+                config
+                    .analytics
+                    .as_ref()
+                    .and_then(|a| a.health.clone())
+                    .unwrap_or_default(),
+            )
+            .await?,
+        );
+        let mut ledger = Ledger::open(
+            config
+                .finance
+                .as_ref()
+                .and_then(|f| f.ledger.clone())
+                .unwrap_or_default(),
+        )
+        .await?;
+        let expenses_config = config
+            .finance
+            .as_ref()
+            .and_then(|f| f.expenses.clone())
+            .unwrap_or_default();
+        let llm_config = LlmConfig::default();
+        let llm: Option<Arc<dyn LlmProvider>> = if llm_config.is_configured() {
+            Some(Arc::new(OpenAiCompatibleProvider::new(llm_config)?))
+        } else {
+            None
+        };
+        if let Some(llm) = &llm {
+            ledger = ledger.with_llm(Arc::clone(llm));
+        }
+        let ledger = Arc::new(ledger);
+        let mut expenses = Expenses::open(expenses_config, Arc::clone(&ledger)).await?;
+        if let Some(llm) = llm {
+            expenses = expenses.with_llm(llm);
+        }
+        let expenses = Arc::new(expenses);
+        let mut background = Vec::new();
+        if reminders {
+            background.push(Arc::clone(&assets).start_scheduler());
+        }
+        let power = Arc::new(PowerController::new(
+            infrastructure
+                .and_then(|i| i.power.clone())
+                .unwrap_or_default(),
+            Arc::clone(&assets),
+        )?);
+        let ups = match infrastructure
+            .and_then(|i| i.ups.clone())
+            .or_else(UpsConfig::from_env)
+            .filter(|u| !u.units.is_empty())
+        {
+            Some(ups_config) => {
+                let mut monitor = UpsMonitor::new(ups_config)?;
+                if let Some(notifier) = &notifier {
+                    monitor = monitor.with_notifier(Arc::clone(notifier));
+                }
+                if let Some(otel) = config
+                    .monitoring
+                    .as_ref()
+                    .and_then(|m| m.opentelemetry.clone())
+                {
+                    monitor = monitor.with_metrics(MonitoringModule::new(
+                        MonitoringConfig {
+                            opentelemetry: Some(otel),
+                            ..MonitoringConfig::default()
+                        },
+                        Arc::clone(&lifecycle),
+                    ));
+                }
+                let monitor = Arc::new(monitor);
+                background.push(Arc::clone(&monitor).start_scheduler());
+                Some(monitor)
+            }
+            None => None,
+        };
+        let proxy = match infrastructure
+            .and_then(|i| i.proxy.clone())
+            .filter(|p| !p.is_empty())
+        {
+            Some(proxy_config) => Some(Arc::new(ProxyManager::new(proxy_config)?)),
+            None => None,
+        };
+
+        let n8n = config
+            .collaboration
+            .as_ref()
+            .and_then(|c| c.n8n.clone())
+            .or_else(N8nConfig::from_env)
+            .map(|n| N8n::new(n).map(Arc::new))
+            .transpose()?;
+
+        let alpaca = config
+            .finance
+            .as_ref()
+            .and_then(|f| f.alpaca.clone())
+            .or_else(AlpacaConfig::from_env)
+            .map(AlpacaClient::new)
+            .transpose()?;
+        let sectors = config
+            .finance
+            .as_ref()
+            .map(|f| f.sectors.clone())
+            .unwrap_or_default();
+        let crypto = crypto::connect(
+            &config
+                .finance
+                .as_ref()
+                .and_then(|f| f.crypto.clone())
+                .unwrap_or_else(CryptoConfig::from_env),
+        )?;
+
+        let grafana = config
+            .monitoring
+            .as_ref()
+            .and_then(|m| m.grafana.clone())
+            .or_else(GrafanaConfig::from_env)
+            .map(|g| GrafanaAlerting::new(&g))
+            .transpose()?;
+        let prometheus = config
+            .monitoring
+            .as_ref()
+            .and_then(|m| m.prometheus.clone())
+            .or_else(PrometheusConfig::from_env)
+            .map(|p| {
+                MonitoringModule::new(
+                    MonitoringConfig {
+                        prometheus: Some(p),
+                        ..MonitoringConfig::default()
+                    },
+                    Arc::clone(&lifecycle),
+                )
+            });
+        let jaeger = config
+            .monitoring
+            .as_ref()
+            .and_then(|m| m.jaeger.clone())
+            .filter(|j| j.query_url.is_some())
+            .or_else(JaegerConfig::from_env)
+            .map(|j| {
+                MonitoringModule::new(
+                    MonitoringConfig {
+                        jaeger: Some(j),
+                        ..MonitoringConfig::default()
+                    },
+                    Arc::clone(&lifecycle),
+                )
+            });
+        let monitoring = config.monitoring.as_ref();
+        let alert_store = Arc::new(
+            AlertStore::open(
+                monitoring
+                    .and_then(|m| m.alert_store.clone())
+                    .or_else(|| std::env::var_os("ALERT_STORE_PATH").map(PathBuf::from)),
+            )
+            .await?,
+        );
+        let uptime_kuma = monitoring
+            .and_then(|m| m.uptime_kuma.clone())
+            .or_else(UptimeKumaConfig::from_env)
+            .map(|u| {
+                let uptime_kuma = Arc::new(UptimeKuma::new(u));
+                background.push(Arc::clone(&uptime_kuma).start_mirror(Arc::clone(&alert_store)));
+                uptime_kuma
+            });
+        let log_backends = logs::backends(&MonitoringConfig {
+            elasticsearch: monitoring
+                .and_then(|m| m.elasticsearch.clone())
+                .or_else(ElasticsearchConfig::from_env),
+            loki: monitoring
+                .and_then(|m| m.loki.clone())
+                .or_else(LokiConfig::from_env),
+            splunk: monitoring
+                .and_then(|m| m.splunk.clone())
+                .filter(|s| s.search_url.is_some())
+                .or_else(SplunkConfig::from_env),
+            ..MonitoringConfig::default()
+        })?;
+        let detections = detections::backends(&MonitoringConfig {
+            elasticsearch: monitoring
+                .and_then(|m| m.elasticsearch.clone())
+                .or_else(ElasticsearchConfig::from_env),
+            sentinel: monitoring
+                .and_then(|m| m.sentinel.clone())
+                .or_else(SentinelConfig::from_env),
+            ..MonitoringConfig::default()
+        })?;
+
+        let memory = match std::env::var("MEMORY_DATABASE_URL") {
+            Ok(url) => match MemoryClient::new_with_postgres(Arc::clone(&lifecycle), url).await {
+                Ok(client) => Some(Arc::new(client)),
+                Err(e) => {
+                    tracing::warn!("Memory store unavailable: {}", e);
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+
+        let search_config = config.search.clone().unwrap_or_default();
+        let mut search_sources: Vec<Arc<dyn SearchSource>> = Vec::new();
+        if let Some(memory) = &memory {
+            search_sources.push(Arc::new(MemorySource::new(Arc::clone(memory))));
+        }
+        search_sources.extend(
+            log_backends
+                .iter()
+                .map(|b| Arc::new(LogSource::new(Arc::clone(b))) as Arc<dyn SearchSource>),
+        );
+        if let Some(notion) = search_config.notion.clone().or_else(NotionConfig::from_env) {
+            search_sources.push(Arc::new(NotionSearch::new(notion)?));
+        }
+        if let Some(slack) = search_config.slack.clone().or_else(SlackConfig::from_env) {
+            search_sources.push(Arc::new(SlackSearch::new(slack)?));
+        }
+
+        let summarization = config.ai.as_ref().and_then(|ai| ai.summarization.clone());
+
+        Ok(Self {
+            lifecycle,
+            containers,
+            #[cfg(feature = "containers")]
+            docker,
+            kubeconfig,
+            #[cfg(feature = "containers")]
+            kube_api,
+            namespace,
+            database_urls,
+            database_pool,
+            database_safety,
+            sqlite,
+            export_dir,
+            databases: Mutex::new(HashMap::new()),
+            home_assistant,
+            geofences,
+            safety,
+            dns_filters,
+            media,
+            photos,
+            meals,
+            health,
+            ledger,
+            expenses,
+            assets,
+            ups,
+            power,
+            proxy,
+            n8n,
+            alpaca,
+            sectors,
+            crypto,
+            grafana,
+            prometheus,
+            jaeger,
+            alert_store,
+            uptime_kuma,
+            log_backends,
+            detections,
+            search_sources,
+            search_timeout: search_config.source_timeout(),
+            search_tables: search_config.tables,
+            memory,
+            summarization,
+            background,
+        })
+    }
+
+    /// Stop the schedulers and watchers these modules started, once they
+    /// have been replaced
+    pub async fn shutdown(&self) {
+        for task in &self.background {
+            task.abort();
+        }
+        if let Some(safety) = &self.safety {
+            safety.stop().await;
+        }
+        self.geofences.stop().await;
+    }
+
+    fn containers(&self) -> Result<&ContainerClient> {
+        self.containers.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "No container runtime available",
+                "Install Docker or Podman and make sure it is on PATH",
+            )
+        })
+    }
+
+    fn kubernetes(&self) -> Result<KubernetesClient<'_>> {
+        let client = KubernetesClient::new(&self.lifecycle, self.kubeconfig.as_deref(), None)?;
+        #[cfg(feature = "containers")]
+        let client = match &self.kube_api {
+            Some(api) => client.with_api(Arc::clone(api)),
+            None => client,
+        };
+        Ok(client)
+    }
+
+    fn home_assistant(&self) -> Result<&HomeAssistantClient> {
+        self.home_assistant.as_deref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Home Assistant is not configured",
+                "Set smart_home.home_assistant in the config file, or HOME_ASSISTANT_URL and HOME_ASSISTANT_TOKEN",
+            )
+        })
+    }
+
+    fn safety(&self) -> Result<&Arc<SafetyMonitor>> {
+        self.safety.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Utility safety alerts need Home Assistant",
+                "Set smart_home.home_assistant in the config file, or HOME_ASSISTANT_URL and HOME_ASSISTANT_TOKEN",
+            )
+        })
+    }
+
+    fn alpaca(&self) -> Result<&AlpacaClient> {
+        self.alpaca.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Alpaca is not configured",
+                "Set finance.alpaca in the config file, or APCA_API_KEY_ID and APCA_API_SECRET_KEY",
+            )
+        })
+    }
+
+    fn grafana(&self) -> Result<&GrafanaAlerting> {
+        self.grafana.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Grafana is not configured",
+                "Set monitoring.grafana in the config file, or GRAFANA_URL and GRAFANA_API_KEY",
+            )
+        })
+    }
+
+    fn prometheus(&self) -> Result<&MonitoringModule> {
+        self.prometheus.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Prometheus is not configured",
+                "Set monitoring.prometheus in the config file, or PROMETHEUS_URL",
+            )
+        })
+    }
+
+    fn jaeger(&self) -> Result<&MonitoringModule> {
+        self.jaeger.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Jaeger is not configured",
+                "Set monitoring.jaeger.query_url in the config file, or JAEGER_QUERY_URL",
+            )
+        })
+    }
+
+    /// Configured sectors with `overrides` from a call taking precedence
+    fn sectors(&self, overrides: HashMap<String, String>) -> HashMap<String, String> {
+        let mut sectors = self.sectors.clone();
+        sectors.extend(
+            overrides
+                .into_iter()
+                .map(|(symbol, sector)| (symbol.to_uppercase(), sector)),
+        );
+        sectors
+    }
+
+    /// Connection for a provider, opened on first use
+    async fn database(&self, provider: &str) -> Result<Arc<dyn Database>> {
+        let mut databases = self.databases.lock().await;
+        if let Some(database) = databases.get(provider) {
+            return Ok(Arc::clone(database));
+        }
+        let url = self.database_urls.get(provider).ok_or_else(|| {
+            let var = DATABASE_URL_VARS
+                .iter()
+                .find(|(p, _)| *p == provider)
+                .map(|(_, var)| format!(" or {}", var))
+                .unwrap_or_default();
+            Error::config_with_suggestion(
+                format!("No connection configured for {}", provider),
+                format!("Set database.connections.{}{}", provider, var),
+            )
+        })?;
+        let database = connect_with_pool(provider, url, &self.database_pool).await?;
+        databases.insert(provider.to_string(), Arc::clone(&database));
+        Ok(database)
+    }
+
+    /// Connection for a provider and database, where SQLite databases are files
+    ///
+    /// Also returns the database name to pass on, which SQLite does not need.
+    async fn database_in<'a>(
+        &self,
+        provider: &str,
+        database: &'a str,
+    ) -> Result<(Arc<dyn Database>, Option<&'a str>)> {
+        if provider != "sqlite" {
+            return Ok((self.database(provider).await?, Some(database)));
+        }
+        let path = self.sqlite.resolve(database)?;
+        let key = format!("sqlite:{}", path.display());
+        let mut databases = self.databases.lock().await;
+        if let Some(database) = databases.get(&key) {
+            return Ok((Arc::clone(database), None));
+        }
+        let opened = sqlite::open(&path, self.sqlite.read_only).await?;
+        databases.insert(key, Arc::clone(&opened));
+        Ok((opened, None))
+    }
+
+    /// Guard for a provider, with read-only SQLite files guarded by default
+    fn query_safety(&self, provider: &str) -> Option<QuerySafety> {
+        self.database_safety.get(provider).cloned().or_else(|| {
+            (provider == "sqlite" && self.sqlite.read_only).then(|| QuerySafety {
+                read_only: true,
+                ..QuerySafety::default()
+            })
+        })
+    }
+
+    /// Register a typed handler that receives these modules
+    fn route<P, F, Fut>(
+        self: &Arc<Self>,
+        registry: &mut ToolRegistry,
+        definition: ToolDefinition,
+        handler: F,
+    ) -> Result<()>
+    where
+        P: DeserializeOwned + Send + 'static,
+        F: Fn(Arc<Self>, P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value>> + Send + 'static,
+    {
+        let modules = Arc::clone(self);
+        registry.register_typed(definition, move |params: P| {
+            handler(Arc::clone(&modules), params)
+        })
+    }
+
+    /// Register a typed streaming handler that receives these modules
+    fn route_streaming<P, F, Fut>(
+        self: &Arc<Self>,
+        registry: &mut ToolRegistry,
+        definition: ToolDefinition,
+        handler: F,
+    ) -> Result<()>
+    where
+        P: DeserializeOwned + Send + 'static,
+        F: Fn(Arc<Self>, P, ToolStream) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value>> + Send + 'static,
+    {
+        let modules = Arc::clone(self);
+        let handler = Arc::new(handler);
+        registry.register_streaming(definition, move |arguments, stream| {
+            let modules = Arc::clone(&modules);
+            let handler = Arc::clone(&handler);
+            async move {
+                let params: P = serde_json::from_value(arguments)
+                    .map_err(|e| Error::validation(format!("Invalid arguments: {}", e)))?;
+                handler(modules, params, stream).await
+            }
+        })
+    }
+
+    /// Memory store, when one is configured
+    pub fn memory(&self) -> Option<Arc<MemoryClient>> {
+        self.memory.clone()
+    }
+
+    /// Register the tools backed by these modules
+    pub fn register(self: &Arc<Self>, registry: &mut ToolRegistry) -> Result<()> {
+        // Infrastructure tools
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_docker_containers",
+                "List all Docker containers with their status",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "all": {"type": "boolean", "description": "Include stopped containers", "default": false}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ListContainersParams| async move { modules.list_containers(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_container_logs",
+                "Get logs from a Docker container",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "container_id": {"type": "string", "description": "Container ID or name"},
+                        "lines": {"type": "integer", "minimum": 1, "description": "Number of lines to fetch", "default": 100}
+                    },
+                    "required": ["container_id"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ContainerLogsParams| async move { modules.container_logs(p).await },
+        )?;
+        #[cfg(feature = "containers")]
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "inspect_container",
+                "Show a Docker container's state, command, environment and labels",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "container_id": {"type": "string", "description": "Container ID or name"}
+                    },
+                    "required": ["container_id"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ContainerParams| async move { modules.inspect_container(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_container_stats",
+                "Get CPU, memory, network and block I/O usage of a Docker container",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "container_id": {"type": "string", "description": "Container ID or name"}
+                    },
+                    "required": ["container_id"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ContainerParams| async move { modules.container_stats(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "exec_in_container",
+                "Run a command inside a running Docker container",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "container_id": {"type": "string", "description": "Container ID or name"},
+                        "command": {"type": "array", "items": {"type": "string"}, "minItems": 1, "description": "Program and arguments, not run through a shell"}
+                    },
+                    "required": ["container_id", "command"]
+                }),
+                None,
+            ),
+            |modules, p: ExecParams| async move { modules.exec_in_container(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "start_container",
+                "Start a stopped Docker container",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "container_id": {"type": "string", "description": "Container ID or name"}
+                    },
+                    "required": ["container_id"]
+                }),
+                None,
+            ),
+            |modules, p: ContainerParams| async move { modules.start_container(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "stop_container",
+                "Stop a running Docker container",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "container_id": {"type": "string", "description": "Container ID or name"},
+                        "timeout_secs": {"type": "integer", "minimum": 0, "description": "Seconds to wait before killing the container"}
+                    },
+                    "required": ["container_id"]
+                }),
+                None,
+            ),
+            |modules, p: StopContainerParams| async move { modules.stop_container(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_k8s_pods",
+                "List Kubernetes pods in a namespace",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "namespace": {"type": "string", "description": "Kubernetes namespace (default: the configured namespace)"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ListPodsParams| async move { modules.list_pods(p).await },
+        )?;
+        self.route_streaming(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_pod_logs",
+                "Get logs from a Kubernetes pod",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "pod_name": {"type": "string", "description": "Pod name"},
+                        "namespace": {"type": "string", "description": "Kubernetes namespace (default: the configured namespace)"},
+                        "lines": {"type": "integer", "minimum": 1, "description": "Number of lines to fetch", "default": 100}
+                    },
+                    "required": ["pod_name"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: PodLogsParams, stream| async move { modules.pod_logs(p, stream).await },
+        )?;
+
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_helm_releases",
+                "List Helm releases with their chart, revision and status",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "namespace": {"type": "string", "description": "Kubernetes namespace (default: the configured namespace)"},
+                        "all_namespaces": {"type": "boolean", "description": "List releases in every namespace", "default": false}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ListHelmReleasesParams| async move { modules.list_helm_releases(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_helm_release_history",
+                "Show the revisions of a Helm release",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "release": {"type": "string", "description": "Release name"},
+                        "namespace": {"type": "string", "description": "Kubernetes namespace (default: the configured namespace)"},
+                        "max": {"type": "integer", "minimum": 1, "description": "Only the most recent revisions"}
+                    },
+                    "required": ["release"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: HelmHistoryParams| async move { modules.helm_release_history(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_helm_values",
+                "Show the values of a Helm release",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "release": {"type": "string", "description": "Release name"},
+                        "namespace": {"type": "string", "description": "Kubernetes namespace (default: the configured namespace)"},
+                        "revision": {"type": "integer", "minimum": 1, "description": "Revision to inspect (default: the current one)"},
+                        "all": {"type": "boolean", "description": "Include chart defaults, giving the values the manifests were rendered with", "default": false}
+                    },
+                    "required": ["release"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: HelmValuesParams| async move { modules.helm_values(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "rollback_helm_release",
+                "Roll a Helm release back to an earlier revision",
+                "infrastructure",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "release": {"type": "string", "description": "Release name"},
+                        "namespace": {"type": "string", "description": "Kubernetes namespace (default: the configured namespace)"},
+                        "revision": {"type": "integer", "minimum": 1, "description": "Revision to restore (default: the previous one)"},
+                        "wait": {"type": "boolean", "description": "Wait until the restored resources are ready", "default": false}
+                    },
+                    "required": ["release"]
+                }),
+                None,
+            ),
+            |modules, p: HelmRollbackParams| async move { modules.rollback_helm_release(p).await },
+        )?;
+
+        // Database tools
+        let provider = json!({
+            "type": "string",
+            "enum": ["postgresql", "mongodb", "supabase", "sqlite"],
+            "description": "Database provider"
+        });
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_databases",
+                "List all available databases",
+                "database",
+                json!({
+                    "type": "object",
+                    "properties": {"provider": provider}
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ListDatabasesParams| async move { modules.list_databases(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "execute_query",
+                "Execute a database query",
+                "database",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "provider": provider,
+                        "database": {"type": "string", "description": "Database name, or the database file for sqlite"},
+                        "query": {"type": "string", "description": "Query to execute, with $1, $2, ... placeholders for params"},
+                        "params": {"type": "array", "description": "Values bound to the query's placeholders; strings bind as text, so cast them as needed (e.g. $1::date)"},
+                        "transaction_id": {"type": "string", "description": "Run inside this transaction from begin_transaction"},
+                        "confirm": {"type": "boolean", "default": false, "description": "Required to run statements that change data on a read-only provider set to confirm writes"},
+                        "output_path": {"type": "string", "description": "Also write the result rows to this file, relative to database.export_dir"},
+                        "format": {"type": "string", "enum": ["jsonl", "csv", "sql"], "default": "jsonl", "description": "Format of the file written to output_path"}
+                    },
+                    "required": ["provider", "database", "query"]
+                }),
+                None,
+            ),
+            |modules, p: QueryParams| async move { modules.execute_query(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "begin_transaction",
+                "Open a database transaction for later execute_query calls",
+                "database",
+                json!({
+                    "type": "object",
+                    "properties": {"provider": provider},
+                    "required": ["provider"]
+                }),
+                None,
+            ),
+            |modules, p: BeginTransactionParams| async move { modules.begin_transaction(p).await },
+        )?;
+        let transaction = json!({
+            "type": "object",
+            "properties": {
+                "provider": provider,
+                "transaction_id": {"type": "string", "description": "Transaction from begin_transaction"}
+            },
+            "required": ["provider", "transaction_id"]
+        });
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "commit_transaction",
+                "Commit a database transaction",
+                "database",
+                transaction.clone(),
+                None,
+            ),
+            |modules, p: TransactionParams| async move { modules.commit_transaction(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "rollback_transaction",
+                "Roll back a database transaction, discarding its changes",
+                "database",
+                transaction,
+                None,
+            ),
+            |modules, p: TransactionParams| async move { modules.rollback_transaction(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_tables",
+                "List tables in a database",
+                "database",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "provider": provider,
+                        "database": {"type": "string", "description": "Database name, or the database file for sqlite"}
+                    },
+                    "required": ["provider", "database"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ListTablesParams| async move { modules.list_tables(p).await },
+        )?;
+        let tables = json!({
+            "type": "array",
+            "items": {"type": "string"},
+            "description": "Only these tables; all of them when empty"
+        });
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "describe_schema",
+                "Describe tables with their columns, types, indexes and foreign keys, to write correct queries",
+                "database",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "provider": provider,
+                        "database": {"type": "string", "description": "Schema (PostgreSQL), or the database file for sqlite"},
+                        "tables": tables
+                    },
+                    "required": ["provider", "database"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: DescribeSchemaParams| async move { modules.describe_schema(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "generate_erd",
+                "Draw an entity-relationship diagram of a schema as Mermaid or Graphviz DOT text",
+                "database",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "provider": provider,
+                        "database": {"type": "string", "description": "Schema (PostgreSQL), or the database file for sqlite"},
+                        "tables": tables,
+                        "format": {"type": "string", "enum": ["mermaid", "dot"], "default": "mermaid", "description": "Diagram language"}
+                    },
+                    "required": ["provider", "database"]
+                }),
+                None,
+            ),
+            |modules, p: ErdParams| async move { modules.generate_erd(p).await },
+        )?;
+
+        // Smart Home tools
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_turn_on",
+                "Turn on a Home Assistant device",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": {"type": "string", "description": "Entity ID of the device"},
+                        "brightness": {"type": "integer", "minimum": 0, "maximum": 255, "description": "Brightness level (0-255)"},
+                        "color": {"type": "string", "description": "Color name or hex code"}
+                    },
+                    "required": ["entity_id"]
+                }),
+                None,
+            ),
+            |modules, p: TurnOnParams| async move { modules.turn_on(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_turn_off",
+                "Turn off a Home Assistant device",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": {"type": "string", "description": "Entity ID of the device"}
+                    },
+                    "required": ["entity_id"]
+                }),
+                None,
+            ),
+            |modules, p: TurnOffParams| async move {
+                let result = modules.home_assistant()?.turn_off(&p.entity_id).await?;
+                Ok(call_result(
+                    i18n::text("messages.device.turned_off", &[("entity", &p.entity_id)]),
+                    result,
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_set_temperature",
+                "Set climate control temperature",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": {"type": "string", "description": "Climate entity ID"},
+                        "temperature": {"type": "number", "description": "Target temperature"}
+                    },
+                    "required": ["entity_id", "temperature"]
+                }),
+                None,
+            ),
+            |modules, p: TemperatureParams| async move {
+                let result = modules
+                    .home_assistant()?
+                    .set_temperature(&p.entity_id, p.temperature)
+                    .await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.climate.set",
+                        &[("entity", &p.entity_id), ("temperature", &p.temperature)],
+                    ),
+                    result,
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_get_state",
+                "Get the current state and attributes of a Home Assistant entity",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": {"type": "string", "description": "Entity ID, such as sensor.hallway_temperature"}
+                    },
+                    "required": ["entity_id"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: EntityStateParams| async move { modules.entity_state(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_list_entities",
+                "List Home Assistant entities with their current state",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "domain": {"type": "string", "description": "Only entities in this domain, such as light or sensor"},
+                        "area": {"type": "string", "description": "Only entities in this area, by area ID or name"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ListEntitiesParams| async move { modules.list_entities(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_call_service",
+                "Call any Home Assistant service",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "domain": {"type": "string", "description": "Service domain, such as light, cover or script"},
+                        "service": {"type": "string", "description": "Service name, such as turn_on or open_cover"},
+                        "entity_id": {"type": "string", "description": "Entity to target"},
+                        "data": {"type": "object", "description": "Service data, such as {\"brightness\": 128}"},
+                        "target": {"type": "object", "description": "Targets by entity_id, device_id or area_id"}
+                    },
+                    "required": ["domain", "service"]
+                }),
+                None,
+            ),
+            |modules, p: CallServiceParams| async move { modules.call_service(p).await },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_devices",
+                "List smart home devices from any provider (Zigbee, Z-Wave, MQTT, Wi-Fi) with what they can do and their current values",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "area": {"type": "string", "description": "Only devices in this room, by area ID or name"},
+                        "capability": {"type": "string", "enum": ["switchable", "dimmable", "thermostat", "sensor"], "description": "Only devices with this capability"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ListDevicesParams| async move {
+                let socket = modules.home_assistant()?.socket().await?;
+                let devices = Devices::new(socket)
+                    .list(p.area.as_deref(), p.capability)
+                    .await?;
+                let mut text = i18n::text("messages.devices.listed", &[("count", &devices.len())]);
+                for device in &devices {
+                    text.push_str(&format!("\n{}", device_text(device)));
+                }
+                Ok(call_result(text, json!({ "devices": devices })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "set_device_power",
+                "Turn a light, switch, plug or fan on or off, whichever provider it uses",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "device": {"type": "string", "description": "Device ID or name from list_devices"},
+                        "on": {"type": "boolean", "description": "true to turn on, false to turn off"}
+                    },
+                    "required": ["device", "on"]
+                }),
+                None,
+            ),
+            |modules, p: DevicePowerParams| async move {
+                let socket = modules.home_assistant()?.socket().await?;
+                let device = Devices::new(socket).set_power(&p.device, p.on).await?;
+                let key = if p.on {
+                    "messages.device.turned_on"
+                } else {
+                    "messages.device.turned_off"
+                };
+                Ok(call_result(
+                    i18n::text(key, &[("entity", &device.name)]),
+                    json!({ "device": device.id, "on": p.on }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "set_device_level",
+                "Set a light's brightness or a fan's speed in percent",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "device": {"type": "string", "description": "Device ID or name from list_devices"},
+                        "level": {"type": "integer", "minimum": 0, "maximum": 100, "description": "Percent; 0 turns it off"}
+                    },
+                    "required": ["device", "level"]
+                }),
+                None,
+            ),
+            |modules, p: DeviceLevelParams| async move {
+                let socket = modules.home_assistant()?.socket().await?;
+                let device = Devices::new(socket).set_level(&p.device, p.level).await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.device.level",
+                        &[("device", &device.name), ("level", &p.level)],
+                    ),
+                    json!({ "device": device.id, "level": p.level }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "set_room_temperature",
+                "Set the target temperature of every thermostat in a room, whichever provider they use",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "area": {"type": "string", "description": "Room by area ID or name, such as Living room"},
+                        "temperature": {"type": "number", "description": "Target temperature in the unit Home Assistant uses"}
+                    },
+                    "required": ["area", "temperature"]
+                }),
+                None,
+            ),
+            |modules, p: RoomTemperatureParams| async move {
+                let socket = modules.home_assistant()?.socket().await?;
+                let thermostats = Devices::new(socket)
+                    .set_room_temperature(&p.area, p.temperature)
+                    .await?;
+                let names: Vec<&str> = thermostats.iter().map(|d| d.name.as_str()).collect();
+                Ok(call_result(
+                    i18n::text(
+                        "messages.room.temperature_set",
+                        &[
+                            ("area", &p.area),
+                            ("temperature", &p.temperature),
+                            ("devices", &names.join(", ")),
+                        ],
+                    ),
+                    json!({ "area": p.area, "temperature": p.temperature, "devices": thermostats }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "suggest_automations",
+                "Find things done by hand at about the same time on the same days, such as lights dimmed every evening, and propose Home Assistant automations and scenes for them as YAML",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "area": {"type": "string", "description": "Only devices in this room, by area ID or name"},
+                        "days": {"type": "integer", "minimum": 7, "maximum": 90, "description": "Days of history to mine", "default": 28},
+                        "min_occurrences": {"type": "integer", "minimum": 2, "description": "Fewest days a habit must have happened on", "default": 4},
+                        "window_minutes": {"type": "integer", "minimum": 5, "maximum": 120, "description": "How far apart times may be and still count as the same habit", "default": 30}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: SuggestAutomationsParams| async move {
+                let socket = modules.home_assistant()?.socket().await?;
+                let options = PatternOptions {
+                    days: p.days,
+                    window_minutes: p.window_minutes,
+                    min_occurrences: p.min_occurrences,
+                    ..PatternOptions::default()
+                };
+                let (suggestions, actions) = Patterns::new(socket)
+                    .suggest(p.area.as_deref(), options)
+                    .await?;
+                let mut text = i18n::text(
+                    "messages.automations.suggested",
+                    &[
+                        ("count", &suggestions.len()),
+                        ("actions", &actions),
+                        ("days", &p.days),
+                    ],
+                );
+                for suggestion in &suggestions {
+                    text.push_str(&format!(
+                        "\n\n{} ({}% of {})\n",
+                        suggestion.alias,
+                        (suggestion.confidence * 100.0).round(),
+                        weekdays_text(&suggestion.weekdays)
+                    ));
+                    if let Some(scene) = &suggestion.scene_yaml {
+                        text.push_str(&format!("# scenes.yaml\n- {}", indent_yaml(scene)));
+                    }
+                    text.push_str(&format!(
+                        "# automations.yaml\n- {}",
+                        indent_yaml(&suggestion.automation_yaml)
+                    ));
+                }
+                Ok(call_result(text, json!({ "suggestions": suggestions, "manual_actions": actions })))
+            },
+        )?;
+        // Geofences come from the maps module; presence is evaluated by the engine
+        let fences = Arc::clone(self.geofences.geofences());
+        let definitions = fences
+            .get_tool_definitions()
+            .into_iter()
+            .filter(|d| d.name != "evaluate_device_tracker")
+            .collect();
+        registry.register_all(definitions, move |name, arguments| {
+            let fences = Arc::clone(&fences);
+            async move { fences.execute_tool(&name, arguments).await }
+        })?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "add_geofence_rule",
+                "Run actions when trackers enter or leave a geofence, or come within a routed travel time of it (e.g. preheat the house when its owner is 10 minutes away)",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "geofence_id": {"type": "string", "description": "Geofence created with define_geofence"},
+                        "on": {"type": "string", "enum": ["enter", "exit", "approaching"]},
+                        "entities": {"type": "array", "items": {"type": "string"}, "description": "device_tracker or person entities, default all"},
+                        "within_minutes": {"type": "number", "description": "Travel time that counts as approaching"},
+                        "mode": {"type": "string", "enum": ["driving", "cycling", "walking"], "default": "driving"},
+                        "actions": {
+                            "type": "array",
+                            "items": {"type": "object"},
+                            "description": "{\"type\": \"event\", \"event_type\"} fires a Home Assistant event, {\"type\": \"service\", \"service\": \"climate.set_temperature\", \"data\", \"target\"} calls a service, {\"type\": \"webhook\", \"url\"} posts the event JSON"
+                        }
+                    },
+                    "required": ["id", "geofence_id", "on"]
+                }),
+                None,
+            ),
+            |modules, rule: GeofenceRule| async move {
+                modules.geofences.add_rule(rule.clone()).await?;
+                // Without Home Assistant, positions come from evaluate_device_tracker
+                if modules.home_assistant.is_some() {
+                    modules.geofences.start().await?;
+                }
+                Ok(call_result(
+                    i18n::text(
+                        "messages.geofence.rule_saved",
+                        &[
+                            ("id", &rule.id),
+                            ("on", &rule.on.as_str()),
+                            ("geofence", &rule.geofence_id),
+                        ],
+                    ),
+                    json!({ "rule": rule }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_geofence_rules",
+                "List geofence rules",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, _: Value| async move {
+                let rules = modules.geofences.list_rules().await;
+                let mut text =
+                    i18n::text("messages.geofence.rules_listed", &[("count", &rules.len())]);
+                for rule in &rules {
+                    text.push_str(&format!(
+                        "\n{}: {} {}",
+                        rule.id,
+                        rule.on.as_str(),
+                        rule.geofence_id
+                    ));
+                    if let Some(minutes) = rule.within_minutes {
+                        text.push_str(&format!(" ({} min)", minutes));
+                    }
+                }
+                Ok(call_result(text, json!({ "rules": rules })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "remove_geofence_rule",
+                "Remove a geofence rule",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            |modules, p: RuleIdParams| async move {
+                let rule = modules.geofences.remove_rule(&p.id).await?;
+                Ok(call_result(
+                    i18n::text("messages.geofence.rule_removed", &[("id", &rule.id)]),
+                    json!({ "id": rule.id }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "evaluate_device_tracker",
+                "Evaluate a device_tracker or person position against geofences and run the matching rules",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": {"type": "string", "description": "e.g. device_tracker.pixel"},
+                        "state": {"type": "object", "description": "Home Assistant state object; fetched from Home Assistant when omitted"},
+                        "lat": {"type": "number"},
+                        "lon": {"type": "number"}
+                    },
+                    "required": ["entity_id"]
+                }),
+                None,
+            ),
+            |modules, p: EvaluateTrackerParams| async move {
+                let state = match (p.state, p.lat, p.lon) {
+                    (Some(state), _, _) => state,
+                    (None, Some(lat), Some(lon)) => json!({ "latitude": lat, "longitude": lon }),
+                    _ => modules.home_assistant()?.get_entity(&p.entity_id).await?,
+                };
+                let events = modules
+                    .geofences
+                    .handle_update(&p.entity_id, &state)
+                    .await?;
+                Ok(call_result(
+                    geofence_events_text(&events),
+                    json!({ "events": events }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "geofence_events",
+                "Recent geofence enter, exit and approaching events, newest last",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 100, "default": 20}
+                    }
+                }),
+                None,
+            ),
+            |modules, p: GeofenceEventsParams| async move {
+                let events = modules.geofences.recent_events(p.limit).await;
+                Ok(call_result(
+                    geofence_events_text(&events),
+                    json!({ "events": events }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "add_safety_rule",
+                "Watch a water flow, power or gas sensor for leaks and failed appliances, posting critical alerts to the collaboration channels",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"},
+                        "entity_id": {"type": "string", "description": "Numeric sensor, e.g. sensor.main_water_flow or sensor.freezer_power"},
+                        "name": {"type": "string", "description": "Name used in alerts, e.g. Chest freezer"},
+                        "preset": {
+                            "type": "string",
+                            "enum": ["water_leak", "appliance_off", "power_spike"],
+                            "description": "water_leak: flow for 2 hours without a break; appliance_off: under 5 W for 3 hours; power_spike: far above the recent median"
+                        },
+                        "utility": {"type": "string", "enum": ["water", "power", "gas"]},
+                        "detector": {
+                            "type": "object",
+                            "description": "Instead of a preset: {\"type\": \"sustained\", \"above\" or \"below\", \"minutes\"} or {\"type\": \"spike\", \"window\", \"threshold\", \"min_change\"}"
+                        },
+                        "severity": {"type": "string", "enum": ["info", "warning", "critical"], "default": "critical"}
+                    },
+                    "required": ["id", "entity_id"]
+                }),
+                None,
+            ),
+            |modules, p: AddSafetyRuleParams| async move {
+                let (detector, utility) = match (p.detector, p.preset) {
+                    (Some(detector), preset) => (detector, p.utility.or(preset.map(|p| p.utility()))),
+                    (None, Some(preset)) => (preset.detector(), p.utility.or(Some(preset.utility()))),
+                    (None, None) => {
+                        return Err(Error::validation_with_field(
+                            "Give a preset or a detector",
+                            "preset",
+                        ))
+                    }
+                };
+                let utility = utility.ok_or_else(|| {
+                    Error::validation_with_field("utility is required with a detector", "utility")
+                })?;
+                let rule = SafetyRule {
+                    id: p.id,
+                    entity_id: p.entity_id,
+                    name: p.name,
+                    utility,
+                    detector,
+                    severity: p.severity.unwrap_or(Severity::Critical),
+                };
+                let safety = modules.safety()?;
+                safety.add_rule(rule.clone()).await?;
+                safety.start().await;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.safety.rule_saved",
+                        &[("id", &rule.id), ("entity", &rule.entity_id)],
+                    ),
+                    json!({ "rule": rule }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_safety_rules",
+                "List utility safety rules and the alerts still open",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, _: Value| async move {
+                let safety = modules.safety()?;
+                let rules = safety.list_rules().await;
+                let alerts = safety.active_alerts().await;
+                let mut text =
+                    i18n::text("messages.safety.rules_listed", &[("count", &rules.len())]);
+                for rule in &rules {
+                    text.push_str(&format!(
+                        "\n{}: {} ({})",
+                        rule.id,
+                        rule.entity_id,
+                        rule.detector.kind()
+                    ));
+                }
+                if !alerts.is_empty() {
+                    text.push_str("\n\n");
+                    text.push_str(&safety_alerts_text(&alerts));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "rules": rules, "alerts": alerts }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "remove_safety_rule",
+                "Remove a utility safety rule",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "string"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            |modules, p: RuleIdParams| async move {
+                let rule = modules.safety()?.remove_rule(&p.id).await?;
+                Ok(call_result(
+                    i18n::text("messages.safety.rule_removed", &[("id", &rule.id)]),
+                    json!({ "id": rule.id }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "check_utility_safety",
+                "Check every utility safety rule now, posting any new alerts",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+                None,
+            ),
+            |modules, _: Value| async move {
+                let alerts = modules.safety()?.check().await?;
+                Ok(call_result(
+                    safety_alerts_text(&alerts),
+                    json!({ "alerts": alerts }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_list_automations",
+                "List Home Assistant automations with whether they are enabled and when they last ran",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "area": {"type": "string", "description": "Only automations in this area, by area ID or name"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ListAutomationsParams| async move {
+                let automations = modules
+                    .home_assistant()?
+                    .list_automations(p.area.as_deref())
+                    .await?;
+                let enabled = automations.iter().filter(|a| a.enabled).count();
+                let mut text = i18n::text(
+                    "messages.automations.listed",
+                    &[("count", &automations.len()), ("enabled", &enabled)],
+                );
+                for automation in &automations {
+                    text.push_str(&format!(
+                        "\n{} ({}): {}",
+                        automation.name,
+                        automation.entity_id,
+                        if automation.enabled { "on" } else { "off" }
+                    ));
+                }
+                Ok(call_result(text, json!({ "automations": automations })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_trigger_automation",
+                "Run a Home Assistant automation's actions now",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": {"type": "string", "description": "Automation entity ID, such as automation.porch_lights"},
+                        "skip_condition": {"type": "boolean", "default": false, "description": "Run the actions even if the conditions do not hold"},
+                        "variables": {"type": "object", "description": "Variables available to the actions"}
+                    },
+                    "required": ["entity_id"]
+                }),
+                None,
+            ),
+            |modules, p: TriggerAutomationParams| async move {
+                let result = modules
+                    .home_assistant()?
+                    .trigger_automation(&p.entity_id, p.skip_condition, p.variables)
+                    .await?;
+                Ok(call_result(
+                    i18n::text("messages.automation.triggered", &[("entity", &p.entity_id)]),
+                    result,
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_enable_automation",
+                "Enable a Home Assistant automation so its triggers fire again",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": {"type": "string", "description": "Automation entity ID"}
+                    },
+                    "required": ["entity_id"]
+                }),
+                None,
+            ),
+            |modules, p: TurnOffParams| async move {
+                let result = modules
+                    .home_assistant()?
+                    .set_automation_enabled(&p.entity_id, true)
+                    .await?;
+                Ok(call_result(
+                    i18n::text("messages.automation.enabled", &[("entity", &p.entity_id)]),
+                    result,
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_disable_automation",
+                "Disable a Home Assistant automation; it stays disabled until enabled again",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": {"type": "string", "description": "Automation entity ID"}
+                    },
+                    "required": ["entity_id"]
+                }),
+                None,
+            ),
+            |modules, p: TurnOffParams| async move {
+                let result = modules
+                    .home_assistant()?
+                    .set_automation_enabled(&p.entity_id, false)
+                    .await?;
+                Ok(call_result(
+                    i18n::text("messages.automation.disabled", &[("entity", &p.entity_id)]),
+                    result,
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_activate_scene",
+                "Activate a Home Assistant scene",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "entity_id": {"type": "string", "description": "Scene entity ID, such as scene.movie_night; ha_list_entities with domain scene lists them"},
+                        "transition": {"type": "number", "minimum": 0, "description": "Seconds to fade lights into the scene"}
+                    },
+                    "required": ["entity_id"]
+                }),
+                None,
+            ),
+            |modules, p: SceneParams| async move {
+                let result = modules
+                    .home_assistant()?
+                    .activate_scene(&p.entity_id, p.transition)
+                    .await?;
+                Ok(call_result(
+                    i18n::text("messages.scene.activated", &[("entity", &p.entity_id)]),
+                    result,
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_render_template",
+                "Render a Home Assistant template, such as {{ states('sensor.outdoor_temperature') }}",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "template": {"type": "string", "description": "Jinja template using Home Assistant's template functions"},
+                        "variables": {"type": "object", "description": "Variables available to the template"}
+                    },
+                    "required": ["template"]
+                }),
+                None,
+            ),
+            |modules, p: TemplateParams| async move {
+                let rendered = modules
+                    .home_assistant()?
+                    .render_template(&p.template, p.variables)
+                    .await?;
+                Ok(call_result(rendered.clone(), json!({ "result": rendered })))
+            },
+        )?;
+
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_list_assist_pipelines",
+                "List Home Assistant Assist pipelines with their conversation, speech-to-text, text-to-speech and wake word engines",
+                "smart_home",
+                json!({"type": "object", "properties": {}}),
+                None,
+            )
+            .read_only(),
+            |modules, _: Value| async move {
+                let socket = modules.home_assistant()?.socket().await?;
+                let pipelines = Assist::new(socket).pipelines().await?;
+                let mut text = i18n::text(
+                    "messages.assist.pipelines",
+                    &[("count", &pipelines.pipelines.len())],
+                );
+                for pipeline in &pipelines.pipelines {
+                    text.push_str(&format!(
+                        "\n{} ({}): {}, {}",
+                        pipeline.name,
+                        pipeline.id,
+                        pipeline.language,
+                        pipeline.conversation_engine.as_deref().unwrap_or("-")
+                    ));
+                    if pipelines.preferred_pipeline.as_ref() == Some(&pipeline.id) {
+                        text.push_str(" *");
+                    }
+                }
+                Ok(call_result(text, json!(pipelines)))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_assist_process",
+                "Send text to a Home Assistant Assist pipeline as if it had been spoken, and return the agent's answer",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "text": {"type": "string", "description": "Request in natural language, e.g. Turn off the kitchen light"},
+                        "pipeline": {"type": "string", "description": "Pipeline ID; the preferred pipeline when omitted"},
+                        "conversation_id": {"type": "string", "description": "Conversation ID from an earlier answer, to follow up"},
+                        "speak": {"type": "boolean", "description": "Also generate speech for the answer", "default": false}
+                    },
+                    "required": ["text"]
+                }),
+                None,
+            ),
+            |modules, p: AssistTextParams| async move {
+                let socket = modules.home_assistant()?.socket().await?;
+                let options = RunOptions {
+                    pipeline: p.pipeline,
+                    conversation_id: p.conversation_id,
+                    speak: p.speak,
+                };
+                let run = Assist::new(socket).run_text(&p.text, &options).await?;
+                let response = run.response().unwrap_or_default();
+                Ok(call_result(
+                    response.speech.clone(),
+                    json!({ "response": response, "tts_url": run.tts_url(), "events": run.events }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_assist_announce",
+                "Speak a message on a Home Assistant voice satellite, optionally listening for a reply",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "satellite": {"type": "string", "description": "Satellite entity, e.g. assist_satellite.kitchen"},
+                        "message": {"type": "string", "description": "Text to speak"},
+                        "start_conversation": {"type": "boolean", "description": "Listen for a reply after speaking", "default": false}
+                    },
+                    "required": ["satellite", "message"]
+                }),
+                None,
+            ),
+            |modules, p: AnnounceParams| async move {
+                let socket = modules.home_assistant()?.socket().await?;
+                let assist = Assist::new(socket);
+                if p.start_conversation {
+                    assist.start_conversation(&p.satellite, &p.message).await?;
+                } else {
+                    assist.announce(&p.satellite, &p.message).await?;
+                }
+                Ok(call_result(
+                    i18n::text("messages.assist.announced", &[("satellite", &p.satellite)]),
+                    json!({ "satellite": p.satellite, "message": p.message }),
+                ))
+            },
+        )?;
+        self.route_streaming(
+            registry,
+            ToolDefinition::from_json_schema(
+                "ha_wait_for_voice_command",
+                "Wait for a voice satellite to hear its wake word and return what was said, to answer with ha_assist_announce",
+                "smart_home",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "satellite": {"type": "string", "description": "Only this satellite entity; any satellite when omitted"},
+                        "pipeline": {"type": "string", "description": "Pipeline the satellite uses; the preferred pipeline when omitted"},
+                        "timeout_secs": {"type": "integer", "minimum": 1, "maximum": MAX_VOICE_WAIT_SECS, "description": "How long to wait for a wake word", "default": 300}
+                    }
+                }),
+                None,
+            ),
+            |modules, p: VoiceCommandParams, stream| async move {
+                modules.wait_for_voice_command(p, stream).await
+            },
+        )?;
+        // Pi-hole and AdGuard Home
+        if let Some(dns_filters) = &self.dns_filters {
+            let dns_filters = Arc::clone(dns_filters);
+            registry.register_all(
+                dns_filters.get_tool_definitions(),
+                move |name, arguments| {
+                    let dns_filters = Arc::clone(&dns_filters);
+                    async move { dns_filters.execute_tool(&name, arguments).await }
+                },
+            )?;
+        }
+        // Jellyfin and Plex
+        if let Some(media) = &self.media {
+            let media = Arc::clone(media);
+            registry.register_all(media.get_tool_definitions(), move |name, arguments| {
+                let media = Arc::clone(&media);
+                async move { media.execute_tool(&name, arguments).await }
+            })?;
+        }
+        // Immich
+        if let Some(photos) = &self.photos {
+            let photos = Arc::clone(photos);
+            registry.register_all(photos.get_tool_definitions(), move |name, arguments| {
+                let photos = Arc::clone(&photos);
+                async move { photos.execute_tool(&name, arguments).await }
+            })?;
+        }
+        // Tandoor and Mealie
+        if let Some(meals) = &self.meals {
+            let meals = Arc::clone(meals);
+            registry.register_all(meals.get_tool_definitions(), move |name, arguments| {
+                let meals = Arc::clone(&meals);
+                async move { meals.execute_tool(&name, arguments).await }
+            })?;
+        }
+        // Garmin and Fitbit
+        let health = Arc::clone(&self.health);
+        registry.register_all(health.get_tool_definitions(), move |name, arguments| {
+            let health = Arc::clone(&health);
+            async move { health.execute_tool(&name, arguments).await }
+        })?;
+
+        // Personal finance ledger
+        let ledger = Arc::clone(&self.ledger);
+        registry.register_all(ledger.get_tool_definitions(), move |name, arguments| {
+            let ledger = Arc::clone(&ledger);
+            async move { ledger.execute_tool(&name, arguments).await }
+        })?;
+        let expenses = Arc::clone(&self.expenses);
+        registry.register_all(expenses.get_tool_definitions(), move |name, arguments| {
+            let expenses = Arc::clone(&expenses);
+            async move { expenses.execute_tool(&name, arguments).await }
+        })?;
+
+        // Finance tools
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_account_info",
+                "Get the Alpaca trading account's status, equity, cash and buying power",
+                "finance",
+                json!({"type": "object", "properties": {}}),
+                None,
+            )
+            .read_only(),
+            |modules, _: Value| async move {
+                let alpaca = modules.alpaca()?;
+                let account = alpaca.get_account().await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.account.summary",
+                        &[
+                            ("status", &account.status),
+                            ("equity", &account.equity),
+                            ("currency", &account.currency),
+                            ("cash", &account.cash),
+                            ("buying_power", &account.buying_power),
+                        ],
+                    ),
+                    json!({ "account": account, "paper": alpaca.is_paper() }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_positions",
+                "List open positions in the Alpaca account with unrealized profit and loss",
+                "finance",
+                json!({"type": "object", "properties": {}}),
+                None,
+            )
+            .read_only(),
+            |modules, _: Value| async move {
+                let positions = modules.alpaca()?.get_positions().await?;
+                let mut text =
+                    i18n::text("messages.positions.listed", &[("count", &positions.len())]);
+                for position in &positions {
+                    text.push_str(&format!(
+                        "\n{} {} @ {}, P/L {}",
+                        position.symbol,
+                        position.qty,
+                        position.avg_entry_price,
+                        position.unrealized_pl
+                    ));
+                }
+                Ok(call_result(text, json!({ "positions": positions })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_stock_quote",
+                "Get the latest bid and ask for a stock",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "symbol": {"type": "string", "description": "Stock ticker symbol (e.g., AAPL, MSFT)"}
+                    },
+                    "required": ["symbol"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: SymbolParams| async move {
+                let quote = modules.alpaca()?.get_stock_quote(&p.symbol).await?;
+                Ok(call_result(quote_text(&quote), json!({ "quote": quote })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_stock_bars",
+                "Get daily open, high, low, close and volume for a stock",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "symbol": {"type": "string", "description": "Stock ticker symbol (e.g., AAPL, MSFT)"},
+                        "days": {"type": "integer", "minimum": 1, "description": "Calendar days to look back", "default": 30}
+                    },
+                    "required": ["symbol"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: BarsParams| async move {
+                let bars = modules.alpaca()?.get_stock_bars(&p.symbol, p.days).await?;
+                let symbol = p.symbol.to_uppercase();
+                let mut text = i18n::text(
+                    "messages.bars.listed",
+                    &[("count", &bars.len()), ("symbol", &symbol)],
+                );
+                for bar in &bars {
+                    text.push_str(&format!(
+                        "\n{} O {} H {} L {} C {} V {}",
+                        bar.timestamp.format("%Y-%m-%d"),
+                        bar.open,
+                        bar.high,
+                        bar.low,
+                        bar.close,
+                        bar.volume
+                    ));
+                }
+                Ok(call_result(text, json!({ "bars": bars })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_orders",
+                "List Alpaca orders by status",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "status": {"type": "string", "enum": ["open", "closed", "all"], "description": "Order status to filter by", "default": "open"},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 500, "description": "Maximum number of orders to return", "default": 50}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: OrdersParams| async move {
+                let orders = modules.alpaca()?.get_orders(p.status, p.limit).await?;
+                let mut text = i18n::text("messages.orders.listed", &[("count", &orders.len())]);
+                for order in &orders {
+                    text.push_str(&format!(
+                        "\n{} {} {} {}: {:?}",
+                        order.id,
+                        order.side.as_str(),
+                        order.qty.as_deref().or(order.notional.as_deref()).unwrap_or("?"),
+                        order.symbol,
+                        order.status
+                    ));
+                }
+                Ok(call_result(text, json!({ "orders": orders })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "place_order",
+                "Place a stock order; orders go to the paper account unless live trading is configured",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "symbol": {"type": "string", "description": "Stock ticker symbol"},
+                        "side": {"type": "string", "enum": ["buy", "sell"], "description": "Order side"},
+                        "type": {"type": "string", "enum": ["market", "limit", "stop", "stop_limit", "trailing_stop"], "description": "Order type", "default": "market"},
+                        "time_in_force": {"type": "string", "enum": ["day", "gtc", "ioc", "fok", "opg", "cls"], "description": "How long the order stays open", "default": "day"},
+                        "qty": {"type": "number", "exclusiveMinimum": 0, "description": "Number of shares, fractional for market day orders"},
+                        "notional": {"type": "number", "exclusiveMinimum": 0, "description": "Dollar amount to trade instead of qty"},
+                        "limit_price": {"type": "number", "exclusiveMinimum": 0, "description": "Limit price for limit and stop_limit orders"},
+                        "stop_price": {"type": "number", "exclusiveMinimum": 0, "description": "Stop price for stop and stop_limit orders"},
+                        "trail_percent": {"type": "number", "exclusiveMinimum": 0, "description": "Trail in percent for trailing_stop orders"},
+                        "client_order_id": {"type": "string", "description": "Your own ID for the order"},
+                        "extended_hours": {"type": "boolean", "description": "Allow filling outside regular hours (limit day orders)", "default": false},
+                        "confirm": {"type": "boolean", "description": "Required to trade on a live account", "default": false}
+                    },
+                    "required": ["symbol", "side"]
+                }),
+                None,
+            ),
+            |modules, p: PlaceOrderParams| async move {
+                let alpaca = modules.alpaca()?;
+                let order = alpaca.place_order(&p.order, p.confirm).await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.order.placed",
+                        &[
+                            ("side", &order.side.as_str()),
+                            ("symbol", &order.symbol),
+                            ("id", &order.id),
+                        ],
+                    ),
+                    json!({ "order": order, "paper": alpaca.is_paper() }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "cancel_order",
+                "Cancel an open Alpaca order",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "order_id": {"type": "string", "description": "Order ID to cancel"}
+                    },
+                    "required": ["order_id"]
+                }),
+                None,
+            ),
+            |modules, p: OrderIdParams| async move {
+                modules.alpaca()?.cancel_order(&p.order_id).await?;
+                Ok(call_result(
+                    i18n::text("messages.order.canceled", &[("id", &p.order_id)]),
+                    json!({ "order_id": p.order_id, "canceled": true }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "replace_order",
+                "Change the quantity, prices or time in force of an open Alpaca order",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "order_id": {"type": "string", "description": "Order ID to replace"},
+                        "qty": {"type": "number", "exclusiveMinimum": 0, "description": "New number of shares"},
+                        "limit_price": {"type": "number", "exclusiveMinimum": 0, "description": "New limit price"},
+                        "stop_price": {"type": "number", "exclusiveMinimum": 0, "description": "New stop price"},
+                        "time_in_force": {"type": "string", "enum": ["day", "gtc", "ioc", "fok", "opg", "cls"], "description": "New time in force"},
+                        "client_order_id": {"type": "string", "description": "Your own ID for the new order"},
+                        "confirm": {"type": "boolean", "description": "Required to trade on a live account", "default": false}
+                    },
+                    "required": ["order_id"]
+                }),
+                None,
+            ),
+            |modules, p: ReplaceOrderParams| async move {
+                let alpaca = modules.alpaca()?;
+                let order = alpaca
+                    .replace_order(&p.order_id, &p.changes, p.confirm)
+                    .await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.order.replaced",
+                        &[("old", &p.order_id), ("id", &order.id)],
+                    ),
+                    json!({ "order": order, "replaced": p.order_id, "paper": alpaca.is_paper() }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_crypto_quote",
+                "Get the last price, best bid and ask and 24 hour range and volume for a crypto pair",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "pair": {"type": "string", "description": "Trading pair with a separator, e.g. BTC-USDT or ETH/BTC"}
+                    },
+                    "required": ["pair"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: PairParams| async move {
+                let ticker = modules.crypto.ticker(&p.pair).await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.crypto.quote",
+                        &[
+                            ("pair", &ticker.pair),
+                            ("exchange", &ticker.exchange),
+                            ("last", &ticker.last),
+                            ("bid", &ticker.bid),
+                            ("ask", &ticker.ask),
+                            ("change", &format!("{:+.2}", ticker.change_percent_24h)),
+                        ],
+                    ),
+                    json!({ "ticker": ticker }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_crypto_candles",
+                "Get open, high, low, close and volume candles for a crypto pair, oldest first",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "pair": {"type": "string", "description": "Trading pair with a separator, e.g. BTC-USDT or ETH/BTC"},
+                        "interval": {"type": "string", "enum": ["1m", "5m", "15m", "1h", "4h", "1d", "1w"], "description": "Candle length", "default": "1h"},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 1000, "description": "Number of most recent candles", "default": 100}
+                    },
+                    "required": ["pair"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: CandlesParams| async move {
+                let candles = modules
+                    .crypto
+                    .candles(&p.pair, p.interval, p.limit)
+                    .await?;
+                let mut text = i18n::text(
+                    "messages.crypto.candles",
+                    &[
+                        ("count", &candles.len()),
+                        ("interval", &p.interval.as_str()),
+                        ("pair", &p.pair.to_uppercase()),
+                    ],
+                );
+                for candle in candles.iter().rev().take(10) {
+                    text.push_str(&format!(
+                        "\n{} O {} H {} L {} C {} V {}",
+                        candle.open_time.format("%Y-%m-%d %H:%M"),
+                        candle.open,
+                        candle.high,
+                        candle.low,
+                        candle.close,
+                        candle.volume
+                    ));
+                }
+                Ok(call_result(text, json!({ "candles": candles })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_crypto_order_book",
+                "Get a snapshot of the best bids and asks for a crypto pair",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "pair": {"type": "string", "description": "Trading pair with a separator, e.g. BTC-USDT or ETH/BTC"},
+                        "depth": {"type": "integer", "minimum": 1, "maximum": 5000, "description": "Price levels per side", "default": 20}
+                    },
+                    "required": ["pair"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: OrderBookParams| async move {
+                let book = modules.crypto.order_book(&p.pair, p.depth).await?;
+                let best = |levels: &[crypto::BookLevel]| {
+                    levels
+                        .first()
+                        .map_or_else(|| "-".to_string(), |l| format!("{} x {}", l.price, l.size))
+                };
+                let spread = book
+                    .spread()
+                    .map_or_else(|| "-".to_string(), |s| s.to_string());
+                Ok(call_result(
+                    i18n::text(
+                        "messages.crypto.order_book",
+                        &[
+                            ("pair", &book.pair),
+                            ("bid", &best(&book.bids)),
+                            ("ask", &best(&book.asks)),
+                            ("spread", &spread),
+                            ("levels", &book.bids.len().max(book.asks.len())),
+                        ],
+                    ),
+                    json!({ "order_book": book }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_crypto_balances",
+                "List non-zero balances on the crypto exchange account; needs an API key",
+                "finance",
+                json!({"type": "object", "properties": {}}),
+                None,
+            )
+            .read_only(),
+            |modules, _: Value| async move {
+                let balances = modules.crypto.balances().await?;
+                let mut text = i18n::text(
+                    "messages.crypto.balances",
+                    &[
+                        ("count", &balances.len()),
+                        ("exchange", &modules.crypto.name()),
+                    ],
+                );
+                for balance in &balances {
+                    text.push_str(&format!(
+                        "\n{} {} free, {} locked",
+                        balance.asset, balance.free, balance.locked
+                    ));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "exchange": modules.crypto.name(), "balances": balances }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "analyze_portfolio",
+                "Break down the Alpaca positions by sector with exposure, unrealized profit and loss and concentration",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "sectors": {"type": "object", "additionalProperties": {"type": "string"}, "description": "Sector per symbol, added to finance.sectors from the config"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: AnalyzePortfolioParams| async move {
+                let sectors = modules.sectors(p.sectors);
+                let analysis = modules.alpaca()?.analyze_portfolio(&sectors).await?;
+                Ok(call_result(
+                    portfolio_text(&analysis),
+                    json!({ "analysis": analysis }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "get_risk_report",
+                "Report max drawdown, Sharpe ratio, volatility, value at risk and concentration warnings for the current Alpaca positions over past daily prices",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "days": {"type": "integer", "minimum": 7, "maximum": 3650, "description": "Calendar days of prices to replay the positions over", "default": 365},
+                        "risk_free_rate": {"type": "number", "description": "Yearly risk-free rate for the Sharpe ratio, e.g. 0.04", "default": 0},
+                        "max_position_weight": {"type": "number", "exclusiveMinimum": 0, "maximum": 1, "description": "Warn about positions above this share of gross exposure", "default": 0.2},
+                        "max_sector_weight": {"type": "number", "exclusiveMinimum": 0, "maximum": 1, "description": "Warn about sectors above this share of gross exposure", "default": 0.4},
+                        "sectors": {"type": "object", "additionalProperties": {"type": "string"}, "description": "Sector per symbol, added to finance.sectors from the config"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: RiskReportParams| async move {
+                let sectors = modules.sectors(p.sectors);
+                let (analysis, report) = modules
+                    .alpaca()?
+                    .risk_report(&sectors, p.days, p.risk_free_rate, p.limits)
+                    .await?;
+                let percent = |value: f64| format!("{:.2}", value * 100.0);
+                let mut text = i18n::text(
+                    "messages.risk.report",
+                    &[
+                        ("days", &report.observations),
+                        ("return", &percent(report.total_return)),
+                        ("volatility", &percent(report.annualized_volatility)),
+                        (
+                            "sharpe",
+                            &report
+                                .sharpe_ratio
+                                .map_or_else(|| "-".to_string(), |s| format!("{:.2}", s)),
+                        ),
+                        ("drawdown", &percent(report.max_drawdown)),
+                        ("var", &format!("{:.2}", report.value_at_risk_95)),
+                    ],
+                );
+                for warning in &report.warnings {
+                    text.push_str(&format!("\n- {}", warning));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "report": report, "analysis": analysis }),
+                ))
+            },
+        )?;
+        self.route_streaming(
+            registry,
+            ToolDefinition::from_json_schema(
+                "stream_quotes",
+                "Stream live quotes for stocks as progress notifications, then return the latest quote per symbol",
+                "finance",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "symbols": {"type": "array", "items": {"type": "string"}, "minItems": 1, "description": "Stock ticker symbols"},
+                        "duration_secs": {"type": "integer", "minimum": 1, "maximum": MAX_QUOTE_STREAM_SECS, "description": "How long to stay subscribed", "default": 30},
+                        "max_quotes": {"type": "integer", "minimum": 1, "description": "Stop after this many quotes"}
+                    },
+                    "required": ["symbols"]
+                }),
+                None,
+            ),
+            |modules, p: StreamQuotesParams, stream| async move {
+                modules.stream_quotes(p, stream).await
+            },
+        )?;
+
+        // Monitoring tools
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "prometheus_query",
+                "Evaluate a PromQL query at one point in time",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string", "description": "PromQL expression; find metric names with prometheus_label_values first"},
+                        "time": {"type": "string", "format": "date-time", "description": "Evaluation time; now when omitted"}
+                    },
+                    "required": ["query"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: PrometheusQueryParams| async move {
+                let result = modules
+                    .prometheus()?
+                    .prometheus_query(&p.query, p.time)
+                    .await?;
+                let mut text = i18n::text(
+                    "messages.prometheus.results",
+                    &[("count", &result.values.len())],
+                );
+                for value in result.values.iter().take(PROMETHEUS_TEXT_LINES) {
+                    text.push_str(&format!("\n{} {}", labels_text(&value.metric), value.value));
+                }
+                Ok(call_result(text, json!({ "result": result })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "prometheus_query_range",
+                "Evaluate a PromQL query over a recent time range",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string", "description": "PromQL expression"},
+                        "range": {"type": "string", "description": "How far back from now, e.g. 30m, 6h or 7d", "default": "1h"},
+                        "step": {"type": "string", "description": "Resolution, e.g. 15s, 1m or 1h", "default": "1m"}
+                    },
+                    "required": ["query"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: PrometheusRangeParams| async move {
+                let end = chrono::Utc::now();
+                let start = end - alerting::parse_duration(&p.range)?;
+                alerting::parse_duration(&p.step)?;
+                let result = modules
+                    .prometheus()?
+                    .prometheus_query_range(&p.query, start, end, &p.step)
+                    .await?;
+                let mut text = i18n::text(
+                    "messages.prometheus.range",
+                    &[("count", &result.values.len()), ("range", &p.range)],
+                );
+                for series in result.values.iter().take(PROMETHEUS_TEXT_LINES) {
+                    if let Some((at, value)) = series.values.last() {
+                        text.push_str(&format!(
+                            "\n{} {} @ {}",
+                            labels_text(&series.metric),
+                            value,
+                            at.format("%H:%M:%S")
+                        ));
+                    }
+                }
+                Ok(call_result(text, json!({ "result": result })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "prometheus_series",
+                "Find the series, with all their labels, that match PromQL selectors",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "match": {"type": "array", "items": {"type": "string"}, "minItems": 1, "description": "Series selectors, e.g. up or {job=\"node\"}"},
+                        "lookback": {"type": "string", "description": "Only series with samples in this recent window, e.g. 1h or 1d", "default": "1h"},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 10000, "description": "Most series to return", "default": 100}
+                    },
+                    "required": ["match"]
+                }),
+                None,
+            ),
+            |modules, p: SeriesParams| async move {
+                let end = chrono::Utc::now();
+                let start = end - alerting::parse_duration(&p.lookback)?;
+                let mut series = modules
+                    .prometheus()?
+                    .prometheus_series(&p.matchers, Some(start), Some(end))
+                    .await?;
+                let total = series.len();
+                series.truncate(p.limit);
+                let mut text = i18n::text(
+                    "messages.prometheus.series",
+                    &[("count", &total), ("shown", &series.len())],
+                );
+                for labels in series.iter().take(PROMETHEUS_TEXT_LINES) {
+                    text.push_str(&format!("\n{}", labels_text(labels)));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "total": total, "series": series }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "prometheus_label_values",
+                "List the values of a Prometheus label; the label __name__ lists metric names",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "label": {"type": "string", "description": "Label name, e.g. job, instance or __name__"},
+                        "match": {"type": "array", "items": {"type": "string"}, "description": "Only values on series matching these selectors, e.g. {job=\"node\"}"},
+                        "lookback": {"type": "string", "description": "Only values seen in this recent window, e.g. 1h or 1d", "default": "1h"}
+                    },
+                    "required": ["label"]
+                }),
+                None,
+            ),
+            |modules, p: LabelValuesParams| async move {
+                let end = chrono::Utc::now();
+                let start = end - alerting::parse_duration(&p.lookback)?;
+                let values = modules
+                    .prometheus()?
+                    .prometheus_label_values(&p.label, &p.matchers, Some(start), Some(end))
+                    .await?;
+                let mut text = i18n::text(
+                    "messages.prometheus.label_values",
+                    &[("count", &values.len()), ("label", &p.label)],
+                );
+                for value in values.iter().take(PROMETHEUS_TEXT_LINES) {
+                    text.push_str(&format!("\n{}", value));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "label": p.label, "values": values }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "prometheus_metadata",
+                "Describe Prometheus metrics: their type (counter, gauge, histogram, summary), help text and unit",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "metric": {"type": "string", "description": "Metric family name; all metrics when omitted"},
+                        "search": {"type": "string", "description": "Only metrics whose name or help contains this text, e.g. memory"}
+                    }
+                }),
+                None,
+            ),
+            |modules, p: MetadataParams| async move {
+                let mut metadata = modules
+                    .prometheus()?
+                    .prometheus_metadata(p.metric.as_deref())
+                    .await?;
+                if let Some(search) = p.search.map(|s| s.to_lowercase()) {
+                    metadata.retain(|m| {
+                        m.metric.to_lowercase().contains(&search)
+                            || m.help.to_lowercase().contains(&search)
+                    });
+                }
+                let mut text =
+                    i18n::text("messages.prometheus.metadata", &[("count", &metadata.len())]);
+                for entry in metadata.iter().take(PROMETHEUS_TEXT_LINES) {
+                    text.push_str(&format!("\n{} ({}): {}", entry.metric, entry.kind, entry.help));
+                }
+                Ok(call_result(text, json!({ "metadata": metadata })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "prometheus_targets",
+                "List the targets Prometheus scrapes with their health and last scrape error",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "health": {"type": "string", "enum": ["up", "down", "unknown"], "description": "Only targets in this health"}
+                    }
+                }),
+                None,
+            ),
+            |modules, p: TargetsParams| async move {
+                let mut targets = modules.prometheus()?.prometheus_targets().await?;
+                let up = targets.iter().filter(|t| t.health == "up").count();
+                let total = targets.len();
+                if let Some(health) = &p.health {
+                    targets.retain(|t| &t.health == health);
+                }
+                let mut text = i18n::text(
+                    "messages.prometheus.targets",
+                    &[("up", &up), ("count", &total)],
+                );
+                for target in &targets {
+                    text.push_str(&format!(
+                        "\n{} {} {}",
+                        target.scrape_pool, target.scrape_url, target.health
+                    ));
+                    if !target.last_error.is_empty() {
+                        text.push_str(&format!(": {}", target.last_error));
+                    }
+                }
+                Ok(call_result(text, json!({ "targets": targets })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "prometheus_rules",
+                "List Prometheus alerting and recording rules with their query, state and health",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "type": {"type": "string", "enum": ["alert", "record"], "description": "Only alerting or only recording rules"}
+                    }
+                }),
+                None,
+            ),
+            |modules, p: RulesParams| async move {
+                let groups = modules.prometheus()?.prometheus_rules(p.kind).await?;
+                let count: usize = groups.iter().map(|g| g.rules.len()).sum();
+                let mut text = i18n::text(
+                    "messages.prometheus.rules",
+                    &[("count", &count), ("groups", &groups.len())],
+                );
+                for group in &groups {
+                    for rule in &group.rules {
+                        text.push_str(&format!(
+                            "\n{}/{} ({}{}): {}",
+                            group.name,
+                            rule.name,
+                            rule.kind,
+                            rule.state
+                                .as_deref()
+                                .map_or_else(String::new, |s| format!(", {}", s)),
+                            rule.query
+                        ));
+                    }
+                }
+                Ok(call_result(text, json!({ "groups": groups })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "prometheus_alerts",
+                "List alerts Prometheus currently has pending or firing",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "state": {"type": "string", "enum": ["pending", "firing"], "description": "Only alerts in this state"}
+                    }
+                }),
+                None,
+            ),
+            |modules, p: AlertsParams| async move {
+                let mut alerts = modules.prometheus()?.prometheus_alerts().await?;
+                if let Some(state) = &p.state {
+                    alerts.retain(|a| &a.state == state);
+                }
+                let mut text = i18n::text("messages.prometheus.alerts", &[("count", &alerts.len())]);
+                for alert in &alerts {
+                    text.push_str(&format!(
+                        "\n{} ({}) {}",
+                        alert.name(),
+                        alert.state,
+                        labels_text(alert.labels.iter().filter(|(name, _)| *name != "alertname"))
+                    ));
+                    if let Some(since) = alert.active_at {
+                        text.push_str(&format!(" since {}", since.format("%Y-%m-%d %H:%M UTC")));
+                    }
+                }
+                Ok(call_result(text, json!({ "alerts": alerts })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "jaeger_list_services",
+                "List the services that report traces to Jaeger, or one service's operations",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "service": {"type": "string", "description": "List this service's operations instead"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: JaegerServicesParams| async move {
+                let jaeger = modules.jaeger()?;
+                let Some(service) = p.service else {
+                    let services = jaeger.jaeger_list_services().await?;
+                    let mut text =
+                        i18n::text("messages.jaeger.services", &[("count", &services.len())]);
+                    for service in &services {
+                        text.push_str(&format!("\n{}", service));
+                    }
+                    return Ok(call_result(text, json!({ "services": services })));
+                };
+                let operations = jaeger.jaeger_list_operations(&service).await?;
+                let mut text = i18n::text(
+                    "messages.jaeger.operations",
+                    &[("count", &operations.len()), ("service", &service)],
+                );
+                for operation in &operations {
+                    text.push_str(&format!("\n{}", operation));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "service": service, "operations": operations }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "jaeger_find_traces",
+                "Search Jaeger for recent traces of a service by operation, span tags and duration",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "service": {"type": "string", "description": "Service name from jaeger_list_services"},
+                        "operation": {"type": "string", "description": "Only traces with this operation"},
+                        "tags": {"type": "object", "additionalProperties": {"type": "string"}, "description": "Span tags that must all match, e.g. {\"http.status_code\": \"500\"} or {\"error\": \"true\"}"},
+                        "min_duration": {"type": "string", "description": "Only traces at least this long, e.g. 500ms or 2s"},
+                        "max_duration": {"type": "string", "description": "Only traces at most this long"},
+                        "lookback": {"type": "string", "description": "How far back to search, e.g. 1h or 2d", "default": "1h"},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 1500, "description": "Most traces to return", "default": 20}
+                    },
+                    "required": ["service"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: FindTracesParams| async move {
+                let end = chrono::Utc::now();
+                let query = TraceQuery {
+                    service: p.service,
+                    operation: p.operation,
+                    tags: p.tags,
+                    min_duration: p.min_duration,
+                    max_duration: p.max_duration,
+                    start: Some(end - alerting::parse_duration(&p.lookback)?),
+                    end: Some(end),
+                    limit: Some(p.limit),
+                };
+                let traces = modules.jaeger()?.jaeger_find_traces(&query).await?;
+                let mut text = i18n::text(
+                    "messages.jaeger.traces",
+                    &[("count", &traces.len()), ("service", &query.service)],
+                );
+                for trace in &traces {
+                    text.push_str(&format!("\n{}", trace_summary(trace)));
+                }
+                Ok(call_result(text, json!({ "traces": traces })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "jaeger_get_trace",
+                "Get one trace from Jaeger with all its spans, their timing, tags and errors",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "trace_id": {"type": "string", "description": "Hex trace ID, from jaeger_find_traces or a log line"}
+                    },
+                    "required": ["trace_id"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: GetTraceParams| async move {
+                let trace = modules.jaeger()?.jaeger_get_trace(&p.trace_id).await?;
+                let mut text = trace_summary(&trace);
+                text.push_str(&trace_tree_text(&trace));
+                Ok(call_result(text, json!({ "trace": trace })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "search_logs",
+                "Search Elasticsearch, Loki and Splunk at once for log lines containing all the given words, merged newest first with the backend each line came from",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "text": {"type": "string", "description": "Words that must all appear, case-insensitive; empty matches every line"},
+                        "lookback": {"type": "string", "description": "How far back to search, e.g. 15m or 2h", "default": "1h"},
+                        "start": {"type": "string", "format": "date-time", "description": "Start of the range instead of lookback"},
+                        "end": {"type": "string", "format": "date-time", "description": "End of the range, default now"},
+                        "severity": {"type": "string", "enum": ["debug", "info", "warning", "error", "critical"], "description": "Only lines at or above this level"},
+                        "sources": {"type": "array", "items": {"type": "string", "enum": ["elasticsearch", "loki", "splunk"]}, "description": "Only these backends, default all configured"},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 1000, "description": "Most lines to return", "default": 100}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: SearchLogsParams| async move {
+                let end = p.end.unwrap_or_else(chrono::Utc::now);
+                let query = LogQuery {
+                    text: p.text,
+                    start: match p.start {
+                        Some(start) => start,
+                        None => end - alerting::parse_duration(&p.lookback)?,
+                    },
+                    end,
+                    severity: p.severity.as_deref().map(str::parse::<LogSeverity>).transpose()?,
+                    limit: p.limit,
+                };
+                let backends: Vec<Arc<dyn LogSearch>> = modules
+                    .log_backends
+                    .iter()
+                    .filter(|b| p.sources.is_empty() || p.sources.iter().any(|s| s == b.name()))
+                    .cloned()
+                    .collect();
+                let result = logs::search_all(&backends, &query).await?;
+                let sources = result
+                    .sources
+                    .iter()
+                    .map(|(name, count)| format!("{} {}", name, count))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let mut text = i18n::text(
+                    "messages.logs.found",
+                    &[("count", &result.entries.len()), ("sources", &sources)],
+                );
+                for (name, error) in &result.errors {
+                    text.push_str(&format!(
+                        "\n{}",
+                        i18n::text("messages.logs.backend_failed", &[("source", name), ("error", error)])
+                    ));
+                }
+                for entry in &result.entries {
+                    text.push_str(&format!("\n{}", log_entry_text(entry)));
+                }
+                Ok(call_result(text, json!(result)))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "search_everything",
+                "Search memories, logs, database tables, Notion and Slack at once and return one ranked list, each hit attributed to the system it came from",
+                "search",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string", "description": "What to look for, e.g. a host, service, error or topic"},
+                        "sources": {"type": "array", "items": {"type": "string"}, "description": "Only these sources, e.g. memories, loki, notion, slack or a table's name; default all configured"},
+                        "lookback": {"type": "string", "description": "How far back to search logs and messages, e.g. 24h or 30d", "default": "24h"},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 100, "description": "Most hits to return", "default": 20}
+                    },
+                    "required": ["query"]
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: SearchEverythingParams| async move {
+                let query = SearchQuery {
+                    text: p.query,
+                    since: chrono::Utc::now() - alerting::parse_duration(&p.lookback)?,
+                    limit: p.limit,
+                };
+                let mut sources = modules.search_sources.clone();
+                sources.extend(modules.search_tables.iter().map(|table| {
+                    Arc::new(TableSearch {
+                        modules: Arc::clone(&modules),
+                        name: table.name(),
+                        table: table.clone(),
+                    }) as Arc<dyn SearchSource>
+                }));
+                if !p.sources.is_empty() {
+                    if let Some(unknown) = p
+                        .sources
+                        .iter()
+                        .find(|s| !sources.iter().any(|source| source.name() == s.as_str()))
+                    {
+                        return Err(Error::not_found_with_resource(
+                            "Search source not configured",
+                            "search_source",
+                            unknown,
+                        ));
+                    }
+                    sources.retain(|source| p.sources.iter().any(|s| s == source.name()));
+                }
+                let result = search::search_all(&sources, &query, modules.search_timeout).await?;
+                Ok(call_result(search_result_text(&result), json!(result)))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "siem_list_rules",
+                "List Elastic Security and Microsoft Sentinel detection rules with their severity, schedule and whether they are enabled",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "source": {"type": "string", "enum": ["elastic", "sentinel"], "description": "Only this SIEM, default all configured"},
+                        "enabled": {"type": "boolean", "description": "Only enabled or only disabled rules"},
+                        "text": {"type": "string", "description": "Only rules whose name, ID or query contains this, case-insensitive"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: SiemListRulesParams| async move {
+                let backends = match &p.source {
+                    None if !modules.detections.is_empty() => modules.detections.clone(),
+                    source => vec![Arc::clone(detections::select(
+                        &modules.detections,
+                        source.as_deref(),
+                    )?)],
+                };
+                let text_filter = p.text.to_lowercase();
+                let mut rules = Vec::new();
+                for backend in &backends {
+                    rules.extend(backend.list().await?.into_iter().filter(|rule| {
+                        p.enabled.is_none_or(|enabled| rule.enabled == enabled)
+                            && (text_filter.is_empty()
+                                || [&rule.name, &rule.id, &rule.query]
+                                    .iter()
+                                    .any(|f| f.to_lowercase().contains(&text_filter)))
+                    }));
+                }
+                let enabled = rules.iter().filter(|r| r.enabled).count();
+                let mut text = i18n::text(
+                    "messages.siem.rules_listed",
+                    &[("count", &rules.len()), ("enabled", &enabled)],
+                );
+                for rule in &rules {
+                    text.push_str(&format!("\n{}", detection_rule_text(rule)));
+                }
+                Ok(call_result(text, json!({ "rules": rules })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "siem_create_rule",
+                "Create a scheduled query detection rule in Elastic Security (KQL, Lucene or EQL) or as a Microsoft Sentinel analytics rule (KQL)",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "source": {"type": "string", "enum": ["elastic", "sentinel"], "description": "Needed when both are configured"},
+                        "id": {"type": "string", "description": "Stable rule ID, generated when omitted"},
+                        "name": {"type": "string"},
+                        "description": {"type": "string"},
+                        "severity": {"type": "string", "enum": ["informational", "low", "medium", "high", "critical"], "default": "medium"},
+                        "query": {"type": "string", "description": "KQL for Sentinel, e.g. SigninLogs | where ResultType != 0 | summarize count() by UserPrincipalName | where count_ > 20"},
+                        "language": {"type": "string", "enum": ["kuery", "lucene", "eql", "esql"], "description": "Elastic query language, default kuery"},
+                        "index": {"type": "array", "items": {"type": "string"}, "description": "Elastic index patterns, default the configured one"},
+                        "interval_minutes": {"type": "integer", "minimum": 1, "default": 5},
+                        "lookback_minutes": {"type": "integer", "minimum": 1, "description": "Minutes of events each run covers, default the interval"},
+                        "enabled": {"type": "boolean", "default": true},
+                        "tactics": {"type": "array", "items": {"type": "string"}, "description": "MITRE ATT&CK tactics, e.g. CredentialAccess"}
+                    },
+                    "required": ["name", "query"]
+                }),
+                None,
+            ),
+            |modules, p: SiemCreateRuleParams| async move {
+                let rule = detections::select(&modules.detections, p.source.as_deref())?
+                    .create(&p.rule)
+                    .await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.siem.rule_created",
+                        &[("rule", &detection_rule_text(&rule))],
+                    ),
+                    json!({ "rule": rule }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "siem_set_rule_enabled",
+                "Enable or disable an Elastic Security or Microsoft Sentinel detection rule",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "source": {"type": "string", "enum": ["elastic", "sentinel"], "description": "Needed when both are configured"},
+                        "id": {"type": "string", "description": "Elastic rule_id or Sentinel rule name, as listed by siem_list_rules"},
+                        "enabled": {"type": "boolean"}
+                    },
+                    "required": ["id", "enabled"]
+                }),
+                None,
+            ),
+            |modules, p: SiemSetRuleEnabledParams| async move {
+                let rule = detections::select(&modules.detections, p.source.as_deref())?
+                    .set_enabled(&p.id, p.enabled)
+                    .await?;
+                let key = if rule.enabled {
+                    "messages.siem.rule_enabled"
+                } else {
+                    "messages.siem.rule_disabled"
+                };
+                Ok(call_result(
+                    i18n::text(key, &[("rule", &detection_rule_text(&rule))]),
+                    json!({ "rule": rule }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "siem_run_rule",
+                "Run a detection rule over past events without raising alerts, to see what it would have caught",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "source": {"type": "string", "enum": ["elastic", "sentinel"], "description": "Needed when both are configured"},
+                        "id": {"type": "string"},
+                        "lookback": {"type": "string", "description": "How far back to run, e.g. 24h or 7d", "default": "24h"},
+                        "start": {"type": "string", "format": "date-time", "description": "Start of the range instead of lookback"},
+                        "end": {"type": "string", "format": "date-time", "description": "End of the range, default now"}
+                    },
+                    "required": ["id"]
+                }),
+                None,
+            ),
+            |modules, p: SiemRunRuleParams| async move {
+                let end = p.end.unwrap_or_else(chrono::Utc::now);
+                let start = match p.start {
+                    Some(start) => start,
+                    None => end - alerting::parse_duration(&p.lookback)?,
+                };
+                if start >= end {
+                    return Err(Error::validation_with_field(
+                        "Start must be before end",
+                        "start",
+                    ));
+                }
+                let run = detections::select(&modules.detections, p.source.as_deref())?
+                    .run(&p.id, start, end)
+                    .await?;
+                let mut text = i18n::text(
+                    "messages.siem.run",
+                    &[
+                        ("id", &run.rule_id),
+                        ("count", &run.matches),
+                        ("start", &run.start.format("%Y-%m-%d %H:%M")),
+                        ("end", &run.end.format("%Y-%m-%d %H:%M")),
+                    ],
+                );
+                for message in &run.messages {
+                    text.push_str(&format!("\n! {}", message));
+                }
+                for event in &run.sample {
+                    text.push_str(&format!("\n{}", event));
+                }
+                Ok(call_result(text, json!(run)))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_alert_rules",
+                "List Grafana alert rules with their folder, group, threshold and paused state",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "folder_uid": {"type": "string", "description": "Only rules in this folder"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ListAlertRulesParams| async move {
+                let rules = modules
+                    .grafana()?
+                    .list_rules(p.folder_uid.as_deref())
+                    .await?;
+                let mut text =
+                    i18n::text("messages.alert_rules.listed", &[("count", &rules.len())]);
+                for rule in &rules {
+                    text.push_str(&format!("\n{}", alert_rule_text(rule)));
+                }
+                Ok(call_result(text, json!({ "rules": rules })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "create_alert_rule",
+                "Create a Grafana alert rule that fires when a query stays above or below a threshold",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "title": {"type": "string", "description": "Rule name, also the alertname label"},
+                        "folder_uid": {"type": "string", "description": "UID of the folder the rule is stored in"},
+                        "rule_group": {"type": "string", "description": "Evaluation group; rules in a group are evaluated together"},
+                        "datasource_uid": {"type": "string", "description": "UID of the data source to query"},
+                        "expr": {"type": "string", "description": "Query in the data source's language, e.g. PromQL"},
+                        "comparison": {"type": "string", "enum": ["above", "below"], "description": "Fire when the query is above or below the threshold", "default": "above"},
+                        "threshold": {"type": "number", "description": "Value the query is compared with"},
+                        "for": {"type": "string", "description": "How long the condition must hold before firing, e.g. 5m or 1h", "default": "5m"},
+                        "labels": {"type": "object", "additionalProperties": {"type": "string"}, "description": "Labels for notification routing, e.g. severity"},
+                        "annotations": {"type": "object", "additionalProperties": {"type": "string"}, "description": "Annotations such as summary or runbook_url"}
+                    },
+                    "required": ["title", "folder_uid", "rule_group", "datasource_uid", "expr", "threshold"]
+                }),
+                None,
+            ),
+            |modules, p: CreateAlertRuleParams| async move {
+                let grafana = modules.grafana()?;
+                let rule = ThresholdRule {
+                    title: p.title,
+                    folder_uid: p.folder_uid,
+                    rule_group: p.rule_group,
+                    datasource_uid: p.datasource_uid,
+                    expr: p.expr,
+                    comparison: p.comparison,
+                    threshold: p.threshold,
+                    for_duration: p.for_duration,
+                    labels: p.labels,
+                    annotations: p.annotations,
+                }
+                .build(grafana.org_id())?;
+                let created = grafana.create_rule(&rule).await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.alert_rule.created",
+                        &[
+                            ("title", &created.title),
+                            ("uid", &created.uid.as_deref().unwrap_or("-")),
+                        ],
+                    ),
+                    json!({ "rule": created }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "update_alert_rule",
+                "Change the query, threshold, pending period, labels or paused state of a Grafana alert rule",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "uid": {"type": "string", "description": "UID of the rule, from list_alert_rules"},
+                        "title": {"type": "string", "description": "New rule name"},
+                        "expr": {"type": "string", "description": "New query"},
+                        "comparison": {"type": "string", "enum": ["above", "below"], "description": "Fire when the query is above or below the threshold"},
+                        "threshold": {"type": "number", "description": "New threshold"},
+                        "for": {"type": "string", "description": "New pending period, e.g. 5m or 1h"},
+                        "paused": {"type": "boolean", "description": "Pause or resume evaluation"},
+                        "labels": {"type": "object", "additionalProperties": {"type": "string"}, "description": "Labels replacing the current ones"},
+                        "annotations": {"type": "object", "additionalProperties": {"type": "string"}, "description": "Annotations replacing the current ones"}
+                    },
+                    "required": ["uid"]
+                }),
+                None,
+            ),
+            |modules, p: UpdateAlertRuleParams| async move {
+                let grafana = modules.grafana()?;
+                let mut rule = grafana.get_rule(&p.uid).await?;
+                if let Some(title) = p.title {
+                    rule.title = title;
+                }
+                if let Some(expr) = &p.expr {
+                    rule.set_expr(expr)?;
+                }
+                if p.comparison.is_some() || p.threshold.is_some() {
+                    let current = rule.threshold();
+                    let comparison = p
+                        .comparison
+                        .or(current.map(|(c, _)| c))
+                        .unwrap_or_default();
+                    let threshold = p.threshold.or(current.map(|(_, t)| t)).ok_or_else(|| {
+                        Error::validation_with_field(
+                            "The rule has no threshold to keep; pass threshold",
+                            "threshold",
+                        )
+                    })?;
+                    rule.set_threshold(comparison, threshold)?;
+                }
+                if let Some(for_duration) = p.for_duration {
+                    alerting::parse_duration(&for_duration)?;
+                    rule.for_duration = for_duration;
+                }
+                if let Some(paused) = p.paused {
+                    rule.is_paused = paused;
+                }
+                if let Some(labels) = p.labels {
+                    rule.labels = labels;
+                }
+                if let Some(annotations) = p.annotations {
+                    rule.annotations = annotations;
+                }
+                let updated = grafana.update_rule(&p.uid, &rule).await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.alert_rule.updated",
+                        &[("title", &updated.title), ("uid", &p.uid)],
+                    ),
+                    json!({ "rule": updated }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_contact_points",
+                "List the Grafana contact points alert notifications are sent to",
+                "monitoring",
+                json!({"type": "object", "properties": {}}),
+                None,
+            )
+            .read_only(),
+            |modules, _: Value| async move {
+                let contact_points = modules.grafana()?.contact_points().await?;
+                let mut text = i18n::text(
+                    "messages.contact_points.listed",
+                    &[("count", &contact_points.len())],
+                );
+                for point in &contact_points {
+                    text.push_str(&format!("\n{} ({})", point.name, point.kind));
+                }
+                Ok(call_result(
+                    text,
+                    json!({ "contact_points": contact_points }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "list_silences",
+                "List Grafana alert silences, newest first",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "include_expired": {"type": "boolean", "description": "Include silences that have ended", "default": false}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            |modules, p: ListSilencesParams| async move {
+                let silences = modules.grafana()?.silences(p.include_expired).await?;
+                let mut text =
+                    i18n::text("messages.silences.listed", &[("count", &silences.len())]);
+                for silence in &silences {
+                    text.push_str(&format!(
+                        "\n{} {} until {}: {} ({})",
+                        silence.id.as_deref().unwrap_or("-"),
+                        matchers_text(silence),
+                        silence.ends_at.format("%Y-%m-%d %H:%M UTC"),
+                        silence.comment,
+                        silence.status.as_ref().map_or("-", |s| s.state.as_str())
+                    ));
+                }
+                Ok(call_result(text, json!({ "silences": silences })))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "silence_alerts",
+                "Silence Grafana alerts whose labels match, starting now",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "labels": {"type": "object", "additionalProperties": {"type": "string"}, "minProperties": 1, "description": "Labels an alert must have to be silenced, e.g. {\"alertname\": \"High error rate\"}"},
+                        "duration": {"type": "string", "description": "How long to silence, e.g. 30m, 2h or 1d"},
+                        "comment": {"type": "string", "minLength": 1, "description": "Why the alerts are silenced"}
+                    },
+                    "required": ["labels", "duration", "comment"]
+                }),
+                None,
+            ),
+            |modules, p: SilenceAlertsParams| async move {
+                let duration = alerting::parse_duration(&p.duration)?;
+                let mut silence = Silence::new(&p.labels, duration, "devops-mcp", &p.comment)?;
+                let id = modules.grafana()?.silence(&silence).await?;
+                silence.id = Some(id.clone());
+                Ok(call_result(
+                    i18n::text(
+                        "messages.silence.created",
+                        &[
+                            ("matchers", &matchers_text(&silence)),
+                            ("until", &silence.ends_at.format("%Y-%m-%d %H:%M UTC")),
+                            ("id", &id),
+                        ],
+                    ),
+                    json!({ "silence": silence }),
+                ))
+            },
+        )?;
+        self.route(
+            registry,
+            ToolDefinition::from_json_schema(
+                "expire_silence",
+                "End a Grafana alert silence now",
+                "monitoring",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "silence_id": {"type": "string", "description": "Silence ID, from list_silences"}
+                    },
+                    "required": ["silence_id"]
+                }),
+                None,
+            ),
+            |modules, p: SilenceIdParams| async move {
+                modules.grafana()?.expire_silence(&p.silence_id).await?;
+                Ok(call_result(
+                    i18n::text("messages.silence.expired", &[("id", &p.silence_id)]),
+                    json!({ "silence_id": p.silence_id, "expired": true }),
+                ))
+            },
+        )?;
+        // Homelab hardware inventory
+        let assets = Arc::clone(&self.assets);
+        registry.register_all(assets.get_tool_definitions(), move |name, arguments| {
+            let assets = Arc::clone(&assets);
+            async move { assets.execute_tool(&name, arguments).await }
+        })?;
+        // UPS status from Network UPS Tools
+        if let Some(ups) = &self.ups {
+            let ups = Arc::clone(ups);
+            registry.register_all(ups.get_tool_definitions(), move |name, arguments| {
+                let ups = Arc::clone(&ups);
+                async move { ups.execute_tool(&name, arguments).await }
+            })?;
+        }
+        // Wake-on-LAN and BMC power control for inventory assets
+        let power = Arc::clone(&self.power);
+        registry.register_all(power.get_tool_definitions(), move |name, arguments| {
+            let power = Arc::clone(&power);
+            async move { power.execute_tool(&name, arguments).await }
+        })?;
+        // Traefik, Caddy and Nginx Proxy Manager
+        if let Some(proxy) = &self.proxy {
+            let proxy = Arc::clone(proxy);
+            registry.register_all(proxy.get_tool_definitions(), move |name, arguments| {
+                let proxy = Arc::clone(&proxy);
+                async move { proxy.execute_tool(&name, arguments).await }
+            })?;
+        }
+
+        // n8n workflows and executions
+        if let Some(n8n) = &self.n8n {
+            let n8n = Arc::clone(n8n);
+            registry.register_all(n8n.get_tool_definitions(), move |name, arguments| {
+                let n8n = Arc::clone(&n8n);
+                async move { n8n.execute_tool(&name, arguments).await }
+            })?;
+        }
+        // Unified alert store and Uptime Kuma
+        let alert_store = Arc::clone(&self.alert_store);
+        registry.register_all(
+            alert_store.get_tool_definitions(),
+            move |name, arguments| {
+                let alert_store = Arc::clone(&alert_store);
+                async move { alert_store.execute_tool(&name, arguments).await }
+            },
+        )?;
+        if let Some(uptime_kuma) = &self.uptime_kuma {
+            let uptime_kuma = Arc::clone(uptime_kuma);
+            registry.register_all(
+                uptime_kuma.get_tool_definitions(),
+                move |name, arguments| {
+                    let uptime_kuma = Arc::clone(&uptime_kuma);
+                    async move { uptime_kuma.execute_tool(&name, arguments).await }
+                },
+            )?;
+        }
+
+        // Entity resolution across the modules above
+        let resolver = Arc::new(EntityResolver::new(
+            Arc::clone(&self.lifecycle),
+            self.kubeconfig.clone(),
+            None,
+        ));
+        let context = {
+            let builder = ContextPackBuilder::new().with_alerts(Arc::clone(&resolver));
+            Arc::new(match &self.memory {
+                Some(memory) => builder.with_memory(Arc::clone(memory)),
+                None => builder,
+            })
+        };
+        registry.register_all(resolver.get_tool_definitions(), move |name, arguments| {
+            let resolver = Arc::clone(&resolver);
+            async move { resolver.execute_tool(&name, arguments).await }
+        })?;
+
+        // Context packs for model sessions
+        registry.register_all(context.get_tool_definitions(), move |name, arguments| {
+            let context = Arc::clone(&context);
+            async move { context.execute_tool(&name, arguments).await }
+        })?;
+
+        // Query translation and summarization need a model to generate with
+        let llm_config = LlmConfig::default();
+        if llm_config.is_configured() {
+            let llm: Arc<dyn LlmProvider> = Arc::new(OpenAiCompatibleProvider::new(llm_config)?);
+            let translator = Arc::new(QueryTranslator::new(Arc::clone(&llm)));
+            registry.register_all(translator.get_tool_definitions(), move |name, arguments| {
+                let translator = Arc::clone(&translator);
+                async move { translator.execute_tool(&name, arguments).await }
+            })?;
+
+            if let Some(config) = &self.summarization {
+                let summarizer = Arc::new(ResponseSummarizer::new(llm, config.clone()));
+                registry.add_middleware(Arc::clone(&summarizer) as Arc<dyn ToolMiddleware>);
+                registry.register_all(
+                    summarizer.get_tool_definitions(),
+                    move |name, arguments| {
+                        let summarizer = Arc::clone(&summarizer);
+                        async move { summarizer.execute_tool(&name, arguments).await }
+                    },
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn list_containers(&self, params: ListContainersParams) -> Result<Value> {
+        #[cfg(feature = "containers")]
+        let containers = match &self.docker {
+            Some(docker) => docker.list_containers(params.all).await?,
+            None => self.containers()?.list_containers(None, params.all).await?,
+        };
+        #[cfg(not(feature = "containers"))]
+        let containers = self.containers()?.list_containers(None, params.all).await?;
+
+        let mut text = i18n::text(
+            "messages.containers.listed",
+            &[("count", &containers.len())],
+        );
+        for container in &containers {
+            text.push_str(&format!(
+                "\n{} ({}) {}",
+                container.name, container.status, container.image
+            ));
+        }
+        Ok(call_result(text, json!({ "containers": containers })))
+    }
+
+    async fn container_logs(&self, params: ContainerLogsParams) -> Result<Value> {
+        let id = &params.container_id;
+        validate_container_id(id)?;
+
+        #[cfg(feature = "containers")]
+        let logs = match &self.docker {
+            Some(docker) => docker.container_logs(id, params.lines, false).await?,
+            None => {
+                self.containers()?
+                    .get_container_logs(id, Some(params.lines), false, false, None)
+                    .await?
+            }
+        };
+        #[cfg(not(feature = "containers"))]
+        let logs = self
+            .containers()?
+            .get_container_logs(id, Some(params.lines), false, false, None)
+            .await?;
+
+        Ok(call_result(
+            logs.clone(),
+            json!({"container_id": id, "logs": logs}),
+        ))
+    }
+
+    #[cfg(feature = "containers")]
+    async fn inspect_container(&self, params: ContainerParams) -> Result<Value> {
+        let id = &params.container_id;
+        validate_container_id(id)?;
+        let docker = self.docker.as_ref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "Docker Engine API is not reachable",
+                "Start the Docker daemon or set DOCKER_HOST",
+            )
+        })?;
+        let details = docker.inspect_container(id).await?;
+        let text = format!(
+            "{} ({}) {}{}",
+            details.name,
+            details.status,
+            details.image,
+            details
+                .exit_code
+                .filter(|_| !details.running)
+                .map(|code| format!(
+                    ", {}",
+                    i18n::text("messages.container.exit_code", &[("code", &code)])
+                ))
+                .unwrap_or_default()
+        );
+        Ok(call_result(text, json!({ "container": details })))
+    }
+
+    async fn container_stats(&self, params: ContainerParams) -> Result<Value> {
+        let id = &params.container_id;
+        validate_container_id(id)?;
+
+        #[cfg(feature = "containers")]
+        let usage = match &self.docker {
+            Some(docker) => docker.container_stats(id).await?,
+            None => self.containers()?.get_container_stats(id, None).await?,
+        };
+        #[cfg(not(feature = "containers"))]
+        let usage = self.containers()?.get_container_stats(id, None).await?;
+
+        let text = i18n::text(
+            "messages.container.stats",
+            &[
+                ("id", id),
+                ("cpu", &format!("{:.1}", usage.cpu_percent)),
+                ("used", &usage.memory_usage),
+                ("limit", &usage.memory_limit),
+            ],
+        );
+        Ok(call_result(
+            text,
+            json!({"container_id": id, "stats": usage}),
+        ))
+    }
+
+    async fn exec_in_container(&self, params: ExecParams) -> Result<Value> {
+        let id = &params.container_id;
+        validate_container_id(id)?;
+        if params.command.is_empty() {
+            return Err(Error::validation_with_field("Command is empty", "command"));
+        }
+
+        #[cfg(feature = "containers")]
+        if let Some(docker) = &self.docker {
+            let output = docker.exec(id, &params.command).await?;
+            let mut text = output.stdout.clone();
+            text.push_str(&output.stderr);
+            if let Some(code) = output.exit_code.filter(|&code| code != 0) {
+                text.push_str(&format!(
+                    "\n({})",
+                    i18n::text("messages.container.exit_code", &[("code", &code)])
+                ));
+            }
+            return Ok(call_result(
+                text,
+                json!({"container_id": id, "output": output}),
+            ));
+        }
+
+        let command: Vec<&str> = params.command.iter().map(String::as_str).collect();
+        let stdout = self
+            .containers()?
+            .exec_in_container(id, &command, false, None)
+            .await?;
+        Ok(call_result(
+            stdout.clone(),
+            json!({"container_id": id, "output": {"stdout": stdout}}),
+        ))
+    }
+
+    async fn start_container(&self, params: ContainerParams) -> Result<Value> {
+        let id = &params.container_id;
+        validate_container_id(id)?;
+
+        #[cfg(feature = "containers")]
+        match &self.docker {
+            Some(docker) => docker.start_container(id).await?,
+            None => {
+                self.containers()?.start_container(id, None).await?;
+            }
+        }
+        #[cfg(not(feature = "containers"))]
+        self.containers()?.start_container(id, None).await?;
+
+        Ok(call_result(
+            i18n::text("messages.container.started", &[("id", id)]),
+            json!({"container_id": id, "started": true}),
+        ))
+    }
+
+    async fn stop_container(&self, params: StopContainerParams) -> Result<Value> {
+        let id = &params.container_id;
+        validate_container_id(id)?;
+
+        #[cfg(feature = "containers")]
+        match &self.docker {
+            Some(docker) => docker.stop_container(id, params.timeout_secs).await?,
+            None => {
+                self.containers()?.stop_container(id, None).await?;
+            }
+        }
+        #[cfg(not(feature = "containers"))]
+        self.containers()?.stop_container(id, None).await?;
+
+        Ok(call_result(
+            i18n::text("messages.container.stopped", &[("id", id)]),
+            json!({"container_id": id, "stopped": true}),
+        ))
+    }
+
+    async fn list_pods(&self, params: ListPodsParams) -> Result<Value> {
+        let namespace = params.namespace.unwrap_or_else(|| self.namespace.clone());
+        let pods = self.kubernetes()?.list_pods(Some(&namespace)).await?;
+        let mut text = i18n::text(
+            "messages.pods.listed",
+            &[("count", &pods.len()), ("namespace", &namespace)],
+        );
+        for pod in &pods {
+            text.push_str(&format!(
+                "\n{} {} ready {} restarts {} age {}",
+                pod.name, pod.status, pod.ready, pod.restarts, pod.age
+            ));
+        }
+        Ok(call_result(
+            text,
+            json!({"namespace": namespace, "pods": pods}),
+        ))
+    }
+
+    async fn pod_logs(&self, params: PodLogsParams, stream: ToolStream) -> Result<Value> {
+        let namespace = params.namespace.unwrap_or_else(|| self.namespace.clone());
+        let mut chunk = String::new();
+        let mut chunk_lines = 0;
+        let logs = self
+            .kubernetes()?
+            .stream_pod_logs(
+                &params.pod_name,
+                Some(&namespace),
+                Some(params.lines),
+                |line| {
+                    chunk.push_str(line);
+                    chunk.push('\n');
+                    chunk_lines += 1;
+                    if chunk_lines == LOG_CHUNK_LINES {
+                        stream.text(std::mem::take(&mut chunk));
+                        chunk_lines = 0;
+                    }
+                },
+            )
+            .await?;
+        if !chunk.is_empty() {
+            stream.text(chunk);
+        }
+        Ok(call_result(
+            logs.clone(),
+            json!({"pod": params.pod_name, "namespace": namespace, "logs": logs}),
+        ))
+    }
+
+    async fn list_helm_releases(&self, params: ListHelmReleasesParams) -> Result<Value> {
+        let namespace = if params.all_namespaces {
+            None
+        } else {
+            Some(params.namespace.unwrap_or_else(|| self.namespace.clone()))
+        };
+        let releases = self
+            .kubernetes()?
+            .list_helm_releases(namespace.as_deref())
+            .await?;
+        let mut text = i18n::text(
+            "messages.helm.releases_listed",
+            &[("count", &releases.len())],
+        );
+        for release in &releases {
+            text.push_str(&format!(
+                "\n{}/{} {} revision {} {}",
+                release.namespace, release.name, release.chart, release.revision, release.status
+            ));
+        }
+        Ok(call_result(
+            text,
+            json!({"namespace": namespace, "releases": releases}),
+        ))
+    }
+
+    async fn helm_release_history(&self, params: HelmHistoryParams) -> Result<Value> {
+        let namespace = params.namespace.unwrap_or_else(|| self.namespace.clone());
+        let history = self
+            .kubernetes()?
+            .get_helm_release_history(&params.release, &namespace, params.max)
+            .await?;
+        let mut text = i18n::text(
+            "messages.helm.history",
+            &[("count", &history.len()), ("release", &params.release)],
+        );
+        for revision in &history {
+            text.push_str(&format!(
+                "\n{} {} {} {}: {}",
+                revision.revision,
+                revision.updated,
+                revision.chart,
+                revision.status,
+                revision.description
+            ));
+        }
+        Ok(call_result(
+            text,
+            json!({"release": params.release, "namespace": namespace, "history": history}),
+        ))
+    }
+
+    async fn helm_values(&self, params: HelmValuesParams) -> Result<Value> {
+        let namespace = params.namespace.unwrap_or_else(|| self.namespace.clone());
+        let values = self
+            .kubernetes()?
+            .get_helm_values(&params.release, &namespace, params.revision, params.all)
+            .await?;
+        let text = format!(
+            "{}\n{}",
+            i18n::text("messages.helm.values", &[("release", &params.release)]),
+            serde_json::to_string_pretty(&values)?
+        );
+        Ok(call_result(
+            text,
+            json!({
+                "release": params.release,
+                "namespace": namespace,
+                "revision": params.revision,
+                "values": values
+            }),
+        ))
+    }
+
+    async fn rollback_helm_release(&self, params: HelmRollbackParams) -> Result<Value> {
+        let namespace = params.namespace.unwrap_or_else(|| self.namespace.clone());
+        let revision = self
+            .kubernetes()?
+            .rollback_helm_release(&params.release, &namespace, params.revision, params.wait)
+            .await?;
+        Ok(call_result(
+            i18n::text(
+                "messages.helm.rolled_back",
+                &[
+                    ("release", &params.release),
+                    ("revision", &revision.revision),
+                    ("description", &revision.description),
+                ],
+            ),
+            json!({"release": params.release, "namespace": namespace, "revision": revision}),
+        ))
+    }
+
+    async fn list_databases(&self, params: ListDatabasesParams) -> Result<Value> {
+        let providers: Vec<String> = match params.provider {
+            Some(provider) => vec![provider],
+            None => {
+                let mut providers: Vec<String> = self.database_urls.keys().cloned().collect();
+                if !self.sqlite.dirs.is_empty() {
+                    providers.push("sqlite".to_string());
+                }
+                providers
+            }
+        };
+        if providers.is_empty() {
+            return Err(Error::config_with_suggestion(
+                "No database connections configured",
+                "Set database.connections or database.sqlite.dirs in the config file, or DATABASE_URL or MONGODB_URI",
+            ));
+        }
+        let mut listed = BTreeMap::new();
+        let mut text = String::new();
+        for provider in providers {
+            let databases = if provider == "sqlite" {
+                self.sqlite.find_files()
+            } else {
+                self.database(&provider).await?.list_databases().await?
+            };
+            text.push_str(&format!("{}: {}\n", provider, databases.join(", ")));
+            listed.insert(provider, databases);
+        }
+        Ok(call_result(text.trim_end(), json!({ "databases": listed })))
+    }
+
+    async fn execute_query(&self, params: QueryParams) -> Result<Value> {
+        let safety = self.query_safety(&params.provider).unwrap_or_default();
+        safety.check(&params.provider, &params.query, params.confirm)?;
+        let (database, name) = self.database_in(&params.provider, &params.database).await?;
+        let result = match &params.transaction_id {
+            Some(id) => {
+                database
+                    .execute_in_transaction(id, &params.query, &params.params)
+                    .await?
+            }
+            None if safety.runs_read_only(&params.provider, &params.query) => {
+                database
+                    .execute_read_only(&params.query, &params.params, name)
+                    .await?
+            }
+            None => {
+                database
+                    .execute_with_params(&params.query, &params.params, name)
+                    .await?
+            }
+        };
+        let mut text = query_result_text(&result)?;
+        let mut structured = serde_json::to_value(&result)?;
+        if let Some(id) = params.transaction_id {
+            structured["transaction_id"] = json!(id);
+        }
+        if let Some(path) = &params.output_path {
+            let columns: Vec<String> = result.columns.iter().map(|c| c.name.clone()).collect();
+            let rendered =
+                DataMasker::render(&result.rows, &columns, params.format, "query_result");
+            let path = write_export(self.export_dir.as_deref(), path, rendered).await?;
+            text = format!(
+                "{}\n{}",
+                i18n::text(
+                    "messages.query.exported",
+                    &[("count", &result.rows.len()), ("path", &path.display())],
+                ),
+                text
+            );
+            structured["output_path"] = json!(path);
+        }
+        Ok(call_result(text, structured))
+    }
+
+    async fn begin_transaction(&self, params: BeginTransactionParams) -> Result<Value> {
+        let database = self.database(&params.provider).await?;
+        let safety = self.query_safety(&params.provider).unwrap_or_default();
+        let id = if safety.read_only_transactions() {
+            database.begin_read_only_transaction().await?
+        } else {
+            database.begin_transaction().await?
+        };
+        Ok(call_result(
+            i18n::text("messages.transaction.begun", &[("id", &id)]),
+            json!({"provider": params.provider, "transaction_id": id}),
+        ))
+    }
+
+    async fn commit_transaction(&self, params: TransactionParams) -> Result<Value> {
+        self.database(&params.provider)
+            .await?
+            .commit(&params.transaction_id)
+            .await?;
+        Ok(call_result(
+            i18n::text(
+                "messages.transaction.committed",
+                &[("id", &params.transaction_id)],
+            ),
+            json!({"provider": params.provider, "transaction_id": params.transaction_id}),
+        ))
+    }
+
+    async fn rollback_transaction(&self, params: TransactionParams) -> Result<Value> {
+        self.database(&params.provider)
+            .await?
+            .rollback(&params.transaction_id)
+            .await?;
+        Ok(call_result(
+            i18n::text(
+                "messages.transaction.rolled_back",
+                &[("id", &params.transaction_id)],
+            ),
+            json!({"provider": params.provider, "transaction_id": params.transaction_id}),
+        ))
+    }
+
+    async fn list_tables(&self, params: ListTablesParams) -> Result<Value> {
+        let (database, name) = self.database_in(&params.provider, &params.database).await?;
+        let tables = database.list_tables(name).await?;
+        let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+        Ok(call_result(
+            i18n::text(
+                "messages.tables.listed",
+                &[("count", &tables.len()), ("names", &names.join(", "))],
+            ),
+            json!({ "tables": tables }),
+        ))
+    }
+
+    async fn describe_schema(&self, params: DescribeSchemaParams) -> Result<Value> {
+        let (database, name) = self.database_in(&params.provider, &params.database).await?;
+        let mut schema = database.describe_schema(name).await?;
+        schema.retain_tables(&params.tables);
+        let text = format!(
+            "{}\n{}",
+            i18n::text(
+                "messages.schema.described",
+                &[
+                    ("count", &schema.tables.len()),
+                    ("keys", &schema.foreign_key_count()),
+                    ("schema", &schema.schema),
+                ],
+            ),
+            schema.outline()
+        );
+        Ok(call_result(text.trim_end(), json!({ "schema": schema })))
+    }
+
+    async fn generate_erd(&self, params: ErdParams) -> Result<Value> {
+        let (database, name) = self.database_in(&params.provider, &params.database).await?;
+        let mut schema = database.describe_schema(name).await?;
+        schema.retain_tables(&params.tables);
+        let diagram = schema.erd(params.format);
+        Ok(call_result(
+            diagram.clone(),
+            json!({"format": params.format, "tables": schema.tables.len(), "diagram": diagram}),
+        ))
+    }
+
+    async fn entity_state(&self, params: EntityStateParams) -> Result<Value> {
+        let socket = self.home_assistant()?.socket().await?;
+        let entity = socket.state(&params.entity_id).await?;
+        Ok(call_result(
+            i18n::text(
+                "messages.entity.state",
+                &[("entity", &entity.name()), ("state", &entity.state)],
+            ),
+            json!({ "entity": entity }),
+        ))
+    }
+
+    async fn list_entities(&self, params: ListEntitiesParams) -> Result<Value> {
+        let socket = self.home_assistant()?.socket().await?;
+        let entities = socket
+            .entities(params.domain.as_deref(), params.area.as_deref())
+            .await?;
+        let mut text = i18n::text("messages.entities.listed", &[("count", &entities.len())]);
+        for entity in &entities {
+            text.push_str(&format!("\n{}: {}", entity.entity_id, entity.state));
+            if let Some(area) = &entity.area {
+                text.push_str(&format!(" ({})", area));
+            }
+        }
+        Ok(call_result(text, json!({ "entities": entities })))
+    }
+
+    async fn call_service(&self, params: CallServiceParams) -> Result<Value> {
+        let target = match (params.target, params.entity_id) {
+            (Some(mut target), Some(entity_id)) => {
+                if let Value::Object(map) = &mut target {
+                    map.insert("entity_id".to_string(), json!(entity_id));
+                }
+                Some(target)
+            }
+            (target, entity_id) => target.or(entity_id.map(|id| json!({ "entity_id": id }))),
+        };
+        let socket = self.home_assistant()?.socket().await?;
+        let result = socket
+            .call_service(&params.domain, &params.service, params.data, target)
+            .await?;
+        let service = format!("{}.{}", params.domain, params.service);
+        Ok(call_result(
+            i18n::text("messages.service.called", &[("service", &service)]),
+            json!({ "service": service, "result": result }),
+        ))
+    }
+
+    async fn stream_quotes(&self, params: StreamQuotesParams, stream: ToolStream) -> Result<Value> {
+        let mut quotes = self.alpaca()?.stream_quotes(&params.symbols).await?;
+        let seconds = params.duration_secs.clamp(1, MAX_QUOTE_STREAM_SECS);
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(seconds);
+        let mut latest = BTreeMap::new();
+        let mut count = 0;
+        while let Ok(Some(quote)) = tokio::time::timeout_at(deadline, quotes.next()).await {
+            stream.text(quote_text(&quote));
+            count += 1;
+            latest.insert(quote.symbol.clone(), quote);
+            if params.max_quotes.is_some_and(|max| count >= max) {
+                break;
+            }
+        }
+        let symbols = params.symbols.join(", ").to_uppercase();
+        let mut text = i18n::text(
+            "messages.quotes.streamed",
+            &[("count", &count), ("symbols", &symbols)],
+        );
+        for quote in latest.values() {
+            text.push('\n');
+            text.push_str(&quote_text(quote));
+        }
+        Ok(call_result(
+            text,
+            json!({ "count": count, "latest": latest.into_values().collect::<Vec<_>>() }),
+        ))
+    }
+
+    async fn wait_for_voice_command(
+        &self,
+        params: VoiceCommandParams,
+        stream: ToolStream,
+    ) -> Result<Value> {
+        let assist = Assist::new(self.home_assistant()?.socket().await?);
+        let pipelines = assist.pipelines().await?;
+        let pipeline = pipelines.find(params.pipeline.as_deref())?;
+        let mut events = assist.satellite_events().await?;
+        let seconds = params.timeout_secs.clamp(1, MAX_VOICE_WAIT_SECS);
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(seconds);
+        let wanted = |event: &SatelliteEvent| {
+            params
+                .satellite
+                .as_deref()
+                .is_none_or(|satellite| event.entity_id == satellite)
+        };
+
+        let mut woken = None;
+        while let Ok(Some(event)) = tokio::time::timeout_at(deadline, events.next()).await {
+            if !wanted(&event) {
+                continue;
+            }
+            match (&woken, &event.state) {
+                (None, _) if event.is_wake() => {
+                    stream.text(i18n::text(
+                        "messages.assist.wake",
+                        &[("satellite", &event.entity_id)],
+                    ));
+                    woken = Some(event);
+                }
+                // The pipeline has run once the satellite stops listening and processing
+                (Some(_), SatelliteState::Responding | SatelliteState::Idle) => break,
+                _ => {}
+            }
+        }
+        let Some(wake) = woken else {
+            return Ok(call_result(
+                i18n::text("messages.assist.no_wake", &[("seconds", &seconds)]),
+                json!({ "heard": false }),
+            ));
+        };
+        let run = assist.last_run(&pipeline.id, Some(wake.at)).await?;
+        let transcript = run
+            .as_ref()
+            .and_then(|r| r.transcript())
+            .unwrap_or_default();
+        let response = run.as_ref().and_then(|r| r.response());
+        Ok(call_result(
+            i18n::text(
+                "messages.assist.heard",
+                &[("satellite", &wake.entity_id), ("text", &transcript)],
+            ),
+            json!({
+                "heard": true,
+                "satellite": wake.entity_id,
+                "pipeline": pipeline.id,
+                "transcript": transcript,
+                "response": response,
+            }),
+        ))
+    }
+
+    async fn turn_on(&self, params: TurnOnParams) -> Result<Value> {
+        let client = self.home_assistant()?;
+        let data = light_data(params.brightness, params.color.as_deref());
+        let result = match data {
+            Some(data) => {
+                let domain = params.entity_id.split('.').next().unwrap_or_default();
+                client
+                    .call_service(domain, "turn_on", &params.entity_id, Some(data))
+                    .await?
+            }
+            None => client.turn_on(&params.entity_id).await?,
+        };
+        Ok(call_result(
+            i18n::text(
+                "messages.device.turned_on",
+                &[("entity", &params.entity_id)],
+            ),
+            result,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::kubernetes::pods_from_list;
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn reads_pods_and_light_data() {
+        let now = DateTime::parse_from_rfc3339("2026-01-03T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let pods = pods_from_list(
+            &json!({"items": [{
+                "metadata": {"name": "api-1", "namespace": "prod", "creationTimestamp": "2026-01-01T00:00:00Z"},
+                "spec": {"nodeName": "node-a"},
+                "status": {
+                    "phase": "Running",
+                    "podIP": "10.0.0.5",
+                    "containerStatuses": [
+                        {"ready": true, "restartCount": 2},
+                        {"ready": false, "restartCount": 1}
+                    ]
+                }
+            }]}),
+            now,
+        );
+        assert_eq!(pods[0].ready, "1/2");
+        assert_eq!(pods[0].restarts, 3);
+        assert_eq!(pods[0].age, "2d");
+        assert_eq!(pods[0].node.as_deref(), Some("node-a"));
+
+        assert_eq!(
+            light_data(Some(128), Some("#ff8000")),
+            Some(json!({"brightness": 128, "rgb_color": [255, 128, 0]}))
+        );
+        assert_eq!(
+            light_data(None, Some("Red")),
+            Some(json!({"color_name": "red"}))
+        );
+        assert_eq!(light_data(None, None), None);
+    }
+
+    #[tokio::test]
+    async fn unconfigured_backends_fail_with_config_errors() {
+        if std::env::var("MONGODB_URI").is_ok() {
+            return;
+        }
+        let modules = Arc::new(
+            ServerModules::from_config(&Config::default())
+                .await
+                .unwrap(),
+        );
+        let mut registry = ToolRegistry::new();
+        modules.register(&mut registry).unwrap();
+        assert!(registry.contains("describe_entity"));
+
+        let error = registry
+            .call(
+                "execute_query",
+                json!({"provider": "mongodb", "database": "app", "query": "{}"}),
+            )
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("No connection configured for mongodb"));
+    }
+
+    #[tokio::test]
+    async fn read_only_providers_refuse_writes_before_connecting() {
+        use crate::config::DatabaseConfig;
+        use crate::database::safety::QuerySafety;
+
+        let mut database = DatabaseConfig::default();
+        database.safety.insert(
+            "postgresql".to_string(),
+            QuerySafety {
+                read_only: true,
+                ..QuerySafety::default()
+            },
+        );
+        let config = Config {
+            database: Some(database),
+            ..Config::default()
+        };
+        let modules = Arc::new(ServerModules::from_config(&config).await.unwrap());
+        let error = modules
+            .execute_query(QueryParams {
+                provider: "postgresql".to_string(),
+                database: "app".to_string(),
+                query: "SELECT 1; DELETE FROM users".to_string(),
+                params: Vec::new(),
+                transaction_id: None,
+                confirm: true,
+                output_path: None,
+                format: ExportFormat::default(),
+            })
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("postgresql is read-only"));
+    }
+}
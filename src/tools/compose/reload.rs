@@ -0,0 +1,75 @@
+//! Rebuilding the module clients from a reloaded config
+//!
+//! A new `ServerModules` is built and registered into a scratch registry,
+//! then its tools and middleware replace the previous modules' in the live
+//! registry in one step, along with the permission policy. Calls already
+//! running finish on the old clients; their schedulers are stopped once the
+//! swap is done. State the old clients only kept in memory, such as an
+//! unpersisted alert store, does not carry over.
+
+use super::ServerModules;
+use crate::config::reload::Reloadable;
+use crate::config::Config;
+use crate::error::Result;
+use crate::security::PermissionPolicy;
+use crate::tools::registry::{ToolRegistry, ToolSet};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Modules serving one registry, swapped for new ones on reload
+pub struct ReloadableModules {
+    registry: Arc<ToolRegistry>,
+    /// Tenant whose section of the config the modules are built from
+    tenant: Option<String>,
+    current: Mutex<(Arc<ServerModules>, ToolSet)>,
+}
+
+impl ReloadableModules {
+    /// `tools` are the tools and middleware `modules` registered
+    pub fn new(
+        registry: Arc<ToolRegistry>,
+        modules: Arc<ServerModules>,
+        tools: ToolSet,
+        tenant: Option<String>,
+    ) -> Self {
+        Self {
+            registry,
+            tenant,
+            current: Mutex::new((modules, tools)),
+        }
+    }
+}
+
+#[async_trait]
+impl Reloadable for ReloadableModules {
+    async fn reload(&self, config: &Config) -> Result<()> {
+        let (config, tenant) = match &self.tenant {
+            None => (config.clone(), None),
+            Some(id) => match config.tenants.as_ref().and_then(|t| t.get(id)) {
+                Some(tenant) => (tenant.module_config(id, config), Some(tenant)),
+                None => {
+                    tracing::warn!(
+                        "Tenant {} left the config; it is served until a restart",
+                        id
+                    );
+                    return Ok(());
+                }
+            },
+        };
+        let policy = PermissionPolicy::from_config(&config)?.map(Arc::new);
+        let modules = Arc::new(ServerModules::from_config(&config).await?);
+        let mut fresh = ToolRegistry::new();
+        modules.register(&mut fresh)?;
+        if let Some(tenant) = tenant {
+            tenant.restrict(&fresh);
+        }
+
+        let mut current = self.current.lock().await;
+        let tools = self.registry.swap_set(&current.1, fresh);
+        self.registry.set_policy(policy);
+        let (previous, _) = std::mem::replace(&mut *current, (modules, tools));
+        previous.shutdown().await;
+        Ok(())
+    }
+}
@@ -0,0 +1,535 @@
+/// Favorites: tagged entities recalled across modules
+///
+/// A favorite gives a short tag to something a client keeps coming back to:
+/// a pod, a dashboard, a grant opportunity, a stock symbol. `favorites_list`
+/// recalls them, most used first, and any tool argument written as `@tag`
+/// is replaced with the favorite's value before the call is validated, so
+/// `"@prod-api"` can stand in for a deployment reference. Arguments naming
+/// an unknown tag are passed through untouched.
+use crate::error::{Error, Result};
+use crate::i18n;
+use crate::tools::preferences::DEFAULT_CLIENT;
+use crate::tools::registry::{current_client, ToolRegistry};
+use crate::tools::snapshot::SnapshotSource;
+use crate::tools::{call_result, ToolDefinition, ToolMiddleware};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Prefix marking an argument as a favorite's tag
+pub const TAG_PREFIX: char = '@';
+
+/// Favorite settings, under `favorites` in the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FavoritesConfig {
+    /// Where favorites are persisted; `None` keeps them in memory
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+/// A tagged entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Favorite {
+    pub tag: String,
+    /// What the entity is, such as `pod`, `dashboard` or `symbol`
+    pub kind: String,
+    /// What `@tag` expands to in tool arguments
+    pub value: Value,
+    /// Module the entity belongs to
+    #[serde(default)]
+    pub module: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Times the tag was expanded in a tool call
+    #[serde(default)]
+    pub uses: u64,
+    #[serde(default)]
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// Filter for [`FavoriteStore::list`]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FavoriteFilter {
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub module: Option<String>,
+    /// Text found in the tag, value or note
+    #[serde(default)]
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FavoriteData {
+    clients: BTreeMap<String, BTreeMap<String, Favorite>>,
+}
+
+/// Persisted favorites by client
+pub struct FavoriteStore {
+    path: Option<PathBuf>,
+    store: RwLock<FavoriteData>,
+}
+
+impl FavoriteStore {
+    /// Open the store, loading favorites from the configured path if it exists
+    pub async fn open(config: FavoritesConfig) -> Result<Self> {
+        let store = match &config.path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse favorites store {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => FavoriteData::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read favorites store: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => FavoriteData::default(),
+        };
+        Ok(Self {
+            path: config.path,
+            store: RwLock::new(store),
+        })
+    }
+
+    async fn persist(&self, store: &FavoriteData) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create favorites store directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to write favorites store: {}", e),
+                    temp.clone(),
+                )
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace favorites store: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Save a favorite, replacing any with the same tag but keeping its usage
+    pub async fn add(&self, client: &str, mut favorite: Favorite) -> Result<Favorite> {
+        check_tag(&favorite.tag)?;
+        if favorite.kind.trim().is_empty() {
+            return Err(Error::validation_with_field("Kind is required", "kind"));
+        }
+        if favorite.value.is_null() {
+            return Err(Error::validation_with_field("Value is required", "value"));
+        }
+        let mut store = self.store.write().await;
+        let favorites = store.clients.entry(client.to_string()).or_default();
+        if let Some(existing) = favorites.get(&favorite.tag) {
+            favorite.created_at = existing.created_at;
+            favorite.uses = existing.uses;
+            favorite.last_used = existing.last_used;
+        }
+        favorites.insert(favorite.tag.clone(), favorite.clone());
+        self.persist(&store).await?;
+        Ok(favorite)
+    }
+
+    /// Remove a favorite by tag
+    pub async fn remove(&self, client: &str, tag: &str) -> Result<Favorite> {
+        let tag = tag.trim_start_matches(TAG_PREFIX);
+        let mut store = self.store.write().await;
+        let removed = store
+            .clients
+            .get_mut(client)
+            .and_then(|favorites| favorites.remove(tag))
+            .ok_or_else(|| Error::not_found_with_resource("Favorite not found", "favorite", tag))?;
+        store.clients.retain(|_, favorites| !favorites.is_empty());
+        self.persist(&store).await?;
+        Ok(removed)
+    }
+
+    /// A client's favorites matching `filter`, most used first
+    pub async fn list(&self, client: &str, filter: &FavoriteFilter) -> Vec<Favorite> {
+        let query = filter.query.as_ref().map(|q| q.to_lowercase());
+        let store = self.store.read().await;
+        let mut favorites: Vec<Favorite> = store
+            .clients
+            .get(client)
+            .into_iter()
+            .flat_map(|favorites| favorites.values())
+            .filter(|f| filter.kind.as_ref().is_none_or(|kind| f.kind == *kind))
+            .filter(|f| {
+                filter
+                    .module
+                    .as_ref()
+                    .is_none_or(|module| f.module.as_ref() == Some(module))
+            })
+            .filter(|f| {
+                query.as_ref().is_none_or(|q| {
+                    f.tag.to_lowercase().contains(q)
+                        || f.value.to_string().to_lowercase().contains(q)
+                        || f.note
+                            .as_ref()
+                            .is_some_and(|n| n.to_lowercase().contains(q))
+                })
+            })
+            .cloned()
+            .collect();
+        favorites.sort_by(|a, b| b.uses.cmp(&a.uses).then_with(|| a.tag.cmp(&b.tag)));
+        favorites
+    }
+
+    /// Replace `@tag` arguments with the values of `client`'s favorites
+    pub async fn expand(&self, client: &str, arguments: Value) -> Result<Value> {
+        let mut tags = BTreeSet::new();
+        collect_tags(&arguments, &mut tags);
+        if tags.is_empty() {
+            return Ok(arguments);
+        }
+        let mut store = self.store.write().await;
+        let Some(favorites) = store.clients.get_mut(client) else {
+            return Ok(arguments);
+        };
+        let now = Utc::now();
+        let mut expanded = false;
+        for tag in &tags {
+            if let Some(favorite) = favorites.get_mut(tag.as_str()) {
+                favorite.uses += 1;
+                favorite.last_used = Some(now);
+                expanded = true;
+            }
+        }
+        if !expanded {
+            return Ok(arguments);
+        }
+        let arguments = substitute(arguments, favorites);
+        self.persist(&store).await?;
+        Ok(arguments)
+    }
+
+    /// Get tool definitions for managing favorites
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "favorites_add",
+                "Tag an entity you use often; write @tag in any tool argument to use it",
+                "core",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "tag": {"type": "string", "description": "Short name, used as @tag"},
+                        "kind": {"type": "string", "description": "What the entity is, such as pod, deployment, dashboard, grant or symbol"},
+                        "value": {"description": "What @tag expands to, such as a name, ID or reference object"},
+                        "module": {"type": "string", "description": "Module the entity belongs to"},
+                        "note": {"type": "string", "description": "Reminder of why it matters"}
+                    },
+                    "required": ["tag", "kind", "value"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "favorites_list",
+                "List your favorites, most used first",
+                "core",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "kind": {"type": "string", "description": "Only favorites of this kind"},
+                        "module": {"type": "string", "description": "Only favorites from this module"},
+                        "query": {"type": "string", "description": "Text to find in the tag, value or note"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+            ToolDefinition::from_json_schema(
+                "favorites_remove",
+                "Remove a favorite",
+                "core",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "tag": {"type": "string", "description": "Tag to remove"}
+                    },
+                    "required": ["tag"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a favorites tool for the calling client
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let client = current_client().unwrap_or_else(|| DEFAULT_CLIENT.to_string());
+        match name {
+            "favorites_add" => {
+                let params: AddParams = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid parameters: {}", e)))?;
+                let favorite = Favorite {
+                    tag: params.tag.trim_start_matches(TAG_PREFIX).to_string(),
+                    kind: params.kind,
+                    value: params.value,
+                    module: params.module,
+                    note: params.note,
+                    created_at: Utc::now(),
+                    uses: 0,
+                    last_used: None,
+                };
+                let favorite = self.add(&client, favorite).await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.favorites.added",
+                        &[("tag", &favorite.tag), ("value", &favorite.value)],
+                    ),
+                    serde_json::to_value(&favorite)?,
+                ))
+            }
+            "favorites_list" => {
+                let filter: FavoriteFilter = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid parameters: {}", e)))?;
+                let favorites = self.list(&client, &filter).await;
+                let mut text =
+                    i18n::text("messages.favorites.listed", &[("count", &favorites.len())]);
+                for favorite in &favorites {
+                    text.push_str(&format!(
+                        "\n{}{} ({}) = {}",
+                        TAG_PREFIX, favorite.tag, favorite.kind, favorite.value
+                    ));
+                }
+                Ok(call_result(
+                    text,
+                    json!({"client": client, "favorites": favorites}),
+                ))
+            }
+            "favorites_remove" => {
+                let tag = parameters
+                    .get("tag")
+                    .and_then(|t| t.as_str())
+                    .ok_or_else(|| Error::validation_with_field("Missing tag", "tag"))?;
+                let removed = self.remove(&client, tag).await?;
+                Ok(call_result(
+                    i18n::text("messages.favorites.removed", &[("tag", &removed.tag)]),
+                    serde_json::to_value(&removed)?,
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "favorites_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolMiddleware for FavoriteStore {
+    async fn before_call(&self, tool: &ToolDefinition, arguments: Value) -> Result<Value> {
+        // Favorites are managed by their literal tags
+        if tool.name.starts_with("favorites_") {
+            return Ok(arguments);
+        }
+        let client = current_client().unwrap_or_else(|| DEFAULT_CLIENT.to_string());
+        self.expand(&client, arguments).await
+    }
+
+    async fn after_call(&self, _tool: &str, result: Value) -> Result<Value> {
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl SnapshotSource for FavoriteStore {
+    fn name(&self) -> &str {
+        "favorites"
+    }
+
+    async fn export_state(&self) -> Result<Value> {
+        Ok(serde_json::to_value(&*self.store.read().await)?)
+    }
+
+    async fn restore_state(&self, state: Value) -> Result<()> {
+        let restored: FavoriteData = serde_json::from_value(state)
+            .map_err(|e| Error::parsing(format!("Invalid favorites snapshot: {}", e)))?;
+        let mut store = self.store.write().await;
+        self.persist(&restored).await?;
+        *store = restored;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AddParams {
+    tag: String,
+    kind: String,
+    value: Value,
+    #[serde(default)]
+    module: Option<String>,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+/// Check that a tag can be written as `@tag`
+fn check_tag(tag: &str) -> Result<()> {
+    let valid = !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::validation_with_field(
+            format!(
+                "Invalid tag {}: use letters, digits, '-', '_', '.' or '/'",
+                tag
+            ),
+            "tag",
+        ))
+    }
+}
+
+/// Tag named by an argument written as `@tag`
+fn tag_of(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(TAG_PREFIX)
+        .filter(|tag| check_tag(tag).is_ok())
+}
+
+fn collect_tags(value: &Value, tags: &mut BTreeSet<String>) {
+    match value {
+        Value::String(s) => tags.extend(tag_of(s).map(String::from)),
+        Value::Array(items) => items.iter().for_each(|item| collect_tags(item, tags)),
+        Value::Object(map) => map.values().for_each(|item| collect_tags(item, tags)),
+        _ => {}
+    }
+}
+
+fn substitute(value: Value, favorites: &BTreeMap<String, Favorite>) -> Value {
+    match value {
+        Value::String(s) => match tag_of(&s).and_then(|tag| favorites.get(tag)) {
+            Some(favorite) => favorite.value.clone(),
+            None => Value::String(s),
+        },
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| substitute(item, favorites))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, item)| (key, substitute(item, favorites)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Register the favorites tools and expand `@tag` arguments in every call
+pub fn register(registry: &mut ToolRegistry, store: Arc<FavoriteStore>) -> Result<()> {
+    registry.add_middleware(Arc::clone(&store) as Arc<dyn ToolMiddleware>);
+    registry.register_all(store.get_tool_definitions(), move |name, arguments| {
+        let store = Arc::clone(&store);
+        async move { store.execute_tool(&name, arguments).await }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn favorite(tag: &str, kind: &str, value: Value) -> Favorite {
+        Favorite {
+            tag: tag.to_string(),
+            kind: kind.to_string(),
+            value,
+            module: Some("infrastructure".to_string()),
+            note: None,
+            created_at: Utc::now(),
+            uses: 0,
+            last_used: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn expands_tags_in_nested_arguments() {
+        let store = FavoriteStore::open(FavoritesConfig::default())
+            .await
+            .unwrap();
+        store
+            .add("ops", favorite("prod-api", "deployment", json!("shop/api")))
+            .await
+            .unwrap();
+        store
+            .add("ops", favorite("AAPL", "symbol", json!("AAPL")))
+            .await
+            .unwrap();
+        assert!(store
+            .add("ops", favorite("bad tag", "pod", json!("x")))
+            .await
+            .is_err());
+
+        let expanded = store
+            .expand(
+                "ops",
+                json!({"deployment": "@prod-api", "targets": ["@prod-api", "@unknown"], "text": "ping @prod-api"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(expanded["deployment"], "shop/api");
+        assert_eq!(expanded["targets"], json!(["shop/api", "@unknown"]));
+        assert_eq!(expanded["text"], "ping @prod-api");
+
+        let other = store
+            .expand("dev", json!({"deployment": "@prod-api"}))
+            .await
+            .unwrap();
+        assert_eq!(other["deployment"], "@prod-api");
+    }
+
+    #[tokio::test]
+    async fn lists_most_used_favorites_first() {
+        let store = FavoriteStore::open(FavoritesConfig::default())
+            .await
+            .unwrap();
+        store
+            .add("ops", favorite("api", "deployment", json!("shop/api")))
+            .await
+            .unwrap();
+        store
+            .add("ops", favorite("board", "dashboard", json!("abc123")))
+            .await
+            .unwrap();
+        store.expand("ops", json!({"uid": "@board"})).await.unwrap();
+
+        let all = store.list("ops", &FavoriteFilter::default()).await;
+        assert_eq!(all[0].tag, "board");
+        assert_eq!(all[0].uses, 1);
+        let filter = FavoriteFilter {
+            kind: Some("deployment".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(store.list("ops", &filter).await.len(), 1);
+        store.remove("ops", "@api").await.unwrap();
+        assert!(store.remove("ops", "api").await.is_err());
+    }
+}
@@ -0,0 +1,598 @@
+/// Tool help for self-correcting clients
+///
+/// `describe_tool` answers with everything a model needs to fix a rejected
+/// call: the full input and output schemas, example calls, the errors the
+/// tool commonly returns with how to fix them, and related tools. Examples
+/// and errors come from two places: curated JSON files, and ones generated
+/// from the schema so every registered tool has some. Each example is
+/// checked against the schema, so a stale curated example shows up as
+/// `valid: false` instead of misleading the model.
+use crate::ai::query_translation::edit_distance;
+use crate::error::{Error, Result};
+use crate::tools::registry::ToolRegistry;
+use crate::tools::{call_result, ToolDefinition};
+use jsonschema::JSONSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Name of the help tool
+pub const DESCRIBE_TOOL: &str = "describe_tool";
+
+/// Most related tools listed per description
+const MAX_RELATED: usize = 5;
+
+const BUILTIN_EXAMPLES: &[&str] = &[
+    include_str!("examples/infrastructure.json"),
+    include_str!("examples/data.json"),
+];
+
+/// Example call to a tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolExample {
+    pub description: String,
+    pub arguments: Value,
+}
+
+/// An error a tool commonly returns and how to avoid it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommonError {
+    /// Text found in the error message
+    pub error: String,
+    pub fix: String,
+}
+
+/// Curated help for one tool, as found in example files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CuratedHelp {
+    pub tool: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub examples: Vec<ToolExample>,
+    #[serde(default)]
+    pub errors: Vec<CommonError>,
+    #[serde(default)]
+    pub related: Vec<String>,
+}
+
+/// Example with where it came from and whether the schema accepts it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotatedExample {
+    pub description: String,
+    pub arguments: Value,
+    /// `curated` or `generated`
+    pub source: String,
+    pub valid: bool,
+}
+
+/// Tool worth knowing about alongside the described one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedTool {
+    pub name: String,
+    pub description: String,
+    pub reason: String,
+}
+
+/// Everything `describe_tool` reports about a tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDescription {
+    pub name: String,
+    pub description: String,
+    pub category: Option<String>,
+    pub notes: Option<String>,
+    pub input_schema: Value,
+    pub output_schema: Option<Value>,
+    pub examples: Vec<AnnotatedExample>,
+    pub common_errors: Vec<CommonError>,
+    pub related: Vec<RelatedTool>,
+}
+
+/// Help built from a registry's definitions and curated example files
+#[derive(Debug, Clone, Default)]
+pub struct HelpIndex {
+    tools: BTreeMap<String, ToolDefinition>,
+    curated: HashMap<String, CuratedHelp>,
+}
+
+impl HelpIndex {
+    /// Index the tools registered so far, with the built-in curated examples
+    pub fn from_registry(registry: &ToolRegistry) -> Self {
+        let mut index = Self {
+            tools: registry
+                .definitions()
+                .into_iter()
+                .map(|definition| (definition.name.clone(), definition))
+                .collect(),
+            curated: HashMap::new(),
+        };
+        for examples in BUILTIN_EXAMPLES {
+            index
+                .add_examples_json(examples)
+                .expect("built-in tool examples are valid");
+        }
+        index
+    }
+
+    /// Add a tool definition to the index
+    pub fn add_tool(&mut self, definition: ToolDefinition) {
+        self.tools.insert(definition.name.clone(), definition);
+    }
+
+    /// Add curated help from a JSON array of entries
+    ///
+    /// Entries for the same tool are merged, so a deployment can add
+    /// examples to a built-in tool without repeating the shipped ones.
+    pub fn add_examples_json(&mut self, json: &str) -> Result<()> {
+        let entries: Vec<CuratedHelp> = serde_json::from_str(json)
+            .map_err(|e| Error::parsing(format!("Invalid tool examples: {}", e)))?;
+        for entry in entries {
+            let help = self
+                .curated
+                .entry(entry.tool.clone())
+                .or_insert_with(|| CuratedHelp {
+                    tool: entry.tool.clone(),
+                    ..Default::default()
+                });
+            if entry.notes.is_some() {
+                help.notes = entry.notes;
+            }
+            help.examples.extend(entry.examples);
+            help.errors.extend(entry.errors);
+            help.related.extend(entry.related);
+        }
+        Ok(())
+    }
+
+    /// Add curated help from every `.json` file in `dir`
+    pub fn load_dir(&mut self, dir: &Path) -> Result<usize> {
+        let mut loaded = 0;
+        let entries = std::fs::read_dir(dir).map_err(|e| {
+            Error::io_with_path(format!("Failed to read examples: {}", e), dir.into())
+        })?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let json = std::fs::read_to_string(&path).map_err(|e| {
+                    Error::io_with_path(format!("Failed to read examples: {}", e), path.clone())
+                })?;
+                self.add_examples_json(&json)?;
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Describe a tool, or fail with the closest tool names
+    pub fn describe(&self, name: &str) -> Result<ToolDescription> {
+        let Some(tool) = self.tools.get(name) else {
+            let suggestions = self.similar_names(name);
+            let message = if suggestions.is_empty() {
+                format!("Tool not found: {}", name)
+            } else {
+                format!(
+                    "Tool not found: {}. Did you mean: {}?",
+                    name,
+                    suggestions.join(", ")
+                )
+            };
+            return Err(Error::not_found_with_resource(message, "tool", name));
+        };
+
+        let schema = tool
+            .parameters
+            .clone()
+            .unwrap_or_else(|| json!({"type": "object", "properties": {}}));
+        let validator = JSONSchema::compile(&schema).ok();
+        let is_valid = |arguments: &Value| {
+            validator
+                .as_ref()
+                .is_none_or(|validator| validator.is_valid(arguments))
+        };
+        let curated = self.curated.get(name);
+
+        let mut examples: Vec<AnnotatedExample> = curated
+            .into_iter()
+            .flat_map(|help| &help.examples)
+            .map(|example| AnnotatedExample {
+                description: example.description.clone(),
+                arguments: example.arguments.clone(),
+                source: "curated".to_string(),
+                valid: is_valid(&example.arguments),
+            })
+            .collect();
+        for (description, arguments) in generated_examples(&schema) {
+            if !examples.iter().any(|e| e.arguments == arguments) {
+                examples.push(AnnotatedExample {
+                    valid: is_valid(&arguments),
+                    description,
+                    arguments,
+                    source: "generated".to_string(),
+                });
+            }
+        }
+
+        let mut common_errors: Vec<CommonError> = curated
+            .into_iter()
+            .flat_map(|help| help.errors.clone())
+            .collect();
+        common_errors.extend(schema_errors(&schema));
+
+        Ok(ToolDescription {
+            name: tool.name.clone(),
+            description: tool.description.clone(),
+            category: tool.category().map(String::from),
+            notes: curated.and_then(|help| help.notes.clone()),
+            input_schema: schema,
+            output_schema: tool.output_schema.clone(),
+            examples,
+            common_errors,
+            related: self.related(tool, curated),
+        })
+    }
+
+    /// Curated related tools first, then tools in the same category sharing name words
+    fn related(&self, tool: &ToolDefinition, curated: Option<&CuratedHelp>) -> Vec<RelatedTool> {
+        let mut related: Vec<RelatedTool> = curated
+            .into_iter()
+            .flat_map(|help| &help.related)
+            .filter_map(|name| self.tools.get(name))
+            .map(|other| RelatedTool {
+                name: other.name.clone(),
+                description: other.description.clone(),
+                reason: "often used together".to_string(),
+            })
+            .collect();
+
+        let words: Vec<&str> = tool.name.split('_').collect();
+        let mut candidates: Vec<(usize, &ToolDefinition)> = self
+            .tools
+            .values()
+            .filter(|other| other.name != tool.name && other.name != DESCRIBE_TOOL)
+            .filter(|other| other.category().is_some() && other.category() == tool.category())
+            .map(|other| {
+                let shared = other
+                    .name
+                    .split('_')
+                    .filter(|word| word.len() > 2 && words.contains(word))
+                    .count();
+                (shared, other)
+            })
+            .filter(|(shared, _)| *shared > 0)
+            .collect();
+        candidates.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.name.cmp(&b.1.name)));
+        for (_, other) in candidates {
+            if related.len() >= MAX_RELATED {
+                break;
+            }
+            if !related.iter().any(|r| r.name == other.name) {
+                related.push(RelatedTool {
+                    name: other.name.clone(),
+                    description: other.description.clone(),
+                    reason: "same category".to_string(),
+                });
+            }
+        }
+        related.truncate(MAX_RELATED);
+        related
+    }
+
+    /// Registered names containing, or contained in, `name`, or a close misspelling
+    fn similar_names(&self, name: &str) -> Vec<String> {
+        let name = name.to_ascii_lowercase();
+        let mut scored: Vec<(usize, &String)> = self
+            .tools
+            .keys()
+            .filter_map(|candidate| {
+                let distance = if candidate.contains(&name) || name.contains(candidate.as_str()) {
+                    0
+                } else {
+                    edit_distance(candidate, &name)
+                };
+                (distance <= 3).then_some((distance, candidate))
+            })
+            .collect();
+        scored.sort();
+        scored
+            .into_iter()
+            .take(3)
+            .map(|(_, name)| name.clone())
+            .collect()
+    }
+
+    /// Get tool definitions for the help tool
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition::from_json_schema(
+            DESCRIBE_TOOL,
+            "Describe a tool: full schema, example calls, common errors and related tools. Use it after a call is rejected",
+            "core",
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string", "description": "Tool name"}
+                },
+                "required": ["name"]
+            }),
+            None,
+        )
+        .read_only()]
+    }
+
+    /// Execute the help tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            DESCRIBE_TOOL => {
+                let params: DescribeParams = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid parameters: {}", e)))?;
+                let description = self.describe(&params.name)?;
+                Ok(call_result(
+                    render(&description),
+                    serde_json::to_value(&description)?,
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "help_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DescribeParams {
+    name: String,
+}
+
+/// Register `describe_tool` over every tool registered so far
+///
+/// Call this last so the index covers the whole registry.
+pub fn register(registry: &ToolRegistry) -> Result<()> {
+    let mut index = HelpIndex::from_registry(registry);
+    for definition in index.get_tool_definitions() {
+        index.add_tool(definition);
+    }
+    let index = Arc::new(index);
+    registry.register_all(index.get_tool_definitions(), move |name, arguments| {
+        let index = Arc::clone(&index);
+        async move { index.execute_tool(&name, arguments).await }
+    })
+}
+
+/// Placeholder value satisfying a property schema
+fn sample_value(name: &str, schema: &Value) -> Value {
+    if let Some(value) = schema.get("default").or_else(|| {
+        schema
+            .get("examples")
+            .and_then(|e| e.as_array())
+            .and_then(|e| e.first())
+    }) {
+        return value.clone();
+    }
+    if let Some(first) = schema
+        .get("enum")
+        .and_then(|e| e.as_array())
+        .and_then(|e| e.first())
+    {
+        return first.clone();
+    }
+    match schema.get("type").and_then(|t| t.as_str()) {
+        Some("integer") => json!(schema
+            .get("minimum")
+            .and_then(|m| m.as_i64())
+            .unwrap_or(1)
+            .max(1)),
+        Some("number") => json!(schema
+            .get("minimum")
+            .and_then(|m| m.as_f64())
+            .unwrap_or(1.0)),
+        Some("boolean") => json!(true),
+        Some("array") => {
+            let item = schema
+                .get("items")
+                .map(|items| sample_value(name, items))
+                .unwrap_or_else(|| json!(format!("<{}>", name)));
+            json!([item])
+        }
+        Some("object") => sample_object(schema, false),
+        _ => json!(format!("<{}>", name)),
+    }
+}
+
+/// Object with the required properties, or all of them when `full`
+fn sample_object(schema: &Value, full: bool) -> Value {
+    let required = required(schema);
+    let mut arguments = Map::new();
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        for (name, property) in properties {
+            if full || required.contains(&name.as_str()) {
+                arguments.insert(name.clone(), sample_value(name, property));
+            }
+        }
+    }
+    Value::Object(arguments)
+}
+
+fn required(schema: &Value) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|name| name.as_str()).collect())
+        .unwrap_or_default()
+}
+
+/// A minimal call and, when there are optional parameters, one using all of them
+fn generated_examples(schema: &Value) -> Vec<(String, Value)> {
+    let minimal = sample_object(schema, false);
+    let full = sample_object(schema, true);
+    let mut examples = vec![(
+        "Required parameters only; replace <placeholders> with real values".to_string(),
+        minimal.clone(),
+    )];
+    if full != minimal {
+        examples.push((
+            "Every parameter, optional ones at their defaults".to_string(),
+            full,
+        ));
+    }
+    examples
+}
+
+/// Errors the schema itself will produce, with fixes
+fn schema_errors(schema: &Value) -> Vec<CommonError> {
+    let properties = schema.get("properties").and_then(|p| p.as_object());
+    let describe = |name: &str| {
+        properties
+            .and_then(|p| p.get(name))
+            .and_then(|p| p.get("description"))
+            .and_then(|d| d.as_str())
+            .map(|d| format!(": {}", d))
+            .unwrap_or_default()
+    };
+
+    let mut errors: Vec<CommonError> = required(schema)
+        .into_iter()
+        .map(|name| CommonError {
+            error: format!("\"{}\" is a required property", name),
+            fix: format!("Pass `{}`{}", name, describe(name)),
+        })
+        .collect();
+    for (name, property) in properties.into_iter().flatten() {
+        if let Some(allowed) = property.get("enum").and_then(|e| e.as_array()) {
+            let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+            errors.push(CommonError {
+                error: "is not one of".to_string(),
+                fix: format!("`{}` must be one of {}", name, allowed.join(", ")),
+            });
+        }
+        if let Some(minimum) = property.get("minimum") {
+            errors.push(CommonError {
+                error: format!("is less than the minimum of {}", minimum),
+                fix: format!("`{}` must be at least {}", name, minimum),
+            });
+        }
+        if let Some(maximum) = property.get("maximum") {
+            errors.push(CommonError {
+                error: format!("is greater than the maximum of {}", maximum),
+                fix: format!("`{}` must be at most {}", name, maximum),
+            });
+        }
+        if let Some(kind) = property.get("type").and_then(|t| t.as_str()) {
+            if kind != "string" {
+                errors.push(CommonError {
+                    error: format!("is not of type \"{}\"", kind),
+                    fix: format!("`{}` must be a JSON {}, not a quoted string", name, kind),
+                });
+            }
+        }
+    }
+    errors
+}
+
+fn render(description: &ToolDescription) -> String {
+    let mut text = format!("{}: {}", description.name, description.description);
+    if let Some(notes) = &description.notes {
+        text.push_str(&format!("\n\n{}", notes));
+    }
+    text.push_str("\n\nExamples:");
+    for example in &description.examples {
+        text.push_str(&format!(
+            "\n- {}{}\n  {}",
+            example.description,
+            if example.valid {
+                ""
+            } else {
+                " (rejected by the current schema)"
+            },
+            example.arguments
+        ));
+    }
+    if !description.common_errors.is_empty() {
+        text.push_str("\n\nCommon errors:");
+        for error in &description.common_errors {
+            text.push_str(&format!("\n- {}: {}", error.error, error.fix));
+        }
+    }
+    if !description.related.is_empty() {
+        text.push_str("\n\nRelated tools:");
+        for tool in &description.related {
+            text.push_str(&format!("\n- {}: {}", tool.name, tool.description));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> ToolRegistry {
+        let registry = ToolRegistry::new();
+        for (name, schema) in [
+            (
+                "get_container_logs",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "container_id": {"type": "string", "description": "Container ID or name"},
+                        "lines": {"type": "integer", "minimum": 1, "default": 100}
+                    },
+                    "required": ["container_id"]
+                }),
+            ),
+            (
+                "list_docker_containers",
+                json!({"type": "object", "properties": {"all": {"type": "boolean"}}}),
+            ),
+        ] {
+            registry
+                .register(
+                    ToolDefinition::from_json_schema(name, name, "infrastructure", schema, None),
+                    |_| async { Ok(json!({})) },
+                )
+                .unwrap();
+        }
+        registry
+    }
+
+    #[test]
+    fn describes_tools_with_examples_errors_and_related_tools() {
+        let index = HelpIndex::from_registry(&registry());
+        let description = index.describe("get_container_logs").unwrap();
+
+        assert!(description.examples.iter().any(|e| e.source == "curated"));
+        assert!(description.examples.iter().all(|e| e.valid));
+        let generated = description
+            .examples
+            .iter()
+            .find(|e| e.source == "generated")
+            .unwrap();
+        assert_eq!(
+            generated.arguments,
+            json!({"container_id": "<container_id>"})
+        );
+        assert!(description
+            .common_errors
+            .iter()
+            .any(|e| e.error == "\"container_id\" is a required property"));
+        assert_eq!(description.related[0].name, "list_docker_containers");
+
+        let error = index.describe("get_container_log").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Did you mean: get_container_logs"));
+    }
+
+    #[tokio::test]
+    async fn registers_describe_tool_over_the_registry() {
+        let registry = registry();
+        register(&registry).unwrap();
+        let result = registry
+            .call(DESCRIBE_TOOL, json!({"name": DESCRIBE_TOOL}))
+            .await
+            .unwrap();
+        assert_eq!(result["structuredContent"]["name"], DESCRIBE_TOOL);
+    }
+}
@@ -6,6 +6,22 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+pub mod bulk;
+pub mod compose;
+pub mod favorites;
+pub mod help;
+pub mod preferences;
+pub mod registry;
+pub mod snapshot;
+pub mod stream;
+
+pub use compose::{ReloadableModules, ServerModules};
+pub use registry::{
+    current_client, Session, ToolChangeKind, ToolHandler, ToolListDiff, ToolMiddleware,
+    ToolRegistry, ToolSet,
+};
+pub use stream::{ToolResultChunk, ToolResultStream, ToolStream};
+
 /// Content block for tool outputs with performance optimization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContentBlock {
@@ -32,6 +48,121 @@ impl ContentBlock {
             metadata: None,
         }
     }
+
+    /// Whether the block holds text
+    pub fn is_text(&self) -> bool {
+        self.content_type == "text" || self.content_type.starts_with("text/")
+    }
+
+    /// Block in MCP `tools/call` content form
+    pub fn to_mcp(&self) -> Value {
+        if self.is_text() {
+            serde_json::json!({"type": "text", "text": self.content})
+        } else if self.content_type.starts_with("image/") {
+            serde_json::json!({"type": "image", "data": self.content, "mimeType": self.content_type})
+        } else {
+            serde_json::json!({
+                "type": "resource",
+                "resource": {"mimeType": self.content_type, "text": self.content}
+            })
+        }
+    }
+
+    /// Block from MCP `tools/call` content, for the types this crate produces
+    pub fn from_mcp(block: &Value) -> Option<Self> {
+        let str_at = |pointer: &str| block.pointer(pointer).and_then(|v| v.as_str());
+        match block.get("type")?.as_str()? {
+            "text" => Some(Self::text(str_at("/text")?)),
+            "image" => Some(Self::new(str_at("/mimeType")?, str_at("/data")?)),
+            "resource" => Some(Self::new(
+                str_at("/resource/mimeType").unwrap_or("text/plain"),
+                str_at("/resource/text")?,
+            )),
+            "resource_link" => Some(Self::new("text/uri-list", str_at("/uri")?)),
+            _ => None,
+        }
+    }
+
+    /// Like [`ContentBlock::from_mcp`], moving the strings out of an owned block
+    pub fn from_mcp_owned(block: Value) -> Option<Self> {
+        let Value::Object(mut block) = block else {
+            return None;
+        };
+        match take_string(&mut block, "type")?.as_str() {
+            "text" => Some(Self::text(take_string(&mut block, "text")?)),
+            "image" => {
+                let mime_type = take_string(&mut block, "mimeType")?;
+                Some(Self::new(mime_type, take_string(&mut block, "data")?))
+            }
+            "resource" => {
+                let Some(Value::Object(mut resource)) = block.remove("resource") else {
+                    return None;
+                };
+                let mime_type = take_string(&mut resource, "mimeType")
+                    .unwrap_or_else(|| "text/plain".to_string());
+                Some(Self::new(mime_type, take_string(&mut resource, "text")?))
+            }
+            "resource_link" => Some(Self::new("text/uri-list", take_string(&mut block, "uri")?)),
+            _ => None,
+        }
+    }
+}
+
+fn take_string(object: &mut serde_json::Map<String, Value>, key: &str) -> Option<String> {
+    match object.remove(key)? {
+        Value::String(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Build a `tools/call` result with a text summary and structured content
+pub fn call_result(text: impl Into<String>, structured: Value) -> Value {
+    serde_json::json!({
+        "content": [{
+            "type": "text",
+            "text": text.into()
+        }],
+        "structuredContent": structured
+    })
+}
+
+/// Build a `tools/call` result with a text summary, an image and structured content
+pub fn image_result(
+    text: impl Into<String>,
+    data: &[u8],
+    mime_type: &str,
+    structured: Value,
+) -> Value {
+    use base64::Engine;
+
+    serde_json::json!({
+        "content": [
+            {
+                "type": "text",
+                "text": text.into()
+            },
+            {
+                "type": "image",
+                "data": base64::engine::general_purpose::STANDARD.encode(data),
+                "mimeType": mime_type
+            }
+        ],
+        "structuredContent": structured
+    })
+}
+
+/// `YYYY-MM-DD` date argument, or `default` when unset
+pub fn parse_date(
+    value: Option<&str>,
+    field: &str,
+    default: chrono::NaiveDate,
+) -> Result<chrono::NaiveDate> {
+    match value {
+        None => Ok(default),
+        Some(value) => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+            Error::validation_with_field(format!("{} must be a date like 2024-03-15", field), field)
+        }),
+    }
 }
 
 /// Progress information for long-running operations
@@ -240,6 +371,32 @@ impl ToolDefinition {
         self.required_parameters = required;
         self
     }
+
+    /// Category recorded by `from_json_schema`, naming the module the tool belongs to
+    pub fn category(&self) -> Option<&str> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("category"))
+            .and_then(|category| category.as_str())
+    }
+
+    /// Mark the tool as only reading state, so `<category>:read` scopes cover it
+    pub fn read_only(mut self) -> Self {
+        self.metadata
+            .get_or_insert_with(HashMap::new)
+            .insert("read_only".to_string(), Value::Bool(true));
+        self
+    }
+
+    /// Whether the tool is marked as only reading state; unmarked tools
+    /// count as writing
+    pub fn is_read_only(&self) -> bool {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("read_only"))
+            .and_then(|read_only| read_only.as_bool())
+            .unwrap_or(false)
+    }
 }
 
 /// Schema validator with performance optimizations
@@ -0,0 +1,564 @@
+/// Per-client preferences applied as tool argument defaults
+///
+/// Clients store defaults such as their Kubernetes namespace or cloud region
+/// with `preferences_set`; when a later call leaves out an argument a
+/// preference covers, the store fills it in before the arguments are
+/// validated. Operators set policy per preference in the config file: a
+/// default for clients that set nothing, the values clients may pick, or a
+/// locked value that clients cannot change and calls cannot override.
+use crate::error::{Error, Result};
+use crate::i18n;
+use crate::tools::registry::{current_client, ToolRegistry};
+use crate::tools::snapshot::SnapshotSource;
+use crate::tools::{call_result, ToolDefinition, ToolMiddleware};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Client name used when a caller does not identify itself
+pub const DEFAULT_CLIENT: &str = "default";
+
+/// Known preferences and the tool arguments each one fills
+const PREFERENCES: &[(&str, &[&str])] = &[
+    ("namespace", &["namespace"]),
+    ("subscription", &["subscription_id", "subscription"]),
+    ("region", &["region", "location"]),
+    ("risk_tolerance", &["risk_tolerance"]),
+];
+
+const RISK_TOLERANCES: &[&str] = &["conservative", "moderate", "aggressive"];
+
+/// Operator policy for one preference
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreferenceRule {
+    /// Value used for clients that have not set their own
+    #[serde(default)]
+    pub default: Option<Value>,
+    /// Values clients may choose; any valid value when unset
+    #[serde(default)]
+    pub allowed: Option<Vec<Value>>,
+    /// Always use `default`, rejecting client preferences and differing arguments
+    #[serde(default)]
+    pub locked: bool,
+}
+
+/// Preference settings, under `preferences` in the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreferencesConfig {
+    /// Where client preferences are persisted; `None` keeps them in memory
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Policy by preference key
+    #[serde(default)]
+    pub rules: BTreeMap<String, PreferenceRule>,
+}
+
+impl PreferencesConfig {
+    /// Check that rules name known preferences and their defaults are allowed
+    pub fn validate(&self) -> Result<()> {
+        for (key, rule) in &self.rules {
+            aliases(key)?;
+            if rule.locked && rule.default.is_none() {
+                return Err(Error::config_with_suggestion(
+                    format!("Preference {} is locked without a default", key),
+                    "Set a default for locked preferences",
+                ));
+            }
+            if let Some(default) = &rule.default {
+                check_value(key, default, Some(rule))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where an effective preference comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreferenceSource {
+    Client,
+    Policy,
+}
+
+/// A preference value as applied to a client's calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectivePreference {
+    pub key: String,
+    pub value: Value,
+    pub source: PreferenceSource,
+    pub locked: bool,
+    /// Tool arguments the value fills in
+    pub arguments: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PreferenceData {
+    clients: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+/// Persisted preferences by client
+pub struct PreferenceStore {
+    path: Option<PathBuf>,
+    rules: BTreeMap<String, PreferenceRule>,
+    store: RwLock<PreferenceData>,
+}
+
+impl PreferenceStore {
+    /// Open the store, loading preferences from the configured path if it exists
+    pub async fn open(config: PreferencesConfig) -> Result<Self> {
+        config.validate()?;
+        let store = match &config.path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    Error::parsing(format!(
+                        "Failed to parse preference store {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => PreferenceData::default(),
+                Err(e) => {
+                    return Err(Error::io_with_path(
+                        format!("Failed to read preference store: {}", e),
+                        path.clone(),
+                    ))
+                }
+            },
+            None => PreferenceData::default(),
+        };
+        Ok(Self {
+            path: config.path,
+            rules: config.rules,
+            store: RwLock::new(store),
+        })
+    }
+
+    async fn persist(&self, store: &PreferenceData) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to create preference store directory: {}", e),
+                    parent.to_path_buf(),
+                )
+            })?;
+        }
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(store)?)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to write preference store: {}", e),
+                    temp.clone(),
+                )
+            })?;
+        tokio::fs::rename(&temp, path).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to replace preference store: {}", e),
+                path.clone(),
+            )
+        })
+    }
+
+    /// Set or, with `None`, clear a client's preference, returning the old value
+    pub async fn set(
+        &self,
+        client: &str,
+        key: &str,
+        value: Option<Value>,
+    ) -> Result<Option<Value>> {
+        aliases(key)?;
+        let rule = self.rules.get(key);
+        if rule.is_some_and(|rule| rule.locked) {
+            return Err(Error::validation_with_field(
+                format!("Preference {} is locked by policy", key),
+                "key",
+            ));
+        }
+        if let Some(value) = &value {
+            check_value(key, value, rule)?;
+        }
+        let mut store = self.store.write().await;
+        let previous = match value {
+            Some(value) => store
+                .clients
+                .entry(client.to_string())
+                .or_default()
+                .insert(key.to_string(), value),
+            None => {
+                let previous = store
+                    .clients
+                    .get_mut(client)
+                    .and_then(|preferences| preferences.remove(key));
+                store
+                    .clients
+                    .retain(|_, preferences| !preferences.is_empty());
+                previous
+            }
+        };
+        self.persist(&store).await?;
+        Ok(previous)
+    }
+
+    /// Preferences applied to `client`'s calls, by key
+    pub async fn effective(&self, client: &str) -> BTreeMap<String, EffectivePreference> {
+        let store = self.store.read().await;
+        let saved = store.clients.get(client);
+        PREFERENCES
+            .iter()
+            .filter_map(|(key, arguments)| {
+                let rule = self.rules.get(*key);
+                let locked = rule.is_some_and(|rule| rule.locked);
+                let policy = rule.and_then(|rule| rule.default.clone());
+                let (value, source) = match saved.and_then(|saved| saved.get(*key)) {
+                    Some(value) if !locked => (value.clone(), PreferenceSource::Client),
+                    _ => (policy?, PreferenceSource::Policy),
+                };
+                let preference = EffectivePreference {
+                    key: key.to_string(),
+                    value,
+                    source,
+                    locked,
+                    arguments: arguments.iter().map(|a| a.to_string()).collect(),
+                };
+                Some((key.to_string(), preference))
+            })
+            .collect()
+    }
+
+    /// Fill arguments of `tool` the call left out from `client`'s preferences
+    ///
+    /// Only arguments in the tool's input schema are filled. An explicit
+    /// argument wins unless the preference is locked, in which case a
+    /// differing value is rejected.
+    pub async fn apply(
+        &self,
+        client: &str,
+        tool: &ToolDefinition,
+        arguments: Value,
+    ) -> Result<Value> {
+        let Some(properties) = tool
+            .parameters
+            .as_ref()
+            .and_then(|schema| schema.get("properties"))
+            .and_then(|p| p.as_object())
+        else {
+            return Ok(arguments);
+        };
+        let mut arguments = match arguments {
+            Value::Null => json!({}),
+            Value::Object(arguments) => Value::Object(arguments),
+            other => return Ok(other),
+        };
+        for preference in self.effective(client).await.into_values() {
+            for argument in &preference.arguments {
+                if !properties.contains_key(argument) {
+                    continue;
+                }
+                match arguments.get(argument) {
+                    None | Some(Value::Null) => {
+                        arguments[argument] = preference.value.clone();
+                    }
+                    Some(given) if preference.locked && *given != preference.value => {
+                        return Err(Error::validation_with_field(
+                            format!(
+                                "{} is fixed to {} by policy for {}",
+                                argument, preference.value, tool.name
+                            ),
+                            argument.clone(),
+                        ));
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        Ok(arguments)
+    }
+
+    /// Get tool definitions for reading and setting preferences
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let keys: Vec<&str> = PREFERENCES.iter().map(|(key, _)| *key).collect();
+        vec![
+            ToolDefinition::from_json_schema(
+                "preferences_set",
+                "Save a default the server fills in when your tool calls leave the matching argument out",
+                "core",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "key": {"type": "string", "enum": keys, "description": "Preference to set"},
+                        "value": {"description": "New value; omit or null to clear the preference"}
+                    },
+                    "required": ["key"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "preferences_get",
+                "Show the defaults applied to your tool calls and whether they come from you or from policy",
+                "core",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "key": {"type": "string", "enum": keys, "description": "Preference to show (default: all)"}
+                    }
+                }),
+                None,
+            )
+            .read_only(),
+        ]
+    }
+
+    /// Execute a preference tool for the calling client
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let client = current_client().unwrap_or_else(|| DEFAULT_CLIENT.to_string());
+        match name {
+            "preferences_set" => {
+                let params: SetParams = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid parameters: {}", e)))?;
+                let value = params.value.filter(|v| !v.is_null());
+                let previous = self.set(&client, &params.key, value.clone()).await?;
+                let text = match &value {
+                    Some(value) => i18n::text(
+                        "messages.preferences.set",
+                        &[("key", &params.key), ("value", value)],
+                    ),
+                    None => i18n::text("messages.preferences.cleared", &[("key", &params.key)]),
+                };
+                Ok(call_result(
+                    text,
+                    json!({
+                        "client": client,
+                        "key": params.key,
+                        "value": value,
+                        "previous": previous,
+                    }),
+                ))
+            }
+            "preferences_get" => {
+                let params: GetParams = serde_json::from_value(parameters)
+                    .map_err(|e| Error::validation(format!("Invalid parameters: {}", e)))?;
+                if let Some(key) = &params.key {
+                    aliases(key)?;
+                }
+                let preferences: Vec<EffectivePreference> = self
+                    .effective(&client)
+                    .await
+                    .into_values()
+                    .filter(|p| params.key.as_ref().is_none_or(|key| *key == p.key))
+                    .collect();
+                let mut text = i18n::text(
+                    "messages.preferences.listed",
+                    &[("count", &preferences.len()), ("client", &client)],
+                );
+                for preference in &preferences {
+                    text.push_str(&format!("\n{} = {}", preference.key, preference.value));
+                    if preference.locked {
+                        text.push_str(" 🔒");
+                    }
+                }
+                Ok(call_result(
+                    text,
+                    json!({"client": client, "preferences": preferences}),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "preferences_tool",
+                name,
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolMiddleware for PreferenceStore {
+    async fn before_call(&self, tool: &ToolDefinition, arguments: Value) -> Result<Value> {
+        let client = current_client().unwrap_or_else(|| DEFAULT_CLIENT.to_string());
+        self.apply(&client, tool, arguments).await
+    }
+
+    async fn after_call(&self, _tool: &str, result: Value) -> Result<Value> {
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl SnapshotSource for PreferenceStore {
+    fn name(&self) -> &str {
+        "preferences"
+    }
+
+    async fn export_state(&self) -> Result<Value> {
+        Ok(serde_json::to_value(&*self.store.read().await)?)
+    }
+
+    async fn restore_state(&self, state: Value) -> Result<()> {
+        let restored: PreferenceData = serde_json::from_value(state)
+            .map_err(|e| Error::parsing(format!("Invalid preference snapshot: {}", e)))?;
+        let mut store = self.store.write().await;
+        self.persist(&restored).await?;
+        *store = restored;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SetParams {
+    key: String,
+    #[serde(default)]
+    value: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetParams {
+    #[serde(default)]
+    key: Option<String>,
+}
+
+/// Tool arguments filled by the preference `key`
+fn aliases(key: &str) -> Result<&'static [&'static str]> {
+    PREFERENCES
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, arguments)| *arguments)
+        .ok_or_else(|| {
+            Error::validation_with_field(
+                format!(
+                    "Unknown preference {}; expected one of: {}",
+                    key,
+                    PREFERENCES
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                "key",
+            )
+        })
+}
+
+/// Check a value for `key` against its type and the policy's allowed values
+fn check_value(key: &str, value: &Value, rule: Option<&PreferenceRule>) -> Result<()> {
+    let Some(text) = value.as_str().filter(|v| !v.trim().is_empty()) else {
+        return Err(Error::validation_with_field(
+            format!("Preference {} must be a non-empty string", key),
+            "value",
+        ));
+    };
+    if key == "risk_tolerance" && !RISK_TOLERANCES.contains(&text) {
+        return Err(Error::validation_with_field(
+            format!(
+                "Risk tolerance must be one of: {}",
+                RISK_TOLERANCES.join(", ")
+            ),
+            "value",
+        ));
+    }
+    if let Some(allowed) = rule.and_then(|rule| rule.allowed.as_ref()) {
+        if !allowed.contains(value) {
+            return Err(Error::validation_with_field(
+                format!("{} is not an allowed value for preference {}", value, key),
+                "value",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Register the preference tools and apply stored preferences to every call
+pub fn register(registry: &mut ToolRegistry, store: Arc<PreferenceStore>) -> Result<()> {
+    registry.add_middleware(Arc::clone(&store) as Arc<dyn ToolMiddleware>);
+    registry.register_all(store.get_tool_definitions(), move |name, arguments| {
+        let store = Arc::clone(&store);
+        async move { store.execute_tool(&name, arguments).await }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pods_tool() -> ToolDefinition {
+        ToolDefinition::from_json_schema(
+            "list_pods",
+            "List pods",
+            "infrastructure",
+            json!({
+                "type": "object",
+                "properties": {
+                    "namespace": {"type": "string"},
+                    "location": {"type": "string"}
+                }
+            }),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn fills_omitted_arguments_from_client_preferences() {
+        let path = std::env::temp_dir().join(format!("prefs-{}.json", uuid::Uuid::new_v4()));
+        let config = PreferencesConfig {
+            path: Some(path.clone()),
+            rules: BTreeMap::new(),
+        };
+        let store = PreferenceStore::open(config.clone()).await.unwrap();
+        store
+            .set("cursor", "namespace", Some(json!("shop")))
+            .await
+            .unwrap();
+        assert!(store
+            .set("cursor", "risk_tolerance", Some(json!("reckless")))
+            .await
+            .is_err());
+
+        let reopened = PreferenceStore::open(config).await.unwrap();
+        let tool = pods_tool();
+        let filled = reopened.apply("cursor", &tool, json!({})).await.unwrap();
+        assert_eq!(filled, json!({"namespace": "shop"}));
+        let explicit = reopened
+            .apply("cursor", &tool, json!({"namespace": "ops"}))
+            .await
+            .unwrap();
+        assert_eq!(explicit["namespace"], "ops");
+        let other = reopened.apply("zed", &tool, Value::Null).await.unwrap();
+        assert_eq!(other, json!({}));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn locked_policy_overrides_clients_and_arguments() {
+        let mut rules = BTreeMap::new();
+        rules.insert(
+            "region".to_string(),
+            PreferenceRule {
+                default: Some(json!("westeurope")),
+                allowed: None,
+                locked: true,
+            },
+        );
+        let store = PreferenceStore::open(PreferencesConfig { path: None, rules })
+            .await
+            .unwrap();
+        assert!(store
+            .set("cursor", "region", Some(json!("eastus")))
+            .await
+            .is_err());
+
+        let tool = pods_tool();
+        let filled = store.apply("cursor", &tool, json!({})).await.unwrap();
+        assert_eq!(filled["location"], "westeurope");
+        assert!(store
+            .apply("cursor", &tool, json!({"location": "eastus"}))
+            .await
+            .is_err());
+        let effective = store.effective("cursor").await;
+        assert_eq!(effective["region"].source, PreferenceSource::Policy);
+    }
+}
@@ -0,0 +1,1402 @@
+use crate::error::{Error, Result};
+use crate::i18n::{Catalog, Localizer};
+use crate::security::{PermissionPolicy, Principal};
+use crate::tools::stream::ToolStream;
+use crate::tools::ToolDefinition;
+use async_trait::async_trait;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::routing::post;
+use axum::{Json, Router};
+use futures::future::BoxFuture;
+use jsonschema::JSONSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+
+/// MCP protocol version served by the JSON-RPC router
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// MCP notification telling clients to refetch the tool list
+pub const LIST_CHANGED_METHOD: &str = "notifications/tools/list_changed";
+
+/// Changes kept for `tools_changed_since`; older cursors get the full list
+const CHANGE_LOG_LIMIT: usize = 1024;
+
+/// Async tool handler taking the call's arguments and a stream for partial output
+pub type ToolHandler =
+    Arc<dyn Fn(Value, ToolStream) -> BoxFuture<'static, Result<Value>> + Send + Sync>;
+
+tokio::task_local! {
+    static CLIENT: Option<String>;
+    static ACCESS: Access;
+}
+
+/// Caller of the request being served, checked against the registry's policy
+#[derive(Clone)]
+struct Access {
+    principal: Principal,
+    /// Token from `_meta.confirmation`
+    confirmation: Option<String>,
+}
+
+/// Client making the tool call being served, if it identified itself
+pub fn current_client() -> Option<String> {
+    CLIENT.try_with(Clone::clone).ok().flatten()
+}
+
+/// Principal the tool call being served was checked as, when a policy is set
+pub fn current_principal() -> Option<String> {
+    ACCESS.try_with(|access| access.principal.name.clone()).ok()
+}
+
+/// Processing around tool calls, applied in registration order
+#[async_trait]
+pub trait ToolMiddleware: Send + Sync {
+    /// Rewrite the arguments of a call to `tool` before they are validated
+    async fn before_call(&self, _tool: &ToolDefinition, arguments: Value) -> Result<Value> {
+        Ok(arguments)
+    }
+
+    /// Rewrite the result of a call to `tool`
+    async fn after_call(&self, tool: &str, result: Value) -> Result<Value>;
+}
+
+/// Per-connection state kept between requests
+///
+/// Holds the locale and client name declared in `initialize`, and the
+/// principal the connection's API key identifies. Stateless HTTP requests
+/// get a fresh session each time and pass `_meta.locale` and `_meta.client`
+/// instead.
+#[derive(Debug, Default)]
+pub struct Session {
+    locale: RwLock<Option<String>>,
+    client: RwLock<Option<String>>,
+    principal: RwLock<Option<Principal>>,
+}
+
+impl Session {
+    /// A session with no declared locale
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locale declared by the client, if any
+    pub fn locale(&self) -> Option<String> {
+        self.locale.read().ok().and_then(|locale| locale.clone())
+    }
+
+    /// Record the client's preferred locale
+    pub fn set_locale(&self, locale: Option<String>) {
+        if let Ok(mut current) = self.locale.write() {
+            *current = locale;
+        }
+    }
+
+    /// Name the client gave in `clientInfo`, if any
+    pub fn client(&self) -> Option<String> {
+        self.client.read().ok().and_then(|client| client.clone())
+    }
+
+    /// Record the client's name
+    pub fn set_client(&self, client: Option<String>) {
+        if let Ok(mut current) = self.client.write() {
+            *current = client;
+        }
+    }
+
+    /// Caller identified when the connection was opened, if any
+    pub fn principal(&self) -> Option<Principal> {
+        self.principal
+            .read()
+            .ok()
+            .and_then(|principal| principal.clone())
+    }
+
+    /// Record the caller
+    pub fn set_principal(&self, principal: Option<Principal>) {
+        if let Ok(mut current) = self.principal.write() {
+            *current = principal;
+        }
+    }
+}
+
+struct RegisteredTool {
+    definition: ToolDefinition,
+    schema: Option<JSONSchema>,
+    handler: ToolHandler,
+    enabled: bool,
+}
+
+/// How a tool changed, as seen by clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolChangeKind {
+    Added,
+    Updated,
+    Removed,
+}
+
+#[derive(Debug)]
+struct ToolChange {
+    revision: u64,
+    tool: String,
+    kind: ToolChangeKind,
+}
+
+#[derive(Debug, Default)]
+struct ChangeLog {
+    revision: u64,
+    /// Revision of the newest entry dropped to stay within the limit
+    trimmed_through: u64,
+    entries: VecDeque<ToolChange>,
+}
+
+/// Tools that changed after a cursor, in `tools/list` form
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolListDiff {
+    /// Cursor to pass on the next call
+    pub cursor: u64,
+    /// The cursor was too old or unknown, so `added` holds every tool
+    pub reset: bool,
+    pub added: Vec<Value>,
+    pub updated: Vec<Value>,
+    pub removed: Vec<String>,
+}
+
+/// Tools and middleware registered by one part of the server, so they can
+/// later be swapped for a rebuilt set as a whole
+#[derive(Clone, Default)]
+pub struct ToolSet {
+    names: BTreeSet<String>,
+    middleware: Vec<Arc<dyn ToolMiddleware>>,
+}
+
+impl ToolSet {
+    /// Names of the tools in the set
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+}
+
+/// Registry of tool definitions and their handlers
+///
+/// Modules register a `ToolDefinition` with an async handler; `tools/list`
+/// and `tools/call` are then served from the registry, so custom tools can be
+/// added by embedding applications without touching the server. Tools can
+/// also be added, updated, toggled or removed while the server runs; every
+/// such change advances a cursor and is announced to connected clients with
+/// `notifications/tools/list_changed`.
+pub struct ToolRegistry {
+    tools: RwLock<BTreeMap<String, RegisteredTool>>,
+    changes: RwLock<ChangeLog>,
+    list_changed: broadcast::Sender<u64>,
+    middleware: RwLock<Vec<Arc<dyn ToolMiddleware>>>,
+    policy: RwLock<Option<Arc<PermissionPolicy>>>,
+    catalog: Arc<Catalog>,
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self {
+            tools: RwLock::new(BTreeMap::new()),
+            changes: RwLock::new(ChangeLog::default()),
+            list_changed: broadcast::channel(16).0,
+            middleware: RwLock::new(Vec::new()),
+            policy: RwLock::new(None),
+            catalog: Catalog::shared_builtin(),
+        }
+    }
+}
+
+impl std::fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolRegistry")
+            .field("tools", &self.read_tools().keys().collect::<Vec<_>>())
+            .field("cursor", &self.cursor())
+            .field("middleware", &self.read_middleware().len())
+            .field("locales", &self.catalog.locales())
+            .finish()
+    }
+}
+
+impl ToolRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a tool with a handler receiving its raw JSON arguments
+    pub fn register<F, Fut>(&self, definition: ToolDefinition, handler: F) -> Result<()>
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value>> + Send + 'static,
+    {
+        self.register_streaming(definition, move |arguments, _stream| handler(arguments))
+    }
+
+    /// Register a tool whose handler may send partial output before its result
+    pub fn register_streaming<F, Fut>(&self, definition: ToolDefinition, handler: F) -> Result<()>
+    where
+        F: Fn(Value, ToolStream) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value>> + Send + 'static,
+    {
+        let schema = compile_schema(&definition)?;
+        let handler: ToolHandler =
+            Arc::new(move |arguments, stream| Box::pin(handler(arguments, stream)));
+        let name = definition.name.clone();
+        {
+            let mut tools = self.write_tools();
+            if tools.contains_key(&name) {
+                return Err(Error::validation_with_field(
+                    format!("Tool already registered: {}", name),
+                    "name",
+                ));
+            }
+            tools.insert(
+                name.clone(),
+                RegisteredTool {
+                    definition,
+                    schema,
+                    handler,
+                    enabled: true,
+                },
+            );
+        }
+        self.record(vec![(name, ToolChangeKind::Added)]);
+        Ok(())
+    }
+
+    /// Register a tool whose arguments are deserialized into `P`
+    pub fn register_typed<P, F, Fut>(&self, definition: ToolDefinition, handler: F) -> Result<()>
+    where
+        P: DeserializeOwned + Send + 'static,
+        F: Fn(P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.register(definition, move |arguments| {
+            let handler = Arc::clone(&handler);
+            async move {
+                let parameters: P = serde_json::from_value(arguments)
+                    .map_err(|e| Error::validation(format!("Invalid arguments: {}", e)))?;
+                handler(parameters).await
+            }
+        })
+    }
+
+    /// Register several tools served by one handler taking the tool name,
+    /// as with a module's `get_tool_definitions` and `execute_tool`
+    pub fn register_all<F, Fut>(&self, definitions: Vec<ToolDefinition>, handler: F) -> Result<()>
+    where
+        F: Fn(String, Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        for definition in definitions {
+            let handler = Arc::clone(&handler);
+            let name = definition.name.clone();
+            self.register(definition, move |arguments| {
+                handler(name.clone(), arguments)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Run `middleware` over every successful result, after any added before it
+    pub fn add_middleware(&mut self, middleware: Arc<dyn ToolMiddleware>) {
+        self.write_middleware().push(middleware);
+    }
+
+    /// Every tool and middleware registered so far
+    pub fn tool_set(&self) -> ToolSet {
+        ToolSet {
+            names: self.read_tools().keys().cloned().collect(),
+            middleware: self.read_middleware().clone(),
+        }
+    }
+
+    /// Replace the tools and middleware of `previous` with everything in
+    /// `next`, returning the new set
+    ///
+    /// Calls see either the old set or the new one, never a mix. Tools that
+    /// stay keep their enabled state, and clients are told about tools that
+    /// were added, removed or changed. Middleware of the new set takes the
+    /// place of the old set's, or runs first if the old set had none.
+    pub fn swap_set(&self, previous: &ToolSet, next: ToolRegistry) -> ToolSet {
+        let next_middleware = std::mem::take(&mut *next.write_middleware());
+        let next_tools = std::mem::take(&mut *next.write_tools());
+        let set = ToolSet {
+            names: next_tools.keys().cloned().collect(),
+            middleware: next_middleware.clone(),
+        };
+        let mut changes = Vec::new();
+        {
+            let mut tools = self.write_tools();
+            let mut middleware = self.write_middleware();
+            for name in previous.names.difference(&set.names) {
+                if tools.remove(name).is_some_and(|tool| tool.enabled) {
+                    changes.push((name.clone(), ToolChangeKind::Removed));
+                }
+            }
+            for (name, mut tool) in next_tools {
+                match tools.get(&name) {
+                    Some(current) => {
+                        tool.enabled = current.enabled;
+                        if tool.enabled
+                            && list_entry(&current.definition) != list_entry(&tool.definition)
+                        {
+                            changes.push((name.clone(), ToolChangeKind::Updated));
+                        }
+                    }
+                    None => changes.push((name.clone(), ToolChangeKind::Added)),
+                }
+                tools.insert(name, tool);
+            }
+            let position = middleware
+                .iter()
+                .position(|m| previous.middleware.iter().any(|p| Arc::ptr_eq(m, p)))
+                .unwrap_or(0);
+            middleware.retain(|m| !previous.middleware.iter().any(|p| Arc::ptr_eq(m, p)));
+            middleware.splice(position..position, next_middleware);
+        }
+        self.record(changes);
+        set
+    }
+
+    /// Check calls from clients against `policy`, or let every client use
+    /// every tool when `None`
+    pub fn set_policy(&self, policy: Option<Arc<PermissionPolicy>>) {
+        *self.policy.write().unwrap_or_else(PoisonError::into_inner) = policy;
+    }
+
+    fn policy(&self) -> Option<Arc<PermissionPolicy>> {
+        self.policy
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Session for a connection opened with `headers`, identifying its
+    /// caller by API key when a policy is set
+    pub fn session_for(&self, headers: &HeaderMap) -> Session {
+        let session = Session::new();
+        if let Some(policy) = self.policy() {
+            let key = crate::transport::tenancy::api_key(headers);
+            session.set_principal(Some(policy.authenticate(key)));
+        }
+        session
+    }
+
+    /// Whether the policy, if any, lets `principal` use the tool `name`
+    fn visible(&self, principal: Option<&Principal>, name: &str) -> bool {
+        let (Some(policy), Some(principal)) = (self.policy(), principal) else {
+            return true;
+        };
+        self.read_tools()
+            .get(name)
+            .is_some_and(|tool| policy.allows(principal, &tool.definition))
+    }
+
+    /// Serve translations from `catalog` instead of the built-in bundles
+    pub fn set_catalog(&mut self, catalog: Catalog) {
+        self.catalog = Arc::new(catalog);
+    }
+
+    /// Localizer reading this registry's catalog in `locale`
+    pub fn localizer(&self, locale: Option<&str>) -> Localizer {
+        Localizer::new(Arc::clone(&self.catalog), locale)
+    }
+
+    /// Remove a tool, returning its definition
+    pub fn unregister(&self, name: &str) -> Option<ToolDefinition> {
+        let tool = self.write_tools().remove(name)?;
+        if tool.enabled {
+            self.record(vec![(name.to_string(), ToolChangeKind::Removed)]);
+        }
+        Some(tool.definition)
+    }
+
+    /// Replace a tool's definition and schema, keeping its handler
+    pub fn update_definition(&self, definition: ToolDefinition) -> Result<()> {
+        let schema = compile_schema(&definition)?;
+        let name = definition.name.clone();
+        let enabled = {
+            let mut tools = self.write_tools();
+            let tool = tools
+                .get_mut(&name)
+                .ok_or_else(|| Error::not_found_with_resource("Tool not found", "tool", &name))?;
+            tool.definition = definition;
+            tool.schema = schema;
+            tool.enabled
+        };
+        if enabled {
+            self.record(vec![(name, ToolChangeKind::Updated)]);
+        }
+        Ok(())
+    }
+
+    /// Show or hide a tool; hidden tools are left out of `tools/list` and cannot be called
+    pub fn set_enabled(&self, name: &str, enabled: bool) -> Result<()> {
+        let changed = {
+            let mut tools = self.write_tools();
+            let tool = tools
+                .get_mut(name)
+                .ok_or_else(|| Error::not_found_with_resource("Tool not found", "tool", name))?;
+            std::mem::replace(&mut tool.enabled, enabled) != enabled
+        };
+        if changed {
+            self.record(vec![(name.to_string(), toggle_kind(enabled))]);
+        }
+        Ok(())
+    }
+
+    /// Show or hide every tool of a module's category, returning how many changed
+    pub fn set_category_enabled(&self, category: &str, enabled: bool) -> usize {
+        let changed: Vec<(String, ToolChangeKind)> = self
+            .write_tools()
+            .iter_mut()
+            .filter(|(_, tool)| {
+                tool.enabled != enabled && tool.definition.category() == Some(category)
+            })
+            .map(|(name, tool)| {
+                tool.enabled = enabled;
+                (name.clone(), toggle_kind(enabled))
+            })
+            .collect();
+        let count = changed.len();
+        self.record(changed);
+        count
+    }
+
+    /// Whether a tool is registered and enabled
+    pub fn contains(&self, name: &str) -> bool {
+        self.read_tools().get(name).is_some_and(|tool| tool.enabled)
+    }
+
+    /// Number of enabled tools
+    pub fn len(&self) -> usize {
+        self.read_tools()
+            .values()
+            .filter(|tool| tool.enabled)
+            .count()
+    }
+
+    /// Whether no tools are enabled
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Enabled definitions, ordered by name
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.read_tools()
+            .values()
+            .filter(|tool| tool.enabled)
+            .map(|tool| tool.definition.clone())
+            .collect()
+    }
+
+    /// Tools in `tools/list` form
+    pub fn list(&self) -> Vec<Value> {
+        self.read_tools()
+            .values()
+            .filter(|tool| tool.enabled)
+            .map(|tool| list_entry(&tool.definition))
+            .collect()
+    }
+
+    /// Cursor for the current tool list
+    pub fn cursor(&self) -> u64 {
+        self.read_changes().revision
+    }
+
+    /// Receive the new cursor after every change to the tool list
+    pub fn subscribe(&self) -> broadcast::Receiver<u64> {
+        self.list_changed.subscribe()
+    }
+
+    /// Send `notifications/tools/list_changed` to `sender` after every change
+    /// to the tool list, until its receiver is dropped or the task is aborted
+    pub fn forward_list_changes(&self, sender: mpsc::Sender<Value>) -> JoinHandle<()> {
+        let mut changes = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let cursor = tokio::select! {
+                    _ = sender.closed() => break,
+                    changed = changes.recv() => match changed {
+                        Ok(cursor) => cursor,
+                        // Missed changes are covered by the next notification's cursor
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                };
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": LIST_CHANGED_METHOD,
+                    "params": {"_meta": {"cursor": cursor}}
+                });
+                if sender.send(notification).await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Tools added, updated or removed after `cursor`
+    ///
+    /// A tool changed several times is reported once with its current
+    /// definition. A cursor older than the retained history, or newer than
+    /// the registry's own, yields `reset` and the whole list.
+    pub fn tools_changed_since(&self, cursor: u64) -> ToolListDiff {
+        let (current, changes) = {
+            let log = self.read_changes();
+            if cursor < log.trimmed_through || cursor > log.revision {
+                drop(log);
+                return ToolListDiff {
+                    cursor: self.cursor(),
+                    reset: true,
+                    added: self.list(),
+                    ..Default::default()
+                };
+            }
+            let mut first_change: HashMap<&str, ToolChangeKind> = HashMap::new();
+            for change in log.entries.iter().filter(|c| c.revision > cursor) {
+                first_change.entry(&change.tool).or_insert(change.kind);
+            }
+            let changes: BTreeMap<String, ToolChangeKind> = first_change
+                .into_iter()
+                .map(|(tool, kind)| (tool.to_string(), kind))
+                .collect();
+            (log.revision, changes)
+        };
+        let tools = self.read_tools();
+        let mut diff = ToolListDiff {
+            cursor: current,
+            ..Default::default()
+        };
+        for (name, first) in changes {
+            let existed = first != ToolChangeKind::Added;
+            match tools.get(&name).filter(|tool| tool.enabled) {
+                Some(tool) if existed => diff.updated.push(list_entry(&tool.definition)),
+                Some(tool) => diff.added.push(list_entry(&tool.definition)),
+                None if existed => diff.removed.push(name),
+                None => {}
+            }
+        }
+        diff
+    }
+
+    fn read_tools(&self) -> RwLockReadGuard<'_, BTreeMap<String, RegisteredTool>> {
+        self.tools.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_tools(&self) -> RwLockWriteGuard<'_, BTreeMap<String, RegisteredTool>> {
+        self.tools.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn enabled_tool<T>(&self, name: &str, f: impl FnOnce(&RegisteredTool) -> T) -> Result<T> {
+        self.read_tools()
+            .get(name)
+            .filter(|tool| tool.enabled)
+            .map(f)
+            .ok_or_else(|| Error::not_found_with_resource("Tool not found", "tool", name))
+    }
+
+    fn read_middleware(&self) -> RwLockReadGuard<'_, Vec<Arc<dyn ToolMiddleware>>> {
+        self.middleware
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_middleware(&self) -> RwLockWriteGuard<'_, Vec<Arc<dyn ToolMiddleware>>> {
+        self.middleware
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn read_changes(&self) -> RwLockReadGuard<'_, ChangeLog> {
+        self.changes.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Log changes under one cursor each and announce the newest
+    fn record(&self, changes: Vec<(String, ToolChangeKind)>) {
+        if changes.is_empty() {
+            return;
+        }
+        let revision = {
+            let mut log = self.changes.write().unwrap_or_else(PoisonError::into_inner);
+            for (tool, kind) in changes {
+                log.revision += 1;
+                let revision = log.revision;
+                log.entries.push_back(ToolChange {
+                    revision,
+                    tool,
+                    kind,
+                });
+                if log.entries.len() > CHANGE_LOG_LIMIT {
+                    if let Some(dropped) = log.entries.pop_front() {
+                        log.trimmed_through = dropped.revision;
+                    }
+                }
+            }
+            log.revision
+        };
+        // Nobody listening is fine; clients catch up with the cursor
+        let _ = self.list_changed.send(revision);
+    }
+
+    /// Tools in `tools/list` form with descriptions and hints in the localizer's locale
+    pub fn list_localized(&self, localizer: &Localizer) -> Vec<Value> {
+        let mut tools = self.list();
+        for tool in &mut tools {
+            localizer.localize_tool(tool);
+        }
+        tools
+    }
+
+    /// Validate arguments against the tool's schema and run its handler
+    pub async fn call(&self, name: &str, arguments: Value) -> Result<Value> {
+        self.call_streaming(name, arguments, ToolStream::disabled())
+            .await
+    }
+
+    /// Run a tool, passing partial output to `stream`
+    ///
+    /// Calls from a client checked against the policy are written to the
+    /// `audit` log target with the caller, its key's scopes and the outcome.
+    pub async fn call_streaming(
+        &self,
+        name: &str,
+        arguments: Value,
+        stream: ToolStream,
+    ) -> Result<Value> {
+        // Calls made by the server itself run outside any client's request
+        let Some((policy, access)) = self.policy().zip(ACCESS.try_with(Clone::clone).ok()) else {
+            return self.dispatch(name, arguments, stream).await;
+        };
+        let definition = self.enabled_tool(name, |tool| tool.definition.clone())?;
+        let principal = &access.principal;
+        if let Err(e) = policy.check(
+            principal,
+            &definition,
+            &arguments,
+            access.confirmation.as_deref(),
+        ) {
+            tracing::warn!(
+                target: "audit",
+                principal = %principal.name,
+                scopes = ?principal.scopes,
+                tool = name,
+                error = %e,
+                "Tool call refused"
+            );
+            return Err(e);
+        }
+        let result = self.dispatch(name, arguments, stream).await;
+        tracing::info!(
+            target: "audit",
+            principal = %principal.name,
+            scopes = ?principal.scopes,
+            tool = name,
+            ok = result.is_ok(),
+            "Tool call"
+        );
+        result
+    }
+
+    /// Run middleware, validation and the handler of a tool
+    async fn dispatch(&self, name: &str, arguments: Value, stream: ToolStream) -> Result<Value> {
+        let mut arguments = arguments;
+        let middlewares = self.read_middleware().clone();
+        if !middlewares.is_empty() {
+            let definition = self.enabled_tool(name, |tool| tool.definition.clone())?;
+            for middleware in &middlewares {
+                arguments = middleware.before_call(&definition, arguments).await?;
+            }
+        }
+        let handler = {
+            let tools = self.read_tools();
+            let tool = tools
+                .get(name)
+                .filter(|tool| tool.enabled)
+                .ok_or_else(|| Error::not_found_with_resource("Tool not found", "tool", name))?;
+            if let Some(schema) = &tool.schema {
+                if let Err(errors) = schema.validate(&arguments) {
+                    let messages: Vec<String> = errors.map(|e| e.to_string()).collect();
+                    return Err(Error::validation(format!(
+                        "Invalid arguments for {}: {}",
+                        name,
+                        messages.join(", ")
+                    )));
+                }
+            }
+            Arc::clone(&tool.handler)
+        };
+        let mut result = handler(arguments, stream).await?;
+        for middleware in &middlewares {
+            result = middleware.after_call(name, result).await?;
+        }
+        Ok(result)
+    }
+
+    /// Run a tool, forwarding its chunks as progress notifications as they arrive
+    async fn call_with_progress(
+        &self,
+        name: &str,
+        arguments: Value,
+        token: &Value,
+        notifications: &mpsc::Sender<Value>,
+    ) -> Result<Value> {
+        let (stream, mut chunks) = ToolStream::channel();
+        let call = self.call_streaming(name, arguments, stream);
+        tokio::pin!(call);
+        let result = loop {
+            tokio::select! {
+                biased;
+                Some(chunk) = chunks.recv() => {
+                    let _ = notifications.send(chunk.to_notification(token)).await;
+                }
+                result = &mut call => break result,
+            }
+        };
+        // Chunks sent just before the handler returned
+        while let Ok(chunk) = chunks.try_recv() {
+            let _ = notifications.send(chunk.to_notification(token)).await;
+        }
+        result
+    }
+
+    /// JSON-RPC router serving `initialize`, `tools/list`, `tools/changedSince`
+    /// and `tools/call` at `/`
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new().route("/", post(rpc_handler)).with_state(self)
+    }
+
+    /// Handle one JSON-RPC request
+    pub async fn handle(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+        self.handle_with_notifications(request, None).await
+    }
+
+    /// Handle one JSON-RPC request on a connection that can carry notifications
+    pub async fn handle_with_notifications(
+        &self,
+        request: JsonRpcRequest,
+        notifications: Option<&mpsc::Sender<Value>>,
+    ) -> JsonRpcResponse {
+        self.handle_in_session(request, &Session::new(), notifications)
+            .await
+    }
+
+    /// Handle one JSON-RPC request within a client session
+    ///
+    /// A `tools/call` with `_meta.progressToken` has its partial output sent
+    /// to `notifications` as `notifications/progress` messages, all of them
+    /// before the call's response is returned. Descriptions and result text
+    /// use the locale from the request's `_meta.locale`, falling back to the
+    /// one declared as `locale` in `initialize`; the client named in
+    /// `_meta.client` or `clientInfo` is available to tools through
+    /// [`current_client`]. With a policy set, clients only see and call
+    /// the tools their principal may use, and `_meta.confirmation` carries
+    /// the token of a confirmed call. `tools/list` results carry
+    /// the list's cursor in `_meta.cursor`; `tools/changedSince` takes it back
+    /// as `cursor` and answers with a [`ToolListDiff`].
+    pub async fn handle_in_session(
+        &self,
+        request: JsonRpcRequest,
+        session: &Session,
+        notifications: Option<&mpsc::Sender<Value>>,
+    ) -> JsonRpcResponse {
+        tracing::info!(
+            "Received MCP request: method={}, id={:?}",
+            request.method,
+            request.id
+        );
+        let id = request.id;
+        let params = request.params.unwrap_or(Value::Null);
+        if request.method == "initialize" {
+            if let Some(locale) = params.get("locale").and_then(|l| l.as_str()) {
+                session.set_locale(Some(locale.to_string()));
+            }
+            if let Some(client) = params.pointer("/clientInfo/name").and_then(|n| n.as_str()) {
+                session.set_client(Some(client.to_string()));
+            }
+        }
+        let locale = params
+            .pointer("/_meta/locale")
+            .and_then(|l| l.as_str())
+            .map(String::from)
+            .or_else(|| session.locale());
+        let localizer = self.localizer(locale.as_deref());
+        let policy = self.policy();
+        let principal = policy.as_ref().map(|policy| {
+            session
+                .principal()
+                .unwrap_or_else(|| policy.authenticate(None))
+        });
+        match request.method.as_str() {
+            "initialize" => JsonRpcResponse::result(
+                id,
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {"tools": {"listChanged": true}},
+                    "serverInfo": {
+                        "name": "devops-mcp-rust",
+                        "version": env!("CARGO_PKG_VERSION")
+                    },
+                    "locale": localizer.locale()
+                }),
+            ),
+            "tools/list" => {
+                let cursor = self.cursor();
+                JsonRpcResponse::result(
+                    id,
+                    json!({
+                        "tools": self
+                            .list_localized(&localizer)
+                            .into_iter()
+                            .filter(|tool| self.visible(
+                                principal.as_ref(),
+                                tool["name"].as_str().unwrap_or_default()
+                            ))
+                            .collect::<Vec<_>>(),
+                        "_meta": {"cursor": cursor}
+                    }),
+                )
+            }
+            "tools/changedSince" => {
+                let Some(cursor) = params.get("cursor").and_then(|c| c.as_u64()) else {
+                    return JsonRpcResponse::error(
+                        id,
+                        -32602,
+                        "Invalid params: cursor is required",
+                    );
+                };
+                let mut diff = self.tools_changed_since(cursor);
+                for tools in [&mut diff.added, &mut diff.updated] {
+                    tools.retain(|tool| {
+                        self.visible(
+                            principal.as_ref(),
+                            tool["name"].as_str().unwrap_or_default(),
+                        )
+                    });
+                }
+                for tool in diff.added.iter_mut().chain(diff.updated.iter_mut()) {
+                    localizer.localize_tool(tool);
+                }
+                match serde_json::to_value(&diff) {
+                    Ok(diff) => JsonRpcResponse::result(id, diff),
+                    Err(e) => JsonRpcResponse::error(id, -32603, e.to_string()),
+                }
+            }
+            "tools/call" => {
+                let Some(name) = params.get("name").and_then(|n| n.as_str()) else {
+                    return JsonRpcResponse::error(id, -32602, "Invalid params");
+                };
+                if !self.contains(name) {
+                    return JsonRpcResponse::error(id, -32602, format!("Unknown tool: {}", name));
+                }
+                let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+                let token = params.pointer("/_meta/progressToken");
+                let call = async {
+                    match (token, notifications) {
+                        (Some(token), Some(notifications)) => {
+                            self.call_with_progress(name, arguments, token, notifications)
+                                .await
+                        }
+                        _ => self.call(name, arguments).await,
+                    }
+                };
+                let client = params
+                    .pointer("/_meta/client")
+                    .and_then(|c| c.as_str())
+                    .map(String::from)
+                    .or_else(|| session.client());
+                let call = CLIENT.scope(client, call);
+                let result = match principal {
+                    Some(principal) => {
+                        let access = Access {
+                            principal,
+                            confirmation: params
+                                .pointer("/_meta/confirmation")
+                                .and_then(|c| c.as_str())
+                                .map(String::from),
+                        };
+                        localizer.clone().scope(ACCESS.scope(access, call)).await
+                    }
+                    None => localizer.clone().scope(call).await,
+                };
+                match result {
+                    Ok(result) => JsonRpcResponse::result(id, result),
+                    // Tool failures are results so the model can see and react to them
+                    Err(e) => JsonRpcResponse::result(
+                        id,
+                        json!({
+                            "content": [{
+                                "type": "text",
+                                "text": localizer.text("messages.error", &[("error", &e)])
+                            }],
+                            "isError": true
+                        }),
+                    ),
+                }
+            }
+            method => JsonRpcResponse::error(id, -32601, format!("Method not found: {}", method)),
+        }
+    }
+}
+
+/// JSON-RPC 2.0 request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    pub id: Option<Value>,
+    pub method: String,
+    pub params: Option<Value>,
+}
+
+/// JSON-RPC 2.0 response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    pub id: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+/// JSON-RPC 2.0 error object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i32,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl JsonRpcResponse {
+    /// Successful response
+    pub fn result(id: Option<Value>, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// Error response
+    pub fn error(id: Option<Value>, code: i32, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code,
+                message: message.into(),
+                data: None,
+            }),
+        }
+    }
+}
+
+impl From<JsonRpcResponse> for Value {
+    /// Moves the result into the message where `serde_json::to_value` would copy it
+    fn from(response: JsonRpcResponse) -> Self {
+        let mut message = serde_json::Map::new();
+        message.insert("jsonrpc".to_string(), Value::String(response.jsonrpc));
+        message.insert("id".to_string(), response.id.unwrap_or(Value::Null));
+        if let Some(result) = response.result {
+            message.insert("result".to_string(), result);
+        }
+        if let Some(error) = response.error {
+            message.insert("error".to_string(), json!(error));
+        }
+        Value::Object(message)
+    }
+}
+
+fn compile_schema(definition: &ToolDefinition) -> Result<Option<JSONSchema>> {
+    definition
+        .parameters
+        .as_ref()
+        .map(|schema| {
+            JSONSchema::compile(schema).map_err(|e| {
+                Error::validation(format!(
+                    "Invalid input schema for {}: {}",
+                    definition.name, e
+                ))
+            })
+        })
+        .transpose()
+}
+
+fn list_entry(tool: &ToolDefinition) -> Value {
+    json!({
+        "name": tool.name,
+        "description": tool.description,
+        "inputSchema": tool.parameters.clone()
+            .unwrap_or_else(|| json!({"type": "object", "properties": {}}))
+    })
+}
+
+fn toggle_kind(enabled: bool) -> ToolChangeKind {
+    if enabled {
+        ToolChangeKind::Added
+    } else {
+        ToolChangeKind::Removed
+    }
+}
+
+async fn rpc_handler(
+    State(registry): State<Arc<ToolRegistry>>,
+    headers: HeaderMap,
+    Json(request): Json<JsonRpcRequest>,
+) -> Json<JsonRpcResponse> {
+    let session = registry.session_for(&headers);
+    Json(registry.handle_in_session(request, &session, None).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::call_result;
+
+    #[derive(Deserialize)]
+    struct Greet {
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn dispatches_registered_tools_and_validates_arguments() {
+        let registry = ToolRegistry::new();
+        registry
+            .register_typed(
+                ToolDefinition::from_json_schema(
+                    "greet",
+                    "Say hello",
+                    "test",
+                    json!({
+                        "type": "object",
+                        "properties": {"name": {"type": "string"}},
+                        "required": ["name"]
+                    }),
+                    None,
+                ),
+                |p: Greet| async move { Ok(call_result(format!("Hello, {}", p.name), json!({}))) },
+            )
+            .unwrap();
+        registry
+            .register_all(
+                vec![
+                    ToolDefinition::new("first", "First"),
+                    ToolDefinition::new("second", "Second"),
+                ],
+                |name, _| async move { Ok(json!({ "tool": name })) },
+            )
+            .unwrap();
+        assert!(registry
+            .register(ToolDefinition::new("first", "Again"), |_| async {
+                Ok(json!({}))
+            })
+            .is_err());
+
+        let names: Vec<String> = registry.definitions().into_iter().map(|d| d.name).collect();
+        assert_eq!(names, vec!["first", "greet", "second"]);
+        assert_eq!(
+            registry.call("second", json!({})).await.unwrap(),
+            json!({"tool": "second"})
+        );
+        assert!(registry.call("greet", json!({})).await.is_err());
+
+        let request = |method: &str, params: Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(1)),
+            method: method.to_string(),
+            params: Some(params),
+        };
+        let response = registry
+            .handle(request(
+                "tools/call",
+                json!({"name": "greet", "arguments": {"name": "Ada"}}),
+            ))
+            .await;
+        assert_eq!(response.result.unwrap()["content"][0]["text"], "Hello, Ada");
+        let response = registry
+            .handle(request(
+                "tools/call",
+                json!({"name": "greet", "arguments": {"name": 7}}),
+            ))
+            .await;
+        assert_eq!(response.result.unwrap()["isError"], true);
+        let response = registry
+            .handle(request("tools/call", json!({"name": "missing"})))
+            .await;
+        assert_eq!(response.error.unwrap().code, -32602);
+        let response = registry.handle(request("tools/list", json!({}))).await;
+        assert_eq!(
+            response.result.unwrap()["tools"].as_array().unwrap().len(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn serves_the_locale_declared_at_initialize() {
+        let registry = ToolRegistry::new();
+        registry
+            .register(
+                ToolDefinition::from_json_schema(
+                    "start_container",
+                    "Start a stopped Docker container",
+                    "test",
+                    json!({"type": "object", "properties": {"container_id": {"type": "string"}}}),
+                    None,
+                ),
+                |_| async {
+                    Ok(call_result(
+                        crate::i18n::text("messages.container.started", &[("id", &"web")]),
+                        json!({}),
+                    ))
+                },
+            )
+            .unwrap();
+
+        let session = Session::new();
+        let request = |method: &str, params: Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(1)),
+            method: method.to_string(),
+            params: Some(params),
+        };
+        let response = registry
+            .handle_in_session(
+                request("initialize", json!({"locale": "de-CH"})),
+                &session,
+                None,
+            )
+            .await;
+        assert_eq!(response.result.unwrap()["locale"], "de");
+
+        let response = registry
+            .handle_in_session(request("tools/list", json!({})), &session, None)
+            .await;
+        let tool = &response.result.unwrap()["tools"][0];
+        assert_eq!(
+            tool["description"],
+            "Startet einen gestoppten Docker-Container"
+        );
+        assert_eq!(
+            tool["inputSchema"]["properties"]["container_id"]["description"],
+            "Container-ID oder -Name"
+        );
+
+        let call = json!({"name": "start_container", "arguments": {}});
+        let response = registry
+            .handle_in_session(request("tools/call", call.clone()), &session, None)
+            .await;
+        assert_eq!(
+            response.result.unwrap()["content"][0]["text"],
+            "web gestartet"
+        );
+        let mut call = call;
+        call["_meta"] = json!({"locale": "es"});
+        let response = registry
+            .handle_in_session(request("tools/call", call), &session, None)
+            .await;
+        assert_eq!(
+            response.result.unwrap()["content"][0]["text"],
+            "web iniciado"
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_tool_changes_since_a_cursor() {
+        let registry = ToolRegistry::new();
+        let noop = |_| async { Ok(json!({})) };
+        registry
+            .register(
+                ToolDefinition::from_json_schema("ping", "Ping", "net", json!({}), None),
+                noop,
+            )
+            .unwrap();
+        registry
+            .register(
+                ToolDefinition::from_json_schema("trace", "Trace", "net", json!({}), None),
+                noop,
+            )
+            .unwrap();
+        registry
+            .register(ToolDefinition::new("echo", "Echo"), noop)
+            .unwrap();
+        let cursor = registry.cursor();
+        assert_eq!(cursor, 3);
+
+        let (sender, mut notifications) = mpsc::channel(8);
+        let forwarder = registry.forward_list_changes(sender);
+        registry
+            .update_definition(ToolDefinition::new("echo", "Echo the input"))
+            .unwrap();
+        assert_eq!(registry.set_category_enabled("net", false), 2);
+        registry
+            .register(ToolDefinition::new("dig", "Look up DNS records"), noop)
+            .unwrap();
+        registry.set_enabled("trace", true).unwrap();
+        assert!(registry.unregister("dig").is_some());
+        assert!(registry.call("ping", json!({})).await.is_err());
+
+        let diff = registry.tools_changed_since(cursor);
+        assert_eq!(diff.cursor, registry.cursor());
+        assert!(!diff.reset && diff.added.is_empty());
+        assert_eq!(diff.updated.len(), 2);
+        assert_eq!(diff.updated[0]["description"], "Echo the input");
+        assert_eq!(diff.updated[1]["name"], "trace");
+        assert_eq!(diff.removed, vec!["ping"]);
+        assert!(registry.tools_changed_since(99).reset);
+
+        let notification = notifications.recv().await.unwrap();
+        assert_eq!(notification["method"], LIST_CHANGED_METHOD);
+        assert_eq!(notification["params"]["_meta"]["cursor"], 4);
+        forwarder.abort();
+
+        let response = registry
+            .handle(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: Some(json!(1)),
+                method: "tools/changedSince".to_string(),
+                params: Some(json!({"cursor": diff.cursor - 1})),
+            })
+            .await;
+        assert_eq!(response.result.unwrap()["removed"], json!(["dig"]));
+    }
+
+    struct Tag(&'static str);
+
+    #[async_trait]
+    impl ToolMiddleware for Tag {
+        async fn after_call(&self, _tool: &str, mut result: Value) -> Result<Value> {
+            if let Some(tags) = result["tags"].as_array_mut() {
+                tags.push(json!(self.0));
+            }
+            Ok(result)
+        }
+    }
+
+    fn module(version: &'static str, tools: &[&str]) -> ToolRegistry {
+        let mut registry = ToolRegistry::new();
+        for tool in tools {
+            registry
+                .register(
+                    ToolDefinition::new(*tool, format!("{} {}", tool, version)),
+                    move |_| async move { Ok(json!({"version": version, "tags": []})) },
+                )
+                .unwrap();
+        }
+        registry.add_middleware(Arc::new(Tag(version)));
+        registry
+    }
+
+    #[tokio::test]
+    async fn swaps_a_tool_set_with_its_middleware() {
+        let mut registry = module("v1", &["ping", "trace"]);
+        let set = registry.tool_set();
+        registry.add_middleware(Arc::new(Tag("server")));
+        registry
+            .register(ToolDefinition::new("help", "Help"), |_| async {
+                Ok(json!({"tags": []}))
+            })
+            .unwrap();
+        registry.set_enabled("trace", false).unwrap();
+        let cursor = registry.cursor();
+
+        let set = registry.swap_set(&set, module("v2", &["trace", "dig"]));
+        assert_eq!(set.names().collect::<Vec<_>>(), ["dig", "trace"]);
+        let result = registry.call("dig", json!({})).await.unwrap();
+        assert_eq!(result["version"], "v2");
+        // The module's middleware keeps its place ahead of the server's own
+        assert_eq!(result["tags"], json!(["v2", "server"]));
+        assert!(registry.call("ping", json!({})).await.is_err());
+        assert!(!registry.contains("trace"));
+        assert!(registry.contains("help"));
+
+        let diff = registry.tools_changed_since(cursor);
+        assert_eq!(diff.added[0]["name"], "dig");
+        assert_eq!(diff.removed, vec!["ping"]);
+        assert!(diff.updated.is_empty());
+    }
+
+    #[tokio::test]
+    async fn enforces_the_policy_of_the_connected_principal() {
+        let registry = ToolRegistry::new();
+        for name in ["list_pods", "delete_pod"] {
+            registry
+                .register(ToolDefinition::new(name, name), move |_| async move {
+                    Ok(call_result(name, json!({})))
+                })
+                .unwrap();
+        }
+        let policy = serde_json::from_value(json!({
+            "roles": {
+                "viewer": {"allow": ["list_*"]},
+                "admin": {"allow": ["*"], "confirm": ["delete_*"]}
+            },
+            "principals": {
+                "ops": {"api_keys": [crate::transport::tenancy::hash_api_key("ops-key")], "roles": ["admin"]}
+            },
+            "default_role": "viewer"
+        }))
+        .unwrap();
+        registry.set_policy(Some(Arc::new(PermissionPolicy::new(policy).unwrap())));
+        let request = |method: &str, params: Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(1)),
+            method: method.to_string(),
+            params: Some(params),
+        };
+        let text = |response: JsonRpcResponse| {
+            response.result.unwrap()["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        let delete = json!({"name": "delete_pod", "arguments": {"pod": "web-1"}});
+
+        let anonymous = registry.session_for(&HeaderMap::new());
+        let response = registry
+            .handle_in_session(request("tools/list", json!({})), &anonymous, None)
+            .await;
+        assert_eq!(
+            response.result.unwrap()["tools"].as_array().unwrap().len(),
+            1
+        );
+        let response = registry
+            .handle_in_session(request("tools/call", delete.clone()), &anonymous, None)
+            .await;
+        assert!(text(response).contains("anonymous is not permitted to use delete_pod"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "ops-key".parse().unwrap());
+        let ops = registry.session_for(&headers);
+        let response = registry
+            .handle_in_session(request("tools/call", delete.clone()), &ops, None)
+            .await;
+        let refused = text(response);
+        let token = refused
+            .split("_meta.confirmation set to ")
+            .nth(1)
+            .and_then(|rest| rest.split(' ').next())
+            .unwrap();
+        let mut confirmed = delete;
+        confirmed["_meta"] = json!({"confirmation": token});
+        let response = registry
+            .handle_in_session(request("tools/call", confirmed), &ops, None)
+            .await;
+        assert_eq!(text(response), "delete_pod");
+        // Calls the server makes itself are not a client's
+        assert!(registry.call("delete_pod", json!({})).await.is_ok());
+    }
+}
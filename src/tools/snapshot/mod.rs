@@ -0,0 +1,709 @@
+/// Encrypted snapshots of server state for disaster recovery
+///
+/// `snapshot_export` gathers the state of every registered source, such as
+/// client preferences, favorites, memories and the state files an operator
+/// lists in the config, into one archive encrypted with a key derived from
+/// the snapshot passphrase. `snapshot_verify` checks an archive without
+/// changing anything and `snapshot_restore` loads it on this or a new host,
+/// saving the current state first so a restore can itself be undone.
+use crate::error::{Error, Result};
+use crate::i18n;
+use crate::tools::registry::ToolRegistry;
+use crate::tools::{call_result, ToolDefinition};
+use async_trait::async_trait;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// Marks a file as a server snapshot
+const FORMAT: &str = "devops-mcp-snapshot";
+
+/// Archive layout written by this build; older versions stay readable
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+const KDF: &str = "pbkdf2-hmac-sha256";
+const CIPHER: &str = "aes-256-gcm";
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// Refuse archives that would tie up the server deriving their key
+const MAX_ITERATIONS: u32 = 10_000_000;
+const SALT_LEN: usize = 16;
+
+/// Shortest passphrase accepted
+const MIN_PASSPHRASE_LEN: usize = 12;
+
+/// State that can be saved into and loaded from a snapshot
+#[async_trait]
+pub trait SnapshotSource: Send + Sync {
+    /// Name of the source within an archive
+    fn name(&self) -> &str;
+
+    /// Current state as JSON
+    async fn export_state(&self) -> Result<Value>;
+
+    /// Replace the current state with an exported one
+    async fn restore_state(&self, state: Value) -> Result<()>;
+}
+
+/// A state file on disk, saved byte for byte
+pub struct FileSource {
+    name: String,
+    path: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            path: path.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl SnapshotSource for FileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn export_state(&self) -> Result<Value> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(json!({
+                "present": true,
+                "data": base64::engine::general_purpose::STANDARD.encode(bytes),
+            })),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(json!({"present": false})),
+            Err(e) => Err(Error::io_with_path(
+                format!("Failed to read state file: {}", e),
+                self.path.clone(),
+            )),
+        }
+    }
+
+    async fn restore_state(&self, state: Value) -> Result<()> {
+        if state.get("present").and_then(|p| p.as_bool()) != Some(true) {
+            return match tokio::fs::remove_file(&self.path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(Error::io_with_path(
+                    format!("Failed to remove state file: {}", e),
+                    self.path.clone(),
+                )),
+                _ => Ok(()),
+            };
+        }
+        let bytes = state
+            .get("data")
+            .and_then(|d| d.as_str())
+            .and_then(|d| base64::engine::general_purpose::STANDARD.decode(d).ok())
+            .ok_or_else(|| Error::parsing(format!("Invalid state for {}", self.name)))?;
+        write_atomic(&self.path, &bytes).await
+    }
+}
+
+/// Snapshot settings, under `snapshot` in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    /// Directory archives are written to and read from
+    #[serde(default = "default_dir")]
+    pub dir: PathBuf,
+    /// Passphrase the archive key is derived from; defaults to `SNAPSHOT_PASSPHRASE`
+    #[serde(default = "default_passphrase", skip_serializing)]
+    pub passphrase: Option<String>,
+    /// Further state files to include, by source name
+    #[serde(default)]
+    pub files: BTreeMap<String, PathBuf>,
+}
+
+fn default_dir() -> PathBuf {
+    PathBuf::from("snapshots")
+}
+
+fn default_passphrase() -> Option<String> {
+    std::env::var("SNAPSHOT_PASSPHRASE").ok()
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_dir(),
+            passphrase: default_passphrase(),
+            files: BTreeMap::new(),
+        }
+    }
+}
+
+impl SnapshotConfig {
+    /// Check the passphrase is long enough to protect the archive
+    pub fn validate(&self) -> Result<()> {
+        match &self.passphrase {
+            Some(passphrase) if passphrase.chars().count() < MIN_PASSPHRASE_LEN => {
+                Err(Error::validation_with_field(
+                    format!(
+                        "Snapshot passphrase must be at least {} characters",
+                        MIN_PASSPHRASE_LEN
+                    ),
+                    "passphrase",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Key derivation parameters stored with an archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    iterations: u32,
+    salt: String,
+}
+
+/// Unencrypted part of an archive, authenticated along with the contents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchiveHeader {
+    format: String,
+    version: u32,
+    created_at: DateTime<Utc>,
+    sources: Vec<String>,
+    kdf: KdfParams,
+    cipher: String,
+    nonce: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Archive {
+    #[serde(flatten)]
+    header: ArchiveHeader,
+    ciphertext: String,
+}
+
+/// One source's state with its digest
+#[derive(Debug, Serialize, Deserialize)]
+struct SourceState {
+    sha256: String,
+    state: Value,
+}
+
+/// What an archive holds, once decrypted and checked
+#[derive(Debug)]
+pub struct SnapshotContents {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub states: BTreeMap<String, Value>,
+}
+
+fn digest(state: &Value) -> Result<String> {
+    Ok(Sha256::digest(serde_json::to_vec(state)?)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey> {
+    let iterations = std::num::NonZeroU32::new(iterations)
+        .ok_or_else(|| Error::validation("Snapshot key derivation needs iterations"))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    ring::pbkdf2::derive(
+        ring::pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        key.as_mut(),
+    );
+    let key = UnboundKey::new(&AES_256_GCM, key.as_ref())
+        .map_err(|_| Error::internal("Failed to create snapshot key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Encrypt source states into archive bytes
+fn seal(passphrase: &str, iterations: u32, states: BTreeMap<String, Value>) -> Result<Vec<u8>> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| Error::internal("Failed to generate random bytes"))?;
+    let engine = base64::engine::general_purpose::STANDARD;
+    let header = ArchiveHeader {
+        format: FORMAT.to_string(),
+        version: SNAPSHOT_VERSION,
+        created_at: Utc::now(),
+        sources: states.keys().cloned().collect(),
+        kdf: KdfParams {
+            algorithm: KDF.to_string(),
+            iterations,
+            salt: engine.encode(salt),
+        },
+        cipher: CIPHER.to_string(),
+        nonce: engine.encode(nonce),
+    };
+    let payload = states
+        .into_iter()
+        .map(|(name, state)| {
+            Ok((
+                name,
+                SourceState {
+                    sha256: digest(&state)?,
+                    state,
+                },
+            ))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+    let mut sealed = Zeroizing::new(serde_json::to_vec(&payload)?);
+    derive_key(passphrase, &salt, iterations)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(serde_json::to_vec(&header)?),
+            &mut *sealed,
+        )
+        .map_err(|_| Error::internal("Failed to encrypt snapshot"))?;
+    let archive = Archive {
+        header,
+        ciphertext: engine.encode(&*sealed),
+    };
+    Ok(serde_json::to_vec_pretty(&archive)?)
+}
+
+/// Decrypt archive bytes and check every source against its digest
+fn open(passphrase: &str, bytes: &[u8]) -> Result<SnapshotContents> {
+    let archive: Archive = serde_json::from_slice(bytes)
+        .map_err(|e| Error::parsing(format!("Not a snapshot archive: {}", e)))?;
+    let header = &archive.header;
+    if header.format != FORMAT {
+        return Err(Error::parsing("Not a snapshot archive"));
+    }
+    if header.version == 0 || header.version > SNAPSHOT_VERSION {
+        return Err(Error::validation(format!(
+            "Snapshot version {} is not supported; this server reads up to version {}",
+            header.version, SNAPSHOT_VERSION
+        )));
+    }
+    if header.kdf.algorithm != KDF
+        || header.cipher != CIPHER
+        || header.kdf.iterations > MAX_ITERATIONS
+    {
+        return Err(Error::validation(format!(
+            "Unsupported snapshot encryption {} with {}",
+            header.cipher, header.kdf.algorithm
+        )));
+    }
+    let engine = base64::engine::general_purpose::STANDARD;
+    let decode = |field: &str, text: &str| {
+        engine
+            .decode(text)
+            .map_err(|e| Error::parsing(format!("Invalid snapshot {}: {}", field, e)))
+    };
+    let salt = decode("salt", &header.kdf.salt)?;
+    let nonce = Nonce::try_assume_unique_for_key(&decode("nonce", &header.nonce)?)
+        .map_err(|_| Error::parsing("Invalid snapshot nonce"))?;
+    let mut sealed = Zeroizing::new(decode("ciphertext", &archive.ciphertext)?);
+    let plain = derive_key(passphrase, &salt, header.kdf.iterations)?
+        .open_in_place(nonce, Aad::from(serde_json::to_vec(header)?), &mut sealed)
+        .map_err(|_| {
+            Error::validation(
+                "Snapshot does not decrypt: wrong passphrase or the archive was modified",
+            )
+        })?;
+    let payload: BTreeMap<String, SourceState> = serde_json::from_slice(plain)
+        .map_err(|e| Error::parsing(format!("Invalid snapshot contents: {}", e)))?;
+    if !payload.keys().eq(header.sources.iter()) {
+        return Err(Error::validation(
+            "Snapshot contents do not match its list of sources",
+        ));
+    }
+    let mut states = BTreeMap::new();
+    for (name, source) in payload {
+        if digest(&source.state)? != source.sha256 {
+            return Err(Error::validation(format!(
+                "Snapshot state for {} fails its integrity check",
+                name
+            )));
+        }
+        states.insert(name, source.state);
+    }
+    Ok(SnapshotContents {
+        version: header.version,
+        created_at: header.created_at,
+        states,
+    })
+}
+
+async fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+            Error::io_with_path(
+                format!("Failed to create directory: {}", e),
+                parent.to_path_buf(),
+            )
+        })?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    tokio::fs::write(&temp, bytes)
+        .await
+        .map_err(|e| Error::io_with_path(format!("Failed to write file: {}", e), temp.clone()))?;
+    tokio::fs::rename(&temp, path).await.map_err(|e| {
+        Error::io_with_path(format!("Failed to replace file: {}", e), path.to_path_buf())
+    })
+}
+
+/// Exports and restores the registered sources
+pub struct SnapshotManager {
+    config: SnapshotConfig,
+    iterations: u32,
+    sources: Vec<Arc<dyn SnapshotSource>>,
+}
+
+impl SnapshotManager {
+    /// Manager over the state files listed in the config
+    pub fn new(config: SnapshotConfig) -> Result<Self> {
+        config.validate()?;
+        let files: Vec<Arc<dyn SnapshotSource>> = config
+            .files
+            .iter()
+            .map(|(name, path)| {
+                Arc::new(FileSource::new(name.clone(), path.clone())) as Arc<dyn SnapshotSource>
+            })
+            .collect();
+        let mut manager = Self {
+            config,
+            iterations: PBKDF2_ITERATIONS,
+            sources: Vec::new(),
+        };
+        for source in files {
+            manager.add_source(source)?;
+        }
+        Ok(manager)
+    }
+
+    /// Include another source in snapshots
+    pub fn add_source(&mut self, source: Arc<dyn SnapshotSource>) -> Result<()> {
+        if self.sources.iter().any(|s| s.name() == source.name()) {
+            return Err(Error::validation_with_field(
+                format!("Snapshot source {} is registered twice", source.name()),
+                "snapshot.files",
+            ));
+        }
+        self.sources.push(source);
+        Ok(())
+    }
+
+    /// Names of the registered sources
+    pub fn source_names(&self) -> Vec<&str> {
+        self.sources.iter().map(|s| s.name()).collect()
+    }
+
+    fn passphrase(&self) -> Result<&str> {
+        self.config.passphrase.as_deref().ok_or_else(|| {
+            Error::config_with_suggestion(
+                "No snapshot passphrase is configured",
+                "Set snapshot.passphrase in the config file or SNAPSHOT_PASSPHRASE",
+            )
+        })
+    }
+
+    /// Path of an archive, which must be a plain file name in the snapshot directory
+    fn archive_path(&self, name: &str) -> Result<PathBuf> {
+        let plain = Path::new(name).file_name().and_then(|n| n.to_str()) == Some(name);
+        if !plain || name.starts_with('.') {
+            return Err(Error::validation_with_field(
+                "Archive name must be a file name within the snapshot directory",
+                "name",
+            ));
+        }
+        Ok(self.config.dir.join(name))
+    }
+
+    /// Write an archive of every source, returning its path and SHA-256
+    pub async fn export(&self, name: Option<&str>) -> Result<(PathBuf, String)> {
+        let passphrase = self.passphrase()?;
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => format!("snapshot-{}.json", Utc::now().format("%Y%m%dT%H%M%SZ")),
+        };
+        let path = self.archive_path(&name)?;
+        let mut states = BTreeMap::new();
+        for source in &self.sources {
+            states.insert(source.name().to_string(), source.export_state().await?);
+        }
+        let bytes = seal(passphrase, self.iterations, states)?;
+        write_atomic(&path, &bytes).await?;
+        let sha256 = Sha256::digest(&bytes)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        Ok((path, sha256))
+    }
+
+    /// Decrypt and check an archive without changing anything
+    pub async fn verify(&self, name: &str) -> Result<SnapshotContents> {
+        let passphrase = self.passphrase()?;
+        let path = self.archive_path(name)?;
+        let bytes = tokio::fs::read(&path).await.map_err(|e| {
+            Error::io_with_path(format!("Failed to read snapshot: {}", e), path.clone())
+        })?;
+        open(passphrase, &bytes)
+    }
+
+    /// Restore sources from an archive, all of them unless `only` names some
+    ///
+    /// The current state is exported first; returns the restored sources,
+    /// the archive's sources this server has no source for, and the path of
+    /// the pre-restore snapshot.
+    pub async fn restore(
+        &self,
+        name: &str,
+        only: Option<&[String]>,
+    ) -> Result<(Vec<String>, Vec<String>, PathBuf)> {
+        let contents = self.verify(name).await?;
+        if let Some(only) = only {
+            if let Some(missing) = only.iter().find(|n| !contents.states.contains_key(*n)) {
+                return Err(Error::not_found_with_resource(
+                    "Snapshot has no such source",
+                    "snapshot_source",
+                    missing,
+                ));
+            }
+        }
+        let (backup, _) = self
+            .export(Some(&format!(
+                "pre-restore-{}.json",
+                Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+            )))
+            .await?;
+        let mut restored = Vec::new();
+        let mut skipped = Vec::new();
+        for (name, state) in contents.states {
+            if only.is_some_and(|only| !only.contains(&name)) {
+                continue;
+            }
+            match self.sources.iter().find(|s| s.name() == name) {
+                Some(source) => {
+                    source.restore_state(state).await?;
+                    restored.push(name);
+                }
+                None => skipped.push(name),
+            }
+        }
+        Ok((restored, skipped, backup))
+    }
+
+    /// Get tool definitions for snapshots
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let name =
+            json!({"type": "string", "description": "Archive file name in the snapshot directory"});
+        vec![
+            ToolDefinition::from_json_schema(
+                "snapshot_export",
+                "Save the server's state, such as preferences, favorites and memories, into an encrypted archive",
+                "admin",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "description": "Archive file name (default: snapshot-<timestamp>.json)"}
+                    }
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "snapshot_verify",
+                "Decrypt a snapshot archive and check its integrity without restoring it",
+                "admin",
+                json!({
+                    "type": "object",
+                    "properties": {"name": name},
+                    "required": ["name"]
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "snapshot_restore",
+                "Replace the server's state with a snapshot archive, saving the current state first",
+                "admin",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": name,
+                        "sources": {"type": "array", "items": {"type": "string"}, "description": "Restore only these sources"},
+                        "confirm": {"type": "boolean", "default": false, "description": "Required, as restoring replaces current state"}
+                    },
+                    "required": ["name"]
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a snapshot tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        let archive = parameters.get("name").and_then(|n| n.as_str());
+        let required =
+            || archive.ok_or_else(|| Error::validation_with_field("Missing name", "name"));
+        match name {
+            "snapshot_export" => {
+                let (path, sha256) = self.export(archive).await?;
+                let sources = self.source_names();
+                Ok(call_result(
+                    i18n::text(
+                        "messages.snapshot.exported",
+                        &[
+                            ("path", &path.display()),
+                            ("count", &sources.len()),
+                            ("sha256", &sha256),
+                        ],
+                    ),
+                    json!({"path": path, "sha256": sha256, "sources": sources}),
+                ))
+            }
+            "snapshot_verify" => {
+                let contents = self.verify(required()?).await?;
+                let sources: Vec<&String> = contents.states.keys().collect();
+                Ok(call_result(
+                    i18n::text(
+                        "messages.snapshot.verified",
+                        &[
+                            ("version", &contents.version),
+                            ("created_at", &contents.created_at.to_rfc3339()),
+                            ("count", &sources.len()),
+                        ],
+                    ),
+                    json!({
+                        "version": contents.version,
+                        "created_at": contents.created_at,
+                        "sources": sources,
+                    }),
+                ))
+            }
+            "snapshot_restore" => {
+                let archive = required()?;
+                if parameters.get("confirm").and_then(|c| c.as_bool()) != Some(true) {
+                    return Err(Error::validation_with_field(
+                        "Restoring replaces the server's current state; set confirm to true",
+                        "confirm",
+                    ));
+                }
+                let only: Option<Vec<String>> = parameters
+                    .get("sources")
+                    .cloned()
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_err(|e| Error::validation_with_field(e.to_string(), "sources"))?;
+                let (restored, skipped, backup) = self.restore(archive, only.as_deref()).await?;
+                Ok(call_result(
+                    i18n::text(
+                        "messages.snapshot.restored",
+                        &[("count", &restored.len()), ("backup", &backup.display())],
+                    ),
+                    json!({"restored": restored, "skipped": skipped, "backup": backup}),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "snapshot_tool",
+                name,
+            )),
+        }
+    }
+}
+
+/// Register the snapshot tools
+pub fn register(registry: &ToolRegistry, manager: Arc<SnapshotManager>) -> Result<()> {
+    registry.register_all(manager.get_tool_definitions(), move |name, arguments| {
+        let manager = Arc::clone(&manager);
+        async move { manager.execute_tool(&name, arguments).await }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(dir: &Path, passphrase: &str, files: &[(&str, PathBuf)]) -> SnapshotManager {
+        let mut manager = SnapshotManager::new(SnapshotConfig {
+            dir: dir.join("snapshots"),
+            passphrase: Some(passphrase.to_string()),
+            files: files
+                .iter()
+                .map(|(name, path)| (name.to_string(), path.clone()))
+                .collect(),
+        })
+        .unwrap();
+        // Keep the tests quick
+        manager.iterations = 1_000;
+        manager
+    }
+
+    #[tokio::test]
+    async fn restores_state_files_on_a_new_host() {
+        let old_host = tempfile::tempdir().unwrap();
+        let schedules = old_host.path().join("schedules.json");
+        tokio::fs::write(&schedules, br#"{"jobs": ["nightly-backup"]}"#)
+            .await
+            .unwrap();
+        let old = manager(
+            old_host.path(),
+            "correct horse battery",
+            &[
+                ("schedules", schedules),
+                ("watchlists", old_host.path().join("none")),
+            ],
+        );
+        let (archive, _) = old.export(Some("dr.json")).await.unwrap();
+
+        let new_host = tempfile::tempdir().unwrap();
+        let restored_schedules = new_host.path().join("state/schedules.json");
+        let new = manager(
+            new_host.path(),
+            "correct horse battery",
+            &[("schedules", restored_schedules.clone())],
+        );
+        tokio::fs::create_dir_all(new_host.path().join("snapshots"))
+            .await
+            .unwrap();
+        tokio::fs::copy(&archive, new_host.path().join("snapshots/dr.json"))
+            .await
+            .unwrap();
+
+        let contents = new.verify("dr.json").await.unwrap();
+        assert_eq!(contents.version, SNAPSHOT_VERSION);
+        let (restored, skipped, backup) = new.restore("dr.json", None).await.unwrap();
+        assert_eq!(restored, vec!["schedules"]);
+        assert_eq!(skipped, vec!["watchlists"]);
+        assert!(backup.exists());
+        assert_eq!(
+            tokio::fs::read(&restored_schedules).await.unwrap(),
+            br#"{"jobs": ["nightly-backup"]}"#
+        );
+        assert!(new.archive_path("../dr.json").is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_passphrases_and_tampering() {
+        let states = BTreeMap::from([("favorites".to_string(), json!({"clients": {}}))]);
+        let bytes = seal("correct horse battery", 1_000, states).unwrap();
+        assert!(open("correct horse battery", &bytes).is_ok());
+        assert!(open("wrong horse battery", &bytes)
+            .unwrap_err()
+            .to_string()
+            .contains("does not decrypt"));
+
+        let mut archive: Value = serde_json::from_slice(&bytes).unwrap();
+        archive["sources"] = json!(["favorites", "memories"]);
+        let tampered = serde_json::to_vec(&archive).unwrap();
+        assert!(open("correct horse battery", &tampered).is_err());
+
+        archive["version"] = json!(SNAPSHOT_VERSION + 1);
+        let future = serde_json::to_vec(&archive).unwrap();
+        assert!(open("correct horse battery", &future)
+            .unwrap_err()
+            .to_string()
+            .contains("not supported"));
+    }
+}
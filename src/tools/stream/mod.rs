@@ -0,0 +1,195 @@
+/// Streaming tool results
+///
+/// A tool call made with a `progressToken` in its `_meta` may report partial
+/// output before its final result. Each chunk travels as an MCP
+/// `notifications/progress` message: `progress` is the chunk's sequence
+/// number, so it increases with every notification as the spec requires,
+/// `message` carries a human-readable status, and the partial output rides
+/// along in `content` using the same block format as `tools/call` results.
+/// Clients that did not ask for progress get only the final result.
+use crate::error::{Error, Result};
+use crate::tools::{ContentBlock, ProgressInfo, ToolExecutionResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+/// MCP progress notification method
+pub const PROGRESS_METHOD: &str = "notifications/progress";
+
+/// Partial output of a streaming tool call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolResultChunk {
+    /// Position of the chunk in the call's output, from 1
+    pub sequence: u64,
+    pub content: Vec<ContentBlock>,
+    pub progress: Option<ProgressInfo>,
+}
+
+impl ToolResultChunk {
+    /// Text of the chunk's text blocks
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter(|block| block.is_text())
+            .map(|block| block.content.as_str())
+            .collect()
+    }
+
+    /// `notifications/progress` params for this chunk
+    pub fn to_notification(&self, progress_token: &Value) -> Value {
+        let mut params = json!({
+            "progressToken": progress_token,
+            "progress": self.sequence,
+        });
+        if let Some(progress) = &self.progress {
+            params["message"] = json!(progress
+                .message
+                .clone()
+                .unwrap_or_else(|| format!("{:.0}%", progress.percentage)));
+            params["percentage"] = json!(progress.percentage);
+        }
+        if !self.content.is_empty() {
+            params["content"] =
+                Value::Array(self.content.iter().map(ContentBlock::to_mcp).collect());
+        }
+        json!({
+            "jsonrpc": "2.0",
+            "method": PROGRESS_METHOD,
+            "params": params
+        })
+    }
+
+    /// Chunk and progress token from `notifications/progress` params
+    pub fn from_notification(params: &Value) -> Option<(String, Self)> {
+        let token = match params.get("progressToken")? {
+            Value::String(token) => token.clone(),
+            token => token.to_string(),
+        };
+        let content = params
+            .get("content")
+            .and_then(|c| c.as_array())
+            .map(|blocks| blocks.iter().filter_map(ContentBlock::from_mcp).collect())
+            .unwrap_or_default();
+        let progress = params
+            .get("percentage")
+            .and_then(|p| p.as_f64())
+            .map(|percentage| ProgressInfo {
+                percentage: percentage as f32,
+                message: params
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .map(String::from),
+                estimated_time_remaining: None,
+            });
+        Some((
+            token,
+            Self {
+                sequence: params.get("progress")?.as_f64()? as u64,
+                content,
+                progress,
+            },
+        ))
+    }
+}
+
+/// Sending half handed to a streaming tool handler
+///
+/// Sends are fire-and-forget; a stream whose client did not ask for progress
+/// drops everything, so handlers can report unconditionally.
+#[derive(Debug, Clone)]
+pub struct ToolStream {
+    sender: Option<mpsc::UnboundedSender<ToolResultChunk>>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl ToolStream {
+    /// A stream that discards its chunks
+    pub fn disabled() -> Self {
+        Self {
+            sender: None,
+            sequence: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// A stream and the receiver its chunks arrive on
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<ToolResultChunk>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (
+            Self {
+                sender: Some(sender),
+                sequence: Arc::new(AtomicU64::new(0)),
+            },
+            receiver,
+        )
+    }
+
+    /// Whether anyone receives the chunks
+    pub fn is_enabled(&self) -> bool {
+        self.sender.as_ref().is_some_and(|s| !s.is_closed())
+    }
+
+    /// Send partial content with optional progress
+    pub fn send(&self, content: Vec<ContentBlock>, progress: Option<ProgressInfo>) {
+        if let Some(sender) = &self.sender {
+            let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+            let _ = sender.send(ToolResultChunk {
+                sequence,
+                content,
+                progress,
+            });
+        }
+    }
+
+    /// Send a piece of text output
+    pub fn text(&self, text: impl Into<String>) {
+        self.send(vec![ContentBlock::text(text)], None);
+    }
+
+    /// Report progress without output
+    pub fn progress(&self, percentage: f32, message: Option<String>) {
+        self.send(
+            Vec::new(),
+            Some(ProgressInfo {
+                percentage,
+                message,
+                estimated_time_remaining: None,
+            }),
+        );
+    }
+}
+
+/// Receiving half of a streaming tool call made by a client
+pub struct ToolResultStream {
+    chunks: mpsc::UnboundedReceiver<ToolResultChunk>,
+    result: oneshot::Receiver<Result<ToolExecutionResult>>,
+}
+
+impl ToolResultStream {
+    /// Stream fed by `chunks` and completed by `result`
+    pub fn new(
+        chunks: mpsc::UnboundedReceiver<ToolResultChunk>,
+        result: oneshot::Receiver<Result<ToolExecutionResult>>,
+    ) -> Self {
+        Self { chunks, result }
+    }
+
+    /// The next chunk, or `None` once the call has finished
+    pub async fn next_chunk(&mut self) -> Option<ToolResultChunk> {
+        self.chunks.recv().await
+    }
+
+    /// Wait for the final result, discarding chunks not yet read
+    pub async fn finish(self) -> Result<ToolExecutionResult> {
+        self.result
+            .await
+            .map_err(|_| Error::service("Streaming tool call was abandoned"))?
+    }
+}
+
+impl std::fmt::Debug for ToolResultStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolResultStream").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,327 @@
+//! Pooled byte buffers for transport frames and subprocess output
+//!
+//! Streaming a large `kubectl logs` or tool result otherwise allocates and
+//! frees a buffer for every read. Buffers taken from a [`BufferPool`] return
+//! to it when dropped, so steady streaming reuses a handful of allocations.
+
+use bytes::{Buf, BytesMut};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::ops::{Deref, DerefMut};
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+/// Capacity of freshly allocated buffers
+pub const DEFAULT_BUFFER_CAPACITY: usize = 16 * 1024;
+
+/// Buffers that grew past this are freed instead of pooled, so one huge
+/// message does not pin its memory for the life of the server
+pub const MAX_RETAINED_CAPACITY: usize = 4 * 1024 * 1024;
+
+/// Idle buffers kept by the shared pool
+const MAX_POOLED_BUFFERS: usize = 64;
+
+/// Bytes requested from the reader per read
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Counters for a pool, as served on `/health/buffers`
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PoolStats {
+    /// Buffers handed out from the pool
+    pub hits: u64,
+    /// Buffers that had to be allocated
+    pub misses: u64,
+    /// Buffers returned for reuse
+    pub returned: u64,
+    /// Buffers dropped because the pool was full or they had grown too large
+    pub discarded: u64,
+    /// Buffers currently idle in the pool
+    pub pooled: usize,
+    /// `hits / (hits + misses)`, 0 before the first request
+    pub hit_rate: f64,
+}
+
+/// Free list of `BytesMut` buffers
+#[derive(Debug)]
+pub struct BufferPool {
+    free: Mutex<Vec<BytesMut>>,
+    capacity: usize,
+    max_retained: usize,
+    max_pooled: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    returned: AtomicU64,
+    discarded: AtomicU64,
+}
+
+impl BufferPool {
+    /// Pool of buffers starting at `capacity` bytes, keeping at most `max_pooled` idle
+    pub fn new(capacity: usize, max_retained: usize, max_pooled: usize) -> Arc<Self> {
+        Arc::new(Self {
+            free: Mutex::new(Vec::with_capacity(max_pooled)),
+            capacity,
+            max_retained,
+            max_pooled,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            returned: AtomicU64::new(0),
+            discarded: AtomicU64::new(0),
+        })
+    }
+
+    /// Pool shared by the transports and command runners
+    pub fn shared() -> &'static Arc<BufferPool> {
+        static SHARED: OnceLock<Arc<BufferPool>> = OnceLock::new();
+        SHARED.get_or_init(|| {
+            Self::new(
+                DEFAULT_BUFFER_CAPACITY,
+                MAX_RETAINED_CAPACITY,
+                MAX_POOLED_BUFFERS,
+            )
+        })
+    }
+
+    /// An empty buffer, reused when one is idle
+    pub fn get(self: &Arc<Self>) -> PooledBuffer {
+        let reused = self.free.lock().ok().and_then(|mut free| free.pop());
+        let buffer = match reused {
+            Some(buffer) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                buffer
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                BytesMut::with_capacity(self.capacity)
+            }
+        };
+        PooledBuffer {
+            buffer,
+            pool: Arc::clone(self),
+        }
+    }
+
+    fn put(&self, mut buffer: BytesMut) {
+        if buffer.capacity() <= self.max_retained {
+            buffer.clear();
+            if let Ok(mut free) = self.free.lock() {
+                if free.len() < self.max_pooled {
+                    free.push(buffer);
+                    self.returned.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+            }
+        }
+        self.discarded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> PoolStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let requests = hits + misses;
+        PoolStats {
+            hits,
+            misses,
+            returned: self.returned.load(Ordering::Relaxed),
+            discarded: self.discarded.load(Ordering::Relaxed),
+            pooled: self.free.lock().map(|free| free.len()).unwrap_or(0),
+            hit_rate: if requests == 0 {
+                0.0
+            } else {
+                hits as f64 / requests as f64
+            },
+        }
+    }
+}
+
+/// Buffer that goes back to its pool when dropped
+///
+/// Bytes split off and frozen keep sharing the allocation; the pool gets it
+/// back whole once they are dropped.
+#[derive(Debug)]
+pub struct PooledBuffer {
+    buffer: BytesMut,
+    pool: Arc<BufferPool>,
+}
+
+impl Default for PooledBuffer {
+    fn default() -> Self {
+        BufferPool::shared().get()
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = BytesMut;
+
+    fn deref(&self) -> &BytesMut {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut BytesMut {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        self.pool.put(std::mem::take(&mut self.buffer));
+    }
+}
+
+/// Read `reader` to the end into `buffer`
+pub async fn read_to_end<R: AsyncRead + Unpin>(
+    mut reader: R,
+    buffer: &mut BytesMut,
+) -> std::io::Result<()> {
+    loop {
+        buffer.reserve(READ_CHUNK);
+        if reader.read_buf(buffer).await? == 0 {
+            return Ok(());
+        }
+    }
+}
+
+/// Call `on_line` for each line of `reader`, without its line ending
+///
+/// Lines are read through one pooled buffer instead of a `String` each.
+pub async fn for_each_line<R, F>(mut reader: R, mut on_line: F) -> std::io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    F: FnMut(&str),
+{
+    let mut buffer = BufferPool::shared().get();
+    let mut scanned = 0;
+    loop {
+        while let Some(end) = buffer[scanned..].iter().position(|b| *b == b'\n') {
+            let end = scanned + end;
+            on_line(&line_text(&buffer[..end]));
+            buffer.advance(end + 1);
+            scanned = 0;
+        }
+        scanned = buffer.len();
+        buffer.reserve(READ_CHUNK);
+        if reader.read_buf(&mut *buffer).await? == 0 {
+            if !buffer.is_empty() {
+                on_line(&line_text(&buffer));
+            }
+            return Ok(());
+        }
+    }
+}
+
+fn line_text(line: &[u8]) -> Cow<'_, str> {
+    String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+/// Output of a finished command, captured into pooled buffers
+#[derive(Debug)]
+pub struct CapturedOutput {
+    pub status: ExitStatus,
+    pub stdout: PooledBuffer,
+    pub stderr: PooledBuffer,
+}
+
+impl CapturedOutput {
+    /// Standard output as text, invalid UTF-8 replaced
+    pub fn stdout_text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.stdout)
+    }
+
+    /// Standard error as text, invalid UTF-8 replaced
+    pub fn stderr_text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.stderr)
+    }
+}
+
+/// Run `command` to completion like `Command::output`, reading into pooled buffers
+pub async fn capture_output(command: &mut Command) -> std::io::Result<CapturedOutput> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let pool = BufferPool::shared();
+    let (mut stdout, mut stderr) = (pool.get(), pool.get());
+    let (out, err) = (child.stdout.take(), child.stderr.take());
+    let (read_out, read_err) = tokio::join!(
+        async {
+            match out {
+                Some(out) => read_to_end(out, &mut stdout).await,
+                None => Ok(()),
+            }
+        },
+        async {
+            match err {
+                Some(err) => read_to_end(err, &mut stderr).await,
+                None => Ok(()),
+            }
+        }
+    );
+    read_out?;
+    read_err?;
+    Ok(CapturedOutput {
+        status: child.wait().await?,
+        stdout,
+        stderr,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_buffers_and_counts_hits() {
+        let pool = BufferPool::new(64, 1024, 2);
+        drop(pool.get());
+        let mut buffer = pool.get();
+        buffer.extend_from_slice(b"frame");
+        let frozen = buffer.split().freeze();
+        drop(buffer);
+        assert_eq!(&frozen[..], b"frame");
+
+        let mut large = pool.get();
+        large.reserve(4096);
+        drop(large);
+
+        let stats = pool.stats();
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+        assert_eq!((stats.returned, stats.discarded), (2, 1));
+        assert_eq!(stats.pooled, 0);
+        assert!((stats.hit_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert!(pool.get().is_empty());
+    }
+
+    #[tokio::test]
+    async fn splits_lines_and_captures_command_output() {
+        let (reader, mut writer) = tokio::io::duplex(8);
+        let writing = tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            writer
+                .write_all(b"first line\r\nsecond line that spans reads\nlast")
+                .await
+                .unwrap();
+        });
+        let mut lines = Vec::new();
+        for_each_line(reader, |line| lines.push(line.to_string()))
+            .await
+            .unwrap();
+        writing.await.unwrap();
+        assert_eq!(
+            lines,
+            ["first line", "second line that spans reads", "last"]
+        );
+
+        let output = capture_output(Command::new("sh").args(["-c", "echo out; echo err >&2"]))
+            .await
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout_text(), "out\n");
+        assert_eq!(output.stderr_text(), "err\n");
+    }
+}
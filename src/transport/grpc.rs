@@ -0,0 +1,708 @@
+//! gRPC transport carrying MCP messages as protobuf frames
+//!
+//! Implements the `mcp.v1.Mcp` service from `proto/mcp.proto` on the HTTP
+//! server's port over HTTP/2 without TLS (h2c), so clients generated from the
+//! proto file, such as tonic's, can talk to it. Each `Frame` holds one
+//! JSON-RPC message; `Stream` keeps a session open and also carries progress
+//! notifications and tool list changes.
+
+use crate::error::{Error, Result};
+use crate::tools::registry::{JsonRpcRequest, JsonRpcResponse, ToolRegistry};
+use crate::transport::buffer::{BufferPool, PooledBuffer};
+use crate::transport::jsonrpc::RawMessage;
+use crate::transport::{NotificationHandler, Transport, TransportError};
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use bytes::BufMut;
+use futures::{stream, Stream, StreamExt};
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::{Bytes, Frame};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, Mutex, RwLock};
+
+/// Requests waiting for their response, keyed by request id
+type PendingRequests =
+    Mutex<HashMap<String, oneshot::Sender<std::result::Result<Value, TransportError>>>>;
+
+/// Path of the unary `Call` method
+pub const CALL_PATH: &str = "/mcp.v1.Mcp/Call";
+
+/// Path of the bidirectional `Stream` method
+pub const STREAM_PATH: &str = "/mcp.v1.Mcp/Stream";
+
+/// Largest message accepted, gRPC's default
+pub const MAX_MESSAGE_BYTES: usize = 4 * 1024 * 1024;
+
+const GRPC_CONTENT_TYPE: &str = "application/grpc";
+
+/// Bytes percent-encoded in `grpc-message`
+const GRPC_MESSAGE: &AsciiSet = &CONTROLS.add(b'%');
+
+/// Outgoing messages buffered per stream
+const CONNECTION_BUFFER: usize = 64;
+
+type FrameStream =
+    Pin<Box<dyn Stream<Item = std::result::Result<Frame<Bytes>, Infallible>> + Send>>;
+
+/// gRPC status codes this transport answers with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrpcStatus {
+    Ok = 0,
+    InvalidArgument = 3,
+    ResourceExhausted = 8,
+    Internal = 13,
+}
+
+fn put_varint(out: &mut impl BufMut, mut value: u64) {
+    while value >= 0x80 {
+        out.put_u8(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.put_u8(value as u8);
+}
+
+fn get_varint(bytes: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| Error::protocol("Truncated protobuf varint"))?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(Error::protocol("Protobuf varint is too long"))
+}
+
+/// Protobuf encoding of `Frame { payload }`
+pub fn encode_frame(payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    if !payload.is_empty() {
+        frame.push(0x0A);
+        put_varint(&mut frame, payload.len() as u64);
+        frame.extend_from_slice(payload);
+    }
+    frame
+}
+
+/// Payload of a protobuf `Frame`, skipping fields it does not know
+pub fn decode_frame(frame: &[u8]) -> Result<&[u8]> {
+    let mut payload: &[u8] = &[];
+    let mut pos = 0;
+    while pos < frame.len() {
+        let key = get_varint(frame, &mut pos)?;
+        let skip = match key & 7 {
+            0 => {
+                get_varint(frame, &mut pos)?;
+                0
+            }
+            1 => 8,
+            2 => usize::try_from(get_varint(frame, &mut pos)?)
+                .map_err(|_| Error::protocol("Protobuf field is too long"))?,
+            5 => 4,
+            wire_type => {
+                return Err(Error::protocol(format!(
+                    "Unsupported protobuf wire type {}",
+                    wire_type
+                )))
+            }
+        };
+        let end = pos
+            .checked_add(skip)
+            .filter(|end| *end <= frame.len())
+            .ok_or_else(|| Error::protocol("Truncated protobuf field"))?;
+        if key == 0x0A {
+            payload = &frame[pos..end];
+        }
+        pos = end;
+    }
+    Ok(payload)
+}
+
+/// A payload as one gRPC message: uncompressed flag, big-endian length, `Frame`
+pub fn encode_message(payload: &[u8]) -> Bytes {
+    let mut message = BufferPool::shared().get();
+    message.reserve(payload.len() + 15);
+    message.put_u8(0);
+    message.put_u32(0);
+    if !payload.is_empty() {
+        message.put_u8(0x0A);
+        put_varint(&mut *message, payload.len() as u64);
+        message.extend_from_slice(payload);
+    }
+    let frame_len = (message.len() - 5) as u32;
+    message[1..5].copy_from_slice(&frame_len.to_be_bytes());
+    message.split().freeze()
+}
+
+/// Splits received bytes into the payloads of gRPC messages
+///
+/// Bytes are buffered in a pooled buffer and payloads share its allocation.
+#[derive(Debug, Default)]
+pub struct MessageDecoder {
+    buffer: PooledBuffer,
+}
+
+impl MessageDecoder {
+    /// Buffer received bytes
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Whether no partial message is buffered
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Payload of the next complete message, if one is buffered
+    pub fn next_payload(&mut self) -> Result<Option<Bytes>> {
+        if self.buffer.len() < 5 {
+            return Ok(None);
+        }
+        if self.buffer[0] != 0 {
+            return Err(Error::protocol(
+                "Compressed gRPC messages are not supported",
+            ));
+        }
+        let length = u32::from_be_bytes([
+            self.buffer[1],
+            self.buffer[2],
+            self.buffer[3],
+            self.buffer[4],
+        ]) as usize;
+        if length > MAX_MESSAGE_BYTES {
+            return Err(Error::protocol(format!(
+                "gRPC message of {} bytes exceeds the {} byte limit",
+                length, MAX_MESSAGE_BYTES
+            )));
+        }
+        if self.buffer.len() < 5 + length {
+            return Ok(None);
+        }
+        let message = self.buffer.split_to(5 + length).freeze();
+        let payload = decode_frame(&message[5..])?;
+        Ok(Some(message.slice_ref(payload)))
+    }
+}
+
+fn trailers(status: GrpcStatus, message: &str) -> HeaderMap {
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", HeaderValue::from(status as u16));
+    let encoded = utf8_percent_encode(message, GRPC_MESSAGE).to_string();
+    if let (false, Ok(value)) = (message.is_empty(), HeaderValue::from_str(&encoded)) {
+        trailers.insert("grpc-message", value);
+    }
+    trailers
+}
+
+fn grpc_response(frames: FrameStream) -> Response {
+    let mut response = Response::new(Body::new(StreamBody::new(frames)));
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(GRPC_CONTENT_TYPE),
+    );
+    response
+}
+
+/// Response for a call that fails before sending any message
+fn status_response(status: GrpcStatus, message: &str) -> Response {
+    let mut response = grpc_response(Box::pin(stream::empty()));
+    response.headers_mut().extend(trailers(status, message));
+    response
+}
+
+fn is_grpc(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with(GRPC_CONTENT_TYPE))
+}
+
+fn response_message(response: &JsonRpcResponse) -> Option<Bytes> {
+    match serde_json::to_vec(response) {
+        Ok(payload) => Some(encode_message(&payload)),
+        Err(e) => {
+            tracing::error!("Failed to serialize gRPC response: {}", e);
+            None
+        }
+    }
+}
+
+/// Server side of the gRPC transport
+pub fn router(registry: Arc<ToolRegistry>) -> Router {
+    Router::new()
+        .route(CALL_PATH, post(call_handler))
+        .route(STREAM_PATH, post(stream_handler))
+        .with_state(registry)
+}
+
+async fn call_handler(State(registry): State<Arc<ToolRegistry>>, request: Request) -> Response {
+    if !is_grpc(request.headers()) {
+        return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+    }
+    let session = registry.session_for(request.headers());
+    let body = match axum::body::to_bytes(request.into_body(), MAX_MESSAGE_BYTES + 5).await {
+        Ok(body) => body,
+        Err(e) => return status_response(GrpcStatus::ResourceExhausted, &e.to_string()),
+    };
+    let mut decoder = MessageDecoder::default();
+    decoder.push(&body);
+    let payload = match decoder.next_payload() {
+        Ok(Some(payload)) if decoder.is_empty() => payload,
+        Ok(_) => {
+            return status_response(
+                GrpcStatus::InvalidArgument,
+                "Call takes exactly one message",
+            )
+        }
+        Err(e) => return status_response(GrpcStatus::InvalidArgument, &e.to_string()),
+    };
+    let request: JsonRpcRequest = match serde_json::from_slice(&payload) {
+        Ok(request) => request,
+        Err(e) => {
+            return status_response(GrpcStatus::InvalidArgument, &format!("Parse error: {}", e))
+        }
+    };
+    let response = registry.handle_in_session(request, &session, None).await;
+    let Some(message) = response_message(&response) else {
+        return status_response(GrpcStatus::Internal, "Failed to serialize response");
+    };
+    grpc_response(Box::pin(stream::iter([
+        Ok(Frame::data(message)),
+        Ok(Frame::trailers(trailers(GrpcStatus::Ok, ""))),
+    ])))
+}
+
+async fn stream_handler(State(registry): State<Arc<ToolRegistry>>, request: Request) -> Response {
+    if !is_grpc(request.headers()) {
+        return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+    }
+    let session = Arc::new(registry.session_for(request.headers()));
+    let mut incoming = request.into_body().into_data_stream();
+    let (sender, outgoing) = mpsc::channel::<Value>(CONNECTION_BUFFER);
+    let (status_sender, status) = oneshot::channel();
+    tokio::spawn(async move {
+        let list_changes = registry.forward_list_changes(sender.clone());
+        let mut decoder = MessageDecoder::default();
+        let mut status = (GrpcStatus::Ok, String::new());
+        'read: while let Some(chunk) = incoming.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    status = (GrpcStatus::Internal, e.to_string());
+                    break;
+                }
+            };
+            decoder.push(&chunk);
+            loop {
+                let payload = match decoder.next_payload() {
+                    Ok(Some(payload)) => payload,
+                    Ok(None) => break,
+                    Err(e) => {
+                        status = (GrpcStatus::InvalidArgument, e.to_string());
+                        break 'read;
+                    }
+                };
+                let request: JsonRpcRequest = match serde_json::from_slice(&payload) {
+                    Ok(request) => request,
+                    Err(e) => {
+                        let response =
+                            JsonRpcResponse::error(None, -32700, format!("Parse error: {}", e));
+                        let _ = sender.send(response.into()).await;
+                        continue;
+                    }
+                };
+                // Notifications get no response
+                if request.id.is_none() {
+                    continue;
+                }
+                let registry = Arc::clone(&registry);
+                let session = Arc::clone(&session);
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    let response = registry
+                        .handle_in_session(request, &session, Some(&sender))
+                        .await;
+                    if sender.send(response.into()).await.is_err() {
+                        tracing::warn!("gRPC stream closed before its response was sent");
+                    }
+                });
+            }
+        }
+        // The response stream ends once calls still running have answered
+        list_changes.abort();
+        let _ = status_sender.send(status);
+    });
+
+    let messages = stream::unfold(outgoing, |mut outgoing| async move {
+        let message = outgoing.recv().await?;
+        Some((message, outgoing))
+    })
+    .filter_map(|message| async move {
+        match serde_json::to_vec(&message) {
+            Ok(payload) => Some(Ok(Frame::data(encode_message(&payload)))),
+            Err(e) => {
+                tracing::error!("Failed to serialize gRPC message: {}", e);
+                None
+            }
+        }
+    });
+    let end = stream::once(async move {
+        let (code, message) = status.await.unwrap_or((GrpcStatus::Ok, String::new()));
+        Ok(Frame::trailers(trailers(code, &message)))
+    });
+    grpc_response(Box::pin(messages.chain(end)))
+}
+
+/// Client side of the gRPC transport, over one `Stream` call
+pub struct GrpcTransport {
+    url: String,
+    auth_token: Option<String>,
+    outgoing: Option<mpsc::Sender<Bytes>>,
+    pending: Arc<PendingRequests>,
+    notification_handlers: Arc<RwLock<Vec<NotificationHandler>>>,
+}
+
+impl GrpcTransport {
+    /// Transport for a server at an `http://host:port` URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            auth_token: None,
+            outgoing: None,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            notification_handlers: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Send a bearer token as `authorization` metadata
+    pub fn with_auth_token(mut self, auth_token: impl Into<String>) -> Self {
+        self.auth_token = Some(auth_token.into());
+        self
+    }
+
+    async fn send(&self, message: &Value) -> std::result::Result<(), TransportError> {
+        let payload = serde_json::to_vec(message)
+            .map_err(|e| TransportError::send(format!("Failed to serialize message: {}", e)))?;
+        let outgoing = self
+            .outgoing
+            .as_ref()
+            .ok_or_else(|| TransportError::connection_failed("Not connected"))?;
+        outgoing
+            .send(encode_message(&payload))
+            .await
+            .map_err(|_| TransportError::connection_failed("gRPC stream closed"))
+    }
+
+    /// Route one received message to its waiting request or the notification handlers
+    async fn dispatch(
+        payload: &[u8],
+        pending: &PendingRequests,
+        handlers: &RwLock<Vec<NotificationHandler>>,
+    ) {
+        let Ok(message) = RawMessage::from_slice(payload) else {
+            tracing::warn!("Ignoring gRPC message that is not JSON");
+            return;
+        };
+        if let Some(id) = message.id_str() {
+            if let Some(waiting) = pending.lock().await.remove(id) {
+                let _ = waiting.send(Self::outcome(&message));
+            }
+        } else if let (true, Some(method)) = (message.is_notification(), &message.method) {
+            let params = message.params_value().unwrap_or(Value::Null);
+            for handler in handlers.read().await.iter() {
+                handler(method.to_string(), params.clone()).await;
+            }
+        }
+    }
+
+    /// A response's result, built straight from its raw text, or its error
+    fn outcome(message: &RawMessage<'_>) -> std::result::Result<Value, TransportError> {
+        match (message.result, message.error) {
+            (Some(_), _) => message
+                .result_value()
+                .map_err(|e| TransportError::parse(e.to_string())),
+            (None, Some(error)) => Err(TransportError::RequestFailed(error.get().to_string())),
+            (None, None) => Err(TransportError::parse(
+                "Response has neither result nor error",
+            )),
+        }
+    }
+}
+
+impl std::fmt::Debug for GrpcTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrpcTransport")
+            .field("url", &self.url)
+            .field("auth_token", &self.auth_token.is_some())
+            .field("connected", &self.outgoing.is_some())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Transport for GrpcTransport {
+    async fn connect(&mut self) -> std::result::Result<(), TransportError> {
+        let uri: hyper::Uri = format!("{}{}", self.url.trim_end_matches('/'), STREAM_PATH)
+            .parse()
+            .map_err(|e| TransportError::connection_failed(format!("Invalid URL: {}", e)))?;
+        if uri.scheme_str() != Some("http") {
+            return Err(TransportError::NotSupported(
+                "gRPC transport needs an http:// URL (h2c)".to_string(),
+            ));
+        }
+        let (outgoing, receiver) = mpsc::channel::<Bytes>(CONNECTION_BUFFER);
+        let frames: FrameStream = Box::pin(stream::unfold(receiver, |mut receiver| async move {
+            let message = receiver.recv().await?;
+            Some((Ok(Frame::data(message)), receiver))
+        }));
+        let mut request = hyper::Request::post(uri)
+            .header(header::CONTENT_TYPE, GRPC_CONTENT_TYPE)
+            .header(header::TE, "trailers");
+        if let Some(token) = &self.auth_token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = request
+            .body(StreamBody::new(frames))
+            .map_err(|e| TransportError::connection_failed(e.to_string()))?;
+        let client = Client::builder(TokioExecutor::new())
+            .http2_only(true)
+            .build_http();
+        let response = client.request(request).await.map_err(|e| {
+            TransportError::connection_failed(format!("gRPC connection failed: {}", e))
+        })?;
+        if !response.status().is_success() {
+            return Err(TransportError::connection_failed(format!(
+                "gRPC server answered {}",
+                response.status()
+            )));
+        }
+        if let Some(status) = response.headers().get("grpc-status") {
+            if status != "0" {
+                let message = response
+                    .headers()
+                    .get("grpc-message")
+                    .and_then(|m| m.to_str().ok())
+                    .unwrap_or_default();
+                return Err(TransportError::connection_failed(format!(
+                    "gRPC status {:?}: {}",
+                    status, message
+                )));
+            }
+        }
+
+        let pending = Arc::clone(&self.pending);
+        let handlers = Arc::clone(&self.notification_handlers);
+        let mut body = response.into_body();
+        tokio::spawn(async move {
+            let mut decoder = MessageDecoder::default();
+            while let Some(frame) = body.frame().await {
+                let data = match frame.map(Frame::into_data) {
+                    Ok(Ok(data)) => data,
+                    Ok(Err(trailers)) => {
+                        if let Some(status) =
+                            trailers.trailers_ref().and_then(|t| t.get("grpc-status"))
+                        {
+                            if status != "0" {
+                                tracing::warn!("gRPC stream ended with status {:?}", status);
+                            }
+                        }
+                        continue;
+                    }
+                    Err(e) => {
+                        tracing::warn!("gRPC stream failed: {}", e);
+                        break;
+                    }
+                };
+                decoder.push(&data);
+                loop {
+                    match decoder.next_payload() {
+                        Ok(Some(payload)) => Self::dispatch(&payload, &pending, &handlers).await,
+                        Ok(None) => break,
+                        Err(e) => {
+                            tracing::warn!("Invalid gRPC message: {}", e);
+                            return;
+                        }
+                    }
+                }
+            }
+            // Requests still waiting fail once their senders are dropped
+            pending.lock().await.clear();
+            drop(client);
+        });
+        self.outgoing = Some(outgoing);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> std::result::Result<(), TransportError> {
+        // Ending the request stream lets the server finish and close the call
+        self.outgoing = None;
+        Ok(())
+    }
+
+    async fn request(
+        &mut self,
+        method: &str,
+        params: Option<Value>,
+    ) -> std::result::Result<Value, TransportError> {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let (waiting, response) = oneshot::channel();
+        self.pending
+            .lock()
+            .await
+            .insert(request_id.clone(), waiting);
+        let message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "method": method,
+            "params": params.unwrap_or(Value::Null)
+        });
+        if let Err(e) = self.send(&message).await {
+            self.pending.lock().await.remove(&request_id);
+            return Err(e);
+        }
+        response.await.map_err(|_| {
+            TransportError::connection_failed("gRPC stream closed before the response")
+        })?
+    }
+
+    async fn notify(
+        &mut self,
+        method: &str,
+        params: Option<Value>,
+    ) -> std::result::Result<(), TransportError> {
+        self.send(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params.unwrap_or(Value::Null)
+        }))
+        .await
+    }
+
+    async fn add_notification_handler(
+        &mut self,
+        handler: NotificationHandler,
+    ) -> std::result::Result<(), TransportError> {
+        self.notification_handlers.write().await.push(handler);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifecycle::LifecycleManager;
+    use crate::tools::{call_result, ToolDefinition};
+    use http_body_util::Full;
+    use serde_json::json;
+
+    #[test]
+    fn splits_messages_across_chunk_boundaries() {
+        let mut bytes = encode_message(br#"{"id":"1"}"#).to_vec();
+        // A frame with an unknown varint field before the payload
+        let mut frame = vec![0x10, 0x96, 0x01];
+        frame.extend(encode_frame(b"second"));
+        bytes.push(0);
+        bytes.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+        bytes.extend(&frame);
+        bytes.extend_from_slice(&encode_message(b""));
+
+        let mut decoder = MessageDecoder::default();
+        let mut payloads = Vec::new();
+        for chunk in bytes.chunks(3) {
+            decoder.push(chunk);
+            while let Some(payload) = decoder.next_payload().unwrap() {
+                payloads.push(payload);
+            }
+        }
+        assert!(decoder.is_empty());
+        assert_eq!(
+            payloads,
+            vec![br#"{"id":"1"}"#.to_vec(), b"second".to_vec(), Vec::new()]
+        );
+
+        let mut compressed = MessageDecoder::default();
+        compressed.push(&[1, 0, 0, 0, 0]);
+        assert!(compressed.next_payload().is_err());
+        let mut oversized = MessageDecoder::default();
+        oversized.push(&[0, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert!(oversized.next_payload().is_err());
+        assert!(decode_frame(&[0x0A, 0x05, b'a']).is_err());
+    }
+
+    #[tokio::test]
+    async fn serves_unary_and_streaming_calls() {
+        let registry = ToolRegistry::new();
+        registry
+            .register_streaming(
+                ToolDefinition::new("count", "Count"),
+                |_, stream| async move {
+                    stream.text("one\n");
+                    stream.progress(50.0, None);
+                    stream.text("two\n");
+                    Ok(call_result("one\ntwo\n", json!({"count": 2})))
+                },
+            )
+            .unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router(Arc::new(registry)))
+                .await
+                .unwrap();
+        });
+
+        let client = Client::builder(TokioExecutor::new())
+            .http2_only(true)
+            .build_http();
+        let request = hyper::Request::post(format!("http://{}{}", addr, CALL_PATH))
+            .header(header::CONTENT_TYPE, GRPC_CONTENT_TYPE)
+            .body(Full::new(encode_message(
+                br#"{"jsonrpc":"2.0","id":7,"method":"tools/list"}"#,
+            )))
+            .unwrap();
+        let response = client.request(request).await.unwrap();
+        let collected = response.into_body().collect().await.unwrap();
+        assert_eq!(collected.trailers().unwrap()["grpc-status"], "0");
+        let mut decoder = MessageDecoder::default();
+        decoder.push(&collected.to_bytes());
+        let answer: Value =
+            serde_json::from_slice(&decoder.next_payload().unwrap().unwrap()).unwrap();
+        assert_eq!(answer["id"], 7);
+        assert_eq!(answer["result"]["tools"][0]["name"], "count");
+
+        let mut transport = GrpcTransport::new(format!("http://{}", addr));
+        transport.connect().await.unwrap();
+        let lifecycle = LifecycleManager::new(Box::new(transport));
+        let mut stream = lifecycle
+            .call_tool_streaming("count", json!({}))
+            .await
+            .unwrap();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = stream.next_chunk().await {
+            chunks.push(chunk);
+        }
+        assert_eq!(
+            chunks.iter().map(|c| c.sequence).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(chunks[1].progress.as_ref().unwrap().percentage, 50.0);
+        let result = stream.finish().await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.content[0].content, "one\ntwo\n");
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 use serde_json::{json, Value};
+use std::borrow::Cow;
 use std::fmt;
 use uuid::Uuid;
 
@@ -65,6 +67,71 @@ impl fmt::Display for JsonRpcError {
     }
 }
 
+/// Incoming JSON-RPC message parsed only as far as its envelope
+///
+/// `params`, `result` and `error` borrow their raw text from the input, so a
+/// reader can route a multi-MB tool result to its request and build the
+/// result's `Value` once, without an intermediate tree or clone.
+#[derive(Debug, Deserialize)]
+pub struct RawMessage<'a> {
+    /// Present (even when `null`) on requests and responses
+    #[serde(borrow, default, deserialize_with = "present")]
+    pub id: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    pub method: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    pub params: Option<&'a RawValue>,
+    /// Present (even when `null`) on successful responses
+    #[serde(borrow, default, deserialize_with = "present")]
+    pub result: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    pub error: Option<&'a RawValue>,
+}
+
+impl<'a> RawMessage<'a> {
+    /// Parse the envelope of a text message
+    pub fn parse(text: &'a str) -> serde_json::Result<Self> {
+        serde_json::from_str(text)
+    }
+
+    /// Parse the envelope of a message held as bytes
+    pub fn from_slice(data: &'a [u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(data)
+    }
+
+    /// The id when it is a string without escapes, as the uuid ids this crate sends are
+    pub fn id_str(&self) -> Option<&'a str> {
+        self.id.and_then(|id| serde_json::from_str(id.get()).ok())
+    }
+
+    /// Whether this is a notification rather than a request or response
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none() && self.method.is_some()
+    }
+
+    /// Build the result value, `Null` when the response carries none
+    pub fn result_value(&self) -> serde_json::Result<Value> {
+        parse_raw(self.result)
+    }
+
+    /// Build the params value, `Null` when the message carries none
+    pub fn params_value(&self) -> serde_json::Result<Value> {
+        parse_raw(self.params)
+    }
+}
+
+/// Keep a `null` field as its raw text instead of folding it into `None`
+fn present<'de, D>(deserializer: D) -> std::result::Result<Option<&'de RawValue>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    <&RawValue>::deserialize(deserializer).map(Some)
+}
+
+fn parse_raw(raw: Option<&RawValue>) -> serde_json::Result<Value> {
+    raw.map_or(Ok(Value::Null), |raw| serde_json::from_str(raw.get()))
+}
+
 /// JSON-RPC message (request, response, or notification)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -381,3 +448,40 @@ pub async fn perform_request<T: Transport>(
         .await
         .map_err(|e| Error::Transport(crate::error::TransportError::from(e)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrows_the_envelope_of_raw_messages() {
+        let text =
+            r#"{"jsonrpc":"2.0","id":"req-1","result":{"content":[{"type":"text","text":"ok"}]}}"#;
+        let message = RawMessage::parse(text).unwrap();
+        assert_eq!(message.id_str(), Some("req-1"));
+        assert!(!message.is_notification());
+        assert!(message.error.is_none());
+        assert_eq!(message.result_value().unwrap()["content"][0]["text"], "ok");
+
+        let notification = RawMessage::from_slice(
+            br#"{"jsonrpc":"2.0","method":"notifications/progress","params":{"progress":1}}"#,
+        )
+        .unwrap();
+        assert!(notification.is_notification());
+        assert_eq!(
+            notification.method.as_deref(),
+            Some("notifications/progress")
+        );
+        assert_eq!(notification.params_value().unwrap()["progress"], 1);
+        assert_eq!(notification.result_value().unwrap(), Value::Null);
+
+        let numbered =
+            RawMessage::parse(r#"{"id":7,"error":{"code":-32601,"message":"x"}}"#).unwrap();
+        assert_eq!(numbered.id.unwrap().get(), "7");
+        assert_eq!(numbered.id_str(), None);
+        assert!(numbered.result.is_none());
+
+        let empty = RawMessage::parse(r#"{"id":"req-2","result":null}"#).unwrap();
+        assert_eq!(empty.result.unwrap().get(), "null");
+    }
+}
@@ -39,20 +39,29 @@ impl MockTransport {
 
     /// Set response for a method with efficient mutex handling
     pub fn set_response(&self, method: &str, response: Value) -> Result<()> {
-        let mut responses = self.responses.lock().map_err(|_| crate::error::Error::service("Failed to acquire lock"))?;
+        let mut responses = self
+            .responses
+            .lock()
+            .map_err(|_| crate::error::Error::service("Failed to acquire lock"))?;
         responses.insert(method.to_string(), response);
         Ok(())
     }
 
     /// Get requests with zero-copy access
     pub fn get_requests(&self) -> Result<Vec<(String, Option<Value>)>> {
-        let requests = self.requests.lock().map_err(|_| crate::error::Error::service("Failed to acquire lock"))?;
+        let requests = self
+            .requests
+            .lock()
+            .map_err(|_| crate::error::Error::service("Failed to acquire lock"))?;
         Ok(requests.clone())
     }
 
     /// Get messages with optimized collection handling
     pub fn get_messages(&self) -> Result<Vec<Value>> {
-        let messages = self.messages.lock().map_err(|_| crate::error::Error::service("Failed to acquire lock"))?;
+        let messages = self
+            .messages
+            .lock()
+            .map_err(|_| crate::error::Error::service("Failed to acquire lock"))?;
         Ok(messages.clone())
     }
 
@@ -63,7 +72,10 @@ impl MockTransport {
 
     /// Efficient message handling for testing
     pub async fn receive(&mut self) -> crate::error::Result<Value> {
-        let mut messages = self.messages.lock().map_err(|_| crate::error::Error::service("Failed to acquire lock"))?;
+        let mut messages = self
+            .messages
+            .lock()
+            .map_err(|_| crate::error::Error::service("Failed to acquire lock"))?;
         if messages.is_empty() {
             Err(crate::error::Error::Transport(
                 crate::error::TransportError::ConnectionFailed {
@@ -98,7 +110,11 @@ impl MockTransport {
 
 impl std::fmt::Debug for MockTransport {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let handlers_count = self.notification_handlers.lock().map(|h| h.len()).unwrap_or(0);
+        let handlers_count = self
+            .notification_handlers
+            .lock()
+            .map(|h| h.len())
+            .unwrap_or(0);
         f.debug_struct("MockTransport")
             .field("connected", &self.connected)
             .field("requests", &self.requests)
@@ -112,13 +128,17 @@ impl std::fmt::Debug for MockTransport {
 #[async_trait]
 impl Transport for MockTransport {
     async fn connect(&mut self) -> std::result::Result<(), crate::transport::TransportError> {
-        let mut connected = self.connected.lock().map_err(|_| crate::transport::TransportError::connection_failed("Failed to acquire lock"))?;
+        let mut connected = self.connected.lock().map_err(|_| {
+            crate::transport::TransportError::connection_failed("Failed to acquire lock")
+        })?;
         *connected = true;
         Ok(())
     }
 
     async fn disconnect(&mut self) -> std::result::Result<(), crate::transport::TransportError> {
-        let mut connected = self.connected.lock().map_err(|_| crate::transport::TransportError::connection_failed("Failed to acquire lock"))?;
+        let mut connected = self.connected.lock().map_err(|_| {
+            crate::transport::TransportError::connection_failed("Failed to acquire lock")
+        })?;
         *connected = false;
         Ok(())
     }
@@ -137,12 +157,16 @@ impl Transport for MockTransport {
 
         // Store request with efficient mutex handling
         {
-            let mut requests = self.requests.lock().map_err(|_| crate::transport::TransportError::connection_failed("Failed to acquire lock"))?;
+            let mut requests = self.requests.lock().map_err(|_| {
+                crate::transport::TransportError::connection_failed("Failed to acquire lock")
+            })?;
             requests.push((method.to_string(), params.clone()));
         }
 
         // Return mock response with zero-copy when possible
-        let responses = self.responses.lock().map_err(|_| crate::transport::TransportError::connection_failed("Failed to acquire lock"))?;
+        let responses = self.responses.lock().map_err(|_| {
+            crate::transport::TransportError::connection_failed("Failed to acquire lock")
+        })?;
         if let Some(response) = responses.get(method) {
             Ok(response.clone())
         } else {
@@ -167,7 +191,9 @@ impl Transport for MockTransport {
         }
 
         // Store notification efficiently
-        let mut messages = self.messages.lock().map_err(|_| crate::transport::TransportError::connection_failed("Failed to acquire lock"))?;
+        let mut messages = self.messages.lock().map_err(|_| {
+            crate::transport::TransportError::connection_failed("Failed to acquire lock")
+        })?;
         messages.push(json!({
             "method": method,
             "params": params
@@ -180,7 +206,9 @@ impl Transport for MockTransport {
         &mut self,
         handler: NotificationHandler,
     ) -> std::result::Result<(), crate::transport::TransportError> {
-        let mut handlers = self.notification_handlers.lock().map_err(|_| crate::transport::TransportError::connection_failed("Failed to acquire lock"))?;
+        let mut handlers = self.notification_handlers.lock().map_err(|_| {
+            crate::transport::TransportError::connection_failed("Failed to acquire lock")
+        })?;
         handlers.push(handler);
         Ok(())
     }
@@ -6,13 +6,20 @@ use std::pin::Pin;
 use std::sync::Arc;
 use thiserror::Error;
 
+pub mod buffer;
+pub mod framing;
+pub mod grpc;
 pub mod http;
 pub mod jsonrpc;
 pub mod mock;
+pub mod sse;
 pub mod stdio;
+pub mod tenancy;
 pub mod websocket;
 
+pub use grpc::GrpcTransport;
 pub use mock::MockTransport;
+pub use sse::SseTransport;
 pub use stdio::StdioTransport;
 pub use websocket::WebSocketTransport;
 
@@ -204,6 +211,8 @@ pub enum TransportType {
     Stdio,
     Http,
     WebSocket,
+    Sse,
+    Grpc,
 }
 
 /// Notification type
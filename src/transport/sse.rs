@@ -0,0 +1,566 @@
+use crate::error::{Error, Result};
+use crate::tools::registry::{JsonRpcRequest, Session, ToolRegistry};
+use crate::transport::jsonrpc::RawMessage;
+use crate::transport::{NotificationHandler, Transport, TransportError};
+use async_trait::async_trait;
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::stream::Stream;
+use futures::StreamExt;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
+
+/// Path of the server's event stream
+pub const SSE_PATH: &str = "/sse";
+
+/// Path clients POST messages to
+pub const MESSAGES_PATH: &str = "/messages";
+
+/// Messages buffered per session before POSTs are rejected
+const SESSION_BUFFER: usize = 64;
+
+/// One event parsed from a `text/event-stream` body
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    pub event: String,
+    pub data: String,
+}
+
+/// Incremental `text/event-stream` parser
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: String,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feed a chunk of the body and return the events it completes
+    pub fn feed(&mut self, chunk: &str) -> Vec<SseEvent> {
+        self.buffer.push_str(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.find('\n') {
+            let line: String = self.buffer.drain(..=end).collect();
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                // A blank line dispatches the event; events without data are ignored
+                if !self.data.is_empty() {
+                    events.push(SseEvent {
+                        event: self.event.take().unwrap_or_else(|| "message".to_string()),
+                        data: self.data.join("\n"),
+                    });
+                }
+                self.event = None;
+                self.data.clear();
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+type PendingRequests =
+    Arc<Mutex<HashMap<String, oneshot::Sender<std::result::Result<Value, TransportError>>>>>;
+
+/// MCP client transport over Server-Sent Events
+///
+/// Opens the server's event stream, waits for the `endpoint` event naming
+/// where to POST messages, and matches responses arriving on the stream to
+/// requests by id. Server notifications go to the registered handlers.
+pub struct SseTransport {
+    url: String,
+    client: Client,
+    endpoint: Option<url::Url>,
+    pending: PendingRequests,
+    notification_handlers: Arc<Mutex<Vec<NotificationHandler>>>,
+    reader: Option<JoinHandle<()>>,
+    next_id: AtomicU64,
+    timeout: Duration,
+}
+
+impl SseTransport {
+    /// Create a transport for the server's SSE endpoint, e.g. `http://host:8080/sse`
+    pub fn new(url: String) -> Result<Self> {
+        let client = Client::builder()
+            .pool_idle_timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            url,
+            client,
+            endpoint: None,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            notification_handlers: Arc::new(Mutex::new(Vec::new())),
+            reader: None,
+            next_id: AtomicU64::new(1),
+            timeout: Duration::from_secs(30),
+        })
+    }
+
+    /// Set how long to wait for the endpoint event and for each response
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// URL messages are POSTed to, once connected
+    pub fn endpoint(&self) -> Option<&url::Url> {
+        self.endpoint.as_ref()
+    }
+
+    async fn post(&self, message: &Value) -> std::result::Result<(), TransportError> {
+        let endpoint = self
+            .endpoint
+            .as_ref()
+            .ok_or_else(|| TransportError::connection_failed("SSE transport is not connected"))?;
+        let response = self
+            .client
+            .post(endpoint.clone())
+            .json(message)
+            .send()
+            .await
+            .map_err(|e| TransportError::send(format!("SSE message POST failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(TransportError::request_failed(format!(
+                "SSE message POST returned {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for SseTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SseTransport")
+            .field("url", &self.url)
+            .field("endpoint", &self.endpoint.as_ref().map(|e| e.as_str()))
+            .field("connected", &self.reader.is_some())
+            .finish()
+    }
+}
+
+impl Drop for SseTransport {
+    fn drop(&mut self) {
+        if let Some(reader) = self.reader.take() {
+            reader.abort();
+        }
+    }
+}
+
+/// Route one message from the stream to its pending request or the notification handlers
+async fn dispatch(
+    message: RawMessage<'_>,
+    pending: &PendingRequests,
+    handlers: &Arc<Mutex<Vec<NotificationHandler>>>,
+) {
+    match (message.id, &message.method) {
+        (Some(id), None) => {
+            if let Some(sender) = pending.lock().await.remove(id.get()) {
+                let _ = sender.send(outcome(&message));
+            }
+        }
+        (None, Some(method)) => {
+            let params = message.params_value().unwrap_or(Value::Null);
+            let handlers = handlers.lock().await.clone();
+            for handler in handlers {
+                handler(method.to_string(), params.clone()).await;
+            }
+        }
+        _ => tracing::debug!("Ignoring SSE message: {:?}", message),
+    }
+}
+
+/// A response's result, built straight from its raw text, or its error
+fn outcome(message: &RawMessage<'_>) -> std::result::Result<Value, TransportError> {
+    if let Some(error) = message.error {
+        let error: Value = serde_json::from_str(error.get()).unwrap_or(Value::Null);
+        return Err(TransportError::Protocol {
+            code: error.get("code").and_then(|c| c.as_i64()).unwrap_or(-32603) as i32,
+            message: error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("Unknown error")
+                .to_string(),
+        });
+    }
+    message
+        .result_value()
+        .map_err(|e| TransportError::parse(e.to_string()))
+}
+
+#[async_trait]
+impl Transport for SseTransport {
+    async fn connect(&mut self) -> std::result::Result<(), TransportError> {
+        let base = url::Url::parse(&self.url)
+            .map_err(|e| TransportError::connection_failed(format!("Invalid URL: {}", e)))?;
+        let response = self
+            .client
+            .get(base.clone())
+            .header("Accept", "text/event-stream")
+            .send()
+            .await
+            .map_err(|e| {
+                TransportError::connection_failed(format!("SSE connection failed: {}", e))
+            })?;
+        if !response.status().is_success() {
+            return Err(TransportError::connection_failed(format!(
+                "SSE endpoint returned {}",
+                response.status()
+            )));
+        }
+
+        let (endpoint_tx, endpoint_rx) = oneshot::channel();
+        let pending = Arc::clone(&self.pending);
+        let handlers = Arc::clone(&self.notification_handlers);
+        let mut body = response.bytes_stream();
+        self.reader = Some(tokio::spawn(async move {
+            let mut parser = SseParser::default();
+            let mut endpoint_tx = Some(endpoint_tx);
+            while let Some(chunk) = body.next().await {
+                let Ok(chunk) = chunk else { break };
+                for event in parser.feed(&String::from_utf8_lossy(&chunk)) {
+                    match event.event.as_str() {
+                        "endpoint" => {
+                            if let Some(tx) = endpoint_tx.take() {
+                                let _ = tx.send(event.data);
+                            }
+                        }
+                        "message" => match RawMessage::parse(&event.data) {
+                            Ok(message) => dispatch(message, &pending, &handlers).await,
+                            Err(e) => tracing::warn!("Invalid SSE message: {}", e),
+                        },
+                        _ => {}
+                    }
+                }
+            }
+            // Stream closed: fail outstanding requests rather than leave them waiting
+            pending.lock().await.clear();
+        }));
+
+        let endpoint = tokio::time::timeout(self.timeout, endpoint_rx)
+            .await
+            .map_err(|_| TransportError::Timeout("No endpoint event from SSE server".to_string()))?
+            .map_err(|_| {
+                TransportError::connection_failed("SSE stream closed before endpoint event")
+            })?;
+        let endpoint = base
+            .join(&endpoint)
+            .map_err(|e| TransportError::parse(format!("Invalid endpoint {}: {}", endpoint, e)))?;
+        if endpoint.origin() != base.origin() {
+            return Err(TransportError::connection_failed(format!(
+                "SSE endpoint {} is not on the server's origin",
+                endpoint
+            )));
+        }
+        self.endpoint = Some(endpoint);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> std::result::Result<(), TransportError> {
+        if let Some(reader) = self.reader.take() {
+            reader.abort();
+        }
+        self.endpoint = None;
+        self.pending.lock().await.clear();
+        Ok(())
+    }
+
+    async fn request(
+        &mut self,
+        method: &str,
+        params: Option<Value>,
+    ) -> std::result::Result<Value, TransportError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(json!(id).to_string(), tx);
+
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        });
+        if let Err(e) = self.post(&message).await {
+            self.pending.lock().await.remove(&json!(id).to_string());
+            return Err(e);
+        }
+
+        match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(outcome)) => outcome,
+            Ok(Err(_)) => Err(TransportError::ReceiveError(
+                "SSE stream closed before the response arrived".to_string(),
+            )),
+            Err(_) => {
+                self.pending.lock().await.remove(&json!(id).to_string());
+                Err(TransportError::RequestTimeout {
+                    message: format!("No response to {}", method),
+                    duration: Some(self.timeout),
+                })
+            }
+        }
+    }
+
+    async fn notify(
+        &mut self,
+        method: &str,
+        params: Option<Value>,
+    ) -> std::result::Result<(), TransportError> {
+        self.post(&json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params
+        }))
+        .await
+    }
+
+    async fn add_notification_handler(
+        &mut self,
+        handler: NotificationHandler,
+    ) -> std::result::Result<(), TransportError> {
+        self.notification_handlers.lock().await.push(handler);
+        Ok(())
+    }
+}
+
+type Sessions = Arc<std::sync::Mutex<HashMap<String, (mpsc::Sender<Value>, Arc<Session>)>>>;
+
+#[derive(Clone)]
+struct SseState {
+    registry: Arc<ToolRegistry>,
+    sessions: Sessions,
+}
+
+/// Removes a session when its event stream is dropped
+struct SessionGuard {
+    id: String,
+    sessions: Sessions,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(&self.id);
+        }
+        tracing::info!("SSE session {} closed", self.id);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageQuery {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+/// Server side of the SSE transport
+///
+/// `GET /sse` opens a session whose first event is `endpoint`, naming
+/// `/messages?sessionId=...`; requests POSTed there are answered with 202
+/// and their JSON-RPC responses are sent as `message` events on the stream,
+/// preceded by progress notifications for calls that asked for them. Tool
+/// list changes are announced on every open stream.
+pub fn router(registry: Arc<ToolRegistry>) -> Router {
+    Router::new()
+        .route(SSE_PATH, get(stream_handler))
+        .route(MESSAGES_PATH, post(message_handler))
+        .with_state(SseState {
+            registry,
+            sessions: Arc::new(std::sync::Mutex::new(HashMap::new())),
+        })
+}
+
+async fn stream_handler(
+    State(state): State<SseState>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = std::result::Result<Event, Infallible>>> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = mpsc::channel(SESSION_BUFFER);
+    // Ends by itself once the stream, and with it the receiver, is dropped
+    state.registry.forward_list_changes(tx.clone());
+    if let Ok(mut sessions) = state.sessions.lock() {
+        sessions.insert(
+            id.clone(),
+            (tx, Arc::new(state.registry.session_for(&headers))),
+        );
+    }
+    tracing::info!("SSE session {} opened", id);
+
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("{}?sessionId={}", MESSAGES_PATH, id));
+    let guard = SessionGuard {
+        id,
+        sessions: Arc::clone(&state.sessions),
+    };
+    let messages = futures::stream::unfold((rx, guard), |(mut rx, guard)| async move {
+        let message = rx.recv().await?;
+        let event = Event::default().event("message").data(message.to_string());
+        Some((Ok(event), (rx, guard)))
+    });
+    Sse::new(futures::stream::once(async { Ok(endpoint) }).chain(messages))
+        .keep_alive(KeepAlive::default())
+}
+
+async fn message_handler(
+    State(state): State<SseState>,
+    Query(query): Query<MessageQuery>,
+    Json(request): Json<JsonRpcRequest>,
+) -> impl IntoResponse {
+    let Some((sender, session)) = state
+        .sessions
+        .lock()
+        .ok()
+        .and_then(|sessions| sessions.get(&query.session_id).cloned())
+    else {
+        return (StatusCode::NOT_FOUND, "Unknown session");
+    };
+    // Notifications get no response
+    if request.id.is_none() {
+        return (StatusCode::ACCEPTED, "Accepted");
+    }
+    tokio::spawn(async move {
+        let response = state
+            .registry
+            .handle_in_session(request, &session, Some(&sender))
+            .await;
+        if sender.send(response.into()).await.is_err() {
+            tracing::warn!("SSE session closed before its response was sent");
+        }
+    });
+    (StatusCode::ACCEPTED, "Accepted")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{call_result, ToolDefinition};
+
+    #[test]
+    fn parses_events_split_across_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser
+            .feed(": keep-alive\n\nevent: endpoint\ndata: /messages?sess")
+            .is_empty());
+        assert_eq!(
+            parser.feed("ionId=1\r\n\r\ndata: {\"a\":\ndata: 1}\n\n"),
+            vec![
+                SseEvent {
+                    event: "endpoint".to_string(),
+                    data: "/messages?sessionId=1".to_string()
+                },
+                SseEvent {
+                    event: "message".to_string(),
+                    data: "{\"a\":\n1}".to_string()
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn client_round_trips_through_the_server_router() {
+        let registry = ToolRegistry::new();
+        registry
+            .register(ToolDefinition::new("ping", "Ping"), |_| async {
+                Ok(call_result("pong", json!({})))
+            })
+            .unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router(Arc::new(registry)))
+                .await
+                .unwrap();
+        });
+
+        let mut transport = SseTransport::new(format!("http://{}{}", addr, SSE_PATH))
+            .unwrap()
+            .with_timeout(Duration::from_secs(5));
+        transport.connect().await.unwrap();
+        assert_eq!(transport.endpoint().unwrap().path(), MESSAGES_PATH);
+
+        let result = transport
+            .request("tools/call", Some(json!({"name": "ping"})))
+            .await
+            .unwrap();
+        assert_eq!(result["content"][0]["text"], "pong");
+        let error = transport.request("nope", None).await.unwrap_err();
+        assert!(matches!(
+            error,
+            TransportError::Protocol { code: -32601, .. }
+        ));
+        transport
+            .notify("notifications/initialized", None)
+            .await
+            .unwrap();
+        transport.disconnect().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn progress_notifications_precede_the_result() {
+        let registry = ToolRegistry::new();
+        registry
+            .register_streaming(
+                ToolDefinition::new("tail", "Tail"),
+                |_, stream| async move {
+                    for line in ["a", "b", "c"] {
+                        stream.text(format!("{}\n", line));
+                    }
+                    Ok(call_result("a\nb\nc\n", json!({})))
+                },
+            )
+            .unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router(Arc::new(registry)))
+                .await
+                .unwrap();
+        });
+
+        let mut transport = SseTransport::new(format!("http://{}{}", addr, SSE_PATH))
+            .unwrap()
+            .with_timeout(Duration::from_secs(5));
+        transport.connect().await.unwrap();
+        let lifecycle = crate::lifecycle::LifecycleManager::new(Box::new(transport));
+        let mut stream = lifecycle
+            .call_tool_streaming("tail", json!({}))
+            .await
+            .unwrap();
+        let mut text = String::new();
+        while let Some(chunk) = stream.next_chunk().await {
+            text.push_str(&chunk.text());
+        }
+        assert_eq!(text, "a\nb\nc\n");
+        assert_eq!(stream.finish().await.unwrap().content[0].content, text);
+
+        // Without a progress token the same tool just returns its result
+        let result = lifecycle
+            .call_method("tools/call", Some(json!({"name": "tail"})))
+            .await
+            .unwrap();
+        assert_eq!(result["content"][0]["text"], "a\nb\nc\n");
+    }
+}
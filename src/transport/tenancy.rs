@@ -0,0 +1,577 @@
+/// Several isolated tenants served from one process
+///
+/// Each tenant gets its own tool registry, built from the tenant's own config
+/// sections and only those server sections it lists under `inherit`, so
+/// credentials in the server's or another tenant's config never reach its
+/// tools. Stores such as preferences, favorites, snapshots, the asset
+/// inventory, health data, the ledger, expenses, alerts and query exports live
+/// under the tenant's storage directory.
+/// Requests pick their tenant with an API key, sent as
+/// `Authorization: Bearer <key>` or `X-API-Key`, and are then served by that
+/// tenant's JSON-RPC, SSE and WebSocket endpoints, subject to the tenant's
+/// tool policy and rate limit.
+///
+/// Credentials read from the process environment are still shared, so keep
+/// per-tenant secrets in the tenant config.
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::tools::ToolRegistry;
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use governor::clock::DefaultClock;
+use governor::state::{InMemoryState, NotKeyed};
+use governor::{Quota, RateLimiter};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tower::Service;
+
+/// One tenant, under `tenants.<id>` in the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TenantConfig {
+    /// SHA-256 hex digests of the tenant's API keys
+    pub api_keys: Vec<String>,
+    /// Where the tenant's stores are kept (default: `tenants/<id>`)
+    #[serde(default)]
+    pub storage_dir: Option<PathBuf>,
+    /// Tools the tenant may use, by name or `prefix*`; all when unset
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+    /// Requests allowed per minute; unlimited when unset
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Server config sections the tenant shares, such as `monitoring`;
+    /// none by default
+    #[serde(default)]
+    pub inherit: Vec<String>,
+    /// Config sections of this tenant, replacing inherited ones
+    #[serde(default)]
+    pub config: Box<Config>,
+}
+
+impl TenantConfig {
+    /// Check the tenant can be served
+    pub fn validate(&self, id: &str) -> Result<()> {
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(Error::validation_with_field(
+                format!("Tenant id {:?} must be letters, digits, - or _", id),
+                "tenants",
+            ));
+        }
+        if self.api_keys.is_empty() {
+            return Err(Error::validation_with_field(
+                format!("Tenant {} has no API keys", id),
+                "api_keys",
+            ));
+        }
+        if let Some(key) = self
+            .api_keys
+            .iter()
+            .find(|k| k.len() != 64 || !k.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Err(Error::validation_with_field(
+                format!(
+                    "Tenant {} API key {:.8}... is not a SHA-256 hex digest",
+                    id, key
+                ),
+                "api_keys",
+            ));
+        }
+        if self.requests_per_minute == Some(0) {
+            return Err(Error::validation_with_field(
+                format!("Tenant {} requests_per_minute must be at least 1", id),
+                "requests_per_minute",
+            ));
+        }
+        if self.config.tenants.is_some() {
+            return Err(Error::validation_with_field(
+                format!("Tenant {} config cannot declare tenants", id),
+                "config",
+            ));
+        }
+        Config::default().sections(&self.inherit)?;
+        self.config.validate()
+    }
+
+    /// Module config for the tenant: the inherited server sections, with the
+    /// tenant's own laid over them and stores moved into the tenant's storage
+    /// directory
+    pub fn module_config(&self, id: &str, server: &Config) -> Config {
+        let dir = self
+            .storage_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("tenants").join(id));
+        // Validation has already refused unknown section names
+        let mut config = server.sections(&self.inherit).unwrap_or_default();
+        config.merge((*self.config).clone());
+        // Stores the tenant does not place itself must not be shared
+        let own = &self.config;
+        if own
+            .preferences
+            .as_ref()
+            .and_then(|p| p.path.as_ref())
+            .is_none()
+        {
+            config.preferences.get_or_insert_with(Default::default).path =
+                Some(dir.join("preferences.json"));
+        }
+        if own
+            .favorites
+            .as_ref()
+            .and_then(|f| f.path.as_ref())
+            .is_none()
+        {
+            config.favorites.get_or_insert_with(Default::default).path =
+                Some(dir.join("favorites.json"));
+        }
+        if own.snapshot.is_none() {
+            let snapshot = config.snapshot.get_or_insert_with(Default::default);
+            snapshot.dir = dir.join("snapshots");
+            // Another tenant's state files are not this tenant's to archive
+            snapshot.files.clear();
+        }
+        if own
+            .infrastructure
+            .as_ref()
+            .and_then(|i| i.assets.as_ref())
+            .and_then(|a| a.path.as_ref())
+            .is_none()
+        {
+            config
+                .infrastructure
+                .get_or_insert_with(Default::default)
+                .assets
+                .get_or_insert_with(Default::default)
+                .path = Some(dir.join("assets.json"));
+        }
+        if own
+            .analytics
+            .as_ref()
+            .and_then(|a| a.health.as_ref())
+            .and_then(|h| h.path.as_ref())
+            .is_none()
+        {
+            config
+                .analytics
+                .get_or_insert_with(Default::default)
+                .health
+                .get_or_insert_with(Default::default)
+                .path = Some(dir.join("health.json"));
+        }
+        let finance = own.finance.as_ref();
+        if finance
+            .and_then(|f| f.ledger.as_ref())
+            .and_then(|l| l.path.as_ref())
+            .is_none()
+        {
+            config
+                .finance
+                .get_or_insert_with(Default::default)
+                .ledger
+                .get_or_insert_with(Default::default)
+                .path = Some(dir.join("ledger.json"));
+        }
+        if finance
+            .and_then(|f| f.expenses.as_ref())
+            .and_then(|e| e.path.as_ref())
+            .is_none()
+        {
+            config
+                .finance
+                .get_or_insert_with(Default::default)
+                .expenses
+                .get_or_insert_with(Default::default)
+                .path = Some(dir.join("expenses.json"));
+        }
+        if own
+            .monitoring
+            .as_ref()
+            .and_then(|m| m.alert_store.as_ref())
+            .is_none()
+        {
+            config
+                .monitoring
+                .get_or_insert_with(Default::default)
+                .alert_store = Some(dir.join("alerts.json"));
+        }
+        if let Some(database) = config.database.as_mut() {
+            let own_dir = own.database.as_ref().and_then(|d| d.export_dir.as_ref());
+            if own_dir.is_none() {
+                database.export_dir = Some(dir.join("exports"));
+            }
+        }
+        config
+    }
+
+    /// Whether the tenant's policy lets it use `tool`
+    pub fn allows(&self, tool: &str) -> bool {
+        self.tools.as_ref().is_none_or(|patterns| {
+            patterns
+                .iter()
+                .any(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => tool.starts_with(prefix),
+                    None => tool == pattern,
+                })
+        })
+    }
+
+    /// Remove the tools the tenant's policy does not allow, returning how many
+    pub fn restrict(&self, registry: &ToolRegistry) -> usize {
+        registry
+            .definitions()
+            .iter()
+            .filter(|definition| !self.allows(&definition.name))
+            .filter(|definition| registry.unregister(&definition.name).is_some())
+            .count()
+    }
+}
+
+/// SHA-256 hex digest of an API key, as listed in `api_keys`
+pub fn hash_api_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+struct Tenant {
+    id: String,
+    key_hashes: Vec<String>,
+    limiter: Option<RateLimiter<NotKeyed, InMemoryState, DefaultClock>>,
+    router: Router,
+}
+
+/// Tenants and the endpoints serving each of them
+#[derive(Default)]
+pub struct Tenants {
+    tenants: Vec<Tenant>,
+}
+
+impl Tenants {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve a tenant with `router`, usually its registry's transport routes
+    pub fn add(&mut self, id: &str, config: &TenantConfig, router: Router) -> Result<()> {
+        config.validate(id)?;
+        if self.tenants.iter().any(|t| t.id == id) {
+            return Err(Error::validation_with_field(
+                format!("Tenant {} is declared twice", id),
+                "tenants",
+            ));
+        }
+        let key_hashes: Vec<String> = config
+            .api_keys
+            .iter()
+            .map(|k| k.to_ascii_lowercase())
+            .collect();
+        if let Some(other) = self
+            .tenants
+            .iter()
+            .find(|t| t.key_hashes.iter().any(|k| key_hashes.contains(k)))
+        {
+            return Err(Error::validation_with_field(
+                format!("Tenants {} and {} share an API key", other.id, id),
+                "api_keys",
+            ));
+        }
+        self.tenants.push(Tenant {
+            id: id.to_string(),
+            key_hashes,
+            limiter: config
+                .requests_per_minute
+                .and_then(NonZeroU32::new)
+                .map(|n| RateLimiter::direct(Quota::per_minute(n))),
+            router,
+        });
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tenants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tenants.is_empty()
+    }
+
+    /// The tenant an API key belongs to
+    fn find(&self, key: &str) -> Option<&Tenant> {
+        let hash = hash_api_key(key);
+        // Compare against every key so timing does not reveal which matched
+        let mut found = None;
+        for tenant in &self.tenants {
+            for k in &tenant.key_hashes {
+                if constant_time_eq::constant_time_eq(k.as_bytes(), hash.as_bytes()) {
+                    found = Some(tenant);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// API key from `Authorization: Bearer` or `X-API-Key`
+pub(crate) fn api_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+fn rejection(status: StatusCode, code: i32, message: &str) -> Response {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": {"code": code, "message": message}
+    });
+    (status, Json(body)).into_response()
+}
+
+/// Routes every request to the endpoints of the tenant its API key selects
+pub fn router(tenants: Arc<Tenants>) -> Router {
+    Router::new()
+        .route("/", post(dispatch))
+        .fallback(dispatch)
+        .with_state(tenants)
+}
+
+async fn dispatch(State(tenants): State<Arc<Tenants>>, request: Request<Body>) -> Response {
+    let Some(tenant) = api_key(request.headers()).and_then(|key| tenants.find(key)) else {
+        return rejection(
+            StatusCode::UNAUTHORIZED,
+            -32001,
+            "A valid API key is required",
+        );
+    };
+    if let Some(limiter) = &tenant.limiter {
+        if limiter.check().is_err() {
+            let mut response =
+                rejection(StatusCode::TOO_MANY_REQUESTS, -32002, "Rate limit exceeded");
+            response
+                .headers_mut()
+                .insert("retry-after", axum::http::HeaderValue::from_static("60"));
+            return response;
+        }
+    }
+    tracing::debug!(
+        "Serving {} {} for tenant {}",
+        request.method(),
+        request.uri(),
+        tenant.id
+    );
+    let mut router = tenant.router.clone();
+    match router.call(request).await {
+        Ok(response) => response,
+        Err(infallible) => match infallible {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::preferences::PreferencesConfig;
+    use crate::tools::{call_result, ToolDefinition};
+    use serde_json::{json, Value};
+
+    fn registry(tools: &[&str]) -> Arc<ToolRegistry> {
+        let registry = ToolRegistry::new();
+        for name in tools {
+            let definition = ToolDefinition::from_json_schema(
+                name,
+                "Test tool",
+                "core",
+                json!({"type": "object"}),
+                None,
+            );
+            let name = name.to_string();
+            registry
+                .register(definition, move |_| {
+                    let name = name.clone();
+                    async move { Ok(call_result(name, json!({}))) }
+                })
+                .unwrap();
+        }
+        Arc::new(registry)
+    }
+
+    fn tenant(key: &str, tools: Option<Vec<String>>, limit: Option<u32>) -> TenantConfig {
+        TenantConfig {
+            api_keys: vec![hash_api_key(key)],
+            tools,
+            requests_per_minute: limit,
+            ..TenantConfig::default()
+        }
+    }
+
+    async fn post(app: &Router, key: Option<&str>, body: Value) -> (StatusCode, Value) {
+        let mut request = Request::post("/").header("content-type", "application/json");
+        if let Some(key) = key {
+            request = request.header("authorization", format!("Bearer {}", key));
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+        let response = app.clone().call(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn routes_each_key_to_its_own_tenant() {
+        let alpha = tenant("alpha-key", Some(vec!["list_*".to_string()]), None);
+        let alpha_registry = registry(&["list_pods", "delete_pod"]);
+        assert_eq!(alpha.restrict(&alpha_registry), 1);
+        let beta = tenant("beta-key", None, Some(1));
+
+        let mut tenants = Tenants::new();
+        tenants
+            .add("alpha", &alpha, alpha_registry.router())
+            .unwrap();
+        tenants
+            .add(
+                "beta",
+                &beta,
+                registry(&["list_pods", "delete_pod"]).router(),
+            )
+            .unwrap();
+        assert!(tenants
+            .add("gamma", &tenant("alpha-key", None, None), Router::new())
+            .is_err());
+        let app = router(Arc::new(tenants));
+
+        let list = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"});
+        let (status, _) = post(&app, None, list.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = post(&app, Some("wrong-key"), list.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = post(&app, Some("alpha-key"), list.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["result"]["tools"].as_array().unwrap().len(), 1);
+
+        let (_, body) = post(&app, Some("beta-key"), list.clone()).await;
+        assert_eq!(body["result"]["tools"].as_array().unwrap().len(), 2);
+        let (status, _) = post(&app, Some("beta-key"), list).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn keeps_tenant_stores_and_credentials_apart() {
+        let server = Config {
+            preferences: Some(PreferencesConfig {
+                path: Some(PathBuf::from("state/preferences.json")),
+                ..PreferencesConfig::default()
+            }),
+            research: Some(crate::config::ResearchConfig::default()),
+            ai: Some(crate::config::AiConfig::default()),
+            monitoring: Some(crate::config::MonitoringConfig {
+                alert_store: Some(PathBuf::from("state/alerts.json")),
+                ..Default::default()
+            }),
+            ..Config::default()
+        };
+        let mut team = tenant("team-key", None, None);
+        team.config.database = Some(crate::config::DatabaseConfig {
+            connections: [("postgresql".to_string(), "postgres://team".to_string())].into(),
+            ..Default::default()
+        });
+        team.validate("team").unwrap();
+        assert!(team.validate("../team").is_err());
+
+        let config = team.module_config("team", &server);
+        assert_eq!(
+            config.preferences.unwrap().path,
+            Some(PathBuf::from("tenants/team/preferences.json"))
+        );
+        assert_eq!(
+            config.snapshot.unwrap().dir,
+            PathBuf::from("tenants/team/snapshots")
+        );
+        assert_eq!(
+            config.infrastructure.unwrap().assets.unwrap().path,
+            Some(PathBuf::from("tenants/team/assets.json"))
+        );
+        assert_eq!(
+            config.analytics.unwrap().health.unwrap().path,
+            Some(PathBuf::from("tenants/team/health.json"))
+        );
+        assert_eq!(
+            config.monitoring.unwrap().alert_store,
+            Some(PathBuf::from("tenants/team/alerts.json"))
+        );
+        let finance = config.finance.unwrap();
+        assert_eq!(
+            finance.expenses.unwrap().path,
+            Some(PathBuf::from("tenants/team/expenses.json"))
+        );
+        let database = config.database.unwrap();
+        assert_eq!(database.connections["postgresql"], "postgres://team");
+        assert_eq!(
+            database.export_dir,
+            Some(PathBuf::from("tenants/team/exports"))
+        );
+        assert!(server.database.is_none());
+        // Server sections are only shared when the tenant asks for them
+        assert!(config.ai.is_none() && config.research.is_none());
+
+        team.inherit = vec!["ai".to_string(), "monitoring".to_string()];
+        team.validate("team").unwrap();
+        let config = team.module_config("team", &server);
+        assert!(config.ai.is_some() && config.research.is_none());
+        // Inherited sections still keep their stores apart
+        assert_eq!(
+            config.monitoring.unwrap().alert_store,
+            Some(PathBuf::from("tenants/team/alerts.json"))
+        );
+        team.inherit = vec!["tenants".to_string()];
+        assert!(team.validate("team").is_err());
+    }
+
+    #[tokio::test]
+    async fn keeps_each_tenants_ledger_to_itself() {
+        use crate::finance::ledger::{Ledger, LedgerFormat};
+
+        let storage = tempfile::tempdir().unwrap();
+        let server = Config::default();
+        let open = |id: &str| {
+            let mut tenant = tenant(&format!("{}-key", id), None, None);
+            tenant.storage_dir = Some(storage.path().join(id));
+            let config = tenant.module_config(id, &server);
+            Ledger::open(config.finance.unwrap().ledger.unwrap())
+        };
+        let march = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let alpha = open("alpha").await.unwrap();
+        let beta = open("beta").await.unwrap();
+        let export = "Date,Description,Amount\n2024-03-01,WHOLE FOODS,-84.10\n";
+        alpha
+            .import_text(LedgerFormat::Csv, export, "Checking", None, None)
+            .await
+            .unwrap();
+        assert_eq!(beta.budget_report(march).await.uncategorized_count, 0);
+        assert!(storage.path().join("alpha/ledger.json").exists());
+        assert!(!storage.path().join("beta/ledger.json").exists());
+
+        // Reopened, each tenant sees only its own transactions
+        let alpha = open("alpha").await.unwrap();
+        let beta = open("beta").await.unwrap();
+        assert_eq!(alpha.budget_report(march).await.uncategorized_count, 1);
+        assert_eq!(beta.budget_report(march).await.uncategorized_count, 0);
+    }
+}
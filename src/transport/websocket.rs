@@ -1,17 +1,33 @@
 use crate::error::{Error, Result};
 use crate::security::SanitizationOptions;
+use crate::tools::registry::{JsonRpcRequest, JsonRpcResponse, Session, ToolRegistry};
+use crate::transport::framing::{self, Frame, Framing, FramingConfig};
+use crate::transport::jsonrpc::RawMessage;
 use crate::transport::{NotificationHandler, Transport, TransportError};
 use async_trait::async_trait;
+use axum::extract::ws::{Message as WsMessage, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
 use futures::{SinkExt, StreamExt};
 use governor::{
     clock::DefaultClock,
     state::{InMemoryState, NotKeyed},
     Quota, RateLimiter,
 };
+use serde_json::Value;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 use tokio_tungstenite::tungstenite::Message;
 
+/// Path of the server's WebSocket endpoint
+pub const WS_PATH: &str = "/ws";
+
+/// Outgoing messages buffered per connection
+const CONNECTION_BUFFER: usize = 64;
+
 /// WebSocket transport implementation with rate limiting
 pub struct WebSocketTransport {
     url: String,
@@ -25,6 +41,10 @@ pub struct WebSocketTransport {
     notifications: Arc<Mutex<Vec<String>>>,
     notification_handlers: Vec<NotificationHandler>,
     rate_limiter: RateLimiter<NotKeyed, InMemoryState, DefaultClock>,
+    /// Framing offered at initialize, if any
+    framing_offer: Option<FramingConfig>,
+    /// Framing the server accepted
+    framing: Framing,
 }
 
 impl WebSocketTransport {
@@ -39,9 +59,22 @@ impl WebSocketTransport {
             notification_handlers: Vec::with_capacity(8),
             rate_limiter: RateLimiter::direct(quota),
             auth_token: None,
+            framing_offer: None,
+            framing: Framing::default(),
         })
     }
 
+    /// Offer binary framing and compression when initializing
+    pub fn with_framing(mut self, config: FramingConfig) -> Self {
+        self.framing_offer = Some(config);
+        self
+    }
+
+    /// Framing agreed with the server at initialize
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
     /// Set authentication token for secure WebSocket connections
     pub fn with_auth_token(self, auth_token: &str) -> Result<Self> {
         let _validation_opts = SanitizationOptions {
@@ -145,43 +178,79 @@ impl Transport for WebSocketTransport {
         params: Option<serde_json::Value>,
     ) -> std::result::Result<serde_json::Value, TransportError> {
         let request_id = uuid::Uuid::new_v4().to_string();
+        let initialize = method == "initialize";
+        let mut params = params.unwrap_or(serde_json::Value::Null);
+        if let (true, Some(offer)) = (initialize, &self.framing_offer) {
+            Framing::add_offer(offer, &mut params);
+        }
 
         let message = serde_json::json!({
             "jsonrpc": "2.0",
             "id": request_id,
             "method": method,
-            "params": params.unwrap_or(serde_json::Value::Null)
+            "params": params
         });
 
         let request_str = serde_json::to_string(&message)
             .map_err(|e| TransportError::send(format!("Failed to serialize request: {}", e)))?;
+        let frame = match self
+            .framing
+            .encode(request_str)
+            .map_err(|e| TransportError::send(e.to_string()))?
+        {
+            Frame::Text(text) => Message::Text(text),
+            Frame::Binary(data) => Message::Binary(data),
+        };
 
         if let Some(ref mut stream) = self.websocket {
-            stream.send(Message::Text(request_str)).await.map_err(|e| {
+            stream.send(frame).await.map_err(|e| {
                 TransportError::ConnectionError(format!("Failed to send message: {}", e))
             })?;
 
-            // Wait for response - simplified for now
+            // Wait for the response, handing notifications sent meanwhile to the handlers
             while let Some(msg) = stream.next().await {
+                let msg = match msg {
+                    Ok(Message::Binary(data)) => framing::decode_frame(&data)
+                        .and_then(|bytes| {
+                            String::from_utf8(bytes)
+                                .map_err(|e| Error::parsing(format!("Invalid UTF-8 frame: {}", e)))
+                        })
+                        .map(Message::Text)
+                        .map_err(|e| TransportError::parse(e.to_string())),
+                    other => other.map_err(|e| {
+                        TransportError::ConnectionError(format!("WebSocket error: {}", e))
+                    }),
+                };
                 match msg {
                     Ok(Message::Text(text)) => {
-                        if let Ok(response) = serde_json::from_str::<serde_json::Value>(&text) {
-                            if response.get("id").and_then(|id| id.as_str()) == Some(&request_id) {
-                                if let Some(result) = response.get("result") {
-                                    return Ok(result.clone());
-                                } else if let Some(error) = response.get("error") {
-                                    return Err(TransportError::RequestFailed(error.to_string()));
+                        // Only the envelope is parsed until the message is known to be ours
+                        let Ok(response) = RawMessage::parse(&text) else {
+                            continue;
+                        };
+                        if response.id_str() == Some(request_id.as_str()) {
+                            if response.result.is_some() {
+                                let result = response
+                                    .result_value()
+                                    .map_err(|e| TransportError::parse(e.to_string()))?;
+                                if initialize && self.framing_offer.is_some() {
+                                    self.framing = Framing::accepted(&result);
                                 }
+                                return Ok(result);
+                            } else if let Some(error) = response.error {
+                                return Err(TransportError::RequestFailed(error.get().to_string()));
                             }
+                        } else if let (true, Some(method)) =
+                            (response.is_notification(), &response.method)
+                        {
+                            let params = response.params_value().unwrap_or(Value::Null);
+                            for handler in &self.notification_handlers {
+                                handler(method.to_string(), params.clone()).await;
+                            }
+                            self.notifications.lock().await.push(text);
                         }
                     }
                     Ok(_) => continue,
-                    Err(e) => {
-                        return Err(TransportError::ConnectionError(format!(
-                            "WebSocket error: {}",
-                            e
-                        )))
-                    }
+                    Err(e) => return Err(e),
                 }
             }
         }
@@ -226,3 +295,206 @@ impl Transport for WebSocketTransport {
         Ok(())
     }
 }
+
+/// Server side of the WebSocket transport
+///
+/// Each text frame is one JSON-RPC message. Responses, the progress
+/// notifications of calls that asked for them, and tool list changes go back
+/// on the same socket.
+pub fn router(registry: Arc<ToolRegistry>) -> Router {
+    router_with_framing(registry, FramingConfig::default())
+}
+
+/// WebSocket endpoint with the framing clients may negotiate at initialize
+///
+/// Clients that offer no framing get plain text frames.
+pub fn router_with_framing(registry: Arc<ToolRegistry>, framing: FramingConfig) -> Router {
+    Router::new()
+        .route(WS_PATH, get(upgrade_handler))
+        .with_state((registry, Arc::new(framing)))
+}
+
+async fn upgrade_handler(
+    State((registry, framing)): State<(Arc<ToolRegistry>, Arc<FramingConfig>)>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let session = Arc::new(registry.session_for(&headers));
+    upgrade.on_upgrade(move |socket| serve_socket(registry, framing, session, socket))
+}
+
+async fn serve_socket(
+    registry: Arc<ToolRegistry>,
+    config: Arc<FramingConfig>,
+    session: Arc<Session>,
+    socket: WebSocket,
+) {
+    let (mut sink, mut incoming) = socket.split();
+    let (sender, mut outgoing) = mpsc::channel::<Value>(CONNECTION_BUFFER);
+    let framing = Arc::new(std::sync::RwLock::new(Framing::default()));
+    let writer_framing = Arc::clone(&framing);
+    tokio::spawn(async move {
+        while let Some(message) = outgoing.recv().await {
+            let current = writer_framing.read().map(|f| *f).unwrap_or_default();
+            let frame = match current.encode(message.to_string()) {
+                Ok(Frame::Text(text)) => WsMessage::Text(text),
+                Ok(Frame::Binary(data)) => WsMessage::Binary(data),
+                Err(e) => {
+                    tracing::error!("Failed to frame WebSocket message: {}", e);
+                    continue;
+                }
+            };
+            if sink.send(frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let list_changes = registry.forward_list_changes(sender.clone());
+    while let Some(Ok(frame)) = incoming.next().await {
+        let text = match frame {
+            WsMessage::Text(text) => text,
+            WsMessage::Binary(data) => match framing::decode_frame(&data).and_then(|bytes| {
+                String::from_utf8(bytes)
+                    .map_err(|e| Error::parsing(format!("Invalid UTF-8 frame: {}", e)))
+            }) {
+                Ok(text) => text,
+                Err(e) => {
+                    let response =
+                        JsonRpcResponse::error(None, -32700, format!("Parse error: {}", e));
+                    let _ = sender.send(response.into()).await;
+                    continue;
+                }
+            },
+            WsMessage::Close(_) => break,
+            _ => continue,
+        };
+        let request: JsonRpcRequest = match serde_json::from_str(&text) {
+            Ok(request) => request,
+            Err(e) => {
+                let response = JsonRpcResponse::error(None, -32700, format!("Parse error: {}", e));
+                let _ = sender.send(response.into()).await;
+                continue;
+            }
+        };
+        // Notifications get no response
+        if request.id.is_none() {
+            continue;
+        }
+        let negotiated = (request.method == "initialize")
+            .then(|| Framing::negotiate(&config, request.params.as_ref().unwrap_or(&Value::Null)));
+        let registry = Arc::clone(&registry);
+        let session = Arc::clone(&session);
+        let sender = sender.clone();
+        let framing = Arc::clone(&framing);
+        tokio::spawn(async move {
+            let response = registry
+                .handle_in_session(request, &session, Some(&sender))
+                .await;
+            let mut message = Value::from(response);
+            if let (Some(negotiated), Some(result)) = (negotiated, message.get_mut("result")) {
+                negotiated.add_capability(result);
+            }
+            if sender.send(message).await.is_err() {
+                tracing::warn!("WebSocket closed before its response was sent");
+            }
+            // Later messages use the agreed framing; the answer itself was queued first
+            if let (Some(negotiated), Ok(mut current)) = (negotiated, framing.write()) {
+                *current = negotiated;
+            }
+        });
+    }
+    list_changes.abort();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifecycle::LifecycleManager;
+    use crate::tools::{call_result, ToolDefinition};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn streams_tool_output_over_the_socket() {
+        let registry = ToolRegistry::new();
+        registry
+            .register_streaming(
+                ToolDefinition::new("count", "Count"),
+                |_, stream| async move {
+                    stream.text("one\n");
+                    stream.progress(50.0, None);
+                    stream.text("two\n");
+                    Ok(call_result("one\ntwo\n", json!({"count": 2})))
+                },
+            )
+            .unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router(Arc::new(registry)))
+                .await
+                .unwrap();
+        });
+
+        let mut transport = WebSocketTransport::new(format!("ws://{}{}", addr, WS_PATH)).unwrap();
+        transport.connect().await.unwrap();
+        let lifecycle = LifecycleManager::new(Box::new(transport));
+        let mut stream = lifecycle
+            .call_tool_streaming("count", json!({}))
+            .await
+            .unwrap();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = stream.next_chunk().await {
+            chunks.push(chunk);
+        }
+        assert_eq!(
+            chunks.iter().map(|c| c.sequence).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(chunks[0].text(), "one\n");
+        assert_eq!(chunks[1].progress.as_ref().unwrap().percentage, 50.0);
+        let result = stream.finish().await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.content[0].content, "one\ntwo\n");
+    }
+
+    #[tokio::test]
+    async fn negotiates_compressed_binary_frames() {
+        let registry = ToolRegistry::new();
+        registry
+            .register(ToolDefinition::new("logs", "Logs"), |_| async move {
+                Ok(call_result("log line\n".repeat(5000), json!({})))
+            })
+            .unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router(Arc::new(registry)))
+                .await
+                .unwrap();
+        });
+
+        let mut transport = WebSocketTransport::new(format!("ws://{}{}", addr, WS_PATH))
+            .unwrap()
+            .with_framing(FramingConfig::default());
+        transport.connect().await.unwrap();
+        let result = transport
+            .request("initialize", Some(json!({"clientInfo": {"name": "test"}})))
+            .await
+            .unwrap();
+        assert_eq!(
+            result["capabilities"]["experimental"]["framing"]["compression"],
+            "zstd"
+        );
+        assert!(transport.framing().binary);
+
+        let result = transport
+            .request("tools/call", Some(json!({"name": "logs", "arguments": {}})))
+            .await
+            .unwrap();
+        assert_eq!(
+            result["content"][0]["text"].as_str().unwrap().len(),
+            "log line\n".len() * 5000
+        );
+    }
+}
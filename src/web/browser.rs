@@ -0,0 +1,240 @@
+/// Browser automation over the W3C WebDriver protocol
+///
+/// Talks to any WebDriver endpoint (chromedriver, geckodriver, Selenium Grid)
+/// over HTTP, so no browser bindings are linked into the binary.
+use crate::error::{Error, Result};
+use reqwest::{Client, Method};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// W3C element reference key
+const ELEMENT_KEY: &str = "element-6066-11e4-a52e-4f735466cecf";
+
+/// WebDriver endpoint configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebDriverConfig {
+    /// WebDriver server URL
+    pub url: String,
+    /// Browser name (chrome, firefox, MicrosoftEdge)
+    pub browser: String,
+    /// Run without a visible window
+    pub headless: bool,
+}
+
+impl Default for WebDriverConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:4444".to_string(),
+            browser: "chrome".to_string(),
+            headless: true,
+        }
+    }
+}
+
+/// Client used to open browser sessions
+#[derive(Debug, Clone)]
+pub struct WebDriverClient {
+    client: Client,
+    config: WebDriverConfig,
+}
+
+impl WebDriverClient {
+    /// Create a new WebDriver client
+    pub fn new(config: WebDriverConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create WebDriver client: {}", e)))?;
+        Ok(Self { client, config })
+    }
+
+    fn capabilities(&self) -> Value {
+        let mut always_match = json!({ "browserName": self.config.browser });
+        if self.config.headless {
+            match self.config.browser.as_str() {
+                "firefox" => {
+                    always_match["moz:firefoxOptions"] = json!({ "args": ["-headless"] });
+                }
+                "MicrosoftEdge" => {
+                    always_match["ms:edgeOptions"] = json!({ "args": ["--headless=new"] });
+                }
+                _ => {
+                    always_match["goog:chromeOptions"] =
+                        json!({ "args": ["--headless=new", "--no-sandbox", "--disable-gpu"] });
+                }
+            }
+        }
+        json!({ "capabilities": { "alwaysMatch": always_match } })
+    }
+
+    /// Start a new browser session
+    pub async fn new_session(&self) -> Result<BrowserSession> {
+        let base = self.config.url.trim_end_matches('/').to_string();
+        let value = send(
+            &self.client,
+            Method::POST,
+            &format!("{}/session", base),
+            Some(self.capabilities()),
+        )
+        .await?;
+
+        let session_id = value
+            .get("sessionId")
+            .and_then(|s| s.as_str())
+            .ok_or_else(|| Error::parsing("WebDriver new session response missing sessionId"))?;
+
+        Ok(BrowserSession {
+            client: self.client.clone(),
+            session_url: format!("{}/session/{}", base, session_id),
+        })
+    }
+}
+
+/// An open browser session
+#[derive(Debug)]
+pub struct BrowserSession {
+    client: Client,
+    session_url: String,
+}
+
+impl BrowserSession {
+    async fn command(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        let url = format!("{}{}", self.session_url, path);
+        send(&self.client, method, &url, body).await
+    }
+
+    /// Set how long element lookups wait before failing
+    pub async fn set_implicit_wait(&self, timeout: Duration) -> Result<()> {
+        self.command(
+            Method::POST,
+            "/timeouts",
+            Some(json!({ "implicit": timeout.as_millis() as u64 })),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Navigate to a URL
+    pub async fn goto(&self, url: &str) -> Result<()> {
+        self.command(Method::POST, "/url", Some(json!({ "url": url })))
+            .await
+            .map(|_| ())
+    }
+
+    /// Current page URL
+    pub async fn current_url(&self) -> Result<String> {
+        let value = self.command(Method::GET, "/url", None).await?;
+        Ok(value.as_str().unwrap_or_default().to_string())
+    }
+
+    /// Find an element by CSS selector, returning its WebDriver reference
+    pub async fn find(&self, selector: &str) -> Result<String> {
+        let value = self
+            .command(
+                Method::POST,
+                "/element",
+                Some(json!({ "using": "css selector", "value": selector })),
+            )
+            .await?;
+        value
+            .get(ELEMENT_KEY)
+            .and_then(|e| e.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| Error::not_found_with_resource("Element not found", "element", selector))
+    }
+
+    /// Click the element matching a selector
+    pub async fn click(&self, selector: &str) -> Result<()> {
+        let element = self.find(selector).await?;
+        self.command(
+            Method::POST,
+            &format!("/element/{}/click", element),
+            Some(json!({})),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Clear and type into the element matching a selector
+    pub async fn fill(&self, selector: &str, text: &str) -> Result<()> {
+        let element = self.find(selector).await?;
+        self.command(
+            Method::POST,
+            &format!("/element/{}/clear", element),
+            Some(json!({})),
+        )
+        .await?;
+        self.command(
+            Method::POST,
+            &format!("/element/{}/value", element),
+            Some(json!({ "text": text })),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Visible text of the element matching a selector
+    pub async fn text(&self, selector: &str) -> Result<String> {
+        let element = self.find(selector).await?;
+        let value = self
+            .command(Method::GET, &format!("/element/{}/text", element), None)
+            .await?;
+        Ok(value.as_str().unwrap_or_default().to_string())
+    }
+
+    /// Capture a PNG screenshot, base64 encoded
+    pub async fn screenshot(&self) -> Result<String> {
+        let value = self.command(Method::GET, "/screenshot", None).await?;
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| Error::parsing("WebDriver screenshot response was not a string"))
+    }
+
+    /// End the session and close the browser
+    pub async fn close(self) -> Result<()> {
+        send(&self.client, Method::DELETE, &self.session_url, None)
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Send a WebDriver command and unwrap the `value` envelope
+async fn send(client: &Client, method: Method, url: &str, body: Option<Value>) -> Result<Value> {
+    let mut request = client.request(method, url);
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+
+    let response = request.send().await.map_err(|e| {
+        Error::network_with_endpoint(format!("WebDriver request failed: {}", e), url)
+    })?;
+    let status = response.status();
+    let payload: Value = response
+        .json()
+        .await
+        .map_err(|e| Error::parsing(format!("Failed to parse WebDriver response: {}", e)))?;
+    let value = payload.get("value").cloned().unwrap_or(Value::Null);
+
+    if !status.is_success() {
+        let error = value
+            .get("error")
+            .and_then(|e| e.as_str())
+            .unwrap_or("unknown error");
+        let message = value
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default();
+        return Err(match error {
+            "no such element" => Error::not_found(format!("WebDriver: {}", message)),
+            "timeout" | "script timeout" => Error::timeout(format!("WebDriver: {}", message)),
+            _ => Error::api_with_status(
+                format!("WebDriver {}: {}", error, message),
+                "webdriver",
+                status.as_u16(),
+            ),
+        });
+    }
+    Ok(value)
+}
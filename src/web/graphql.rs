@@ -0,0 +1,1088 @@
+/// GraphQL client tooling
+///
+/// Supports schema introspection, validating queries against the fetched
+/// schema before they are sent, variables, and named persisted queries that
+/// can be saved to and loaded from disk.
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Trimmed introspection query: enough to validate fields and arguments
+const INTROSPECTION_QUERY: &str = r#"
+query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      kind
+      name
+      description
+      fields(includeDeprecated: true) {
+        name
+        args { name type { ...TypeRef } }
+        type { ...TypeRef }
+      }
+      inputFields { name type { ...TypeRef } }
+      possibleTypes { name }
+    }
+  }
+}
+fragment TypeRef on __Type {
+  kind name
+  ofType { kind name ofType { kind name ofType { kind name ofType { kind name } } } }
+}
+"#;
+
+/// GraphQL endpoint configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphQlConfig {
+    /// GraphQL endpoint URL
+    pub endpoint: String,
+    /// Bearer token (e.g. GitHub v4)
+    pub bearer_token: Option<String>,
+    /// Extra headers (e.g. X-Shopify-Access-Token)
+    pub headers: HashMap<String, String>,
+}
+
+/// A field on an object or interface type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphQlField {
+    pub name: String,
+    /// Innermost named type of the field
+    pub type_name: String,
+    /// Argument names with their rendered types
+    pub args: HashMap<String, String>,
+}
+
+/// A named type in the schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphQlType {
+    pub name: String,
+    pub kind: String,
+    pub description: Option<String>,
+    pub fields: HashMap<String, GraphQlField>,
+    /// Concrete types for interfaces and unions
+    pub possible_types: Vec<String>,
+}
+
+/// Schema fetched through introspection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphQlSchema {
+    pub query_type: String,
+    pub mutation_type: Option<String>,
+    pub subscription_type: Option<String>,
+    pub types: HashMap<String, GraphQlType>,
+}
+
+impl GraphQlSchema {
+    /// Parse the `data` of an introspection response
+    pub fn from_introspection(data: &Value) -> Result<Self> {
+        let schema = data
+            .get("__schema")
+            .ok_or_else(|| Error::parsing("Introspection response missing __schema"))?;
+        let root_name = |key: &str| {
+            schema
+                .get(key)
+                .and_then(|t| t.get("name"))
+                .and_then(|n| n.as_str())
+                .map(str::to_string)
+        };
+
+        let mut types = HashMap::new();
+        for ty in schema
+            .get("types")
+            .and_then(|t| t.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let Some(name) = ty.get("name").and_then(|n| n.as_str()) else {
+                continue;
+            };
+            let fields = ty
+                .get("fields")
+                .and_then(|f| f.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|field| {
+                    let name = field.get("name")?.as_str()?.to_string();
+                    let args = field
+                        .get("args")
+                        .and_then(|a| a.as_array())
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|arg| {
+                            Some((
+                                arg.get("name")?.as_str()?.to_string(),
+                                render_type_ref(arg.get("type")?),
+                            ))
+                        })
+                        .collect();
+                    Some((
+                        name.clone(),
+                        GraphQlField {
+                            name,
+                            type_name: named_type(field.get("type")?)?,
+                            args,
+                        },
+                    ))
+                })
+                .collect();
+
+            types.insert(
+                name.to_string(),
+                GraphQlType {
+                    name: name.to_string(),
+                    kind: ty
+                        .get("kind")
+                        .and_then(|k| k.as_str())
+                        .unwrap_or("OBJECT")
+                        .to_string(),
+                    description: ty
+                        .get("description")
+                        .and_then(|d| d.as_str())
+                        .map(str::to_string),
+                    fields,
+                    possible_types: ty
+                        .get("possibleTypes")
+                        .and_then(|p| p.as_array())
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|p| p.get("name").and_then(|n| n.as_str()).map(str::to_string))
+                        .collect(),
+                },
+            );
+        }
+
+        Ok(Self {
+            query_type: root_name("queryType").unwrap_or_else(|| "Query".to_string()),
+            mutation_type: root_name("mutationType"),
+            subscription_type: root_name("subscriptionType"),
+            types,
+        })
+    }
+
+    /// Validate a query document, returning every problem found
+    pub fn validate(&self, query: &str) -> std::result::Result<(), Vec<String>> {
+        let document = match Parser::new(query).and_then(|mut p| p.document()) {
+            Ok(document) => document,
+            Err(e) => return Err(vec![e]),
+        };
+        let mut errors = Vec::new();
+
+        for op in &document.operations {
+            let root = match op.kind.as_str() {
+                "query" => Some(self.query_type.clone()),
+                "mutation" => self.mutation_type.clone(),
+                "subscription" => self.subscription_type.clone(),
+                _ => None,
+            };
+            let Some(root) = root else {
+                errors.push(format!("Schema does not support {} operations", op.kind));
+                continue;
+            };
+
+            let mut used = HashSet::new();
+            self.validate_selection(&root, &op.selection, &mut used, &mut errors);
+            for fragment in &document.fragments {
+                self.validate_selection(
+                    &fragment.on_type,
+                    &fragment.selection,
+                    &mut used,
+                    &mut errors,
+                );
+            }
+
+            for var in &used {
+                if !op.variables.contains_key(var) {
+                    errors.push(format!("Variable ${} is not defined", var));
+                }
+            }
+            for var in op.variables.keys() {
+                if !used.contains(var) {
+                    errors.push(format!("Variable ${} is never used", var));
+                }
+            }
+        }
+
+        errors.sort();
+        errors.dedup();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_selection(
+        &self,
+        parent: &str,
+        selection: &[Selection],
+        used_vars: &mut HashSet<String>,
+        errors: &mut Vec<String>,
+    ) {
+        let Some(parent_type) = self.types.get(parent) else {
+            errors.push(format!("Unknown type {}", parent));
+            return;
+        };
+
+        for item in selection {
+            match item {
+                Selection::Field {
+                    name,
+                    arguments,
+                    variables,
+                    children,
+                } => {
+                    used_vars.extend(variables.iter().cloned());
+                    if name == "__typename" {
+                        continue;
+                    }
+                    let Some(field) = parent_type.fields.get(name) else {
+                        errors.push(format!(
+                            "Cannot query field '{}' on type '{}'",
+                            name, parent
+                        ));
+                        continue;
+                    };
+                    for arg in arguments {
+                        if !field.args.contains_key(arg) {
+                            errors.push(format!(
+                                "Unknown argument '{}' on field '{}.{}'",
+                                arg, parent, name
+                            ));
+                        }
+                    }
+                    if !children.is_empty() {
+                        self.validate_selection(&field.type_name, children, used_vars, errors);
+                    }
+                }
+                Selection::InlineFragment { on_type, children } => {
+                    let target = on_type.as_deref().unwrap_or(parent);
+                    self.validate_selection(target, children, used_vars, errors);
+                }
+                Selection::Spread => {}
+            }
+        }
+    }
+
+    /// Variables declared non-null in the operation
+    pub fn required_variables(query: &str) -> Vec<String> {
+        Parser::new(query)
+            .and_then(|mut p| p.document())
+            .map(|doc| {
+                doc.operations
+                    .iter()
+                    .flat_map(|op| op.variables.iter())
+                    .filter(|(_, ty)| ty.ends_with('!'))
+                    .map(|(name, _)| name.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn named_type(type_ref: &Value) -> Option<String> {
+    match type_ref.get("name").and_then(|n| n.as_str()) {
+        Some(name) => Some(name.to_string()),
+        None => named_type(type_ref.get("ofType")?),
+    }
+}
+
+fn render_type_ref(type_ref: &Value) -> String {
+    let inner = || {
+        type_ref
+            .get("ofType")
+            .map(render_type_ref)
+            .unwrap_or_default()
+    };
+    match type_ref.get("kind").and_then(|k| k.as_str()) {
+        Some("NON_NULL") => format!("{}!", inner()),
+        Some("LIST") => format!("[{}]", inner()),
+        _ => type_ref
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+/// A saved, named query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedQuery {
+    pub name: String,
+    pub query: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub default_variables: Value,
+}
+
+/// GraphQL client with schema caching and persisted queries
+#[derive(Debug)]
+pub struct GraphQlClient {
+    client: Client,
+    config: GraphQlConfig,
+    schema: RwLock<Option<GraphQlSchema>>,
+    persisted: RwLock<HashMap<String, PersistedQuery>>,
+}
+
+impl GraphQlClient {
+    /// Create a new GraphQL client
+    pub fn new(config: GraphQlConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent("MCP-Rust-Client/1.0")
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create GraphQL client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            config,
+            schema: RwLock::new(None),
+            persisted: RwLock::new(HashMap::new()),
+        })
+    }
+
+    async fn post(&self, body: Value) -> Result<Value> {
+        let mut request = self.client.post(&self.config.endpoint).json(&body);
+        if let Some(token) = &self.config.bearer_token {
+            request = request.bearer_auth(token);
+        }
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            Error::network_with_endpoint(
+                format!("GraphQL request failed: {}", e),
+                self.config.endpoint.clone(),
+            )
+        })?;
+        let status = response.status();
+        let payload: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse GraphQL response: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(Error::api_with_status(
+                format!("GraphQL endpoint returned {}: {}", status, payload),
+                "graphql",
+                status.as_u16(),
+            ));
+        }
+        Ok(payload)
+    }
+
+    /// Fetch and cache the schema through introspection
+    pub async fn introspect(&self) -> Result<GraphQlSchema> {
+        let response = self.post(json!({ "query": INTROSPECTION_QUERY })).await?;
+        let data = response
+            .get("data")
+            .filter(|d| !d.is_null())
+            .ok_or_else(|| Error::api(format!("Introspection failed: {}", response["errors"])))?;
+        let schema = GraphQlSchema::from_introspection(data)?;
+        *self.schema.write().await = Some(schema.clone());
+        Ok(schema)
+    }
+
+    /// Get the cached schema, introspecting if necessary
+    pub async fn schema(&self) -> Result<GraphQlSchema> {
+        if let Some(schema) = self.schema.read().await.as_ref() {
+            return Ok(schema.clone());
+        }
+        self.introspect().await
+    }
+
+    /// Validate a query against the schema
+    pub async fn validate(&self, query: &str) -> Result<()> {
+        self.schema().await?.validate(query).map_err(|errors| {
+            Error::validation(format!("Invalid GraphQL query: {}", errors.join("; ")))
+        })
+    }
+
+    /// Execute a query with optional variables and operation name
+    pub async fn execute(
+        &self,
+        query: &str,
+        variables: Option<Value>,
+        operation_name: Option<&str>,
+        validate: bool,
+    ) -> Result<Value> {
+        if validate {
+            self.validate(query).await?;
+        }
+
+        let variables = variables.unwrap_or_else(|| json!({}));
+        let missing: Vec<_> = GraphQlSchema::required_variables(query)
+            .into_iter()
+            .filter(|name| variables.get(name).is_none_or(Value::is_null))
+            .collect();
+        if !missing.is_empty() {
+            return Err(Error::validation(format!(
+                "Missing required variables: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let response = self
+            .post(json!({
+                "query": query,
+                "variables": variables,
+                "operationName": operation_name
+            }))
+            .await?;
+
+        let has_data = response.get("data").is_some_and(|d| !d.is_null());
+        if let Some(errors) = response.get("errors").filter(|_| !has_data) {
+            return Err(Error::api(format!("GraphQL errors: {}", errors)));
+        }
+        Ok(response)
+    }
+
+    /// Save a named query
+    pub async fn persist_query(&self, query: PersistedQuery) -> Result<()> {
+        if let Some(schema) = self.schema.read().await.as_ref() {
+            schema.validate(&query.query).map_err(|errors| {
+                Error::validation(format!("Invalid GraphQL query: {}", errors.join("; ")))
+            })?;
+        }
+        self.persisted
+            .write()
+            .await
+            .insert(query.name.clone(), query);
+        Ok(())
+    }
+
+    /// List persisted queries
+    pub async fn persisted_queries(&self) -> Vec<PersistedQuery> {
+        let mut queries: Vec<_> = self.persisted.read().await.values().cloned().collect();
+        queries.sort_by(|a, b| a.name.cmp(&b.name));
+        queries
+    }
+
+    /// Execute a persisted query, merging variables over its defaults
+    pub async fn execute_persisted(&self, name: &str, variables: Option<Value>) -> Result<Value> {
+        let query = self
+            .persisted
+            .read()
+            .await
+            .get(name)
+            .cloned()
+            .ok_or_else(|| {
+                Error::not_found_with_resource("Persisted query not found", "graphql_query", name)
+            })?;
+
+        let mut merged = match query.default_variables {
+            Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        if let Some(Value::Object(overrides)) = variables {
+            merged.extend(overrides);
+        }
+        self.execute(&query.query, Some(Value::Object(merged)), None, false)
+            .await
+    }
+
+    /// Load a persisted query collection from a JSON file
+    pub async fn load_collection(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let contents = tokio::fs::read_to_string(path.as_ref())
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to read collection: {}", e),
+                    path.as_ref().to_path_buf(),
+                )
+            })?;
+        let queries: Vec<PersistedQuery> = serde_json::from_str(&contents)?;
+        let count = queries.len();
+        let mut persisted = self.persisted.write().await;
+        for query in queries {
+            persisted.insert(query.name.clone(), query);
+        }
+        Ok(count)
+    }
+
+    /// Save the persisted query collection to a JSON file
+    pub async fn save_collection(&self, path: impl AsRef<Path>) -> Result<()> {
+        let queries = self.persisted_queries().await;
+        let contents = serde_json::to_string_pretty(&queries)?;
+        tokio::fs::write(path.as_ref(), contents)
+            .await
+            .map_err(|e| {
+                Error::io_with_path(
+                    format!("Failed to write collection: {}", e),
+                    path.as_ref().to_path_buf(),
+                )
+            })
+    }
+
+    /// Get tool definitions for GraphQL operations
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        vec![
+            ToolDefinition::from_json_schema(
+                "graphql_query",
+                "Execute a GraphQL query or a persisted query, validating it against the schema first",
+                "web_graphql",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": {
+                            "type": "string",
+                            "description": "GraphQL query document"
+                        },
+                        "persisted_query": {
+                            "type": "string",
+                            "description": "Name of a persisted query to run instead of 'query'"
+                        },
+                        "variables": {
+                            "type": "object",
+                            "description": "Query variables"
+                        },
+                        "operation_name": {
+                            "type": "string",
+                            "description": "Operation to run when the document has several"
+                        },
+                        "validate": {
+                            "type": "boolean",
+                            "description": "Validate against the introspected schema before sending",
+                            "default": true
+                        }
+                    }
+                }),
+                None,
+            ),
+            ToolDefinition::from_json_schema(
+                "graphql_introspect",
+                "Introspect the GraphQL schema and describe its root operations or a specific type",
+                "web_graphql",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "type_name": {
+                            "type": "string",
+                            "description": "Type to describe (defaults to the root types)"
+                        },
+                        "refresh": {
+                            "type": "boolean",
+                            "description": "Re-fetch the schema instead of using the cache",
+                            "default": false
+                        }
+                    }
+                }),
+                None,
+            ),
+        ]
+    }
+
+    /// Execute a GraphQL tool
+    pub async fn execute_tool(&self, name: &str, parameters: Value) -> Result<Value> {
+        match name {
+            "graphql_query" => {
+                let variables = parameters.get("variables").cloned();
+                let response = match parameters.get("persisted_query").and_then(|p| p.as_str()) {
+                    Some(persisted) => self.execute_persisted(persisted, variables).await?,
+                    None => {
+                        let query = parameters
+                            .get("query")
+                            .and_then(|q| q.as_str())
+                            .ok_or_else(|| {
+                                Error::validation_with_field(
+                                    "query or persisted_query is required",
+                                    "query",
+                                )
+                            })?;
+                        let validate = parameters
+                            .get("validate")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(true);
+                        let operation_name =
+                            parameters.get("operation_name").and_then(|o| o.as_str());
+                        self.execute(query, variables, operation_name, validate)
+                            .await?
+                    }
+                };
+                Ok(call_result(
+                    serde_json::to_string_pretty(&response)?,
+                    response,
+                ))
+            }
+            "graphql_introspect" => {
+                let schema = if parameters.get("refresh").and_then(|r| r.as_bool()) == Some(true) {
+                    self.introspect().await?
+                } else {
+                    self.schema().await?
+                };
+
+                let type_name = parameters
+                    .get("type_name")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or(&schema.query_type);
+                let ty = schema.types.get(type_name).ok_or_else(|| {
+                    Error::not_found_with_resource(
+                        "Type not found in schema",
+                        "graphql_type",
+                        type_name,
+                    )
+                })?;
+
+                let mut fields: Vec<_> = ty
+                    .fields
+                    .values()
+                    .map(|f| {
+                        let mut args: Vec<_> = f
+                            .args
+                            .iter()
+                            .map(|(n, t)| format!("{}: {}", n, t))
+                            .collect();
+                        args.sort();
+                        if args.is_empty() {
+                            format!("{}: {}", f.name, f.type_name)
+                        } else {
+                            format!("{}({}): {}", f.name, args.join(", "), f.type_name)
+                        }
+                    })
+                    .collect();
+                fields.sort();
+
+                Ok(call_result(
+                    format!(
+                        "{} {} ({} types in schema)\n{}",
+                        ty.kind,
+                        ty.name,
+                        schema.types.len(),
+                        fields.join("\n")
+                    ),
+                    json!({
+                        "query_type": schema.query_type,
+                        "mutation_type": schema.mutation_type,
+                        "subscription_type": schema.subscription_type,
+                        "type": ty,
+                    }),
+                ))
+            }
+            _ => Err(Error::not_found_with_resource(
+                "Tool not found",
+                "graphql_tool",
+                name,
+            )),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Minimal query document parser used for validation
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum Selection {
+    Field {
+        name: String,
+        arguments: Vec<String>,
+        variables: Vec<String>,
+        children: Vec<Selection>,
+    },
+    InlineFragment {
+        on_type: Option<String>,
+        children: Vec<Selection>,
+    },
+    Spread,
+}
+
+#[derive(Debug)]
+struct Operation {
+    kind: String,
+    variables: HashMap<String, String>,
+    selection: Vec<Selection>,
+}
+
+#[derive(Debug)]
+struct Fragment {
+    on_type: String,
+    selection: Vec<Selection>,
+}
+
+#[derive(Debug, Default)]
+struct QueryDocument {
+    operations: Vec<Operation>,
+    fragments: Vec<Fragment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Punct(char),
+    Spread,
+    Name(String),
+    Value,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(source: &str) -> std::result::Result<Self, String> {
+        let mut tokens = Vec::new();
+        let mut chars = source.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            match c {
+                c if c.is_whitespace() || c == ',' => {
+                    chars.next();
+                }
+                '#' => while chars.next().is_some_and(|c| c != '\n') {},
+                '.' => {
+                    let dots: String = chars.by_ref().take(3).collect();
+                    if dots != "..." {
+                        return Err("Unexpected '.'".to_string());
+                    }
+                    tokens.push(Token::Spread);
+                }
+                '"' => {
+                    chars.next();
+                    let mut escaped = false;
+                    loop {
+                        match chars.next() {
+                            Some('\\') if !escaped => escaped = true,
+                            Some('"') if !escaped => break,
+                            Some(_) => escaped = false,
+                            None => return Err("Unterminated string".to_string()),
+                        }
+                    }
+                    tokens.push(Token::Value);
+                }
+                c if c.is_ascii_digit() || c == '-' => {
+                    while chars
+                        .peek()
+                        .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '+'))
+                    {
+                        chars.next();
+                    }
+                    tokens.push(Token::Value);
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let mut name = String::new();
+                    while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_')
+                    {
+                        name.push(c);
+                        chars.next();
+                    }
+                    tokens.push(Token::Name(name));
+                }
+                '{' | '}' | '(' | ')' | '[' | ']' | ':' | '!' | '$' | '@' | '=' | '|' | '&' => {
+                    tokens.push(Token::Punct(c));
+                    chars.next();
+                }
+                other => return Err(format!("Unexpected character '{}'", other)),
+            }
+        }
+
+        Ok(Self { tokens, pos: 0 })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, c: char) -> std::result::Result<(), String> {
+        match self.next() {
+            Some(Token::Punct(p)) if p == c => Ok(()),
+            other => Err(format!("Expected '{}', found {:?}", c, other)),
+        }
+    }
+
+    fn name(&mut self) -> std::result::Result<String, String> {
+        match self.next() {
+            Some(Token::Name(name)) => Ok(name),
+            other => Err(format!("Expected name, found {:?}", other)),
+        }
+    }
+
+    fn document(&mut self) -> std::result::Result<QueryDocument, String> {
+        let mut document = QueryDocument::default();
+
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Punct('{') => document.operations.push(Operation {
+                    kind: "query".to_string(),
+                    variables: HashMap::new(),
+                    selection: self.selection_set()?,
+                }),
+                Token::Name(keyword) if keyword == "fragment" => {
+                    self.next();
+                    self.name()?;
+                    if self.name()? != "on" {
+                        return Err("Expected 'on' in fragment definition".to_string());
+                    }
+                    let on_type = self.name()?;
+                    self.directives()?;
+                    document.fragments.push(Fragment {
+                        on_type,
+                        selection: self.selection_set()?,
+                    });
+                }
+                Token::Name(keyword) => {
+                    self.next();
+                    if let Some(Token::Name(_)) = self.peek() {
+                        self.next();
+                    }
+                    let variables = self.variable_definitions()?;
+                    self.directives()?;
+                    document.operations.push(Operation {
+                        kind: keyword,
+                        variables,
+                        selection: self.selection_set()?,
+                    });
+                }
+                other => return Err(format!("Unexpected token {:?}", other)),
+            }
+        }
+
+        if document.operations.is_empty() {
+            return Err("Document contains no operations".to_string());
+        }
+        Ok(document)
+    }
+
+    fn variable_definitions(&mut self) -> std::result::Result<HashMap<String, String>, String> {
+        let mut variables = HashMap::new();
+        if self.peek() != Some(&Token::Punct('(')) {
+            return Ok(variables);
+        }
+        self.next();
+
+        while self.peek() != Some(&Token::Punct(')')) {
+            self.expect('$')?;
+            let name = self.name()?;
+            self.expect(':')?;
+            let ty = self.type_ref()?;
+            if self.peek() == Some(&Token::Punct('=')) {
+                self.next();
+                self.skip_value()?;
+            }
+            variables.insert(name, ty);
+        }
+        self.expect(')')?;
+        Ok(variables)
+    }
+
+    fn type_ref(&mut self) -> std::result::Result<String, String> {
+        let mut ty = if self.peek() == Some(&Token::Punct('[')) {
+            self.next();
+            let inner = self.type_ref()?;
+            self.expect(']')?;
+            format!("[{}]", inner)
+        } else {
+            self.name()?
+        };
+        if self.peek() == Some(&Token::Punct('!')) {
+            self.next();
+            ty.push('!');
+        }
+        Ok(ty)
+    }
+
+    fn directives(&mut self) -> std::result::Result<Vec<String>, String> {
+        let mut variables = Vec::new();
+        while self.peek() == Some(&Token::Punct('@')) {
+            self.next();
+            self.name()?;
+            if self.peek() == Some(&Token::Punct('(')) {
+                variables.extend(self.arguments()?.1);
+            }
+        }
+        Ok(variables)
+    }
+
+    /// Parse `(name: value, ...)`, returning argument names and referenced variables
+    fn arguments(&mut self) -> std::result::Result<(Vec<String>, Vec<String>), String> {
+        self.expect('(')?;
+        let mut names = Vec::new();
+        let mut variables = Vec::new();
+        while self.peek() != Some(&Token::Punct(')')) {
+            names.push(self.name()?);
+            self.expect(':')?;
+            variables.extend(self.skip_value()?);
+        }
+        self.expect(')')?;
+        Ok((names, variables))
+    }
+
+    /// Skip over a value literal, returning variables it references
+    fn skip_value(&mut self) -> std::result::Result<Vec<String>, String> {
+        let mut variables = Vec::new();
+        match self.next() {
+            Some(Token::Punct('$')) => variables.push(self.name()?),
+            Some(Token::Punct(open @ ('[' | '{'))) => {
+                let close = if open == '[' { ']' } else { '}' };
+                while self.peek() != Some(&Token::Punct(close)) {
+                    if self.peek().is_none() {
+                        return Err(format!("Unterminated '{}'", open));
+                    }
+                    if open == '{' {
+                        self.name()?;
+                        self.expect(':')?;
+                    }
+                    variables.extend(self.skip_value()?);
+                }
+                self.next();
+            }
+            Some(Token::Value) | Some(Token::Name(_)) => {}
+            other => return Err(format!("Expected value, found {:?}", other)),
+        }
+        Ok(variables)
+    }
+
+    fn selection_set(&mut self) -> std::result::Result<Vec<Selection>, String> {
+        self.expect('{')?;
+        let mut selection = Vec::new();
+
+        while self.peek() != Some(&Token::Punct('}')) {
+            match self.peek().cloned() {
+                Some(Token::Spread) => {
+                    self.next();
+                    match self.peek().cloned() {
+                        Some(Token::Name(n)) if n == "on" => {
+                            self.next();
+                            let on_type = Some(self.name()?);
+                            self.directives()?;
+                            selection.push(Selection::InlineFragment {
+                                on_type,
+                                children: self.selection_set()?,
+                            });
+                        }
+                        Some(Token::Name(_)) => {
+                            self.next();
+                            self.directives()?;
+                            selection.push(Selection::Spread);
+                        }
+                        _ => {
+                            self.directives()?;
+                            selection.push(Selection::InlineFragment {
+                                on_type: None,
+                                children: self.selection_set()?,
+                            });
+                        }
+                    }
+                }
+                Some(Token::Name(_)) => {
+                    let mut name = self.name()?;
+                    if self.peek() == Some(&Token::Punct(':')) {
+                        self.next();
+                        name = self.name()?;
+                    }
+                    let (arguments, mut variables) = if self.peek() == Some(&Token::Punct('(')) {
+                        self.arguments()?
+                    } else {
+                        (Vec::new(), Vec::new())
+                    };
+                    variables.extend(self.directives()?);
+                    let children = if self.peek() == Some(&Token::Punct('{')) {
+                        self.selection_set()?
+                    } else {
+                        Vec::new()
+                    };
+                    selection.push(Selection::Field {
+                        name,
+                        arguments,
+                        variables,
+                        children,
+                    });
+                }
+                other => return Err(format!("Unexpected token in selection set: {:?}", other)),
+            }
+        }
+        self.expect('}')?;
+        Ok(selection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> GraphQlSchema {
+        let data = json!({
+            "__schema": {
+                "queryType": {"name": "Query"},
+                "mutationType": null,
+                "subscriptionType": null,
+                "types": [
+                    {
+                        "kind": "OBJECT",
+                        "name": "Query",
+                        "fields": [{
+                            "name": "repository",
+                            "args": [
+                                {"name": "owner", "type": {"kind": "NON_NULL", "name": null, "ofType": {"kind": "SCALAR", "name": "String"}}},
+                                {"name": "name", "type": {"kind": "NON_NULL", "name": null, "ofType": {"kind": "SCALAR", "name": "String"}}}
+                            ],
+                            "type": {"kind": "OBJECT", "name": "Repository"}
+                        }]
+                    },
+                    {
+                        "kind": "OBJECT",
+                        "name": "Repository",
+                        "fields": [
+                            {"name": "name", "args": [], "type": {"kind": "SCALAR", "name": "String"}},
+                            {"name": "stargazerCount", "args": [], "type": {"kind": "NON_NULL", "name": null, "ofType": {"kind": "SCALAR", "name": "Int"}}}
+                        ]
+                    }
+                ]
+            }
+        });
+        GraphQlSchema::from_introspection(&data).unwrap()
+    }
+
+    #[test]
+    fn test_valid_query_with_variables() {
+        let query = r#"
+            query Repo($owner: String!, $name: String!) {
+                repository(owner: $owner, name: $name) {
+                    __typename
+                    name
+                    stars: stargazerCount
+                }
+            }
+        "#;
+        assert!(schema().validate(query).is_ok());
+        assert_eq!(GraphQlSchema::required_variables(query).len(), 2);
+    }
+
+    #[test]
+    fn test_reports_unknown_fields_arguments_and_variables() {
+        let query = r#"
+            query {
+                repository(owner: $owner, nam: "x") {
+                    forks
+                }
+            }
+        "#;
+        let errors = schema().validate(query).unwrap_err();
+
+        assert!(errors.iter().any(|e| e.contains("field 'forks'")));
+        assert!(errors.iter().any(|e| e.contains("argument 'nam'")));
+        assert!(errors.iter().any(|e| e.contains("$owner is not defined")));
+    }
+
+    #[test]
+    fn test_rejects_unsupported_operation() {
+        let errors = schema()
+            .validate("mutation { addStar { starrable { id } } }")
+            .unwrap_err();
+        assert_eq!(errors, vec!["Schema does not support mutation operations"]);
+    }
+}
@@ -10,6 +10,14 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
+pub mod browser;
+pub mod graphql;
+pub mod openapi;
+
+pub use browser::{BrowserSession, WebDriverClient, WebDriverConfig};
+pub use graphql::{GraphQlClient, GraphQlConfig, GraphQlSchema};
+pub use openapi::{OpenApiAuth, OpenApiToolset};
+
 /// High-performance web client with connection pooling and caching
 #[derive(Debug)]
 pub struct WebClient {
@@ -0,0 +1,571 @@
+/// OpenAPI 3.x ingestion for auto-generated tools
+///
+/// Turns every operation in an OpenAPI document into a namespaced
+/// `ToolDefinition` whose input schema is derived from the operation's
+/// parameters and request body, and dispatches tool calls to the described
+/// endpoint with the configured authentication.
+use crate::error::{Error, Result};
+use crate::tools::{call_result, ToolDefinition};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::{Client, Method};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Maximum depth when inlining `$ref` schemas, guarding against cycles
+const MAX_REF_DEPTH: usize = 16;
+
+/// Authentication applied to generated tool calls
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OpenApiAuth {
+    #[default]
+    None,
+    Bearer {
+        token: String,
+    },
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    ApiKey {
+        name: String,
+        value: String,
+        location: ParameterLocation,
+    },
+}
+
+/// Where an operation parameter is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterLocation {
+    Path,
+    Query,
+    Header,
+    Cookie,
+}
+
+/// A single operation parameter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationParameter {
+    pub name: String,
+    pub location: ParameterLocation,
+    pub required: bool,
+    pub description: Option<String>,
+    pub schema: Value,
+}
+
+/// An operation extracted from the spec
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenApiOperation {
+    /// Namespaced tool name
+    pub tool_name: String,
+    /// Original operationId, if present
+    pub operation_id: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub summary: String,
+    pub parameters: Vec<OperationParameter>,
+    pub body_schema: Option<Value>,
+    pub body_required: bool,
+}
+
+impl OpenApiOperation {
+    /// Build the JSON Schema for the tool's arguments
+    pub fn input_schema(&self) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+
+        for param in &self.parameters {
+            let mut schema = param.schema.clone();
+            if let (Some(description), Some(obj)) = (&param.description, schema.as_object_mut()) {
+                obj.entry("description")
+                    .or_insert_with(|| Value::String(description.clone()));
+            }
+            properties.insert(param.name.clone(), schema);
+            if param.required {
+                required.push(Value::String(param.name.clone()));
+            }
+        }
+
+        if let Some(body) = &self.body_schema {
+            properties.insert("body".to_string(), body.clone());
+            if self.body_required {
+                required.push(Value::String("body".to_string()));
+            }
+        }
+
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required
+        })
+    }
+}
+
+/// Tools generated from an OpenAPI document
+#[derive(Debug, Clone)]
+pub struct OpenApiToolset {
+    namespace: String,
+    title: String,
+    base_url: String,
+    auth: OpenApiAuth,
+    operations: HashMap<String, OpenApiOperation>,
+    client: Client,
+}
+
+impl OpenApiToolset {
+    /// Build a toolset from a parsed OpenAPI document
+    pub fn from_value(namespace: impl Into<String>, spec: &Value) -> Result<Self> {
+        let namespace = sanitize_identifier(&namespace.into());
+        let version = spec
+            .get("openapi")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::validation("Document is missing the 'openapi' version field"))?;
+        if !version.starts_with("3.") {
+            return Err(Error::validation(format!(
+                "Unsupported OpenAPI version {}; only 3.x is supported",
+                version
+            )));
+        }
+
+        let title = spec
+            .pointer("/info/title")
+            .and_then(|t| t.as_str())
+            .unwrap_or(&namespace)
+            .to_string();
+        let base_url = spec
+            .pointer("/servers/0/url")
+            .and_then(|u| u.as_str())
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_string();
+
+        let paths = spec
+            .get("paths")
+            .and_then(|p| p.as_object())
+            .ok_or_else(|| Error::validation("Document has no 'paths' object"))?;
+
+        let mut operations = HashMap::new();
+        for (path, item) in paths {
+            let item = resolve_refs(item, spec, 0);
+            let shared_params = item.get("parameters").cloned().unwrap_or(Value::Null);
+
+            for method in ["get", "put", "post", "delete", "patch", "head", "options"] {
+                let Some(op) = item.get(method) else {
+                    continue;
+                };
+
+                let operation_id = op
+                    .get("operationId")
+                    .and_then(|o| o.as_str())
+                    .map(str::to_string);
+                let base_name = operation_id
+                    .as_deref()
+                    .map(to_snake_case)
+                    .unwrap_or_else(|| format!("{}_{}", method, sanitize_identifier(path)));
+                let tool_name = format!("{}_{}", namespace, base_name);
+
+                let mut parameters = parse_parameters(&shared_params);
+                for param in parse_parameters(op.get("parameters").unwrap_or(&Value::Null)) {
+                    parameters.retain(|p| !(p.name == param.name && p.location == param.location));
+                    parameters.push(param);
+                }
+
+                let body = op.get("requestBody");
+                let body_schema = body
+                    .and_then(|b| b.pointer("/content/application~1json/schema"))
+                    .cloned();
+                let body_required = body
+                    .and_then(|b| b.get("required"))
+                    .and_then(|r| r.as_bool())
+                    .unwrap_or(false);
+
+                let summary = op
+                    .get("summary")
+                    .or_else(|| op.get("description"))
+                    .and_then(|s| s.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("{} {}", method.to_uppercase(), path));
+
+                operations.insert(
+                    tool_name.clone(),
+                    OpenApiOperation {
+                        tool_name,
+                        operation_id,
+                        method: method.to_uppercase(),
+                        path: path.clone(),
+                        summary,
+                        parameters,
+                        body_schema,
+                        body_required,
+                    },
+                );
+            }
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::network(format!("Failed to create OpenAPI client: {}", e)))?;
+
+        Ok(Self {
+            namespace,
+            title,
+            base_url,
+            auth: OpenApiAuth::None,
+            operations,
+            client,
+        })
+    }
+
+    /// Build a toolset from an OpenAPI JSON document
+    pub fn from_json(namespace: impl Into<String>, spec: &str) -> Result<Self> {
+        let spec: Value = serde_json::from_str(spec)
+            .map_err(|e| Error::parsing(format!("Failed to parse OpenAPI document: {}", e)))?;
+        Self::from_value(namespace, &spec)
+    }
+
+    /// Fetch an OpenAPI JSON document over HTTP and build a toolset
+    pub async fn fetch(namespace: impl Into<String>, spec_url: &str) -> Result<Self> {
+        let response = reqwest::get(spec_url).await.map_err(|e| {
+            Error::network_with_endpoint(format!("Failed to fetch spec: {}", e), spec_url)
+        })?;
+        if !response.status().is_success() {
+            return Err(Error::api_with_status(
+                format!("Fetching OpenAPI spec returned {}", response.status()),
+                "openapi",
+                response.status().as_u16(),
+            ));
+        }
+        let spec: Value = response
+            .json()
+            .await
+            .map_err(|e| Error::parsing(format!("Failed to parse OpenAPI document: {}", e)))?;
+        Self::from_value(namespace, &spec)
+    }
+
+    /// Override the server URL from the spec
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the authentication used for calls
+    pub fn with_auth(mut self, auth: OpenApiAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Tool namespace
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Get an operation by tool name
+    pub fn operation(&self, tool_name: &str) -> Option<&OpenApiOperation> {
+        self.operations.get(tool_name)
+    }
+
+    /// Generate tool definitions for every operation
+    pub fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
+        let mut operations: Vec<_> = self.operations.values().collect();
+        operations.sort_by(|a, b| a.tool_name.cmp(&b.tool_name));
+
+        operations
+            .into_iter()
+            .map(|op| {
+                let mut tool = ToolDefinition::from_json_schema(
+                    &op.tool_name,
+                    &format!("[{}] {}", self.title, op.summary),
+                    &format!("openapi_{}", self.namespace),
+                    op.input_schema(),
+                    None,
+                );
+                tool.required_parameters = op
+                    .parameters
+                    .iter()
+                    .filter(|p| p.required)
+                    .map(|p| p.name.clone())
+                    .chain(op.body_required.then(|| "body".to_string()))
+                    .collect();
+                if matches!(op.method.as_str(), "GET" | "HEAD") {
+                    tool = tool.read_only();
+                }
+                tool
+            })
+            .collect()
+    }
+
+    /// Execute a generated tool by calling the described endpoint
+    pub async fn execute_tool(&self, name: &str, arguments: Value) -> Result<Value> {
+        let op = self.operations.get(name).ok_or_else(|| {
+            Error::not_found_with_resource("Tool not found", "openapi_tool", name)
+        })?;
+        if self.base_url.is_empty() {
+            return Err(Error::config_with_suggestion(
+                format!("No server URL available for {}", self.title),
+                "Call with_base_url or add a 'servers' entry to the spec",
+            ));
+        }
+
+        let mut path = op.path.clone();
+        let mut query = Vec::new();
+        let mut headers = Vec::new();
+
+        for param in &op.parameters {
+            let value = match arguments.get(&param.name) {
+                Some(Value::Null) | None if param.required => {
+                    return Err(Error::validation_with_field(
+                        format!("Missing required parameter '{}'", param.name),
+                        param.name.clone(),
+                    ))
+                }
+                Some(Value::Null) | None => continue,
+                Some(Value::String(s)) => s.clone(),
+                Some(other) => other.to_string(),
+            };
+
+            match param.location {
+                ParameterLocation::Path => {
+                    let encoded = utf8_percent_encode(&value, NON_ALPHANUMERIC).to_string();
+                    path = path.replace(&format!("{{{}}}", param.name), &encoded);
+                }
+                ParameterLocation::Query => query.push((param.name.clone(), value)),
+                ParameterLocation::Header => headers.push((param.name.clone(), value)),
+                ParameterLocation::Cookie => {
+                    headers.push(("Cookie".to_string(), format!("{}={}", param.name, value)))
+                }
+            }
+        }
+
+        let method = Method::from_bytes(op.method.as_bytes())
+            .map_err(|e| Error::internal(format!("Invalid HTTP method: {}", e)))?;
+        let url = format!("{}{}", self.base_url, path);
+        let mut request = self.client.request(method, &url).query(&query);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+
+        request = match &self.auth {
+            OpenApiAuth::None => request,
+            OpenApiAuth::Bearer { token } => request.bearer_auth(token),
+            OpenApiAuth::Basic { username, password } => {
+                request.basic_auth(username, password.as_ref())
+            }
+            OpenApiAuth::ApiKey {
+                name,
+                value,
+                location: ParameterLocation::Query,
+            } => request.query(&[(name, value)]),
+            OpenApiAuth::ApiKey { name, value, .. } => request.header(name, value),
+        };
+
+        match arguments.get("body") {
+            Some(body) if op.body_schema.is_some() => request = request.json(body),
+            None if op.body_required => {
+                return Err(Error::validation_with_field(
+                    "Request body is required",
+                    "body",
+                ))
+            }
+            _ => {}
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::network_with_endpoint(format!("Request failed: {}", e), &url))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| Error::network(format!("Failed to read response: {}", e)))?;
+        let body = serde_json::from_str(&text).unwrap_or(Value::String(text));
+
+        if !status.is_success() {
+            return Err(Error::api_with_status(
+                format!("{} {} returned {}: {}", op.method, op.path, status, body),
+                self.namespace.clone(),
+                status.as_u16(),
+            ));
+        }
+
+        Ok(call_result(
+            format!("{} {} returned {}", op.method, path, status),
+            json!({ "status": status.as_u16(), "body": body }),
+        ))
+    }
+}
+
+fn parse_parameters(params: &Value) -> Vec<OperationParameter> {
+    params
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|param| {
+            let location = match param.get("in")?.as_str()? {
+                "path" => ParameterLocation::Path,
+                "query" => ParameterLocation::Query,
+                "header" => ParameterLocation::Header,
+                "cookie" => ParameterLocation::Cookie,
+                _ => return None,
+            };
+            Some(OperationParameter {
+                name: param.get("name")?.as_str()?.to_string(),
+                location,
+                // Path parameters are always required per the specification
+                required: location == ParameterLocation::Path
+                    || param
+                        .get("required")
+                        .and_then(|r| r.as_bool())
+                        .unwrap_or(false),
+                description: param
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .map(str::to_string),
+                schema: param
+                    .get("schema")
+                    .cloned()
+                    .unwrap_or_else(|| json!({"type": "string"})),
+            })
+        })
+        .collect()
+}
+
+/// Inline local `#/...` references so generated schemas are self-contained
+fn resolve_refs(value: &Value, root: &Value, depth: usize) -> Value {
+    match value {
+        Value::Object(obj) => {
+            if let Some(reference) = obj.get("$ref").and_then(|r| r.as_str()) {
+                if depth >= MAX_REF_DEPTH {
+                    return json!({});
+                }
+                return reference
+                    .strip_prefix('#')
+                    .and_then(|pointer| root.pointer(pointer))
+                    .map(|target| resolve_refs(target, root, depth + 1))
+                    .unwrap_or_else(|| json!({}));
+            }
+            Value::Object(
+                obj.iter()
+                    .map(|(k, v)| (k.clone(), resolve_refs(v, root, depth)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => {
+            Value::Array(items.iter().map(|v| resolve_refs(v, root, depth)).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+fn sanitize_identifier(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    out.trim_matches('_').to_string()
+}
+
+fn to_snake_case(input: &str) -> String {
+    let mut out = String::with_capacity(input.len() + 4);
+    let mut prev_lower = false;
+    for c in input.chars() {
+        if c.is_ascii_uppercase() && prev_lower {
+            out.push('_');
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        out.push(c);
+    }
+    sanitize_identifier(&out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn petstore() -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "info": {"title": "Petstore", "version": "1.0.0"},
+            "servers": [{"url": "https://petstore.example.com/v1/"}],
+            "paths": {
+                "/pets/{petId}": {
+                    "parameters": [
+                        {"name": "petId", "in": "path", "schema": {"type": "string"}}
+                    ],
+                    "get": {
+                        "operationId": "showPetById",
+                        "summary": "Info for a specific pet",
+                        "parameters": [
+                            {"name": "verbose", "in": "query", "schema": {"type": "boolean"}}
+                        ]
+                    }
+                },
+                "/pets": {
+                    "post": {
+                        "summary": "Create a pet",
+                        "requestBody": {
+                            "required": true,
+                            "content": {
+                                "application/json": {
+                                    "schema": {"$ref": "#/components/schemas/Pet"}
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {"name": {"type": "string"}}
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_generates_namespaced_tools() {
+        let toolset = OpenApiToolset::from_value("Pet Store", &petstore()).unwrap();
+        let tools = toolset.get_tool_definitions();
+        let names: Vec<_> = tools.iter().map(|t| t.name.as_str()).collect();
+
+        assert_eq!(
+            names,
+            vec!["pet_store_post_pets", "pet_store_show_pet_by_id"]
+        );
+
+        let show = toolset.operation("pet_store_show_pet_by_id").unwrap();
+        assert_eq!(show.parameters.len(), 2);
+        assert!(show
+            .parameters
+            .iter()
+            .any(|p| p.name == "petId" && p.required));
+    }
+
+    #[test]
+    fn test_resolves_body_refs() {
+        let toolset = OpenApiToolset::from_value("pets", &petstore()).unwrap();
+        let create = toolset.operation("pets_post_pets").unwrap();
+        let schema = create.input_schema();
+
+        assert_eq!(schema["properties"]["body"]["required"], json!(["name"]));
+        assert_eq!(schema["required"], json!(["body"]));
+    }
+
+    #[test]
+    fn test_rejects_swagger_2() {
+        let spec = json!({"swagger": "2.0", "paths": {}});
+        assert!(OpenApiToolset::from_value("legacy", &spec).is_err());
+    }
+}
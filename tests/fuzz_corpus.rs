@@ -0,0 +1,228 @@
+//! Property tests over the fuzz entry points, plus a replay of the fuzz corpora
+//!
+//! Tool arguments are generated from the input schemas the server actually
+//! registers: mostly values a schema accepts, with values just outside its
+//! bounds and of the wrong type mixed in. The only property checked is the one
+//! the fuzz targets check, that dispatch never panics. Finds from `cargo fuzz`
+//! belong in `fuzz/corpus` so they are replayed here from then on.
+
+use devops_mcp::fuzz;
+use devops_mcp::tools::ToolDefinition;
+use proptest::prelude::*;
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+/// How deep generated values nest before only scalars are produced
+const MAX_DEPTH: u32 = 4;
+
+/// Strings that tend to reach interesting branches in paths, SQL and shells
+const INTERESTING: &[&str] = &[
+    "",
+    "../../etc/passwd",
+    "'; DROP TABLE users; --",
+    "$(reboot)",
+    "a\u{0}b",
+    "\\\\server\\share",
+    "ünïcødé 🚀",
+    "-rf",
+];
+
+fn definitions() -> Vec<ToolDefinition> {
+    let definitions = fuzz::registry().definitions();
+    assert!(!definitions.is_empty(), "the server registered no tools");
+    definitions
+}
+
+/// Values for `schema`: mostly ones it accepts, some of any type
+fn value(schema: &Value, depth: u32) -> BoxedStrategy<Value> {
+    prop_oneof![8 => conforming(schema, depth), 1 => any_json()].boxed()
+}
+
+fn conforming(schema: &Value, depth: u32) -> BoxedStrategy<Value> {
+    if let Some(options) = schema["enum"].as_array().filter(|o| !o.is_empty()) {
+        return prop::sample::select(options.clone()).boxed();
+    }
+    let kind = match &schema["type"] {
+        Value::String(kind) => kind.as_str(),
+        Value::Array(kinds) => kinds.first().and_then(Value::as_str).unwrap_or(""),
+        _ if schema.get("properties").is_some() => "object",
+        _ => "",
+    };
+    match kind {
+        "object" if depth < MAX_DEPTH => object(schema, depth),
+        "array" if depth < MAX_DEPTH => array(schema, depth),
+        "string" => string(),
+        "integer" => integer(schema),
+        "number" => number(schema),
+        "boolean" => any::<bool>().prop_map(Value::Bool).boxed(),
+        "null" => Just(Value::Null).boxed(),
+        _ => any_json(),
+    }
+}
+
+/// Every required property and a random subset of the optional ones
+fn object(schema: &Value, depth: u32) -> BoxedStrategy<Value> {
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let properties: Vec<BoxedStrategy<Option<(String, Value)>>> = schema["properties"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, property)| {
+            let required = required.contains(&name.as_str());
+            let name = name.clone();
+            let entry = value(property, depth + 1).prop_map(move |v| (name.clone(), v));
+            if required {
+                entry.prop_map(Some).boxed()
+            } else {
+                prop::option::of(entry).boxed()
+            }
+        })
+        .collect();
+    properties
+        .prop_map(|entries| Value::Object(entries.into_iter().flatten().collect::<Map<_, _>>()))
+        .boxed()
+}
+
+fn array(schema: &Value, depth: u32) -> BoxedStrategy<Value> {
+    let items = schema.get("items").cloned().unwrap_or(json!({}));
+    let min = schema["minItems"].as_u64().unwrap_or(0) as usize;
+    let max = schema["maxItems"]
+        .as_u64()
+        .map_or(min + 4, |max| max as usize);
+    prop::collection::vec(value(&items, depth + 1), min..=max.clamp(min, min + 4))
+        .prop_map(Value::Array)
+        .boxed()
+}
+
+fn string() -> BoxedStrategy<Value> {
+    prop_oneof![
+        "[a-z0-9][-a-z0-9]{0,15}",
+        ".{0,32}",
+        prop::sample::select(INTERESTING).prop_map(str::to_string),
+    ]
+    .prop_map(Value::String)
+    .boxed()
+}
+
+/// Integers inside the schema's bounds, on them, just past them, or anywhere
+fn integer(schema: &Value) -> BoxedStrategy<Value> {
+    let min = schema["minimum"].as_i64().unwrap_or(-1_000);
+    let max = schema["maximum"].as_i64().unwrap_or(1_000).max(min);
+    prop_oneof![
+        4 => min..=max,
+        1 => prop::sample::select(vec![
+            min,
+            max,
+            min.saturating_sub(1),
+            max.saturating_add(1)
+        ]),
+        1 => any::<i64>(),
+    ]
+    .prop_map(|n| json!(n))
+    .boxed()
+}
+
+fn number(schema: &Value) -> BoxedStrategy<Value> {
+    let min = schema["minimum"].as_f64().unwrap_or(-1e6);
+    let max = schema["maximum"].as_f64().unwrap_or(1e6).max(min);
+    prop_oneof![
+        4 => min..=max,
+        1 => prop::sample::select(vec![min, max, min - 1.0, max + 1.0]),
+        1 => any::<f64>(),
+    ]
+    .prop_map(|n| serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number))
+    .boxed()
+}
+
+fn any_json() -> BoxedStrategy<Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(|n| json!(n)),
+        ".{0,16}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(3, 16, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+            prop::collection::btree_map(".{0,8}", inner, 0..4)
+                .prop_map(|entries| Value::Object(entries.into_iter().collect())),
+        ]
+    })
+    .boxed()
+}
+
+/// A registered tool's name with arguments generated from its input schema
+fn tool_call() -> impl Strategy<Value = (String, Value)> {
+    prop::sample::select(definitions()).prop_flat_map(|definition| {
+        let schema = definition
+            .parameters
+            .unwrap_or_else(|| json!({"type": "object", "properties": {}}));
+        (Just(definition.name), value(&schema, 0))
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1024))]
+
+    #[test]
+    fn tool_dispatch_never_panics((name, arguments) in tool_call()) {
+        fuzz::call(&name, arguments);
+    }
+
+    #[test]
+    fn jsonrpc_tool_calls_never_panic((name, arguments) in tool_call(), id in any_json()) {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": name, "arguments": arguments}
+        });
+        fuzz::jsonrpc(&serde_json::to_vec(&request).unwrap());
+    }
+
+    #[test]
+    fn jsonrpc_parser_never_panics(data in prop::collection::vec(any::<u8>(), 0..512)) {
+        fuzz::jsonrpc(&data);
+    }
+
+    #[test]
+    fn validators_never_panic(text in prop_oneof![
+        ".{0,64}".prop_map(String::into_bytes),
+        prop::collection::vec(any::<u8>(), 0..128),
+        prop::sample::select(INTERESTING).prop_map(|s| s.as_bytes().to_vec()),
+    ]) {
+        fuzz::validators(&text);
+    }
+}
+
+fn replay(target: &str, run: fn(&[u8])) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fuzz/corpus")
+        .join(target);
+    let seeds: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", dir.display(), e))
+        .map(|entry| std::fs::read(entry.unwrap().path()).unwrap())
+        .collect();
+    assert!(!seeds.is_empty(), "{} has no seeds", dir.display());
+    for seed in seeds {
+        run(&seed);
+    }
+}
+
+#[test]
+fn jsonrpc_parser_survives_the_corpus() {
+    replay("jsonrpc", fuzz::jsonrpc);
+}
+
+#[test]
+fn tool_arguments_survive_the_corpus() {
+    replay("tool_arguments", fuzz::tool_arguments);
+}
+
+#[test]
+fn validators_survive_the_corpus() {
+    replay("validators", fuzz::validators);
+}
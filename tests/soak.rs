@@ -0,0 +1,257 @@
+//! Soak test: the server under sustained synthetic load
+//!
+//! Runs rounds of concurrent tool calls over HTTP, WebSocket clients that
+//! connect, call and go away (some without closing), and port-forward
+//! sessions that are stopped, fail at start or exit on their own. After a
+//! warm-up it checks that file descriptors, child processes and resident
+//! memory stay bounded. Linux only, as it reads `/proc`.
+//!
+//! By default it runs a few seconds so it fits in `cargo test`; set
+//! `SOAK_SECONDS` for a real soak:
+//!
+//! ```text
+//! SOAK_SECONDS=1800 cargo test --release --test soak -- --nocapture
+//! ```
+#![cfg(target_os = "linux")]
+
+use devops_mcp::infrastructure::kubernetes::PortForwardManager;
+use devops_mcp::tools::{call_result, ToolDefinition, ToolRegistry};
+use devops_mcp::transport::http::HttpTransport;
+use devops_mcp::transport::websocket::WS_PATH;
+use devops_mcp::transport::{Transport, WebSocketTransport};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::os::unix::fs::PermissionsExt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Concurrent HTTP clients and calls each makes per round
+const HTTP_CLIENTS: usize = 8;
+const HTTP_CALLS: usize = 50;
+
+/// WebSocket connections per round and calls on each
+const WS_CLIENTS: usize = 10;
+const WS_CALLS: usize = 5;
+
+/// Rounds before the baseline is taken, and the fewest measured rounds
+const WARM_UP_ROUNDS: usize = 3;
+const MIN_ROUNDS: usize = 10;
+
+/// Growth allowed over the baseline
+const FD_SLACK: usize = 16;
+const RSS_SLACK_KIB: u64 = 64 * 1024;
+
+/// Stands in for kubectl: `pod/broken` fails, `pod/exit` forwards and exits,
+/// anything else forwards until killed
+const FAKE_KUBECTL: &str = r#"#!/bin/sh
+case "$2" in
+  */broken) echo "error: pods \"broken\" not found" >&2; exit 1 ;;
+  */exit) echo "Forwarding from 127.0.0.1:${3%%:*} -> ${3##*:}"; exit 0 ;;
+esac
+echo "Forwarding from 127.0.0.1:${3%%:*} -> ${3##*:}"
+exec sleep 600
+"#;
+
+/// Process resources that leak
+#[derive(Debug, Clone, Copy)]
+struct Usage {
+    fds: usize,
+    /// Child processes, zombies included
+    children: usize,
+    rss_kib: u64,
+}
+
+impl Usage {
+    fn sample() -> Self {
+        let pid = std::process::id().to_string();
+        let children = std::fs::read_dir("/proc")
+            .unwrap()
+            .filter_map(|entry| std::fs::read_to_string(entry.ok()?.path().join("stat")).ok())
+            .filter(|stat| {
+                // "pid (comm) state ppid ..", where comm may contain anything
+                let rest = stat.rsplit_once(')').map_or("", |(_, rest)| rest);
+                rest.split_whitespace().nth(1) == Some(pid.as_str())
+            })
+            .count();
+        let rss_kib = std::fs::read_to_string("/proc/self/status")
+            .unwrap()
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))
+            .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+            .unwrap_or(0);
+        Self {
+            fds: std::fs::read_dir("/proc/self/fd").unwrap().count(),
+            children,
+            rss_kib,
+        }
+    }
+}
+
+async fn serve() -> SocketAddr {
+    let registry = ToolRegistry::new();
+    registry
+        .register(
+            ToolDefinition::new("echo", "Echo"),
+            |arguments| async move { Ok(call_result("echo", arguments)) },
+        )
+        .unwrap();
+    let registry = Arc::new(registry);
+    let app = Arc::clone(&registry)
+        .router()
+        .merge(devops_mcp::transport::websocket::router(registry));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    addr
+}
+
+async fn call(client: &mut dyn Transport, i: usize) {
+    let result = client
+        .request(
+            "tools/call",
+            Some(json!({"name": "echo", "arguments": {"i": i}})),
+        )
+        .await
+        .unwrap();
+    // The HTTP transport hands back the whole JSON-RPC envelope
+    let result = result.get("result").cloned().unwrap_or(result);
+    assert_eq!(result["structuredContent"]["i"], i);
+}
+
+async fn http_calls(addr: SocketAddr) {
+    let clients = (0..HTTP_CLIENTS).map(|_| {
+        tokio::spawn(async move {
+            let mut client = HttpTransport::new(format!("http://{}/", addr)).unwrap();
+            client.connect().await.unwrap();
+            for i in 0..HTTP_CALLS {
+                call(&mut client, i).await;
+            }
+        })
+    });
+    for client in futures::future::join_all(clients).await {
+        client.unwrap();
+    }
+}
+
+async fn websocket_churn(addr: SocketAddr) {
+    for n in 0..WS_CLIENTS {
+        let mut client = WebSocketTransport::new(format!("ws://{}{}", addr, WS_PATH)).unwrap();
+        client.connect().await.unwrap();
+        for i in 0..WS_CALLS {
+            call(&mut client, i).await;
+        }
+        // Every other client just drops its socket, as a crashed client would
+        if n % 2 == 0 {
+            client.disconnect().await.unwrap();
+        }
+    }
+}
+
+async fn port_forward_churn(forwards: &PortForwardManager, round: usize) {
+    let port = 20000 + (round % 1000) as u16;
+    let kept = forwards
+        .start_session("pod", "api", port, 80, "default")
+        .await
+        .unwrap();
+    forwards
+        .start_session("pod", "exit", port, 80, "default")
+        .await
+        .unwrap();
+    assert!(forwards
+        .start_session("pod", "broken", port, 80, "default")
+        .await
+        .is_err());
+    forwards.stop_session(&kept.id).await.unwrap();
+}
+
+/// Poll until descriptors and children settle back under the limits, as
+/// servers close their side of dropped connections asynchronously
+async fn settle(baseline: Usage, forwards: &PortForwardManager) -> Usage {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let sessions = forwards.list_sessions().unwrap();
+        let usage = Usage::sample();
+        let settled = sessions.is_empty()
+            && usage.children <= baseline.children
+            && usage.fds <= baseline.fds + FD_SLACK;
+        if settled || Instant::now() > deadline {
+            return usage;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn resources_stay_bounded_under_sustained_load() {
+    let duration = Duration::from_secs(
+        std::env::var("SOAK_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(3),
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let kubectl = dir.path().join("kubectl");
+    std::fs::write(&kubectl, FAKE_KUBECTL).unwrap();
+    std::fs::set_permissions(&kubectl, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let forwards = PortForwardManager::with_program(kubectl.to_string_lossy());
+
+    let addr = serve().await;
+    let run_round = |round: usize| {
+        let forwards = &forwards;
+        async move {
+            tokio::join!(
+                http_calls(addr),
+                websocket_churn(addr),
+                port_forward_churn(forwards, round)
+            );
+        }
+    };
+
+    for round in 0..WARM_UP_ROUNDS {
+        run_round(round).await;
+    }
+    let baseline = settle(Usage::sample(), &forwards).await;
+    println!("baseline: {:?}", baseline);
+
+    let started = Instant::now();
+    let mut rounds = 0;
+    while rounds < MIN_ROUNDS || started.elapsed() < duration {
+        run_round(WARM_UP_ROUNDS + rounds).await;
+        rounds += 1;
+        if rounds % 100 == 0 {
+            println!(
+                "{} rounds in {:?}: {:?}",
+                rounds,
+                started.elapsed(),
+                Usage::sample()
+            );
+        }
+    }
+
+    let usage = settle(baseline, &forwards).await;
+    let calls = rounds * (HTTP_CLIENTS * HTTP_CALLS + WS_CLIENTS * WS_CALLS);
+    println!(
+        "{} rounds, {} tool calls in {:?}: {:?}",
+        rounds,
+        calls,
+        started.elapsed(),
+        usage
+    );
+    assert_eq!(forwards.list_sessions().unwrap(), Vec::<String>::new());
+    assert_eq!(
+        usage.children, baseline.children,
+        "port-forward children leaked"
+    );
+    assert!(
+        usage.fds <= baseline.fds + FD_SLACK,
+        "file descriptors grew from {} to {}",
+        baseline.fds,
+        usage.fds
+    );
+    assert!(
+        usage.rss_kib <= baseline.rss_kib + RSS_SLACK_KIB,
+        "resident memory grew from {} KiB to {} KiB",
+        baseline.rss_kib,
+        usage.rss_kib
+    );
+}